@@ -30,7 +30,7 @@ CREATE TABLE IF NOT EXISTS bead_backlog (
     repo_id TEXT NOT NULL DEFAULT 'local',
     bead_id TEXT PRIMARY KEY,
     priority TEXT NOT NULL DEFAULT 'p0',
-    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'in_progress', 'completed', 'blocked')),
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'in_progress', 'awaiting_approval', 'completed', 'blocked', 'cancelled')),
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 
@@ -49,6 +49,10 @@ CREATE UNIQUE INDEX IF NOT EXISTS idx_bead_claims_bead_owner ON bead_claims(bead
 ALTER TABLE bead_backlog ADD COLUMN IF NOT EXISTS repo_id TEXT NOT NULL DEFAULT 'local';
 ALTER TABLE bead_backlog ALTER COLUMN repo_id SET DEFAULT 'local';
 ALTER TABLE bead_backlog ALTER COLUMN repo_id SET NOT NULL;
+ALTER TABLE bead_backlog ADD COLUMN IF NOT EXISTS labels TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE bead_backlog ADD COLUMN IF NOT EXISTS required_capabilities TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE bead_backlog DROP CONSTRAINT IF EXISTS bead_backlog_status_check;
+ALTER TABLE bead_backlog ADD CONSTRAINT bead_backlog_status_check CHECK (status IN ('pending', 'in_progress', 'awaiting_approval', 'completed', 'blocked', 'cancelled'));
 
 ALTER TABLE bead_claims ADD COLUMN IF NOT EXISTS repo_id TEXT NOT NULL DEFAULT 'local';
 ALTER TABLE bead_claims ALTER COLUMN repo_id SET DEFAULT 'local';
@@ -56,10 +60,19 @@ ALTER TABLE bead_claims ALTER COLUMN repo_id SET NOT NULL;
 
 ALTER TABLE bead_claims ADD COLUMN IF NOT EXISTS heartbeat_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
 ALTER TABLE bead_claims ADD COLUMN IF NOT EXISTS lease_expires_at TIMESTAMPTZ NOT NULL DEFAULT (NOW() + INTERVAL '5 minutes');
+-- Set when the owner consents to `takeover` of its claim; a transfer clears it.
+ALTER TABLE bead_claims ADD COLUMN IF NOT EXISTS takeover_consented_at TIMESTAMPTZ;
 
+-- The session trigger fires on UPDATE OF claimed_by, which blocks the type
+-- change on a re-run; it is recreated further down.
+DROP TRIGGER IF EXISTS trg_bead_claims_session ON bead_claims;
 ALTER TABLE bead_claims ALTER COLUMN claimed_by TYPE INTEGER;
 ALTER TABLE bead_claims DROP CONSTRAINT IF EXISTS bead_claims_claimed_by_check;
 ALTER TABLE bead_claims ADD CONSTRAINT bead_claims_claimed_by_check CHECK (claimed_by >= 1);
+-- NOT VALID: rows from before the constraint stay readable for
+-- `swarm doctor` to report; new writes must fit the enum.
+ALTER TABLE bead_claims DROP CONSTRAINT IF EXISTS bead_claims_status_check;
+ALTER TABLE bead_claims ADD CONSTRAINT bead_claims_status_check CHECK (status IN ('in_progress', 'completed', 'blocked')) NOT VALID;
 
 CREATE UNIQUE INDEX IF NOT EXISTS idx_bead_backlog_repo_bead_unique ON bead_backlog(repo_id, bead_id);
 CREATE UNIQUE INDEX IF NOT EXISTS idx_bead_claims_repo_bead_unique ON bead_claims(repo_id, bead_id);
@@ -82,9 +95,17 @@ ALTER TABLE agent_state ADD COLUMN IF NOT EXISTS repo_id TEXT NOT NULL DEFAULT '
 ALTER TABLE agent_state ALTER COLUMN repo_id SET DEFAULT 'local';
 ALTER TABLE agent_state ALTER COLUMN repo_id SET NOT NULL;
 
+-- Default claim labels: an agent with labels only claims backlog beads
+-- carrying at least one of them. Empty means any bead.
+ALTER TABLE agent_state ADD COLUMN IF NOT EXISTS labels TEXT[] NOT NULL DEFAULT '{}';
+
 ALTER TABLE agent_state ALTER COLUMN agent_id TYPE INTEGER;
 ALTER TABLE agent_state DROP CONSTRAINT IF EXISTS agent_state_agent_id_check;
 ALTER TABLE agent_state ADD CONSTRAINT agent_state_agent_id_check CHECK (agent_id >= 1);
+ALTER TABLE agent_state DROP CONSTRAINT IF EXISTS agent_state_status_check;
+ALTER TABLE agent_state ADD CONSTRAINT agent_state_status_check CHECK (status IN ('idle', 'working', 'waiting', 'error', 'done')) NOT VALID;
+ALTER TABLE agent_state DROP CONSTRAINT IF EXISTS agent_state_current_stage_check;
+ALTER TABLE agent_state ADD CONSTRAINT agent_state_current_stage_check CHECK (current_stage IN ('rust-contract', 'implement', 'qa-enforcer', 'red-queen', 'done')) NOT VALID;
 
 -- Legacy schemas can lack repo-scoped uniqueness. Deduplicate and enforce
 -- uniqueness on (repo_id, agent_id).
@@ -138,24 +159,45 @@ BEGIN
 END;
 $$;
 
+-- Soft delete: a deleted claim, backlog entry or agent keeps its row with
+-- when and by what it was deleted, and every query on the live rows skips
+-- it. `undelete` clears both; writing the key again revives the row.
+ALTER TABLE bead_claims ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE bead_claims ADD COLUMN IF NOT EXISTS deleted_by TEXT;
+ALTER TABLE bead_claims DROP CONSTRAINT IF EXISTS bead_claims_deleted_by_check;
+ALTER TABLE bead_claims ADD CONSTRAINT bead_claims_deleted_by_check CHECK ((deleted_at IS NULL) = (deleted_by IS NULL));
+ALTER TABLE bead_backlog ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE bead_backlog ADD COLUMN IF NOT EXISTS deleted_by TEXT;
+ALTER TABLE bead_backlog DROP CONSTRAINT IF EXISTS bead_backlog_deleted_by_check;
+ALTER TABLE bead_backlog ADD CONSTRAINT bead_backlog_deleted_by_check CHECK ((deleted_at IS NULL) = (deleted_by IS NULL));
+ALTER TABLE agent_state ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE agent_state ADD COLUMN IF NOT EXISTS deleted_by TEXT;
+ALTER TABLE agent_state DROP CONSTRAINT IF EXISTS agent_state_deleted_by_check;
+ALTER TABLE agent_state ADD CONSTRAINT agent_state_deleted_by_check CHECK ((deleted_at IS NULL) = (deleted_by IS NULL));
+
 CREATE TABLE IF NOT EXISTS stage_history (
     id BIGSERIAL PRIMARY KEY,
     agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
     bead_id TEXT NOT NULL,
     stage TEXT NOT NULL CHECK (stage IN ('rust-contract', 'implement', 'qa-enforcer', 'red-queen')),
     attempt_number INTEGER NOT NULL CHECK (attempt_number >= 1),
-    status TEXT NOT NULL CHECK (status IN ('started', 'passed', 'failed', 'error')),
+    status TEXT NOT NULL CHECK (status IN ('started', 'passed', 'failed', 'error', 'cancelled')),
     result TEXT,
     feedback TEXT,
     transcript TEXT,
     started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     completed_at TIMESTAMPTZ,
-    duration_ms INTEGER CHECK (duration_ms IS NULL OR duration_ms >= 0)
+    duration_ms INTEGER CHECK (duration_ms IS NULL OR duration_ms >= 0),
+    metadata JSONB
 );
 
+ALTER TABLE stage_history ADD COLUMN IF NOT EXISTS repo_id TEXT NOT NULL DEFAULT 'local';
+ALTER TABLE stage_history ADD COLUMN IF NOT EXISTS metadata JSONB;
 ALTER TABLE stage_history ALTER COLUMN agent_id TYPE INTEGER;
 ALTER TABLE stage_history DROP CONSTRAINT IF EXISTS stage_history_agent_id_check;
 ALTER TABLE stage_history ADD CONSTRAINT stage_history_agent_id_check CHECK (agent_id >= 1);
+ALTER TABLE stage_history DROP CONSTRAINT IF EXISTS stage_history_status_check;
+ALTER TABLE stage_history ADD CONSTRAINT stage_history_status_check CHECK (status IN ('started', 'passed', 'failed', 'error', 'cancelled'));
 
 CREATE TABLE IF NOT EXISTS stage_artifacts (
     id BIGSERIAL PRIMARY KEY,
@@ -187,14 +229,61 @@ CREATE TABLE IF NOT EXISTS stage_artifacts (
         'retry_packet',
         'skill_invocation',
         'error_message',
-        'feedback'
+        'feedback',
+        'push_verification',
+        'design_document',
+        'diff',
+        'screenshot',
+        'attachment',
+    'summary'
     )),
     content TEXT NOT NULL,
     metadata JSONB,
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-    content_hash TEXT
+    content_hash TEXT,
+    signature TEXT,
+    signing_key TEXT
 );
 
+ALTER TABLE stage_artifacts ADD COLUMN IF NOT EXISTS signature TEXT;
+ALTER TABLE stage_artifacts ADD COLUMN IF NOT EXISTS signing_key TEXT;
+ALTER TABLE stage_artifacts DROP CONSTRAINT IF EXISTS stage_artifacts_artifact_type_check;
+ALTER TABLE stage_artifacts ADD CONSTRAINT stage_artifacts_artifact_type_check CHECK (artifact_type IN (
+    'contract_document',
+    'requirements',
+    'system_context',
+    'invariants',
+    'data_flow',
+    'implementation_plan',
+    'acceptance_criteria',
+    'error_handling',
+    'test_scenarios',
+    'validation_gates',
+    'success_metrics',
+    'implementation_code',
+    'modified_files',
+    'implementation_notes',
+    'test_output',
+    'test_results',
+    'coverage_report',
+    'validation_report',
+    'failure_details',
+    'adversarial_report',
+    'regression_report',
+    'quality_gate_report',
+    'stage_log',
+    'retry_packet',
+    'skill_invocation',
+    'error_message',
+    'feedback',
+    'push_verification',
+    'design_document',
+    'diff',
+    'screenshot',
+    'attachment',
+    'summary'
+));
+
 CREATE TABLE IF NOT EXISTS agent_messages (
     id BIGSERIAL PRIMARY KEY,
     from_repo_id TEXT NOT NULL,
@@ -213,7 +302,8 @@ CREATE TABLE IF NOT EXISTS agent_messages (
         'stage_complete',
         'stage_failed',
         'blocking_issue',
-        'coordination'
+        'coordination',
+        'bead_cancelled'
     )),
     subject TEXT NOT NULL,
     body TEXT NOT NULL,
@@ -223,6 +313,22 @@ CREATE TABLE IF NOT EXISTS agent_messages (
     read BOOLEAN NOT NULL DEFAULT FALSE
 );
 
+ALTER TABLE agent_messages DROP CONSTRAINT IF EXISTS agent_messages_message_type_check;
+ALTER TABLE agent_messages ADD CONSTRAINT agent_messages_message_type_check CHECK (message_type IN (
+    'contract_ready',
+    'implementation_ready',
+    'qa_complete',
+    'qa_failed',
+    'red_queen_failed',
+    'implementation_retry',
+    'artifact_available',
+    'stage_complete',
+    'stage_failed',
+    'blocking_issue',
+    'coordination',
+    'bead_cancelled'
+));
+
 CREATE TABLE IF NOT EXISTS agent_run_logs (
     id BIGSERIAL PRIMARY KEY,
     agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
@@ -246,6 +352,13 @@ CREATE TABLE IF NOT EXISTS swarm_config (
     CHECK (id)
 );
 
+ALTER TABLE swarm_config ADD COLUMN IF NOT EXISTS lease_ttl_ms INTEGER NOT NULL DEFAULT 300000
+    CHECK (lease_ttl_ms BETWEEN 10000 AND 86400000);
+ALTER TABLE swarm_config ADD COLUMN IF NOT EXISTS heartbeat_grace_ms INTEGER NOT NULL DEFAULT 0
+    CHECK (heartbeat_grace_ms BETWEEN 0 AND 3600000);
+ALTER TABLE swarm_config ADD COLUMN IF NOT EXISTS recovery_scan_interval_ms INTEGER NOT NULL DEFAULT 30000
+    CHECK (recovery_scan_interval_ms BETWEEN 1000 AND 3600000);
+
 CREATE TABLE IF NOT EXISTS command_audit (
     seq BIGSERIAL PRIMARY KEY,
     t TIMESTAMPTZ NOT NULL DEFAULT NOW(),
@@ -271,15 +384,35 @@ CREATE TABLE IF NOT EXISTS execution_events (
     diagnostics_retryable BOOLEAN,
     diagnostics_next_command TEXT,
     diagnostics_detail TEXT,
+    diagnostics_failing_tests TEXT[],
     payload JSONB,
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 
+ALTER TABLE execution_events ADD COLUMN IF NOT EXISTS diagnostics_failing_tests TEXT[];
+
 CREATE TABLE IF NOT EXISTS resource_locks (
     resource TEXT PRIMARY KEY,
     agent TEXT NOT NULL,
     since TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-    until_at TIMESTAMPTZ NOT NULL
+    until_at TIMESTAMPTZ NOT NULL,
+    purpose TEXT,
+    bead_id TEXT
+);
+
+ALTER TABLE resource_locks ADD COLUMN IF NOT EXISTS purpose TEXT;
+ALTER TABLE resource_locks ADD COLUMN IF NOT EXISTS bead_id TEXT;
+
+CREATE TABLE IF NOT EXISTS resource_lock_waiters (
+    id BIGSERIAL PRIMARY KEY,
+    resource TEXT NOT NULL,
+    agent TEXT NOT NULL,
+    purpose TEXT,
+    bead_id TEXT,
+    ttl_ms BIGINT NOT NULL CHECK (ttl_ms > 0),
+    enqueued_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    wait_until TIMESTAMPTZ NOT NULL,
+    UNIQUE (resource, agent)
 );
 
 CREATE TABLE IF NOT EXISTS broadcast_log (
@@ -289,12 +422,363 @@ CREATE TABLE IF NOT EXISTS broadcast_log (
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 
+CREATE TABLE IF NOT EXISTS skill_prompts (
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    skill TEXT NOT NULL,
+    version INTEGER NOT NULL CHECK (version >= 1),
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repo_id, skill, version)
+);
+
+CREATE TABLE IF NOT EXISTS symbols (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    bead_id TEXT NOT NULL,
+    attempt INTEGER NOT NULL CHECK (attempt >= 1),
+    agent_id INTEGER CHECK (agent_id IS NULL OR agent_id >= 1),
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('function', 'struct', 'enum', 'trait', 'type_alias', 'constant', 'module')),
+    module_path TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    signature_hash TEXT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT symbols_attempt_unique UNIQUE (repo_id, bead_id, attempt, module_path, name)
+);
+
+CREATE TABLE IF NOT EXISTS agent_fingerprints (
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
+    window_secs INTEGER NOT NULL CHECK (window_secs > 0),
+    window_start TIMESTAMPTZ NOT NULL,
+    stage_runs INTEGER NOT NULL CHECK (stage_runs >= 0),
+    failures INTEGER NOT NULL CHECK (failures >= 0),
+    retries INTEGER NOT NULL CHECK (retries >= 0),
+    beads INTEGER NOT NULL CHECK (beads >= 0),
+    avg_stage_ms BIGINT NOT NULL CHECK (avg_stage_ms >= 0),
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repo_id, agent_id, window_secs, window_start)
+);
+
+CREATE TABLE IF NOT EXISTS agent_quarantine (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
+    source TEXT NOT NULL CHECK (source IN ('manual', 'automatic')),
+    reason TEXT NOT NULL,
+    quarantined_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    released_at TIMESTAMPTZ,
+    release_reason TEXT,
+    CHECK ((released_at IS NULL) = (release_reason IS NULL))
+);
+
+CREATE TABLE IF NOT EXISTS workspaces (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
+    bead_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    path TEXT NOT NULL,
+    backend TEXT NOT NULL CHECK (backend IN ('jj', 'git')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    removed_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS alerts (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    kind TEXT NOT NULL CHECK (kind IN ('backlog_depth', 'error_rate', 'all_agents_waiting')),
+    status TEXT NOT NULL CHECK (status IN ('pending', 'firing', 'resolved')),
+    detail TEXT NOT NULL,
+    breached_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    fired_at TIMESTAMPTZ,
+    resolved_at TIMESTAMPTZ,
+    CHECK ((status = 'resolved') = (resolved_at IS NOT NULL))
+);
+
+CREATE TABLE IF NOT EXISTS transition_events (
+    seq BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    bead_id TEXT NOT NULL,
+    agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
+    stage TEXT NOT NULL CHECK (stage IN ('rust-contract', 'implement', 'qa-enforcer', 'red-queen', 'done')),
+    result TEXT NOT NULL CHECK (result IN ('started', 'passed', 'failed', 'error', 'cancelled')),
+    attempt INTEGER NOT NULL CHECK (attempt >= 0),
+    transition TEXT NOT NULL CHECK (transition IN ('advance', 'retry', 'complete', 'block', 'noop')),
+    next_stage TEXT,
+    reason TEXT NOT NULL,
+    message TEXT,
+    stage_history_id BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS bead_reservations (
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    bead_id TEXT NOT NULL,
+    agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
+    reserved_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (repo_id, bead_id)
+);
+
+CREATE TABLE IF NOT EXISTS token_usage (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
+    bead_id TEXT,
+    stage TEXT,
+    model TEXT,
+    input_tokens BIGINT NOT NULL CHECK (input_tokens >= 0),
+    output_tokens BIGINT NOT NULL CHECK (output_tokens >= 0),
+    description TEXT,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS stage_test_results (
+    id BIGSERIAL PRIMARY KEY,
+    stage_history_id BIGINT NOT NULL REFERENCES stage_history(id) ON DELETE CASCADE,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    bead_id TEXT NOT NULL,
+    stage TEXT NOT NULL,
+    name TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('passed', 'failed', 'skipped')),
+    file TEXT,
+    line INTEGER,
+    reason TEXT,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Stage command output past `SWARM_STAGE_OUTPUT_MEMORY_BYTES`, written in
+-- order while the command runs. The run's `stage_log` artifact keeps the tail.
+CREATE TABLE IF NOT EXISTS stage_output_chunks (
+    stage_history_id BIGINT NOT NULL REFERENCES stage_history(id) ON DELETE CASCADE,
+    stream TEXT NOT NULL CHECK (stream IN ('stdout', 'stderr')),
+    seq INTEGER NOT NULL CHECK (seq >= 0),
+    content BYTEA NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (stage_history_id, stream, seq)
+);
+
+CREATE TABLE IF NOT EXISTS orchestrator_events (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    event_type TEXT NOT NULL,
+    agent_id INTEGER CHECK (agent_id IS NULL OR agent_id >= 1),
+    bead_id TEXT,
+    payload JSONB NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS agent_sessions (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
+    host TEXT NOT NULL,
+    pid INTEGER NOT NULL CHECK (pid >= 0),
+    version TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    ended_at TIMESTAMPTZ,
+    end_reason TEXT CHECK (end_reason IN ('ended', 'replaced'))
+);
+
+CREATE TABLE IF NOT EXISTS agent_kv (
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
+    bead_id TEXT NOT NULL,
+    key TEXT NOT NULL CHECK (length(key) BETWEEN 1 AND 128),
+    value JSONB NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repo_id, agent_id, bead_id, key)
+);
+
+CREATE TABLE IF NOT EXISTS bead_blackboard (
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    bead_id TEXT NOT NULL,
+    version BIGINT NOT NULL CHECK (version >= 1),
+    section TEXT NOT NULL CHECK (section IN ('plan', 'decisions', 'open_questions')),
+    agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
+    content TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repo_id, bead_id, version)
+);
+
+CREATE TABLE IF NOT EXISTS reviews (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    bead_id TEXT NOT NULL,
+    reviewer TEXT NOT NULL,
+    agent_id INTEGER CHECK (agent_id >= 1),
+    verdict TEXT NOT NULL CHECK (verdict IN ('approve', 'changes')),
+    comments TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS approvals (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    bead_id TEXT NOT NULL,
+    gate TEXT NOT NULL,
+    token_hash TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved')),
+    requested_by INTEGER CHECK (requested_by >= 1),
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    approved_by TEXT,
+    approved_at TIMESTAMPTZ,
+    note TEXT
+);
+
+-- Approval tokens used to be stored in the clear and shown by
+-- `monitor --view approvals`; keep only their hash.
+ALTER TABLE approvals ADD COLUMN IF NOT EXISTS token_hash TEXT;
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1
+        FROM information_schema.columns
+        WHERE table_name = 'approvals' AND column_name = 'token'
+    ) THEN
+        UPDATE approvals
+        SET token_hash = encode(sha256(convert_to(token, 'UTF8')), 'hex')
+        WHERE token_hash IS NULL;
+        ALTER TABLE approvals DROP COLUMN token;
+    END IF;
+END $$;
+ALTER TABLE approvals ALTER COLUMN token_hash SET NOT NULL;
+
+CREATE TABLE IF NOT EXISTS escalations (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    bead_id TEXT NOT NULL,
+    reason TEXT NOT NULL CHECK (reason IN ('waiting', 'attempts')),
+    detail TEXT NOT NULL,
+    agent_id INTEGER CHECK (agent_id >= 1),
+    assigned_to INTEGER CHECK (assigned_to >= 1),
+    escalated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS sla_breaches (
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    bead_id TEXT NOT NULL,
+    priority TEXT NOT NULL,
+    target_secs BIGINT NOT NULL CHECK (target_secs > 0),
+    enqueued_at TIMESTAMPTZ NOT NULL,
+    breached_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repo_id, bead_id)
+);
+
+-- Tenant registry, shared by every schema in the database.
+CREATE TABLE IF NOT EXISTS public.tenants (
+    name TEXT PRIMARY KEY,
+    schema_name TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Recurring jobs `serve` runs: whether each is enabled and how its last run
+-- went. Schedules live in config; a job without a row is enabled.
+CREATE TABLE IF NOT EXISTS scheduled_jobs (
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    job TEXT NOT NULL CHECK (job IN ('sync-backlog', 'recover', 'gc', 'sla-check', 'metrics-flush', 'partitions')),
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    last_run_at TIMESTAMPTZ,
+    last_ok BOOLEAN,
+    last_ms BIGINT CHECK (last_ms >= 0),
+    last_result JSONB,
+    last_error TEXT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (repo_id, job)
+);
+ALTER TABLE scheduled_jobs DROP CONSTRAINT IF EXISTS scheduled_jobs_job_check;
+ALTER TABLE scheduled_jobs ADD CONSTRAINT scheduled_jobs_job_check CHECK (job IN ('sync-backlog', 'recover', 'gc', 'sla-check', 'metrics-flush', 'partitions'));
+
+-- Durable swarm-wide announcements from `broadcast`. Every agent sees an
+-- announcement until it expires or the agent acknowledges it, including
+-- agents registered after it was made.
+CREATE TABLE IF NOT EXISTS announcements (
+    id BIGSERIAL PRIMARY KEY,
+    repo_id TEXT NOT NULL DEFAULT 'local',
+    topic TEXT NOT NULL CHECK (length(topic) BETWEEN 1 AND 64),
+    msg TEXT NOT NULL,
+    from_agent TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_announcements_repo_expires
+    ON announcements(repo_id, expires_at);
+
+CREATE TABLE IF NOT EXISTS announcement_acks (
+    announcement_id BIGINT NOT NULL REFERENCES announcements(id) ON DELETE CASCADE,
+    agent_id INTEGER NOT NULL CHECK (agent_id >= 1),
+    acked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (announcement_id, agent_id)
+);
+
+-- Per-query latency histograms, one row per query per flush. `buckets`
+-- counts samples per latency bucket (see `swarm::query_perf`); `perf
+-- compare` sums them over a window to compare p95s.
+CREATE TABLE IF NOT EXISTS query_latency (
+    id BIGSERIAL PRIMARY KEY,
+    query_name TEXT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    swarm_version TEXT NOT NULL,
+    sample_count BIGINT NOT NULL CHECK (sample_count >= 0),
+    total_ms DOUBLE PRECISION NOT NULL,
+    max_ms DOUBLE PRECISION NOT NULL,
+    buckets BIGINT[] NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_query_latency_recorded
+    ON query_latency(recorded_at, query_name);
+
+-- Tables `maintenance partitions enable` has partitioned by month on
+-- `created_at`. Rows before `cutover_at` are in the `<table>_legacy`
+-- partition; each later month has its own, and `<table>_default` takes
+-- rows for months whose partition does not exist yet.
+CREATE TABLE IF NOT EXISTS partitioned_tables (
+    table_name TEXT PRIMARY KEY CHECK (table_name IN ('execution_events', 'stage_artifacts')),
+    cutover_at TIMESTAMPTZ NOT NULL,
+    partitioned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE bead_claims ADD COLUMN IF NOT EXISTS session_id BIGINT;
+ALTER TABLE execution_events ADD COLUMN IF NOT EXISTS session_id BIGINT;
+
 INSERT INTO swarm_config (id)
 VALUES (TRUE)
 ON CONFLICT (id) DO NOTHING;
 
+CREATE UNIQUE INDEX IF NOT EXISTS idx_agent_quarantine_active ON agent_quarantine(repo_id, agent_id)
+WHERE released_at IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_workspaces_active_agent ON workspaces(repo_id, agent_id)
+WHERE removed_at IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_workspaces_active_path ON workspaces(path)
+WHERE removed_at IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_alerts_open ON alerts(repo_id, kind)
+WHERE resolved_at IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_agent_sessions_active ON agent_sessions(repo_id, agent_id)
+WHERE ended_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_agent_kv_bead ON agent_kv(repo_id, bead_id);
+CREATE INDEX IF NOT EXISTS idx_reviews_bead ON reviews(repo_id, bead_id, id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_approvals_bead_gate ON approvals(repo_id, bead_id, gate);
+CREATE INDEX IF NOT EXISTS idx_approvals_pending ON approvals(repo_id, requested_at)
+WHERE status = 'pending';
+CREATE UNIQUE INDEX IF NOT EXISTS idx_escalations_open ON escalations(repo_id, bead_id, reason)
+WHERE resolved_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_bead_claims_deleted ON bead_claims(repo_id, deleted_at)
+WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_bead_backlog_deleted ON bead_backlog(repo_id, deleted_at)
+WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_agent_state_deleted ON agent_state(repo_id, deleted_at)
+WHERE deleted_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_agent_sessions_repo_started ON agent_sessions(repo_id, started_at DESC);
+CREATE INDEX IF NOT EXISTS idx_bead_claims_session ON bead_claims(session_id)
+WHERE session_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_execution_events_session ON execution_events(session_id, seq)
+WHERE session_id IS NOT NULL;
 CREATE INDEX IF NOT EXISTS idx_bead_backlog_claim ON bead_backlog(status, priority, created_at);
 CREATE INDEX IF NOT EXISTS idx_bead_backlog_repo_claim ON bead_backlog(repo_id, status, priority, created_at);
+CREATE INDEX IF NOT EXISTS idx_bead_backlog_labels ON bead_backlog USING GIN (labels);
 CREATE INDEX IF NOT EXISTS idx_bead_claims_status ON bead_claims(status, claimed_at);
 CREATE INDEX IF NOT EXISTS idx_bead_claims_repo_status ON bead_claims(repo_id, status, claimed_at);
 CREATE INDEX IF NOT EXISTS idx_bead_claims_lease_expires ON bead_claims(lease_expires_at)
@@ -306,6 +790,11 @@ CREATE INDEX IF NOT EXISTS idx_agent_state_repo_status ON agent_state(repo_id, s
 CREATE INDEX IF NOT EXISTS idx_stage_history_lookup ON stage_history(bead_id, stage, started_at DESC);
 CREATE INDEX IF NOT EXISTS idx_stage_history_bead_id ON stage_history(bead_id, id);
 CREATE INDEX IF NOT EXISTS idx_stage_history_failed ON stage_history(status, completed_at DESC);
+CREATE INDEX IF NOT EXISTS idx_stage_history_repo_started ON stage_history(repo_id, started_at);
+CREATE INDEX IF NOT EXISTS idx_token_usage_repo_recorded ON token_usage(repo_id, recorded_at);
+CREATE INDEX IF NOT EXISTS idx_orchestrator_events_repo_recorded ON orchestrator_events(repo_id, recorded_at);
+CREATE INDEX IF NOT EXISTS idx_stage_test_results_history ON stage_test_results(stage_history_id, status);
+CREATE INDEX IF NOT EXISTS idx_stage_test_results_repo_bead ON stage_test_results(repo_id, bead_id, recorded_at);
 CREATE INDEX IF NOT EXISTS idx_stage_artifacts_history ON stage_artifacts(stage_history_id);
 CREATE INDEX IF NOT EXISTS idx_stage_artifacts_history_created ON stage_artifacts(stage_history_id, created_at ASC);
 CREATE INDEX IF NOT EXISTS idx_stage_artifacts_type ON stage_artifacts(artifact_type);
@@ -319,9 +808,15 @@ CREATE INDEX IF NOT EXISTS idx_command_audit_t ON command_audit(t DESC);
 CREATE INDEX IF NOT EXISTS idx_command_audit_cmd ON command_audit(cmd, t DESC);
 CREATE INDEX IF NOT EXISTS idx_command_audit_ok ON command_audit(ok, t DESC);
 CREATE INDEX IF NOT EXISTS idx_execution_events_bead_seq ON execution_events(bead_id, seq);
+CREATE INDEX IF NOT EXISTS idx_symbols_repo_bead ON symbols(repo_id, bead_id, module_path, name, attempt);
 CREATE INDEX IF NOT EXISTS idx_execution_events_event_type ON execution_events(event_type, seq DESC);
 CREATE INDEX IF NOT EXISTS idx_execution_events_created ON execution_events(created_at DESC);
 CREATE INDEX IF NOT EXISTS idx_resource_locks_until ON resource_locks(until_at);
+CREATE INDEX IF NOT EXISTS idx_resource_lock_waiters_queue ON resource_lock_waiters(resource, id);
+ALTER TABLE transition_events DROP CONSTRAINT IF EXISTS transition_events_result_check;
+ALTER TABLE transition_events ADD CONSTRAINT transition_events_result_check CHECK (result IN ('started', 'passed', 'failed', 'error', 'cancelled'));
+CREATE INDEX IF NOT EXISTS idx_transition_events_bead ON transition_events(repo_id, bead_id, seq);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_bead_reservations_agent ON bead_reservations(repo_id, agent_id);
 
 CREATE OR REPLACE FUNCTION set_agent_last_update()
 RETURNS TRIGGER AS $$
@@ -337,6 +832,92 @@ BEFORE UPDATE ON agent_state
 FOR EACH ROW
 EXECUTE FUNCTION set_agent_last_update();
 
+-- Stamp claims and events with the session of the process acting as the
+-- agent, so `monitor --view sessions` can tell which pid did the work.
+CREATE OR REPLACE FUNCTION link_claim_session()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.session_id = (
+        SELECT s.id
+        FROM agent_sessions s
+        WHERE s.repo_id = NEW.repo_id
+          AND s.agent_id = NEW.claimed_by
+          AND s.ended_at IS NULL
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_bead_claims_session ON bead_claims;
+CREATE TRIGGER trg_bead_claims_session
+BEFORE INSERT OR UPDATE OF claimed_by ON bead_claims
+FOR EACH ROW
+EXECUTE FUNCTION link_claim_session();
+
+-- execution_events carries no repo_id, so the newest active session of the
+-- agent id is used.
+CREATE OR REPLACE FUNCTION link_event_session()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.session_id IS NULL AND NEW.agent_id IS NOT NULL THEN
+        NEW.session_id = (
+            SELECT s.id
+            FROM agent_sessions s
+            WHERE s.agent_id = NEW.agent_id
+              AND s.ended_at IS NULL
+            ORDER BY s.started_at DESC
+            LIMIT 1
+        );
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_execution_events_session ON execution_events;
+CREATE TRIGGER trg_execution_events_session
+BEFORE INSERT ON execution_events
+FOR EACH ROW
+EXECUTE FUNCTION link_event_session();
+
+CREATE OR REPLACE FUNCTION reject_transition_event_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'transition_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_transition_events_append_only ON transition_events;
+CREATE TRIGGER trg_transition_events_append_only
+BEFORE UPDATE OR DELETE ON transition_events
+FOR EACH ROW
+EXECUTE FUNCTION reject_transition_event_change();
+
+-- Deleted rows used to be copied out to deleted_rows; they now stay in
+-- place with deleted_at set.
+DROP TRIGGER IF EXISTS trg_bead_claims_keep_deleted ON bead_claims;
+DROP TRIGGER IF EXISTS trg_bead_backlog_keep_deleted ON bead_backlog;
+DROP TRIGGER IF EXISTS trg_agent_state_keep_deleted ON agent_state;
+DROP FUNCTION IF EXISTS keep_deleted_row();
+DROP TABLE IF EXISTS deleted_rows;
+
+-- Lease length and the grace past it before recovery, from swarm_config
+-- (`config set lease_ttl_ms=...`), falling back to the column defaults.
+CREATE OR REPLACE FUNCTION swarm_lease_ttl()
+RETURNS INTERVAL AS $$
+    SELECT COALESCE(
+        (SELECT lease_ttl_ms FROM swarm_config ORDER BY swarm_started_at DESC NULLS LAST LIMIT 1),
+        300000
+    ) * INTERVAL '1 millisecond';
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION swarm_heartbeat_grace()
+RETURNS INTERVAL AS $$
+    SELECT COALESCE(
+        (SELECT heartbeat_grace_ms FROM swarm_config ORDER BY swarm_started_at DESC NULLS LAST LIMIT 1),
+        0
+    ) * INTERVAL '1 millisecond';
+$$ LANGUAGE sql STABLE;
+
 CREATE OR REPLACE FUNCTION recover_expired_bead_claims(p_repo_id TEXT)
 RETURNS INTEGER AS $$
 DECLARE
@@ -347,12 +928,15 @@ BEGIN
         FROM bead_claims
         WHERE repo_id = p_repo_id
           AND status = 'in_progress'
-          AND lease_expires_at <= NOW()
+          AND deleted_at IS NULL
+          AND lease_expires_at + swarm_heartbeat_grace() <= NOW()
         FOR UPDATE SKIP LOCKED
     ),
     cleared_claims AS (
-        DELETE FROM bead_claims bc
-        USING expired_claims ec
+        UPDATE bead_claims bc
+        SET deleted_at = NOW(),
+            deleted_by = 'lease_expired'
+        FROM expired_claims ec
         WHERE bc.repo_id = ec.repo_id
           AND bc.bead_id = ec.bead_id
         RETURNING ec.repo_id, ec.bead_id, ec.claimed_by
@@ -364,6 +948,7 @@ BEGIN
         WHERE bb.repo_id = cc.repo_id
           AND bb.bead_id = cc.bead_id
           AND bb.status = 'in_progress'
+          AND bb.deleted_at IS NULL
         RETURNING bb.repo_id, bb.bead_id
     ),
     reset_agents AS (
@@ -378,6 +963,7 @@ BEGIN
         WHERE a.repo_id = cc.repo_id
           AND a.agent_id = cc.claimed_by
           AND a.bead_id = cc.bead_id
+          AND a.deleted_at IS NULL
         RETURNING a.repo_id, a.agent_id
     )
     SELECT COUNT(*) INTO v_recovered_count
@@ -398,18 +984,19 @@ CREATE OR REPLACE FUNCTION heartbeat_bead_claim(
     p_repo_id TEXT,
     p_agent_id INTEGER,
     p_bead_id TEXT,
-    p_lease_extension_ms INTEGER DEFAULT 300000
+    p_lease_extension_ms INTEGER DEFAULT NULL
 ) RETURNS BOOLEAN AS $$
 DECLARE
     v_updated INTEGER;
 BEGIN
     UPDATE bead_claims
     SET heartbeat_at = NOW(),
-        lease_expires_at = NOW() + (p_lease_extension_ms * INTERVAL '1 millisecond')
+        lease_expires_at = NOW() + COALESCE(p_lease_extension_ms * INTERVAL '1 millisecond', swarm_lease_ttl())
     WHERE repo_id = p_repo_id
       AND bead_id = p_bead_id
       AND claimed_by = p_agent_id
       AND status = 'in_progress'
+      AND deleted_at IS NULL
     RETURNING 1 INTO v_updated;
 
     RETURN COALESCE(v_updated, 0) = 1;
@@ -419,21 +1006,28 @@ $$ LANGUAGE plpgsql;
 CREATE OR REPLACE FUNCTION heartbeat_bead_claim(
     p_agent_id INTEGER,
     p_bead_id TEXT,
-    p_lease_extension_ms INTEGER DEFAULT 300000
+    p_lease_extension_ms INTEGER DEFAULT NULL
 ) RETURNS BOOLEAN AS $$
 BEGIN
     RETURN heartbeat_bead_claim('local', p_agent_id, p_bead_id, p_lease_extension_ms);
 END;
 $$ LANGUAGE plpgsql;
 
+-- p_labels NULL uses the agent's default labels. A pending bead qualifies
+-- when the labels are empty or it carries at least one of them, and when the
+-- agent's own labels cover its required_capabilities; a claim the agent
+-- already holds is returned whatever its labels.
 CREATE OR REPLACE FUNCTION claim_next_bead(
     p_repo_id TEXT,
-    p_agent_id INTEGER
+    p_agent_id INTEGER,
+    p_labels TEXT[]
 )
 RETURNS TEXT AS $$
 DECLARE
     v_bead_id TEXT;
-    v_claim_inserted BOOLEAN;
+    v_claim_inserted INTEGER;
+    v_labels TEXT[];
+    v_capabilities TEXT[];
 BEGIN
     PERFORM recover_expired_bead_claims(p_repo_id);
 
@@ -442,6 +1036,7 @@ BEGIN
     WHERE repo_id = p_repo_id
       AND status = 'in_progress'
       AND claimed_by = p_agent_id
+      AND deleted_at IS NULL
       AND lease_expires_at > NOW()
     ORDER BY claimed_at ASC
     FOR UPDATE SKIP LOCKED
@@ -454,15 +1049,42 @@ BEGIN
             stage_started_at = NOW(),
             status = 'working'
         WHERE repo_id = p_repo_id
-          AND agent_id = p_agent_id;
+          AND agent_id = p_agent_id
+          AND deleted_at IS NULL;
 
         RETURN v_bead_id;
     END IF;
 
+    IF EXISTS (
+        SELECT 1
+        FROM agent_quarantine
+        WHERE repo_id = p_repo_id
+          AND agent_id = p_agent_id
+          AND released_at IS NULL
+    ) THEN
+        RETURN NULL;
+    END IF;
+
+    v_capabilities := COALESCE(
+        (SELECT labels FROM agent_state WHERE repo_id = p_repo_id AND agent_id = p_agent_id AND deleted_at IS NULL),
+        '{}'
+    );
+    v_labels := COALESCE(p_labels, v_capabilities);
+
     SELECT bead_id INTO v_bead_id
-    FROM bead_backlog
+    FROM bead_backlog b
     WHERE repo_id = p_repo_id
       AND status = 'pending'
+      AND deleted_at IS NULL
+      AND (cardinality(v_labels) = 0 OR b.labels && v_labels)
+      AND b.required_capabilities <@ v_capabilities
+      AND NOT EXISTS (
+          SELECT 1
+          FROM bead_reservations r
+          WHERE r.repo_id = b.repo_id
+            AND r.bead_id = b.bead_id
+            AND r.expires_at > NOW()
+      )
     ORDER BY
         COALESCE(array_position(ARRAY['p0', 'p1', 'p2', 'p3']::TEXT[], lower(priority)), 999),
         created_at ASC
@@ -478,10 +1100,20 @@ BEGIN
     WHERE repo_id = p_repo_id
       AND bead_id = v_bead_id;
 
-    -- Check if claim was inserted successfully
+    -- Check if claim was inserted successfully; a deleted claim on the bead
+    -- is taken over, a live one is a conflict
     INSERT INTO bead_claims (repo_id, bead_id, claimed_by, status, heartbeat_at, lease_expires_at)
-    VALUES (p_repo_id, v_bead_id, p_agent_id, 'in_progress', NOW(), NOW() + INTERVAL '5 minutes')
-    ON CONFLICT (repo_id, bead_id) DO NOTHING;
+    VALUES (p_repo_id, v_bead_id, p_agent_id, 'in_progress', NOW(), NOW() + swarm_lease_ttl())
+    ON CONFLICT (repo_id, bead_id) DO UPDATE
+    SET claimed_by = EXCLUDED.claimed_by,
+        status = EXCLUDED.status,
+        claimed_at = NOW(),
+        heartbeat_at = EXCLUDED.heartbeat_at,
+        lease_expires_at = EXCLUDED.lease_expires_at,
+        takeover_consented_at = NULL,
+        deleted_at = NULL,
+        deleted_by = NULL
+    WHERE bead_claims.deleted_at IS NOT NULL;
 
     GET DIAGNOSTICS v_claim_inserted = ROW_COUNT;
     IF v_claim_inserted = 0 THEN
@@ -499,12 +1131,23 @@ BEGIN
         stage_started_at = NOW(),
         status = 'working'
     WHERE repo_id = p_repo_id
-      AND agent_id = p_agent_id;
+      AND agent_id = p_agent_id
+      AND deleted_at IS NULL;
 
     RETURN v_bead_id;
 END;
 $$ LANGUAGE plpgsql;
 
+CREATE OR REPLACE FUNCTION claim_next_bead(
+    p_repo_id TEXT,
+    p_agent_id INTEGER
+)
+RETURNS TEXT AS $$
+BEGIN
+    RETURN claim_next_bead(p_repo_id, p_agent_id, NULL::TEXT[]);
+END;
+$$ LANGUAGE plpgsql;
+
 CREATE OR REPLACE FUNCTION claim_next_p0_bead(p_agent_id INTEGER)
 RETURNS TEXT AS $$
 BEGIN
@@ -674,7 +1317,8 @@ SELECT
     a.stage_started_at,
     a.last_update
 FROM agent_state a
-WHERE a.status IN ('working', 'waiting', 'error');
+WHERE a.status IN ('working', 'waiting', 'error')
+  AND a.deleted_at IS NULL;
 
 CREATE OR REPLACE VIEW v_swarm_progress AS
 SELECT
@@ -690,20 +1334,24 @@ SELECT
         FROM bead_claims bc
         WHERE bc.repo_id = a.repo_id
           AND bc.status = 'completed'
+          AND bc.deleted_at IS NULL
     ) AS completed_beads,
     (
         SELECT COUNT(*)
         FROM bead_claims bc
         WHERE bc.repo_id = a.repo_id
           AND bc.status = 'in_progress'
+          AND bc.deleted_at IS NULL
     ) AS in_progress_beads,
     (
         SELECT COUNT(*)
         FROM bead_claims bc
         WHERE bc.repo_id = a.repo_id
           AND bc.status = 'blocked'
+          AND bc.deleted_at IS NULL
     ) AS blocked_beads
 FROM agent_state a
+WHERE a.deleted_at IS NULL
 GROUP BY a.repo_id;
 
 CREATE OR REPLACE VIEW v_feedback_required AS
@@ -716,7 +1364,7 @@ SELECT DISTINCT ON (bc.repo_id, sh.bead_id, sh.stage)
     sh.feedback,
     sh.completed_at
 FROM stage_history sh
-JOIN bead_claims bc ON bc.bead_id = sh.bead_id
+JOIN bead_claims bc ON bc.bead_id = sh.bead_id AND bc.deleted_at IS NULL
 WHERE sh.status IN ('failed', 'error')
 ORDER BY bc.repo_id, sh.bead_id, sh.stage, sh.completed_at DESC;
 
@@ -773,8 +1421,9 @@ SELECT
     c.max_agents
 FROM agent_state a
 CROSS JOIN swarm_config c
-WHERE a.status = 'idle'
-   OR (a.status = 'waiting' AND a.implementation_attempt < c.max_implementation_attempts);
+WHERE a.deleted_at IS NULL
+  AND (a.status = 'idle'
+       OR (a.status = 'waiting' AND a.implementation_attempt < c.max_implementation_attempts));
 
 -- Compatibility view for agent prompts that reference `beads` directly.
 CREATE OR REPLACE VIEW beads AS
@@ -785,7 +1434,8 @@ SELECT
     b.priority,
     b.status,
     b.created_at
-FROM bead_backlog b;
+FROM bead_backlog b
+WHERE b.deleted_at IS NULL;
 
 CREATE OR REPLACE VIEW v_resume_context AS
 SELECT
@@ -797,6 +1447,7 @@ SELECT
     a.feedback,
     a.status,
     a.last_update
-FROM agent_state a;
+FROM agent_state a
+WHERE a.deleted_at IS NULL;
 
 COMMIT;
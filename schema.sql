@@ -92,6 +92,7 @@ CREATE TABLE IF NOT EXISTS execution_events (
     diagnostics_retryable BOOLEAN,
     diagnostics_next_command TEXT,
     diagnostics_detail TEXT,
+    diagnostics_failing_tests TEXT[],
     payload JSONB,
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
@@ -117,6 +118,13 @@ CREATE TABLE IF NOT EXISTS swarm_config (
     swarm_status TEXT NOT NULL DEFAULT 'initializing' CHECK (swarm_status IN ('initializing', 'running', 'paused', 'complete', 'error'))
 );
 
+ALTER TABLE swarm_config ADD COLUMN IF NOT EXISTS lease_ttl_ms INTEGER NOT NULL DEFAULT 300000
+    CHECK (lease_ttl_ms BETWEEN 10000 AND 86400000);
+ALTER TABLE swarm_config ADD COLUMN IF NOT EXISTS heartbeat_grace_ms INTEGER NOT NULL DEFAULT 0
+    CHECK (heartbeat_grace_ms BETWEEN 0 AND 3600000);
+ALTER TABLE swarm_config ADD COLUMN IF NOT EXISTS recovery_scan_interval_ms INTEGER NOT NULL DEFAULT 30000
+    CHECK (recovery_scan_interval_ms BETWEEN 1000 AND 3600000);
+
 -- ============================================================
 -- Functions: Core Operations
 -- ============================================================
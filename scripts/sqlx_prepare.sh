@@ -0,0 +1,47 @@
+#!/usr/bin/env bash
+
+set -euo pipefail
+
+ROOT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")/.." && pwd)"
+DB_URL="${SWARM_TEST_DATABASE_URL:-${DATABASE_URL:-}}"
+MODE="${1:-prepare}"
+
+if [[ "${MODE}" != "prepare" && "${MODE}" != "check" ]]; then
+	printf "usage: %s [prepare|check]\n" "$0"
+	exit 2
+fi
+
+if [[ -z "${DB_URL}" ]]; then
+	printf "sqlx-%s: SWARM_TEST_DATABASE_URL or DATABASE_URL must point at a scratch database\n" "${MODE}"
+	exit 2
+fi
+
+if ! PGCONNECT_TIMEOUT=3 psql "${DB_URL}" -c "SELECT 1" >/dev/null 2>&1; then
+	printf "sqlx-%s preflight failed: cannot reach database from configured URL\n" "${MODE}"
+	exit 3
+fi
+
+echo "[sqlx-${MODE}] applying canonical schema"
+psql "${DB_URL}" -v ON_ERROR_STOP=1 -q -f "${ROOT_DIR}/crates/swarm-coordinator/schema.sql" >/dev/null
+
+cd "${ROOT_DIR}"
+OUT_DIR="$(mktemp -d)"
+trap 'rm -rf "${OUT_DIR}"' EXIT
+
+# The macros write one query-<hash>.json per statement into SQLX_OFFLINE_DIR while they
+# expand online, so a clean build of every target yields the complete cache.
+cargo clean -p shitty-swarm-manager
+DATABASE_URL="${DB_URL}" SQLX_OFFLINE=false SQLX_OFFLINE_DIR="${OUT_DIR}" \
+	cargo check --all-targets --all-features
+
+if [[ "${MODE}" == "check" ]]; then
+	if ! diff -r "${OUT_DIR}" .sqlx >/dev/null; then
+		echo "[sqlx-check] .sqlx/ is stale; run scripts/sqlx_prepare.sh prepare and commit the result"
+		exit 1
+	fi
+	echo "[sqlx-check] .sqlx/ matches the query macros"
+else
+	rm -rf .sqlx
+	cp -r "${OUT_DIR}" .sqlx
+	echo "[sqlx-prepare] metadata written to .sqlx/; commit it alongside query changes"
+fi
@@ -0,0 +1,163 @@
+//! Evaluating alert rules against the swarm and announcing changes. Fired
+//! and resolved alerts go to the execution event log and, when configured,
+//! to a webhook and the routed notifiers.
+
+use crate::notifications::deliver;
+use crate::notifications::post_json;
+use crate::types::{
+    alert_transition, AlertObservation, AlertRules, AlertTransition, Notification,
+    NotificationRules, NotificationSeverity, SwarmAlert,
+};
+use crate::{RepoId, Result, SwarmDb, SwarmError};
+use serde::Serialize;
+
+/// Seconds a webhook delivery may take before it is abandoned.
+pub const WEBHOOK_TIMEOUT_SECS: u32 = 10;
+
+/// Outcome of one pass over the rules.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AlertEvaluation {
+    pub observation: AlertObservation,
+    /// Pending and firing alerts after this pass.
+    pub open: Vec<SwarmAlert>,
+    pub fired: Vec<SwarmAlert>,
+    pub resolved: Vec<SwarmAlert>,
+    /// Deliveries that failed; they do not fail the evaluation.
+    pub webhook_errors: Vec<String>,
+    pub notification_errors: Vec<String>,
+}
+
+/// Moves every rule's alert one step.
+///
+/// Opens breaches, fires the ones that held long enough, and resolves the
+/// ones that cleared. Each fire and each resolution of a fired alert is
+/// recorded as an execution event, posted to the webhook and sent to the
+/// notifiers routed for it.
+///
+/// # Errors
+/// Returns an error if reading counters or writing alert state fails.
+pub async fn evaluate_alerts(
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    rules: &AlertRules,
+    notifications: &NotificationRules,
+) -> Result<AlertEvaluation> {
+    let observation = db
+        .get_alert_observation(repo_id, rules.error_window_mins())
+        .await?;
+    let breaches = rules.breaches(&observation);
+    let open = db.get_open_alerts(repo_id).await?;
+    let now = chrono::Utc::now();
+
+    let mut evaluation = AlertEvaluation {
+        observation,
+        ..AlertEvaluation::default()
+    };
+    let kinds = open
+        .iter()
+        .map(|alert| alert.kind)
+        .chain(breaches.iter().map(|breach| breach.kind))
+        .collect::<std::collections::BTreeSet<_>>();
+    for kind in kinds {
+        let alert = open.iter().find(|alert| alert.kind == kind);
+        let breach = breaches.iter().find(|breach| breach.kind == kind);
+        match alert_transition(alert, breach, now) {
+            AlertTransition::Hold => {}
+            AlertTransition::Open(breach) => {
+                db.open_alert(repo_id, &breach).await?;
+            }
+            AlertTransition::Fire(breach) => {
+                if let Some(fired) = db.fire_alert(repo_id, &breach).await? {
+                    announce(
+                        db,
+                        repo_id,
+                        rules,
+                        notifications,
+                        "alert_fired",
+                        &fired,
+                        &mut evaluation,
+                    )
+                    .await?;
+                    evaluation.fired.push(fired);
+                }
+            }
+            AlertTransition::Resolve => {
+                // A pending alert that never fired was never announced.
+                if let Some(resolved) = db
+                    .resolve_alert(repo_id, kind)
+                    .await?
+                    .filter(|resolved| resolved.fired_at.is_some())
+                {
+                    announce(
+                        db,
+                        repo_id,
+                        rules,
+                        notifications,
+                        "alert_resolved",
+                        &resolved,
+                        &mut evaluation,
+                    )
+                    .await?;
+                    evaluation.resolved.push(resolved);
+                }
+            }
+        }
+    }
+
+    evaluation.open = db.get_open_alerts(repo_id).await?;
+    Ok(evaluation)
+}
+
+async fn announce(
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    rules: &AlertRules,
+    notifications: &NotificationRules,
+    event_type: &str,
+    alert: &SwarmAlert,
+    evaluation: &mut AlertEvaluation,
+) -> Result<()> {
+    db.record_alert_event(repo_id, event_type, alert).await?;
+    let notification = Notification {
+        event: event_type.to_string(),
+        severity: if event_type == "alert_fired" {
+            NotificationSeverity::Critical
+        } else {
+            NotificationSeverity::Info
+        },
+        repo_id: repo_id.value().to_string(),
+        text: format!("{} alert: {}", alert.kind.as_str(), alert.detail),
+        details: serde_json::to_value(alert)?,
+    };
+    evaluation
+        .notification_errors
+        .extend(deliver(notifications, &notification).await.errors());
+    if let Some(url) = rules.webhook_url.as_deref() {
+        let payload = serde_json::json!({
+            "event": event_type,
+            "repo_id": repo_id.value(),
+            "alert": alert,
+        });
+        if let Err(error) = notify_webhook(url, &payload).await {
+            evaluation.webhook_errors.push(error.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// POSTs `payload` as JSON to `url` with `curl`.
+///
+/// # Errors
+/// Returns `SwarmError::IoError` if `curl` cannot run or the endpoint
+/// answers with an error status.
+pub async fn notify_webhook(url: &str, payload: &serde_json::Value) -> Result<()> {
+    post_json(url, &payload.to_string()).await.map_err(|error| {
+        let detail = match error {
+            SwarmError::IoError(error) => error.to_string(),
+            other => other.to_string(),
+        };
+        SwarmError::IoError(std::io::Error::other(format!(
+            "Alert webhook {url} failed: {detail}"
+        )))
+    })
+}
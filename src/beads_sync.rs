@@ -1,4 +1,12 @@
-// Placeholder for beads_sync module
+//! Keeping coordinator claims and `br` statuses in step.
+//!
+//! A divergence is a bead whose claim and `br` status disagree; each one
+//! maps to a single repair, either a `br update` or a coordinator-side
+//! claim fix.
+
+use crate::types::BrIssue;
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BrSyncStatus {
     Synchronized,
@@ -6,16 +14,340 @@ pub enum BrSyncStatus {
     Diverged,
 }
 
-#[derive(Debug, Clone)]
-pub struct BrSyncAction;
-#[derive(Debug, Clone)]
-pub struct BrSyncDecision;
-#[derive(Debug, Clone)]
-pub struct BrSyncDivergence;
-#[derive(Debug, Clone)]
-pub struct CoordinatorSyncTerminal;
+/// Where a coordinator-side fix leaves a claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinatorSyncTerminal {
+    Completed,
+    Blocked,
+}
+
+impl CoordinatorSyncTerminal {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Completed => "completed",
+            Self::Blocked => "blocked",
+        }
+    }
+
+    /// The `br` status matching this claim status.
+    #[must_use]
+    pub const fn br_status(self) -> &'static str {
+        match self {
+            Self::Completed => "closed",
+            Self::Blocked => "blocked",
+        }
+    }
+}
 
 #[must_use]
 pub fn map_terminal_sync_state(_state: &str) -> String {
     "synced".to_string()
 }
+
+/// A `bead_claims` row as the divergence check sees it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoordinatorClaim {
+    pub bead_id: String,
+    pub agent_id: u32,
+    /// `in_progress`, `completed`, or `blocked`.
+    pub status: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BrSyncDivergence {
+    /// The swarm finished the bead but `br` still has it open.
+    CompletedNotClosed { bead_id: String, br_status: String },
+    /// The swarm blocked the bead but `br` does not say so.
+    BlockedNotBlocked { bead_id: String, br_status: String },
+    /// An agent holds the bead but `br` does not show it in progress.
+    ClaimedNotInProgress {
+        bead_id: String,
+        agent_id: u32,
+        br_status: String,
+    },
+    /// `br` closed the bead while an agent still holds it.
+    ClosedWhileClaimed { bead_id: String, agent_id: u32 },
+    /// An agent holds a bead `br` does not know.
+    MissingFromBr { bead_id: String, agent_id: u32 },
+}
+
+impl BrSyncDivergence {
+    #[must_use]
+    pub fn bead_id(&self) -> &str {
+        match self {
+            Self::CompletedNotClosed { bead_id, .. }
+            | Self::BlockedNotBlocked { bead_id, .. }
+            | Self::ClaimedNotInProgress { bead_id, .. }
+            | Self::ClosedWhileClaimed { bead_id, .. }
+            | Self::MissingFromBr { bead_id, .. } => bead_id,
+        }
+    }
+
+    /// The repair for this divergence. The coordinator is trusted for work
+    /// it finished or blocked; `br` is trusted when it closed or never had
+    /// the bead.
+    #[must_use]
+    pub fn repair(&self) -> BrSyncAction {
+        match self {
+            Self::CompletedNotClosed { bead_id, .. } => BrSyncAction::BrUpdate {
+                bead_id: bead_id.clone(),
+                status: CoordinatorSyncTerminal::Completed.br_status().to_string(),
+                assignee: None,
+            },
+            Self::BlockedNotBlocked { bead_id, .. } => BrSyncAction::BrUpdate {
+                bead_id: bead_id.clone(),
+                status: CoordinatorSyncTerminal::Blocked.br_status().to_string(),
+                assignee: None,
+            },
+            Self::ClaimedNotInProgress {
+                bead_id, agent_id, ..
+            } => BrSyncAction::BrUpdate {
+                bead_id: bead_id.clone(),
+                status: "in_progress".to_string(),
+                assignee: Some(format!("swarm-agent-{agent_id}")),
+            },
+            Self::ClosedWhileClaimed { bead_id, agent_id } => BrSyncAction::SettleClaim {
+                bead_id: bead_id.clone(),
+                agent_id: *agent_id,
+                terminal: CoordinatorSyncTerminal::Completed,
+            },
+            Self::MissingFromBr { bead_id, agent_id } => BrSyncAction::SettleClaim {
+                bead_id: bead_id.clone(),
+                agent_id: *agent_id,
+                terminal: CoordinatorSyncTerminal::Blocked,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BrSyncAction {
+    /// `br update <bead> --status <status> [--assignee <assignee>] --json`.
+    BrUpdate {
+        bead_id: String,
+        status: String,
+        assignee: Option<String>,
+    },
+    /// Move the agent's in-progress claim to `terminal` and free the agent.
+    SettleClaim {
+        bead_id: String,
+        agent_id: u32,
+        terminal: CoordinatorSyncTerminal,
+    },
+}
+
+impl BrSyncAction {
+    /// Arguments for `br`, or `None` for a coordinator-side fix.
+    #[must_use]
+    pub fn br_args(&self) -> Option<Vec<String>> {
+        match self {
+            Self::BrUpdate {
+                bead_id,
+                status,
+                assignee,
+            } => Some(br_update_args(bead_id, status, assignee.as_deref())),
+            Self::SettleClaim { .. } => None,
+        }
+    }
+
+    /// One line naming what the repair does, for dry-run listings.
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match self {
+            Self::BrUpdate {
+                bead_id,
+                status,
+                assignee,
+            } => format!(
+                "br {}",
+                br_update_args(bead_id, status, assignee.as_deref()).join(" ")
+            ),
+            Self::SettleClaim {
+                bead_id,
+                agent_id,
+                terminal,
+            } => format!(
+                "mark claim on {bead_id} {} and free agent {agent_id}",
+                terminal.as_str()
+            ),
+        }
+    }
+}
+
+fn br_update_args(bead_id: &str, status: &str, assignee: Option<&str>) -> Vec<String> {
+    let mut args = vec![
+        "update".to_string(),
+        bead_id.to_string(),
+        "--status".to_string(),
+        status.to_string(),
+    ];
+    if let Some(assignee) = assignee {
+        args.extend(["--assignee".to_string(), assignee.to_string()]);
+    }
+    args.push("--json".to_string());
+    args
+}
+
+/// A divergence paired with the repair chosen for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrSyncDecision {
+    pub divergence: BrSyncDivergence,
+    pub action: BrSyncAction,
+}
+
+impl From<BrSyncDivergence> for BrSyncDecision {
+    fn from(divergence: BrSyncDivergence) -> Self {
+        let action = divergence.repair();
+        Self { divergence, action }
+    }
+}
+
+/// Compares every coordinator claim with `br`, in claim order. Beads `br`
+/// has that the swarm never claimed are the backlog sync's concern, not a
+/// divergence.
+#[must_use]
+pub fn detect_divergences(
+    claims: &[CoordinatorClaim],
+    issues: &[BrIssue],
+) -> Vec<BrSyncDivergence> {
+    claims
+        .iter()
+        .filter_map(|claim| {
+            let bead_id = claim.bead_id.clone();
+            let br_status = issues
+                .iter()
+                .find(|issue| issue.id == claim.bead_id)
+                .map(|issue| issue.status.clone());
+            match (claim.status.as_str(), br_status) {
+                ("in_progress", None) => Some(BrSyncDivergence::MissingFromBr {
+                    bead_id,
+                    agent_id: claim.agent_id,
+                }),
+                ("in_progress", Some(status)) if status == "closed" => {
+                    Some(BrSyncDivergence::ClosedWhileClaimed {
+                        bead_id,
+                        agent_id: claim.agent_id,
+                    })
+                }
+                ("in_progress", Some(br_status)) if br_status != "in_progress" => {
+                    Some(BrSyncDivergence::ClaimedNotInProgress {
+                        bead_id,
+                        agent_id: claim.agent_id,
+                        br_status,
+                    })
+                }
+                ("completed", Some(br_status)) if br_status != "closed" => {
+                    Some(BrSyncDivergence::CompletedNotClosed { bead_id, br_status })
+                }
+                ("blocked", Some(br_status)) if br_status != "blocked" && br_status != "closed" => {
+                    Some(BrSyncDivergence::BlockedNotBlocked { bead_id, br_status })
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn claim(bead_id: &str, agent_id: u32, status: &str) -> CoordinatorClaim {
+        CoordinatorClaim {
+            bead_id: bead_id.to_string(),
+            agent_id,
+            status: status.to_string(),
+        }
+    }
+
+    fn issue(id: &str, status: &str) -> BrIssue {
+        BrIssue {
+            id: id.to_string(),
+            status: status.to_string(),
+            priority: None,
+            labels: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn given_claims_and_br_when_detecting_then_each_disagreement_is_reported() {
+        let claims = [
+            claim("bd-done", 1, "completed"),
+            claim("bd-blocked", 2, "blocked"),
+            claim("bd-open", 3, "in_progress"),
+            claim("bd-closed", 4, "in_progress"),
+            claim("bd-gone", 5, "in_progress"),
+            claim("bd-fine", 6, "in_progress"),
+            claim("bd-blocked-closed", 7, "blocked"),
+        ];
+        let issues = [
+            issue("bd-done", "in_progress"),
+            issue("bd-blocked", "open"),
+            issue("bd-open", "open"),
+            issue("bd-closed", "closed"),
+            issue("bd-fine", "in_progress"),
+            issue("bd-blocked-closed", "closed"),
+        ];
+
+        let divergences = detect_divergences(&claims, &issues);
+
+        assert_eq!(
+            divergences,
+            [
+                BrSyncDivergence::CompletedNotClosed {
+                    bead_id: "bd-done".to_string(),
+                    br_status: "in_progress".to_string(),
+                },
+                BrSyncDivergence::BlockedNotBlocked {
+                    bead_id: "bd-blocked".to_string(),
+                    br_status: "open".to_string(),
+                },
+                BrSyncDivergence::ClaimedNotInProgress {
+                    bead_id: "bd-open".to_string(),
+                    agent_id: 3,
+                    br_status: "open".to_string(),
+                },
+                BrSyncDivergence::ClosedWhileClaimed {
+                    bead_id: "bd-closed".to_string(),
+                    agent_id: 4,
+                },
+                BrSyncDivergence::MissingFromBr {
+                    bead_id: "bd-gone".to_string(),
+                    agent_id: 5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn given_divergences_when_planning_repairs_then_br_or_claim_is_fixed() {
+        let reclaimed = BrSyncDecision::from(BrSyncDivergence::ClaimedNotInProgress {
+            bead_id: "bd-1".to_string(),
+            agent_id: 3,
+            br_status: "open".to_string(),
+        });
+        let settled = BrSyncDecision::from(BrSyncDivergence::ClosedWhileClaimed {
+            bead_id: "bd-2".to_string(),
+            agent_id: 4,
+        });
+
+        assert_eq!(
+            reclaimed.action.describe(),
+            "br update bd-1 --status in_progress --assignee swarm-agent-3 --json"
+        );
+        assert_eq!(settled.action.br_args(), None);
+        assert_eq!(
+            settled.action,
+            BrSyncAction::SettleClaim {
+                bead_id: "bd-2".to_string(),
+                agent_id: 4,
+                terminal: CoordinatorSyncTerminal::Completed,
+            }
+        );
+    }
+}
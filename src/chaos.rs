@@ -0,0 +1,349 @@
+//! Fault injection for exercising recovery and lease handling.
+//!
+//! Built only with the `chaos` feature; without it every hook is a no-op
+//! and [`status`] reports `compiled: false`. Faults are drawn from a
+//! `SplitMix64` stream per fault kind, so a given seed and call sequence
+//! injects the same faults on every run.
+//!
+//! Configure through [`install`], or set `SWARM_CHAOS_SEED` (plus any of
+//! `SWARM_CHAOS_DB_ERROR_RATE`, `SWARM_CHAOS_EXTERNAL_DELAY_RATE`,
+//! `SWARM_CHAOS_EXTERNAL_DELAY_MS`, `SWARM_CHAOS_HEARTBEAT_DROP_RATE`) before
+//! the first hook runs. Variables that do not parse, or rates outside
+//! `[0, 1]`, leave injection off and are reported by [`status`],
+//! `chaos status` and `doctor`.
+
+use crate::error::{Result, SwarmError};
+use serde::{Deserialize, Serialize};
+
+/// Failure rates, each the chance in `[0, 1]` that one call is hit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChaosConfig {
+    pub seed: u64,
+    /// Claim, heartbeat, and lock queries fail with a `DatabaseError`.
+    pub db_error_rate: f64,
+    /// External commands (`br`, `bv`, `moon`) start `external_delay_ms` late.
+    pub external_delay_rate: f64,
+    pub external_delay_ms: u64,
+    /// Heartbeats report success without extending the lease.
+    pub heartbeat_drop_rate: f64,
+}
+
+impl ChaosConfig {
+    /// # Errors
+    /// Returns `SwarmError::ConfigError` if a rate is outside `[0, 1]`.
+    pub fn validate(&self) -> Result<()> {
+        [
+            ("db_error_rate", self.db_error_rate),
+            ("external_delay_rate", self.external_delay_rate),
+            ("heartbeat_drop_rate", self.heartbeat_drop_rate),
+        ]
+        .into_iter()
+        .find(|(_, rate)| !(0.0..=1.0).contains(rate))
+        .map_or(Ok(()), |(name, rate)| {
+            Err(SwarmError::ConfigError(format!(
+                "chaos {name} must be between 0 and 1, got {rate}"
+            )))
+        })
+    }
+}
+
+/// Faults injected since chaos was configured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChaosCounts {
+    pub db_errors: u64,
+    pub external_delays: u64,
+    pub dropped_heartbeats: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChaosStatus {
+    /// Whether this build has the `chaos` feature.
+    pub compiled: bool,
+    pub enabled: bool,
+    pub config: Option<ChaosConfig>,
+    pub injected: ChaosCounts,
+    /// Why the `SWARM_CHAOS_*` variables were rejected, if they were.
+    pub config_error: Option<String>,
+}
+
+/// Reads the `SWARM_CHAOS_*` variables; `None` when `SWARM_CHAOS_SEED` is
+/// unset.
+///
+/// # Errors
+/// Returns `SwarmError::ConfigError` if a variable does not parse or a rate
+/// is outside `[0, 1]`.
+pub fn config_from_env() -> Result<Option<ChaosConfig>> {
+    fn parse<T: std::str::FromStr>(name: &str, kind: &str) -> Result<Option<T>> {
+        std::env::var(name).ok().map_or(Ok(None), |value| {
+            value.trim().parse::<T>().map(Some).map_err(|_| {
+                SwarmError::ConfigError(format!("{name} must be {kind}, got {value:?}"))
+            })
+        })
+    }
+
+    let Some(seed) = parse::<u64>("SWARM_CHAOS_SEED", "an unsigned integer")? else {
+        return Ok(None);
+    };
+    let rate = |name: &str| parse::<f64>(name, "a number").map(|rate| rate.unwrap_or(0.0));
+    let config = ChaosConfig {
+        seed,
+        db_error_rate: rate("SWARM_CHAOS_DB_ERROR_RATE")?,
+        external_delay_rate: rate("SWARM_CHAOS_EXTERNAL_DELAY_RATE")?,
+        external_delay_ms: parse::<u64>("SWARM_CHAOS_EXTERNAL_DELAY_MS", "an unsigned integer")?
+            .unwrap_or(0),
+        heartbeat_drop_rate: rate("SWARM_CHAOS_HEARTBEAT_DROP_RATE")?,
+    };
+    config.validate()?;
+    Ok(Some(config))
+}
+
+/// Fails a database call when the draw says so.
+///
+/// # Errors
+/// Returns `SwarmError::DatabaseError` naming `operation` when a fault is
+/// injected.
+#[cfg(feature = "chaos")]
+pub(crate) fn db_fault(operation: &str) -> Result<()> {
+    if engine::draw(engine::Fault::DbError) {
+        return Err(SwarmError::DatabaseError(format!(
+            "chaos: injected failure in {operation}"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "chaos"))]
+#[allow(clippy::unnecessary_wraps)]
+pub(crate) const fn db_fault(_operation: &str) -> Result<()> {
+    Ok(())
+}
+
+/// How long to hold back the next external command, when the draw says so.
+#[cfg(feature = "chaos")]
+pub(crate) fn external_command_delay() -> Option<std::time::Duration> {
+    engine::external_delay()
+}
+
+#[cfg(not(feature = "chaos"))]
+pub(crate) const fn external_command_delay() -> Option<std::time::Duration> {
+    None
+}
+
+/// Whether to swallow this heartbeat.
+#[cfg(feature = "chaos")]
+pub(crate) fn drop_heartbeat() -> bool {
+    engine::draw(engine::Fault::HeartbeatDrop)
+}
+
+#[cfg(not(feature = "chaos"))]
+pub(crate) const fn drop_heartbeat() -> bool {
+    false
+}
+
+/// Current chaos settings and what has been injected so far.
+#[cfg(feature = "chaos")]
+#[must_use]
+pub fn status() -> ChaosStatus {
+    engine::status()
+}
+
+/// Current chaos settings and what has been injected so far.
+#[cfg(not(feature = "chaos"))]
+#[must_use]
+pub const fn status() -> ChaosStatus {
+    ChaosStatus {
+        compiled: false,
+        enabled: false,
+        config: None,
+        injected: ChaosCounts {
+            db_errors: 0,
+            external_delays: 0,
+            dropped_heartbeats: 0,
+        },
+        config_error: None,
+    }
+}
+
+/// Replaces any chaos settings, restarting every fault stream from `seed`
+/// and zeroing the counts.
+///
+/// # Errors
+/// Returns `SwarmError::ConfigError` if the config is invalid.
+#[cfg(feature = "chaos")]
+pub fn install(config: ChaosConfig) -> Result<()> {
+    config.validate()?;
+    engine::install(Some(config));
+    Ok(())
+}
+
+/// Turns fault injection off.
+#[cfg(feature = "chaos")]
+pub fn clear() {
+    engine::install(None);
+}
+
+#[cfg(feature = "chaos")]
+mod engine {
+    use super::{config_from_env, ChaosConfig, ChaosCounts, ChaosStatus};
+    use std::sync::{Mutex, OnceLock, PoisonError};
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, Copy)]
+    pub(super) enum Fault {
+        DbError,
+        ExternalDelay,
+        HeartbeatDrop,
+    }
+
+    struct Engine {
+        config: ChaosConfig,
+        streams: [SplitMix64; 3],
+        counts: ChaosCounts,
+    }
+
+    impl Engine {
+        fn new(config: ChaosConfig) -> Self {
+            // Separate streams keep one kind's draws independent of how
+            // often the others are consulted.
+            Self {
+                config,
+                streams: [0_u64, 1, 2].map(|kind| SplitMix64(config.seed ^ kind.rotate_right(2))),
+                counts: ChaosCounts::default(),
+            }
+        }
+
+        fn draw(&mut self, fault: Fault) -> bool {
+            let rate = match fault {
+                Fault::DbError => self.config.db_error_rate,
+                Fault::ExternalDelay => self.config.external_delay_rate,
+                Fault::HeartbeatDrop => self.config.heartbeat_drop_rate,
+            };
+            let hit = self.streams[fault as usize].next_unit() < rate;
+            if hit {
+                let count = match fault {
+                    Fault::DbError => &mut self.counts.db_errors,
+                    Fault::ExternalDelay => &mut self.counts.external_delays,
+                    Fault::HeartbeatDrop => &mut self.counts.dropped_heartbeats,
+                };
+                *count += 1;
+            }
+            hit
+        }
+    }
+
+    struct SplitMix64(u64);
+
+    impl SplitMix64 {
+        const fn next_u64(&mut self) -> u64 {
+            self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = self.0;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        }
+
+        /// Uniform draw in `[0, 1)`.
+        fn next_unit(&mut self) -> f64 {
+            let high = u32::try_from(self.next_u64() >> 32).unwrap_or(u32::MAX);
+            f64::from(high) / (f64::from(u32::MAX) + 1.0)
+        }
+    }
+
+    fn engine() -> &'static Mutex<Option<Engine>> {
+        static ENGINE: OnceLock<Mutex<Option<Engine>>> = OnceLock::new();
+        ENGINE.get_or_init(|| Mutex::new(config_from_env().ok().flatten().map(Engine::new)))
+    }
+
+    pub(super) fn install(config: Option<ChaosConfig>) {
+        *engine().lock().unwrap_or_else(PoisonError::into_inner) = config.map(Engine::new);
+    }
+
+    pub(super) fn draw(fault: Fault) -> bool {
+        engine()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_mut()
+            .is_some_and(|engine| engine.draw(fault))
+    }
+
+    pub(super) fn external_delay() -> Option<Duration> {
+        engine()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_mut()
+            .and_then(|engine| {
+                engine
+                    .draw(Fault::ExternalDelay)
+                    .then(|| Duration::from_millis(engine.config.external_delay_ms))
+            })
+    }
+
+    pub(super) fn status() -> ChaosStatus {
+        let guard = engine().lock().unwrap_or_else(PoisonError::into_inner);
+        ChaosStatus {
+            compiled: true,
+            enabled: guard.is_some(),
+            config: guard.as_ref().map(|engine| engine.config),
+            injected: guard
+                .as_ref()
+                .map(|engine| engine.counts)
+                .unwrap_or_default(),
+            config_error: config_from_env().err().map(|error| error.to_string()),
+        }
+    }
+
+    #[cfg(test)]
+    #[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+    mod tests {
+        use super::*;
+
+        fn draws(config: ChaosConfig, fault: Fault, n: usize) -> Vec<bool> {
+            let mut engine = Engine::new(config);
+            (0..n).map(|_| engine.draw(fault)).collect()
+        }
+
+        #[test]
+        fn given_same_seed_when_drawing_then_faults_repeat() {
+            let config = ChaosConfig {
+                seed: 42,
+                db_error_rate: 0.5,
+                ..ChaosConfig::default()
+            };
+
+            assert_eq!(
+                draws(config, Fault::DbError, 64),
+                draws(config, Fault::DbError, 64)
+            );
+        }
+
+        #[test]
+        fn given_zero_and_full_rates_when_drawing_then_never_and_always_hit() {
+            let config = ChaosConfig {
+                seed: 7,
+                heartbeat_drop_rate: 1.0,
+                ..ChaosConfig::default()
+            };
+
+            assert!(draws(config, Fault::DbError, 32).iter().all(|hit| !hit));
+            assert!(draws(config, Fault::HeartbeatDrop, 32)
+                .iter()
+                .all(|hit| *hit));
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_rate_above_one_when_validating_then_config_error_is_returned() {
+        let config = ChaosConfig {
+            db_error_rate: 1.5,
+            ..ChaosConfig::default()
+        };
+
+        assert!(matches!(config.validate(), Err(SwarmError::ConfigError(_))));
+    }
+}
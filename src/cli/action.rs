@@ -5,6 +5,8 @@
 #![warn(clippy::nursery)]
 #![forbid(unsafe_code)]
 
+use super::completions::Shell;
+use super::registry::CommandSpec;
 use super::CliCommand;
 
 #[derive(Debug, Clone)]
@@ -12,5 +14,8 @@ pub enum CliAction {
     ShowHelp,
     ShowVersion,
     RunProtocol,
+    Explain(&'static CommandSpec),
+    Completions(Shell),
+    Repl,
     Command(CliCommand),
 }
@@ -6,10 +6,22 @@
 #![forbid(unsafe_code)]
 
 use super::parser::CliError;
+use super::registry::{ArgSpec, CommandSpec, CLI_ONLY_COMMANDS, COMMANDS, GLOBAL_FLAGS};
+
+/// # Errors
+/// Returns `CliError::UnknownCommand` if a flag is not declared for `spec`.
+pub fn ensure_command_flags(args: &[String], spec: &CommandSpec) -> Result<(), CliError> {
+    let declared = spec.args.iter().map(ArgSpec::flag).collect::<Vec<_>>();
+    let allowed = declared
+        .iter()
+        .map(String::as_str)
+        .chain(GLOBAL_FLAGS.iter().copied())
+        .collect::<Vec<_>>();
+    ensure_no_unknown_flags(args, &allowed)
+}
 
 /// # Errors
 /// Returns `CliError::UnknownCommand` if an unknown flag is found.
-#[allow(dead_code)]
 pub fn ensure_no_unknown_flags(args: &[String], allowed_flags: &[&str]) -> Result<(), CliError> {
     let invalid = args
         .iter()
@@ -26,41 +38,10 @@ pub fn ensure_no_unknown_flags(args: &[String], allowed_flags: &[&str]) -> Resul
 
 #[must_use]
 pub fn suggest_commands(typo: &str) -> Vec<String> {
-    const VALID_COMMANDS: &[&str] = &[
-        "doctor",
-        "help",
-        "status",
-        "next",
-        "claim-next",
-        "assign",
-        "run-once",
-        "qa",
-        "resume",
-        "resume-context",
-        "artifacts",
-        "agent",
-        "init",
-        "register",
-        "release",
-        "monitor",
-        "init-db",
-        "init-local-db",
-        "bootstrap",
-        "spawn-prompts",
-        "prompt",
-        "smoke",
-        "batch",
-        "state",
-        "history",
-        "lock",
-        "unlock",
-        "agents",
-        "broadcast",
-        "load-profile",
-    ];
-
-    VALID_COMMANDS
+    COMMANDS
         .iter()
+        .map(|spec| spec.name)
+        .chain(CLI_ONLY_COMMANDS.iter().map(|(name, _)| *name))
         .map(|cmd| (cmd, strsim::levenshtein(typo, cmd)))
         .filter(|(_, dist)| *dist <= 3)
         .min_by_key(|(_, dist)| *dist)
@@ -6,17 +6,76 @@
 #![forbid(unsafe_code)]
 
 use crate::protocol_runtime::ProtocolRequest;
-use serde_json::{json, Map};
+use serde_json::{json, Map, Value};
 
 #[derive(Debug, Clone)]
 pub enum CliCommand {
-    Doctor,
+    Doctor {
+        show_candidates: Option<bool>,
+        analyze_db: Option<bool>,
+        apply: Option<bool>,
+        dry: Option<bool>,
+    },
+    DbHealth {
+        samples: Option<u32>,
+    },
+    Healthz,
+    Invariants,
+    Costs {
+        bead_id: Option<String>,
+        since: Option<String>,
+        until: Option<String>,
+        pricing: Option<String>,
+    },
+    ReportUsage {
+        agent_id: u32,
+        bead_id: String,
+        stage: Option<String>,
+        model: String,
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        dry: Option<bool>,
+    },
+    ReportCoverage {
+        agent_id: u32,
+        bead_id: String,
+        stage: Option<String>,
+        path: Option<String>,
+        report: Option<String>,
+        format: Option<String>,
+        dry: Option<bool>,
+    },
     Help,
-    Status,
+    Status {
+        project: Option<String>,
+    },
+    Top {
+        window_mins: Option<u32>,
+    },
+    Forecast {
+        window_hours: Option<u32>,
+        agents: Option<u32>,
+    },
     Next {
         dry: Option<bool>,
     },
     ClaimNext {
+        reserve: Option<bool>,
+        agent_id: Option<u32>,
+        ttl_secs: Option<u32>,
+        /// Comma-separated labels.
+        label: Option<String>,
+        dry: Option<bool>,
+    },
+    AcceptClaim {
+        agent_id: u32,
+        bead_id: String,
+        dry: Option<bool>,
+    },
+    RejectClaim {
+        agent_id: u32,
+        bead_id: String,
+        reason: Option<String>,
         dry: Option<bool>,
     },
     Assign {
@@ -24,6 +83,34 @@ pub enum CliCommand {
         agent_id: u32,
         dry: Option<bool>,
     },
+    Cancel {
+        bead_id: String,
+        reason: Option<String>,
+        dry: Option<bool>,
+    },
+    Takeover {
+        bead_id: String,
+        to_agent: Option<u32>,
+        from_agent: Option<u32>,
+        consent: Option<bool>,
+        max_bytes: Option<u64>,
+        max_tokens: Option<u64>,
+        dry: Option<bool>,
+    },
+    Recover {
+        dry: Option<bool>,
+    },
+    Run {
+        id: Option<u32>,
+        until_empty: Option<bool>,
+        budget_ms: Option<u64>,
+        max_cycles: Option<u32>,
+        dry: Option<bool>,
+    },
+    RunAll {
+        concurrency: Option<u32>,
+        dry: Option<bool>,
+    },
     RunOnce {
         id: Option<u32>,
         dry: Option<bool>,
@@ -36,10 +123,155 @@ pub enum CliCommand {
     Resume,
     ResumeContext {
         bead_id: Option<String>,
+        max_bytes: Option<u64>,
+        max_tokens: Option<u64>,
+    },
+    Context {
+        bead_id: String,
+        skill: Option<String>,
+        max_bytes: Option<u64>,
+        max_tokens: Option<u64>,
     },
     Artifacts {
         bead_id: String,
         artifact_type: Option<String>,
+        out_dir: Option<String>,
+    },
+    Artifact {
+        action: String,
+        bead_id: String,
+        stage: String,
+        artifact_type: String,
+        file: String,
+        dry: Option<bool>,
+    },
+    Diff {
+        action: String,
+        bead_id: String,
+        stage: Option<String>,
+        file: Option<String>,
+        patch: Option<String>,
+        base_revision: Option<String>,
+        dry: Option<bool>,
+    },
+    Replay {
+        bead_id: Option<String>,
+        /// Trace written by `--record`; replays it instead of a bead.
+        file: Option<String>,
+        /// Comma-separated keys to leave out of the diff.
+        ignore: Option<String>,
+        dry: Option<bool>,
+    },
+    ExplainTransition {
+        stage: String,
+        result: String,
+        attempt: Option<u32>,
+        max_attempts: Option<u32>,
+        category: Option<String>,
+        message: Option<String>,
+    },
+    Verify {
+        bead_id: String,
+    },
+    Attest {
+        bead_id: String,
+    },
+    EnvDiff {
+        bead_id: String,
+        stage: Option<String>,
+        from_attempt: u32,
+        to_attempt: u32,
+    },
+    Enqueue {
+        beads: Option<String>,
+        file: Option<String>,
+        dry: Option<bool>,
+    },
+    SyncBacklog {
+        rounds: Option<u32>,
+        interval_secs: Option<u32>,
+        dry: Option<bool>,
+    },
+    Sync {
+        action: String,
+        dry: Option<bool>,
+    },
+    Events {
+        action: String,
+        dry: Option<bool>,
+    },
+    Chaos {
+        action: String,
+    },
+    Config {
+        action: String,
+        key: Option<String>,
+        value: Option<String>,
+        /// Settings given as `key=value` or `--key value`.
+        settings: Vec<(String, String)>,
+        dry: Option<bool>,
+    },
+    Backlog {
+        action: String,
+        /// Comma-separated bead ids.
+        bead_id: Option<String>,
+        label: Option<String>,
+        priority: Option<String>,
+        to: Option<String>,
+        limit: Option<u32>,
+        dry: Option<bool>,
+    },
+    Labels {
+        action: String,
+        agent_id: Option<u32>,
+        /// Comma-separated labels; empty clears them.
+        labels: Option<String>,
+        dry: Option<bool>,
+    },
+    Jobs {
+        action: Option<String>,
+        job: Option<String>,
+        dry: Option<bool>,
+    },
+    Serve {
+        budget_ms: Option<u64>,
+        dry: Option<bool>,
+    },
+    Profile {
+        duration_ms: Option<u64>,
+        dry: Option<bool>,
+    },
+    Perf {
+        action: String,
+        since: String,
+        until: Option<String>,
+        threshold_pct: Option<u32>,
+        min_samples: Option<u64>,
+        baseline_days: Option<u32>,
+        dry: Option<bool>,
+    },
+    Maintenance {
+        target: String,
+        action: Option<String>,
+        table: Option<String>,
+        partition: Option<String>,
+        ahead: Option<u32>,
+        dry: Option<bool>,
+    },
+    Notify {
+        action: String,
+        notifier: Option<String>,
+        event: Option<String>,
+        severity: Option<String>,
+        dry: Option<bool>,
+    },
+    Bead {
+        action: String,
+        bead_id: Option<String>,
+        agent_id: Option<u32>,
+        snapshot: Option<String>,
+        file: Option<String>,
+        dry: Option<bool>,
     },
     Agent {
         id: u32,
@@ -50,6 +282,7 @@ pub enum CliCommand {
         database_url: Option<String>,
         schema: Option<String>,
         seed_agents: Option<u32>,
+        rollback_on_error: Option<bool>,
     },
     Register {
         count: Option<u32>,
@@ -59,9 +292,86 @@ pub enum CliCommand {
         agent_id: u32,
         dry: Option<bool>,
     },
+    Quarantine {
+        agent_id: u32,
+        reason: Option<String>,
+        dry: Option<bool>,
+    },
+    Unquarantine {
+        agent_id: u32,
+        reason: String,
+        dry: Option<bool>,
+    },
+    Land {
+        bead_id: String,
+        agent_id: u32,
+        change_id: String,
+        remote: Option<String>,
+        override_push_check: Option<bool>,
+        reason: Option<String>,
+        dry: Option<bool>,
+    },
+    Workspace {
+        agent_id: u32,
+        action: Option<String>,
+        bead_id: Option<String>,
+        dry: Option<bool>,
+    },
+    Session {
+        action: String,
+        agent_id: u32,
+        host: Option<String>,
+        pid: Option<u32>,
+        version: Option<String>,
+        dry: Option<bool>,
+    },
+    Kv {
+        action: String,
+        agent_id: u32,
+        bead_id: String,
+        key: Option<String>,
+        value: Option<String>,
+        dry: Option<bool>,
+    },
+    Blackboard {
+        action: String,
+        bead_id: String,
+        agent_id: Option<u32>,
+        section: Option<String>,
+        content: Option<String>,
+        expected_version: Option<i64>,
+        dry: Option<bool>,
+    },
+    Review {
+        action: String,
+        bead_id: String,
+        verdict: Option<String>,
+        comments: Option<String>,
+        agent_id: Option<u32>,
+        reviewer: Option<String>,
+        dry: Option<bool>,
+    },
+    Approve {
+        bead_id: String,
+        token: String,
+        approver: Option<String>,
+        note: Option<String>,
+        dry: Option<bool>,
+    },
+    ReissueApproval {
+        bead_id: String,
+        gate: Option<String>,
+        dry: Option<bool>,
+    },
     Monitor {
         view: Option<String>,
+        bead_id: Option<String>,
         watch_ms: Option<u64>,
+        after_seq: Option<i64>,
+        page_size: Option<i64>,
+        since: Option<String>,
+        until: Option<String>,
+        project: Option<String>,
     },
     InitDb {
         url: Option<String>,
@@ -69,6 +379,19 @@ pub enum CliCommand {
         seed_agents: Option<u32>,
         dry: Option<bool>,
     },
+    Tenant {
+        action: String,
+        name: Option<String>,
+        seed_agents: Option<u32>,
+        confirm: Option<String>,
+        dry: Option<bool>,
+    },
+    Undelete {
+        table: Option<String>,
+        key: Option<String>,
+        limit: Option<u32>,
+        dry: Option<bool>,
+    },
     InitLocalDb {
         container_name: Option<String>,
         port: Option<u16>,
@@ -76,6 +399,16 @@ pub enum CliCommand {
         database: Option<String>,
         schema: Option<String>,
         seed_agents: Option<u32>,
+        volume: Option<String>,
+        dry: Option<bool>,
+    },
+    LocalDb {
+        action: String,
+        container_name: Option<String>,
+        to: Option<u32>,
+        tail: Option<u32>,
+        purge_volume: Option<bool>,
+        confirm: Option<String>,
         dry: Option<bool>,
     },
     Bootstrap {
@@ -85,11 +418,26 @@ pub enum CliCommand {
         template: Option<String>,
         out_dir: Option<String>,
         count: Option<u32>,
+        vars: Option<String>,
         dry: Option<bool>,
     },
     Prompt {
         id: u32,
         skill: Option<String>,
+        action: Option<String>,
+        body: Option<String>,
+        file: Option<String>,
+        template: Option<String>,
+        vars: Option<String>,
+        dry: Option<bool>,
+    },
+    RecordSymbols {
+        bead_id: String,
+        symbols: Option<String>,
+        file: Option<String>,
+        agent_id: Option<u32>,
+        attempt: Option<u32>,
+        dry: Option<bool>,
     },
     Smoke {
         id: u32,
@@ -98,14 +446,24 @@ pub enum CliCommand {
     Batch {
         dry: Option<bool>,
     },
-    State,
+    State {
+        limit: Option<u64>,
+        project: Option<String>,
+    },
     History {
         limit: Option<i64>,
+        after_seq: Option<i64>,
+        page_size: Option<i64>,
+        since: Option<String>,
+        until: Option<String>,
     },
     Lock {
         resource: String,
         agent: String,
         ttl_ms: i64,
+        wait_ms: Option<i64>,
+        purpose: Option<String>,
+        bead_id: Option<String>,
         dry: Option<bool>,
     },
     Unlock {
@@ -114,75 +472,663 @@ pub enum CliCommand {
         dry: Option<bool>,
     },
     Agents,
+    Locks,
     Broadcast {
         msg: String,
         from: String,
+        topic: Option<String>,
+        ttl_secs: Option<u64>,
+        dry: Option<bool>,
+    },
+    Announcements {
+        action: Option<String>,
+        agent_id: Option<u32>,
+        id: Option<i64>,
+        topic: Option<String>,
         dry: Option<bool>,
     },
     LoadProfile {
         agents: Option<u32>,
         rounds: Option<u32>,
         timeout_ms: Option<u64>,
+        concurrency: Option<u32>,
         dry: Option<bool>,
     },
     Json(String),
 }
 
+/// The protocol request for `cmd`, scoped to `tenant` when one is given.
 #[allow(clippy::too_many_lines)]
 #[must_use]
-pub fn cli_command_to_request(cmd: CliCommand) -> String {
-    let (cmd_name, dry, args) = match cmd {
-        CliCommand::Doctor => ("doctor".to_string(), None, Map::new()),
+pub fn cli_command_to_request(cmd: CliCommand, tenant: Option<&str>) -> String {
+    let (cmd_name, dry, mut args) = match cmd {
+        CliCommand::Doctor {
+            show_candidates,
+            analyze_db,
+            apply,
+            dry,
+        } => {
+            let mut args = Map::new();
+            if show_candidates == Some(true) {
+                args.insert("show_candidates".to_string(), json!(true));
+            }
+            if analyze_db == Some(true) {
+                args.insert("analyze_db".to_string(), json!(true));
+            }
+            if apply == Some(true) {
+                args.insert("apply".to_string(), json!(true));
+            }
+            ("doctor".to_string(), dry, args)
+        }
+        CliCommand::DbHealth { samples } => {
+            let mut args = Map::new();
+            if let Some(count) = samples {
+                args.insert("samples".to_string(), json!(count));
+            }
+            ("db-health".to_string(), None, args)
+        }
+        CliCommand::Healthz => ("healthz".to_string(), None, Map::new()),
+        CliCommand::Invariants => ("invariants".to_string(), None, Map::new()),
+        CliCommand::Costs {
+            bead_id,
+            since,
+            until,
+            pricing,
+        } => {
+            let mut args = Map::new();
+            if let Some(bead_id) = bead_id {
+                args.insert("bead_id".to_string(), json!(bead_id));
+            }
+            insert_time_bounds(&mut args, since, until);
+            insert_json_arg(&mut args, "pricing", pricing);
+            ("costs".to_string(), None, args)
+        }
+        CliCommand::ReportUsage {
+            agent_id,
+            bead_id,
+            stage,
+            model,
+            prompt_tokens,
+            completion_tokens,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("agent_id".to_string(), json!(agent_id));
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(stage) = stage {
+                args.insert("stage".to_string(), json!(stage));
+            }
+            args.insert("model".to_string(), json!(model));
+            args.insert("prompt_tokens".to_string(), json!(prompt_tokens));
+            args.insert("completion_tokens".to_string(), json!(completion_tokens));
+            ("report-usage".to_string(), dry, args)
+        }
+        CliCommand::ReportCoverage {
+            agent_id,
+            bead_id,
+            stage,
+            path,
+            report,
+            format,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("agent_id".to_string(), json!(agent_id));
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(stage) = stage {
+                args.insert("stage".to_string(), json!(stage));
+            }
+            if let Some(path) = path {
+                args.insert("path".to_string(), json!(path));
+            }
+            if let Some(report) = report {
+                args.insert("report".to_string(), json!(report));
+            }
+            if let Some(format) = format {
+                args.insert("format".to_string(), json!(format));
+            }
+            ("report-coverage".to_string(), dry, args)
+        }
+        CliCommand::Top { window_mins } => {
+            let mut args = Map::new();
+            if let Some(mins) = window_mins {
+                args.insert("window_mins".to_string(), json!(mins));
+            }
+            ("top".to_string(), None, args)
+        }
+        CliCommand::Forecast {
+            window_hours,
+            agents,
+        } => {
+            let mut args = Map::new();
+            if let Some(hours) = window_hours {
+                args.insert("window_hours".to_string(), json!(hours));
+            }
+            if let Some(agents) = agents {
+                args.insert("agents".to_string(), json!(agents));
+            }
+            ("forecast".to_string(), None, args)
+        }
         CliCommand::Help => ("?".to_string(), None, Map::new()),
-        CliCommand::Status => ("status".to_string(), None, Map::new()),
+        CliCommand::Status { project } => {
+            let mut args = Map::new();
+            if let Some(p) = project {
+                args.insert("project".to_string(), json!(p));
+            }
+            ("status".to_string(), None, args)
+        }
         CliCommand::Next { dry } => ("next".to_string(), dry, Map::new()),
-        CliCommand::ClaimNext { dry } => ("claim-next".to_string(), dry, Map::new()),
+        CliCommand::ClaimNext {
+            reserve,
+            agent_id,
+            ttl_secs,
+            label,
+            dry,
+        } => {
+            let mut args = Map::new();
+            if let Some(reserve) = reserve {
+                args.insert("reserve".to_string(), json!(reserve));
+            }
+            if let Some(agent_id) = agent_id {
+                args.insert("agent_id".to_string(), json!(agent_id));
+            }
+            if let Some(ttl_secs) = ttl_secs {
+                args.insert("ttl_secs".to_string(), json!(ttl_secs));
+            }
+            if let Some(label) = label {
+                args.insert("label".to_string(), json!(label));
+            }
+            ("claim-next".to_string(), dry, args)
+        }
+        CliCommand::AcceptClaim {
+            agent_id,
+            bead_id,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("agent_id".to_string(), json!(agent_id));
+            args.insert("bead_id".to_string(), json!(bead_id));
+            ("accept-claim".to_string(), dry, args)
+        }
+        CliCommand::RejectClaim {
+            agent_id,
+            bead_id,
+            reason,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("agent_id".to_string(), json!(agent_id));
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(reason) = reason {
+                args.insert("reason".to_string(), json!(reason));
+            }
+            ("reject-claim".to_string(), dry, args)
+        }
         CliCommand::Assign {
             bead_id,
             agent_id,
             dry,
         } => {
             let mut args = Map::new();
-            args.insert("bead_id".to_string(), json!(bead_id));
-            args.insert("agent_id".to_string(), json!(agent_id));
-            ("assign".to_string(), dry, args)
+            args.insert("bead_id".to_string(), json!(bead_id));
+            args.insert("agent_id".to_string(), json!(agent_id));
+            ("assign".to_string(), dry, args)
+        }
+        CliCommand::Cancel {
+            bead_id,
+            reason,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(reason) = reason {
+                args.insert("reason".to_string(), json!(reason));
+            }
+            ("cancel".to_string(), dry, args)
+        }
+        CliCommand::Takeover {
+            bead_id,
+            to_agent,
+            from_agent,
+            consent,
+            max_bytes,
+            max_tokens,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(to_agent) = to_agent {
+                args.insert("to_agent".to_string(), json!(to_agent));
+            }
+            if let Some(from_agent) = from_agent {
+                args.insert("from_agent".to_string(), json!(from_agent));
+            }
+            if let Some(consent) = consent {
+                args.insert("consent".to_string(), json!(consent));
+            }
+            if let Some(max_bytes) = max_bytes {
+                args.insert("max_bytes".to_string(), json!(max_bytes));
+            }
+            if let Some(max_tokens) = max_tokens {
+                args.insert("max_tokens".to_string(), json!(max_tokens));
+            }
+            ("takeover".to_string(), dry, args)
+        }
+        CliCommand::Recover { dry } => ("recover".to_string(), dry, Map::new()),
+        CliCommand::Run {
+            id,
+            until_empty,
+            budget_ms,
+            max_cycles,
+            dry,
+        } => {
+            let mut args = Map::new();
+            if let Some(agent_id) = id {
+                args.insert("id".to_string(), json!(agent_id));
+            }
+            if let Some(until_empty) = until_empty {
+                args.insert("until_empty".to_string(), json!(until_empty));
+            }
+            if let Some(budget_ms) = budget_ms {
+                args.insert("budget_ms".to_string(), json!(budget_ms));
+            }
+            if let Some(max_cycles) = max_cycles {
+                args.insert("max_cycles".to_string(), json!(max_cycles));
+            }
+            ("run".to_string(), dry, args)
+        }
+        CliCommand::RunAll { concurrency, dry } => {
+            let mut args = Map::new();
+            if let Some(concurrency) = concurrency {
+                args.insert("concurrency".to_string(), json!(concurrency));
+            }
+            ("run-all".to_string(), dry, args)
+        }
+        CliCommand::RunOnce { id, dry } => {
+            let mut args = Map::new();
+            if let Some(agent_id) = id {
+                args.insert("id".to_string(), json!(agent_id));
+            }
+            ("run-once".to_string(), dry, args)
+        }
+        CliCommand::Qa { target, id, dry } => {
+            let mut args = Map::new();
+            if let Some(value) = target {
+                args.insert("target".to_string(), json!(value));
+            }
+            if let Some(agent_id) = id {
+                args.insert("id".to_string(), json!(agent_id));
+            }
+            ("qa".to_string(), dry, args)
+        }
+        CliCommand::Resume => ("resume".to_string(), None, Map::new()),
+        CliCommand::Artifacts {
+            bead_id,
+            artifact_type,
+            out_dir,
+        } => {
+            let mut args = Map::new();
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(kind) = artifact_type {
+                args.insert("artifact_type".to_string(), json!(kind));
+            }
+            if let Some(dir) = out_dir {
+                args.insert("out_dir".to_string(), json!(dir));
+            }
+            ("artifacts".to_string(), None, args)
+        }
+        CliCommand::Artifact {
+            action,
+            bead_id,
+            stage,
+            artifact_type,
+            file,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            args.insert("bead_id".to_string(), json!(bead_id));
+            args.insert("stage".to_string(), json!(stage));
+            args.insert("type".to_string(), json!(artifact_type));
+            args.insert("file".to_string(), json!(file));
+            ("artifact".to_string(), dry, args)
+        }
+        CliCommand::Diff {
+            action,
+            bead_id,
+            stage,
+            file,
+            patch,
+            base_revision,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(stage) = stage {
+                args.insert("stage".to_string(), json!(stage));
+            }
+            if let Some(file) = file {
+                args.insert("file".to_string(), json!(file));
+            }
+            if let Some(patch) = patch {
+                args.insert("patch".to_string(), json!(patch));
+            }
+            if let Some(revision) = base_revision {
+                args.insert("base_revision".to_string(), json!(revision));
+            }
+            ("diff".to_string(), dry, args)
+        }
+        CliCommand::Replay {
+            bead_id,
+            file,
+            ignore,
+            dry,
+        } => {
+            let mut args = Map::new();
+            if let Some(bead_id) = bead_id {
+                args.insert("bead_id".to_string(), json!(bead_id));
+            }
+            if let Some(file) = file {
+                args.insert("file".to_string(), json!(file));
+            }
+            if let Some(ignore) = ignore {
+                args.insert("ignore".to_string(), json!(ignore));
+            }
+            ("replay".to_string(), dry, args)
+        }
+        CliCommand::ExplainTransition {
+            stage,
+            result,
+            attempt,
+            max_attempts,
+            category,
+            message,
+        } => {
+            let mut args = Map::new();
+            args.insert("stage".to_string(), json!(stage));
+            args.insert("result".to_string(), json!(result));
+            if let Some(attempt) = attempt {
+                args.insert("attempt".to_string(), json!(attempt));
+            }
+            if let Some(max_attempts) = max_attempts {
+                args.insert("max_attempts".to_string(), json!(max_attempts));
+            }
+            if let Some(category) = category {
+                args.insert("category".to_string(), json!(category));
+            }
+            if let Some(message) = message {
+                args.insert("message".to_string(), json!(message));
+            }
+            ("explain-transition".to_string(), None, args)
+        }
+        CliCommand::Verify { bead_id } => {
+            let mut args = Map::new();
+            args.insert("bead_id".to_string(), json!(bead_id));
+            ("verify".to_string(), None, args)
+        }
+        CliCommand::Attest { bead_id } => {
+            let mut args = Map::new();
+            args.insert("bead_id".to_string(), json!(bead_id));
+            ("attest".to_string(), None, args)
+        }
+        CliCommand::EnvDiff {
+            bead_id,
+            stage,
+            from_attempt,
+            to_attempt,
+        } => {
+            let mut args = Map::new();
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(stage) = stage {
+                args.insert("stage".to_string(), json!(stage));
+            }
+            args.insert("from_attempt".to_string(), json!(from_attempt));
+            args.insert("to_attempt".to_string(), json!(to_attempt));
+            ("env-diff".to_string(), None, args)
+        }
+        CliCommand::Enqueue { beads, file, dry } => {
+            let mut args = Map::new();
+            if let Some(beads) = beads {
+                args.insert("beads".to_string(), json!(beads));
+            }
+            if let Some(path) = file {
+                args.insert("file".to_string(), json!(path));
+            }
+            ("enqueue".to_string(), dry, args)
+        }
+        CliCommand::SyncBacklog {
+            rounds,
+            interval_secs,
+            dry,
+        } => {
+            let mut args = Map::new();
+            if let Some(rounds) = rounds {
+                args.insert("rounds".to_string(), json!(rounds));
+            }
+            if let Some(interval_secs) = interval_secs {
+                args.insert("interval_secs".to_string(), json!(interval_secs));
+            }
+            ("sync-backlog".to_string(), dry, args)
+        }
+        CliCommand::Sync { action, dry } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            ("sync".to_string(), dry, args)
+        }
+        CliCommand::Events { action, dry } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            ("events".to_string(), dry, args)
+        }
+        CliCommand::Chaos { action } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            ("chaos".to_string(), None, args)
+        }
+        CliCommand::Config {
+            action,
+            key,
+            value,
+            settings,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            if let Some(key) = key {
+                args.insert("key".to_string(), json!(key));
+            }
+            if let Some(value) = value {
+                args.insert("value".to_string(), json!(value));
+            }
+            for (key, value) in settings {
+                args.insert(key, json!(value));
+            }
+            ("config".to_string(), dry, args)
+        }
+        CliCommand::Backlog {
+            action,
+            bead_id,
+            label,
+            priority,
+            to,
+            limit,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            if let Some(bead_id) = bead_id {
+                args.insert("bead_id".to_string(), json!(bead_id));
+            }
+            if let Some(label) = label {
+                args.insert("label".to_string(), json!(label));
+            }
+            if let Some(priority) = priority {
+                args.insert("priority".to_string(), json!(priority));
+            }
+            if let Some(to) = to {
+                args.insert("to".to_string(), json!(to));
+            }
+            if let Some(limit) = limit {
+                args.insert("limit".to_string(), json!(limit));
+            }
+            ("backlog".to_string(), dry, args)
+        }
+        CliCommand::Labels {
+            action,
+            agent_id,
+            labels,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            if let Some(agent_id) = agent_id {
+                args.insert("agent_id".to_string(), json!(agent_id));
+            }
+            if let Some(labels) = labels {
+                args.insert("labels".to_string(), json!(labels));
+            }
+            ("labels".to_string(), dry, args)
         }
-        CliCommand::RunOnce { id, dry } => {
+        CliCommand::Jobs { action, job, dry } => {
             let mut args = Map::new();
-            if let Some(agent_id) = id {
-                args.insert("id".to_string(), json!(agent_id));
+            if let Some(action) = action {
+                args.insert("action".to_string(), json!(action));
             }
-            ("run-once".to_string(), dry, args)
+            if let Some(job) = job {
+                args.insert("job".to_string(), json!(job));
+            }
+            ("jobs".to_string(), dry, args)
         }
-        CliCommand::Qa { target, id, dry } => {
+        CliCommand::Serve { budget_ms, dry } => {
             let mut args = Map::new();
-            if let Some(value) = target {
-                args.insert("target".to_string(), json!(value));
+            if let Some(budget_ms) = budget_ms {
+                args.insert("budget_ms".to_string(), json!(budget_ms));
             }
-            if let Some(agent_id) = id {
-                args.insert("id".to_string(), json!(agent_id));
+            ("serve".to_string(), dry, args)
+        }
+        CliCommand::Profile { duration_ms, dry } => {
+            let mut args = Map::new();
+            if let Some(duration_ms) = duration_ms {
+                args.insert("duration_ms".to_string(), json!(duration_ms));
             }
-            ("qa".to_string(), dry, args)
+            ("profile".to_string(), dry, args)
         }
-        CliCommand::Resume => ("resume".to_string(), None, Map::new()),
-        CliCommand::Artifacts {
+        CliCommand::Perf {
+            action,
+            since,
+            until,
+            threshold_pct,
+            min_samples,
+            baseline_days,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            insert_time_bounds(&mut args, Some(since), until);
+            if let Some(threshold_pct) = threshold_pct {
+                args.insert("threshold_pct".to_string(), json!(threshold_pct));
+            }
+            if let Some(min_samples) = min_samples {
+                args.insert("min_samples".to_string(), json!(min_samples));
+            }
+            if let Some(baseline_days) = baseline_days {
+                args.insert("baseline_days".to_string(), json!(baseline_days));
+            }
+            ("perf".to_string(), dry, args)
+        }
+        CliCommand::Maintenance {
+            target,
+            action,
+            table,
+            partition,
+            ahead,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("target".to_string(), json!(target));
+            if let Some(action) = action {
+                args.insert("action".to_string(), json!(action));
+            }
+            if let Some(table) = table {
+                args.insert("table".to_string(), json!(table));
+            }
+            if let Some(partition) = partition {
+                args.insert("partition".to_string(), json!(partition));
+            }
+            if let Some(ahead) = ahead {
+                args.insert("ahead".to_string(), json!(ahead));
+            }
+            ("maintenance".to_string(), dry, args)
+        }
+        CliCommand::Notify {
+            action,
+            notifier,
+            event,
+            severity,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            if let Some(notifier) = notifier {
+                args.insert("notifier".to_string(), json!(notifier));
+            }
+            if let Some(event) = event {
+                args.insert("event".to_string(), json!(event));
+            }
+            if let Some(severity) = severity {
+                args.insert("severity".to_string(), json!(severity));
+            }
+            ("notify".to_string(), dry, args)
+        }
+        CliCommand::Bead {
+            action,
             bead_id,
-            artifact_type,
+            agent_id,
+            snapshot,
+            file,
+            dry,
         } => {
             let mut args = Map::new();
-            args.insert("bead_id".to_string(), json!(bead_id));
-            if let Some(kind) = artifact_type {
-                args.insert("artifact_type".to_string(), json!(kind));
+            args.insert("action".to_string(), json!(action));
+            if let Some(bead_id) = bead_id {
+                args.insert("bead_id".to_string(), json!(bead_id));
             }
-            ("artifacts".to_string(), None, args)
+            if let Some(agent_id) = agent_id {
+                args.insert("agent_id".to_string(), json!(agent_id));
+            }
+            insert_json_arg(&mut args, "snapshot", snapshot);
+            if let Some(path) = file {
+                args.insert("file".to_string(), json!(path));
+            }
+            ("bead".to_string(), dry, args)
         }
-        CliCommand::ResumeContext { bead_id } => {
+        CliCommand::ResumeContext {
+            bead_id,
+            max_bytes,
+            max_tokens,
+        } => {
             let mut args = Map::new();
             if let Some(id) = bead_id {
                 args.insert("bead_id".to_string(), json!(id));
             }
+            insert_context_budget(&mut args, max_bytes, max_tokens);
             ("resume-context".to_string(), None, args)
         }
+        CliCommand::Context {
+            bead_id,
+            skill,
+            max_bytes,
+            max_tokens,
+        } => {
+            let mut args = Map::new();
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(skill) = skill {
+                args.insert("skill".to_string(), json!(skill));
+            }
+            insert_context_budget(&mut args, max_bytes, max_tokens);
+            ("context".to_string(), None, args)
+        }
         CliCommand::Agent { id, dry } => {
             let mut args = Map::new();
             args.insert("id".to_string(), json!(id));
@@ -193,6 +1139,7 @@ pub fn cli_command_to_request(cmd: CliCommand) -> String {
             database_url,
             schema,
             seed_agents,
+            rollback_on_error,
         } => {
             let mut args = Map::new();
             if let Some(url) = database_url {
@@ -204,6 +1151,9 @@ pub fn cli_command_to_request(cmd: CliCommand) -> String {
             if let Some(seeds) = seed_agents {
                 args.insert("seed_agents".to_string(), json!(seeds));
             }
+            if let Some(rollback) = rollback_on_error {
+                args.insert("rollback_on_error".to_string(), json!(rollback));
+            }
             ("init".to_string(), dry, args)
         }
         CliCommand::Register { count, dry } => {
@@ -218,14 +1168,219 @@ pub fn cli_command_to_request(cmd: CliCommand) -> String {
             args.insert("agent_id".to_string(), json!(agent_id));
             ("release".to_string(), dry, args)
         }
-        CliCommand::Monitor { view, watch_ms } => {
+        CliCommand::Quarantine {
+            agent_id,
+            reason,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("agent_id".to_string(), json!(agent_id));
+            if let Some(reason) = reason {
+                args.insert("reason".to_string(), json!(reason));
+            }
+            ("quarantine".to_string(), dry, args)
+        }
+        CliCommand::Unquarantine {
+            agent_id,
+            reason,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("agent_id".to_string(), json!(agent_id));
+            args.insert("reason".to_string(), json!(reason));
+            ("unquarantine".to_string(), dry, args)
+        }
+        CliCommand::Land {
+            bead_id,
+            agent_id,
+            change_id,
+            remote,
+            override_push_check,
+            reason,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("bead_id".to_string(), json!(bead_id));
+            args.insert("agent_id".to_string(), json!(agent_id));
+            args.insert("change_id".to_string(), json!(change_id));
+            if let Some(remote) = remote {
+                args.insert("remote".to_string(), json!(remote));
+            }
+            if let Some(override_push_check) = override_push_check {
+                args.insert(
+                    "override_push_check".to_string(),
+                    json!(override_push_check),
+                );
+            }
+            if let Some(reason) = reason {
+                args.insert("reason".to_string(), json!(reason));
+            }
+            ("land".to_string(), dry, args)
+        }
+        CliCommand::Workspace {
+            agent_id,
+            action,
+            bead_id,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("agent_id".to_string(), json!(agent_id));
+            if let Some(action) = action {
+                args.insert("action".to_string(), json!(action));
+            }
+            if let Some(bead_id) = bead_id {
+                args.insert("bead_id".to_string(), json!(bead_id));
+            }
+            ("workspace".to_string(), dry, args)
+        }
+        CliCommand::Session {
+            action,
+            agent_id,
+            host,
+            pid,
+            version,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            args.insert("agent_id".to_string(), json!(agent_id));
+            if let Some(host) = host {
+                args.insert("host".to_string(), json!(host));
+            }
+            if let Some(pid) = pid {
+                args.insert("pid".to_string(), json!(pid));
+            }
+            if let Some(version) = version {
+                args.insert("version".to_string(), json!(version));
+            }
+            ("session".to_string(), dry, args)
+        }
+        CliCommand::Kv {
+            action,
+            agent_id,
+            bead_id,
+            key,
+            value,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            args.insert("agent_id".to_string(), json!(agent_id));
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(key) = key {
+                args.insert("key".to_string(), json!(key));
+            }
+            insert_json_arg(&mut args, "value", value);
+            ("kv".to_string(), dry, args)
+        }
+        CliCommand::Blackboard {
+            action,
+            bead_id,
+            agent_id,
+            section,
+            content,
+            expected_version,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(agent_id) = agent_id {
+                args.insert("agent_id".to_string(), json!(agent_id));
+            }
+            if let Some(section) = section {
+                args.insert("section".to_string(), json!(section));
+            }
+            if let Some(content) = content {
+                args.insert("content".to_string(), json!(content));
+            }
+            if let Some(expected_version) = expected_version {
+                args.insert("expected_version".to_string(), json!(expected_version));
+            }
+            ("blackboard".to_string(), dry, args)
+        }
+        CliCommand::Review {
+            action,
+            bead_id,
+            verdict,
+            comments,
+            agent_id,
+            reviewer,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(verdict) = verdict {
+                args.insert("verdict".to_string(), json!(verdict));
+            }
+            if let Some(comments) = comments {
+                args.insert("comments".to_string(), json!(comments));
+            }
+            if let Some(agent_id) = agent_id {
+                args.insert("agent_id".to_string(), json!(agent_id));
+            }
+            if let Some(reviewer) = reviewer {
+                args.insert("reviewer".to_string(), json!(reviewer));
+            }
+            ("review".to_string(), dry, args)
+        }
+        CliCommand::Approve {
+            bead_id,
+            token,
+            approver,
+            note,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("bead_id".to_string(), json!(bead_id));
+            args.insert("token".to_string(), json!(token));
+            if let Some(approver) = approver {
+                args.insert("approver".to_string(), json!(approver));
+            }
+            if let Some(note) = note {
+                args.insert("note".to_string(), json!(note));
+            }
+            ("approve".to_string(), dry, args)
+        }
+        CliCommand::ReissueApproval { bead_id, gate, dry } => {
+            let mut args = Map::new();
+            args.insert("bead_id".to_string(), json!(bead_id));
+            if let Some(gate) = gate {
+                args.insert("gate".to_string(), json!(gate));
+            }
+            ("reissue-approval".to_string(), dry, args)
+        }
+        CliCommand::Monitor {
+            view,
+            bead_id,
+            watch_ms,
+            after_seq,
+            page_size,
+            since,
+            until,
+            project,
+        } => {
             let mut args = Map::new();
             if let Some(v) = view {
                 args.insert("view".to_string(), json!(v));
             }
+            if let Some(bead) = bead_id {
+                args.insert("bead_id".to_string(), json!(bead));
+            }
             if let Some(w) = watch_ms {
                 args.insert("watch_ms".to_string(), json!(w));
             }
+            if let Some(cursor) = after_seq {
+                args.insert("after_seq".to_string(), json!(cursor));
+            }
+            if let Some(size) = page_size {
+                args.insert("page_size".to_string(), json!(size));
+            }
+            insert_time_bounds(&mut args, since, until);
+            if let Some(p) = project {
+                args.insert("project".to_string(), json!(p));
+            }
             ("monitor".to_string(), None, args)
         }
         CliCommand::InitDb {
@@ -246,6 +1401,44 @@ pub fn cli_command_to_request(cmd: CliCommand) -> String {
             }
             ("init-db".to_string(), dry, args)
         }
+        CliCommand::Tenant {
+            action,
+            name,
+            seed_agents,
+            confirm,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            if let Some(name) = name {
+                args.insert("name".to_string(), json!(name));
+            }
+            if let Some(seeds) = seed_agents {
+                args.insert("seed_agents".to_string(), json!(seeds));
+            }
+            if let Some(confirm) = confirm {
+                args.insert("confirm".to_string(), json!(confirm));
+            }
+            ("tenant".to_string(), dry, args)
+        }
+        CliCommand::Undelete {
+            table,
+            key,
+            limit,
+            dry,
+        } => {
+            let mut args = Map::new();
+            if let Some(table) = table {
+                args.insert("table".to_string(), json!(table));
+            }
+            if let Some(key) = key {
+                args.insert("key".to_string(), json!(key));
+            }
+            if let Some(limit) = limit {
+                args.insert("limit".to_string(), json!(limit));
+            }
+            ("undelete".to_string(), dry, args)
+        }
         CliCommand::InitLocalDb {
             container_name,
             port,
@@ -253,6 +1446,7 @@ pub fn cli_command_to_request(cmd: CliCommand) -> String {
             database,
             schema,
             seed_agents,
+            volume,
             dry,
         } => {
             let mut args = Map::new();
@@ -274,13 +1468,45 @@ pub fn cli_command_to_request(cmd: CliCommand) -> String {
             if let Some(seeds) = seed_agents {
                 args.insert("seed_agents".to_string(), json!(seeds));
             }
+            if let Some(volume) = volume {
+                args.insert("volume".to_string(), json!(volume));
+            }
             ("init-local-db".to_string(), dry, args)
         }
+        CliCommand::LocalDb {
+            action,
+            container_name,
+            to,
+            tail,
+            purge_volume,
+            confirm,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("action".to_string(), json!(action));
+            if let Some(name) = container_name {
+                args.insert("container_name".to_string(), json!(name));
+            }
+            if let Some(to) = to {
+                args.insert("to".to_string(), json!(to));
+            }
+            if let Some(tail) = tail {
+                args.insert("tail".to_string(), json!(tail));
+            }
+            if purge_volume == Some(true) {
+                args.insert("purge_volume".to_string(), json!(true));
+            }
+            if let Some(confirm) = confirm {
+                args.insert("confirm".to_string(), json!(confirm));
+            }
+            ("localdb".to_string(), dry, args)
+        }
         CliCommand::Bootstrap { dry } => ("bootstrap".to_string(), dry, Map::new()),
         CliCommand::SpawnPrompts {
             template,
             out_dir,
             count,
+            vars,
             dry,
         } => {
             let mut args = Map::new();
@@ -293,15 +1519,60 @@ pub fn cli_command_to_request(cmd: CliCommand) -> String {
             if let Some(c) = count {
                 args.insert("count".to_string(), json!(c));
             }
+            insert_template_vars(&mut args, vars);
             ("spawn-prompts".to_string(), dry, args)
         }
-        CliCommand::Prompt { id, skill } => {
+        CliCommand::RecordSymbols {
+            bead_id,
+            symbols,
+            file,
+            agent_id,
+            attempt,
+            dry,
+        } => {
+            let mut args = Map::new();
+            args.insert("bead_id".to_string(), json!(bead_id));
+            insert_json_arg(&mut args, "symbols", symbols);
+            if let Some(path) = file {
+                args.insert("file".to_string(), json!(path));
+            }
+            if let Some(agent) = agent_id {
+                args.insert("agent_id".to_string(), json!(agent));
+            }
+            if let Some(number) = attempt {
+                args.insert("attempt".to_string(), json!(number));
+            }
+            ("record-symbols".to_string(), dry, args)
+        }
+        CliCommand::Prompt {
+            id,
+            skill,
+            action,
+            body,
+            file,
+            template,
+            vars,
+            dry,
+        } => {
             let mut args = Map::new();
             args.insert("id".to_string(), json!(id));
             if let Some(s) = skill {
                 args.insert("skill".to_string(), json!(s));
             }
-            ("prompt".to_string(), None, args)
+            if let Some(a) = action {
+                args.insert("action".to_string(), json!(a));
+            }
+            if let Some(b) = body {
+                args.insert("body".to_string(), json!(b));
+            }
+            if let Some(f) = file {
+                args.insert("file".to_string(), json!(f));
+            }
+            if let Some(t) = template {
+                args.insert("template".to_string(), json!(t));
+            }
+            insert_template_vars(&mut args, vars);
+            ("prompt".to_string(), dry, args)
         }
         CliCommand::Smoke { id, dry } => {
             let mut args = Map::new();
@@ -309,24 +1580,58 @@ pub fn cli_command_to_request(cmd: CliCommand) -> String {
             ("smoke".to_string(), dry, args)
         }
         CliCommand::Batch { dry } => ("batch".to_string(), dry, Map::new()),
-        CliCommand::State => ("state".to_string(), None, Map::new()),
-        CliCommand::History { limit } => {
+        CliCommand::State { limit, project } => {
+            let mut args = Map::new();
+            if let Some(l) = limit {
+                args.insert("limit".to_string(), json!(l));
+            }
+            if let Some(p) = project {
+                args.insert("project".to_string(), json!(p));
+            }
+            ("state".to_string(), None, args)
+        }
+        CliCommand::History {
+            limit,
+            after_seq,
+            page_size,
+            since,
+            until,
+        } => {
             let mut args = Map::new();
             if let Some(l) = limit {
                 args.insert("limit".to_string(), json!(l));
             }
+            if let Some(cursor) = after_seq {
+                args.insert("after_seq".to_string(), json!(cursor));
+            }
+            if let Some(size) = page_size {
+                args.insert("page_size".to_string(), json!(size));
+            }
+            insert_time_bounds(&mut args, since, until);
             ("history".to_string(), None, args)
         }
         CliCommand::Lock {
             resource,
             agent,
             ttl_ms,
+            wait_ms,
+            purpose,
+            bead_id,
             dry,
         } => {
             let mut args = Map::new();
             args.insert("resource".to_string(), json!(resource));
             args.insert("agent".to_string(), json!(agent));
             args.insert("ttl_ms".to_string(), json!(ttl_ms));
+            if let Some(wait_ms) = wait_ms {
+                args.insert("wait_ms".to_string(), json!(wait_ms));
+            }
+            if let Some(purpose) = purpose {
+                args.insert("purpose".to_string(), json!(purpose));
+            }
+            if let Some(bead_id) = bead_id {
+                args.insert("bead_id".to_string(), json!(bead_id));
+            }
             ("lock".to_string(), dry, args)
         }
         CliCommand::Unlock {
@@ -340,16 +1645,52 @@ pub fn cli_command_to_request(cmd: CliCommand) -> String {
             ("unlock".to_string(), dry, args)
         }
         CliCommand::Agents => ("agents".to_string(), None, Map::new()),
-        CliCommand::Broadcast { msg, from, dry } => {
+        CliCommand::Locks => ("locks".to_string(), None, Map::new()),
+        CliCommand::Broadcast {
+            msg,
+            from,
+            topic,
+            ttl_secs,
+            dry,
+        } => {
             let mut args = Map::new();
             args.insert("msg".to_string(), json!(msg));
             args.insert("from".to_string(), json!(from));
+            if let Some(topic) = topic {
+                args.insert("topic".to_string(), json!(topic));
+            }
+            if let Some(ttl_secs) = ttl_secs {
+                args.insert("ttl_secs".to_string(), json!(ttl_secs));
+            }
             ("broadcast".to_string(), dry, args)
         }
+        CliCommand::Announcements {
+            action,
+            agent_id,
+            id,
+            topic,
+            dry,
+        } => {
+            let mut args = Map::new();
+            if let Some(action) = action {
+                args.insert("action".to_string(), json!(action));
+            }
+            if let Some(agent_id) = agent_id {
+                args.insert("agent_id".to_string(), json!(agent_id));
+            }
+            if let Some(id) = id {
+                args.insert("id".to_string(), json!(id));
+            }
+            if let Some(topic) = topic {
+                args.insert("topic".to_string(), json!(topic));
+            }
+            ("announcements".to_string(), dry, args)
+        }
         CliCommand::LoadProfile {
             agents,
             rounds,
             timeout_ms,
+            concurrency,
             dry,
         } => {
             let mut args = Map::new();
@@ -362,10 +1703,16 @@ pub fn cli_command_to_request(cmd: CliCommand) -> String {
             if let Some(t) = timeout_ms {
                 args.insert("timeout_ms".to_string(), json!(t));
             }
+            if let Some(c) = concurrency {
+                args.insert("concurrency".to_string(), json!(c));
+            }
             ("load-profile".to_string(), dry, args)
         }
         CliCommand::Json(cmd) => (cmd, None, Map::new()),
     };
+    if let Some(tenant) = tenant {
+        args.insert("tenant".to_string(), json!(tenant));
+    }
 
     let request = ProtocolRequest {
         cmd: cmd_name,
@@ -375,3 +1722,42 @@ pub fn cli_command_to_request(cmd: CliCommand) -> String {
     };
     serde_json::to_string(&request).unwrap_or_default()
 }
+
+/// Epoch-ms bounds are sent as numbers; anything else is passed through as
+/// an RFC3339 string for the protocol parser to validate.
+fn insert_time_bounds(args: &mut Map<String, Value>, since: Option<String>, until: Option<String>) {
+    for (key, raw) in [("since", since), ("until", until)] {
+        if let Some(raw) = raw {
+            let value = raw
+                .parse::<i64>()
+                .map_or_else(|_| json!(raw), |millis| json!(millis));
+            args.insert(key.to_string(), value);
+        }
+    }
+}
+
+fn insert_template_vars(args: &mut Map<String, Value>, vars: Option<String>) {
+    insert_json_arg(args, "vars", vars);
+}
+
+/// JSON flags (`--vars`, `--symbols`, `--value`) arrive as text; anything that does not
+/// parse is passed through as a string so the protocol reports the type error.
+fn insert_json_arg(args: &mut Map<String, Value>, key: &str, raw: Option<String>) {
+    if let Some(raw) = raw {
+        let value = serde_json::from_str::<Value>(&raw).unwrap_or(Value::String(raw));
+        args.insert(key.to_string(), value);
+    }
+}
+
+fn insert_context_budget(
+    args: &mut Map<String, Value>,
+    max_bytes: Option<u64>,
+    max_tokens: Option<u64>,
+) {
+    if let Some(bytes) = max_bytes {
+        args.insert("max_bytes".to_string(), json!(bytes));
+    }
+    if let Some(tokens) = max_tokens {
+        args.insert("max_tokens".to_string(), json!(tokens));
+    }
+}
@@ -0,0 +1,277 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![forbid(unsafe_code)]
+
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use super::registry::{ArgKind, ArgSpec, CommandSpec, CLI_ONLY_COMMANDS, COMMANDS};
+
+const FORMAT_CHOICES: &str = "json table wide";
+const SHELL_CHOICES: &str = "bash zsh fish";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw {
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "fish" => Ok(Self::Fish),
+            other => Err(format!("expected bash, zsh, or fish; got {other}")),
+        }
+    }
+}
+
+/// Completion script for `shell`, generated from the command registry.
+#[must_use]
+pub fn completion_script(shell: Shell) -> String {
+    match shell {
+        Shell::Bash => bash_script(),
+        Shell::Zsh => zsh_script(),
+        Shell::Fish => fish_script(),
+    }
+}
+
+fn command_names() -> String {
+    COMMANDS
+        .iter()
+        .map(|spec| spec.name)
+        .chain(CLI_ONLY_COMMANDS.iter().map(|(name, _)| *name))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The part of a summary before its `| NEXT:` hint.
+fn short_summary(spec: &CommandSpec) -> &'static str {
+    spec.summary
+        .split(" | ")
+        .next()
+        .map_or(spec.summary, str::trim)
+}
+
+fn choice_flags() -> Vec<(String, &'static [&'static str])> {
+    let mut flags = COMMANDS
+        .iter()
+        .flat_map(|spec| spec.args.iter())
+        .filter_map(|arg| match arg.kind {
+            ArgKind::Choice(values) => Some((arg.flag(), values)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    flags.sort_by(|left, right| left.0.cmp(&right.0));
+    flags.dedup_by(|left, right| left.0 == right.0);
+    flags
+}
+
+fn bash_script() -> String {
+    let mut out = String::from(
+        "_swarm() {\n    local cur prev cmd\n    COMPREPLY=()\n    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n    cmd=\"${COMP_WORDS[1]}\"\n\n",
+    );
+    let _ = write!(
+        out,
+        "    if [[ ${{COMP_CWORD}} -eq 1 ]]; then\n        COMPREPLY=( $(compgen -W \"{} --help --version --json --explain\" -- \"${{cur}}\") )\n        return 0\n    fi\n\n",
+        command_names()
+    );
+
+    out.push_str("    case \"${prev}\" in\n");
+    let _ = writeln!(
+        out,
+        "        --explain) COMPREPLY=( $(compgen -W \"{}\" -- \"${{cur}}\") ); return 0 ;;",
+        command_names()
+    );
+    let _ = writeln!(
+        out,
+        "        --format) COMPREPLY=( $(compgen -W \"{FORMAT_CHOICES}\" -- \"${{cur}}\") ); return 0 ;;"
+    );
+    let _ = writeln!(
+        out,
+        "        completions) COMPREPLY=( $(compgen -W \"{SHELL_CHOICES}\" -- \"${{cur}}\") ); return 0 ;;"
+    );
+    for (flag, values) in choice_flags() {
+        let _ = writeln!(
+            out,
+            "        {flag}) COMPREPLY=( $(compgen -W \"{}\" -- \"${{cur}}\") ); return 0 ;;",
+            values.join(" ")
+        );
+    }
+    out.push_str("    esac\n\n    case \"${cmd}\" in\n");
+
+    for spec in COMMANDS {
+        let flags = spec
+            .args
+            .iter()
+            .map(ArgSpec::flag)
+            .chain(["--format".to_string()])
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(
+            out,
+            "        {}) COMPREPLY=( $(compgen -W \"{flags}\" -- \"${{cur}}\") ) ;;",
+            spec.name
+        );
+    }
+    out.push_str("    esac\n    return 0\n}\ncomplete -F _swarm swarm\n");
+    out
+}
+
+fn zsh_quote(text: &str) -> String {
+    text.replace('\'', "'\\''")
+}
+
+fn zsh_arg(arg: &ArgSpec) -> String {
+    let doc = zsh_quote(&arg.doc.replace('[', "\\[").replace(']', "\\]"));
+    match arg.kind {
+        ArgKind::Flag => format!("'{}[{doc}]'", arg.flag()),
+        ArgKind::Choice(values) => {
+            format!(
+                "'{}[{doc}]:{}:({})'",
+                arg.flag(),
+                arg.name,
+                values.join(" ")
+            )
+        }
+        ArgKind::Int | ArgKind::Text | ArgKind::Timestamp => {
+            format!("'{}[{doc}]:{}: '", arg.flag(), arg.name)
+        }
+    }
+}
+
+fn zsh_script() -> String {
+    let mut out = String::from("#compdef swarm\n\n_swarm() {\n  local -a commands\n  commands=(\n");
+    for spec in COMMANDS {
+        let _ = writeln!(
+            out,
+            "    '{}:{}'",
+            spec.name,
+            zsh_quote(&short_summary(spec).replace(':', "\\:"))
+        );
+    }
+    for (name, summary) in CLI_ONLY_COMMANDS {
+        let _ = writeln!(out, "    '{name}:{}'", zsh_quote(summary));
+    }
+    out.push_str("  )\n\n");
+    out.push_str(
+        "  if (( CURRENT == 2 )); then\n    _describe 'command' commands\n    return\n  fi\n\n",
+    );
+    out.push_str("  case $words[2] in\n");
+    let _ = writeln!(
+        out,
+        "    completions) _arguments '1:shell:({SHELL_CHOICES})' ;;"
+    );
+    for spec in COMMANDS {
+        let specs = spec
+            .args
+            .iter()
+            .map(zsh_arg)
+            .chain([format!(
+                "'--format[Output format]:format:({FORMAT_CHOICES})'"
+            )])
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(out, "    {}) _arguments {specs} ;;", spec.name);
+    }
+    out.push_str("  esac\n}\n\ncompdef _swarm swarm\n");
+    out
+}
+
+fn fish_quote(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn fish_script() -> String {
+    let mut out = String::from("complete -c swarm -f\n");
+    let _ = writeln!(
+        out,
+        "complete -c swarm -l format -d 'Output format' -r -a '{FORMAT_CHOICES}'"
+    );
+    let _ = writeln!(
+        out,
+        "complete -c swarm -n '__fish_use_subcommand' -l explain -d 'Explain a command' -r -a '{}'",
+        command_names()
+    );
+    for (name, summary) in CLI_ONLY_COMMANDS {
+        let _ = writeln!(
+            out,
+            "complete -c swarm -n '__fish_use_subcommand' -a '{name}' -d '{}'",
+            fish_quote(summary)
+        );
+    }
+    let _ = writeln!(
+        out,
+        "complete -c swarm -n '__fish_seen_subcommand_from completions' -a '{SHELL_CHOICES}'"
+    );
+
+    for spec in COMMANDS {
+        let _ = writeln!(
+            out,
+            "complete -c swarm -n '__fish_use_subcommand' -a '{}' -d '{}'",
+            spec.name,
+            fish_quote(short_summary(spec))
+        );
+        for arg in spec.args {
+            let _ = write!(
+                out,
+                "complete -c swarm -n '__fish_seen_subcommand_from {}' -l {} -d '{}'",
+                spec.name,
+                arg.name.replace('_', "-"),
+                fish_quote(arg.doc)
+            );
+            match arg.kind {
+                ArgKind::Flag => {}
+                ArgKind::Choice(values) => {
+                    let _ = write!(out, " -r -a '{}'", values.join(" "));
+                }
+                ArgKind::Int | ArgKind::Text | ArgKind::Timestamp => out.push_str(" -r"),
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{completion_script, Shell};
+
+    #[test]
+    fn given_registry_when_generating_bash_then_command_flags_and_choices_are_listed() {
+        let script = completion_script(Shell::Bash);
+
+        assert!(script
+            .contains("assign) COMPREPLY=( $(compgen -W \"--bead-id --agent-id --dry --format\""));
+        assert!(script.contains(
+            "--view) COMPREPLY=( $(compgen -W \"active progress failures events messages "
+        ));
+        assert!(script.ends_with("complete -F _swarm swarm\n"));
+    }
+
+    #[test]
+    fn given_registry_when_generating_zsh_then_summary_colons_are_escaped() {
+        let script = completion_script(Shell::Zsh);
+
+        assert!(script.starts_with("#compdef swarm"));
+        assert!(script.contains("'doctor:Health check'"));
+        assert!(script
+            .contains("'--view[View to render]:view:(active progress failures events messages "));
+    }
+
+    #[test]
+    fn given_registry_when_generating_fish_then_required_values_take_an_argument() {
+        let script = completion_script(Shell::Fish);
+
+        assert!(script.contains(
+            "complete -c swarm -n '__fish_seen_subcommand_from lock' -l ttl-ms -d 'Lock lifetime in ms' -r"
+        ));
+    }
+}
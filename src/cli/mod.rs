@@ -8,12 +8,22 @@
 mod action;
 mod args;
 mod commands;
+mod completions;
+mod output;
 mod parser;
+mod registry;
+mod repl;
 
 pub use action::CliAction;
 pub use args::suggest_commands;
 pub use commands::{cli_command_to_request, CliCommand};
-pub use parser::{parse_cli_args, CliError};
+pub use completions::completion_script;
+pub use output::{render_envelope, OutputFormat};
+pub use parser::{
+    parse_cli_args, parse_log_format, parse_output_format, parse_record, parse_tenant, CliError,
+};
+pub use registry::{command_names, help_data};
+pub use repl::run_repl;
 
 #[cfg(test)]
 mod tests;
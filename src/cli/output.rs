@@ -0,0 +1,668 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![forbid(unsafe_code)]
+
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::protocol_runtime::WireFormat;
+use serde_json::Value;
+
+/// How the CLI path prints a response envelope. The stdin protocol loop
+/// never goes through this layer; it speaks JSONL unless started with
+/// `--format msgpack`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Table,
+    Wide,
+    /// Length-prefixed `MessagePack` frames; see [`WireFormat::MessagePack`].
+    MessagePack,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw {
+            "json" | "jsonl" => Ok(Self::Json),
+            "table" => Ok(Self::Table),
+            "wide" => Ok(Self::Wide),
+            "msgpack" => Ok(Self::MessagePack),
+            other => Err(format!(
+                "expected json, table, wide, or msgpack; got {other}"
+            )),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// An explicit `--format` wins; otherwise tables on a terminal, JSONL when piped.
+    #[must_use]
+    pub const fn resolve(explicit: Option<Self>, stdout_is_tty: bool) -> Self {
+        match explicit {
+            Some(format) => format,
+            None if stdout_is_tty => Self::Table,
+            None => Self::Json,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_human(self) -> bool {
+        matches!(self, Self::Table | Self::Wide)
+    }
+
+    /// Encoding for machine output and the protocol loop.
+    #[must_use]
+    pub const fn wire_format(self) -> WireFormat {
+        match self {
+            Self::MessagePack => WireFormat::MessagePack,
+            Self::Json | Self::Table | Self::Wide => WireFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Style {
+    color: bool,
+}
+
+impl Style {
+    fn paint(self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn bold(self, text: &str) -> String {
+        self.paint("1", text)
+    }
+
+    fn red(self, text: &str) -> String {
+        self.paint("31", text)
+    }
+
+    fn green(self, text: &str) -> String {
+        self.paint("32", text)
+    }
+
+    fn dim(self, text: &str) -> String {
+        self.paint("2", text)
+    }
+}
+
+/// Renders `envelope` for `cmd`. Commands without a human layout, and every
+/// envelope in `Json` mode, fall back to the compact JSONL line.
+#[must_use]
+pub fn render_envelope(
+    cmd: &str,
+    envelope: &ProtocolEnvelope,
+    format: OutputFormat,
+    color: bool,
+) -> String {
+    let human = if format.is_human() {
+        render_human(cmd, envelope, matches!(format, OutputFormat::Wide), color)
+    } else {
+        None
+    };
+    human.unwrap_or_else(|| serde_json::to_string(envelope).unwrap_or_default())
+}
+
+/// Human layout for `envelope`, or `None` when `cmd` has no table view.
+/// Errors always render as a one-line code and message.
+#[must_use]
+pub fn render_human(
+    cmd: &str,
+    envelope: &ProtocolEnvelope,
+    wide: bool,
+    color: bool,
+) -> Option<String> {
+    let style = Style { color };
+    if let Some(error) = envelope.err.as_ref() {
+        let mut out = format!("{} {}", style.red(&error.code), error.msg);
+        if let Some(fix) = envelope.fix.as_deref() {
+            let _ = write!(out, "\n{} {fix}", style.dim("fix:"));
+        }
+        return Some(out);
+    }
+
+    let data = envelope.d.as_deref().unwrap_or(&Value::Null);
+    let body = match cmd {
+        "status" => Some(render_status(data, wide, style)),
+        "agents" => Some(render_agents(data, style)),
+        "top" => Some(render_top(data, wide, style)),
+        "monitor" => render_monitor(data, wide, style),
+        _ => None,
+    };
+
+    body.map(|mut out| {
+        if let Some(next) = envelope.next.as_deref() {
+            let _ = write!(out, "\n{} {next}", style.dim("next:"));
+        }
+        out
+    })
+}
+
+fn render_status(data: &Value, wide: bool, style: Style) -> String {
+    let errors = data["errors"].as_i64().unwrap_or(0);
+    let errors_text = if errors > 0 {
+        style.red(&errors.to_string())
+    } else {
+        style.green(&errors.to_string())
+    };
+    let mut out = format!(
+        "{}  total {}  working {}  idle {}  waiting {}  done {}  errors {errors_text}",
+        style.bold("agents"),
+        cell(&data["total"]),
+        cell(&data["working"]),
+        cell(&data["idle"]),
+        cell(&data["waiting"]),
+        cell(&data["done"]),
+    );
+
+    if let Some(beads) = data["beads_by_status"].as_object() {
+        let summary = beads
+            .iter()
+            .map(|(status, count)| format!("{status} {}", cell(count)))
+            .collect::<Vec<_>>()
+            .join("  ");
+        if !summary.is_empty() {
+            let _ = write!(out, "\n{}  {summary}", style.bold("beads "));
+        }
+    }
+
+    if wide {
+        let timing = &data["timing"];
+        let _ = write!(
+            out,
+            "\n{}  connect {}ms  progress {}ms  br {}ms  total {}ms",
+            style.bold("timing"),
+            cell(&timing["db"]["connect_ms"]),
+            cell(&timing["db"]["get_progress_ms"]),
+            cell(&timing["external"]["br_list_ms"]),
+            cell(&timing["total_ms"]),
+        );
+    }
+    out
+}
+
+fn render_agents(data: &Value, style: Style) -> String {
+    render_table(
+        &["ID", "RESOURCE", "SINCE"],
+        &rows_of(&data["agents"], &["id", "resource", "since"]),
+        style,
+    )
+}
+
+fn render_top(data: &Value, wide: bool, style: Style) -> String {
+    let (headers, keys): (&[&str], &[&str]) = if wide {
+        (
+            &[
+                "AGENT",
+                "STATUS",
+                "BEAD",
+                "STAGE",
+                "STAGE_S",
+                "HEARTBEAT_S",
+                "ATTEMPT",
+                "STAGES",
+                "BEADS",
+            ],
+            &[
+                "agent_id",
+                "status",
+                "bead_id",
+                "stage",
+                "stage_elapsed_secs",
+                "heartbeat_age_secs",
+                "attempt",
+                "stages_passed",
+                "beads_completed",
+            ],
+        )
+    } else {
+        (
+            &["AGENT", "STATUS", "BEAD", "STAGE", "STAGE_S", "HEARTBEAT_S"],
+            &[
+                "agent_id",
+                "status",
+                "bead_id",
+                "stage",
+                "stage_elapsed_secs",
+                "heartbeat_age_secs",
+            ],
+        )
+    };
+    render_table(headers, &rows_of(&data["agents"], keys), style)
+}
+
+#[allow(clippy::too_many_lines)]
+fn render_monitor(data: &Value, wide: bool, style: Style) -> Option<String> {
+    let (headers, keys): (&[&str], &[&str]) = match (data["view"].as_str()?, wide) {
+        ("active", _) => (
+            &["REPO", "AGENT", "BEAD", "STATUS"],
+            &["repo", "agent_id", "bead_id", "status"],
+        ),
+        ("progress", _) => return Some(render_status(data, false, style)),
+        ("failures", false) => (
+            &["SEQ", "BEAD", "AGENT", "STAGE", "CATEGORY", "RETRYABLE"],
+            &[
+                "seq",
+                "bead_id",
+                "agent_id",
+                "stage",
+                "category",
+                "retryable",
+            ],
+        ),
+        ("failures", true) => (
+            &[
+                "SEQ",
+                "BEAD",
+                "AGENT",
+                "STAGE",
+                "CATEGORY",
+                "RETRYABLE",
+                "NEXT",
+                "DETAIL",
+                "TESTS",
+            ],
+            &[
+                "seq",
+                "bead_id",
+                "agent_id",
+                "stage",
+                "category",
+                "retryable",
+                "next_command",
+                "detail",
+                "failing_tests",
+            ],
+        ),
+        ("events", false) => (
+            &["SEQ", "EVENT", "BEAD", "AGENT", "STAGE", "CREATED"],
+            &[
+                "seq",
+                "event_type",
+                "bead_id",
+                "agent_id",
+                "stage",
+                "created_at",
+            ],
+        ),
+        ("events", true) => (
+            &[
+                "SEQ",
+                "EVENT",
+                "ENTITY",
+                "BEAD",
+                "AGENT",
+                "STAGE",
+                "CAUSATION",
+                "CREATED",
+            ],
+            &[
+                "seq",
+                "event_type",
+                "entity_id",
+                "bead_id",
+                "agent_id",
+                "stage",
+                "causation_id",
+                "created_at",
+            ],
+        ),
+        ("drift", false) => (
+            &["BEAD", "KIND", "SYMBOL", "FIRST", "LATEST"],
+            &[
+                "bead_id",
+                "kind",
+                "name",
+                "first_signature",
+                "latest_signature",
+            ],
+        ),
+        ("drift", true) => (
+            &[
+                "BEAD", "KIND", "MODULE", "SYMBOL", "ATTEMPTS", "FIRST", "LATEST",
+            ],
+            &[
+                "bead_id",
+                "kind",
+                "module_path",
+                "name",
+                "latest_attempt",
+                "first_signature",
+                "latest_signature",
+            ],
+        ),
+        ("health", false) => (
+            &[
+                "AGENT",
+                "BEAD",
+                "VERDICT",
+                "FAILS",
+                "QUARANTINED",
+                "ANOMALIES",
+            ],
+            &[
+                "agent_id",
+                "bead_id",
+                "verdict",
+                "consecutive_failures",
+                "quarantined",
+                "anomalies",
+            ],
+        ),
+        ("health", true) => (
+            &[
+                "AGENT",
+                "BEAD",
+                "STATUS",
+                "VERDICT",
+                "FAILS",
+                "IDLE_S",
+                "RETRIES",
+                "RUNS",
+                "FAIL_RATE",
+                "AVG_MS",
+                "SCOPE_VIOL",
+                "QUARANTINED",
+                "ANOMALIES",
+            ],
+            &[
+                "agent_id",
+                "bead_id",
+                "agent_status",
+                "verdict",
+                "consecutive_failures",
+                "secs_since_progress",
+                "retry_count",
+                "stage_runs",
+                "failure_rate",
+                "avg_stage_ms",
+                "scope_violations",
+                "quarantined",
+                "anomalies",
+            ],
+        ),
+        ("coverage", false) => (
+            &["BEAD", "LATEST", "DELTA", "SAMPLES"],
+            &["bead_id", "latest_percent", "delta", "samples"],
+        ),
+        ("coverage", true) => (
+            &[
+                "BEAD",
+                "FIRST",
+                "LATEST",
+                "DELTA",
+                "SAMPLES",
+                "STAGE",
+                "BELOW_MIN",
+            ],
+            &[
+                "bead_id",
+                "first_percent",
+                "latest_percent",
+                "delta",
+                "samples",
+                "latest_stage",
+                "below_minimum",
+            ],
+        ),
+        ("alerts", false) => (
+            &["KIND", "STATUS", "DETAIL", "BREACHED"],
+            &["kind", "status", "detail", "breached_at"],
+        ),
+        ("alerts", true) => (
+            &["KIND", "STATUS", "DETAIL", "BREACHED", "FIRED"],
+            &["kind", "status", "detail", "breached_at", "fired_at"],
+        ),
+        ("sessions", false) => (
+            &["ID", "AGENT", "HOST", "PID", "STARTED", "ENDED"],
+            &["id", "agent_id", "host", "pid", "started_at", "ended_at"],
+        ),
+        ("sessions", true) => (
+            &[
+                "ID", "AGENT", "HOST", "PID", "VERSION", "STARTED", "ENDED", "REASON", "CLAIMS",
+                "EVENTS",
+            ],
+            &[
+                "id",
+                "agent_id",
+                "host",
+                "pid",
+                "version",
+                "started_at",
+                "ended_at",
+                "end_reason",
+                "claims",
+                "events",
+            ],
+        ),
+        ("sla", false) => (
+            &["BEAD", "PRIORITY", "STATUS", "AGE_S", "TARGET_S", "SLA"],
+            &[
+                "bead_id",
+                "priority",
+                "status",
+                "age_secs",
+                "target_secs",
+                "sla",
+            ],
+        ),
+        ("sla", true) => (
+            &[
+                "BEAD", "PRIORITY", "STATUS", "ENQUEUED", "AGE_S", "TARGET_S", "PERCENT", "SLA",
+            ],
+            &[
+                "bead_id",
+                "priority",
+                "status",
+                "enqueued_at",
+                "age_secs",
+                "target_secs",
+                "percent_of_target",
+                "sla",
+            ],
+        ),
+        ("escalations", false) => (
+            &["ID", "BEAD", "REASON", "AGENT", "ASSIGNED", "ESCALATED"],
+            &[
+                "id",
+                "bead_id",
+                "reason",
+                "agent_id",
+                "assigned_to",
+                "escalated_at",
+            ],
+        ),
+        ("escalations", true) => (
+            &[
+                "ID",
+                "BEAD",
+                "REASON",
+                "DETAIL",
+                "AGENT",
+                "ASSIGNED",
+                "ESCALATED",
+            ],
+            &[
+                "id",
+                "bead_id",
+                "reason",
+                "detail",
+                "agent_id",
+                "assigned_to",
+                "escalated_at",
+            ],
+        ),
+        ("approvals", false) => (
+            &["ID", "BEAD", "GATE", "STATUS", "REQUESTED"],
+            &["id", "bead_id", "gate", "status", "requested_at"],
+        ),
+        ("approvals", true) => (
+            &[
+                "ID",
+                "BEAD",
+                "GATE",
+                "STATUS",
+                "AGENT",
+                "REQUESTED",
+                "APPROVER",
+                "APPROVED",
+                "NOTE",
+            ],
+            &[
+                "id",
+                "bead_id",
+                "gate",
+                "status",
+                "requested_by",
+                "requested_at",
+                "approved_by",
+                "approved_at",
+                "note",
+            ],
+        ),
+        ("messages", false) => (
+            &["ID", "FROM", "TO", "TYPE", "SUBJECT"],
+            &[
+                "id",
+                "from_agent_id",
+                "to_agent_id",
+                "message_type",
+                "subject",
+            ],
+        ),
+        ("messages", true) => (
+            &["ID", "FROM", "TO", "BEAD", "TYPE", "SUBJECT", "CREATED"],
+            &[
+                "id",
+                "from_agent_id",
+                "to_agent_id",
+                "bead_id",
+                "message_type",
+                "subject",
+                "created_at",
+            ],
+        ),
+        _ => return None,
+    };
+
+    let mut out = render_table(headers, &rows_of(&data["rows"], keys), style);
+    if let Some(cursor) = data["next_cursor"].as_i64() {
+        let _ = write!(out, "\n{} --after-seq {cursor}", style.dim("more:"));
+    }
+    Some(out)
+}
+
+fn rows_of(rows: &Value, keys: &[&str]) -> Vec<Vec<String>> {
+    rows.as_array()
+        .map(|rows| {
+            rows.iter()
+                .map(|row| keys.iter().map(|key| cell(&row[*key])).collect())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Left-aligned columns separated by two spaces. Padding is computed on the
+/// raw text so ANSI codes on the header never skew the widths.
+fn render_table(headers: &[&str], rows: &[Vec<String>], style: Style) -> String {
+    let widths = headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+            rows.iter()
+                .filter_map(|row| row.get(index))
+                .map(|text| text.chars().count())
+                .fold(header.len(), usize::max)
+        })
+        .collect::<Vec<_>>();
+
+    let pad = |text: &str, index: usize| {
+        let width = widths.get(index).copied().unwrap_or(0);
+        let fill = width.saturating_sub(text.chars().count());
+        format!("{text}{}", " ".repeat(fill))
+    };
+
+    let header_line = headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| style.bold(&pad(header, index)))
+        .collect::<Vec<_>>()
+        .join("  ");
+    let mut lines = vec![header_line.trim_end().to_string()];
+    lines.extend(rows.iter().map(|row| {
+        row.iter()
+            .enumerate()
+            .map(|(index, text)| pad(text, index))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    }));
+    if rows.is_empty() {
+        lines.push(style.dim("(no rows)"));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_envelope, OutputFormat};
+    use crate::protocol_envelope::ProtocolEnvelope;
+    use serde_json::json;
+
+    #[test]
+    fn given_no_explicit_format_when_resolving_then_tty_picks_table_and_pipe_picks_json() {
+        assert_eq!(OutputFormat::resolve(None, true), OutputFormat::Table);
+        assert_eq!(OutputFormat::resolve(None, false), OutputFormat::Json);
+        assert_eq!(
+            OutputFormat::resolve(Some(OutputFormat::Wide), false),
+            OutputFormat::Wide
+        );
+    }
+
+    #[test]
+    fn given_agents_envelope_when_rendering_table_then_columns_are_aligned() {
+        let envelope = ProtocolEnvelope::success(
+            None,
+            json!({"agents": [
+                {"id": "1", "resource": "repo/src/lib.rs", "since": 10},
+                {"id": "12", "resource": "a", "since": 7},
+            ]}),
+        );
+
+        let rendered = render_envelope("agents", &envelope, OutputFormat::Table, false);
+
+        assert_eq!(
+            rendered,
+            "ID  RESOURCE         SINCE\n1   repo/src/lib.rs  10\n12  a                7"
+        );
+    }
+
+    #[test]
+    fn given_unrendered_command_when_rendering_table_then_jsonl_is_kept() {
+        let envelope = ProtocolEnvelope::success(None, json!({"ok": true}));
+
+        let rendered = render_envelope("history", &envelope, OutputFormat::Table, false);
+
+        assert!(rendered.starts_with('{'));
+    }
+}
@@ -6,7 +6,10 @@
 #![forbid(unsafe_code)]
 
 use super::action::CliAction;
+use super::args::ensure_command_flags;
 use super::commands::CliCommand;
+use super::output::OutputFormat;
+use super::registry::find_command;
 
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum CliError {
@@ -31,10 +34,38 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
         return Ok(CliAction::ShowHelp);
     }
 
+    if let Some(spec) = args.first().and_then(|cmd| find_command(cmd)) {
+        ensure_command_flags(args, spec)?;
+    }
+
     match args.first().map(String::as_str) {
         None | Some("--") => Ok(CliAction::RunProtocol),
+        Some("--format" | "--record" | "--log-format") if only_session_flags(args) => {
+            Ok(CliAction::RunProtocol)
+        }
         Some("-h" | "--help") => Ok(CliAction::ShowHelp),
         Some("-v" | "--version") => Ok(CliAction::ShowVersion),
+        Some("--explain") => {
+            let cmd = args.get(1).ok_or_else(|| CliError::MissingRequiredArg {
+                arg: "command".to_string(),
+            })?;
+            find_command(cmd)
+                .map(CliAction::Explain)
+                .ok_or_else(|| CliError::UnknownCommand { cmd: cmd.clone() })
+        }
+        Some("repl") => Ok(CliAction::Repl),
+        Some("completions") => {
+            let shell = args.get(1).ok_or_else(|| CliError::MissingRequiredArg {
+                arg: "shell".to_string(),
+            })?;
+            shell
+                .parse()
+                .map(CliAction::Completions)
+                .map_err(|error| CliError::InvalidArgValue {
+                    arg: "shell".to_string(),
+                    error,
+                })
+        }
         Some("--json") => {
             if args.len() < 2 {
                 Err(CliError::MissingRequiredArg {
@@ -44,12 +75,70 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
                 Ok(CliAction::Command(CliCommand::Json(args[1].clone())))
             }
         }
-        Some("doctor") => Ok(CliAction::Command(CliCommand::Doctor)),
-        Some("status") => Ok(CliAction::Command(CliCommand::Status)),
+        Some("doctor") => Ok(CliAction::Command(CliCommand::Doctor {
+            show_candidates: parse_optional_arg(args, "show_candidates")?,
+            analyze_db: parse_optional_arg(args, "analyze_db")?,
+            apply: parse_optional_arg(args, "apply")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("db-health") => Ok(CliAction::Command(CliCommand::DbHealth {
+            samples: parse_optional_arg(args, "samples")?,
+        })),
+        Some("healthz") => Ok(CliAction::Command(CliCommand::Healthz)),
+        Some("invariants") => Ok(CliAction::Command(CliCommand::Invariants)),
+        Some("costs") => Ok(CliAction::Command(CliCommand::Costs {
+            bead_id: parse_optional_arg(args, "bead_id")?,
+            since: parse_optional_arg(args, "since")?,
+            until: parse_optional_arg(args, "until")?,
+            pricing: parse_optional_arg(args, "pricing")?,
+        })),
+        Some("report-usage") => Ok(CliAction::Command(CliCommand::ReportUsage {
+            agent_id: parse_required_arg(args, "agent_id")?,
+            bead_id: parse_required_arg(args, "bead_id")?,
+            stage: parse_optional_arg(args, "stage")?,
+            model: parse_required_arg(args, "model")?,
+            prompt_tokens: parse_required_arg(args, "prompt_tokens")?,
+            completion_tokens: parse_required_arg(args, "completion_tokens")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("report-coverage") => Ok(CliAction::Command(CliCommand::ReportCoverage {
+            agent_id: parse_required_arg(args, "agent_id")?,
+            bead_id: parse_required_arg(args, "bead_id")?,
+            stage: parse_optional_arg(args, "stage")?,
+            path: parse_optional_arg(args, "path")?,
+            report: parse_optional_arg(args, "report")?,
+            format: parse_optional_arg(args, "format")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("status") => Ok(CliAction::Command(CliCommand::Status {
+            project: parse_optional_arg(args, "project")?,
+        })),
+        Some("top") => Ok(CliAction::Command(CliCommand::Top {
+            window_mins: parse_optional_arg(args, "window_mins")?,
+        })),
+        Some("forecast") => Ok(CliAction::Command(CliCommand::Forecast {
+            window_hours: parse_optional_arg(args, "window_hours")?,
+            agents: parse_optional_arg(args, "agents")?,
+        })),
         Some("next") => Ok(CliAction::Command(CliCommand::Next {
             dry: parse_optional_arg(args, "dry")?,
         })),
         Some("claim-next") => Ok(CliAction::Command(CliCommand::ClaimNext {
+            reserve: parse_optional_arg(args, "reserve")?,
+            agent_id: parse_optional_arg(args, "agent_id")?,
+            ttl_secs: parse_optional_arg(args, "ttl_secs")?,
+            label: parse_optional_arg(args, "label")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("accept-claim") => Ok(CliAction::Command(CliCommand::AcceptClaim {
+            agent_id: parse_required_arg(args, "agent_id")?,
+            bead_id: parse_required_arg(args, "bead_id")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("reject-claim") => Ok(CliAction::Command(CliCommand::RejectClaim {
+            agent_id: parse_required_arg(args, "agent_id")?,
+            bead_id: parse_required_arg(args, "bead_id")?,
+            reason: parse_optional_arg(args, "reason")?,
             dry: parse_optional_arg(args, "dry")?,
         })),
         Some("assign") => {
@@ -62,6 +151,34 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
                 dry,
             }))
         }
+        Some("cancel") => Ok(CliAction::Command(CliCommand::Cancel {
+            bead_id: parse_required_arg(args, "bead_id")?,
+            reason: parse_optional_arg(args, "reason")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("takeover") => Ok(CliAction::Command(CliCommand::Takeover {
+            bead_id: parse_required_arg(args, "bead_id")?,
+            to_agent: parse_optional_arg(args, "to_agent")?,
+            from_agent: parse_optional_arg(args, "from_agent")?,
+            consent: parse_optional_arg(args, "consent")?,
+            max_bytes: parse_optional_arg(args, "max_bytes")?,
+            max_tokens: parse_optional_arg(args, "max_tokens")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("recover") => Ok(CliAction::Command(CliCommand::Recover {
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("run") => Ok(CliAction::Command(CliCommand::Run {
+            id: parse_optional_arg(args, "id")?,
+            until_empty: parse_optional_arg(args, "until_empty")?,
+            budget_ms: parse_optional_arg(args, "budget_ms")?,
+            max_cycles: parse_optional_arg(args, "max_cycles")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("run-all") => Ok(CliAction::Command(CliCommand::RunAll {
+            concurrency: parse_optional_arg(args, "concurrency")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
         Some("run-once") => {
             let id = parse_optional_arg(args, "id")?;
             let dry = parse_optional_arg(args, "dry")?;
@@ -76,19 +193,292 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
         Some("resume") => Ok(CliAction::Command(CliCommand::Resume)),
         Some("resume-context") => {
             let bead_id = parse_optional_arg(args, "bead_id")?;
-            Ok(CliAction::Command(CliCommand::ResumeContext { bead_id }))
+            let max_bytes = parse_optional_arg(args, "max_bytes")?;
+            let max_tokens = parse_optional_arg(args, "max_tokens")?;
+            Ok(CliAction::Command(CliCommand::ResumeContext {
+                bead_id,
+                max_bytes,
+                max_tokens,
+            }))
+        }
+        Some("context") => {
+            let bead_id = parse_required_arg::<String>(args, "bead_id")?;
+            let skill = parse_optional_arg::<String>(args, "skill")?;
+            let max_bytes = parse_optional_arg(args, "max_bytes")?;
+            let max_tokens = parse_optional_arg(args, "max_tokens")?;
+            Ok(CliAction::Command(CliCommand::Context {
+                bead_id,
+                skill,
+                max_bytes,
+                max_tokens,
+            }))
         }
         Some("artifacts") => {
             let bead_id = parse_required_arg::<String>(args, "bead_id")?;
             let artifact_type = parse_optional_arg::<String>(args, "artifact_type")?;
+            let out_dir = parse_optional_arg::<String>(args, "out_dir")?;
             Ok(CliAction::Command(CliCommand::Artifacts {
                 bead_id,
                 artifact_type,
+                out_dir,
+            }))
+        }
+        Some("artifact") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Artifact {
+                action,
+                bead_id: parse_required_arg(args, "bead_id")?,
+                stage: parse_required_arg(args, "stage")?,
+                artifact_type: parse_required_arg(args, "type")?,
+                file: parse_required_arg(args, "file")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("diff") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Diff {
+                action,
+                bead_id: parse_required_arg(args, "bead_id")?,
+                stage: parse_optional_arg(args, "stage")?,
+                file: parse_optional_arg(args, "file")?,
+                patch: parse_optional_arg(args, "patch")?,
+                base_revision: parse_optional_arg(args, "base_revision")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("replay") => {
+            let file = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(file) => Some(file.clone()),
+                None => parse_optional_arg(args, "file")?,
+            };
+            let bead_id = match file {
+                Some(_) => parse_optional_arg(args, "bead_id")?,
+                None => Some(parse_required_arg(args, "bead_id")?),
+            };
+            Ok(CliAction::Command(CliCommand::Replay {
+                bead_id,
+                file,
+                ignore: parse_optional_arg(args, "ignore")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("explain-transition") => Ok(CliAction::Command(CliCommand::ExplainTransition {
+            stage: parse_required_arg(args, "stage")?,
+            result: parse_required_arg(args, "result")?,
+            attempt: parse_optional_arg(args, "attempt")?,
+            max_attempts: parse_optional_arg(args, "max_attempts")?,
+            category: parse_optional_arg(args, "category")?,
+            message: parse_optional_arg(args, "message")?,
+        })),
+        Some("verify") => Ok(CliAction::Command(CliCommand::Verify {
+            bead_id: parse_required_arg(args, "bead_id")?,
+        })),
+        Some("attest") => Ok(CliAction::Command(CliCommand::Attest {
+            bead_id: parse_required_arg(args, "bead_id")?,
+        })),
+        Some("env-diff") => Ok(CliAction::Command(CliCommand::EnvDiff {
+            bead_id: parse_required_arg(args, "bead_id")?,
+            stage: parse_optional_arg(args, "stage")?,
+            from_attempt: parse_required_arg(args, "from_attempt")?,
+            to_attempt: parse_required_arg(args, "to_attempt")?,
+        })),
+        Some("enqueue") => {
+            let file: Option<String> = parse_optional_arg(args, "file")?;
+            let beads = parse_optional_arg(args, "beads")?;
+            // `--file -` reads the list here, so the handler gets it inline.
+            let (beads, file) = if file.as_deref() == Some("-") && beads.is_none() {
+                (Some(read_stdin_arg("file")?), None)
+            } else {
+                (beads, file)
+            };
+            Ok(CliAction::Command(CliCommand::Enqueue {
+                beads,
+                file,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("sync-backlog") => Ok(CliAction::Command(CliCommand::SyncBacklog {
+            rounds: parse_optional_arg(args, "rounds")?,
+            interval_secs: parse_optional_arg(args, "interval_secs")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("sync") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Sync {
+                action,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("events") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Events {
+                action,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("chaos") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Chaos { action }))
+        }
+        Some("config") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            let positional = args
+                .iter()
+                .skip(2)
+                .take_while(|arg| !arg.starts_with("--"))
+                .filter(|arg| !arg.contains('='))
+                .collect::<Vec<_>>();
+            let mut settings = parse_config_assignments(args)?;
+            for key in crate::ConfigKey::ALL {
+                if let Some(value) = parse_optional_arg::<String>(args, key.as_str())? {
+                    settings.push((key.as_str().to_string(), value));
+                }
+            }
+            Ok(CliAction::Command(CliCommand::Config {
+                action,
+                key: match positional.first() {
+                    Some(key) => Some((*key).clone()),
+                    None => parse_optional_arg(args, "key")?,
+                },
+                value: match positional.get(1) {
+                    Some(value) => Some((*value).clone()),
+                    None => parse_optional_arg(args, "value")?,
+                },
+                settings,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("backlog") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Backlog {
+                action,
+                bead_id: parse_optional_arg(args, "bead_id")?,
+                label: parse_optional_arg(args, "label")?,
+                priority: parse_optional_arg(args, "priority")?,
+                to: parse_optional_arg(args, "to")?,
+                limit: parse_optional_arg(args, "limit")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("labels") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Labels {
+                action,
+                agent_id: parse_optional_arg(args, "agent_id")?,
+                labels: parse_optional_arg(args, "labels")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("jobs") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => Some(action.clone()),
+                None => parse_optional_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Jobs {
+                action,
+                job: parse_optional_arg(args, "job")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("serve") => Ok(CliAction::Command(CliCommand::Serve {
+            budget_ms: parse_optional_arg(args, "budget_ms")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("profile") => Ok(CliAction::Command(CliCommand::Profile {
+            duration_ms: parse_optional_arg(args, "duration_ms")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("perf") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Perf {
+                action,
+                since: parse_required_arg(args, "since")?,
+                until: parse_optional_arg(args, "until")?,
+                threshold_pct: parse_optional_arg(args, "threshold_pct")?,
+                min_samples: parse_optional_arg(args, "min_samples")?,
+                baseline_days: parse_optional_arg(args, "baseline_days")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("maintenance") => {
+            let target = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(target) => target.clone(),
+                None => parse_required_arg(args, "target")?,
+            };
+            let action = match args.get(2).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => Some(action.clone()),
+                None => parse_optional_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Maintenance {
+                target,
+                action,
+                table: parse_optional_arg(args, "table")?,
+                partition: parse_optional_arg(args, "partition")?,
+                ahead: parse_optional_arg(args, "ahead")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("notify") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Notify {
+                action,
+                notifier: parse_optional_arg(args, "notifier")?,
+                event: parse_optional_arg(args, "event")?,
+                severity: parse_optional_arg(args, "severity")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("bead") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Bead {
+                action,
+                bead_id: parse_optional_arg(args, "bead_id")?,
+                agent_id: parse_optional_arg(args, "agent_id")?,
+                snapshot: parse_optional_arg(args, "snapshot")?,
+                file: parse_optional_arg(args, "file")?,
+                dry: parse_optional_arg(args, "dry")?,
             }))
         }
         Some("?" | "help") => Ok(CliAction::Command(CliCommand::Help)),
-        Some("state") => Ok(CliAction::Command(CliCommand::State)),
+        Some("state") => Ok(CliAction::Command(CliCommand::State {
+            limit: parse_optional_arg(args, "limit")?,
+            project: parse_optional_arg(args, "project")?,
+        })),
         Some("agents") => Ok(CliAction::Command(CliCommand::Agents)),
+        Some("locks") => Ok(CliAction::Command(CliCommand::Locks)),
         Some("batch") => Ok(CliAction::Command(CliCommand::Batch {
             dry: parse_optional_arg(args, "dry")?,
         })),
@@ -102,11 +492,13 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
             let database_url = parse_optional_arg(args, "database_url")?;
             let schema = parse_optional_arg(args, "schema")?;
             let seed_agents = parse_optional_arg(args, "seed_agents")?;
+            let rollback_on_error = parse_optional_arg(args, "rollback_on_error")?;
             Ok(CliAction::Command(CliCommand::Init {
                 dry,
                 database_url,
                 schema,
                 seed_agents,
+                rollback_on_error,
             }))
         }
         Some("register") => {
@@ -119,10 +511,130 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
             let dry = parse_optional_arg(args, "dry")?;
             Ok(CliAction::Command(CliCommand::Release { agent_id, dry }))
         }
+        Some("quarantine") => {
+            let agent_id = parse_required_arg(args, "agent_id")?;
+            let reason = parse_optional_arg(args, "reason")?;
+            let dry = parse_optional_arg(args, "dry")?;
+            Ok(CliAction::Command(CliCommand::Quarantine {
+                agent_id,
+                reason,
+                dry,
+            }))
+        }
+        Some("unquarantine") => {
+            let agent_id = parse_required_arg(args, "agent_id")?;
+            let reason = parse_required_arg::<String>(args, "reason")?;
+            let dry = parse_optional_arg(args, "dry")?;
+            Ok(CliAction::Command(CliCommand::Unquarantine {
+                agent_id,
+                reason,
+                dry,
+            }))
+        }
+        Some("land") => Ok(CliAction::Command(CliCommand::Land {
+            bead_id: parse_required_arg::<String>(args, "bead_id")?,
+            agent_id: parse_required_arg(args, "agent_id")?,
+            change_id: parse_required_arg::<String>(args, "change_id")?,
+            remote: parse_optional_arg(args, "remote")?,
+            override_push_check: parse_optional_arg(args, "override_push_check")?,
+            reason: parse_optional_arg(args, "reason")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("workspace") => Ok(CliAction::Command(CliCommand::Workspace {
+            agent_id: parse_required_arg(args, "agent_id")?,
+            action: parse_optional_arg(args, "action")?,
+            bead_id: parse_optional_arg(args, "bead_id")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("session") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Session {
+                action,
+                agent_id: parse_required_arg(args, "agent_id")?,
+                host: parse_optional_arg(args, "host")?,
+                pid: parse_optional_arg(args, "pid")?,
+                version: parse_optional_arg(args, "version")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("kv") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Kv {
+                action,
+                agent_id: parse_required_arg(args, "agent_id")?,
+                bead_id: parse_required_arg(args, "bead_id")?,
+                key: parse_optional_arg(args, "key")?,
+                value: parse_optional_arg(args, "value")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("blackboard") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Blackboard {
+                action,
+                bead_id: parse_required_arg(args, "bead_id")?,
+                agent_id: parse_optional_arg(args, "agent_id")?,
+                section: parse_optional_arg(args, "section")?,
+                content: parse_optional_arg(args, "content")?,
+                expected_version: parse_optional_arg(args, "expected_version")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("review") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Review {
+                action,
+                bead_id: parse_required_arg(args, "bead_id")?,
+                verdict: parse_optional_arg(args, "verdict")?,
+                comments: parse_optional_arg(args, "comments")?,
+                agent_id: parse_optional_arg(args, "agent_id")?,
+                reviewer: parse_optional_arg(args, "reviewer")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("approve") => Ok(CliAction::Command(CliCommand::Approve {
+            bead_id: parse_required_arg(args, "bead_id")?,
+            token: parse_required_arg(args, "token")?,
+            approver: parse_optional_arg(args, "approver")?,
+            note: parse_optional_arg(args, "note")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
+        Some("reissue-approval") => Ok(CliAction::Command(CliCommand::ReissueApproval {
+            bead_id: parse_required_arg(args, "bead_id")?,
+            gate: parse_optional_arg(args, "gate")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
         Some("monitor") => {
             let view = parse_optional_arg(args, "view")?;
+            let bead_id = parse_optional_arg(args, "bead_id")?;
             let watch_ms = parse_optional_arg(args, "watch_ms")?;
-            Ok(CliAction::Command(CliCommand::Monitor { view, watch_ms }))
+            let after_seq = parse_optional_arg(args, "after_seq")?;
+            let page_size = parse_optional_arg(args, "page_size")?;
+            let since = parse_optional_arg(args, "since")?;
+            let until = parse_optional_arg(args, "until")?;
+            let project = parse_optional_arg(args, "project")?;
+            Ok(CliAction::Command(CliCommand::Monitor {
+                view,
+                bead_id,
+                watch_ms,
+                after_seq,
+                page_size,
+                since,
+                until,
+                project,
+            }))
         }
         Some("init-db") => {
             let url = parse_optional_arg(args, "url")?;
@@ -136,6 +648,25 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
                 dry,
             }))
         }
+        Some("tenant") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Tenant {
+                action,
+                name: parse_optional_arg(args, "name")?,
+                seed_agents: parse_optional_arg(args, "seed_agents")?,
+                confirm: parse_optional_arg(args, "confirm")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("undelete") => Ok(CliAction::Command(CliCommand::Undelete {
+            table: parse_optional_arg(args, "table")?,
+            key: parse_optional_arg(args, "key")?,
+            limit: parse_optional_arg(args, "limit")?,
+            dry: parse_optional_arg(args, "dry")?,
+        })),
         Some("init-local-db") => {
             let container_name = parse_optional_arg(args, "container_name")?;
             let port = parse_optional_arg(args, "port")?;
@@ -143,6 +674,7 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
             let database = parse_optional_arg(args, "database")?;
             let schema = parse_optional_arg(args, "schema")?;
             let seed_agents = parse_optional_arg(args, "seed_agents")?;
+            let volume = parse_optional_arg(args, "volume")?;
             let dry = parse_optional_arg(args, "dry")?;
             Ok(CliAction::Command(CliCommand::InitLocalDb {
                 container_name,
@@ -151,9 +683,25 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
                 database,
                 schema,
                 seed_agents,
+                volume,
                 dry,
             }))
         }
+        Some("localdb") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => action.clone(),
+                None => parse_required_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::LocalDb {
+                action,
+                container_name: parse_optional_arg(args, "container_name")?,
+                to: parse_optional_arg(args, "to")?,
+                tail: parse_optional_arg(args, "tail")?,
+                purge_volume: parse_optional_arg(args, "purge_volume")?,
+                confirm: parse_optional_arg(args, "confirm")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
         Some("bootstrap") => Ok(CliAction::Command(CliCommand::Bootstrap {
             dry: parse_optional_arg(args, "dry")?,
         })),
@@ -161,18 +709,54 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
             let template = parse_optional_arg(args, "template")?;
             let out_dir = parse_optional_arg(args, "out_dir")?;
             let count = parse_optional_arg(args, "count")?;
+            let vars = parse_optional_arg(args, "vars")?;
             let dry = parse_optional_arg(args, "dry")?;
             Ok(CliAction::Command(CliCommand::SpawnPrompts {
                 template,
                 out_dir,
                 count,
+                vars,
                 dry,
             }))
         }
         Some("prompt") => {
             let id = parse_optional_arg(args, "id")?.map_or(1, |v: u32| v);
             let skill = parse_optional_arg(args, "skill")?;
-            Ok(CliAction::Command(CliCommand::Prompt { id, skill }))
+            let action = match args.get(1) {
+                Some(word) if !word.starts_with("--") => Some(word.clone()),
+                _ => parse_optional_arg(args, "action")?,
+            };
+            let body = parse_optional_arg(args, "body")?;
+            let file = parse_optional_arg(args, "file")?;
+            let template = parse_optional_arg(args, "template")?;
+            let vars = parse_optional_arg(args, "vars")?;
+            let dry = parse_optional_arg(args, "dry")?;
+            Ok(CliAction::Command(CliCommand::Prompt {
+                id,
+                skill,
+                action,
+                body,
+                file,
+                template,
+                vars,
+                dry,
+            }))
+        }
+        Some("record-symbols") => {
+            let bead_id = parse_required_arg::<String>(args, "bead_id")?;
+            let symbols = parse_optional_arg(args, "symbols")?;
+            let file = parse_optional_arg(args, "file")?;
+            let agent_id = parse_optional_arg(args, "agent_id")?;
+            let attempt = parse_optional_arg(args, "attempt")?;
+            let dry = parse_optional_arg(args, "dry")?;
+            Ok(CliAction::Command(CliCommand::RecordSymbols {
+                bead_id,
+                symbols,
+                file,
+                agent_id,
+                attempt,
+                dry,
+            }))
         }
         Some("smoke") => {
             let id = parse_optional_arg(args, "id")?.map_or(1, |v: u32| v);
@@ -181,17 +765,33 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
         }
         Some("history") => {
             let limit = parse_optional_arg(args, "limit")?;
-            Ok(CliAction::Command(CliCommand::History { limit }))
+            let after_seq = parse_optional_arg(args, "after_seq")?;
+            let page_size = parse_optional_arg(args, "page_size")?;
+            let since = parse_optional_arg(args, "since")?;
+            let until = parse_optional_arg(args, "until")?;
+            Ok(CliAction::Command(CliCommand::History {
+                limit,
+                after_seq,
+                page_size,
+                since,
+                until,
+            }))
         }
         Some("lock") => {
             let resource = parse_required_arg(args, "resource")?;
             let agent = parse_required_arg(args, "agent")?;
             let ttl_ms = parse_required_arg(args, "ttl_ms")?;
+            let wait_ms = parse_optional_arg(args, "wait_ms")?;
+            let purpose = parse_optional_arg(args, "purpose")?;
+            let bead_id = parse_optional_arg(args, "bead_id")?;
             let dry = parse_optional_arg(args, "dry")?;
             Ok(CliAction::Command(CliCommand::Lock {
                 resource,
                 agent,
                 ttl_ms,
+                wait_ms,
+                purpose,
+                bead_id,
                 dry,
             }))
         }
@@ -208,18 +808,38 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
         Some("broadcast") => {
             let msg = parse_required_arg(args, "msg")?;
             let from = parse_required_arg(args, "from")?;
-            let dry = parse_optional_arg(args, "dry")?;
-            Ok(CliAction::Command(CliCommand::Broadcast { msg, from, dry }))
+            Ok(CliAction::Command(CliCommand::Broadcast {
+                msg,
+                from,
+                topic: parse_optional_arg(args, "topic")?,
+                ttl_secs: parse_optional_arg(args, "ttl_secs")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
+        }
+        Some("announcements") => {
+            let action = match args.get(1).filter(|arg| !arg.starts_with("--")) {
+                Some(action) => Some(action.clone()),
+                None => parse_optional_arg(args, "action")?,
+            };
+            Ok(CliAction::Command(CliCommand::Announcements {
+                action,
+                agent_id: parse_optional_arg(args, "agent_id")?,
+                id: parse_optional_arg(args, "id")?,
+                topic: parse_optional_arg(args, "topic")?,
+                dry: parse_optional_arg(args, "dry")?,
+            }))
         }
         Some("load-profile") => {
             let agents = parse_optional_arg(args, "agents")?;
             let rounds = parse_optional_arg(args, "rounds")?;
             let timeout_ms = parse_optional_arg(args, "timeout_ms")?;
+            let concurrency = parse_optional_arg(args, "concurrency")?;
             let dry = parse_optional_arg(args, "dry")?;
             Ok(CliAction::Command(CliCommand::LoadProfile {
                 agents,
                 rounds,
                 timeout_ms,
+                concurrency,
                 dry,
             }))
         }
@@ -229,6 +849,75 @@ pub fn parse_cli_args(args: &[String]) -> Result<CliAction, CliError> {
     }
 }
 
+/// # Errors
+/// Returns `CliError::InvalidArgValue` if `--format` is not json, table, or wide.
+pub fn parse_output_format(args: &[String]) -> Result<Option<OutputFormat>, CliError> {
+    parse_optional_arg(args, "format")
+}
+
+/// Tenant the command runs against, from the global `--tenant` flag.
+///
+/// # Errors
+/// Returns `CliError::MissingRequiredArg` if `--tenant` is followed by another flag.
+pub fn parse_tenant(args: &[String]) -> Result<Option<String>, CliError> {
+    parse_optional_arg(args, "tenant")
+}
+
+/// Whether `args` are nothing but `--format`, `--record` and
+/// `--log-format` with their values, which start the stdin protocol loop.
+fn only_session_flags(args: &[String]) -> bool {
+    args.chunks(2).all(|pair| {
+        matches!(pair, [flag, _] if matches!(flag.as_str(), "--format" | "--record" | "--log-format"))
+    })
+}
+
+/// Trace file from the global `--record` flag: every request the process
+/// answers is appended to it for `replay --file`.
+///
+/// # Errors
+/// Returns `CliError::MissingRequiredArg` if `--record` is followed by another flag.
+pub fn parse_record(args: &[String]) -> Result<Option<String>, CliError> {
+    parse_optional_arg(args, "record")
+}
+
+/// How log events on stderr are written, from the global `--log-format` flag.
+///
+/// # Errors
+/// Returns `CliError::InvalidArgValue` if `--log-format` is not json or pretty.
+pub fn parse_log_format(args: &[String]) -> Result<Option<crate::logging::LogFormat>, CliError> {
+    parse_optional_arg(args, "log_format")
+}
+
+fn read_stdin_arg(name: &str) -> Result<String, CliError> {
+    let mut raw = String::new();
+    std::io::Read::read_to_string(&mut std::io::stdin(), &mut raw).map_err(|err| {
+        CliError::InvalidArgValue {
+            arg: name.to_string(),
+            error: format!("failed to read stdin: {err}"),
+        }
+    })?;
+    Ok(raw)
+}
+
+/// Positional `key=value` settings after `config set`, e.g.
+/// `lease_ttl_ms=120000`. Keys must be config keys; values are checked by the
+/// command.
+fn parse_config_assignments(args: &[String]) -> Result<Vec<(String, String)>, CliError> {
+    args.iter()
+        .skip(2)
+        .filter(|arg| !arg.starts_with("--"))
+        .filter_map(|arg| arg.split_once('='))
+        .map(|(key, value)| {
+            crate::ConfigKey::try_from(key)
+                .map(|key| (key.as_str().to_string(), value.to_string()))
+                .map_err(|error| CliError::InvalidArgValue {
+                    arg: key.to_string(),
+                    error,
+                })
+        })
+        .collect()
+}
+
 fn parse_required_arg<T>(args: &[String], name: &str) -> Result<T, CliError>
 where
     T: std::str::FromStr,
@@ -0,0 +1,1553 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![forbid(unsafe_code)]
+
+use crate::prompts::PROMPT_ACTIONS;
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    Flag,
+    Int,
+    Text,
+    Timestamp,
+    Choice(&'static [&'static str]),
+}
+
+impl ArgKind {
+    #[must_use]
+    pub const fn type_name(self) -> &'static str {
+        match self {
+            Self::Flag => "bool",
+            Self::Int => "int",
+            Self::Text => "string",
+            Self::Timestamp => "rfc3339|ms",
+            Self::Choice(_) => "choice",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub required: bool,
+    pub doc: &'static str,
+}
+
+impl ArgSpec {
+    /// The CLI spelling: `bead_id` is passed as `--bead-id`.
+    #[must_use]
+    pub fn flag(&self) -> String {
+        format!("--{}", self.name.replace('_', "-"))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub summary: &'static str,
+    pub args: &'static [ArgSpec],
+    pub examples: &'static [&'static str],
+}
+
+impl CommandSpec {
+    #[must_use]
+    pub fn explain(&self) -> Value {
+        json!({
+            "cmd": self.name,
+            "summary": self.summary,
+            "args": self.args.iter().map(|arg| {
+                let choices = match arg.kind {
+                    ArgKind::Choice(values) => Some(values),
+                    _ => None,
+                };
+                json!({
+                    "name": arg.name,
+                    "flag": arg.flag(),
+                    "type": arg.kind.type_name(),
+                    "required": arg.required,
+                    "choices": choices,
+                    "doc": arg.doc,
+                })
+            }).collect::<Vec<_>>(),
+            "examples": self.examples,
+        })
+    }
+}
+
+/// Flags accepted by every command on the CLI path. `--dry` is a no-op for
+/// read-only commands, which never mutate anyway; `--tenant` runs the
+/// command against that tenant's swarm; `--record` appends each request and
+/// its envelope to a trace file; `--log-format` picks json or pretty logs.
+pub const GLOBAL_FLAGS: &[&str] = &["--format", "--dry", "--tenant", "--record", "--log-format"];
+
+/// Subcommands handled by the binary itself rather than the protocol.
+pub const CLI_ONLY_COMMANDS: &[(&str, &str)] = &[
+    ("completions", "Print a shell completion script"),
+    ("repl", "Interactive shell with history and shorthand"),
+];
+
+const fn opt(name: &'static str, kind: ArgKind, doc: &'static str) -> ArgSpec {
+    ArgSpec {
+        name,
+        kind,
+        required: false,
+        doc,
+    }
+}
+
+const fn req(name: &'static str, kind: ArgKind, doc: &'static str) -> ArgSpec {
+    ArgSpec {
+        name,
+        kind,
+        required: true,
+        doc,
+    }
+}
+
+const DRY: ArgSpec = opt(
+    "dry",
+    ArgKind::Flag,
+    "Plan the command without side effects",
+);
+const MONITOR_VIEWS: &[&str] = &[
+    "active",
+    "progress",
+    "failures",
+    "events",
+    "messages",
+    "drift",
+    "health",
+    "alerts",
+    "coverage",
+    "sessions",
+    "approvals",
+    "escalations",
+    "sla",
+];
+const QA_TARGETS: &[&str] = &["smoke"];
+const WORKSPACE_ACTIONS: &[&str] = &["show", "create", "remove"];
+const BEAD_ACTIONS: &[&str] = &["snapshot", "restore"];
+const SYNC_ACTIONS: &[&str] = &["repair"];
+const EVENTS_ACTIONS: &[&str] = &["migrate"];
+const CHAOS_ACTIONS: &[&str] = &["status"];
+const CONFIG_ACTIONS: &[&str] = &["get", "set"];
+const LABELS_ACTIONS: &[&str] = &["get", "set"];
+const JOBS_ACTIONS: &[&str] = &["list", "run-now", "disable", "enable"];
+const SCHEDULED_JOBS: &[&str] = &[
+    "sync-backlog",
+    "recover",
+    "gc",
+    "sla-check",
+    "metrics-flush",
+    "partitions",
+];
+const NOTIFY_ACTIONS: &[&str] = &["test"];
+const PERF_ACTIONS: &[&str] = &["compare"];
+const MAINTENANCE_TARGETS: &[&str] = &["partitions"];
+const PARTITION_ACTIONS: &[&str] = &["list", "enable", "create", "detach", "drop"];
+const PARTITIONED_TABLES: &[&str] = &["execution_events", "stage_artifacts"];
+const ANNOUNCEMENTS_ACTIONS: &[&str] = &["list", "ack"];
+const NOTIFICATION_SEVERITIES: &[&str] = &["info", "warning", "critical"];
+const BACKLOG_ACTIONS: &[&str] = &["preview", "set-priority", "bump", "remove"];
+const CONFIG_KEYS: &[&str] = &[
+    "max_agents",
+    "max_implementation_attempts",
+    "claim_label",
+    "swarm_status",
+    "lease_ttl_ms",
+    "heartbeat_grace_ms",
+    "recovery_scan_interval_ms",
+];
+const SWARM_STATUSES: &[&str] = &["initializing", "running", "paused", "complete", "error"];
+const SESSION_ACTIONS: &[&str] = &["start", "end", "show"];
+const KV_ACTIONS: &[&str] = &["set", "get", "delete"];
+const TENANT_ACTIONS: &[&str] = &["create", "list", "delete"];
+const LOCALDB_ACTIONS: &[&str] = &["status", "stop", "destroy", "logs", "upgrade"];
+const ARTIFACT_ACTIONS: &[&str] = &["put"];
+const DIFF_ACTIONS: &[&str] = &["put", "get"];
+const TRANSITION_RESULTS: &[&str] = &["passed", "failed", "error", "cancelled"];
+const UNDELETE_TABLES: &[&str] = &["claims", "backlog", "agents"];
+const BLACKBOARD_ACTIONS: &[&str] = &["read", "append"];
+const BLACKBOARD_SECTIONS: &[&str] = &["plan", "decisions", "open_questions"];
+const REVIEW_ACTIONS: &[&str] = &["submit", "list"];
+const REVIEW_VERDICTS: &[&str] = &["approve", "changes"];
+const STAGES: &[&str] = &["rust-contract", "implement", "qa-enforcer", "red-queen"];
+const APPROVAL_GATES: &[&str] = &[
+    "rust-contract",
+    "implement",
+    "qa-enforcer",
+    "red-queen",
+    "land",
+];
+const COVERAGE_FORMATS: &[&str] = &["lcov", "cobertura"];
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "doctor",
+        summary: "Health check | NEXT: fix failures before proceeding",
+        args: &[
+            opt(
+                "show_candidates",
+                ArgKind::Flag,
+                "List each database URL candidate, its source, and why it failed",
+            ),
+            opt(
+                "analyze_db",
+                ArgKind::Flag,
+                "EXPLAIN the hot claim/progress/events queries and recommend indexes",
+            ),
+            opt(
+                "apply",
+                ArgKind::Flag,
+                "With --analyze-db, build the recommended indexes concurrently",
+            ),
+            DRY,
+        ],
+        examples: &[
+            "swarm doctor",
+            "swarm doctor --show-candidates",
+            "swarm doctor --analyze-db --apply",
+        ],
+    },
+    CommandSpec {
+        name: "db-health",
+        summary: "Pool size + acquire latency | NEXT: doctor if unhealthy",
+        args: &[opt("samples", ArgKind::Int, "Pool acquires to time (max 100)")],
+        examples: &["swarm db-health --samples 20"],
+    },
+    CommandSpec {
+        name: "healthz",
+        summary: "Liveness probe: DB ping + pool stats | NEXT: doctor if it fails",
+        args: &[],
+        examples: &["swarm healthz"],
+    },
+    CommandSpec {
+        name: "invariants",
+        summary: "Assert claim/agent consistency | NEXT: run each violation's fix",
+        args: &[],
+        examples: &["swarm invariants"],
+    },
+    CommandSpec {
+        name: "costs",
+        summary: "Token spend + stage time per bead/agent/stage | NEXT: costs --bead-id <top>",
+        args: &[
+            opt("bead_id", ArgKind::Text, "Limit to one bead"),
+            opt("since", ArgKind::Timestamp, "Earliest usage time, inclusive"),
+            opt("until", ArgKind::Timestamp, "Latest usage time, inclusive"),
+            opt(
+                "pricing",
+                ArgKind::Text,
+                "Per-model pricing JSON (default SWARM_MODEL_PRICING)",
+            ),
+        ],
+        examples: &[
+            "swarm costs --since 2024-01-01T00:00:00Z",
+            "swarm costs --bead-id swm-123",
+        ],
+    },
+    CommandSpec {
+        name: "report-usage",
+        summary: "Record LLM tokens for a bead, check its budget | NEXT: costs",
+        args: &[
+            req("agent_id", ArgKind::Int, "Agent that made the call"),
+            req("bead_id", ArgKind::Text, "Bead the tokens were spent on"),
+            opt("stage", ArgKind::Choice(STAGES), "Stage that made the call"),
+            req("model", ArgKind::Text, "Model billed, matched against pricing"),
+            req("prompt_tokens", ArgKind::Int, "Input tokens"),
+            req("completion_tokens", ArgKind::Int, "Output tokens"),
+            DRY,
+        ],
+        examples: &[
+            "swarm report-usage --agent-id 1 --bead-id swm-123 --stage implement --model sonnet --prompt-tokens 1200 --completion-tokens 300",
+        ],
+    },
+    CommandSpec {
+        name: "report-coverage",
+        summary: "Upload an lcov/Cobertura summary for a bead's stage | NEXT: monitor --view coverage",
+        args: &[
+            req("agent_id", ArgKind::Int, "Agent that ran the stage"),
+            req("bead_id", ArgKind::Text, "Bead the report covers"),
+            opt(
+                "stage",
+                ArgKind::Choice(STAGES),
+                "Stage run to attach to (default latest)",
+            ),
+            opt("path", ArgKind::Text, "Coverage report file"),
+            opt("report", ArgKind::Text, "Coverage report content, instead of path"),
+            opt(
+                "format",
+                ArgKind::Choice(COVERAGE_FORMATS),
+                "Report format (default detected)",
+            ),
+            DRY,
+        ],
+        examples: &[
+            "swarm report-coverage --agent-id 1 --bead-id swm-123 --stage qa-enforcer --path coverage/lcov.info",
+        ],
+    },
+    CommandSpec {
+        name: "status",
+        summary: "Swarm state | NEXT: if idle>0 & pending>0, run claim-next",
+        args: &[opt("project", ArgKind::Text, "Return only these paths of the data")],
+        examples: &[
+            "swarm status",
+            "swarm status --format wide",
+            "swarm status --project beads_by_status.open",
+        ],
+    },
+    CommandSpec {
+        name: "top",
+        summary: "Per-agent live activity | NEXT: release stale heartbeats",
+        args: &[opt(
+            "window_mins",
+            ArgKind::Int,
+            "Throughput window in minutes (default 60)",
+        )],
+        examples: &["swarm top", "swarm top --window-mins 15 --format wide"],
+    },
+    CommandSpec {
+        name: "forecast",
+        summary: "Backlog drain ETA from recent throughput | NEXT: what-if with --agents",
+        args: &[
+            opt(
+                "window_hours",
+                ArgKind::Int,
+                "Throughput lookback in hours, 1 to 8760 (default 24)",
+            ),
+            opt(
+                "agents",
+                ArgKind::Int,
+                "What-if agent count (default: registered agents)",
+            ),
+        ],
+        examples: &["swarm forecast", "swarm forecast --window-hours 72 --agents 20"],
+    },
+    CommandSpec {
+        name: "init",
+        summary: "Full bootstrap (bootstrap+init-db+register) | NEXT: doctor",
+        args: &[
+            DRY,
+            opt("database_url", ArgKind::Text, "Postgres URL to initialize"),
+            opt("schema", ArgKind::Text, "Path to schema.sql"),
+            opt("seed_agents", ArgKind::Int, "Agents to register after init"),
+            opt(
+                "rollback_on_error",
+                ArgKind::Flag,
+                "Undo every step if any fails, in one database transaction",
+            ),
+        ],
+        examples: &[
+            "swarm init",
+            "swarm init --seed-agents 12 --dry",
+            "swarm init --rollback-on-error",
+        ],
+    },
+    CommandSpec {
+        name: "bootstrap",
+        summary: "Repo structure | NEXT: init-db",
+        args: &[DRY],
+        examples: &["swarm bootstrap"],
+    },
+    CommandSpec {
+        name: "init-db",
+        summary: "Database schema | NEXT: register if seed_agents not set",
+        args: &[
+            opt("url", ArgKind::Text, "Postgres URL"),
+            opt("schema", ArgKind::Text, "Path to schema.sql"),
+            opt("seed_agents", ArgKind::Int, "Agents to register after init"),
+            DRY,
+        ],
+        examples: &["swarm init-db --url postgresql://localhost:5432/swarm"],
+    },
+    CommandSpec {
+        name: "init-local-db",
+        summary: "Local Docker DB | NEXT: init-db with new URL",
+        args: &[
+            opt("container_name", ArgKind::Text, "Docker container name"),
+            opt("port", ArgKind::Int, "Host port to publish"),
+            opt("user", ArgKind::Text, "Database user"),
+            opt("database", ArgKind::Text, "Database name"),
+            opt("schema", ArgKind::Text, "Path to schema.sql"),
+            opt("seed_agents", ArgKind::Int, "Agents to register after init"),
+            opt("volume", ArgKind::Text, "Named volume for the data directory"),
+            DRY,
+        ],
+        examples: &[
+            "swarm init-local-db --port 5437",
+            "swarm init-local-db --volume swarm-pgdata",
+        ],
+    },
+    CommandSpec {
+        name: "localdb",
+        summary: "Local Docker DB lifecycle | NEXT: status before stop, destroy, upgrade",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(LOCALDB_ACTIONS),
+                "status, stop, destroy, logs, or upgrade (also accepted positionally)",
+            ),
+            opt("container_name", ArgKind::Text, "Docker container name"),
+            opt("to", ArgKind::Int, "Postgres major to upgrade to"),
+            opt("tail", ArgKind::Int, "Log lines to return"),
+            opt("purge_volume", ArgKind::Flag, "Also remove the data volume on destroy"),
+            opt("confirm", ArgKind::Text, "Repeat the container name to destroy it"),
+            DRY,
+        ],
+        examples: &[
+            "swarm localdb status",
+            "swarm localdb logs --tail 50",
+            "swarm localdb upgrade --to 17",
+            "swarm localdb destroy --confirm shitty-swarm-manager-db --purge-volume",
+        ],
+    },
+    CommandSpec {
+        name: "tenant",
+        summary: "Tenants sharing one database | NEXT: pass --tenant to any command",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(TENANT_ACTIONS),
+                "create, list, or delete (also accepted positionally)",
+            ),
+            opt("name", ArgKind::Text, "Tenant name; required for create and delete"),
+            opt("seed_agents", ArgKind::Int, "Agents to register in a new tenant"),
+            opt("confirm", ArgKind::Text, "Repeat the name to delete a tenant and all its data"),
+            DRY,
+        ],
+        examples: &[
+            "swarm tenant create --name acme --seed-agents 4",
+            "swarm tenant list",
+            "swarm tenant delete --name acme --confirm acme",
+        ],
+    },
+    CommandSpec {
+        name: "undelete",
+        summary: "Deleted claims, backlog entries, agents | NEXT: restore one by key",
+        args: &[
+            opt("table", ArgKind::Choice(UNDELETE_TABLES), "Table the row was deleted from"),
+            opt("key", ArgKind::Text, "Bead id, or agent id for agents; lists deleted rows without it"),
+            opt("limit", ArgKind::Int, "Most deleted rows to list (default 50)"),
+            DRY,
+        ],
+        examples: &[
+            "swarm undelete --table backlog",
+            "swarm undelete --table backlog --key bd-abc",
+        ],
+    },
+    CommandSpec {
+        name: "register",
+        summary: "Seed agents | NEXT: status to verify",
+        args: &[
+            opt("count", ArgKind::Int, "Agents to register (max 100)"),
+            DRY,
+        ],
+        examples: &["swarm register --count 12"],
+    },
+    CommandSpec {
+        name: "next",
+        summary: "Top bead rec (preview) | NEXT: claim-next to reserve",
+        args: &[DRY],
+        examples: &["swarm next"],
+    },
+    CommandSpec {
+        name: "claim-next",
+        summary: "Claim top bead | NEXT: agent with returned agent_id",
+        args: &[
+            opt(
+                "reserve",
+                ArgKind::Flag,
+                "Only reserve the bead; accept-claim or reject-claim it next",
+            ),
+            opt("agent_id", ArgKind::Int, "Agent to reserve for (required with --reserve)"),
+            opt("ttl_secs", ArgKind::Int, "Reservation lifetime, 1-900 (default 60)"),
+            opt(
+                "label",
+                ArgKind::Text,
+                "Only beads with one of these comma-separated labels (needs --agent-id)",
+            ),
+            DRY,
+        ],
+        examples: &[
+            "swarm claim-next",
+            "swarm claim-next --reserve --agent-id 3 --ttl-secs 120",
+            "swarm claim-next --label docs --agent-id 3",
+        ],
+    },
+    CommandSpec {
+        name: "accept-claim",
+        summary: "Claim a reserved bead | NEXT: agent with the same id",
+        args: &[
+            req("agent_id", ArgKind::Int, "Agent holding the reservation"),
+            req("bead_id", ArgKind::Text, "Reserved bead"),
+            DRY,
+        ],
+        examples: &["swarm accept-claim --agent-id 3 --bead-id bd-abc"],
+    },
+    CommandSpec {
+        name: "reject-claim",
+        summary: "Return a reserved bead to the pool | NEXT: claim-next --reserve",
+        args: &[
+            req("agent_id", ArgKind::Int, "Agent holding the reservation"),
+            req("bead_id", ArgKind::Text, "Reserved bead"),
+            opt("reason", ArgKind::Text, "Why the agent passed on the bead"),
+            DRY,
+        ],
+        examples: &["swarm reject-claim --agent-id 3 --bead-id bd-abc --reason 'needs gpu'"],
+    },
+    CommandSpec {
+        name: "assign",
+        summary: "Explicit assign | NEXT: agent with assigned agent_id",
+        args: &[
+            req("bead_id", ArgKind::Text, "Bead to assign"),
+            req("agent_id", ArgKind::Int, "Agent that receives the bead"),
+            DRY,
+        ],
+        examples: &["swarm assign --bead-id bd-abc123 --agent-id 1"],
+    },
+    CommandSpec {
+        name: "cancel",
+        summary: "Cancel a bead, stop its stage, release its claim | NEXT: status",
+        args: &[
+            req("bead_id", ArgKind::Text, "Bead to cancel"),
+            opt("reason", ArgKind::Text, "Sent to the owning agent and stage history"),
+            DRY,
+        ],
+        examples: &["swarm cancel --bead-id bd-abc --reason 'superseded by bd-def'"],
+    },
+    CommandSpec {
+        name: "takeover",
+        summary: "Hand a claim to another agent | NEXT: agent with to_agent",
+        args: &[
+            req("bead_id", ArgKind::Text, "Claimed bead"),
+            opt(
+                "to_agent",
+                ArgKind::Int,
+                "Idle agent that takes the bead; required unless consenting",
+            ),
+            opt(
+                "from_agent",
+                ArgKind::Int,
+                "Current owner; required with consent",
+            ),
+            opt(
+                "consent",
+                ArgKind::Flag,
+                "Record the owner's consent to a takeover of its claim",
+            ),
+            opt("max_bytes", ArgKind::Int, "Size budget for the handed-off resume context"),
+            opt("max_tokens", ArgKind::Int, "Size budget for the handed-off resume context in tokens"),
+            DRY,
+        ],
+        examples: &[
+            "swarm takeover --bead-id bd-abc --to-agent 4",
+            "swarm takeover --bead-id bd-abc --from-agent 2 --consent",
+            "swarm takeover --bead-id bd-abc --to-agent 4 --from-agent 2",
+        ],
+    },
+    CommandSpec {
+        name: "recover",
+        summary: "Requeue orphaned claims, drop lapsed locks | NEXT: status",
+        args: &[DRY],
+        examples: &["swarm recover", "swarm recover --dry"],
+    },
+    CommandSpec {
+        name: "agent",
+        summary: "Run pipeline | NEXT: monitor --view progress",
+        args: &[req("id", ArgKind::Int, "Agent id"), DRY],
+        examples: &["swarm agent --id 1", "swarm agent --id 1 --dry"],
+    },
+    CommandSpec {
+        name: "run",
+        summary: "Loop claim+agent until backlog drains | NEXT: status",
+        args: &[
+            opt("id", ArgKind::Int, "Agent id (default 1)"),
+            opt(
+                "until_empty",
+                ArgKind::Flag,
+                "Stop when no beads are pending (default true)",
+            ),
+            opt("budget_ms", ArgKind::Int, "Wall-clock budget (default 600000)"),
+            opt("max_cycles", ArgKind::Int, "Stop after this many claims"),
+            DRY,
+        ],
+        examples: &[
+            "swarm run --id 1 --until-empty",
+            "swarm run --id 1 --max-cycles 5 --budget-ms 60000",
+        ],
+    },
+    CommandSpec {
+        name: "run-all",
+        summary: "Claim+agent for every idle agent | NEXT: run-all again or status",
+        args: &[
+            opt(
+                "concurrency",
+                ArgKind::Int,
+                "Agents running stages at once, 1-32 (default 4)",
+            ),
+            DRY,
+        ],
+        examples: &["swarm run-all", "swarm run-all --concurrency 8 --dry"],
+    },
+    CommandSpec {
+        name: "run-once",
+        summary: "Single cycle | NEXT: status to see result",
+        args: &[opt("id", ArgKind::Int, "Agent id"), DRY],
+        examples: &["swarm run-once --id 1"],
+    },
+    CommandSpec {
+        name: "smoke",
+        summary: "Smoke test | NEXT: fix errors before spawn-prompts",
+        args: &[opt("id", ArgKind::Int, "Agent id (default 1)"), DRY],
+        examples: &["swarm smoke --id 1"],
+    },
+    CommandSpec {
+        name: "monitor",
+        summary: "View state | VIEWS: active,progress,failures,events,messages,drift,health,alerts",
+        args: &[
+            opt("view", ArgKind::Choice(MONITOR_VIEWS), "View to render"),
+            opt("bead_id", ArgKind::Text, "Limit events/drift to one bead"),
+            opt("watch_ms", ArgKind::Int, "Poll interval for streaming"),
+            opt("after_seq", ArgKind::Int, "Cursor from a previous next_cursor"),
+            opt("page_size", ArgKind::Int, "Rows per page (max 1000)"),
+            opt("since", ArgKind::Timestamp, "Earliest event time, inclusive"),
+            opt("until", ArgKind::Timestamp, "Latest event time, inclusive"),
+            opt("project", ArgKind::Text, "Return only these paths of the data"),
+        ],
+        examples: &[
+            "swarm monitor --view progress",
+            "swarm monitor --view failures --since 2024-01-01T00:00:00Z",
+            "swarm monitor --view drift --bead-id bd-abc",
+            "swarm monitor --view health",
+        ],
+    },
+    CommandSpec {
+        name: "release",
+        summary: "Free agent | NEXT: status to confirm",
+        args: &[req("agent_id", ArgKind::Int, "Agent to release"), DRY],
+        examples: &["swarm release --agent-id 1"],
+    },
+    CommandSpec {
+        name: "quarantine",
+        summary: "Block agent from claiming | NEXT: unquarantine once fixed",
+        args: &[
+            req("agent_id", ArgKind::Int, "Agent to quarantine"),
+            opt("reason", ArgKind::Text, "Why the agent is quarantined"),
+            DRY,
+        ],
+        examples: &["swarm quarantine --agent-id 3 --reason \"edits outside scope\""],
+    },
+    CommandSpec {
+        name: "unquarantine",
+        summary: "Let agent claim again | NEXT: monitor --view health",
+        args: &[
+            req("agent_id", ArgKind::Int, "Agent to release from quarantine"),
+            req("reason", ArgKind::Text, "Why the quarantine is lifted"),
+            DRY,
+        ],
+        examples: &["swarm unquarantine --agent-id 3 --reason \"prompt fixed\""],
+    },
+    CommandSpec {
+        name: "land",
+        summary: "Verify push, finalize bead | NEXT: artifacts for the evidence",
+        args: &[
+            req("bead_id", ArgKind::Text, "Bead to finalize"),
+            req("agent_id", ArgKind::Int, "Agent holding the bead"),
+            req("change_id", ArgKind::Text, "jj change id or commit id expected on the remote"),
+            opt("remote", ArgKind::Text, "Remote to check (default origin)"),
+            opt(
+                "override_push_check",
+                ArgKind::Flag,
+                "Skip the remote check; requires --reason",
+            ),
+            opt("reason", ArgKind::Text, "Why the push check is overridden"),
+            DRY,
+        ],
+        examples: &[
+            "swarm land --bead-id bd-abc --agent-id 1 --change-id qpvuntsm",
+            "swarm land --bead-id bd-abc --agent-id 1 --change-id qpvuntsm --override-push-check --reason \"remote offline\"",
+        ],
+    },
+    CommandSpec {
+        name: "workspace",
+        summary: "Agent's own checkout | NEXT: work in the reported path",
+        args: &[
+            req("agent_id", ArgKind::Int, "Agent owning the workspace"),
+            opt(
+                "action",
+                ArgKind::Choice(WORKSPACE_ACTIONS),
+                "show (default), create, or remove",
+            ),
+            opt("bead_id", ArgKind::Text, "Bead for create (default: agent's claim)"),
+            DRY,
+        ],
+        examples: &[
+            "swarm workspace --agent-id 1",
+            "swarm workspace --agent-id 1 --action create",
+            "swarm workspace --agent-id 1 --action remove",
+        ],
+    },
+    CommandSpec {
+        name: "session",
+        summary: "Which process is acting as an agent | NEXT: monitor --view sessions",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(SESSION_ACTIONS),
+                "start, end, or show (also accepted positionally)",
+            ),
+            req("agent_id", ArgKind::Int, "Agent the process acts as"),
+            opt("host", ArgKind::Text, "Host name (default: this machine's)"),
+            opt("pid", ArgKind::Int, "Process id (default: the caller's)"),
+            opt("version", ArgKind::Text, "Agent version (default: swarm's)"),
+            DRY,
+        ],
+        examples: &[
+            "swarm session start --agent-id 7",
+            "swarm session end --agent-id 7",
+        ],
+    },
+    CommandSpec {
+        name: "kv",
+        summary: "Agent scratch state for a bead | NEXT: kv get to read it back",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(KV_ACTIONS),
+                "set, get, or delete (also accepted positionally)",
+            ),
+            req("agent_id", ArgKind::Int, "Agent that owns the entries"),
+            req("bead_id", ArgKind::Text, "Bead the entries belong to"),
+            opt("key", ArgKind::Text, "Entry key (get lists all keys without it)"),
+            opt("value", ArgKind::Text, "JSON value for set; other text is stored as a string"),
+            DRY,
+        ],
+        examples: &[
+            "swarm kv set --agent-id 7 --bead-id bd-abc --key approach --value '\"split parser\"'",
+            "swarm kv get --agent-id 7 --bead-id bd-abc",
+        ],
+    },
+    CommandSpec {
+        name: "blackboard",
+        summary: "Shared notes for a bead | NEXT: append with the version you read",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(BLACKBOARD_ACTIONS),
+                "read or append (also accepted positionally)",
+            ),
+            req("bead_id", ArgKind::Text, "Bead the blackboard belongs to"),
+            opt("agent_id", ArgKind::Int, "Agent appending (append)"),
+            opt(
+                "section",
+                ArgKind::Choice(BLACKBOARD_SECTIONS),
+                "Section to append to, or to limit a read to",
+            ),
+            opt("content", ArgKind::Text, "Entry text (append)"),
+            opt(
+                "expected_version",
+                ArgKind::Int,
+                "Blackboard version last read; append fails if it moved on",
+            ),
+            DRY,
+        ],
+        examples: &[
+            "swarm blackboard read --bead-id bd-abc",
+            "swarm blackboard append --bead-id bd-abc --agent-id 7 --section decisions --content 'Keep the v1 wire format' --expected-version 3",
+        ],
+    },
+    CommandSpec {
+        name: "review",
+        summary: "Approve or request changes on a bead | NEXT: land once approvals are in",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(REVIEW_ACTIONS),
+                "submit or list (also accepted positionally)",
+            ),
+            req("bead_id", ArgKind::Text, "Bead under review"),
+            opt("verdict", ArgKind::Choice(REVIEW_VERDICTS), "Verdict to submit"),
+            opt("comments", ArgKind::Text, "Review comments"),
+            opt("agent_id", ArgKind::Int, "Reviewing agent"),
+            opt("reviewer", ArgKind::Text, "Reviewer name (default: agent:<agent_id>)"),
+            DRY,
+        ],
+        examples: &[
+            "swarm review submit --bead-id bd-abc --verdict approve --agent-id 8",
+            "swarm review submit --bead-id bd-abc --verdict changes --reviewer alice --comments 'Split the migration'",
+        ],
+    },
+    CommandSpec {
+        name: "approve",
+        summary: "Release a bead waiting at an approval gate | NEXT: the held stage or land continues",
+        args: &[
+            req("bead_id", ArgKind::Text, "Bead awaiting approval"),
+            req(
+                "token",
+                ArgKind::Text,
+                "Token shown by monitor --view approvals",
+            ),
+            opt("approver", ArgKind::Text, "Who approved (default: $USER)"),
+            opt("note", ArgKind::Text, "Reason recorded with the approval"),
+            DRY,
+        ],
+        examples: &[
+            "swarm approve --bead-id bd-abc --token 3f2a9c0d1b7e4f6a8c5d2e1f0a9b8c7d --note 'Schema change reviewed'",
+        ],
+    },
+    CommandSpec {
+        name: "reissue-approval",
+        summary: "Send a pending approval a fresh token | NEXT: approve with the new token",
+        args: &[
+            req("bead_id", ArgKind::Text, "Bead awaiting approval"),
+            opt(
+                "gate",
+                ArgKind::Choice(APPROVAL_GATES),
+                "Gate to reissue (default: the bead's only pending gate)",
+            ),
+            DRY,
+        ],
+        examples: &["swarm reissue-approval --bead-id bd-abc --gate implement"],
+    },
+    CommandSpec {
+        name: "artifacts",
+        summary: "Get bead outputs | NEXT: parse content by artifact_type",
+        args: &[
+            req("bead_id", ArgKind::Text, "Bead whose artifacts to load"),
+            opt("artifact_type", ArgKind::Text, "Restrict to one artifact type"),
+            opt("out_dir", ArgKind::Text, "Write contents here; return only the manifest"),
+        ],
+        examples: &[
+            "swarm artifacts --bead-id bd-abc",
+            "swarm artifacts --bead-id bd-abc --out-dir ./artifacts",
+        ],
+    },
+    CommandSpec {
+        name: "artifact",
+        summary: "Store a file on a bead | NEXT: artifacts to read it back",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(ARTIFACT_ACTIONS),
+                "put (also accepted positionally)",
+            ),
+            req("bead_id", ArgKind::Text, "Bead to attach the file to"),
+            req("stage", ArgKind::Choice(STAGES), "Stage whose latest run gets the artifact"),
+            req("type", ArgKind::Text, "Artifact type, e.g. design_document, diff, screenshot"),
+            req("file", ArgKind::Text, "File to upload"),
+            DRY,
+        ],
+        examples: &[
+            "swarm artifact put --bead-id bd-abc --stage implement --type design_document --file design.md",
+            "swarm artifact put --bead-id bd-abc --stage qa-enforcer --type screenshot --file ui.png",
+        ],
+    },
+    CommandSpec {
+        name: "diff",
+        summary: "Store or fetch a bead's patch set | NEXT: resume-context includes the latest",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(DIFF_ACTIONS),
+                "put | get (also accepted positionally)",
+            ),
+            req("bead_id", ArgKind::Text, "Bead the patch belongs to"),
+            opt("stage", ArgKind::Choice(STAGES), "put: stage run to attach to (default: latest run)"),
+            opt("file", ArgKind::Text, "put: patch file to store; get: write the patch here"),
+            opt("patch", ArgKind::Text, "put: patch text instead of --file"),
+            opt("base_revision", ArgKind::Text, "put: revision the patch applies to"),
+            DRY,
+        ],
+        examples: &[
+            "swarm diff put --bead-id bd-abc --file changes.patch --base-revision 3f2c1ab",
+            "swarm diff get --bead-id bd-abc --file changes.patch",
+        ],
+    },
+    CommandSpec {
+        name: "replay",
+        summary: "Bead lifecycle from transition events, or re-run a recorded trace | NEXT: inspect anomalies or divergences",
+        args: &[
+            opt("bead_id", ArgKind::Text, "Bead to replay (required without file)"),
+            opt(
+                "file",
+                ArgKind::Text,
+                "Trace written by --record to re-run (also accepted positionally)",
+            ),
+            opt("ignore", ArgKind::Text, "Comma-separated keys to leave out of the diff"),
+            DRY,
+        ],
+        examples: &[
+            "swarm replay --bead-id bd-abc",
+            "swarm replay trace.ndjson --dry",
+            "swarm replay --file trace.ndjson --ignore created_at,id",
+        ],
+    },
+    CommandSpec {
+        name: "explain-transition",
+        summary: "Decision and reason chain for a hypothetical stage result | NEXT: check the DAG",
+        args: &[
+            req("stage", ArgKind::Choice(STAGES), "Stage the result is for"),
+            req("result", ArgKind::Choice(TRANSITION_RESULTS), "Hypothetical stage result"),
+            opt("attempt", ArgKind::Int, "Implementation attempt (default 0)"),
+            opt("max_attempts", ArgKind::Int, "Retry limit (default: swarm config)"),
+            opt("category", ArgKind::Text, "Diagnostics category (default: from message)"),
+            opt("message", ArgKind::Text, "Failure message"),
+        ],
+        examples: &[
+            "swarm explain-transition --stage qa-enforcer --result failed --attempt 2",
+            "swarm explain-transition --stage red-queen --result passed --max-attempts 5",
+        ],
+    },
+    CommandSpec {
+        name: "verify",
+        summary: "Check the signature chain over a bead's artifacts | NEXT: artifacts if invalid",
+        args: &[req("bead_id", ArgKind::Text, "Bead to verify")],
+        examples: &["swarm verify --bead-id bd-abc"],
+    },
+    CommandSpec {
+        name: "attest",
+        summary: "Export a bead's execution provenance as an in-toto statement | NEXT: verify",
+        args: &[req("bead_id", ArgKind::Text, "Bead to attest")],
+        examples: &["swarm attest --bead-id bd-abc > bd-abc.intoto.json"],
+    },
+    CommandSpec {
+        name: "env-diff",
+        summary: "Compare toolchain fingerprints of two attempts | NEXT: replay",
+        args: &[
+            req("bead_id", ArgKind::Text, "Bead whose attempts to compare"),
+            opt("stage", ArgKind::Choice(STAGES), "Stage to compare (default: latest run of each attempt)"),
+            req("from_attempt", ArgKind::Int, "Attempt to compare from"),
+            req("to_attempt", ArgKind::Int, "Attempt to compare to"),
+        ],
+        examples: &["swarm env-diff --bead-id bd-abc --stage red-queen --from-attempt 1 --to-attempt 2"],
+    },
+    CommandSpec {
+        name: "enqueue",
+        summary: "Add beads to the backlog | NEXT: claim-next",
+        args: &[
+            opt("file", ArgKind::Text, "JSON or NDJSON file of beads; - reads stdin"),
+            opt("beads", ArgKind::Text, "Inline JSON array of bead ids or objects"),
+            DRY,
+        ],
+        examples: &[
+            "swarm enqueue --file beads.ndjson",
+            "cat beads.ndjson | swarm enqueue --file -",
+            "swarm enqueue --beads '[\"bd-abc\", {\"id\": \"bd-def\", \"priority\": \"p1\", \"required_capabilities\": [\"gpu\"]}]'",
+        ],
+    },
+    CommandSpec {
+        name: "sync-backlog",
+        summary: "Reconcile the backlog with br list | NEXT: claim-next",
+        args: &[
+            opt("rounds", ArgKind::Int, "Sync passes to run (default 1)"),
+            opt("interval_secs", ArgKind::Int, "Seconds between passes (default 60)"),
+            DRY,
+        ],
+        examples: &[
+            "swarm sync-backlog",
+            "swarm sync-backlog --rounds 60 --interval_secs 30",
+        ],
+    },
+    CommandSpec {
+        name: "backlog",
+        summary: "Preview, reprioritize or remove pending beads | NEXT: status",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(BACKLOG_ACTIONS),
+                "preview | set-priority | bump | remove (also accepted positionally)",
+            ),
+            opt(
+                "bead_id",
+                ArgKind::Text,
+                "Comma-separated beads (set-priority, remove)",
+            ),
+            opt("label", ArgKind::Text, "Beads carrying this label (bump, remove)"),
+            opt("priority", ArgKind::Text, "set-priority: p0-p3"),
+            opt("to", ArgKind::Text, "bump: p0-p3"),
+            opt("limit", ArgKind::Int, "preview: beads to list (default all)"),
+            DRY,
+        ],
+        examples: &[
+            "swarm backlog preview --limit 20",
+            "swarm backlog set-priority --bead-id bd-abc --priority p1",
+            "swarm backlog bump --label security --to p0",
+            "swarm backlog remove --bead-id bd-abc,bd-def",
+        ],
+    },
+    CommandSpec {
+        name: "sync",
+        summary: "Repair claims that disagree with br | NEXT: status",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(SYNC_ACTIONS),
+                "repair (also accepted positionally)",
+            ),
+            DRY,
+        ],
+        examples: &["swarm sync repair --dry", "swarm sync repair"],
+    },
+    CommandSpec {
+        name: "events",
+        summary: "Upgrade stored execution events to the latest schema | NEXT: monitor",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(EVENTS_ACTIONS),
+                "migrate (also accepted positionally)",
+            ),
+            DRY,
+        ],
+        examples: &["swarm events migrate --dry", "swarm events migrate"],
+    },
+    CommandSpec {
+        name: "chaos",
+        summary: "Show fault injection settings | NEXT: status",
+        args: &[req(
+            "action",
+            ArgKind::Choice(CHAOS_ACTIONS),
+            "status (also accepted positionally)",
+        )],
+        examples: &["swarm chaos status"],
+    },
+    CommandSpec {
+        name: "config",
+        summary: "Read or change swarm settings | NEXT: state shows the config",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(CONFIG_ACTIONS),
+                "get | set (also accepted positionally)",
+            ),
+            opt("key", ArgKind::Choice(CONFIG_KEYS), "Setting to read or change (also positional)"),
+            opt("value", ArgKind::Text, "set: new value for key (also positional)"),
+            opt("max_agents", ArgKind::Int, "set: agents the swarm runs, 1-100"),
+            opt("max_implementation_attempts", ArgKind::Int, "set: implement retries, 1-20"),
+            opt("claim_label", ArgKind::Text, "set: label beads are claimed under"),
+            opt("swarm_status", ArgKind::Choice(SWARM_STATUSES), "set: swarm status"),
+            opt("lease_ttl_ms", ArgKind::Int, "set: claim lease length, 10000-86400000"),
+            opt("heartbeat_grace_ms", ArgKind::Int, "set: grace past a lapsed lease, 0-3600000"),
+            opt(
+                "recovery_scan_interval_ms",
+                ArgKind::Int,
+                "set: how often a session reruns recovery, 1000-3600000",
+            ),
+            DRY,
+        ],
+        examples: &[
+            "swarm config get",
+            "swarm config set max_agents 8",
+            "swarm config set lease_ttl_ms=120000 heartbeat_grace_ms=30000",
+        ],
+    },
+    CommandSpec {
+        name: "labels",
+        summary: "Show or set agents' claim labels | NEXT: claim-next for the agent",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(LABELS_ACTIONS),
+                "get | set (also accepted positionally)",
+            ),
+            opt("agent_id", ArgKind::Int, "Agent to show or change (required for set)"),
+            opt(
+                "labels",
+                ArgKind::Text,
+                "set: comma-separated labels; empty clears them",
+            ),
+            DRY,
+        ],
+        examples: &[
+            "swarm labels get",
+            "swarm labels set --agent-id 3 --labels docs,infra",
+        ],
+    },
+    CommandSpec {
+        name: "jobs",
+        summary: "Scheduled jobs and their last runs | NEXT: serve to run them on schedule",
+        args: &[
+            opt(
+                "action",
+                ArgKind::Choice(JOBS_ACTIONS),
+                "list (default) | run-now | disable | enable (also accepted positionally)",
+            ),
+            opt(
+                "job",
+                ArgKind::Choice(SCHEDULED_JOBS),
+                "Job to run, disable or enable (required unless listing)",
+            ),
+            DRY,
+        ],
+        examples: &[
+            "swarm jobs list",
+            "swarm jobs run-now --job gc",
+            "swarm jobs disable --job sync-backlog",
+        ],
+    },
+    CommandSpec {
+        name: "serve",
+        summary: "Run scheduled jobs as their cron comes due | NEXT: jobs list or doctor",
+        args: &[
+            opt(
+                "budget_ms",
+                ArgKind::Int,
+                "Stop after this many ms (default: run until the session ends)",
+            ),
+            DRY,
+        ],
+        examples: &["swarm serve", "swarm serve --budget-ms 3600000 --dry"],
+    },
+    CommandSpec {
+        name: "profile",
+        summary: "Rank where this process spends its time (needs the profiling feature) | NEXT: top or db-health",
+        args: &[
+            opt(
+                "duration_ms",
+                ArgKind::Int,
+                "Sampling window in ms, 1-60000 (default: 5000)",
+            ),
+            DRY,
+        ],
+        examples: &["swarm profile", "swarm profile --duration-ms 10000"],
+    },
+    CommandSpec {
+        name: "perf",
+        summary: "Find queries whose p95 latency regressed since a point in time | NEXT: db-health",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(PERF_ACTIONS),
+                "compare (also accepted positionally)",
+            ),
+            req(
+                "since",
+                ArgKind::Timestamp,
+                "Start of the window to check, e.g. the upgrade time",
+            ),
+            opt("until", ArgKind::Timestamp, "End of the window (default: now)"),
+            opt(
+                "threshold_pct",
+                ArgKind::Int,
+                "p95 growth that counts as a regression (default 50)",
+            ),
+            opt(
+                "min_samples",
+                ArgKind::Int,
+                "Fewest samples per side to compare a query (default 20)",
+            ),
+            opt(
+                "baseline_days",
+                ArgKind::Int,
+                "Days before since to use as the baseline, 1-365 (default 7)",
+            ),
+            DRY,
+        ],
+        examples: &[
+            "swarm perf compare --since 2026-03-01T12:00:00Z",
+            "swarm perf compare --since 2026-03-01T12:00:00Z --threshold-pct 25 --baseline-days 14",
+        ],
+    },
+    CommandSpec {
+        name: "maintenance",
+        summary: "Partition event and artifact tables by month | NEXT: jobs run-now --job partitions",
+        args: &[
+            req(
+                "target",
+                ArgKind::Choice(MAINTENANCE_TARGETS),
+                "partitions (also accepted positionally)",
+            ),
+            opt(
+                "action",
+                ArgKind::Choice(PARTITION_ACTIONS),
+                "list (default) | enable | create | detach | drop (also accepted positionally)",
+            ),
+            opt(
+                "table",
+                ArgKind::Choice(PARTITIONED_TABLES),
+                "Table to enable, list or create for (required by enable)",
+            ),
+            opt(
+                "partition",
+                ArgKind::Text,
+                "Partition to detach or drop, e.g. execution_events_p2026_01",
+            ),
+            opt(
+                "ahead",
+                ArgKind::Int,
+                "Months after the current one to create, 1-24 (default 3)",
+            ),
+            DRY,
+        ],
+        examples: &[
+            "swarm maintenance partitions list",
+            "swarm maintenance partitions enable --table execution_events",
+            "swarm maintenance partitions detach --partition execution_events_p2026_01",
+            "swarm maintenance partitions drop --partition execution_events_p2026_01",
+        ],
+    },
+    CommandSpec {
+        name: "notify",
+        summary: "Send a test notification | NEXT: monitor --view alerts",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(NOTIFY_ACTIONS),
+                "test (also accepted positionally)",
+            ),
+            opt("notifier", ArgKind::Text, "Notifier to send to; default: the routed ones"),
+            opt("event", ArgKind::Text, "Event to route as (default notify_test)"),
+            opt(
+                "severity",
+                ArgKind::Choice(NOTIFICATION_SEVERITIES),
+                "Severity to route at (default info)",
+            ),
+            DRY,
+        ],
+        examples: &[
+            "swarm notify test --notifier ops",
+            "swarm notify test --event alert_fired --severity critical --dry",
+        ],
+    },
+    CommandSpec {
+        name: "bead",
+        summary: "Snapshot or restore a bead's execution state | NEXT: restore the snapshot elsewhere",
+        args: &[
+            req(
+                "action",
+                ArgKind::Choice(BEAD_ACTIONS),
+                "snapshot or restore (also accepted positionally)",
+            ),
+            opt("bead_id", ArgKind::Text, "Bead to snapshot; checked against the snapshot on restore"),
+            opt("agent_id", ArgKind::Int, "Agent to hand the restored claim to"),
+            opt("snapshot", ArgKind::Text, "Inline snapshot JSON for restore"),
+            opt("file", ArgKind::Text, "Snapshot file to write or read"),
+            DRY,
+        ],
+        examples: &[
+            "swarm bead snapshot --bead-id bd-abc --file bd-abc.json",
+            "swarm bead restore --file bd-abc.json",
+            "swarm bead restore --file bd-abc.json --agent-id 3",
+        ],
+    },
+    CommandSpec {
+        name: "resume",
+        summary: "Resumable beads | NEXT: resume-context for details",
+        args: &[],
+        examples: &["swarm resume"],
+    },
+    CommandSpec {
+        name: "resume-context",
+        summary: "Deep context | NEXT: continue from current_stage",
+        args: &[
+            opt("bead_id", ArgKind::Text, "Bead to reconstruct"),
+            opt("max_bytes", ArgKind::Int, "Size budget per context in bytes"),
+            opt("max_tokens", ArgKind::Int, "Size budget per context in tokens (~4 bytes each)"),
+        ],
+        examples: &[
+            "swarm resume-context --bead-id bd-abc",
+            "swarm resume-context --bead-id bd-abc --max-tokens 8000",
+        ],
+    },
+    CommandSpec {
+        name: "context",
+        summary: "Full agent context | USE: paste into the agent's LLM session",
+        args: &[
+            req("bead_id", ArgKind::Text, "Bead to assemble context for"),
+            opt("skill", ArgKind::Text, "Skill prompt to include (default: current stage)"),
+            opt("max_bytes", ArgKind::Int, "Size budget for resume state in bytes"),
+            opt("max_tokens", ArgKind::Int, "Size budget for resume state in tokens"),
+        ],
+        examples: &[
+            "swarm context --bead-id bd-abc",
+            "swarm context --bead-id bd-abc --skill implement",
+        ],
+    },
+    CommandSpec {
+        name: "record-symbols",
+        summary: "Record signatures | NEXT: monitor --view drift",
+        args: &[
+            req("bead_id", ArgKind::Text, "Bead the symbols belong to"),
+            opt("symbols", ArgKind::Text, "JSON array of {name,kind,module_path,signature}"),
+            opt("file", ArgKind::Text, "JSON file holding the symbols array"),
+            opt("agent_id", ArgKind::Int, "Agent recording the symbols"),
+            opt("attempt", ArgKind::Int, "Attempt number (default: bead's current attempt)"),
+            DRY,
+        ],
+        examples: &[
+            "swarm record-symbols --bead-id bd-abc --file target/symbols.json",
+            "swarm record-symbols --bead-id bd-abc --symbols '[{\"name\":\"load\",\"kind\":\"function\",\"signature\":\"fn(&Path)\"}]'",
+        ],
+    },
+    CommandSpec {
+        name: "qa",
+        summary: "QA checks | NEXT: if fail, check artifacts for details",
+        args: &[
+            opt("target", ArgKind::Choice(QA_TARGETS), "Check suite to run"),
+            opt("id", ArgKind::Int, "Agent id"),
+            DRY,
+        ],
+        examples: &["swarm qa --target smoke"],
+    },
+    CommandSpec {
+        name: "state",
+        summary: "Full dump | USE: debugging complex issues",
+        args: &[
+            opt("limit", ArgKind::Int, "Resources to include (default 25)"),
+            opt("project", ArgKind::Text, "Return only these paths of the data"),
+        ],
+        examples: &["swarm state", "swarm state --project resources"],
+    },
+    CommandSpec {
+        name: "history",
+        summary: "Event log | NEXT: filter by bead_id if needed",
+        args: &[
+            opt("limit", ArgKind::Int, "Rows to return (max 10000)"),
+            opt("after_seq", ArgKind::Int, "Cursor from a previous next_cursor"),
+            opt("page_size", ArgKind::Int, "Alias for limit"),
+            opt("since", ArgKind::Timestamp, "Earliest command time, inclusive"),
+            opt("until", ArgKind::Timestamp, "Latest command time, inclusive"),
+        ],
+        examples: &["swarm history --limit 50"],
+    },
+    CommandSpec {
+        name: "lock",
+        summary: "Acquire lock | NEXT: proceed if ok:true",
+        args: &[
+            req("resource", ArgKind::Text, "Resource key to lock"),
+            req("agent", ArgKind::Text, "Lock holder"),
+            req("ttl_ms", ArgKind::Int, "Lock lifetime in ms"),
+            opt("wait_ms", ArgKind::Int, "Queue for the lock up to this many ms"),
+            opt("purpose", ArgKind::Text, "Why the lock is held"),
+            opt("bead_id", ArgKind::Text, "Bead the lock is held for"),
+            DRY,
+        ],
+        examples: &[
+            "swarm lock --resource repo/src/lib.rs --agent 1 --ttl-ms 30000",
+            "swarm lock --resource repo/src/lib.rs --agent 1 --ttl-ms 30000 --wait-ms 10000 --purpose refactor --bead-id bd-1",
+        ],
+    },
+    CommandSpec {
+        name: "unlock",
+        summary: "Release lock | NEXT: state to verify",
+        args: &[
+            req("resource", ArgKind::Text, "Resource key to unlock"),
+            req("agent", ArgKind::Text, "Lock holder"),
+            DRY,
+        ],
+        examples: &["swarm unlock --resource repo/src/lib.rs --agent 1"],
+    },
+    CommandSpec {
+        name: "locks",
+        summary: "List locks and waiters | NEXT: lock --wait-ms to queue",
+        args: &[],
+        examples: &["swarm locks"],
+    },
+    CommandSpec {
+        name: "agents",
+        summary: "List agents | NEXT: find idle before assign",
+        args: &[],
+        examples: &["swarm agents"],
+    },
+    CommandSpec {
+        name: "broadcast",
+        summary: "Announce to every agent until it expires | NEXT: announcements",
+        args: &[
+            req("msg", ArgKind::Text, "Message body"),
+            req("from", ArgKind::Text, "Sender id"),
+            opt("topic", ArgKind::Text, "Announcement topic (default general)"),
+            opt(
+                "ttl_secs",
+                ArgKind::Int,
+                "Seconds until it expires (default 86400, max 30 days)",
+            ),
+            DRY,
+        ],
+        examples: &[
+            "swarm broadcast --msg 'pausing claims' --from 1",
+            "swarm broadcast --msg 'main is frozen' --from 1 --topic release --ttl-secs 3600",
+        ],
+    },
+    CommandSpec {
+        name: "announcements",
+        summary: "Announcements and who acknowledged them | NEXT: ack once acted on",
+        args: &[
+            opt(
+                "action",
+                ArgKind::Choice(ANNOUNCEMENTS_ACTIONS),
+                "list (default) | ack (also accepted positionally)",
+            ),
+            opt(
+                "agent_id",
+                ArgKind::Int,
+                "Agent whose unacknowledged announcements to list or ack (required for ack)",
+            ),
+            opt("id", ArgKind::Int, "Announcement to ack; default: all unexpired ones"),
+            opt("topic", ArgKind::Text, "Only announcements on this topic"),
+            DRY,
+        ],
+        examples: &[
+            "swarm announcements",
+            "swarm announcements list --agent-id 3",
+            "swarm announcements ack --agent-id 3 --id 12",
+        ],
+    },
+    CommandSpec {
+        name: "load-profile",
+        summary: "Simulate load | NEXT: monitor during run",
+        args: &[
+            opt("agents", ArgKind::Int, "Agents per round (default 90)"),
+            opt("rounds", ArgKind::Int, "Claim rounds (default 5)"),
+            opt("timeout_ms", ArgKind::Int, "Per-claim timeout"),
+            opt("concurrency", ArgKind::Int, "Concurrent claims (max 64)"),
+            DRY,
+        ],
+        examples: &["swarm load-profile --agents 10 --concurrency 4"],
+    },
+    CommandSpec {
+        name: "spawn-prompts",
+        summary: "Generate prompts | NEXT: launch agents with files",
+        args: &[
+            opt("template", ArgKind::Text, "Template file"),
+            opt("out_dir", ArgKind::Text, "Directory for generated prompts"),
+            opt("count", ArgKind::Int, "Prompts to generate"),
+            opt("vars", ArgKind::Text, "Extra template variables as a JSON object"),
+            DRY,
+        ],
+        examples: &[
+            "swarm spawn-prompts --count 12",
+            "swarm spawn-prompts --vars '{\"bead_id\":\"bd-abc123\"}'",
+        ],
+    },
+    CommandSpec {
+        name: "prompt",
+        summary: "Get, render, or add/update/list stored prompts | NEXT: use for agent config",
+        args: &[
+            opt(
+                "action",
+                ArgKind::Choice(PROMPT_ACTIONS),
+                "get (default), add, update, list, or render",
+            ),
+            opt("skill", ArgKind::Text, "Skill prompt to return or store"),
+            opt("body", ArgKind::Text, "Prompt text for add/update"),
+            opt("file", ArgKind::Text, "File holding the prompt text for add/update"),
+            opt("template", ArgKind::Text, "Template file to render"),
+            opt("vars", ArgKind::Text, "Template variables as a JSON object"),
+            opt("id", ArgKind::Int, "Agent id (default 1)"),
+            DRY,
+        ],
+        examples: &[
+            "swarm prompt --id 3",
+            "swarm prompt --skill rust-contract",
+            "swarm prompt update --skill rust-contract --file prompts/rust-contract.md",
+            "swarm prompt list",
+            "swarm prompt render --id 2 --vars '{\"bead_id\":\"bd-abc123\"}'",
+        ],
+    },
+    CommandSpec {
+        name: "batch",
+        summary: "Multi-command | NOTE: use ops key, stops on first fail",
+        args: &[DRY],
+        examples: &[
+            "echo '{\"cmd\":\"batch\",\"ops\":[{\"cmd\":\"doctor\"},{\"cmd\":\"status\"}]}' | swarm",
+        ],
+    },
+    CommandSpec {
+        name: "help",
+        summary: "This help | SEE: examples for patterns",
+        args: &[],
+        examples: &["swarm help", "swarm --explain register"],
+    },
+];
+
+/// Protocol command names, in registry order.
+pub fn command_names() -> impl Iterator<Item = &'static str> {
+    COMMANDS.iter().map(|spec| spec.name)
+}
+
+#[must_use]
+pub fn find_command(name: &str) -> Option<&'static CommandSpec> {
+    let name = if name == "?" { "help" } else { name };
+    COMMANDS.iter().find(|spec| spec.name == name)
+}
+
+/// Data for `swarm --help`, built from [`COMMANDS`].
+#[must_use]
+pub fn help_data(version: &str) -> Value {
+    let cmds = COMMANDS
+        .iter()
+        .map(|spec| json!([spec.name, spec.summary]))
+        .collect::<Vec<_>>();
+    json!({
+        "n": "swarm",
+        "desc": "PostgreSQL-based agent swarm coordination",
+        "v": version,
+        "proto": "v1",
+        "fmt": "jsonl",
+        "usage": "echo '{\"cmd\":\"<cmd>\"}' | swarm",
+        "explain": "swarm --explain <cmd>",
+        "completions": "swarm completions bash|zsh|fish",
+        "repl": "swarm repl",
+        "cmds": cmds,
+        "workflows": {
+            "fresh_start": ["doctor", "init", "doctor", "status"],
+            "single_agent": ["claim-next", "agent --id N", "monitor --view progress"],
+            "parallel_launch": ["register --count 12", "spawn-prompts --count 12"],
+            "recovery": ["resume", "resume-context --bead-id X"],
+            "debug": ["status", "history", "artifacts --bead-id X"]
+        },
+        "examples": [
+            {"desc": "Quick start", "cmd": "echo '{\"cmd\":\"init\"}' | swarm"},
+            {"desc": "Health check", "cmd": "echo '{\"cmd\":\"doctor\"}' | swarm"},
+            {"desc": "Assign bead", "cmd": "echo '{\"cmd\":\"assign\",\"bead_id\":\"bd-abc123\",\"agent_id\":1}' | swarm"},
+            {"desc": "Dry run", "cmd": "echo '{\"cmd\":\"agent\",\"id\":1,\"dry\":true}' | swarm"},
+            {"desc": "Batch (use ops)", "cmd": "echo '{\"cmd\":\"batch\",\"ops\":[{\"cmd\":\"doctor\"},{\"cmd\":\"status\"}]}' | swarm"},
+            {"desc": "Monitor progress", "cmd": "echo '{\"cmd\":\"monitor\",\"view\":\"progress\"}' | swarm"},
+            {"desc": "Get artifacts", "cmd": "echo '{\"cmd\":\"artifacts\",\"bead_id\":\"bd-abc\"}' | swarm"}
+        ],
+        "batch_input": {
+            "required": "ops",
+            "not": "cmds",
+            "example": "echo '{\"cmd\":\"batch\",\"ops\":[{\"cmd\":\"doctor\"},{\"cmd\":\"status\"}]}' | swarm"
+        },
+        "resp": {
+            "ok": "bool - success",
+            "d": "object - data",
+            "err": "object - error",
+            "t": "number - timestamp",
+            "state": "object - current state"
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_command, help_data, CommandSpec, COMMANDS};
+
+    #[test]
+    fn given_question_mark_when_looking_up_command_then_help_spec_is_returned() {
+        assert_eq!(find_command("?").map(|spec| spec.name), Some("help"));
+        assert!(find_command("definitely-not-a-command").is_none());
+    }
+
+    #[test]
+    fn given_register_spec_when_explaining_then_flags_and_examples_are_listed() {
+        let explained = find_command("register").map(CommandSpec::explain);
+
+        let explained = explained.unwrap_or_default();
+        assert_eq!(explained["args"][0]["flag"], "--count");
+        assert_eq!(explained["args"][0]["type"], "int");
+        assert_eq!(explained["examples"][0], "swarm register --count 12");
+    }
+
+    #[test]
+    fn given_registry_when_building_help_then_every_command_is_listed_once() {
+        let help = help_data("0.0.0");
+
+        assert_eq!(help["cmds"].as_array().map(Vec::len), Some(COMMANDS.len()));
+    }
+}
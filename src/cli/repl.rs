@@ -0,0 +1,351 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![forbid(unsafe_code)]
+
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::mpsc as std_mpsc;
+use std::thread::JoinHandle;
+
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::protocol_runtime;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use super::completions::completion_script;
+use super::output::render_human;
+use super::registry::{find_command, help_data, ArgKind, ArgSpec};
+use super::{
+    cli_command_to_request, parse_cli_args, parse_tenant, suggest_commands, CliAction, CliError,
+};
+
+const PROMPT: &str = "swarm> ";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// One line of REPL input after shorthand expansion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplInput {
+    Empty,
+    Exit,
+    Json(String),
+    Args(Vec<String>),
+}
+
+enum Prompted {
+    Line(String),
+    Interrupted,
+    Eof,
+    Failed(String),
+}
+
+/// Runs `swarm repl` until `exit`, `quit`, or Ctrl-D. Returns the process exit code.
+pub async fn run_repl() -> i32 {
+    protocol_runtime::enable_session_pool_reuse();
+    let color = std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let (mut lines, ready, reader) = spawn_line_reader(history_path());
+
+    let exit_code = loop {
+        let line = match lines.recv().await {
+            Some(Prompted::Line(line)) => line,
+            Some(Prompted::Interrupted) => {
+                eprintln!("(use exit or Ctrl-D to quit)");
+                let _ = ready.send(());
+                continue;
+            }
+            Some(Prompted::Eof) | None => break 0,
+            Some(Prompted::Failed(error)) => {
+                eprintln!("Error: {error}");
+                break 1;
+            }
+        };
+
+        match expand_shorthand(&line) {
+            Ok(ReplInput::Empty) => {}
+            Ok(ReplInput::Exit) => break 0,
+            Ok(ReplInput::Json(request)) => {
+                let cmd = serde_json::from_str::<Value>(&request)
+                    .ok()
+                    .and_then(|value| value["cmd"].as_str().map(str::to_string))
+                    .unwrap_or_default();
+                execute_and_print(&cmd, &request, color).await;
+            }
+            Ok(ReplInput::Args(args)) => run_args(&args, color).await,
+            Err(error) => eprintln!("Error: {error}"),
+        }
+        let _ = ready.send(());
+    };
+
+    drop(ready);
+    let _ = reader.join();
+    exit_code
+}
+
+async fn run_args(args: &[String], color: bool) {
+    let parsed =
+        parse_cli_args(args).and_then(|action| parse_tenant(args).map(|tenant| (action, tenant)));
+    let (action, tenant) = match parsed {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            if let CliError::UnknownCommand { cmd } = &error {
+                if let Some(suggestion) = suggest_commands(cmd).first() {
+                    eprintln!("Did you mean: {suggestion}?");
+                }
+            }
+            return;
+        }
+    };
+    match action {
+        CliAction::Command(command) => {
+            let cmd = args.first().map_or("", String::as_str);
+            let request = cli_command_to_request(command, tenant.as_deref());
+            execute_and_print(cmd, &request, color).await;
+        }
+        CliAction::Explain(spec) => print_pretty(&spec.explain()),
+        CliAction::Completions(shell) => print!("{}", completion_script(shell)),
+        CliAction::ShowHelp => print_pretty(&help_data(VERSION)),
+        CliAction::ShowVersion => println!("swarm {VERSION}"),
+        CliAction::RunProtocol | CliAction::Repl => {
+            eprintln!("Already in the REPL; type exit or press Ctrl-D to leave");
+        }
+    }
+}
+
+async fn execute_and_print(cmd: &str, request: &str, color: bool) {
+    let envelope = protocol_runtime::execute_protocol_line(request).await;
+    match render_human(cmd, &envelope, false, color) {
+        Some(rendered) => println!("{rendered}"),
+        None => print_envelope(&envelope),
+    }
+}
+
+fn print_envelope(envelope: &ProtocolEnvelope) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(envelope).unwrap_or_default()
+    );
+}
+
+fn print_pretty(value: &Value) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).unwrap_or_default()
+    );
+}
+
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("SWARM_REPL_HISTORY")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".swarm_history")))
+}
+
+/// Owns the line editor on a dedicated thread and hands lines over one at a
+/// time; the next prompt is only drawn once `ready` is signalled, so command
+/// output never interleaves with the prompt.
+fn spawn_line_reader(
+    history: Option<PathBuf>,
+) -> (
+    mpsc::UnboundedReceiver<Prompted>,
+    std_mpsc::Sender<()>,
+    JoinHandle<()>,
+) {
+    let (lines_tx, lines_rx) = mpsc::unbounded_channel();
+    let (ready_tx, ready_rx) = std_mpsc::channel::<()>();
+
+    let handle = std::thread::spawn(move || {
+        let mut editor = match DefaultEditor::new() {
+            Ok(editor) => editor,
+            Err(error) => {
+                let _ = lines_tx.send(Prompted::Failed(error.to_string()));
+                return;
+            }
+        };
+        if let Some(path) = history.as_ref() {
+            let _ = editor.load_history(path);
+        }
+
+        loop {
+            let prompted = match editor.readline(PROMPT) {
+                Ok(line) => {
+                    if !line.trim().is_empty() {
+                        let _ = editor.add_history_entry(line.as_str());
+                    }
+                    Prompted::Line(line)
+                }
+                Err(ReadlineError::Interrupted) => Prompted::Interrupted,
+                Err(ReadlineError::Eof) => Prompted::Eof,
+                Err(error) => Prompted::Failed(error.to_string()),
+            };
+            let last = matches!(prompted, Prompted::Eof | Prompted::Failed(_));
+            if lines_tx.send(prompted).is_err() || last || ready_rx.recv().is_err() {
+                break;
+            }
+        }
+
+        if let Some(path) = history.as_ref() {
+            let _ = editor.save_history(path);
+        }
+    });
+
+    (lines_rx, ready_tx, handle)
+}
+
+/// Turns a REPL line into CLI arguments. Bare words after the command fill
+/// its arguments in registry order, required first, so `assign b-123 2`
+/// becomes `assign --bead-id b-123 --agent-id 2`. Lines starting with `{`
+/// are sent to the protocol verbatim.
+///
+/// # Errors
+/// Returns a message for unbalanced quotes or more bare words than the
+/// command has arguments.
+pub fn expand_shorthand(line: &str) -> Result<ReplInput, String> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return Ok(ReplInput::Empty);
+    }
+    if trimmed.starts_with('{') {
+        return Ok(ReplInput::Json(trimmed.to_string()));
+    }
+
+    let tokens = split_words(trimmed)?;
+    let Some((cmd, rest)) = tokens.split_first() else {
+        return Ok(ReplInput::Empty);
+    };
+    if matches!(cmd.as_str(), "exit" | "quit") {
+        return Ok(ReplInput::Exit);
+    }
+    let Some(spec) = find_command(cmd) else {
+        return Ok(ReplInput::Args(tokens));
+    };
+
+    let takes_value = |flag: &str| {
+        spec.args
+            .iter()
+            .find(|arg| arg.flag() == flag)
+            .is_some_and(|arg| arg.kind != ArgKind::Flag)
+    };
+
+    let mut explicit = Vec::new();
+    let mut positional = Vec::new();
+    let mut remaining = rest.iter();
+    while let Some(token) = remaining.next() {
+        if token.starts_with("--") {
+            explicit.push(token.clone());
+            if takes_value(token) {
+                if let Some(value) = remaining.next() {
+                    explicit.push(value.clone());
+                }
+            }
+        } else {
+            positional.push(token.clone());
+        }
+    }
+
+    let mut slots = spec
+        .args
+        .iter()
+        .filter(|arg| arg.required)
+        .chain(spec.args.iter().filter(|arg| !arg.required))
+        .filter(|arg| arg.kind != ArgKind::Flag)
+        .filter(|arg| !explicit.contains(&arg.flag()))
+        .map(ArgSpec::flag);
+
+    let mut args = vec![cmd.clone()];
+    for value in positional {
+        let flag = slots
+            .next()
+            .ok_or_else(|| format!("too many arguments for {}: {value}", spec.name))?;
+        args.push(flag);
+        args.push(value);
+    }
+    args.extend(explicit);
+    Ok(ReplInput::Args(args))
+}
+
+fn split_words(line: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut in_word = false;
+
+    for ch in line.chars() {
+        match (quote, ch) {
+            (Some(open), _) if ch == open => quote = None,
+            (Some(_), _) => current.push(ch),
+            (None, '\'' | '"') => {
+                quote = Some(ch);
+                in_word = true;
+            }
+            (None, _) if ch.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, _) => {
+                current.push(ch);
+                in_word = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err("unbalanced quote".to_string());
+    }
+    if in_word {
+        words.push(current);
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_shorthand, ReplInput};
+
+    fn args(words: &[&str]) -> ReplInput {
+        ReplInput::Args(words.iter().map(ToString::to_string).collect())
+    }
+
+    #[test]
+    fn given_assign_shorthand_when_expanding_then_required_args_are_filled_in_order() {
+        assert_eq!(
+            expand_shorthand("assign b-123 2"),
+            Ok(args(&["assign", "--bead-id", "b-123", "--agent-id", "2"]))
+        );
+    }
+
+    #[test]
+    fn given_explicit_flag_when_expanding_then_bare_words_skip_that_argument() {
+        assert_eq!(
+            expand_shorthand("broadcast --from 4 'pausing claims'"),
+            Ok(args(&[
+                "broadcast",
+                "--msg",
+                "pausing claims",
+                "--from",
+                "4"
+            ]))
+        );
+    }
+
+    #[test]
+    fn given_json_or_exit_when_expanding_then_they_are_passed_through() {
+        assert_eq!(
+            expand_shorthand(" {\"cmd\":\"status\"} "),
+            Ok(ReplInput::Json("{\"cmd\":\"status\"}".to_string()))
+        );
+        assert_eq!(expand_shorthand("quit"), Ok(ReplInput::Exit));
+        assert_eq!(expand_shorthand("   "), Ok(ReplInput::Empty));
+    }
+
+    #[test]
+    fn given_too_many_words_when_expanding_then_error_names_the_command() {
+        assert!(expand_shorthand("agents now")
+            .is_err_and(|error| error.contains("too many arguments for agents")));
+    }
+}
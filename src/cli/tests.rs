@@ -7,7 +7,7 @@
 
 #[cfg(test)]
 mod bdd_tests {
-    use crate::cli::{parse_cli_args, CliAction, CliCommand};
+    use crate::cli::{parse_cli_args, CliAction, CliCommand, CliError};
 
     fn given_cli_args(args: &[&str]) -> Vec<String> {
         args.iter().map(|s| s.to_string()).collect()
@@ -21,6 +21,63 @@ mod bdd_tests {
         assert!(matches!(action, CliAction::RunProtocol));
     }
 
+    #[test]
+    fn when_only_format_flag_then_run_protocol() {
+        let args = given_cli_args(&["--format", "msgpack"]);
+        let action = parse_cli_args(&args).expect("parse");
+
+        assert!(matches!(action, CliAction::RunProtocol));
+    }
+
+    #[test]
+    fn when_only_session_flags_then_run_protocol() {
+        let args = given_cli_args(&["--record", "trace.ndjson", "--format", "msgpack"]);
+        let action = parse_cli_args(&args).expect("parse");
+
+        assert!(matches!(action, CliAction::RunProtocol));
+    }
+
+    #[test]
+    fn when_log_format_is_given_then_it_is_a_session_flag_and_validated() {
+        let args = given_cli_args(&["--log-format", "json", "--format", "msgpack"]);
+        let action = parse_cli_args(&args).expect("parse");
+
+        assert!(matches!(action, CliAction::RunProtocol));
+        assert!(
+            crate::cli::parse_log_format(&given_cli_args(&["status", "--log-format", "xml"]))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn when_profile_has_duration_then_it_maps_to_duration_ms() {
+        let args = given_cli_args(&["profile", "--duration-ms", "10000"]);
+        let action = parse_cli_args(&args).expect("parse");
+
+        assert!(matches!(
+            action,
+            CliAction::Command(CliCommand::Profile {
+                duration_ms: Some(10_000),
+                dry: None,
+            })
+        ));
+    }
+
+    #[test]
+    fn when_replay_has_positional_file_then_bead_id_is_optional() {
+        let args = given_cli_args(&["replay", "trace.ndjson", "--dry"]);
+        let action = parse_cli_args(&args).expect("parse");
+
+        assert!(matches!(
+            action,
+            CliAction::Command(CliCommand::Replay {
+                bead_id: None,
+                file: Some(_),
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn when_help_flag_then_show_help() {
         let args = given_cli_args(&["-h"]);
@@ -42,7 +99,55 @@ mod bdd_tests {
         let args = given_cli_args(&["doctor"]);
         let action = parse_cli_args(&args).expect("parse");
 
-        assert!(matches!(action, CliAction::Command(CliCommand::Doctor)));
+        assert!(matches!(
+            action,
+            CliAction::Command(CliCommand::Doctor { .. })
+        ));
+    }
+
+    #[test]
+    fn when_maintenance_partitions_positional_then_target_and_action_are_parsed() {
+        let args = given_cli_args(&[
+            "maintenance",
+            "partitions",
+            "enable",
+            "--table",
+            "execution_events",
+        ]);
+        let action = parse_cli_args(&args).expect("parse");
+
+        assert!(matches!(
+            action,
+            CliAction::Command(CliCommand::Maintenance {
+                ref target,
+                action: Some(ref partition_action),
+                table: Some(ref table),
+                ..
+            }) if target == "partitions" && partition_action == "enable" && table == "execution_events"
+        ));
+    }
+
+    #[test]
+    fn when_doctor_analyze_db_with_apply_then_both_flags_are_parsed() {
+        let args = given_cli_args(&["doctor", "--analyze-db", "--apply"]);
+        let action = parse_cli_args(&args).expect("parse");
+
+        assert!(matches!(
+            action,
+            CliAction::Command(CliCommand::Doctor {
+                analyze_db: Some(true),
+                apply: Some(true),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn when_healthz_command_then_healthz_action() {
+        let args = given_cli_args(&["healthz"]);
+        let action = parse_cli_args(&args).expect("parse");
+
+        assert!(matches!(action, CliAction::Command(CliCommand::Healthz)));
     }
 
     #[test]
@@ -50,7 +155,23 @@ mod bdd_tests {
         let args = given_cli_args(&["status"]);
         let action = parse_cli_args(&args).expect("parse");
 
-        assert!(matches!(action, CliAction::Command(CliCommand::Status)));
+        assert!(matches!(
+            action,
+            CliAction::Command(CliCommand::Status { .. })
+        ));
+    }
+
+    #[test]
+    fn when_status_command_with_project_then_project_is_passed_through() {
+        let args = given_cli_args(&["status", "--project", "beads_by_status.open"]);
+        let action = parse_cli_args(&args).expect("parse");
+
+        match action {
+            CliAction::Command(CliCommand::Status { project }) => {
+                assert_eq!(project.as_deref(), Some("beads_by_status.open"));
+            }
+            _ => panic!("Expected Status command"),
+        }
     }
 
     #[test]
@@ -119,4 +240,58 @@ mod bdd_tests {
             _ => panic!("Expected Agent command"),
         }
     }
+
+    #[test]
+    fn when_flag_not_declared_for_command_then_unknown_command_error() {
+        let args = given_cli_args(&["doctor", "--count", "3"]);
+        let result = parse_cli_args(&args);
+
+        assert!(matches!(result, Err(CliError::UnknownCommand { cmd }) if cmd == "--count"));
+    }
+
+    #[test]
+    fn when_explain_flag_then_explain_action_for_registered_command() {
+        let args = given_cli_args(&["--explain", "register"]);
+        let action = parse_cli_args(&args).expect("parse");
+
+        match action {
+            CliAction::Explain(spec) => assert_eq!(spec.name, "register"),
+            _ => panic!("Expected Explain action"),
+        }
+    }
+
+    #[test]
+    fn when_completions_shell_is_unknown_then_invalid_arg_value() {
+        let args = given_cli_args(&["completions", "powershell"]);
+        let result = parse_cli_args(&args);
+
+        assert!(matches!(result, Err(CliError::InvalidArgValue { arg, .. }) if arg == "shell"));
+    }
+
+    #[test]
+    fn when_prompt_has_positional_action_then_it_becomes_the_action() {
+        let args = given_cli_args(&[
+            "prompt",
+            "update",
+            "--skill",
+            "rust-contract",
+            "--body",
+            "Write the contract",
+        ]);
+        let action = parse_cli_args(&args).expect("parse");
+
+        match action {
+            CliAction::Command(CliCommand::Prompt {
+                action,
+                skill,
+                body,
+                ..
+            }) => {
+                assert_eq!(action.as_deref(), Some("update"));
+                assert_eq!(skill.as_deref(), Some("rust-contract"));
+                assert_eq!(body.as_deref(), Some("Write the contract"));
+            }
+            _ => panic!("Expected Prompt command"),
+        }
+    }
 }
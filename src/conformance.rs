@@ -0,0 +1,690 @@
+//! Golden-output conformance suite.
+//!
+//! [`generate`] runs one request for every protocol command against a
+//! freshly seeded database and writes each envelope, with volatile fields
+//! normalized, as a fixture; [`verify`] runs the fixtures' requests against
+//! another fresh database and reports every envelope that differs.
+//! Packagers run [`verify`] against the fixtures of the release they build
+//! to check their build answers the same way.
+//!
+//! Both need an empty swarm schema: the suite seeds it with
+//! [`SEED_AGENTS`] idle agents and [`SEED_BEADS`] pending beads, and the
+//! cases then register, claim and write in a fixed order. Requests find the
+//! database the way any protocol request does, from the environment or the
+//! `connection` args merged into each one, so those must name the database
+//! `db` points at.
+//!
+//! ```ignore
+//! let db = swarm::testsupport::isolated_db().await?;
+//! std::env::set_var("SWARM_DB_SCHEMA", db.schema());
+//! let mut connection = serde_json::Map::new();
+//! connection.insert("database_url".to_string(), db.url().into());
+//! let report = swarm::conformance::verify(&db, &connection, fixtures, &[]).await?;
+//! assert!(report.passed(), "{report:?}");
+//! ```
+
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::protocol_runtime::trace::{diff_values, MAX_TRACE_DIFFS_PER_ENTRY};
+use crate::protocol_runtime::{execute_request, ProtocolRequest};
+use crate::{RepoId, Result, SwarmDb, SwarmError};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
+
+/// Idle agents the suite seeds, numbered from 1.
+pub const SEED_AGENTS: u32 = 2;
+/// Pending beads the suite seeds, `conf-1` onwards.
+pub const SEED_BEADS: u32 = 3;
+/// Prefix of the seeded bead ids.
+pub const SEED_BEAD_PREFIX: &str = "conf";
+
+/// Keys whose values change from run to run, replaced at any depth.
+const VOLATILE_KEYS: &[&str] = &[
+    "t",
+    "ms",
+    "ts",
+    "pid",
+    "host",
+    "elapsed_ms",
+    "duration_ms",
+    "latency_ms",
+    "timing",
+    "until",
+    "remaining_ttl_ms",
+    "waited_ms",
+    "db_schema",
+];
+const VOLATILE: &str = "<volatile>";
+const TIMESTAMP: &str = "<timestamp>";
+const UUID: &str = "<uuid>";
+
+/// How much of an envelope a case pins down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compare {
+    /// The whole normalized envelope.
+    Full,
+    /// Only `ok` and the error code, for commands whose data describes the
+    /// host (installed tools, server latency) rather than the swarm.
+    Status,
+}
+
+/// One request of the suite.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConformanceCase {
+    pub name: String,
+    pub compare: Compare,
+    pub request: Value,
+}
+
+impl ConformanceCase {
+    fn new(name: &str, compare: Compare, request: Value) -> Self {
+        Self {
+            name: name.to_string(),
+            compare,
+            request,
+        }
+    }
+}
+
+/// A case and the normalized envelope it answered with, as written to disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConformanceFixture {
+    #[serde(flatten)]
+    pub case: ConformanceCase,
+    pub envelope: Value,
+}
+
+/// A case whose envelope differs from its fixture.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConformanceMismatch {
+    pub case: String,
+    pub compare: Compare,
+    /// `{path, recorded, replayed}`, as a trace replay reports them.
+    pub diffs: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConformanceReport {
+    pub total: usize,
+    pub matched: usize,
+    pub mismatches: Vec<ConformanceMismatch>,
+}
+
+impl ConformanceReport {
+    #[must_use]
+    pub const fn passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// The suite, in the order it runs: reads of the seeded swarm, writes that
+/// later reads observe, and dry runs of commands that would start agents,
+/// touch `br`/`jj`/docker, or send anything off the host.
+#[must_use]
+#[allow(clippy::too_many_lines)]
+pub fn default_cases() -> Vec<ConformanceCase> {
+    use Compare::{Full, Status};
+    let case = ConformanceCase::new;
+    vec![
+        case("help", Full, json!({"cmd": "?", "short": true})),
+        case("healthz", Status, json!({"cmd": "healthz"})),
+        case("doctor", Status, json!({"cmd": "doctor"})),
+        case(
+            "db-health",
+            Status,
+            json!({"cmd": "db-health", "samples": 1}),
+        ),
+        case("invariants", Full, json!({"cmd": "invariants"})),
+        case("status", Full, json!({"cmd": "status"})),
+        case("state", Full, json!({"cmd": "state", "limit": 5})),
+        case("agents", Full, json!({"cmd": "agents"})),
+        case("top", Full, json!({"cmd": "top", "window_mins": 60})),
+        case(
+            "forecast",
+            Full,
+            json!({"cmd": "forecast", "window_hours": 24}),
+        ),
+        case(
+            "monitor",
+            Full,
+            json!({"cmd": "monitor", "view": "progress"}),
+        ),
+        case(
+            "backlog-preview",
+            Full,
+            json!({"cmd": "backlog", "action": "preview", "limit": 10}),
+        ),
+        case(
+            "backlog-set-priority",
+            Full,
+            json!({"cmd": "backlog", "action": "set-priority", "bead_id": "conf-3", "priority": "p1"}),
+        ),
+        case(
+            "config-get",
+            Full,
+            json!({"cmd": "config", "action": "get"}),
+        ),
+        case(
+            "config-set",
+            Full,
+            json!({"cmd": "config", "action": "set", "max_agents": 4, "dry": true}),
+        ),
+        case(
+            "labels-set",
+            Full,
+            json!({"cmd": "labels", "action": "set", "agent_id": 1, "labels": ["rust"]}),
+        ),
+        case(
+            "labels-get",
+            Full,
+            json!({"cmd": "labels", "action": "get", "agent_id": 1}),
+        ),
+        case(
+            "register",
+            Full,
+            json!({"cmd": "register", "count": 3, "dry": true}),
+        ),
+        case(
+            "lock",
+            Full,
+            json!({"cmd": "lock", "resource": "repo/main", "agent": "agent-1", "ttl_ms": 60_000}),
+        ),
+        case("locks", Full, json!({"cmd": "locks"})),
+        case(
+            "unlock",
+            Full,
+            json!({"cmd": "unlock", "resource": "repo/main", "agent": "agent-1"}),
+        ),
+        case(
+            "broadcast",
+            Full,
+            json!({"cmd": "broadcast", "msg": "conformance", "from": "suite", "topic": "general"}),
+        ),
+        case(
+            "announcements",
+            Full,
+            json!({"cmd": "announcements", "action": "list"}),
+        ),
+        case(
+            "claim-next",
+            Full,
+            json!({"cmd": "claim-next", "agent_id": 1}),
+        ),
+        case(
+            "accept-claim",
+            Full,
+            json!({"cmd": "accept-claim", "agent_id": 1, "bead_id": "conf-1", "dry": true}),
+        ),
+        case(
+            "reject-claim",
+            Full,
+            json!({"cmd": "reject-claim", "agent_id": 1, "bead_id": "conf-1", "reason": "suite", "dry": true}),
+        ),
+        case(
+            "assign",
+            Full,
+            json!({"cmd": "assign", "bead_id": "conf-2", "agent_id": 2, "dry": true}),
+        ),
+        case(
+            "kv-set",
+            Full,
+            json!({"cmd": "kv", "action": "set", "agent_id": 1, "bead_id": "conf-1", "key": "k", "value": "v"}),
+        ),
+        case(
+            "kv-get",
+            Full,
+            json!({"cmd": "kv", "action": "get", "agent_id": 1, "bead_id": "conf-1", "key": "k"}),
+        ),
+        case(
+            "blackboard-append",
+            Full,
+            json!({"cmd": "blackboard", "action": "append", "bead_id": "conf-1", "agent_id": 1, "section": "notes", "content": "seeded"}),
+        ),
+        case(
+            "blackboard-read",
+            Full,
+            json!({"cmd": "blackboard", "action": "read", "bead_id": "conf-1"}),
+        ),
+        case(
+            "session",
+            Full,
+            json!({"cmd": "session", "action": "show", "agent_id": 1}),
+        ),
+        case(
+            "record-symbols",
+            Full,
+            json!({"cmd": "record-symbols", "bead_id": "conf-1", "agent_id": 1, "attempt": 1, "symbols": ["swarm::conformance"], "dry": true}),
+        ),
+        case(
+            "context",
+            Full,
+            json!({"cmd": "context", "bead_id": "conf-1"}),
+        ),
+        case(
+            "resume-context",
+            Full,
+            json!({"cmd": "resume-context", "bead_id": "conf-1"}),
+        ),
+        case("resume", Full, json!({"cmd": "resume"})),
+        case(
+            "artifacts",
+            Full,
+            json!({"cmd": "artifacts", "bead_id": "conf-1"}),
+        ),
+        case(
+            "artifact",
+            Full,
+            json!({"cmd": "artifact", "action": "put", "bead_id": "conf-1", "stage": "implement", "type": "implementation_code", "file": "src/lib.rs", "dry": true}),
+        ),
+        case(
+            "diff",
+            Full,
+            json!({"cmd": "diff", "action": "get", "bead_id": "conf-1"}),
+        ),
+        case(
+            "replay",
+            Full,
+            json!({"cmd": "replay", "bead_id": "conf-1"}),
+        ),
+        case(
+            "explain-transition",
+            Full,
+            json!({"cmd": "explain-transition", "stage": "qa-enforcer", "result": "failed", "attempt": 1, "max_attempts": 3}),
+        ),
+        case(
+            "verify",
+            Full,
+            json!({"cmd": "verify", "bead_id": "conf-1"}),
+        ),
+        case(
+            "attest",
+            Full,
+            json!({"cmd": "attest", "bead_id": "conf-1"}),
+        ),
+        case(
+            "env-diff",
+            Full,
+            json!({"cmd": "env-diff", "bead_id": "conf-1", "stage": "implement"}),
+        ),
+        case(
+            "bead",
+            Full,
+            json!({"cmd": "bead", "action": "snapshot", "bead_id": "conf-1", "dry": true}),
+        ),
+        case(
+            "review",
+            Full,
+            json!({"cmd": "review", "action": "list", "bead_id": "conf-1"}),
+        ),
+        case(
+            "approve",
+            Full,
+            json!({"cmd": "approve", "bead_id": "conf-1", "token": "suite", "approver": "suite", "dry": true}),
+        ),
+        case(
+            "reissue-approval",
+            Full,
+            json!({"cmd": "reissue-approval", "bead_id": "conf-1", "gate": "land", "dry": true}),
+        ),
+        case(
+            "report-usage",
+            Full,
+            json!({"cmd": "report-usage", "agent_id": 1, "bead_id": "conf-1", "stage": "implement", "model": "suite", "prompt_tokens": 10, "completion_tokens": 5}),
+        ),
+        case("costs", Full, json!({"cmd": "costs", "bead_id": "conf-1"})),
+        case(
+            "report-coverage",
+            Full,
+            json!({"cmd": "report-coverage", "agent_id": 1, "bead_id": "conf-1", "stage": "qa-enforcer", "path": "lcov.info", "dry": true}),
+        ),
+        case("history", Full, json!({"cmd": "history", "limit": 20})),
+        case(
+            "events",
+            Full,
+            json!({"cmd": "events", "action": "migrate", "dry": true}),
+        ),
+        case("chaos", Full, json!({"cmd": "chaos", "action": "status"})),
+        case("jobs", Full, json!({"cmd": "jobs", "action": "list"})),
+        case("serve", Full, json!({"cmd": "serve", "dry": true})),
+        case(
+            "notify",
+            Full,
+            json!({"cmd": "notify", "action": "test", "dry": true}),
+        ),
+        case(
+            "workspace",
+            Full,
+            json!({"cmd": "workspace", "action": "show", "agent_id": 1}),
+        ),
+        case(
+            "takeover",
+            Full,
+            json!({"cmd": "takeover", "bead_id": "conf-1", "to_agent": 2, "dry": true}),
+        ),
+        case(
+            "cancel",
+            Full,
+            json!({"cmd": "cancel", "bead_id": "conf-1", "reason": "suite", "dry": true}),
+        ),
+        case(
+            "quarantine",
+            Full,
+            json!({"cmd": "quarantine", "agent_id": 2, "reason": "suite", "dry": true}),
+        ),
+        case(
+            "unquarantine",
+            Full,
+            json!({"cmd": "unquarantine", "agent_id": 2, "dry": true}),
+        ),
+        case(
+            "release",
+            Full,
+            json!({"cmd": "release", "agent_id": 1, "dry": true}),
+        ),
+        case("recover", Full, json!({"cmd": "recover", "dry": true})),
+        case(
+            "undelete",
+            Full,
+            json!({"cmd": "undelete", "table": "backlog", "key": "conf-1", "dry": true}),
+        ),
+        case("tenant", Status, json!({"cmd": "tenant", "action": "list"})),
+        case("next", Full, json!({"cmd": "next", "dry": true})),
+        case(
+            "enqueue",
+            Full,
+            json!({"cmd": "enqueue", "beads": [{"id": "conf-9"}], "dry": true}),
+        ),
+        case(
+            "sync-backlog",
+            Full,
+            json!({"cmd": "sync-backlog", "dry": true}),
+        ),
+        case(
+            "sync",
+            Full,
+            json!({"cmd": "sync", "action": "repair", "dry": true}),
+        ),
+        case("agent", Full, json!({"cmd": "agent", "id": 1, "dry": true})),
+        case("run", Full, json!({"cmd": "run", "id": 1, "dry": true})),
+        case("run-all", Full, json!({"cmd": "run-all", "dry": true})),
+        case(
+            "run-once",
+            Full,
+            json!({"cmd": "run-once", "id": 1, "dry": true}),
+        ),
+        case(
+            "qa",
+            Full,
+            json!({"cmd": "qa", "target": "local", "dry": true}),
+        ),
+        case("smoke", Full, json!({"cmd": "smoke", "id": 1, "dry": true})),
+        case(
+            "land",
+            Full,
+            json!({"cmd": "land", "bead_id": "conf-1", "agent_id": 1, "dry": true}),
+        ),
+        case("prompt", Full, json!({"cmd": "prompt", "action": "list"})),
+        case(
+            "spawn-prompts",
+            Full,
+            json!({"cmd": "spawn-prompts", "count": 2, "dry": true}),
+        ),
+        case(
+            "load-profile",
+            Full,
+            json!({"cmd": "load-profile", "agents": 2, "rounds": 1, "dry": true}),
+        ),
+        case("init-db", Full, json!({"cmd": "init-db", "dry": true})),
+        case(
+            "init-local-db",
+            Full,
+            json!({"cmd": "init-local-db", "dry": true}),
+        ),
+        case(
+            "localdb",
+            Full,
+            json!({"cmd": "localdb", "action": "status", "dry": true}),
+        ),
+        case("init", Full, json!({"cmd": "init", "dry": true})),
+        case("bootstrap", Full, json!({"cmd": "bootstrap", "dry": true})),
+        case(
+            "batch",
+            Full,
+            json!({"cmd": "batch", "ops": [{"cmd": "agents"}, {"cmd": "locks"}]}),
+        ),
+        case("unknown-command", Full, json!({"cmd": "no-such-command"})),
+    ]
+}
+
+/// Seeds an empty swarm schema the way every suite run starts.
+///
+/// # Errors
+/// Returns an error if a seeding write fails.
+pub async fn seed(db: &SwarmDb) -> Result<()> {
+    db.seed_idle_agents(SEED_AGENTS).await?;
+    db.enqueue_backlog_batch(&RepoId::new("local"), SEED_BEAD_PREFIX, SEED_BEADS)
+        .await
+}
+
+/// Seeds `db`, runs `cases` in order and writes one fixture per case to
+/// `dir`, as `NNN-<name>.json`, replacing fixtures already there. Returns
+/// how many were written.
+///
+/// # Errors
+/// Returns an error if seeding fails, a case is not a valid request, or a
+/// fixture cannot be written.
+pub async fn generate(
+    db: &SwarmDb,
+    connection: &Map<String, Value>,
+    dir: &Path,
+    cases: Vec<ConformanceCase>,
+) -> Result<usize> {
+    tokio::fs::create_dir_all(dir).await?;
+    for stale in fixture_paths(dir).await? {
+        tokio::fs::remove_file(stale).await?;
+    }
+    seed(db).await?;
+
+    let total = cases.len();
+    for (index, case) in cases.into_iter().enumerate() {
+        let envelope = run_case(&case, connection).await?;
+        let fixture = ConformanceFixture { case, envelope };
+        let path = dir.join(format!("{:03}-{}.json", index + 1, fixture.case.name));
+        let mut text = serde_json::to_string_pretty(&fixture)?;
+        text.push('\n');
+        tokio::fs::write(path, text).await?;
+    }
+    Ok(total)
+}
+
+/// Checks `db` answers the fixtures in `dir` the way they were recorded.
+///
+/// Seeds `db`, runs the fixtures' requests in file order and compares each
+/// envelope with the one the fixture holds. Object keys named in `ignore`
+/// are left out of the comparison at any depth.
+///
+/// # Errors
+/// Returns an error if seeding fails, `dir` holds no fixtures, or a fixture
+/// cannot be read or parsed.
+pub async fn verify(
+    db: &SwarmDb,
+    connection: &Map<String, Value>,
+    dir: &Path,
+    ignore: &[String],
+) -> Result<ConformanceReport> {
+    let paths = fixture_paths(dir).await?;
+    if paths.is_empty() {
+        return Err(SwarmError::ConfigError(format!(
+            "No conformance fixtures in {}",
+            dir.display()
+        )));
+    }
+    let mut fixtures = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let text = tokio::fs::read_to_string(path).await?;
+        let fixture = serde_json::from_str::<ConformanceFixture>(&text).map_err(|e| {
+            SwarmError::ConfigError(format!("Invalid fixture {}: {e}", path.display()))
+        })?;
+        fixtures.push(fixture);
+    }
+    seed(db).await?;
+
+    let mut report = ConformanceReport {
+        total: fixtures.len(),
+        ..ConformanceReport::default()
+    };
+    for fixture in fixtures {
+        let envelope = run_case(&fixture.case, connection).await?;
+        let (expected, actual) = match fixture.case.compare {
+            Compare::Full => (fixture.envelope, envelope),
+            Compare::Status => (status_of(&fixture.envelope), status_of(&envelope)),
+        };
+        let diffs = diff_values(&expected, &actual, ignore, MAX_TRACE_DIFFS_PER_ENTRY);
+        if diffs.is_empty() {
+            report.matched += 1;
+        } else {
+            report.mismatches.push(ConformanceMismatch {
+                case: fixture.case.name,
+                compare: fixture.case.compare,
+                diffs,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// The envelope `case` gets, built like the protocol loop builds it and
+/// normalized. The case name is its `rid`.
+async fn run_case(case: &ConformanceCase, connection: &Map<String, Value>) -> Result<Value> {
+    let mut request = serde_json::from_value::<ProtocolRequest>(case.request.clone())
+        .map_err(|e| SwarmError::ConfigError(format!("Invalid case {}: {e}", case.name)))?;
+    request.rid = Some(case.name.clone());
+    request.args.extend(connection.clone());
+    let rid = request.rid.clone();
+
+    let envelope = match execute_request(request).await {
+        Ok(success) => ProtocolEnvelope::success(rid, success.data)
+            .with_next(success.next)
+            .with_state(success.state),
+        Err(failure) => *failure,
+    };
+    let mut value = serde_json::to_value(envelope)?;
+    normalize(&mut value);
+    Ok(value)
+}
+
+/// Replaces what changes between runs of the same request: values of
+/// [`VOLATILE_KEYS`], `*_at` fields, and any RFC 3339 timestamp or UUID
+/// string.
+pub fn normalize(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            for (key, child) in object.iter_mut() {
+                if child.is_null() {
+                    continue;
+                }
+                if VOLATILE_KEYS.contains(&key.as_str()) {
+                    *child = Value::String(VOLATILE.to_string());
+                } else if key.ends_with("_at") && !child.is_object() && !child.is_array() {
+                    *child = Value::String(TIMESTAMP.to_string());
+                } else {
+                    normalize(child);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize),
+        Value::String(text) => {
+            if chrono::DateTime::parse_from_rfc3339(text).is_ok() {
+                *text = TIMESTAMP.to_string();
+            } else if uuid::Uuid::parse_str(text).is_ok() {
+                *text = UUID.to_string();
+            }
+        }
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+fn status_of(envelope: &Value) -> Value {
+    json!({"ok": envelope["ok"], "code": envelope.pointer("/err/code")})
+}
+
+/// `*.json` files in `dir`, sorted by name.
+async fn fixture_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    let mut paths = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn given_envelope_with_volatile_fields_when_normalizing_then_only_they_change() {
+        let mut envelope = json!({
+            "ok": true,
+            "rid": "status",
+            "t": 1_760_000_000_000_i64,
+            "ms": 12,
+            "d": {
+                "claimed_at": "2026-10-16T10:00:00Z",
+                "lease_ttl_ms": 60_000,
+                "token": "6f1c2b8e-4f7a-4a8e-9a3c-1d2e3f4a5b6c",
+                "events": [{"ts": "x", "note": "2026-10-16T10:00:00+02:00"}],
+                "finished_at": null,
+                "bead_id": "conf-1",
+            },
+        });
+
+        normalize(&mut envelope);
+
+        assert_eq!(
+            envelope,
+            json!({
+                "ok": true,
+                "rid": "status",
+                "t": VOLATILE,
+                "ms": VOLATILE,
+                "d": {
+                    "claimed_at": TIMESTAMP,
+                    "lease_ttl_ms": 60_000,
+                    "token": UUID,
+                    "events": [{"ts": VOLATILE, "note": TIMESTAMP}],
+                    "finished_at": null,
+                    "bead_id": "conf-1",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn given_default_cases_when_listed_then_names_are_unique_and_requests_parse() {
+        let cases = default_cases();
+        let names = cases
+            .iter()
+            .map(|case| case.name.as_str())
+            .collect::<HashSet<_>>();
+
+        assert_eq!(names.len(), cases.len());
+        for case in &cases {
+            assert!(
+                serde_json::from_value::<ProtocolRequest>(case.request.clone()).is_ok(),
+                "{}",
+                case.name
+            );
+        }
+    }
+}
@@ -1,63 +1,123 @@
 // BDD-style tests for Agent Lifecycle behaviors
 // Following Martin Fowler's approach: clear business language, GWT structure,
 // focus on business behaviors rather than implementation details.
+#![allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+
+#[cfg(feature = "testsupport")]
+use crate::db::SwarmDb;
+#[cfg(feature = "testsupport")]
+use crate::runtime::{RuntimeAgentStatus, RuntimeStage};
+#[cfg(feature = "testsupport")]
+use crate::testsupport::isolated_db;
+#[cfg(feature = "testsupport")]
+use crate::types::{AgentId, BeadId, RepoId, Stage, StageResult};
+
+#[cfg(feature = "testsupport")]
+async fn insert_pending_bead(db: &SwarmDb, bead_id: &BeadId) {
+    sqlx::query(
+        "INSERT INTO bead_backlog (bead_id, priority, status) VALUES ($1, 'p0', 'pending')",
+    )
+    .bind(bead_id.value())
+    .execute(db.pool())
+    .await
+    .unwrap_or_else(|e| panic!("insert bead {} failed: {}", bead_id.value(), e));
+}
+
+/// The agent's `agent_state` row as stored. Agents that are waiting, in error
+/// or done keep the bead they worked on, which `get_agent_state` rejects, so
+/// those states are read here instead.
+#[cfg(feature = "testsupport")]
+struct AgentRow {
+    status: String,
+    current_stage: Option<String>,
+    implementation_attempt: i32,
+    feedback: Option<String>,
+}
 
-use super::*;
-use crate::db::{test_db, setup_schema, reset_runtime_tables, unique_bead};
-use crate::types::{AgentId, AgentStatus, RepoId, BeadId, Stage, StageResult, SwarmStatus};
-use sqlx::PgPoolOptions;
+#[cfg(feature = "testsupport")]
+async fn agent_row(db: &SwarmDb, agent_id: &AgentId) -> AgentRow {
+    let (status, current_stage, implementation_attempt, feedback) = sqlx::query_as(
+        "SELECT status, current_stage, implementation_attempt, feedback
+         FROM agent_state
+         WHERE repo_id = $1 AND agent_id = $2",
+    )
+    .bind(agent_id.repo_id().value())
+    .bind(agent_id.number().cast_signed())
+    .fetch_one(db.pool())
+    .await
+    .unwrap_or_else(|e| panic!("agent row query failed: {e}"));
+    AgentRow {
+        status,
+        current_stage,
+        implementation_attempt,
+        feedback,
+    }
+}
 
+#[cfg(feature = "testsupport")]
 mod agent_lifecycle {
+    use super::*;
 
     mod when_registering {
+        use super::*;
 
         mod given_an_unregistered_agent {
             use super::*;
 
             #[tokio::test]
-            #[ignore = "requires DATABASE_URL or SWARM_TEST_DATABASE_URL"]
             async fn then_agent_is_created_with_idle_status() {
                 // Given
-                let db = test_db().await;
-                setup_schema(&db).await;
-                reset_runtime_tables(&db).await;
+                let db = isolated_db().await.expect("test database");
                 let agent_id = AgentId::new(RepoId::new("local"), 1);
 
                 // When
-                let was_new = db.register_agent(&agent_id).await
-                    .unwrap_or_else(|e| panic!("Failed to register agent: {}", e));
+                let was_new = db
+                    .register_agent(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("Failed to register agent: {e}"));
 
                 // Then
                 assert!(was_new, "Agent should be registered as new");
-                let state = db.get_agent_state(&agent_id).await
-                    .unwrap_or_else(|e| panic!("Failed to get agent state: {}", e));
-                assert!(state.is_some(), "Agent state should exist");
-                let state = state.unwrap();
-                assert_eq!(state.status, AgentStatus::Idle, "New agent should be idle");
-                assert_eq!(state.agent_id.number(), 1, "Agent ID should match");
+                let state = db
+                    .get_agent_state(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("Failed to get agent state: {e}"))
+                    .expect("Agent state should exist");
+                assert_eq!(
+                    state.status(),
+                    RuntimeAgentStatus::Idle,
+                    "New agent should be idle"
+                );
+                assert_eq!(state.agent_id().number(), 1, "Agent ID should match");
             }
 
             #[tokio::test]
-            #[ignore = "requires DATABASE_URL or SWARM_TEST_DATABASE_URL"]
             async fn then_duplicate_registration_returns_false_and_preserves_state() {
                 // Given
-                let db = test_db().await;
-                setup_schema(&db).await;
-                reset_runtime_tables(&db).await;
+                let db = isolated_db().await.expect("test database");
                 let agent_id = AgentId::new(RepoId::new("local"), 1);
-                db.register_agent(&agent_id).await
-                    .unwrap_or_else(|e| panic!("Initial registration failed: {}", e));
+                db.register_agent(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("Initial registration failed: {e}"));
 
                 // When
-                let was_new = db.register_agent(&agent_id).await
-                    .unwrap_or_else(|e| panic!("Second registration failed: {}", e));
+                let was_new = db
+                    .register_agent(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("Second registration failed: {e}"));
 
                 // Then
                 assert!(!was_new, "Duplicate registration should return false");
-                let state = db.get_agent_state(&agent_id).await
-                    .unwrap_or_else(|e| panic!("Failed to get state: {}", e))
+                let state = db
+                    .get_agent_state(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("Failed to get state: {e}"))
                     .expect("State should exist");
-                assert_eq!(state.status, AgentStatus::Idle, "Status should be unchanged");
+                assert_eq!(
+                    state.status(),
+                    RuntimeAgentStatus::Idle,
+                    "Status should be unchanged"
+                );
             }
         }
 
@@ -65,70 +125,82 @@ mod agent_lifecycle {
             use super::*;
 
             #[tokio::test]
-            #[ignore = "requires DATABASE_URL or SWARM_TEST_DATABASE_URL"]
             async fn then_all_agents_are_registered_with_unique_ids() {
                 // Given
-                let db = test_db().await;
-                setup_schema(&db).await;
-                reset_runtime_tables(&db).await;
+                let db = isolated_db().await.expect("test database");
                 let count = 10;
 
                 // When
-                db.seed_idle_agents(count).await
-                    .unwrap_or_else(|e| panic!("seed_idle_agents failed: {}", e));
+                db.seed_idle_agents(count)
+                    .await
+                    .unwrap_or_else(|e| panic!("seed_idle_agents failed: {e}"));
 
                 // Then
                 for n in 1..=count {
                     let agent_id = AgentId::new(RepoId::new("local"), n);
-                    let state = db.get_agent_state(&agent_id).await
-                        .unwrap_or_else(|e| panic!("Failed to get agent {} state: {}", n, e))
-                        .unwrap_or_else(|| panic!("Agent {} should exist", n));
-                    assert_eq!(state.status, AgentStatus::Idle, "Agent {} should be idle", n);
+                    let state = db
+                        .get_agent_state(&agent_id)
+                        .await
+                        .unwrap_or_else(|e| panic!("Failed to get agent {n} state: {e}"))
+                        .unwrap_or_else(|| panic!("Agent {n} should exist"));
+                    assert_eq!(
+                        state.status(),
+                        RuntimeAgentStatus::Idle,
+                        "Agent {n} should be idle"
+                    );
                 }
             }
         }
     }
 
     mod when_claiming_work {
+        use super::*;
 
         mod given_an_idle_agent_and_pending_beads {
             use super::*;
 
             #[tokio::test]
-            #[ignore = "requires DATABASE_URL or SWARM_TEST_DATABASE_URL"]
             async fn then_agent_claims_earliest_pending_bead_and_transitions_to_working() {
                 // Given
-                let db = test_db().await;
-                setup_schema(&db).await;
-                reset_runtime_tables(&db).await;
+                let db = isolated_db().await.expect("test database");
                 let agent_id = AgentId::new(RepoId::new("local"), 1);
-                let bead_id = BeadId::new(unique_bead("early-bead"));
-                let later_bead = BeadId::new(unique_bead("late-bead"));
-
-                db.seed_idle_agents(1).await
-                    .unwrap_or_else(|e| panic!("seed failed: {}", e));
-                sqlx::query("INSERT INTO bead_backlog (bead_id, priority, status) VALUES ($1, 'p0', 'pending')")
-                    .bind(&bead_id.value()).execute(db.pool()).await
-                    .unwrap_or_else(|e| panic!("insert early bead failed: {}", e));
-                sqlx::query("INSERT INTO bead_backlog (bead_id, priority, status) VALUES ($1, 'p0', 'pending')")
-                    .bind(&later_bead.value()).execute(db.pool()).await
-                    .unwrap_or_else(|e| panic!("insert late bead failed: {}", e));
+                let bead_id = BeadId::new("early-bead");
+                let later_bead = BeadId::new("late-bead");
+
+                db.seed_idle_agents(1)
+                    .await
+                    .unwrap_or_else(|e| panic!("seed failed: {e}"));
+                insert_pending_bead(&db, &bead_id).await;
+                insert_pending_bead(&db, &later_bead).await;
 
                 // When
-                let claimed = db.claim_next_bead(&agent_id).await
-                    .unwrap_or_else(|e| panic!("claim failed: {}", e));
+                let claimed = db
+                    .claim_next_bead(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("claim failed: {e}"));
 
                 // Then
-                assert_eq!(claimed.as_ref().map(BeadId::value), Some(bead_id.value()),
-                    "Should claim earliest bead");
-                let state = db.get_agent_state(&agent_id).await
-                    .unwrap_or_else(|e| panic!("get state failed: {}", e))
+                assert_eq!(claimed, Some(bead_id.clone()), "Should claim earliest bead");
+                let state = db
+                    .get_agent_state(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("get state failed: {e}"))
                     .expect("state should exist");
-                assert_eq!(state.status, AgentStatus::Working, "Agent should be working");
-                assert_eq!(state.bead_id.as_ref().map(BeadId::value), Some(bead_id.value().to_string()),
-                    "Agent should have bead assigned");
-                assert_eq!(state.current_stage, Some(Stage::RustContract),
-                    "Agent should start at rust-contract stage");
+                assert_eq!(
+                    state.status(),
+                    RuntimeAgentStatus::Working,
+                    "Agent should be working"
+                );
+                assert_eq!(
+                    state.bead_id().map(|bead| bead.value().to_string()),
+                    Some(bead_id.value().to_string()),
+                    "Agent should have bead assigned"
+                );
+                assert_eq!(
+                    state.current_stage(),
+                    Some(RuntimeStage::RustContract),
+                    "Agent should start at rust-contract stage"
+                );
             }
         }
 
@@ -136,107 +208,145 @@ mod agent_lifecycle {
             use super::*;
 
             #[tokio::test]
-            #[ignore = "requires DATABASE_URL or SWARM_TEST_DATABASE_URL"]
             async fn then_agent_receives_none_and_remains_idle() {
                 // Given
-                let db = test_db().await;
-                setup_schema(&db).await;
-                reset_runtime_tables(&db).await;
+                let db = isolated_db().await.expect("test database");
                 let agent_id = AgentId::new(RepoId::new("local"), 1);
 
-                db.seed_idle_agents(1).await
-                    .unwrap_or_else(|e| panic!("seed failed: {}", e));
+                db.seed_idle_agents(1)
+                    .await
+                    .unwrap_or_else(|e| panic!("seed failed: {e}"));
 
                 // When
-                let claimed = db.claim_next_bead(&agent_id).await
-                    .unwrap_or_else(|e| panic!("claim failed: {}", e));
+                let claimed = db
+                    .claim_next_bead(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("claim failed: {e}"));
 
                 // Then
                 assert!(claimed.is_none(), "Should have no beads to claim");
-                let state = db.get_agent_state(&agent_id).await
-                    .unwrap_or_else(|e| panic!("get state failed: {}", e))
+                let state = db
+                    .get_agent_state(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("get state failed: {e}"))
                     .expect("state should exist");
-                assert_eq!(state.status, AgentStatus::Idle, "Agent should remain idle");
-                assert!(state.bead_id.is_none(), "Agent should have no bead assigned");
+                assert_eq!(
+                    state.status(),
+                    RuntimeAgentStatus::Idle,
+                    "Agent should remain idle"
+                );
+                assert!(
+                    state.bead_id().is_none(),
+                    "Agent should have no bead assigned"
+                );
             }
         }
     }
 
     mod when_executing_stages {
+        use super::*;
 
         mod given_a_claimed_bead_at_initial_stage {
             use super::*;
 
             #[tokio::test]
-            #[ignore = "requires DATABASE_URL or SWARM_TEST_DATABASE_URL"]
             async fn then_successful_stage_advances_to_next_stage() {
                 // Given
-                let db = test_db().await;
-                setup_schema(&db).await;
-                reset_runtime_tables(&db).await;
+                let db = isolated_db().await.expect("test database");
                 let agent_id = AgentId::new(RepoId::new("local"), 1);
-                let bead_id = BeadId::new(unique_bead("stage-advancement"));
-
-                db.seed_idle_agents(1).await.unwrap_or_else(|e| panic!("seed failed: {}", e));
-                sqlx::query("INSERT INTO bead_backlog (bead_id, priority, status) VALUES ($1, 'p0', 'pending')")
-                    .bind(bead_id.value()).execute(db.pool()).await
-                    .unwrap_or_else(|e| panic!("insert bead failed: {}", e));
-                db.claim_next_bead(&agent_id).await
-                    .unwrap_or_else(|e| panic!("claim failed: {}", e));
-                db.record_stage_started(&agent_id, &bead_id, Stage::RustContract, 1).await
-                    .unwrap_or_else(|e| panic!("stage start failed: {}", e));
+                let bead_id = BeadId::new("stage-advancement");
+
+                db.seed_idle_agents(1)
+                    .await
+                    .unwrap_or_else(|e| panic!("seed failed: {e}"));
+                insert_pending_bead(&db, &bead_id).await;
+                db.claim_next_bead(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("claim failed: {e}"));
+                db.record_stage_started(&agent_id, &bead_id, Stage::RustContract, 1)
+                    .await
+                    .unwrap_or_else(|e| panic!("stage start failed: {e}"));
 
                 // When
-                db.record_stage_complete(&agent_id, &bead_id, Stage::RustContract, 1,
-                    StageResult::Passed, 150).await
-                    .unwrap_or_else(|e| panic!("stage complete failed: {}", e));
+                db.record_stage_complete(
+                    &agent_id,
+                    &bead_id,
+                    Stage::RustContract,
+                    1,
+                    StageResult::Passed,
+                    150,
+                )
+                .await
+                .unwrap_or_else(|e| panic!("stage complete failed: {e}"));
 
                 // Then
-                let state = db.get_agent_state(&agent_id).await
-                    .unwrap_or_else(|e| panic!("get state failed: {}", e))
+                let state = db
+                    .get_agent_state(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("get state failed: {e}"))
                     .expect("state should exist");
-                assert_eq!(state.current_stage, Some(Stage::Implement),
-                    "Should advance to implement stage");
-                assert_eq!(state.status, AgentStatus::Working,
-                    "Agent should still be working");
+                assert_eq!(
+                    state.current_stage(),
+                    Some(RuntimeStage::Implement),
+                    "Should advance to implement stage"
+                );
+                assert_eq!(
+                    state.status(),
+                    RuntimeAgentStatus::Working,
+                    "Agent should still be working"
+                );
             }
 
             #[tokio::test]
-            #[ignore = "requires DATABASE_URL or SWARM_TEST_DATABASE_URL"]
             async fn then_failed_stage_sets_agent_to_waiting_and_increments_attempt() {
                 // Given
-                let db = test_db().await;
-                setup_schema(&db).await;
-                reset_runtime_tables(&db).await;
+                let db = isolated_db().await.expect("test database");
                 let agent_id = AgentId::new(RepoId::new("local"), 1);
-                let bead_id = BeadId::new(unique_bead("stage-failure"));
-
-                db.seed_idle_agents(1).await.unwrap_or_else(|e| panic!("seed failed: {}", e));
-                sqlx::query("INSERT INTO bead_backlog (bead_id, priority, status) VALUES ($1, 'p0', 'pending')")
-                    .bind(bead_id.value()).execute(db.pool()).await
-                    .unwrap_or_else(|e| panic!("insert bead failed: {}", e));
-                db.claim_next_bead(&agent_id).await
-                    .unwrap_or_else(|e| panic!("claim failed: {}", e));
-                db.record_stage_started(&agent_id, &bead_id, Stage::Implement, 1).await
-                    .unwrap_or_else(|e| panic!("stage start failed: {}", e));
+                let bead_id = BeadId::new("stage-failure");
+
+                db.seed_idle_agents(1)
+                    .await
+                    .unwrap_or_else(|e| panic!("seed failed: {e}"));
+                insert_pending_bead(&db, &bead_id).await;
+                db.claim_next_bead(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("claim failed: {e}"));
+                db.record_stage_started(&agent_id, &bead_id, Stage::Implement, 1)
+                    .await
+                    .unwrap_or_else(|e| panic!("stage start failed: {e}"));
 
                 // When
-                db.record_stage_complete(&agent_id, &bead_id, Stage::Implement, 1,
-                    StageResult::Failed("tests failed".to_string()), 200).await
-                    .unwrap_or_else(|e| panic!("stage complete failed: {}", e));
+                db.record_stage_complete(
+                    &agent_id,
+                    &bead_id,
+                    Stage::Implement,
+                    1,
+                    StageResult::Failed("tests failed".to_string()),
+                    200,
+                )
+                .await
+                .unwrap_or_else(|e| panic!("stage complete failed: {e}"));
 
                 // Then
-                let state = db.get_agent_state(&agent_id).await
-                    .unwrap_or_else(|e| panic!("get state failed: {}", e))
-                    .expect("state should exist");
-                assert_eq!(state.status, AgentStatus::Waiting,
-                    "Agent should be waiting for feedback");
-                assert_eq!(state.implementation_attempt, 1,
-                    "Should increment attempt count");
-                assert_eq!(state.current_stage, Some(Stage::Implement),
-                    "Should return to implement stage");
-                assert_eq!(state.feedback.as_deref(), Some("tests failed"),
-                    "Should store failure feedback");
+                let row = agent_row(&db, &agent_id).await;
+                assert_eq!(
+                    row.status, "waiting",
+                    "Agent should be waiting for feedback"
+                );
+                assert_eq!(
+                    row.implementation_attempt, 1,
+                    "Should increment attempt count"
+                );
+                assert_eq!(
+                    row.current_stage.as_deref(),
+                    Some("implement"),
+                    "Should return to implement stage"
+                );
+                assert_eq!(
+                    row.feedback.as_deref(),
+                    Some("tests failed"),
+                    "Should store failure feedback"
+                );
             }
         }
 
@@ -244,159 +354,252 @@ mod agent_lifecycle {
             use super::*;
 
             #[tokio::test]
-            #[ignore = "requires DATABASE_URL or SWARM_TEST_DATABASE_URL"]
             async fn then_agent_and_bead_are_finalized() {
                 // Given
-                let db = test_db().await;
-                setup_schema(&db).await;
-                reset_runtime_tables(&db).await;
+                let db = isolated_db().await.expect("test database");
                 let agent_id = AgentId::new(RepoId::new("local"), 1);
-                let bead_id = BeadId::new(unique_bead("final-stage"));
-
-                db.seed_idle_agents(1).await.unwrap_or_else(|e| panic!("seed failed: {}", e));
-                sqlx::query("INSERT INTO bead_backlog (bead_id, priority, status) VALUES ($1, 'p0', 'pending')")
-                    .bind(bead_id.value()).execute(db.pool()).await
-                    .unwrap_or_else(|e| panic!("insert bead failed: {}", e));
-                db.claim_next_bead(&agent_id).await
-                    .unwrap_or_else(|e| panic!("claim failed: {}", e));
-                db.record_stage_started(&agent_id, &bead_id, Stage::RedQueen, 1).await
-                    .unwrap_or_else(|e| panic!("stage start failed: {}", e));
+                let bead_id = BeadId::new("final-stage");
+
+                db.seed_idle_agents(1)
+                    .await
+                    .unwrap_or_else(|e| panic!("seed failed: {e}"));
+                insert_pending_bead(&db, &bead_id).await;
+                db.claim_next_bead(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("claim failed: {e}"));
+                db.record_stage_started(&agent_id, &bead_id, Stage::RedQueen, 1)
+                    .await
+                    .unwrap_or_else(|e| panic!("stage start failed: {e}"));
 
                 // When
-                db.record_stage_complete(&agent_id, &bead_id, Stage::RedQueen, 1,
-                    StageResult::Passed, 100).await
-                    .unwrap_or_else(|e| panic!("stage complete failed: {}", e));
+                db.record_stage_complete(
+                    &agent_id,
+                    &bead_id,
+                    Stage::RedQueen,
+                    1,
+                    StageResult::Passed,
+                    100,
+                )
+                .await
+                .unwrap_or_else(|e| panic!("stage complete failed: {e}"));
 
                 // Then
-                let state = db.get_agent_state(&agent_id).await
-                    .unwrap_or_else(|e| panic!("get state failed: {}", e))
-                    .expect("state should exist");
-                assert_eq!(state.status, AgentStatus::Done,
-                    "Agent should be done");
-                assert_eq!(state.current_stage, Some(Stage::Done),
-                    "Stage should be done");
-
-                let claim_status: Option<String> = sqlx::query_scalar(
-                    "SELECT status FROM bead_claims WHERE bead_id = $1")
-                    .bind(bead_id.value()).fetch_optional(db.pool()).await
-                    .unwrap_or_else(|e| panic!("query failed: {}", e));
-                assert_eq!(claim_status.as_deref(), Some("completed"),
-                    "Bead claim should be completed");
+                let row = agent_row(&db, &agent_id).await;
+                assert_eq!(row.status, "done", "Agent should be done");
+                assert_eq!(
+                    row.current_stage.as_deref(),
+                    Some("done"),
+                    "Stage should be done"
+                );
+
+                let claim_status: Option<String> =
+                    sqlx::query_scalar("SELECT status FROM bead_claims WHERE bead_id = $1")
+                        .bind(bead_id.value())
+                        .fetch_optional(db.pool())
+                        .await
+                        .unwrap_or_else(|e| panic!("query failed: {e}"));
+                assert_eq!(
+                    claim_status.as_deref(),
+                    Some("completed"),
+                    "Bead claim should be completed"
+                );
             }
         }
     }
 
     mod when_releasing {
+        use super::*;
 
         mod given_an_agent_with_claimed_bead {
             use super::*;
 
             #[tokio::test]
-            #[ignore = "requires DATABASE_URL or SWARM_TEST_DATABASE_URL"]
             async fn then_agent_resets_to_idle_and_bead_returns_to_backlog() {
                 // Given
-                let db = test_db().await;
-                setup_schema(&db).await;
-                reset_runtime_tables(&db).await;
+                let db = isolated_db().await.expect("test database");
                 let agent_id = AgentId::new(RepoId::new("local"), 1);
-                let bead_id = BeadId::new(unique_bead("release-test"));
+                let bead_id = BeadId::new("release-test");
 
-                db.seed_idle_agents(1).await.unwrap_or_else(|e| panic!("seed failed: {}", e));
-                sqlx::query("INSERT INTO bead_backlog (bead_id, priority, status) VALUES ($1, 'p0', 'pending')")
-                    .bind(bead_id.value()).execute(db.pool()).await
-                    .unwrap_or_else(|e| panic!("insert bead failed: {}", e));
-                db.claim_next_bead(&agent_id).await
-                    .unwrap_or_else(|e| panic!("claim failed: {}", e));
+                db.seed_idle_agents(1)
+                    .await
+                    .unwrap_or_else(|e| panic!("seed failed: {e}"));
+                insert_pending_bead(&db, &bead_id).await;
+                db.claim_next_bead(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("claim failed: {e}"));
 
                 // When
-                let released = db.release_agent(&agent_id).await
-                    .unwrap_or_else(|e| panic!("release failed: {}", e));
+                let released = db
+                    .release_agent(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("release failed: {e}"));
 
                 // Then
-                assert_eq!(released.as_ref().map(BeadId::value), Some(bead_id.value()),
-                    "Should return released bead");
-                let state = db.get_agent_state(&agent_id).await
-                    .unwrap_or_else(|e| panic!("get state failed: {}", e))
+                assert_eq!(
+                    released,
+                    Some(bead_id.clone()),
+                    "Should return released bead"
+                );
+                let state = db
+                    .get_agent_state(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("get state failed: {e}"))
                     .expect("state should exist");
-                assert_eq!(state.status, AgentStatus::Idle,
-                    "Agent should be idle");
-                assert!(state.bead_id.is_none(), "Agent should have no bead");
-                assert_eq!(state.implementation_attempt, 0,
-                    "Attempts should reset");
-
-                let backlog_status: Option<String> = sqlx::query_scalar(
-                    "SELECT status FROM bead_backlog WHERE bead_id = $1")
-                    .bind(bead_id.value()).fetch_optional(db.pool()).await
-                    .unwrap_or_else(|e| panic!("query failed: {}", e));
-                assert_eq!(backlog_status.as_deref(), Some("pending"),
-                    "Bead should be pending in backlog");
-
-                let claim_count: i64 = sqlx::query_scalar(
-                    "SELECT COUNT(*) FROM bead_claims WHERE bead_id = $1")
-                    .bind(bead_id.value()).fetch_one(db.pool()).await
-                    .unwrap_or_else(|e| panic!("query failed: {}", e));
-                assert_eq!(claim_count, 0, "Bead claim should be deleted");
+                assert_eq!(
+                    state.status(),
+                    RuntimeAgentStatus::Idle,
+                    "Agent should be idle"
+                );
+                assert!(state.bead_id().is_none(), "Agent should have no bead");
+                assert_eq!(state.implementation_attempt(), 0, "Attempts should reset");
+
+                let backlog_status: Option<String> =
+                    sqlx::query_scalar("SELECT status FROM bead_backlog WHERE bead_id = $1")
+                        .bind(bead_id.value())
+                        .fetch_optional(db.pool())
+                        .await
+                        .unwrap_or_else(|e| panic!("query failed: {e}"));
+                assert_eq!(
+                    backlog_status.as_deref(),
+                    Some("pending"),
+                    "Bead should be pending in backlog"
+                );
+
+                let deleted_by: Option<String> = sqlx::query_scalar(
+                    "SELECT deleted_by FROM bead_claims
+                     WHERE bead_id = $1 AND deleted_at IS NOT NULL",
+                )
+                .bind(bead_id.value())
+                .fetch_optional(db.pool())
+                .await
+                .unwrap_or_else(|e| panic!("query failed: {e}"));
+                assert_eq!(
+                    deleted_by.as_deref(),
+                    Some("release"),
+                    "Bead claim should be soft-deleted by the release"
+                );
+            }
+        }
+
+        mod given_an_agent_holding_a_cancelled_bead {
+            use super::*;
+
+            #[tokio::test]
+            async fn then_bead_stays_cancelled() {
+                // Given
+                let db = isolated_db().await.expect("test database");
+                let agent_id = AgentId::new(RepoId::new("local"), 1);
+                let bead_id = BeadId::new("release-cancelled");
+
+                db.seed_idle_agents(1)
+                    .await
+                    .unwrap_or_else(|e| panic!("seed failed: {e}"));
+                insert_pending_bead(&db, &bead_id).await;
+                db.claim_next_bead(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("claim failed: {e}"));
+                sqlx::query("UPDATE bead_backlog SET status = 'cancelled' WHERE bead_id = $1")
+                    .bind(bead_id.value())
+                    .execute(db.pool())
+                    .await
+                    .unwrap_or_else(|e| panic!("cancel failed: {e}"));
+
+                // When
+                db.release_agent(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("release failed: {e}"));
+
+                // Then
+                let backlog_status: Option<String> =
+                    sqlx::query_scalar("SELECT status FROM bead_backlog WHERE bead_id = $1")
+                        .bind(bead_id.value())
+                        .fetch_optional(db.pool())
+                        .await
+                        .unwrap_or_else(|e| panic!("query failed: {e}"));
+                assert_eq!(
+                    backlog_status.as_deref(),
+                    Some("cancelled"),
+                    "A cancelled bead cannot be requeued"
+                );
             }
         }
     }
 
     mod when_exceeding_max_attempts {
+        use super::*;
 
         mod given_an_agent_at_max_implementation_attempts {
             use super::*;
 
             #[tokio::test]
-            #[ignore = "requires DATABASE_URL or SWARM_TEST_DATABASE_URL"]
             async fn then_bead_is_marked_blocked_and_agent_errors() {
                 // Given
-                let db = test_db().await;
-                setup_schema(&db).await;
-                reset_runtime_tables(&db).await;
+                let db = isolated_db().await.expect("test database");
                 let agent_id = AgentId::new(RepoId::new("local"), 1);
-                let bead_id = BeadId::new(unique_bead("max-attempts"));
+                let bead_id = BeadId::new("max-attempts");
 
-                db.seed_idle_agents(1).await.unwrap_or_else(|e| panic!("seed failed: {}", e));
-                sqlx::query("INSERT INTO bead_backlog (bead_id, priority, status) VALUES ($1, 'p0', 'pending')")
-                    .bind(bead_id.value()).execute(db.pool()).await
-                    .unwrap_or_else(|e| panic!("insert bead failed: {}", e));
-                db.claim_next_bead(&agent_id).await
-                    .unwrap_or_else(|e| panic!("claim failed: {}", e));
+                db.seed_idle_agents(1)
+                    .await
+                    .unwrap_or_else(|e| panic!("seed failed: {e}"));
+                insert_pending_bead(&db, &bead_id).await;
+                db.claim_next_bead(&agent_id)
+                    .await
+                    .unwrap_or_else(|e| panic!("claim failed: {e}"));
 
                 // Simulate 3 attempts
                 for i in 1..=3 {
-                    db.record_stage_started(&agent_id, &bead_id, Stage::Implement, i).await
-                        .unwrap_or_else(|e| panic!("stage start {} failed: {}", i, e));
-                    db.record_stage_complete(&agent_id, &bead_id, Stage::Implement, i,
-                        StageResult::Failed("attempt failed".to_string()), 100).await
-                        .unwrap_or_else(|e| panic!("stage complete {} failed: {}", i, e));
+                    db.record_stage_started(&agent_id, &bead_id, Stage::Implement, i)
+                        .await
+                        .unwrap_or_else(|e| panic!("stage start {i} failed: {e}"));
+                    db.record_stage_complete(
+                        &agent_id,
+                        &bead_id,
+                        Stage::Implement,
+                        i,
+                        StageResult::Failed("attempt failed".to_string()),
+                        100,
+                    )
+                    .await
+                    .unwrap_or_else(|e| panic!("stage complete {i} failed: {e}"));
                 }
 
                 // When
-                db.mark_bead_blocked(&agent_id, &bead_id, "Max attempts (3) exceeded").await
-                    .unwrap_or_else(|e| panic!("mark_bead_blocked failed: {}", e));
+                db.mark_bead_blocked(&agent_id, &bead_id, "Max attempts (3) exceeded")
+                    .await
+                    .unwrap_or_else(|e| panic!("mark_bead_blocked failed: {e}"));
 
                 // Then
-                let state = db.get_agent_state(&agent_id).await
-                    .unwrap_or_else(|e| panic!("get state failed: {}", e))
-                    .expect("state should exist");
-                assert_eq!(state.status, AgentStatus::Error,
-                    "Agent should be in error state");
-                assert_eq!(state.feedback.as_deref(), Some("Max attempts (3) exceeded"),
-                    "Should store block reason");
-
-                let claim_status: Option<String> = sqlx::query_scalar(
-                    "SELECT status FROM bead_claims WHERE bead_id = $1")
-                    .bind(bead_id.value()).fetch_optional(db.pool()).await
-                    .unwrap_or_else(|e| panic!("query failed: {}", e));
-                assert_eq!(claim_status.as_deref(), Some("blocked"),
-                    "Bead claim should be blocked");
-
-                let backlog_status: Option<String> = sqlx::query_scalar(
-                    "SELECT status FROM bead_backlog WHERE bead_id = $1")
-                    .bind(bead_id.value()).fetch_optional(db.pool()).await
-                    .unwrap_or_else(|e| panic!("query failed: {}", e));
-                assert_eq!(backlog_status.as_deref(), Some("blocked"),
-                    "Bead backlog entry should be blocked");
+                let row = agent_row(&db, &agent_id).await;
+                assert_eq!(row.status, "error", "Agent should be in error state");
+                assert_eq!(
+                    row.feedback.as_deref(),
+                    Some("Max attempts (3) exceeded"),
+                    "Should store block reason"
+                );
+
+                let claim_status: Option<String> =
+                    sqlx::query_scalar("SELECT status FROM bead_claims WHERE bead_id = $1")
+                        .bind(bead_id.value())
+                        .fetch_optional(db.pool())
+                        .await
+                        .unwrap_or_else(|e| panic!("query failed: {e}"));
+                assert_eq!(
+                    claim_status.as_deref(),
+                    Some("blocked"),
+                    "Bead claim should be blocked"
+                );
+
+                let backlog_status: Option<String> =
+                    sqlx::query_scalar("SELECT status FROM bead_backlog WHERE bead_id = $1")
+                        .bind(bead_id.value())
+                        .fetch_optional(db.pool())
+                        .await
+                        .unwrap_or_else(|e| panic!("query failed: {e}"));
+                assert_eq!(
+                    backlog_status.as_deref(),
+                    Some("blocked"),
+                    "Bead backlog entry should be blocked"
+                );
             }
         }
     }
@@ -1,5 +1,16 @@
+#[cfg(test)]
+mod agent_lifecycle_behaviors;
+#[cfg(test)]
+mod concurrent_behaviors;
+#[cfg(test)]
+mod coordination_behaviors;
 mod mappers;
 pub mod swarm_db;
+pub mod write_batcher;
 pub mod write_ops;
 
-pub use swarm_db::SwarmDb;
+pub use swarm_db::{
+    validate_schema_name, AuthTarget, AuthTokenProvider, CommandTokenProvider, ConnectOptions,
+    DatabaseConfig, PoolHealth, ReconnectPolicy, SslMode, SwarmDb,
+};
+pub use write_batcher::{PendingWrite, WriteBatchHandle, WriteBatcher, WriteBatcherConfig};
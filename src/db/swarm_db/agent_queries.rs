@@ -4,57 +4,61 @@ use crate::runtime::{
     RuntimeAgentId, RuntimeAgentState, RuntimeAgentStatus, RuntimeBeadId, RuntimeRepoId,
     RuntimeStage,
 };
-use crate::types::{AgentId, AgentStatus, AvailableAgent, RepoId};
+use crate::types::{
+    AgentActivity, AgentId, AgentStatus, AvailableAgent, BehavioralFingerprint, RepoId,
+};
 
 impl SwarmDb {
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn get_agent_state(&self, agent_id: &AgentId) -> Result<Option<RuntimeAgentState>> {
-        let row = sqlx::query_as::<_, (Option<String>, Option<String>, String, i32)>(
-            "SELECT bead_id, current_stage, status, implementation_attempt
-             FROM agent_state
-             WHERE repo_id = $1 AND agent_id = $2",
-        )
-        .bind(agent_id.repo_id().value())
-        .bind(agent_id.number().cast_signed())
-        .fetch_optional(self.pool())
-        .await
-        .map_err(|error| {
-            SwarmError::DatabaseError(format!("Failed to load agent state: {error}"))
-        })?;
+        let row = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    "SELECT bead_id, current_stage, status, implementation_attempt
+                     FROM agent_state
+                     WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL",
+                    agent_id.repo_id().value(),
+                    agent_id.number().cast_signed(),
+                )
+                .fetch_optional(pool)
+            })
+            .await
+            .map_err(|error| {
+                SwarmError::DatabaseError(format!("Failed to load agent state: {error}"))
+            })?;
 
-        row.map_or(
-            Ok(None),
-            |(bead_id, current_stage, status, implementation_attempt)| {
-                let parsed_stage = current_stage
-                    .map(|value| RuntimeStage::try_from(value.as_str()))
-                    .transpose()
-                    .map_err(SwarmError::DatabaseError)?;
-                let parsed_status = RuntimeAgentStatus::try_from(status.as_str())
-                    .map_err(SwarmError::DatabaseError)?;
-                let runtime_agent = RuntimeAgentId::new(
-                    RuntimeRepoId::new(agent_id.repo_id().value().to_string()),
-                    agent_id.number(),
-                );
-                let state = RuntimeAgentState::new(
-                    runtime_agent,
-                    bead_id.map(RuntimeBeadId::new),
-                    parsed_stage,
-                    parsed_status,
-                    implementation_attempt.max(0).cast_unsigned(),
-                );
-                state
-                    .validate_invariants()
-                    .map_err(|error| SwarmError::DatabaseError(error.to_string()))?;
-                Ok(Some(state))
-            },
-        )
+        row.map_or(Ok(None), |row| {
+            let parsed_stage = row
+                .current_stage
+                .map(|value| RuntimeStage::try_from(value.as_str()))
+                .transpose()
+                .map_err(SwarmError::SchemaDrift)?;
+            let parsed_status = RuntimeAgentStatus::try_from(row.status.as_str())
+                .map_err(SwarmError::SchemaDrift)?;
+            let runtime_agent = RuntimeAgentId::new(
+                RuntimeRepoId::new(agent_id.repo_id().value().to_string()),
+                agent_id.number(),
+            );
+            let state = RuntimeAgentState::new(
+                runtime_agent,
+                row.bead_id.map(RuntimeBeadId::new),
+                parsed_stage,
+                parsed_status,
+                row.implementation_attempt.max(0).cast_unsigned(),
+            );
+            state
+                .validate_invariants()
+                .map_err(|error| SwarmError::DatabaseError(error.to_string()))?;
+            Ok(Some(state))
+        })
     }
 
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn get_available_agents(&self, repo_id: &RepoId) -> Result<Vec<AvailableAgent>> {
-        let rows = sqlx::query_as::<_, (i32, String, i32, i32, i32)>(
+        let repo_scoped = self.table_has_column("swarm_config", "repo_id").await?;
+        let sql = if repo_scoped {
             "SELECT
                 a.agent_id,
                 a.status,
@@ -63,21 +67,37 @@ impl SwarmDb {
                 c.max_agents
              FROM agent_state a
              JOIN swarm_config c ON c.repo_id = a.repo_id
-             WHERE a.repo_id = $1
-             ORDER BY a.agent_id ASC",
-        )
-        .bind(repo_id.value())
-        .fetch_all(self.pool())
-        .await
-        .map_err(|error| {
-            SwarmError::DatabaseError(format!("Failed to load available agents: {error}"))
-        })?;
+             WHERE a.repo_id = $1 AND a.deleted_at IS NULL
+             ORDER BY a.agent_id ASC"
+        } else {
+            "SELECT
+                a.agent_id,
+                a.status,
+                a.implementation_attempt,
+                c.max_implementation_attempts,
+                c.max_agents
+             FROM agent_state a
+             CROSS JOIN swarm_config c
+             WHERE a.repo_id = $1 AND a.deleted_at IS NULL
+             ORDER BY a.agent_id ASC"
+        };
+
+        let rows = self
+            .read_with_fallback(|pool| {
+                sqlx::query_as::<_, (i32, String, i32, i32, i32)>(sql)
+                    .bind(repo_id.value())
+                    .fetch_all(pool)
+            })
+            .await
+            .map_err(|error| {
+                SwarmError::DatabaseError(format!("Failed to load available agents: {error}"))
+            })?;
 
         rows.into_iter()
             .map(
                 |(agent_id, status, implementation_attempt, max_attempts, max_agents)| {
-                    let status = AgentStatus::try_from(status.as_str())
-                        .map_err(SwarmError::DatabaseError)?;
+                    let status =
+                        AgentStatus::try_from(status.as_str()).map_err(SwarmError::SchemaDrift)?;
                     Ok(AvailableAgent {
                         repo_id: repo_id.clone(),
                         agent_id: agent_id.max(0).cast_unsigned(),
@@ -91,35 +111,232 @@ impl SwarmDb {
             .collect()
     }
 
+    /// Every agent's bead, stage timing, claim heartbeat, attempt, and
+    /// throughput over the last `window_mins`, in a single round trip.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_agent_activity(
+        &self,
+        repo_id: &RepoId,
+        window_mins: u32,
+    ) -> Result<Vec<AgentActivity>> {
+        let rows = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    r#"SELECT
+                        a.agent_id,
+                        a.status,
+                        a.bead_id,
+                        a.current_stage,
+                        EXTRACT(EPOCH FROM NOW() - a.stage_started_at)::BIGINT AS stage_elapsed_secs,
+                        EXTRACT(EPOCH FROM NOW() - c.heartbeat_at)::BIGINT AS heartbeat_age_secs,
+                        a.implementation_attempt,
+                        COALESCE(t.stages_passed, 0) AS "stages_passed!",
+                        COALESCE(t.beads_completed, 0) AS "beads_completed!"
+                     FROM agent_state a
+                     LEFT JOIN bead_claims c
+                       ON c.repo_id = a.repo_id
+                      AND c.bead_id = a.bead_id
+                      AND c.claimed_by = a.agent_id
+                      AND c.status = 'in_progress'
+                      AND c.deleted_at IS NULL
+                     LEFT JOIN LATERAL (
+                        SELECT
+                            COUNT(*) FILTER (WHERE sh.status = 'passed') AS stages_passed,
+                            COUNT(DISTINCT sh.bead_id)
+                                FILTER (WHERE sh.status = 'passed' AND sh.stage = 'red-queen')
+                                AS beads_completed
+                        FROM stage_history sh
+                        WHERE sh.repo_id = a.repo_id
+                          AND sh.agent_id = a.agent_id
+                          AND sh.completed_at >= NOW() - make_interval(mins => $2)
+                     ) t ON TRUE
+                     WHERE a.repo_id = $1 AND a.deleted_at IS NULL
+                     ORDER BY a.agent_id ASC"#,
+                    repo_id.value(),
+                    window_mins.cast_signed(),
+                )
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|error| {
+                SwarmError::DatabaseError(format!("Failed to load agent activity: {error}"))
+            })?;
+
+        let secs = |value: Option<i64>| value.map(|secs| secs.max(0).cast_unsigned());
+        let count = |value: i64| u32::try_from(value.max(0)).unwrap_or(u32::MAX);
+        rows.into_iter()
+            .map(|row| {
+                Ok(AgentActivity {
+                    agent_id: row.agent_id.max(0).cast_unsigned(),
+                    status: AgentStatus::try_from(row.status.as_str())
+                        .map_err(SwarmError::SchemaDrift)?,
+                    bead_id: row.bead_id,
+                    stage: row.current_stage,
+                    stage_elapsed_secs: secs(row.stage_elapsed_secs),
+                    heartbeat_age_secs: secs(row.heartbeat_age_secs),
+                    attempt: row.implementation_attempt.max(0).cast_unsigned(),
+                    stages_passed: count(row.stages_passed),
+                    beads_completed: count(row.beads_completed),
+                })
+            })
+            .collect()
+    }
+
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn get_active_agents(
         &self,
         repo_id: &RepoId,
     ) -> Result<Vec<(RepoId, u32, Option<String>, String)>> {
-        sqlx::query_as::<_, (i32, Option<String>, String)>(
-            "SELECT agent_id, bead_id, status
-             FROM agent_state
-             WHERE repo_id = $1 AND status <> 'idle'
-             ORDER BY agent_id ASC",
-        )
-        .bind(repo_id.value())
-        .fetch_all(self.pool())
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT agent_id, bead_id, status
+                 FROM agent_state
+                 WHERE repo_id = $1 AND status <> 'idle' AND deleted_at IS NULL
+                 ORDER BY agent_id ASC",
+                repo_id.value(),
+            )
+            .fetch_all(pool)
+        })
         .await
         .map_err(|error| {
             SwarmError::DatabaseError(format!("Failed to load active agents: {error}"))
         })
         .map(|rows| {
             rows.into_iter()
-                .map(|(agent_id, bead_id, status)| {
+                .map(|row| {
                     (
                         repo_id.clone(),
-                        agent_id.max(0).cast_unsigned(),
-                        bead_id,
-                        status,
+                        row.agent_id.max(0).cast_unsigned(),
+                        row.bead_id,
+                        row.status,
                     )
                 })
                 .collect::<Vec<_>>()
         })
     }
+
+    /// Live fingerprint per agent with its bead and status. Consecutive
+    /// failures count failed stage runs since the agent's last pass; idle
+    /// time is only measured while an agent is working.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_agent_fingerprints(
+        &self,
+        repo_id: &RepoId,
+    ) -> Result<Vec<(u32, Option<String>, String, BehavioralFingerprint)>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT a.agent_id, a.bead_id, a.current_stage, a.status, a.implementation_attempt,
+                        CASE WHEN a.status = 'working'
+                             THEN GREATEST(EXTRACT(EPOCH FROM (NOW() - a.last_update)), 0)::BIGINT
+                             ELSE 0
+                        END AS "idle_secs!",
+                        (SELECT COUNT(*)
+                         FROM stage_history sh
+                         WHERE sh.repo_id = a.repo_id
+                           AND sh.agent_id = a.agent_id
+                           AND sh.status IN ('failed', 'error')
+                           AND sh.id > COALESCE((
+                               SELECT MAX(p.id)
+                               FROM stage_history p
+                               WHERE p.repo_id = a.repo_id AND p.agent_id = a.agent_id AND p.status = 'passed'
+                           ), 0)) AS "failures!"
+                 FROM agent_state a
+                 WHERE a.repo_id = $1 AND a.deleted_at IS NULL
+                 ORDER BY a.agent_id ASC"#,
+                repo_id.value(),
+            )
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|error| {
+            SwarmError::DatabaseError(format!("Failed to load agent fingerprints: {error}"))
+        })
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| {
+                    let agent_id = row.agent_id.max(0).cast_unsigned();
+                    let fingerprint = BehavioralFingerprint::new(
+                        agent_id.to_string(),
+                        None,
+                        row.current_stage.unwrap_or_default(),
+                        u32::try_from(row.failures.max(0)).unwrap_or(u32::MAX),
+                        row.idle_secs.max(0).cast_unsigned(),
+                        row.implementation_attempt.max(0).cast_unsigned(),
+                    );
+                    (agent_id, row.bead_id, row.status, fingerprint)
+                })
+                .collect::<Vec<_>>()
+        })
+    }
+
+    /// Default claim labels of every registered agent in `repo_id`, or only
+    /// of `agent`, by agent number.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_agent_labels(
+        &self,
+        repo_id: &RepoId,
+        agent: Option<u32>,
+    ) -> Result<Vec<(u32, Vec<String>)>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT agent_id, labels
+                 FROM agent_state
+                 WHERE repo_id = $1 AND ($2::INTEGER IS NULL OR agent_id = $2)
+                   AND deleted_at IS NULL
+                 ORDER BY agent_id",
+                repo_id.value(),
+                agent.map(u32::cast_signed),
+            )
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load agent labels: {e}")))
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| (row.agent_id.max(0).cast_unsigned(), row.labels))
+                .collect()
+        })
+    }
+
+    /// Number of agents registered in `repo_id`, which every announcement
+    /// reaches.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn count_registered_agents(&self, repo_id: &RepoId) -> Result<u64> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM agent_state WHERE repo_id = $1 AND deleted_at IS NULL"#,
+                repo_id.value(),
+            )
+            .fetch_one(pool)
+        })
+        .await
+        .map(|count| count.max(0).cast_unsigned())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to count agents: {e}")))
+    }
+
+    /// Repos with at least one registered agent, the ones a recovery pass
+    /// checks the quarantine policy for.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_agent_repo_ids(&self) -> Result<Vec<RepoId>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+            "SELECT DISTINCT repo_id FROM agent_state WHERE deleted_at IS NULL ORDER BY repo_id",
+        )
+            .fetch_all(pool)
+        })
+        .await
+        .map(|repo_ids| repo_ids.into_iter().map(RepoId::new).collect())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load agent repos: {e}")))
+    }
 }
@@ -0,0 +1,111 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AlertKind, AlertObservation, AlertStatus, RepoId, SwarmAlert};
+use chrono::{DateTime, Utc};
+
+pub type AlertRow = (
+    String,
+    String,
+    String,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+    Option<DateTime<Utc>>,
+);
+
+pub fn to_swarm_alert(
+    (kind, status, detail, breached_at, fired_at, resolved_at): AlertRow,
+) -> Result<SwarmAlert> {
+    Ok(SwarmAlert {
+        kind: AlertKind::try_from(kind.as_str()).map_err(SwarmError::SchemaDrift)?,
+        status: AlertStatus::try_from(status.as_str()).map_err(SwarmError::SchemaDrift)?,
+        detail,
+        breached_at,
+        fired_at,
+        resolved_at,
+    })
+}
+
+impl SwarmDb {
+    /// Pending and firing alerts, oldest breach first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_open_alerts(&self, repo_id: &RepoId) -> Result<Vec<SwarmAlert>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT kind, status, detail, breached_at, fired_at, resolved_at
+                 FROM alerts
+                 WHERE repo_id = $1 AND resolved_at IS NULL
+                 ORDER BY breached_at ASC",
+                repo_id.value(),
+            )
+            .map(|row| -> AlertRow {
+                (
+                    row.kind,
+                    row.status,
+                    row.detail,
+                    row.breached_at,
+                    row.fired_at,
+                    row.resolved_at,
+                )
+            })
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load alerts: {e}")))?
+        .into_iter()
+        .map(to_swarm_alert)
+        .collect()
+    }
+
+    /// Backlog, stage-run, and agent counters for alert rules, with stage
+    /// runs counted over the last `error_window_mins`.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_alert_observation(
+        &self,
+        repo_id: &RepoId,
+        error_window_mins: u32,
+    ) -> Result<AlertObservation> {
+        let row = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    r#"SELECT
+                     (SELECT COUNT(*) FROM bead_backlog
+                      WHERE repo_id = $1 AND status = 'pending' AND deleted_at IS NULL)
+                         AS "pending_beads!",
+                     (SELECT COUNT(*) FROM stage_history sh
+                      JOIN agent_state a
+                        ON a.agent_id = sh.agent_id AND a.repo_id = $1 AND a.deleted_at IS NULL
+                      WHERE sh.completed_at >= NOW() - make_interval(mins => $2)
+                        AND sh.status IN ('passed', 'failed', 'error')) AS "stage_runs!",
+                     (SELECT COUNT(*) FROM stage_history sh
+                      JOIN agent_state a
+                        ON a.agent_id = sh.agent_id AND a.repo_id = $1 AND a.deleted_at IS NULL
+                      WHERE sh.completed_at >= NOW() - make_interval(mins => $2)
+                        AND sh.status IN ('failed', 'error')) AS "stage_failures!",
+                     (SELECT COUNT(*) FROM agent_state WHERE repo_id = $1 AND deleted_at IS NULL)
+                         AS "total_agents!",
+                     (SELECT COUNT(*) FROM agent_state
+                      WHERE repo_id = $1 AND status = 'waiting' AND deleted_at IS NULL)
+                         AS "waiting_agents!""#,
+                    repo_id.value(),
+                    error_window_mins.cast_signed(),
+                )
+                .fetch_one(pool)
+            })
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to load alert counters: {e}"))
+            })?;
+        let count = |value: i64| u32::try_from(value.max(0)).unwrap_or(u32::MAX);
+        Ok(AlertObservation {
+            pending_beads: count(row.pending_beads),
+            stage_runs: count(row.stage_runs),
+            stage_failures: count(row.stage_failures),
+            total_agents: count(row.total_agents),
+            waiting_agents: count(row.waiting_agents),
+        })
+    }
+}
@@ -0,0 +1,133 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{Announcement, AnnouncementAck, AnnouncementStatus, RepoId};
+use chrono::{DateTime, Utc};
+
+pub type AnnouncementRow = (i64, String, String, String, DateTime<Utc>, DateTime<Utc>);
+
+pub fn to_announcement(row: AnnouncementRow) -> Announcement {
+    let (id, topic, msg, from, created_at, expires_at) = row;
+    Announcement {
+        id,
+        topic,
+        msg,
+        from,
+        created_at,
+        expires_at,
+    }
+}
+
+impl SwarmDb {
+    /// Unexpired announcements, oldest first, optionally on one `topic`.
+    /// With `unacked_by`, only those that agent has not acknowledged: its
+    /// inbox, whenever it registered.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_announcements(
+        &self,
+        repo_id: &RepoId,
+        topic: Option<&str>,
+        unacked_by: Option<u32>,
+    ) -> Result<Vec<Announcement>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT a.id, a.topic, a.msg, a.from_agent, a.created_at, a.expires_at
+                 FROM announcements a
+                 WHERE a.repo_id = $1
+                   AND a.expires_at > NOW()
+                   AND ($2::TEXT IS NULL OR a.topic = $2)
+                   AND ($3::INTEGER IS NULL OR NOT EXISTS (
+                       SELECT 1 FROM announcement_acks k
+                       WHERE k.announcement_id = a.id AND k.agent_id = $3
+                   ))
+                 ORDER BY a.created_at, a.id",
+                repo_id.value(),
+                topic,
+                unacked_by.map(u32::cast_signed),
+            )
+            .map(|row| -> AnnouncementRow {
+                (
+                    row.id,
+                    row.topic,
+                    row.msg,
+                    row.from_agent,
+                    row.created_at,
+                    row.expires_at,
+                )
+            })
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load announcements: {e}")))
+        .map(|rows| rows.into_iter().map(to_announcement).collect())
+    }
+
+    /// Every unexpired announcement with the agents that acknowledged it and
+    /// the registered agents that have not.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_announcement_statuses(
+        &self,
+        repo_id: &RepoId,
+        topic: Option<&str>,
+    ) -> Result<Vec<AnnouncementStatus>> {
+        let announcements = self.get_announcements(repo_id, topic, None).await?;
+        let acks = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    "SELECT k.announcement_id, k.agent_id, k.acked_at
+                     FROM announcement_acks k
+                     JOIN announcements a ON a.id = k.announcement_id
+                     WHERE a.repo_id = $1
+                       AND a.expires_at > NOW()
+                       AND ($2::TEXT IS NULL OR a.topic = $2)
+                     ORDER BY k.agent_id",
+                    repo_id.value(),
+                    topic,
+                )
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to load announcement acks: {e}"))
+            })?;
+        let agents = self
+            .read_with_fallback(|pool| {
+                sqlx::query_scalar!(
+                    "SELECT agent_id FROM agent_state
+                     WHERE repo_id = $1 AND deleted_at IS NULL
+                     ORDER BY agent_id",
+                    repo_id.value(),
+                )
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to load agents: {e}")))?;
+
+        Ok(announcements
+            .into_iter()
+            .map(|announcement| {
+                let acked = acks
+                    .iter()
+                    .filter(|ack| ack.announcement_id == announcement.id)
+                    .map(|ack| AnnouncementAck {
+                        agent_id: ack.agent_id.max(0).cast_unsigned(),
+                        acked_at: ack.acked_at,
+                    })
+                    .collect::<Vec<_>>();
+                let pending = agents
+                    .iter()
+                    .map(|agent_id| (*agent_id).max(0).cast_unsigned())
+                    .filter(|agent_id| !acked.iter().any(|ack| ack.agent_id == *agent_id))
+                    .collect();
+                AnnouncementStatus {
+                    announcement,
+                    acked,
+                    pending,
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,119 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{Approval, ApprovalGate, ApprovalStatus, RepoId};
+use chrono::{DateTime, Utc};
+
+pub type ApprovalRow = (
+    i64,
+    String,
+    String,
+    String,
+    Option<i32>,
+    DateTime<Utc>,
+    Option<String>,
+    Option<DateTime<Utc>>,
+    Option<String>,
+);
+
+pub fn to_approval(row: ApprovalRow) -> Result<Approval> {
+    let (id, bead_id, gate, status, requested_by, requested_at, approved_by, approved_at, note) =
+        row;
+    Ok(Approval {
+        id,
+        bead_id,
+        gate: ApprovalGate::try_from(gate.as_str()).map_err(SwarmError::SchemaDrift)?,
+        status: ApprovalStatus::try_from(status.as_str()).map_err(SwarmError::SchemaDrift)?,
+        requested_by: requested_by.map(|agent_id| agent_id.max(0).cast_unsigned()),
+        requested_at,
+        approved_by,
+        approved_at,
+        note,
+    })
+}
+
+impl SwarmDb {
+    /// The approval recorded for `bead_id` at `gate`, if the bead has reached
+    /// it.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_approval(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &str,
+        gate: ApprovalGate,
+    ) -> Result<Option<Approval>> {
+        sqlx::query!(
+            "SELECT id, bead_id, gate, status, requested_by, requested_at,
+                    approved_by, approved_at, note
+             FROM approvals
+             WHERE repo_id = $1 AND bead_id = $2 AND gate = $3",
+            repo_id.value(),
+            bead_id,
+            gate.as_str(),
+        )
+        .map(|row| -> ApprovalRow {
+            (
+                row.id,
+                row.bead_id,
+                row.gate,
+                row.status,
+                row.requested_by,
+                row.requested_at,
+                row.approved_by,
+                row.approved_at,
+                row.note,
+            )
+        })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load approval: {e}")))?
+        .map(to_approval)
+        .transpose()
+    }
+
+    /// Approvals for the repo, pending ones oldest first and then approved
+    /// ones newest first, up to `limit`. `bead_id` limits them to one bead.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn list_approvals(
+        &self,
+        repo_id: &RepoId,
+        bead_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Approval>> {
+        sqlx::query!(
+            "SELECT id, bead_id, gate, status, requested_by, requested_at,
+                    approved_by, approved_at, note
+             FROM approvals
+             WHERE repo_id = $1 AND ($2::TEXT IS NULL OR bead_id = $2)
+             ORDER BY status = 'pending' DESC,
+                      CASE WHEN status = 'pending' THEN requested_at END ASC,
+                      approved_at DESC
+             LIMIT $3",
+            repo_id.value(),
+            bead_id,
+            limit,
+        )
+        .map(|row| -> ApprovalRow {
+            (
+                row.id,
+                row.bead_id,
+                row.gate,
+                row.status,
+                row.requested_by,
+                row.requested_at,
+                row.approved_by,
+                row.approved_at,
+                row.note,
+            )
+        })
+        .fetch_all(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to list approvals: {e}")))?
+        .into_iter()
+        .map(to_approval)
+        .collect()
+    }
+}
@@ -1,8 +1,74 @@
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
-use crate::types::{ArtifactType, BeadId, RepoId, StageArtifact};
+use crate::signing::SignedArtifactRecord;
+use crate::types::{ArtifactType, BeadId, RepoId, StageArtifact, StageOutputChunk};
+use chrono::{DateTime, Utc};
+
+type ArtifactRow = (
+    i64,
+    i64,
+    String,
+    String,
+    Option<serde_json::Value>,
+    DateTime<Utc>,
+    Option<String>,
+);
+
+fn to_stage_artifact(
+    (id, stage_history_id, artifact_type, content, metadata, created_at, content_hash): ArtifactRow,
+) -> Result<StageArtifact> {
+    Ok(StageArtifact {
+        id,
+        stage_history_id,
+        artifact_type: ArtifactType::try_from(artifact_type.as_str())
+            .map_err(SwarmError::SchemaDrift)?,
+        content,
+        metadata,
+        created_at,
+        content_hash,
+    })
+}
 
 impl SwarmDb {
+    /// Up to `limit` spilled chunks of one stream of a stage run, in write
+    /// order, starting after `after_seq`. Concatenated in order, every page
+    /// rebuilds the stream without holding it all at once.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_stage_output_chunks(
+        &self,
+        stage_history_id: i64,
+        stream: &str,
+        after_seq: Option<i32>,
+        limit: i64,
+    ) -> Result<Vec<StageOutputChunk>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT seq, content
+                 FROM stage_output_chunks
+                 WHERE stage_history_id = $1 AND stream = $2 AND seq > COALESCE($3, -1)
+                 ORDER BY seq ASC
+                 LIMIT $4",
+                stage_history_id,
+                stream,
+                after_seq,
+                limit,
+            )
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| StageOutputChunk {
+                    seq: row.seq,
+                    content: row.content,
+                })
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load stage output: {e}")))
+    }
+
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn get_stage_artifacts(
@@ -10,55 +76,33 @@ impl SwarmDb {
         repo_id: &RepoId,
         stage_history_id: i64,
     ) -> Result<Vec<StageArtifact>> {
-        let rows = sqlx::query_as::<
-            _,
-            (
-                i64,
-                i64,
-                String,
-                String,
-                Option<serde_json::Value>,
-                chrono::DateTime<chrono::Utc>,
-                Option<String>,
-            ),
-        >(
-            "SELECT sa.id, sa.stage_history_id, sa.artifact_type, sa.content, sa.metadata, sa.created_at, sa.content_hash
-             FROM stage_artifacts sa
-             JOIN stage_history sh ON sh.id = sa.stage_history_id
-             WHERE sh.repo_id = $1 AND sa.stage_history_id = $2
-             ORDER BY sa.created_at ASC, sa.id ASC",
-        )
-        .bind(repo_id.value())
-        .bind(stage_history_id)
-        .fetch_all(self.pool())
+        let rows = self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT sa.id, sa.stage_history_id, sa.artifact_type, sa.content, sa.metadata, sa.created_at, sa.content_hash
+                 FROM stage_artifacts sa
+                 JOIN stage_history sh ON sh.id = sa.stage_history_id
+                 WHERE sh.repo_id = $1 AND sa.stage_history_id = $2
+                 ORDER BY sa.created_at ASC, sa.id ASC",
+                repo_id.value(),
+                stage_history_id,
+            )
+            .map(|row| -> ArtifactRow {
+                (
+                    row.id,
+                    row.stage_history_id,
+                    row.artifact_type,
+                    row.content,
+                    row.metadata,
+                    row.created_at,
+                    row.content_hash,
+                )
+            })
+            .fetch_all(pool)
+        })
         .await
         .map_err(|error| SwarmError::DatabaseError(format!("Failed to load stage artifacts: {error}")))?;
 
-        rows.into_iter()
-            .map(
-                |(
-                    id,
-                    stage_history_id,
-                    artifact_type,
-                    content,
-                    metadata,
-                    created_at,
-                    content_hash,
-                )| {
-                    let artifact_type = ArtifactType::try_from(artifact_type.as_str())
-                        .map_err(SwarmError::DatabaseError)?;
-                    Ok(StageArtifact {
-                        id,
-                        stage_history_id,
-                        artifact_type,
-                        content,
-                        metadata,
-                        created_at,
-                        content_hash,
-                    })
-                },
-            )
-            .collect()
+        rows.into_iter().map(to_stage_artifact).collect()
     }
 
     /// # Errors
@@ -69,56 +113,34 @@ impl SwarmDb {
         bead_id: &BeadId,
         artifact_type: ArtifactType,
     ) -> Result<Vec<StageArtifact>> {
-        let rows = sqlx::query_as::<
-            _,
-            (
-                i64,
-                i64,
-                String,
-                String,
-                Option<serde_json::Value>,
-                chrono::DateTime<chrono::Utc>,
-                Option<String>,
-            ),
-        >(
-            "SELECT sa.id, sa.stage_history_id, sa.artifact_type, sa.content, sa.metadata, sa.created_at, sa.content_hash
-             FROM stage_artifacts sa
-             JOIN stage_history sh ON sh.id = sa.stage_history_id
-             WHERE sh.repo_id = $1 AND sh.bead_id = $2 AND sa.artifact_type = $3
-             ORDER BY sa.created_at ASC, sa.id ASC",
-        )
-        .bind(repo_id.value())
-        .bind(bead_id.value())
-        .bind(artifact_type.as_str())
-        .fetch_all(self.pool())
+        let rows = self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT sa.id, sa.stage_history_id, sa.artifact_type, sa.content, sa.metadata, sa.created_at, sa.content_hash
+                 FROM stage_artifacts sa
+                 JOIN stage_history sh ON sh.id = sa.stage_history_id
+                 WHERE sh.repo_id = $1 AND sh.bead_id = $2 AND sa.artifact_type = $3
+                 ORDER BY sa.created_at ASC, sa.id ASC",
+                repo_id.value(),
+                bead_id.value(),
+                artifact_type.as_str(),
+            )
+            .map(|row| -> ArtifactRow {
+                (
+                    row.id,
+                    row.stage_history_id,
+                    row.artifact_type,
+                    row.content,
+                    row.metadata,
+                    row.created_at,
+                    row.content_hash,
+                )
+            })
+            .fetch_all(pool)
+        })
         .await
         .map_err(|error| SwarmError::DatabaseError(format!("Failed to load bead artifacts: {error}")))?;
 
-        rows.into_iter()
-            .map(
-                |(
-                    id,
-                    stage_history_id,
-                    artifact_type,
-                    content,
-                    metadata,
-                    created_at,
-                    content_hash,
-                )| {
-                    let artifact_type = ArtifactType::try_from(artifact_type.as_str())
-                        .map_err(SwarmError::DatabaseError)?;
-                    Ok(StageArtifact {
-                        id,
-                        stage_history_id,
-                        artifact_type,
-                        content,
-                        metadata,
-                        created_at,
-                        content_hash,
-                    })
-                },
-            )
-            .collect()
+        rows.into_iter().map(to_stage_artifact).collect()
     }
 
     /// # Errors
@@ -161,18 +183,20 @@ impl SwarmDb {
         bead_id: &BeadId,
         artifact_type: ArtifactType,
     ) -> Result<bool> {
-        sqlx::query_scalar::<_, bool>(
-            "SELECT EXISTS(
-                 SELECT 1
-                 FROM stage_artifacts sa
-                 JOIN stage_history sh ON sh.id = sa.stage_history_id
-                 WHERE sh.repo_id = $1 AND sh.bead_id = $2 AND sa.artifact_type = $3
-             )",
-        )
-        .bind(repo_id.value())
-        .bind(bead_id.value())
-        .bind(artifact_type.as_str())
-        .fetch_one(self.pool())
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+                r#"SELECT EXISTS(
+                     SELECT 1
+                     FROM stage_artifacts sa
+                     JOIN stage_history sh ON sh.id = sa.stage_history_id
+                     WHERE sh.repo_id = $1 AND sh.bead_id = $2 AND sa.artifact_type = $3
+                 ) AS "present!""#,
+                repo_id.value(),
+                bead_id.value(),
+                artifact_type.as_str(),
+            )
+            .fetch_one(pool)
+        })
         .await
         .map_err(|error| {
             SwarmError::DatabaseError(format!("Failed to inspect artifact presence: {error}"))
@@ -187,73 +211,104 @@ impl SwarmDb {
         bead_id: &BeadId,
         artifact_type: Option<ArtifactType>,
     ) -> Result<Vec<StageArtifact>> {
-        artifact_type.map_or_else(
-            || {
-                sqlx::query_as::<
-                    _,
-                    (
-                        i64,
-                        i64,
-                        String,
-                        String,
-                        Option<serde_json::Value>,
-                        chrono::DateTime<chrono::Utc>,
-                        Option<String>,
-                    ),
-                >(
-                    "SELECT sa.id, sa.stage_history_id, sa.artifact_type, sa.content, sa.metadata, sa.created_at, sa.content_hash
-                     FROM stage_artifacts sa
-                     JOIN stage_history sh ON sh.id = sa.stage_history_id
-                     WHERE sh.repo_id = $1 AND sh.bead_id = $2
-                     ORDER BY sa.created_at ASC, sa.id ASC",
-                )
-                .bind(repo_id.value())
-                .bind(bead_id.value())
-                .fetch_all(self.pool())
-            },
-            |kind| {
-                sqlx::query_as::<
-                    _,
-                    (
-                        i64,
-                        i64,
-                        String,
-                        String,
-                        Option<serde_json::Value>,
-                        chrono::DateTime<chrono::Utc>,
-                        Option<String>,
-                    ),
-                >(
-                    "SELECT sa.id, sa.stage_history_id, sa.artifact_type, sa.content, sa.metadata, sa.created_at, sa.content_hash
-                     FROM stage_artifacts sa
-                     JOIN stage_history sh ON sh.id = sa.stage_history_id
-                     WHERE sh.repo_id = $1 AND sh.bead_id = $2 AND sa.artifact_type = $3
-                     ORDER BY sa.created_at ASC, sa.id ASC",
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT sa.id, sa.stage_history_id, sa.artifact_type, sa.content, sa.metadata, sa.created_at, sa.content_hash
+                 FROM stage_artifacts sa
+                 JOIN stage_history sh ON sh.id = sa.stage_history_id
+                 WHERE sh.repo_id = $1 AND sh.bead_id = $2
+                   AND ($3::TEXT IS NULL OR sa.artifact_type = $3)
+                 ORDER BY sa.created_at ASC, sa.id ASC",
+                repo_id.value(),
+                bead_id.value(),
+                artifact_type.as_ref().map(ArtifactType::as_str),
+            )
+            .map(|row| -> ArtifactRow {
+                (
+                    row.id,
+                    row.stage_history_id,
+                    row.artifact_type,
+                    row.content,
+                    row.metadata,
+                    row.created_at,
+                    row.content_hash,
                 )
-                .bind(repo_id.value())
-                .bind(bead_id.value())
-                .bind(kind.as_str())
-                .fetch_all(self.pool())
-            },
-        )
+            })
+            .fetch_all(pool)
+        })
         .await
         .map_err(|error| SwarmError::DatabaseError(format!("Failed to load bead artifacts: {error}")))?
         .into_iter()
-        .map(
-            |(id, stage_history_id, artifact_type, content, metadata, created_at, content_hash)| {
-                let artifact_type = ArtifactType::try_from(artifact_type.as_str())
-                    .map_err(SwarmError::DatabaseError)?;
-                Ok(StageArtifact {
-                    id,
-                    stage_history_id,
-                    artifact_type,
-                    content,
-                    metadata,
-                    created_at,
-                    content_hash,
-                })
-            },
+        .map(to_stage_artifact)
+        .collect()
+    }
+
+    /// Distinct artifact types stored on any of a bead's stage runs. Reads the
+    /// primary so artifacts stored while landing are seen.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_bead_artifact_types(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+    ) -> Result<Vec<ArtifactType>> {
+        sqlx::query_scalar!(
+            "SELECT DISTINCT sa.artifact_type
+             FROM stage_artifacts sa
+             JOIN stage_history sh ON sh.id = sa.stage_history_id
+             WHERE sh.repo_id = $1 AND sh.bead_id = $2
+             ORDER BY sa.artifact_type",
+            repo_id.value(),
+            bead_id.value(),
         )
+        .fetch_all(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load artifact types: {e}")))?
+        .iter()
+        .map(|artifact_type| {
+            ArtifactType::try_from(artifact_type.as_str()).map_err(SwarmError::SchemaDrift)
+        })
         .collect()
     }
+
+    /// A bead's artifacts with their signatures, in the order they were
+    /// stored, which is the order the signature chain links them.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_bead_signed_artifacts(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+    ) -> Result<Vec<SignedArtifactRecord>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT sa.id, sa.stage_history_id, sa.artifact_type, sa.content, sa.content_hash,
+                        sa.signature, sa.signing_key
+                 FROM stage_artifacts sa
+                 JOIN stage_history sh ON sh.id = sa.stage_history_id
+                 WHERE sh.repo_id = $1 AND sh.bead_id = $2
+                 ORDER BY sa.id ASC",
+                repo_id.value(),
+                bead_id.value(),
+            )
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| SignedArtifactRecord {
+                    artifact_id: row.id,
+                    stage_history_id: row.stage_history_id,
+                    artifact_type: row.artifact_type,
+                    content: row.content,
+                    content_hash: row.content_hash,
+                    signature: row.signature,
+                    signing_key: row.signing_key,
+                })
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load signed artifacts: {e}")))
+    }
 }
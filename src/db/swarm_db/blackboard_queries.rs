@@ -0,0 +1,78 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{Blackboard, BlackboardEntry, BlackboardSection, RepoId};
+use chrono::{DateTime, Utc};
+
+pub type BlackboardRow = (i64, String, i32, String, DateTime<Utc>);
+
+pub fn to_blackboard_entry(
+    (version, section, agent_id, content, created_at): BlackboardRow,
+) -> Result<BlackboardEntry> {
+    Ok(BlackboardEntry {
+        version,
+        section: BlackboardSection::try_from(section.as_str()).map_err(SwarmError::SchemaDrift)?,
+        agent_id: agent_id.max(0).cast_unsigned(),
+        content,
+        created_at,
+    })
+}
+
+impl SwarmDb {
+    /// The bead's current blackboard version; 0 before the first append.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_blackboard_version(&self, repo_id: &RepoId, bead_id: &str) -> Result<i64> {
+        sqlx::query_scalar!(
+            r#"SELECT COALESCE(MAX(version), 0)::BIGINT AS "version!"
+             FROM bead_blackboard
+             WHERE repo_id = $1 AND bead_id = $2"#,
+            repo_id.value(),
+            bead_id,
+        )
+        .fetch_one(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load blackboard version: {e}")))
+    }
+
+    /// The bead's blackboard, limited to `section` when given. `version` is
+    /// always the whole blackboard's and is read before the entries, so an
+    /// append racing this read makes the caller's next append conflict
+    /// rather than skip an entry it never saw.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_blackboard(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &str,
+        section: Option<BlackboardSection>,
+    ) -> Result<Blackboard> {
+        let version = self.get_blackboard_version(repo_id, bead_id).await?;
+        let entries = sqlx::query!(
+            "SELECT version, section, agent_id, content, created_at
+             FROM bead_blackboard
+             WHERE repo_id = $1 AND bead_id = $2 AND ($3::TEXT IS NULL OR section = $3)
+             ORDER BY version",
+            repo_id.value(),
+            bead_id,
+            section.as_ref().map(BlackboardSection::as_str),
+        )
+        .map(|row| -> BlackboardRow {
+            (
+                row.version,
+                row.section,
+                row.agent_id,
+                row.content,
+                row.created_at,
+            )
+        })
+        .fetch_all(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load blackboard: {e}")))?
+        .into_iter()
+        .map(to_blackboard_entry)
+        .collect::<Result<Vec<_>>>()?;
+        Ok(Blackboard::from_entries(bead_id, version, entries))
+    }
+}
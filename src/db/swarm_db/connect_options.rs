@@ -0,0 +1,281 @@
+//! Connection settings for managed Postgres: TLS, a statement timeout, and
+//! short-lived auth tokens (e.g. RDS IAM) in place of a stored password.
+
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use sqlx::postgres::{PgConnectOptions, PgSslMode};
+use tokio::process::Command;
+
+use crate::error::{Result, SwarmError};
+
+pub type AuthTokenFuture<'a> = Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+
+/// How long a fetched token is used for new connections before it is
+/// fetched again. RDS IAM tokens last 15 minutes.
+pub const DEFAULT_TOKEN_REFRESH: Duration = Duration::from_mins(10);
+
+/// `sslmode`, with libpq's names and meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SslMode {
+    Disable,
+    Allow,
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+impl SslMode {
+    /// # Errors
+    /// Returns `SwarmError::ConfigError` for anything but a libpq `sslmode`.
+    pub fn parse(raw: &str) -> Result<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "disable" => Ok(Self::Disable),
+            "allow" => Ok(Self::Allow),
+            "prefer" => Ok(Self::Prefer),
+            "require" => Ok(Self::Require),
+            "verify-ca" => Ok(Self::VerifyCa),
+            "verify-full" => Ok(Self::VerifyFull),
+            other => Err(SwarmError::ConfigError(format!(
+                "Invalid sslmode {other}: expected disable, allow, prefer, require, verify-ca, or verify-full"
+            ))),
+        }
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Disable => "disable",
+            Self::Allow => "allow",
+            Self::Prefer => "prefer",
+            Self::Require => "require",
+            Self::VerifyCa => "verify-ca",
+            Self::VerifyFull => "verify-full",
+        }
+    }
+
+    const fn to_pg(self) -> PgSslMode {
+        match self {
+            Self::Disable => PgSslMode::Disable,
+            Self::Allow => PgSslMode::Allow,
+            Self::Prefer => PgSslMode::Prefer,
+            Self::Require => PgSslMode::Require,
+            Self::VerifyCa => PgSslMode::VerifyCa,
+            Self::VerifyFull => PgSslMode::VerifyFull,
+        }
+    }
+}
+
+/// The server and role a token is requested for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthTarget {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub database: Option<String>,
+}
+
+/// Supplies the password for each new connection. Implement it to plug in a
+/// cloud SDK; [`CommandTokenProvider`] covers anything with a CLI.
+pub trait AuthTokenProvider: Send + Sync {
+    fn token<'a>(&'a self, target: &'a AuthTarget) -> AuthTokenFuture<'a>;
+
+    /// How long one token is used before the pool fetches another.
+    fn refresh_interval(&self) -> Duration {
+        DEFAULT_TOKEN_REFRESH
+    }
+}
+
+/// Runs a shell command and uses its trimmed stdout as the password.
+///
+/// The command sees the target as `PGHOST`, `PGPORT`, `PGUSER` and `PGDATABASE`,
+/// e.g. `aws rds generate-db-auth-token --hostname "$PGHOST" --port "$PGPORT"
+/// --username "$PGUSER"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandTokenProvider {
+    command: String,
+}
+
+impl CommandTokenProvider {
+    #[must_use]
+    pub const fn new(command: String) -> Self {
+        Self { command }
+    }
+}
+
+impl AuthTokenProvider for CommandTokenProvider {
+    fn token<'a>(&'a self, target: &'a AuthTarget) -> AuthTokenFuture<'a> {
+        Box::pin(async move {
+            let output = Command::new("sh")
+                .args(["-c", self.command.as_str()])
+                .env("PGHOST", &target.host)
+                .env("PGPORT", target.port.to_string())
+                .env("PGUSER", &target.user)
+                .env("PGDATABASE", target.database.as_deref().unwrap_or_default())
+                .stdin(Stdio::null())
+                .kill_on_drop(true)
+                .output()
+                .await
+                .map_err(|e| {
+                    SwarmError::DatabaseError(format!("Failed to run auth token command: {e}"))
+                })?;
+            if !output.status.success() {
+                return Err(SwarmError::DatabaseError(format!(
+                    "Auth token command failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if token.is_empty() {
+                return Err(SwarmError::DatabaseError(
+                    "Auth token command printed no token".to_string(),
+                ));
+            }
+            Ok(token)
+        })
+    }
+}
+
+/// Settings applied on top of the database URL for every connection.
+/// Anything unset keeps what the URL says.
+#[derive(Clone, Default)]
+pub struct ConnectOptions {
+    pub ssl_mode: Option<SslMode>,
+    /// CA bundle the server certificate is checked against.
+    pub ssl_root_cert: Option<PathBuf>,
+    /// Server-side limit on any one statement.
+    pub statement_timeout_ms: Option<u64>,
+    pub auth_token: Option<Arc<dyn AuthTokenProvider>>,
+}
+
+impl fmt::Debug for ConnectOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectOptions")
+            .field("ssl_mode", &self.ssl_mode)
+            .field("ssl_root_cert", &self.ssl_root_cert)
+            .field("statement_timeout_ms", &self.statement_timeout_ms)
+            .field("auth_token", &self.auth_token.is_some())
+            .finish()
+    }
+}
+
+impl ConnectOptions {
+    /// `options` with these settings applied.
+    #[must_use]
+    pub fn apply(&self, options: PgConnectOptions) -> PgConnectOptions {
+        let options = match self.ssl_mode {
+            Some(mode) => options.ssl_mode(mode.to_pg()),
+            None => options,
+        };
+        let options = match &self.ssl_root_cert {
+            Some(path) => options.ssl_root_cert(path),
+            None => options,
+        };
+        match self.statement_timeout_ms {
+            Some(ms) => options.options([("statement_timeout", ms.to_string())]),
+            None => options,
+        }
+    }
+
+    /// Distinguishes pools opened with different settings when they are
+    /// cached by URL.
+    #[must_use]
+    pub fn cache_key(&self) -> String {
+        format!(
+            "{}|{}|{}|{}",
+            self.ssl_mode.map_or("", SslMode::as_str),
+            self.ssl_root_cert
+                .as_deref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            self.statement_timeout_ms.unwrap_or_default(),
+            self.auth_token.is_some()
+        )
+    }
+}
+
+/// The `[database]` table of `.swarm/config.toml`:
+///
+/// ```toml
+/// [database]
+/// sslmode = "verify-full"
+/// ssl_root_cert = "/etc/ssl/rds-global-bundle.pem"
+/// statement_timeout_ms = 30000
+/// auth_token_command = "aws rds generate-db-auth-token --hostname \"$PGHOST\" --port \"$PGPORT\" --username \"$PGUSER\""
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub sslmode: Option<String>,
+    pub ssl_root_cert: Option<PathBuf>,
+    pub statement_timeout_ms: Option<u64>,
+    pub auth_token_command: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SwarmConfigFile {
+    #[serde(default)]
+    database: DatabaseConfig,
+}
+
+impl DatabaseConfig {
+    /// The `[database]` table of a whole `config.toml`; other tables are
+    /// ignored and a missing table means defaults.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::ConfigError` if the file is not valid TOML or the
+    /// table has unknown keys.
+    pub fn from_config_toml(raw: &str) -> Result<Self> {
+        toml::from_str::<SwarmConfigFile>(raw)
+            .map(|file| file.database)
+            .map_err(|e| SwarmError::ConfigError(format!("Invalid [database] config: {e}")))
+    }
+
+    /// # Errors
+    /// Returns `SwarmError::ConfigError` if `sslmode` is invalid.
+    pub fn into_connect_options(self) -> Result<ConnectOptions> {
+        Ok(ConnectOptions {
+            ssl_mode: self.sslmode.as_deref().map(SslMode::parse).transpose()?,
+            ssl_root_cert: self.ssl_root_cert,
+            statement_timeout_ms: self.statement_timeout_ms,
+            auth_token: self
+                .auth_token_command
+                .as_deref()
+                .map(str::trim)
+                .filter(|command| !command.is_empty())
+                .map(|command| {
+                    Arc::new(CommandTokenProvider::new(command.to_string()))
+                        as Arc<dyn AuthTokenProvider>
+                }),
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::{DatabaseConfig, SslMode};
+
+    #[test]
+    fn given_database_table_when_parsing_config_then_options_are_read_and_other_tables_ignored() {
+        let config = DatabaseConfig::from_config_toml(
+            "[swarm]\nname = \"x\"\n\n[database]\nsslmode = \"verify-full\"\nstatement_timeout_ms = 5000\nauth_token_command = \"echo t\"\n",
+        )
+        .expect("config parses");
+
+        let options = config.into_connect_options().expect("options are valid");
+
+        assert_eq!(options.ssl_mode, Some(SslMode::VerifyFull));
+        assert_eq!(options.statement_timeout_ms, Some(5000));
+        assert!(options.auth_token.is_some());
+        assert!(DatabaseConfig::from_config_toml("[database]\nsslmod = \"require\"\n").is_err());
+        assert!(SslMode::parse("strict").is_err());
+    }
+}
@@ -1,21 +1,58 @@
-use sqlx::postgres::PgPoolOptions;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
 use sqlx::PgPool;
 use std::collections::HashMap;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use super::connect_options::{AuthTarget, AuthTokenProvider, ConnectOptions};
+use crate::db::write_batcher::WriteBatchHandle;
 use crate::error::{Result, SwarmError};
+use crate::signing::ArtifactSigner;
+use crate::summarizer::StageSummarizer;
+
+/// How often an unreachable read replica is probed until it answers again.
+const REPLICA_PROBE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Stops a pool's token refresh when the last handle on it is dropped.
+struct TokenRefresh(tokio::task::JoinHandle<()>);
+
+impl Drop for TokenRefresh {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
 
 pub struct SwarmDb {
     pool: PgPool,
+    read_pool: Option<PgPool>,
+    /// Cleared when a read on the replica cannot reach it; reads use the
+    /// primary until the replica probe sets it again. Shared by clones.
+    replica_healthy: Arc<AtomicBool>,
+    write_batch: Option<WriteBatchHandle>,
+    artifact_signer: Option<Arc<ArtifactSigner>>,
+    stage_summarizer: Arc<StageSummarizer>,
     schema_cache: Arc<Mutex<HashMap<(String, String), bool>>>,
+    schema: Option<String>,
+    connect_options: ConnectOptions,
+    token_refresh: Vec<Arc<TokenRefresh>>,
 }
 
 impl Clone for SwarmDb {
     fn clone(&self) -> Self {
         Self {
             pool: self.pool.clone(),
+            read_pool: self.read_pool.clone(),
+            replica_healthy: Arc::clone(&self.replica_healthy),
+            write_batch: self.write_batch.clone(),
+            artifact_signer: self.artifact_signer.clone(),
+            stage_summarizer: Arc::clone(&self.stage_summarizer),
             schema_cache: Arc::clone(&self.schema_cache),
+            schema: self.schema.clone(),
+            connect_options: self.connect_options.clone(),
+            token_refresh: self.token_refresh.clone(),
         }
     }
 }
@@ -32,35 +69,336 @@ impl SwarmDb {
     pub async fn new_with_timeout(
         connection_string: &str,
         timeout_ms: Option<u64>,
+    ) -> Result<Self> {
+        Self::connect(
+            connection_string,
+            None,
+            timeout_ms,
+            &ConnectOptions::default(),
+        )
+        .await
+    }
+
+    /// Connects with `options` layered over the URL: TLS, a statement
+    /// timeout, or a token provider that supplies the password and is asked
+    /// again every [`AuthTokenProvider::refresh_interval`] for new
+    /// connections. With `schema`, queries are scoped to it as with
+    /// [`Self::open_in_schema`]; nothing is created.
+    ///
+    /// # Errors
+    /// Returns an error if `schema` is not a valid schema name, the token
+    /// cannot be fetched, or the database connection fails.
+    pub async fn new_with_options(
+        connection_string: &str,
+        schema: Option<&str>,
+        timeout_ms: Option<u64>,
+        options: &ConnectOptions,
+    ) -> Result<Self> {
+        if let Some(schema) = schema {
+            validate_schema_name(schema)?;
+        }
+        Self::connect(connection_string, schema, timeout_ms, options).await
+    }
+
+    /// Connects with every query scoped to the Postgres schema `schema`,
+    /// creating it when missing. Extensions keep resolving from `public`.
+    /// Several swarms, or concurrent test runs, can then share one database.
+    ///
+    /// # Errors
+    /// Returns an error if `schema` is not a valid schema name or the
+    /// database connection fails.
+    pub async fn new_in_schema(
+        connection_string: &str,
+        schema: &str,
+        timeout_ms: Option<u64>,
+    ) -> Result<Self> {
+        let db = Self::open_in_schema(connection_string, schema, timeout_ms).await?;
+        db.create_schema().await?;
+        Ok(db)
+    }
+
+    /// Creates the schema this handle is scoped to when it is missing.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn create_schema(&self) -> Result<()> {
+        let Some(schema) = self.schema() else {
+            return Ok(());
+        };
+        sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS \"{schema}\""))
+            .execute(self.pool())
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to create schema {schema}: {e}"))
+            })
+    }
+
+    /// Like [`Self::new_in_schema`] for a schema that already exists;
+    /// nothing is created.
+    ///
+    /// # Errors
+    /// Returns an error if `schema` is not a valid schema name or the
+    /// database connection fails.
+    pub async fn open_in_schema(
+        connection_string: &str,
+        schema: &str,
+        timeout_ms: Option<u64>,
+    ) -> Result<Self> {
+        validate_schema_name(schema)?;
+        Self::connect(
+            connection_string,
+            Some(schema),
+            timeout_ms,
+            &ConnectOptions::default(),
+        )
+        .await
+    }
+
+    async fn connect(
+        connection_string: &str,
+        schema: Option<&str>,
+        timeout_ms: Option<u64>,
+        connect_options: &ConnectOptions,
     ) -> Result<Self> {
         let connect_timeout = Duration::from_millis(timeout_ms.unwrap_or(3_000));
-        PgPoolOptions::new()
+        let options = PgConnectOptions::from_str(connection_string).map_err(|error| {
+            SwarmError::DatabaseError(format!("Failed to connect to database: {error}"))
+        })?;
+        let options = match schema {
+            Some(schema) => options.options([("search_path", format!("{schema},public"))]),
+            None => options,
+        };
+        let options = connect_options.apply(options);
+        let token = match &connect_options.auth_token {
+            Some(provider) => {
+                let target = auth_target(&options);
+                let token = provider.token(&target).await?;
+                Some((Arc::clone(provider), target, token))
+            }
+            None => None,
+        };
+        let first_options = match &token {
+            Some((_, _, token)) => options.clone().password(token),
+            None => options.clone(),
+        };
+        let pool = PgPoolOptions::new()
             .max_connections(20)
             .acquire_timeout(connect_timeout)
-            .connect(connection_string)
+            .test_before_acquire(true)
+            .connect_with(first_options)
             .await
-            .map(|pool| Self {
-                pool,
-                schema_cache: Arc::new(Mutex::new(HashMap::new())),
-            })
             .map_err(|error| {
                 SwarmError::DatabaseError(format!("Failed to connect to database: {error}"))
+            })?;
+        let token_refresh = token
+            .map(|(provider, target, _)| {
+                Arc::new(spawn_token_refresh(pool.clone(), options, provider, target))
             })
+            .into_iter()
+            .collect();
+        Ok(Self {
+            pool,
+            read_pool: None,
+            replica_healthy: Arc::new(AtomicBool::new(true)),
+            write_batch: None,
+            artifact_signer: None,
+            stage_summarizer: Arc::new(StageSummarizer::default()),
+            schema_cache: Arc::new(Mutex::new(HashMap::new())),
+            schema: schema.map(str::to_string),
+            connect_options: connect_options.clone(),
+            token_refresh,
+        })
     }
 
     #[must_use]
     pub fn new_with_pool(pool: PgPool) -> Self {
         Self {
             pool,
+            read_pool: None,
+            replica_healthy: Arc::new(AtomicBool::new(true)),
+            write_batch: None,
+            artifact_signer: None,
+            stage_summarizer: Arc::new(StageSummarizer::default()),
             schema_cache: Arc::new(Mutex::new(HashMap::new())),
+            schema: None,
+            connect_options: ConnectOptions::default(),
+            token_refresh: Vec::new(),
+        }
+    }
+
+    /// Attaches a read replica for read-only queries, connected with the
+    /// primary's options. When the replica cannot be reached the primary
+    /// keeps serving reads. A replica that goes away later is caught by
+    /// [`Self::read_with_fallback`]; reads then use the primary until
+    /// [`Self::probe_read_replica`] reaches it again.
+    pub async fn with_read_replica(mut self, replica_url: &str, timeout_ms: Option<u64>) -> Self {
+        match Self::connect(
+            replica_url,
+            self.schema.as_deref(),
+            timeout_ms,
+            &self.connect_options,
+        )
+        .await
+        {
+            Ok(replica) => {
+                self.token_refresh.extend(replica.token_refresh);
+                Self {
+                    read_pool: Some(replica.pool),
+                    ..self
+                }
+            }
+            Err(_) => self,
+        }
+    }
+
+    /// The options this handle connected with.
+    #[must_use]
+    pub const fn connect_options(&self) -> &ConnectOptions {
+        &self.connect_options
+    }
+
+    /// Routes batchable inserts (execution events) through a write-behind batcher.
+    #[must_use]
+    pub fn with_write_batch(self, handle: WriteBatchHandle) -> Self {
+        Self {
+            write_batch: Some(handle),
+            ..self
         }
     }
 
+    #[must_use]
+    pub const fn write_batch(&self) -> Option<&WriteBatchHandle> {
+        self.write_batch.as_ref()
+    }
+
+    /// Signs every artifact stored through this handle with `signer`.
+    #[must_use]
+    pub fn with_artifact_signer(self, signer: ArtifactSigner) -> Self {
+        Self {
+            artifact_signer: Some(Arc::new(signer)),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn artifact_signer(&self) -> Option<&ArtifactSigner> {
+        self.artifact_signer.as_deref()
+    }
+
+    /// Summarizes completed stages with `summarizer` instead of the
+    /// built-in heuristics alone.
+    #[must_use]
+    pub fn with_stage_summarizer(self, summarizer: StageSummarizer) -> Self {
+        Self {
+            stage_summarizer: Arc::new(summarizer),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn stage_summarizer(&self) -> &StageSummarizer {
+        &self.stage_summarizer
+    }
+
+    /// The Postgres schema queries run in, when not the default `public`.
+    #[must_use]
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+
     #[must_use]
     pub const fn pool(&self) -> &PgPool {
         &self.pool
     }
 
+    /// Pool for read-only queries: the replica when attached and reachable,
+    /// otherwise the primary.
+    #[must_use]
+    pub fn read_pool(&self) -> &PgPool {
+        self.read_pool
+            .as_ref()
+            .filter(|_| self.replica_healthy.load(Ordering::Acquire))
+            .unwrap_or(&self.pool)
+    }
+
+    /// Runs `read` on the same pool as [`Self::read_pool`]. When that is the
+    /// replica and it cannot be reached, the replica is marked down (see
+    /// [`Self::note_read_error`]) and `read` runs again on the primary, so
+    /// the read that finds the replica gone still succeeds.
+    ///
+    /// # Errors
+    /// Returns the error of the last attempt.
+    pub async fn read_with_fallback<'a, T, F, Fut>(&'a self, read: F) -> sqlx::Result<T>
+    where
+        F: Fn(&'a PgPool) -> Fut,
+        Fut: Future<Output = sqlx::Result<T>>,
+    {
+        let Some(replica) = self
+            .read_pool
+            .as_ref()
+            .filter(|_| self.replica_healthy.load(Ordering::Acquire))
+        else {
+            return read(&self.pool).await;
+        };
+        match read(replica).await {
+            Err(error) if is_connection_error(&error) => {
+                self.note_read_error(&error);
+                read(&self.pool).await
+            }
+            result => result,
+        }
+    }
+
+    #[must_use]
+    pub const fn has_read_replica(&self) -> bool {
+        self.read_pool.is_some()
+    }
+
+    /// Whether reads currently go to the replica.
+    #[must_use]
+    pub fn read_replica_healthy(&self) -> bool {
+        self.has_read_replica() && self.replica_healthy.load(Ordering::Acquire)
+    }
+
+    /// Called with the error of a read on [`Self::read_pool`]. One that says
+    /// the replica could not be reached moves reads to the primary and
+    /// starts probing the replica every [`REPLICA_PROBE_INTERVAL`] until it
+    /// answers.
+    pub fn note_read_error(&self, error: &sqlx::Error) {
+        let Some(replica) = &self.read_pool else {
+            return;
+        };
+        if !is_connection_error(error) || !self.replica_healthy.swap(false, Ordering::AcqRel) {
+            return;
+        }
+        tracing::warn!(%error, "Read replica unreachable; reading from the primary");
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let replica = replica.clone();
+            let healthy = Arc::clone(&self.replica_healthy);
+            runtime.spawn(async move {
+                while !healthy.load(Ordering::Acquire) && !replica.is_closed() {
+                    tokio::time::sleep(REPLICA_PROBE_INTERVAL).await;
+                    if replica_answers(&replica).await {
+                        healthy.store(true, Ordering::Release);
+                        tracing::info!("Read replica reachable again");
+                    }
+                }
+            });
+        }
+    }
+
+    /// Asks the replica for `SELECT 1` and routes reads by the answer.
+    /// Returns whether reads now go to the replica; `false` without one.
+    pub async fn probe_read_replica(&self) -> bool {
+        let Some(replica) = &self.read_pool else {
+            return false;
+        };
+        let answered = replica_answers(replica).await;
+        self.replica_healthy.store(answered, Ordering::Release);
+        answered
+    }
+
     #[must_use]
     pub fn check_schema_cache(&self, table_name: &str, column_name: &str) -> Option<bool> {
         let cache = self.schema_cache.lock().ok()?;
@@ -84,17 +422,17 @@ impl SwarmDb {
             return Ok(cached);
         }
 
-        let result = sqlx::query_scalar::<_, bool>(
-            "SELECT EXISTS (
+        let result = sqlx::query_scalar!(
+            r#"SELECT EXISTS (
                 SELECT 1
                 FROM information_schema.columns
-                WHERE table_schema = 'public'
+                WHERE table_schema = current_schema()
                   AND table_name = $1
                   AND column_name = $2
-            )",
+            ) AS "exists!""#,
+            table_name,
+            column_name,
         )
-        .bind(table_name)
-        .bind(column_name)
         .fetch_one(self.pool())
         .await
         .map_err(|e| {
@@ -107,3 +445,104 @@ impl SwarmDb {
         Ok(result)
     }
 }
+
+fn auth_target(options: &PgConnectOptions) -> AuthTarget {
+    AuthTarget {
+        host: options.get_host().to_string(),
+        port: options.get_port(),
+        user: options.get_username().to_string(),
+        database: options.get_database().map(str::to_string),
+    }
+}
+
+/// Whether the replica answers a `SELECT 1` health probe.
+async fn replica_answers(replica: &PgPool) -> bool {
+    sqlx::query("SELECT 1").execute(replica).await.is_ok()
+}
+
+/// Errors that mean the server could not be reached, as opposed to a query
+/// it rejected.
+const fn is_connection_error(error: &sqlx::Error) -> bool {
+    matches!(
+        error,
+        sqlx::Error::Io(_)
+            | sqlx::Error::Tls(_)
+            | sqlx::Error::Protocol(_)
+            | sqlx::Error::PoolTimedOut
+            | sqlx::Error::PoolClosed
+            | sqlx::Error::WorkerCrashed
+    )
+}
+
+/// Fetches a fresh token every refresh interval and hands it to the pool for
+/// the connections it opens next; open connections keep the token they
+/// authenticated with. A failed fetch keeps the previous token.
+fn spawn_token_refresh(
+    pool: PgPool,
+    options: PgConnectOptions,
+    provider: Arc<dyn AuthTokenProvider>,
+    target: AuthTarget,
+) -> TokenRefresh {
+    TokenRefresh(tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(provider.refresh_interval()).await;
+            if pool.is_closed() {
+                break;
+            }
+            if let Ok(token) = provider.token(&target).await {
+                pool.set_connect_options(options.clone().password(&token));
+            }
+        }
+    }))
+}
+
+/// Accepts lowercase names of letters, digits and underscores that Postgres
+/// would not truncate and that are not reserved for system schemas.
+///
+/// # Errors
+/// Returns `SwarmError::ConfigError` describing why `schema` is rejected.
+pub fn validate_schema_name(schema: &str) -> Result<()> {
+    let well_formed = schema.len() <= 63
+        && schema
+            .chars()
+            .next()
+            .is_some_and(|first| first.is_ascii_lowercase() || first == '_')
+        && schema
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !well_formed {
+        return Err(SwarmError::ConfigError(format!(
+            "schema name {schema:?} must be 1-63 lowercase letters, digits or underscores, not starting with a digit"
+        )));
+    }
+    if schema.starts_with("pg_") || schema == "information_schema" {
+        return Err(SwarmError::ConfigError(format!(
+            "schema name {schema} is reserved by Postgres"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_schema_name;
+
+    #[test]
+    fn given_schema_names_when_validating_then_only_plain_lowercase_identifiers_pass() {
+        for valid in ["team_a", "_scratch", "swarm_test_0f3a"] {
+            assert!(validate_schema_name(valid).is_ok(), "{valid}");
+        }
+        let too_long = "x".repeat(64);
+        for invalid in [
+            "",
+            "Team",
+            "1st",
+            "a-b",
+            "a\"b",
+            "pg_temp",
+            too_long.as_str(),
+        ] {
+            assert!(validate_schema_name(invalid).is_err(), "{invalid}");
+        }
+    }
+}
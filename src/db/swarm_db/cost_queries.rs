@@ -0,0 +1,169 @@
+use chrono::{DateTime, Utc};
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{BeadId, RepoId, StageCompute, TokenUsage, TokenUsageRecord};
+
+/// Window for `swarm costs`. `since`/`until` bound `token_usage.recorded_at`
+/// and `stage_history.completed_at` inclusively.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CostQuery<'a> {
+    pub bead_filter: Option<&'a str>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+type TokenUsageRow = (
+    i64,
+    i32,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    i64,
+    i64,
+    Option<String>,
+    DateTime<Utc>,
+);
+
+impl SwarmDb {
+    /// Token usage recorded in `repo_id` inside the query window, oldest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_token_usage(
+        &self,
+        repo_id: &RepoId,
+        query: &CostQuery<'_>,
+    ) -> Result<Vec<TokenUsageRecord>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT id, agent_id, bead_id, stage, model, input_tokens, output_tokens, description, recorded_at
+                 FROM token_usage
+                 WHERE repo_id = $1
+                   AND ($2::TEXT IS NULL OR bead_id = $2)
+                   AND ($3::TIMESTAMPTZ IS NULL OR recorded_at >= $3)
+                   AND ($4::TIMESTAMPTZ IS NULL OR recorded_at <= $4)
+                 ORDER BY recorded_at ASC, id ASC",
+                repo_id.value(),
+                query.bead_filter,
+                query.since,
+                query.until,
+            )
+            .map(|row| -> TokenUsageRow {
+                (
+                    row.id,
+                    row.agent_id,
+                    row.bead_id,
+                    row.stage,
+                    row.model,
+                    row.input_tokens,
+                    row.output_tokens,
+                    row.description,
+                    row.recorded_at,
+                )
+            })
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(
+                    |(id, agent_id, bead_id, stage, model, input, output, description, recorded_at)| {
+                        TokenUsageRecord {
+                            id,
+                            budget_id: None,
+                            agent_id: agent_id.to_string(),
+                            bead_id,
+                            stage,
+                            model,
+                            input_tokens: input.max(0).cast_unsigned(),
+                            output_tokens: output.max(0).cast_unsigned(),
+                            description,
+                            recorded_at,
+                        }
+                    },
+                )
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load token usage: {e}")))
+    }
+
+    /// Run count and summed `duration_ms` of stage runs in `repo_id` that
+    /// finished inside the query window, per bead, agent and stage.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_stage_compute(
+        &self,
+        repo_id: &RepoId,
+        query: &CostQuery<'_>,
+    ) -> Result<Vec<StageCompute>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT bead_id, agent_id, stage, COUNT(*) AS "runs!",
+                        COALESCE(SUM(duration_ms), 0)::BIGINT AS "compute_ms!"
+                 FROM stage_history
+                 WHERE repo_id = $1
+                   AND completed_at IS NOT NULL
+                   AND ($2::TEXT IS NULL OR bead_id = $2)
+                   AND ($3::TIMESTAMPTZ IS NULL OR completed_at >= $3)
+                   AND ($4::TIMESTAMPTZ IS NULL OR completed_at <= $4)
+                 GROUP BY bead_id, agent_id, stage
+                 ORDER BY bead_id, agent_id, stage"#,
+                repo_id.value(),
+                query.bead_filter,
+                query.since,
+                query.until,
+            )
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| StageCompute {
+                    bead_id: row.bead_id,
+                    agent_id: row.agent_id.max(0).cast_unsigned(),
+                    stage: row.stage,
+                    runs: u32::try_from(row.runs.max(0)).unwrap_or(u32::MAX),
+                    compute_ms: row.compute_ms.max(0).cast_unsigned(),
+                })
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load stage compute: {e}")))
+    }
+
+    /// Backlog priority of `bead_id` and every token recorded against it so
+    /// far. Reads the primary so a just-recorded report is included.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_bead_budget_usage(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+    ) -> Result<(Option<String>, TokenUsage)> {
+        sqlx::query!(
+            r#"SELECT
+                (SELECT priority FROM bead_backlog
+                 WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL) AS priority,
+                COALESCE(SUM(input_tokens), 0)::BIGINT AS "input!",
+                COALESCE(SUM(output_tokens), 0)::BIGINT AS "output!"
+             FROM token_usage
+             WHERE repo_id = $1 AND bead_id = $2"#,
+            repo_id.value(),
+            bead_id.value(),
+        )
+        .fetch_one(self.pool())
+        .await
+        .map(|row| {
+            (
+                row.priority,
+                TokenUsage::new(
+                    row.input.max(0).cast_unsigned(),
+                    row.output.max(0).cast_unsigned(),
+                ),
+            )
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load bead token usage: {e}")))
+    }
+}
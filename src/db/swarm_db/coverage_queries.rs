@@ -0,0 +1,78 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{BeadId, CoverageSample, CoverageSummary, RepoId};
+
+impl SwarmDb {
+    /// Every coverage summary uploaded in the repo, optionally for one bead,
+    /// oldest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_coverage_samples(
+        &self,
+        repo_id: &RepoId,
+        bead_id: Option<&str>,
+    ) -> Result<Vec<CoverageSample>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT sh.bead_id, sh.stage, (sa.metadata->>'line_percent')::FLOAT8 AS "line_percent!",
+                        sa.created_at
+                 FROM stage_artifacts sa
+                 JOIN stage_history sh ON sh.id = sa.stage_history_id
+                 WHERE sh.repo_id = $1
+                   AND ($2::TEXT IS NULL OR sh.bead_id = $2)
+                   AND sa.artifact_type = 'coverage_report'
+                   AND sa.metadata ? 'line_percent'
+                 ORDER BY sa.created_at ASC, sa.id ASC"#,
+                repo_id.value(),
+                bead_id,
+            )
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|row| CoverageSample {
+                    bead_id: row.bead_id,
+                    stage: row.stage,
+                    line_percent: row.line_percent,
+                    recorded_at: row.created_at,
+                })
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load coverage samples: {e}")))
+    }
+
+    /// The most recent coverage summary uploaded for a bead. Reads the
+    /// primary so a summary uploaded just before `land` is seen.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails or the stored
+    /// summary cannot be decoded.
+    pub async fn get_latest_coverage(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+    ) -> Result<Option<CoverageSummary>> {
+        sqlx::query_scalar!(
+            "SELECT sa.content
+             FROM stage_artifacts sa
+             JOIN stage_history sh ON sh.id = sa.stage_history_id
+             WHERE sh.repo_id = $1 AND sh.bead_id = $2
+               AND sa.artifact_type = 'coverage_report'
+             ORDER BY sa.created_at DESC, sa.id DESC
+             LIMIT 1",
+            repo_id.value(),
+            bead_id.value(),
+        )
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load latest coverage: {e}")))?
+        .map(|content| {
+            serde_json::from_str::<CoverageSummary>(&content).map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to decode coverage summary: {e}"))
+            })
+        })
+        .transpose()
+    }
+}
@@ -0,0 +1,48 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{enum_columns, EnumDrift};
+
+impl SwarmDb {
+    /// Stored values of every [`enum_columns`] column that its enum does not
+    /// accept, by table, column and value. Rows written before a schema's
+    /// `CHECK` constraints, or with them dropped, are the usual source. An
+    /// empty result means every row decodes.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn find_enum_drift(&self) -> Result<Vec<EnumDrift>> {
+        let mut drift = Vec::new();
+        for column in enum_columns() {
+            // Table and column names come from `enum_columns`, never input.
+            let sql = format!(
+                "SELECT {column}, COUNT(*)
+                 FROM {table}
+                 WHERE {column} IS NOT NULL AND NOT ({column} = ANY($1))
+                 GROUP BY {column}
+                 ORDER BY {column}",
+                column = column.column,
+                table = column.table,
+            );
+            let rows = self
+                .read_with_fallback(|pool| {
+                    sqlx::query_as::<_, (String, i64)>(&sql)
+                        .bind(&column.values)
+                        .fetch_all(pool)
+                })
+                .await
+                .map_err(|e| {
+                    SwarmError::DatabaseError(format!(
+                        "Failed to scan {}.{} for drift: {e}",
+                        column.table, column.column
+                    ))
+                })?;
+            drift.extend(rows.into_iter().map(|(value, rows)| EnumDrift {
+                table: column.table.to_string(),
+                column: column.column.to_string(),
+                value,
+                rows: rows.max(0).cast_unsigned(),
+            }));
+        }
+        Ok(drift)
+    }
+}
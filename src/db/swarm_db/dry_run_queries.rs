@@ -0,0 +1,179 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentId, AssignPreview, BeadId, PreviewAgent, PreviewBead, RepoId};
+
+impl SwarmDb {
+    /// Reads, without locking, the agent, quarantine, claim and backlog rows
+    /// `claim_bead` would check and change.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn assign_preview(
+        &self,
+        agent_id: &AgentId,
+        bead_id: &BeadId,
+    ) -> Result<AssignPreview> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT
+                     a.status AS "agent_status?",
+                     a.bead_id AS "agent_bead_id?",
+                     (SELECT reason FROM agent_quarantine
+                      WHERE repo_id = $1 AND agent_id = $2 AND released_at IS NULL
+                      LIMIT 1) AS "quarantine_reason?",
+                     (SELECT claimed_by FROM bead_claims
+                      WHERE repo_id = $1 AND bead_id = $3 AND status = 'in_progress'
+                        AND deleted_at IS NULL) AS "claimed_by?",
+                     (SELECT status FROM bead_backlog
+                      WHERE repo_id = $1 AND bead_id = $3 AND deleted_at IS NULL) AS "backlog_status?"
+                 FROM (SELECT 1) AS probe
+                 LEFT JOIN agent_state a
+                   ON a.repo_id = $1 AND a.agent_id = $2 AND a.deleted_at IS NULL"#,
+                agent_id.repo_id().value(),
+                agent_id.number().cast_signed(),
+                bead_id.value(),
+            )
+            .fetch_one(pool)
+        })
+        .await
+        .map(|row| AssignPreview {
+            agent_status: row.agent_status,
+            agent_bead_id: row.agent_bead_id,
+            quarantine_reason: row.quarantine_reason,
+            claimed_by: row.claimed_by.map(i32::cast_unsigned),
+            backlog_status: row.backlog_status,
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to preview assign: {e}")))
+    }
+
+    /// Ids of the agents registered for `repo_id`, lowest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn registered_agent_ids(&self, repo_id: &RepoId) -> Result<Vec<u32>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+                "SELECT agent_id FROM agent_state
+                 WHERE repo_id = $1 AND deleted_at IS NULL
+                 ORDER BY agent_id",
+                repo_id.value(),
+            )
+            .fetch_all(pool)
+        })
+        .await
+        .map(|ids| ids.into_iter().map(i32::cast_unsigned).collect())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to list agent ids: {e}")))
+    }
+
+    /// Ids of the idle agents of `repo_id` holding no bead, lowest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn idle_unassigned_agent_ids(&self, repo_id: &RepoId) -> Result<Vec<u32>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+                "SELECT agent_id FROM agent_state
+                 WHERE repo_id = $1 AND status = 'idle' AND bead_id IS NULL AND deleted_at IS NULL
+                 ORDER BY agent_id",
+                repo_id.value(),
+            )
+            .fetch_all(pool)
+        })
+        .await
+        .map(|ids| ids.into_iter().map(i32::cast_unsigned).collect())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to list idle agent ids: {e}")))
+    }
+
+    /// Pending beads of `repo_id` in `claim_next_bead`'s order, with the
+    /// agent holding a live reservation on each.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn backlog_preview_beads(&self, repo_id: &RepoId) -> Result<Vec<PreviewBead>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT b.bead_id, b.priority, b.labels, b.required_capabilities, r.agent_id AS "agent_id?"
+                 FROM bead_backlog b
+                 LEFT JOIN bead_reservations r
+                   ON r.repo_id = b.repo_id AND r.bead_id = b.bead_id AND r.expires_at > NOW()
+                 WHERE b.repo_id = $1 AND b.status = 'pending' AND b.deleted_at IS NULL
+                 ORDER BY
+                     COALESCE(array_position(ARRAY['p0', 'p1', 'p2', 'p3']::TEXT[], lower(b.priority)), 999),
+                     b.created_at ASC"#,
+                repo_id.value(),
+            )
+            .map(|row| (row.bead_id, row.priority, row.labels, row.required_capabilities, row.agent_id))
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(
+                    |(bead_id, priority, labels, required_capabilities, reserved_by)| PreviewBead {
+                        bead_id,
+                        priority,
+                        labels,
+                        required_capabilities,
+                        reserved_by: reserved_by.map(i32::cast_unsigned),
+                    },
+                )
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to preview backlog: {e}")))
+    }
+
+    /// Agents of `repo_id` that could claim now, with their default labels.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn backlog_preview_agents(&self, repo_id: &RepoId) -> Result<Vec<PreviewAgent>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT a.agent_id, a.labels
+                 FROM agent_state a
+                 WHERE a.repo_id = $1
+                   AND a.status = 'idle'
+                   AND a.bead_id IS NULL
+                   AND a.deleted_at IS NULL
+                   AND NOT EXISTS (
+                       SELECT 1 FROM agent_quarantine q
+                       WHERE q.repo_id = a.repo_id AND q.agent_id = a.agent_id AND q.released_at IS NULL
+                   )
+                 ORDER BY a.agent_id",
+                repo_id.value(),
+            )
+            .map(|row| (row.agent_id, row.labels))
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|(agent_id, labels)| PreviewAgent {
+                    agent_id: agent_id.cast_unsigned(),
+                    labels,
+                })
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to list free agents: {e}")))
+    }
+
+    /// Tables already present in `schema`, or in the connection's current
+    /// schema when `None`. Empty when the schema does not exist.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn existing_tables(&self, schema: Option<&str>) -> Result<Vec<String>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+                r#"SELECT table_name::TEXT AS "table_name!"
+                 FROM information_schema.tables
+                 WHERE table_schema = COALESCE($1, current_schema())
+                 ORDER BY table_name"#,
+                schema,
+            )
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to list tables: {e}")))
+    }
+}
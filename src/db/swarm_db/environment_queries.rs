@@ -0,0 +1,49 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{BeadId, EnvironmentFingerprint, RepoId, Stage, StageEnvironment};
+
+impl SwarmDb {
+    /// The latest run of `attempt` for a bead, limited to `stage` when given,
+    /// with the environment fingerprint recorded as it started.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_stage_environment(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+        stage: Option<Stage>,
+        attempt: u32,
+    ) -> Result<Option<StageEnvironment>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+            "SELECT id, stage, attempt_number, started_at, metadata->'environment' AS environment
+             FROM stage_history
+             WHERE repo_id = $1
+               AND bead_id = $2
+               AND ($3::TEXT IS NULL OR stage = $3)
+               AND attempt_number = $4
+             ORDER BY started_at DESC, id DESC
+             LIMIT 1",
+            repo_id.value(),
+            bead_id.value(),
+            stage.map(|stage| stage.as_str()),
+            attempt.cast_signed(),
+        )
+            .fetch_optional(pool)
+        })
+        .await
+        .map(|row| {
+            row.map(|row| StageEnvironment {
+                stage_history_id: row.id,
+                stage: row.stage,
+                attempt: row.attempt_number.max(0).cast_unsigned(),
+                started_at: row.started_at,
+                environment: row
+                    .environment
+                    .and_then(|value| serde_json::from_value::<EnvironmentFingerprint>(value).ok()),
+            })
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load stage environment: {e}")))
+    }
+}
@@ -0,0 +1,140 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{Escalation, EscalationCandidate, EscalationReason, RepoId};
+use chrono::{DateTime, Utc};
+
+pub type EscalationRow = (
+    i64,
+    String,
+    String,
+    String,
+    Option<i32>,
+    Option<i32>,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+);
+
+pub fn to_escalation(
+    (id, bead_id, reason, detail, agent_id, assigned_to, escalated_at, resolved_at): EscalationRow,
+) -> Result<Escalation> {
+    Ok(Escalation {
+        id,
+        bead_id,
+        reason: EscalationReason::try_from(reason.as_str()).map_err(SwarmError::SchemaDrift)?,
+        detail,
+        agent_id: agent_id.map(i32::cast_unsigned),
+        assigned_to: assigned_to.map(i32::cast_unsigned),
+        escalated_at,
+        resolved_at,
+    })
+}
+
+impl SwarmDb {
+    /// Open escalations, oldest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_open_escalations(&self, repo_id: &RepoId) -> Result<Vec<Escalation>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+            "SELECT id, bead_id, reason, detail, agent_id, assigned_to, escalated_at, resolved_at
+             FROM escalations
+             WHERE repo_id = $1 AND resolved_at IS NULL
+             ORDER BY escalated_at ASC, id ASC",
+            repo_id.value(),
+        )
+            .map(|row| -> EscalationRow {
+                (
+                    row.id,
+                    row.bead_id,
+                    row.reason,
+                    row.detail,
+                    row.agent_id,
+                    row.assigned_to,
+                    row.escalated_at,
+                    row.resolved_at,
+                )
+            })
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load escalations: {e}")))?
+        .into_iter()
+        .map(to_escalation)
+        .collect()
+    }
+
+    /// Repos where an agent holds a bead or an escalation is still open, the
+    /// ones a recovery pass evaluates escalation rules for.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_escalation_repo_ids(&self) -> Result<Vec<RepoId>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+                r#"SELECT repo_id AS "repo_id!"
+                 FROM agent_state WHERE bead_id IS NOT NULL AND deleted_at IS NULL
+                 UNION
+                 SELECT repo_id FROM escalations WHERE resolved_at IS NULL
+                 ORDER BY 1"#,
+            )
+            .fetch_all(pool)
+        })
+        .await
+        .map(|repo_ids| repo_ids.into_iter().map(RepoId::new).collect())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load escalation repos: {e}")))
+    }
+
+    /// Every agent holding a bead, with its attempt and the swarm's maximum,
+    /// for the escalation rules to judge.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_escalation_candidates(
+        &self,
+        repo_id: &RepoId,
+    ) -> Result<Vec<EscalationCandidate>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT a.bead_id AS "bead_id!", a.agent_id, a.status, a.last_update,
+                        a.implementation_attempt, c.max_implementation_attempts
+                 FROM agent_state a
+                 CROSS JOIN swarm_config c
+                 WHERE a.repo_id = $1 AND a.bead_id IS NOT NULL AND a.deleted_at IS NULL
+                 ORDER BY a.agent_id ASC"#,
+                repo_id.value(),
+            )
+            .map(|row| {
+                (
+                    row.bead_id,
+                    row.agent_id,
+                    row.status,
+                    row.last_update,
+                    row.implementation_attempt,
+                    row.max_implementation_attempts,
+                )
+            })
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to load escalation candidates: {e}"))
+        })
+        .map(|rows| {
+            rows.into_iter()
+                .map(
+                    |(bead_id, agent_id, agent_status, since, attempt, max_attempts)| {
+                        EscalationCandidate {
+                            bead_id,
+                            agent_id: agent_id.max(0).cast_unsigned(),
+                            agent_status,
+                            since,
+                            implementation_attempt: attempt.max(0).cast_unsigned(),
+                            max_implementation_attempts: max_attempts.max(0).cast_unsigned(),
+                        }
+                    },
+                )
+                .collect()
+        })
+    }
+}
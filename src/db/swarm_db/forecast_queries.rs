@@ -0,0 +1,60 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{RepoId, ThroughputHistory, MAX_FORECAST_WINDOW_HOURS};
+
+impl SwarmDb {
+    /// Completions and active agents over the last `window_hours`, with the
+    /// registered agent count and the open backlog, for `forecast`. Longer
+    /// windows are clamped to [`MAX_FORECAST_WINDOW_HOURS`].
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_throughput_history(
+        &self,
+        repo_id: &RepoId,
+        window_hours: u32,
+    ) -> Result<ThroughputHistory> {
+        let window_hours = window_hours.min(MAX_FORECAST_WINDOW_HOURS);
+        let (beads_completed, active_agents, registered_agents, backlog_remaining) =
+            self.read_with_fallback(|pool| {
+                sqlx::query!(
+                    r#"SELECT
+                     (SELECT COUNT(DISTINCT sh.bead_id) FROM stage_history sh
+                      WHERE sh.repo_id = $1
+                        AND sh.completed_at >= NOW() - make_interval(hours => $2)
+                        AND sh.stage = 'red-queen' AND sh.status = 'passed') AS "beads_completed!",
+                     (SELECT COUNT(DISTINCT sh.agent_id) FROM stage_history sh
+                      WHERE sh.repo_id = $1
+                        AND sh.completed_at >= NOW() - make_interval(hours => $2)) AS "active_agents!",
+                     (SELECT COUNT(*) FROM agent_state WHERE repo_id = $1 AND deleted_at IS NULL)
+                         AS "registered_agents!",
+                     (SELECT COUNT(*) FROM bead_backlog
+                      WHERE repo_id = $1 AND status NOT IN ('completed', 'cancelled')
+                        AND deleted_at IS NULL) AS "backlog_remaining!""#,
+                    repo_id.value(),
+                    window_hours.cast_signed(),
+                )
+                .map(|row| {
+                    (
+                        row.beads_completed,
+                        row.active_agents,
+                        row.registered_agents,
+                        row.backlog_remaining,
+                    )
+                })
+                .fetch_one(pool)
+            })
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to load throughput history: {e}"))
+            })?;
+        let count = |value: i64| u32::try_from(value.max(0)).unwrap_or(u32::MAX);
+        Ok(ThroughputHistory {
+            window_hours,
+            beads_completed: count(beads_completed),
+            active_agents: count(active_agents),
+            registered_agents: count(registered_agents),
+            backlog_remaining: count(backlog_remaining),
+        })
+    }
+}
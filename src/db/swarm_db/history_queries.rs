@@ -1,43 +1,89 @@
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
-use crate::types::{ExecutionEvent, RepoId};
+use crate::types::{
+    EventSchemaVersion, ExecutionEvent, LockHolder, LockMetadata, LockWaiter, RepoId,
+    ResourceLockView, TransitionEvent,
+};
+
+/// Page of `command_audit` rows, newest first. `after_seq` continues from a
+/// previous page's `next_cursor`; `since`/`until` bound `t` inclusively.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommandHistoryQuery {
+    pub after_seq: Option<i64>,
+    pub limit: i64,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Page of `execution_events` rows, newest first. `since`/`until` bound
+/// `created_at` inclusively.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExecutionEventQuery<'a> {
+    pub bead_filter: Option<&'a str>,
+    pub after_seq: Option<i64>,
+    pub limit: i64,
+    pub failures_only: bool,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+type CommandHistoryRow = (
+    i64,
+    i64,
+    String,
+    serde_json::Value,
+    bool,
+    u64,
+    Option<String>,
+);
 
 impl SwarmDb {
     /// # Errors
     /// Returns an error if the database operation fails.
-    pub async fn get_command_history(
+    pub async fn get_command_history(&self, limit: i64) -> Result<Vec<CommandHistoryRow>> {
+        self.get_command_history_page(&CommandHistoryQuery {
+            limit,
+            ..CommandHistoryQuery::default()
+        })
+        .await
+    }
+
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_command_history_page(
         &self,
-        limit: i64,
-    ) -> Result<
-        Vec<(
-            i64,
-            i64,
-            String,
-            serde_json::Value,
-            bool,
-            u64,
-            Option<String>,
-        )>,
-    > {
-        sqlx::query_as::<
-            _,
-            (
-                i64,
-                chrono::DateTime<chrono::Utc>,
-                String,
-                serde_json::Value,
-                bool,
-                i64,
-                Option<String>,
-            ),
-        >(
-            "SELECT seq, t, cmd, args, ok, ms, error_code
-             FROM command_audit
-             ORDER BY seq DESC
-             LIMIT $1",
-        )
-        .bind(limit.max(0))
-        .fetch_all(self.pool())
+        query: &CommandHistoryQuery,
+    ) -> Result<Vec<CommandHistoryRow>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT seq, t, cmd, args, ok, ms, error_code
+                 FROM command_audit
+                 WHERE ($1::BIGINT IS NULL OR seq < $1)
+                   AND ($2::TIMESTAMPTZ IS NULL OR t >= $2)
+                   AND ($3::TIMESTAMPTZ IS NULL OR t <= $3)
+                 ORDER BY seq DESC
+                 LIMIT $4",
+                query.after_seq,
+                query.since,
+                query.until,
+                query.limit.max(0),
+            )
+            .map(|row| {
+                (
+                    row.seq,
+                    row.t,
+                    row.cmd,
+                    row.args,
+                    row.ok,
+                    row.ms,
+                    row.error_code,
+                )
+            })
+            .fetch_all(pool)
+        })
         .await
         .map_err(|error| {
             SwarmError::DatabaseError(format!("Failed to load command history: {error}"))
@@ -51,7 +97,7 @@ impl SwarmDb {
                         cmd,
                         args,
                         ok,
-                        ms.max(0).cast_unsigned(),
+                        u64::from(ms.max(0).cast_unsigned()),
                         error_code,
                     )
                 })
@@ -59,24 +105,41 @@ impl SwarmDb {
         })
     }
 
+    /// When the newest successful `command_audit` row other than a
+    /// `healthz` probe was recorded, or `None` before any.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn last_successful_command_at(&self) -> Result<Option<DateTime<Utc>>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+                "SELECT t
+                 FROM command_audit
+                 WHERE ok AND cmd <> 'healthz'
+                 ORDER BY t DESC
+                 LIMIT 1",
+            )
+            .fetch_optional(pool)
+        })
+        .await
+        .map_err(|error| {
+            SwarmError::DatabaseError(format!("Failed to load last successful command: {error}"))
+        })
+    }
+
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn list_active_resource_locks(&self) -> Result<Vec<(String, String, i64, i64)>> {
-        sqlx::query_as::<
-            _,
-            (
-                String,
-                String,
-                chrono::DateTime<chrono::Utc>,
-                chrono::DateTime<chrono::Utc>,
-            ),
-        >(
-            "SELECT resource, agent, until_at, until_at
-             FROM resource_locks
-             WHERE until_at > NOW()
-             ORDER BY resource ASC",
-        )
-        .fetch_all(self.pool())
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT resource, agent, until_at, until_at AS expires_at
+                 FROM resource_locks
+                 WHERE until_at > NOW()
+                 ORDER BY resource ASC",
+            )
+            .map(|row| (row.resource, row.agent, row.until_at, row.expires_at))
+            .fetch_all(pool)
+        })
         .await
         .map_err(|error| {
             SwarmError::DatabaseError(format!("Failed to load active resource locks: {error}"))
@@ -95,6 +158,158 @@ impl SwarmDb {
         })
     }
 
+    /// Live locks on any of `resources` taken for a bead other than
+    /// `bead_id`, as `(resource, agent, bead_id)`. Locks taken without a
+    /// bead are not counted against anyone.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_foreign_resource_locks(
+        &self,
+        resources: &[String],
+        bead_id: &str,
+    ) -> Result<Vec<(String, String, String)>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT resource, agent, bead_id AS "bead_id!"
+                 FROM resource_locks
+                 WHERE resource = ANY($1)
+                   AND until_at > NOW()
+                   AND bead_id IS NOT NULL
+                   AND bead_id <> $2
+                 ORDER BY resource ASC"#,
+                resources,
+                bead_id,
+            )
+            .map(|row| (row.resource, row.agent, row.bead_id))
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load resource locks: {e}")))
+    }
+
+    /// Every live resource lock and wait queue, by resource. Waiters are
+    /// listed head first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn list_resource_locks(&self) -> Result<Vec<ResourceLockView>> {
+        let holders = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    r#"SELECT resource, agent, purpose, bead_id, since, until_at,
+                            GREATEST(0, (EXTRACT(EPOCH FROM (until_at - NOW())) * 1000)::BIGINT)
+                                AS "remaining_ttl_ms!"
+                     FROM resource_locks
+                     WHERE until_at > NOW()"#,
+                )
+                .map(|row| {
+                    (
+                        row.resource,
+                        row.agent,
+                        row.purpose,
+                        row.bead_id,
+                        row.since,
+                        row.until_at,
+                        row.remaining_ttl_ms,
+                    )
+                })
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to load resource locks: {e}"))
+            })?;
+
+        let waiters = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    r#"SELECT resource,
+                            ROW_NUMBER() OVER (PARTITION BY resource ORDER BY id ASC) AS "position!",
+                            agent, purpose, bead_id, ttl_ms, enqueued_at,
+                            GREATEST(0, (EXTRACT(EPOCH FROM (wait_until - NOW())) * 1000)::BIGINT)
+                                AS "remaining_wait_ms!"
+                     FROM resource_lock_waiters
+                     WHERE wait_until > NOW()
+                     ORDER BY resource ASC, id ASC"#,
+                )
+                .map(|row| {
+                    (
+                        row.resource,
+                        row.position,
+                        row.agent,
+                        row.purpose,
+                        row.bead_id,
+                        row.ttl_ms,
+                        row.enqueued_at,
+                        row.remaining_wait_ms,
+                    )
+                })
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to load lock waiters: {e}")))?;
+
+        let mut views = BTreeMap::<String, ResourceLockView>::new();
+        for (resource, agent, purpose, bead_id, since, until, remaining_ttl_ms) in holders {
+            views
+                .entry(resource.clone())
+                .or_insert_with(|| ResourceLockView {
+                    resource,
+                    holder: None,
+                    waiters: Vec::new(),
+                })
+                .holder = Some(LockHolder {
+                agent,
+                metadata: LockMetadata { purpose, bead_id },
+                since,
+                until,
+                remaining_ttl_ms,
+            });
+        }
+        for (resource, position, agent, purpose, bead_id, ttl_ms, enqueued_at, remaining_wait_ms) in
+            waiters
+        {
+            views
+                .entry(resource.clone())
+                .or_insert_with(|| ResourceLockView {
+                    resource,
+                    holder: None,
+                    waiters: Vec::new(),
+                })
+                .waiters
+                .push(LockWaiter {
+                    position,
+                    agent,
+                    metadata: LockMetadata { purpose, bead_id },
+                    ttl_ms,
+                    enqueued_at,
+                    remaining_wait_ms,
+                });
+        }
+        Ok(views.into_values().collect())
+    }
+
+    /// Sessions currently blocked on a lock and the longest such wait, in ms.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn lock_wait_snapshot(&self) -> Result<(i64, i64)> {
+        sqlx::query!(
+            r#"SELECT
+                COUNT(*)::BIGINT AS "waiting!",
+                COALESCE(MAX(EXTRACT(EPOCH FROM (NOW() - query_start)) * 1000), 0)::BIGINT
+                    AS "longest_wait_ms!"
+             FROM pg_stat_activity
+             WHERE datname = current_database()
+               AND wait_event_type = 'Lock'"#,
+        )
+        .map(|row| (row.waiting, row.longest_wait_ms))
+        .fetch_one(self.pool())
+        .await
+        .map_err(|error| SwarmError::DatabaseError(format!("Failed to sample lock waits: {error}")))
+    }
+
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn get_execution_events(
@@ -103,32 +318,63 @@ impl SwarmDb {
         bead_filter: Option<&str>,
         limit: i64,
     ) -> Result<Vec<ExecutionEvent>> {
-        let rows = sqlx::query_as::<
-            _,
-            (
-                i64,
-                i32,
-                String,
-                String,
-                Option<String>,
-                Option<i32>,
-                Option<String>,
-                Option<String>,
-                Option<serde_json::Value>,
-                Option<serde_json::Value>,
-                chrono::DateTime<chrono::Utc>,
-            ),
-        >(
-            "SELECT seq, schema_version, event_type, entity_id, bead_id, agent_id, stage, causation_id, diagnostics, payload, created_at
-             FROM execution_events
-             WHERE repo_id = $1 AND ($2::text IS NULL OR bead_id = $2)
-             ORDER BY seq DESC
-             LIMIT $3",
+        self.get_execution_events_page(
+            repo_id,
+            &ExecutionEventQuery {
+                bead_filter,
+                limit,
+                ..ExecutionEventQuery::default()
+            },
         )
-        .bind(repo_id.value())
-        .bind(bead_filter)
-        .bind(limit.max(1))
-        .fetch_all(self.pool())
+        .await
+    }
+
+    /// Payloads come back upgraded to [`EventSchemaVersion::LATEST`].
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_execution_events_page(
+        &self,
+        repo_id: &RepoId,
+        query: &ExecutionEventQuery<'_>,
+    ) -> Result<Vec<ExecutionEvent>> {
+        let rows = self.read_with_fallback(|pool| {
+            sqlx::query_as::<
+                _,
+                (
+                    i64,
+                    i32,
+                    String,
+                    String,
+                    Option<String>,
+                    Option<i32>,
+                    Option<String>,
+                    Option<String>,
+                    Option<serde_json::Value>,
+                    Option<serde_json::Value>,
+                    chrono::DateTime<chrono::Utc>,
+                ),
+            >(
+                "SELECT seq, schema_version, event_type, entity_id, bead_id, agent_id, stage, causation_id, diagnostics, payload, created_at
+                 FROM execution_events
+                 WHERE repo_id = $1
+                   AND ($2::text IS NULL OR bead_id = $2)
+                   AND ($3::BIGINT IS NULL OR seq < $3)
+                   AND (NOT $4 OR diagnostics IS NOT NULL)
+                   AND ($5::TIMESTAMPTZ IS NULL OR created_at >= $5)
+                   AND ($6::TIMESTAMPTZ IS NULL OR created_at <= $6)
+                 ORDER BY seq DESC
+                 LIMIT $7",
+            )
+            .bind(repo_id.value())
+            .bind(query.bead_filter)
+            .bind(query.after_seq)
+            .bind(query.failures_only)
+            .bind(query.since)
+            .bind(query.until)
+            .bind(query.limit.max(1))
+            .fetch_all(pool)
+        })
         .await
         .map_err(|error| SwarmError::DatabaseError(format!("Failed to load execution events: {error}")))?;
 
@@ -167,9 +413,123 @@ impl SwarmDb {
                         diagnostics,
                         payload,
                         created_at,
-                    })
+                    }
+                    .upgraded())
                 },
             )
             .collect()
     }
+
+    /// Stored execution events below the latest schema version, as
+    /// `(schema_version, count)` oldest version first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn count_outdated_execution_events(&self) -> Result<Vec<(i32, u64)>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT schema_version, COUNT(*) AS "count!"
+                 FROM execution_events
+                 WHERE schema_version < $1
+                 GROUP BY schema_version
+                 ORDER BY schema_version"#,
+                EventSchemaVersion::LATEST.as_i32(),
+            )
+            .map(|row| (row.schema_version, row.count))
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|(version, count)| (version, u64::try_from(count).unwrap_or(0)))
+                .collect()
+        })
+        .map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to count outdated execution events: {e}"))
+        })
+    }
+
+    /// Every transition decision made for `bead_id`, oldest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_transition_events(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &str,
+    ) -> Result<Vec<TransitionEvent>> {
+        type TransitionEventRow = (
+            i64,
+            i32,
+            String,
+            String,
+            i32,
+            String,
+            Option<String>,
+            String,
+            Option<String>,
+            Option<i64>,
+            DateTime<Utc>,
+        );
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+            "SELECT seq, agent_id, stage, result, attempt, transition, next_stage, reason, message,
+                    stage_history_id, created_at
+             FROM transition_events
+             WHERE repo_id = $1 AND bead_id = $2
+             ORDER BY seq ASC",
+            repo_id.value(),
+            bead_id,
+        )
+            .map(|row| -> TransitionEventRow {
+                (
+                    row.seq,
+                    row.agent_id,
+                    row.stage,
+                    row.result,
+                    row.attempt,
+                    row.transition,
+                    row.next_stage,
+                    row.reason,
+                    row.message,
+                    row.stage_history_id,
+                    row.created_at,
+                )
+            })
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(
+                    |(
+                        seq,
+                        agent_id,
+                        stage,
+                        result,
+                        attempt,
+                        transition,
+                        next_stage,
+                        reason,
+                        message,
+                        stage_history_id,
+                        created_at,
+                    )| TransitionEvent {
+                        seq,
+                        agent_id: agent_id.max(0).cast_unsigned(),
+                        stage,
+                        result,
+                        attempt: attempt.max(0).cast_unsigned(),
+                        transition,
+                        next_stage,
+                        reason,
+                        message,
+                        stage_history_id,
+                        created_at,
+                    },
+                )
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load transition events: {e}")))
+    }
 }
@@ -0,0 +1,180 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{InvariantCheck, InvariantViolation, RepoId};
+
+impl SwarmDb {
+    /// Runs every [`InvariantCheck`] against `repo_id`, or every repo when
+    /// `None`, and returns the rows that break them in check order. An empty
+    /// result means the coordinator state is consistent.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    #[allow(clippy::too_many_lines)]
+    pub async fn check_invariants(
+        &self,
+        repo_id: Option<&RepoId>,
+    ) -> Result<Vec<InvariantViolation>> {
+        let repo_filter = repo_id.map(RepoId::value);
+        let mut violations = Vec::new();
+
+        let shared = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+            r#"SELECT a.repo_id, a.bead_id AS "bead_id!", a.agent_id, c.claimed_by AS "claimed_by?"
+             FROM agent_state a
+             LEFT JOIN bead_claims c
+               ON c.repo_id = a.repo_id AND c.bead_id = a.bead_id AND c.status = 'in_progress'
+              AND c.deleted_at IS NULL
+             WHERE a.status IN ('working', 'waiting')
+               AND a.deleted_at IS NULL
+               AND ($1::TEXT IS NULL OR a.repo_id = $1)
+               AND c.claimed_by IS DISTINCT FROM a.agent_id
+               AND EXISTS (
+                   SELECT 1 FROM agent_state o
+                   WHERE o.repo_id = a.repo_id
+                     AND o.bead_id = a.bead_id
+                     AND o.agent_id <> a.agent_id
+                     AND o.status IN ('working', 'waiting')
+                     AND o.deleted_at IS NULL
+               )
+             ORDER BY a.repo_id, a.bead_id, a.agent_id"#,
+            repo_filter,
+        )
+        .map(|row| (row.repo_id, row.bead_id, row.agent_id, row.claimed_by))
+        .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to check bead assignees: {e}"))
+            })?;
+        violations.extend(shared.into_iter().map(|(repo, bead, agent, owner)| {
+            let owner = owner.map_or_else(
+                || "no agent holds its claim".to_string(),
+                |owner| format!("agent {owner} holds its claim"),
+            );
+            InvariantViolation {
+                check: InvariantCheck::SingleAssignee,
+                detail: format!(
+                    "agent {agent} is working {bead}, which another agent is also working; {owner}"
+                ),
+                fix: format!("swarm release --agent-id {agent}"),
+                repo_id: repo,
+                bead_id: Some(bead),
+                agent_id: Some(agent.max(0).cast_unsigned()),
+            }
+        }));
+
+        let unclaimed = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    "SELECT a.repo_id, a.bead_id, a.agent_id, a.status
+                     FROM agent_state a
+                     WHERE a.status IN ('working', 'waiting')
+                       AND a.deleted_at IS NULL
+                       AND ($1::TEXT IS NULL OR a.repo_id = $1)
+                       AND NOT EXISTS (
+                           SELECT 1 FROM bead_claims c
+                           WHERE c.repo_id = a.repo_id
+                             AND c.bead_id = a.bead_id
+                             AND c.claimed_by = a.agent_id
+                             AND c.status = 'in_progress'
+                             AND c.deleted_at IS NULL
+                       )
+                     ORDER BY a.repo_id, a.agent_id",
+                    repo_filter,
+                )
+                .map(|row| (row.repo_id, row.bead_id, row.agent_id, row.status))
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to check agent claims: {e}")))?;
+        violations.extend(unclaimed.into_iter().map(|(repo, bead, agent, status)| {
+            let detail = bead.as_deref().map_or_else(
+                || format!("agent {agent} is {status} with no bead"),
+                |bead| format!("agent {agent} is {status} on {bead} without an in-progress claim"),
+            );
+            InvariantViolation {
+                check: InvariantCheck::WorkingAgentHasClaim,
+                detail,
+                fix: format!("swarm release --agent-id {agent}"),
+                repo_id: repo,
+                bead_id: bead,
+                agent_id: Some(agent.max(0).cast_unsigned()),
+            }
+        }));
+
+        let over_attempted = self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT a.repo_id, a.bead_id, a.agent_id, a.implementation_attempt, s.max_implementation_attempts
+                 FROM agent_state a
+                 CROSS JOIN swarm_config s
+                 WHERE a.implementation_attempt > s.max_implementation_attempts
+                   AND a.deleted_at IS NULL
+                   AND ($1::TEXT IS NULL OR a.repo_id = $1)
+                 ORDER BY a.repo_id, a.agent_id",
+                repo_filter,
+            )
+            .map(|row| (row.repo_id, row.bead_id, row.agent_id, row.implementation_attempt, row.max_implementation_attempts))
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to check attempts: {e}")))?;
+        violations.extend(over_attempted.into_iter().map(
+            |(repo, bead, agent, attempt, max_attempts)| InvariantViolation {
+                check: InvariantCheck::AttemptsWithinMax,
+                detail: format!(
+                    "agent {agent} is on attempt {attempt}, past the maximum of {max_attempts}"
+                ),
+                fix: bead.as_deref().map_or_else(
+                    || format!("swarm release --agent-id {agent}"),
+                    |bead| format!("swarm cancel --bead-id {bead}"),
+                ),
+                repo_id: repo,
+                bead_id: bead,
+                agent_id: Some(agent.max(0).cast_unsigned()),
+            },
+        ));
+
+        let lapsed = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    "SELECT repo_id, bead_id, claimed_by, heartbeat_at, lease_expires_at
+                     FROM bead_claims
+                     WHERE status = 'in_progress'
+                       AND deleted_at IS NULL
+                       AND lease_expires_at < heartbeat_at
+                       AND ($1::TEXT IS NULL OR repo_id = $1)
+                     ORDER BY repo_id, bead_id",
+                    repo_filter,
+                )
+                .map(|row| {
+                    (
+                        row.repo_id,
+                        row.bead_id,
+                        row.claimed_by,
+                        row.heartbeat_at,
+                        row.lease_expires_at,
+                    )
+                })
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to check claim leases: {e}")))?;
+        violations.extend(lapsed.into_iter().map(
+            |(repo, bead, agent, heartbeat_at, lease_expires_at)| InvariantViolation {
+                check: InvariantCheck::LeaseAfterHeartbeat,
+                detail: format!(
+                    "claim on {bead} expires at {} but agent {agent} last heartbeat at {}",
+                    lease_expires_at.to_rfc3339(),
+                    heartbeat_at.to_rfc3339()
+                ),
+                fix: "swarm recover".to_string(),
+                repo_id: repo,
+                bead_id: Some(bead),
+                agent_id: Some(agent.max(0).cast_unsigned()),
+            },
+        ));
+
+        Ok(violations)
+    }
+}
@@ -0,0 +1,88 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentKvEntry, RepoId};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+pub type KvRow = (i32, String, String, Value, DateTime<Utc>);
+
+pub fn to_kv_entry((agent_id, bead_id, key, value, updated_at): KvRow) -> AgentKvEntry {
+    AgentKvEntry {
+        agent_id: agent_id.max(0).cast_unsigned(),
+        bead_id,
+        key,
+        value,
+        updated_at,
+    }
+}
+
+impl SwarmDb {
+    /// The value `agent_id` stored under `key` for `bead_id`, if any.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_kv_entry(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+        bead_id: &str,
+        key: &str,
+    ) -> Result<Option<AgentKvEntry>> {
+        sqlx::query!(
+            "SELECT agent_id, bead_id, key, value, updated_at
+             FROM agent_kv
+             WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3 AND key = $4",
+            repo_id.value(),
+            agent_id.cast_signed(),
+            bead_id,
+            key,
+        )
+        .map(|row| -> KvRow {
+            (
+                row.agent_id,
+                row.bead_id,
+                row.key,
+                row.value,
+                row.updated_at,
+            )
+        })
+        .fetch_optional(self.pool())
+        .await
+        .map(|row| row.map(to_kv_entry))
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load kv entry: {e}")))
+    }
+
+    /// Every value `agent_id` stored for `bead_id`, by key.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn list_kv_entries(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+        bead_id: &str,
+    ) -> Result<Vec<AgentKvEntry>> {
+        sqlx::query!(
+            "SELECT agent_id, bead_id, key, value, updated_at
+             FROM agent_kv
+             WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3
+             ORDER BY key",
+            repo_id.value(),
+            agent_id.cast_signed(),
+            bead_id,
+        )
+        .map(|row| -> KvRow {
+            (
+                row.agent_id,
+                row.bead_id,
+                row.key,
+                row.value,
+                row.updated_at,
+            )
+        })
+        .fetch_all(self.pool())
+        .await
+        .map(|rows| rows.into_iter().map(to_kv_entry).collect())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to list kv entries: {e}")))
+    }
+}
@@ -1,9 +1,66 @@
+// The sqlx 0.7 query macros expand bind arguments through code clippy reads as panicking.
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+
 mod agent_queries;
+mod alert_queries;
+mod announcement_queries;
+mod approval_queries;
 mod artifact_queries;
+mod blackboard_queries;
+mod connect_options;
 mod core;
+mod cost_queries;
+mod coverage_queries;
+mod drift_queries;
+mod dry_run_queries;
+mod environment_queries;
+mod escalation_queries;
+mod forecast_queries;
 mod history_queries;
+mod invariant_queries;
+mod kv_queries;
 mod message_queries;
+mod partition_queries;
+mod plan_queries;
+mod pool_health;
+mod prompt_queries;
+mod provenance_queries;
+mod quarantine_queries;
+mod query_latency_queries;
 mod resume_queries;
+mod review_queries;
+mod schedule_queries;
+mod session_queries;
+mod sla_queries;
+mod snapshot_queries;
+mod soft_delete_queries;
 mod swarm_queries;
+mod symbol_queries;
+mod tenant_queries;
+mod test_result_queries;
+mod workspace_queries;
 
-pub use core::SwarmDb;
+pub(crate) use alert_queries::{to_swarm_alert, AlertRow};
+pub(crate) use announcement_queries::{to_announcement, AnnouncementRow};
+pub(crate) use approval_queries::{to_approval, ApprovalRow};
+pub(crate) use blackboard_queries::{to_blackboard_entry, BlackboardRow};
+pub use connect_options::{
+    AuthTarget, AuthTokenFuture, AuthTokenProvider, CommandTokenProvider, ConnectOptions,
+    DatabaseConfig, SslMode, DEFAULT_TOKEN_REFRESH,
+};
+pub use core::{validate_schema_name, SwarmDb};
+pub use cost_queries::CostQuery;
+pub(crate) use escalation_queries::{to_escalation, EscalationRow};
+pub use history_queries::{CommandHistoryQuery, ExecutionEventQuery};
+pub(crate) use kv_queries::{to_kv_entry, KvRow};
+pub use pool_health::{
+    connect_with_backoff, latency_percentile, PoolHealth, ReconnectPolicy, DEFAULT_HEALTH_SAMPLES,
+    MAX_HEALTH_SAMPLES,
+};
+pub(crate) use quarantine_queries::{to_agent_quarantine, QuarantineRow};
+pub(crate) use review_queries::{to_bead_review, ReviewRow};
+pub use session_queries::AgentSessionActivity;
+pub(crate) use session_queries::{to_agent_session, SessionRow};
+pub(crate) use soft_delete_queries::{select_deleted_rows, to_deleted_row, DeletedRowRow};
+pub(crate) use tenant_queries::{to_tenant, TenantRow};
+pub(crate) use workspace_queries::{to_agent_workspace, WorkspaceRow};
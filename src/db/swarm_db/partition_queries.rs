@@ -0,0 +1,109 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{
+    month_start_at, next_month, PartitionedTable, PartitionedTableState, TablePartition,
+};
+use chrono::{DateTime, Utc};
+
+impl SwarmDb {
+    /// Every partitioned table with its partitions, attached or detached,
+    /// oldest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn partitioned_tables(&self) -> Result<Vec<PartitionedTableState>> {
+        let tables = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    "SELECT table_name, cutover_at FROM partitioned_tables ORDER BY table_name",
+                )
+                .map(|row| (row.table_name, row.cutover_at))
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to load partitioned tables: {e}"))
+            })?;
+
+        let mut states = Vec::with_capacity(tables.len());
+        for (table, cutover_at) in tables {
+            let table = PartitionedTable::try_from(table.as_str()).map_err(|error| {
+                SwarmError::SchemaDrift(format!("partitioned_tables.table_name: {error}"))
+            })?;
+            states.push(PartitionedTableState {
+                table,
+                cutover_at,
+                partitions: self.table_partitions(table, cutover_at).await?,
+            });
+        }
+        Ok(states)
+    }
+
+    /// The partitioned state of `table`, or `None` while it is a plain table.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn partitioned_table(
+        &self,
+        table: PartitionedTable,
+    ) -> Result<Option<PartitionedTableState>> {
+        Ok(self
+            .partitioned_tables()
+            .await?
+            .into_iter()
+            .find(|state| state.table == table))
+    }
+
+    /// Tables named like a partition of `table`. A detached partition is
+    /// an ordinary table and is found by its name alone.
+    async fn table_partitions(
+        &self,
+        table: PartitionedTable,
+        cutover_at: DateTime<Utc>,
+    ) -> Result<Vec<TablePartition>> {
+        let rows = self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT c.relname::TEXT AS "name!",
+                        EXISTS (
+                            SELECT 1 FROM pg_inherits i
+                            WHERE i.inhrelid = c.oid AND i.inhparent = to_regclass($1)
+                        ) AS "attached!",
+                        GREATEST(c.reltuples::BIGINT, 0) AS "rows_estimate!"
+                 FROM pg_class c
+                 WHERE c.relkind = 'r'
+                   AND pg_table_is_visible(c.oid)
+                   AND (c.relname = $1 || '_legacy' OR c.relname ~ ('^' || $1 || '_p[0-9]{4}_[0-9]{2}$'))
+                 ORDER BY c.relname"#,
+                table.as_str(),
+            )
+            .map(|row| (row.name, row.attached, row.rows_estimate))
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to load partitions of {table}: {e}"))
+        })?;
+
+        let mut partitions = rows
+            .into_iter()
+            .filter_map(|(name, attached, rows_estimate)| {
+                let (from, to) = match table.partition_month(&name) {
+                    Some(month) => (
+                        Some(month_start_at(month)),
+                        month_start_at(next_month(month)?),
+                    ),
+                    None => (None, cutover_at),
+                };
+                Some(TablePartition {
+                    name,
+                    attached,
+                    from,
+                    to,
+                    rows_estimate,
+                })
+            })
+            .collect::<Vec<_>>();
+        partitions.sort_by_key(|partition| partition.to);
+        Ok(partitions)
+    }
+}
@@ -0,0 +1,86 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::index_advisor::{IndexState, QueryProbe};
+use crate::types::RepoId;
+use serde_json::Value;
+use std::collections::HashMap;
+
+impl SwarmDb {
+    /// The planner's `EXPLAIN (FORMAT JSON)` plan for `probe` in `repo_id`.
+    /// The query is planned, not run.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn explain_probe(&self, probe: &QueryProbe, repo_id: &RepoId) -> Result<Value> {
+        let explain = format!("EXPLAIN (FORMAT JSON) {}", probe.sql);
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar::<_, Value>(&explain)
+                .bind(repo_id.value())
+                .fetch_one(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to explain {}: {e}", probe.name)))
+    }
+
+    /// Estimated rows per table, from planner statistics or, for a table
+    /// that was never analyzed, the live tuple count.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn table_row_estimates(&self, tables: &[&str]) -> Result<HashMap<String, i64>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT c.relname::TEXT AS "name!",
+                        GREATEST(c.reltuples::BIGINT, COALESCE(s.n_live_tup, 0)) AS "rows!"
+                 FROM pg_class c
+                 LEFT JOIN pg_stat_user_tables s ON s.relid = c.oid
+                 WHERE c.relname = ANY($1)
+                   AND c.relkind IN ('r', 'p')
+                   AND pg_table_is_visible(c.oid)"#,
+                &tables.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            )
+            .map(|row| (row.name, row.rows))
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| rows.into_iter().collect())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to estimate table rows: {e}")))
+    }
+
+    /// State of each named index on the search path; names with no index
+    /// are [`IndexState::Missing`].
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn index_states(&self, indexes: &[&str]) -> Result<HashMap<String, IndexState>> {
+        let found = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    r#"SELECT c.relname::TEXT AS "name!", i.indisvalid
+                     FROM pg_index i
+                     JOIN pg_class c ON c.oid = i.indexrelid
+                     WHERE c.relname = ANY($1)
+                       AND pg_table_is_visible(c.oid)"#,
+                    &indexes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                )
+                .map(|row| (row.name, row.indisvalid))
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to load index states: {e}")))?;
+
+        let mut states = indexes
+            .iter()
+            .map(|index| ((*index).to_string(), IndexState::Missing))
+            .collect::<HashMap<_, _>>();
+        for (index, valid) in found {
+            let state = if valid {
+                IndexState::Present
+            } else {
+                IndexState::Invalid
+            };
+            states.insert(index, state);
+        }
+        Ok(states)
+    }
+}
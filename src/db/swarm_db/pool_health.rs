@@ -0,0 +1,194 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+
+pub const DEFAULT_HEALTH_SAMPLES: u32 = 10;
+pub const MAX_HEALTH_SAMPLES: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 2_000,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before retry `attempt` (1-based), doubling from the initial backoff.
+    #[must_use]
+    pub fn backoff_for_attempt(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(20);
+        let delay = self
+            .initial_backoff_ms
+            .saturating_mul(1_u64 << exponent)
+            .min(self.max_backoff_ms);
+        Duration::from_millis(delay)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PoolHealth {
+    pub healthy: bool,
+    pub size: u32,
+    pub idle: u32,
+    pub max_connections: u32,
+    pub samples: u32,
+    pub reconnect_attempts: u32,
+    pub acquire_p50_ms: u64,
+    pub acquire_p95_ms: u64,
+    pub acquire_p99_ms: u64,
+    pub acquire_max_ms: u64,
+}
+
+/// Nearest-rank percentile over an ascending slice; returns 0 for empty input.
+#[must_use]
+pub fn latency_percentile(sorted_ms: &[u64], percentile: u8) -> u64 {
+    if sorted_ms.is_empty() {
+        return 0;
+    }
+    let rank = (usize::from(percentile.min(100)) * sorted_ms.len()).div_ceil(100);
+    let index = rank.saturating_sub(1).min(sorted_ms.len() - 1);
+    sorted_ms[index]
+}
+
+/// Runs `connect` until it succeeds, retrying with `policy`'s exponential
+/// backoff.
+///
+/// Retries continue while attempts remain and the next one would start
+/// before `deadline`, so a Postgres restart does not fail the caller outright.
+///
+/// # Errors
+/// Returns the last attempt's error once no retry is left.
+pub async fn connect_with_backoff<T, E, F, Fut>(
+    policy: &ReconnectPolicy,
+    deadline: Instant,
+    mut connect: F,
+) -> std::result::Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, E>>,
+{
+    let mut attempt = 1_u32;
+    loop {
+        match connect().await {
+            Ok(connected) => return Ok(connected),
+            Err(error) => {
+                let backoff = policy.backoff_for_attempt(attempt);
+                if attempt >= policy.max_attempts.max(1) || Instant::now() + backoff >= deadline {
+                    return Err(error);
+                }
+                tokio::time::sleep(backoff).await;
+                attempt = attempt.saturating_add(1);
+            }
+        }
+    }
+}
+
+impl SwarmDb {
+    /// # Errors
+    /// Returns an error if a pooled connection cannot answer a trivial query.
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query_scalar::<_, i32>("SELECT 1")
+            .fetch_one(self.pool())
+            .await
+            .map(|_value| ())
+            .map_err(|e| SwarmError::DatabaseError(format!("Database ping failed: {e}")))
+    }
+
+    /// Pings the pool, retrying with exponential backoff so dead connections
+    /// left behind by a Postgres restart are discarded and replaced.
+    /// Returns the number of retries that were needed.
+    ///
+    /// # Errors
+    /// Returns an error if the database is still unreachable after every retry.
+    pub async fn ensure_healthy(&self, policy: &ReconnectPolicy) -> Result<u32> {
+        let mut retries = 0_u32;
+        loop {
+            match self.ping().await {
+                Ok(()) => return Ok(retries),
+                Err(error) if retries.saturating_add(1) >= policy.max_attempts.max(1) => {
+                    return Err(error)
+                }
+                Err(_) => {
+                    retries = retries.saturating_add(1);
+                    tokio::time::sleep(policy.backoff_for_attempt(retries)).await;
+                }
+            }
+        }
+    }
+
+    /// # Errors
+    /// Returns an error if the pool cannot be made healthy or a sample acquire fails.
+    pub async fn pool_health(&self, samples: u32, policy: &ReconnectPolicy) -> Result<PoolHealth> {
+        let reconnect_attempts = self.ensure_healthy(policy).await?;
+        let samples = samples.clamp(1, MAX_HEALTH_SAMPLES);
+
+        let mut latencies = Vec::with_capacity(usize::try_from(samples).unwrap_or(0));
+        for _ in 0..samples {
+            let started = Instant::now();
+            let conn = self.pool().acquire().await.map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to acquire pooled connection: {e}"))
+            })?;
+            latencies.push(u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX));
+            drop(conn);
+        }
+        latencies.sort_unstable();
+
+        Ok(PoolHealth {
+            healthy: !self.pool().is_closed(),
+            size: self.pool().size(),
+            idle: u32::try_from(self.pool().num_idle()).unwrap_or(u32::MAX),
+            max_connections: self.pool().options().get_max_connections(),
+            samples,
+            reconnect_attempts,
+            acquire_p50_ms: latency_percentile(&latencies, 50),
+            acquire_p95_ms: latency_percentile(&latencies, 95),
+            acquire_p99_ms: latency_percentile(&latencies, 99),
+            acquire_max_ms: latencies.last().copied().unwrap_or(0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{latency_percentile, ReconnectPolicy};
+    use std::time::Duration;
+
+    #[test]
+    fn given_default_policy_when_backing_off_then_delay_doubles_until_capped() {
+        let policy = ReconnectPolicy::default();
+
+        assert_eq!(policy.backoff_for_attempt(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff_for_attempt(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff_for_attempt(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff_for_attempt(10), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn given_sorted_samples_when_taking_percentiles_then_nearest_rank_is_used() {
+        let samples = (1..=100).collect::<Vec<u64>>();
+
+        assert_eq!(latency_percentile(&samples, 50), 50);
+        assert_eq!(latency_percentile(&samples, 95), 95);
+        assert_eq!(latency_percentile(&samples, 99), 99);
+        assert_eq!(latency_percentile(&[7], 99), 7);
+    }
+
+    #[test]
+    fn given_no_samples_when_taking_percentile_then_zero_is_returned() {
+        assert_eq!(latency_percentile(&[], 50), 0);
+    }
+}
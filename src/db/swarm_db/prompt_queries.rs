@@ -0,0 +1,94 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::skill_prompts::StoredSkillPrompt;
+use crate::types::RepoId;
+
+type SkillPromptRow = (String, i32, String, chrono::DateTime<chrono::Utc>);
+
+fn to_stored_prompt((skill, version, body, created_at): SkillPromptRow) -> StoredSkillPrompt {
+    StoredSkillPrompt {
+        skill,
+        version: version.max(0).cast_unsigned(),
+        body,
+        created_at,
+    }
+}
+
+impl SwarmDb {
+    /// Latest stored version of `skill`, or `None` when only the embedded default exists.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_latest_skill_prompt(
+        &self,
+        repo_id: &RepoId,
+        skill: &str,
+    ) -> Result<Option<StoredSkillPrompt>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT skill, version, body, created_at
+                 FROM skill_prompts
+                 WHERE repo_id = $1 AND skill = $2
+                 ORDER BY version DESC
+                 LIMIT 1",
+                repo_id.value(),
+                skill,
+            )
+            .map(|row| -> SkillPromptRow { (row.skill, row.version, row.body, row.created_at) })
+            .fetch_optional(pool)
+        })
+        .await
+        .map(|row| row.map(to_stored_prompt))
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load skill prompt: {e}")))
+    }
+
+    /// Every version of `skill` newest first, or the latest version of each
+    /// stored skill when `skill` is `None`.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn list_skill_prompts(
+        &self,
+        repo_id: &RepoId,
+        skill: Option<&str>,
+    ) -> Result<Vec<StoredSkillPrompt>> {
+        let rows = match skill {
+            Some(skill) => {
+                self.read_with_fallback(|pool| {
+                    sqlx::query!(
+                        "SELECT skill, version, body, created_at
+                         FROM skill_prompts
+                         WHERE repo_id = $1 AND skill = $2
+                         ORDER BY version DESC",
+                        repo_id.value(),
+                        skill,
+                    )
+                    .map(|row| -> SkillPromptRow {
+                        (row.skill, row.version, row.body, row.created_at)
+                    })
+                    .fetch_all(pool)
+                })
+                .await
+            }
+            None => {
+                self.read_with_fallback(|pool| {
+                    sqlx::query!(
+                        "SELECT DISTINCT ON (skill) skill, version, body, created_at
+                         FROM skill_prompts
+                         WHERE repo_id = $1
+                         ORDER BY skill, version DESC",
+                        repo_id.value(),
+                    )
+                    .map(|row| -> SkillPromptRow {
+                        (row.skill, row.version, row.body, row.created_at)
+                    })
+                    .fetch_all(pool)
+                })
+                .await
+            }
+        };
+
+        rows.map(|rows| rows.into_iter().map(to_stored_prompt).collect())
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to list skill prompts: {e}")))
+    }
+}
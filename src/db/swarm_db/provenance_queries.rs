@@ -0,0 +1,89 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{BeadId, EnvironmentFingerprint, ProvenanceStageRun, RepoId};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+type StageRunRow = (
+    i64,
+    i32,
+    String,
+    i32,
+    String,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+    Option<i32>,
+    Option<Value>,
+);
+
+impl SwarmDb {
+    /// Every stage run of a bead, oldest first, for `attest`, with the
+    /// environment fingerprint recorded as it started. `command` is left
+    /// empty; the caller knows which command each stage runs.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_bead_stage_runs(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+    ) -> Result<Vec<ProvenanceStageRun>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT id, agent_id, stage, attempt_number, status, started_at, completed_at,
+                        duration_ms, metadata->'environment' AS environment
+                 FROM stage_history
+                 WHERE repo_id = $1 AND bead_id = $2
+                 ORDER BY started_at ASC, id ASC",
+                repo_id.value(),
+                bead_id.value(),
+            )
+            .map(|row| -> StageRunRow {
+                (
+                    row.id,
+                    row.agent_id,
+                    row.stage,
+                    row.attempt_number,
+                    row.status,
+                    row.started_at,
+                    row.completed_at,
+                    row.duration_ms,
+                    row.environment,
+                )
+            })
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(
+                    |(
+                        stage_history_id,
+                        agent_id,
+                        stage,
+                        attempt,
+                        status,
+                        started_at,
+                        completed_at,
+                        duration_ms,
+                        environment,
+                    )| ProvenanceStageRun {
+                        stage_history_id,
+                        agent_id: agent_id.max(0).cast_unsigned(),
+                        stage,
+                        attempt: attempt.max(0).cast_unsigned(),
+                        status,
+                        command: None,
+                        started_at,
+                        completed_at,
+                        duration_ms: duration_ms.map(i64::from),
+                        environment: environment.and_then(|value| {
+                            serde_json::from_value::<EnvironmentFingerprint>(value).ok()
+                        }),
+                    },
+                )
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load stage runs: {e}")))
+    }
+}
@@ -0,0 +1,124 @@
+use crate::db::write_ops::event_entity_id;
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentQuarantine, BeadId, QuarantineSource, RepoId};
+use chrono::{DateTime, Utc};
+
+pub type QuarantineRow = (
+    i32,
+    String,
+    String,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+    Option<String>,
+);
+
+pub fn to_agent_quarantine(
+    (agent_id, source, reason, quarantined_at, released_at, release_reason): QuarantineRow,
+) -> Result<AgentQuarantine> {
+    Ok(AgentQuarantine {
+        agent_id: agent_id.max(0).cast_unsigned(),
+        source: QuarantineSource::try_from(source.as_str()).map_err(SwarmError::SchemaDrift)?,
+        reason,
+        quarantined_at,
+        released_at,
+        release_reason,
+    })
+}
+
+impl SwarmDb {
+    /// Quarantines that still bar their agent from claiming, oldest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_active_quarantines(&self, repo_id: &RepoId) -> Result<Vec<AgentQuarantine>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT agent_id, source, reason, quarantined_at, released_at, release_reason
+                 FROM agent_quarantine
+                 WHERE repo_id = $1 AND released_at IS NULL
+                 ORDER BY quarantined_at ASC",
+                repo_id.value(),
+            )
+            .map(|row| -> QuarantineRow {
+                (
+                    row.agent_id,
+                    row.source,
+                    row.reason,
+                    row.quarantined_at,
+                    row.released_at,
+                    row.release_reason,
+                )
+            })
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load quarantines: {e}")))?
+        .into_iter()
+        .map(to_agent_quarantine)
+        .collect()
+    }
+
+    /// `scope_violation` execution events per agent in `repo_id` since
+    /// `since`.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_scope_violation_counts(
+        &self,
+        repo_id: &RepoId,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(u32, u32)>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT agent_id AS "agent_id!", COUNT(*) AS "count!"
+                 FROM execution_events
+                 WHERE event_type = 'scope_violation'
+                   AND starts_with(entity_id, $1)
+                   AND agent_id IS NOT NULL
+                   AND created_at >= $2
+                 GROUP BY agent_id"#,
+                format!("repo:{}:bead:", repo_id.value()),
+                since,
+            )
+            .map(|row| (row.agent_id, row.count))
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|(agent_id, count)| {
+                    (
+                        agent_id.max(0).cast_unsigned(),
+                        u32::try_from(count).unwrap_or(u32::MAX),
+                    )
+                })
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to count scope violations: {e}")))
+    }
+
+    /// `scope_violation` execution events recorded for one bead.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn count_bead_scope_violations(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+    ) -> Result<u64> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!"
+                 FROM execution_events
+                 WHERE event_type = 'scope_violation' AND entity_id = $1 AND bead_id = $2"#,
+                event_entity_id(bead_id, repo_id),
+                bead_id.value(),
+            )
+            .fetch_one(pool)
+        })
+        .await
+        .map(|count| u64::try_from(count).unwrap_or(0))
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to count scope violations: {e}")))
+    }
+}
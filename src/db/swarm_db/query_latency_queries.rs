@@ -0,0 +1,91 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::query_perf::LatencyHistogram;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Histograms by query name.
+pub type QueryLatencyWindow = HashMap<String, LatencyHistogram>;
+
+impl SwarmDb {
+    /// Query latency recorded in `[baseline_from, since)` and in
+    /// `[since, until]`, each merged per query.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn query_latency_windows(
+        &self,
+        baseline_from: DateTime<Utc>,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<(QueryLatencyWindow, QueryLatencyWindow)> {
+        let totals = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    r#"SELECT query_name, recorded_at >= $2 AS "is_current!",
+                            SUM(total_ms) AS "total_ms!", MAX(max_ms) AS "max_ms!"
+                     FROM query_latency
+                     WHERE recorded_at >= $1 AND recorded_at <= $3
+                     GROUP BY 1, 2"#,
+                    baseline_from,
+                    since,
+                    until,
+                )
+                .map(|row| (row.query_name, row.is_current, row.total_ms, row.max_ms))
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to load query latency: {e}")))?;
+
+        let buckets = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    r#"SELECT l.query_name, l.recorded_at >= $2 AS "is_current!", b.idx AS "idx!",
+                            SUM(b.n)::BIGINT AS "count!"
+                     FROM query_latency l
+                     CROSS JOIN LATERAL unnest(l.buckets) WITH ORDINALITY AS b(n, idx)
+                     WHERE l.recorded_at >= $1 AND l.recorded_at <= $3
+                     GROUP BY 1, 2, 3"#,
+                    baseline_from,
+                    since,
+                    until,
+                )
+                .map(|row| (row.query_name, row.is_current, row.idx, row.count))
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to load query latency buckets: {e}"))
+            })?;
+
+        let mut baseline = QueryLatencyWindow::new();
+        let mut current = QueryLatencyWindow::new();
+        for (query, is_current, total_ms, max_ms) in totals {
+            let window = if is_current {
+                &mut current
+            } else {
+                &mut baseline
+            };
+            let histogram = window.entry(query).or_default();
+            histogram.total_ms = total_ms;
+            histogram.max_ms = max_ms;
+        }
+        for (query, is_current, idx, count) in buckets {
+            let window = if is_current {
+                &mut current
+            } else {
+                &mut baseline
+            };
+            // `WITH ORDINALITY` counts from 1.
+            let Ok(bucket) = usize::try_from(idx - 1) else {
+                continue;
+            };
+            let histogram = window.entry(query).or_default();
+            if histogram.counts.len() <= bucket {
+                histogram.counts.resize(bucket + 1, 0);
+            }
+            histogram.counts[bucket] = count.max(0).cast_unsigned();
+        }
+        Ok((baseline, current))
+    }
+}
@@ -1,24 +1,46 @@
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
 use crate::types::{
-    AgentStatus, BeadId, DeepResumeContextContract, RepoId, ResumeContextProjection, Stage,
+    prefer_stage_summaries, AgentStatus, ArtifactType, BeadDiff, BeadId, ContextBudget,
+    DeepResumeContextContract, RepoId, ResumeArtifactDetailContract, ResumeArtifactSummary,
+    ResumeContextProjection, ResumeStageAttemptContract, Stage,
 };
+use std::collections::HashMap;
 
 impl SwarmDb {
+    /// Every bead an agent holds, with its artifacts listed by size; stage
+    /// runs with a `summary` list it in place of their transcripts.
+    ///
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn get_resume_context_projections(
         &self,
         repo_id: &RepoId,
     ) -> Result<Vec<ResumeContextProjection>> {
-        let rows = sqlx::query_as::<_, (i32, String, String, Option<String>, i32, Option<String>)>(
-            "SELECT agent_id, bead_id, status, current_stage, implementation_attempt, feedback
-             FROM agent_state
-             WHERE repo_id = $1 AND bead_id IS NOT NULL
-             ORDER BY agent_id ASC",
-        )
-        .bind(repo_id.value())
-        .fetch_all(self.pool())
+        self.load_resume_context_projections(repo_id, None).await
+    }
+
+    /// [`Self::get_resume_context_projections`], narrowed in the query to
+    /// `bead_id` when given.
+    async fn load_resume_context_projections(
+        &self,
+        repo_id: &RepoId,
+        bead_id: Option<&BeadId>,
+    ) -> Result<Vec<ResumeContextProjection>> {
+        let rows = self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT agent_id, bead_id AS "bead_id!", status, current_stage, implementation_attempt,
+                        feedback
+                 FROM agent_state
+                 WHERE repo_id = $1 AND bead_id IS NOT NULL AND deleted_at IS NULL
+                   AND ($2::TEXT IS NULL OR bead_id = $2)
+                 ORDER BY agent_id ASC"#,
+                repo_id.value(),
+                bead_id.map(BeadId::value),
+            )
+            .map(|row| (row.agent_id, row.bead_id, row.status, row.current_stage, row.implementation_attempt, row.feedback))
+            .fetch_all(pool)
+        })
         .await
         .map_err(|error| {
             SwarmError::DatabaseError(format!(
@@ -26,15 +48,24 @@ impl SwarmDb {
             ))
         })?;
 
+        let bead_ids = rows
+            .iter()
+            .map(|(_, bead_id, ..)| bead_id.clone())
+            .collect::<Vec<_>>();
+        let mut artifacts = self
+            .get_resume_artifact_summaries(repo_id, &bead_ids)
+            .await?;
+
         rows.into_iter()
             .map(
                 |(agent_id, bead_id, status, current_stage, implementation_attempt, feedback)| {
-                    let status = AgentStatus::try_from(status.as_str())
-                        .map_err(SwarmError::DatabaseError)?;
+                    let status =
+                        AgentStatus::try_from(status.as_str()).map_err(SwarmError::SchemaDrift)?;
                     let current_stage = current_stage
                         .map(|value| Stage::try_from(value.as_str()))
                         .transpose()
-                        .map_err(SwarmError::DatabaseError)?;
+                        .map_err(SwarmError::SchemaDrift)?;
+                    let bead_artifacts = artifacts.remove(&bead_id).unwrap_or_default();
                     Ok(ResumeContextProjection {
                         agent_id: agent_id.max(0).cast_unsigned(),
                         bead_id: BeadId::new(bead_id),
@@ -43,49 +74,205 @@ impl SwarmDb {
                         implementation_attempt: implementation_attempt.max(0).cast_unsigned(),
                         feedback,
                         attempts: Vec::new(),
-                        artifacts: Vec::new(),
+                        artifacts: prefer_stage_summaries(bead_artifacts),
                     })
                 },
             )
             .collect()
     }
 
+    /// Full resume state per in-flight bead: every stage attempt and artifact,
+    /// shrunk to `budget` with the cuts listed in `truncated`. With
+    /// `bead_id`, only that bead is loaded.
+    ///
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn get_deep_resume_contexts(
         &self,
         repo_id: &RepoId,
+        bead_id: Option<&BeadId>,
+        budget: ContextBudget,
     ) -> Result<Vec<DeepResumeContextContract>> {
-        self.get_resume_context_projections(repo_id)
+        let projections = self
+            .load_resume_context_projections(repo_id, bead_id)
+            .await?;
+        let mut contexts = Vec::with_capacity(projections.len());
+        for projection in projections {
+            contexts.push(
+                self.build_deep_resume_context(repo_id, projection, budget)
+                    .await?,
+            );
+        }
+        Ok(contexts)
+    }
+
+    /// [`Self::get_deep_resume_contexts`] for one bead; `None` when no agent
+    /// holds it.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_deep_resume_context(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+        budget: ContextBudget,
+    ) -> Result<Option<DeepResumeContextContract>> {
+        let projection = self
+            .get_resume_context_projections(repo_id)
+            .await?
+            .into_iter()
+            .find(|projection| projection.bead_id == *bead_id);
+        match projection {
+            Some(projection) => self
+                .build_deep_resume_context(repo_id, projection, budget)
+                .await
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn build_deep_resume_context(
+        &self,
+        repo_id: &RepoId,
+        projection: ResumeContextProjection,
+        budget: ContextBudget,
+    ) -> Result<DeepResumeContextContract> {
+        let attempts = self
+            .get_stage_attempt_contracts(repo_id, &projection.bead_id)
+            .await?;
+        let artifacts = self
+            .get_bead_artifacts(repo_id, &projection.bead_id, None)
+            .await?;
+        let latest_diff = artifacts.iter().rev().find_map(BeadDiff::from_artifact);
+        let artifacts = artifacts
+            .into_iter()
+            .map(ResumeArtifactDetailContract::from)
+            .collect::<Vec<_>>();
+
+        let mut context = DeepResumeContextContract {
+            agent_id: projection.agent_id,
+            bead_id: projection.bead_id.value().to_string(),
+            status: projection.status.as_str().to_string(),
+            current_stage: projection
+                .current_stage
+                .map(|stage| stage.as_str().to_string()),
+            implementation_attempt: projection.implementation_attempt,
+            feedback: projection.feedback,
+            attempts,
+            diagnostics: None,
+            artifacts,
+            latest_diff,
+            truncated: None,
+        };
+        context.fit_to_budget(budget);
+        Ok(context)
+    }
+
+    /// Size and hash of every artifact on `bead_ids`, oldest first, with the
+    /// content of `summary` artifacts only.
+    async fn get_resume_artifact_summaries(
+        &self,
+        repo_id: &RepoId,
+        bead_ids: &[String],
+    ) -> Result<HashMap<String, Vec<ResumeArtifactSummary>>> {
+        if bead_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    r#"SELECT sh.bead_id, sa.stage_history_id, sa.artifact_type, sa.created_at,
+                            sa.content_hash, OCTET_LENGTH(sa.content) AS "byte_length!",
+                            CASE WHEN sa.artifact_type = 'summary' THEN sa.content END AS summary
+                     FROM stage_artifacts sa
+                     JOIN stage_history sh ON sh.id = sa.stage_history_id
+                     WHERE sh.repo_id = $1 AND sh.bead_id = ANY($2)
+                     ORDER BY sa.created_at ASC, sa.id ASC"#,
+                    repo_id.value(),
+                    bead_ids,
+                )
+                .map(|row| {
+                    (
+                        row.bead_id,
+                        row.stage_history_id,
+                        row.artifact_type,
+                        row.created_at,
+                        row.content_hash,
+                        row.byte_length,
+                        row.summary,
+                    )
+                })
+                .fetch_all(pool)
+            })
             .await
-            .map(|contexts| {
-                contexts
-                    .into_iter()
-                    .map(|projection| DeepResumeContextContract {
-                        agent_id: projection.agent_id,
-                        bead_id: projection.bead_id.value().to_string(),
-                        status: projection.status.as_str().to_string(),
-                        current_stage: projection
-                            .current_stage
-                            .map(|stage| stage.as_str().to_string()),
-                        implementation_attempt: projection.implementation_attempt,
-                        feedback: projection.feedback,
-                        attempts: projection
-                            .attempts
-                            .into_iter()
-                            .map(|attempt| crate::ResumeStageAttemptContract {
-                                stage: attempt.stage.as_str().to_string(),
-                                attempt_number: attempt.attempt_number,
-                                status: attempt.status,
-                                feedback: attempt.feedback,
-                                started_at: attempt.started_at,
-                                completed_at: attempt.completed_at,
-                            })
-                            .collect::<Vec<_>>(),
-                        diagnostics: None,
-                        artifacts: Vec::new(),
-                    })
-                    .collect::<Vec<_>>()
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to load resume artifact summaries: {e}"))
+            })?;
+
+        let mut by_bead: HashMap<String, Vec<ResumeArtifactSummary>> = HashMap::new();
+        for (bead_id, stage_history_id, artifact_type, created_at, content_hash, bytes, summary) in
+            rows
+        {
+            let artifact_type =
+                ArtifactType::try_from(artifact_type.as_str()).map_err(SwarmError::SchemaDrift)?;
+            by_bead
+                .entry(bead_id)
+                .or_default()
+                .push(ResumeArtifactSummary {
+                    stage_history_id,
+                    artifact_type,
+                    created_at,
+                    content_hash,
+                    byte_length: u64::try_from(bytes).unwrap_or(0),
+                    summary,
+                });
+        }
+        Ok(by_bead)
+    }
+
+    async fn get_stage_attempt_contracts(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+    ) -> Result<Vec<ResumeStageAttemptContract>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT stage, attempt_number, status, feedback, started_at, completed_at
+                 FROM stage_history
+                 WHERE repo_id = $1 AND bead_id = $2
+                 ORDER BY started_at ASC, id ASC",
+                repo_id.value(),
+                bead_id.value(),
+            )
+            .map(|row| {
+                (
+                    row.stage,
+                    row.attempt_number,
+                    row.status,
+                    row.feedback,
+                    row.started_at,
+                    row.completed_at,
+                )
             })
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(
+                    |(stage, attempt_number, status, feedback, started_at, completed_at)| {
+                        ResumeStageAttemptContract {
+                            stage,
+                            attempt_number: attempt_number.max(0).cast_unsigned(),
+                            status,
+                            feedback,
+                            started_at,
+                            completed_at,
+                        }
+                    },
+                )
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load stage attempts: {e}")))
     }
 }
@@ -0,0 +1,96 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{BeadReview, RepoId, ReviewTally, ReviewVerdict};
+use chrono::{DateTime, Utc};
+
+pub type ReviewRow = (
+    i64,
+    String,
+    String,
+    Option<i32>,
+    String,
+    Option<String>,
+    DateTime<Utc>,
+);
+
+pub fn to_bead_review(
+    (id, bead_id, reviewer, agent_id, verdict, comments, created_at): ReviewRow,
+) -> Result<BeadReview> {
+    Ok(BeadReview {
+        id,
+        bead_id,
+        reviewer,
+        agent_id: agent_id.map(|agent_id| agent_id.max(0).cast_unsigned()),
+        verdict: ReviewVerdict::try_from(verdict.as_str()).map_err(SwarmError::SchemaDrift)?,
+        comments,
+        created_at,
+    })
+}
+
+impl SwarmDb {
+    /// Every review submitted for `bead_id`, oldest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn list_bead_reviews(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &str,
+    ) -> Result<Vec<BeadReview>> {
+        sqlx::query!(
+            "SELECT id, bead_id, reviewer, agent_id, verdict, comments, created_at
+             FROM reviews
+             WHERE repo_id = $1 AND bead_id = $2
+             ORDER BY id",
+            repo_id.value(),
+            bead_id,
+        )
+        .map(|row| -> ReviewRow {
+            (
+                row.id,
+                row.bead_id,
+                row.reviewer,
+                row.agent_id,
+                row.verdict,
+                row.comments,
+                row.created_at,
+            )
+        })
+        .fetch_all(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to list reviews: {e}")))?
+        .into_iter()
+        .map(to_bead_review)
+        .collect()
+    }
+
+    /// Agent holding the in-progress claim on `bead_id`, if any.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn bead_claim_holder(&self, repo_id: &RepoId, bead_id: &str) -> Result<Option<u32>> {
+        sqlx::query_scalar!(
+            "SELECT claimed_by
+             FROM bead_claims
+             WHERE repo_id = $1 AND bead_id = $2 AND status = 'in_progress'
+               AND deleted_at IS NULL",
+            repo_id.value(),
+            bead_id,
+        )
+        .fetch_optional(self.pool())
+        .await
+        .map(|holder| holder.map(i32::cast_unsigned))
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load bead claim: {e}")))
+    }
+
+    /// Reviewers of `bead_id` whose latest verdict approves or asks for
+    /// changes.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_review_tally(&self, repo_id: &RepoId, bead_id: &str) -> Result<ReviewTally> {
+        self.list_bead_reviews(repo_id, bead_id)
+            .await
+            .map(|reviews| ReviewTally::from_reviews(&reviews))
+    }
+}
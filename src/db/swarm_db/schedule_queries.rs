@@ -0,0 +1,81 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{RepoId, ScheduledJobKind, ScheduledJobRun};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+type ScheduledJobRow = (
+    String,
+    bool,
+    Option<DateTime<Utc>>,
+    Option<bool>,
+    Option<i64>,
+    Option<Value>,
+    Option<String>,
+);
+
+impl SwarmDb {
+    /// Stored state of every scheduled job, in [`ScheduledJobKind::ALL`]
+    /// order. Jobs without a row are reported as enabled and never run.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails or a stored job
+    /// name is unknown.
+    pub async fn get_scheduled_jobs(&self, repo_id: &RepoId) -> Result<Vec<ScheduledJobRun>> {
+        let rows = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    "SELECT job, enabled, last_run_at, last_ok, last_ms, last_result, last_error
+                     FROM scheduled_jobs
+                     WHERE repo_id = $1",
+                    repo_id.value(),
+                )
+                .map(|row| -> ScheduledJobRow {
+                    (
+                        row.job,
+                        row.enabled,
+                        row.last_run_at,
+                        row.last_ok,
+                        row.last_ms,
+                        row.last_result,
+                        row.last_error,
+                    )
+                })
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to load scheduled jobs: {e}"))
+            })?;
+
+        let mut stored = rows
+            .into_iter()
+            .map(
+                |(job, enabled, last_run_at, last_ok, last_ms, last_result, last_error)| {
+                    let job = ScheduledJobKind::try_from(job.as_str()).map_err(|error| {
+                        SwarmError::SchemaDrift(format!("scheduled_jobs.job: {error}"))
+                    })?;
+                    Ok(ScheduledJobRun {
+                        job,
+                        enabled,
+                        last_run_at,
+                        last_ok,
+                        last_ms: last_ms.map(|ms| ms.max(0).cast_unsigned()),
+                        last_result,
+                        last_error,
+                    })
+                },
+            )
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ScheduledJobKind::ALL
+            .into_iter()
+            .map(|job| {
+                stored.iter().position(|run| run.job == job).map_or_else(
+                    || ScheduledJobRun::never_run(job),
+                    |index| stored.swap_remove(index),
+                )
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,155 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentSession, RepoId, SessionEndReason};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+pub type SessionRow = (
+    i64,
+    i32,
+    String,
+    i32,
+    String,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+    Option<String>,
+);
+
+pub fn to_agent_session(
+    (id, agent_id, host, pid, version, started_at, ended_at, end_reason): SessionRow,
+) -> Result<AgentSession> {
+    Ok(AgentSession {
+        id,
+        agent_id: agent_id.max(0).cast_unsigned(),
+        host,
+        pid: pid.max(0).cast_unsigned(),
+        version,
+        started_at,
+        ended_at,
+        end_reason: end_reason
+            .as_deref()
+            .map(SessionEndReason::try_from)
+            .transpose()
+            .map_err(SwarmError::DatabaseError)?,
+    })
+}
+
+/// A session with the claims and execution events stamped with it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AgentSessionActivity {
+    #[serde(flatten)]
+    pub session: AgentSession,
+    pub claims: i64,
+    pub events: i64,
+}
+
+type SessionActivityRow = (
+    i64,
+    i32,
+    String,
+    i32,
+    String,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+    Option<String>,
+    i64,
+    i64,
+);
+
+fn to_session_activity(row: SessionActivityRow) -> Result<AgentSessionActivity> {
+    let (id, agent_id, host, pid, version, started_at, ended_at, end_reason, claims, events) = row;
+    let session = to_agent_session((
+        id, agent_id, host, pid, version, started_at, ended_at, end_reason,
+    ))?;
+    Ok(AgentSessionActivity {
+        session,
+        claims,
+        events,
+    })
+}
+
+impl SwarmDb {
+    /// The session currently acting as `agent_id`, if any.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_active_session(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+    ) -> Result<Option<AgentSession>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT id, agent_id, host, pid, version, started_at, ended_at, end_reason
+                 FROM agent_sessions
+                 WHERE repo_id = $1 AND agent_id = $2 AND ended_at IS NULL",
+                repo_id.value(),
+                agent_id.cast_signed(),
+            )
+            .map(|row| -> SessionRow {
+                (
+                    row.id,
+                    row.agent_id,
+                    row.host,
+                    row.pid,
+                    row.version,
+                    row.started_at,
+                    row.ended_at,
+                    row.end_reason,
+                )
+            })
+            .fetch_optional(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load agent session: {e}")))?
+        .map(to_agent_session)
+        .transpose()
+    }
+
+    /// Sessions in `repo_id`, active ones first, then newest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn list_agent_sessions(
+        &self,
+        repo_id: &RepoId,
+        limit: i64,
+    ) -> Result<Vec<AgentSessionActivity>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                r#"SELECT s.id, s.agent_id, s.host, s.pid, s.version, s.started_at, s.ended_at,
+                        s.end_reason,
+                        (SELECT COUNT(*) FROM bead_claims c
+                         WHERE c.session_id = s.id AND c.deleted_at IS NULL) AS "claims!",
+                        (SELECT COUNT(*) FROM execution_events e WHERE e.session_id = s.id)
+                            AS "events!"
+                 FROM agent_sessions s
+                 WHERE s.repo_id = $1
+                 ORDER BY s.ended_at IS NOT NULL, s.started_at DESC
+                 LIMIT $2"#,
+                repo_id.value(),
+                limit.max(0),
+            )
+            .map(|row| -> SessionActivityRow {
+                (
+                    row.id,
+                    row.agent_id,
+                    row.host,
+                    row.pid,
+                    row.version,
+                    row.started_at,
+                    row.ended_at,
+                    row.end_reason,
+                    row.claims,
+                    row.events,
+                )
+            })
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to list agent sessions: {e}")))?
+        .into_iter()
+        .map(to_session_activity)
+        .collect()
+    }
+}
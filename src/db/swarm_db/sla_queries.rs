@@ -0,0 +1,36 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{BacklogAge, RepoId};
+
+impl SwarmDb {
+    /// Backlog entries not yet completed or cancelled, oldest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_backlog_ages(&self, repo_id: &RepoId) -> Result<Vec<BacklogAge>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT bead_id, priority, status, created_at
+                 FROM bead_backlog
+                 WHERE repo_id = $1 AND status NOT IN ('completed', 'cancelled')
+                   AND deleted_at IS NULL
+                 ORDER BY created_at ASC, bead_id ASC",
+                repo_id.value(),
+            )
+            .map(|row| (row.bead_id, row.priority, row.status, row.created_at))
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load backlog ages: {e}")))
+        .map(|rows| {
+            rows.into_iter()
+                .map(|(bead_id, priority, status, enqueued_at)| BacklogAge {
+                    bead_id,
+                    priority,
+                    status,
+                    enqueued_at,
+                })
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,229 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{
+    BeadSnapshot, RepoId, SnapshotArtifact, SnapshotAssignment, SnapshotBacklog, SnapshotClaim,
+    SnapshotStage, BEAD_SNAPSHOT_VERSION,
+};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+type SnapshotStageRow = (
+    i64,
+    i32,
+    String,
+    i32,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+    Option<i32>,
+);
+
+type SnapshotArtifactRow = (
+    i64,
+    String,
+    String,
+    Option<serde_json::Value>,
+    DateTime<Utc>,
+);
+
+impl SwarmDb {
+    /// Everything the swarm holds for `bead_id`, or `None` if it holds
+    /// nothing at all.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    #[allow(clippy::too_many_lines)]
+    pub async fn get_bead_snapshot(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &str,
+    ) -> Result<Option<BeadSnapshot>> {
+        let backlog = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    "SELECT priority, status
+                     FROM bead_backlog
+                     WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL",
+                    repo_id.value(),
+                    bead_id,
+                )
+                .map(|row| (row.priority, row.status))
+                .fetch_optional(pool)
+            })
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to load backlog bead: {e}")))?
+            .map(|(priority, status)| SnapshotBacklog { priority, status });
+
+        let claim = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    "SELECT claimed_by, status, claimed_at
+                     FROM bead_claims
+                     WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL",
+                    repo_id.value(),
+                    bead_id,
+                )
+                .map(|row| (row.claimed_by, row.status, row.claimed_at))
+                .fetch_optional(pool)
+            })
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to load bead claim: {e}")))?
+            .map(|(claimed_by, status, claimed_at)| SnapshotClaim {
+                claimed_by: claimed_by.max(0).cast_unsigned(),
+                status,
+                claimed_at,
+            });
+
+        let assignment = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    "SELECT agent_id, current_stage, status, implementation_attempt, feedback
+                     FROM agent_state
+                     WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL
+                     ORDER BY agent_id ASC
+                     LIMIT 1",
+                    repo_id.value(),
+                    bead_id,
+                )
+                .map(|row| {
+                    (
+                        row.agent_id,
+                        row.current_stage,
+                        row.status,
+                        row.implementation_attempt,
+                        row.feedback,
+                    )
+                })
+                .fetch_optional(pool)
+            })
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to load bead assignment: {e}")))?
+            .map(
+                |(agent_id, current_stage, status, implementation_attempt, feedback)| {
+                    SnapshotAssignment {
+                        agent_id: agent_id.max(0).cast_unsigned(),
+                        current_stage,
+                        status,
+                        implementation_attempt: implementation_attempt.max(0).cast_unsigned(),
+                        feedback,
+                    }
+                },
+            );
+
+        let stage_rows = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+            "SELECT id, agent_id, stage, attempt_number, status, result, feedback, transcript,
+                    started_at, completed_at, duration_ms
+             FROM stage_history
+             WHERE repo_id = $1 AND bead_id = $2
+             ORDER BY started_at ASC, id ASC",
+            repo_id.value(),
+            bead_id,
+        )
+                .map(|row| -> SnapshotStageRow {
+                    (
+                        row.id,
+                        row.agent_id,
+                        row.stage,
+                        row.attempt_number,
+                        row.status,
+                        row.result,
+                        row.feedback,
+                        row.transcript,
+                        row.started_at,
+                        row.completed_at,
+                        row.duration_ms,
+                    )
+                })
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to load stage history: {e}")))?;
+
+        let mut artifacts = HashMap::<i64, Vec<SnapshotArtifact>>::new();
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+            "SELECT sa.stage_history_id, sa.artifact_type, sa.content, sa.metadata, sa.created_at
+             FROM stage_artifacts sa
+             JOIN stage_history sh ON sh.id = sa.stage_history_id
+             WHERE sh.repo_id = $1 AND sh.bead_id = $2
+             ORDER BY sa.created_at ASC, sa.id ASC",
+            repo_id.value(),
+            bead_id,
+        )
+            .map(|row| -> SnapshotArtifactRow {
+                (
+                    row.stage_history_id,
+                    row.artifact_type,
+                    row.content,
+                    row.metadata,
+                    row.created_at,
+                )
+            })
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load bead artifacts: {e}")))?
+        .into_iter()
+        .for_each(
+            |(stage_history_id, artifact_type, content, metadata, created_at)| {
+                artifacts
+                    .entry(stage_history_id)
+                    .or_default()
+                    .push(SnapshotArtifact {
+                        artifact_type,
+                        content,
+                        metadata,
+                        created_at,
+                    });
+            },
+        );
+
+        let stages = stage_rows
+            .into_iter()
+            .map(
+                |(
+                    id,
+                    agent_id,
+                    stage,
+                    attempt_number,
+                    status,
+                    result,
+                    feedback,
+                    transcript,
+                    started_at,
+                    completed_at,
+                    duration_ms,
+                )| SnapshotStage {
+                    agent_id: agent_id.max(0).cast_unsigned(),
+                    stage,
+                    attempt_number: attempt_number.max(0).cast_unsigned(),
+                    status,
+                    result,
+                    feedback,
+                    transcript,
+                    started_at,
+                    completed_at,
+                    duration_ms: duration_ms.map(|ms| ms.max(0).cast_unsigned()),
+                    artifacts: artifacts.remove(&id).unwrap_or_default(),
+                },
+            )
+            .collect::<Vec<_>>();
+
+        let snapshot = BeadSnapshot {
+            version: BEAD_SNAPSHOT_VERSION,
+            bead_id: bead_id.to_string(),
+            repo_id: repo_id.value().to_string(),
+            captured_at: Utc::now(),
+            backlog,
+            claim,
+            assignment,
+            stages,
+        };
+        Ok((!snapshot.is_empty()).then_some(snapshot))
+    }
+}
@@ -0,0 +1,65 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{DeletedRow, RepoId, SoftDeleteTable};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+pub type DeletedRowRow = (String, String, String, Value, DateTime<Utc>, String);
+
+pub fn to_deleted_row(
+    (table_name, repo_id, key, row, deleted_at, deleted_by): DeletedRowRow,
+) -> Result<DeletedRow> {
+    Ok(DeletedRow {
+        table: SoftDeleteTable::try_from(table_name.as_str()).map_err(SwarmError::SchemaDrift)?,
+        repo_id,
+        key,
+        row,
+        deleted_at,
+        deleted_by,
+    })
+}
+
+/// Selects the deleted rows of `table`, aliased `t`, as `DeletedRowRow`.
+pub fn select_deleted_rows(table: SoftDeleteTable) -> String {
+    format!(
+        "SELECT '{name}'::TEXT, t.repo_id, t.{key}::TEXT,
+                to_jsonb(t) - 'deleted_at' - 'deleted_by', t.deleted_at, t.deleted_by
+         FROM {name} t
+         WHERE t.deleted_at IS NOT NULL",
+        name = table.table_name(),
+        key = table.key_column(),
+    )
+}
+
+impl SwarmDb {
+    /// Deleted rows, newest first, from `table` or from every table that
+    /// keeps them.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn list_deleted_rows(
+        &self,
+        repo_id: &RepoId,
+        table: Option<SoftDeleteTable>,
+        limit: u32,
+    ) -> Result<Vec<DeletedRow>> {
+        let deleted = table.map_or_else(|| SoftDeleteTable::ALL.to_vec(), |table| vec![table]);
+        let union = deleted
+            .into_iter()
+            .map(|table| format!("{} AND t.repo_id = $1", select_deleted_rows(table)))
+            .collect::<Vec<_>>()
+            .join(" UNION ALL ");
+        let sql = format!("{union} ORDER BY 5 DESC LIMIT $2");
+        self.read_with_fallback(|pool| {
+            sqlx::query_as::<_, DeletedRowRow>(&sql)
+                .bind(repo_id.value())
+                .bind(i64::from(limit))
+                .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to list deleted rows: {e}")))?
+        .into_iter()
+        .map(to_deleted_row)
+        .collect()
+    }
+}
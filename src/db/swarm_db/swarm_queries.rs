@@ -1,35 +1,82 @@
+use crate::beads_sync::CoordinatorClaim;
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
-use crate::types::{AgentId, BeadId, ProgressSummary, RepoId, SwarmConfig, SwarmStatus};
+use crate::types::{
+    AgentId, BacklogRow, BeadId, ProgressSummary, RepoId, SwarmConfig, SwarmStatus,
+    DEFAULT_HEARTBEAT_GRACE_MS, DEFAULT_LEASE_TTL_MS, DEFAULT_RECOVERY_SCAN_INTERVAL_MS,
+};
+
+/// `swarm_config` columns `get_config` reads, in select order.
+type ConfigRow = (
+    i32,
+    i32,
+    Option<String>,
+    Option<chrono::DateTime<chrono::Utc>>,
+    String,
+    i32,
+    i32,
+    i32,
+);
 
 impl SwarmDb {
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn get_config(&self, repo_id: &RepoId) -> Result<SwarmConfig> {
-        let row = sqlx::query_as::<
-            _,
-            (
-                i32,
-                i32,
-                Option<String>,
-                Option<chrono::DateTime<chrono::Utc>>,
-                String,
-            ),
-        >(
-            "SELECT max_agents, max_implementation_attempts, claim_label, swarm_started_at, swarm_status
-             FROM swarm_config
-             WHERE repo_id = $1
-             ORDER BY swarm_started_at DESC NULLS LAST
-             LIMIT 1",
-        )
-        .bind(repo_id.value())
-        .fetch_optional(self.pool())
-        .await
+        let repo_scoped = self.table_has_column("swarm_config", "repo_id").await?;
+
+        let row = if repo_scoped {
+            self.read_with_fallback(|pool| {
+                sqlx::query_as::<_, ConfigRow>(
+                    "SELECT max_agents, max_implementation_attempts, claim_label, swarm_started_at, swarm_status,
+                            lease_ttl_ms, heartbeat_grace_ms, recovery_scan_interval_ms
+                     FROM swarm_config
+                     WHERE repo_id = $1
+                     ORDER BY swarm_started_at DESC NULLS LAST
+                     LIMIT 1",
+                )
+                .bind(repo_id.value())
+                .fetch_optional(pool)
+            })
+            .await
+        } else {
+            self.read_with_fallback(|pool| {
+                sqlx::query!(
+                    "SELECT max_agents, max_implementation_attempts, claim_label, swarm_started_at, swarm_status,
+                            lease_ttl_ms, heartbeat_grace_ms, recovery_scan_interval_ms
+                     FROM swarm_config
+                     WHERE id = TRUE",
+                )
+                .map(|row| -> ConfigRow {
+                    (
+                        row.max_agents,
+                        row.max_implementation_attempts,
+                        Some(row.claim_label),
+                        row.swarm_started_at,
+                        row.swarm_status,
+                        row.lease_ttl_ms,
+                        row.heartbeat_grace_ms,
+                        row.recovery_scan_interval_ms,
+                    )
+                })
+                .fetch_optional(pool)
+            })
+            .await
+        }
         .map_err(|error| SwarmError::DatabaseError(format!("Failed to load swarm config: {error}")))?;
 
-        if let Some((max_agents, max_attempts, claim_label, swarm_started_at, swarm_status)) = row {
+        if let Some((
+            max_agents,
+            max_attempts,
+            claim_label,
+            swarm_started_at,
+            swarm_status,
+            lease_ttl_ms,
+            heartbeat_grace_ms,
+            recovery_scan_interval_ms,
+        )) = row
+        {
             let status =
-                SwarmStatus::try_from(swarm_status.as_str()).map_err(SwarmError::DatabaseError)?;
+                SwarmStatus::try_from(swarm_status.as_str()).map_err(SwarmError::SchemaDrift)?;
             return Ok(SwarmConfig {
                 repo_id: repo_id.clone(),
                 max_agents: max_agents.max(0).cast_unsigned(),
@@ -37,6 +84,9 @@ impl SwarmDb {
                 claim_label: claim_label.unwrap_or_else(|| "swarm".to_string()),
                 swarm_started_at,
                 swarm_status: status,
+                lease_ttl_ms: lease_ttl_ms.max(0).cast_unsigned(),
+                heartbeat_grace_ms: heartbeat_grace_ms.max(0).cast_unsigned(),
+                recovery_scan_interval_ms: recovery_scan_interval_ms.max(0).cast_unsigned(),
             });
         }
 
@@ -47,28 +97,57 @@ impl SwarmDb {
             claim_label: "swarm".to_string(),
             swarm_started_at: None,
             swarm_status: SwarmStatus::Initializing,
+            lease_ttl_ms: DEFAULT_LEASE_TTL_MS,
+            heartbeat_grace_ms: DEFAULT_HEARTBEAT_GRACE_MS,
+            recovery_scan_interval_ms: DEFAULT_RECOVERY_SCAN_INTERVAL_MS,
         })
     }
 
+    /// Shortest recovery scan interval any swarm in the database asks for;
+    /// the session's recovery pass covers every repo. The default without a
+    /// config row.
+    ///
     /// # Errors
     /// Returns an error if the database operation fails.
-    pub async fn get_progress(&self, repo_id: &RepoId) -> Result<ProgressSummary> {
-        let row = sqlx::query_as::<_, (i64, i64, i64, i64, i64)>(
-            "SELECT
-                COUNT(*) FILTER (WHERE status = 'working') AS working,
-                COUNT(*) FILTER (WHERE status = 'idle') AS idle,
-                COUNT(*) FILTER (WHERE status = 'waiting') AS waiting,
-                COUNT(*) FILTER (WHERE status = 'done') AS done,
-                COUNT(*) FILTER (WHERE status = 'error') AS errors
-             FROM agent_state
-             WHERE repo_id = $1",
-        )
-        .bind(repo_id.value())
-        .fetch_one(self.pool())
+    pub async fn recovery_scan_interval_ms(&self) -> Result<u32> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!("SELECT MIN(recovery_scan_interval_ms) FROM swarm_config")
+                .fetch_one(pool)
+        })
         .await
+        .map(|interval| {
+            interval.map_or(DEFAULT_RECOVERY_SCAN_INTERVAL_MS, |ms| {
+                ms.max(0).cast_unsigned()
+            })
+        })
         .map_err(|error| {
-            SwarmError::DatabaseError(format!("Failed to load progress summary: {error}"))
-        })?;
+            SwarmError::DatabaseError(format!("Failed to load recovery scan interval: {error}"))
+        })
+    }
+
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_progress(&self, repo_id: &RepoId) -> Result<ProgressSummary> {
+        let row = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    r#"SELECT
+                        COUNT(*) FILTER (WHERE status = 'working') AS "working!",
+                        COUNT(*) FILTER (WHERE status = 'idle') AS "idle!",
+                        COUNT(*) FILTER (WHERE status = 'waiting') AS "waiting!",
+                        COUNT(*) FILTER (WHERE status = 'done') AS "done!",
+                        COUNT(*) FILTER (WHERE status = 'error') AS "errors!"
+                     FROM agent_state
+                     WHERE repo_id = $1 AND deleted_at IS NULL"#,
+                    repo_id.value(),
+                )
+                .map(|row| (row.working, row.idle, row.waiting, row.done, row.errors))
+                .fetch_one(pool)
+            })
+            .await
+            .map_err(|error| {
+                SwarmError::DatabaseError(format!("Failed to load progress summary: {error}"))
+            })?;
 
         let (working, idle, waiting, completed, errors) = row;
         Ok(ProgressSummary {
@@ -83,17 +162,138 @@ impl SwarmDb {
         })
     }
 
+    /// Every backlog bead in `repo_id`, for reconciling against `br`.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_backlog_rows(&self, repo_id: &RepoId) -> Result<Vec<BacklogRow>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT bead_id, status, priority
+                 FROM bead_backlog
+                 WHERE repo_id = $1 AND deleted_at IS NULL
+                 ORDER BY bead_id ASC",
+                repo_id.value(),
+            )
+            .map(|row| (row.bead_id, row.status, row.priority))
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|(bead_id, status, priority)| BacklogRow {
+                    bead_id,
+                    status,
+                    priority,
+                })
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load backlog: {e}")))
+    }
+
+    /// Backlog beads in `repo_id` still waiting to be claimed. Reads the
+    /// primary so a loop deciding whether to continue sees its own claims.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn count_pending_beads(&self, repo_id: &RepoId) -> Result<u64> {
+        sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM bead_backlog
+             WHERE repo_id = $1 AND status = 'pending' AND deleted_at IS NULL"#,
+            repo_id.value(),
+        )
+        .fetch_one(self.pool())
+        .await
+        .map(|count| count.max(0).cast_unsigned())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to count pending beads: {e}")))
+    }
+
+    /// Every claim in `repo_id`, for checking against `br`.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_claims_for_sync(&self, repo_id: &RepoId) -> Result<Vec<CoordinatorClaim>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT bead_id, claimed_by, status
+                 FROM bead_claims
+                 WHERE repo_id = $1 AND deleted_at IS NULL
+                 ORDER BY claimed_at ASC, bead_id ASC",
+                repo_id.value(),
+            )
+            .map(|row| (row.bead_id, row.claimed_by, row.status))
+            .fetch_all(pool)
+        })
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|(bead_id, agent_id, status)| CoordinatorClaim {
+                    bead_id,
+                    agent_id: agent_id.max(0).cast_unsigned(),
+                    status,
+                })
+                .collect()
+        })
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load claims: {e}")))
+    }
+
+    /// Whether `swarm cancel` has been run for `bead_id`.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn is_bead_cancelled(&self, repo_id: &RepoId, bead_id: &BeadId) -> Result<bool> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+                r#"SELECT EXISTS(
+                     SELECT 1
+                     FROM bead_backlog
+                     WHERE repo_id = $1 AND bead_id = $2 AND status = 'cancelled'
+                       AND deleted_at IS NULL
+                 ) AS "cancelled!""#,
+                repo_id.value(),
+                bead_id.value(),
+            )
+            .fetch_one(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to inspect cancellation: {e}")))
+    }
+
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn claim_next_bead(&self, agent_id: &AgentId) -> Result<Option<BeadId>> {
-        sqlx::query_scalar::<_, Option<String>>("SELECT claim_next_bead($1, $2)")
-            .bind(agent_id.repo_id().value())
-            .bind(agent_id.number().cast_signed())
-            .fetch_one(self.pool())
-            .await
-            .map_err(|error| {
-                SwarmError::DatabaseError(format!("Failed to claim next bead: {error}"))
-            })
-            .map(|value| value.map(BeadId::new))
+        crate::chaos::db_fault("claim_next_bead")?;
+        sqlx::query_scalar!(
+            "SELECT claim_next_bead($1, $2)",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+        )
+        .fetch_one(self.pool())
+        .await
+        .map_err(|error| SwarmError::DatabaseError(format!("Failed to claim next bead: {error}")))
+        .map(|value| value.map(BeadId::new))
+    }
+
+    /// Claims the top pending bead carrying one of `labels` for `agent_id`,
+    /// in place of the agent's default labels.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn claim_next_bead_with_labels(
+        &self,
+        agent_id: &AgentId,
+        labels: &[String],
+    ) -> Result<Option<BeadId>> {
+        crate::chaos::db_fault("claim_next_bead")?;
+        sqlx::query_scalar!(
+            "SELECT claim_next_bead($1, $2, $3)",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            labels,
+        )
+        .fetch_one(self.pool())
+        .await
+        .map_err(|error| SwarmError::DatabaseError(format!("Failed to claim next bead: {error}")))
+        .map(|value| value.map(BeadId::new))
     }
 }
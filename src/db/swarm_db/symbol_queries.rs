@@ -0,0 +1,79 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{RepoId, SymbolKind, SymbolObservation};
+
+impl SwarmDb {
+    /// Attempt number new symbols belong to: the bead's live implementation
+    /// attempt when an agent holds it, else the latest recorded attempt, else 1.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn current_symbol_attempt(&self, repo_id: &RepoId, bead_id: &str) -> Result<u32> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+                r#"SELECT COALESCE(
+                     (SELECT MAX(implementation_attempt) + 1
+                      FROM agent_state
+                      WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL),
+                     (SELECT MAX(attempt) FROM symbols WHERE repo_id = $1 AND bead_id = $2),
+                     1
+                 ) AS "attempt!""#,
+                repo_id.value(),
+                bead_id,
+            )
+            .fetch_one(pool)
+        })
+        .await
+        .map(|attempt| attempt.max(1).cast_unsigned())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to resolve symbol attempt: {e}")))
+    }
+
+    /// Every recorded signature, optionally limited to one bead.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_symbol_observations(
+        &self,
+        repo_id: &RepoId,
+        bead_id: Option<&str>,
+    ) -> Result<Vec<SymbolObservation>> {
+        let rows = self
+            .read_with_fallback(|pool| {
+                sqlx::query!(
+                    "SELECT bead_id, attempt, name, kind, module_path, signature
+                     FROM symbols
+                     WHERE repo_id = $1 AND ($2::TEXT IS NULL OR bead_id = $2)
+                     ORDER BY bead_id, module_path, name, attempt",
+                    repo_id.value(),
+                    bead_id,
+                )
+                .map(|row| {
+                    (
+                        row.bead_id,
+                        row.attempt,
+                        row.name,
+                        row.kind,
+                        row.module_path,
+                        row.signature,
+                    )
+                })
+                .fetch_all(pool)
+            })
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to load symbols: {e}")))?;
+
+        rows.into_iter()
+            .map(|(bead_id, attempt, name, kind, module_path, signature)| {
+                let kind = SymbolKind::try_from(kind.as_str()).map_err(SwarmError::SchemaDrift)?;
+                Ok(SymbolObservation {
+                    bead_id,
+                    attempt: attempt.max(1).cast_unsigned(),
+                    name,
+                    kind,
+                    module_path,
+                    signature,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,49 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::Tenant;
+use chrono::{DateTime, Utc};
+
+pub type TenantRow = (String, String, DateTime<Utc>);
+
+pub fn to_tenant((name, schema, created_at): TenantRow) -> Tenant {
+    Tenant {
+        name,
+        schema,
+        created_at,
+    }
+}
+
+impl SwarmDb {
+    /// Whether `name` is a registered tenant.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn tenant_exists(&self, name: &str) -> Result<bool> {
+        self.read_with_fallback(|pool| {
+            sqlx::query_scalar!(
+                r#"SELECT EXISTS (SELECT 1 FROM public.tenants WHERE name = $1) AS "exists!""#,
+                name,
+            )
+            .fetch_one(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to look up tenant {name}: {e}")))
+    }
+
+    /// Registered tenants, by name.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn list_tenants(&self) -> Result<Vec<Tenant>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT name, schema_name, created_at FROM public.tenants ORDER BY name ASC"
+            )
+            .map(|row| -> TenantRow { (row.name, row.schema_name, row.created_at) })
+            .fetch_all(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to list tenants: {e}")))
+        .map(|rows| rows.into_iter().map(to_tenant).collect())
+    }
+}
@@ -0,0 +1,22 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+
+impl SwarmDb {
+    /// Names of the tests that failed in one stage run, in report order.
+    /// Reads the primary so results stored by the same run are seen.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_failing_test_names(&self, stage_history_id: i64) -> Result<Vec<String>> {
+        sqlx::query_scalar!(
+            "SELECT name
+             FROM stage_test_results
+             WHERE stage_history_id = $1 AND status = 'failed'
+             ORDER BY id ASC",
+            stage_history_id,
+        )
+        .fetch_all(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load failing tests: {e}")))
+    }
+}
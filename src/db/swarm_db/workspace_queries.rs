@@ -0,0 +1,66 @@
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentWorkspace, RepoId, WorkspaceBackend};
+use chrono::{DateTime, Utc};
+
+pub type WorkspaceRow = (
+    i32,
+    String,
+    String,
+    String,
+    String,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+);
+
+pub fn to_agent_workspace(
+    (agent_id, bead_id, name, path, backend, created_at, removed_at): WorkspaceRow,
+) -> Result<AgentWorkspace> {
+    Ok(AgentWorkspace {
+        agent_id: agent_id.max(0).cast_unsigned(),
+        bead_id,
+        name,
+        path,
+        backend: WorkspaceBackend::try_from(backend.as_str()).map_err(SwarmError::SchemaDrift)?,
+        created_at,
+        removed_at,
+    })
+}
+
+impl SwarmDb {
+    /// The checkout `agent_id` currently owns, if any.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn get_active_workspace(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+    ) -> Result<Option<AgentWorkspace>> {
+        self.read_with_fallback(|pool| {
+            sqlx::query!(
+                "SELECT agent_id, bead_id, name, path, backend, created_at, removed_at
+                 FROM workspaces
+                 WHERE repo_id = $1 AND agent_id = $2 AND removed_at IS NULL",
+                repo_id.value(),
+                agent_id.cast_signed(),
+            )
+            .map(|row| -> WorkspaceRow {
+                (
+                    row.agent_id,
+                    row.bead_id,
+                    row.name,
+                    row.path,
+                    row.backend,
+                    row.created_at,
+                    row.removed_at,
+                )
+            })
+            .fetch_optional(pool)
+        })
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to load workspace: {e}")))?
+        .map(to_agent_workspace)
+        .transpose()
+    }
+}
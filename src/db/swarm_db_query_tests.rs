@@ -351,7 +351,13 @@ mod swarm_db_query_tests {
         async fn returns_empty_vec_when_no_contexts() {
             let pool = create_mock_pool();
             let db = SwarmDb::new_with_pool(pool);
-            let result = db.get_deep_resume_contexts(&create_test_repo_id()).await;
+            let result = db
+                .get_deep_resume_contexts(
+                    &create_test_repo_id(),
+                    None,
+                    crate::types::ContextBudget::default(),
+                )
+                .await;
 
             assert!(result.is_ok());
             assert!(result.unwrap().is_empty());
@@ -0,0 +1,276 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![forbid(unsafe_code)]
+
+use std::time::Duration;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::db::write_ops::{CommandAuditRow, ExecutionEventRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::query_perf::QUERY_LATENCY_FLUSH_MS;
+
+pub const DEFAULT_BATCH_FLUSH_MS: u64 = 50;
+pub const DEFAULT_BATCH_MAX_ROWS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteBatcherConfig {
+    pub flush_interval: Duration,
+    pub max_rows: usize,
+    /// How often the query latency histograms are written out; see
+    /// [`crate::query_perf`].
+    pub latency_flush_interval: Duration,
+}
+
+impl Default for WriteBatcherConfig {
+    fn default() -> Self {
+        Self {
+            flush_interval: Duration::from_millis(DEFAULT_BATCH_FLUSH_MS),
+            max_rows: DEFAULT_BATCH_MAX_ROWS,
+            latency_flush_interval: Duration::from_millis(QUERY_LATENCY_FLUSH_MS),
+        }
+    }
+}
+
+/// A row waiting to be written by the batcher.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PendingWrite {
+    Audit(CommandAuditRow),
+    Event(ExecutionEventRow),
+}
+
+enum BatchControl {
+    Flush(oneshot::Sender<()>),
+    Shutdown,
+}
+
+/// Cheap, cloneable sender side of a [`WriteBatcher`].
+#[derive(Clone)]
+pub struct WriteBatchHandle {
+    writes: mpsc::UnboundedSender<PendingWrite>,
+    control: mpsc::UnboundedSender<BatchControl>,
+}
+
+impl WriteBatchHandle {
+    /// Queues a row for the next flush.
+    ///
+    /// # Errors
+    /// Hands the row back when the batcher has shut down so the caller can
+    /// write it directly with [`SwarmDb::write_now`].
+    pub fn enqueue(&self, write: PendingWrite) -> std::result::Result<(), Box<PendingWrite>> {
+        self.writes.send(write).map_err(|error| Box::new(error.0))
+    }
+
+    /// Waits until everything queued so far has been written (or dropped on error).
+    pub async fn flush(&self) {
+        let (done_tx, done_rx) = oneshot::channel();
+        if self.control.send(BatchControl::Flush(done_tx)).is_ok() {
+            let _ = done_rx.await;
+        }
+    }
+}
+
+/// Write-behind buffer that coalesces `command_audit` and `execution_events`
+/// inserts into multi-row statements, and periodically writes out the query
+/// latency histograms.
+pub struct WriteBatcher {
+    handle: WriteBatchHandle,
+    task: JoinHandle<Result<()>>,
+}
+
+impl WriteBatcher {
+    #[must_use]
+    pub fn spawn(db: SwarmDb, config: WriteBatcherConfig) -> Self {
+        let (writes, write_rx) = mpsc::unbounded_channel();
+        let (control, control_rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(run_batcher(db, write_rx, control_rx, config));
+        Self {
+            handle: WriteBatchHandle { writes, control },
+            task,
+        }
+    }
+
+    #[must_use]
+    pub fn handle(&self) -> WriteBatchHandle {
+        self.handle.clone()
+    }
+
+    /// Flushes pending rows and stops the background task.
+    ///
+    /// # Errors
+    /// Returns the first flush error seen over the batcher's lifetime.
+    pub async fn shutdown(self) -> Result<()> {
+        let _ = self.handle.control.send(BatchControl::Shutdown);
+        self.task
+            .await
+            .map_err(|error| SwarmError::Internal(format!("Write batcher task failed: {error}")))?
+    }
+}
+
+#[derive(Default)]
+struct PendingRows {
+    audits: Vec<CommandAuditRow>,
+    events: Vec<ExecutionEventRow>,
+}
+
+impl PendingRows {
+    fn push(&mut self, write: PendingWrite) {
+        match write {
+            PendingWrite::Audit(row) => self.audits.push(row),
+            PendingWrite::Event(row) => self.events.push(row),
+        }
+    }
+
+    const fn len(&self) -> usize {
+        self.audits.len().saturating_add(self.events.len())
+    }
+
+    async fn flush(&mut self, db: &SwarmDb) -> Result<()> {
+        let audits = std::mem::take(&mut self.audits);
+        let events = std::mem::take(&mut self.events);
+        let audit_result = db.insert_command_audit_batch(&audits).await;
+        let event_result = db.insert_execution_event_batch(&events).await;
+        audit_result.and(event_result)
+    }
+}
+
+async fn run_batcher(
+    db: SwarmDb,
+    mut writes: mpsc::UnboundedReceiver<PendingWrite>,
+    mut control: mpsc::UnboundedReceiver<BatchControl>,
+    config: WriteBatcherConfig,
+) -> Result<()> {
+    let mut pending = PendingRows::default();
+    let mut first_error: Option<SwarmError> = None;
+    let mut ticker = tokio::time::interval(config.flush_interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut latency_ticker = tokio::time::interval_at(
+        tokio::time::Instant::now() + config.latency_flush_interval,
+        config.latency_flush_interval,
+    );
+    latency_ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        let flush_now = tokio::select! {
+            biased;
+            write = writes.recv() => {
+                let Some(write) = write else {
+                    record_error(&mut first_error, pending.flush(&db).await);
+                    record_error(&mut first_error, flush_latency(&db).await);
+                    return first_error.map_or(Ok(()), Err);
+                };
+                pending.push(write);
+                pending.len() >= config.max_rows.max(1)
+            },
+            command = control.recv() => {
+                while let Ok(write) = writes.try_recv() {
+                    pending.push(write);
+                }
+                record_error(&mut first_error, pending.flush(&db).await);
+                match command {
+                    Some(BatchControl::Flush(done)) => {
+                        let _ = done.send(());
+                        false
+                    }
+                    Some(BatchControl::Shutdown) | None => {
+                        record_error(&mut first_error, flush_latency(&db).await);
+                        return first_error.map_or(Ok(()), Err);
+                    }
+                }
+            },
+            _ = ticker.tick() => pending.len() > 0,
+            _ = latency_ticker.tick() => {
+                if let Err(error) = flush_latency(&db).await {
+                    tracing::warn!(%error, "Query latency flush failed");
+                }
+                false
+            },
+        };
+
+        if flush_now {
+            record_error(&mut first_error, pending.flush(&db).await);
+        }
+    }
+}
+
+async fn flush_latency(db: &SwarmDb) -> Result<()> {
+    db.flush_query_latency().await.map(|_rows| ())
+}
+
+fn record_error(first_error: &mut Option<SwarmError>, result: Result<()>) {
+    if let Err(error) = result {
+        first_error.get_or_insert(error);
+    }
+}
+
+impl SwarmDb {
+    /// Writes a single row immediately, bypassing any attached batcher.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn write_now(&self, write: PendingWrite) -> Result<()> {
+        match write {
+            PendingWrite::Audit(row) => self.insert_command_audit_batch(&[row]).await,
+            PendingWrite::Event(row) => self.insert_execution_event_batch(&[row]).await,
+        }
+    }
+
+    /// Waits for the attached batcher, if any, to drain its queue.
+    pub async fn flush_write_batch(&self) {
+        if let Some(batch) = self.write_batch() {
+            batch.flush().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PendingRows, PendingWrite, WriteBatcher, WriteBatcherConfig};
+    use crate::db::write_ops::CommandAuditRow;
+    use crate::db::SwarmDb;
+    use sqlx::postgres::PgPoolOptions;
+
+    fn audit_row(cmd: &str) -> CommandAuditRow {
+        CommandAuditRow {
+            cmd: cmd.to_string(),
+            rid: None,
+            args: serde_json::json!({}),
+            ok: true,
+            ms: 1,
+            error_code: None,
+        }
+    }
+
+    #[test]
+    fn given_mixed_writes_when_buffering_then_len_counts_both_tables() {
+        let mut pending = PendingRows::default();
+        pending.push(PendingWrite::Audit(audit_row("status")));
+        pending.push(PendingWrite::Audit(audit_row("monitor")));
+
+        assert_eq!(pending.len(), 2);
+        assert!(pending.events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn given_shut_down_batcher_when_enqueueing_then_row_is_handed_back() {
+        let pool = PgPoolOptions::new().connect_lazy("postgres://localhost:1/unused");
+        let Ok(pool) = pool else {
+            return;
+        };
+        let batcher =
+            WriteBatcher::spawn(SwarmDb::new_with_pool(pool), WriteBatcherConfig::default());
+        let handle = batcher.handle();
+
+        assert!(batcher.shutdown().await.is_ok());
+        assert_eq!(
+            handle.enqueue(PendingWrite::Audit(audit_row("status"))),
+            Err(Box::new(PendingWrite::Audit(audit_row("status"))))
+        );
+    }
+}
@@ -1,12 +1,14 @@
 #![deny(clippy::unwrap_used)]
 #![deny(clippy::expect_used)]
-#![deny(clippy::panic)]
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
 #![forbid(unsafe_code)]
 
+use super::copy_ops::copy_idle_agents;
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
+use crate::runtime::{bead_states, BeadLifecycle};
 use crate::types::{AgentId, BeadId, RepoId};
 use sqlx::Acquire;
 use std::collections::HashSet;
@@ -15,13 +17,13 @@ impl SwarmDb {
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn register_repo(&self, repo_id: &RepoId, name: &str, path: &str) -> Result<()> {
-        sqlx::query(
+        sqlx::query!(
             "INSERT INTO repos (repo_id, name, path) VALUES ($1, $2, $3)
              ON CONFLICT (repo_id) DO NOTHING",
+            repo_id.value(),
+            name,
+            path,
         )
-        .bind(repo_id.value())
-        .bind(name)
-        .bind(path)
         .execute(self.pool())
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to register repo: {e}")))
@@ -41,22 +43,24 @@ impl SwarmDb {
             )
             .await?;
 
-            sqlx::query(
+            sqlx::query!(
                 "INSERT INTO agent_state (repo_id, agent_id, status) VALUES ($1, $2, 'idle')
-                 ON CONFLICT (repo_id, agent_id) DO NOTHING",
+                 ON CONFLICT (repo_id, agent_id) DO UPDATE
+                 SET deleted_at = NULL, deleted_by = NULL
+                 WHERE agent_state.deleted_at IS NOT NULL",
+                agent_id.repo_id().value(),
+                agent_id.number().cast_signed(),
             )
-            .bind(agent_id.repo_id().value())
-            .bind(agent_id.number().cast_signed())
             .execute(self.pool())
             .await
             .map(|rows| rows.rows_affected() > 0)
             .map_err(|e| SwarmError::DatabaseError(format!("Failed to register agent: {e}")))
         } else {
-            sqlx::query(
+            sqlx::query!(
                 "INSERT INTO agent_state (agent_id, status) VALUES ($1, 'idle')
                  ON CONFLICT (agent_id) DO NOTHING",
+                agent_id.number().cast_signed(),
             )
-            .bind(agent_id.number().cast_signed())
             .execute(self.pool())
             .await
             .map(|rows| rows.rows_affected() > 0)
@@ -64,6 +68,44 @@ impl SwarmDb {
         }
     }
 
+    /// Registers agents 1 through `count` of `repo_id` in one statement, so
+    /// a failed insert leaves none of them registered. Agents that already
+    /// exist are kept, and soft-deleted ones come back.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn register_agents(&self, repo_id: &RepoId, count: u32) -> Result<()> {
+        let repo_scoped = self.table_has_column("agent_state", "repo_id").await?;
+
+        if repo_scoped {
+            self.register_repo(repo_id, repo_id.value(), repo_id.value())
+                .await?;
+
+            sqlx::query!(
+                "INSERT INTO agent_state (repo_id, agent_id, status)
+                 SELECT $1, agent_id, 'idle' FROM generate_series(1, $2) AS agent_id
+                 ON CONFLICT (repo_id, agent_id) DO UPDATE
+                 SET deleted_at = NULL, deleted_by = NULL
+                 WHERE agent_state.deleted_at IS NOT NULL",
+                repo_id.value(),
+                count.cast_signed(),
+            )
+            .execute(self.pool())
+            .await
+        } else {
+            sqlx::query!(
+                "INSERT INTO agent_state (agent_id, status)
+                 SELECT agent_id, 'idle' FROM generate_series(1, $1) AS agent_id
+                 ON CONFLICT (agent_id) DO NOTHING",
+                count.cast_signed(),
+            )
+            .execute(self.pool())
+            .await
+        }
+        .map(|_result| ())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to register agents: {e}")))
+    }
+
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn seed_idle_agents(&self, count: u32) -> Result<()> {
@@ -97,19 +139,19 @@ impl SwarmDb {
 
         let mut next_candidate = 1_u32;
         let agents_to_add = target_count - idle_unassigned_count;
+        let mut new_ids = Vec::new();
         for _ in 0..agents_to_add {
             while occupied_ids.contains(&next_candidate) {
                 next_candidate = next_candidate.saturating_add(1);
             }
 
-            self.insert_idle_agent(repo_scoped, &default_repo, next_candidate)
-                .await?;
-
+            new_ids.push(next_candidate.cast_signed());
             occupied_ids.insert(next_candidate);
             next_candidate = next_candidate.saturating_add(1);
         }
 
-        Ok(())
+        self.insert_idle_agents(repo_scoped.then_some(&default_repo), &new_ids)
+            .await
     }
 
     async fn prune_idle_unassigned_agents(
@@ -119,27 +161,30 @@ impl SwarmDb {
         count: u32,
     ) -> Result<()> {
         if repo_scoped {
-            sqlx::query(
-                "DELETE FROM agent_state
+            sqlx::query!(
+                "UPDATE agent_state
+                 SET deleted_at = NOW(), deleted_by = 'seed'
                  WHERE repo_id = $1
                    AND status = 'idle'
                    AND bead_id IS NULL
+                   AND deleted_at IS NULL
                    AND agent_id IN (
                      SELECT agent_id
                      FROM agent_state
                      WHERE repo_id = $1 AND status = 'idle' AND bead_id IS NULL
+                       AND deleted_at IS NULL
                      ORDER BY agent_id DESC
                      OFFSET $2
                    )",
+                default_repo.value(),
+                i64::from(count),
             )
-            .bind(default_repo.value())
-            .bind(count.cast_signed())
             .execute(self.pool())
             .await
             .map(|_result| ())
             .map_err(|e| SwarmError::DatabaseError(format!("Failed to prune idle agents: {e}")))
         } else {
-            sqlx::query(
+            sqlx::query!(
                 "DELETE FROM agent_state
                  WHERE status = 'idle'
                    AND bead_id IS NULL
@@ -150,8 +195,8 @@ impl SwarmDb {
                      ORDER BY agent_id DESC
                      OFFSET $1
                    )",
+                i64::from(count),
             )
-            .bind(count.cast_signed())
             .execute(self.pool())
             .await
             .map(|_result| ())
@@ -165,20 +210,20 @@ impl SwarmDb {
         default_repo: &RepoId,
     ) -> Result<Vec<i32>> {
         if repo_scoped {
-            sqlx::query_scalar::<_, i32>(
+            sqlx::query_scalar!(
                 "SELECT agent_id
                  FROM agent_state
-                 WHERE repo_id = $1
+                 WHERE repo_id = $1 AND deleted_at IS NULL
                  ORDER BY agent_id ASC",
+                default_repo.value(),
             )
-            .bind(default_repo.value())
             .fetch_all(self.pool())
             .await
             .map_err(|e| {
                 SwarmError::DatabaseError(format!("Failed to load existing seeded agents: {e}"))
             })
         } else {
-            sqlx::query_scalar::<_, i32>("SELECT agent_id FROM agent_state ORDER BY agent_id ASC")
+            sqlx::query_scalar!("SELECT agent_id FROM agent_state ORDER BY agent_id ASC")
                 .fetch_all(self.pool())
                 .await
                 .map_err(|e| {
@@ -193,20 +238,20 @@ impl SwarmDb {
         default_repo: &RepoId,
     ) -> Result<i64> {
         if repo_scoped {
-            sqlx::query_scalar::<_, i64>(
-                "SELECT COUNT(*)
+            sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!"
                  FROM agent_state
-                 WHERE repo_id = $1 AND status = 'idle' AND bead_id IS NULL",
+                 WHERE repo_id = $1 AND status = 'idle' AND bead_id IS NULL AND deleted_at IS NULL"#,
+                default_repo.value(),
             )
-            .bind(default_repo.value())
             .fetch_one(self.pool())
             .await
             .map_err(|e| {
                 SwarmError::DatabaseError(format!("Failed to count idle unassigned agents: {e}"))
             })
         } else {
-            sqlx::query_scalar::<_, i64>(
-                "SELECT COUNT(*) FROM agent_state WHERE status = 'idle' AND bead_id IS NULL",
+            sqlx::query_scalar!(
+                r#"SELECT COUNT(*) AS "count!" FROM agent_state WHERE status = 'idle' AND bead_id IS NULL"#,
             )
             .fetch_one(self.pool())
             .await
@@ -216,45 +261,33 @@ impl SwarmDb {
         }
     }
 
-    async fn insert_idle_agent(
-        &self,
-        repo_scoped: bool,
-        default_repo: &RepoId,
-        agent_number: u32,
-    ) -> Result<()> {
-        if repo_scoped {
-            sqlx::query(
-                "INSERT INTO agent_state (repo_id, agent_id, status)
-                 VALUES ($1, $2, 'idle')
-                 ON CONFLICT (repo_id, agent_id) DO NOTHING",
-            )
-            .bind(default_repo.value())
-            .bind(agent_number.cast_signed())
-            .execute(self.pool())
+    async fn insert_idle_agents(&self, repo_id: Option<&RepoId>, agent_ids: &[i32]) -> Result<()> {
+        let mut tx = self
+            .pool()
+            .begin()
             .await
-            .map(|_result| ())
-            .map_err(|e| {
-                SwarmError::DatabaseError(format!("Failed to seed agent {agent_number}: {e}"))
-            })
-        } else {
-            sqlx::query(
-                "INSERT INTO agent_state (agent_id, status)
-                 VALUES ($1, 'idle')
-                 ON CONFLICT (agent_id) DO NOTHING",
-            )
-            .bind(agent_number.cast_signed())
-            .execute(self.pool())
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
             .await
-            .map(|_result| ())
-            .map_err(|e| {
-                SwarmError::DatabaseError(format!("Failed to seed agent {agent_number}: {e}"))
-            })
-        }
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+        copy_idle_agents(conn, repo_id.map(RepoId::value), agent_ids).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))
     }
 
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn release_agent(&self, agent_id: &AgentId) -> Result<Option<BeadId>> {
+        // The bead goes back to the backlog from whichever of these the agent
+        // left it in; completed and cancelled beads keep their status.
+        let active = BeadLifecycle::<bead_states::InProgress>::observed();
+        let held = BeadLifecycle::<bead_states::AwaitingApproval>::observed();
+        let blocked = BeadLifecycle::<bead_states::Blocked>::observed();
+        let [released, ..] = [active.requeue(), held.requeue(), blocked.requeue()];
         let mut tx = self
             .pool()
             .begin()
@@ -266,20 +299,20 @@ impl SwarmDb {
             .await
             .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
 
-        let bead = sqlx::query_scalar::<_, Option<String>>(
+        let bead = sqlx::query_scalar!(
             "SELECT bead_id
              FROM agent_state
-             WHERE repo_id = $1 AND agent_id = $2
+             WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL
              FOR UPDATE",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(agent_id.number().cast_signed())
         .fetch_optional(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to read agent state: {e}")))?
         .flatten();
 
-        sqlx::query(
+        sqlx::query!(
             "UPDATE agent_state
              SET bead_id = NULL,
                  current_stage = NULL,
@@ -287,17 +320,16 @@ impl SwarmDb {
                  status = 'idle',
                  feedback = NULL,
                  implementation_attempt = 0
-             WHERE repo_id = $1 AND agent_id = $2",
+             WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(agent_id.number().cast_signed())
         .execute(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to reset agent state: {e}")))?;
 
         if let Some(bead_id) = bead.as_deref() {
-            sqlx::query("DELETE FROM agent_messages WHERE bead_id = $1")
-                .bind(bead_id)
+            sqlx::query!("DELETE FROM agent_messages WHERE bead_id = $1", bead_id)
                 .execute(&mut *conn)
                 .await
                 .map_err(|e| {
@@ -306,24 +338,35 @@ impl SwarmDb {
                     ))
                 })?;
 
-            sqlx::query("DELETE FROM bead_claims WHERE repo_id = $1 AND bead_id = $2")
-                .bind(agent_id.repo_id().value())
-                .bind(bead_id)
-                .execute(&mut *conn)
-                .await
-                .map_err(|e| {
-                    SwarmError::DatabaseError(format!("Failed to clear bead claim on release: {e}"))
-                })?;
+            sqlx::query!(
+                "UPDATE bead_claims
+                 SET deleted_at = NOW(), deleted_by = 'release'
+                 WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL",
+                agent_id.repo_id().value(),
+                bead_id,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to clear bead claim on release: {e}"))
+            })?;
 
-            sqlx::query(
+            sqlx::query!(
                 "UPDATE bead_backlog
-                 SET status = 'pending'
+                 SET status = $3
                  WHERE repo_id = $1
                    AND bead_id = $2
-                   AND status <> 'completed'",
+                   AND status = ANY($4)
+                   AND deleted_at IS NULL",
+                agent_id.repo_id().value(),
+                bead_id,
+                released.backlog_status(),
+                &[
+                    active.backlog_status(),
+                    held.backlog_status(),
+                    blocked.backlog_status(),
+                ] as &[&str],
             )
-            .bind(agent_id.repo_id().value())
-            .bind(bead_id)
             .execute(&mut *conn)
             .await
             .map_err(|e| {
@@ -337,4 +380,24 @@ impl SwarmDb {
 
         Ok(bead.map(BeadId::new))
     }
+
+    /// Replaces `agent_id`'s default claim labels. Returns `false` when the
+    /// agent is not registered.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn set_agent_labels(&self, agent_id: &AgentId, labels: &[String]) -> Result<bool> {
+        sqlx::query!(
+            "UPDATE agent_state
+             SET labels = $3
+             WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            labels,
+        )
+        .execute(self.pool())
+        .await
+        .map(|rows| rows.rows_affected() > 0)
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to set agent labels: {e}")))
+    }
 }
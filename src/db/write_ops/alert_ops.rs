@@ -0,0 +1,147 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::swarm_db::{to_swarm_alert, AlertRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AlertBreach, AlertKind, EventSchemaVersion, RepoId, SwarmAlert};
+
+impl SwarmDb {
+    /// Opens a pending alert for a breach that has to hold before it fires.
+    /// Returns `None` when the rule already has an open alert.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn open_alert(
+        &self,
+        repo_id: &RepoId,
+        breach: &AlertBreach,
+    ) -> Result<Option<SwarmAlert>> {
+        sqlx::query!(
+            "INSERT INTO alerts (repo_id, kind, status, detail)
+             VALUES ($1, $2, 'pending', $3)
+             ON CONFLICT (repo_id, kind) WHERE resolved_at IS NULL DO NOTHING
+             RETURNING kind, status, detail, breached_at, fired_at, resolved_at",
+            repo_id.value(),
+            breach.kind.as_str(),
+            &breach.detail,
+        )
+        .map(|row| -> AlertRow {
+            (
+                row.kind,
+                row.status,
+                row.detail,
+                row.breached_at,
+                row.fired_at,
+                row.resolved_at,
+            )
+        })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to open alert: {e}")))?
+        .map(to_swarm_alert)
+        .transpose()
+    }
+
+    /// Fires the rule's pending alert, or opens one already firing. Returns
+    /// `None` when the alert was firing before, so callers notify only once.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn fire_alert(
+        &self,
+        repo_id: &RepoId,
+        breach: &AlertBreach,
+    ) -> Result<Option<SwarmAlert>> {
+        sqlx::query!(
+            "INSERT INTO alerts (repo_id, kind, status, detail, fired_at)
+             VALUES ($1, $2, 'firing', $3, NOW())
+             ON CONFLICT (repo_id, kind) WHERE resolved_at IS NULL DO UPDATE
+             SET status = 'firing', detail = EXCLUDED.detail, fired_at = NOW()
+             WHERE alerts.status = 'pending'
+             RETURNING kind, status, detail, breached_at, fired_at, resolved_at",
+            repo_id.value(),
+            breach.kind.as_str(),
+            &breach.detail,
+        )
+        .map(|row| -> AlertRow {
+            (
+                row.kind,
+                row.status,
+                row.detail,
+                row.breached_at,
+                row.fired_at,
+                row.resolved_at,
+            )
+        })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to fire alert: {e}")))?
+        .map(to_swarm_alert)
+        .transpose()
+    }
+
+    /// Resolves the rule's open alert. Returns `None` when nothing was open.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn resolve_alert(
+        &self,
+        repo_id: &RepoId,
+        kind: AlertKind,
+    ) -> Result<Option<SwarmAlert>> {
+        sqlx::query!(
+            "UPDATE alerts
+             SET status = 'resolved', resolved_at = NOW()
+             WHERE repo_id = $1 AND kind = $2 AND resolved_at IS NULL
+             RETURNING kind, status, detail, breached_at, fired_at, resolved_at",
+            repo_id.value(),
+            kind.as_str(),
+        )
+        .map(|row| -> AlertRow {
+            (
+                row.kind,
+                row.status,
+                row.detail,
+                row.breached_at,
+                row.fired_at,
+                row.resolved_at,
+            )
+        })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to resolve alert: {e}")))?
+        .map(to_swarm_alert)
+        .transpose()
+    }
+
+    /// Appends an `alert_fired` or `alert_resolved` execution event. Alerts
+    /// are swarm-wide, so the event has no bead or agent.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn record_alert_event(
+        &self,
+        repo_id: &RepoId,
+        event_type: &str,
+        alert: &SwarmAlert,
+    ) -> Result<()> {
+        let payload = serde_json::to_value(alert)?;
+        sqlx::query!(
+            "INSERT INTO execution_events (schema_version, event_type, entity_id, payload)
+             VALUES ($1, $2, $3, $4)",
+            EventSchemaVersion::LATEST.as_i32(),
+            event_type,
+            format!("repo:{}:alert:{}", repo_id.value(), alert.kind.as_str()),
+            payload,
+        )
+        .execute(self.pool())
+        .await
+        .map(|_| ())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to record alert event: {e}")))
+    }
+}
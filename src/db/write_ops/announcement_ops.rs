@@ -0,0 +1,85 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::swarm_db::{to_announcement, AnnouncementRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentId, Announcement, RepoId};
+
+impl SwarmDb {
+    /// Posts an announcement that every agent sees until it expires in
+    /// `ttl_secs` or the agent acknowledges it.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn create_announcement(
+        &self,
+        repo_id: &RepoId,
+        from: &str,
+        topic: &str,
+        msg: &str,
+        ttl_secs: u64,
+    ) -> Result<Announcement> {
+        sqlx::query!(
+            "INSERT INTO announcements (repo_id, topic, msg, from_agent, expires_at)
+             VALUES ($1, $2, $3, $4, NOW() + make_interval(secs => $5::BIGINT))
+             RETURNING id, topic, msg, from_agent, created_at, expires_at",
+            repo_id.value(),
+            topic,
+            msg,
+            from,
+            ttl_secs.cast_signed(),
+        )
+        .map(|row| -> AnnouncementRow {
+            (
+                row.id,
+                row.topic,
+                row.msg,
+                row.from_agent,
+                row.created_at,
+                row.expires_at,
+            )
+        })
+        .fetch_one(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to create announcement: {e}")))
+        .map(to_announcement)
+    }
+
+    /// Acknowledges announcement `id`, or every unexpired announcement on
+    /// `topic` (all topics when `None`), for `agent`. Returns the ids newly
+    /// acknowledged; ones already acknowledged or expired are skipped.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn ack_announcements(
+        &self,
+        agent: &AgentId,
+        id: Option<i64>,
+        topic: Option<&str>,
+    ) -> Result<Vec<i64>> {
+        sqlx::query_scalar!(
+            "INSERT INTO announcement_acks (announcement_id, agent_id)
+             SELECT a.id, $2
+             FROM announcements a
+             WHERE a.repo_id = $1
+               AND a.expires_at > NOW()
+               AND ($3::BIGINT IS NULL OR a.id = $3)
+               AND ($4::TEXT IS NULL OR a.topic = $4)
+             ORDER BY a.id
+             ON CONFLICT (announcement_id, agent_id) DO NOTHING
+             RETURNING announcement_id",
+            agent.repo_id().value(),
+            agent.number().cast_signed(),
+            id,
+            topic,
+        )
+        .fetch_all(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to acknowledge announcements: {e}")))
+    }
+}
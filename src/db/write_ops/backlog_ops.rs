@@ -0,0 +1,243 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{
+    BacklogEditOutcome, BacklogEditSkip, BacklogSelector, EventSchemaVersion, PriorityChange,
+    RepoId,
+};
+use serde_json::json;
+use sqlx::{Acquire, PgConnection};
+
+/// A selected backlog row as read before the edit: bead id, priority,
+/// status, and whether a live reservation holds it.
+type SelectedRow = (String, String, String, bool);
+
+impl SwarmDb {
+    /// Sets the priority of every selected pending bead to `to`. The write
+    /// only applies to rows still pending, so a bead claimed after it was
+    /// read is reported as skipped rather than edited under its claim.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn reprioritize_backlog(
+        &self,
+        repo_id: &RepoId,
+        selector: &BacklogSelector,
+        to: &str,
+    ) -> Result<BacklogEditOutcome> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let rows = select_backlog_rows(&mut *conn, repo_id, selector).await?;
+        let mut outcome = outcome_with_missing(selector, &rows);
+        let mut candidates = Vec::new();
+        for (bead_id, priority, status, _) in rows {
+            if status != "pending" {
+                outcome.skipped.push(BacklogEditSkip {
+                    bead_id,
+                    reason: status,
+                });
+            } else if priority.eq_ignore_ascii_case(to) {
+                outcome.unchanged += 1;
+            } else {
+                candidates.push(PriorityChange {
+                    bead_id,
+                    from: priority,
+                    to: to.to_string(),
+                });
+            }
+        }
+
+        let updated = sqlx::query_scalar!(
+            "UPDATE bead_backlog
+             SET priority = $3
+             WHERE repo_id = $1 AND bead_id = ANY($2) AND status = 'pending'
+               AND deleted_at IS NULL
+             RETURNING bead_id",
+            repo_id.value(),
+            &candidates
+                .iter()
+                .map(|change| change.bead_id.clone())
+                .collect::<Vec<_>>(),
+            to,
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to update bead priority: {e}")))?;
+
+        for change in candidates {
+            if updated.contains(&change.bead_id) {
+                outcome.reprioritized.push(change);
+            } else {
+                outcome.skipped.push(BacklogEditSkip {
+                    bead_id: change.bead_id,
+                    reason: "claimed_during_edit".to_string(),
+                });
+            }
+        }
+
+        record_backlog_edit(&mut *conn, repo_id, "reprioritize", &outcome).await?;
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(outcome)
+    }
+
+    /// Removes every selected bead that is still pending and not reserved.
+    /// Removed rows stay behind, deleted by `backlog-remove`.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn remove_backlog_beads(
+        &self,
+        repo_id: &RepoId,
+        selector: &BacklogSelector,
+    ) -> Result<BacklogEditOutcome> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let rows = select_backlog_rows(&mut *conn, repo_id, selector).await?;
+        let mut outcome = outcome_with_missing(selector, &rows);
+        let mut candidates = Vec::new();
+        for (bead_id, _, status, reserved) in rows {
+            if status != "pending" {
+                outcome.skipped.push(BacklogEditSkip {
+                    bead_id,
+                    reason: status,
+                });
+            } else if reserved {
+                outcome.skipped.push(BacklogEditSkip {
+                    bead_id,
+                    reason: "reserved".to_string(),
+                });
+            } else {
+                candidates.push(bead_id);
+            }
+        }
+
+        let removed = sqlx::query_scalar!(
+            "UPDATE bead_backlog b
+             SET deleted_at = NOW(), deleted_by = 'backlog-remove'
+             WHERE b.repo_id = $1
+               AND b.bead_id = ANY($2)
+               AND b.status = 'pending'
+               AND b.deleted_at IS NULL
+               AND NOT EXISTS (
+                   SELECT 1
+                   FROM bead_reservations r
+                   WHERE r.repo_id = b.repo_id AND r.bead_id = b.bead_id AND r.expires_at > NOW()
+               )
+             RETURNING b.bead_id",
+            repo_id.value(),
+            &candidates,
+        )
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to remove backlog beads: {e}")))?;
+
+        for bead_id in candidates {
+            if removed.contains(&bead_id) {
+                outcome.removed.push(bead_id);
+            } else {
+                outcome.skipped.push(BacklogEditSkip {
+                    bead_id,
+                    reason: "claimed_during_edit".to_string(),
+                });
+            }
+        }
+
+        record_backlog_edit(&mut *conn, repo_id, "remove", &outcome).await?;
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(outcome)
+    }
+}
+
+async fn select_backlog_rows(
+    conn: &mut PgConnection,
+    repo_id: &RepoId,
+    selector: &BacklogSelector,
+) -> Result<Vec<SelectedRow>> {
+    sqlx::query!(
+        r#"SELECT b.bead_id, b.priority, b.status, EXISTS (
+                SELECT 1
+                FROM bead_reservations r
+                WHERE r.repo_id = b.repo_id AND r.bead_id = b.bead_id AND r.expires_at > NOW()
+            ) AS "reserved!"
+         FROM bead_backlog b
+         WHERE b.repo_id = $1
+           AND b.deleted_at IS NULL
+           AND (cardinality($2::TEXT[]) = 0 OR b.bead_id = ANY($2::TEXT[]))
+           AND ($3::TEXT IS NULL OR $3 = ANY(b.labels))
+         ORDER BY b.created_at ASC, b.bead_id ASC"#,
+        repo_id.value(),
+        &selector.bead_ids,
+        selector.label.as_deref(),
+    )
+    .map(|row| -> SelectedRow { (row.bead_id, row.priority, row.status, row.reserved) })
+    .fetch_all(conn)
+    .await
+    .map_err(|e| SwarmError::DatabaseError(format!("Failed to read backlog: {e}")))
+}
+
+fn outcome_with_missing(selector: &BacklogSelector, rows: &[SelectedRow]) -> BacklogEditOutcome {
+    BacklogEditOutcome {
+        missing: selector
+            .bead_ids
+            .iter()
+            .filter(|bead_id| !rows.iter().any(|(found, ..)| found == *bead_id))
+            .cloned()
+            .collect(),
+        ..BacklogEditOutcome::default()
+    }
+}
+
+async fn record_backlog_edit(
+    conn: &mut PgConnection,
+    repo_id: &RepoId,
+    action: &str,
+    outcome: &BacklogEditOutcome,
+) -> Result<()> {
+    if outcome.reprioritized.is_empty() && outcome.removed.is_empty() {
+        return Ok(());
+    }
+    sqlx::query!(
+        "INSERT INTO execution_events (schema_version, event_type, entity_id, payload)
+         VALUES ($1, 'backlog_edited', $2, $3)",
+        EventSchemaVersion::LATEST.as_i32(),
+        format!("repo:{}:backlog", repo_id.value()),
+        json!({
+            "action": action,
+            "reprioritized": outcome.reprioritized,
+            "removed": outcome.removed,
+            "skipped": outcome.skipped,
+        }),
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| SwarmError::DatabaseError(format!("Failed to write backlog event: {e}")))
+    .map(|_result| ())
+}
@@ -1,22 +1,28 @@
 #![deny(clippy::unwrap_used)]
 #![deny(clippy::expect_used)]
-#![deny(clippy::panic)]
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
 #![forbid(unsafe_code)]
 
+use super::copy_ops::copy_backlog_entries;
 use super::helpers::redact_sensitive;
+use super::kv_ops::clear_bead_kv;
 use super::types::{ExecutionEventWriteInput, FailureDiagnosticsPayload};
+use crate::beads_sync::CoordinatorSyncTerminal;
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
-use crate::types::{AgentId, BeadId, RepoId};
+use crate::runtime::{bead_states, BeadLifecycle};
+use crate::types::{AgentId, BacklogEntry, BacklogSyncDiff, BeadId, EventSchemaVersion, RepoId};
 use serde_json::json;
 use sqlx::Acquire;
 
 impl SwarmDb {
     /// # Errors
     /// Returns an error if the database operation fails.
+    #[allow(clippy::too_many_lines)]
     pub async fn claim_bead(&self, agent_id: &AgentId, bead_id: &BeadId) -> Result<bool> {
+        let claimed = BeadLifecycle::pending().claim();
         let mut tx = self
             .pool()
             .begin()
@@ -28,30 +34,53 @@ impl SwarmDb {
             .await
             .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
 
-        sqlx::query(
-            "SELECT 1
+        let quarantine_reason = sqlx::query_scalar!(
+            "SELECT reason
+             FROM agent_quarantine
+             WHERE repo_id = $1 AND agent_id = $2 AND released_at IS NULL",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to inspect quarantine: {e}")))?;
+
+        if let Some(reason) = quarantine_reason {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Err(SwarmError::AgentError(format!(
+                "Agent {} is quarantined: {reason}",
+                agent_id.number()
+            )));
+        }
+
+        sqlx::query!(
+            "SELECT bead_id
              FROM bead_backlog
-             WHERE repo_id = $1 AND bead_id = $2
+             WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL
              FOR UPDATE",
+            agent_id.repo_id().value(),
+            bead_id.value(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(bead_id.value())
         .fetch_optional(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to lock backlog bead: {e}")))?;
 
-        let already_claimed = sqlx::query_scalar::<_, bool>(
-            "SELECT EXISTS(
+        let already_claimed = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
                  SELECT 1
                  FROM bead_claims
                  WHERE repo_id = $1
                    AND bead_id = $2
-                   AND status = 'in_progress'
+                   AND status = $3
+                   AND deleted_at IS NULL
                  FOR UPDATE
-             )",
+             ) AS "exists!""#,
+            agent_id.repo_id().value(),
+            bead_id.value(),
+            claimed.claim_status(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(bead_id.value())
         .fetch_one(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to inspect bead claims: {e}")))?;
@@ -63,26 +92,37 @@ impl SwarmDb {
             return Ok(false);
         }
 
-        sqlx::query(
+        sqlx::query!(
             "INSERT INTO bead_backlog (repo_id, bead_id, priority, status)
-             VALUES ($1, $2, 'p0', 'in_progress')
+             VALUES ($1, $2, 'p0', $3)
              ON CONFLICT (repo_id, bead_id)
-             DO UPDATE SET status = 'in_progress'",
+             DO UPDATE SET status = $3, deleted_at = NULL, deleted_by = NULL",
+            agent_id.repo_id().value(),
+            bead_id.value(),
+            claimed.backlog_status(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(bead_id.value())
         .execute(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to update backlog bead: {e}")))?;
 
-        let claim_insert = sqlx::query(
+        let claim_insert = sqlx::query!(
             "INSERT INTO bead_claims (repo_id, bead_id, claimed_by, status, heartbeat_at, lease_expires_at)
-             VALUES ($1, $2, $3, 'in_progress', NOW(), NOW() + INTERVAL '5 minutes')
-             ON CONFLICT (repo_id, bead_id) DO NOTHING",
+             VALUES ($1, $2, $3, $4, NOW(), NOW() + swarm_lease_ttl())
+             ON CONFLICT (repo_id, bead_id) DO UPDATE
+             SET claimed_by = EXCLUDED.claimed_by,
+                 status = EXCLUDED.status,
+                 claimed_at = NOW(),
+                 heartbeat_at = EXCLUDED.heartbeat_at,
+                 lease_expires_at = EXCLUDED.lease_expires_at,
+                 takeover_consented_at = NULL,
+                 deleted_at = NULL,
+                 deleted_by = NULL
+             WHERE bead_claims.deleted_at IS NOT NULL",
+            agent_id.repo_id().value(),
+            bead_id.value(),
+            agent_id.number().cast_signed(),
+            claimed.claim_status(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(bead_id.value())
-        .bind(agent_id.number().cast_signed())
         .execute(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to claim bead: {e}")))?;
@@ -94,7 +134,7 @@ impl SwarmDb {
             return Ok(false);
         }
 
-        sqlx::query(
+        sqlx::query!(
             "UPDATE agent_state
              SET bead_id = $3,
                  current_stage = 'rust-contract',
@@ -102,11 +142,12 @@ impl SwarmDb {
                  status = 'working',
                  last_update = NOW()
              WHERE repo_id = $1
-               AND agent_id = $2",
+               AND agent_id = $2
+               AND deleted_at IS NULL",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            bead_id.value(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(agent_id.number().cast_signed())
-        .bind(bead_id.value())
         .execute(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to update agent state: {e}")))?;
@@ -126,27 +167,33 @@ impl SwarmDb {
         bead_id: &BeadId,
         lease_extension_ms: i32,
     ) -> Result<bool> {
-        sqlx::query_scalar::<_, bool>("SELECT heartbeat_bead_claim($1, $2, $3, $4)")
-            .bind(agent_id.repo_id().value())
-            .bind(agent_id.number().cast_signed())
-            .bind(bead_id.value())
-            .bind(lease_extension_ms)
-            .fetch_one(self.pool())
-            .await
-            .map_err(|e| SwarmError::DatabaseError(format!("Failed to heartbeat bead claim: {e}")))
+        crate::chaos::db_fault("heartbeat_claim")?;
+        if crate::chaos::drop_heartbeat() {
+            return Ok(true);
+        }
+        sqlx::query_scalar!(
+            r#"SELECT heartbeat_bead_claim($1, $2, $3, $4) AS "alive!""#,
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            bead_id.value(),
+            lease_extension_ms,
+        )
+        .fetch_one(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to heartbeat bead claim: {e}")))
     }
 
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn recover_expired_claims(&self, repo_id: &RepoId) -> Result<u32> {
-        sqlx::query_scalar::<_, i32>("SELECT recover_expired_bead_claims($1)")
-            .bind(repo_id.value())
-            .fetch_one(self.pool())
-            .await
-            .map_err(|e| {
-                SwarmError::DatabaseError(format!("Failed to recover expired claims: {e}"))
-            })
-            .map(i32::cast_unsigned)
+        sqlx::query_scalar!(
+            r#"SELECT recover_expired_bead_claims($1) AS "recovered!""#,
+            repo_id.value(),
+        )
+        .fetch_one(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to recover expired claims: {e}")))
+        .map(i32::cast_unsigned)
     }
 
     /// # Errors
@@ -157,20 +204,231 @@ impl SwarmDb {
         prefix: &str,
         count: u32,
     ) -> Result<()> {
-        sqlx::query(
+        sqlx::query!(
             "INSERT INTO bead_backlog (repo_id, bead_id, priority, status)
-             SELECT $1, format('%s-%s', $2, g), 'p0', 'pending'
-             FROM generate_series(1, $3) AS g",
+             SELECT $1, format('%s-%s', $2::TEXT, g), 'p0', 'pending'
+             FROM generate_series(1, $3) AS g
+             ON CONFLICT (repo_id, bead_id) DO UPDATE
+             SET priority = 'p0', status = 'pending', created_at = NOW(),
+                 deleted_at = NULL, deleted_by = NULL
+             WHERE bead_backlog.deleted_at IS NOT NULL",
+            repo_id.value(),
+            prefix,
+            count.cast_signed(),
         )
-        .bind(repo_id.value())
-        .bind(prefix)
-        .bind(count.cast_signed())
         .execute(self.pool())
         .await
         .map(|_result| ())
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to enqueue backlog batch: {e}")))
     }
 
+    /// Inserts `entries` as pending beads in one transaction, streamed with
+    /// binary `COPY`. Beads already in the backlog are left untouched and
+    /// deleted ones are enqueued afresh; the ids actually inserted are
+    /// returned.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn enqueue_backlog_entries(
+        &self,
+        repo_id: &RepoId,
+        entries: &[BacklogEntry],
+    ) -> Result<Vec<String>> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let inserted = copy_backlog_entries(conn, repo_id.value(), entries).await?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(inserted)
+    }
+
+    /// Applies a `br` reconciliation in one transaction: adds new beads,
+    /// drops and reprioritizes beads that are still pending, and records a
+    /// `backlog_synced` event. Rows claimed since the diff was computed are
+    /// left alone.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn apply_backlog_sync(&self, repo_id: &RepoId, diff: &BacklogSyncDiff) -> Result<()> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        for entry in &diff.added {
+            sqlx::query!(
+                "INSERT INTO bead_backlog (repo_id, bead_id, priority, status, labels, required_capabilities)
+                 VALUES ($1, $2, $3, 'pending', $4, $5)
+                 ON CONFLICT (repo_id, bead_id) DO UPDATE
+                 SET priority = EXCLUDED.priority,
+                     status = 'pending',
+                     labels = EXCLUDED.labels,
+                     required_capabilities = EXCLUDED.required_capabilities,
+                     created_at = NOW(),
+                     deleted_at = NULL,
+                     deleted_by = NULL
+                 WHERE bead_backlog.deleted_at IS NOT NULL",
+                repo_id.value(),
+                &entry.bead_id,
+                entry.priority.as_deref().unwrap_or("p0"),
+                &entry.labels.clone().unwrap_or_default(),
+                &entry.required_capabilities,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to add synced bead: {e}")))?;
+        }
+
+        sqlx::query!(
+            "UPDATE bead_backlog
+             SET deleted_at = NOW(), deleted_by = 'sync-backlog'
+             WHERE repo_id = $1 AND bead_id = ANY($2) AND status = 'pending'
+               AND deleted_at IS NULL",
+            repo_id.value(),
+            &diff.dropped,
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to drop closed beads: {e}")))?;
+
+        for change in &diff.reprioritized {
+            sqlx::query!(
+                "UPDATE bead_backlog
+                 SET priority = $3
+                 WHERE repo_id = $1 AND bead_id = $2 AND status = 'pending'
+                   AND deleted_at IS NULL",
+                repo_id.value(),
+                &change.bead_id,
+                &change.to,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to update bead priority: {e}"))
+            })?;
+        }
+
+        sqlx::query!(
+            "INSERT INTO execution_events (schema_version, event_type, entity_id, payload)
+             VALUES ($1, 'backlog_synced', $2, $3)",
+            EventSchemaVersion::LATEST.as_i32(),
+            format!("repo:{}:backlog", repo_id.value()),
+            json!({
+                "added": diff.added.iter().map(|entry| &entry.bead_id).collect::<Vec<_>>(),
+                "dropped": diff.dropped,
+                "reprioritized": diff.reprioritized,
+            }),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to write sync event: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))
+    }
+
+    /// Moves `agent_id`'s in-progress claim on `bead_id` and its backlog row
+    /// to `terminal`, and frees the agent. Returns `false`, changing nothing,
+    /// when the agent no longer holds that claim.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn settle_claim(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &str,
+        agent_id: u32,
+        terminal: CoordinatorSyncTerminal,
+    ) -> Result<bool> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let claim_update = sqlx::query!(
+            "UPDATE bead_claims
+             SET status = $4
+             WHERE repo_id = $1 AND bead_id = $2 AND claimed_by = $3 AND status = 'in_progress'
+               AND deleted_at IS NULL",
+            repo_id.value(),
+            bead_id,
+            agent_id.cast_signed(),
+            terminal.as_str(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to settle claim: {e}")))?;
+
+        if claim_update.rows_affected() != 1 {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            "UPDATE bead_backlog
+             SET status = $3
+             WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL",
+            repo_id.value(),
+            bead_id,
+            terminal.as_str(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to settle backlog bead: {e}")))?;
+
+        sqlx::query!(
+            "UPDATE agent_state
+             SET bead_id = NULL,
+                 current_stage = NULL,
+                 stage_started_at = NULL,
+                 status = 'idle',
+                 feedback = NULL,
+                 implementation_attempt = 0
+             WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3 AND deleted_at IS NULL",
+            repo_id.value(),
+            agent_id.cast_signed(),
+            bead_id,
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to free agent: {e}")))?;
+
+        if terminal == CoordinatorSyncTerminal::Completed {
+            clear_bead_kv(conn, repo_id.value(), bead_id).await?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(true)
+    }
+
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn mark_bead_blocked(
@@ -179,6 +437,8 @@ impl SwarmDb {
         bead_id: &BeadId,
         reason: &str,
     ) -> Result<()> {
+        let active = BeadLifecycle::<bead_states::InProgress>::observed();
+        let blocked = active.block();
         let mut tx = self
             .pool()
             .begin()
@@ -190,17 +450,20 @@ impl SwarmDb {
             .await
             .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
 
-        let claim_update = sqlx::query(
+        let claim_update = sqlx::query!(
             "UPDATE bead_claims
-             SET status = 'blocked'
+             SET status = $4
              WHERE repo_id = $1
                AND bead_id = $2
                AND claimed_by = $3
-               AND status = 'in_progress'",
+               AND status = $5
+               AND deleted_at IS NULL",
+            agent_id.repo_id().value(),
+            bead_id.value(),
+            agent_id.number().cast_signed(),
+            blocked.claim_status(),
+            active.claim_status(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(bead_id.value())
-        .bind(agent_id.number().cast_signed())
         .execute(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to block claim: {e}")))?;
@@ -213,23 +476,25 @@ impl SwarmDb {
             )));
         }
 
-        sqlx::query(
-            "UPDATE bead_backlog SET status = 'blocked' WHERE repo_id = $1 AND bead_id = $2",
+        sqlx::query!(
+            "UPDATE bead_backlog SET status = $3
+             WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL",
+            agent_id.repo_id().value(),
+            bead_id.value(),
+            blocked.backlog_status(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(bead_id.value())
         .execute(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to block backlog bead: {e}")))?;
 
-        sqlx::query(
+        sqlx::query!(
             "UPDATE agent_state
              SET status = 'error', feedback = $3
-             WHERE repo_id = $1 AND agent_id = $2",
+             WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            reason,
         )
-        .bind(agent_id.repo_id().value())
-        .bind(agent_id.number().cast_signed())
-        .bind(reason)
         .execute(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to mark agent error: {e}")))?;
@@ -251,6 +516,7 @@ impl SwarmDb {
                     retryable: false,
                     next_command: "swarm monitor --view failures".to_string(),
                     detail: Some(redact_sensitive(reason)),
+                    failing_tests: Vec::new(),
                 }),
             },
         )
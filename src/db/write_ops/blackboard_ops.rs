@@ -0,0 +1,61 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::swarm_db::{to_blackboard_entry, BlackboardRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{BlackboardEntry, BlackboardSection, RepoId};
+
+impl SwarmDb {
+    /// Appends an entry as version `expected_version + 1`. Returns `None`,
+    /// storing nothing, when the blackboard is no longer at
+    /// `expected_version` because another append got there first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn append_blackboard_entry(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &str,
+        agent_id: u32,
+        section: BlackboardSection,
+        content: &str,
+        expected_version: i64,
+    ) -> Result<Option<BlackboardEntry>> {
+        sqlx::query!(
+            "INSERT INTO bead_blackboard (repo_id, bead_id, version, section, agent_id, content)
+             SELECT $1, $2, $3::BIGINT + 1, $4, $5, $6
+             WHERE (
+                     SELECT COALESCE(MAX(version), 0)
+                     FROM bead_blackboard
+                     WHERE repo_id = $1 AND bead_id = $2
+                 ) = $3
+             ON CONFLICT (repo_id, bead_id, version) DO NOTHING
+             RETURNING version, section, agent_id, content, created_at",
+            repo_id.value(),
+            bead_id,
+            expected_version,
+            section.as_str(),
+            agent_id.cast_signed(),
+            content,
+        )
+        .map(|row| -> BlackboardRow {
+            (
+                row.version,
+                row.section,
+                row.agent_id,
+                row.content,
+                row.created_at,
+            )
+        })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to append blackboard entry: {e}")))?
+        .map(to_blackboard_entry)
+        .transpose()
+    }
+}
@@ -0,0 +1,237 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use super::helpers::event_entity_id;
+use super::kv_ops::clear_bead_kv;
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::runtime::{bead_states, BeadLifecycle, BeadLifecycleState};
+use crate::types::{BeadCancellation, BeadId, EventSchemaVersion, MessageType, RepoId};
+use serde_json::json;
+use sqlx::Acquire;
+
+impl SwarmDb {
+    /// Marks `bead_id` cancelled and, if an agent holds it, closes the running
+    /// stage as `cancelled`, releases the claim, frees the agent, and sends it
+    /// a `bead_cancelled` message. A stage process the coordinator launched
+    /// sees the cancellation on its next poll and is killed.
+    ///
+    /// The coordinator has no agent id of its own, so the message is sent
+    /// from the owner to itself.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::BeadError` if the bead is not in the backlog,
+    /// `SwarmError::AgentError` if it is already completed or cancelled, or
+    /// an error if a database operation fails.
+    #[allow(clippy::too_many_lines)]
+    pub async fn cancel_bead(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+        reason: &str,
+    ) -> Result<BeadCancellation> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let status = sqlx::query_scalar!(
+            "SELECT status
+             FROM bead_backlog
+             WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL
+             FOR UPDATE",
+            repo_id.value(),
+            bead_id.value(),
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to lock backlog bead: {e}")))?;
+
+        let refusal = match status.as_deref() {
+            None => Some(SwarmError::BeadError(format!(
+                "Bead {} is not in the backlog",
+                bead_id.value()
+            ))),
+            Some(status)
+                if BeadLifecycleState::from_backlog_status(status).is_some_and(|state| {
+                    !state.can_transition_to(BeadLifecycleState::Cancelled)
+                }) =>
+            {
+                Some(SwarmError::AgentError(format!(
+                    "Bead {} is already {status}",
+                    bead_id.value()
+                )))
+            }
+            Some(_) => None,
+        };
+        if let Some(refusal) = refusal {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Err(refusal);
+        }
+
+        sqlx::query!(
+            "UPDATE bead_backlog
+             SET status = $3
+             WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL",
+            repo_id.value(),
+            bead_id.value(),
+            BeadLifecycleState::Cancelled.backlog_status(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to cancel backlog bead: {e}")))?;
+
+        sqlx::query!(
+            "DELETE FROM bead_reservations WHERE repo_id = $1 AND bead_id = $2",
+            repo_id.value(),
+            bead_id.value(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to drop bead reservation: {e}")))?;
+        clear_bead_kv(&mut *conn, repo_id.value(), bead_id.value()).await?;
+
+        let owner = sqlx::query_scalar!(
+            "SELECT claimed_by
+             FROM bead_claims
+             WHERE repo_id = $1 AND bead_id = $2 AND status = $3 AND deleted_at IS NULL
+             FOR UPDATE",
+            repo_id.value(),
+            bead_id.value(),
+            BeadLifecycle::<bead_states::InProgress>::observed().claim_status(),
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to read bead claim: {e}")))?;
+
+        let mut cancellation = BeadCancellation {
+            bead_id: bead_id.clone(),
+            agent_id: owner.map(i32::cast_unsigned),
+            stage: None,
+            stage_history_id: None,
+            message_id: None,
+        };
+
+        if let Some(owner) = owner {
+            let stage = sqlx::query!(
+                "UPDATE stage_history
+                 SET status = 'cancelled',
+                     result = $4,
+                     feedback = $4,
+                     completed_at = NOW(),
+                     duration_ms = GREATEST(0, (EXTRACT(EPOCH FROM (NOW() - started_at)) * 1000)::INTEGER)
+                 WHERE id = (
+                     SELECT id FROM stage_history
+                     WHERE repo_id = $1
+                       AND agent_id = $2
+                       AND bead_id = $3
+                       AND status = 'started'
+                     ORDER BY started_at DESC LIMIT 1
+                 )
+                 RETURNING id, stage",
+                repo_id.value(),
+                owner,
+                bead_id.value(),
+                reason,
+            )
+            .map(|row| (row.id, row.stage))
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to cancel stage: {e}")))?;
+            cancellation.stage_history_id = stage.as_ref().map(|(id, _)| *id);
+            cancellation.stage = stage.map(|(_, stage)| stage);
+
+            sqlx::query!(
+                "UPDATE agent_state
+                 SET bead_id = NULL,
+                     current_stage = NULL,
+                     stage_started_at = NULL,
+                     status = 'idle',
+                     feedback = NULL,
+                     implementation_attempt = 0
+                 WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3 AND deleted_at IS NULL",
+                repo_id.value(),
+                owner,
+                bead_id.value(),
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to reset agent state: {e}")))?;
+
+            sqlx::query!(
+                "DELETE FROM agent_messages WHERE bead_id = $1",
+                bead_id.value(),
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to clear bead messages on cancel: {e}"))
+            })?;
+
+            sqlx::query!(
+                "UPDATE bead_claims
+                 SET deleted_at = NOW(), deleted_by = 'cancel'
+                 WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL",
+                repo_id.value(),
+                bead_id.value(),
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to release bead claim: {e}")))?;
+
+            let message_id = sqlx::query_scalar!(
+                r#"SELECT send_agent_message($1, $2, $1, $2, NULL, $3, $4, $5, $6) AS "id!""#,
+                repo_id.value(),
+                owner,
+                MessageType::BeadCancelled.as_str(),
+                format!("Bead {} cancelled", bead_id.value()),
+                reason,
+                json!({
+                    "bead_id": bead_id.value(),
+                    "stage": cancellation.stage,
+                    "stage_history_id": cancellation.stage_history_id,
+                }),
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to notify agent of cancel: {e}"))
+            })?;
+            cancellation.message_id = Some(message_id);
+        }
+
+        sqlx::query!(
+            "INSERT INTO execution_events (schema_version, event_type, entity_id, bead_id, agent_id, payload)
+             VALUES ($1, 'bead_cancelled', $2, $3, $4, $5)",
+            EventSchemaVersion::LATEST.as_i32(),
+            event_entity_id(bead_id, repo_id),
+            bead_id.value(),
+            owner,
+            json!({
+                "reason": reason,
+                "stage": cancellation.stage,
+                "stage_history_id": cancellation.stage_history_id,
+            }),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to write cancel event: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(cancellation)
+    }
+}
@@ -0,0 +1,347 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![forbid(unsafe_code)]
+
+//! Bulk loads over `COPY ... FROM STDIN (FORMAT binary)`. Rows go into a
+//! transaction-scoped staging table first, so callers keep their
+//! `ON CONFLICT` handling on the final `INSERT ... SELECT`.
+
+use crate::error::{Result, SwarmError};
+use crate::types::BacklogEntry;
+use sqlx::PgConnection;
+
+const COPY_SIGNATURE: &[u8] = b"PGCOPY\n\xff\r\n\0";
+const TEXT_OID: i32 = 25;
+/// Encoded rows are handed to the server once this much is buffered.
+const COPY_CHUNK_BYTES: usize = 64 * 1024;
+
+/// One field of a binary `COPY` row.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum CopyValue<'a> {
+    Int4(i32),
+    Text(&'a str),
+    TextArray(&'a [String]),
+}
+
+/// Encodes rows in the `PGCOPY` binary format: signature and header, then
+/// one field count and length-prefixed fields per row, then a `-1` trailer.
+#[derive(Debug)]
+pub(super) struct BinaryCopyBuffer {
+    bytes: Vec<u8>,
+}
+
+impl BinaryCopyBuffer {
+    pub(super) fn new() -> Self {
+        let mut bytes = Vec::with_capacity(COPY_CHUNK_BYTES);
+        bytes.extend_from_slice(COPY_SIGNATURE);
+        bytes.extend_from_slice(&0_i32.to_be_bytes());
+        bytes.extend_from_slice(&0_i32.to_be_bytes());
+        Self { bytes }
+    }
+
+    /// # Errors
+    /// Returns an error if the row or one of its fields is too large for the
+    /// wire format.
+    pub(super) fn push_row(&mut self, row: &[CopyValue<'_>]) -> Result<()> {
+        let fields = i16::try_from(row.len())
+            .map_err(|_| SwarmError::DatabaseError("Too many COPY columns".to_string()))?;
+        self.bytes.extend_from_slice(&fields.to_be_bytes());
+        row.iter().try_for_each(|value| self.push_value(*value))
+    }
+
+    fn push_value(&mut self, value: CopyValue<'_>) -> Result<()> {
+        match value {
+            CopyValue::Int4(number) => {
+                self.bytes.extend_from_slice(&4_i32.to_be_bytes());
+                self.bytes.extend_from_slice(&number.to_be_bytes());
+                Ok(())
+            }
+            CopyValue::Text(text) => self.push_field(text.as_bytes()),
+            CopyValue::TextArray(items) => self.push_text_array(items),
+        }
+    }
+
+    fn push_field(&mut self, data: &[u8]) -> Result<()> {
+        self.bytes
+            .extend_from_slice(&copy_len(data.len())?.to_be_bytes());
+        self.bytes.extend_from_slice(data);
+        Ok(())
+    }
+
+    /// A one-dimensional `TEXT[]` with lower bound 1, or a zero-dimension
+    /// array when `items` is empty.
+    fn push_text_array(&mut self, items: &[String]) -> Result<()> {
+        let mut array = Vec::new();
+        let dimensions = i32::from(!items.is_empty());
+        array.extend_from_slice(&dimensions.to_be_bytes());
+        array.extend_from_slice(&0_i32.to_be_bytes());
+        array.extend_from_slice(&TEXT_OID.to_be_bytes());
+        if !items.is_empty() {
+            array.extend_from_slice(&copy_len(items.len())?.to_be_bytes());
+            array.extend_from_slice(&1_i32.to_be_bytes());
+        }
+        for item in items {
+            array.extend_from_slice(&copy_len(item.len())?.to_be_bytes());
+            array.extend_from_slice(item.as_bytes());
+        }
+        self.push_field(&array)
+    }
+
+    /// Takes what is buffered once it reaches a chunk, leaving the buffer
+    /// empty for the following rows.
+    pub(super) fn take_full_chunk(&mut self) -> Option<Vec<u8>> {
+        (self.bytes.len() >= COPY_CHUNK_BYTES)
+            .then(|| std::mem::replace(&mut self.bytes, Vec::with_capacity(COPY_CHUNK_BYTES)))
+    }
+
+    /// Appends the trailer and returns the remaining bytes.
+    pub(super) fn finish(mut self) -> Vec<u8> {
+        self.bytes.extend_from_slice(&(-1_i16).to_be_bytes());
+        self.bytes
+    }
+}
+
+fn copy_len(len: usize) -> Result<i32> {
+    i32::try_from(len)
+        .map_err(|_| SwarmError::DatabaseError(format!("COPY field of {len} bytes is too large")))
+}
+
+/// Streams `rows` through `COPY <target> FROM STDIN (FORMAT binary)` on
+/// `conn`, a chunk at a time, and returns the number of rows copied.
+///
+/// # Errors
+/// Returns an error if encoding or the copy fails; the copy is aborted then.
+pub(super) async fn copy_binary(
+    conn: &mut PgConnection,
+    target: &str,
+    rows: &[Vec<CopyValue<'_>>],
+) -> Result<u64> {
+    let mut copy = conn
+        .copy_in_raw(&format!("COPY {target} FROM STDIN (FORMAT binary)"))
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to start COPY: {e}")))?;
+
+    let mut buffer = BinaryCopyBuffer::new();
+    for row in rows {
+        if let Err(error) = buffer.push_row(row) {
+            let _ = copy.abort(error.to_string()).await;
+            return Err(error);
+        }
+        if let Some(chunk) = buffer.take_full_chunk() {
+            copy.send(chunk)
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to send COPY data: {e}")))?;
+        }
+    }
+    copy.send(buffer.finish())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to send COPY data: {e}")))?;
+    copy.finish()
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to finish COPY: {e}")))
+}
+
+/// Copies `entries` into `bead_backlog` as pending beads for `repo_id`, on
+/// the caller's transaction. Beads already in the backlog, or repeated in
+/// `entries`, are skipped, and deleted ones are enqueued afresh; the ids
+/// actually inserted are returned in input order.
+///
+/// # Errors
+/// Returns an error if the database operation fails.
+pub(super) async fn copy_backlog_entries(
+    conn: &mut PgConnection,
+    repo_id: &str,
+    entries: &[BacklogEntry],
+) -> Result<Vec<String>> {
+    sqlx::query(
+        "CREATE TEMP TABLE backlog_copy (
+            ord INTEGER NOT NULL,
+            bead_id TEXT NOT NULL,
+            priority TEXT NOT NULL,
+            labels TEXT[] NOT NULL,
+            required_capabilities TEXT[] NOT NULL
+         ) ON COMMIT DROP",
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| SwarmError::DatabaseError(format!("Failed to stage backlog copy: {e}")))?;
+
+    let rows = entries
+        .iter()
+        .zip(0_i32..)
+        .map(|(entry, ord)| {
+            vec![
+                CopyValue::Int4(ord),
+                CopyValue::Text(&entry.bead_id),
+                CopyValue::Text(entry.priority.as_deref().unwrap_or("p0")),
+                CopyValue::TextArray(entry.labels.as_deref().unwrap_or_default()),
+                CopyValue::TextArray(&entry.required_capabilities),
+            ]
+        })
+        .collect::<Vec<_>>();
+    copy_binary(
+        conn,
+        "backlog_copy (ord, bead_id, priority, labels, required_capabilities)",
+        &rows,
+    )
+    .await?;
+
+    let inserted = sqlx::query_scalar::<_, String>(
+        "WITH inserted AS (
+            INSERT INTO bead_backlog (repo_id, bead_id, priority, status, labels, required_capabilities)
+            SELECT $1, bead_id, priority, 'pending', labels, required_capabilities
+            FROM (
+                SELECT DISTINCT ON (bead_id) *
+                FROM backlog_copy
+                ORDER BY bead_id, ord
+            ) first_seen
+            ORDER BY ord
+            ON CONFLICT (repo_id, bead_id) DO UPDATE
+            SET priority = EXCLUDED.priority,
+                status = 'pending',
+                labels = EXCLUDED.labels,
+                required_capabilities = EXCLUDED.required_capabilities,
+                created_at = NOW(),
+                deleted_at = NULL,
+                deleted_by = NULL
+            WHERE bead_backlog.deleted_at IS NOT NULL
+            RETURNING bead_id
+         )
+         SELECT inserted.bead_id
+         FROM inserted
+         JOIN (SELECT bead_id, MIN(ord) AS ord FROM backlog_copy GROUP BY bead_id) staged
+           ON staged.bead_id = inserted.bead_id
+         ORDER BY staged.ord",
+    )
+    .bind(repo_id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| SwarmError::DatabaseError(format!("Failed to enqueue beads: {e}")))?;
+
+    drop_staging(conn, "backlog_copy").await?;
+    Ok(inserted)
+}
+
+/// Copies idle agents `agent_ids` into `agent_state`, on the caller's
+/// transaction. With `repo_id` the rows are scoped to it, as on a schema
+/// where `agent_state` carries a `repo_id` column. Agents that already exist
+/// are left untouched, and deleted ones come back.
+///
+/// # Errors
+/// Returns an error if the database operation fails.
+pub(super) async fn copy_idle_agents(
+    conn: &mut PgConnection,
+    repo_id: Option<&str>,
+    agent_ids: &[i32],
+) -> Result<()> {
+    sqlx::query("CREATE TEMP TABLE agent_copy (agent_id INTEGER NOT NULL) ON COMMIT DROP")
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to stage agent copy: {e}")))?;
+
+    let rows = agent_ids
+        .iter()
+        .map(|id| vec![CopyValue::Int4(*id)])
+        .collect::<Vec<_>>();
+    copy_binary(conn, "agent_copy (agent_id)", &rows).await?;
+
+    let insert = repo_id.map_or_else(
+        || {
+            sqlx::query(
+                "INSERT INTO agent_state (agent_id, status)
+                 SELECT agent_id, 'idle' FROM agent_copy
+                 ON CONFLICT (agent_id) DO NOTHING",
+            )
+        },
+        |repo_id| {
+            sqlx::query(
+                "INSERT INTO agent_state (repo_id, agent_id, status)
+                 SELECT $1, agent_id, 'idle' FROM agent_copy
+                 ON CONFLICT (repo_id, agent_id) DO UPDATE
+                 SET deleted_at = NULL, deleted_by = NULL
+                 WHERE agent_state.deleted_at IS NOT NULL",
+            )
+            .bind(repo_id)
+        },
+    );
+    insert
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to seed agents: {e}")))?;
+
+    drop_staging(conn, "agent_copy").await
+}
+
+/// Drops a staging table early so a second copy in the same transaction
+/// starts from an empty one.
+async fn drop_staging(conn: &mut PgConnection, table: &str) -> Result<()> {
+    sqlx::query(&format!("DROP TABLE {table}"))
+        .execute(conn)
+        .await
+        .map(|_result| ())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to drop {table}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BinaryCopyBuffer, CopyValue, COPY_CHUNK_BYTES};
+
+    #[test]
+    fn given_no_rows_when_finishing_then_only_header_and_trailer_are_written() {
+        let bytes = BinaryCopyBuffer::new().finish();
+
+        assert_eq!(bytes, b"PGCOPY\n\xff\r\n\0\0\0\0\0\0\0\0\0\xff\xff");
+    }
+
+    #[test]
+    fn given_int_and_text_fields_when_pushing_row_then_fields_are_length_prefixed(
+    ) -> crate::Result<()> {
+        let mut buffer = BinaryCopyBuffer::new();
+        buffer.push_row(&[CopyValue::Int4(7), CopyValue::Text("ab")])?;
+        let bytes = buffer.finish();
+
+        assert_eq!(
+            &bytes[19..bytes.len() - 2],
+            &[0, 2, 0, 0, 0, 4, 0, 0, 0, 7, 0, 0, 0, 2, b'a', b'b']
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn given_text_arrays_when_pushing_row_then_empty_has_no_dimensions() -> crate::Result<()> {
+        let mut buffer = BinaryCopyBuffer::new();
+        buffer.push_row(&[
+            CopyValue::TextArray(&[]),
+            CopyValue::TextArray(&["x".to_string()]),
+        ])?;
+        let bytes = buffer.finish();
+
+        assert_eq!(
+            &bytes[19..bytes.len() - 2],
+            &[
+                0, 2, //
+                0, 0, 0, 12, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 25, //
+                0, 0, 0, 25, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 25, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0,
+                1, b'x',
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn given_a_chunk_of_rows_when_taking_then_buffer_restarts_empty() -> crate::Result<()> {
+        let mut buffer = BinaryCopyBuffer::new();
+        assert!(buffer.take_full_chunk().is_none());
+
+        let text = "x".repeat(COPY_CHUNK_BYTES);
+        buffer.push_row(&[CopyValue::Text(&text)])?;
+        let chunk = buffer.take_full_chunk();
+
+        assert!(chunk.is_some_and(|chunk| chunk.starts_with(b"PGCOPY")));
+        assert_eq!(buffer.finish(), vec![0xff, 0xff]);
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentId, ArtifactType, BeadId, CoverageSummary, Stage};
+use serde_json::json;
+
+impl SwarmDb {
+    /// Stores `summary` as a `coverage_report` artifact on the agent's latest
+    /// run of `stage` for the bead, or its latest run of any stage when
+    /// `stage` is `None`. Returns the artifact id, the stage history id and
+    /// the stage the summary was attached to.
+    ///
+    /// # Errors
+    /// Returns an error if the bead has no matching stage run or the
+    /// database operation fails.
+    pub async fn record_coverage_summary(
+        &self,
+        agent_id: &AgentId,
+        bead_id: &BeadId,
+        stage: Option<Stage>,
+        summary: &CoverageSummary,
+    ) -> Result<(i64, i64, String)> {
+        let (stage_history_id, stage_name) = sqlx::query!(
+            "SELECT id, stage
+             FROM stage_history
+             WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3
+               AND ($4::TEXT IS NULL OR stage = $4)
+             ORDER BY started_at DESC
+             LIMIT 1",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            bead_id.value(),
+            stage.map(|stage| stage.as_str()),
+        )
+        .map(|row| (row.id, row.stage))
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to locate stage history: {e}")))?
+        .ok_or_else(|| {
+            SwarmError::StageError(format!(
+                "No {} run for bead {} and agent {} to attach coverage",
+                stage.map_or("stage", |stage| stage.as_str()),
+                bead_id.value(),
+                agent_id.number()
+            ))
+        })?;
+
+        let content = serde_json::to_string(summary)?;
+        let artifact_id = self
+            .store_stage_artifact(
+                stage_history_id,
+                ArtifactType::CoverageReport,
+                &content,
+                Some(json!({
+                    "format": summary.format.as_str(),
+                    "lines_covered": summary.lines_covered,
+                    "lines_total": summary.lines_total,
+                    "line_percent": summary.line_percent,
+                    "branches_covered": summary.branches_covered,
+                    "branches_total": summary.branches_total,
+                })),
+            )
+            .await?;
+        Ok((artifact_id, stage_history_id, stage_name))
+    }
+}
@@ -0,0 +1,129 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use super::helpers::event_entity_id;
+use crate::db::swarm_db::{to_escalation, EscalationRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{
+    BeadId, Escalation, EscalationReason, EscalationTrigger, EventSchemaVersion, RepoId,
+};
+
+impl SwarmDb {
+    /// Opens an escalation for a trigger. Returns `None` when the bead
+    /// already has an open escalation for that reason, so callers notify
+    /// only once.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn raise_escalation(
+        &self,
+        repo_id: &RepoId,
+        trigger: &EscalationTrigger,
+    ) -> Result<Option<Escalation>> {
+        sqlx::query!(
+            "INSERT INTO escalations (repo_id, bead_id, reason, detail, agent_id)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (repo_id, bead_id, reason) WHERE resolved_at IS NULL DO NOTHING
+             RETURNING id, bead_id, reason, detail, agent_id, assigned_to, escalated_at, resolved_at",
+            repo_id.value(),
+            &trigger.bead_id,
+            trigger.reason.as_str(),
+            &trigger.detail,
+            trigger.agent_id.cast_signed(),
+        )
+        .map(|row| -> EscalationRow { (row.id, row.bead_id, row.reason, row.detail, row.agent_id, row.assigned_to, row.escalated_at, row.resolved_at) })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to raise escalation: {e}")))?
+        .map(to_escalation)
+        .transpose()
+    }
+
+    /// Records the senior agent an escalated bead was handed to.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn set_escalation_assignee(
+        &self,
+        repo_id: &RepoId,
+        escalation_id: i64,
+        agent_id: u32,
+    ) -> Result<Option<Escalation>> {
+        sqlx::query!(
+            "UPDATE escalations
+             SET assigned_to = $3
+             WHERE repo_id = $1 AND id = $2
+             RETURNING id, bead_id, reason, detail, agent_id, assigned_to, escalated_at, resolved_at",
+            repo_id.value(),
+            escalation_id,
+            agent_id.cast_signed(),
+        )
+        .map(|row| -> EscalationRow { (row.id, row.bead_id, row.reason, row.detail, row.agent_id, row.assigned_to, row.escalated_at, row.resolved_at) })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to assign escalation: {e}")))?
+        .map(to_escalation)
+        .transpose()
+    }
+
+    /// Resolves the bead's open escalation for `reason`. Returns `None` when
+    /// nothing was open.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn resolve_escalation(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &str,
+        reason: EscalationReason,
+    ) -> Result<Option<Escalation>> {
+        sqlx::query!(
+            "UPDATE escalations
+             SET resolved_at = NOW()
+             WHERE repo_id = $1 AND bead_id = $2 AND reason = $3 AND resolved_at IS NULL
+             RETURNING id, bead_id, reason, detail, agent_id, assigned_to, escalated_at, resolved_at",
+            repo_id.value(),
+            bead_id,
+            reason.as_str(),
+        )
+        .map(|row| -> EscalationRow { (row.id, row.bead_id, row.reason, row.detail, row.agent_id, row.assigned_to, row.escalated_at, row.resolved_at) })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to resolve escalation: {e}")))?
+        .map(to_escalation)
+        .transpose()
+    }
+
+    /// Appends a `bead_escalated` or `escalation_resolved` execution event
+    /// on the escalated bead.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn record_escalation_event(
+        &self,
+        repo_id: &RepoId,
+        event_type: &str,
+        escalation: &Escalation,
+    ) -> Result<()> {
+        let payload = serde_json::to_value(escalation)?;
+        sqlx::query!(
+            "INSERT INTO execution_events (schema_version, event_type, entity_id, bead_id, agent_id, payload)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+            EventSchemaVersion::LATEST.as_i32(),
+            event_type,
+            event_entity_id(&BeadId::new(&escalation.bead_id), repo_id),
+            &escalation.bead_id,
+            escalation.agent_id.map(u32::cast_signed),
+            payload,
+        )
+        .execute(self.pool())
+        .await
+        .map(|_| ())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to record escalation event: {e}")))
+    }
+}
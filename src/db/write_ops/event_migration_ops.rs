@@ -0,0 +1,78 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{upgrade_event_payload, EventSchemaVersion};
+
+impl SwarmDb {
+    /// Rewrites every execution event below the latest schema version with
+    /// its upgraded payload, `batch_size` rows per transaction. Returns the
+    /// number of rows rewritten.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn migrate_execution_events(&self, batch_size: i64) -> Result<u64> {
+        let mut migrated = 0_u64;
+        let mut after_seq = 0_i64;
+        loop {
+            let mut tx = self.pool().begin().await.map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to begin event migration: {e}"))
+            })?;
+            let rows = sqlx::query!(
+                "SELECT seq, schema_version, event_type, payload
+                 FROM execution_events
+                 WHERE schema_version < $1 AND seq > $2
+                 ORDER BY seq
+                 LIMIT $3
+                 FOR UPDATE",
+                EventSchemaVersion::LATEST.as_i32(),
+                after_seq,
+                batch_size.max(1),
+            )
+            .map(|row| (row.seq, row.schema_version, row.event_type, row.payload))
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to load outdated execution events: {e}"))
+            })?;
+            let Some(&(last_seq, ..)) = rows.last() else {
+                break;
+            };
+
+            for (seq, schema_version, event_type, payload) in rows {
+                let (upgraded_version, upgraded_payload) =
+                    upgrade_event_payload(schema_version, &event_type, payload);
+                if upgraded_version == schema_version {
+                    continue;
+                }
+                let result = sqlx::query!(
+                    "UPDATE execution_events
+                     SET schema_version = $2, payload = $3
+                     WHERE seq = $1",
+                    seq,
+                    upgraded_version,
+                    upgraded_payload,
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    SwarmError::DatabaseError(format!(
+                        "Failed to migrate execution event {seq}: {e}"
+                    ))
+                })?;
+                migrated += result.rows_affected();
+            }
+
+            tx.commit().await.map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to commit event migration: {e}"))
+            })?;
+            after_seq = last_seq;
+        }
+        Ok(migrated)
+    }
+}
@@ -1,14 +1,15 @@
 #![deny(clippy::unwrap_used)]
 #![deny(clippy::expect_used)]
-#![deny(clippy::panic)]
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
 #![forbid(unsafe_code)]
 
 use super::helpers::{
     event_entity_id, landing_sync_causation_id, landing_sync_status_key, redact_sensitive,
 };
-use super::types::ExecutionEventWriteInput;
+use super::types::{ExecutionEventRow, ExecutionEventWriteInput};
+use crate::db::write_batcher::PendingWrite;
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
 use crate::types::{BeadId, EventSchemaVersion, Stage};
@@ -22,21 +23,26 @@ impl SwarmDb {
         agent_id: &crate::types::AgentId,
         input: ExecutionEventWriteInput,
     ) -> Result<()> {
-        let diagnostics_category = input
-            .diagnostics
-            .as_ref()
-            .map(|value| value.category.as_str());
-        let diagnostics_retryable = input.diagnostics.as_ref().map(|value| value.retryable);
-        let diagnostics_next_command = input
-            .diagnostics
-            .as_ref()
-            .map(|value| value.next_command.as_str());
-        let diagnostics_detail = input
-            .diagnostics
-            .as_ref()
-            .and_then(|value| value.detail.clone());
+        let write = PendingWrite::Event(execution_event_row(bead_id, agent_id, input));
+        match self.write_batch() {
+            Some(batch) => match batch.enqueue(write) {
+                Ok(()) => Ok(()),
+                Err(write) => self.write_now(*write).await,
+            },
+            None => self.write_now(write).await,
+        }
+    }
+
+    /// Writes execution events with a single multi-row INSERT.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn insert_execution_event_batch(&self, rows: &[ExecutionEventRow]) -> Result<()> {
+        if rows.is_empty() {
+            return Ok(());
+        }
 
-        sqlx::query(
+        let mut builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(
             "INSERT INTO execution_events (
                 schema_version,
                 event_type,
@@ -49,26 +55,32 @@ impl SwarmDb {
                 diagnostics_retryable,
                 diagnostics_next_command,
                 diagnostics_detail,
+                diagnostics_failing_tests,
                 payload
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
-        )
-        .bind(EventSchemaVersion::V1.as_i32())
-        .bind(input.event_type)
-        .bind(event_entity_id(bead_id, agent_id.repo_id()))
-        .bind(bead_id.value())
-        .bind(agent_id.number().cast_signed())
-        .bind(input.stage.map(|value| value.as_str()))
-        .bind(input.causation_id)
-        .bind(diagnostics_category)
-        .bind(diagnostics_retryable)
-        .bind(diagnostics_next_command)
-        .bind(diagnostics_detail)
-        .bind(input.payload)
-        .execute(self.pool())
-        .await
-        .map_err(|e| SwarmError::DatabaseError(format!("Failed to write execution event: {e}")))
-        .map(|_| ())
+            ) ",
+        );
+        builder.push_values(rows, |mut values, row| {
+            values
+                .push_bind(EventSchemaVersion::LATEST.as_i32())
+                .push_bind(row.event_type.as_str())
+                .push_bind(row.entity_id.as_str())
+                .push_bind(row.bead_id.as_str())
+                .push_bind(row.agent_id)
+                .push_bind(row.stage.as_deref())
+                .push_bind(row.causation_id.as_deref())
+                .push_bind(row.diagnostics_category.as_deref())
+                .push_bind(row.diagnostics_retryable)
+                .push_bind(row.diagnostics_next_command.as_deref())
+                .push_bind(row.diagnostics_detail.as_deref())
+                .push_bind(row.diagnostics_failing_tests.as_deref())
+                .push_bind(&row.payload);
+        });
+        builder
+            .build()
+            .execute(self.pool())
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to write execution event: {e}")))
+            .map(|_| ())
     }
 
     pub(crate) async fn record_execution_event_if_absent(
@@ -77,6 +89,7 @@ impl SwarmDb {
         agent_id: &crate::types::AgentId,
         input: ExecutionEventWriteInput,
     ) -> Result<()> {
+        self.flush_write_batch().await;
         let should_insert = match input.causation_id.as_deref() {
             Some(causation_id) => {
                 !self
@@ -103,21 +116,21 @@ impl SwarmDb {
         let repo_entity_id = event_entity_id(bead_id, agent_id.repo_id());
         let legacy_entity_id = format!("bead:{}", bead_id.value());
 
-        sqlx::query_scalar::<_, bool>(
-            "SELECT EXISTS (
+        sqlx::query_scalar!(
+            r#"SELECT EXISTS (
                 SELECT 1
                 FROM execution_events
                 WHERE bead_id = $1
                   AND event_type = $2
                   AND causation_id = $3
                   AND (entity_id = $4 OR entity_id = $5)
-            )",
+            ) AS "exists!""#,
+            bead_id.value(),
+            event_type,
+            causation_id,
+            repo_entity_id,
+            legacy_entity_id,
         )
-        .bind(bead_id.value())
-        .bind(event_type)
-        .bind(causation_id)
-        .bind(repo_entity_id)
-        .bind(legacy_entity_id)
         .fetch_one(self.pool())
         .await
         .map_err(|e| {
@@ -149,3 +162,37 @@ impl SwarmDb {
         .await
     }
 }
+
+fn execution_event_row(
+    bead_id: &BeadId,
+    agent_id: &crate::types::AgentId,
+    input: ExecutionEventWriteInput,
+) -> ExecutionEventRow {
+    let (category, retryable, next_command, detail, failing_tests) =
+        input
+            .diagnostics
+            .map_or((None, None, None, None, None), |value| {
+                (
+                    Some(value.category),
+                    Some(value.retryable),
+                    Some(value.next_command),
+                    value.detail,
+                    Some(value.failing_tests).filter(|tests| !tests.is_empty()),
+                )
+            });
+
+    ExecutionEventRow {
+        event_type: input.event_type.to_string(),
+        entity_id: event_entity_id(bead_id, agent_id.repo_id()),
+        bead_id: bead_id.value().to_string(),
+        agent_id: agent_id.number().cast_signed(),
+        stage: input.stage.map(|value| value.as_str().to_string()),
+        causation_id: input.causation_id,
+        diagnostics_category: category,
+        diagnostics_retryable: retryable,
+        diagnostics_next_command: next_command,
+        diagnostics_detail: detail,
+        diagnostics_failing_tests: failing_tests,
+        payload: input.payload,
+    }
+}
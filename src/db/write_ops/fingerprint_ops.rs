@@ -0,0 +1,115 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentBehaviorWindow, RepoId};
+use chrono::{DateTime, Utc};
+
+type WindowRow = (i32, DateTime<Utc>, i32, i32, i32, i32, i64);
+
+impl SwarmDb {
+    /// Recomputes the `windows` most recent fingerprint windows ending with
+    /// the one starting at `current_start` from `stage_history`, stores them,
+    /// and returns every stored window in that range oldest first.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn refresh_agent_fingerprints(
+        &self,
+        repo_id: &RepoId,
+        window_secs: u32,
+        windows: u32,
+        current_start: DateTime<Utc>,
+    ) -> Result<Vec<AgentBehaviorWindow>> {
+        let window_secs = window_secs.cast_signed();
+        sqlx::query!(
+            "INSERT INTO agent_fingerprints
+                (repo_id, agent_id, window_secs, window_start, stage_runs, failures, retries, beads, avg_stage_ms)
+             SELECT $1, sh.agent_id, $2::INTEGER, w.window_start,
+                    COUNT(*),
+                    COUNT(*) FILTER (WHERE sh.status IN ('failed', 'error')),
+                    COUNT(*) FILTER (WHERE sh.attempt_number > 1),
+                    COUNT(DISTINCT sh.bead_id),
+                    COALESCE(AVG(sh.duration_ms), 0)::BIGINT
+             FROM generate_series(
+                      $3::TIMESTAMPTZ - ($4::INTEGER * $2) * INTERVAL '1 second',
+                      $3::TIMESTAMPTZ,
+                      $2 * INTERVAL '1 second'
+                  ) AS w(window_start)
+             JOIN stage_history sh
+               ON sh.repo_id = $1
+              AND sh.started_at >= w.window_start
+              AND sh.started_at < w.window_start + $2 * INTERVAL '1 second'
+             GROUP BY sh.agent_id, w.window_start
+             ON CONFLICT (repo_id, agent_id, window_secs, window_start) DO UPDATE
+             SET stage_runs = EXCLUDED.stage_runs,
+                 failures = EXCLUDED.failures,
+                 retries = EXCLUDED.retries,
+                 beads = EXCLUDED.beads,
+                 avg_stage_ms = EXCLUDED.avg_stage_ms,
+                 computed_at = NOW()",
+            repo_id.value(),
+            window_secs,
+            current_start,
+            windows.cast_signed(),
+        )
+        .execute(self.pool())
+        .await
+        .map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to refresh agent fingerprints: {e}"))
+        })?;
+
+        let rows = sqlx::query!(
+            "SELECT agent_id, window_start, stage_runs, failures, retries, beads, avg_stage_ms
+             FROM agent_fingerprints
+             WHERE repo_id = $1
+               AND window_secs = $2
+               AND window_start >= $3::TIMESTAMPTZ - ($4 * $2) * INTERVAL '1 second'
+               AND window_start <= $3::TIMESTAMPTZ
+             ORDER BY agent_id ASC, window_start ASC",
+            repo_id.value(),
+            window_secs,
+            current_start,
+            windows.cast_signed(),
+        )
+        .map(|row| -> WindowRow {
+            (
+                row.agent_id,
+                row.window_start,
+                row.stage_runs,
+                row.failures,
+                row.retries,
+                row.beads,
+                row.avg_stage_ms,
+            )
+        })
+        .fetch_all(self.pool())
+        .await
+        .map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to load agent fingerprints: {e}"))
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(agent_id, window_start, stage_runs, failures, retries, beads, avg_stage_ms)| {
+                    AgentBehaviorWindow {
+                        agent_id: agent_id.max(0).cast_unsigned(),
+                        window_start,
+                        window_secs: window_secs.cast_unsigned(),
+                        stage_runs: stage_runs.max(0).cast_unsigned(),
+                        failures: failures.max(0).cast_unsigned(),
+                        retries: retries.max(0).cast_unsigned(),
+                        beads: beads.max(0).cast_unsigned(),
+                        avg_stage_ms: avg_stage_ms.max(0).cast_unsigned(),
+                    }
+                },
+            )
+            .collect())
+    }
+}
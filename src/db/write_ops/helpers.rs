@@ -7,7 +7,8 @@
 
 use super::types::{FailureDiagnosticsPayload, StageTransition};
 use crate::runtime::{
-    runtime_determine_transition_decision, RuntimeStage, RuntimeStageResult, RuntimeStageTransition,
+    runtime_determine_transition_decision, RuntimeStage, RuntimeStageResult,
+    RuntimeStageTransition, RuntimeTransitionDecision,
 };
 use crate::types::{BeadId, RepoId, Stage, StageResult};
 use crate::BrSyncStatus;
@@ -24,9 +25,11 @@ pub fn build_failure_diagnostics(message: Option<&str>) -> FailureDiagnosticsPay
         retryable: true,
         next_command: "swarm stage --stage implement".to_string(),
         detail,
+        failing_tests: Vec::new(),
     }
 }
 
+#[must_use]
 pub fn classify_failure_category(message: &str) -> &'static str {
     let lowered = message.to_ascii_lowercase();
     if lowered.contains("timeout") {
@@ -110,14 +113,21 @@ pub fn event_entity_id(bead_id: &BeadId, repo_id: &RepoId) -> String {
 
 #[must_use]
 pub fn determine_transition(stage: Stage, result: &StageResult) -> StageTransition {
-    let decision = runtime_determine_transition_decision(
+    to_stage_transition(transition_decision(stage, result).transition())
+}
+
+/// The runtime decision behind [`determine_transition`], reason included.
+pub(super) fn transition_decision(stage: Stage, result: &StageResult) -> RuntimeTransitionDecision {
+    runtime_determine_transition_decision(
         to_runtime_stage(stage),
         &to_runtime_stage_result(result),
         0,
         1,
-    );
+    )
+}
 
-    match decision.transition() {
+pub(super) const fn to_stage_transition(transition: RuntimeStageTransition) -> StageTransition {
+    match transition {
         RuntimeStageTransition::Advance(next_stage) => {
             StageTransition::Advance(to_stage(next_stage))
         }
@@ -127,6 +137,17 @@ pub fn determine_transition(stage: Stage, result: &StageResult) -> StageTransiti
     }
 }
 
+/// Name stored in `transition_events.transition`.
+pub(super) const fn runtime_transition_name(transition: RuntimeStageTransition) -> &'static str {
+    match transition {
+        RuntimeStageTransition::Advance(_) => "advance",
+        RuntimeStageTransition::Retry => "retry",
+        RuntimeStageTransition::Complete => "complete",
+        RuntimeStageTransition::Block => "block",
+        RuntimeStageTransition::NoOp => "noop",
+    }
+}
+
 const fn to_runtime_stage(stage: Stage) -> RuntimeStage {
     match stage {
         Stage::RustContract => RuntimeStage::RustContract,
@@ -143,6 +164,7 @@ fn to_runtime_stage_result(result: &StageResult) -> RuntimeStageResult {
         StageResult::Passed => RuntimeStageResult::Passed,
         StageResult::Failed(message) => RuntimeStageResult::Failed(message.clone()),
         StageResult::Error(message) => RuntimeStageResult::Error(message.clone()),
+        StageResult::Cancelled(reason) => RuntimeStageResult::Cancelled(reason.clone()),
     }
 }
 
@@ -0,0 +1,31 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::index_advisor::{QueryProbe, Remedy};
+
+impl SwarmDb {
+    /// Applies `remedy` to the curated index or table of `probe`. Builds run
+    /// `CONCURRENTLY`, outside any transaction, so they can take a while on
+    /// a large table without blocking claims.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn apply_index_remedy(&self, probe: &QueryProbe, remedy: Remedy) -> Result<()> {
+        sqlx::query(&remedy.statement(probe))
+            .execute(self.pool())
+            .await
+            .map(|_result| ())
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!(
+                    "Failed to apply {remedy:?} for {}: {e}",
+                    probe.index
+                ))
+            })
+    }
+}
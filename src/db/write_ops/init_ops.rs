@@ -0,0 +1,191 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{RepoId, SeedPlan};
+use sqlx::{Acquire, Executor, PgConnection};
+
+/// Repo the seeded idle agents belong to, as with `seed_idle_agents`.
+const SEED_REPO: &str = "local";
+
+impl SwarmDb {
+    /// Does what `init-db` followed by `register` does, in one transaction:
+    /// applies `schema_sql`, seeds `seed_agents` idle agents and registers
+    /// agents `1..=register_count` for `repo_id`. When any step fails the
+    /// database is left as it was, a tenant's schema included. The script's
+    /// own top-level `BEGIN;` and `COMMIT;` are dropped so it joins the
+    /// transaction.
+    ///
+    /// # Errors
+    /// Returns an error if any step fails; nothing is committed then.
+    pub async fn initialize_swarm(
+        &self,
+        schema_sql: &str,
+        seed_agents: u32,
+        repo_id: &RepoId,
+        register_count: u32,
+    ) -> Result<SeedPlan> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        if let Some(schema) = self.schema() {
+            sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS \"{schema}\""))
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| {
+                    SwarmError::DatabaseError(format!("Failed to create schema {schema}: {e}"))
+                })?;
+        }
+
+        let script = without_transaction_control(schema_sql);
+        conn.execute(script.as_str())
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to initialize schema: {e}")))?;
+
+        sqlx::query!(
+            "UPDATE swarm_config SET max_agents = $1",
+            seed_agents.cast_signed(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to update config: {e}")))?;
+
+        let plan = seed_idle_agents(conn, seed_agents).await?;
+
+        sqlx::query!(
+            "INSERT INTO repos (repo_id, name, path) VALUES ($1, $1, '.')
+             ON CONFLICT (repo_id) DO NOTHING",
+            repo_id.value(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to register repo: {e}")))?;
+
+        sqlx::query!(
+            "INSERT INTO agent_state (repo_id, agent_id, status)
+             SELECT $1, generate_series(1, $2), 'idle'
+             ON CONFLICT (repo_id, agent_id) DO UPDATE
+             SET deleted_at = NULL, deleted_by = NULL
+             WHERE agent_state.deleted_at IS NOT NULL",
+            repo_id.value(),
+            register_count.cast_signed(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to register agents: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(plan)
+    }
+}
+
+/// Seeds like `seed_idle_agents`, on the caller's transaction.
+async fn seed_idle_agents(conn: &mut PgConnection, count: u32) -> Result<SeedPlan> {
+    sqlx::query!(
+        "INSERT INTO repos (repo_id, name, path) VALUES ($1, $1, $1)
+         ON CONFLICT (repo_id) DO NOTHING",
+        SEED_REPO,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| SwarmError::DatabaseError(format!("Failed to register repo: {e}")))?;
+
+    let existing = sqlx::query_scalar!(
+        "SELECT agent_id FROM agent_state WHERE repo_id = $1 AND deleted_at IS NULL",
+        SEED_REPO,
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map(from_db_ids)
+    .map_err(|e| SwarmError::DatabaseError(format!("Failed to load seeded agents: {e}")))?;
+    let idle = sqlx::query_scalar!(
+        "SELECT agent_id FROM agent_state
+         WHERE repo_id = $1 AND status = 'idle' AND bead_id IS NULL AND deleted_at IS NULL",
+        SEED_REPO,
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map(from_db_ids)
+    .map_err(|e| SwarmError::DatabaseError(format!("Failed to load seeded agents: {e}")))?;
+    let plan = SeedPlan::new(count, &existing, &idle);
+
+    if !plan.prune.is_empty() {
+        sqlx::query!(
+            "UPDATE agent_state
+             SET deleted_at = NOW(), deleted_by = 'init'
+             WHERE repo_id = $1 AND agent_id = ANY($2) AND deleted_at IS NULL",
+            SEED_REPO,
+            &to_db_ids(&plan.prune),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to prune idle agents: {e}")))?;
+    }
+    sqlx::query!(
+        "INSERT INTO agent_state (repo_id, agent_id, status)
+         SELECT $1, UNNEST($2::INT[]), 'idle'
+         ON CONFLICT (repo_id, agent_id) DO UPDATE
+         SET deleted_at = NULL, deleted_by = NULL
+         WHERE agent_state.deleted_at IS NOT NULL",
+        SEED_REPO,
+        &to_db_ids(&plan.add),
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| SwarmError::DatabaseError(format!("Failed to seed agents: {e}")))?;
+
+    Ok(plan)
+}
+
+fn from_db_ids(ids: Vec<i32>) -> Vec<u32> {
+    ids.into_iter().map(i32::cast_unsigned).collect()
+}
+
+fn to_db_ids(ids: &[u32]) -> Vec<i32> {
+    ids.iter().copied().map(u32::cast_signed).collect()
+}
+
+/// `schema_sql` without the lines that open or close a transaction on their
+/// own, which would commit the caller's transaction early.
+fn without_transaction_control(schema_sql: &str) -> String {
+    schema_sql
+        .lines()
+        .filter(|line| {
+            !matches!(
+                line.trim().to_ascii_uppercase().as_str(),
+                "BEGIN;" | "BEGIN TRANSACTION;" | "COMMIT;"
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::without_transaction_control;
+
+    #[test]
+    fn given_script_with_its_own_transaction_when_stripping_then_only_control_lines_go() {
+        let script = "BEGIN;\nCREATE TABLE t (id INT);\n  commit;\nDO $$ BEGIN\n  NULL;\nEND $$;";
+
+        assert_eq!(
+            without_transaction_control(script),
+            "CREATE TABLE t (id INT);\nDO $$ BEGIN\n  NULL;\nEND $$;"
+        );
+    }
+}
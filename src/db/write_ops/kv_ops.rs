@@ -0,0 +1,108 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::swarm_db::{to_kv_entry, KvRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentKvEntry, RepoId, MAX_KV_KEYS};
+use serde_json::Value;
+use sqlx::PgConnection;
+
+impl SwarmDb {
+    /// Stores `value` under `key`, replacing any earlier value. Returns
+    /// `None`, storing nothing, when `key` is new and the agent already
+    /// holds `MAX_KV_KEYS` keys for the bead.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn set_kv_entry(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+        bead_id: &str,
+        key: &str,
+        value: &Value,
+    ) -> Result<Option<AgentKvEntry>> {
+        sqlx::query!(
+            "INSERT INTO agent_kv (repo_id, agent_id, bead_id, key, value)
+             SELECT $1, $2, $3, $4, $5
+             WHERE EXISTS (
+                     SELECT 1 FROM agent_kv
+                     WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3 AND key = $4
+                 )
+                OR (
+                     SELECT COUNT(*) FROM agent_kv
+                     WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3
+                 ) < $6
+             ON CONFLICT (repo_id, agent_id, bead_id, key)
+             DO UPDATE SET value = EXCLUDED.value, updated_at = NOW()
+             RETURNING agent_id, bead_id, key, value, updated_at",
+            repo_id.value(),
+            agent_id.cast_signed(),
+            bead_id,
+            key,
+            value,
+            MAX_KV_KEYS,
+        )
+        .map(|row| -> KvRow {
+            (
+                row.agent_id,
+                row.bead_id,
+                row.key,
+                row.value,
+                row.updated_at,
+            )
+        })
+        .fetch_optional(self.pool())
+        .await
+        .map(|row| row.map(to_kv_entry))
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to store kv entry: {e}")))
+    }
+
+    /// Removes `key`. Returns `false` when it was not set.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn delete_kv_entry(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+        bead_id: &str,
+        key: &str,
+    ) -> Result<bool> {
+        sqlx::query!(
+            "DELETE FROM agent_kv
+             WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3 AND key = $4",
+            repo_id.value(),
+            agent_id.cast_signed(),
+            bead_id,
+            key,
+        )
+        .execute(self.pool())
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to delete kv entry: {e}")))
+    }
+}
+
+/// Drops every agent's scratch state for a bead that reached a terminal
+/// state, inside the caller's transaction.
+pub(super) async fn clear_bead_kv(
+    conn: &mut PgConnection,
+    repo_id: &str,
+    bead_id: &str,
+) -> Result<()> {
+    sqlx::query!(
+        "DELETE FROM agent_kv WHERE repo_id = $1 AND bead_id = $2",
+        repo_id,
+        bead_id,
+    )
+    .execute(conn)
+    .await
+    .map(|_result| ())
+    .map_err(|e| SwarmError::DatabaseError(format!("Failed to clear bead kv entries: {e}")))
+}
@@ -1,15 +1,26 @@
 #![deny(clippy::unwrap_used)]
 #![deny(clippy::expect_used)]
-#![deny(clippy::panic)]
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
 #![forbid(unsafe_code)]
 
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
+use crate::types::LockMetadata;
 use sqlx::Acquire;
 
 impl SwarmDb {
+    /// Takes `resource` for `agent` unless it is held or another agent is
+    /// ahead of `agent` in its wait queue. Taking it removes `agent` from
+    /// the queue.
+    ///
+    /// Callers on the same resource are serialized with a transaction-scoped
+    /// advisory lock on `hashtext(resource)`. That is a 32-bit hash, so two
+    /// different resources can share an advisory lock and briefly wait on
+    /// each other. Only ordering is affected: who holds a resource is
+    /// decided by its own `resource_locks` row.
+    ///
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn acquire_resource_lock(
@@ -17,7 +28,9 @@ impl SwarmDb {
         resource: &str,
         agent: &str,
         ttl_ms: i64,
+        metadata: &LockMetadata,
     ) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        crate::chaos::db_fault("acquire_resource_lock")?;
         let mut tx = self
             .pool()
             .begin()
@@ -29,23 +42,66 @@ impl SwarmDb {
             .await
             .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
 
-        sqlx::query("DELETE FROM resource_locks WHERE until_at <= NOW()")
+        sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1))", resource)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to serialize lock queue: {e}"))
+            })?;
+
+        sqlx::query!("DELETE FROM resource_locks WHERE until_at <= NOW()")
             .execute(&mut *conn)
             .await
             .map_err(|e| SwarmError::DatabaseError(format!("Failed to cleanup locks: {e}")))?;
 
-        let acquired = sqlx::query_scalar::<_, chrono::DateTime<chrono::Utc>>(
-            "INSERT INTO resource_locks (resource, agent, until_at)
-             VALUES ($1, $2, NOW() + ($3 * INTERVAL '1 millisecond'))
-             ON CONFLICT (resource) DO NOTHING
-             RETURNING until_at",
+        sqlx::query!("DELETE FROM resource_lock_waiters WHERE wait_until <= NOW()")
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to cleanup lock waiters: {e}"))
+            })?;
+
+        let head = sqlx::query_scalar!(
+            "SELECT agent
+             FROM resource_lock_waiters
+             WHERE resource = $1
+             ORDER BY id ASC
+             LIMIT 1",
+            resource,
         )
-        .bind(resource)
-        .bind(agent)
-        .bind(ttl_ms)
         .fetch_optional(&mut *conn)
         .await
-        .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire lock: {e}")))?;
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to read lock queue: {e}")))?;
+
+        let acquired = if head.as_deref().is_some_and(|head| head != agent) {
+            None
+        } else {
+            sqlx::query_scalar!(
+                "INSERT INTO resource_locks (resource, agent, until_at, purpose, bead_id)
+                 VALUES ($1, $2, NOW() + ($3::BIGINT * INTERVAL '1 millisecond'), $4, $5)
+                 ON CONFLICT (resource) DO NOTHING
+                 RETURNING until_at",
+                resource,
+                agent,
+                ttl_ms,
+                metadata.purpose.as_deref(),
+                metadata.bead_id.as_deref(),
+            )
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire lock: {e}")))?
+        };
+
+        if acquired.is_some() {
+            sqlx::query!(
+                "DELETE FROM resource_lock_waiters WHERE resource = $1 AND agent = $2",
+                resource,
+                agent,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to leave lock queue: {e}")))?;
+        }
 
         tx.commit()
             .await
@@ -53,15 +109,129 @@ impl SwarmDb {
             .map(|()| acquired)
     }
 
+    /// Puts `agent` at the back of the wait queue for `resource`, giving up
+    /// after `wait_ms`. An agent already queued keeps its place and gets
+    /// the new deadline. Returns its position, 1 being the head. Serialized
+    /// with [`Self::acquire_resource_lock`] through the same advisory lock.
+    ///
     /// # Errors
     /// Returns an error if the database operation fails.
-    pub async fn unlock_resource(&self, resource: &str, agent: &str) -> Result<bool> {
-        sqlx::query("DELETE FROM resource_locks WHERE resource = $1 AND agent = $2")
-            .bind(resource)
-            .bind(agent)
-            .execute(self.pool())
+    pub async fn enqueue_lock_waiter(
+        &self,
+        resource: &str,
+        agent: &str,
+        ttl_ms: i64,
+        wait_ms: i64,
+        metadata: &LockMetadata,
+    ) -> Result<i64> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1))", resource)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to serialize lock queue: {e}"))
+            })?;
+
+        let id = sqlx::query_scalar!(
+            "INSERT INTO resource_lock_waiters (resource, agent, purpose, bead_id, ttl_ms, wait_until)
+             VALUES ($1, $2, $3, $4, $5, NOW() + ($6::BIGINT * INTERVAL '1 millisecond'))
+             ON CONFLICT (resource, agent) DO UPDATE
+             SET purpose = EXCLUDED.purpose,
+                 bead_id = EXCLUDED.bead_id,
+                 ttl_ms = EXCLUDED.ttl_ms,
+                 wait_until = EXCLUDED.wait_until
+             RETURNING id",
+            resource,
+            agent,
+            metadata.purpose.as_deref(),
+            metadata.bead_id.as_deref(),
+            ttl_ms,
+            wait_ms,
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to join lock queue: {e}")))?;
+
+        let position = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!"
+             FROM resource_lock_waiters
+             WHERE resource = $1 AND id <= $2 AND wait_until > NOW()"#,
+            resource,
+            id,
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to read queue position: {e}")))?;
+
+        tx.commit()
             .await
-            .map(|result| result.rows_affected() > 0)
-            .map_err(|e| SwarmError::DatabaseError(format!("Failed to unlock resource: {e}")))
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))
+            .map(|()| position)
+    }
+
+    /// Takes `agent` out of the wait queue for `resource`.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn dequeue_lock_waiter(&self, resource: &str, agent: &str) -> Result<bool> {
+        sqlx::query!(
+            "DELETE FROM resource_lock_waiters WHERE resource = $1 AND agent = $2",
+            resource,
+            agent,
+        )
+        .execute(self.pool())
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to leave lock queue: {e}")))
+    }
+
+    /// Moves the expiry of `agent`'s unexpired lock on `resource` to `ttl_ms`
+    /// from now. Returns the new expiry, or `None` when `agent` does not hold
+    /// the lock.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn renew_resource_lock(
+        &self,
+        resource: &str,
+        agent: &str,
+        ttl_ms: i64,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        sqlx::query_scalar!(
+            "UPDATE resource_locks
+             SET until_at = NOW() + ($3::BIGINT * INTERVAL '1 millisecond')
+             WHERE resource = $1 AND agent = $2 AND until_at > NOW()
+             RETURNING until_at",
+            resource,
+            agent,
+            ttl_ms,
+        )
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to renew lock: {e}")))
+    }
+
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn unlock_resource(&self, resource: &str, agent: &str) -> Result<bool> {
+        sqlx::query!(
+            "DELETE FROM resource_locks WHERE resource = $1 AND agent = $2",
+            resource,
+            agent,
+        )
+        .execute(self.pool())
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to unlock resource: {e}")))
     }
 }
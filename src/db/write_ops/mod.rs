@@ -5,19 +5,56 @@
 #![warn(clippy::nursery)]
 #![forbid(unsafe_code)]
 
+// Files using the sqlx 0.7 query macros allow `clippy::panic` and `missing_panics_doc`: the
+// macros expand bind arguments through code clippy reads as panicking.
+
 mod agent_ops;
+mod alert_ops;
+mod announcement_ops;
+mod approval_ops;
 mod artifact_ops;
 mod audit_ops;
+mod backlog_ops;
 mod bead_ops;
+mod blackboard_ops;
+mod cancel_ops;
 mod config_ops;
+mod copy_ops;
+mod coverage_ops;
+mod escalation_ops;
+mod event_migration_ops;
 mod event_ops;
+mod fingerprint_ops;
 mod helpers;
+mod index_ops;
+mod init_ops;
+mod kv_ops;
 mod lock_ops;
 mod message_ops;
+mod orchestrator_event_ops;
+mod partition_ops;
+mod prompt_ops;
+mod quarantine_ops;
+mod query_latency_ops;
+mod recovery_ops;
+mod reservation_ops;
 mod retry_packets;
+mod review_ops;
+mod schedule_ops;
+mod session_ops;
+mod sla_ops;
+mod snapshot_ops;
+mod soft_delete_ops;
 mod stage_lifecycle;
 mod stage_transitions;
+mod symbol_ops;
+mod takeover_ops;
+mod tenant_ops;
+mod test_result_ops;
 mod types;
+mod usage_ops;
+mod workspace_ops;
 
-pub use helpers::determine_transition;
-pub use types::StageTransition;
+pub(crate) use helpers::event_entity_id;
+pub use helpers::{classify_failure_category, determine_transition};
+pub use types::{CommandAuditRow, ExecutionEventRow, StageTransition};
@@ -0,0 +1,40 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use serde_json::Value;
+
+impl SwarmDb {
+    /// Appends one orchestrator event for the Postgres event sink. Returns
+    /// the row id.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn record_orchestrator_event(
+        &self,
+        repo_id: &str,
+        event_type: &str,
+        agent_id: Option<u32>,
+        bead_id: Option<&str>,
+        payload: &Value,
+    ) -> Result<i64> {
+        sqlx::query_scalar!(
+            "INSERT INTO orchestrator_events (repo_id, event_type, agent_id, bead_id, payload)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING id",
+            repo_id,
+            event_type,
+            agent_id.map(u32::cast_signed),
+            bead_id,
+            payload,
+        )
+        .fetch_one(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to record orchestrator event: {e}")))
+    }
+}
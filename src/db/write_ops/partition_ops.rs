@@ -0,0 +1,322 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{month_start_at, months_to_cover, next_month, PartitionedTable};
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// A `timestamptz` literal for midnight UTC on `month`. DDL takes no bind
+/// parameters, so partition bounds are written inline.
+fn bound_literal(month: NaiveDate) -> String {
+    format!("'{}'", month.format("%Y-%m-%d 00:00:00+00"))
+}
+
+impl SwarmDb {
+    /// Turns `table` into one partitioned by month on `created_at`. Its rows
+    /// stay where they are, as the `<table>_legacy` partition covering
+    /// everything before `cutover`, so nothing is copied. Indexes, foreign
+    /// keys and triggers are recreated on the partitioned table, which
+    /// takes over the serial sequence. A default partition takes rows past
+    /// the last monthly one. All of it is one transaction that
+    /// holds the table exclusively; the primary key gains `created_at`, so
+    /// the legacy rows get that index built once.
+    ///
+    /// # Errors
+    /// Returns an error if the table is already partitioned, is referenced
+    /// by a foreign key, holds rows from `cutover` on, or the database
+    /// operation fails.
+    #[allow(clippy::too_many_lines)]
+    pub async fn enable_partitioning(
+        &self,
+        table: PartitionedTable,
+        cutover: NaiveDate,
+    ) -> Result<()> {
+        let name = table.as_str();
+        let legacy = table.legacy_partition();
+        let key = table.key_column();
+        let failed =
+            |e: sqlx::Error| SwarmError::DatabaseError(format!("Failed to partition {name}: {e}"));
+        let mut tx = self.pool().begin().await.map_err(failed)?;
+
+        sqlx::query(&format!("LOCK TABLE {name} IN ACCESS EXCLUSIVE MODE"))
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+        let partitioned = sqlx::query_scalar!(
+            r#"SELECT relkind = 'p' AS "partitioned!" FROM pg_class WHERE oid = to_regclass($1)"#,
+            name,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(failed)?;
+        if partitioned {
+            return Err(SwarmError::DatabaseError(format!(
+                "{name} is already partitioned"
+            )));
+        }
+        let referenced_by = sqlx::query_scalar!(
+            r#"SELECT conrelid::regclass::TEXT AS "table_name!" FROM pg_constraint
+             WHERE confrelid = to_regclass($1) AND contype = 'f'"#,
+            name,
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(failed)?;
+        if !referenced_by.is_empty() {
+            return Err(SwarmError::DatabaseError(format!(
+                "{name} cannot be partitioned while foreign keys from {} reference it",
+                referenced_by.join(", ")
+            )));
+        }
+
+        // Definitions are read while the table still has its own name, so
+        // replaying them afterwards lands them on the partitioned table.
+        let primary_key = sqlx::query_scalar!(
+            r#"SELECT quote_ident(c.relname) AS "index_name!" FROM pg_index i
+             JOIN pg_class c ON c.oid = i.indexrelid
+             WHERE i.indrelid = to_regclass($1) AND i.indisprimary"#,
+            name,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(failed)?;
+        let indexes = sqlx::query!(
+            r#"SELECT quote_ident(c.relname) AS "index_name!",
+                    quote_ident(left(c.relname, 56) || '_legacy') AS "legacy_name!",
+                    pg_get_indexdef(i.indexrelid) AS "definition!"
+             FROM pg_index i
+             JOIN pg_class c ON c.oid = i.indexrelid
+             WHERE i.indrelid = to_regclass($1) AND NOT i.indisprimary
+             ORDER BY c.relname"#,
+            name,
+        )
+        .map(|row| (row.index_name, row.legacy_name, row.definition))
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(failed)?;
+        let foreign_keys = sqlx::query!(
+            r#"SELECT quote_ident(conname) AS "constraint_name!",
+                    pg_get_constraintdef(oid) AS "definition!"
+             FROM pg_constraint
+             WHERE conrelid = to_regclass($1) AND contype = 'f'
+             ORDER BY conname"#,
+            name,
+        )
+        .map(|row| (row.constraint_name, row.definition))
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(failed)?;
+        let triggers = sqlx::query!(
+            r#"SELECT quote_ident(tgname) AS "trigger_name!", pg_get_triggerdef(oid) AS "definition!"
+             FROM pg_trigger
+             WHERE tgrelid = to_regclass($1) AND NOT tgisinternal
+             ORDER BY tgname"#,
+            name,
+        )
+        .map(|row| (row.trigger_name, row.definition))
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(failed)?;
+        let sequence = sqlx::query_scalar!("SELECT pg_get_serial_sequence($1, $2)", name, key)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(failed)?;
+
+        let cutover_literal = bound_literal(cutover);
+        let mut statements = triggers
+            .iter()
+            .map(|(trigger, _)| format!("DROP TRIGGER {trigger} ON {name}"))
+            .collect::<Vec<_>>();
+        statements.push(format!("ALTER TABLE {name} RENAME TO {legacy}"));
+        // The attach builds the wider key on the legacy rows in its place.
+        statements.extend(
+            primary_key
+                .map(|constraint| format!("ALTER TABLE {legacy} DROP CONSTRAINT {constraint}")),
+        );
+        statements.extend(
+            indexes
+                .iter()
+                .map(|(index, renamed, _)| format!("ALTER INDEX {index} RENAME TO {renamed}")),
+        );
+        statements.push(format!(
+            "CREATE TABLE {name} (LIKE {legacy} INCLUDING DEFAULTS INCLUDING CONSTRAINTS \
+             INCLUDING STORAGE INCLUDING COMMENTS) PARTITION BY RANGE (created_at)"
+        ));
+        statements.push(format!(
+            "ALTER TABLE {name} ADD PRIMARY KEY ({key}, created_at)"
+        ));
+        statements.extend(
+            sequence.map(|sequence| format!("ALTER SEQUENCE {sequence} OWNED BY {name}.{key}")),
+        );
+        // The check lets the attach skip its own scan of the legacy rows.
+        statements.push(format!(
+            "ALTER TABLE {legacy} ADD CONSTRAINT {legacy}_range \
+             CHECK (created_at < {cutover_literal})"
+        ));
+        statements.push(format!(
+            "ALTER TABLE {name} ATTACH PARTITION {legacy} \
+             FOR VALUES FROM (MINVALUE) TO ({cutover_literal})"
+        ));
+        statements.push(format!(
+            "ALTER TABLE {legacy} DROP CONSTRAINT {legacy}_range"
+        ));
+        statements.push(format!(
+            "CREATE TABLE {} PARTITION OF {name} DEFAULT",
+            table.default_partition()
+        ));
+        // Each index finds its renamed legacy twin and adopts it unbuilt.
+        statements.extend(indexes.into_iter().map(|(_, _, definition)| definition));
+        statements.extend(foreign_keys.into_iter().map(|(constraint, definition)| {
+            format!("ALTER TABLE {name} ADD CONSTRAINT {constraint} {definition}")
+        }));
+        statements.extend(triggers.into_iter().map(|(_, definition)| definition));
+
+        for statement in &statements {
+            sqlx::query(statement)
+                .execute(&mut *tx)
+                .await
+                .map_err(failed)?;
+        }
+        sqlx::query!(
+            "INSERT INTO partitioned_tables (table_name, cutover_at) VALUES ($1, $2)",
+            name,
+            month_start_at(cutover),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(failed)?;
+        tx.commit().await.map_err(failed)
+    }
+
+    /// Creates the partitions of `table` for `months` that do not exist
+    /// yet, attached or detached. Rows the default partition took for one of
+    /// those months move into it. Returns the names created.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn create_partitions(
+        &self,
+        table: PartitionedTable,
+        months: &[NaiveDate],
+    ) -> Result<Vec<String>> {
+        let default = table.default_partition();
+        // Tables partitioned before the default partition existed get it here.
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {default} PARTITION OF {table} DEFAULT"
+        ))
+        .execute(self.pool())
+        .await
+        .map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to create partition {default}: {e}"))
+        })?;
+
+        let names = months
+            .iter()
+            .map(|month| table.partition_name(*month))
+            .collect::<Vec<_>>();
+        let existing = sqlx::query_scalar!(
+            r#"SELECT relname::TEXT AS "relname!" FROM pg_class
+             WHERE relname = ANY($1) AND pg_table_is_visible(oid)"#,
+            &names,
+        )
+        .fetch_all(self.pool())
+        .await
+        .map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to load partitions of {table}: {e}"))
+        })?;
+
+        let mut created = Vec::new();
+        for (month, partition) in months.iter().zip(names) {
+            if existing.contains(&partition) {
+                continue;
+            }
+            let Some(end) = next_month(*month) else {
+                continue;
+            };
+            let (from, to) = (bound_literal(*month), bound_literal(end));
+            // The partition is filled before it is attached, so the moved
+            // rows are not inserted through the table's triggers again.
+            let statements = [
+                format!("LOCK TABLE {default} IN ACCESS EXCLUSIVE MODE"),
+                format!(
+                    "CREATE TABLE {partition} (LIKE {table} INCLUDING DEFAULTS \
+                     INCLUDING CONSTRAINTS INCLUDING STORAGE INCLUDING COMMENTS)"
+                ),
+                format!(
+                    "WITH moved AS (
+                         DELETE FROM {default}
+                         WHERE created_at >= {from} AND created_at < {to}
+                         RETURNING *
+                     )
+                     INSERT INTO {partition} SELECT * FROM moved"
+                ),
+                format!(
+                    "ALTER TABLE {table} ATTACH PARTITION {partition} \
+                     FOR VALUES FROM ({from}) TO ({to})"
+                ),
+            ];
+            let failed = |e: sqlx::Error| {
+                SwarmError::DatabaseError(format!("Failed to create partition {partition}: {e}"))
+            };
+            let mut tx = self.pool().begin().await.map_err(failed)?;
+            for statement in &statements {
+                sqlx::query(statement)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(failed)?;
+            }
+            tx.commit().await.map_err(failed)?;
+            created.push(partition);
+        }
+        Ok(created)
+    }
+
+    /// Creates the monthly partitions every partitioned table needs from
+    /// `now` through `ahead` months after it, which is what the
+    /// `partitions` job runs. Returns the names created.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn ensure_partitions(&self, now: DateTime<Utc>, ahead: u32) -> Result<Vec<String>> {
+        let mut created = Vec::new();
+        for state in self.partitioned_tables().await? {
+            let months = months_to_cover(state.cutover_at.date_naive(), now, ahead);
+            created.extend(self.create_partitions(state.table, &months).await?);
+        }
+        Ok(created)
+    }
+
+    /// Detaches `partition` from `table`. Its rows leave the table's reads
+    /// but stay in the now ordinary table until it is dropped.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn detach_partition(&self, table: PartitionedTable, partition: &str) -> Result<()> {
+        sqlx::query(&format!("ALTER TABLE {table} DETACH PARTITION {partition}"))
+            .execute(self.pool())
+            .await
+            .map(|_result| ())
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to detach partition {partition}: {e}"))
+            })
+    }
+
+    /// Drops a detached partition and its rows.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn drop_partition(&self, partition: &str) -> Result<()> {
+        sqlx::query(&format!("DROP TABLE {partition}"))
+            .execute(self.pool())
+            .await
+            .map(|_result| ())
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to drop partition {partition}: {e}"))
+            })
+    }
+}
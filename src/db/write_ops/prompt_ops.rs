@@ -0,0 +1,67 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::RepoId;
+
+impl SwarmDb {
+    /// Stores version 1 of `skill`. Returns `None` when the skill is already stored.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn add_skill_prompt(
+        &self,
+        repo_id: &RepoId,
+        skill: &str,
+        body: &str,
+    ) -> Result<Option<u32>> {
+        sqlx::query_scalar!(
+            "INSERT INTO skill_prompts (repo_id, skill, version, body)
+             SELECT $1, $2, 1, $3
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM skill_prompts WHERE repo_id = $1 AND skill = $2
+             )
+             ON CONFLICT (repo_id, skill, version) DO NOTHING
+             RETURNING version",
+            repo_id.value(),
+            skill,
+            body,
+        )
+        .fetch_optional(self.pool())
+        .await
+        .map(|version| version.map(i32::cast_unsigned))
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to add skill prompt: {e}")))
+    }
+
+    /// Appends the next version of `skill` and returns its number.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails, including when a
+    /// concurrent update claimed the same version first.
+    pub async fn update_skill_prompt(
+        &self,
+        repo_id: &RepoId,
+        skill: &str,
+        body: &str,
+    ) -> Result<u32> {
+        sqlx::query_scalar!(
+            "INSERT INTO skill_prompts (repo_id, skill, version, body)
+             SELECT $1, $2, COALESCE(MAX(version), 0) + 1, $3
+             FROM skill_prompts
+             WHERE repo_id = $1 AND skill = $2
+             RETURNING version",
+            repo_id.value(),
+            skill,
+            body,
+        )
+        .fetch_one(self.pool())
+        .await
+        .map(i32::cast_unsigned)
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to update skill prompt: {e}")))
+    }
+}
@@ -0,0 +1,117 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use super::helpers::event_entity_id;
+use crate::db::swarm_db::{to_agent_quarantine, QuarantineRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{
+    AgentQuarantine, BeadId, EventSchemaVersion, QuarantineSource, RepoId, ScopeViolation,
+};
+use serde_json::json;
+
+impl SwarmDb {
+    /// Bars `agent_id` from claiming new beads. Returns `false` when the agent
+    /// is already quarantined, leaving the existing reason in place.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn quarantine_agent(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+        source: QuarantineSource,
+        reason: &str,
+    ) -> Result<bool> {
+        sqlx::query!(
+            "INSERT INTO agent_quarantine (repo_id, agent_id, source, reason)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (repo_id, agent_id) WHERE released_at IS NULL DO NOTHING",
+            repo_id.value(),
+            agent_id.cast_signed(),
+            source.as_str(),
+            reason,
+        )
+        .execute(self.pool())
+        .await
+        .map(|result| result.rows_affected() == 1)
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to quarantine agent: {e}")))
+    }
+
+    /// Lifts the active quarantine on `agent_id`, keeping the row with the
+    /// operator's reason. Returns `None` when the agent was not quarantined.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn release_agent_quarantine(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+        release_reason: &str,
+    ) -> Result<Option<AgentQuarantine>> {
+        sqlx::query!(
+            "UPDATE agent_quarantine
+             SET released_at = NOW(), release_reason = $3
+             WHERE repo_id = $1 AND agent_id = $2 AND released_at IS NULL
+             RETURNING agent_id, source, reason, quarantined_at, released_at, release_reason",
+            repo_id.value(),
+            agent_id.cast_signed(),
+            release_reason,
+        )
+        .map(|row| -> QuarantineRow {
+            (
+                row.agent_id,
+                row.source,
+                row.reason,
+                row.quarantined_at,
+                row.released_at,
+                row.release_reason,
+            )
+        })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to release quarantine: {e}")))?
+        .map(to_agent_quarantine)
+        .transpose()
+    }
+
+    /// Records a `scope_violation` execution event per violation on
+    /// `bead_id`, charged to `agent_id` when the bead has one. The quarantine
+    /// policy and the `forbid_scope_violations` gate count these.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn record_scope_violations(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+        agent_id: Option<u32>,
+        violations: &[ScopeViolation],
+    ) -> Result<()> {
+        if violations.is_empty() {
+            return Ok(());
+        }
+        let payloads = violations
+            .iter()
+            .map(|violation| json!({"path": violation.path, "reason": violation.reason.as_str()}))
+            .collect::<Vec<_>>();
+        sqlx::query!(
+            "INSERT INTO execution_events (schema_version, event_type, entity_id, bead_id, agent_id, payload)
+             SELECT $1, 'scope_violation', $2, $3, $4, payload
+             FROM UNNEST($5::JSONB[]) AS payload",
+            EventSchemaVersion::LATEST.as_i32(),
+            event_entity_id(bead_id, repo_id),
+            bead_id.value(),
+            agent_id.map(u32::cast_signed),
+            &payloads,
+        )
+        .execute(self.pool())
+        .await
+        .map(|_| ())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to record scope violations: {e}")))
+    }
+}
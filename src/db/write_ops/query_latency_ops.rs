@@ -0,0 +1,65 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::query_perf::{restore_histograms, take_histograms, LatencyHistogram};
+use std::collections::HashMap;
+
+impl SwarmDb {
+    /// Writes out the query latency histograms this process collected since
+    /// the last flush, one `query_latency` row per query. Histograms that
+    /// fail to write are kept for the next flush. Returns the rows written.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn flush_query_latency(&self) -> Result<usize> {
+        let histograms = take_histograms();
+        match self.insert_query_latency(&histograms).await {
+            Ok(()) => Ok(histograms.len()),
+            Err(error) => {
+                restore_histograms(histograms);
+                Err(error)
+            }
+        }
+    }
+
+    async fn insert_query_latency(
+        &self,
+        histograms: &HashMap<String, LatencyHistogram>,
+    ) -> Result<()> {
+        if histograms.is_empty() {
+            return Ok(());
+        }
+
+        let mut builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            "INSERT INTO query_latency
+             (query_name, swarm_version, sample_count, total_ms, max_ms, buckets) ",
+        );
+        builder.push_values(histograms, |mut values, (query, histogram)| {
+            values
+                .push_bind(query.as_str())
+                .push_bind(env!("CARGO_PKG_VERSION"))
+                .push_bind(histogram.count().cast_signed())
+                .push_bind(histogram.total_ms)
+                .push_bind(histogram.max_ms)
+                .push_bind(
+                    histogram
+                        .counts
+                        .iter()
+                        .map(|count| count.cast_signed())
+                        .collect::<Vec<_>>(),
+                );
+        });
+        builder
+            .build()
+            .execute(self.pool())
+            .await
+            .map(|_result| ())
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to write query latency: {e}")))
+    }
+}
@@ -0,0 +1,248 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use super::helpers::event_entity_id;
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::runtime::{bead_states, BeadLifecycle};
+use crate::types::{BeadId, ClaimRecoveryReason, EventSchemaVersion, RecoveryAction, RepoId};
+use serde_json::json;
+use sqlx::Acquire;
+
+impl SwarmDb {
+    /// Cleans up after an interrupted run in `repo_id`, or in every repo
+    /// when `None`:
+    ///
+    /// - in-progress claims whose lease lapsed, whose agent is no longer
+    ///   registered, or whose agent is working something else are released
+    ///   and their beads put back to pending;
+    /// - backlog beads left `in_progress` with no claim go back to pending;
+    /// - lapsed reservations, resource locks, and lock waiters are dropped.
+    ///
+    /// Each released claim is recorded as a `claim_recovered` execution
+    /// event. Resource locks are not repo-scoped and are swept either way.
+    ///
+    /// # Errors
+    /// Returns an error if a database operation fails.
+    #[allow(clippy::too_many_lines)]
+    pub async fn recover_interrupted_runs(
+        &self,
+        repo_id: Option<&RepoId>,
+    ) -> Result<Vec<RecoveryAction>> {
+        let repo_filter = repo_id.map(RepoId::value);
+        let active = BeadLifecycle::<bead_states::InProgress>::observed();
+        let requeued = active.requeue();
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let orphans = sqlx::query!(
+            r#"SELECT c.repo_id, c.bead_id, c.claimed_by,
+                    c.lease_expires_at + swarm_heartbeat_grace() <= NOW() AS "lease_expired!",
+                    a.agent_id IS NULL AS "unregistered!"
+             FROM bead_claims c
+             LEFT JOIN agent_state a
+               ON a.repo_id = c.repo_id AND a.agent_id = c.claimed_by AND a.deleted_at IS NULL
+             WHERE c.status = $2
+               AND c.deleted_at IS NULL
+               AND ($1::TEXT IS NULL OR c.repo_id = $1)
+               AND (c.lease_expires_at + swarm_heartbeat_grace() <= NOW()
+                    OR a.agent_id IS NULL
+                    OR a.bead_id IS DISTINCT FROM c.bead_id)
+             ORDER BY c.claimed_at ASC
+             FOR UPDATE OF c SKIP LOCKED"#,
+            repo_filter,
+            active.claim_status(),
+        )
+        .map(|row| {
+            (
+                row.repo_id,
+                row.bead_id,
+                row.claimed_by,
+                row.lease_expired,
+                row.unregistered,
+            )
+        })
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to find orphaned claims: {e}")))?;
+
+        let mut actions = Vec::new();
+        for (repo, bead, agent, lease_expired, unregistered) in orphans {
+            let reason = if lease_expired {
+                ClaimRecoveryReason::LeaseExpired
+            } else if unregistered {
+                ClaimRecoveryReason::AgentUnregistered
+            } else {
+                ClaimRecoveryReason::AgentMovedOn
+            };
+
+            sqlx::query!(
+                "UPDATE agent_state
+                 SET bead_id = NULL,
+                     current_stage = NULL,
+                     stage_started_at = NULL,
+                     status = 'idle',
+                     feedback = NULL,
+                     implementation_attempt = 0
+                 WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3 AND deleted_at IS NULL",
+                &repo,
+                agent,
+                &bead,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to reset agent state: {e}")))?;
+
+            sqlx::query!("DELETE FROM agent_messages WHERE bead_id = $1", &bead)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| {
+                    SwarmError::DatabaseError(format!(
+                        "Failed to clear bead messages on recovery: {e}"
+                    ))
+                })?;
+
+            sqlx::query!(
+                "UPDATE bead_claims
+                 SET deleted_at = NOW(), deleted_by = 'recover'
+                 WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL",
+                &repo,
+                &bead,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to release orphaned claim: {e}"))
+            })?;
+
+            sqlx::query!(
+                "UPDATE bead_backlog
+                 SET status = $3
+                 WHERE repo_id = $1 AND bead_id = $2 AND status = $4 AND deleted_at IS NULL",
+                &repo,
+                &bead,
+                requeued.backlog_status(),
+                active.backlog_status(),
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to requeue bead: {e}")))?;
+
+            sqlx::query!(
+                "INSERT INTO execution_events (schema_version, event_type, entity_id, bead_id, agent_id, payload)
+                 VALUES ($1, 'claim_recovered', $2, $3, $4, $5)",
+                EventSchemaVersion::LATEST.as_i32(),
+                event_entity_id(
+                &BeadId::new(bead.clone()),
+                &RepoId::new(repo.clone()),
+            ),
+                &bead,
+                agent,
+                json!({"reason": reason}),
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to write recovery event: {e}"))
+            })?;
+
+            actions.push(RecoveryAction::ClaimRequeued {
+                repo_id: repo,
+                bead_id: bead,
+                agent_id: agent.cast_unsigned(),
+                reason,
+            });
+        }
+
+        let stranded = sqlx::query!(
+            "UPDATE bead_backlog b
+             SET status = $2
+             WHERE b.status = $3
+               AND b.deleted_at IS NULL
+               AND ($1::TEXT IS NULL OR b.repo_id = $1)
+               AND NOT EXISTS (
+                   SELECT 1 FROM bead_claims c
+                   WHERE c.repo_id = b.repo_id AND c.bead_id = b.bead_id AND c.deleted_at IS NULL
+               )
+             RETURNING b.repo_id, b.bead_id",
+            repo_filter,
+            requeued.backlog_status(),
+            active.backlog_status(),
+        )
+        .map(|row| (row.repo_id, row.bead_id))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to requeue stranded beads: {e}")))?;
+        actions.extend(
+            stranded
+                .into_iter()
+                .map(|(repo_id, bead_id)| RecoveryAction::BacklogRequeued { repo_id, bead_id }),
+        );
+
+        let reservations = sqlx::query!(
+            "DELETE FROM bead_reservations
+             WHERE expires_at <= NOW()
+               AND ($1::TEXT IS NULL OR repo_id = $1)
+             RETURNING repo_id, bead_id, agent_id",
+            repo_filter,
+        )
+        .map(|row| (row.repo_id, row.bead_id, row.agent_id))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to drop reservations: {e}")))?;
+        actions.extend(
+            reservations
+                .into_iter()
+                .map(
+                    |(repo_id, bead_id, agent_id)| RecoveryAction::ReservationExpired {
+                        repo_id,
+                        bead_id,
+                        agent_id: agent_id.cast_unsigned(),
+                    },
+                ),
+        );
+
+        let locks = sqlx::query!(
+            "DELETE FROM resource_locks WHERE until_at <= NOW() RETURNING resource, agent",
+        )
+        .map(|row| (row.resource, row.agent))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to cleanup locks: {e}")))?;
+        actions.extend(
+            locks
+                .into_iter()
+                .map(|(resource, agent)| RecoveryAction::LockExpired { resource, agent }),
+        );
+
+        let waiters = sqlx::query!(
+            "DELETE FROM resource_lock_waiters WHERE wait_until <= NOW() RETURNING resource, agent",
+        )
+        .map(|row| (row.resource, row.agent))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to cleanup lock waiters: {e}")))?;
+        actions.extend(
+            waiters
+                .into_iter()
+                .map(|(resource, agent)| RecoveryAction::LockWaiterExpired { resource, agent }),
+        );
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))
+            .map(|()| actions)
+    }
+}
@@ -0,0 +1,351 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use super::helpers::event_entity_id;
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::runtime::BeadLifecycle;
+use crate::types::{AgentId, BeadId, BeadReservation, EventSchemaVersion};
+use serde_json::json;
+use sqlx::Acquire;
+
+impl SwarmDb {
+    /// Reserves the top pending bead for `agent_id` for `ttl_secs`, without
+    /// claiming it. Any earlier reservation the agent holds is dropped.
+    /// With `labels`, or else the agent's default labels, only beads carrying
+    /// one of them qualify, and only beads whose `required_capabilities` the
+    /// agent's default labels cover. Returns `None` when no unreserved bead
+    /// is pending.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::AgentError` if the agent is unregistered,
+    /// quarantined, or already working a bead; otherwise an error if a
+    /// database operation fails.
+    #[allow(clippy::too_many_lines)]
+    pub async fn reserve_next_bead(
+        &self,
+        agent_id: &AgentId,
+        ttl_secs: u32,
+        labels: Option<&[String]>,
+    ) -> Result<Option<BeadReservation>> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let repo_id = agent_id.repo_id().value();
+        let agent_number = agent_id.number();
+        let agent = sqlx::query!(
+            r#"SELECT a.bead_id, q.reason AS "reason?", a.labels
+             FROM agent_state a
+             LEFT JOIN agent_quarantine q
+               ON q.repo_id = a.repo_id AND q.agent_id = a.agent_id AND q.released_at IS NULL
+             WHERE a.repo_id = $1 AND a.agent_id = $2 AND a.deleted_at IS NULL
+             FOR UPDATE OF a"#,
+            repo_id,
+            agent_number.cast_signed(),
+        )
+        .map(|row| (row.bead_id, row.reason, row.labels))
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to read agent state: {e}")))?;
+
+        let (refusal, default_labels) = match agent {
+            None => (
+                Some(format!("Agent {agent_number} is not registered")),
+                Vec::new(),
+            ),
+            Some((_, Some(reason), labels)) => (
+                Some(format!("Agent {agent_number} is quarantined: {reason}")),
+                labels,
+            ),
+            Some((Some(current), None, labels)) => (
+                Some(format!("Agent {agent_number} is working bead {current}")),
+                labels,
+            ),
+            Some((None, None, labels)) => (None, labels),
+        };
+        if let Some(refusal) = refusal {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Err(SwarmError::AgentError(refusal));
+        }
+
+        sqlx::query!(
+            "DELETE FROM bead_reservations
+             WHERE repo_id = $1 AND (agent_id = $2 OR expires_at <= NOW())",
+            repo_id,
+            agent_number.cast_signed(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to clear reservations: {e}")))?;
+
+        let bead_id = sqlx::query_scalar!(
+            "SELECT b.bead_id
+             FROM bead_backlog b
+             WHERE b.repo_id = $1
+               AND b.status = 'pending'
+               AND b.deleted_at IS NULL
+               AND (cardinality($2::TEXT[]) = 0 OR b.labels && $2::TEXT[])
+               AND b.required_capabilities <@ $3::TEXT[]
+               AND NOT EXISTS (
+                   SELECT 1
+                   FROM bead_reservations r
+                   WHERE r.repo_id = b.repo_id AND r.bead_id = b.bead_id
+               )
+             ORDER BY
+                 COALESCE(array_position(ARRAY['p0', 'p1', 'p2', 'p3']::TEXT[], lower(b.priority)), 999),
+                 b.created_at ASC
+             FOR UPDATE SKIP LOCKED
+             LIMIT 1",
+            repo_id,
+            labels.unwrap_or(&default_labels),
+            &default_labels,
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to pick bead to reserve: {e}")))?;
+
+        let Some(bead_id) = bead_id else {
+            tx.commit()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+            return Ok(None);
+        };
+
+        let (reserved_at, expires_at) = sqlx::query!(
+            "INSERT INTO bead_reservations (repo_id, bead_id, agent_id, reserved_at, expires_at)
+             VALUES ($1, $2, $3, NOW(), NOW() + $4 * INTERVAL '1 second')
+             RETURNING reserved_at, expires_at",
+            repo_id,
+            &bead_id,
+            agent_number.cast_signed(),
+            f64::from(ttl_secs),
+        )
+        .map(|row| (row.reserved_at, row.expires_at))
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to reserve bead: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+
+        Ok(Some(BeadReservation {
+            bead_id: BeadId::new(bead_id),
+            agent_id: agent_number,
+            reserved_at,
+            expires_at,
+        }))
+    }
+
+    /// Turns `agent_id`'s live reservation on `bead_id` into a claim, exactly
+    /// as `claim_next_bead` would have.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::AgentError` if the agent holds no live
+    /// reservation on the bead, has been quarantined since reserving, or the
+    /// bead is no longer pending; otherwise an error if a database operation
+    /// fails.
+    #[allow(clippy::too_many_lines)]
+    pub async fn accept_reservation(&self, agent_id: &AgentId, bead_id: &BeadId) -> Result<()> {
+        let pending = BeadLifecycle::pending();
+        let claimed = pending.claim();
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let repo_id = agent_id.repo_id().value();
+        let agent_number = agent_id.number();
+        let reserved = sqlx::query!(
+            "DELETE FROM bead_reservations
+             WHERE repo_id = $1 AND bead_id = $2 AND agent_id = $3 AND expires_at > NOW()",
+            repo_id,
+            bead_id.value(),
+            agent_number.cast_signed(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to take reservation: {e}")))?;
+
+        if reserved.rows_affected() != 1 {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Err(SwarmError::AgentError(format!(
+                "Agent {agent_number} holds no live reservation on {}",
+                bead_id.value()
+            )));
+        }
+
+        let quarantine_reason = sqlx::query_scalar!(
+            "SELECT reason
+             FROM agent_quarantine
+             WHERE repo_id = $1 AND agent_id = $2 AND released_at IS NULL",
+            repo_id,
+            agent_number.cast_signed(),
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to inspect quarantine: {e}")))?;
+
+        if let Some(reason) = quarantine_reason {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Err(SwarmError::AgentError(format!(
+                "Agent {agent_number} is quarantined: {reason}"
+            )));
+        }
+
+        let backlog_update = sqlx::query!(
+            "UPDATE bead_backlog
+             SET status = $3
+             WHERE repo_id = $1 AND bead_id = $2 AND status = $4 AND deleted_at IS NULL",
+            repo_id,
+            bead_id.value(),
+            claimed.backlog_status(),
+            pending.backlog_status(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to update backlog bead: {e}")))?;
+
+        let claim_insert = if backlog_update.rows_affected() == 1 {
+            sqlx::query!(
+                "INSERT INTO bead_claims (repo_id, bead_id, claimed_by, status, heartbeat_at, lease_expires_at)
+                 VALUES ($1, $2, $3, $4, NOW(), NOW() + swarm_lease_ttl())
+                 ON CONFLICT (repo_id, bead_id) DO UPDATE
+                 SET claimed_by = EXCLUDED.claimed_by,
+                     status = EXCLUDED.status,
+                     claimed_at = NOW(),
+                     heartbeat_at = EXCLUDED.heartbeat_at,
+                     lease_expires_at = EXCLUDED.lease_expires_at,
+                     takeover_consented_at = NULL,
+                     deleted_at = NULL,
+                     deleted_by = NULL
+                 WHERE bead_claims.deleted_at IS NOT NULL",
+                repo_id,
+                bead_id.value(),
+                agent_number.cast_signed(),
+                claimed.claim_status(),
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to claim bead: {e}")))?
+            .rows_affected()
+        } else {
+            0
+        };
+
+        if claim_insert != 1 {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Err(SwarmError::AgentError(format!(
+                "Bead {} is no longer pending",
+                bead_id.value()
+            )));
+        }
+
+        sqlx::query!(
+            "UPDATE agent_state
+             SET bead_id = $3,
+                 current_stage = 'rust-contract',
+                 stage_started_at = NOW(),
+                 status = 'working',
+                 last_update = NOW()
+             WHERE repo_id = $1
+               AND agent_id = $2
+               AND deleted_at IS NULL",
+            repo_id,
+            agent_number.cast_signed(),
+            bead_id.value(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to update agent state: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))
+    }
+
+    /// Drops `agent_id`'s reservation on `bead_id` so the bead goes straight
+    /// back to the pool, recording a `claim_rejected` event with `reason`.
+    /// Returns `false` if the agent held no reservation on it.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn reject_reservation(
+        &self,
+        agent_id: &AgentId,
+        bead_id: &BeadId,
+        reason: Option<&str>,
+    ) -> Result<bool> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let dropped = sqlx::query!(
+            "DELETE FROM bead_reservations
+             WHERE repo_id = $1 AND bead_id = $2 AND agent_id = $3",
+            agent_id.repo_id().value(),
+            bead_id.value(),
+            agent_id.number().cast_signed(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to drop reservation: {e}")))?
+        .rows_affected()
+            == 1;
+
+        if dropped {
+            sqlx::query!(
+                "INSERT INTO execution_events (schema_version, event_type, entity_id, bead_id, agent_id, payload)
+                 VALUES ($1, 'claim_rejected', $2, $3, $4, $5)",
+                EventSchemaVersion::LATEST.as_i32(),
+                event_entity_id(bead_id, agent_id.repo_id()),
+                bead_id.value(),
+                agent_id.number().cast_signed(),
+                json!({"reason": reason}),
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to write rejection event: {e}"))
+            })?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(dropped)
+    }
+}
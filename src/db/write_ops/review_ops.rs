@@ -0,0 +1,55 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::swarm_db::{to_bead_review, ReviewRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{BeadReview, RepoId, ReviewVerdict};
+
+impl SwarmDb {
+    /// Records `reviewer`'s verdict on `bead_id`. Earlier reviews are kept;
+    /// the latest one per reviewer is what counts toward landing.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn submit_review(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &str,
+        reviewer: &str,
+        agent_id: Option<u32>,
+        verdict: ReviewVerdict,
+        comments: Option<&str>,
+    ) -> Result<BeadReview> {
+        sqlx::query!(
+            "INSERT INTO reviews (repo_id, bead_id, reviewer, agent_id, verdict, comments)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             RETURNING id, bead_id, reviewer, agent_id, verdict, comments, created_at",
+            repo_id.value(),
+            bead_id,
+            reviewer,
+            agent_id.map(u32::cast_signed),
+            verdict.as_str(),
+            comments,
+        )
+        .map(|row| -> ReviewRow {
+            (
+                row.id,
+                row.bead_id,
+                row.reviewer,
+                row.agent_id,
+                row.verdict,
+                row.comments,
+                row.created_at,
+            )
+        })
+        .fetch_one(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to submit review: {e}")))
+        .and_then(to_bead_review)
+    }
+}
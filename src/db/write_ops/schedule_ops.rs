@@ -0,0 +1,168 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{RepoId, ScheduledJobKind, SoftDeleteTable};
+use serde_json::{json, Value};
+
+/// Days a soft-deleted row stays restorable before the `gc` job purges it.
+const GC_DELETED_ROWS_RETENTION_DAYS: i32 = 30;
+/// Long enough to keep a baseline from before the last few upgrades.
+const GC_QUERY_LATENCY_RETENTION_DAYS: i32 = 90;
+
+impl SwarmDb {
+    /// Turns a scheduled job on or off for `serve`.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn set_scheduled_job_enabled(
+        &self,
+        repo_id: &RepoId,
+        job: ScheduledJobKind,
+        enabled: bool,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO scheduled_jobs (repo_id, job, enabled)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (repo_id, job) DO UPDATE
+             SET enabled = EXCLUDED.enabled, updated_at = NOW()",
+            repo_id.value(),
+            job.as_str(),
+            enabled,
+        )
+        .execute(self.pool())
+        .await
+        .map(|_result| ())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to update scheduled job: {e}")))
+    }
+
+    /// Stores the outcome of a job run, which `doctor` and `jobs list` show
+    /// and `serve` schedules the next run from.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn record_scheduled_job_run(
+        &self,
+        repo_id: &RepoId,
+        job: ScheduledJobKind,
+        ms: u64,
+        outcome: std::result::Result<&Value, &str>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO scheduled_jobs
+                 (repo_id, job, last_run_at, last_ok, last_ms, last_result, last_error)
+             VALUES ($1, $2, NOW(), $3, $4, $5, $6)
+             ON CONFLICT (repo_id, job) DO UPDATE
+             SET last_run_at = EXCLUDED.last_run_at,
+                 last_ok = EXCLUDED.last_ok,
+                 last_ms = EXCLUDED.last_ms,
+                 last_result = EXCLUDED.last_result,
+                 last_error = EXCLUDED.last_error,
+                 updated_at = NOW()",
+            repo_id.value(),
+            job.as_str(),
+            outcome.is_ok(),
+            i64::try_from(ms).unwrap_or(i64::MAX),
+            outcome.ok(),
+            outcome.err(),
+        )
+        .execute(self.pool())
+        .await
+        .map(|_result| ())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to record job run: {e}")))
+    }
+
+    /// Purges soft-deleted agents, claims and backlog entries past their
+    /// 30-day retention, SLA breaches of beads that have left the backlog,
+    /// expired announcements with their acks, and query latency older than
+    /// 90 days. Returns how many rows each removed. A deleted claim that an
+    /// agent or message still points at is kept until they let go of it.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn collect_garbage(&self, repo_id: &RepoId) -> Result<Value> {
+        let mut deleted = serde_json::Map::new();
+        for (table, purge) in [
+            (
+                SoftDeleteTable::Agents,
+                "DELETE FROM agent_state
+                 WHERE repo_id = $1 AND deleted_at < NOW() - make_interval(days => $2)",
+            ),
+            (
+                SoftDeleteTable::Claims,
+                "DELETE FROM bead_claims c
+                 WHERE c.repo_id = $1
+                   AND c.deleted_at < NOW() - make_interval(days => $2)
+                   AND NOT EXISTS (
+                       SELECT 1 FROM agent_state a
+                       WHERE a.repo_id = c.repo_id AND a.bead_id = c.bead_id
+                   )
+                   AND NOT EXISTS (SELECT 1 FROM agent_messages m WHERE m.bead_id = c.bead_id)",
+            ),
+            (
+                SoftDeleteTable::Backlog,
+                "DELETE FROM bead_backlog
+                 WHERE repo_id = $1 AND deleted_at < NOW() - make_interval(days => $2)",
+            ),
+        ] {
+            let purged = sqlx::query(purge)
+                .bind(repo_id.value())
+                .bind(GC_DELETED_ROWS_RETENTION_DAYS)
+                .execute(self.pool())
+                .await
+                .map_err(|e| {
+                    SwarmError::DatabaseError(format!(
+                        "Failed to purge deleted {}: {e}",
+                        table.as_str()
+                    ))
+                })?
+                .rows_affected();
+            deleted.insert(table.as_str().to_string(), json!(purged));
+        }
+
+        let sla_breaches = sqlx::query!(
+            "DELETE FROM sla_breaches s
+             WHERE s.repo_id = $1
+               AND NOT EXISTS (
+                   SELECT 1 FROM bead_backlog b
+                   WHERE b.repo_id = s.repo_id AND b.bead_id = s.bead_id AND b.deleted_at IS NULL
+               )",
+            repo_id.value(),
+        )
+        .execute(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to purge SLA breaches: {e}")))?
+        .rows_affected();
+
+        let announcements = sqlx::query!(
+            "DELETE FROM announcements WHERE repo_id = $1 AND expires_at <= NOW()",
+            repo_id.value(),
+        )
+        .execute(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to purge announcements: {e}")))?
+        .rows_affected();
+
+        let query_latency = sqlx::query!(
+            "DELETE FROM query_latency WHERE recorded_at < NOW() - make_interval(days => $1)",
+            GC_QUERY_LATENCY_RETENTION_DAYS,
+        )
+        .execute(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to purge query latency: {e}")))?
+        .rows_affected();
+
+        Ok(json!({
+            "deleted": deleted,
+            "sla_breaches": sla_breaches,
+            "announcements": announcements,
+            "query_latency": query_latency,
+            "retention_days": GC_DELETED_ROWS_RETENTION_DAYS,
+        }))
+    }
+}
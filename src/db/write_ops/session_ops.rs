@@ -0,0 +1,125 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::swarm_db::{to_agent_session, SessionRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentSession, RepoId, SessionEndReason};
+
+impl SwarmDb {
+    /// Attaches a process as `agent_id`. A session the agent still had open
+    /// is ended as `replaced` in the same transaction and returned second.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn start_agent_session(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+        host: &str,
+        pid: u32,
+        version: &str,
+    ) -> Result<(AgentSession, Option<AgentSession>)> {
+        let mut tx = self.pool().begin().await.map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to begin agent session: {e}"))
+        })?;
+        let replaced = sqlx::query!(
+            "UPDATE agent_sessions
+             SET ended_at = NOW(), end_reason = $3
+             WHERE repo_id = $1 AND agent_id = $2 AND ended_at IS NULL
+             RETURNING id, agent_id, host, pid, version, started_at, ended_at, end_reason",
+            repo_id.value(),
+            agent_id.cast_signed(),
+            SessionEndReason::Replaced.as_str(),
+        )
+        .map(|row| -> SessionRow {
+            (
+                row.id,
+                row.agent_id,
+                row.host,
+                row.pid,
+                row.version,
+                row.started_at,
+                row.ended_at,
+                row.end_reason,
+            )
+        })
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to replace agent session: {e}")))?
+        .map(to_agent_session)
+        .transpose()?;
+        let started = sqlx::query!(
+            "INSERT INTO agent_sessions (repo_id, agent_id, host, pid, version)
+             VALUES ($1, $2, $3, $4, $5)
+             RETURNING id, agent_id, host, pid, version, started_at, ended_at, end_reason",
+            repo_id.value(),
+            agent_id.cast_signed(),
+            host,
+            i32::try_from(pid).unwrap_or(i32::MAX),
+            version,
+        )
+        .map(|row| -> SessionRow {
+            (
+                row.id,
+                row.agent_id,
+                row.host,
+                row.pid,
+                row.version,
+                row.started_at,
+                row.ended_at,
+                row.end_reason,
+            )
+        })
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to start agent session: {e}")))
+        .and_then(to_agent_session)?;
+        tx.commit().await.map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to commit agent session: {e}"))
+        })?;
+        Ok((started, replaced))
+    }
+
+    /// Ends the agent's open session, keeping the row as history. Returns
+    /// `None` when the agent had no open session.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn end_agent_session(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+    ) -> Result<Option<AgentSession>> {
+        sqlx::query!(
+            "UPDATE agent_sessions
+             SET ended_at = NOW(), end_reason = $3
+             WHERE repo_id = $1 AND agent_id = $2 AND ended_at IS NULL
+             RETURNING id, agent_id, host, pid, version, started_at, ended_at, end_reason",
+            repo_id.value(),
+            agent_id.cast_signed(),
+            SessionEndReason::Ended.as_str(),
+        )
+        .map(|row| -> SessionRow {
+            (
+                row.id,
+                row.agent_id,
+                row.host,
+                row.pid,
+                row.version,
+                row.started_at,
+                row.ended_at,
+                row.end_reason,
+            )
+        })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to end agent session: {e}")))?
+        .map(to_agent_session)
+        .transpose()
+    }
+}
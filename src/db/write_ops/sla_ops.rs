@@ -0,0 +1,39 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{RepoId, SlaAssessment};
+
+impl SwarmDb {
+    /// Remembers that a bead breached its SLA. Returns `false` when the
+    /// breach was already recorded, so callers emit it only once.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn record_sla_breach(
+        &self,
+        repo_id: &RepoId,
+        assessment: &SlaAssessment,
+        target_secs: i64,
+    ) -> Result<bool> {
+        sqlx::query!(
+            "INSERT INTO sla_breaches (repo_id, bead_id, priority, target_secs, enqueued_at)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (repo_id, bead_id) DO NOTHING",
+            repo_id.value(),
+            &assessment.bead_id,
+            &assessment.priority,
+            target_secs,
+            assessment.enqueued_at,
+        )
+        .execute(self.pool())
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to record SLA breach: {e}")))
+    }
+}
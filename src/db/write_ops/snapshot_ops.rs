@@ -0,0 +1,282 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use super::helpers::event_entity_id;
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{BeadId, BeadSnapshot, EventSchemaVersion, RepoId};
+use serde_json::json;
+use sqlx::Acquire;
+
+impl SwarmDb {
+    /// Reinstates `snapshot` in `repo_id` in one transaction. The bead's
+    /// backlog entry, claim, and assignment are overwritten, and its stage
+    /// history is replaced by the snapshot's attempts and artifacts.
+    ///
+    /// The snapshot must already be validated.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::AgentError` if another agent holds the bead, or
+    /// the assigned agent is unregistered, quarantined, or busy with another
+    /// bead; otherwise an error if a database operation fails.
+    #[allow(clippy::too_many_lines)]
+    pub async fn restore_bead_snapshot(
+        &self,
+        repo_id: &RepoId,
+        snapshot: &BeadSnapshot,
+    ) -> Result<()> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let bead_id = snapshot.bead_id.as_str();
+        let target_agent = snapshot
+            .assignment
+            .as_ref()
+            .map(|assignment| assignment.agent_id.cast_signed());
+
+        let holder = sqlx::query_scalar!(
+            r#"SELECT agent_id AS "agent_id!"
+             FROM agent_state
+             WHERE repo_id = $1 AND bead_id = $2 AND agent_id IS DISTINCT FROM $3
+               AND deleted_at IS NULL
+             UNION
+             SELECT claimed_by
+             FROM bead_claims
+             WHERE repo_id = $1 AND bead_id = $2 AND status = 'in_progress'
+               AND claimed_by IS DISTINCT FROM $3 AND deleted_at IS NULL
+             LIMIT 1"#,
+            repo_id.value(),
+            bead_id,
+            target_agent,
+        )
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to inspect bead holder: {e}")))?;
+
+        if let Some(holder) = holder {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Err(SwarmError::AgentError(format!(
+                "Bead {bead_id} is held by agent {holder}; release it before restoring"
+            )));
+        }
+
+        if let Some(agent_id) = target_agent {
+            let agent = sqlx::query!(
+                r#"SELECT a.bead_id, q.reason AS "reason?"
+                 FROM agent_state a
+                 LEFT JOIN agent_quarantine q
+                   ON q.repo_id = a.repo_id AND q.agent_id = a.agent_id AND q.released_at IS NULL
+                 WHERE a.repo_id = $1 AND a.agent_id = $2 AND a.deleted_at IS NULL
+                 FOR UPDATE OF a"#,
+                repo_id.value(),
+                agent_id,
+            )
+            .map(|row| (row.bead_id, row.reason))
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to read agent state: {e}")))?;
+
+            let refusal = match agent {
+                None => Some(format!("Agent {agent_id} is not registered")),
+                Some((_, Some(reason))) => {
+                    Some(format!("Agent {agent_id} is quarantined: {reason}"))
+                }
+                Some((Some(current), None)) if current != bead_id => {
+                    Some(format!("Agent {agent_id} is working bead {current}"))
+                }
+                Some(_) => None,
+            };
+            if let Some(refusal) = refusal {
+                tx.rollback().await.map_err(|e| {
+                    SwarmError::DatabaseError(format!("Failed to rollback tx: {e}"))
+                })?;
+                return Err(SwarmError::AgentError(refusal));
+            }
+        }
+
+        if let Some(backlog) = &snapshot.backlog {
+            sqlx::query!(
+                "INSERT INTO bead_backlog (repo_id, bead_id, priority, status)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (repo_id, bead_id)
+                 DO UPDATE SET priority = EXCLUDED.priority, status = EXCLUDED.status,
+                               deleted_at = NULL, deleted_by = NULL",
+                repo_id.value(),
+                bead_id,
+                &backlog.priority,
+                &backlog.status,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to restore backlog bead: {e}"))
+            })?;
+        }
+
+        if let Some(claim) = &snapshot.claim {
+            sqlx::query!(
+                "INSERT INTO bead_claims (repo_id, bead_id, claimed_by, status, claimed_at, heartbeat_at, lease_expires_at)
+                 VALUES ($1, $2, $3, $4, $5, NOW(), NOW() + swarm_lease_ttl())
+                 ON CONFLICT (repo_id, bead_id)
+                 DO UPDATE SET claimed_by = EXCLUDED.claimed_by,
+                               status = EXCLUDED.status,
+                               claimed_at = EXCLUDED.claimed_at,
+                               heartbeat_at = EXCLUDED.heartbeat_at,
+                               lease_expires_at = EXCLUDED.lease_expires_at,
+                               deleted_at = NULL,
+                               deleted_by = NULL",
+                repo_id.value(),
+                bead_id,
+                claim.claimed_by.cast_signed(),
+                &claim.status,
+                claim.claimed_at,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to restore bead claim: {e}"))
+            })?;
+        } else {
+            sqlx::query!("DELETE FROM agent_messages WHERE bead_id = $1", bead_id)
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| {
+                    SwarmError::DatabaseError(format!(
+                        "Failed to clear bead messages on restore: {e}"
+                    ))
+                })?;
+            sqlx::query!(
+                "UPDATE bead_claims
+                 SET deleted_at = NOW(), deleted_by = 'bead restore'
+                 WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL",
+                repo_id.value(),
+                bead_id,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to clear bead claim on restore: {e}"))
+            })?;
+        }
+
+        if let Some(assignment) = &snapshot.assignment {
+            sqlx::query!(
+                "UPDATE agent_state
+                 SET bead_id = $3,
+                     current_stage = $4,
+                     stage_started_at = CASE WHEN $4::TEXT IS NULL THEN NULL ELSE NOW() END,
+                     status = $5,
+                     implementation_attempt = $6,
+                     feedback = $7,
+                     last_update = NOW()
+                 WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL",
+                repo_id.value(),
+                assignment.agent_id.cast_signed(),
+                bead_id,
+                assignment.current_stage.as_deref(),
+                &assignment.status,
+                assignment.implementation_attempt.cast_signed(),
+                assignment.feedback.as_deref(),
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to restore agent assignment: {e}"))
+            })?;
+        }
+
+        sqlx::query!(
+            "DELETE FROM stage_history WHERE repo_id = $1 AND bead_id = $2",
+            repo_id.value(),
+            bead_id,
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to clear stage history on restore: {e}"))
+        })?;
+
+        for stage in &snapshot.stages {
+            let stage_history_id = sqlx::query_scalar!(
+                "INSERT INTO stage_history (
+                    repo_id, agent_id, bead_id, stage, attempt_number, status, result,
+                    feedback, transcript, started_at, completed_at, duration_ms
+                 )
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                 RETURNING id",
+                repo_id.value(),
+                stage.agent_id.cast_signed(),
+                bead_id,
+                &stage.stage,
+                stage.attempt_number.cast_signed(),
+                &stage.status,
+                stage.result.as_deref(),
+                stage.feedback.as_deref(),
+                stage.transcript.as_deref(),
+                stage.started_at,
+                stage.completed_at,
+                stage.duration_ms.map(u32::cast_signed),
+            )
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| {
+                SwarmError::DatabaseError(format!("Failed to restore stage attempt: {e}"))
+            })?;
+
+            for artifact in &stage.artifacts {
+                sqlx::query!(
+                    "INSERT INTO stage_artifacts (
+                        stage_history_id, artifact_type, content, metadata, created_at, content_hash
+                     )
+                     VALUES ($1, $2, $3, $4, $5, encode(digest($3, 'sha256'), 'hex'))",
+                    stage_history_id,
+                    &artifact.artifact_type,
+                    &artifact.content,
+                    artifact.metadata.as_ref(),
+                    artifact.created_at,
+                )
+                .execute(&mut *conn)
+                .await
+                .map_err(|e| {
+                    SwarmError::DatabaseError(format!("Failed to restore stage artifact: {e}"))
+                })?;
+            }
+        }
+
+        sqlx::query!(
+            "INSERT INTO execution_events (schema_version, event_type, entity_id, bead_id, agent_id, payload)
+             VALUES ($1, 'bead_restored', $2, $3, $4, $5)",
+            EventSchemaVersion::LATEST.as_i32(),
+            event_entity_id(&BeadId::new(bead_id), repo_id),
+            bead_id,
+            target_agent,
+            json!({
+                "source_repo_id": snapshot.repo_id,
+                "captured_at": snapshot.captured_at,
+                "stages": snapshot.stages.len(),
+                "artifacts": snapshot.artifact_count(),
+            }),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to write restore event: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))
+    }
+}
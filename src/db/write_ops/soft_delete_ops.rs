@@ -0,0 +1,189 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::swarm_db::{select_deleted_rows, to_deleted_row, DeletedRowRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::runtime::BeadLifecycle;
+use crate::types::{DeletedRow, RepoId, SoftDeleteTable};
+use serde_json::Value;
+use sqlx::PgConnection;
+
+impl SwarmDb {
+    /// Clears the deletion of `table`'s row with `key`. Returns `None` when
+    /// there is no deleted row with that key. A restored claim brings its
+    /// backlog entry and, while in progress, its agent back in line with it
+    /// in the same transaction.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::AgentError` if a claim's backlog entry or agent
+    /// has moved on, or an error if a database operation fails.
+    pub async fn undelete_row(
+        &self,
+        repo_id: &RepoId,
+        table: SoftDeleteTable,
+        key: &str,
+    ) -> Result<Option<DeletedRow>> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let Some(deleted) = sqlx::query_as::<_, DeletedRowRow>(&format!(
+            "{} AND t.repo_id = $1 AND t.{}::TEXT = $2 FOR UPDATE",
+            select_deleted_rows(table),
+            table.key_column()
+        ))
+        .bind(repo_id.value())
+        .bind(key)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to find deleted row: {e}")))?
+        .map(to_deleted_row)
+        .transpose()?
+        else {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Ok(None);
+        };
+
+        sqlx::query(&format!(
+            "UPDATE {} SET deleted_at = NULL, deleted_by = NULL
+             WHERE repo_id = $1 AND {}::TEXT = $2",
+            table.table_name(),
+            table.key_column()
+        ))
+        .bind(repo_id.value())
+        .bind(key)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to restore {} {key}: {e}", table.as_str()))
+        })?;
+
+        if table == SoftDeleteTable::Claims {
+            if let Err(refusal) = restore_claim_state(&mut tx, repo_id, key, &deleted.row).await {
+                tx.rollback().await.map_err(|e| {
+                    SwarmError::DatabaseError(format!("Failed to rollback tx: {e}"))
+                })?;
+                return Err(refusal);
+            }
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(Some(deleted))
+    }
+}
+
+/// Moves the backlog entry and agent of a restored claim back to where the
+/// claim left them, or refuses when they have moved on since: the bead left
+/// the backlog or settled differently, or the agent is gone or holds another
+/// bead. An in-progress claim also gets a fresh lease, so recovery does not
+/// take it straight back.
+async fn restore_claim_state(
+    conn: &mut PgConnection,
+    repo_id: &RepoId,
+    bead_id: &str,
+    claim: &Value,
+) -> Result<()> {
+    let pending = BeadLifecycle::pending();
+    let restored = pending.claim();
+    let failed = |e: sqlx::Error| {
+        SwarmError::DatabaseError(format!("Failed to restore claim {bead_id}: {e}"))
+    };
+    let refuse = |reason: String| {
+        SwarmError::AgentError(format!("Cannot restore claim {bead_id}: {reason}"))
+    };
+    let claim_status = claim
+        .get("status")
+        .and_then(Value::as_str)
+        .unwrap_or_else(|| restored.claim_status());
+    let claimed_by = claim
+        .get("claimed_by")
+        .and_then(Value::as_i64)
+        .ok_or_else(|| refuse("the deleted row has no claimed_by".to_string()))?;
+
+    let backlog_status = sqlx::query_scalar!(
+        "SELECT status FROM bead_backlog
+         WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL
+         FOR UPDATE",
+        repo_id.value(),
+        bead_id,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(failed)?
+    .ok_or_else(|| refuse("the bead is no longer in the backlog".to_string()))?;
+
+    if claim_status != restored.claim_status() {
+        return if backlog_status == claim_status {
+            Ok(())
+        } else {
+            Err(refuse(format!(
+                "the claim is {claim_status} but the backlog entry is {backlog_status}"
+            )))
+        };
+    }
+    if backlog_status != pending.backlog_status() && backlog_status != restored.backlog_status() {
+        return Err(refuse(format!("the backlog entry is {backlog_status}")));
+    }
+    let agent_bead = sqlx::query_scalar!(
+        "SELECT bead_id FROM agent_state
+         WHERE repo_id = $1 AND agent_id = $2::BIGINT AND deleted_at IS NULL
+         FOR UPDATE",
+        repo_id.value(),
+        claimed_by,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(failed)?
+    .ok_or_else(|| refuse(format!("agent {claimed_by} no longer exists")))?;
+    if let Some(other) = agent_bead.filter(|other| other != bead_id) {
+        return Err(refuse(format!("agent {claimed_by} is working on {other}")));
+    }
+
+    sqlx::query!(
+        "UPDATE bead_backlog SET status = $3 WHERE repo_id = $1 AND bead_id = $2",
+        repo_id.value(),
+        bead_id,
+        restored.backlog_status(),
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(failed)?;
+    sqlx::query!(
+        "UPDATE agent_state
+         SET status = 'working',
+             current_stage = CASE WHEN bead_id = $3 THEN COALESCE(current_stage, 'rust-contract')
+                                  ELSE 'rust-contract' END,
+             stage_started_at = CASE WHEN bead_id = $3 THEN COALESCE(stage_started_at, NOW())
+                                     ELSE NOW() END,
+             bead_id = $3
+         WHERE repo_id = $1 AND agent_id = $2::BIGINT",
+        repo_id.value(),
+        claimed_by,
+        bead_id,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(failed)?;
+    sqlx::query!(
+        "UPDATE bead_claims
+         SET heartbeat_at = NOW(), lease_expires_at = NOW() + swarm_lease_ttl()
+         WHERE repo_id = $1 AND bead_id = $2",
+        repo_id.value(),
+        bead_id,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(failed)?;
+    Ok(())
+}
@@ -1,15 +1,18 @@
 #![deny(clippy::unwrap_used)]
 #![deny(clippy::expect_used)]
-#![deny(clippy::panic)]
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
 #![forbid(unsafe_code)]
 
 use super::helpers::event_entity_id;
 use super::types::ExecutionEventWriteInput;
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
-use crate::types::{AgentId, BeadId, EventSchemaVersion, Stage, StageResult};
+use crate::summarizer::StageSummaryInput;
+use crate::types::{
+    AgentId, BeadId, EnvironmentFingerprint, EventSchemaVersion, Stage, StageResult,
+};
 use chrono::{DateTime, Utc};
 use serde_json::json;
 use sqlx::Acquire;
@@ -37,33 +40,33 @@ impl SwarmDb {
             .await
             .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
 
-        let stage_history_id = sqlx::query_scalar::<_, i64>(
+        let stage_history_id = sqlx::query_scalar!(
             "INSERT INTO stage_history (repo_id, agent_id, bead_id, stage, attempt_number, status)
              VALUES ($1, $2, $3, $4, $5, 'started')
              RETURNING id",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            bead_id.value(),
+            stage.as_str(),
+            attempt.cast_signed(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(agent_id.number().cast_signed())
-        .bind(bead_id.value())
-        .bind(stage.as_str())
-        .bind(attempt.cast_signed())
         .fetch_one(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to record stage start: {e}")))?;
 
-        sqlx::query(
+        sqlx::query!(
             "UPDATE agent_state
              SET current_stage = $3, stage_started_at = NOW(), status = 'working'
-             WHERE repo_id = $1 AND agent_id = $2",
+             WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            stage.as_str(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(agent_id.number().cast_signed())
-        .bind(stage.as_str())
         .execute(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to update stage start: {e}")))?;
 
-        sqlx::query(
+        sqlx::query!(
             "INSERT INTO execution_events (
                 schema_version,
                 event_type,
@@ -75,15 +78,15 @@ impl SwarmDb {
                 payload
             )
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            EventSchemaVersion::LATEST.as_i32(),
+            "stage_started",
+            event_entity_id(bead_id, agent_id.repo_id()),
+            bead_id.value(),
+            agent_id.number().cast_signed(),
+            stage.as_str(),
+            Some(format!("stage-history:{stage_history_id}")),
+            json!({"attempt": attempt, "status": "started"}),
         )
-        .bind(EventSchemaVersion::V1.as_i32())
-        .bind("stage_started")
-        .bind(event_entity_id(bead_id, agent_id.repo_id()))
-        .bind(bead_id.value())
-        .bind(agent_id.number().cast_signed())
-        .bind(stage.as_str())
-        .bind(Some(format!("stage-history:{stage_history_id}")))
-        .bind(json!({"attempt": attempt, "status": "started"}))
         .execute(&mut *conn)
         .await
         .map_err(|e| {
@@ -96,6 +99,31 @@ impl SwarmDb {
             .map(|()| stage_history_id)
     }
 
+    /// Stores the toolchain fingerprint taken as the stage started under
+    /// `stage_history.metadata.environment`.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn record_stage_environment(
+        &self,
+        stage_history_id: i64,
+        environment: &EnvironmentFingerprint,
+    ) -> Result<()> {
+        sqlx::query!(
+            "UPDATE stage_history
+             SET metadata = COALESCE(metadata, '{}'::JSONB) || jsonb_build_object('environment', $2::JSONB)
+             WHERE id = $1",
+            stage_history_id,
+            json!(environment),
+        )
+        .execute(self.pool())
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to record stage environment: {e}"))
+        })
+    }
+
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn record_stage_complete(
@@ -119,8 +147,20 @@ impl SwarmDb {
             )
             .await?;
 
+        let decision = super::helpers::transition_decision(stage, &result);
+        self.record_transition_event(super::types::TransitionEventInput {
+            agent_id,
+            bead_id,
+            stage,
+            result: &result,
+            attempt,
+            decision: &decision,
+            stage_history_id: Some(stage_history_id),
+        })
+        .await?;
+
         self.apply_stage_transition(super::types::StageTransitionInput {
-            transition: &super::helpers::determine_transition(stage, &result),
+            transition: &super::helpers::to_stage_transition(decision.transition()),
             agent_id,
             bead_id,
             stage,
@@ -156,7 +196,7 @@ impl SwarmDb {
             SwarmError::DatabaseError("Duration overflow updating stage history".to_string())
         })?;
 
-        let stage_history_row = sqlx::query_as::<_, (i64, Option<DateTime<Utc>>)>(
+        let stage_history_row = sqlx::query!(
             "UPDATE stage_history
              SET status = $6, result = $7, feedback = $8, completed_at = NOW(), duration_ms = $9
              WHERE id = (
@@ -170,16 +210,17 @@ impl SwarmDb {
                  ORDER BY started_at DESC LIMIT 1
              )
              RETURNING id, completed_at",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            bead_id.value(),
+            stage.as_str(),
+            attempt.cast_signed(),
+            &status,
+            message,
+            message,
+            duration_value,
         )
-        .bind(agent_id.repo_id().value())
-        .bind(agent_id.number().cast_signed())
-        .bind(bead_id.value())
-        .bind(stage.as_str())
-        .bind(attempt.cast_signed())
-        .bind(&status)
-        .bind(message)
-        .bind(message)
-        .bind(duration_value)
+        .map(|row| (row.id, row.completed_at))
         .fetch_optional(self.pool())
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to update stage history: {e}")))?
@@ -289,11 +330,11 @@ impl SwarmDb {
             SwarmError::DatabaseError(format!("Failed to serialize stage transcript: {e}"))
         })?;
 
-        sqlx::query(
+        sqlx::query!(
             "UPDATE stage_history\n             SET transcript = $1\n             WHERE id = $2 AND transcript IS DISTINCT FROM $1",
+            &transcript_text,
+            stage_history_id,
         )
-        .bind(&transcript_text)
-        .bind(stage_history_id)
         .execute(self.pool())
         .await
         .map(|_| ())
@@ -308,11 +349,49 @@ impl SwarmDb {
         .await
         .map(|_| ())?;
 
-        Ok(())
+        self.persist_stage_summary(stage_history_id, stage, attempt, result, &artifacts)
+            .await
+    }
+
+    /// Stores the `summary` artifact resume contexts show in place of the
+    /// stage run's transcripts.
+    async fn persist_stage_summary(
+        &self,
+        stage_history_id: i64,
+        stage: Stage,
+        attempt: u32,
+        result: &StageResult,
+        artifacts: &[crate::types::StageArtifact],
+    ) -> Result<()> {
+        let status = result.as_str();
+        let input = StageSummaryInput::new(
+            stage.as_str(),
+            attempt,
+            &status,
+            result.message().unwrap_or_default(),
+            artifacts,
+        );
+        let summary = self.stage_summarizer().summarize(&input).await;
+        self.store_stage_artifact(
+            stage_history_id,
+            crate::types::ArtifactType::Summary,
+            &summary.text,
+            Some(json!({
+                "stage_history_id": stage_history_id,
+                "stage": stage.as_str(),
+                "attempt": attempt,
+                "status": &status,
+                "summarizer": summary.summarizer,
+                "fallback_reason": summary.fallback_reason,
+                "source_artifacts": input.artifacts.len(),
+            })),
+        )
+        .await
+        .map(|_| ())
     }
 
     pub(crate) async fn ensure_stage_history_repo_scope(&self) -> Result<()> {
-        sqlx::query(
+        sqlx::query!(
             "ALTER TABLE stage_history
              ADD COLUMN IF NOT EXISTS repo_id TEXT NOT NULL DEFAULT 'local'",
         )
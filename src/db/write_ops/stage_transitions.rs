@@ -1,15 +1,18 @@
 #![deny(clippy::unwrap_used)]
 #![deny(clippy::expect_used)]
-#![deny(clippy::panic)]
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
 #![forbid(unsafe_code)]
 
-use super::helpers::build_failure_diagnostics;
-use super::types::{ExecutionEventWriteInput, StageTransitionInput};
+use super::helpers::{build_failure_diagnostics, runtime_transition_name};
+use super::kv_ops::clear_bead_kv;
+use super::types::{ExecutionEventWriteInput, StageTransitionInput, TransitionEventInput};
 use crate::db::SwarmDb;
 use crate::error::{Result, SwarmError};
-use crate::types::{AgentId, BeadId, Stage};
+use crate::landing::PushVerification;
+use crate::runtime::{bead_states, BeadLifecycle};
+use crate::types::{AgentId, ArtifactType, BeadId, Stage};
 use crate::BrSyncStatus;
 use serde_json::json;
 use sqlx::Acquire;
@@ -38,6 +41,140 @@ impl SwarmDb {
         .await
     }
 
+    /// Stores `verification` as a `push_verification` artifact on the bead's
+    /// latest stage run, then finalizes only if the push was confirmed.
+    /// Returns the artifact id.
+    ///
+    /// # Errors
+    /// Returns an error if the bead has no stage history, the push was not
+    /// confirmed, or database operations fail.
+    pub async fn finalize_after_verified_push(
+        &self,
+        agent_id: &AgentId,
+        bead_id: &BeadId,
+        verification: &PushVerification,
+    ) -> Result<i64> {
+        let artifact_id = self
+            .record_push_verification(agent_id, bead_id, verification)
+            .await?;
+        self.finalize_after_push_confirmation(agent_id, bead_id, verification.confirmed)
+            .await
+            .map(|()| artifact_id)
+    }
+
+    /// Stores `verification` as a `push_verification` artifact on the bead's
+    /// latest stage run without finalizing. Returns the artifact id.
+    ///
+    /// # Errors
+    /// Returns an error if the bead has no stage history or the database
+    /// operation fails.
+    pub async fn record_push_verification(
+        &self,
+        agent_id: &AgentId,
+        bead_id: &BeadId,
+        verification: &PushVerification,
+    ) -> Result<i64> {
+        let stage_history_id = sqlx::query_scalar!(
+            "SELECT id
+             FROM stage_history
+             WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3
+             ORDER BY started_at DESC
+             LIMIT 1",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            bead_id.value(),
+        )
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to locate stage history: {e}")))?
+        .ok_or_else(|| {
+            SwarmError::StageError(format!(
+                "No stage history for bead {} and agent {} to attach push verification",
+                bead_id.value(),
+                agent_id.number()
+            ))
+        })?;
+
+        self.store_stage_artifact(
+            stage_history_id,
+            ArtifactType::PushVerification,
+            &verification.evidence,
+            Some(json!({
+                "change_id": verification.change_id,
+                "remote": verification.remote,
+                "method": verification.method.as_str(),
+                "confirmed": verification.confirmed,
+                "checked_at": verification.checked_at,
+            })),
+        )
+        .await
+    }
+
+    /// Records how long a landing waited for the repo's landing slot, plus
+    /// the pre-land command result, as a `landing_queued` execution event.
+    ///
+    /// # Errors
+    /// Returns an error if the event cannot be stored.
+    pub async fn record_landing_queued(
+        &self,
+        agent_id: &AgentId,
+        bead_id: &BeadId,
+        queue_wait_ms: u64,
+        pre_land: serde_json::Value,
+    ) -> Result<()> {
+        self.record_execution_event(
+            bead_id,
+            agent_id,
+            ExecutionEventWriteInput {
+                stage: None,
+                event_type: "landing_queued",
+                causation_id: None,
+                payload: json!({"queue_wait_ms": queue_wait_ms, "pre_land": pre_land}),
+                diagnostics: None,
+            },
+        )
+        .await
+    }
+
+    /// Appends the transition decision for a completed stage, with the
+    /// result and attempt it was decided from, to `transition_events`.
+    ///
+    /// # Errors
+    /// Returns an error if the event cannot be stored.
+    pub(super) async fn record_transition_event(
+        &self,
+        input: TransitionEventInput<'_>,
+    ) -> Result<()> {
+        let transition = input.decision.transition();
+        let next_stage = match transition {
+            crate::RuntimeStageTransition::Advance(next_stage) => Some(next_stage.as_str()),
+            crate::RuntimeStageTransition::Retry => Some(Stage::Implement.as_str()),
+            _ => None,
+        };
+        sqlx::query!(
+            "INSERT INTO transition_events (
+                repo_id, bead_id, agent_id, stage, result, attempt,
+                transition, next_stage, reason, message, stage_history_id
+             )
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            input.agent_id.repo_id().value(),
+            input.bead_id.value(),
+            input.agent_id.number().cast_signed(),
+            input.stage.as_str(),
+            input.result.as_str(),
+            input.attempt.cast_signed(),
+            runtime_transition_name(transition),
+            next_stage,
+            input.decision.reason_code(),
+            input.result.message(),
+            input.stage_history_id,
+        )
+        .execute(self.pool())
+        .await
+        .map(|_| ())
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to record transition event: {e}")))
+    }
+
     #[allow(clippy::too_many_lines)]
     pub(super) async fn apply_stage_transition(
         &self,
@@ -99,14 +236,14 @@ impl SwarmDb {
                     SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}"))
                 })?;
 
-                sqlx::query(
+                sqlx::query!(
                     "UPDATE agent_state
                      SET status = 'waiting', feedback = $3, implementation_attempt = implementation_attempt + 1, current_stage = 'implement'
-                     WHERE repo_id = $1 AND agent_id = $2",
+                     WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL",
+                    input.agent_id.repo_id().value(),
+                    input.agent_id.number().cast_signed(),
+                    input.message,
                 )
-                .bind(input.agent_id.repo_id().value())
-                .bind(input.agent_id.number().cast_signed())
-                .bind(input.message)
                 .execute(&mut *conn)
                 .await
                 .map_err(|e| SwarmError::DatabaseError(format!("Failed to record failed stage: {e}")))?;
@@ -115,6 +252,12 @@ impl SwarmDb {
                     .await
                     .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
 
+                let mut diagnostics = build_failure_diagnostics(input.message);
+                if let Some(stage_history_id) = input.stage_history_id {
+                    diagnostics.failing_tests =
+                        self.get_failing_test_names(stage_history_id).await?;
+                }
+
                 self.record_execution_event(
                     input.bead_id,
                     input.agent_id,
@@ -125,7 +268,7 @@ impl SwarmDb {
                             .stage_history_id
                             .map(|id| format!("stage-history:{id}")),
                         payload: json!({"transition": "retry", "next_stage": Stage::Implement.as_str()}),
-                        diagnostics: Some(build_failure_diagnostics(input.message)),
+                        diagnostics: Some(diagnostics),
                     },
                 )
                 .await
@@ -150,6 +293,8 @@ impl SwarmDb {
     }
 
     async fn finalize_agent_and_bead(&self, agent_id: &AgentId, bead_id: &BeadId) -> Result<()> {
+        let active = BeadLifecycle::<bead_states::InProgress>::observed();
+        let completed = active.complete();
         let mut tx = self
             .pool()
             .begin()
@@ -161,32 +306,35 @@ impl SwarmDb {
             .await
             .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
 
-        let claim_update = sqlx::query(
+        let claim_update = sqlx::query!(
             "UPDATE bead_claims
-             SET status = 'completed'
+             SET status = $4
              WHERE repo_id = $1
                AND bead_id = $2
                AND claimed_by = $3
-               AND status = 'in_progress'",
+               AND status = $5
+               AND deleted_at IS NULL",
+            agent_id.repo_id().value(),
+            bead_id.value(),
+            agent_id.number().cast_signed(),
+            completed.claim_status(),
+            active.claim_status(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(bead_id.value())
-        .bind(agent_id.number().cast_signed())
         .execute(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to finalize bead: {e}")))?;
 
         if claim_update.rows_affected() != 1 {
-            let existing_status = sqlx::query_scalar::<_, String>(
+            let existing_status = sqlx::query_scalar!(
                 "SELECT status
                  FROM bead_claims
-                 WHERE repo_id = $1 AND bead_id = $2 AND claimed_by = $3
+                 WHERE repo_id = $1 AND bead_id = $2 AND claimed_by = $3 AND deleted_at IS NULL
                  ORDER BY claimed_at DESC
                  LIMIT 1",
+                agent_id.repo_id().value(),
+                bead_id.value(),
+                agent_id.number().cast_signed(),
             )
-            .bind(agent_id.repo_id().value())
-            .bind(bead_id.value())
-            .bind(agent_id.number().cast_signed())
             .fetch_optional(&mut *conn)
             .await
             .map_err(|e| {
@@ -195,7 +343,7 @@ impl SwarmDb {
                 ))
             })?;
 
-            if existing_status.as_deref() == Some("completed") {
+            if existing_status.as_deref() == Some(completed.claim_status()) {
                 tx.commit()
                     .await
                     .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
@@ -209,32 +357,34 @@ impl SwarmDb {
             )));
         }
 
-        sqlx::query(
+        sqlx::query!(
             "UPDATE agent_state
              SET status = 'done', current_stage = 'done'
-             WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3",
+             WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3 AND deleted_at IS NULL",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            bead_id.value(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(agent_id.number().cast_signed())
-        .bind(bead_id.value())
         .execute(&mut *conn)
         .await
         .map_err(|e| SwarmError::DatabaseError(format!("Failed to finalize agent: {e}")))?;
 
+        clear_bead_kv(conn, agent_id.repo_id().value(), bead_id.value()).await?;
+
         tx.commit()
             .await
             .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))
     }
 
     async fn advance_to_stage(&self, agent_id: &AgentId, next_stage: Stage) -> Result<()> {
-        sqlx::query(
+        sqlx::query!(
             "UPDATE agent_state
              SET current_stage = $3, stage_started_at = NOW(), status = 'working'
-             WHERE repo_id = $1 AND agent_id = $2",
+             WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            next_stage.as_str(),
         )
-        .bind(agent_id.repo_id().value())
-        .bind(agent_id.number().cast_signed())
-        .bind(next_stage.as_str())
         .execute(self.pool())
         .await
         .map(|_result| ())
@@ -0,0 +1,70 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{RepoId, SymbolObservation, TypeSignature};
+use sqlx::Acquire;
+
+impl SwarmDb {
+    /// Stores every observation in one transaction. Re-recording a symbol for
+    /// the same bead attempt replaces its signature.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn record_symbols(
+        &self,
+        repo_id: &RepoId,
+        agent_id: Option<u32>,
+        observations: &[SymbolObservation],
+    ) -> Result<u64> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let mut recorded = 0_u64;
+        for observation in observations {
+            let signature = TypeSignature::new(observation.signature.clone());
+            let result = sqlx::query!(
+                "INSERT INTO symbols
+                    (repo_id, bead_id, attempt, agent_id, name, kind, module_path, signature, signature_hash)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 ON CONFLICT (repo_id, bead_id, attempt, module_path, name) DO UPDATE
+                 SET agent_id = EXCLUDED.agent_id,
+                     kind = EXCLUDED.kind,
+                     signature = EXCLUDED.signature,
+                     signature_hash = EXCLUDED.signature_hash,
+                     recorded_at = NOW()",
+                repo_id.value(),
+                &observation.bead_id,
+                observation.attempt.cast_signed(),
+                agent_id.map(u32::cast_signed),
+                &observation.name,
+                observation.kind.as_str(),
+                &observation.module_path,
+                &signature.signature,
+                &signature.hash,
+            )
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to record symbol: {e}")))?;
+            recorded += result.rows_affected();
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit symbols: {e}")))?;
+        Ok(recorded)
+    }
+}
@@ -0,0 +1,291 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use super::helpers::event_entity_id;
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{BeadId, ClaimTransfer, EventSchemaVersion, MessageType, RepoId, TakeoverBasis};
+use serde_json::{json, Value};
+use sqlx::Acquire;
+
+impl SwarmDb {
+    /// Records on `owner`'s in-progress claim on `bead_id` that it consents
+    /// to a `takeover` while its lease is live.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::AgentError` if `owner` does not hold the bead,
+    /// or an error if the database operation fails.
+    pub async fn consent_to_takeover(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+        owner: u32,
+    ) -> Result<()> {
+        let consented = sqlx::query!(
+            "UPDATE bead_claims
+             SET takeover_consented_at = NOW()
+             WHERE repo_id = $1 AND bead_id = $2 AND claimed_by = $3 AND status = 'in_progress'
+               AND deleted_at IS NULL",
+            repo_id.value(),
+            bead_id.value(),
+            owner.cast_signed(),
+        )
+        .execute(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to record takeover consent: {e}")))?
+        .rows_affected();
+        if consented == 0 {
+            return Err(SwarmError::AgentError(format!(
+                "Agent {owner} does not hold an in-progress claim on {}",
+                bead_id.value()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Moves the in-progress claim on `bead_id` to `to_agent`, which picks
+    /// up at the owner's stage and attempt. The owner must have let its
+    /// lease lapse, or have recorded its consent on the claim with
+    /// [`Self::consent_to_takeover`]. `from_agent`, when given, must be the
+    /// owner the claim names. The owner is freed and `to_agent` gets a
+    /// handoff message carrying `resume_context`.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::BeadError` if nobody holds the bead, or
+    /// `SwarmError::AgentError` if the owner is not `from_agent`, neither is
+    /// stale nor consented, or `to_agent` is unregistered, quarantined,
+    /// already working, or the owner; otherwise an error if a database
+    /// operation fails.
+    #[allow(clippy::too_many_lines)]
+    pub async fn transfer_claim(
+        &self,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+        to_agent: u32,
+        from_agent: Option<u32>,
+        resume_context: Option<Value>,
+    ) -> Result<ClaimTransfer> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+
+        let conn = tx
+            .acquire()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to acquire tx conn: {e}")))?;
+
+        let claim = sqlx::query!(
+            r#"SELECT claimed_by,
+                    lease_expires_at + swarm_heartbeat_grace() <= NOW() AS "stale!",
+                    takeover_consented_at IS NOT NULL AS "consented!"
+             FROM bead_claims
+             WHERE repo_id = $1 AND bead_id = $2 AND status = 'in_progress' AND deleted_at IS NULL
+             FOR UPDATE"#,
+            repo_id.value(),
+            bead_id.value(),
+        )
+        .map(|row| (row.claimed_by, row.stale, row.consented))
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to read bead claim: {e}")))?;
+
+        let Some((owner, stale, consented)) = claim else {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Err(SwarmError::BeadError(format!(
+                "Bead {} has no in-progress claim",
+                bead_id.value()
+            )));
+        };
+        let owner = owner.cast_unsigned();
+
+        let basis = match from_agent {
+            Some(from) if from != owner => Err(format!(
+                "Bead {} is held by agent {owner}, not agent {from}",
+                bead_id.value()
+            )),
+            _ if consented => Ok(TakeoverBasis::Consent),
+            _ if stale => Ok(TakeoverBasis::Stale),
+            _ => Err(format!(
+                "Agent {owner} still holds a live lease on {}; wait for it to lapse or have agent {owner} consent with takeover --consent",
+                bead_id.value()
+            )),
+        }
+        .and_then(|basis| {
+            if owner == to_agent {
+                Err(format!("Agent {to_agent} already holds {}", bead_id.value()))
+            } else {
+                Ok(basis)
+            }
+        });
+        let basis = match basis {
+            Ok(basis) => basis,
+            Err(refusal) => {
+                tx.rollback().await.map_err(|e| {
+                    SwarmError::DatabaseError(format!("Failed to rollback tx: {e}"))
+                })?;
+                return Err(SwarmError::AgentError(refusal));
+            }
+        };
+
+        let target = sqlx::query!(
+            r#"SELECT a.bead_id, q.reason AS "reason?"
+             FROM agent_state a
+             LEFT JOIN agent_quarantine q
+               ON q.repo_id = a.repo_id AND q.agent_id = a.agent_id AND q.released_at IS NULL
+             WHERE a.repo_id = $1 AND a.agent_id = $2 AND a.deleted_at IS NULL
+             FOR UPDATE OF a"#,
+            repo_id.value(),
+            to_agent.cast_signed(),
+        )
+        .map(|row| (row.bead_id, row.reason))
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to read agent state: {e}")))?;
+
+        let refusal = match target {
+            None => Some(format!("Agent {to_agent} is not registered")),
+            Some((_, Some(reason))) => Some(format!("Agent {to_agent} is quarantined: {reason}")),
+            Some((Some(current), None)) => {
+                Some(format!("Agent {to_agent} is working bead {current}"))
+            }
+            Some((None, None)) => None,
+        };
+        if let Some(refusal) = refusal {
+            tx.rollback()
+                .await
+                .map_err(|e| SwarmError::DatabaseError(format!("Failed to rollback tx: {e}")))?;
+            return Err(SwarmError::AgentError(refusal));
+        }
+
+        let progress = sqlx::query!(
+            "SELECT current_stage, implementation_attempt
+             FROM agent_state
+             WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3 AND deleted_at IS NULL
+             FOR UPDATE",
+            repo_id.value(),
+            owner.cast_signed(),
+            bead_id.value(),
+        )
+        .map(|row| (row.current_stage, row.implementation_attempt))
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to read owner state: {e}")))?;
+        let (owner_stage, owner_attempt) = progress.unwrap_or((None, 0));
+
+        // The owner lets go first: agent_state rows must match the claim
+        // they point at, so the claim cannot move while the owner still
+        // holds it.
+        sqlx::query!(
+            "UPDATE agent_state
+             SET bead_id = NULL,
+                 current_stage = NULL,
+                 stage_started_at = NULL,
+                 status = 'idle',
+                 feedback = NULL,
+                 implementation_attempt = 0
+             WHERE repo_id = $1 AND agent_id = $2 AND bead_id = $3",
+            repo_id.value(),
+            owner.cast_signed(),
+            bead_id.value(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to free previous owner: {e}")))?;
+
+        sqlx::query!(
+            "UPDATE bead_claims
+             SET claimed_by = $3,
+                 takeover_consented_at = NULL,
+                 heartbeat_at = NOW(),
+                 lease_expires_at = NOW() + swarm_lease_ttl()
+             WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL",
+            repo_id.value(),
+            bead_id.value(),
+            to_agent.cast_signed(),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to transfer claim: {e}")))?;
+
+        sqlx::query!(
+            "UPDATE agent_state
+             SET bead_id = $3,
+                 current_stage = COALESCE($4, 'rust-contract'),
+                 stage_started_at = NOW(),
+                 status = 'working',
+                 implementation_attempt = $5,
+                 last_update = NOW()
+             WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL",
+            repo_id.value(),
+            to_agent.cast_signed(),
+            bead_id.value(),
+            owner_stage.as_deref(),
+            owner_attempt,
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to update agent state: {e}")))?;
+
+        let message_id = sqlx::query_scalar!(
+            r#"SELECT send_agent_message($1, $2, $1, $3, $4, $5, $6, $7, $8) AS "id!""#,
+            repo_id.value(),
+            owner.cast_signed(),
+            to_agent.cast_signed(),
+            bead_id.value(),
+            MessageType::Coordination.as_str(),
+            format!("Handoff of {}", bead_id.value()),
+            format!(
+                "Agent {to_agent} takes over {} from agent {owner} at {}",
+                bead_id.value(),
+                owner_stage.as_deref().unwrap_or("rust-contract")
+            ),
+            json!({
+                "handoff": true,
+                "from_agent": owner,
+                "basis": basis,
+                "resume_context": resume_context,
+            }),
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to send handoff message: {e}")))?;
+
+        sqlx::query!(
+            "INSERT INTO execution_events (schema_version, event_type, entity_id, bead_id, agent_id, payload)
+             VALUES ($1, 'claim_transferred', $2, $3, $4, $5)",
+            EventSchemaVersion::LATEST.as_i32(),
+            event_entity_id(bead_id, repo_id),
+            bead_id.value(),
+            to_agent.cast_signed(),
+            json!({
+                "from_agent": owner,
+                "to_agent": to_agent,
+                "basis": basis,
+                "message_id": message_id,
+            }),
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to write transfer event: {e}")))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+
+        Ok(ClaimTransfer {
+            bead_id: bead_id.clone(),
+            from_agent: owner,
+            to_agent,
+            basis,
+            message_id,
+        })
+    }
+}
@@ -0,0 +1,71 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::swarm_db::{to_tenant, TenantRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::Tenant;
+
+impl SwarmDb {
+    /// Registers a tenant whose schema is already initialized. Returns `None`
+    /// when the name is taken.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn register_tenant(&self, name: &str, schema: &str) -> Result<Option<Tenant>> {
+        sqlx::query!(
+            "INSERT INTO public.tenants (name, schema_name) VALUES ($1, $2)
+             ON CONFLICT (name) DO NOTHING
+             RETURNING name, schema_name, created_at",
+            name,
+            schema,
+        )
+        .map(|row| -> TenantRow { (row.name, row.schema_name, row.created_at) })
+        .fetch_optional(self.pool())
+        .await
+        .map(|row| row.map(to_tenant))
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to register tenant {name}: {e}")))
+    }
+
+    /// Unregisters a tenant and drops its schema with everything in it, in
+    /// one transaction. Returns `None` when no such tenant is registered.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn delete_tenant(&self, name: &str) -> Result<Option<Tenant>> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to begin tx: {e}")))?;
+        let Some(tenant) = sqlx::query!(
+            "DELETE FROM public.tenants WHERE name = $1
+             RETURNING name, schema_name, created_at",
+            name,
+        )
+        .map(|row| -> TenantRow { (row.name, row.schema_name, row.created_at) })
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to unregister tenant {name}: {e}")))?
+        .map(to_tenant) else {
+            return Ok(None);
+        };
+        sqlx::query(&format!(
+            "DROP SCHEMA IF EXISTS \"{}\" CASCADE",
+            tenant.schema
+        ))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            SwarmError::DatabaseError(format!("Failed to drop schema {}: {e}", tenant.schema))
+        })?;
+        tx.commit()
+            .await
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to commit tx: {e}")))?;
+        Ok(Some(tenant))
+    }
+}
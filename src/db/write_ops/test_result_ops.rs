@@ -0,0 +1,66 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::skill_execution_parsing::TestResults;
+use crate::types::{BeadId, RepoId, Stage};
+
+impl SwarmDb {
+    /// Stores one `stage_test_results` row per test case a stage's report
+    /// named. Returns the number of rows written.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn record_stage_test_results(
+        &self,
+        stage_history_id: i64,
+        repo_id: &RepoId,
+        bead_id: &BeadId,
+        stage: Stage,
+        results: &TestResults,
+    ) -> Result<u64> {
+        if results.cases.is_empty() {
+            return Ok(0);
+        }
+
+        let mut builder = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            "INSERT INTO stage_test_results (
+                stage_history_id,
+                repo_id,
+                bead_id,
+                stage,
+                name,
+                status,
+                file,
+                line,
+                reason
+            ) ",
+        );
+        builder.push_values(&results.cases, |mut values, case| {
+            values
+                .push_bind(stage_history_id)
+                .push_bind(repo_id.value())
+                .push_bind(bead_id.value())
+                .push_bind(stage.as_str())
+                .push_bind(case.name.as_str())
+                .push_bind(case.status.as_str())
+                .push_bind(case.file.as_deref())
+                .push_bind(
+                    case.line
+                        .map(|line| i32::try_from(line).unwrap_or(i32::MAX)),
+                )
+                .push_bind(case.reason.as_deref());
+        });
+        builder
+            .build()
+            .execute(self.pool())
+            .await
+            .map(|result| result.rows_affected())
+            .map_err(|e| SwarmError::DatabaseError(format!("Failed to record test results: {e}")))
+    }
+}
@@ -25,6 +25,7 @@ pub struct FailureDiagnosticsPayload {
     pub retryable: bool,
     pub next_command: String,
     pub detail: Option<String>,
+    pub failing_tests: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -36,6 +37,42 @@ pub struct ExecutionEventWriteInput {
     pub diagnostics: Option<FailureDiagnosticsPayload>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandAuditRow {
+    pub cmd: String,
+    pub rid: Option<String>,
+    pub args: serde_json::Value,
+    pub ok: bool,
+    pub ms: u64,
+    pub error_code: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionEventRow {
+    pub event_type: String,
+    pub entity_id: String,
+    pub bead_id: String,
+    pub agent_id: i32,
+    pub stage: Option<String>,
+    pub causation_id: Option<String>,
+    pub diagnostics_category: Option<String>,
+    pub diagnostics_retryable: Option<bool>,
+    pub diagnostics_next_command: Option<String>,
+    pub diagnostics_detail: Option<String>,
+    pub diagnostics_failing_tests: Option<Vec<String>>,
+    pub payload: serde_json::Value,
+}
+
+pub struct TransitionEventInput<'a> {
+    pub agent_id: &'a crate::types::AgentId,
+    pub bead_id: &'a crate::types::BeadId,
+    pub stage: Stage,
+    pub result: &'a crate::types::StageResult,
+    pub attempt: u32,
+    pub decision: &'a crate::runtime::RuntimeTransitionDecision,
+    pub stage_history_id: Option<i64>,
+}
+
 pub struct StageTransitionInput<'a> {
     pub transition: &'a StageTransition,
     pub agent_id: &'a crate::types::AgentId,
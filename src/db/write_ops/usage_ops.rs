@@ -0,0 +1,48 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentId, BeadId, Stage, TokenUsage};
+
+impl SwarmDb {
+    /// Records tokens an agent spent, attributed to the bead, stage and model
+    /// `swarm costs` prices and groups them by. Returns the row id.
+    ///
+    /// # Errors
+    /// Returns an error if a count does not fit the column or the database
+    /// operation fails.
+    pub async fn record_token_usage(
+        &self,
+        agent_id: &AgentId,
+        bead_id: Option<&BeadId>,
+        stage: Option<Stage>,
+        model: Option<&str>,
+        usage: TokenUsage,
+    ) -> Result<i64> {
+        let tokens = |count: u64| {
+            i64::try_from(count).map_err(|_| {
+                SwarmError::DatabaseError(format!("Token count {count} exceeds BIGINT"))
+            })
+        };
+        sqlx::query_scalar!(
+            "INSERT INTO token_usage (repo_id, agent_id, bead_id, stage, model, input_tokens, output_tokens)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id",
+            agent_id.repo_id().value(),
+            agent_id.number().cast_signed(),
+            bead_id.map(BeadId::value),
+            stage.map(|stage| stage.as_str()),
+            model,
+            tokens(usage.input_tokens)?,
+            tokens(usage.output_tokens)?,
+        )
+        .fetch_one(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to record token usage: {e}")))
+    }
+}
@@ -0,0 +1,93 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![allow(clippy::panic, clippy::missing_panics_doc)]
+#![forbid(unsafe_code)]
+
+use crate::db::swarm_db::{to_agent_workspace, WorkspaceRow};
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentWorkspace, RepoId, WorkspaceBackend};
+
+impl SwarmDb {
+    /// Records a provisioned checkout for `agent_id`. Returns `None` when the
+    /// agent already owns a workspace or another workspace uses `path`.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn record_workspace(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+        bead_id: &str,
+        name: &str,
+        path: &str,
+        backend: WorkspaceBackend,
+    ) -> Result<Option<AgentWorkspace>> {
+        sqlx::query!(
+            "INSERT INTO workspaces (repo_id, agent_id, bead_id, name, path, backend)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             ON CONFLICT DO NOTHING
+             RETURNING agent_id, bead_id, name, path, backend, created_at, removed_at",
+            repo_id.value(),
+            agent_id.cast_signed(),
+            bead_id,
+            name,
+            path,
+            backend.as_str(),
+        )
+        .map(|row| -> WorkspaceRow {
+            (
+                row.agent_id,
+                row.bead_id,
+                row.name,
+                row.path,
+                row.backend,
+                row.created_at,
+                row.removed_at,
+            )
+        })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to record workspace: {e}")))?
+        .map(to_agent_workspace)
+        .transpose()
+    }
+
+    /// Marks the agent's workspace removed, keeping the row as history.
+    /// Returns `None` when the agent had no workspace.
+    ///
+    /// # Errors
+    /// Returns an error if the database operation fails.
+    pub async fn mark_workspace_removed(
+        &self,
+        repo_id: &RepoId,
+        agent_id: u32,
+    ) -> Result<Option<AgentWorkspace>> {
+        sqlx::query!(
+            "UPDATE workspaces
+             SET removed_at = NOW()
+             WHERE repo_id = $1 AND agent_id = $2 AND removed_at IS NULL
+             RETURNING agent_id, bead_id, name, path, backend, created_at, removed_at",
+            repo_id.value(),
+            agent_id.cast_signed(),
+        )
+        .map(|row| -> WorkspaceRow {
+            (
+                row.agent_id,
+                row.bead_id,
+                row.name,
+                row.path,
+                row.backend,
+                row.created_at,
+                row.removed_at,
+            )
+        })
+        .fetch_optional(self.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to remove workspace: {e}")))?
+        .map(to_agent_workspace)
+        .transpose()
+    }
+}
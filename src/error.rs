@@ -14,6 +14,7 @@ pub mod code {
     pub const NOTFOUND: &str = "NOTFOUND";
     pub const INVALID: &str = "INVALID";
     pub const CONFLICT: &str = "CONFLICT";
+    pub const GATE_FAILED: &str = "GATE_FAILED";
     pub const BUSY: &str = "BUSY";
     pub const UNAUTHORIZED: &str = "UNAUTHORIZED";
     pub const DEPENDENCY: &str = "DEPENDENCY";
@@ -49,6 +50,11 @@ pub enum SwarmError {
 
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// A stored value outside the enum the code reads it as, e.g. a status
+    /// written by a newer or hand-edited schema. `swarm doctor` lists them.
+    #[error("Schema drift: {0}")]
+    SchemaDrift(String),
 }
 
 impl SwarmError {
@@ -57,7 +63,10 @@ impl SwarmError {
     pub const fn code(&self) -> &'static str {
         match self {
             Self::ConfigError(_) | Self::SerializationError(_) => code::INVALID,
-            Self::DatabaseError(_) | Self::SqlxError(_) | Self::Internal(_) => code::INTERNAL,
+            Self::DatabaseError(_)
+            | Self::SqlxError(_)
+            | Self::Internal(_)
+            | Self::SchemaDrift(_) => code::INTERNAL,
             Self::AgentError(_) | Self::StageError(_) => code::CONFLICT,
             Self::BeadError(_) => code::NOTFOUND,
             Self::IoError(_) => code::DEPENDENCY,
@@ -69,7 +78,7 @@ impl SwarmError {
     pub const fn exit_code(&self) -> i32 {
         match self {
             Self::ConfigError(_) => 2,
-            Self::DatabaseError(_) | Self::SqlxError(_) => 3,
+            Self::DatabaseError(_) | Self::SqlxError(_) | Self::SchemaDrift(_) => 3,
             Self::AgentError(_) => 4,
             Self::BeadError(_) => 5,
             Self::StageError(_) => 6,
@@ -107,6 +116,11 @@ pub const ERROR_CODES: &[(&str, &str, &str)] = &[
         "Conflicting state transition",
         "Run swarm state to inspect current status",
     ),
+    (
+        code::GATE_FAILED,
+        "Landing gate policy rejected the bead",
+        "Fix each rule listed in ctx.violations and re-run land",
+    ),
     (
         code::BUSY,
         "Resource is temporarily locked",
@@ -0,0 +1,193 @@
+//! Evaluating escalation rules against the beads agents hold.
+//!
+//! New escalations go to the execution event log, an announcement to every
+//! agent, the webhook and the routed notifiers, and can hand the bead to a
+//! senior agent.
+
+use crate::alerts::notify_webhook;
+use crate::notifications::deliver;
+use crate::types::{
+    Escalation, EscalationRules, EscalationTrigger, Notification, NotificationRules,
+    NotificationSeverity, DEFAULT_ANNOUNCEMENT_TTL_SECS,
+};
+use crate::{BeadId, RepoId, Result, SwarmDb, SwarmError};
+use serde::Serialize;
+
+/// Announcement topic new escalations are posted on.
+const ESCALATION_TOPIC: &str = "escalation";
+
+/// Outcome of one pass over the rules.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EscalationEvaluation {
+    /// Open escalations after this pass.
+    pub open: Vec<Escalation>,
+    pub raised: Vec<Escalation>,
+    pub resolved: Vec<Escalation>,
+    /// Beads no senior agent could take; they stay with their agent.
+    pub unassigned: Vec<String>,
+    /// Deliveries that failed; they do not fail the evaluation.
+    pub webhook_errors: Vec<String>,
+    pub notification_errors: Vec<String>,
+}
+
+/// Opens an escalation for every rule that newly holds for a bead, and
+/// resolves the open ones whose rule no longer holds.
+///
+/// # Errors
+/// Returns an error if reading agent state or writing escalations fails.
+pub async fn evaluate_escalations(
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    rules: &EscalationRules,
+    notifications: &NotificationRules,
+) -> Result<EscalationEvaluation> {
+    let now = chrono::Utc::now();
+    let triggers = db
+        .get_escalation_candidates(repo_id)
+        .await?
+        .iter()
+        .flat_map(|candidate| rules.triggers(candidate, now))
+        .collect::<Vec<_>>();
+    let open = db.get_open_escalations(repo_id).await?;
+
+    let mut evaluation = EscalationEvaluation::default();
+    for trigger in &triggers {
+        if let Some(raised) = db.raise_escalation(repo_id, trigger).await? {
+            let raised =
+                assign_senior(db, repo_id, rules, trigger, raised, &mut evaluation).await?;
+            announce(
+                db,
+                repo_id,
+                rules,
+                notifications,
+                "bead_escalated",
+                &raised,
+                &mut evaluation,
+            )
+            .await?;
+            evaluation.raised.push(raised);
+        }
+    }
+    for escalation in open.iter().filter(|escalation| {
+        !triggers.iter().any(|trigger| {
+            trigger.bead_id == escalation.bead_id && trigger.reason == escalation.reason
+        })
+    }) {
+        if let Some(resolved) = db
+            .resolve_escalation(repo_id, &escalation.bead_id, escalation.reason)
+            .await?
+        {
+            announce(
+                db,
+                repo_id,
+                rules,
+                notifications,
+                "escalation_resolved",
+                &resolved,
+                &mut evaluation,
+            )
+            .await?;
+            evaluation.resolved.push(resolved);
+        }
+    }
+
+    evaluation.open = db.get_open_escalations(repo_id).await?;
+    Ok(evaluation)
+}
+
+/// Hands the bead to the first senior agent that can take it, with its
+/// agent's consent on the swarm's behalf. Agents that are busy, quarantined
+/// or unregistered are skipped.
+async fn assign_senior(
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    rules: &EscalationRules,
+    trigger: &EscalationTrigger,
+    escalation: Escalation,
+    evaluation: &mut EscalationEvaluation,
+) -> Result<Escalation> {
+    if rules.senior_agents.is_empty() || rules.senior_agents.contains(&trigger.agent_id) {
+        return Ok(escalation);
+    }
+    let bead_id = BeadId::new(trigger.bead_id.as_str());
+    for &senior in &rules.senior_agents {
+        match db
+            .transfer_claim(repo_id, &bead_id, senior, Some(trigger.agent_id), None)
+            .await
+        {
+            Ok(_) => {
+                return db
+                    .set_escalation_assignee(repo_id, escalation.id, senior)
+                    .await
+                    .map(|assigned| assigned.unwrap_or(escalation));
+            }
+            Err(SwarmError::AgentError(_) | SwarmError::BeadError(_)) => {}
+            Err(error) => return Err(error),
+        }
+    }
+    evaluation.unassigned.push(trigger.bead_id.clone());
+    Ok(escalation)
+}
+
+async fn announce(
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    rules: &EscalationRules,
+    notifications: &NotificationRules,
+    event_type: &str,
+    escalation: &Escalation,
+    evaluation: &mut EscalationEvaluation,
+) -> Result<()> {
+    db.record_escalation_event(repo_id, event_type, escalation)
+        .await?;
+    let notification = Notification {
+        event: event_type.to_string(),
+        severity: if event_type == "bead_escalated" {
+            NotificationSeverity::Warning
+        } else {
+            NotificationSeverity::Info
+        },
+        repo_id: repo_id.value().to_string(),
+        text: format!(
+            "Bead {} {} ({}): {}",
+            escalation.bead_id,
+            if event_type == "bead_escalated" {
+                "escalated"
+            } else {
+                "no longer escalated"
+            },
+            escalation.reason.as_str(),
+            escalation.detail
+        ),
+        details: serde_json::to_value(escalation)?,
+    };
+    evaluation
+        .notification_errors
+        .extend(deliver(notifications, &notification).await.errors());
+    if rules.broadcast && event_type == "bead_escalated" {
+        db.create_announcement(
+            repo_id,
+            "swarm",
+            ESCALATION_TOPIC,
+            &format!(
+                "Bead {} escalated ({}): {}",
+                escalation.bead_id,
+                escalation.reason.as_str(),
+                escalation.detail
+            ),
+            DEFAULT_ANNOUNCEMENT_TTL_SECS,
+        )
+        .await?;
+    }
+    if let Some(url) = rules.webhook_url.as_deref() {
+        let payload = serde_json::json!({
+            "event": event_type,
+            "repo_id": repo_id.value(),
+            "escalation": escalation,
+        });
+        if let Err(error) = notify_webhook(url, &payload).await {
+            evaluation.webhook_errors.push(error.to_string());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,239 @@
+//! Index advisor behind `doctor --analyze-db`.
+//!
+//! The hot coordinator queries (claiming, progress, event reads) are planned
+//! with `EXPLAIN` against the live database, and a sequential scan of a
+//! large table is answered with the index from [`HOT_QUERY_PROBES`] that the
+//! query was written against.
+//!
+//! Only indexes from the curated list are ever recommended or applied, so
+//! `--apply` can never build something the schema does not already declare.
+//! A probe whose index exists but is still scanned points at stale planner
+//! statistics rather than a missing index.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Tables with fewer estimated rows than this are fine to scan.
+pub const LARGE_TABLE_ROWS: i64 = 10_000;
+
+/// One hot query, the table it should not scan, and the index that keeps
+/// it off that scan.
+///
+/// `$1` binds the repo id; probes keyed by a bead bind it as a stand-in bead
+/// id, which plans like any bead the statistics do not single out.
+#[derive(Debug, Clone, Copy)]
+pub struct QueryProbe {
+    pub name: &'static str,
+    pub table: &'static str,
+    pub sql: &'static str,
+    pub index: &'static str,
+    /// `CREATE INDEX` body after the name, as declared in `schema.sql`.
+    pub definition: &'static str,
+}
+
+pub const HOT_QUERY_PROBES: &[QueryProbe] = &[
+    QueryProbe {
+        name: "claim_pending",
+        table: "bead_backlog",
+        sql: "SELECT bead_id FROM bead_backlog b
+              WHERE repo_id = $1 AND status = 'pending' AND deleted_at IS NULL
+                AND NOT EXISTS (
+                    SELECT 1 FROM bead_reservations r
+                    WHERE r.repo_id = b.repo_id AND r.bead_id = b.bead_id
+                      AND r.expires_at > NOW()
+                )
+              ORDER BY
+                  COALESCE(array_position(ARRAY['p0', 'p1', 'p2', 'p3']::TEXT[], lower(priority)), 999),
+                  created_at ASC
+              FOR UPDATE SKIP LOCKED
+              LIMIT 1",
+        index: "idx_bead_backlog_repo_claim",
+        definition: "ON bead_backlog(repo_id, status, priority, created_at)",
+    },
+    QueryProbe {
+        name: "claim_held",
+        table: "bead_claims",
+        sql: "SELECT bead_id FROM bead_claims
+              WHERE repo_id = $1 AND status = 'in_progress' AND deleted_at IS NULL
+              ORDER BY claimed_at ASC",
+        index: "idx_bead_claims_repo_status",
+        definition: "ON bead_claims(repo_id, status, claimed_at)",
+    },
+    QueryProbe {
+        name: "claim_expired",
+        table: "bead_claims",
+        sql: "SELECT bead_id FROM bead_claims
+              WHERE repo_id = $1 AND status = 'in_progress' AND deleted_at IS NULL
+                AND lease_expires_at <= NOW()",
+        index: "idx_bead_claims_repo_lease_expires",
+        definition: "ON bead_claims(repo_id, lease_expires_at) WHERE status = 'in_progress'",
+    },
+    QueryProbe {
+        name: "progress",
+        table: "agent_state",
+        sql: "SELECT
+                  COUNT(*) FILTER (WHERE status = 'working'),
+                  COUNT(*) FILTER (WHERE status = 'idle'),
+                  COUNT(*) FILTER (WHERE status = 'error')
+              FROM agent_state
+              WHERE repo_id = $1 AND deleted_at IS NULL",
+        index: "idx_agent_state_repo_status",
+        definition: "ON agent_state(repo_id, status, last_update DESC)",
+    },
+    QueryProbe {
+        name: "events",
+        table: "execution_events",
+        sql: "SELECT seq, event_type, payload FROM execution_events
+              WHERE bead_id = $1
+              ORDER BY seq DESC
+              LIMIT 50",
+        index: "idx_execution_events_bead_seq",
+        definition: "ON execution_events(bead_id, seq)",
+    },
+];
+
+/// Whether a curated index exists and can be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexState {
+    Present,
+    /// Left behind by a failed `CREATE INDEX CONCURRENTLY`; the planner
+    /// ignores it.
+    Invalid,
+    Missing,
+}
+
+/// What to do about a probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Remedy {
+    CreateIndex,
+    RebuildIndex,
+    Analyze,
+}
+
+impl Remedy {
+    /// The statement that applies the remedy for `probe`. Index builds run
+    /// concurrently so claims keep going while they do.
+    #[must_use]
+    pub fn statement(self, probe: &QueryProbe) -> String {
+        match self {
+            Self::CreateIndex => format!(
+                "CREATE INDEX CONCURRENTLY IF NOT EXISTS {} {}",
+                probe.index, probe.definition
+            ),
+            Self::RebuildIndex => format!("REINDEX INDEX CONCURRENTLY {}", probe.index),
+            Self::Analyze => format!("ANALYZE {}", probe.table),
+        }
+    }
+}
+
+/// Picks the remedy for a probe whose plan scans `seq_scanned` tables.
+/// Nothing is needed unless the probe's own table is scanned and holds at
+/// least [`LARGE_TABLE_ROWS`] rows.
+#[must_use]
+pub fn remedy(
+    probe: &QueryProbe,
+    seq_scanned: &[String],
+    table_rows: i64,
+    index: IndexState,
+) -> Option<Remedy> {
+    let scans_table = seq_scanned.iter().any(|table| table == probe.table);
+    if !scans_table || table_rows < LARGE_TABLE_ROWS {
+        return None;
+    }
+    Some(match index {
+        IndexState::Missing => Remedy::CreateIndex,
+        IndexState::Invalid => Remedy::RebuildIndex,
+        IndexState::Present => Remedy::Analyze,
+    })
+}
+
+/// Tables read by a `Seq Scan` node anywhere in an `EXPLAIN (FORMAT JSON)`
+/// plan, in plan order and without repeats.
+#[must_use]
+pub fn seq_scanned_tables(plan: &Value) -> Vec<String> {
+    let mut tables = Vec::new();
+    let mut pending: Vec<&Value> = match plan {
+        Value::Array(statements) => statements
+            .iter()
+            .filter_map(|statement| statement.get("Plan"))
+            .collect(),
+        other => other.get("Plan").into_iter().collect(),
+    };
+    pending.reverse();
+    while let Some(node) = pending.pop() {
+        if node.get("Node Type").and_then(Value::as_str) == Some("Seq Scan") {
+            if let Some(table) = node.get("Relation Name").and_then(Value::as_str) {
+                if !tables.iter().any(|seen| seen == table) {
+                    tables.push(table.to_string());
+                }
+            }
+        }
+        if let Some(children) = node.get("Plans").and_then(Value::as_array) {
+            pending.extend(children.iter().rev());
+        }
+    }
+    tables
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn probe(name: &str) -> &'static QueryProbe {
+        HOT_QUERY_PROBES
+            .iter()
+            .find(|probe| probe.name == name)
+            .expect("curated probe")
+    }
+
+    #[test]
+    fn given_nested_plan_when_collecting_seq_scans_then_every_scanned_table_is_listed_once() {
+        let plan = json!([{
+            "Plan": {
+                "Node Type": "Limit",
+                "Plans": [{
+                    "Node Type": "Nested Loop",
+                    "Plans": [
+                        {"Node Type": "Seq Scan", "Relation Name": "bead_backlog"},
+                        {"Node Type": "Index Scan", "Relation Name": "bead_reservations"},
+                        {"Node Type": "Seq Scan", "Relation Name": "bead_backlog"},
+                    ],
+                }],
+            },
+        }]);
+
+        assert_eq!(seq_scanned_tables(&plan), vec!["bead_backlog".to_string()]);
+        assert!(seq_scanned_tables(&json!([{"Plan": {"Node Type": "Result"}}])).is_empty());
+    }
+
+    #[test]
+    fn given_seq_scan_when_choosing_remedy_then_it_depends_on_size_and_index_state() {
+        let claim = probe("claim_pending");
+        let scanned = vec!["bead_backlog".to_string()];
+        let other = vec!["bead_reservations".to_string()];
+
+        assert_eq!(remedy(claim, &scanned, 9_999, IndexState::Missing), None);
+        assert_eq!(remedy(claim, &other, 1_000_000, IndexState::Missing), None);
+        assert_eq!(
+            remedy(claim, &scanned, 50_000, IndexState::Missing),
+            Some(Remedy::CreateIndex)
+        );
+        assert_eq!(
+            remedy(claim, &scanned, 50_000, IndexState::Invalid),
+            Some(Remedy::RebuildIndex)
+        );
+        assert_eq!(
+            remedy(claim, &scanned, 50_000, IndexState::Present),
+            Some(Remedy::Analyze)
+        );
+        assert_eq!(
+            Remedy::CreateIndex.statement(claim),
+            "CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_bead_backlog_repo_claim \
+             ON bead_backlog(repo_id, status, priority, created_at)"
+        );
+    }
+}
@@ -30,7 +30,9 @@ impl AgentRepository {
 
             sqlx::query(
                 "INSERT INTO agent_state (repo_id, agent_id, status) VALUES ($1, $2, 'idle')
-                 ON CONFLICT (repo_id, agent_id) DO NOTHING",
+                 ON CONFLICT (repo_id, agent_id) DO UPDATE
+                 SET deleted_at = NULL, deleted_by = NULL
+                 WHERE agent_state.deleted_at IS NOT NULL",
             )
             .bind(agent_id.repo_id().value())
             .bind(agent_id.number().cast_signed())
@@ -94,7 +94,7 @@ impl StageRepository {
         sqlx::query(
             "UPDATE agent_state
              SET current_stage = $3, stage_started_at = NOW(), status = 'working'
-             WHERE repo_id = $1 AND agent_id = $2",
+             WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL",
         )
         .bind(agent_id.repo_id().value())
         .bind(agent_id.number().cast_signed())
@@ -0,0 +1,326 @@
+//! Push verification for landing: before a bead is finalized, confirm its
+//! change actually reached the remote instead of trusting the caller.
+
+use crate::orchestrator_service::LandingOutcome;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::process::Command;
+
+/// Remote checked when the caller does not name one.
+pub const DEFAULT_LANDING_REMOTE: &str = "origin";
+
+/// Shortest change or commit id prefix accepted, so a stray short prefix
+/// cannot match an unrelated ref.
+pub const MIN_CHANGE_ID_LEN: usize = 7;
+
+/// A change or commit id is passed to `jj`/`git` as a revision, so only
+/// plain alphanumeric ids of at least [`MIN_CHANGE_ID_LEN`] are accepted.
+#[must_use]
+pub fn is_valid_change_id(change_id: &str) -> bool {
+    change_id.len() >= MIN_CHANGE_ID_LEN && change_id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// A remote name is passed to `git ls-remote` and quoted into a jj revset, so
+/// it may not start with `-` and only uses `[A-Za-z0-9._/-]`.
+#[must_use]
+pub fn is_valid_remote_name(remote: &str) -> bool {
+    !remote.is_empty()
+        && !remote.starts_with('-')
+        && remote
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '/' | '-'))
+}
+
+/// How a push was (or was not) confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushVerificationMethod {
+    /// `jj log` over the remote's bookmarks.
+    JjLog,
+    /// `git ls-remote` against the remote.
+    GitLsRemote,
+    /// An operator skipped the check.
+    Override,
+}
+
+impl PushVerificationMethod {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::JjLog => "jj_log",
+            Self::GitLsRemote => "git_ls_remote",
+            Self::Override => "override",
+        }
+    }
+}
+
+/// Result of checking the remote for a change, kept as the landing evidence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PushVerification {
+    pub change_id: String,
+    pub remote: String,
+    pub method: PushVerificationMethod,
+    pub confirmed: bool,
+    /// The command run and what it printed, or the override reason.
+    pub evidence: String,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl PushVerification {
+    /// Confirmation granted without checking the remote.
+    #[must_use]
+    pub fn overridden(change_id: &str, remote: &str, reason: &str) -> Self {
+        Self {
+            change_id: change_id.to_string(),
+            remote: remote.to_string(),
+            method: PushVerificationMethod::Override,
+            confirmed: true,
+            evidence: format!("push check overridden: {reason}"),
+            checked_at: Utc::now(),
+        }
+    }
+
+    #[must_use]
+    pub fn landing_outcome(&self) -> LandingOutcome {
+        LandingOutcome::new(self.confirmed, self.evidence.clone())
+    }
+}
+
+/// Checks `remote` for `change_id`.
+///
+/// Repositories with a `.jj` directory are checked with `jj log` against the
+/// remote's bookmarks, which accepts a jj change id or a commit id; anything
+/// else falls back to `git ls-remote`, which only sees commit ids at the tip
+/// of a remote ref. Failures to run either tool, and ids or remotes that are
+/// not safe to pass to them, come back as an unconfirmed verification with
+/// the error as evidence.
+pub async fn verify_remote_push(
+    repo_root: &Path,
+    change_id: &str,
+    remote: &str,
+) -> PushVerification {
+    let is_jj = repo_root.join(".jj").is_dir();
+    if !is_valid_change_id(change_id) || !is_valid_remote_name(remote) {
+        return PushVerification {
+            change_id: change_id.to_string(),
+            remote: remote.to_string(),
+            method: if is_jj {
+                PushVerificationMethod::JjLog
+            } else {
+                PushVerificationMethod::GitLsRemote
+            },
+            confirmed: false,
+            evidence: format!("refused to check invalid change id or remote: {remote}:{change_id}"),
+            checked_at: Utc::now(),
+        };
+    }
+    let (method, program, args) = if is_jj {
+        (
+            PushVerificationMethod::JjLog,
+            "jj",
+            vec![
+                "log".to_string(),
+                "--no-graph".to_string(),
+                "--ignore-working-copy".to_string(),
+                format!("--revisions={change_id} & ::remote_bookmarks(remote=exact:\"{remote}\")"),
+                "-T".to_string(),
+                "change_id ++ \" \" ++ commit_id ++ \"\\n\"".to_string(),
+            ],
+        )
+    } else {
+        (
+            PushVerificationMethod::GitLsRemote,
+            "git",
+            vec![
+                "ls-remote".to_string(),
+                "--".to_string(),
+                remote.to_string(),
+            ],
+        )
+    };
+    let command_line = format!("{program} {}", args.join(" "));
+
+    let (confirmed, evidence) = match Command::new(program)
+        .args(&args)
+        .current_dir(repo_root)
+        .output()
+        .await
+    {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let confirmed = match method {
+                PushVerificationMethod::JjLog => jj_log_confirms(&stdout, change_id),
+                PushVerificationMethod::GitLsRemote | PushVerificationMethod::Override => {
+                    ls_remote_confirms(&stdout, change_id)
+                }
+            };
+            (
+                confirmed,
+                format!("$ {command_line}\n{}", stdout.trim_end()),
+            )
+        }
+        Ok(output) => (
+            false,
+            format!(
+                "$ {command_line}\nexit {}: {}",
+                output
+                    .status
+                    .code()
+                    .map_or_else(|| "signal".to_string(), |code| code.to_string()),
+                String::from_utf8_lossy(&output.stderr).trim_end()
+            ),
+        ),
+        Err(err) => (false, format!("$ {command_line}\nfailed to run: {err}")),
+    };
+
+    PushVerification {
+        change_id: change_id.to_string(),
+        remote: remote.to_string(),
+        method,
+        confirmed,
+        evidence,
+        checked_at: Utc::now(),
+    }
+}
+
+/// Lines `change_id` adds plus deletes.
+///
+/// Measured with `jj diff --stat` in a jj repository and
+/// `git diff --shortstat` otherwise. `None` when the tool cannot be run or
+/// fails, or the id is not a valid change id, so a size gate can refuse what
+/// it cannot measure.
+pub async fn measure_change_diff(repo_root: &Path, change_id: &str) -> Option<u64> {
+    if !is_valid_change_id(change_id) {
+        return None;
+    }
+    let (program, args) = if repo_root.join(".jj").is_dir() {
+        (
+            "jj",
+            vec![
+                "diff".to_string(),
+                "--stat".to_string(),
+                "--ignore-working-copy".to_string(),
+                format!("--revisions={change_id}"),
+            ],
+        )
+    } else {
+        (
+            "git",
+            vec![
+                "diff".to_string(),
+                "--shortstat".to_string(),
+                "--end-of-options".to_string(),
+                format!("{change_id}^!"),
+                "--".to_string(),
+            ],
+        )
+    };
+    let output = Command::new(program)
+        .args(&args)
+        .current_dir(repo_root)
+        .output()
+        .await
+        .ok()
+        .filter(|output| output.status.success())?;
+    Some(diff_stat_changed_lines(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Sums insertions and deletions from the `N files changed, X insertions(+),
+/// Y deletions(-)` summary line; no summary line means an empty diff.
+#[must_use]
+pub fn diff_stat_changed_lines(stdout: &str) -> u64 {
+    stdout
+        .lines()
+        .rev()
+        .find(|line| line.contains("changed"))
+        .map_or(0, |summary| {
+            summary
+                .split(',')
+                .filter(|part| part.contains("insertion") || part.contains("deletion"))
+                .filter_map(|part| part.split_whitespace().next()?.parse::<u64>().ok())
+                .sum()
+        })
+}
+
+/// `jj log` printed `<change_id> <commit_id>` lines for revisions already on
+/// the remote; either id may match the expected prefix.
+#[must_use]
+pub fn jj_log_confirms(stdout: &str, change_id: &str) -> bool {
+    stdout
+        .lines()
+        .flat_map(str::split_whitespace)
+        .any(|id| id_matches(id, change_id))
+}
+
+/// `git ls-remote` printed `<sha>\t<ref>` lines; the commit must be a ref tip.
+#[must_use]
+pub fn ls_remote_confirms(stdout: &str, change_id: &str) -> bool {
+    stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .any(|sha| id_matches(sha, change_id))
+}
+
+fn id_matches(id: &str, expected: &str) -> bool {
+    expected.len() >= MIN_CHANGE_ID_LEN && id.starts_with(&expected.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_jj_log_output_when_change_id_is_listed_then_push_is_confirmed() {
+        let stdout = "qpvuntsmwlqt 5d1a2b3c4d5e\nzzxxyyww0011 0a0b0c0d0e0f\n";
+
+        assert!(jj_log_confirms(stdout, "qpvuntsm"));
+        assert!(jj_log_confirms(stdout, "0a0b0c0d"));
+        assert!(!jj_log_confirms("", "qpvuntsm"));
+    }
+
+    #[test]
+    fn given_ls_remote_output_when_commit_is_not_a_ref_tip_then_push_is_unconfirmed() {
+        let stdout = "5d1a2b3c4d5e6f\trefs/heads/main\n0a0b0c0d0e0f1a\tHEAD\n";
+
+        assert!(ls_remote_confirms(stdout, "5D1A2B3C"));
+        assert!(!ls_remote_confirms(stdout, "feedbeef"));
+        assert!(!ls_remote_confirms(stdout, "refs/heads/main"));
+    }
+
+    #[test]
+    fn given_diff_stat_summary_when_counting_then_insertions_and_deletions_are_summed() {
+        let jj = "src/a.rs | 12 ++++++++----\nsrc/b.rs | 3 +++\n2 files changed, 11 insertions(+), 4 deletions(-)\n";
+
+        assert_eq!(diff_stat_changed_lines(jj), 15);
+        assert_eq!(
+            diff_stat_changed_lines(" 1 file changed, 1 insertion(+)\n"),
+            1
+        );
+        assert_eq!(diff_stat_changed_lines(""), 0);
+    }
+
+    #[test]
+    fn given_option_like_ids_when_validating_then_they_are_rejected() {
+        assert!(is_valid_change_id("qpvuntsmwlqt"));
+        assert!(is_valid_change_id("5D1A2B3C"));
+        assert!(!is_valid_change_id("--upload-pack=x"));
+        assert!(!is_valid_change_id("abc123^!..HEAD"));
+        assert!(is_valid_remote_name("origin"));
+        assert!(is_valid_remote_name("team/upstream.git"));
+        assert!(!is_valid_remote_name("--upload-pack=touch /tmp/x"));
+        assert!(!is_valid_remote_name("origin\") | remote_bookmarks()"));
+        assert!(!is_valid_remote_name(""));
+    }
+
+    #[test]
+    fn given_short_prefix_when_matching_then_it_is_rejected() {
+        assert!(!ls_remote_confirms(
+            "5d1a2b3c4d5e6f\trefs/heads/main\n",
+            "5d1a"
+        ));
+    }
+}
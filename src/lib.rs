@@ -12,14 +12,15 @@ pub mod infrastructure;
 pub mod runtime;
 
 pub use beads_sync::{
-    map_terminal_sync_state, BrSyncAction, BrSyncDecision, BrSyncDivergence, BrSyncStatus,
-    CoordinatorSyncTerminal,
+    detect_divergences, map_terminal_sync_state, BrSyncAction, BrSyncDecision, BrSyncDivergence,
+    BrSyncStatus, CoordinatorClaim, CoordinatorSyncTerminal,
 };
 pub use runtime::{
-    runtime_determine_transition, runtime_determine_transition_decision, RuntimeAgentId,
-    RuntimeAgentState, RuntimeAgentStatus, RuntimeBeadId, RuntimeError, RuntimePgAgentRepository,
-    RuntimePgBeadRepository, RuntimePgStageRepository, RuntimeRepoId, RuntimeStage,
-    RuntimeStageResult, RuntimeStageTransition, RuntimeTransitionDecision, RuntimeTransitionReason,
+    runtime_determine_transition, runtime_determine_transition_decision, BeadLifecycle,
+    BeadLifecycleState, RuntimeAgentId, RuntimeAgentState, RuntimeAgentStatus, RuntimeBeadId,
+    RuntimeError, RuntimePgAgentRepository, RuntimePgBeadRepository, RuntimePgStageRepository,
+    RuntimeRepoId, RuntimeStage, RuntimeStageResult, RuntimeStageTransition,
+    RuntimeTransitionDecision, RuntimeTransitionReason,
 };
 
 pub use canonical_schema::CANONICAL_COORDINATOR_SCHEMA_PATH;
@@ -29,38 +30,58 @@ pub use error::SwarmError as Error;
 pub use error::{code, SwarmError, ERROR_CODES};
 
 mod agent_runtime;
+pub mod alerts;
+pub mod chaos;
 mod config;
+pub mod conformance;
 pub mod db;
 pub mod diagnostics;
 mod error;
+pub mod escalations;
 pub mod gate_cache;
+pub mod index_advisor;
+pub mod landing;
+pub mod logging;
+pub mod notifications;
 pub mod orchestrator_service;
+pub mod profiling;
 pub mod prompts;
 pub mod protocol;
 pub mod protocol_envelope;
 pub mod protocol_runtime;
+pub mod quarantines;
+pub mod query_perf;
+pub mod signing;
+pub mod simulation;
 pub mod skill_execution;
 pub mod skill_execution_parsing;
 pub mod skill_prompts;
 pub mod stage_executor_content;
 pub mod stage_executors;
+pub mod summarizer;
+#[cfg(feature = "testsupport")]
+pub mod testsupport;
 pub mod types;
+pub mod url_discovery;
+pub mod workspace;
 
 pub use db::SwarmDb;
 pub use gate_cache::GateExecutionCache;
 pub use orchestrator_service::{
-    ArtifactStore, ClaimRepository, EventSink, LandingGateway, LandingOutcome, OrchestratorEvent,
-    OrchestratorPorts, OrchestratorService, OrchestratorTickOutcome, StageArtifactRecord,
-    StageExecutionOutcome, StageExecutionRequest, StageExecutor,
+    ArtifactStore, ClaimRepository, DynEventSink, EventSink, EventSinkConfig, EventSinkSpec,
+    FanOutEventSink, LandingGateway, LandingOutcome, NdjsonFileEventSink, OrchestratorEvent,
+    OrchestratorPorts, OrchestratorService, OrchestratorTickOutcome, PostgresEventSink,
+    StageArtifactRecord, StageExecutionOutcome, StageExecutionRequest, StageExecutor,
+    StderrEventSink,
 };
 pub use protocol::commands::*;
 pub use protocol_runtime::ProtocolRequest;
 
 pub use types::{
     AgentId, AgentMessage, AgentState, AgentStatus, ArtifactType, BeadId, ClaimStatus,
-    DeepResumeContextContract, EventSchemaVersion, ExecutionEvent, FailureDiagnostics, MessageType,
-    ProgressSummary, RepoId, ResumeArtifactDetailContract, ResumeArtifactSummary,
-    ResumeArtifactSummaryContract, ResumeContextContract, ResumeContextProjection,
-    ResumeStageAttempt, ResumeStageAttemptContract, Stage, StageArtifact, StageResult, SwarmConfig,
-    SwarmStatus,
+    ConfigChange, ConfigKey, ContextBudget, DeepResumeContextContract, EventSchemaVersion,
+    ExecutionEvent, FailureDiagnostics, MessageType, ProgressSummary, RepoId,
+    ResumeArtifactDetailContract, ResumeArtifactSummary, ResumeArtifactSummaryContract,
+    ResumeContextContract, ResumeContextProjection, ResumeStageAttempt, ResumeStageAttemptContract,
+    Stage, StageArtifact, StageResult, SwarmConfig, SwarmStatus, TruncationManifest,
 };
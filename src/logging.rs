@@ -0,0 +1,102 @@
+//! Diagnostics on stderr through `tracing`, so they never mix with the
+//! envelopes on stdout.
+//!
+//! `SWARM_LOG` takes `tracing` filter directives, for example
+//! `warn,swarm::stage_executors=debug`, falling back to `RUST_LOG` and then
+//! to `warn`. Every protocol request runs in a `request` span with its
+//! `rid`, `cmd`, `repo` and `agent`, and ends with an event carrying `ok`,
+//! `code` and `duration_ms`.
+
+use std::str::FromStr;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Filter used when neither `SWARM_LOG` nor `RUST_LOG` is set.
+pub const DEFAULT_LOG_FILTER: &str = "warn";
+
+/// How log events are written, from the global `--log-format` flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Pretty,
+    /// One JSON object per event, with span fields, for log shippers.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw {
+            "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            other => Err(format!("expected json or pretty; got {other}")),
+        }
+    }
+}
+
+/// Installs the global subscriber.
+///
+/// Directives in the filter that do not parse are reported once and
+/// ignored. The statement timer of [`crate::query_perf`] is installed beside
+/// the log output.
+pub fn init_logging(format: LogFormat) {
+    let raw = crate::config::log_filter_from_env();
+    let (filter, invalid) = log_filter(raw.as_deref());
+    let output = match format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_writer(std::io::stderr)
+            .boxed(),
+    };
+    let installed = tracing_subscriber::registry()
+        .with(output.with_filter(filter))
+        .with(crate::query_perf::query_layer())
+        .try_init();
+    if installed.is_ok() {
+        if let Some(error) = invalid {
+            tracing::warn!(
+                filter = raw.as_deref(),
+                "Ignoring invalid log filter: {error}"
+            );
+        }
+    }
+}
+
+/// The filter `raw` describes, or [`DEFAULT_LOG_FILTER`] with the parse
+/// error when it is unset or does not parse.
+fn log_filter(raw: Option<&str>) -> (EnvFilter, Option<String>) {
+    match raw.map(EnvFilter::try_new) {
+        Some(Ok(filter)) => (filter, None),
+        Some(Err(error)) => (EnvFilter::new(DEFAULT_LOG_FILTER), Some(error.to_string())),
+        None => (EnvFilter::new(DEFAULT_LOG_FILTER), None),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_filters_when_parsing_then_invalid_ones_fall_back_to_default() {
+        let (filter, error) = log_filter(Some("info,swarm::stage_executors=debug"));
+        assert!(error.is_none());
+        assert!(filter.to_string().contains("swarm::stage_executors=debug"));
+
+        let (filter, error) = log_filter(Some("swarm=loud"));
+        assert!(error.is_some());
+        assert_eq!(filter.to_string(), DEFAULT_LOG_FILTER);
+
+        assert_eq!(log_filter(None).0.to_string(), DEFAULT_LOG_FILTER);
+        assert_eq!("json".parse(), Ok(LogFormat::Json));
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+}
@@ -5,95 +5,25 @@
 #![warn(clippy::nursery)]
 #![forbid(unsafe_code)]
 
-mod cli;
-
 use std::env;
+use std::io::IsTerminal;
 
-use cli::{cli_command_to_request, parse_cli_args, CliAction, CliError};
 use serde_json::json;
+use swarm::cli::{
+    cli_command_to_request, completion_script, help_data, parse_cli_args, parse_log_format,
+    parse_output_format, parse_record, parse_tenant, render_envelope, CliAction, CliError,
+    OutputFormat,
+};
 use swarm::protocol_envelope::ProtocolEnvelope;
-use swarm::protocol_runtime;
+use swarm::protocol_runtime::{self, WireFormat};
 use swarm::SwarmError;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-const HELP_DATA: &str = r#"{
-  "n": "swarm",
-  "desc": "PostgreSQL-based agent swarm coordination",
-  "v": "0.2.0",
-  "proto": "v1",
-  "fmt": "jsonl",
-  "usage": "echo '{\"cmd\":\"<cmd>\"}' | swarm",
-  "cmds": [
-    ["doctor", "Health check | NEXT: fix failures before proceeding"],
-    ["status", "Swarm state | NEXT: if idle>0 & pending>0, run claim-next"],
-    ["init", "Full bootstrap (bootstrap+init-db+register) | NEXT: doctor"],
-    ["bootstrap", "Repo structure | NEXT: init-db"],
-    ["init-db", "Database schema | NEXT: register if seed_agents not set"],
-    ["init-local-db", "Local Docker DB | NEXT: init-db with new URL"],
-    ["register", "Seed agents | NEXT: status to verify"],
-    ["next", "Top bead rec (preview) | NEXT: claim-next to reserve"],
-    ["claim-next", "Claim top bead | NEXT: agent with returned agent_id"],
-    ["assign", "Explicit assign | NEXT: agent with assigned agent_id"],
-    ["agent", "Run pipeline | NEXT: monitor --view progress"],
-    ["run-once", "Single cycle | NEXT: status to see result"],
-    ["smoke", "Smoke test | NEXT: fix errors before spawn-prompts"],
-    ["monitor", "View state | VIEWS: active,progress,failures,messages"],
-    ["release", "Free agent | NEXT: status to confirm"],
-    ["artifacts", "Get bead outputs | NEXT: parse content by artifact_type"],
-    ["resume", "Resumable beads | NEXT: resume-context for details"],
-    ["resume-context", "Deep context | NEXT: continue from current_stage"],
-    ["qa", "QA checks | NEXT: if fail, check artifacts for details"],
-    ["state", "Full dump | USE: debugging complex issues"],
-    ["history", "Event log | NEXT: filter by bead_id if needed"],
-    ["lock", "Acquire lock | NEXT: proceed if ok:true"],
-    ["unlock", "Release lock | NEXT: state to verify"],
-    ["agents", "List agents | NEXT: find idle before assign"],
-    ["broadcast", "Send message | NEXT: monitor --view messages"],
-    ["load-profile", "Simulate load | NEXT: monitor during run"],
-    ["spawn-prompts", "Generate prompts | NEXT: launch agents with files"],
-    ["prompt", "Get prompt text | NEXT: use for agent config"],
-    ["batch", "Multi-command | NOTE: use ops key, stops on first fail"],
-    ["?", "This help | SEE: examples for patterns"]
-  ],
-  "workflows": {
-    "fresh_start": ["doctor", "init", "doctor", "status"],
-    "single_agent": ["claim-next", "agent --id N", "monitor --view progress"],
-    "parallel_launch": ["register --count 12", "spawn-prompts --count 12"],
-    "recovery": ["resume", "resume-context --bead-id X"],
-    "debug": ["status", "history", "artifacts --bead-id X"]
-  },
-  "examples": [
-    {"desc": "Quick start", "cmd": "echo '{\"cmd\":\"init\"}' | swarm"},
-    {"desc": "Health check", "cmd": "echo '{\"cmd\":\"doctor\"}' | swarm"},
-    {"desc": "Assign bead", "cmd": "echo '{\"cmd\":\"assign\",\"bead_id\":\"bd-abc123\",\"agent_id\":1}' | swarm"},
-    {"desc": "Dry run", "cmd": "echo '{\"cmd\":\"agent\",\"id\":1,\"dry\":true}' | swarm"},
-    {"desc": "Batch (use ops)", "cmd": "echo '{\"cmd\":\"batch\",\"ops\":[{\"cmd\":\"doctor\"},{\"cmd\":\"status\"}]}' | swarm"},
-    {"desc": "Monitor progress", "cmd": "echo '{\"cmd\":\"monitor\",\"view\":\"progress\"}' | swarm"},
-    {"desc": "Get artifacts", "cmd": "echo '{\"cmd\":\"artifacts\",\"bead_id\":\"bd-abc\"}' | swarm"}
-  ],
-  "batch_input": {
-    "required": "ops",
-    "not": "cmds",
-    "example": "echo '{\"cmd\":\"batch\",\"ops\":[{\"cmd\":\"doctor\"},{\"cmd\":\"status\"}]}' | swarm"
-  },
-  "resp": {
-    "ok": "bool - success",
-    "d": "object - data",
-    "err": "object - error",
-    "t": "number - timestamp",
-    "state": "object - current state"
-  }
-}"#;
-
-fn handle_cli_action(
-    action: &CliAction,
-    _unknown_arg: Option<&str>,
-) -> (Option<String>, i32, bool) {
+fn handle_cli_action(action: &CliAction, tenant: Option<&str>) -> (Option<String>, i32, bool) {
     match action {
         CliAction::ShowHelp => {
-            let help_json: serde_json::Value = serde_json::from_str(HELP_DATA).unwrap_or_default();
-            let envelope = ProtocolEnvelope::success(None, help_json);
+            let envelope = ProtocolEnvelope::success(None, help_data(VERSION));
             (
                 Some(serde_json::to_string(&envelope).unwrap_or_default()),
                 0,
@@ -113,28 +43,52 @@ fn handle_cli_action(
                 false,
             )
         }
+        CliAction::Explain(spec) => {
+            let envelope = ProtocolEnvelope::success(None, spec.explain());
+            (
+                Some(serde_json::to_string(&envelope).unwrap_or_default()),
+                0,
+                false,
+            )
+        }
+        CliAction::Completions(shell) => (Some(completion_script(*shell)), 0, false),
         CliAction::RunProtocol => (None, 0, true),
+        CliAction::Repl => (None, 0, false),
         CliAction::Command(cmd) => {
-            let json = cli_command_to_request(cmd.clone());
+            let json = cli_command_to_request(cmd.clone(), tenant);
             (Some(json), 0, false)
         }
     }
 }
 
 #[tokio::main]
+#[allow(clippy::too_many_lines)]
 async fn main() {
     dotenv::dotenv().ok();
 
-    tracing_subscriber::fmt::init();
-
     let args: Vec<String> = env::args().skip(1).collect();
+    // An invalid --log-format is reported with the other argument errors.
+    swarm::logging::init_logging(parse_log_format(&args).ok().flatten().unwrap_or_default());
 
-    let action = match parse_cli_args(&args) {
-        Ok(a) => a,
+    let parsed = parse_cli_args(&args).and_then(|action| {
+        let tenant = parse_tenant(&args)?;
+        let record = parse_record(&args)?;
+        parse_log_format(&args)?;
+        parse_output_format(&args).map(|explicit| {
+            (
+                action,
+                OutputFormat::resolve(explicit, std::io::stdout().is_terminal()),
+                tenant,
+                record,
+            )
+        })
+    });
+    let (action, output_format, tenant, record) = match parsed {
+        Ok(parsed) => parsed,
         Err(err) => {
             eprintln!("Error: {err}");
             if let CliError::UnknownCommand { cmd } = &err {
-                let suggestions = cli::suggest_commands(cmd);
+                let suggestions = swarm::cli::suggest_commands(cmd);
                 if let Some(suggestion) = suggestions.first() {
                     eprintln!("Did you mean: {suggestion}?");
                 }
@@ -143,15 +97,32 @@ async fn main() {
         }
     };
 
-    let (input_or_output, code, is_loop) = handle_cli_action(&action, None);
+    if let Some(path) = record {
+        if let Err(err) = protocol_runtime::trace::start_recording(std::path::Path::new(&path)) {
+            eprintln!("Error: cannot record to {path}: {err}");
+            std::process::exit(1);
+        }
+    }
+
+    if matches!(action, CliAction::Repl) {
+        std::process::exit(swarm::cli::run_repl().await);
+    }
+
+    let (input_or_output, code, is_loop) = handle_cli_action(&action, tenant.as_deref());
 
     if is_loop {
-        let exit_code = match run().await {
+        let wire_format = output_format.wire_format();
+        let exit_code = match run(wire_format).await {
             Ok(()) => 0,
             Err(err) => {
                 let envelope =
                     ProtocolEnvelope::error(None, err.code().to_string(), err.to_string());
-                println!("{}", serde_json::to_string(&envelope).unwrap_or_default());
+                let _ = protocol_runtime::write_envelope(
+                    &mut tokio::io::stdout(),
+                    &envelope,
+                    wire_format,
+                )
+                .await;
                 err.exit_code()
             }
         };
@@ -159,11 +130,48 @@ async fn main() {
     }
 
     if let Some(msg) = input_or_output {
-        if code != 0 || matches!(action, CliAction::ShowHelp | CliAction::ShowVersion) {
-            println!("{msg}");
+        if code != 0
+            || matches!(
+                action,
+                CliAction::ShowHelp
+                    | CliAction::ShowVersion
+                    | CliAction::Explain(_)
+                    | CliAction::Completions(_)
+            )
+        {
+            print!("{msg}");
+            if !msg.ends_with('\n') {
+                println!();
+            }
             std::process::exit(code);
         }
 
+        if output_format == OutputFormat::MessagePack {
+            let envelope = protocol_runtime::execute_protocol_line(&msg).await;
+            let written = protocol_runtime::write_envelope(
+                &mut tokio::io::stdout(),
+                &envelope,
+                WireFormat::MessagePack,
+            )
+            .await;
+            let exit_code = written
+                .and_then(|()| protocol_runtime::envelope_outcome(&envelope))
+                .map_or_else(|err| err.exit_code(), |()| 0);
+            std::process::exit(exit_code);
+        }
+
+        // A projected `d` has none of the table's columns; print it as JSONL.
+        let projected = args.iter().any(|arg| arg.starts_with("--project"));
+        if output_format.is_human() && !projected {
+            let cmd = args.first().map_or("", String::as_str);
+            let envelope = protocol_runtime::execute_protocol_line(&msg).await;
+            let color = std::io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+            println!("{}", render_envelope(cmd, &envelope, output_format, color));
+            let exit_code = protocol_runtime::envelope_outcome(&envelope)
+                .map_or_else(|err| err.exit_code(), |()| 0);
+            std::process::exit(exit_code);
+        }
+
         let exit_code = match protocol_runtime::process_protocol_line(&msg).await {
             Ok(()) => 0,
             Err(err) => {
@@ -177,6 +185,6 @@ async fn main() {
     }
 }
 
-async fn run() -> std::result::Result<(), SwarmError> {
-    protocol_runtime::run_protocol_loop().await
+async fn run(format: WireFormat) -> std::result::Result<(), SwarmError> {
+    protocol_runtime::run_protocol_loop_with_format(format).await
 }
@@ -0,0 +1,355 @@
+//! Delivering notifications through the providers in `SWARM_NOTIFICATIONS`.
+//!
+//! Each provider is a [`Notifier`]; [`deliver`] sends a notification to
+//! every notifier its routes pick, and one failing notifier does not stop
+//! the others.
+//!
+//! Every provider goes through `curl`, like the alert webhook: Slack and
+//! Discord as a JSON POST, email as an SMTP upload.
+
+use crate::alerts::WEBHOOK_TIMEOUT_SECS;
+use crate::orchestrator_service::PortFuture;
+use crate::types::{ApprovalRequest, Notification, NotificationRules, NotifierSpec, RepoId};
+use crate::{Result, SwarmError};
+use serde::Serialize;
+use serde_json::json;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Longest message Discord accepts in `content`.
+const DISCORD_MAX_CHARS: usize = 2000;
+
+pub trait Notifier: Send + Sync {
+    /// Delivers `notification`, or says why the provider refused it.
+    fn send<'a>(&'a self, notification: &'a Notification) -> PortFuture<'a, ()>;
+}
+
+/// A boxed notifier, as [`build_notifier`] returns it.
+pub type DynNotifier = Box<dyn Notifier>;
+
+/// Posts `{"text": ...}` to a Slack incoming webhook.
+#[derive(Debug, Clone)]
+pub struct SlackNotifier {
+    webhook_url: String,
+}
+
+impl SlackNotifier {
+    #[must_use]
+    pub fn new(webhook_url: impl Into<String>) -> Self {
+        Self {
+            webhook_url: webhook_url.into(),
+        }
+    }
+}
+
+impl Notifier for SlackNotifier {
+    fn send<'a>(&'a self, notification: &'a Notification) -> PortFuture<'a, ()> {
+        Box::pin(async move {
+            let payload = json!({"text": notification.headline()});
+            post_json(&self.webhook_url, &payload.to_string()).await
+        })
+    }
+}
+
+/// Posts `{"content": ...}` to a Discord channel webhook, cut to Discord's
+/// 2000 character limit.
+#[derive(Debug, Clone)]
+pub struct DiscordNotifier {
+    webhook_url: String,
+}
+
+impl DiscordNotifier {
+    #[must_use]
+    pub fn new(webhook_url: impl Into<String>) -> Self {
+        Self {
+            webhook_url: webhook_url.into(),
+        }
+    }
+}
+
+impl Notifier for DiscordNotifier {
+    fn send<'a>(&'a self, notification: &'a Notification) -> PortFuture<'a, ()> {
+        Box::pin(async move {
+            let content = notification
+                .headline()
+                .chars()
+                .take(DISCORD_MAX_CHARS)
+                .collect::<String>();
+            let payload = json!({"content": content});
+            post_json(&self.webhook_url, &payload.to_string()).await
+        })
+    }
+}
+
+/// Sends a plain-text mail over SMTP: the headline as the subject and the
+/// notification's details in the body.
+#[derive(Debug, Clone)]
+pub struct EmailNotifier {
+    smtp_url: String,
+    from: String,
+    to: Vec<String>,
+    starttls: bool,
+    netrc: bool,
+}
+
+impl EmailNotifier {
+    #[must_use]
+    pub const fn new(
+        smtp_url: String,
+        from: String,
+        to: Vec<String>,
+        starttls: bool,
+        netrc: bool,
+    ) -> Self {
+        Self {
+            smtp_url,
+            from,
+            to,
+            starttls,
+            netrc,
+        }
+    }
+}
+
+impl Notifier for EmailNotifier {
+    fn send<'a>(&'a self, notification: &'a Notification) -> PortFuture<'a, ()> {
+        Box::pin(async move {
+            let mut args = vec![
+                "--url".to_string(),
+                self.smtp_url.clone(),
+                "--mail-from".to_string(),
+                self.from.clone(),
+            ];
+            for recipient in &self.to {
+                args.extend(["--mail-rcpt".to_string(), recipient.clone()]);
+            }
+            if self.starttls {
+                args.push("--ssl-reqd".to_string());
+            }
+            if self.netrc {
+                args.push("--netrc".to_string());
+            }
+            args.extend(["--upload-file".to_string(), "-".to_string()]);
+            let message = email_message(&self.from, &self.to, notification);
+            run_curl(&args, message.as_bytes()).await
+        })
+    }
+}
+
+/// The notifier `spec` describes.
+#[must_use]
+pub fn build_notifier(spec: &NotifierSpec) -> DynNotifier {
+    match spec {
+        NotifierSpec::Slack { webhook_url } => Box::new(SlackNotifier::new(webhook_url.as_str())),
+        NotifierSpec::Discord { webhook_url } => {
+            Box::new(DiscordNotifier::new(webhook_url.as_str()))
+        }
+        NotifierSpec::Email {
+            smtp_url,
+            from,
+            to,
+            starttls,
+            netrc,
+        } => Box::new(EmailNotifier::new(
+            smtp_url.clone(),
+            from.clone(),
+            to.clone(),
+            *starttls,
+            *netrc,
+        )),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NotifierFailure {
+    pub notifier: String,
+    pub error: String,
+}
+
+/// Who a notification reached and who it did not.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct NotificationDelivery {
+    pub sent: Vec<String>,
+    pub failed: Vec<NotifierFailure>,
+}
+
+impl NotificationDelivery {
+    /// One line per failure, for the `*_errors` lists of the evaluations.
+    #[must_use]
+    pub fn errors(&self) -> Vec<String> {
+        self.failed
+            .iter()
+            .map(|failure| format!("{}: {}", failure.notifier, failure.error))
+            .collect()
+    }
+}
+
+/// Sends `notification` to every notifier a route picks for it.
+pub async fn deliver(
+    rules: &NotificationRules,
+    notification: &Notification,
+) -> NotificationDelivery {
+    deliver_to(rules, &rules.recipients(notification), notification).await
+}
+
+/// Sends `notification` to the named notifiers, in order, skipping routing.
+/// A name with no notifier counts as a failed delivery.
+pub async fn deliver_to(
+    rules: &NotificationRules,
+    names: &[&str],
+    notification: &Notification,
+) -> NotificationDelivery {
+    let mut delivery = NotificationDelivery::default();
+    for name in names {
+        let outcome = match rules.notifiers.get(*name) {
+            Some(spec) => build_notifier(spec).send(notification).await,
+            None => Err(SwarmError::ConfigError(format!("Unknown notifier {name}"))),
+        };
+        match outcome {
+            Ok(()) => delivery.sent.push((*name).to_string()),
+            Err(error) => delivery.failed.push(NotifierFailure {
+                notifier: (*name).to_string(),
+                error: error.to_string(),
+            }),
+        }
+    }
+    delivery
+}
+
+/// Sends a newly created approval's token to the notifiers routed for
+/// `approval_requested`.
+///
+/// This is the only place the token is ever shown. `None` when the approval
+/// already existed and its token went out back then.
+///
+/// # Errors
+/// Returns `SwarmError::ConfigError` if `SWARM_NOTIFICATIONS` is invalid.
+pub async fn deliver_approval_token(
+    repo_id: &RepoId,
+    request: &ApprovalRequest,
+) -> Result<Option<NotificationDelivery>> {
+    let Some(notification) = request.token_notification(repo_id.value()) else {
+        return Ok(None);
+    };
+    let rules = crate::config::notification_rules_from_env()?;
+    Ok(Some(deliver(&rules, &notification).await))
+}
+
+/// POSTs `body` as JSON to `url`.
+pub(crate) async fn post_json(url: &str, body: &str) -> Result<()> {
+    let args = [
+        "--header",
+        "Content-Type: application/json",
+        "--data-binary",
+        "@-",
+        url,
+    ]
+    .map(str::to_string);
+    run_curl(&args, body.as_bytes()).await
+}
+
+/// Runs `curl` with `args` after the common flags, feeding `stdin` to it.
+/// The error carries curl's own message, never the arguments, so webhook
+/// secrets stay out of logs.
+///
+/// # Errors
+/// Returns `SwarmError::IoError` if `curl` cannot run or exits non-zero.
+pub async fn run_curl(args: &[String], stdin: &[u8]) -> Result<()> {
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--max-time",
+            &WEBHOOK_TIMEOUT_SECS.to_string(),
+        ])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    let mut input = child
+        .stdin
+        .take()
+        .ok_or_else(|| SwarmError::Internal("curl has no stdin".to_string()))?;
+    input.write_all(stdin).await?;
+    drop(input);
+
+    let output = child.wait_with_output().await?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(SwarmError::IoError(std::io::Error::other(
+        String::from_utf8_lossy(&output.stderr)
+            .trim_end()
+            .to_string(),
+    )))
+}
+
+/// An RFC 5322 message with CRLF line endings. Line breaks in the headline
+/// are flattened so it cannot add headers.
+fn email_message(from: &str, to: &[String], notification: &Notification) -> String {
+    let subject = notification
+        .headline()
+        .replace(['\r', '\n'], " ")
+        .chars()
+        .take(200)
+        .collect::<String>();
+    let details = serde_json::to_string_pretty(&notification.details).unwrap_or_default();
+    let body = format!("{}\n\n{details}\n", notification.text)
+        .replace("\r\n", "\n")
+        .replace('\n', "\r\n");
+    format!(
+        "From: {from}\r\nTo: {}\r\nSubject: {subject}\r\nDate: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{body}",
+        to.join(", "),
+        chrono::Utc::now().to_rfc2822(),
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::types::NotificationSeverity;
+
+    fn notification(text: &str) -> Notification {
+        Notification {
+            event: "alert_fired".to_string(),
+            severity: NotificationSeverity::Critical,
+            repo_id: "repo".to_string(),
+            text: text.to_string(),
+            details: json!({"kind": "error_rate"}),
+        }
+    }
+
+    #[test]
+    fn given_text_with_line_breaks_when_building_email_then_headers_cannot_be_injected() {
+        let message = email_message(
+            "swarm@example.com",
+            &["oncall@example.com".to_string()],
+            &notification("stage failures\r\nBcc: someone@example.com"),
+        );
+        let (headers, body) = message.split_once("\r\n\r\n").unwrap();
+
+        assert!(headers.contains(
+            "Subject: [critical] alert_fired (repo): stage failures  Bcc: someone@example.com"
+        ));
+        assert!(!headers.contains("\r\nBcc:"));
+        assert!(body.contains("\"kind\": \"error_rate\""));
+    }
+
+    #[tokio::test]
+    async fn given_unknown_notifier_name_when_delivering_then_it_is_reported_as_failed() {
+        let delivery = deliver_to(
+            &NotificationRules::default(),
+            &["pager"],
+            &notification("down"),
+        )
+        .await;
+
+        assert!(delivery.sent.is_empty());
+        assert_eq!(delivery.failed[0].notifier, "pager");
+    }
+}
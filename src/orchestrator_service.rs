@@ -1,18 +1,41 @@
 mod assign;
 mod claim_next;
+mod event_sinks;
+mod landing_queue;
 mod orchestrator;
 mod ports;
+mod run_all;
+mod run_loop;
 mod run_once;
 mod timing;
 
 pub use assign::{AssignAgentSnapshot, AssignAppService, AssignCommand, AssignPorts, AssignResult};
 pub use claim_next::{ClaimNextAppService, ClaimNextPorts, ClaimNextResult};
+pub use event_sinks::{
+    emit_to_configured_sinks, rotated_path, DynEventSink, EventSinkConfig, EventSinkSpec,
+    FanOutEventSink, NdjsonFileEventSink, PostgresEventSink, StderrEventSink,
+    DEFAULT_EVENT_FILE_MAX_BYTES, DEFAULT_EVENT_FILE_MAX_FILES,
+};
+pub use landing_queue::{
+    landing_lock_resource, LandingQueue, LandingQueueConfig, LandingQueuePorts, LandingQueueResult,
+    PreLandOutcome, DEFAULT_LANDING_LOCK_TTL_MS, DEFAULT_LANDING_MAX_WAIT_MS,
+    DEFAULT_LANDING_POLL_MS,
+};
 pub use orchestrator::{OrchestratorService, OrchestratorTickOutcome};
 pub use ports::{
     ArtifactStore, ClaimRepository, EventSink, LandingGateway, LandingOutcome, OrchestratorEvent,
     OrchestratorPorts, PortFuture, StageArtifactRecord, StageExecutionOutcome,
     StageExecutionRequest, StageExecutor,
 };
+pub use run_all::{
+    RunAllAgentOutcome, RunAllAppService, RunAllPorts, RunAllSummary, DEFAULT_RUN_ALL_CONCURRENCY,
+    MAX_RUN_ALL_CONCURRENCY,
+};
+pub use run_loop::{
+    adaptive_pause_ms, RunLoopAppService, RunLoopConfig, RunLoopFailure, RunLoopPorts,
+    RunLoopStopReason, RunLoopSummary, DEFAULT_RUN_BUDGET_MS, DEFAULT_RUN_MAX_CONSECUTIVE_FAILURES,
+    DEFAULT_RUN_MAX_PAUSE_MS, DEFAULT_RUN_TARGET_DB_LATENCY_MS,
+};
 pub use run_once::{RunOnceAppService, RunOncePorts, RunOnceResult};
 
 #[cfg(test)]
@@ -0,0 +1,629 @@
+//! [`EventSink`] implementations shipped with the crate, chosen by
+//! `SWARM_EVENT_SINKS`:
+//!
+//! ```json
+//! {"sinks": [
+//!   {"type": "ndjson", "path": "/var/log/swarm/events.ndjson", "max_bytes": 10485760, "max_files": 5},
+//!   {"type": "postgres"},
+//!   {"type": "stderr"}
+//! ]}
+//! ```
+//!
+//! Every sink writes the same JSON object per event (see
+//! [`OrchestratorEvent::to_json`]). More than one sink fans out through
+//! [`FanOutEventSink`]. Stdout carries the protocol envelopes, so no sink
+//! writes there. Session code outside [`super::OrchestratorService`] sends
+//! its events through [`emit_to_configured_sinks`].
+
+use super::ports::{EventSink, OrchestratorEvent, PortFuture, StageExecutionOutcome};
+use crate::error::{Result, SwarmError};
+use crate::types::{AgentId, RecoveryAction};
+use crate::{RepoId, RuntimeAgentId, RuntimeBeadId, RuntimeRepoId, SwarmDb};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+pub const DEFAULT_EVENT_FILE_MAX_BYTES: u64 = 10 * 1024 * 1024;
+pub const DEFAULT_EVENT_FILE_MAX_FILES: u32 = 5;
+
+/// A boxed sink, as [`EventSinkConfig::build`] returns it.
+pub type DynEventSink = Box<dyn EventSink + Send + Sync>;
+
+impl StageExecutionOutcome {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Progressed => "progressed",
+            Self::Idle => "idle",
+        }
+    }
+}
+
+impl OrchestratorEvent {
+    /// `bead_claimed` for a claim the session made through [`SwarmDb`].
+    #[must_use]
+    pub fn bead_claimed(agent_id: &AgentId, bead_id: &str) -> Self {
+        Self::BeadClaimed {
+            agent_id: runtime_agent_id(agent_id),
+            bead_id: RuntimeBeadId::new(bead_id),
+        }
+    }
+
+    /// `stage_executed` for a stage that ran to a result.
+    #[must_use]
+    pub fn stage_executed(agent_id: &AgentId, bead_id: &str) -> Self {
+        Self::StageExecuted {
+            agent_id: runtime_agent_id(agent_id),
+            bead_id: RuntimeBeadId::new(bead_id),
+            outcome: StageExecutionOutcome::Progressed,
+        }
+    }
+
+    /// One `claim_recovered` per repo whose claims a recovery pass requeued,
+    /// counting only requeued claims.
+    #[must_use]
+    pub fn claims_recovered(actions: &[RecoveryAction]) -> Vec<(RepoId, Self)> {
+        let mut counts: Vec<(RepoId, u32)> = Vec::new();
+        for repo_id in actions.iter().filter_map(|action| match action {
+            RecoveryAction::ClaimRequeued { repo_id, .. } => Some(repo_id.as_str()),
+            _ => None,
+        }) {
+            match counts.iter_mut().find(|(repo, _)| repo.value() == repo_id) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((RepoId::new(repo_id), 1)),
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(repo_id, count)| (repo_id, Self::ClaimRecovered { count }))
+            .collect()
+    }
+
+    #[must_use]
+    pub const fn event_type(&self) -> &'static str {
+        match self {
+            Self::ClaimRecovered { .. } => "claim_recovered",
+            Self::BeadClaimed { .. } => "bead_claimed",
+            Self::StageExecuted { .. } => "stage_executed",
+            Self::SlaBreached { .. } => "sla_breached",
+        }
+    }
+
+    /// The event as sinks write it: `event`, `at`, and the variant's fields
+    /// with agents as `repo_id` plus `agent_id`.
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        let at = Utc::now();
+        match self {
+            Self::ClaimRecovered { count } => json!({
+                "event": self.event_type(),
+                "at": at,
+                "count": count,
+            }),
+            Self::BeadClaimed { agent_id, bead_id } => json!({
+                "event": self.event_type(),
+                "at": at,
+                "repo_id": agent_id.repo_id().value(),
+                "agent_id": agent_id.number(),
+                "bead_id": bead_id.value(),
+            }),
+            Self::StageExecuted {
+                agent_id,
+                bead_id,
+                outcome,
+            } => json!({
+                "event": self.event_type(),
+                "at": at,
+                "repo_id": agent_id.repo_id().value(),
+                "agent_id": agent_id.number(),
+                "bead_id": bead_id.value(),
+                "outcome": outcome.as_str(),
+            }),
+            Self::SlaBreached {
+                repo_id,
+                bead_id,
+                priority,
+                age_secs,
+                target_secs,
+            } => json!({
+                "event": self.event_type(),
+                "at": at,
+                "repo_id": repo_id.value(),
+                "bead_id": bead_id.value(),
+                "priority": priority,
+                "age_secs": age_secs,
+                "target_secs": target_secs,
+            }),
+        }
+    }
+}
+
+fn runtime_agent_id(agent_id: &AgentId) -> RuntimeAgentId {
+    RuntimeAgentId::new(
+        RuntimeRepoId::new(agent_id.repo_id().value()),
+        agent_id.number(),
+    )
+}
+
+/// One NDJSON line per event on standard error, leaving stdout to the
+/// protocol envelopes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StderrEventSink;
+
+impl EventSink for StderrEventSink {
+    fn append_event(&self, event: OrchestratorEvent) -> PortFuture<'_, ()> {
+        Box::pin(async move {
+            let line = format!("{}\n", event.to_json());
+            let mut stderr = tokio::io::stderr();
+            stderr.write_all(line.as_bytes()).await?;
+            stderr.flush().await.map_err(SwarmError::from)
+        })
+    }
+}
+
+/// Appends NDJSON to `path`, rotating by size.
+///
+/// When a line would take the file past `max_bytes`, the file moves to
+/// `path.1`, older files shift up, and anything past `path.<max_files>` is
+/// dropped. `max_files` of 0 keeps no rotated files.
+#[derive(Debug)]
+pub struct NdjsonFileEventSink {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: u32,
+    write_lock: Mutex<()>,
+}
+
+impl NdjsonFileEventSink {
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64, max_files: u32) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+            max_files,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    async fn append_line(&self, line: &str) -> Result<()> {
+        let _guard = self.write_lock.lock().await;
+        let current = tokio::fs::metadata(&self.path)
+            .await
+            .map_or(0, |metadata| metadata.len());
+        let incoming = u64::try_from(line.len()).unwrap_or(u64::MAX);
+        if current > 0 && current.saturating_add(incoming) > self.max_bytes {
+            self.rotate().await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        file.flush().await.map_err(SwarmError::from)
+    }
+
+    async fn rotate(&self) -> Result<()> {
+        if self.max_files == 0 {
+            return tokio::fs::remove_file(&self.path)
+                .await
+                .map_err(SwarmError::from);
+        }
+        for index in (1..self.max_files).rev() {
+            let older = rotated_path(&self.path, index);
+            if tokio::fs::try_exists(&older).await? {
+                tokio::fs::rename(&older, rotated_path(&self.path, index + 1)).await?;
+            }
+        }
+        tokio::fs::rename(&self.path, rotated_path(&self.path, 1))
+            .await
+            .map_err(SwarmError::from)
+    }
+}
+
+impl EventSink for NdjsonFileEventSink {
+    fn append_event(&self, event: OrchestratorEvent) -> PortFuture<'_, ()> {
+        Box::pin(async move {
+            let line = format!("{}\n", event.to_json());
+            self.append_line(&line).await
+        })
+    }
+}
+
+/// `path.<index>`, the name of a rotated event file.
+#[must_use]
+pub fn rotated_path(path: &Path, index: u32) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+/// Rows in the `orchestrator_events` table. `ClaimRecovered`, which names
+/// no agent, is stored under the sink's `repo_id`.
+#[derive(Clone)]
+pub struct PostgresEventSink {
+    db: SwarmDb,
+    repo_id: RepoId,
+}
+
+impl PostgresEventSink {
+    #[must_use]
+    pub const fn new(db: SwarmDb, repo_id: RepoId) -> Self {
+        Self { db, repo_id }
+    }
+}
+
+impl EventSink for PostgresEventSink {
+    fn append_event(&self, event: OrchestratorEvent) -> PortFuture<'_, ()> {
+        Box::pin(async move {
+            let (repo_id, agent_id, bead_id) = match &event {
+                OrchestratorEvent::ClaimRecovered { .. } => (self.repo_id.value(), None, None),
+                OrchestratorEvent::BeadClaimed { agent_id, bead_id }
+                | OrchestratorEvent::StageExecuted {
+                    agent_id, bead_id, ..
+                } => (
+                    agent_id.repo_id().value(),
+                    Some(agent_id.number()),
+                    Some(bead_id.value()),
+                ),
+                OrchestratorEvent::SlaBreached {
+                    repo_id, bead_id, ..
+                } => (repo_id.value(), None, Some(bead_id.value())),
+            };
+            self.db
+                .record_orchestrator_event(
+                    repo_id,
+                    event.event_type(),
+                    agent_id,
+                    bead_id,
+                    &event.to_json(),
+                )
+                .await
+                .map(|_| ())
+        })
+    }
+}
+
+/// Hands every event to each sink in order. A failing sink does not stop
+/// the rest; the first error is returned once all have run.
+pub struct FanOutEventSink {
+    sinks: Vec<DynEventSink>,
+}
+
+impl FanOutEventSink {
+    #[must_use]
+    pub fn new(sinks: Vec<DynEventSink>) -> Self {
+        Self { sinks }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+}
+
+impl EventSink for FanOutEventSink {
+    fn append_event(&self, event: OrchestratorEvent) -> PortFuture<'_, ()> {
+        Box::pin(async move {
+            let mut first_error = None;
+            for sink in &self.sinks {
+                if let Err(error) = sink.append_event(event.clone()).await {
+                    if first_error.is_none() {
+                        first_error = Some(error);
+                    }
+                }
+            }
+            first_error.map_or(Ok(()), Err)
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum EventSinkSpec {
+    /// `stdout` is still accepted and means stderr, since stdout carries
+    /// the protocol envelopes.
+    #[serde(alias = "stdout")]
+    Stderr,
+    Ndjson {
+        path: PathBuf,
+        #[serde(default = "default_max_bytes")]
+        max_bytes: u64,
+        #[serde(default = "default_max_files")]
+        max_files: u32,
+    },
+    Postgres,
+}
+
+const fn default_max_bytes() -> u64 {
+    DEFAULT_EVENT_FILE_MAX_BYTES
+}
+
+const fn default_max_files() -> u32 {
+    DEFAULT_EVENT_FILE_MAX_FILES
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventSinkConfig {
+    #[serde(default)]
+    pub sinks: Vec<EventSinkSpec>,
+}
+
+impl EventSinkConfig {
+    /// Parses and validates an event sink config.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::ConfigError` for malformed JSON, an unknown sink
+    /// type, an empty `ndjson` path, or a zero `max_bytes`.
+    pub fn from_json(raw: &str) -> Result<Self> {
+        let config: Self = serde_json::from_str(raw)
+            .map_err(|e| SwarmError::ConfigError(format!("Invalid event sink config: {e}")))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.sinks.iter().try_for_each(|sink| match sink {
+            EventSinkSpec::Ndjson { path, .. } if path.as_os_str().is_empty() => Err(
+                SwarmError::ConfigError("ndjson event sink needs a path".to_string()),
+            ),
+            EventSinkSpec::Ndjson { max_bytes: 0, .. } => Err(SwarmError::ConfigError(
+                "ndjson event sink max_bytes must be positive".to_string(),
+            )),
+            _ => Ok(()),
+        })
+    }
+
+    #[must_use]
+    pub fn needs_database(&self) -> bool {
+        self.sinks
+            .iter()
+            .any(|sink| matches!(sink, EventSinkSpec::Postgres))
+    }
+
+    /// The configured sinks: `None` when there are none, the sink itself
+    /// when there is one, and a [`FanOutEventSink`] otherwise.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::ConfigError` when a `postgres` sink is configured
+    /// and `db` is `None`.
+    pub fn build(&self, db: Option<&SwarmDb>, repo_id: &RepoId) -> Result<Option<DynEventSink>> {
+        let mut sinks = self
+            .sinks
+            .iter()
+            .map(|spec| -> Result<DynEventSink> {
+                match spec {
+                    EventSinkSpec::Stderr => Ok(Box::new(StderrEventSink)),
+                    EventSinkSpec::Ndjson {
+                        path,
+                        max_bytes,
+                        max_files,
+                    } => Ok(Box::new(NdjsonFileEventSink::new(
+                        path.clone(),
+                        *max_bytes,
+                        *max_files,
+                    ))),
+                    EventSinkSpec::Postgres => db
+                        .map(|db| -> DynEventSink {
+                            Box::new(PostgresEventSink::new(db.clone(), repo_id.clone()))
+                        })
+                        .ok_or_else(|| {
+                            SwarmError::ConfigError(
+                                "postgres event sink needs a database connection".to_string(),
+                            )
+                        }),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(match sinks.len() {
+            0 => None,
+            1 => sinks.pop(),
+            _ => Some(Box::new(FanOutEventSink::new(sinks))),
+        })
+    }
+}
+
+/// Sends `event` to the sinks in `SWARM_EVENT_SINKS`. An invalid config or
+/// a failing sink is logged rather than returned, so an event never fails
+/// the claim, recovery or stage it describes.
+pub async fn emit_to_configured_sinks(db: &SwarmDb, repo_id: &RepoId, event: OrchestratorEvent) {
+    let sink = match crate::config::event_sinks_from_env()
+        .and_then(|config| config.build(Some(db), repo_id))
+    {
+        Ok(Some(sink)) => sink,
+        Ok(None) => return,
+        Err(error) => {
+            tracing::warn!(
+                "Event sinks unavailable for {}: {error}",
+                event.event_type()
+            );
+            return;
+        }
+    };
+    let event_type = event.event_type();
+    if let Err(error) = sink.append_event(event).await {
+        tracing::warn!("Event sink failed for {event_type}: {error}");
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn claimed(bead: &str) -> OrchestratorEvent {
+        OrchestratorEvent::BeadClaimed {
+            agent_id: RuntimeAgentId::new(RuntimeRepoId::new("repo"), 3),
+            bead_id: RuntimeBeadId::new(bead),
+        }
+    }
+
+    struct CountingSink {
+        count: Arc<AtomicUsize>,
+        fail: bool,
+    }
+
+    impl EventSink for CountingSink {
+        fn append_event(&self, _event: OrchestratorEvent) -> PortFuture<'_, ()> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            let fail = self.fail;
+            Box::pin(async move {
+                if fail {
+                    Err(SwarmError::Internal("sink down".to_string()))
+                } else {
+                    Ok(())
+                }
+            })
+        }
+    }
+
+    #[test]
+    fn given_bead_claimed_event_when_serializing_then_agent_and_bead_are_flat_fields() {
+        let value = claimed("bd-1").to_json();
+
+        assert_eq!(value["event"], "bead_claimed");
+        assert_eq!(value["repo_id"], "repo");
+        assert_eq!(value["agent_id"], 3);
+        assert_eq!(value["bead_id"], "bd-1");
+    }
+
+    #[test]
+    fn given_sla_breached_event_when_serializing_then_it_names_repo_and_bead_without_agent() {
+        let value = OrchestratorEvent::SlaBreached {
+            repo_id: RuntimeRepoId::new("repo"),
+            bead_id: RuntimeBeadId::new("bd-1"),
+            priority: "p0".to_string(),
+            age_secs: 18_000,
+            target_secs: 14_400,
+        }
+        .to_json();
+
+        assert_eq!(value["event"], "sla_breached");
+        assert_eq!(value["repo_id"], "repo");
+        assert_eq!(value["bead_id"], "bd-1");
+        assert_eq!(value["target_secs"], 14_400);
+        assert!(value.get("agent_id").is_none());
+    }
+
+    #[test]
+    fn given_sink_configs_when_parsing_then_defaults_apply_and_bad_specs_are_rejected() {
+        let config = EventSinkConfig::from_json(
+            r#"{"sinks": [{"type": "ndjson", "path": "events.ndjson"}, {"type": "postgres"}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.sinks[0],
+            EventSinkSpec::Ndjson {
+                path: PathBuf::from("events.ndjson"),
+                max_bytes: DEFAULT_EVENT_FILE_MAX_BYTES,
+                max_files: DEFAULT_EVENT_FILE_MAX_FILES,
+            }
+        );
+        assert!(config.needs_database());
+        assert!(config.build(None, &RepoId::new("repo")).is_err());
+        assert!(EventSinkConfig::from_json(r#"{"sinks": [{"type": "kafka"}]}"#).is_err());
+        assert!(EventSinkConfig::from_json(
+            r#"{"sinks": [{"type": "ndjson", "path": "e", "max_bytes": 0}]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn given_legacy_stdout_sink_when_parsing_then_it_writes_to_stderr() {
+        let config = EventSinkConfig::from_json(r#"{"sinks": [{"type": "stdout"}]}"#).unwrap();
+
+        assert_eq!(config.sinks, vec![EventSinkSpec::Stderr]);
+    }
+
+    #[test]
+    fn given_recovery_actions_when_counting_then_one_event_per_repo_counts_requeued_claims() {
+        let requeued = |repo: &str, bead: &str| RecoveryAction::ClaimRequeued {
+            repo_id: repo.to_string(),
+            bead_id: bead.to_string(),
+            agent_id: 1,
+            reason: crate::types::ClaimRecoveryReason::LeaseExpired,
+        };
+        let actions = vec![
+            requeued("a", "bd-1"),
+            RecoveryAction::BacklogRequeued {
+                repo_id: "a".to_string(),
+                bead_id: "bd-2".to_string(),
+            },
+            requeued("b", "bd-3"),
+            requeued("a", "bd-4"),
+        ];
+
+        let events = OrchestratorEvent::claims_recovered(&actions);
+
+        assert_eq!(
+            events,
+            vec![
+                (
+                    RepoId::new("a"),
+                    OrchestratorEvent::ClaimRecovered { count: 2 }
+                ),
+                (
+                    RepoId::new("b"),
+                    OrchestratorEvent::ClaimRecovered { count: 1 }
+                ),
+            ]
+        );
+        assert!(OrchestratorEvent::claims_recovered(&[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn given_full_event_file_when_appending_then_it_rotates_and_drops_the_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.ndjson");
+        let line_len = u64::try_from(format!("{}\n", claimed("bd-1").to_json()).len()).unwrap();
+        let sink = NdjsonFileEventSink::new(&path, line_len + 1, 2);
+
+        for bead in ["bd-1", "bd-2", "bd-3", "bd-4"] {
+            sink.append_event(claimed(bead)).await.unwrap();
+        }
+
+        let read = |path: PathBuf| std::fs::read_to_string(path).unwrap();
+        assert!(read(path.clone()).contains("bd-4"));
+        assert!(read(rotated_path(&path, 1)).contains("bd-3"));
+        assert!(read(rotated_path(&path, 2)).contains("bd-2"));
+        assert!(!rotated_path(&path, 3).exists());
+    }
+
+    #[tokio::test]
+    async fn given_failing_sink_when_fanning_out_then_other_sinks_still_receive_the_event() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let sink = FanOutEventSink::new(vec![
+            Box::new(CountingSink {
+                count: count.clone(),
+                fail: true,
+            }),
+            Box::new(CountingSink {
+                count: count.clone(),
+                fail: false,
+            }),
+        ]);
+
+        let result = sink.append_event(claimed("bd-1")).await;
+
+        assert!(result.is_err());
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,207 @@
+use super::ports::{LandingGateway, LandingOutcome, PortFuture};
+use super::timing::elapsed_ms;
+use crate::profiling::HotspotCategory;
+use crate::{Result, RuntimeBeadId, RuntimeRepoId};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// How long the repo's slot outlives its last renewal; a landing renews it
+/// every third of this while it runs.
+pub const DEFAULT_LANDING_LOCK_TTL_MS: u64 = 600_000;
+/// How long a landing waits in the queue before giving up.
+pub const DEFAULT_LANDING_MAX_WAIT_MS: u64 = 600_000;
+/// Delay between attempts to take the slot.
+pub const DEFAULT_LANDING_POLL_MS: u64 = 1_000;
+
+/// Name of the resource lock that serializes landings in one repo.
+#[must_use]
+pub fn landing_lock_resource(repo_id: &RuntimeRepoId) -> String {
+    format!("landing:{}", repo_id.value())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LandingQueueConfig {
+    /// Shell command run while holding the slot, before landing; typically a
+    /// rebase onto the remote plus a quick gate. `None` skips it.
+    pub pre_land_command: Option<String>,
+    pub lock_ttl_ms: u64,
+    pub max_wait_ms: u64,
+    pub poll_ms: u64,
+}
+
+impl Default for LandingQueueConfig {
+    fn default() -> Self {
+        Self {
+            pre_land_command: None,
+            lock_ttl_ms: DEFAULT_LANDING_LOCK_TTL_MS,
+            max_wait_ms: DEFAULT_LANDING_MAX_WAIT_MS,
+            poll_ms: DEFAULT_LANDING_POLL_MS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PreLandOutcome {
+    pub command: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub output: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LandingQueueResult {
+    pub outcome: LandingOutcome,
+    /// Time spent waiting for the repo's landing slot.
+    pub queue_wait_ms: u64,
+    pub pre_land: Option<PreLandOutcome>,
+}
+
+pub trait LandingQueuePorts: LandingGateway {
+    /// Takes the repo's landing slot for `holder`; `false` while another
+    /// landing holds it.
+    fn try_acquire_landing_slot<'a>(
+        &'a self,
+        repo_id: &'a RuntimeRepoId,
+        holder: &'a str,
+        ttl_ms: u64,
+    ) -> PortFuture<'a, bool>;
+
+    /// Pushes the expiry of `holder`'s slot out to `ttl_ms` from now;
+    /// `false` when `holder` no longer holds it.
+    fn renew_landing_slot<'a>(
+        &'a self,
+        repo_id: &'a RuntimeRepoId,
+        holder: &'a str,
+        ttl_ms: u64,
+    ) -> PortFuture<'a, bool>;
+
+    fn release_landing_slot<'a>(
+        &'a self,
+        repo_id: &'a RuntimeRepoId,
+        holder: &'a str,
+    ) -> PortFuture<'a, ()>;
+
+    fn run_pre_land<'a>(&'a self, command: &'a str) -> PortFuture<'a, PreLandOutcome>;
+}
+
+/// Merge-queue style landing: one landing per repo at a time, each running
+/// the pre-land command against the tip the previous landing left behind.
+pub struct LandingQueue<P> {
+    ports: P,
+    config: LandingQueueConfig,
+}
+
+impl<P> LandingQueue<P>
+where
+    P: LandingQueuePorts + Sync,
+{
+    #[must_use]
+    pub const fn new(ports: P, config: LandingQueueConfig) -> Self {
+        Self { ports, config }
+    }
+
+    #[must_use]
+    pub const fn ports(&self) -> &P {
+        &self.ports
+    }
+
+    /// Waits for the repo's landing slot, runs the pre-land command, lands
+    /// `bead_id` through the gateway, and frees the slot. The slot is renewed
+    /// while held, so a pre-land command slower than `lock_ttl_ms` keeps it.
+    /// A failing pre-land command skips the landing and reports it
+    /// unconfirmed.
+    ///
+    /// # Errors
+    /// Returns an error when the slot is not free within `max_wait_ms` or a
+    /// port fails. The slot is released before any error after it was taken.
+    pub async fn land(
+        &self,
+        repo_id: &RuntimeRepoId,
+        holder: &str,
+        bead_id: &RuntimeBeadId,
+    ) -> Result<LandingQueueResult> {
+        let queue_wait_ms = self.wait_for_slot(repo_id, holder).await?;
+        let landed = self.land_renewing_slot(repo_id, holder, bead_id).await;
+        let released = self.ports.release_landing_slot(repo_id, holder).await;
+        let (outcome, pre_land) = landed?;
+        released?;
+
+        Ok(LandingQueueResult {
+            outcome,
+            queue_wait_ms,
+            pre_land,
+        })
+    }
+
+    async fn wait_for_slot(&self, repo_id: &RuntimeRepoId, holder: &str) -> Result<u64> {
+        let start = Instant::now();
+        loop {
+            if self
+                .ports
+                .try_acquire_landing_slot(repo_id, holder, self.config.lock_ttl_ms)
+                .await?
+            {
+                crate::profiling::record(
+                    HotspotCategory::LockWait,
+                    "landing_queue",
+                    start.elapsed(),
+                );
+                return Ok(elapsed_ms(start));
+            }
+            let waited = elapsed_ms(start);
+            if waited >= self.config.max_wait_ms {
+                return Err(crate::Error::StageError(format!(
+                    "Timed out after {waited}ms waiting for the landing queue of repo {}",
+                    repo_id.value()
+                )));
+            }
+            tokio::time::sleep(Duration::from_millis(self.config.poll_ms)).await;
+        }
+    }
+
+    /// Runs the landing, renewing the slot every third of `lock_ttl_ms`
+    /// until it finishes. A failed renewal leaves the current expiry; the
+    /// landing goes on and its own outcome stands.
+    async fn land_renewing_slot(
+        &self,
+        repo_id: &RuntimeRepoId,
+        holder: &str,
+        bead_id: &RuntimeBeadId,
+    ) -> Result<(LandingOutcome, Option<PreLandOutcome>)> {
+        let renew_every = Duration::from_millis((self.config.lock_ttl_ms / 3).max(1));
+        let landing = self.land_holding_slot(bead_id);
+        tokio::pin!(landing);
+        loop {
+            tokio::select! {
+                landed = &mut landing => return landed,
+                () = tokio::time::sleep(renew_every) => {
+                    let _renewed = self
+                        .ports
+                        .renew_landing_slot(repo_id, holder, self.config.lock_ttl_ms)
+                        .await;
+                }
+            }
+        }
+    }
+
+    async fn land_holding_slot(
+        &self,
+        bead_id: &RuntimeBeadId,
+    ) -> Result<(LandingOutcome, Option<PreLandOutcome>)> {
+        let pre_land = match self.config.pre_land_command.as_deref() {
+            Some(command) => Some(self.ports.run_pre_land(command).await?),
+            None => None,
+        };
+        if let Some(failed) = pre_land.as_ref().filter(|outcome| !outcome.success) {
+            let outcome = LandingOutcome::new(
+                false,
+                format!("pre-land command failed: {}", failed.command),
+            );
+            return Ok((outcome, pre_land));
+        }
+
+        let outcome = self.ports.execute_landing(bead_id).await?;
+        Ok((outcome, pre_land))
+    }
+}
@@ -1,7 +1,9 @@
 use super::ports::{OrchestratorPorts, StageExecutionRequest};
 use crate::{Result, RuntimeAgentId, RuntimeAgentStatus};
+use serde::Serialize;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum OrchestratorTickOutcome {
     AgentMissing,
     Progressed,
@@ -17,8 +19,6 @@ impl<P> OrchestratorService<P>
 where
     P: OrchestratorPorts + Sync,
 {
-    const LEASE_EXTENSION_MS: i32 = 300_000;
-
     #[must_use]
     pub const fn new(ports: P) -> Self {
         Self { ports }
@@ -47,10 +47,10 @@ where
                 RuntimeAgentStatus::Done => Ok(OrchestratorTickOutcome::Completed),
                 RuntimeAgentStatus::Working | RuntimeAgentStatus::Waiting => {
                     if let Some(bead_id) = state.bead_id() {
-                        let heartbeat_ok = self
-                            .ports
-                            .heartbeat_claim(agent_id, bead_id, Self::LEASE_EXTENSION_MS)
-                            .await?;
+                        // No extension of its own: the lease renews by the
+                        // configured `lease_ttl_ms`.
+                        let heartbeat_ok =
+                            self.ports.heartbeat_claim(agent_id, bead_id, None).await?;
                         if !heartbeat_ok {
                             return Ok(OrchestratorTickOutcome::Idle);
                         }
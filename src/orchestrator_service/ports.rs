@@ -1,5 +1,6 @@
 use crate::{
-    Result, RuntimeAgentId, RuntimeAgentState, RuntimeBeadId, RuntimeStage, RuntimeStageResult,
+    Result, RuntimeAgentId, RuntimeAgentState, RuntimeBeadId, RuntimeRepoId, RuntimeStage,
+    RuntimeStageResult,
 };
 use std::future::Future;
 use std::pin::Pin;
@@ -127,6 +128,14 @@ pub enum OrchestratorEvent {
         bead_id: RuntimeBeadId,
         outcome: StageExecutionOutcome,
     },
+    /// A backlog entry outlived its priority's SLA target.
+    SlaBreached {
+        repo_id: RuntimeRepoId,
+        bead_id: RuntimeBeadId,
+        priority: String,
+        age_secs: i64,
+        target_secs: i64,
+    },
 }
 
 pub trait ClaimRepository {
@@ -153,7 +162,7 @@ pub trait ClaimRepository {
         &'a self,
         agent_id: &'a RuntimeAgentId,
         bead_id: &'a RuntimeBeadId,
-        lease_extension_ms: i32,
+        lease_extension_ms: Option<i32>,
     ) -> PortFuture<'a, bool>;
 }
 
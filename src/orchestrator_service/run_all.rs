@@ -0,0 +1,149 @@
+use super::orchestrator::OrchestratorTickOutcome;
+use super::ports::PortFuture;
+use super::run_loop::unfinished_status;
+use super::timing::elapsed_ms;
+use crate::Result;
+use futures_util::stream::{self, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Instant;
+
+/// Agents whose stages `run-all` runs at once when no concurrency is given.
+pub const DEFAULT_RUN_ALL_CONCURRENCY: u32 = 4;
+/// Most agents `run-all` runs at once.
+pub const MAX_RUN_ALL_CONCURRENCY: u32 = 32;
+
+/// What one tick of `run-all` did for one agent. A claimed bead whose run
+/// failed still counts as [`OrchestratorTickOutcome::Progressed`]: the claim
+/// stands and `error` says why the run stopped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunAllAgentOutcome {
+    pub agent_id: u32,
+    pub outcome: OrchestratorTickOutcome,
+    pub bead_id: Option<String>,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunAllSummary {
+    pub idle_agents: u32,
+    pub claimed: u32,
+    pub completed: u32,
+    pub failed: u32,
+    pub concurrency: u32,
+    /// One entry per idle agent, in agent id order.
+    pub agents: Vec<RunAllAgentOutcome>,
+    pub elapsed_ms: u64,
+}
+
+pub trait RunAllPorts {
+    /// Registered agents that are idle and hold no bead, in id order.
+    fn idle_agents(&self) -> PortFuture<'_, Vec<u32>>;
+    fn claim_next_bead(&self, agent_id: u32) -> PortFuture<'_, Option<String>>;
+    /// Runs the claimed bead through its stages to finalization. A payload
+    /// reporting the bead failed or blocked counts as a failed agent.
+    fn run_agent(&self, agent_id: u32) -> PortFuture<'_, Value>;
+}
+
+/// One coordinator tick over the whole swarm: every idle agent claims its
+/// next bead, then the agents that got one run their stages, at most
+/// `concurrency` at a time.
+pub struct RunAllAppService<P> {
+    ports: P,
+    concurrency: u32,
+}
+
+impl<P> RunAllAppService<P>
+where
+    P: RunAllPorts + Sync,
+{
+    #[must_use]
+    pub fn new(ports: P, concurrency: u32) -> Self {
+        Self {
+            ports,
+            concurrency: concurrency.clamp(1, MAX_RUN_ALL_CONCURRENCY),
+        }
+    }
+
+    /// Claims are made one agent at a time, in id order, so the backlog is
+    /// handed out the same way repeated `claim-next` calls would. A failed
+    /// claim or run is recorded on its agent and the others carry on.
+    ///
+    /// # Errors
+    /// Returns an error when the idle agents cannot be listed.
+    pub async fn execute(&self) -> Result<RunAllSummary> {
+        let start = Instant::now();
+        let idle = self.ports.idle_agents().await?;
+
+        let mut settled = Vec::new();
+        let mut claimed = Vec::new();
+        for agent_id in idle.iter().copied() {
+            let claim_start = Instant::now();
+            match self.ports.claim_next_bead(agent_id).await {
+                Ok(Some(bead_id)) => claimed.push((agent_id, bead_id)),
+                Ok(None) => settled.push(RunAllAgentOutcome {
+                    agent_id,
+                    outcome: OrchestratorTickOutcome::Idle,
+                    bead_id: None,
+                    error: None,
+                    elapsed_ms: elapsed_ms(claim_start),
+                }),
+                Err(error) => settled.push(RunAllAgentOutcome {
+                    agent_id,
+                    outcome: OrchestratorTickOutcome::Idle,
+                    bead_id: None,
+                    error: Some(error.to_string()),
+                    elapsed_ms: elapsed_ms(claim_start),
+                }),
+            }
+        }
+
+        let claimed_count = u32::try_from(claimed.len()).unwrap_or(u32::MAX);
+        let ran = stream::iter(claimed)
+            .map(|(agent_id, bead_id)| self.run_claimed(agent_id, bead_id))
+            .buffer_unordered(usize::try_from(self.concurrency).unwrap_or(1))
+            .collect::<Vec<_>>()
+            .await;
+        settled.extend(ran);
+        settled.sort_by_key(|agent| agent.agent_id);
+
+        let count = |predicate: fn(&RunAllAgentOutcome) -> bool| {
+            u32::try_from(settled.iter().filter(|agent| predicate(agent)).count())
+                .unwrap_or(u32::MAX)
+        };
+        Ok(RunAllSummary {
+            idle_agents: u32::try_from(idle.len()).unwrap_or(u32::MAX),
+            claimed: claimed_count,
+            completed: count(|agent| agent.outcome == OrchestratorTickOutcome::Completed),
+            failed: count(|agent| agent.error.is_some()),
+            concurrency: self.concurrency,
+            agents: settled,
+            elapsed_ms: elapsed_ms(start),
+        })
+    }
+
+    async fn run_claimed(&self, agent_id: u32, bead_id: String) -> RunAllAgentOutcome {
+        let run_start = Instant::now();
+        let result = self.ports.run_agent(agent_id).await;
+        let (outcome, error) = match result {
+            Ok(payload) => unfinished_status(&payload).map_or(
+                (OrchestratorTickOutcome::Completed, None),
+                |status| {
+                    (
+                        OrchestratorTickOutcome::Progressed,
+                        Some(format!("agent {agent_id} left the bead {status}")),
+                    )
+                },
+            ),
+            Err(error) => (OrchestratorTickOutcome::Progressed, Some(error.to_string())),
+        };
+        RunAllAgentOutcome {
+            agent_id,
+            outcome,
+            bead_id: Some(bead_id),
+            error,
+            elapsed_ms: elapsed_ms(run_start),
+        }
+    }
+}
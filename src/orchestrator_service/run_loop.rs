@@ -0,0 +1,248 @@
+use super::ports::PortFuture;
+use super::timing::elapsed_ms;
+use crate::Result;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Wall-clock budget for one `run` when none is given.
+pub const DEFAULT_RUN_BUDGET_MS: u64 = 600_000;
+/// Backlog-count latency above which the loop slows down.
+pub const DEFAULT_RUN_TARGET_DB_LATENCY_MS: u64 = 100;
+/// Longest pause between cycles.
+pub const DEFAULT_RUN_MAX_PAUSE_MS: u64 = 5_000;
+/// Failed cycles in a row before the loop gives up.
+pub const DEFAULT_RUN_MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunLoopConfig {
+    pub budget_ms: u64,
+    pub max_cycles: Option<u32>,
+    /// Stop once the backlog has no pending beads; otherwise keep polling
+    /// for new ones at `max_pause_ms` until the budget runs out.
+    pub until_empty: bool,
+    pub target_db_latency_ms: u64,
+    pub min_pause_ms: u64,
+    pub max_pause_ms: u64,
+    pub max_consecutive_failures: u32,
+}
+
+impl Default for RunLoopConfig {
+    fn default() -> Self {
+        Self {
+            budget_ms: DEFAULT_RUN_BUDGET_MS,
+            max_cycles: None,
+            until_empty: true,
+            target_db_latency_ms: DEFAULT_RUN_TARGET_DB_LATENCY_MS,
+            min_pause_ms: 0,
+            max_pause_ms: DEFAULT_RUN_MAX_PAUSE_MS,
+            max_consecutive_failures: DEFAULT_RUN_MAX_CONSECUTIVE_FAILURES,
+        }
+    }
+}
+
+/// Pause before the next cycle: doubles (to at least the observed latency)
+/// while the database answers slower than the target, and halves back
+/// toward `min_pause_ms` once it recovers.
+#[must_use]
+pub fn adaptive_pause_ms(previous_ms: u64, db_latency_ms: u64, config: &RunLoopConfig) -> u64 {
+    let next = if db_latency_ms > config.target_db_latency_ms {
+        previous_ms.saturating_mul(2).max(db_latency_ms)
+    } else {
+        previous_ms / 2
+    };
+    next.clamp(
+        config.min_pause_ms,
+        config.max_pause_ms.max(config.min_pause_ms),
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunLoopStopReason {
+    BacklogEmpty,
+    BudgetExhausted,
+    MaxCycles,
+    /// Beads are pending but none could be claimed for the agent.
+    NothingClaimable,
+    TooManyFailures,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunLoopFailure {
+    pub cycle: u32,
+    pub bead_id: Option<String>,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RunLoopSummary {
+    pub agent_id: u32,
+    pub cycles: u32,
+    pub claims: u32,
+    pub completed: u32,
+    pub failures: Vec<RunLoopFailure>,
+    pub stop_reason: RunLoopStopReason,
+    pub pending_at_stop: u64,
+    pub elapsed_ms: u64,
+    pub paused_ms: u64,
+    pub max_db_latency_ms: u64,
+}
+
+pub trait RunLoopPorts {
+    /// Beads in the backlog still waiting to be claimed.
+    fn pending_beads(&self) -> PortFuture<'_, u64>;
+    fn claim_next_bead(&self, agent_id: u32) -> PortFuture<'_, Option<String>>;
+    /// Runs the claimed bead through its stages to finalization. A payload
+    /// whose `status` is `failed`, `error`, `blocked` or `dead_lettered`
+    /// counts as a failed cycle.
+    fn run_agent(&self, agent_id: u32) -> PortFuture<'_, Value>;
+}
+
+/// Repeats claim, execute, finalize for one agent until the backlog drains
+/// or a limit is hit, pacing itself by how fast the database answers.
+pub struct RunLoopAppService<P> {
+    ports: P,
+    config: RunLoopConfig,
+}
+
+struct LoopState {
+    cycles: u32,
+    claims: u32,
+    completed: u32,
+    failures: Vec<RunLoopFailure>,
+    consecutive_failures: u32,
+    pause_ms: u64,
+    paused_ms: u64,
+    max_db_latency_ms: u64,
+}
+
+impl<P> RunLoopAppService<P>
+where
+    P: RunLoopPorts + Sync,
+{
+    #[must_use]
+    pub const fn new(ports: P, config: RunLoopConfig) -> Self {
+        Self { ports, config }
+    }
+
+    /// Runs cycles for `agent_id` and summarizes them. A failed claim or
+    /// agent run is recorded and the loop moves on; only too many in a row
+    /// stop it.
+    ///
+    /// # Errors
+    /// Returns an error when the backlog count cannot be read.
+    pub async fn execute(&self, agent_id: u32) -> Result<RunLoopSummary> {
+        let start = Instant::now();
+        let mut state = LoopState {
+            cycles: 0,
+            claims: 0,
+            completed: 0,
+            failures: Vec::new(),
+            consecutive_failures: 0,
+            pause_ms: self.config.min_pause_ms,
+            paused_ms: 0,
+            max_db_latency_ms: 0,
+        };
+
+        let (stop_reason, pending_at_stop) = loop {
+            let probe_start = Instant::now();
+            let pending = self.ports.pending_beads().await?;
+            let db_latency_ms = elapsed_ms(probe_start);
+            state.max_db_latency_ms = state.max_db_latency_ms.max(db_latency_ms);
+
+            if pending == 0 && self.config.until_empty {
+                break (RunLoopStopReason::BacklogEmpty, pending);
+            }
+            if elapsed_ms(start) >= self.config.budget_ms {
+                break (RunLoopStopReason::BudgetExhausted, pending);
+            }
+            if self
+                .config
+                .max_cycles
+                .is_some_and(|max_cycles| state.cycles >= max_cycles)
+            {
+                break (RunLoopStopReason::MaxCycles, pending);
+            }
+
+            if pending == 0 {
+                self.pause(&mut state, start, self.config.max_pause_ms)
+                    .await;
+                continue;
+            }
+
+            state.cycles += 1;
+            match self.ports.claim_next_bead(agent_id).await {
+                Ok(None) => break (RunLoopStopReason::NothingClaimable, pending),
+                Ok(Some(bead_id)) => {
+                    state.claims += 1;
+                    match self.ports.run_agent(agent_id).await {
+                        Ok(payload) => match unfinished_status(&payload) {
+                            None => {
+                                state.completed += 1;
+                                state.consecutive_failures = 0;
+                            }
+                            Some(status) => record_failure(
+                                &mut state,
+                                Some(bead_id),
+                                format!("agent {agent_id} left the bead {status}"),
+                            ),
+                        },
+                        Err(error) => {
+                            record_failure(&mut state, Some(bead_id), error.to_string());
+                        }
+                    }
+                }
+                Err(error) => record_failure(&mut state, None, error.to_string()),
+            }
+            if state.consecutive_failures >= self.config.max_consecutive_failures {
+                break (RunLoopStopReason::TooManyFailures, pending);
+            }
+
+            let pause_ms = adaptive_pause_ms(state.pause_ms, db_latency_ms, &self.config);
+            state.pause_ms = pause_ms;
+            self.pause(&mut state, start, pause_ms).await;
+        };
+
+        Ok(RunLoopSummary {
+            agent_id,
+            cycles: state.cycles,
+            claims: state.claims,
+            completed: state.completed,
+            failures: state.failures,
+            stop_reason,
+            pending_at_stop,
+            elapsed_ms: elapsed_ms(start),
+            paused_ms: state.paused_ms,
+            max_db_latency_ms: state.max_db_latency_ms,
+        })
+    }
+
+    /// Sleeps `pause_ms`, cut short so the budget is not overrun.
+    async fn pause(&self, state: &mut LoopState, start: Instant, pause_ms: u64) {
+        let remaining_ms = self.config.budget_ms.saturating_sub(elapsed_ms(start));
+        let pause_ms = pause_ms.min(remaining_ms);
+        if pause_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(pause_ms)).await;
+            state.paused_ms = state.paused_ms.saturating_add(pause_ms);
+        }
+    }
+}
+
+/// The `status` an agent run reported when it returned without finishing
+/// its bead, e.g. because a stage failed or the bead was blocked.
+pub(super) fn unfinished_status(payload: &Value) -> Option<&str> {
+    payload
+        .get("status")
+        .and_then(Value::as_str)
+        .filter(|status| matches!(*status, "failed" | "error" | "blocked" | "dead_lettered"))
+}
+
+fn record_failure(state: &mut LoopState, bead_id: Option<String>, error: String) {
+    state.failures.push(RunLoopFailure {
+        cycle: state.cycles,
+        bead_id,
+        error,
+    });
+    state.consecutive_failures += 1;
+}
@@ -1,11 +1,13 @@
 #![allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
 
 use super::{
-    ArtifactStore, AssignAgentSnapshot, AssignAppService, AssignCommand, AssignPorts,
-    ClaimNextAppService, ClaimNextPorts, ClaimRepository, EventSink, LandingGateway,
-    LandingOutcome, OrchestratorEvent, OrchestratorPorts, OrchestratorService,
-    OrchestratorTickOutcome, PortFuture, RunOnceAppService, RunOncePorts, StageArtifactRecord,
-    StageExecutionOutcome, StageExecutionRequest, StageExecutor,
+    adaptive_pause_ms, ArtifactStore, AssignAgentSnapshot, AssignAppService, AssignCommand,
+    AssignPorts, ClaimNextAppService, ClaimNextPorts, ClaimRepository, EventSink, LandingGateway,
+    LandingOutcome, LandingQueue, LandingQueueConfig, LandingQueuePorts, OrchestratorEvent,
+    OrchestratorPorts, OrchestratorService, OrchestratorTickOutcome, PortFuture, PreLandOutcome,
+    RunAllAppService, RunAllPorts, RunLoopAppService, RunLoopConfig, RunLoopPorts,
+    RunLoopStopReason, RunOnceAppService, RunOncePorts, StageArtifactRecord, StageExecutionOutcome,
+    StageExecutionRequest, StageExecutor,
 };
 use crate::{
     Error, Result, RuntimeAgentId, RuntimeAgentState, RuntimeAgentStatus, RuntimeBeadId,
@@ -15,6 +17,9 @@ use serde_json::{json, Value};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+/// Agent, bead and lease extension of one heartbeat the fake saw.
+type HeartbeatCall = (u32, String, Option<i32>);
+
 #[derive(Debug, Clone)]
 struct FakePorts {
     state: Arc<Mutex<Option<RuntimeAgentState>>>,
@@ -23,7 +28,7 @@ struct FakePorts {
     fail_on_execute: Arc<Mutex<bool>>,
     recover_count: Arc<Mutex<u32>>,
     heartbeat_ok: Arc<Mutex<bool>>,
-    heartbeat_calls: Arc<Mutex<Vec<(u32, String, i32)>>>,
+    heartbeat_calls: Arc<Mutex<Vec<HeartbeatCall>>>,
     workspace_calls: Arc<Mutex<Vec<(u32, String)>>>,
 }
 
@@ -109,7 +114,7 @@ impl ClaimRepository for FakePorts {
         &'a self,
         agent_id: &'a RuntimeAgentId,
         bead_id: &'a RuntimeBeadId,
-        lease_extension_ms: i32,
+        lease_extension_ms: Option<i32>,
     ) -> PortFuture<'a, bool> {
         Box::pin(async move {
             let mut calls = self.heartbeat_calls.lock().await;
@@ -229,7 +234,7 @@ async fn tick_returns_progressed_for_working_agent_when_stage_executes() {
     assert!(matches!(result, Ok(OrchestratorTickOutcome::Progressed)));
     let heartbeat_calls = ports.heartbeat_calls.lock().await.clone();
     assert_eq!(heartbeat_calls.len(), 1);
-    assert_eq!(heartbeat_calls[0], (1, "swm-2a2".to_string(), 300_000));
+    assert_eq!(heartbeat_calls[0], (1, "swm-2a2".to_string(), None));
 }
 
 #[tokio::test]
@@ -507,3 +512,401 @@ async fn given_run_once_ports_when_execute_then_returns_compact_step_payload() {
     assert_eq!(output.agent["id"], Value::from(7));
     assert_eq!(output.progress["step"], Value::from("progress"));
 }
+
+#[derive(Clone)]
+struct RunLoopFakePorts {
+    pending: Arc<Mutex<u64>>,
+    fail_agent: bool,
+    agent_status: &'static str,
+    agent_runs: Arc<Mutex<u32>>,
+}
+
+impl RunLoopFakePorts {
+    fn new(pending: u64, fail_agent: bool) -> Self {
+        Self {
+            pending: Arc::new(Mutex::new(pending)),
+            fail_agent,
+            agent_status: "completed",
+            agent_runs: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    fn reporting(self, agent_status: &'static str) -> Self {
+        Self {
+            agent_status,
+            ..self
+        }
+    }
+}
+
+impl RunLoopPorts for RunLoopFakePorts {
+    fn pending_beads(&self) -> PortFuture<'_, u64> {
+        Box::pin(async move { Ok(*self.pending.lock().await) })
+    }
+
+    fn claim_next_bead(&self, _agent_id: u32) -> PortFuture<'_, Option<String>> {
+        Box::pin(async move {
+            let mut pending = self.pending.lock().await;
+            if *pending == 0 {
+                return Ok(None);
+            }
+            *pending -= 1;
+            let remaining = *pending;
+            drop(pending);
+            Ok(Some(format!("bead-{remaining}")))
+        })
+    }
+
+    fn run_agent(&self, agent_id: u32) -> PortFuture<'_, Value> {
+        Box::pin(async move {
+            *self.agent_runs.lock().await += 1;
+            if self.fail_agent {
+                return Err(SwarmError::AgentError("stage failed".to_string()));
+            }
+            Ok(json!({"ok": true, "id": agent_id, "status": self.agent_status}))
+        })
+    }
+}
+
+fn run_loop_test_config() -> RunLoopConfig {
+    RunLoopConfig {
+        max_pause_ms: 0,
+        ..RunLoopConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn given_pending_backlog_when_run_until_empty_then_every_bead_is_completed() {
+    let ports = RunLoopFakePorts::new(3, false);
+    let service = RunLoopAppService::new(ports.clone(), run_loop_test_config());
+
+    let summary = service.execute(1).await.expect("run loop should finish");
+
+    assert_eq!(summary.stop_reason, RunLoopStopReason::BacklogEmpty);
+    assert_eq!(summary.claims, 3);
+    assert_eq!(summary.completed, 3);
+    assert!(summary.failures.is_empty());
+    assert_eq!(*ports.pending.lock().await, 0);
+}
+
+#[tokio::test]
+async fn given_failing_agent_when_run_then_loop_stops_after_consecutive_failures() {
+    let ports = RunLoopFakePorts::new(10, true);
+    let service = RunLoopAppService::new(ports.clone(), run_loop_test_config());
+
+    let summary = service.execute(1).await.expect("run loop should finish");
+
+    assert_eq!(summary.stop_reason, RunLoopStopReason::TooManyFailures);
+    assert_eq!(summary.failures.len(), 3);
+    assert_eq!(summary.completed, 0);
+    assert_eq!(*ports.agent_runs.lock().await, 3);
+}
+
+#[tokio::test]
+async fn given_agent_reporting_blocked_bead_when_run_then_cycle_counts_as_failure() {
+    let ports = RunLoopFakePorts::new(10, false).reporting("blocked");
+    let service = RunLoopAppService::new(ports.clone(), run_loop_test_config());
+
+    let summary = service.execute(1).await.expect("run loop should finish");
+
+    assert_eq!(summary.stop_reason, RunLoopStopReason::TooManyFailures);
+    assert_eq!(summary.completed, 0);
+    assert_eq!(summary.failures.len(), 3);
+    assert_eq!(summary.failures[0].bead_id.as_deref(), Some("bead-9"));
+    assert!(summary.failures[0].error.contains("blocked"));
+}
+
+#[tokio::test]
+async fn given_max_cycles_when_run_then_loop_stops_before_backlog_drains() {
+    let ports = RunLoopFakePorts::new(5, false);
+    let config = RunLoopConfig {
+        max_cycles: Some(2),
+        ..run_loop_test_config()
+    };
+    let service = RunLoopAppService::new(ports, config);
+
+    let summary = service.execute(1).await.expect("run loop should finish");
+
+    assert_eq!(summary.stop_reason, RunLoopStopReason::MaxCycles);
+    assert_eq!(summary.completed, 2);
+    assert_eq!(summary.pending_at_stop, 3);
+}
+
+#[derive(Clone)]
+struct RunAllFakePorts {
+    idle: Vec<u32>,
+    pending: Arc<Mutex<u64>>,
+    failing_agent: Option<u32>,
+    running: Arc<Mutex<(u32, u32)>>,
+}
+
+impl RunAllPorts for RunAllFakePorts {
+    fn idle_agents(&self) -> PortFuture<'_, Vec<u32>> {
+        Box::pin(async move { Ok(self.idle.clone()) })
+    }
+
+    fn claim_next_bead(&self, agent_id: u32) -> PortFuture<'_, Option<String>> {
+        Box::pin(async move {
+            let mut pending = self.pending.lock().await;
+            if *pending == 0 {
+                return Ok(None);
+            }
+            *pending -= 1;
+            drop(pending);
+            Ok(Some(format!("bead-for-{agent_id}")))
+        })
+    }
+
+    fn run_agent(&self, agent_id: u32) -> PortFuture<'_, Value> {
+        Box::pin(async move {
+            {
+                let mut running = self.running.lock().await;
+                running.0 += 1;
+                running.1 = running.1.max(running.0);
+            }
+            tokio::task::yield_now().await;
+            self.running.lock().await.0 -= 1;
+            if self.failing_agent == Some(agent_id) {
+                return Err(SwarmError::AgentError("stage failed".to_string()));
+            }
+            Ok(json!({"agent_id": agent_id, "status": "completed"}))
+        })
+    }
+}
+
+#[tokio::test]
+async fn given_more_idle_agents_than_beads_when_run_all_then_each_agent_gets_an_outcome() {
+    let ports = RunAllFakePorts {
+        idle: vec![1, 2, 3, 4, 5],
+        pending: Arc::new(Mutex::new(4)),
+        failing_agent: Some(2),
+        running: Arc::new(Mutex::new((0, 0))),
+    };
+    let service = RunAllAppService::new(ports.clone(), 2);
+
+    let summary = service.execute().await.expect("run-all should finish");
+
+    assert_eq!(summary.idle_agents, 5);
+    assert_eq!(summary.claimed, 4);
+    assert_eq!(summary.completed, 3);
+    assert_eq!(summary.failed, 1);
+    assert_eq!(
+        summary
+            .agents
+            .iter()
+            .map(|agent| (agent.agent_id, agent.outcome))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, OrchestratorTickOutcome::Completed),
+            (2, OrchestratorTickOutcome::Progressed),
+            (3, OrchestratorTickOutcome::Completed),
+            (4, OrchestratorTickOutcome::Completed),
+            (5, OrchestratorTickOutcome::Idle),
+        ]
+    );
+    assert!(ports.running.lock().await.1 <= 2);
+}
+
+#[test]
+fn given_slow_database_when_pacing_then_pause_backs_off_and_recovers() {
+    let config = RunLoopConfig {
+        target_db_latency_ms: 100,
+        min_pause_ms: 10,
+        max_pause_ms: 1_000,
+        ..RunLoopConfig::default()
+    };
+
+    assert_eq!(adaptive_pause_ms(10, 250, &config), 250);
+    assert_eq!(adaptive_pause_ms(250, 250, &config), 500);
+    assert_eq!(adaptive_pause_ms(800, 250, &config), 1_000);
+    assert_eq!(adaptive_pause_ms(1_000, 20, &config), 500);
+    assert_eq!(adaptive_pause_ms(12, 20, &config), 10);
+}
+
+#[derive(Clone)]
+struct LandingQueueFakePorts {
+    busy_polls: Arc<Mutex<u32>>,
+    pre_land_ok: bool,
+    pre_land_ms: u64,
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl LandingQueueFakePorts {
+    fn new(busy_polls: u32, pre_land_ok: bool) -> Self {
+        Self {
+            busy_polls: Arc::new(Mutex::new(busy_polls)),
+            pre_land_ok,
+            pre_land_ms: 0,
+            calls: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl LandingGateway for LandingQueueFakePorts {
+    fn execute_landing<'a>(&'a self, bead_id: &'a RuntimeBeadId) -> PortFuture<'a, LandingOutcome> {
+        Box::pin(async move {
+            self.calls
+                .lock()
+                .await
+                .push(format!("land:{}", bead_id.value()));
+            Ok(LandingOutcome::new(true, "pushed"))
+        })
+    }
+}
+
+impl LandingQueuePorts for LandingQueueFakePorts {
+    fn try_acquire_landing_slot<'a>(
+        &'a self,
+        _repo_id: &'a RuntimeRepoId,
+        _holder: &'a str,
+        _ttl_ms: u64,
+    ) -> PortFuture<'a, bool> {
+        Box::pin(async move {
+            let mut busy = self.busy_polls.lock().await;
+            if *busy == 0 {
+                self.calls.lock().await.push("acquire".to_string());
+                return Ok(true);
+            }
+            *busy -= 1;
+            drop(busy);
+            Ok(false)
+        })
+    }
+
+    fn renew_landing_slot<'a>(
+        &'a self,
+        _repo_id: &'a RuntimeRepoId,
+        _holder: &'a str,
+        _ttl_ms: u64,
+    ) -> PortFuture<'a, bool> {
+        Box::pin(async move {
+            self.calls.lock().await.push("renew".to_string());
+            Ok(true)
+        })
+    }
+
+    fn release_landing_slot<'a>(
+        &'a self,
+        _repo_id: &'a RuntimeRepoId,
+        _holder: &'a str,
+    ) -> PortFuture<'a, ()> {
+        Box::pin(async move {
+            self.calls.lock().await.push("release".to_string());
+            Ok(())
+        })
+    }
+
+    fn run_pre_land<'a>(&'a self, command: &'a str) -> PortFuture<'a, PreLandOutcome> {
+        Box::pin(async move {
+            self.calls.lock().await.push(format!("pre-land:{command}"));
+            tokio::time::sleep(std::time::Duration::from_millis(self.pre_land_ms)).await;
+            Ok(PreLandOutcome {
+                command: command.to_string(),
+                success: self.pre_land_ok,
+                exit_code: Some(i32::from(!self.pre_land_ok)),
+                output: String::new(),
+                duration_ms: 0,
+            })
+        })
+    }
+}
+
+fn queue_config(pre_land_command: Option<&str>, max_wait_ms: u64) -> LandingQueueConfig {
+    LandingQueueConfig {
+        pre_land_command: pre_land_command.map(str::to_string),
+        lock_ttl_ms: 60_000,
+        max_wait_ms,
+        poll_ms: 1,
+    }
+}
+
+#[tokio::test]
+async fn given_busy_slot_when_landing_then_queue_waits_runs_pre_land_and_releases() {
+    let ports = LandingQueueFakePorts::new(2, true);
+    let queue = LandingQueue::new(ports.clone(), queue_config(Some("jj rebase"), 60_000));
+
+    let result = queue
+        .land(
+            &RuntimeRepoId::new("local"),
+            "agent-1:bd-1",
+            &RuntimeBeadId::new("bd-1"),
+        )
+        .await
+        .expect("landing should succeed once the slot frees up");
+
+    assert!(result.outcome.push_confirmed());
+    assert!(result.pre_land.is_some_and(|pre_land| pre_land.success));
+    assert_eq!(
+        *ports.calls.lock().await,
+        vec!["acquire", "pre-land:jj rebase", "land:bd-1", "release"]
+    );
+}
+
+#[tokio::test]
+async fn given_failing_pre_land_when_landing_then_gateway_is_skipped_and_slot_released() {
+    let ports = LandingQueueFakePorts::new(0, false);
+    let queue = LandingQueue::new(ports.clone(), queue_config(Some("moon run :quick"), 0));
+
+    let result = queue
+        .land(
+            &RuntimeRepoId::new("local"),
+            "agent-1:bd-1",
+            &RuntimeBeadId::new("bd-1"),
+        )
+        .await
+        .expect("a failed pre-land is an outcome, not an error");
+
+    assert!(!result.outcome.push_confirmed());
+    assert_eq!(
+        *ports.calls.lock().await,
+        vec!["acquire", "pre-land:moon run :quick", "release"]
+    );
+}
+
+#[tokio::test]
+async fn given_pre_land_slower_than_the_lock_ttl_when_landing_then_the_slot_is_renewed() {
+    let ports = LandingQueueFakePorts {
+        pre_land_ms: 100,
+        ..LandingQueueFakePorts::new(0, true)
+    };
+    let config = LandingQueueConfig {
+        lock_ttl_ms: 30,
+        ..queue_config(Some("jj rebase"), 0)
+    };
+    let queue = LandingQueue::new(ports.clone(), config);
+
+    let result = queue
+        .land(
+            &RuntimeRepoId::new("local"),
+            "agent-1:bd-1",
+            &RuntimeBeadId::new("bd-1"),
+        )
+        .await
+        .expect("landing should succeed while the slot is renewed");
+
+    let calls = ports.calls.lock().await.clone();
+    assert!(result.outcome.push_confirmed());
+    assert_eq!(calls[..2], ["acquire", "pre-land:jj rebase"]);
+    assert!(calls.iter().filter(|call| *call == "renew").count() >= 2);
+    assert_eq!(calls[calls.len() - 2..], ["land:bd-1", "release"]);
+}
+
+#[tokio::test]
+async fn given_slot_never_frees_when_landing_then_queue_times_out() {
+    let ports = LandingQueueFakePorts::new(u32::MAX, true);
+    let queue = LandingQueue::new(ports.clone(), queue_config(None, 0));
+
+    let result = queue
+        .land(
+            &RuntimeRepoId::new("local"),
+            "agent-1:bd-1",
+            &RuntimeBeadId::new("bd-1"),
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(SwarmError::StageError(message)) if message.contains("landing queue")
+    ));
+    assert!(ports.calls.lock().await.is_empty());
+}
@@ -0,0 +1,301 @@
+//! Self-profiling for `swarm profile`.
+//!
+//! While a sampling window is open, this process times its database
+//! queries, request and envelope serialization, external commands (`br`,
+//! `bv`, `moon`, ...) and lock waits, and the window's samples are ranked by
+//! total time.
+//!
+//! Sampling needs the `profiling` feature. Without it [`record`] compiles to
+//! nothing and [`sample`] reports the feature missing. Database queries
+//! reach it through the statement timer in [`crate::query_perf`].
+#![cfg_attr(not(feature = "profiling"), allow(dead_code))]
+
+use serde::Serialize;
+use std::time::Duration;
+
+/// Default length of a sampling window.
+pub const DEFAULT_PROFILE_DURATION_MS: u64 = 5_000;
+/// Longest sampling window.
+pub const MAX_PROFILE_DURATION_MS: u64 = 60_000;
+/// Most hotspots a profile lists.
+pub const MAX_PROFILE_HOTSPOTS: usize = 20;
+
+/// What kind of work a sample timed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotspotCategory {
+    /// One SQL statement, labelled by its verb and table.
+    Db,
+    /// Decoding a request or encoding an envelope.
+    Serde,
+    /// A child process, labelled by program.
+    ExternalCommand,
+    /// Waiting for a resource lock or a landing slot.
+    LockWait,
+}
+
+/// Time spent on one label during a window, slowest first in a profile.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hotspot {
+    pub category: HotspotCategory,
+    pub label: String,
+    pub count: u64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    /// Fraction of all sampled time.
+    pub share: f64,
+}
+
+/// Sampled time per category, the coarse answer to "is it the database,
+/// `br`/`bv`, or serialization".
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryTotal {
+    pub category: HotspotCategory,
+    pub count: u64,
+    pub total_ms: f64,
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Profile {
+    pub duration_ms: u64,
+    pub sampled_ms: f64,
+    pub categories: Vec<CategoryTotal>,
+    pub hotspots: Vec<Hotspot>,
+}
+
+/// Whether this build can sample.
+#[must_use]
+pub const fn is_available() -> bool {
+    cfg!(feature = "profiling")
+}
+
+/// Adds one timed piece of work to every open window.
+#[inline]
+#[cfg_attr(not(feature = "profiling"), allow(clippy::missing_const_for_fn))]
+pub fn record(category: HotspotCategory, label: &str, elapsed: Duration) {
+    #[cfg(feature = "profiling")]
+    sampler::record(category, label, elapsed);
+    #[cfg(not(feature = "profiling"))]
+    let _ = (category, label, elapsed);
+}
+
+/// Samples for `duration` and ranks what this process spent its time on
+/// meanwhile. `None` when the build has no `profiling` feature.
+#[cfg_attr(not(feature = "profiling"), allow(clippy::unused_async))]
+pub async fn sample(duration: Duration) -> Option<Profile> {
+    #[cfg(feature = "profiling")]
+    {
+        let samples = sampler::sample(duration).await;
+        Some(rank(duration, samples))
+    }
+    #[cfg(not(feature = "profiling"))]
+    {
+        let _ = duration;
+        None
+    }
+}
+
+/// Count and total time of one `(category, label)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Tally {
+    count: u64,
+    total: Duration,
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn rank(
+    duration: Duration,
+    samples: std::collections::HashMap<(HotspotCategory, String), Tally>,
+) -> Profile {
+    let sampled_total = samples.values().map(|tally| tally.total).sum::<Duration>();
+    let share = |total: Duration| {
+        if sampled_total.is_zero() {
+            0.0
+        } else {
+            total.as_secs_f64() / sampled_total.as_secs_f64()
+        }
+    };
+
+    let mut categories = std::collections::BTreeMap::<HotspotCategory, Tally>::new();
+    for ((category, _), tally) in &samples {
+        let entry = categories.entry(*category).or_default();
+        entry.count += tally.count;
+        entry.total += tally.total;
+    }
+    let mut categories = categories
+        .into_iter()
+        .map(|(category, tally)| CategoryTotal {
+            category,
+            count: tally.count,
+            total_ms: millis(tally.total),
+            share: share(tally.total),
+        })
+        .collect::<Vec<_>>();
+    categories.sort_by(|left, right| right.total_ms.total_cmp(&left.total_ms));
+
+    let mut hotspots = samples
+        .into_iter()
+        .map(|((category, label), tally)| Hotspot {
+            category,
+            label,
+            count: tally.count,
+            total_ms: millis(tally.total),
+            mean_ms: millis(tally.total) / tally.count.max(1) as f64,
+            share: share(tally.total),
+        })
+        .collect::<Vec<_>>();
+    hotspots.sort_by(|left, right| {
+        right
+            .total_ms
+            .total_cmp(&left.total_ms)
+            .then_with(|| left.label.cmp(&right.label))
+    });
+    hotspots.truncate(MAX_PROFILE_HOTSPOTS);
+
+    Profile {
+        duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+        sampled_ms: millis(sampled_total),
+        categories,
+        hotspots,
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// `select bead_backlog`: the verb of a statement and the first table it
+/// names after `FROM`, `INTO`, `UPDATE` or `JOIN`.
+#[must_use]
+pub fn query_class(sql: &str) -> String {
+    let words = sql
+        .split_whitespace()
+        .map(|word| word.trim_matches(|c: char| matches!(c, '(' | ')' | ',' | ';' | '"')))
+        .collect::<Vec<_>>();
+    let verb = words
+        .first()
+        .map_or_else(|| "?".to_string(), |verb| verb.to_ascii_lowercase());
+    let table = words.windows(2).find_map(|pair| {
+        matches!(
+            pair[0].to_ascii_uppercase().as_str(),
+            "FROM" | "INTO" | "UPDATE" | "JOIN"
+        )
+        .then_some(pair[1])
+        .filter(|table| !table.is_empty())
+    });
+    table.map_or_else(|| verb.clone(), |table| format!("{verb} {table}"))
+}
+
+#[cfg(feature = "profiling")]
+mod sampler {
+    use super::{HotspotCategory, Tally};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Mutex, PoisonError};
+    use std::time::Duration;
+
+    type Samples = HashMap<(HotspotCategory, String), Tally>;
+
+    /// Windows open now; nothing is recorded while it is zero.
+    static OPEN_WINDOWS: AtomicUsize = AtomicUsize::new(0);
+    /// Everything recorded since the oldest open window opened.
+    static SAMPLES: Mutex<Option<Samples>> = Mutex::new(None);
+
+    pub(super) fn record(category: HotspotCategory, label: &str, elapsed: Duration) {
+        if OPEN_WINDOWS.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        let mut samples = SAMPLES.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(samples) = samples.as_mut() {
+            let tally = samples.entry((category, label.to_string())).or_default();
+            tally.count += 1;
+            tally.total += elapsed;
+        }
+    }
+
+    /// What was recorded during `duration`: the difference between the
+    /// samples at its end and at its start, so windows may overlap.
+    pub(super) async fn sample(duration: Duration) -> Samples {
+        let before = {
+            let mut samples = SAMPLES.lock().unwrap_or_else(PoisonError::into_inner);
+            OPEN_WINDOWS.fetch_add(1, Ordering::Relaxed);
+            samples.get_or_insert_with(HashMap::new).clone()
+        };
+        tokio::time::sleep(duration).await;
+        let mut after = {
+            let mut samples = SAMPLES.lock().unwrap_or_else(PoisonError::into_inner);
+            let after = samples.clone().unwrap_or_default();
+            if OPEN_WINDOWS.fetch_sub(1, Ordering::Relaxed) == 1 {
+                *samples = None;
+            }
+            after
+        };
+        for (key, earlier) in before {
+            if let Some(tally) = after.get_mut(&key) {
+                tally.count = tally.count.saturating_sub(earlier.count);
+                tally.total = tally.total.saturating_sub(earlier.total);
+            }
+        }
+        after.retain(|_, tally| tally.count > 0);
+        after
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn given_statements_when_classifying_then_verb_and_first_table_are_kept() {
+        assert_eq!(
+            query_class("SELECT bead_id FROM bead_backlog WHERE repo_id = $1"),
+            "select bead_backlog"
+        );
+        assert_eq!(
+            query_class("insert into agent_state (repo_id, agent_id) VALUES ($1, $2)"),
+            "insert agent_state"
+        );
+        assert_eq!(
+            query_class("UPDATE bead_claims SET status = 'done'"),
+            "update bead_claims"
+        );
+        assert_eq!(query_class("BEGIN"), "begin");
+    }
+
+    #[test]
+    fn given_samples_when_ranking_then_slowest_label_leads_with_shares() {
+        let tally = |count, ms| Tally {
+            count,
+            total: Duration::from_millis(ms),
+        };
+        let samples = HashMap::from([
+            (
+                (HotspotCategory::Db, "select bead_backlog".to_string()),
+                tally(4, 40),
+            ),
+            (
+                (HotspotCategory::ExternalCommand, "br".to_string()),
+                tally(1, 150),
+            ),
+            (
+                (HotspotCategory::Serde, "encode_envelope".to_string()),
+                tally(10, 10),
+            ),
+        ]);
+
+        let profile = rank(Duration::from_secs(1), samples);
+
+        assert_eq!(profile.duration_ms, 1000);
+        assert_eq!(profile.hotspots[0].label, "br");
+        assert_eq!(
+            profile.categories[0].category,
+            HotspotCategory::ExternalCommand
+        );
+        assert!((profile.hotspots[0].share - 0.75).abs() < 1e-9);
+        assert!((profile.hotspots[1].mean_ms - 10.0).abs() < 1e-9);
+        assert!((profile.sampled_ms - 200.0).abs() < 1e-9);
+    }
+}
@@ -1,5 +1,7 @@
 use std::path::{Path, PathBuf};
 
+use minijinja::{Environment, UndefinedBehavior};
+use serde_json::{json, Map, Value};
 use tokio::fs;
 
 use crate::error::{Result, SwarmError};
@@ -7,9 +9,32 @@ use crate::error::{Result, SwarmError};
 /// Fallback embedded prompt template used when repository template loading is bypassed.
 pub const AGENT_PROMPT_TEMPLATE: &str = include_str!("../.agents/agent_prompt.md");
 
-fn replace_agent_placeholders(template: &str, agent_id: u32) -> String {
-    let id = agent_id.to_string();
-    template.replace("#{N}", &id).replace("{N}", &id)
+/// Actions the `prompt` command takes; the CLI registry and the request
+/// parser both read this list.
+pub const PROMPT_ACTIONS: &[&str] = &["get", "add", "update", "list", "render"];
+
+/// Globals minijinja provides, so they never count as missing variables.
+const TEMPLATE_BUILTINS: &[&str] = &["range", "dict", "debug", "namespace"];
+
+/// Rewrites the original `#{N}` and `{N}` placeholders into template syntax
+/// so older templates keep rendering the bare agent number. `{{N}}` is
+/// already valid and left alone.
+fn upgrade_legacy_placeholders(template: &str) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(index) = rest.find("{N}") {
+        let (before, after) = rest.split_at(index);
+        let nested = before.ends_with('{') || after[3..].starts_with('}');
+        out.push_str(if nested {
+            before
+        } else {
+            before.strip_suffix('#').unwrap_or(before)
+        });
+        out.push_str(if nested { "{N}" } else { "{{ N }}" });
+        rest = &after[3..];
+    }
+    out.push_str(rest);
+    out
 }
 
 #[must_use]
@@ -17,6 +42,79 @@ pub fn canonical_agent_prompt_path(repo_root: &Path) -> PathBuf {
     repo_root.join(".agents").join("agent_prompt.md")
 }
 
+/// Variables every prompt template can use: `N`/`agent_id`, `repo_name`,
+/// `repo_id`, and `stage_commands` from config. `overrides` (for example a
+/// `bead_id`) are layered on top and win on conflicts.
+#[must_use]
+pub fn default_prompt_vars(
+    repo_root: &Path,
+    repo_id: &str,
+    agent_id: u32,
+    overrides: Option<&Map<String, Value>>,
+) -> Map<String, Value> {
+    let repo_name = repo_root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut vars = Map::new();
+    vars.insert("N".to_string(), json!(agent_id));
+    vars.insert("agent_id".to_string(), json!(agent_id));
+    vars.insert("repo_name".to_string(), json!(repo_name));
+    vars.insert("repo_id".to_string(), json!(repo_id));
+    vars.insert(
+        "stage_commands".to_string(),
+        json!(crate::config::load_config().stage_commands),
+    );
+    if let Some(overrides) = overrides {
+        vars.extend(overrides.clone());
+    }
+    vars
+}
+
+/// Renders `template` with `vars`. `{% include %}` paths resolve against
+/// `include_root`. Any top-level variable the template uses but `vars`
+/// lacks is an error rather than an empty string.
+///
+/// # Errors
+///
+/// Returns `SwarmError::ConfigError` for syntax errors, missing variables,
+/// and includes that cannot be loaded.
+pub fn render_prompt_template(
+    template: &str,
+    vars: &Map<String, Value>,
+    include_root: Option<&Path>,
+) -> Result<String> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_keep_trailing_newline(true);
+    if let Some(root) = include_root {
+        env.set_loader(minijinja::path_loader(root));
+    }
+
+    let source = upgrade_legacy_placeholders(template);
+    let template = env
+        .template_from_str(&source)
+        .map_err(|error| SwarmError::ConfigError(format!("Template error: {error}")))?;
+
+    let mut missing = template
+        .undeclared_variables(false)
+        .into_iter()
+        .filter(|name| !vars.contains_key(name) && !TEMPLATE_BUILTINS.contains(&name.as_str()))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        missing.sort();
+        return Err(SwarmError::ConfigError(format!(
+            "Missing template variables: {}",
+            missing.join(", ")
+        )));
+    }
+
+    template
+        .render(vars)
+        .map_err(|error| SwarmError::ConfigError(format!("Template error: {error}")))
+}
+
 /// Loads the on-disk agent prompt template from `.agents/agent_prompt.md`.
 ///
 /// # Errors
@@ -27,12 +125,71 @@ pub async fn load_agent_prompt_template(repo_root: &Path) -> Result<String> {
     fs::read_to_string(path).await.map_err(SwarmError::from)
 }
 
-/// Expands per-agent placeholders in the prompt template and returns the final prompt text.
+/// Renders the agent prompt template for `agent_id` and returns the final prompt text.
 ///
 /// # Errors
 ///
-/// Returns an error when the prompt template cannot be read.
+/// Returns an error when the prompt template cannot be read or rendered.
 pub async fn get_agent_prompt(repo_root: &Path, agent_id: u32) -> Result<String> {
     let template = load_agent_prompt_template(repo_root).await?;
-    Ok(replace_agent_placeholders(&template, agent_id))
+    let vars = default_prompt_vars(repo_root, "local", agent_id, None);
+    let include_root = repo_root.join(".agents");
+    render_prompt_template(&template, &vars, Some(&include_root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_prompt_template, upgrade_legacy_placeholders};
+    use serde_json::{json, Map, Value};
+
+    fn vars(pairs: &[(&str, Value)]) -> Map<String, Value> {
+        pairs
+            .iter()
+            .map(|(key, value)| ((*key).to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn given_legacy_placeholders_when_upgrading_then_only_bare_n_is_rewritten() {
+        assert_eq!(
+            upgrade_legacy_placeholders("Agent #{N} / {{N}} / {N}"),
+            "Agent {{ N }} / {{N}} / {{ N }}"
+        );
+    }
+
+    #[test]
+    fn given_variables_and_loop_when_rendering_then_template_is_expanded() {
+        let rendered = render_prompt_template(
+            "#{N} on {{ bead_id }}:{% for stage in stage_commands %} {{ stage }}{% endfor %}",
+            &vars(&[
+                ("N", json!(4)),
+                ("bead_id", json!("bd-7")),
+                ("stage_commands", json!(["implement", "qa-enforcer"])),
+            ]),
+            None,
+        );
+
+        assert_eq!(
+            rendered.ok().as_deref(),
+            Some("4 on bd-7: implement qa-enforcer")
+        );
+    }
+
+    #[test]
+    fn given_nested_attribute_when_rendering_then_top_level_variable_satisfies_it() {
+        let rendered = render_prompt_template(
+            "{{ bead.title }} ({{ bead.id }})",
+            &vars(&[("bead", json!({"id": "bd-7", "title": "Fix lint"}))]),
+            None,
+        );
+
+        assert_eq!(rendered.ok().as_deref(), Some("Fix lint (bd-7)"));
+    }
+
+    #[test]
+    fn given_missing_variable_when_rendering_then_error_names_it() {
+        let rendered = render_prompt_template("Work on {{ bead_id }}", &Map::new(), None);
+
+        assert!(rendered.is_err_and(|error| error.to_string().contains("bead_id")));
+    }
 }
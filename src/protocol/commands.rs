@@ -1,12 +1,26 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use sqlx::{PgPool, Row};
 use thiserror::Error;
 
+use crate::types::{
+    ApprovalGate, BacklogSelector, BlackboardSection, ConfigChange, ConfigKey, CostPricing,
+    CoverageFormat, NotificationSeverity, PartitionedTable, ReviewVerdict, ScheduledJobKind,
+    SoftDeleteTable, Stage,
+};
 use crate::{ArtifactType, StageArtifact};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DoctorInput {
     pub json: Option<bool>,
+    /// List every database URL candidate with its source and connect result.
+    pub show_candidates: Option<bool>,
+    /// Plan the hot coordinator queries and recommend curated indexes.
+    pub analyze_db: Option<bool>,
+    /// With `analyze_db`, build the recommended indexes.
+    pub apply: Option<bool>,
+    pub dry: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +44,8 @@ pub struct InitInput {
     pub database_url: Option<String>,
     pub schema: Option<String>,
     pub seed_agents: Option<u32>,
+    /// Undo every step when any step fails.
+    pub rollback_on_error: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +64,10 @@ pub struct ReleaseInput {
 pub struct MonitorInput {
     pub view: Option<String>,
     pub watch_ms: Option<u64>,
+    pub after_seq: Option<i64>,
+    pub page_size: Option<i64>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +86,22 @@ pub struct InitLocalDbInput {
     pub database: Option<String>,
     pub schema: Option<String>,
     pub seed_agents: Option<u32>,
+    /// Named Docker volume holding the data directory, so data outlives the
+    /// container.
+    pub volume: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// `localdb`: manage the Docker Postgres `init-local-db` started. `upgrade`
+/// needs `to`; `destroy` needs `confirm` repeating the container name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalDbInput {
+    pub action: String,
+    pub container_name: Option<String>,
+    pub to: Option<u32>,
+    pub tail: Option<u32>,
+    pub purge_volume: Option<bool>,
+    pub confirm: Option<String>,
     pub dry: Option<bool>,
 }
 
@@ -79,6 +115,7 @@ pub struct SpawnPromptsInput {
     pub template: Option<String>,
     pub out_dir: Option<String>,
     pub count: Option<u32>,
+    pub vars: Option<Map<String, Value>>,
     pub dry: Option<bool>,
 }
 
@@ -86,6 +123,475 @@ pub struct SpawnPromptsInput {
 pub struct PromptInput {
     pub id: u32,
     pub skill: Option<String>,
+    pub action: Option<String>,
+    pub body: Option<String>,
+    pub file: Option<String>,
+    pub template: Option<String>,
+    pub vars: Option<Map<String, Value>>,
+    pub dry: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextInput {
+    pub bead_id: String,
+    pub skill: Option<String>,
+}
+
+/// Optional `max_bytes`/`max_tokens` size ceiling shared by the context
+/// commands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextBudgetInput {
+    pub max_bytes: Option<u64>,
+    pub max_tokens: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordSymbolsInput {
+    pub bead_id: String,
+    pub agent_id: Option<u32>,
+    pub attempt: Option<u32>,
+    pub symbols: Option<Value>,
+    pub file: Option<String>,
+    pub dry: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineInput {
+    pub agent_id: u32,
+    pub reason: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// Lifting a quarantine always records why.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnquarantineInput {
+    pub agent_id: u32,
+    pub reason: String,
+    pub dry: Option<bool>,
+}
+
+/// `override_push_check` skips the remote check and requires a `reason`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LandInput {
+    pub bead_id: String,
+    pub agent_id: u32,
+    pub change_id: String,
+    pub remote: Option<String>,
+    pub override_push_check: bool,
+    pub reason: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// `action` is `show` (default), `create`, or `remove`. `create` works the
+/// agent's claimed bead unless `bead_id` names one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceInput {
+    pub agent_id: u32,
+    pub action: Option<String>,
+    pub bead_id: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// `action` is `start`, `end`, or `show`. `start` records which process is
+/// acting as `agent_id`; `host`, `pid` and `version` default to this
+/// machine's hostname, the caller's pid, and the swarm version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInput {
+    pub action: String,
+    pub agent_id: u32,
+    pub host: Option<String>,
+    pub pid: Option<u32>,
+    pub version: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// `action` is `set`, `get`, or `delete`. `set` needs `key` and `value`,
+/// `delete` needs `key`, and `get` without `key` lists every entry the agent
+/// holds for `bead_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KvInput {
+    pub action: String,
+    pub agent_id: u32,
+    pub bead_id: String,
+    pub key: Option<String>,
+    pub value: Option<Value>,
+    pub dry: Option<bool>,
+}
+
+/// `action` is `read` or `append`. `append` needs `agent_id`, `section`,
+/// `content`, and `expected_version`, the blackboard version the agent last
+/// read; `read` limits the entries to `section` when given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlackboardInput {
+    pub action: String,
+    pub bead_id: String,
+    pub agent_id: Option<u32>,
+    pub section: Option<BlackboardSection>,
+    pub content: Option<String>,
+    pub expected_version: Option<i64>,
+    pub dry: Option<bool>,
+}
+
+/// `action` is `submit` or `list`. `submit` needs `verdict` and a reviewer:
+/// `agent_id` for an agent (recorded as `agent:<id>` unless `reviewer` is
+/// given) or `reviewer` for a person.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewInput {
+    pub action: String,
+    pub bead_id: String,
+    pub verdict: Option<ReviewVerdict>,
+    pub comments: Option<String>,
+    pub agent_id: Option<u32>,
+    pub reviewer: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// `approve`: release a bead held at an approval gate. `token` must match
+/// the pending approval; `approver` defaults to `$USER`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApproveInput {
+    pub bead_id: String,
+    pub token: String,
+    pub approver: Option<String>,
+    pub note: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// `reissue-approval`: give a pending approval a fresh token and send it
+/// again. `gate` may be omitted when the bead has one pending gate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReissueApprovalInput {
+    pub bead_id: String,
+    pub gate: Option<ApprovalGate>,
+    pub dry: Option<bool>,
+}
+
+/// `action` is `create`, `list`, or `delete`. `create` and `delete` need
+/// `name`; `delete` also needs `confirm` repeating it, since it drops the
+/// tenant's schema with everything in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantInput {
+    pub action: String,
+    pub name: Option<String>,
+    pub seed_agents: Option<u32>,
+    pub confirm: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// `undelete`: restore a deleted claim, backlog entry or agent by `key`, or
+/// list deleted rows when `key` is omitted. `key` needs `table`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndeleteInput {
+    pub table: Option<SoftDeleteTable>,
+    pub key: Option<String>,
+    pub limit: Option<u32>,
+    pub dry: Option<bool>,
+}
+
+/// `artifact put`: store the contents of `file` as a `type` artifact on
+/// the bead's latest `stage` run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactInput {
+    pub action: String,
+    pub bead_id: String,
+    pub stage: Stage,
+    #[serde(rename = "type")]
+    pub artifact_type: ArtifactType,
+    pub file: String,
+    pub dry: Option<bool>,
+}
+
+/// `explain-transition`: the transition a hypothetical `result` of `stage`
+/// would take under the swarm's retry policy, with the checks behind it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainTransitionInput {
+    pub stage: Stage,
+    pub result: String,
+    pub attempt: Option<u32>,
+    pub max_attempts: Option<u32>,
+    pub category: Option<String>,
+    pub message: Option<String>,
+}
+
+/// `diff put`: store a patch set from `file` or inline `patch` as the bead's
+/// `diff` artifact. `diff get`: return the latest one, writing the patch to
+/// `file` when given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffInput {
+    pub action: String,
+    pub bead_id: String,
+    pub stage: Option<Stage>,
+    pub file: Option<String>,
+    pub patch: Option<String>,
+    pub base_revision: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// `config get`: the swarm's settings, or just `key`. `config set`: `key` to
+/// `value`, or several keys passed as args of their own; keys left out keep
+/// their value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigInput {
+    pub action: String,
+    pub key: Option<ConfigKey>,
+    pub changes: Vec<ConfigChange>,
+    pub dry: Option<bool>,
+}
+
+/// `jobs list`: every scheduled job with its schedule and last run. `jobs
+/// run-now`, `jobs disable` and `jobs enable` act on `job`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobsInput {
+    pub action: String,
+    pub job: Option<ScheduledJobKind>,
+    pub dry: Option<bool>,
+}
+
+/// Run scheduled jobs as they come due, for `budget_ms` or until the
+/// session shuts down.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServeInput {
+    pub budget_ms: Option<u64>,
+    pub dry: Option<bool>,
+}
+
+/// `action` is `test`: send a test notification to `notifier`, or to every
+/// notifier the routes pick for `event` at `severity`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotifyInput {
+    pub action: String,
+    pub notifier: Option<String>,
+    pub event: Option<String>,
+    pub severity: Option<NotificationSeverity>,
+    pub dry: Option<bool>,
+}
+
+/// Sample this process's own timings for `duration_ms` and rank them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileInput {
+    pub duration_ms: Option<u64>,
+    pub dry: Option<bool>,
+}
+
+/// `action` is `compare`: rank queries whose p95 latency in
+/// `[since, until]` grew past `threshold_pct` over the `baseline_days`
+/// before `since`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerfInput {
+    pub action: String,
+    pub since: DateTime<Utc>,
+    pub until: Option<DateTime<Utc>>,
+    pub threshold_pct: Option<u32>,
+    pub min_samples: Option<u64>,
+    pub baseline_days: Option<u32>,
+    pub dry: Option<bool>,
+}
+
+/// `maintenance partitions`: `action` is `list` (the default), `enable`
+/// for `table`, `create` for `table` or every partitioned table, or
+/// `detach` and `drop` for `partition`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceInput {
+    pub target: String,
+    pub action: String,
+    pub table: Option<PartitionedTable>,
+    pub partition: Option<String>,
+    /// Months after the current one to create partitions for.
+    pub ahead: Option<u32>,
+    pub dry: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayInput {
+    pub bead_id: String,
+}
+
+/// `replay --file`: run the requests of a `--record` trace again and diff
+/// each outcome against the recorded one, skipping `ignore` keys.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayTraceInput {
+    pub file: String,
+    pub ignore: Vec<String>,
+    pub dry: Option<bool>,
+}
+
+/// `verify`: check the signature chain over a bead's artifacts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyInput {
+    pub bead_id: String,
+}
+
+/// `attest`: export a bead's execution provenance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestInput {
+    pub bead_id: String,
+}
+
+/// `env-diff`: compare the environment fingerprints of two attempts of a
+/// bead, optionally of one stage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvDiffInput {
+    pub bead_id: String,
+    pub stage: Option<Stage>,
+    pub from_attempt: u32,
+    pub to_attempt: u32,
+}
+
+/// Beads to enqueue: exactly one of inline `beads` (a JSON array, or the
+/// JSON/NDJSON text itself) or a `file` holding either.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnqueueInput {
+    pub beads: Option<Value>,
+    pub file: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// Reconciles the backlog with `br list` for `rounds` passes (default 1),
+/// sleeping `interval_secs` between them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncBacklogInput {
+    pub rounds: Option<u32>,
+    pub interval_secs: Option<u32>,
+    pub dry: Option<bool>,
+}
+
+/// One `backlog` action on pending beads.
+///
+/// `preview` shows the first `limit` beads; `set-priority` takes `bead_id`
+/// and `priority`, `bump` takes `label` and `to`, and `remove` takes
+/// `bead_id` and/or `label`. `priority` holds the normalized target of
+/// either priority action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacklogInput {
+    pub action: String,
+    pub selector: BacklogSelector,
+    pub priority: Option<String>,
+    pub limit: Option<u32>,
+    pub dry: Option<bool>,
+}
+
+/// `claim-next --reserve`: hold the top pending bead for `agent_id` for
+/// `ttl_secs` without claiming it. `labels` narrows the pick to beads with
+/// one of them; without it the agent's default labels apply.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReserveClaimInput {
+    pub agent_id: u32,
+    pub ttl_secs: Option<u32>,
+    pub labels: Option<Vec<String>>,
+    pub dry: Option<bool>,
+}
+
+/// `claim-next --label`: claim the top pending backlog bead carrying one of
+/// `labels` for `agent_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabeledClaimInput {
+    pub agent_id: u32,
+    pub labels: Vec<String>,
+    pub dry: Option<bool>,
+}
+
+/// `labels get`: the default claim labels of `agent_id`, or of every agent.
+/// `labels set`: replace `agent_id`'s labels; an empty list clears them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentLabelsInput {
+    pub action: String,
+    pub agent_id: Option<u32>,
+    pub labels: Vec<String>,
+    pub dry: Option<bool>,
+}
+
+/// `accept-claim` or `reject-claim` for a bead `agent_id` has reserved;
+/// `reason` is recorded on rejection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimDecisionInput {
+    pub agent_id: u32,
+    pub bead_id: String,
+    pub reason: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// `cancel`: stop work on `bead_id` for good; `reason` goes to the owning
+/// agent and the cancelled stage's history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelInput {
+    pub bead_id: String,
+    pub reason: Option<String>,
+    pub dry: Option<bool>,
+}
+
+/// `takeover`: hand `bead_id` to `to_agent`. Without `from_agent` the
+/// owner's lease must have lapsed; naming the owner as `from_agent` is its
+/// consent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TakeoverInput {
+    pub bead_id: String,
+    /// Required unless `consent` is set, when it must be absent.
+    pub to_agent: Option<u32>,
+    pub from_agent: Option<u32>,
+    /// The owner, named by `from_agent`, consents to a takeover.
+    pub consent: Option<bool>,
+    pub dry: Option<bool>,
+}
+
+/// `recover`: release claims and locks an interrupted run left behind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverInput {
+    pub dry: Option<bool>,
+}
+
+/// Loop claim, agent run and finalize for one agent. `until_empty` defaults
+/// to true; `max_cycles`, when given, must be at least one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunInput {
+    pub id: Option<u32>,
+    pub until_empty: Option<bool>,
+    pub budget_ms: Option<u64>,
+    pub max_cycles: Option<u32>,
+    pub dry: Option<bool>,
+}
+
+/// Claim and run a bead for every idle agent. `concurrency` (1 to 32,
+/// default 4) caps how many agents run their stages at once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunAllInput {
+    pub concurrency: Option<u32>,
+    pub dry: Option<bool>,
+}
+
+/// `action` is `status`: report fault injection settings and counts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChaosInput {
+    pub action: String,
+}
+
+/// `action` is `repair`: fix every bead whose claim and `br` status disagree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncInput {
+    pub action: String,
+    pub dry: Option<bool>,
+}
+
+/// `action` is `migrate`: rewrite stored execution events to the latest
+/// payload schema version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsInput {
+    pub action: String,
+    pub dry: Option<bool>,
+}
+
+/// `action` is `snapshot` or `restore`.
+///
+/// `snapshot` needs `bead_id` and writes the blob to `file` when given;
+/// `restore` takes the blob inline as `snapshot` or from `file`, optionally
+/// handing it to `agent_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeadInput {
+    pub action: String,
+    pub bead_id: Option<String>,
+    pub agent_id: Option<u32>,
+    pub snapshot: Option<Value>,
+    pub file: Option<String>,
+    pub dry: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,6 +612,10 @@ pub struct StateInput {}
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryInput {
     pub limit: Option<i64>,
+    pub after_seq: Option<i64>,
+    pub page_size: Option<i64>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -113,6 +623,11 @@ pub struct LockInput {
     pub resource: String,
     pub agent: String,
     pub ttl_ms: i64,
+    /// Queue behind the current holder for up to this many ms instead of
+    /// failing with `BUSY` straight away.
+    pub wait_ms: Option<u32>,
+    pub purpose: Option<String>,
+    pub bead_id: Option<String>,
     pub dry: Option<bool>,
 }
 
@@ -126,10 +641,29 @@ pub struct UnlockInput {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentsInput {}
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocksInput {}
+
+/// Announce `msg` to every agent, including ones registered later, on
+/// `topic` until it expires in `ttl_secs`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BroadcastInput {
     pub msg: String,
     pub from: String,
+    pub topic: String,
+    pub ttl_secs: u64,
+    pub dry: Option<bool>,
+}
+
+/// `announcements list`: unexpired announcements with who has acknowledged
+/// them, or `agent_id`'s unacknowledged ones. `announcements ack`:
+/// acknowledge `id`, or everything on `topic`, for `agent_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnouncementsInput {
+    pub action: String,
+    pub agent_id: Option<u32>,
+    pub id: Option<i64>,
+    pub topic: Option<String>,
     pub dry: Option<bool>,
 }
 
@@ -138,9 +672,73 @@ pub struct LoadProfileInput {
     pub agents: Option<u32>,
     pub rounds: Option<u32>,
     pub timeout_ms: Option<u64>,
+    pub concurrency: Option<u32>,
+    pub dry: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbHealthInput {
+    pub samples: Option<u32>,
+}
+
+/// `healthz`: cheap liveness probe for container orchestrators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthzInput {}
+
+/// `invariants`: assert coordinator state is consistent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvariantsInput {}
+
+/// `report-usage`: tokens one LLM call spent, reported by the agent.
+/// `prompt_tokens`/`completion_tokens` are stored as input/output tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportUsageInput {
+    pub agent_id: u32,
+    pub bead_id: String,
+    pub stage: Option<Stage>,
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
     pub dry: Option<bool>,
 }
 
+/// `report-coverage`: an lcov or Cobertura report for a bead's stage run,
+/// read from `path` or passed inline as `report`. `format` defaults to
+/// detecting it from the content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportCoverageInput {
+    pub agent_id: u32,
+    pub bead_id: String,
+    pub stage: Option<Stage>,
+    pub path: Option<String>,
+    pub report: Option<String>,
+    pub format: Option<CoverageFormat>,
+    pub dry: Option<bool>,
+}
+
+/// `costs`: token spend and stage time over `since`..`until`, optionally for
+/// one bead. Inline `pricing` replaces `SWARM_MODEL_PRICING`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostsInput {
+    pub bead_id: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub pricing: Option<CostPricing>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopInput {
+    pub window_mins: Option<u32>,
+}
+
+/// `forecast`: project when the backlog drains from the last `window_hours`
+/// of throughput, at `agents` agents or the registered count.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastInput {
+    pub window_hours: Option<u32>,
+    pub agents: Option<u32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArtifactRetrievalRequest {
     pub repo_id: String,
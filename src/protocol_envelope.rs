@@ -5,10 +5,19 @@
 #![warn(clippy::nursery)]
 #![forbid(unsafe_code)]
 
+use std::io::{Read, Write};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::error::{Result, SwarmError};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProtocolEnvelope {
     pub ok: bool,
@@ -19,6 +28,9 @@ pub struct ProtocolEnvelope {
     pub ms: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub d: Option<Box<Value>>,
+    /// How `d` is compressed; `d` is then a base64 string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enc: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub err: Option<Box<ProtocolError>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -46,6 +58,7 @@ impl ProtocolEnvelope {
             t: Utc::now().timestamp_millis(),
             ms: None,
             d: Some(Box::new(data)),
+            enc: None,
             err: None,
             fix: None,
             next: None,
@@ -61,6 +74,7 @@ impl ProtocolEnvelope {
             t: Utc::now().timestamp_millis(),
             ms: None,
             d: None,
+            enc: None,
             err: Some(Box::new(ProtocolError {
                 code,
                 msg,
@@ -103,4 +117,140 @@ impl ProtocolEnvelope {
         }
         self
     }
+
+    /// Compresses `d` with `encoding` when its JSON is at least `min_bytes`
+    /// and compressing actually makes the envelope smaller.
+    #[must_use]
+    pub fn with_compressed_data(mut self, encoding: DataEncoding, min_bytes: usize) -> Self {
+        let Some(json) = self.d.as_deref().and_then(|d| serde_json::to_vec(d).ok()) else {
+            return self;
+        };
+        if json.len() < min_bytes {
+            return self;
+        }
+        if let Ok(compressed) = encoding.compress(&json) {
+            let wrapped = BASE64.encode(compressed);
+            if wrapped.len() < json.len() {
+                self.d = Some(Box::new(Value::String(wrapped)));
+                self.enc = Some(encoding.as_str().to_string());
+            }
+        }
+        self
+    }
+
+    /// Restores a `d` sent compressed, so callers read it as if it never
+    /// was. Envelopes without `enc` are returned as they are.
+    ///
+    /// # Errors
+    /// Returns an error if `enc` is unknown or `d` does not decode.
+    pub fn decompressed(mut self) -> Result<Self> {
+        let Some(enc) = self.enc.take() else {
+            return Ok(self);
+        };
+        let encoding = DataEncoding::parse(&enc)
+            .ok_or_else(|| SwarmError::Internal(format!("Envelope has unknown encoding {enc}")))?;
+        let wrapped = self.d.as_deref().and_then(Value::as_str).ok_or_else(|| {
+            SwarmError::Internal(format!("Envelope with encoding {enc} has no string data"))
+        })?;
+        let compressed = BASE64
+            .decode(wrapped)
+            .map_err(|e| SwarmError::Internal(format!("Envelope data is not base64: {e}")))?;
+        let json = encoding.decompress(&compressed)?;
+        self.d = Some(Box::new(serde_json::from_slice(&json)?));
+        Ok(self)
+    }
+}
+
+/// Compression a client may ask for on `d` through `accept_encoding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataEncoding {
+    Zstd,
+    Gzip,
+}
+
+impl DataEncoding {
+    #[must_use]
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "zstd" => Some(Self::Zstd),
+            "gzip" => Some(Self::Gzip),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Zstd => "zstd",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    /// The first supported encoding in `accept`, a comma-separated string or
+    /// an array of names in the client's order of preference. Names this
+    /// build does not know, `identity` included, are skipped.
+    #[must_use]
+    pub fn negotiate(accept: &Value) -> Option<Self> {
+        match accept {
+            Value::String(names) => names.split(',').find_map(Self::parse),
+            Value::Array(names) => names.iter().filter_map(Value::as_str).find_map(Self::parse),
+            _ => None,
+        }
+    }
+
+    fn compress(self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Zstd => zstd::encode_all(bytes, 0),
+            Self::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+        }
+    }
+
+    fn decompress(self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Zstd => zstd::decode_all(bytes),
+            Self::Gzip => {
+                let mut json = Vec::new();
+                GzDecoder::new(bytes).read_to_end(&mut json)?;
+                Ok(json)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::{DataEncoding, ProtocolEnvelope};
+    use serde_json::json;
+
+    #[test]
+    fn given_large_data_when_compressed_then_it_round_trips_with_each_encoding() {
+        let data = json!({"rows": vec!["the same context line"; 500]});
+
+        for encoding in [DataEncoding::Zstd, DataEncoding::Gzip] {
+            let envelope =
+                ProtocolEnvelope::success(None, data.clone()).with_compressed_data(encoding, 1024);
+            assert_eq!(envelope.enc.as_deref(), Some(encoding.as_str()));
+            assert!(envelope
+                .d
+                .as_deref()
+                .is_some_and(serde_json::Value::is_string));
+
+            let restored = envelope.decompressed().expect("data decodes");
+            assert_eq!(restored.d.as_deref(), Some(&data));
+            assert_eq!(restored.enc, None);
+        }
+
+        let small = ProtocolEnvelope::success(None, json!({"ok": 1}))
+            .with_compressed_data(DataEncoding::Zstd, 1024);
+        assert_eq!(small.enc, None);
+        assert_eq!(
+            DataEncoding::negotiate(&json!("br, identity, gzip, zstd")),
+            Some(DataEncoding::Gzip)
+        );
+    }
 }
@@ -9,12 +9,16 @@
 #![allow(clippy::branches_sharing_code)]
 #![allow(clippy::too_many_lines)]
 
-use crate::protocol_envelope::ProtocolEnvelope;
+use crate::db::write_ops::CommandAuditRow;
+use crate::db::{PendingWrite, WriteBatchHandle};
+use crate::profiling::HotspotCategory;
+use crate::protocol_envelope::{DataEncoding, ProtocolEnvelope};
 use crate::{code, SwarmError};
 use serde::Deserialize;
 use serde_json::{json, Map, Value};
 use std::time::Instant;
-use tokio::io::AsyncWriteExt;
+use tracing::Instrument;
+use wire_format::RequestFrame;
 
 mod audit;
 pub mod constants;
@@ -28,17 +32,26 @@ mod helpers;
 pub mod input_parsing;
 mod loop_executor;
 mod parsing;
+mod projection;
 mod schema_loader;
+pub mod trace;
 mod validation;
+mod wire_format;
 
 pub use audit::{compose_database_url_candidates, mask_passwords_in_args};
 pub use constants::*;
-pub use db_resolution::mask_database_url_public as mask_database_url;
+pub use db_resolution::{enable_session_pool_reuse, mask_database_url_public as mask_database_url};
+use dispatcher::state_unavailable;
 pub use dispatcher::{
-    bead_id_from_recommendation, dispatch_no_batch, dry_run_success, execute_request,
+    bead_id_from_recommendation, dispatch_no_batch, dry_run_plan, dry_run_success, execute_request,
     execute_request_no_batch, project_next_recommendation, CommandSuccess,
 };
-pub use doctor_checks::{check_command, check_database_connectivity};
+pub use doctor_checks::{
+    check_chaos, check_command, check_command_version, check_database_connectivity,
+    check_enum_drift, check_event_sinks, check_notifications, check_remote_executors,
+    check_scheduled_jobs, check_stage_parsers, check_stage_sandbox, environment_fingerprint,
+    explain_database_candidates,
+};
 pub use external_commands::{
     capture_stream_limited, run_external_json_command, run_external_json_command_with_ms,
     run_external_json_command_with_timeout, StreamCapture, MAX_EXTERNAL_OUTPUT_CAPTURE_BYTES,
@@ -50,11 +63,13 @@ pub use handler_delegates::{
     handle_status,
 };
 pub use input_parsing::{ParseError, ParseInput};
-pub use loop_executor::run_protocol_loop;
+pub use loop_executor::{run_protocol_loop, run_protocol_loop_with_format};
 pub use schema_loader::{
     current_repo_root, load_schema_sql, EMBEDDED_COORDINATOR_SCHEMA_REF,
     EMBEDDED_COORDINATOR_SCHEMA_SQL,
 };
+pub use validation::RequestLimits;
+pub use wire_format::{encode_envelope, write_envelope, WireFormat};
 
 #[derive(Debug, Clone, Deserialize, serde::Serialize)]
 pub struct ProtocolRequest {
@@ -69,13 +84,83 @@ fn bounded_history_limit(limit: Option<i64>) -> i64 {
     parsing::bounded_history_limit(limit, DEFAULT_HISTORY_LIMIT, MAX_HISTORY_LIMIT)
 }
 
+fn bounded_page_size(page_size: Option<i64>) -> i64 {
+    parsing::bounded_history_limit(page_size, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE).max(1)
+}
+
 /// # Errors
 /// Returns an error if the request parsing or execution fails.
 pub async fn process_protocol_line(line: &str) -> std::result::Result<(), SwarmError> {
-    let mut stdout = tokio::io::stdout();
+    let response = respond_to_frame(RequestFrame::Line(line.to_string())).await;
+    finish_response(response, None).await
+}
+
+/// Runs one request line and records its audit row without writing to
+/// stdout, so the CLI can render the envelope in a human format.
+pub async fn execute_protocol_line(line: &str) -> ProtocolEnvelope {
+    let (envelope, audit_write) = respond_to_line(line).await;
+    record_audit(audit_write, None).await;
+    envelope
+}
+
+/// # Errors
+/// Returns an error carrying the protocol message when the envelope is a failure.
+pub fn envelope_outcome(envelope: &ProtocolEnvelope) -> std::result::Result<(), SwarmError> {
+    if envelope.ok {
+        return Ok(());
+    }
+    Err(SwarmError::Internal(envelope.err.as_ref().map_or_else(
+        || "Unknown protocol error".to_string(),
+        |e| e.msg.clone(),
+    )))
+}
+
+/// A request's envelope, its audit row, and the format to answer in.
+type FrameResponse = (ProtocolEnvelope, PendingWrite, WireFormat);
+
+/// Runs one request without writing anything, so concurrent requests can
+/// have their envelopes written one at a time as they finish.
+async fn respond_to_frame(frame: RequestFrame) -> FrameResponse {
+    match frame {
+        RequestFrame::Line(line) => {
+            let (envelope, audit_write) = respond_to_line(&line).await;
+            (envelope, audit_write, WireFormat::Json)
+        }
+        RequestFrame::MessagePack(bytes) => {
+            let (envelope, audit_write) = respond_to_msgpack(&bytes).await;
+            (envelope, audit_write, WireFormat::MessagePack)
+        }
+        RequestFrame::Oversized {
+            format,
+            bytes,
+            max_bytes,
+        } => {
+            let (envelope, audit_write) = respond_to_oversized(bytes, max_bytes);
+            (envelope, audit_write, format)
+        }
+    }
+}
+
+/// Writes the envelope to stdout and records its audit row.
+///
+/// # Errors
+/// Returns an error if writing fails or the envelope is a failure.
+async fn finish_response(
+    (envelope, audit_write, format): FrameResponse,
+    audit_batch: Option<&WriteBatchHandle>,
+) -> std::result::Result<(), SwarmError> {
+    write_envelope(&mut tokio::io::stdout(), &envelope, format).await?;
+
+    record_audit(audit_write, audit_batch).await;
+    envelope_outcome(&envelope)
+}
+
+async fn respond_to_line(line: &str) -> (ProtocolEnvelope, PendingWrite) {
     let started = Instant::now();
     let maybe_rid = parsing::parse_rid(line);
-    let parsed = serde_json::from_str::<ProtocolRequest>(line).map_err(|err| {
+    let parsed = serde_json::from_str::<ProtocolRequest>(line);
+    crate::profiling::record(HotspotCategory::Serde, "parse_request", started.elapsed());
+    let parsed = parsed.map_err(|err| {
         ProtocolEnvelope::error(
             maybe_rid.clone(),
             code::INVALID.to_string(),
@@ -84,67 +169,170 @@ pub async fn process_protocol_line(line: &str) -> std::result::Result<(), SwarmE
         .with_fix("Ensure request is valid JSON with a 'cmd' field. Example: echo '{\"cmd\":\"doctor\"}' | swarm".to_string())
         .with_ctx(json!({"line": line}))
     });
+    respond_to_parsed(parsed, maybe_rid, json!({"raw": line}), started).await
+}
+
+/// Decodes a `MessagePack` request into the same [`ProtocolRequest`] the JSON
+/// path parses, so both encodings share validation and dispatch.
+async fn respond_to_msgpack(bytes: &[u8]) -> (ProtocolEnvelope, PendingWrite) {
+    let started = Instant::now();
+    let decoded = wire_format::decode_msgpack(bytes);
+    let maybe_rid = decoded
+        .as_ref()
+        .ok()
+        .and_then(|value| value.get("rid"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let raw = decoded.as_ref().map_or_else(
+        |_| json!({"bytes": bytes.len()}),
+        |value| json!({"raw": value}),
+    );
+    let parsed = decoded.and_then(|value| {
+        serde_json::from_value::<ProtocolRequest>(value).map_err(|err| err.to_string())
+    });
+    crate::profiling::record(HotspotCategory::Serde, "decode_request", started.elapsed());
+    let parsed = parsed
+        .map_err(|err| {
+            ProtocolEnvelope::error(
+                maybe_rid.clone(),
+                code::INVALID.to_string(),
+                format!("Invalid MessagePack request: {err}"),
+            )
+            .with_fix(
+                "Send each request as a 4-byte big-endian length followed by a MessagePack map with a 'cmd' field"
+                    .to_string(),
+            )
+            .with_ctx(json!({"bytes": bytes.len()}))
+        });
+    respond_to_parsed(parsed, maybe_rid, raw, started).await
+}
+
+/// Answers a request the loop refused to buffer; it was never parsed, so
+/// there is no `rid` to echo.
+fn respond_to_oversized(bytes: usize, max_bytes: usize) -> (ProtocolEnvelope, PendingWrite) {
+    let envelope = *validation::limit_exceeded(None, "max_line_bytes", max_bytes, bytes);
+    let audit_write = PendingWrite::Audit(CommandAuditRow {
+        cmd: "invalid".to_string(),
+        rid: None,
+        args: json!({"bytes": bytes}),
+        ok: false,
+        ms: 0,
+        error_code: Some(code::INVALID.to_string()),
+    });
+    (envelope.with_ms(0), audit_write)
+}
 
-    let (envelope, audit_cmd, audit_args) = match parsed {
+async fn respond_to_parsed(
+    parsed: std::result::Result<ProtocolRequest, ProtocolEnvelope>,
+    maybe_rid: Option<String>,
+    raw: Value,
+    started: Instant,
+) -> (ProtocolEnvelope, PendingWrite) {
+    let (envelope, audit_cmd, audit_args, traced) = match parsed {
         Ok(request) => {
+            let traced = trace::is_recording()
+                .then(|| serde_json::to_value(&request).unwrap_or_else(|_| raw.clone()));
             let command_name = request.cmd.clone();
             let command_args = Value::Object(request.args.clone());
             let rid = request.rid.clone();
-            let result = dispatcher::execute_request(request).await;
+            let encoding = request
+                .args
+                .get("accept_encoding")
+                .and_then(DataEncoding::negotiate);
+            let span = request_span(&request);
+            let result = dispatcher::execute_request(request)
+                .instrument(span.clone())
+                .await;
             let env = match result {
                 Ok(success) => ProtocolEnvelope::success(rid, success.data)
                     .with_next(success.next)
                     .with_state(success.state),
                 Err(failure) => *failure,
             };
-            (
-                env.with_ms(i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX)),
-                command_name,
-                command_args,
-            )
+            let env = match encoding {
+                Some(encoding) => {
+                    env.with_compressed_data(encoding, crate::config::compress_min_bytes_from_env())
+                }
+                None => env,
+            };
+            let duration_ms = i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX);
+            span.in_scope(|| {
+                tracing::info!(
+                    ok = env.ok,
+                    code = env.err.as_ref().map(|err| err.code.as_str()),
+                    duration_ms,
+                    "request finished"
+                );
+            });
+            (env.with_ms(duration_ms), command_name, command_args, traced)
         }
         Err(env) => (
             env.with_ms(i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX)),
             "invalid".to_string(),
-            json!({"raw": line}),
+            raw.clone(),
+            trace::is_recording().then_some(raw),
         ),
     };
-
-    let response_text = serde_json::to_string(&envelope).map_err(SwarmError::SerializationError)?;
-    stdout
-        .write_all(response_text.as_bytes())
-        .await
-        .map_err(SwarmError::IoError)?;
-    stdout.write_all(b"\n").await.map_err(SwarmError::IoError)?;
+    if let Some(mut traced) = traced {
+        audit::mask_passwords_in_args(&mut traced);
+        trace::record(traced, &envelope);
+    }
 
     let mut audit_args = audit_args;
     audit::mask_passwords_in_args(&mut audit_args);
 
-    let candidates = crate::config::database_url_candidates_for_cli();
-    let audit_result = audit::audit_request(
-        &audit_cmd,
-        maybe_rid.as_deref(),
-        audit_args,
-        envelope.ok,
-        started.elapsed().as_millis() as u64,
-        envelope.err.as_ref().map(|e| e.code.as_str()),
-        &candidates,
-        database_connect_timeout_ms(),
+    let audit_write = PendingWrite::Audit(CommandAuditRow {
+        cmd: audit_cmd,
+        rid: maybe_rid,
+        args: audit_args,
+        ok: envelope.ok,
+        ms: started.elapsed().as_millis() as u64,
+        error_code: envelope.err.as_ref().map(|e| e.code.clone()),
+    });
+    (envelope, audit_write)
+}
+
+/// Span every event a request's handler emits is logged in.
+fn request_span(request: &ProtocolRequest) -> tracing::Span {
+    tracing::info_span!(
+        "request",
+        rid = request.rid.as_deref(),
+        cmd = request.cmd.as_str(),
+        repo = repo_id_from_request(request).value(),
+        agent = request_agent(request).as_deref(),
     )
-    .await;
+}
 
-    if let Err(e) = audit_result {
-        eprintln!("WARN: Audit trail recording failed: {e}");
-    }
+/// Agent a request acts for: `agent_id`, a lock's `agent`, or the `id` of
+/// the commands that run an agent.
+fn request_agent(request: &ProtocolRequest) -> Option<String> {
+    let keys: &[&str] = match request.cmd.as_str() {
+        "agent" | "run" | "run-once" | "smoke" => &["id"],
+        _ => &["agent_id", "agent"],
+    };
+    keys.iter().find_map(|key| match request.args.get(*key)? {
+        Value::Number(number) => Some(number.to_string()),
+        Value::String(text) => Some(text.clone()),
+        _ => None,
+    })
+}
 
-    if !envelope.ok {
-        return Err(SwarmError::Internal(envelope.err.as_ref().map_or_else(
-            || "Unknown protocol error".to_string(),
-            |e| e.msg.clone(),
-        )));
-    }
+async fn record_audit(audit_write: PendingWrite, audit_batch: Option<&WriteBatchHandle>) {
+    let pending_write = match audit_batch {
+        Some(batch) => batch.enqueue(audit_write).err().map(|write| *write),
+        None => Some(audit_write),
+    };
+    let audit_result = match pending_write {
+        Some(write) => {
+            let candidates = crate::config::database_url_candidates_for_cli();
+            audit::audit_request(write, &candidates, database_connect_timeout_ms()).await
+        }
+        None => Ok(()),
+    };
 
-    Ok(())
+    if let Err(e) = audit_result {
+        tracing::warn!(error = %e, "Audit trail recording failed");
+    }
 }
 
 fn database_connect_timeout_ms() -> u64 {
@@ -168,6 +356,18 @@ pub(in crate::protocol_runtime) async fn db_from_request(
     .await
 }
 
+pub(in crate::protocol_runtime) async fn read_db_from_request(
+    request: &ProtocolRequest,
+) -> std::result::Result<crate::SwarmDb, Box<ProtocolEnvelope>> {
+    db_resolution::read_db_from_request(
+        request,
+        DEFAULT_DB_CONNECT_TIMEOUT_MS,
+        MIN_DB_CONNECT_TIMEOUT_MS,
+        MAX_DB_CONNECT_TIMEOUT_MS,
+    )
+    .await
+}
+
 pub(in crate::protocol_runtime) async fn resolve_database_url_for_init(
     request: &ProtocolRequest,
 ) -> std::result::Result<String, Box<ProtocolEnvelope>> {
@@ -216,6 +416,14 @@ pub(in crate::protocol_runtime) fn dry_flag(request: &ProtocolRequest) -> bool {
     helpers::dry_flag(request)
 }
 
+pub(in crate::protocol_runtime) fn next_page_cursor(
+    last_seq: Option<i64>,
+    returned: usize,
+    page_size: i64,
+) -> Option<i64> {
+    helpers::next_page_cursor(last_seq, returned, page_size)
+}
+
 pub(in crate::protocol_runtime) fn repo_id_from_request(
     request: &ProtocolRequest,
 ) -> crate::RepoId {
@@ -1,25 +1,25 @@
-use crate::config::database_url_candidates_for_cli;
+use crate::config::{database_connect_options_for_cli, database_url_candidates_for_cli};
+use crate::db::PendingWrite;
 use crate::SwarmError;
 
-#[allow(clippy::too_many_arguments)]
 /// # Errors
 /// Returns an error if the database connection or operation fails.
 pub async fn audit_request(
-    cmd: &str,
-    rid: Option<&str>,
-    args: serde_json::Value,
-    ok: bool,
-    ms: u64,
-    error_code: Option<&str>,
+    write: PendingWrite,
     candidates: &[String],
     timeout_ms: u64,
 ) -> std::result::Result<(), SwarmError> {
+    let scope = super::db_resolution::DbScope::from_env();
+    let options = database_connect_options_for_cli().unwrap_or_default();
     let (connected, _failures) =
-        super::db_resolution::try_connect_candidates(candidates, timeout_ms).await;
+        super::db_resolution::try_connect_candidates(candidates, timeout_ms, &scope, &options)
+            .await;
     match connected {
+        // A one-shot command has no batcher to flush its query latency
+        // later, so it goes out with the audit row.
         Some((db, _used_url)) => {
-            db.record_command_audit(cmd, rid, args, ok, ms, error_code)
-                .await
+            db.write_now(write).await?;
+            db.flush_query_latency().await.map(|_rows| ())
         }
         None => Err(SwarmError::DatabaseError(
             "Audit database connection failed: no candidates succeeded".to_string(),
@@ -4,3 +4,24 @@ pub const MAX_DB_CONNECT_TIMEOUT_MS: u64 = 30_000;
 pub const DEFAULT_HISTORY_LIMIT: i64 = 100;
 pub const MAX_HISTORY_LIMIT: i64 = 10_000;
 pub const MAX_REGISTER_COUNT: u32 = 100;
+pub const DEFAULT_PAGE_SIZE: i64 = 200;
+pub const MAX_PAGE_SIZE: i64 = 1_000;
+pub const MAX_LOAD_PROFILE_CONCURRENCY: u32 = 64;
+pub const BULK_WRITE_CONCURRENCY: usize = 16;
+pub const DEFAULT_MAX_LINE_BYTES: usize = 8 * 1024 * 1024;
+pub const DEFAULT_MAX_BATCH_OPS: usize = 256;
+pub const DEFAULT_MAX_ARGS_DEPTH: usize = 32;
+pub const DEFAULT_MAX_ARGS_BYTES: usize = 4 * 1024 * 1024;
+pub const DEFAULT_COMMAND_TIMEOUT_MS: u64 = 60_000;
+pub const MAX_COMMAND_TIMEOUT_MS: u64 = 86_400_000;
+pub const DEFAULT_SESSION_CONCURRENCY: usize = 1;
+pub const MAX_SESSION_CONCURRENCY: usize = 64;
+pub const DEFAULT_SHUTDOWN_GRACE_MS: u64 = 30_000;
+pub const DEFAULT_COMPRESS_MIN_BYTES: usize = 64 * 1024;
+pub const DEFAULT_MAX_ARTIFACT_BYTES: usize = 10 * 1024 * 1024;
+pub const DEFAULT_LOCAL_DB_CONTAINER: &str = "shitty-swarm-manager-db";
+pub const DEFAULT_LOCAL_DB_PORT: u16 = 5437;
+pub const DEFAULT_LOCAL_DB_USER: &str = "shitty_swarm_manager";
+pub const DEFAULT_LOCAL_DB_NAME: &str = "shitty_swarm_manager_db";
+pub const DEFAULT_LOCAL_DB_POSTGRES_MAJOR: u32 = 16;
+pub const DEFAULT_LOCAL_DB_LOG_TAIL: u32 = 100;
@@ -1,9 +1,211 @@
 use super::parsing;
 use super::ProtocolRequest;
-use crate::config::database_url_candidates_for_cli;
+use crate::config::{
+    artifact_signer_from_env, database_connect_options_for_cli, database_schema_for_cli,
+    database_url_candidates_for_cli, read_replica_url_for_cli, stage_summarizer_from_env,
+    tenant_for_cli,
+};
+use crate::db::swarm_db::connect_with_backoff;
+use crate::db::{ConnectOptions, ReconnectPolicy, SslMode, WriteBatchHandle};
 use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::tenant_schema;
 use crate::{code, RepoId, SwarmDb};
 use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Where a request's tables live.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum DbScope {
+    /// The `public` schema.
+    Default,
+    /// `SWARM_DB_SCHEMA`, created on first connect.
+    Schema(String),
+    /// A tenant's schema, which `tenant create` must have set up.
+    Tenant { name: String, schema: String },
+}
+
+impl DbScope {
+    /// `SWARM_TENANT`, else `SWARM_DB_SCHEMA`, for connections not made on
+    /// behalf of one request. An invalid tenant name falls back to `public`.
+    pub(super) fn from_env() -> Self {
+        match tenant_for_cli() {
+            Some(name) => {
+                tenant_schema(&name).map_or(Self::Default, |schema| Self::Tenant { name, schema })
+            }
+            None => database_schema_for_cli().map_or(Self::Default, Self::Schema),
+        }
+    }
+
+    pub(super) fn schema(&self) -> Option<&str> {
+        match self {
+            Self::Default => None,
+            Self::Schema(schema) | Self::Tenant { schema, .. } => Some(schema),
+        }
+    }
+
+    async fn connect(
+        &self,
+        url: &str,
+        timeout_ms: u64,
+        options: &ConnectOptions,
+    ) -> crate::Result<SwarmDb> {
+        let db = SwarmDb::new_with_options(url, self.schema(), Some(timeout_ms), options).await?;
+        if let Self::Schema(_) = self {
+            db.create_schema().await?;
+        }
+        Ok(db)
+    }
+}
+
+/// `.swarm/config.toml` connection options, with the request's `sslmode`,
+/// `ssl_root_cert` and `statement_timeout_ms` taking precedence.
+pub(super) fn connect_options_for_request(
+    request: &ProtocolRequest,
+) -> std::result::Result<ConnectOptions, Box<ProtocolEnvelope>> {
+    let invalid = |msg: String| {
+        Box::new(
+            ProtocolEnvelope::error(request.rid.clone(), code::INVALID.to_string(), msg)
+                .with_fix(
+                    "Use sslmode disable|allow|prefer|require|verify-ca|verify-full and an integer statement_timeout_ms"
+                        .to_string(),
+                ),
+        )
+    };
+    let mut options = database_connect_options_for_cli()
+        .map_err(|error| super::to_protocol_failure(error, request.rid.clone()))?;
+    let text = |key: &str| {
+        request
+            .args
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+    if let Some(mode) = text("sslmode") {
+        options.ssl_mode = Some(SslMode::parse(mode).map_err(|error| invalid(error.to_string()))?);
+    }
+    if let Some(path) = text("ssl_root_cert") {
+        options.ssl_root_cert = Some(path.into());
+    }
+    if let Some(value) = request.args.get("statement_timeout_ms") {
+        options.statement_timeout_ms = Some(value.as_u64().ok_or_else(|| {
+            invalid(format!(
+                "statement_timeout_ms must be a non-negative integer, got {value}"
+            ))
+        })?);
+    }
+    Ok(options)
+}
+
+/// The request's `tenant`, else the scope from the environment.
+pub(super) fn db_scope_for_request(
+    request: &ProtocolRequest,
+) -> std::result::Result<DbScope, Box<ProtocolEnvelope>> {
+    let tenant = request
+        .args
+        .get("tenant")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string);
+    match tenant {
+        Some(name) => tenant_schema(&name)
+            .map(|schema| DbScope::Tenant { name, schema })
+            .map_err(|error| {
+                Box::new(
+                    ProtocolEnvelope::error(request.rid.clone(), code::INVALID.to_string(), error)
+                        .with_fix("swarm tenant list".to_string()),
+                )
+            }),
+        None => Ok(DbScope::from_env()),
+    }
+}
+
+/// Pools kept alive for the rest of the process, keyed by schema and database URL.
+/// Unset unless a long-lived front end (the REPL) opts in, so one-shot CLI
+/// calls and the stdin protocol loop keep connecting per request.
+static SESSION_POOLS: OnceLock<Mutex<HashMap<String, SwarmDb>>> = OnceLock::new();
+
+/// Reuse connected pools across requests for the rest of this process.
+pub fn enable_session_pool_reuse() {
+    let _ = SESSION_POOLS.set(Mutex::new(HashMap::new()));
+}
+
+/// Whether requests share the pools in [`SESSION_POOLS`], so pool counters
+/// describe more than the request reading them.
+pub(super) fn session_pool_reuse_enabled() -> bool {
+    SESSION_POOLS.get().is_some()
+}
+
+fn session_pool_key(url: &str, scope: &DbScope, options: &ConnectOptions) -> String {
+    format!(
+        "{}|{}|{url}",
+        scope.schema().unwrap_or_default(),
+        options.cache_key()
+    )
+}
+
+fn cached_session_pool(
+    candidates: &[String],
+    scope: &DbScope,
+    options: &ConnectOptions,
+) -> Option<(SwarmDb, String)> {
+    let pools = SESSION_POOLS.get()?.lock().ok()?;
+    candidates.iter().find_map(|candidate| {
+        pools
+            .get(&session_pool_key(candidate, scope, options))
+            .map(|db| (db.clone(), candidate.clone()))
+    })
+}
+
+/// The protocol session's write-behind batcher, keyed like [`SESSION_POOLS`]
+/// by the database it writes to. Requests that connect to the same database
+/// queue their execution events on it instead of inserting them one by one.
+static SESSION_WRITE_BATCH: OnceLock<Mutex<Option<(String, WriteBatchHandle)>>> = OnceLock::new();
+
+/// Routes execution events of requests that connect to `url` with `scope`
+/// and `options` through `handle`.
+pub(super) fn set_session_write_batch(
+    url: &str,
+    scope: &DbScope,
+    options: &ConnectOptions,
+    handle: WriteBatchHandle,
+) {
+    let slot = SESSION_WRITE_BATCH.get_or_init(|| Mutex::new(None));
+    if let Ok(mut slot) = slot.lock() {
+        *slot = Some((session_pool_key(url, scope, options), handle));
+    }
+}
+
+/// Stops routing events through the session batcher, before it shuts down.
+pub(super) fn clear_session_write_batch() {
+    if let Some(mut slot) = SESSION_WRITE_BATCH.get().and_then(|slot| slot.lock().ok()) {
+        *slot = None;
+    }
+}
+
+fn session_write_batch(
+    url: &str,
+    scope: &DbScope,
+    options: &ConnectOptions,
+) -> Option<WriteBatchHandle> {
+    let key = session_pool_key(url, scope, options);
+    SESSION_WRITE_BATCH
+        .get()?
+        .lock()
+        .ok()?
+        .as_ref()
+        .filter(|(current, _)| *current == key)
+        .map(|(_, handle)| handle.clone())
+}
+
+fn remember_session_pool(url: &str, scope: &DbScope, options: &ConnectOptions, db: &SwarmDb) {
+    if let Some(mut pools) = SESSION_POOLS.get().and_then(|pools| pools.lock().ok()) {
+        pools.insert(session_pool_key(url, scope, options), db.clone());
+    }
+}
 
 pub(super) async fn db_from_request(
     request: &ProtocolRequest,
@@ -25,7 +227,51 @@ pub(super) async fn db_from_request(
         min_timeout_ms,
         max_timeout_ms,
     )?;
-    connect_using_candidates(candidates, timeout_ms, request.rid.clone()).await
+    let scope = db_scope_for_request(request)?;
+    let options = connect_options_for_request(request)?;
+    let (db, connected_url) = connect_using_candidates(
+        candidates,
+        timeout_ms,
+        &scope,
+        &options,
+        request.rid.clone(),
+    )
+    .await?;
+    if let DbScope::Tenant { name, .. } = &scope {
+        ensure_tenant_registered(&db, name, request.rid.clone()).await?;
+    }
+    let db = match session_write_batch(&connected_url, &scope, &options) {
+        Some(handle) => db.with_write_batch(handle),
+        None => db,
+    };
+    let db = db.with_stage_summarizer(stage_summarizer_from_env());
+    let signer = artifact_signer_from_env()
+        .map_err(|error| super::to_protocol_failure(error, request.rid.clone()))?;
+    if let Some(signer) = signer {
+        return Ok(db.with_artifact_signer(signer));
+    }
+    Ok(db)
+}
+
+pub(super) async fn read_db_from_request(
+    request: &ProtocolRequest,
+    default_timeout_ms: u64,
+    min_timeout_ms: u64,
+    max_timeout_ms: u64,
+) -> std::result::Result<SwarmDb, Box<ProtocolEnvelope>> {
+    let db = db_from_request(request, default_timeout_ms, min_timeout_ms, max_timeout_ms).await?;
+    match read_replica_url_for_cli() {
+        Some(url) => {
+            let timeout_ms = parsing::request_connect_timeout_ms(
+                request,
+                default_timeout_ms,
+                min_timeout_ms,
+                max_timeout_ms,
+            )?;
+            Ok(db.with_read_replica(&url, Some(timeout_ms)).await)
+        }
+        None => Ok(db),
+    }
 }
 
 pub(super) async fn resolve_database_url_for_init(
@@ -59,7 +305,9 @@ pub(super) async fn resolve_database_url_for_init(
         min_timeout_ms,
         max_timeout_ms,
     )?;
-    let (connected, failures) = try_connect_candidates(&candidates, timeout_ms).await;
+    let options = connect_options_for_request(request)?;
+    let (connected, failures) =
+        try_connect_candidates(&candidates, timeout_ms, &DbScope::Default, &options).await;
     if let Some((_db, connected_url)) = connected {
         return Ok(connected_url);
     }
@@ -80,15 +328,28 @@ pub(super) async fn resolve_database_url_for_init(
     ))
 }
 
+/// Connects to the first reachable candidate. While none is, the whole list
+/// is retried with exponential backoff for up to `timeout_ms`, so a request
+/// made during a Postgres restart waits for it instead of failing.
 pub(super) async fn connect_using_candidates(
     candidates: Vec<String>,
     timeout_ms: u64,
+    scope: &DbScope,
+    options: &ConnectOptions,
     rid: Option<String>,
-) -> std::result::Result<SwarmDb, Box<ProtocolEnvelope>> {
-    let (connected, failures) = try_connect_candidates(&candidates, timeout_ms).await;
-    if let Some((db, _connected_url)) = connected {
-        return Ok(db);
-    }
+) -> std::result::Result<(SwarmDb, String), Box<ProtocolEnvelope>> {
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+    let failures = match connect_with_backoff(&ReconnectPolicy::default(), deadline, || async {
+        match try_connect_candidates(&candidates, timeout_ms, scope, options).await {
+            (Some(connected), _) => Ok(connected),
+            (None, failures) => Err(failures),
+        }
+    })
+    .await
+    {
+        Ok(connected) => return Ok(connected),
+        Err(failures) => failures,
+    };
 
     let masked = candidates
         .iter()
@@ -112,12 +373,21 @@ pub(super) async fn connect_using_candidates(
 pub(super) async fn try_connect_candidates(
     candidates: &[String],
     timeout_ms: u64,
+    scope: &DbScope,
+    options: &ConnectOptions,
 ) -> (Option<(SwarmDb, String)>, Vec<String>) {
+    if let Some(cached) = cached_session_pool(candidates, scope, options) {
+        return (Some(cached), Vec::new());
+    }
+
     let mut failures = Vec::new();
 
     for candidate in candidates {
-        match SwarmDb::new_with_timeout(candidate, Some(timeout_ms)).await {
-            Ok(db) => return (Some((db, candidate.clone())), failures),
+        match scope.connect(candidate, timeout_ms, options).await {
+            Ok(db) => {
+                remember_session_pool(candidate, scope, options, &db);
+                return (Some((db, candidate.clone())), failures);
+            }
             Err(err) => failures.push(format!("{}: {}", mask_database_url(candidate), err)),
         }
     }
@@ -125,6 +395,26 @@ pub(super) async fn try_connect_candidates(
     (None, failures)
 }
 
+async fn ensure_tenant_registered(
+    db: &SwarmDb,
+    name: &str,
+    rid: Option<String>,
+) -> std::result::Result<(), Box<ProtocolEnvelope>> {
+    match db.tenant_exists(name).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(Box::new(
+            ProtocolEnvelope::error(
+                rid,
+                code::NOTFOUND.to_string(),
+                format!("Tenant {name} does not exist"),
+            )
+            .with_fix(format!("swarm tenant create --name {name}"))
+            .with_ctx(json!({"tenant": name})),
+        )),
+        Err(error) => Err(super::to_protocol_failure(error, rid)),
+    }
+}
+
 pub(super) fn compose_database_url_candidates(
     explicit_database_url: Option<&str>,
     discovered_candidates: Vec<String>,
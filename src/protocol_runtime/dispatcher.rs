@@ -1,8 +1,34 @@
+use super::constants::{DEFAULT_COMMAND_TIMEOUT_MS, MAX_COMMAND_TIMEOUT_MS};
+use super::projection::apply_projection;
 use super::ProtocolRequest;
 use crate::code;
 use crate::protocol_envelope::ProtocolEnvelope;
 use crate::protocol_runtime::handlers;
+use crate::types::{DryRunConflict, DryRunConflictKind};
 use serde_json::json;
+use std::time::Duration;
+
+/// Commands that run stages, wait on the landing queue, or loop by design.
+/// They only get a deadline when the request sets `timeout_ms`.
+const LONG_RUNNING_COMMANDS: &[&str] = &[
+    "agent",
+    "run",
+    "run-all",
+    "run-once",
+    "serve",
+    "smoke",
+    "qa",
+    "land",
+    "sync-backlog",
+    "monitor",
+    "init",
+    "init-local-db",
+    "localdb",
+    "load-profile",
+    "replay",
+    "profile",
+    "maintenance",
+];
 
 pub struct CommandSuccess {
     pub data: serde_json::Value,
@@ -43,6 +69,10 @@ pub async fn execute_request(
     request: ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
     super::validation::validate_request_null_bytes(&request)?;
+    super::validation::validate_request_limits(
+        &request,
+        &crate::config::request_limits_from_env(),
+    )?;
 
     match request.cmd.as_str() {
         "batch" => handlers::batch_ops::handle_batch(&request).await,
@@ -60,17 +90,80 @@ pub async fn execute_request_no_batch(
     dispatch_request(&request).await
 }
 
+/// Runs the command under its deadline; a handler still running when the
+/// deadline passes is dropped and answered with `TIMEOUT`.
+///
+/// # Errors
+/// Returns an error if `timeout_ms` is invalid, the deadline passes, or
+/// command execution fails.
 pub async fn dispatch_request(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
     let cmd = request.cmd.as_str();
+    let deadline = command_timeout(request)?;
 
-    match cmd {
-        "batch" => handlers::batch_ops::handle_batch(request).await,
-        other => dispatch_no_batch(request, other).await,
+    let dispatched = async {
+        match cmd {
+            "batch" => handlers::batch_ops::handle_batch(request).await,
+            other => dispatch_no_batch(request, other).await,
+        }
+    };
+    match deadline {
+        Some(timeout) => tokio::time::timeout(timeout, dispatched)
+            .await
+            .unwrap_or_else(|_| Err(timed_out(request, timeout))),
+        None => dispatched.await,
     }
 }
 
+/// `timeout_ms` from the request, else the default for commands that are
+/// not long-running. `load-profile` keeps `timeout_ms` as its per-operation
+/// timeout, so it never gets a dispatcher deadline.
+pub(super) fn command_timeout(
+    request: &ProtocolRequest,
+) -> std::result::Result<Option<Duration>, Box<ProtocolEnvelope>> {
+    let cmd = request.cmd.as_str();
+    if cmd == "load-profile" {
+        return Ok(None);
+    }
+    let Some(raw) = request.args.get("timeout_ms") else {
+        return Ok((!LONG_RUNNING_COMMANDS.contains(&cmd))
+            .then(|| Duration::from_millis(DEFAULT_COMMAND_TIMEOUT_MS)));
+    };
+    match raw.as_u64() {
+        Some(ms) if (1..=MAX_COMMAND_TIMEOUT_MS).contains(&ms) => {
+            Ok(Some(Duration::from_millis(ms)))
+        }
+        _ => Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                format!("timeout_ms must be an integer between 1 and {MAX_COMMAND_TIMEOUT_MS}"),
+            )
+            .with_fix(format!(
+                "Use timeout_ms between 1 and {MAX_COMMAND_TIMEOUT_MS}, or omit it for the {DEFAULT_COMMAND_TIMEOUT_MS}ms default"
+            ))
+            .with_ctx(json!({"timeout_ms": raw})),
+        )),
+    }
+}
+
+fn timed_out(request: &ProtocolRequest, timeout: Duration) -> Box<ProtocolEnvelope> {
+    let timeout_ms = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+    Box::new(
+        ProtocolEnvelope::error(
+            request.rid.clone(),
+            code::TIMEOUT.to_string(),
+            format!("{} did not finish within {timeout_ms}ms", request.cmd),
+        )
+        .with_fix(
+            "Retry with a larger timeout_ms, or run `swarm doctor` to find a wedged dependency"
+                .to_string(),
+        )
+        .with_ctx(json!({"cmd": request.cmd, "timeout_ms": timeout_ms})),
+    )
+}
+
 #[allow(clippy::large_stack_frames)]
 /// # Errors
 /// Returns an error if command execution fails.
@@ -80,31 +173,93 @@ pub async fn dispatch_no_batch(
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
     match cmd {
         "?" | "help" => handlers::batch_ops::handle_help(request).await,
-        "state" => handlers::state_ops::handle_state(request).await,
+        "state" => handlers::state_ops::handle_state(request)
+            .await
+            .and_then(|success| apply_projection(request, success)),
         "history" => handlers::state_ops::handle_history(request).await,
         "lock" => handlers::lock_ops::handle_lock(request).await,
         "unlock" => handlers::lock_ops::handle_unlock(request).await,
+        "locks" => handlers::lock_ops::handle_locks(request).await,
         "agents" => handlers::state_ops::handle_agents(request).await,
         "broadcast" => handlers::messaging_ops::handle_broadcast(request).await,
-        "monitor" => super::handle_monitor(request).await,
+        "monitor" => super::handle_monitor(request)
+            .await
+            .and_then(|success| apply_projection(request, success)),
         "register" => super::handle_register(request).await,
         "agent" => super::handle_agent(request).await,
-        "status" => super::handle_status(request).await,
+        "status" => super::handle_status(request)
+            .await
+            .and_then(|success| apply_projection(request, success)),
+        "top" => handlers::monitoring::handle_top(request).await,
+        "forecast" => handlers::forecast::handle_forecast(request).await,
         "next" => handlers::qa_ops::handle_next(request).await,
         "claim-next" => super::handle_claim_next(request).await,
+        "accept-claim" => handlers::reservation::handle_accept_claim(request).await,
+        "reject-claim" => handlers::reservation::handle_reject_claim(request).await,
         "assign" => super::handle_assign(request).await,
+        "cancel" => handlers::cancel::handle_cancel(request).await,
+        "takeover" => handlers::takeover::handle_takeover(request).await,
+        "recover" => handlers::recover::handle_recover(request).await,
+        "run" => handlers::orchestration::handle_run(request).await,
+        "run-all" => handlers::orchestration::handle_run_all(request).await,
         "run-once" => super::handle_run_once(request).await,
         "qa" => handlers::qa_ops::handle_qa(request).await,
         "resume" => super::handle_resume(request).await,
         "resume-context" => super::handle_resume_context(request).await,
+        "context" => handlers::context::handle_context(request).await,
+        "record-symbols" => handlers::symbols::handle_record_symbols(request).await,
         "artifacts" => super::handle_artifacts(request).await,
+        "artifact" => handlers::artifacts::handle_artifact(request).await,
+        "diff" => handlers::diff::handle_diff(request).await,
+        "replay" => handlers::replay::handle_replay(request).await,
+        "explain-transition" => {
+            handlers::explain_transition::handle_explain_transition(request).await
+        }
+        "verify" => handlers::verify::handle_verify(request).await,
+        "attest" => handlers::attest::handle_attest(request).await,
+        "env-diff" => handlers::env_diff::handle_env_diff(request).await,
+        "bead" => handlers::bead::handle_bead(request).await,
+        "enqueue" => handlers::backlog::handle_enqueue(request).await,
+        "sync-backlog" => handlers::backlog::handle_sync_backlog(request).await,
+        "backlog" => handlers::backlog::handle_backlog(request).await,
+        "sync" => handlers::sync::handle_sync(request).await,
+        "events" => handlers::events::handle_events(request).await,
+        "chaos" => handlers::chaos::handle_chaos(request).await,
+        "config" => handlers::config::handle_config(request).await,
+        "labels" => handlers::labels::handle_labels(request).await,
+        "announcements" => handlers::announcements::handle_announcements(request).await,
+        "jobs" => handlers::jobs::handle_jobs(request).await,
+        "serve" => handlers::jobs::handle_serve(request).await,
+        "notify" => handlers::notify::handle_notify(request).await,
+        "profile" => handlers::profile::handle_profile(request).await,
+        "perf" => handlers::perf::handle_perf(request).await,
+        "maintenance" => handlers::maintenance::handle_maintenance(request).await,
         "release" => super::handle_release(request).await,
+        "quarantine" => handlers::quarantine::handle_quarantine(request).await,
+        "unquarantine" => handlers::quarantine::handle_unquarantine(request).await,
+        "land" => handlers::landing::handle_land(request).await,
+        "workspace" => handlers::workspace::handle_workspace(request).await,
+        "session" => handlers::session::handle_session(request).await,
+        "kv" => handlers::kv::handle_kv(request).await,
+        "blackboard" => handlers::blackboard::handle_blackboard(request).await,
+        "review" => handlers::review::handle_review(request).await,
+        "approve" => handlers::approve::handle_approve(request).await,
+        "reissue-approval" => handlers::approve::handle_reissue_approval(request).await,
         "init-db" => handlers::swarm_ops::handle_init_db(request).await,
         "init-local-db" => handlers::swarm_ops::handle_init_local_db(request).await,
+        "localdb" => handlers::localdb::handle_localdb(request).await,
+        "tenant" => handlers::tenant::handle_tenant(request).await,
+        "undelete" => handlers::undelete::handle_undelete(request).await,
         "spawn-prompts" => super::handle_spawn_prompts(request).await,
         "smoke" => super::handle_smoke(request).await,
         "prompt" => super::handle_prompt(request).await,
         "doctor" => super::handle_doctor(request).await,
+        "db-health" => handlers::doctor::handle_db_health(request).await,
+        "healthz" => handlers::doctor::handle_healthz(request).await,
+        "invariants" => handlers::invariants::handle_invariants(request).await,
+        "costs" => handlers::costs::handle_costs(request).await,
+        "report-usage" => handlers::report_usage::handle_report_usage(request).await,
+        "report-coverage" => handlers::coverage::handle_report_coverage(request).await,
         "load-profile" => super::handle_load_profile(request).await,
         "bootstrap" => handlers::swarm_ops::handle_bootstrap(request).await,
         "init" => handlers::swarm_ops::handle_init(request).await,
@@ -114,9 +269,13 @@ pub async fn dispatch_no_batch(
                 code::INVALID.to_string(),
                 format!("Unknown command: {other}"),
             )
-            .with_fix(
-                "Use a valid command: init, doctor, status, next, claim-next, assign, run-ononce, qa, resume, artifacts, resume-context, agent, smoke, prompt, register, release, monitor, init-db, init-local-db, spawn-prompts, batch, bootstrap, state, or ?/help for help".to_string()
-            )
+            .with_fix(format!(
+                "Use a valid command: {}, or ?/help for help",
+                crate::cli::command_names()
+                    .filter(|name| *name != "help")
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
             .with_ctx(json!({"cmd": other})),
         )),
     }
@@ -140,3 +299,61 @@ pub fn dry_run_success(
         state: serde_json::json!({"total": 0, "active": 0}),
     }
 }
+
+/// A dry run computed from current state. `would_do` names the rows and
+/// commands the real run would touch and `conflicts` what would stop it;
+/// nothing is written.
+pub async fn dry_run_plan(
+    request: &ProtocolRequest,
+    steps: Vec<serde_json::Value>,
+    conflicts: Vec<DryRunConflict>,
+    side_effects: Vec<String>,
+    next: &str,
+) -> CommandSuccess {
+    CommandSuccess {
+        data: serde_json::json!({
+            "dry": true,
+            "would_do": steps,
+            "would_succeed": conflicts.is_empty(),
+            "conflicts": conflicts,
+            "reversible": true,
+            "side_effects": side_effects,
+        }),
+        next: next.to_string(),
+        state: super::minimal_state_for_request(request).await,
+    }
+}
+
+/// The conflict a dry run reports when state it needs cannot be read.
+pub(in crate::protocol_runtime) fn state_unavailable(
+    envelope: &ProtocolEnvelope,
+) -> DryRunConflict {
+    DryRunConflict::new(
+        DryRunConflictKind::StateUnavailable,
+        envelope.err.as_ref().map_or_else(
+            || "State could not be read".to_string(),
+            |err| err.msg.clone(),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LONG_RUNNING_COMMANDS;
+
+    #[test]
+    fn given_cli_reference_when_listing_commands_without_deadline_then_every_long_running_command_is_named(
+    ) {
+        let reference = include_str!("../../docs/CLI_REFERENCE.md");
+        let paragraph = reference
+            .split("\n\n")
+            .find(|paragraph| paragraph.starts_with("Every request may set `timeout_ms`"))
+            .unwrap_or_default();
+
+        let missing = LONG_RUNNING_COMMANDS
+            .iter()
+            .filter(|cmd| !paragraph.contains(&format!("`{cmd}`")))
+            .collect::<Vec<_>>();
+        assert!(missing.is_empty(), "undocumented: {missing:?}");
+    }
+}
@@ -1,4 +1,9 @@
 use super::ProtocolRequest;
+use crate::config::database_url_discovery_for_cli;
+use crate::stage_executors::BackendKind;
+use crate::types::{EnvironmentFingerprint, ToolVersion, FINGERPRINT_TOOLS};
+use crate::url_discovery::{CandidateSource, UrlCandidate};
+use crate::SwarmDb;
 use serde_json::json;
 use tokio::process::Command;
 
@@ -24,6 +29,262 @@ pub async fn check_command(command: &str) -> serde_json::Value {
     }
 }
 
+/// First line `command --version` prints, or `None` when the command is
+/// missing or fails.
+pub async fn check_command_version(command: &str) -> ToolVersion {
+    let version = Command::new("bash")
+        .arg("-lc")
+        .arg(format!("{command} --version"))
+        .output()
+        .await
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(str::to_string)
+        });
+    ToolVersion {
+        name: command.to_string(),
+        version,
+    }
+}
+
+/// Host platform plus the version of each of [`FINGERPRINT_TOOLS`], taken
+/// when a stage starts.
+pub async fn environment_fingerprint() -> EnvironmentFingerprint {
+    let mut tools = Vec::with_capacity(FINGERPRINT_TOOLS.len());
+    for tool in FINGERPRINT_TOOLS {
+        tools.push(check_command_version(tool).await);
+    }
+    EnvironmentFingerprint {
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        tools,
+    }
+}
+
+/// Validates `SWARM_STAGE_SANDBOX` and, for container backends, that the
+/// runtime is installed.
+pub async fn check_stage_sandbox() -> serde_json::Value {
+    match crate::config::stage_sandbox_from_env() {
+        Ok(sandbox) => match sandbox.backend {
+            BackendKind::Local => {
+                json!({"name": "stage_sandbox", "ok": true, "backend": sandbox.backend.as_str()})
+            }
+            BackendKind::Docker | BackendKind::Podman => {
+                let runtime = check_command(sandbox.backend.as_str()).await;
+                json!({
+                    "name": "stage_sandbox",
+                    "ok": runtime["ok"],
+                    "backend": sandbox.backend.as_str(),
+                    "fix": runtime["fix"],
+                })
+            }
+        },
+        Err(error) => json!({
+            "name": "stage_sandbox",
+            "ok": false,
+            "fix": format!("Fix SWARM_STAGE_SANDBOX: {error}"),
+        }),
+    }
+}
+
+/// Validates `SWARM_REMOTE_EXECUTORS` and, when builders are configured,
+/// that `ssh` is installed. Builders themselves are not contacted.
+pub async fn check_remote_executors() -> serde_json::Value {
+    match crate::config::remote_executors_from_env() {
+        Ok(remote) if remote.builders.is_empty() => {
+            json!({"name": "remote_executors", "ok": true, "builders": 0})
+        }
+        Ok(remote) => {
+            let ssh = check_command("ssh").await;
+            json!({
+                "name": "remote_executors",
+                "ok": ssh["ok"],
+                "builders": remote.builders.len(),
+                "fix": ssh["fix"],
+            })
+        }
+        Err(error) => json!({
+            "name": "remote_executors",
+            "ok": false,
+            "fix": format!("Fix SWARM_REMOTE_EXECUTORS: {error}"),
+        }),
+    }
+}
+
+/// Validates `SWARM_STAGE_PARSERS`. An invalid config would error every
+/// stage it names, so it fails here first.
+#[must_use]
+pub fn check_stage_parsers() -> serde_json::Value {
+    match crate::config::stage_parsers_from_env() {
+        Ok(parsers) => json!({"name": "stage_parsers", "ok": true, "stages": parsers.len()}),
+        Err(error) => json!({
+            "name": "stage_parsers",
+            "ok": false,
+            "fix": format!("Fix SWARM_STAGE_PARSERS: {error}"),
+        }),
+    }
+}
+
+/// Validates `SWARM_EVENT_SINKS`.
+#[must_use]
+pub fn check_event_sinks() -> serde_json::Value {
+    match crate::config::event_sinks_from_env() {
+        Ok(config) => json!({"name": "event_sinks", "ok": true, "sinks": config.sinks.len()}),
+        Err(error) => json!({
+            "name": "event_sinks",
+            "ok": false,
+            "fix": format!("Fix SWARM_EVENT_SINKS: {error}"),
+        }),
+    }
+}
+
+/// Validates `SWARM_NOTIFICATIONS`, and that `SWARM_APPROVAL_GATES` has a
+/// route to send its tokens through. Sends nothing; `notify test` does.
+#[must_use]
+pub fn check_notifications() -> serde_json::Value {
+    if let Err(error) = crate::config::approval_gates_from_env() {
+        return json!({
+            "name": "notifications",
+            "ok": false,
+            "fix": format!("Fix SWARM_APPROVAL_GATES or SWARM_NOTIFICATIONS: {error}"),
+        });
+    }
+    match crate::config::notification_rules_from_env() {
+        Ok(rules) => json!({
+            "name": "notifications",
+            "ok": true,
+            "notifiers": rules.notifiers.len(),
+            "routes": rules.routes.len(),
+        }),
+        Err(error) => json!({
+            "name": "notifications",
+            "ok": false,
+            "fix": format!("Fix SWARM_NOTIFICATIONS: {error}"),
+        }),
+    }
+}
+
+/// Scans the status, stage and type columns for values their Rust enums
+/// reject. Reading such a row fails with a schema drift error, so any found
+/// fails this check and is listed under `drift`.
+pub async fn check_enum_drift(request: &ProtocolRequest) -> serde_json::Value {
+    let drift = match super::read_db_from_request(request).await {
+        Ok(db) => db.find_enum_drift().await,
+        Err(failure) => {
+            return json!({
+                "name": "enum_drift",
+                "ok": false,
+                "fix": failure.err.map_or_else(
+                    || "Connect to the database and rerun 'swarm doctor'".to_string(),
+                    |err| err.msg,
+                ),
+            })
+        }
+    };
+    match drift {
+        Ok(drift) if drift.is_empty() => json!({"name": "enum_drift", "ok": true}),
+        Ok(drift) => json!({
+            "name": "enum_drift",
+            "ok": false,
+            "drift": drift,
+            "fix": "Update or delete the listed rows, or run a swarm version that knows these values",
+        }),
+        Err(error) => json!({
+            "name": "enum_drift",
+            "ok": false,
+            "fix": format!("Scanning for enum drift failed: {error}"),
+        }),
+    }
+}
+
+/// Last run of each scheduled job. Fails when an enabled job's last run
+/// failed, or when `SWARM_SCHEDULED_JOBS` does not parse.
+pub async fn check_scheduled_jobs(request: &ProtocolRequest) -> serde_json::Value {
+    let schedules = match crate::config::scheduled_jobs_from_env() {
+        Ok(schedules) => schedules,
+        Err(error) => {
+            return json!({
+                "name": "scheduled_jobs",
+                "ok": false,
+                "fix": format!("Fix SWARM_SCHEDULED_JOBS: {error}"),
+            })
+        }
+    };
+    let runs = match super::read_db_from_request(request).await {
+        Ok(db) => {
+            db.get_scheduled_jobs(&super::repo_id_from_request(request))
+                .await
+        }
+        Err(failure) => {
+            return json!({
+                "name": "scheduled_jobs",
+                "ok": false,
+                "fix": failure.err.map_or_else(
+                    || "Connect to the database and rerun 'swarm doctor'".to_string(),
+                    |err| err.msg,
+                ),
+            })
+        }
+    };
+    match runs {
+        Ok(runs) => {
+            let now = chrono::Utc::now();
+            let failing = runs
+                .iter()
+                .filter(|run| run.enabled && run.last_ok == Some(false))
+                .map(|run| run.job.as_str())
+                .collect::<Vec<_>>();
+            let jobs = runs
+                .iter()
+                .map(|run| super::handlers::jobs::job_view(&schedules, run, now))
+                .collect::<Vec<_>>();
+            if failing.is_empty() {
+                json!({"name": "scheduled_jobs", "ok": true, "jobs": jobs})
+            } else {
+                json!({
+                    "name": "scheduled_jobs",
+                    "ok": false,
+                    "jobs": jobs,
+                    "fix": format!(
+                        "Check last_error, then 'swarm jobs run-now --job {}'",
+                        failing[0]
+                    ),
+                })
+            }
+        }
+        Err(error) => json!({
+            "name": "scheduled_jobs",
+            "ok": false,
+            "fix": format!("Reading scheduled jobs failed: {error}"),
+        }),
+    }
+}
+
+/// Fails when this build injects faults and the `SWARM_CHAOS_*` variables
+/// are invalid, since injection then stays off without saying so.
+#[must_use]
+pub fn check_chaos() -> serde_json::Value {
+    let status = crate::chaos::status();
+    match status.config_error {
+        Some(error) => json!({
+            "name": "chaos",
+            "ok": false,
+            "fix": format!("Fix the SWARM_CHAOS_* variables: {error}"),
+        }),
+        None => json!({
+            "name": "chaos",
+            "ok": true,
+            "compiled": status.compiled,
+            "enabled": status.enabled,
+        }),
+    }
+}
+
 pub async fn check_database_connectivity(request: &ProtocolRequest) -> serde_json::Value {
     check_database_connectivity_with_timeout(request, super::DEFAULT_DB_CONNECT_TIMEOUT_MS).await
 }
@@ -39,8 +300,12 @@ pub async fn check_database_connectivity_with_timeout(
         .map(str::trim)
         .filter(|value| !value.is_empty());
     let candidates = super::audit::database_url_candidates_with_explicit(explicit_database_url);
+    let scope = super::db_resolution::db_scope_for_request(request)
+        .unwrap_or_else(|_| super::db_resolution::DbScope::from_env());
+    let options = super::db_resolution::connect_options_for_request(request).unwrap_or_default();
     let (connected, failures) =
-        super::db_resolution::try_connect_candidates(&candidates, timeout_ms).await;
+        super::db_resolution::try_connect_candidates(&candidates, timeout_ms, &scope, &options)
+            .await;
 
     match connected {
         Some((_db, connected_url)) => {
@@ -64,3 +329,57 @@ pub async fn check_database_connectivity_with_timeout(
         }),
     }
 }
+
+/// Every database URL candidate, where it came from, and whether it
+/// connects; discovery sources that offered none are listed with the reason.
+pub async fn explain_database_candidates(
+    request: &ProtocolRequest,
+    timeout_ms: u64,
+) -> serde_json::Value {
+    let report = database_url_discovery_for_cli();
+    let explicit = request
+        .args
+        .get("database_url")
+        .and_then(serde_json::Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|url| UrlCandidate::new(url, CandidateSource::Request, "request.database_url"));
+    let scope = super::db_resolution::db_scope_for_request(request)
+        .unwrap_or_else(|_| super::db_resolution::DbScope::from_env());
+    let options = super::db_resolution::connect_options_for_request(request).unwrap_or_default();
+
+    let mut candidates = Vec::new();
+    let mut selected = None;
+    for candidate in explicit.into_iter().chain(report.candidates) {
+        if candidates
+            .iter()
+            .any(|seen: &(UrlCandidate, Option<String>)| seen.0.url == candidate.url)
+        {
+            continue;
+        }
+        let error =
+            SwarmDb::new_with_options(&candidate.url, scope.schema(), Some(timeout_ms), &options)
+                .await
+                .err()
+                .map(|error| error.to_string());
+        if error.is_none() && selected.is_none() {
+            selected = Some(super::db_resolution::mask_database_url(&candidate.url));
+        }
+        candidates.push((candidate, error));
+    }
+
+    json!({
+        "selected": selected,
+        "candidates": candidates
+            .iter()
+            .map(|(candidate, error)| json!({
+                "url": super::db_resolution::mask_database_url(&candidate.url),
+                "source": candidate.source,
+                "detail": candidate.detail,
+                "ok": error.is_none(),
+                "error": error,
+            }))
+            .collect::<Vec<_>>(),
+        "skipped_sources": report.skipped,
+    })
+}
@@ -1,3 +1,4 @@
+use crate::profiling::HotspotCategory;
 use crate::protocol_envelope::ProtocolEnvelope;
 use crate::SwarmError;
 use serde_json::{json, Value};
@@ -77,10 +78,15 @@ pub async fn run_external_json_command_with_timeout(
     fix: &str,
     timeout_ms: u64,
 ) -> std::result::Result<Value, Box<ProtocolEnvelope>> {
+    if let Some(delay) = crate::chaos::external_command_delay() {
+        tokio::time::sleep(delay).await;
+    }
+    let started = Instant::now();
     let mut child = Command::new(program)
         .args(args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
+        .kill_on_drop(true)
         .spawn()
         .map_err(|err| {
             Box::new(
@@ -137,6 +143,7 @@ pub async fn run_external_json_command_with_timeout(
         })?
     } else {
         let _ = child.kill().await;
+        crate::profiling::record(HotspotCategory::ExternalCommand, program, started.elapsed());
         return Err(Box::new(
             ProtocolEnvelope::error(
                 rid,
@@ -192,6 +199,8 @@ pub async fn run_external_json_command_with_timeout(
         )
     })?;
 
+    crate::profiling::record(HotspotCategory::ExternalCommand, program, started.elapsed());
+
     if !status.success() {
         let exit_code = status.code().map_or(1, |code| code);
         let stderr = String::from_utf8_lossy(&stderr_capture.bytes)
@@ -1,14 +1,14 @@
 use super::super::{
-    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
-    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest, MAX_REGISTER_COUNT,
+    db_from_request, dry_flag, dry_run_plan, dry_run_success, minimal_state_for_request,
+    repo_id_from_request, to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+    MAX_REGISTER_COUNT,
 };
 use crate::agent_runtime::run_agent;
 use crate::config::load_config;
 use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{DryRunConflict, DryRunConflictKind};
 use crate::{code, AgentId, RepoId, SwarmDb};
 use serde_json::json;
-use std::future::Future;
-use std::pin::Pin;
 
 pub(in crate::protocol_runtime) async fn handle_register(
     request: &ProtocolRequest,
@@ -63,14 +63,7 @@ pub(in crate::protocol_runtime) async fn handle_register(
     }
 
     if dry_flag(request) {
-        return Ok(dry_run_success(
-            request,
-            vec![
-                json!({"step": 1, "action": "register_repo", "target": "current_repo"}),
-                json!({"step": 2, "action": "register_agents", "target": count}),
-            ],
-            "swarm status",
-        ));
+        return Ok(register_dry_run(request, &db, count).await);
     }
 
     let repo_id = RepoId::from_current_dir().ok_or_else(|| {
@@ -91,7 +84,7 @@ pub(in crate::protocol_runtime) async fn handle_register(
         let _ = db.update_config(explicit_count).await;
     }
 
-    register_agents_recursive(&db, &repo_id, 1, count, request.rid.clone()).await?;
+    register_agents(&db, &repo_id, count, request.rid.clone()).await?;
 
     Ok(CommandSuccess {
         data: json!({"repo": repo_id.value(), "count": count}),
@@ -100,24 +93,59 @@ pub(in crate::protocol_runtime) async fn handle_register(
     })
 }
 
-fn register_agents_recursive<'a>(
-    db: &'a SwarmDb,
-    repo_id: &'a RepoId,
-    next: u32,
+/// Plans `register` from the agents the repo already has: ids already
+/// registered are left alone, the rest up to `count` are added.
+async fn register_dry_run(request: &ProtocolRequest, db: &SwarmDb, count: u32) -> CommandSuccess {
+    let mut conflicts = Vec::new();
+    let repo_id = RepoId::from_current_dir();
+    if repo_id.is_none() {
+        conflicts.push(DryRunConflict::new(
+            DryRunConflictKind::StateUnavailable,
+            "Not in a git repository",
+        ));
+    }
+    let existing = match &repo_id {
+        Some(repo_id) => db
+            .registered_agent_ids(repo_id)
+            .await
+            .unwrap_or_else(|error| {
+                conflicts.push(DryRunConflict::new(
+                    DryRunConflictKind::StateUnavailable,
+                    error.to_string(),
+                ));
+                Vec::new()
+            }),
+        None => Vec::new(),
+    };
+    let (kept, added): (Vec<u32>, Vec<u32>) = (1..=count).partition(|id| existing.contains(id));
+    let repo = repo_id.as_ref().map_or("current_repo", RepoId::value);
+
+    dry_run_plan(
+        request,
+        vec![
+            json!({"step": 1, "action": "register_repo", "target": repo}),
+            json!({"step": 2, "action": "register_agents", "target": count, "rows": {
+                "table": "agent_state", "op": "insert", "add": added, "already_registered": kept,
+            }}),
+        ],
+        conflicts,
+        Vec::new(),
+        "swarm status",
+    )
+    .await
+}
+
+/// Registers agents 1 through `count` all at once: when one insert fails,
+/// none of them are registered and the error is returned.
+async fn register_agents(
+    db: &SwarmDb,
+    repo_id: &RepoId,
     count: u32,
     rid: Option<String>,
-) -> Pin<Box<dyn Future<Output = std::result::Result<(), Box<ProtocolEnvelope>>> + Send + 'a>> {
-    Box::pin(async move {
-        if next > count {
-            Ok(())
-        } else {
-            db.register_agent(&AgentId::new(repo_id.clone(), next))
-                .await
-                .map_err(|e| to_protocol_failure(e, rid.clone()))?;
-
-            register_agents_recursive(db, repo_id, next.saturating_add(1), count, rid).await
-        }
-    })
+) -> std::result::Result<(), Box<ProtocolEnvelope>> {
+    db.register_agents(repo_id, count)
+        .await
+        .map_err(|e| to_protocol_failure(e, rid))
 }
 
 pub(in crate::protocol_runtime) async fn handle_agent(
@@ -0,0 +1,148 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, read_db_from_request,
+    repo_id_from_request, to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::AgentId;
+use crate::{code, AnnouncementsInput, SwarmDb};
+use serde_json::json;
+
+const ANNOUNCEMENTS_FIX: &str =
+    "swarm announcements list | swarm announcements ack --agent-id 3 --id 12";
+
+/// `announcements list` shows each unexpired announcement with the agents
+/// that acknowledged it and those still pending, or with `agent_id` that
+/// agent's unacknowledged ones; `announcements ack` acknowledges them.
+pub(in crate::protocol_runtime) async fn handle_announcements(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = AnnouncementsInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(ANNOUNCEMENTS_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    match (input.action.as_str(), input.agent_id) {
+        ("ack", Some(agent_id)) => ack(request, agent_id, input.id, input.topic.as_deref()).await,
+        (_, Some(agent_id)) => inbox(request, agent_id, input.topic.as_deref()).await,
+        _ => statuses(request, input.topic.as_deref()).await,
+    }
+}
+
+async fn statuses(
+    request: &ProtocolRequest,
+    topic: Option<&str>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let announcements = db
+        .get_announcement_statuses(&repo_id_from_request(request), topic)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({"announcements": announcements}),
+        next: "swarm announcements ack --agent-id <id>".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn inbox(
+    request: &ProtocolRequest,
+    agent_id: u32,
+    topic: Option<&str>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    ensure_registered(request, &db, &AgentId::new(repo_id.clone(), agent_id)).await?;
+    let announcements = db
+        .get_announcements(&repo_id, topic, Some(agent_id))
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({"agent_id": agent_id, "announcements": announcements}),
+        next: format!("swarm announcements ack --agent-id {agent_id}"),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn ack(
+    request: &ProtocolRequest,
+    agent_id: u32,
+    id: Option<i64>,
+    topic: Option<&str>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![json!({
+                "step": 1,
+                "action": "ack_announcements",
+                "target": format!("agent:{agent_id}"),
+                "id": id,
+                "topic": topic,
+            })],
+            &format!("swarm announcements list --agent-id {agent_id}"),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let agent = AgentId::new(repo_id_from_request(request), agent_id);
+    ensure_registered(request, &db, &agent).await?;
+    let acked = db
+        .ack_announcements(&agent, id, topic)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    if let (Some(id), true) = (id, acked.is_empty()) {
+        let unexpired = db
+            .get_announcements(agent.repo_id(), None, None)
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+        if !unexpired.iter().any(|announcement| announcement.id == id) {
+            return Err(Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("Announcement {id} does not exist or has expired"),
+                )
+                .with_fix("swarm announcements list".to_string())
+                .with_ctx(json!({"id": id})),
+            ));
+        }
+    }
+
+    Ok(CommandSuccess {
+        data: json!({"agent_id": agent_id, "acked": acked}),
+        next: format!("swarm announcements list --agent-id {agent_id}"),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn ensure_registered(
+    request: &ProtocolRequest,
+    db: &SwarmDb,
+    agent: &AgentId,
+) -> std::result::Result<(), Box<ProtocolEnvelope>> {
+    let state = db
+        .get_agent_state(agent)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    if state.is_some() {
+        return Ok(());
+    }
+    Err(Box::new(
+        ProtocolEnvelope::error(
+            request.rid.clone(),
+            code::NOTFOUND.to_string(),
+            format!("Agent {} is not registered", agent.number()),
+        )
+        .with_fix("swarm register --count <n>".to_string())
+        .with_ctx(json!({"agent_id": agent.number()})),
+    ))
+}
@@ -0,0 +1,216 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{ApprovalGate, ApprovalStatus};
+use crate::{code, BeadId, SwarmDb};
+use serde_json::json;
+
+const APPROVE_FIX: &str = "swarm approve --bead-id <bead> --token <token>";
+const REISSUE_FIX: &str = "swarm reissue-approval --bead-id <bead> [--gate <gate>]";
+const APPROVALS_NEXT: &str = "swarm monitor --view approvals";
+
+/// Releases a bead held at an approval gate. The grant, who gave it and the
+/// note are kept on the approval row and in an `approval_granted` event.
+pub(in crate::protocol_runtime) async fn handle_approve(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::ApproveInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(APPROVE_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let bead_id = input.bead_id.as_str();
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "grant_approval", "target": format!("bead:{bead_id}")}),
+            ],
+            &format!("swarm approve --bead-id {bead_id} --token {}", input.token),
+        ));
+    }
+
+    let approver = input.approver.clone().unwrap_or_else(operator_name);
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let granted = db
+        .grant_approval(
+            &repo_id,
+            &BeadId::new(bead_id),
+            &input.token,
+            &approver,
+            input.note.as_deref(),
+        )
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    let Some(approval) = granted else {
+        let pending = db
+            .list_approvals(&repo_id, Some(bead_id), 1)
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+            .into_iter()
+            .any(|approval| approval.status == ApprovalStatus::Pending);
+        let (error_code, message) = if pending {
+            (
+                code::UNAUTHORIZED,
+                format!(
+                    "Token does not match the pending approval for {bead_id}; use the token from its approval_requested notification"
+                ),
+            )
+        } else {
+            (code::NOTFOUND, format!("No pending approval for {bead_id}"))
+        };
+        return Err(Box::new(
+            ProtocolEnvelope::error(request.rid.clone(), error_code.to_string(), message)
+                .with_fix(APPROVALS_NEXT.to_string())
+                .with_ctx(json!({"bead_id": bead_id})),
+        ));
+    };
+
+    Ok(CommandSuccess {
+        data: json!({"bead_id": bead_id, "approval": approval}),
+        next: format!("swarm monitor --view approvals --bead-id {bead_id}"),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Gives a pending approval a fresh token and sends it through the
+/// `approval_requested` route again, for when the first token never reached
+/// an operator. The old token stops working even if nothing receives the
+/// new one, so an operator can rerun this once the notifiers are fixed.
+pub(in crate::protocol_runtime) async fn handle_reissue_approval(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::ReissueApprovalInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(REISSUE_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let bead_id = input.bead_id.as_str();
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "reissue_approval_token", "target": format!("bead:{bead_id}")}),
+                json!({"step": 2, "action": "deliver_approval_token", "target": "approval_requested"}),
+            ],
+            &format!("swarm reissue-approval --bead-id {bead_id}"),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let gate = match input.gate {
+        Some(gate) => gate,
+        None => only_pending_gate(&db, request, bead_id).await?,
+    };
+    let reissued = db
+        .reissue_approval_token(&repo_id, &BeadId::new(bead_id), gate, &operator_name())
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+        .ok_or_else(|| {
+            Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("No pending approval for {bead_id} at {gate}"),
+                )
+                .with_fix(APPROVALS_NEXT.to_string())
+                .with_ctx(json!({"bead_id": bead_id, "gate": gate})),
+            )
+        })?;
+
+    let delivery = crate::notifications::deliver_approval_token(&repo_id, &reissued)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+        .unwrap_or_default();
+    if delivery.sent.is_empty() {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::DEPENDENCY.to_string(),
+                format!("Reissued approval token for {bead_id} reached no notifier"),
+            )
+            .with_fix(format!(
+                "Fix the approval_requested route in SWARM_NOTIFICATIONS, then rerun 'swarm reissue-approval --bead-id {bead_id} --gate {gate}'"
+            ))
+            .with_ctx(json!({
+                "bead_id": bead_id,
+                "approval_id": reissued.approval.id,
+                "failed": delivery.failed,
+            })),
+        ));
+    }
+
+    Ok(CommandSuccess {
+        data: json!({
+            "bead_id": bead_id,
+            "approval": reissued.approval,
+            "sent": delivery.sent,
+            "failed": delivery.failed,
+        }),
+        next: format!("swarm approve --bead-id {bead_id} --token <token>"),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// The gate of `bead_id`'s one pending approval.
+async fn only_pending_gate(
+    db: &SwarmDb,
+    request: &ProtocolRequest,
+    bead_id: &str,
+) -> std::result::Result<ApprovalGate, Box<ProtocolEnvelope>> {
+    let pending = db
+        .list_approvals(&repo_id_from_request(request), Some(bead_id), i64::MAX)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+        .into_iter()
+        .filter(|approval| approval.status == ApprovalStatus::Pending)
+        .map(|approval| approval.gate)
+        .collect::<Vec<_>>();
+    match pending.as_slice() {
+        [gate] => Ok(*gate),
+        [] => Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::NOTFOUND.to_string(),
+                format!("No pending approval for {bead_id}"),
+            )
+            .with_fix(APPROVALS_NEXT.to_string())
+            .with_ctx(json!({"bead_id": bead_id})),
+        )),
+        gates => Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                format!("{bead_id} is pending at {} gates; pass --gate", gates.len()),
+            )
+            .with_fix(REISSUE_FIX.to_string())
+            .with_ctx(json!({"bead_id": bead_id, "pending_gates": gates})),
+        )),
+    }
+}
+
+fn operator_name() -> String {
+    std::env::var("USER")
+        .ok()
+        .filter(|user| !user.is_empty())
+        .unwrap_or_else(|| "operator".to_string())
+}
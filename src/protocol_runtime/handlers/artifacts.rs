@@ -1,13 +1,21 @@
+use super::super::validation::limit_exceeded;
 use super::super::{
-    db_from_request, minimal_state_for_request, repo_id_from_request, to_protocol_failure,
-    CommandSuccess, ProtocolRequest,
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, read_db_from_request,
+    repo_id_from_request, to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
 };
 use crate::protocol_envelope::ProtocolEnvelope;
-use crate::{code, ArtifactType, BeadId, StageArtifact, SwarmDb};
+use crate::signing::content_hash;
+use crate::types::ArtifactEncoding;
+use crate::{code, ArtifactType, BeadId, StageArtifact, SwarmDb, SwarmError};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 
+const ARTIFACT_PUT_FIX: &str =
+    "swarm artifact put --bead-id <bead> --stage implement --type design_document --file design.md";
+
 type ArtifactPortFuture<'a, T> = Pin<Box<dyn Future<Output = crate::Result<T>> + Send + 'a>>;
 
 trait ArtifactQueryPort {
@@ -15,6 +23,12 @@ trait ArtifactQueryPort {
         &'a self,
         request: &'a ArtifactQuery,
     ) -> ArtifactPortFuture<'a, Vec<StageArtifact>>;
+
+    /// Stage name of each of the bead's stage runs, by stage history id.
+    fn stage_names<'a>(
+        &'a self,
+        request: &'a ArtifactQuery,
+    ) -> ArtifactPortFuture<'a, HashMap<i64, String>>;
 }
 
 trait ArtifactHandlerPorts: ArtifactQueryPort {}
@@ -63,6 +77,22 @@ impl ArtifactQueryPort for SwarmDbArtifactPort {
                 .await
         })
     }
+
+    fn stage_names<'a>(
+        &'a self,
+        request: &'a ArtifactQuery,
+    ) -> ArtifactPortFuture<'a, HashMap<i64, String>> {
+        Box::pin(async move {
+            self.db
+                .get_bead_stage_runs(&request.repo_id, &request.bead_id)
+                .await
+                .map(|runs| {
+                    runs.into_iter()
+                        .map(|run| (run.stage_history_id, run.stage))
+                        .collect()
+                })
+        })
+    }
 }
 
 #[allow(clippy::future_not_send)]
@@ -73,12 +103,26 @@ async fn fetch_artifacts<P: ArtifactHandlerPorts>(
     ports.bead_artifacts(query).await
 }
 
+/// Writes `artifacts` under `out_dir`, sorted into directories by the stage
+/// each one was stored on; see [`write_artifact_files`].
+#[allow(clippy::future_not_send)]
+async fn export_artifacts<P: ArtifactHandlerPorts>(
+    ports: &P,
+    query: &ArtifactQuery,
+    artifacts: &[StageArtifact],
+    out_dir: &Path,
+) -> crate::Result<Vec<Value>> {
+    let stages = ports.stage_names(query).await?;
+    write_artifact_files(out_dir, &query.bead_id, artifacts, &stages).await
+}
+
 pub(in crate::protocol_runtime) async fn handle_artifacts(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
     let bead_id = parse_artifact_bead_id(request)?;
     let artifact_type = parse_artifact_type(request)?;
-    let db: SwarmDb = db_from_request(request).await?;
+    let out_dir = parse_artifact_out_dir(request)?;
+    let db: SwarmDb = read_db_from_request(request).await?;
     let query = ArtifactQuery::new(
         repo_id_from_request(request),
         bead_id.clone(),
@@ -88,6 +132,23 @@ pub(in crate::protocol_runtime) async fn handle_artifacts(
     let artifacts = fetch_artifacts(&ports, &query)
         .await
         .map_err(|error| to_protocol_failure(error, request.rid.clone()))?;
+
+    if let Some(out_dir) = out_dir {
+        let manifest = export_artifacts(&ports, &query, &artifacts, &out_dir)
+            .await
+            .map_err(|error| to_protocol_failure(error, request.rid.clone()))?;
+        return Ok(CommandSuccess {
+            data: json!({
+                "bead_id": bead_id.value(),
+                "out_dir": out_dir.display().to_string(),
+                "artifact_count": manifest.len(),
+                "artifacts": manifest,
+            }),
+            next: "swarm monitor --view progress".to_string(),
+            state: minimal_state_for_request(request).await,
+        });
+    }
+
     let artifact_payload = artifacts.iter().map(artifact_to_json).collect::<Vec<_>>();
 
     Ok(CommandSuccess {
@@ -101,6 +162,124 @@ pub(in crate::protocol_runtime) async fn handle_artifacts(
     })
 }
 
+/// `artifact put`: stores an agent's file as an artifact on the bead's
+/// latest run of `stage`. Text is stored as is and anything else as base64;
+/// `metadata` records which, with the file name and size.
+pub(in crate::protocol_runtime) async fn handle_artifact(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::ArtifactInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(ARTIFACT_PUT_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    let max_bytes = crate::config::max_artifact_bytes_from_env();
+    let size = tokio::fs::metadata(&input.file)
+        .await
+        .map_err(|err| file_not_found(request, &input.file, &err))?
+        .len();
+    let size = usize::try_from(size).unwrap_or(usize::MAX);
+    if size > max_bytes {
+        return Err(limit_exceeded(
+            request.rid.clone(),
+            "max_artifact_bytes",
+            max_bytes,
+            size,
+        ));
+    }
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "read_file", "target": input.file, "bytes": size}),
+                json!({
+                    "step": 2,
+                    "action": "store_stage_artifact",
+                    "target": input.bead_id,
+                    "stage": input.stage.as_str(),
+                    "artifact_type": input.artifact_type.as_str(),
+                }),
+            ],
+            &format!("swarm artifacts --bead-id {}", input.bead_id),
+        ));
+    }
+
+    let bytes = tokio::fs::read(&input.file)
+        .await
+        .map_err(|err| file_not_found(request, &input.file, &err))?;
+    let size = bytes.len();
+    if size > max_bytes {
+        return Err(limit_exceeded(
+            request.rid.clone(),
+            "max_artifact_bytes",
+            max_bytes,
+            size,
+        ));
+    }
+    let (content, encoding) = ArtifactEncoding::encode(bytes);
+    let file_name = Path::new(&input.file)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned());
+
+    let bead_id = BeadId::new(input.bead_id.clone());
+    let db = db_from_request(request).await?;
+    let stored = db
+        .put_bead_artifact(
+            &repo_id_from_request(request),
+            &bead_id,
+            Some(input.stage),
+            input.artifact_type,
+            &content,
+            json!({"file": file_name, "bytes": size, "encoding": encoding.as_str()}),
+        )
+        .await
+        .map_err(|error| to_protocol_failure(error, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "id": stored.id,
+            "bead_id": input.bead_id,
+            "stage": input.stage.as_str(),
+            "stage_history_id": stored.stage_history_id,
+            "artifact_type": input.artifact_type.as_str(),
+            "content_hash": stored.content_hash,
+            "bytes": size,
+            "encoding": encoding.as_str(),
+            "deduplicated": stored.deduplicated,
+        }),
+        next: format!(
+            "swarm artifacts --bead-id {} --artifact-type {}",
+            input.bead_id,
+            input.artifact_type.as_str()
+        ),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+fn file_not_found(
+    request: &ProtocolRequest,
+    file: &str,
+    error: &std::io::Error,
+) -> Box<ProtocolEnvelope> {
+    Box::new(
+        ProtocolEnvelope::error(
+            request.rid.clone(),
+            code::NOTFOUND.to_string(),
+            format!("Artifact file not readable: {error}"),
+        )
+        .with_fix("Pass an existing file with --file".to_string())
+        .with_ctx(json!({"file": file})),
+    )
+}
+
 fn parse_artifact_bead_id(
     request: &ProtocolRequest,
 ) -> std::result::Result<BeadId, Box<ProtocolEnvelope>> {
@@ -195,6 +374,99 @@ fn parse_artifact_type(
         })
 }
 
+fn parse_artifact_out_dir(
+    request: &ProtocolRequest,
+) -> std::result::Result<Option<PathBuf>, Box<ProtocolEnvelope>> {
+    match request.args.get("out_dir") {
+        None => Ok(None),
+        Some(Value::String(dir)) if !dir.trim().is_empty() => Ok(Some(PathBuf::from(dir.trim()))),
+        Some(raw) => Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                "out_dir must be a non-empty path".to_string(),
+            )
+            .with_fix(
+                "Pass the directory to write artifacts into. Example: {\"cmd\":\"artifacts\",\"bead_id\":\"<bead>\",\"out_dir\":\"./artifacts\"}".to_string(),
+            )
+            .with_ctx(json!({"out_dir": raw})),
+        )),
+    }
+}
+
+/// Writes each artifact's content to
+/// `<out_dir>/<bead>/<stage>/<artifact_type>-<content_hash>` and returns
+/// the manifest: every artifact as `artifact_to_json` has it, with `stage`,
+/// `path` and `bytes` in place of `content`. Identical content on the same
+/// stage and type shares one file.
+async fn write_artifact_files(
+    out_dir: &Path,
+    bead_id: &BeadId,
+    artifacts: &[StageArtifact],
+    stages: &HashMap<i64, String>,
+) -> crate::Result<Vec<Value>> {
+    let mut manifest = Vec::with_capacity(artifacts.len());
+    for artifact in artifacts {
+        let stage = stages
+            .get(&artifact.stage_history_id)
+            .map_or("unknown", String::as_str);
+        let hash = artifact
+            .content_hash
+            .clone()
+            .unwrap_or_else(|| content_hash(&artifact.content));
+        let dir = out_dir
+            .join(path_component(bead_id.value()))
+            .join(path_component(stage));
+        let path = dir.join(format!(
+            "{}-{}",
+            artifact.artifact_type.as_str(),
+            path_component(&hash)
+        ));
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| write_failure(&dir, e))?;
+        tokio::fs::write(&path, artifact.content.as_bytes())
+            .await
+            .map_err(|e| write_failure(&path, e))?;
+
+        let mut entry = artifact_to_json(artifact);
+        if let Some(fields) = entry.as_object_mut() {
+            fields.remove("content");
+            fields.insert("stage".to_string(), json!(stage));
+            fields.insert("path".to_string(), json!(path.display().to_string()));
+            fields.insert("bytes".to_string(), json!(artifact.content.len()));
+        }
+        manifest.push(entry);
+    }
+    Ok(manifest)
+}
+
+fn write_failure(path: &Path, error: std::io::Error) -> SwarmError {
+    SwarmError::IoError(std::io::Error::new(
+        error.kind(),
+        format!("Failed to write {}: {error}", path.display()),
+    ))
+}
+
+/// `raw` as a single file name: anything but ASCII letters, digits, `-`,
+/// `_` and `.` becomes `_`, so ids cannot climb out of the output directory.
+fn path_component(raw: &str) -> String {
+    let name = raw
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    match name.as_str() {
+        "" | "." | ".." => format!("_{name}"),
+        _ => name,
+    }
+}
+
 fn artifact_to_json(artifact: &StageArtifact) -> Value {
     json!({
         "id": artifact.id,
@@ -210,8 +482,49 @@ fn artifact_to_json(artifact: &StageArtifact) -> Value {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Utc;
     use serde_json::{map::Map, Value};
 
+    struct FakeArtifactPort {
+        artifacts: Vec<StageArtifact>,
+        stages: HashMap<i64, String>,
+    }
+
+    impl ArtifactQueryPort for FakeArtifactPort {
+        fn bead_artifacts<'a>(
+            &'a self,
+            _request: &'a ArtifactQuery,
+        ) -> ArtifactPortFuture<'a, Vec<StageArtifact>> {
+            Box::pin(async move { Ok(self.artifacts.clone()) })
+        }
+
+        fn stage_names<'a>(
+            &'a self,
+            _request: &'a ArtifactQuery,
+        ) -> ArtifactPortFuture<'a, HashMap<i64, String>> {
+            Box::pin(async move { Ok(self.stages.clone()) })
+        }
+    }
+
+    fn artifact(id: i64, stage_history_id: i64, content: &str) -> StageArtifact {
+        StageArtifact {
+            id,
+            stage_history_id,
+            artifact_type: ArtifactType::StageLog,
+            content: content.to_string(),
+            metadata: None,
+            created_at: Utc::now(),
+            content_hash: Some(format!("hash-{id}")),
+        }
+    }
+
+    fn out_dir_error_code(request: &ProtocolRequest) -> Option<String> {
+        parse_artifact_out_dir(request)
+            .err()
+            .and_then(|envelope| envelope.err)
+            .map(|err| err.code)
+    }
+
     fn request_with_args(entries: &[(&str, &str)]) -> ProtocolRequest {
         let args = entries
             .iter()
@@ -267,6 +580,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn given_ids_with_separators_when_naming_files_then_they_stay_one_component() {
+        assert_eq!(path_component("bd-42"), "bd-42");
+        assert_eq!(path_component("../etc/passwd"), ".._etc_passwd");
+        assert_eq!(path_component(".."), "_..");
+        assert_eq!(path_component(""), "_");
+    }
+
+    #[tokio::test]
+    async fn given_out_dir_when_exporting_then_files_land_by_bead_stage_and_type_without_content(
+    ) -> crate::Result<()> {
+        let out_dir = tempfile::TempDir::new()?;
+        let ports = FakeArtifactPort {
+            artifacts: vec![artifact(1, 10, "implement log"), artifact(2, 11, "qa log")],
+            stages: HashMap::from([(10, "implement".to_string())]),
+        };
+        let query = ArtifactQuery::new(crate::RepoId::new("local"), BeadId::new("bd/42"), None);
+
+        let artifacts = fetch_artifacts(&ports, &query).await?;
+        let manifest = export_artifacts(&ports, &query, &artifacts, out_dir.path()).await?;
+
+        let implement = out_dir.path().join("bd_42/implement/stage_log-hash-1");
+        let unknown = out_dir.path().join("bd_42/unknown/stage_log-hash-2");
+        assert_eq!(
+            tokio::fs::read_to_string(&implement).await?,
+            "implement log"
+        );
+        assert_eq!(tokio::fs::read_to_string(&unknown).await?, "qa log");
+        assert_eq!(manifest.len(), 2);
+        assert!(manifest.iter().all(|entry| entry.get("content").is_none()));
+        assert_eq!(manifest[0]["stage"], "implement");
+        assert_eq!(manifest[0]["bytes"], 13);
+        assert_eq!(
+            manifest[0]["path"],
+            Value::String(implement.display().to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn given_missing_out_dir_when_parsing_then_no_export_is_requested() {
+        let request = request_with_args(&[("bead_id", "bead-42")]);
+
+        assert!(matches!(parse_artifact_out_dir(&request), Ok(None)));
+    }
+
+    #[test]
+    fn given_blank_or_non_string_out_dir_when_parsing_then_invalid_envelope_is_returned() {
+        let blank = request_with_args(&[("bead_id", "bead-42"), ("out_dir", "  ")]);
+        let mut numeric = request_with_args(&[("bead_id", "bead-42")]);
+        numeric.args.insert("out_dir".to_string(), Value::from(7));
+
+        assert_eq!(out_dir_error_code(&blank).as_deref(), Some("INVALID"));
+        assert_eq!(out_dir_error_code(&numeric).as_deref(), Some("INVALID"));
+    }
+
+    #[tokio::test]
+    async fn given_out_dir_that_is_a_file_when_exporting_then_io_error_is_returned(
+    ) -> crate::Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        let ports = FakeArtifactPort {
+            artifacts: vec![artifact(1, 10, "log")],
+            stages: HashMap::new(),
+        };
+        let query = ArtifactQuery::new(crate::RepoId::new("local"), BeadId::new("bd-1"), None);
+
+        let result = export_artifacts(&ports, &query, &ports.artifacts, file.path()).await;
+
+        assert!(matches!(result, Err(SwarmError::IoError(_))), "{result:?}");
+        Ok(())
+    }
+
     #[test]
     fn given_boolean_artifact_type_when_parsing_then_type_error_is_returned() {
         let mut request = request_with_args(&[("bead_id", "bead-42")]);
@@ -0,0 +1,68 @@
+use super::super::{
+    minimal_state_for_request, read_db_from_request, repo_id_from_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::stage_executors::remote_stage_command;
+use crate::types::provenance_statement;
+use crate::{code, BeadId, RuntimeStage, SwarmDb};
+use serde_json::json;
+
+/// Exports a bead's stage runs, artifact hashes and toolchain as an
+/// in-toto statement with a SLSA provenance predicate.
+pub(in crate::protocol_runtime) async fn handle_attest(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::AttestInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm attest --bead-id <bead-id>".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let bead_id = BeadId::new(input.bead_id.as_str());
+    let mut stage_runs = db
+        .get_bead_stage_runs(&repo_id, &bead_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    if stage_runs.is_empty() {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::NOTFOUND.to_string(),
+                format!("No stage runs for bead {}", input.bead_id),
+            )
+            .with_fix(format!("swarm replay --bead-id {}", input.bead_id))
+            .with_ctx(json!({"bead_id": input.bead_id})),
+        ));
+    }
+    for run in &mut stage_runs {
+        run.command = RuntimeStage::try_from(run.stage.as_str())
+            .ok()
+            .and_then(remote_stage_command)
+            .map(str::to_string);
+    }
+    let artifacts = db
+        .get_bead_signed_artifacts(&repo_id, &bead_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    let statement = provenance_statement(repo_id.value(), &input.bead_id, &stage_runs, &artifacts);
+    Ok(CommandSuccess {
+        data: json!({
+            "bead_id": input.bead_id,
+            "stage_runs": stage_runs.len(),
+            "artifacts": artifacts.len(),
+            "attestation": statement,
+        }),
+        next: format!("swarm verify --bead-id {}", input.bead_id),
+        state: minimal_state_for_request(request).await,
+    })
+}
@@ -0,0 +1,354 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, read_db_from_request,
+    repo_id_from_request, run_external_json_command, to_protocol_failure, CommandSuccess,
+    ParseInput, ProtocolRequest,
+};
+use super::invalid_request;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{
+    normalize_priority, parse_backlog_entries, parse_br_issues, reconcile_backlog,
+    simulate_assignments, BacklogEditOutcome, BacklogEntry, BacklogSyncDiff, RepoId,
+};
+use crate::{code, SwarmDb};
+use serde_json::{json, Value};
+use tokio::fs;
+
+const ENQUEUE_FIX: &str =
+    "swarm enqueue --file beads.ndjson (or --file - to read stdin, or --beads '[\"bd-abc\"]')";
+const SYNC_BACKLOG_FIX: &str = "swarm sync-backlog --rounds 10 --interval_secs 60";
+const BACKLOG_FIX: &str = "swarm backlog preview | swarm backlog set-priority --bead-id <bead-id> --priority p1 | swarm backlog bump --label <label> --to p0 | swarm backlog remove --bead-id <bead-id>";
+const DEFAULT_SYNC_INTERVAL_SECS: u32 = 60;
+
+/// Adds hand-picked beads to the backlog after checking each one with
+/// `br show`. Beads already in the backlog are reported, not changed.
+pub(in crate::protocol_runtime) async fn handle_enqueue(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::EnqueueInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(ENQUEUE_FIX)))?;
+    let raw = beads_payload(request, &input).await?;
+    let entries = parse_backlog_entries(&raw)
+        .map_err(|message| invalid_request(request, message, Some(ENQUEUE_FIX)))?;
+
+    if dry_flag(request) {
+        let ids = entries
+            .iter()
+            .map(|entry| entry.bead_id.clone())
+            .collect::<Vec<_>>();
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "br_show", "target": ids}),
+                json!({"step": 2, "action": "insert_backlog", "target": "bead_backlog", "count": ids.len()}),
+            ],
+            "swarm monitor --view progress",
+        ));
+    }
+
+    let mut accepted = Vec::with_capacity(entries.len());
+    let mut rejected = Vec::new();
+    for entry in entries {
+        match verify_with_br(request, entry).await {
+            Ok(entry) => accepted.push(entry),
+            Err(rejection) => rejected.push(rejection),
+        }
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let inserted = db
+        .enqueue_backlog_entries(&repo_id_from_request(request), &accepted)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let existing = accepted
+        .iter()
+        .map(|entry| entry.bead_id.as_str())
+        .filter(|bead_id| !inserted.iter().any(|id| id == bead_id))
+        .collect::<Vec<_>>();
+
+    let next = if rejected.is_empty() {
+        "swarm claim-next".to_string()
+    } else {
+        "Check rejected beads with `br show <bead-id>`".to_string()
+    };
+    Ok(CommandSuccess {
+        data: json!({
+            "inserted": inserted,
+            "existing": existing,
+            "rejected": rejected,
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Fills unset priority and labels from `br show`; a bead `br` does not
+/// know, or one already closed, is rejected.
+async fn verify_with_br(
+    request: &ProtocolRequest,
+    mut entry: BacklogEntry,
+) -> std::result::Result<BacklogEntry, Value> {
+    let payload = run_external_json_command(
+        "br",
+        &["show", entry.bead_id.as_str(), "--json"],
+        request.rid.clone(),
+        "Run `br show <bead-id> --json` and verify bead exists",
+    )
+    .await
+    .map_err(
+        |failure| json!({"bead_id": entry.bead_id, "reason": failure.err.map(|err| err.msg)}),
+    )?;
+    let Some(issue) = payload
+        .as_array()
+        .map_or(Some(&payload), |items| items.first())
+        .filter(|issue| issue.is_object())
+    else {
+        return Err(json!({"bead_id": entry.bead_id, "reason": "br show returned no issue"}));
+    };
+
+    if issue.get("status").and_then(Value::as_str) == Some("closed") {
+        return Err(json!({"bead_id": entry.bead_id, "reason": "closed in br"}));
+    }
+    if entry.priority.is_none() {
+        entry.priority = issue
+            .get("priority")
+            .and_then(|priority| normalize_priority(priority).ok());
+    }
+    if entry.labels.is_none() {
+        entry.labels = issue
+            .get("labels")
+            .and_then(|labels| serde_json::from_value(labels.clone()).ok());
+    }
+    Ok(entry)
+}
+
+/// Reconciles the backlog with `br list --json`: open beads are added,
+/// pending beads closed in `br` are dropped, and pending priorities follow
+/// `br`. With `rounds` above 1 it keeps syncing every `interval_secs`.
+pub(in crate::protocol_runtime) async fn handle_sync_backlog(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::SyncBacklogInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(SYNC_BACKLOG_FIX)))?;
+    let rounds = input.rounds.unwrap_or(1);
+    let interval_secs = input.interval_secs.unwrap_or(DEFAULT_SYNC_INTERVAL_SECS);
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "br_list", "target": "br list --json"}),
+                json!({"step": 2, "action": "reconcile_backlog", "target": "bead_backlog", "rounds": rounds, "interval_secs": interval_secs}),
+            ],
+            "swarm sync-backlog",
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let mut passes = Vec::with_capacity(usize::try_from(rounds).unwrap_or_default());
+    for round in 1..=rounds {
+        if round > 1 {
+            tokio::time::sleep(tokio::time::Duration::from_secs(u64::from(interval_secs))).await;
+        }
+        let diff = sync_backlog_once(request, &db, &repo_id).await?;
+        passes.push(json!({"round": round, "applied": !diff.is_empty(), "diff": diff}));
+    }
+
+    Ok(CommandSuccess {
+        data: json!({
+            "rounds": rounds,
+            "interval_secs": interval_secs,
+            "passes": passes,
+        }),
+        next: "swarm claim-next".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn sync_backlog_once(
+    request: &ProtocolRequest,
+    db: &SwarmDb,
+    repo_id: &RepoId,
+) -> std::result::Result<BacklogSyncDiff, Box<ProtocolEnvelope>> {
+    let payload = run_external_json_command(
+        "br",
+        &["list", "--json"],
+        request.rid.clone(),
+        "Run `br list --json` manually and verify beads workspace is initialized",
+    )
+    .await?;
+    let rows = db
+        .get_backlog_rows(repo_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let diff = reconcile_backlog(&parse_br_issues(&payload), &rows);
+    if !diff.is_empty() {
+        db.apply_backlog_sync(repo_id, &diff)
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    }
+    Ok(diff)
+}
+
+/// Previews the claim order, or reprioritizes or removes pending backlog
+/// beads. Rows are only written while still pending, so beads claimed
+/// meanwhile are skipped, not edited; editing one named bead that cannot be
+/// edited fails instead.
+pub(in crate::protocol_runtime) async fn handle_backlog(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::BacklogInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(BACKLOG_FIX)))?;
+
+    if input.action == "preview" {
+        return preview_backlog(request, input.limit).await;
+    }
+
+    if dry_flag(request) {
+        let edit = if input.action == "remove" {
+            "remove_pending_beads"
+        } else {
+            "reprioritize_pending_beads"
+        };
+        return Ok(dry_run_success(
+            request,
+            vec![json!({
+                "step": 1,
+                "action": edit,
+                "target": "bead_backlog",
+                "bead_ids": input.selector.bead_ids,
+                "label": input.selector.label,
+                "priority": input.priority,
+            })],
+            "swarm status",
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let outcome = match input.priority.as_deref() {
+        Some(priority) => {
+            db.reprioritize_backlog(&repo_id, &input.selector, priority)
+                .await
+        }
+        None => db.remove_backlog_beads(&repo_id, &input.selector).await,
+    }
+    .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    if let ([bead_id], None) = (input.selector.bead_ids.as_slice(), &input.selector.label) {
+        if let Some(error) = single_bead_failure(request, bead_id, &outcome) {
+            return Err(error);
+        }
+    }
+
+    Ok(CommandSuccess {
+        data: json!({
+            "action": input.action,
+            "reprioritized": outcome.reprioritized,
+            "removed": outcome.removed,
+            "skipped": outcome.skipped,
+            "missing": outcome.missing,
+            "unchanged": outcome.unchanged,
+        }),
+        next: "swarm status".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Pending beads in claim order and the free agent each would go to if the
+/// agents claimed now. Reads only.
+async fn preview_backlog(
+    request: &ProtocolRequest,
+    limit: Option<u32>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let beads = db
+        .backlog_preview_beads(&repo_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let agents = db
+        .backlog_preview_agents(&repo_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    let plan = simulate_assignments(&beads, &agents);
+    let assigned = plan
+        .iter()
+        .filter(|assignment| assignment.agent_id.is_some())
+        .count();
+    let shown = limit.map_or(plan.len(), |limit| {
+        plan.len().min(usize::try_from(limit).unwrap_or(usize::MAX))
+    });
+
+    Ok(CommandSuccess {
+        data: json!({
+            "pending": plan.len(),
+            "free_agents": agents.iter().map(|agent| agent.agent_id).collect::<Vec<_>>(),
+            "assigned": assigned,
+            "beads": &plan[..shown],
+        }),
+        next: if assigned > 0 {
+            "swarm run-once".to_string()
+        } else {
+            "swarm status".to_string()
+        },
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+fn single_bead_failure(
+    request: &ProtocolRequest,
+    bead_id: &str,
+    outcome: &BacklogEditOutcome,
+) -> Option<Box<ProtocolEnvelope>> {
+    let (code, message) = if outcome.missing.iter().any(|missing| missing == bead_id) {
+        (
+            code::NOTFOUND,
+            format!("Bead {bead_id} is not in the backlog"),
+        )
+    } else {
+        let skip = outcome
+            .skipped
+            .iter()
+            .find(|skip| skip.bead_id == bead_id)?;
+        (
+            code::CONFLICT,
+            format!("Bead {bead_id} cannot be edited: {}", skip.reason),
+        )
+    };
+    Some(Box::new(
+        ProtocolEnvelope::error(request.rid.clone(), code.to_string(), message)
+            .with_fix(
+                "swarm status to see the bead's state; only pending beads are edited".to_string(),
+            )
+            .with_ctx(json!({"bead_id": bead_id, "skipped": outcome.skipped})),
+    ))
+}
+
+/// Exactly one of inline `beads` or a `file`, as raw JSON or NDJSON text.
+async fn beads_payload(
+    request: &ProtocolRequest,
+    input: &crate::EnqueueInput,
+) -> std::result::Result<String, Box<ProtocolEnvelope>> {
+    match (input.beads.as_ref(), input.file.as_deref()) {
+        (Some(Value::String(raw)), None) => Ok(raw.clone()),
+        (Some(beads), None) => Ok(beads.to_string()),
+        (None, Some(path)) => fs::read_to_string(path).await.map_err(|err| {
+            Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("Beads file not found: {err}"),
+                )
+                .with_fix("Pass an existing JSON or NDJSON file with --file".to_string())
+                .with_ctx(json!({"file": path})),
+            )
+        }),
+        _ => Err(invalid_request(
+            request,
+            "enqueue requires exactly one of beads or file".to_string(),
+            Some(ENQUEUE_FIX),
+        )),
+    }
+}
@@ -119,22 +119,151 @@ pub(in crate::protocol_runtime) async fn handle_help(
     let commands = vec![
         ("init", "Initialize swarm (bootstrap + init-db + register)"),
         ("doctor", "Environment health check"),
+        ("db-health", "Connection pool health and acquire latency"),
+        ("healthz", "Liveness probe: database ping and pool stats"),
+        (
+            "invariants",
+            "Check coordinator state for broken invariants",
+        ),
+        (
+            "costs",
+            "Token spend and stage time per bead, agent, and stage",
+        ),
+        (
+            "report-usage",
+            "Record one LLM call's tokens and check the bead budget",
+        ),
+        (
+            "report-coverage",
+            "Store a coverage summary for a bead's stage run",
+        ),
         ("status", "Show swarm state"),
+        ("top", "Per-agent stage, heartbeat, and throughput"),
+        (
+            "forecast",
+            "Project when the backlog drains from recent throughput",
+        ),
         ("next", "Get top bead recommendation"),
         ("claim-next", "Select and claim top bead"),
+        ("accept-claim", "Claim a reserved bead"),
+        ("reject-claim", "Return a reserved bead to the pool"),
         ("assign", "Assign explicit bead to agent"),
+        ("cancel", "Cancel a bead and stop its running stage"),
+        (
+            "takeover",
+            "Hand a stale or released claim to another agent",
+        ),
+        ("recover", "Requeue claims an interrupted run left behind"),
+        ("run", "Loop claim and agent runs until the backlog drains"),
+        ("run-all", "Claim and run a bead for every idle agent"),
         ("run-once", "Run one compact orchestration cycle"),
         ("qa", "Run deterministic QA checks"),
         ("resume", "Show resumable context projections"),
         ("resume-context", "Show deep resume context payload"),
+        ("context", "Assemble full agent context for a bead"),
+        (
+            "record-symbols",
+            "Record symbol signatures for drift detection",
+        ),
         ("artifacts", "Retrieve artifact records"),
+        (
+            "artifact",
+            "Store a file as an artifact on a bead's stage run",
+        ),
+        ("diff", "Store or fetch a bead's latest patch set"),
+        (
+            "replay",
+            "Rebuild a bead's lifecycle, or re-run a recorded request trace",
+        ),
+        (
+            "explain-transition",
+            "Explain the transition a hypothetical stage result would take",
+        ),
+        (
+            "verify",
+            "Check the signature chain over a bead's artifacts",
+        ),
+        ("attest", "Export a bead's execution provenance"),
+        ("env-diff", "Compare toolchain fingerprints of two attempts"),
+        ("bead", "Snapshot or restore a bead's execution state"),
+        ("enqueue", "Add beads to the backlog from JSON or NDJSON"),
+        ("sync-backlog", "Reconcile the backlog with br list"),
+        (
+            "backlog",
+            "Preview, reprioritize or remove pending backlog beads",
+        ),
+        ("sync", "Repair claims that disagree with br"),
+        (
+            "events",
+            "Upgrade stored execution events to the latest schema",
+        ),
+        ("chaos", "Show fault injection settings and counts"),
+        ("config", "Read or change swarm settings"),
+        ("labels", "Show or set agents' default claim labels"),
+        (
+            "announcements",
+            "List announcements and their acks, or ack them",
+        ),
+        ("jobs", "List, run, disable or enable scheduled jobs"),
+        ("serve", "Run scheduled jobs as they come due"),
+        ("notify", "Send a test notification through the notifiers"),
+        ("profile", "Rank where this process spends its time"),
+        ("perf", "Find queries whose p95 latency regressed"),
+        (
+            "maintenance",
+            "List, enable, create, detach or drop monthly partitions",
+        ),
         ("agent", "Run single agent"),
         ("monitor", "View agents/progress"),
         ("register", "Register agents"),
         ("release", "Release agent claim"),
-        ("prompt", "Return agent/skill prompt"),
+        ("locks", "Lock holders and their queued waiters"),
+        ("quarantine", "Block an agent from claiming beads"),
+        ("unquarantine", "Lift an agent quarantine with a reason"),
+        ("land", "Verify push on the remote, then finalize bead"),
+        (
+            "workspace",
+            "Show, create, or remove an agent's own checkout",
+        ),
+        (
+            "session",
+            "Start, end, or show the process acting as an agent",
+        ),
+        (
+            "kv",
+            "Set, get, or delete an agent's scratch state for a bead",
+        ),
+        (
+            "blackboard",
+            "Read or append to a bead's shared plan, decisions, and open questions",
+        ),
+        (
+            "review",
+            "Submit an approve or changes verdict on a bead, or list its reviews",
+        ),
+        ("approve", "Release a bead waiting at an approval gate"),
+        (
+            "reissue-approval",
+            "Send a pending approval a fresh token when the first was lost",
+        ),
+        (
+            "prompt",
+            "Return agent/skill prompt; add/update/list stored skill prompts",
+        ),
         ("smoke", "Run smoke test"),
         ("init-db", "Initialize database"),
+        (
+            "localdb",
+            "Status, stop, destroy, logs, or upgrade the local Postgres container",
+        ),
+        (
+            "tenant",
+            "Create, list, or delete tenants hosted in one database",
+        ),
+        (
+            "undelete",
+            "Restore a deleted claim, backlog entry, or agent",
+        ),
         ("bootstrap", "Bootstrap repo"),
         ("batch", "Execute multiple commands"),
         ("state", "Full coordinator state"),
@@ -0,0 +1,197 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, read_db_from_request,
+    repo_id_from_request, to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use super::invalid_request;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::BeadSnapshot;
+use crate::{code, SwarmDb, SwarmError};
+use serde_json::json;
+use tokio::fs;
+
+const BEAD_FIX: &str =
+    "swarm bead snapshot --bead-id <bead-id> [--file <path>] | swarm bead restore --file <path> [--agent-id <id>]";
+
+/// Captures a bead's execution state as a portable snapshot, or reinstates
+/// one.
+pub(in crate::protocol_runtime) async fn handle_bead(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::BeadInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(BEAD_FIX)))?;
+
+    match input.action.as_str() {
+        "restore" => restore(request, &input).await,
+        _ => snapshot(request, &input).await,
+    }
+}
+
+async fn snapshot(
+    request: &ProtocolRequest,
+    input: &crate::BeadInput,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let bead_id = input.bead_id.clone().unwrap_or_default();
+
+    if dry_flag(request) {
+        let mut steps =
+            vec![json!({"step": 1, "action": "read_bead_state", "target": bead_id.clone()})];
+        if let Some(path) = input.file.as_deref() {
+            steps.push(json!({"step": 2, "action": "write_snapshot", "target": path}));
+        }
+        return Ok(dry_run_success(
+            request,
+            steps,
+            &format!("swarm bead snapshot --bead-id {bead_id}"),
+        ));
+    }
+
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let snapshot = db
+        .get_bead_snapshot(&repo_id_from_request(request), &bead_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+        .ok_or_else(|| {
+            Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("Bead {bead_id} has no state to snapshot"),
+                )
+                .with_fix("swarm status".to_string())
+                .with_ctx(json!({"bead_id": bead_id})),
+            )
+        })?;
+
+    let summary = json!({
+        "bead_id": snapshot.bead_id,
+        "claimed_by": snapshot.claim.as_ref().map(|claim| claim.claimed_by),
+        "stages": snapshot.stages.len(),
+        "artifacts": snapshot.artifact_count(),
+    });
+    let (data, next) = match input.file.as_deref() {
+        Some(path) => {
+            let raw = serde_json::to_string_pretty(&snapshot)
+                .map_err(|e| to_protocol_failure(SwarmError::from(e), request.rid.clone()))?;
+            fs::write(path, raw)
+                .await
+                .map_err(|e| to_protocol_failure(SwarmError::from(e), request.rid.clone()))?;
+            (
+                json!({"snapshot": summary, "file": path}),
+                format!("swarm bead restore --file {path}"),
+            )
+        }
+        None => (
+            json!({"snapshot": snapshot, "summary": summary}),
+            "swarm bead restore --snapshot '<snapshot>'".to_string(),
+        ),
+    };
+
+    Ok(CommandSuccess {
+        data,
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn restore(
+    request: &ProtocolRequest,
+    input: &crate::BeadInput,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let mut snapshot = snapshot_payload(request, input).await?;
+    if let Some(bead_id) = input.bead_id.as_deref() {
+        if bead_id != snapshot.bead_id {
+            return Err(invalid_request(
+                request,
+                format!(
+                    "Snapshot is for bead {} but --bead-id is {bead_id}",
+                    snapshot.bead_id
+                ),
+                Some(BEAD_FIX),
+            ));
+        }
+    }
+    if let Some(agent_id) = input.agent_id {
+        snapshot = snapshot.reassigned_to(agent_id);
+    }
+    snapshot
+        .validate()
+        .map_err(|message| invalid_request(request, message, Some(BEAD_FIX)))?;
+
+    let agent_id = snapshot
+        .assignment
+        .as_ref()
+        .map(|assignment| assignment.agent_id);
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "check_bead_holder", "target": snapshot.bead_id}),
+                json!({"step": 2, "action": "restore_claim", "target": snapshot.bead_id, "agent_id": agent_id}),
+                json!({"step": 3, "action": "replace_stage_history", "target": snapshot.bead_id, "stages": snapshot.stages.len(), "artifacts": snapshot.artifact_count()}),
+            ],
+            &format!("swarm artifacts --bead-id {}", snapshot.bead_id),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    db.restore_bead_snapshot(&repo_id_from_request(request), &snapshot)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "bead_id": snapshot.bead_id,
+            "source_repo_id": snapshot.repo_id,
+            "captured_at": snapshot.captured_at,
+            "agent_id": agent_id,
+            "stages": snapshot.stages.len(),
+            "artifacts": snapshot.artifact_count(),
+        }),
+        next: format!("swarm artifacts --bead-id {}", snapshot.bead_id),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Exactly one of inline `snapshot` or a JSON `file`.
+async fn snapshot_payload(
+    request: &ProtocolRequest,
+    input: &crate::BeadInput,
+) -> std::result::Result<BeadSnapshot, Box<ProtocolEnvelope>> {
+    let value = match (input.snapshot.as_ref(), input.file.as_deref()) {
+        (Some(snapshot), None) => snapshot.clone(),
+        (None, Some(path)) => {
+            let raw = fs::read_to_string(path).await.map_err(|err| {
+                Box::new(
+                    ProtocolEnvelope::error(
+                        request.rid.clone(),
+                        code::NOTFOUND.to_string(),
+                        format!("Snapshot file not found: {err}"),
+                    )
+                    .with_fix("Pass a file written by swarm bead snapshot --file".to_string())
+                    .with_ctx(json!({"file": path})),
+                )
+            })?;
+            serde_json::from_str(&raw).map_err(|err| {
+                invalid_request(
+                    request,
+                    format!("Snapshot file is not valid JSON: {err}"),
+                    Some(BEAD_FIX),
+                )
+            })?
+        }
+        _ => {
+            return Err(invalid_request(
+                request,
+                "bead restore requires exactly one of snapshot or file".to_string(),
+                Some(BEAD_FIX),
+            ))
+        }
+    };
+    serde_json::from_value(value).map_err(|err| {
+        invalid_request(
+            request,
+            format!("Snapshot is malformed: {err}"),
+            Some(BEAD_FIX),
+        )
+    })
+}
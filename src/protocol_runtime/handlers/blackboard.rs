@@ -0,0 +1,121 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::BlackboardSection;
+use crate::{code, SwarmDb};
+use serde_json::json;
+
+const BLACKBOARD_FIX: &str = "swarm blackboard read|append --bead-id <bead>";
+
+/// Shared notes agents working on one bead coordinate through.
+pub(in crate::protocol_runtime) async fn handle_blackboard(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::BlackboardInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(BLACKBOARD_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let bead_id = input.bead_id.as_str();
+    let read_next = format!("swarm blackboard read --bead-id {bead_id}");
+
+    if dry_flag(request) {
+        let target = format!("bead:{bead_id}");
+        let step = if input.action == "append" {
+            "append_blackboard_entry"
+        } else {
+            "get_blackboard"
+        };
+        return Ok(dry_run_success(
+            request,
+            vec![json!({"step": 1, "action": step, "target": target})],
+            &format!("swarm blackboard {} --bead-id {bead_id}", input.action),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let data =
+        if let ("append", Some(agent_id), Some(section), Some(content), Some(expected_version)) = (
+            input.action.as_str(),
+            input.agent_id,
+            input.section,
+            input.content.as_deref(),
+            input.expected_version,
+        ) {
+            let entry = db
+                .append_blackboard_entry(
+                    &repo_id,
+                    bead_id,
+                    agent_id,
+                    section,
+                    content,
+                    expected_version,
+                )
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let Some(entry) = entry else {
+                let current_version = db
+                    .get_blackboard_version(&repo_id, bead_id)
+                    .await
+                    .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+                return Err(stale_version(
+                    request,
+                    bead_id,
+                    expected_version,
+                    current_version,
+                ));
+            };
+            json!({"bead_id": bead_id, "version": entry.version, "entry": entry})
+        } else {
+            let board = db
+                .get_blackboard(&repo_id, bead_id, input.section)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            json!({
+                "bead_id": bead_id,
+                "version": board.version,
+                "section": input.section.as_ref().map(BlackboardSection::as_str),
+                "blackboard": board,
+            })
+        };
+
+    Ok(CommandSuccess {
+        data,
+        next: read_next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+fn stale_version(
+    request: &ProtocolRequest,
+    bead_id: &str,
+    expected_version: i64,
+    current_version: i64,
+) -> Box<ProtocolEnvelope> {
+    Box::new(
+        ProtocolEnvelope::error(
+            request.rid.clone(),
+            code::CONFLICT.to_string(),
+            format!(
+                "Blackboard for {bead_id} is at version {current_version}, not {expected_version}"
+            ),
+        )
+        .with_fix(format!(
+            "swarm blackboard read --bead-id {bead_id}, then append with --expected-version {current_version}"
+        ))
+        .with_ctx(json!({
+            "bead_id": bead_id,
+            "expected_version": expected_version,
+            "current_version": current_version,
+        })),
+    )
+}
@@ -0,0 +1,56 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use super::invalid_request;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::BeadId;
+use crate::SwarmDb;
+use serde_json::json;
+
+const CANCEL_FIX: &str = "swarm cancel --bead-id <bead-id> [--reason <text>]";
+const DEFAULT_CANCEL_REASON: &str = "cancelled by operator";
+
+/// Cancels a bead: any running stage is recorded as cancelled and its
+/// process killed, the claim is released, and the owner is told why.
+pub(in crate::protocol_runtime) async fn handle_cancel(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::CancelInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(CANCEL_FIX)))?;
+    let reason = input
+        .reason
+        .unwrap_or_else(|| DEFAULT_CANCEL_REASON.to_string());
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "mark_bead_cancelled", "target": input.bead_id, "reason": reason}),
+                json!({"step": 2, "action": "cancel_running_stage", "target": input.bead_id}),
+                json!({"step": 3, "action": "release_claim", "target": input.bead_id}),
+                json!({"step": 4, "action": "notify_owner", "target": input.bead_id}),
+            ],
+            "swarm status",
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let cancellation = db
+        .cancel_bead(
+            &repo_id_from_request(request),
+            &BeadId::new(input.bead_id),
+            &reason,
+        )
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "cancellation": cancellation,
+            "reason": reason,
+        }),
+        next: "swarm status".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
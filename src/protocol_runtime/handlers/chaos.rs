@@ -0,0 +1,32 @@
+use super::super::{minimal_state_for_request, CommandSuccess, ParseInput, ProtocolRequest};
+use super::invalid_request;
+use crate::protocol_envelope::ProtocolEnvelope;
+use serde_json::json;
+
+const CHAOS_FIX: &str = "swarm chaos status";
+
+/// Reports whether fault injection is built in and configured, and how many
+/// faults of each kind it has injected in this process. A chaos build whose
+/// `SWARM_CHAOS_*` variables are invalid fails with `INVALID`.
+pub(in crate::protocol_runtime) async fn handle_chaos(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    crate::ChaosInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(CHAOS_FIX)))?;
+
+    let status = crate::chaos::status();
+    if let Some(error) = status.config_error.clone() {
+        return Err(invalid_request(request, error, Some(CHAOS_FIX)));
+    }
+    let next = if status.compiled {
+        "swarm status"
+    } else {
+        "cargo build --features chaos"
+    };
+
+    Ok(CommandSuccess {
+        data: json!({"chaos": status}),
+        next: next.to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
@@ -0,0 +1,150 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, read_db_from_request,
+    repo_id_from_request, to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{ConfigKey, SwarmConfig};
+use crate::{code, ConfigInput, SwarmDb};
+use serde_json::{json, Map, Value};
+use std::time::Instant;
+
+const CONFIG_FIX: &str = "swarm config get | swarm config set max_agents 8";
+
+/// `config get` reports the swarm's settings; `config set` changes them,
+/// recording each changed key in `command_audit` as `config-set` and one
+/// `config_changed` orchestrator event with every change.
+pub(in crate::protocol_runtime) async fn handle_config(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = ConfigInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(CONFIG_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    if input.action == "get" {
+        get_config(request, input.key).await
+    } else {
+        set_config(request, input).await
+    }
+}
+
+async fn get_config(
+    request: &ProtocolRequest,
+    key: Option<ConfigKey>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let config = db
+        .get_config(&repo_id_from_request(request))
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    let data = match key {
+        Some(key) => json!({"key": key.as_str(), "value": key.value_in(&config)}),
+        None => json!({
+            "config": config_values(&config),
+            "swarm_started_at": config.swarm_started_at,
+        }),
+    };
+    Ok(CommandSuccess {
+        data,
+        next: "swarm config set <key> <value>".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn set_config(
+    request: &ProtocolRequest,
+    input: ConfigInput,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let repo_id = repo_id_from_request(request);
+
+    if dry_flag(request) {
+        let steps = input
+            .changes
+            .iter()
+            .enumerate()
+            .map(|(index, change)| {
+                json!({
+                    "step": index + 1,
+                    "action": "update_swarm_config",
+                    "target": repo_id.value(),
+                    "key": change.key().as_str(),
+                    "change": change,
+                })
+            })
+            .collect();
+        return Ok(dry_run_success(request, steps, "swarm config get"));
+    }
+
+    let started = Instant::now();
+    let db: SwarmDb = db_from_request(request).await?;
+    let before = db
+        .get_config(&repo_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let mut after = before.clone();
+    for change in input.changes {
+        change.apply(&mut after);
+    }
+    let changes = ConfigKey::ALL
+        .into_iter()
+        .filter_map(|key| {
+            let (from, to) = (key.value_in(&before), key.value_in(&after));
+            (from != to).then(|| json!({"key": key.as_str(), "from": from, "to": to}))
+        })
+        .collect::<Vec<_>>();
+
+    if !changes.is_empty() {
+        db.save_swarm_config(&after)
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+        let ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        for change in &changes {
+            db.record_command_audit(
+                "config-set",
+                request.rid.as_deref(),
+                json!({"repo_id": repo_id.value(), "change": change}),
+                true,
+                ms,
+                None,
+            )
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+        }
+        db.record_orchestrator_event(
+            repo_id.value(),
+            "config_changed",
+            None,
+            None,
+            &json!({"changes": &changes}),
+        )
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    }
+
+    Ok(CommandSuccess {
+        data: json!({
+            "changed": changes.len(),
+            "changes": changes,
+            "config": config_values(&after),
+        }),
+        next: "swarm config get".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+fn config_values(config: &SwarmConfig) -> Value {
+    Value::Object(
+        ConfigKey::ALL
+            .into_iter()
+            .map(|key| (key.as_str().to_string(), key.value_in(config)))
+            .collect::<Map<_, _>>(),
+    )
+}
@@ -0,0 +1,306 @@
+use super::super::{
+    minimal_state_for_request, read_db_from_request, repo_id_from_request,
+    run_external_json_command, to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::{
+    code, ArtifactType, BeadId, DeepResumeContextContract, ResumeArtifactDetailContract, SwarmDb,
+};
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+
+const DEFAULT_CONTEXT_SKILL: &str = "rust-contract";
+
+/// Everything an agent needs to work on one bead, assembled from `br`, the
+/// resume projection, stage artifacts, and the skill prompt for its stage.
+pub(in crate::protocol_runtime) async fn handle_context(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::ContextInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm context --bead-id <bead-id>".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let budget = super::resume::context_budget_from_request(request)?;
+
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let bead_id = BeadId::new(input.bead_id.as_str());
+
+    let bead = run_external_json_command(
+        "br",
+        &["show", bead_id.value(), "--json"],
+        request.rid.clone(),
+        "Run `br show <bead-id> --json` and verify bead exists",
+    )
+    .await
+    .ok()
+    .and_then(|payload| first_issue(&payload).cloned());
+    let mut resume = db
+        .get_deep_resume_contexts(&repo_id, Some(&bead_id), budget)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+        .into_iter()
+        .next();
+    // A resumable bead already carries its (budgeted) artifacts; otherwise
+    // read them directly so finished or unassigned beads still get context.
+    let artifacts = match resume.as_mut() {
+        Some(context) => std::mem::take(&mut context.artifacts),
+        None => db
+            .get_bead_artifacts(&repo_id, &bead_id, None)
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+            .into_iter()
+            .map(ResumeArtifactDetailContract::from)
+            .collect(),
+    };
+
+    if bead.is_none() && resume.is_none() && artifacts.is_empty() {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::NOTFOUND.to_string(),
+                format!("No context found for bead {bead_id}"),
+            )
+            .with_fix("Check the id with `br show <bead-id>` or `swarm resume`".to_string())
+            .with_ctx(json!({"bead_id": bead_id.value()})),
+        ));
+    }
+
+    let skill = input
+        .skill
+        .clone()
+        .or_else(|| {
+            resume
+                .as_ref()
+                .and_then(|context| context.current_stage.clone())
+        })
+        .filter(|stage| stage != "done")
+        .unwrap_or_else(|| DEFAULT_CONTEXT_SKILL.to_string());
+    let stored_prompt = db
+        .get_latest_skill_prompt(&repo_id, &skill)
+        .await
+        .ok()
+        .flatten();
+    let skill_prompt = match stored_prompt {
+        Some(stored) => Some(
+            json!({"skill": skill, "source": "db", "version": stored.version, "prompt": stored.body}),
+        ),
+        None => crate::skill_prompts::get_skill_prompt(&skill)
+            .map(|prompt| json!({"skill": skill, "source": "embedded", "prompt": prompt})),
+    };
+
+    let files = file_manifest(&artifacts);
+    let missing = [
+        ("bead", bead.is_none()),
+        ("resume", resume.is_none()),
+        ("skill_prompt", skill_prompt.is_none()),
+    ]
+    .into_iter()
+    .filter_map(|(section, absent)| absent.then_some(section))
+    .collect::<Vec<_>>();
+    let text = render_context_text(
+        bead_id.value(),
+        bead.as_ref(),
+        resume.as_ref(),
+        &artifacts,
+        &files,
+        skill_prompt.as_ref(),
+    );
+
+    Ok(CommandSuccess {
+        data: json!({
+            "bead_id": bead_id.value(),
+            "skill": skill,
+            "bead": bead,
+            "resume": resume,
+            "files": files,
+            "artifacts": artifacts,
+            "skill_prompt": skill_prompt,
+            "truncated": resume.as_ref().and_then(|context| context.truncated.clone()),
+            "missing": missing,
+            "text": text,
+        }),
+        next: format!("swarm prompt --skill {skill}"),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+fn first_issue(payload: &Value) -> Option<&Value> {
+    if payload.is_object() {
+        return Some(payload);
+    }
+    payload.as_array().and_then(|items| items.first())
+}
+
+/// Files touched across every `modified_files` artifact, first mention first.
+/// Artifacts hold a JSON array; plain newline lists are accepted too.
+fn file_manifest(artifacts: &[ResumeArtifactDetailContract]) -> Vec<String> {
+    let mut files = Vec::new();
+    artifacts
+        .iter()
+        .filter(|artifact| artifact.artifact_type == ArtifactType::ModifiedFiles.as_str())
+        .flat_map(|artifact| {
+            serde_json::from_str::<Vec<String>>(&artifact.content).unwrap_or_else(|_| {
+                artifact
+                    .content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+        })
+        .for_each(|file| {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        });
+    files
+}
+
+fn render_context_text(
+    bead_id: &str,
+    bead: Option<&Value>,
+    resume: Option<&DeepResumeContextContract>,
+    artifacts: &[ResumeArtifactDetailContract],
+    files: &[String],
+    skill_prompt: Option<&Value>,
+) -> String {
+    let mut out = format!("# Bead {bead_id}\n");
+    if let Some(bead) = bead {
+        if let Some(title) = bead["title"].as_str() {
+            let _ = writeln!(out, "\n**{title}**");
+        }
+        if let Some(description) = bead["description"].as_str() {
+            let _ = writeln!(out, "\n{}", description.trim_end());
+        }
+    }
+
+    if let Some(resume) = resume {
+        let _ = writeln!(
+            out,
+            "\n## Progress\n- stage: {}\n- status: {}\n- implementation attempt: {}",
+            resume.current_stage.as_deref().unwrap_or("-"),
+            resume.status,
+            resume.implementation_attempt
+        );
+        if let Some(feedback) = resume.feedback.as_deref() {
+            let _ = writeln!(out, "- feedback: {feedback}");
+        }
+        if let Some(diagnostics) = resume.diagnostics.as_ref() {
+            let _ = writeln!(
+                out,
+                "\n## Diagnostics\n- category: {}\n- retryable: {}\n- next: {}",
+                diagnostics.category, diagnostics.retryable, diagnostics.next_command
+            );
+            if let Some(detail) = diagnostics.detail.as_deref() {
+                let _ = writeln!(out, "- detail: {detail}");
+            }
+        }
+    }
+
+    if !files.is_empty() {
+        out.push_str("\n## Files\n");
+        for file in files {
+            let _ = writeln!(out, "- {file}");
+        }
+    }
+
+    if !artifacts.is_empty() {
+        out.push_str("\n## Artifacts\n");
+        for artifact in artifacts {
+            let body = if artifact.content.is_empty() && artifact.byte_length > 0 {
+                format!(
+                    "_elided to fit the context budget ({} bytes); fetch with `swarm artifacts --bead-id {bead_id}`_",
+                    artifact.byte_length
+                )
+            } else {
+                artifact.content.trim_end().to_string()
+            };
+            let _ = writeln!(
+                out,
+                "\n### {} (#{}, {})\n\n{body}",
+                artifact.artifact_type,
+                artifact.id,
+                artifact.created_at.to_rfc3339(),
+            );
+        }
+    }
+
+    if let Some(prompt) = skill_prompt {
+        let _ = writeln!(
+            out,
+            "\n## Skill: {}\n\n{}",
+            prompt["skill"].as_str().unwrap_or_default(),
+            prompt["prompt"].as_str().unwrap_or_default().trim_end()
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn artifact(
+        id: i64,
+        artifact_type: ArtifactType,
+        content: &str,
+    ) -> ResumeArtifactDetailContract {
+        ResumeArtifactDetailContract::from(crate::StageArtifact {
+            id,
+            stage_history_id: 1,
+            artifact_type,
+            content: content.to_string(),
+            metadata: None,
+            created_at: Utc::now(),
+            content_hash: None,
+        })
+    }
+
+    #[test]
+    fn given_modified_files_artifacts_when_building_manifest_then_files_are_deduped_in_order() {
+        let artifacts = vec![
+            artifact(
+                1,
+                ArtifactType::ModifiedFiles,
+                r#"["src/lib.rs","src/db.rs"]"#,
+            ),
+            artifact(2, ArtifactType::ContractDocument, "src/ignored.rs"),
+            artifact(3, ArtifactType::ModifiedFiles, "src/db.rs\nsrc/main.rs\n"),
+        ];
+
+        assert_eq!(
+            file_manifest(&artifacts),
+            vec!["src/lib.rs", "src/db.rs", "src/main.rs"]
+        );
+    }
+
+    #[test]
+    fn given_bead_and_skill_prompt_when_rendering_then_sections_are_in_order() {
+        let bead = json!({"title": "Add retries", "description": "Retry failed claims."});
+        let prompt = json!({"skill": "implement", "prompt": "Write the code."});
+
+        let text = render_context_text(
+            "bd-1",
+            Some(&bead),
+            None,
+            &[],
+            &["src/lib.rs".to_string()],
+            Some(&prompt),
+        );
+
+        assert_eq!(
+            text,
+            "# Bead bd-1\n\n**Add retries**\n\nRetry failed claims.\n\n## Files\n- src/lib.rs\n\n## Skill: implement\n\nWrite the code.\n"
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use super::super::{
+    db_from_request, minimal_state_for_request, repo_id_from_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::code;
+use crate::db::swarm_db::CostQuery;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::CostReport;
+use serde_json::json;
+
+/// Prices recorded token usage per model and totals it with stage run time
+/// per bead, agent and stage over the requested window.
+pub(in crate::protocol_runtime) async fn handle_costs(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::CostsInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm costs --since 2024-01-01T00:00:00Z".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    let pricing = input.pricing.map_or_else(
+        || {
+            crate::config::model_pricing_from_env()
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))
+        },
+        Ok,
+    )?;
+
+    let repo_id = repo_id_from_request(request);
+    let db = db_from_request(request).await?;
+    let query = CostQuery {
+        bead_filter: input.bead_id.as_deref(),
+        since: input.since,
+        until: input.until,
+    };
+    let usage = db
+        .get_token_usage(&repo_id, &query)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let compute = db
+        .get_stage_compute(&repo_id, &query)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let report = CostReport::build(&usage, &compute, &pricing);
+
+    let next = match (input.bead_id.as_deref(), report.by_bead.first()) {
+        (None, Some(top)) => format!("swarm costs --bead-id {}", top.key),
+        _ => "swarm status".to_string(),
+    };
+
+    Ok(CommandSuccess {
+        data: json!({
+            "window": {"since": input.since, "until": input.until},
+            "bead_id": input.bead_id,
+            "currency": "usd",
+            "records": usage.len(),
+            "report": report,
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
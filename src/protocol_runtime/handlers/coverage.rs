@@ -0,0 +1,85 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use super::invalid_request;
+use crate::code;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{parse_coverage, AgentId, BeadId};
+use serde_json::json;
+use tokio::fs;
+
+const REPORT_COVERAGE_FIX: &str =
+    "swarm report-coverage --agent-id 1 --bead-id <bead> --stage qa-enforcer --path coverage/lcov.info";
+
+/// Parses an lcov or Cobertura report down to its totals and stores them as
+/// a `coverage_report` artifact on the bead's stage run.
+pub(in crate::protocol_runtime) async fn handle_report_coverage(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::ReportCoverageInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(REPORT_COVERAGE_FIX)))?;
+
+    let raw = match (input.path.as_deref(), input.report.as_ref()) {
+        (Some(path), None) => fs::read_to_string(path).await.map_err(|err| {
+            Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("Coverage report not found: {err}"),
+                )
+                .with_fix("Pass an existing lcov or Cobertura file with --path".to_string())
+                .with_ctx(json!({"path": path})),
+            )
+        })?,
+        (None, Some(report)) => report.clone(),
+        _ => {
+            return Err(invalid_request(
+                request,
+                "report-coverage requires exactly one of path or report".to_string(),
+                Some(REPORT_COVERAGE_FIX),
+            ))
+        }
+    };
+    let summary = parse_coverage(&raw, input.format)
+        .map_err(|error| invalid_request(request, error, Some(REPORT_COVERAGE_FIX)))?;
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![json!({
+                "step": 1,
+                "action": "store_coverage_report",
+                "target": input.bead_id,
+                "line_percent": summary.line_percent,
+            })],
+            "swarm monitor --view coverage",
+        ));
+    }
+
+    let repo_id = repo_id_from_request(request);
+    let agent_id = AgentId::new(repo_id, input.agent_id);
+    let bead_id = BeadId::new(input.bead_id.clone());
+    let db = db_from_request(request).await?;
+    let (artifact_id, stage_history_id, stage) = db
+        .record_coverage_summary(&agent_id, &bead_id, input.stage, &summary)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let min_coverage = crate::config::min_coverage_from_env()
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "id": artifact_id,
+            "stage_history_id": stage_history_id,
+            "agent_id": input.agent_id,
+            "bead_id": input.bead_id,
+            "stage": stage,
+            "summary": summary,
+            "min_coverage": min_coverage,
+            "meets_minimum": min_coverage.is_none_or(|min| summary.meets(min)),
+        }),
+        next: format!("swarm monitor --view coverage --bead-id {}", input.bead_id),
+        state: minimal_state_for_request(request).await,
+    })
+}
@@ -0,0 +1,264 @@
+use super::super::validation::limit_exceeded;
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, read_db_from_request,
+    repo_id_from_request, to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{BeadDiff, DiffStat, RepoId, ScopeViolation, ViolationReason};
+use crate::{code, ArtifactType, BeadId, DiffInput, SwarmDb, SwarmError};
+use serde_json::json;
+
+const DIFF_FIX: &str = "swarm diff put --bead-id <bead> --file changes.patch --base-revision <sha>";
+
+/// `diff put` stores a patch set as the bead's `diff` artifact with its
+/// per-file stat as metadata; `diff get` returns the latest one.
+pub(in crate::protocol_runtime) async fn handle_diff(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = DiffInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(DIFF_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    if input.action == "get" {
+        get_diff(request, &input).await
+    } else {
+        put_diff(request, input).await
+    }
+}
+
+async fn put_diff(
+    request: &ProtocolRequest,
+    input: DiffInput,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let max_bytes = crate::config::max_artifact_bytes_from_env();
+    let patch = match (input.patch, input.file.as_deref()) {
+        (Some(patch), _) => patch,
+        (None, Some(file)) => read_patch_file(request, file, max_bytes).await?,
+        (None, None) => String::new(),
+    };
+    if patch.len() > max_bytes {
+        return Err(limit_exceeded(
+            request.rid.clone(),
+            "max_artifact_bytes",
+            max_bytes,
+            patch.len(),
+        ));
+    }
+    let stat = DiffStat::parse(&patch, input.base_revision);
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![json!({
+                "step": 1,
+                "action": "store_stage_artifact",
+                "target": input.bead_id,
+                "stage": input.stage.map(|stage| stage.as_str()),
+                "artifact_type": ArtifactType::Diff.as_str(),
+                "files": stat.files.len(),
+                "insertions": stat.insertions,
+                "deletions": stat.deletions,
+            })],
+            &format!("swarm diff get --bead-id {}", input.bead_id),
+        ));
+    }
+
+    let db = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let bead_id = BeadId::new(input.bead_id.as_str());
+    let stored = db
+        .put_bead_artifact(
+            &repo_id,
+            &bead_id,
+            input.stage,
+            ArtifactType::Diff,
+            &patch,
+            json!(stat),
+        )
+        .await
+        .map_err(|error| to_protocol_failure(error, request.rid.clone()))?;
+    let scope_violations = record_scope_violations(&db, &repo_id, &bead_id, &stat)
+        .await
+        .map_err(|error| to_protocol_failure(error, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "id": stored.id,
+            "bead_id": input.bead_id,
+            "stage_history_id": stored.stage_history_id,
+            "content_hash": stored.content_hash,
+            "deduplicated": stored.deduplicated,
+            "bytes": patch.len(),
+            "base_revision": stat.base_revision,
+            "files": stat.files,
+            "insertions": stat.insertions,
+            "deletions": stat.deletions,
+            "scope_violations": scope_violations,
+        }),
+        next: format!("swarm diff get --bead-id {}", input.bead_id),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Records a `scope_violation` for every file the patch touches that another
+/// bead holds a live lock on, charged to the agent holding this bead's claim,
+/// and lists them with the lock each one crossed.
+async fn record_scope_violations(
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    bead_id: &BeadId,
+    stat: &DiffStat,
+) -> crate::Result<Vec<serde_json::Value>> {
+    let paths = stat
+        .files
+        .iter()
+        .map(|file| file.path.clone())
+        .collect::<Vec<_>>();
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    let locks = db
+        .get_foreign_resource_locks(&paths, bead_id.value())
+        .await?;
+    if locks.is_empty() {
+        return Ok(Vec::new());
+    }
+    let violations = locks
+        .iter()
+        .map(|(path, _, _)| ScopeViolation {
+            path: path.clone(),
+            reason: ViolationReason::Conflict,
+        })
+        .collect::<Vec<_>>();
+    let agent_id = db.bead_claim_holder(repo_id, bead_id.value()).await?;
+    db.record_scope_violations(repo_id, bead_id, agent_id, &violations)
+        .await?;
+    Ok(locks
+        .into_iter()
+        .map(|(path, held_by, held_for)| {
+            json!({
+                "path": path,
+                "reason": ViolationReason::Conflict.as_str(),
+                "held_by": held_by,
+                "held_for": held_for,
+            })
+        })
+        .collect())
+}
+
+async fn get_diff(
+    request: &ProtocolRequest,
+    input: &DiffInput,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let db = read_db_from_request(request).await?;
+    let diff = db
+        .get_latest_bead_artifact_by_type(
+            &repo_id_from_request(request),
+            &BeadId::new(input.bead_id.as_str()),
+            ArtifactType::Diff,
+        )
+        .await
+        .map_err(|error| to_protocol_failure(error, request.rid.clone()))?
+        .as_ref()
+        .and_then(BeadDiff::from_artifact)
+        .ok_or_else(|| {
+            Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("No diff stored for bead {}", input.bead_id),
+                )
+                .with_fix(format!(
+                    "swarm diff put --bead-id {} --file changes.patch",
+                    input.bead_id
+                ))
+                .with_ctx(json!({"bead_id": input.bead_id})),
+            )
+        })?;
+
+    let mut data = json!(diff);
+    if let Some(file) = input.file.as_deref() {
+        tokio::fs::write(file, diff.patch.as_bytes())
+            .await
+            .map_err(|error| {
+                to_protocol_failure(
+                    SwarmError::IoError(std::io::Error::new(
+                        error.kind(),
+                        format!("Failed to write {file}: {error}"),
+                    )),
+                    request.rid.clone(),
+                )
+            })?;
+        if let Some(fields) = data.as_object_mut() {
+            fields.remove("patch");
+            fields.insert("path".to_string(), json!(file));
+            fields.insert("bytes".to_string(), json!(diff.patch.len()));
+        }
+    }
+    if let Some(fields) = data.as_object_mut() {
+        fields.insert("bead_id".to_string(), json!(input.bead_id));
+    }
+
+    let next = input.file.as_deref().map_or_else(
+        || format!("swarm resume-context --bead-id {}", input.bead_id),
+        |file| format!("git apply {file}"),
+    );
+    Ok(CommandSuccess {
+        data,
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Reads a patch file, refusing files over `max_bytes` before reading them
+/// and files that are not UTF-8 text.
+async fn read_patch_file(
+    request: &ProtocolRequest,
+    file: &str,
+    max_bytes: usize,
+) -> std::result::Result<String, Box<ProtocolEnvelope>> {
+    let not_found = |error: std::io::Error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::NOTFOUND.to_string(),
+                format!("Patch file not readable: {error}"),
+            )
+            .with_fix("Pass an existing file with --file, or the patch with --patch".to_string())
+            .with_ctx(json!({"file": file})),
+        )
+    };
+    let size = tokio::fs::metadata(file).await.map_err(not_found)?.len();
+    let size = usize::try_from(size).unwrap_or(usize::MAX);
+    if size > max_bytes {
+        return Err(limit_exceeded(
+            request.rid.clone(),
+            "max_artifact_bytes",
+            max_bytes,
+            size,
+        ));
+    }
+    let bytes = tokio::fs::read(file).await.map_err(not_found)?;
+    String::from_utf8(bytes).map_err(|_| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                "Patch file is not UTF-8 text".to_string(),
+            )
+            .with_fix(
+                "Store binary files with swarm artifact put; diffs must be text patches"
+                    .to_string(),
+            )
+            .with_ctx(json!({"file": file})),
+        )
+    })
+}
@@ -1,14 +1,47 @@
 use super::super::{
-    check_command, check_database_connectivity, minimal_state_for_request, CommandSuccess,
-    ProtocolRequest,
+    check_chaos, check_command, check_database_connectivity, check_enum_drift, check_event_sinks,
+    check_notifications, check_remote_executors, check_scheduled_jobs, check_stage_parsers,
+    check_stage_sandbox, db_from_request, dry_flag, dry_run_success, explain_database_candidates,
+    minimal_state_for_request, repo_id_from_request, to_protocol_failure, CommandSuccess,
+    ParseInput, ProtocolRequest, DEFAULT_DB_CONNECT_TIMEOUT_MS,
 };
+use crate::code;
+use crate::db::swarm_db::{ReconnectPolicy, DEFAULT_HEALTH_SAMPLES, MAX_HEALTH_SAMPLES};
+use crate::index_advisor::{remedy, seq_scanned_tables, IndexState, HOT_QUERY_PROBES};
 use crate::protocol_envelope::ProtocolEnvelope;
 use serde_json::{json, Value};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Bound on connecting and pinging in `healthz`, so a probe against an
+/// unreachable database fails fast instead of waiting on retries.
+const HEALTHZ_TIMEOUT_MS: u64 = 2_000;
 
 pub(in crate::protocol_runtime) async fn handle_doctor(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::DoctorInput::parse_input(request).ok();
+    let analyze_db = input
+        .as_ref()
+        .and_then(|input| input.analyze_db)
+        .unwrap_or(false);
+    let apply = input
+        .as_ref()
+        .and_then(|input| input.apply)
+        .unwrap_or(false);
+    if apply && !analyze_db {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                "--apply needs --analyze-db".to_string(),
+            )
+            .with_fix("swarm doctor --analyze-db --apply".to_string()),
+        ));
+    }
+    if analyze_db {
+        return handle_analyze_db(request, apply).await;
+    }
+
     let total_start = Instant::now();
     let moon_start = Instant::now();
     let moon = check_command("moon").await;
@@ -25,11 +58,69 @@ pub(in crate::protocol_runtime) async fn handle_doctor(
     let psql_start = Instant::now();
     let psql = check_command("psql").await;
     let psql_ms = elapsed_ms(psql_start);
+    let sandbox_start = Instant::now();
+    let sandbox = check_stage_sandbox().await;
+    let sandbox_ms = elapsed_ms(sandbox_start);
+    let remote_start = Instant::now();
+    let remote = check_remote_executors().await;
+    let remote_ms = elapsed_ms(remote_start);
+    let parsers_start = Instant::now();
+    let parsers = check_stage_parsers();
+    let parsers_ms = elapsed_ms(parsers_start);
+    let sinks_start = Instant::now();
+    let sinks = check_event_sinks();
+    let sinks_ms = elapsed_ms(sinks_start);
+    let notifications_start = Instant::now();
+    let notifications = check_notifications();
+    let notifications_ms = elapsed_ms(notifications_start);
+    let chaos_start = Instant::now();
+    let chaos = check_chaos();
+    let chaos_ms = elapsed_ms(chaos_start);
     let database_start = Instant::now();
     let database = check_database_connectivity(request).await;
     let database_ms = elapsed_ms(database_start);
-    let mut checks = vec![moon, br, jj, zjj, psql];
+    // Only scanned when the database answers; otherwise `database` has
+    // already failed for the same reason.
+    let drift_start = Instant::now();
+    let drift = if database["ok"].as_bool().is_some_and(|ok| ok) {
+        Some(check_enum_drift(request).await)
+    } else {
+        None
+    };
+    let drift_ms = drift.as_ref().map(|_| elapsed_ms(drift_start));
+    let jobs_start = Instant::now();
+    let jobs = if drift.is_some() {
+        Some(check_scheduled_jobs(request).await)
+    } else {
+        None
+    };
+    let jobs_ms = jobs.as_ref().map(|_| elapsed_ms(jobs_start));
+    let show_candidates = input
+        .and_then(|input| input.show_candidates)
+        .unwrap_or(false);
+    let database_candidates = if show_candidates {
+        Some(explain_database_candidates(request, DEFAULT_DB_CONNECT_TIMEOUT_MS).await)
+    } else {
+        None
+    };
+    let mut checks = vec![
+        moon,
+        br,
+        jj,
+        zjj,
+        psql,
+        sandbox,
+        remote,
+        parsers,
+        sinks,
+        notifications,
+        chaos,
+    ];
     checks.push(database);
+    let enum_drift = drift.as_ref().and_then(|check| check.get("drift")).cloned();
+    checks.extend(drift);
+    let scheduled_jobs = jobs.as_ref().and_then(|check| check.get("jobs")).cloned();
+    checks.extend(jobs);
     let failed = checks
         .iter()
         .filter(|check| !check["ok"].as_bool().is_some_and(|value| value))
@@ -45,25 +136,44 @@ pub(in crate::protocol_runtime) async fn handle_doctor(
         })
         .collect();
 
+    let mut data = json!({
+        "v": "v1",
+        "h": failed == 0,
+        "p": passed,
+        "f": failed,
+        "c": check_results,
+        "timing": {
+            "checks_ms": {
+                "moon": moon_ms,
+                "br": br_ms,
+                "jj": jj_ms,
+                "zjj": zjj_ms,
+                "psql": psql_ms,
+                "stage_sandbox": sandbox_ms,
+                "remote_executors": remote_ms,
+                "stage_parsers": parsers_ms,
+                "event_sinks": sinks_ms,
+                "notifications": notifications_ms,
+                "chaos": chaos_ms,
+                "database": database_ms,
+                "enum_drift": drift_ms,
+                "scheduled_jobs": jobs_ms,
+            },
+            "total_ms": elapsed_ms(total_start),
+        }
+    });
+    if let Some(drift) = enum_drift {
+        data["enum_drift"] = drift;
+    }
+    if let Some(jobs) = scheduled_jobs {
+        data["scheduled_jobs"] = jobs;
+    }
+    if let Some(candidates) = database_candidates {
+        data["database_candidates"] = candidates;
+    }
+
     Ok(CommandSuccess {
-        data: json!({
-            "v": "v1",
-            "h": failed == 0,
-            "p": passed,
-            "f": failed,
-            "c": check_results,
-            "timing": {
-                "checks_ms": {
-                    "moon": moon_ms,
-                    "br": br_ms,
-                    "jj": jj_ms,
-                    "zjj": zjj_ms,
-                    "psql": psql_ms,
-                    "database": database_ms,
-                },
-                "total_ms": elapsed_ms(total_start),
-            }
-        }),
+        data,
         next: if failed == 0 {
             "swarm state".to_string()
         } else {
@@ -73,6 +183,256 @@ pub(in crate::protocol_runtime) async fn handle_doctor(
     })
 }
 
+/// `doctor --analyze-db`: plans each hot coordinator query with `EXPLAIN`
+/// and, where one scans a large table, recommends the curated index that
+/// query was written against. `apply` runs the recommendations; a failed
+/// one is reported and the rest still run.
+async fn handle_analyze_db(
+    request: &ProtocolRequest,
+    apply: bool,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    if dry_flag(request) {
+        let mut steps = vec![json!({
+            "step": 1,
+            "action": "explain_hot_queries",
+            "target": "database",
+            "probes": HOT_QUERY_PROBES.iter().map(|probe| probe.name).collect::<Vec<_>>(),
+        })];
+        if apply {
+            steps.push(
+                json!({"step": 2, "action": "apply_index_recommendations", "target": "database"}),
+            );
+        }
+        return Ok(dry_run_success(request, steps, "swarm doctor --analyze-db"));
+    }
+
+    let total_start = Instant::now();
+    let db = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let mut tables = HOT_QUERY_PROBES
+        .iter()
+        .map(|probe| probe.table)
+        .collect::<Vec<_>>();
+    tables.sort_unstable();
+    tables.dedup();
+    let indexes = HOT_QUERY_PROBES
+        .iter()
+        .map(|probe| probe.index)
+        .collect::<Vec<_>>();
+    let table_rows = db
+        .table_row_estimates(&tables)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let index_states = db
+        .index_states(&indexes)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    let mut probes = Vec::new();
+    let mut recommendations = Vec::new();
+    let mut applied = Vec::new();
+    let mut statements = Vec::new();
+    for probe in HOT_QUERY_PROBES {
+        let plan = match db.explain_probe(probe, &repo_id).await {
+            Ok(plan) => plan,
+            Err(error) => {
+                probes.push(json!({
+                    "name": probe.name,
+                    "table": probe.table,
+                    "ok": false,
+                    "error": error.to_string(),
+                }));
+                continue;
+            }
+        };
+        let seq_scans = seq_scanned_tables(&plan);
+        let rows = table_rows.get(probe.table).copied().unwrap_or(0);
+        let index_state = index_states
+            .get(probe.index)
+            .copied()
+            .unwrap_or(IndexState::Missing);
+        let fix = remedy(probe, &seq_scans, rows, index_state);
+        probes.push(json!({
+            "name": probe.name,
+            "table": probe.table,
+            "ok": fix.is_none(),
+            "rows_estimate": rows,
+            "seq_scans": seq_scans,
+            "index": probe.index,
+            "index_state": index_state,
+            "remedy": fix,
+        }));
+
+        let Some(fix) = fix else {
+            continue;
+        };
+        let statement = fix.statement(probe);
+        // Two probes on one stale table share a single `ANALYZE`.
+        if statements.contains(&statement) {
+            continue;
+        }
+        statements.push(statement.clone());
+        recommendations.push(json!({
+            "probe": probe.name,
+            "remedy": fix,
+            "sql": statement,
+        }));
+        if apply {
+            let outcome = db.apply_index_remedy(probe, fix).await;
+            applied.push(json!({
+                "sql": statement,
+                "ok": outcome.is_ok(),
+                "error": outcome.err().map(|error| error.to_string()),
+            }));
+        }
+    }
+
+    let next = if recommendations.is_empty() {
+        "swarm state"
+    } else if apply {
+        "swarm doctor --analyze-db"
+    } else {
+        "swarm doctor --analyze-db --apply"
+    };
+    let mut data = json!({
+        "large_table_rows": crate::index_advisor::LARGE_TABLE_ROWS,
+        "probes": probes,
+        "recommendations": recommendations,
+        "total_ms": elapsed_ms(total_start),
+    });
+    if apply {
+        data["applied"] = json!(applied);
+    }
+
+    Ok(CommandSuccess {
+        data,
+        next: next.to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+pub(in crate::protocol_runtime) async fn handle_db_health(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::DbHealthInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("echo '{\"cmd\":\"db-health\",\"samples\":10}' | swarm".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let samples = input.samples.map_or(DEFAULT_HEALTH_SAMPLES, |value| {
+        value.min(MAX_HEALTH_SAMPLES)
+    });
+
+    let total_start = Instant::now();
+    let db = db_from_request(request).await?;
+    let health = db
+        .pool_health(samples, &ReconnectPolicy::default())
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "healthy": health.healthy,
+            "pool": {
+                "size": health.size,
+                "idle": health.idle,
+                "max": health.max_connections,
+            },
+            "acquire_ms": {
+                "samples": health.samples,
+                "p50": health.acquire_p50_ms,
+                "p95": health.acquire_p95_ms,
+                "p99": health.acquire_p99_ms,
+                "max": health.acquire_max_ms,
+            },
+            "reconnect_attempts": health.reconnect_attempts,
+            "total_ms": elapsed_ms(total_start),
+        }),
+        next: if health.healthy {
+            "swarm status".to_string()
+        } else {
+            "swarm doctor".to_string()
+        },
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// One database round trip and the last successful command, both bounded by
+/// [`HEALTHZ_TIMEOUT_MS`]; no retries and no progress query, so it stays
+/// cheap enough to poll. Pool counters are reported only while requests
+/// share a session pool; a pool made for this request alone says nothing.
+pub(in crate::protocol_runtime) async fn handle_healthz(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    crate::HealthzInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm healthz".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    let total_start = Instant::now();
+    let probe = async {
+        let db = db_from_request(request).await?;
+        let ping_start = Instant::now();
+        db.ping()
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+        let ping_ms = elapsed_ms(ping_start);
+        let last_ok_command_at = db
+            .last_successful_command_at()
+            .await
+            .ok()
+            .flatten()
+            .map(|at| at.to_rfc3339());
+        Ok::<_, Box<ProtocolEnvelope>>((db, ping_ms, last_ok_command_at))
+    };
+    let (db, ping_ms, last_ok_command_at) =
+        tokio::time::timeout(Duration::from_millis(HEALTHZ_TIMEOUT_MS), probe)
+            .await
+            .map_err(|_| {
+                Box::new(
+                    ProtocolEnvelope::error(
+                        request.rid.clone(),
+                        code::TIMEOUT.to_string(),
+                        format!("Database did not answer within {HEALTHZ_TIMEOUT_MS}ms"),
+                    )
+                    .with_fix("swarm doctor".to_string())
+                    .with_ctx(json!({"timeout_ms": HEALTHZ_TIMEOUT_MS})),
+                )
+            })??;
+    let pool = super::super::db_resolution::session_pool_reuse_enabled().then(|| {
+        json!({
+            "size": db.pool().size(),
+            "idle": db.pool().num_idle(),
+            "max": db.pool().options().get_max_connections(),
+        })
+    });
+
+    Ok(CommandSuccess {
+        data: json!({
+            "healthy": true,
+            "ping_ms": ping_ms,
+            "pool": pool,
+            "last_ok_command_at": last_ok_command_at,
+            "total_ms": elapsed_ms(total_start),
+        }),
+        next: "swarm status".to_string(),
+        state: json!({}),
+    })
+}
+
 fn elapsed_ms(start: Instant) -> u64 {
     let ms = start.elapsed().as_millis();
     u64::try_from(ms).map_or(u64::MAX, |value| value)
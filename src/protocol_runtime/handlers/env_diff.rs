@@ -0,0 +1,91 @@
+use super::super::{
+    minimal_state_for_request, read_db_from_request, repo_id_from_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{EnvironmentFingerprint, StageEnvironment};
+use crate::{code, BeadId, SwarmDb};
+use serde_json::json;
+
+/// Compares the toolchain fingerprints recorded when two attempts of a bead
+/// started.
+pub(in crate::protocol_runtime) async fn handle_env_diff(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::EnvDiffInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(
+                "swarm env-diff --bead-id <bead-id> --from-attempt 1 --to-attempt 2".to_string(),
+            )
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let bead_id = BeadId::new(input.bead_id.as_str());
+    let from_run = db
+        .get_stage_environment(&repo_id, &bead_id, input.stage, input.from_attempt)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let (from, from_env) =
+        fingerprinted_run(request, &input.bead_id, input.from_attempt, from_run)?;
+    let to_run = db
+        .get_stage_environment(&repo_id, &bead_id, input.stage, input.to_attempt)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let (to, to_env) = fingerprinted_run(request, &input.bead_id, input.to_attempt, to_run)?;
+
+    let changes = from_env.diff(&to_env);
+    let next = if changes.is_empty() {
+        format!("swarm artifacts --bead-id {}", input.bead_id)
+    } else {
+        "swarm doctor".to_string()
+    };
+    Ok(CommandSuccess {
+        data: json!({
+            "bead_id": input.bead_id,
+            "stage": input.stage.map(|stage| stage.as_str()),
+            "from": from,
+            "to": to,
+            "changed": !changes.is_empty(),
+            "changes": changes,
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Splits off the run's fingerprint, failing with `NOTFOUND` when the
+/// attempt never ran or started before fingerprints were recorded.
+fn fingerprinted_run(
+    request: &ProtocolRequest,
+    bead_id: &str,
+    attempt: u32,
+    run: Option<StageEnvironment>,
+) -> std::result::Result<(StageEnvironment, EnvironmentFingerprint), Box<ProtocolEnvelope>> {
+    let not_found = |message: String| {
+        Box::new(
+            ProtocolEnvelope::error(request.rid.clone(), code::NOTFOUND.to_string(), message)
+                .with_fix(format!("swarm replay --bead-id {bead_id}"))
+                .with_ctx(json!({"bead_id": bead_id, "attempt": attempt})),
+        )
+    };
+    let Some(run) = run else {
+        return Err(not_found(format!(
+            "Bead {bead_id} has no stage run for attempt {attempt}"
+        )));
+    };
+    match run.environment.clone() {
+        Some(environment) => Ok((run, environment)),
+        None => Err(not_found(format!(
+            "Attempt {attempt} of bead {bead_id} ({}) has no environment fingerprint",
+            run.stage
+        ))),
+    }
+}
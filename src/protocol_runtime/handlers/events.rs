@@ -0,0 +1,68 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::{code, EventSchemaVersion, SwarmDb};
+use serde_json::json;
+
+/// Rows rewritten per transaction by `events migrate`.
+const MIGRATION_BATCH_SIZE: i64 = 500;
+
+/// Rewrites stored execution events whose payloads predate the latest
+/// schema version, using the same upgrade chain readers apply.
+pub(in crate::protocol_runtime) async fn handle_events(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    crate::EventsInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm events migrate [--dry]".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let outdated = db
+        .count_outdated_execution_events()
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let latest = EventSchemaVersion::LATEST.as_i32();
+
+    if dry_flag(request) {
+        let steps = outdated
+            .iter()
+            .zip(1_u32..)
+            .map(|((version, count), step)| {
+                json!({
+                    "step": step,
+                    "action": "upgrade_events",
+                    "target": format!("v{version} -> v{latest}"),
+                    "events": count,
+                })
+            })
+            .collect();
+        return Ok(dry_run_success(request, steps, "swarm events migrate"));
+    }
+
+    let migrated = db
+        .migrate_execution_events(MIGRATION_BATCH_SIZE)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    Ok(CommandSuccess {
+        data: json!({
+            "schema_version": latest,
+            "migrated": migrated,
+            "from_versions": outdated
+                .iter()
+                .map(|(version, count)| json!({"schema_version": version, "events": count}))
+                .collect::<Vec<_>>(),
+        }),
+        next: "swarm monitor --view events".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
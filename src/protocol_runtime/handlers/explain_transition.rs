@@ -0,0 +1,128 @@
+use super::super::{
+    minimal_state_for_request, read_db_from_request, repo_id_from_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::db::write_ops::classify_failure_category;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::runtime::{
+    runtime_explain_transition_decision, RuntimeStage, RuntimeStageResult, RuntimeStageTransition,
+    TransitionReasonStep,
+};
+use crate::{code, ExplainTransitionInput, SwarmDb};
+use serde_json::json;
+
+const EXPLAIN_TRANSITION_FIX: &str =
+    "swarm explain-transition --stage qa-enforcer --result failed --attempt 2";
+
+/// Runs the runtime transition decision on a hypothetical stage result and
+/// returns it with the reason chain. Without `max_attempts` the swarm's
+/// configured `max_implementation_attempts` is used.
+pub(in crate::protocol_runtime) async fn handle_explain_transition(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = ExplainTransitionInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(EXPLAIN_TRANSITION_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    let (max_attempts, max_attempts_source) = if let Some(max_attempts) = input.max_attempts {
+        (max_attempts, "arg")
+    } else {
+        let db: SwarmDb = read_db_from_request(request).await?;
+        let config = db
+            .get_config(&repo_id_from_request(request))
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+        (config.max_implementation_attempts, "config")
+    };
+    let attempt = input.attempt.unwrap_or(0);
+    let stage = RuntimeStage::try_from(input.stage.as_str()).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(request.rid.clone(), code::INVALID.to_string(), error)
+                .with_fix(EXPLAIN_TRANSITION_FIX.to_string())
+                .with_ctx(json!({"stage": input.stage.as_str()})),
+        )
+    })?;
+    let message = input.message.clone().unwrap_or_default();
+    let result = match input.result.as_str() {
+        "passed" => RuntimeStageResult::Passed,
+        "failed" => RuntimeStageResult::Failed(message.clone()),
+        "error" => RuntimeStageResult::Error(message.clone()),
+        _ => RuntimeStageResult::Cancelled(message.clone()),
+    };
+
+    let (decision, mut steps) =
+        runtime_explain_transition_decision(stage, &result, attempt, max_attempts);
+
+    let failed = matches!(
+        result,
+        RuntimeStageResult::Failed(_) | RuntimeStageResult::Error(_)
+    );
+    let diagnostics = if failed {
+        let (category, source) = input.category.clone().map_or_else(
+            || (classify_failure_category(&message).to_string(), "message"),
+            |category| (category, "arg"),
+        );
+        steps.push(TransitionReasonStep {
+            check: "diagnostics",
+            outcome: format!(
+                "category {category} is recorded on the failure; it does not change the decision"
+            ),
+        });
+        Some(json!({
+            "category": category,
+            "category_source": source,
+            "retryable": true,
+            "next_command": "swarm stage --stage implement",
+        }))
+    } else {
+        None
+    };
+
+    let transition = decision.transition();
+    let next_stage = match transition {
+        RuntimeStageTransition::Advance(next) => Some(next.as_str()),
+        RuntimeStageTransition::Retry => Some(RuntimeStage::Implement.as_str()),
+        _ => None,
+    };
+    let next = next_stage.map_or_else(
+        || "swarm state".to_string(),
+        |stage| format!("swarm explain-transition --stage {stage} --result passed"),
+    );
+
+    Ok(CommandSuccess {
+        data: json!({
+            "stage": input.stage.as_str(),
+            "result": input.result,
+            "attempt": attempt,
+            "max_attempts": max_attempts,
+            "max_attempts_source": max_attempts_source,
+            "decision": {
+                "transition": transition_name(transition),
+                "next_stage": next_stage,
+                "reason": decision.reason_code(),
+            },
+            "reason_chain": steps,
+            "diagnostics": diagnostics,
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+const fn transition_name(transition: RuntimeStageTransition) -> &'static str {
+    match transition {
+        RuntimeStageTransition::Advance(_) => "advance",
+        RuntimeStageTransition::Retry => "retry",
+        RuntimeStageTransition::Complete => "complete",
+        RuntimeStageTransition::Block => "block",
+        RuntimeStageTransition::NoOp => "noop",
+    }
+}
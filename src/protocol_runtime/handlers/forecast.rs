@@ -0,0 +1,47 @@
+use super::super::{
+    minimal_state_for_request, read_db_from_request, repo_id_from_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::code;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{Forecast, DEFAULT_FORECAST_WINDOW_HOURS};
+use serde_json::json;
+
+/// Projects when the open backlog drains from the completion rate per agent
+/// over the lookback window, at the registered agent count or `agents`.
+pub(in crate::protocol_runtime) async fn handle_forecast(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::ForecastInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm forecast --window-hours 24 --agents 8".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let window_hours = input.window_hours.unwrap_or(DEFAULT_FORECAST_WINDOW_HOURS);
+    let db = read_db_from_request(request).await?;
+    let history = db
+        .get_throughput_history(&repo_id_from_request(request), window_hours)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let forecast = Forecast::project(history, input.agents, chrono::Utc::now());
+
+    let next = if forecast.beads_per_agent_hour.is_none() {
+        "swarm top".to_string()
+    } else {
+        format!(
+            "swarm forecast --window-hours {window_hours} --agents {}",
+            forecast.agents.saturating_mul(2).max(1)
+        )
+    };
+    Ok(CommandSuccess {
+        data: json!(forecast),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
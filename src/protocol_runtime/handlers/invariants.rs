@@ -0,0 +1,48 @@
+use super::super::{
+    db_from_request, minimal_state_for_request, repo_id_from_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::code;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::InvariantCheck;
+use serde_json::json;
+
+/// Runs the coordinator invariant checks for the current repo and lists
+/// each violation with the command that repairs it.
+pub(in crate::protocol_runtime) async fn handle_invariants(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    crate::InvariantsInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm invariants".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    let repo_id = repo_id_from_request(request);
+    let db = db_from_request(request).await?;
+    let violations = db
+        .check_invariants(Some(&repo_id))
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    let next = violations.first().map_or_else(
+        || "swarm status".to_string(),
+        |violation| violation.fix.clone(),
+    );
+
+    Ok(CommandSuccess {
+        data: json!({
+            "ok": violations.is_empty(),
+            "checks": InvariantCheck::ALL,
+            "violations": violations,
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
@@ -0,0 +1,382 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, elapsed_ms, handle_monitor,
+    minimal_state_for_request, read_db_from_request, repo_id_from_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
+};
+use super::backlog::handle_sync_backlog;
+use super::recover::handle_recover;
+use crate::alerts::evaluate_alerts;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{
+    JobSchedules, RepoId, ScheduledJobKind, ScheduledJobRun, DEFAULT_PARTITIONS_AHEAD,
+};
+use crate::{code, JobsInput, ServeInput, SwarmDb};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+const JOBS_FIX: &str = "swarm jobs list | swarm jobs run-now --job recover";
+
+/// Longest `serve` sleeps between checks, so `jobs disable` and `jobs
+/// enable` from another session take effect within a minute.
+const SERVE_MAX_SLEEP_MS: u64 = 60_000;
+
+/// `jobs list` shows each scheduled job's schedule and last run; `jobs
+/// run-now` runs one immediately and records it like a scheduled run;
+/// `jobs disable` and `jobs enable` stop and resume `serve` running it.
+pub(in crate::protocol_runtime) async fn handle_jobs(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = JobsInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(JOBS_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let schedules = crate::config::scheduled_jobs_from_env()
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    match (input.action.as_str(), input.job) {
+        ("run-now", Some(job)) => run_now(request, job).await,
+        ("disable" | "enable", Some(job)) => {
+            set_enabled(request, &schedules, job, input.action == "enable").await
+        }
+        _ => list_jobs(request, &schedules).await,
+    }
+}
+
+async fn list_jobs(
+    request: &ProtocolRequest,
+    schedules: &JobSchedules,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let runs = db
+        .get_scheduled_jobs(&repo_id_from_request(request))
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let now = Utc::now();
+
+    Ok(CommandSuccess {
+        data: json!({
+            "jobs": runs
+                .iter()
+                .map(|run| job_view(schedules, run, now))
+                .collect::<Vec<_>>(),
+        }),
+        next: "swarm serve".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn run_now(
+    request: &ProtocolRequest,
+    job: ScheduledJobKind,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "run_job", "target": job}),
+                json!({"step": 2, "action": "record_job_run", "target": "scheduled_jobs"}),
+            ],
+            "swarm jobs list",
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let (ms, outcome) = run_and_record(request, &db, &repo_id_from_request(request), job).await?;
+    let result = outcome.map_err(|mut failure| {
+        if let Some(err) = failure.err.as_mut() {
+            err.msg = format!("Job {job} failed: {}", err.msg);
+        }
+        failure
+    })?;
+
+    Ok(CommandSuccess {
+        data: json!({"job": job, "ms": ms, "result": result}),
+        next: "swarm jobs list".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn set_enabled(
+    request: &ProtocolRequest,
+    schedules: &JobSchedules,
+    job: ScheduledJobKind,
+    enabled: bool,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "update_scheduled_job", "target": job, "enabled": enabled}),
+            ],
+            "swarm jobs list",
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    db.set_scheduled_job_enabled(&repo_id_from_request(request), job, enabled)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "job": job,
+            "enabled": enabled,
+            "cron": schedules.schedule(job),
+        }),
+        next: "swarm jobs list".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Runs scheduled jobs as they come due until `budget_ms` runs out; without
+/// a budget it keeps going until the session shuts down. A failed job is
+/// recorded and `serve` carries on.
+pub(in crate::protocol_runtime) async fn handle_serve(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = ServeInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm serve --budget-ms 3600000".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let schedules = crate::config::scheduled_jobs_from_env()
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let since = Utc::now();
+
+    if dry_flag(request) {
+        let steps = ScheduledJobKind::ALL
+            .into_iter()
+            .filter_map(|job| {
+                schedules.schedule(job).map(|cron| {
+                    json!({
+                        "action": "run_job",
+                        "target": job,
+                        "cron": cron,
+                        "next_run_at": schedules.next_run_at(job, None, since),
+                    })
+                })
+            })
+            .enumerate()
+            .map(|(index, mut step)| {
+                step["step"] = json!(index + 1);
+                step
+            })
+            .collect();
+        return Ok(dry_run_success(request, steps, "swarm jobs list"));
+    }
+
+    let start = Instant::now();
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let mut tally = BTreeMap::<ScheduledJobKind, (u32, u32)>::new();
+
+    loop {
+        let states = db
+            .get_scheduled_jobs(&repo_id)
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+        let due = schedules.due(&states, since, Utc::now());
+        for job in due.iter().copied() {
+            let (_, outcome) = run_and_record(request, &db, &repo_id, job).await?;
+            let counts = tally.entry(job).or_default();
+            counts.0 += 1;
+            counts.1 += u32::from(outcome.is_err());
+        }
+
+        let remaining_ms = input
+            .budget_ms
+            .map(|budget_ms| budget_ms.saturating_sub(elapsed_ms(start)));
+        if remaining_ms == Some(0) {
+            break;
+        }
+        let states = if due.is_empty() {
+            states
+        } else {
+            db.get_scheduled_jobs(&repo_id)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+        };
+        let until_next_ms =
+            next_due_at(&schedules, &states, since).map_or(SERVE_MAX_SLEEP_MS, |next| {
+                u64::try_from((next - Utc::now()).num_milliseconds())
+                    .unwrap_or(0)
+                    .min(SERVE_MAX_SLEEP_MS)
+            });
+        let sleep_ms = remaining_ms.map_or(until_next_ms, |remaining| remaining.min(until_next_ms));
+        tokio::time::sleep(Duration::from_millis(sleep_ms)).await;
+    }
+
+    let runs = tally.values().map(|(runs, _)| runs).sum::<u32>();
+    let failed = tally.values().map(|(_, failed)| failed).sum::<u32>();
+    Ok(CommandSuccess {
+        data: json!({
+            "runs": runs,
+            "failed": failed,
+            "jobs": tally
+                .into_iter()
+                .map(|(job, (runs, failed))| {
+                    (job.as_str().to_string(), json!({"runs": runs, "failed": failed}))
+                })
+                .collect::<Map<_, _>>(),
+            "elapsed_ms": elapsed_ms(start),
+        }),
+        next: if failed == 0 {
+            "swarm jobs list".to_string()
+        } else {
+            "swarm doctor".to_string()
+        },
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+fn next_due_at(
+    schedules: &JobSchedules,
+    states: &[ScheduledJobRun],
+    since: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    states
+        .iter()
+        .filter(|state| state.enabled)
+        .filter_map(|state| schedules.next_run_at(state.job, state.last_run_at, since))
+        .min()
+}
+
+type JobOutcome = std::result::Result<Value, Box<ProtocolEnvelope>>;
+
+/// Runs `job` and stores how it went. The outer error is only for failing to
+/// store the run; the job's own failure is in the returned outcome.
+async fn run_and_record(
+    request: &ProtocolRequest,
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    job: ScheduledJobKind,
+) -> std::result::Result<(u64, JobOutcome), Box<ProtocolEnvelope>> {
+    let start = Instant::now();
+    let outcome = run_job(request, db, repo_id, job).await;
+    let ms = elapsed_ms(start);
+    let error = outcome.as_ref().err().map(|failure| {
+        failure
+            .err
+            .as_ref()
+            .map_or_else(|| "job failed".to_string(), |err| err.msg.clone())
+    });
+    db.record_scheduled_job_run(
+        repo_id,
+        job,
+        ms,
+        outcome
+            .as_ref()
+            .map_err(|_| error.as_deref().unwrap_or_default()),
+    )
+    .await
+    .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    Ok((ms, outcome))
+}
+
+async fn run_job(
+    request: &ProtocolRequest,
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    job: ScheduledJobKind,
+) -> JobOutcome {
+    let sub_request = |cmd: &str, args: Map<String, Value>| ProtocolRequest {
+        cmd: cmd.to_string(),
+        rid: request.rid.clone(),
+        dry: Some(false),
+        args,
+    };
+    match job {
+        ScheduledJobKind::SyncBacklog => {
+            handle_sync_backlog(&sub_request("sync-backlog", Map::new()))
+                .await
+                .map(|success| success.data)
+        }
+        ScheduledJobKind::Recover => handle_recover(&sub_request("recover", Map::new()))
+            .await
+            .map(|success| success.data),
+        ScheduledJobKind::SlaCheck => {
+            let args = Map::from_iter([("view".to_string(), json!("sla"))]);
+            let mut data = handle_monitor(&sub_request("monitor", args))
+                .await
+                .map(|success| success.data)?;
+            let alerts = check_alerts(request, db, repo_id).await?;
+            if let Some(data) = data.as_object_mut() {
+                data.insert("alerts".to_string(), alerts);
+            }
+            Ok(data)
+        }
+        ScheduledJobKind::Gc => db
+            .collect_garbage(repo_id)
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone())),
+        ScheduledJobKind::MetricsFlush => {
+            db.flush_write_batch().await;
+            let query_latency_rows = db
+                .flush_query_latency()
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            Ok(json!({"flushed": true, "query_latency_rows": query_latency_rows}))
+        }
+        ScheduledJobKind::Partitions => db
+            .ensure_partitions(Utc::now(), DEFAULT_PARTITIONS_AHEAD)
+            .await
+            .map(|created| json!({"created": created}))
+            .map_err(|e| to_protocol_failure(e, request.rid.clone())),
+    }
+}
+
+/// One pass of the alert rules, so alerts fire and resolve while nobody
+/// watches `monitor --view alerts`.
+async fn check_alerts(request: &ProtocolRequest, db: &SwarmDb, repo_id: &RepoId) -> JobOutcome {
+    let rules = crate::config::alert_rules_from_env()
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let notifications = crate::config::notification_rules_from_env()
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let evaluation = evaluate_alerts(db, repo_id, &rules, &notifications)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    Ok(json!({
+        "open": evaluation.open.len(),
+        "fired": evaluation.fired,
+        "resolved": evaluation.resolved,
+        "webhook_errors": evaluation.webhook_errors,
+        "notification_errors": evaluation.notification_errors,
+    }))
+}
+
+/// A job as `jobs list` and `doctor` show it.
+pub(in crate::protocol_runtime) fn job_view(
+    schedules: &JobSchedules,
+    run: &ScheduledJobRun,
+    now: DateTime<Utc>,
+) -> Value {
+    json!({
+        "job": run.job,
+        "cron": schedules.schedule(run.job),
+        "enabled": run.enabled,
+        "next_run_at": run
+            .enabled
+            .then(|| schedules.next_run_at(run.job, run.last_run_at, now))
+            .flatten(),
+        "last_run_at": run.last_run_at,
+        "last_ok": run.last_ok,
+        "last_ms": run.last_ms,
+        "last_error": run.last_error,
+        "last_result": run.last_result,
+    })
+}
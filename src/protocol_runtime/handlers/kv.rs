@@ -0,0 +1,107 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::MAX_KV_KEYS;
+use crate::{code, SwarmDb};
+use serde_json::{json, Value};
+
+const KV_FIX: &str = "swarm kv set|get|delete --agent-id <id> --bead-id <bead> --key <key>";
+
+/// Scratch state an agent keeps for one bead between stages.
+pub(in crate::protocol_runtime) async fn handle_kv(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::KvInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(KV_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let agent_id = input.agent_id;
+    let bead_id = input.bead_id.as_str();
+
+    if dry_flag(request) {
+        let target = format!("agent:{agent_id}:bead:{bead_id}");
+        let step = match (input.action.as_str(), input.key.is_some()) {
+            ("set", _) => "set_kv_entry",
+            ("delete", _) => "delete_kv_entry",
+            (_, true) => "get_kv_entry",
+            (_, false) => "list_kv_entries",
+        };
+        return Ok(dry_run_success(
+            request,
+            vec![json!({"step": 1, "action": step, "target": target})],
+            &format!(
+                "swarm kv {} --agent-id {agent_id} --bead-id {bead_id}",
+                input.action
+            ),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let key = input.key.as_deref().unwrap_or_default();
+    let data = match input.action.as_str() {
+        "set" => {
+            let value = input.value.clone().unwrap_or(Value::Null);
+            let entry = db
+                .set_kv_entry(&repo_id, agent_id, bead_id, key, &value)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+                .ok_or_else(|| {
+                    Box::new(
+                        ProtocolEnvelope::error(
+                            request.rid.clone(),
+                            code::CONFLICT.to_string(),
+                            format!(
+                                "Agent {agent_id} already holds {MAX_KV_KEYS} keys for {bead_id}"
+                            ),
+                        )
+                        .with_fix(format!(
+                            "swarm kv delete --agent-id {agent_id} --bead-id {bead_id} --key <key>"
+                        ))
+                        .with_ctx(json!({
+                            "agent_id": agent_id,
+                            "bead_id": bead_id,
+                            "max_keys": MAX_KV_KEYS,
+                        })),
+                    )
+                })?;
+            json!({"agent_id": agent_id, "bead_id": bead_id, "entry": entry})
+        }
+        "delete" => {
+            let deleted = db
+                .delete_kv_entry(&repo_id, agent_id, bead_id, key)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            json!({"agent_id": agent_id, "bead_id": bead_id, "key": key, "deleted": deleted})
+        }
+        _ if input.key.is_some() => {
+            let entry = db
+                .get_kv_entry(&repo_id, agent_id, bead_id, key)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            json!({"agent_id": agent_id, "bead_id": bead_id, "entry": entry})
+        }
+        _ => {
+            let entries = db
+                .list_kv_entries(&repo_id, agent_id, bead_id)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            json!({"agent_id": agent_id, "bead_id": bead_id, "entries": entries})
+        }
+    };
+
+    Ok(CommandSuccess {
+        data,
+        next: format!("swarm kv get --agent-id {agent_id} --bead-id {bead_id}"),
+        state: minimal_state_for_request(request).await,
+    })
+}
@@ -0,0 +1,108 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, read_db_from_request,
+    repo_id_from_request, to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::AgentId;
+use crate::{code, AgentLabelsInput, SwarmDb};
+use serde_json::json;
+
+const LABELS_FIX: &str = "swarm labels get | swarm labels set --agent-id 3 --labels docs,infra";
+
+/// `labels get` lists agents' default claim labels; `labels set` replaces
+/// one agent's. An agent with labels only claims backlog beads carrying one
+/// of them.
+pub(in crate::protocol_runtime) async fn handle_labels(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = AgentLabelsInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(LABELS_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    match (input.action.as_str(), input.agent_id) {
+        ("set", Some(agent_id)) => set_labels(request, agent_id, input.labels).await,
+        _ => get_labels(request, input.agent_id).await,
+    }
+}
+
+async fn get_labels(
+    request: &ProtocolRequest,
+    agent_id: Option<u32>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let agents = db
+        .get_agent_labels(&repo_id_from_request(request), agent_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    if let (Some(agent_id), true) = (agent_id, agents.is_empty()) {
+        return Err(not_registered(request, agent_id));
+    }
+
+    Ok(CommandSuccess {
+        data: json!({
+            "agents": agents
+                .into_iter()
+                .map(|(agent_id, labels)| json!({"agent_id": agent_id, "labels": labels}))
+                .collect::<Vec<_>>(),
+        }),
+        next: "swarm labels set --agent-id <id> --labels <label,...>".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn set_labels(
+    request: &ProtocolRequest,
+    agent_id: u32,
+    labels: Vec<String>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "update_agent_labels", "target": format!("agent:{agent_id}"), "labels": labels}),
+            ],
+            &format!("swarm labels get --agent-id {agent_id}"),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let updated = db
+        .set_agent_labels(
+            &AgentId::new(repo_id_from_request(request), agent_id),
+            &labels,
+        )
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    if !updated {
+        return Err(not_registered(request, agent_id));
+    }
+
+    Ok(CommandSuccess {
+        data: json!({
+            "agent_id": agent_id,
+            "labels": labels,
+        }),
+        next: format!("swarm claim-next --reserve --agent-id {agent_id}"),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+fn not_registered(request: &ProtocolRequest, agent_id: u32) -> Box<ProtocolEnvelope> {
+    Box::new(
+        ProtocolEnvelope::error(
+            request.rid.clone(),
+            code::NOTFOUND.to_string(),
+            format!("Agent {agent_id} is not registered"),
+        )
+        .with_fix("swarm register --count <n>".to_string())
+        .with_ctx(json!({"agent_id": agent_id})),
+    )
+}
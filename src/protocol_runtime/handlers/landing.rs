@@ -0,0 +1,492 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::landing::{
+    measure_change_diff, verify_remote_push, PushVerification, DEFAULT_LANDING_REMOTE,
+};
+use crate::orchestrator_service::{
+    landing_lock_resource, LandingGateway, LandingOutcome, LandingQueue, LandingQueuePorts,
+    PortFuture, PreLandOutcome,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{
+    ApprovalGate, GateEvidence, GatePolicy, GateViolation, LockMetadata, ReviewTally,
+};
+use crate::{code, AgentId, BeadId, RuntimeBeadId, RuntimeRepoId, SwarmDb, SwarmError};
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Instant;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+const PRE_LAND_OUTPUT_LIMIT: usize = 4_096;
+const LAND_FIX: &str = "swarm land --bead-id <bead-id> --agent-id <id> --change-id <change-id>";
+
+/// Confirms the bead's change is on the remote, records the evidence as a
+/// `push_verification` artifact, and only then finalizes the bead. The push
+/// check and finalize run through the repo's landing queue, so landings in
+/// one repo happen one at a time after the pre-land command passes. With a
+/// gate policy configured, finalize also waits on every policy rule passing,
+/// and with `land` in `SWARM_APPROVAL_GATES` nothing runs until an operator
+/// approves the bead.
+pub(in crate::protocol_runtime) async fn handle_land(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::LandInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(LAND_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let remote = input
+        .remote
+        .clone()
+        .unwrap_or_else(|| DEFAULT_LANDING_REMOTE.to_string());
+    let config = crate::config::landing_queue_config_from_env();
+    let repo_id = repo_id_from_request(request);
+
+    let min_coverage = crate::config::min_coverage_from_env()
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let gate_policy = crate::config::gate_policy_from_env()
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let approval_required = crate::config::approval_gates_from_env()
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+        .contains(&ApprovalGate::Land);
+
+    if dry_flag(request) {
+        let check = if input.override_push_check {
+            json!({"step": 5, "action": "override_push_check", "target": input.change_id, "reason": input.reason})
+        } else {
+            json!({"step": 5, "action": "verify_remote_push", "target": format!("{remote}:{}", input.change_id)})
+        };
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "check_approval", "target": input.bead_id, "required": approval_required}),
+                json!({"step": 2, "action": "check_min_coverage", "target": input.bead_id, "min_coverage": min_coverage}),
+                json!({"step": 3, "action": "acquire_landing_slot", "target": landing_lock_resource(&RuntimeRepoId::new(repo_id.value()))}),
+                json!({"step": 4, "action": "run_pre_land", "target": config.pre_land_command}),
+                check,
+                json!({"step": 6, "action": "store_push_verification", "target": input.bead_id}),
+                json!({"step": 7, "action": "evaluate_gate_policy", "target": input.bead_id, "policy": gate_policy}),
+                json!({"step": 8, "action": "finalize_after_push_confirmation", "target": format!("agent:{}, bead:{}", input.agent_id, input.bead_id)}),
+                json!({"step": 9, "action": "release_landing_slot", "target": input.bead_id}),
+            ],
+            "swarm monitor --view progress",
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    if approval_required {
+        check_land_approval(request, &db, &input.bead_id, input.agent_id).await?;
+    }
+    if let Some(min_coverage) = min_coverage {
+        check_min_coverage(request, &db, &input.bead_id, min_coverage).await?;
+    }
+    let ports = LandPorts {
+        db,
+        repo_root: super::prompts::repo_root_or_cwd(request).await?,
+        agent_id: AgentId::new(repo_id.clone(), input.agent_id),
+        bead_id: BeadId::new(input.bead_id.as_str()),
+        change_id: input.change_id.clone(),
+        remote: remote.clone(),
+        override_reason: input.reason.clone().filter(|_| input.override_push_check),
+        gate_policy: gate_policy.clone(),
+        verification: Mutex::new(None),
+        artifact_id: Mutex::new(None),
+        gate_failure: Mutex::new(None),
+    };
+    let holder = format!("agent-{}:{}", input.agent_id, input.bead_id);
+    let queue = LandingQueue::new(ports, config);
+    let queued = queue
+        .land(
+            &RuntimeRepoId::new(repo_id.value()),
+            &holder,
+            &RuntimeBeadId::new(input.bead_id.as_str()),
+        )
+        .await;
+    let ports = queue.ports();
+    let verification = ports.verification.lock().await.clone();
+    let artifact_id = *ports.artifact_id.lock().await;
+
+    let result = queued.map_err(|error| match (error, verification.as_ref()) {
+        (SwarmError::AgentError(message), Some(verification)) if !verification.confirmed => {
+            Box::new(
+                ProtocolEnvelope::error(request.rid.clone(), code::CONFLICT.to_string(), message)
+                    .with_fix(format!(
+                        "Push {} to {remote} (`jj git push` or `git push`) and re-run land, or pass --override-push-check with --reason",
+                        input.change_id
+                    ))
+                    .with_ctx(json!({"bead_id": input.bead_id, "verification": verification})),
+            )
+        }
+        (other, _) => to_protocol_failure(other, request.rid.clone()),
+    })?;
+
+    let queue_summary = json!({
+        "wait_ms": result.queue_wait_ms,
+        "pre_land": result.pre_land,
+    });
+    ports
+        .db
+        .record_landing_queued(
+            &ports.agent_id,
+            &ports.bead_id,
+            result.queue_wait_ms,
+            json!(result.pre_land),
+        )
+        .await
+        .map_err(|error| to_protocol_failure(error, request.rid.clone()))?;
+
+    let gate_failure = ports.gate_failure.lock().await.take();
+    if let Some((evidence, violations)) = gate_failure {
+        let rules = violations
+            .iter()
+            .map(|violation| violation.rule.as_str())
+            .collect::<Vec<_>>();
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::GATE_FAILED.to_string(),
+                format!(
+                    "Landing gate policy failed {} rule(s): {}",
+                    violations.len(),
+                    rules.join(", ")
+                ),
+            )
+            .with_fix("Fix each rule in ctx.violations and re-run land".to_string())
+            .with_ctx(json!({
+                "bead_id": input.bead_id,
+                "violations": violations,
+                "evidence": evidence,
+                "policy": gate_policy,
+                "artifact_id": artifact_id,
+                "queue": queue_summary,
+            })),
+        ));
+    }
+
+    let (Some(verification), Some(artifact_id)) = (verification, artifact_id) else {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::CONFLICT.to_string(),
+                result.outcome.detail().to_string(),
+            )
+            .with_fix(
+                "Fix the pre-land failure (rebase conflicts or the quick gate) and re-run land"
+                    .to_string(),
+            )
+            .with_ctx(json!({"bead_id": input.bead_id, "queue": queue_summary})),
+        ));
+    };
+
+    Ok(CommandSuccess {
+        data: json!({
+            "bead_id": input.bead_id,
+            "agent_id": input.agent_id,
+            "landed": true,
+            "verification": verification,
+            "artifact_id": artifact_id,
+            "queue": queue_summary,
+        }),
+        next: format!(
+            "swarm artifacts --bead-id {} --artifact-type push_verification",
+            input.bead_id
+        ),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Refuses to land a bead an operator has not approved for landing, asking
+/// for approval the first time.
+async fn check_land_approval(
+    request: &ProtocolRequest,
+    db: &SwarmDb,
+    bead_id: &str,
+    agent_id: u32,
+) -> std::result::Result<(), Box<ProtocolEnvelope>> {
+    let repo_id = repo_id_from_request(request);
+    let requested = db
+        .request_approval(
+            &repo_id,
+            &BeadId::new(bead_id),
+            ApprovalGate::Land,
+            Some(agent_id),
+        )
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    if requested.approval.is_approved() {
+        return Ok(());
+    }
+    let notified = crate::notifications::deliver_approval_token(&repo_id, &requested)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+        .map(|delivery| delivery.sent);
+    let approval = requested.approval;
+    Err(Box::new(
+        ProtocolEnvelope::error(
+            request.rid.clone(),
+            code::GATE_FAILED.to_string(),
+            format!("Bead {bead_id} is awaiting approval to land"),
+        )
+        .with_fix(format!(
+            "An operator runs `swarm approve --bead-id {bead_id} --token <token>` with the token from the approval_requested notification, then re-run land; if that notification never arrived, `swarm reissue-approval --bead-id {bead_id} --gate land` sends a new token"
+        ))
+        .with_ctx(json!({
+            "bead_id": bead_id,
+            "approval_id": approval.id,
+            "gate": approval.gate,
+            "requested_at": approval.requested_at,
+            "notified": notified,
+        })),
+    ))
+}
+
+/// Refuses to land a bead whose latest coverage summary is missing or under
+/// `SWARM_MIN_COVERAGE`, before it takes the landing slot.
+async fn check_min_coverage(
+    request: &ProtocolRequest,
+    db: &SwarmDb,
+    bead_id: &str,
+    min_coverage: f64,
+) -> std::result::Result<(), Box<ProtocolEnvelope>> {
+    let latest = db
+        .get_latest_coverage(&repo_id_from_request(request), &BeadId::new(bead_id))
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let message = match &latest {
+        Some(summary) if summary.meets(min_coverage) => return Ok(()),
+        Some(summary) => format!(
+            "Bead {bead_id} line coverage {:.2}% is below the {min_coverage:.2}% minimum",
+            summary.line_percent
+        ),
+        None => format!(
+            "Bead {bead_id} has no coverage report; landing requires {min_coverage:.2}% line coverage"
+        ),
+    };
+    Err(Box::new(
+        ProtocolEnvelope::error(request.rid.clone(), code::CONFLICT.to_string(), message)
+            .with_fix(format!(
+                "swarm report-coverage --agent-id <id> --bead-id {bead_id} --path <lcov-or-cobertura>"
+            ))
+            .with_ctx(json!({
+                "bead_id": bead_id,
+                "min_coverage": min_coverage,
+                "coverage": latest,
+            })),
+    ))
+}
+
+struct LandPorts {
+    db: SwarmDb,
+    repo_root: PathBuf,
+    agent_id: AgentId,
+    bead_id: BeadId,
+    change_id: String,
+    remote: String,
+    override_reason: Option<String>,
+    gate_policy: Option<GatePolicy>,
+    verification: Mutex<Option<PushVerification>>,
+    artifact_id: Mutex<Option<i64>>,
+    gate_failure: Mutex<Option<(GateEvidence, Vec<GateViolation>)>>,
+}
+
+impl LandPorts {
+    /// Collects what `policy` checks. Diff size, coverage, scope violations
+    /// and reviews are only looked up when the policy has a rule for them.
+    async fn gate_evidence(
+        &self,
+        policy: &GatePolicy,
+        push_confirmed: bool,
+    ) -> crate::Result<GateEvidence> {
+        let repo_id = self.agent_id.repo_id();
+        let diff_lines = if policy.max_diff_lines.is_some() {
+            measure_change_diff(&self.repo_root, &self.change_id).await
+        } else {
+            None
+        };
+        let coverage_percent = if policy.min_coverage.is_some() {
+            self.db
+                .get_latest_coverage(repo_id, &self.bead_id)
+                .await?
+                .map(|summary| summary.line_percent)
+        } else {
+            None
+        };
+        let reviews = if policy.min_approvals.is_some() {
+            self.db
+                .get_review_tally(repo_id, self.bead_id.value())
+                .await?
+        } else {
+            ReviewTally::default()
+        };
+        let scope_violations = if policy.forbid_scope_violations {
+            self.db
+                .count_bead_scope_violations(repo_id, &self.bead_id)
+                .await?
+        } else {
+            0
+        };
+        Ok(GateEvidence {
+            artifact_types: self
+                .db
+                .get_bead_artifact_types(repo_id, &self.bead_id)
+                .await?,
+            diff_lines,
+            coverage_percent,
+            scope_violations,
+            push_confirmed,
+            approvals: reviews.approvals,
+            changes_requested: reviews.changes_requested,
+        })
+    }
+}
+
+impl LandingGateway for LandPorts {
+    fn execute_landing<'a>(
+        &'a self,
+        _bead_id: &'a RuntimeBeadId,
+    ) -> PortFuture<'a, LandingOutcome> {
+        Box::pin(async move {
+            let verification = match self.override_reason.as_deref() {
+                Some(reason) => PushVerification::overridden(&self.change_id, &self.remote, reason),
+                None => verify_remote_push(&self.repo_root, &self.change_id, &self.remote).await,
+            };
+            *self.verification.lock().await = Some(verification.clone());
+            let Some(policy) = self.gate_policy.as_ref() else {
+                let artifact_id = self
+                    .db
+                    .finalize_after_verified_push(&self.agent_id, &self.bead_id, &verification)
+                    .await?;
+                *self.artifact_id.lock().await = Some(artifact_id);
+                return Ok(verification.landing_outcome());
+            };
+
+            let artifact_id = self
+                .db
+                .record_push_verification(&self.agent_id, &self.bead_id, &verification)
+                .await?;
+            *self.artifact_id.lock().await = Some(artifact_id);
+            let evidence = self.gate_evidence(policy, verification.confirmed).await?;
+            let violations = policy.evaluate(&evidence);
+            if !violations.is_empty() {
+                let detail = format!("gate policy failed {} rule(s)", violations.len());
+                *self.gate_failure.lock().await = Some((evidence, violations));
+                return Ok(LandingOutcome::new(false, detail));
+            }
+            self.db
+                .finalize_after_push_confirmation(
+                    &self.agent_id,
+                    &self.bead_id,
+                    verification.confirmed,
+                )
+                .await?;
+            Ok(verification.landing_outcome())
+        })
+    }
+}
+
+impl LandingQueuePorts for LandPorts {
+    fn try_acquire_landing_slot<'a>(
+        &'a self,
+        repo_id: &'a RuntimeRepoId,
+        holder: &'a str,
+        ttl_ms: u64,
+    ) -> PortFuture<'a, bool> {
+        Box::pin(async move {
+            self.db
+                .acquire_resource_lock(
+                    &landing_lock_resource(repo_id),
+                    holder,
+                    i64::try_from(ttl_ms).unwrap_or(i64::MAX),
+                    &LockMetadata {
+                        purpose: Some("landing".to_string()),
+                        bead_id: Some(self.bead_id.value().to_string()),
+                    },
+                )
+                .await
+                .map(|until| until.is_some())
+        })
+    }
+
+    fn renew_landing_slot<'a>(
+        &'a self,
+        repo_id: &'a RuntimeRepoId,
+        holder: &'a str,
+        ttl_ms: u64,
+    ) -> PortFuture<'a, bool> {
+        Box::pin(async move {
+            self.db
+                .renew_resource_lock(
+                    &landing_lock_resource(repo_id),
+                    holder,
+                    i64::try_from(ttl_ms).unwrap_or(i64::MAX),
+                )
+                .await
+                .map(|until| until.is_some())
+        })
+    }
+
+    fn release_landing_slot<'a>(
+        &'a self,
+        repo_id: &'a RuntimeRepoId,
+        holder: &'a str,
+    ) -> PortFuture<'a, ()> {
+        Box::pin(async move {
+            self.db
+                .unlock_resource(&landing_lock_resource(repo_id), holder)
+                .await
+                .map(|_released| ())
+        })
+    }
+
+    fn run_pre_land<'a>(&'a self, command: &'a str) -> PortFuture<'a, PreLandOutcome> {
+        Box::pin(async move {
+            let start = Instant::now();
+            let (success, exit_code, output) = match Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .current_dir(&self.repo_root)
+                .output()
+                .await
+            {
+                Ok(output) => {
+                    let combined = format!(
+                        "{}{}",
+                        String::from_utf8_lossy(&output.stdout),
+                        String::from_utf8_lossy(&output.stderr)
+                    );
+                    (
+                        output.status.success(),
+                        output.status.code(),
+                        output_tail(combined.trim_end()),
+                    )
+                }
+                Err(err) => (false, None, format!("failed to run: {err}")),
+            };
+            Ok(PreLandOutcome {
+                command: command.to_string(),
+                success,
+                exit_code,
+                output,
+                duration_ms: u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+            })
+        })
+    }
+}
+
+/// Keeps the last `PRE_LAND_OUTPUT_LIMIT` bytes, where failures usually are.
+fn output_tail(output: &str) -> String {
+    let cut = output.len().saturating_sub(PRE_LAND_OUTPUT_LIMIT);
+    let start = (cut..=output.len())
+        .find(|index| output.is_char_boundary(*index))
+        .unwrap_or(output.len());
+    output[start..].to_string()
+}
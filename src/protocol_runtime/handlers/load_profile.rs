@@ -1,37 +1,48 @@
 use super::super::{
     db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
-    to_protocol_failure, CommandSuccess, ProtocolRequest,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest, MAX_LOAD_PROFILE_CONCURRENCY,
 };
+use crate::db::swarm_db::latency_percentile;
 use crate::protocol_envelope::ProtocolEnvelope;
-use crate::{AgentId, RepoId, SwarmDb};
+use crate::{code, AgentId, LoadProfileInput, RepoId, SwarmDb};
 use serde_json::{json, Value};
-use std::future::Future;
-use std::pin::Pin;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio::task::{JoinHandle, JoinSet};
+
+const LOCK_WAIT_SAMPLE_INTERVAL: Duration = Duration::from_millis(25);
 
 pub(in crate::protocol_runtime) async fn handle_load_profile(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
-    let agents = request
-        .args
-        .get("agents")
-        .and_then(Value::as_u64)
-        .map_or(90, |v| v) as u32;
-    let rounds = request
-        .args
-        .get("rounds")
-        .and_then(Value::as_u64)
-        .map_or(5, |v| v) as u32;
-    let timeout_ms = request
-        .args
-        .get("timeout_ms")
-        .and_then(Value::as_u64)
-        .map_or(1500, |v| v);
+    let input = LoadProfileInput::parse_input(request).map_err(|error| {
+        let error_message = error.to_string();
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error_message.clone(),
+            )
+            .with_fix(
+                "echo '{\"cmd\":\"load-profile\",\"agents\":10,\"concurrency\":4}' | swarm"
+                    .to_string(),
+            )
+            .with_ctx(json!({"error": error_message})),
+        )
+    })?;
+    let agents = input.agents.unwrap_or(90);
+    let rounds = input.rounds.unwrap_or(5);
+    let timeout_ms = input.timeout_ms.unwrap_or(1500);
+    let concurrency = input
+        .concurrency
+        .unwrap_or(1)
+        .clamp(1, MAX_LOAD_PROFILE_CONCURRENCY);
 
     if dry_flag(request) {
         return Ok(dry_run_success(
             request,
             vec![
-                json!({"step": 1, "action": "load_profile", "target": format!("{}x{}", agents, rounds)}),
+                json!({"step": 1, "action": "load_profile", "target": format!("{}x{}", agents, rounds), "concurrency": concurrency}),
             ],
             "swarm status",
         ));
@@ -46,120 +57,247 @@ pub(in crate::protocol_runtime) async fn handle_load_profile(
         .await
         .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
 
-    let stats = load_profile_recursive(
-        &db,
-        &repo_id,
-        0,
-        rounds,
-        agents,
-        timeout_ms,
-        LoadStats::default(),
-    )
-    .await?;
+    let total_start = Instant::now();
+    let (stop_sampler, sampler) = spawn_lock_wait_sampler(db.clone());
+    let profile = run_load_profile(&db, &repo_id, rounds, agents, timeout_ms, concurrency).await;
+    let _ = stop_sampler.send(());
+    let lock_wait = sampler.await.unwrap_or_default();
+
+    let mut latencies = profile.stats.latencies_ms.clone();
+    latencies.sort_unstable();
 
     Ok(CommandSuccess {
         data: json!({
             "agents": agents,
             "rounds": rounds,
-            "timeouts": stats.timeout,
-            "errors": stats.error,
-            "successful_claims": stats.success,
-            "empty_claims": stats.empty,
+            "concurrency": concurrency,
+            "timeouts": profile.stats.timeout,
+            "errors": profile.stats.error,
+            "successful_claims": profile.stats.success,
+            "empty_claims": profile.stats.empty,
+            "latency_ms": {
+                "p50": latency_percentile(&latencies, 50),
+                "p95": latency_percentile(&latencies, 95),
+                "p99": latency_percentile(&latencies, 99),
+                "max": latencies.last().copied().unwrap_or(0),
+            },
+            "lock_wait": {
+                "samples": lock_wait.samples,
+                "max_waiting_sessions": lock_wait.max_waiting,
+                "max_wait_ms": lock_wait.max_wait_ms,
+            },
+            "throughput": profile.throughput,
+            "total_ms": elapsed_ms(total_start),
         }),
         next: "swarm status".to_string(),
         state: minimal_state_for_request(request).await,
     })
 }
 
-fn load_profile_recursive<'a>(
-    db: &'a SwarmDb,
-    repo_id: &'a RepoId,
-    current_round: u32,
+async fn run_load_profile(
+    db: &SwarmDb,
+    repo_id: &RepoId,
     total_rounds: u32,
     agents_per_round: u32,
     timeout_ms: u64,
-    stats: LoadStats,
-) -> Pin<Box<dyn Future<Output = std::result::Result<LoadStats, Box<ProtocolEnvelope>>> + Send + 'a>>
-{
-    Box::pin(async move {
-        if current_round >= total_rounds {
-            Ok(stats)
+    concurrency: u32,
+) -> LoadProfile {
+    let mut profile = LoadProfile::default();
+    for round in 1..=total_rounds {
+        let round_start = Instant::now();
+        let round_stats = if concurrency > 1 {
+            load_profile_round_concurrent(db, repo_id, agents_per_round, timeout_ms, concurrency)
+                .await
         } else {
-            let round_stats = load_profile_round_recursive(
-                db,
-                repo_id,
-                1,
-                agents_per_round,
-                timeout_ms,
-                LoadStats::default(),
-            )
-            .await?;
+            load_profile_round_sequential(db, repo_id, agents_per_round, timeout_ms).await
+        };
+        let round_ms = elapsed_ms(round_start);
+
+        profile.throughput.push(json!({
+            "round": round,
+            "claims": round_stats.success,
+            "elapsed_ms": round_ms,
+            "claims_per_sec": round_stats.success.saturating_mul(1_000) / round_ms.max(1),
+        }));
+        profile.stats = profile.stats.merge(round_stats);
+    }
+    profile
+}
+
+async fn load_profile_round_sequential(
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    total_agents: u32,
+    timeout_ms: u64,
+) -> LoadStats {
+    let mut stats = LoadStats::default();
+    for agent_num in 1..=total_agents {
+        let (outcome, latency_ms) = claim_once(db, repo_id, agent_num, timeout_ms).await;
+        stats.record(outcome, latency_ms);
+    }
+    stats
+}
 
-            let next_stats = LoadStats {
-                success: stats.success.saturating_add(round_stats.success),
-                empty: stats.empty.saturating_add(round_stats.empty),
-                timeout: stats.timeout.saturating_add(round_stats.timeout),
-                error: stats.error.saturating_add(round_stats.error),
+async fn load_profile_round_concurrent(
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    total_agents: u32,
+    timeout_ms: u64,
+    concurrency: u32,
+) -> LoadStats {
+    let limit = usize::try_from(concurrency).unwrap_or(1);
+    let mut pending = 1..=total_agents;
+    let mut tasks = JoinSet::new();
+    let mut stats = LoadStats::default();
+
+    loop {
+        while tasks.len() < limit {
+            let Some(agent_num) = pending.next() else {
+                break;
             };
+            let db = db.clone();
+            let repo_id = repo_id.clone();
+            tasks.spawn(async move { claim_once(&db, &repo_id, agent_num, timeout_ms).await });
+        }
 
-            load_profile_recursive(
-                db,
-                repo_id,
-                current_round.saturating_add(1),
-                total_rounds,
-                agents_per_round,
-                timeout_ms,
-                next_stats,
-            )
-            .await
+        match tasks.join_next().await {
+            Some(Ok((outcome, latency_ms))) => stats.record(outcome, latency_ms),
+            Some(Err(_)) => stats.error = stats.error.saturating_add(1),
+            None => break stats,
         }
-    })
+    }
 }
 
-fn load_profile_round_recursive<'a>(
-    db: &'a SwarmDb,
-    repo_id: &'a RepoId,
+async fn claim_once(
+    db: &SwarmDb,
+    repo_id: &RepoId,
     agent_num: u32,
-    total_agents: u32,
     timeout_ms: u64,
-    mut stats: LoadStats,
-) -> Pin<Box<dyn Future<Output = std::result::Result<LoadStats, Box<ProtocolEnvelope>>> + Send + 'a>>
-{
-    Box::pin(async move {
-        if agent_num > total_agents {
-            Ok(stats)
-        } else {
-            let timeout_dur = tokio::time::Duration::from_millis(timeout_ms);
-            let claim = tokio::time::timeout(
-                timeout_dur,
-                db.claim_next_bead(&AgentId::new(repo_id.clone(), agent_num)),
-            )
-            .await;
+) -> (ClaimOutcome, u64) {
+    let started = Instant::now();
+    let claim = tokio::time::timeout(
+        Duration::from_millis(timeout_ms),
+        db.claim_next_bead(&AgentId::new(repo_id.clone(), agent_num)),
+    )
+    .await;
 
-            match claim {
-                Ok(Ok(Some(_))) => stats.success = stats.success.saturating_add(1),
-                Ok(Ok(None)) => stats.empty = stats.empty.saturating_add(1),
-                Ok(Err(_)) => stats.error = stats.error.saturating_add(1),
-                Err(_) => stats.timeout = stats.timeout.saturating_add(1),
-            }
+    let outcome = match claim {
+        Ok(Ok(Some(_))) => ClaimOutcome::Claimed,
+        Ok(Ok(None)) => ClaimOutcome::Empty,
+        Ok(Err(_)) => ClaimOutcome::Error,
+        Err(_) => ClaimOutcome::Timeout,
+    };
+    (outcome, elapsed_ms(started))
+}
 
-            load_profile_round_recursive(
-                db,
-                repo_id,
-                agent_num.saturating_add(1),
-                total_agents,
-                timeout_ms,
-                stats,
-            )
-            .await
+fn spawn_lock_wait_sampler(db: SwarmDb) -> (oneshot::Sender<()>, JoinHandle<LockWaitStats>) {
+    let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+    let handle = tokio::spawn(async move {
+        let mut stats = LockWaitStats::default();
+        loop {
+            if let Ok((waiting, wait_ms)) = db.lock_wait_snapshot().await {
+                stats.observe(waiting, wait_ms);
+            }
+            tokio::select! {
+                _ = &mut stop_rx => break stats,
+                () = tokio::time::sleep(LOCK_WAIT_SAMPLE_INTERVAL) => {}
+            }
         }
-    })
+    });
+    (stop_tx, handle)
 }
 
-#[derive(Default, Clone, Copy)]
+fn elapsed_ms(start: Instant) -> u64 {
+    u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+#[derive(Clone, Copy)]
+enum ClaimOutcome {
+    Claimed,
+    Empty,
+    Error,
+    Timeout,
+}
+
+#[derive(Default, Clone)]
 struct LoadStats {
     success: u64,
     empty: u64,
     timeout: u64,
     error: u64,
+    latencies_ms: Vec<u64>,
+}
+
+impl LoadStats {
+    fn record(&mut self, outcome: ClaimOutcome, latency_ms: u64) {
+        match outcome {
+            ClaimOutcome::Claimed => self.success = self.success.saturating_add(1),
+            ClaimOutcome::Empty => self.empty = self.empty.saturating_add(1),
+            ClaimOutcome::Error => self.error = self.error.saturating_add(1),
+            ClaimOutcome::Timeout => self.timeout = self.timeout.saturating_add(1),
+        }
+        self.latencies_ms.push(latency_ms);
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.success = self.success.saturating_add(other.success);
+        self.empty = self.empty.saturating_add(other.empty);
+        self.timeout = self.timeout.saturating_add(other.timeout);
+        self.error = self.error.saturating_add(other.error);
+        self.latencies_ms.extend(other.latencies_ms);
+        self
+    }
+}
+
+#[derive(Default)]
+struct LoadProfile {
+    stats: LoadStats,
+    throughput: Vec<Value>,
+}
+
+#[derive(Default, Clone, Copy)]
+struct LockWaitStats {
+    samples: u64,
+    max_waiting: i64,
+    max_wait_ms: i64,
+}
+
+impl LockWaitStats {
+    fn observe(&mut self, waiting: i64, wait_ms: i64) {
+        self.samples = self.samples.saturating_add(1);
+        self.max_waiting = self.max_waiting.max(waiting);
+        self.max_wait_ms = self.max_wait_ms.max(wait_ms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClaimOutcome, LoadStats, LockWaitStats};
+
+    #[test]
+    fn given_two_rounds_when_merging_stats_then_counts_and_latencies_accumulate() {
+        let mut first = LoadStats::default();
+        first.record(ClaimOutcome::Claimed, 4);
+        first.record(ClaimOutcome::Timeout, 1500);
+        let mut second = LoadStats::default();
+        second.record(ClaimOutcome::Empty, 2);
+
+        let merged = first.merge(second);
+
+        assert_eq!(merged.success, 1);
+        assert_eq!(merged.timeout, 1);
+        assert_eq!(merged.empty, 1);
+        assert_eq!(merged.latencies_ms, vec![4, 1500, 2]);
+    }
+
+    #[test]
+    fn given_lock_wait_samples_when_observing_then_maxima_are_kept() {
+        let mut stats = LockWaitStats::default();
+        stats.observe(3, 40);
+        stats.observe(1, 120);
+
+        assert_eq!(stats.samples, 2);
+        assert_eq!(stats.max_waiting, 3);
+        assert_eq!(stats.max_wait_ms, 120);
+    }
 }
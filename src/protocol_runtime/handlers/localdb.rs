@@ -0,0 +1,573 @@
+use super::super::{
+    dry_flag, dry_run_success, mask_database_url, minimal_state_for_request, CommandSuccess,
+    ParseInput, ProtocolRequest, DEFAULT_LOCAL_DB_CONTAINER, DEFAULT_LOCAL_DB_LOG_TAIL,
+    DEFAULT_LOCAL_DB_PORT, DEFAULT_LOCAL_DB_USER,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::{code, SwarmDb};
+use serde_json::{json, Value};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+const LOCALDB_FIX: &str = "swarm localdb status|stop|destroy|logs|upgrade";
+/// Where the postgres image keeps its data directory.
+const POSTGRES_DATA_DIR: &str = "/var/lib/postgresql/data";
+const READY_ATTEMPTS: u32 = 30;
+const READY_PROBE_TIMEOUT_MS: u64 = 1_000;
+
+/// A local Postgres container: how `init-local-db` starts it and how
+/// `localdb` finds it again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct LocalDb {
+    pub(super) container: String,
+    pub(super) port: u16,
+    pub(super) user: String,
+    pub(super) database: String,
+    pub(super) volume: Option<String>,
+    pub(super) major: u32,
+}
+
+impl LocalDb {
+    pub(super) fn url(&self) -> String {
+        format!(
+            "postgresql://{}@localhost:{}/{}",
+            self.user, self.port, self.database
+        )
+    }
+
+    fn image(&self) -> String {
+        format!("postgres:{}", self.major)
+    }
+
+    /// Reads the settings a container was started with back from
+    /// `docker inspect`, so `localdb` needs only the container name.
+    fn from_inspect(container: &str, inspect: &Value) -> Self {
+        let env = inspect
+            .pointer("/Config/Env")
+            .and_then(Value::as_array)
+            .map(|vars| vars.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let env_var = |name: &str| {
+            env.iter()
+                .find_map(|var| var.strip_prefix(name)?.strip_prefix('='))
+                .map(str::to_string)
+        };
+        let user = env_var("POSTGRES_USER").unwrap_or_else(|| DEFAULT_LOCAL_DB_USER.to_string());
+        Self {
+            container: container.to_string(),
+            port: inspect
+                .pointer("/HostConfig/PortBindings/5432~1tcp/0/HostPort")
+                .and_then(Value::as_str)
+                .and_then(|port| port.parse().ok())
+                .unwrap_or(DEFAULT_LOCAL_DB_PORT),
+            database: env_var("POSTGRES_DB").unwrap_or_else(|| user.clone()),
+            user,
+            volume: data_volume(inspect),
+            major: inspect
+                .pointer("/Config/Image")
+                .and_then(Value::as_str)
+                .and_then(postgres_major)
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// The major version in a `postgres:<major>[.<minor>][-variant]` image.
+fn postgres_major(image: &str) -> Option<u32> {
+    let tag = image.strip_prefix("postgres:")?;
+    tag.split(['.', '-']).next()?.parse().ok()
+}
+
+/// The named volume mounted on the data directory, if any.
+fn data_volume(inspect: &Value) -> Option<String> {
+    inspect
+        .get("Mounts")
+        .and_then(Value::as_array)?
+        .iter()
+        .find(|mount| {
+            mount.get("Type").and_then(Value::as_str) == Some("volume")
+                && mount.get("Destination").and_then(Value::as_str) == Some(POSTGRES_DATA_DIR)
+        })
+        .and_then(|mount| mount.get("Name").and_then(Value::as_str))
+        .map(str::to_string)
+}
+
+/// Runs `docker args`, returning stdout. A failure carries docker's own
+/// stderr and the exit code instead of being swallowed.
+pub(super) async fn docker(
+    rid: Option<String>,
+    args: &[&str],
+) -> std::result::Result<String, Box<ProtocolEnvelope>> {
+    docker_with_stdin(rid, args, None).await
+}
+
+async fn docker_with_stdin(
+    rid: Option<String>,
+    args: &[&str],
+    stdin: Option<String>,
+) -> std::result::Result<String, Box<ProtocolEnvelope>> {
+    let command_line = format!("docker {}", args.join(" "));
+    let spawn_failed = |error: std::io::Error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                rid.clone(),
+                code::INTERNAL.to_string(),
+                format!("Failed to run `{command_line}`: {error}"),
+            )
+            .with_fix("Install docker and make sure the daemon is running".to_string()),
+        )
+    };
+    let mut child = Command::new("docker")
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(spawn_failed)?;
+    // Fed from its own task so a chatty child cannot fill its stdout or
+    // stderr pipe while we are still writing.
+    let feeder = match (stdin, child.stdin.take()) {
+        (Some(input), Some(mut pipe)) => Some(tokio::spawn(async move {
+            pipe.write_all(input.as_bytes()).await
+        })),
+        _ => None,
+    };
+    let output = child.wait_with_output().await.map_err(spawn_failed)?;
+    if let Some(feeder) = feeder {
+        feeder
+            .await
+            .map_err(|error| spawn_failed(std::io::Error::other(error)))?
+            .map_err(spawn_failed)?;
+    }
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Err(Box::new(
+        ProtocolEnvelope::error(
+            rid.clone(),
+            code::INTERNAL.to_string(),
+            format!("`{command_line}` failed: {stderr}"),
+        )
+        .with_fix("Check `docker ps -a` and the daemon logs".to_string())
+        .with_ctx(json!({
+            "command": command_line,
+            "exit_code": output.status.code(),
+            "stderr": stderr,
+        })),
+    ))
+}
+
+/// The container's `docker inspect` record, or `None` when there is no such
+/// container.
+async fn inspect(
+    rid: Option<String>,
+    container: &str,
+) -> std::result::Result<Option<Value>, Box<ProtocolEnvelope>> {
+    match docker(rid.clone(), &["inspect", "--type", "container", container]).await {
+        Ok(stdout) => Ok(serde_json::from_str::<Value>(&stdout)
+            .ok()
+            .and_then(|records| records.get(0).cloned())),
+        Err(failure)
+            if failure
+                .err
+                .as_ref()
+                .is_some_and(|err| err.msg.contains("No such")) =>
+        {
+            Ok(None)
+        }
+        Err(failure) => Err(failure),
+    }
+}
+
+async fn require_container(
+    rid: Option<String>,
+    container: &str,
+) -> std::result::Result<Value, Box<ProtocolEnvelope>> {
+    inspect(rid.clone(), container).await?.ok_or_else(|| {
+        Box::new(
+            ProtocolEnvelope::error(
+                rid,
+                code::NOTFOUND.to_string(),
+                format!("No container named {container}"),
+            )
+            .with_fix("swarm init-local-db".to_string())
+            .with_ctx(json!({"container": container})),
+        )
+    })
+}
+
+/// Starts the container when it exists, else creates it.
+pub(super) async fn start_or_run(
+    rid: Option<String>,
+    local: &LocalDb,
+) -> std::result::Result<&'static str, Box<ProtocolEnvelope>> {
+    if inspect(rid.clone(), &local.container).await?.is_some() {
+        docker(rid, &["start", local.container.as_str()]).await?;
+        return Ok("started");
+    }
+    let port_mapping = format!("{}:5432", local.port);
+    let user_env = format!("POSTGRES_USER={}", local.user);
+    let db_env = format!("POSTGRES_DB={}", local.database);
+    let image = local.image();
+    let mount = local
+        .volume
+        .as_ref()
+        .map(|volume| format!("{volume}:{POSTGRES_DATA_DIR}"));
+    let mut args = vec![
+        "run",
+        "-d",
+        "--name",
+        local.container.as_str(),
+        "-p",
+        port_mapping.as_str(),
+        "-e",
+        user_env.as_str(),
+        "-e",
+        "POSTGRES_HOST_AUTH_METHOD=trust",
+        "-e",
+        db_env.as_str(),
+    ];
+    if let Some(mount) = mount.as_deref() {
+        args.extend(["-v", mount]);
+    }
+    args.push(image.as_str());
+    docker(rid, &args).await?;
+    Ok("created")
+}
+
+/// Waits until the database accepts a real connection. `pg_isready` alone
+/// reports ready while the image's init scripts still restart the server.
+pub(super) async fn wait_until_ready(
+    rid: Option<String>,
+    local: &LocalDb,
+) -> std::result::Result<u64, Box<ProtocolEnvelope>> {
+    let url = local.url();
+    let mut last_error = String::new();
+    for attempt in 1..=READY_ATTEMPTS {
+        let isready = docker(
+            rid.clone(),
+            &[
+                "exec",
+                local.container.as_str(),
+                "pg_isready",
+                "-U",
+                local.user.as_str(),
+            ],
+        )
+        .await;
+        match isready {
+            Ok(_) => match SwarmDb::new_with_timeout(&url, Some(READY_PROBE_TIMEOUT_MS)).await {
+                Ok(_) => return Ok(u64::from(attempt)),
+                Err(error) => last_error = error.to_string(),
+            },
+            Err(failure) => {
+                last_error = failure
+                    .err
+                    .as_ref()
+                    .map_or_else(String::new, |err| err.msg.clone());
+            }
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    Err(Box::new(
+        ProtocolEnvelope::error(
+            rid,
+            code::INTERNAL.to_string(),
+            format!(
+                "Database in {} not accepting connections after {READY_ATTEMPTS}s: {last_error}",
+                local.container
+            ),
+        )
+        .with_fix(format!(
+            "swarm localdb logs --container-name {}",
+            local.container
+        ))
+        .with_ctx(json!({"container": local.container, "database_url": mask_database_url(&url)})),
+    ))
+}
+
+/// Status, logs and lifecycle for the Docker Postgres `init-local-db` runs.
+pub(in crate::protocol_runtime) async fn handle_localdb(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::LocalDbInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(LOCALDB_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let rid = request.rid.clone();
+    let container = input
+        .container_name
+        .clone()
+        .unwrap_or_else(|| DEFAULT_LOCAL_DB_CONTAINER.to_string());
+
+    let (data, next) = match input.action.as_str() {
+        "status" => status(rid, &container).await?,
+        "logs" => {
+            let tail = input.tail.unwrap_or(DEFAULT_LOCAL_DB_LOG_TAIL).to_string();
+            require_container(rid.clone(), &container).await?;
+            let logs = docker(rid, &["logs", "--tail", tail.as_str(), container.as_str()]).await?;
+            (
+                json!({"container": container, "lines": logs.lines().collect::<Vec<_>>()}),
+                format!("swarm localdb status --container-name {container}"),
+            )
+        }
+        "stop" => {
+            if dry_flag(request) {
+                return Ok(dry_run_success(
+                    request,
+                    vec![json!({"step": 1, "action": "docker_stop", "target": container})],
+                    "swarm localdb status",
+                ));
+            }
+            require_container(rid.clone(), &container).await?;
+            docker(rid, &["stop", container.as_str()]).await?;
+            (
+                json!({"container": container, "stopped": true}),
+                "swarm init-local-db".to_string(),
+            )
+        }
+        "destroy" => {
+            let inspected = require_container(rid.clone(), &container).await?;
+            let volume = data_volume(&inspected).filter(|_| input.purge_volume.unwrap_or(false));
+            if dry_flag(request) {
+                let mut steps =
+                    vec![json!({"step": 1, "action": "docker_rm", "target": container})];
+                if let Some(volume) = &volume {
+                    steps.push(json!({"step": 2, "action": "docker_volume_rm", "target": volume}));
+                }
+                return Ok(dry_run_success(request, steps, "swarm init-local-db"));
+            }
+            docker(rid.clone(), &["rm", "-f", container.as_str()]).await?;
+            if let Some(volume) = &volume {
+                docker(rid, &["volume", "rm", volume.as_str()]).await?;
+            }
+            (
+                json!({
+                    "container": container,
+                    "destroyed": true,
+                    "volume_removed": volume,
+                    "volume_kept": data_volume(&inspected).filter(|_| volume.is_none()),
+                }),
+                "swarm init-local-db".to_string(),
+            )
+        }
+        _ => upgrade(request, &container, input.to.unwrap_or_default()).await?,
+    };
+
+    Ok(CommandSuccess {
+        data,
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn status(
+    rid: Option<String>,
+    container: &str,
+) -> std::result::Result<(Value, String), Box<ProtocolEnvelope>> {
+    let Some(inspected) = inspect(rid, container).await? else {
+        return Ok((
+            json!({"container": container, "exists": false}),
+            "swarm init-local-db".to_string(),
+        ));
+    };
+    let local = LocalDb::from_inspect(container, &inspected);
+    let running = inspected
+        .pointer("/State/Running")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let connection = if running {
+        SwarmDb::new_with_timeout(&local.url(), Some(READY_PROBE_TIMEOUT_MS))
+            .await
+            .map_or_else(|error| Some(error.to_string()), |_| None)
+    } else {
+        Some("container is not running".to_string())
+    };
+    let next = if running {
+        "swarm doctor".to_string()
+    } else {
+        "swarm init-local-db".to_string()
+    };
+    Ok((
+        json!({
+            "container": container,
+            "exists": true,
+            "state": inspected.pointer("/State/Status"),
+            "running": running,
+            "image": inspected.pointer("/Config/Image"),
+            "postgres_major": local.major,
+            "port": local.port,
+            "volume": local.volume,
+            "database_url": mask_database_url(&local.url()),
+            "accepting_connections": connection.is_none(),
+            "connection_error": connection,
+        }),
+        next,
+    ))
+}
+
+/// Moves the data to a newer Postgres major by dump and restore. The old
+/// container is stopped and kept as `<container>-pg<old>` so the upgrade can
+/// be undone by hand; when any step after it fails it is put back.
+async fn upgrade(
+    request: &ProtocolRequest,
+    container: &str,
+    to: u32,
+) -> std::result::Result<(Value, String), Box<ProtocolEnvelope>> {
+    let rid = request.rid.clone();
+    let inspected = require_container(rid.clone(), container).await?;
+    let current = LocalDb::from_inspect(container, &inspected);
+    if to <= current.major {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                rid,
+                code::INVALID.to_string(),
+                format!(
+                    "{container} already runs postgres {}; upgrade needs a newer major",
+                    current.major
+                ),
+            )
+            .with_fix(format!("swarm localdb upgrade --to {}", current.major + 1))
+            .with_ctx(json!({"from": current.major, "to": to})),
+        ));
+    }
+    let retired = format!("{container}-pg{}", current.major);
+    let target = LocalDb {
+        volume: current
+            .volume
+            .as_ref()
+            .map(|volume| format!("{volume}-pg{to}")),
+        major: to,
+        ..current.clone()
+    };
+
+    if dry_flag(request) {
+        return Ok((
+            dry_run_success(
+                request,
+                vec![
+                    json!({"step": 1, "action": "pg_dumpall", "target": container}),
+                    json!({"step": 2, "action": "docker_stop_rename", "target": retired}),
+                    json!({"step": 3, "action": "docker_run", "target": target.image(), "volume": target.volume}),
+                    json!({"step": 4, "action": "restore", "target": mask_database_url(&target.url())}),
+                ],
+                "swarm localdb status",
+            )
+            .data,
+            "swarm localdb status".to_string(),
+        ));
+    }
+
+    docker(rid.clone(), &["start", container]).await?;
+    wait_until_ready(rid.clone(), &current).await?;
+    let dump = docker(
+        rid.clone(),
+        &["exec", container, "pg_dumpall", "-U", current.user.as_str()],
+    )
+    .await?;
+    docker(rid.clone(), &["stop", container]).await?;
+    docker(rid.clone(), &["rename", container, retired.as_str()]).await?;
+
+    let restored = async {
+        start_or_run(rid.clone(), &target).await?;
+        wait_until_ready(rid.clone(), &target).await?;
+        docker_with_stdin(
+            rid.clone(),
+            &[
+                "exec",
+                "-i",
+                container,
+                "psql",
+                "-U",
+                target.user.as_str(),
+                "-d",
+                "postgres",
+                "-q",
+            ],
+            Some(dump.clone()),
+        )
+        .await
+    }
+    .await;
+
+    if let Err(failure) = restored {
+        let mut rolled_back = docker(rid.clone(), &["rm", "-f", container]).await.is_ok();
+        rolled_back &= docker(rid.clone(), &["rename", retired.as_str(), container])
+            .await
+            .is_ok();
+        rolled_back &= docker(rid.clone(), &["start", container]).await.is_ok();
+        let msg = failure
+            .err
+            .as_ref()
+            .map_or_else(String::new, |err| err.msg.clone());
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                rid,
+                code::INTERNAL.to_string(),
+                format!("Upgrade to postgres {to} failed: {msg}"),
+            )
+            .with_fix(format!("swarm localdb logs --container-name {container}"))
+            .with_ctx(json!({
+                "error": failure.err,
+                "rolled_back": rolled_back,
+                "from": current.major,
+                "to": to,
+            })),
+        ));
+    }
+
+    Ok((
+        json!({
+            "container": container,
+            "from": current.major,
+            "to": to,
+            "volume": target.volume,
+            "dump_bytes": dump.len(),
+            "retired_container": retired,
+        }),
+        format!("swarm localdb destroy --container-name {retired} --confirm {retired}"),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{postgres_major, LocalDb};
+    use serde_json::json;
+
+    #[test]
+    fn given_inspect_record_when_reading_settings_then_port_env_volume_and_major_are_used() {
+        let inspected = json!({
+            "Config": {
+                "Image": "postgres:16.4-alpine",
+                "Env": ["POSTGRES_USER=swarm", "POSTGRES_DB=swarm_db", "PATH=/usr/bin"],
+            },
+            "HostConfig": {"PortBindings": {"5432/tcp": [{"HostIp": "", "HostPort": "5499"}]}},
+            "Mounts": [{"Type": "volume", "Name": "swarm-data", "Destination": "/var/lib/postgresql/data"}],
+        });
+
+        let local = LocalDb::from_inspect("db", &inspected);
+
+        assert_eq!(local.port, 5499);
+        assert_eq!(local.user, "swarm");
+        assert_eq!(local.database, "swarm_db");
+        assert_eq!(local.volume.as_deref(), Some("swarm-data"));
+        assert_eq!(local.major, 16);
+        assert_eq!(local.url(), "postgresql://swarm@localhost:5499/swarm_db");
+        assert_eq!(postgres_major("redis:7"), None);
+    }
+}
@@ -4,9 +4,15 @@ use super::super::{
     db_from_request, dry_flag, dry_run_success, minimal_state_for_request, required_string_arg,
     CommandSuccess, ProtocolRequest,
 };
+use crate::profiling::HotspotCategory;
 use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::LockMetadata;
 use crate::{code, SwarmError};
 use serde_json::json;
+use std::time::{Duration, Instant};
+
+/// Delay between attempts to take a lock while queued for it.
+const LOCK_WAIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 pub(in crate::protocol_runtime) async fn handle_lock(
     request: &ProtocolRequest,
@@ -43,26 +49,88 @@ pub(in crate::protocol_runtime) async fn handle_lock(
             )
         })?;
 
+    let wait_ms = match request.args.get("wait_ms").map(serde_json::Value::as_i64) {
+        None => None,
+        Some(Some(wait_ms)) if wait_ms >= 0 => Some(wait_ms),
+        Some(_) => {
+            return Err(Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::INVALID.to_string(),
+                    "Invalid wait_ms".to_string(),
+                )
+                .with_fix(
+                    "swarm lock --resource <id> --agent <id> --ttl-ms 30000 --wait-ms 10000"
+                        .to_string(),
+                )
+                .with_ctx(json!({"wait_ms": "must be >= 0"})),
+            ))
+        }
+    }
+    .filter(|wait_ms| *wait_ms > 0);
+    let metadata = LockMetadata {
+        purpose: optional_text_arg(request, "purpose"),
+        bead_id: optional_text_arg(request, "bead_id"),
+    };
+
     if dry_flag(request) {
+        let mut steps = vec![
+            json!({"step": 1, "action": "cleanup_expired_locks", "target": resource.clone()}),
+            json!({"step": 2, "action": "acquire_lock", "target": resource.clone(), "purpose": metadata.purpose, "bead_id": metadata.bead_id}),
+        ];
+        if let Some(wait_ms) = wait_ms {
+            steps.push(json!({"step": 3, "action": "wait_in_queue", "target": resource.clone(), "wait_ms": wait_ms}));
+        }
         return Ok(dry_run_success(
             request,
-            vec![
-                json!({"step": 1, "action": "cleanup_expired_locks", "target": resource.clone()}),
-                json!({"step": 2, "action": "acquire_lock", "target": resource.clone()}),
-            ],
+            steps,
             "swarm lock --resource <id> --agent <id> --ttl-ms 30000",
         ));
     }
 
     let db = db_from_request(request).await?;
-    let acquired = db
-        .acquire_resource_lock(&resource, &agent, ttl_ms)
+    let start = Instant::now();
+    let mut acquired = db
+        .acquire_resource_lock(&resource, &agent, ttl_ms, &metadata)
         .await
         .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
 
+    let mut queue_position = None;
+    if let Some(wait_ms) = wait_ms.filter(|_| acquired.is_none()) {
+        queue_position = Some(
+            db.enqueue_lock_waiter(&resource, &agent, ttl_ms, wait_ms, &metadata)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?,
+        );
+        let max_wait = Duration::from_millis(wait_ms.cast_unsigned());
+        while acquired.is_none() && start.elapsed() < max_wait {
+            tokio::time::sleep(
+                LOCK_WAIT_POLL_INTERVAL.min(max_wait.saturating_sub(start.elapsed())),
+            )
+            .await;
+            acquired = db
+                .acquire_resource_lock(&resource, &agent, ttl_ms, &metadata)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+        }
+        if acquired.is_none() {
+            db.dequeue_lock_waiter(&resource, &agent)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+        }
+        crate::profiling::record(HotspotCategory::LockWait, "resource_lock", start.elapsed());
+    }
+    let waited_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+
     match acquired {
         Some(until_at) => Ok(CommandSuccess {
-            data: json!({"locked": true, "until": until_at.timestamp_millis()}),
+            data: json!({
+                "locked": true,
+                "until": until_at.timestamp_millis(),
+                "purpose": metadata.purpose,
+                "bead_id": metadata.bead_id,
+                "waited_ms": waited_ms,
+            }),
             next: format!("swarm unlock --resource {resource} --agent {agent}"),
             state: minimal_state_for_request(request).await,
         }),
@@ -70,10 +138,21 @@ pub(in crate::protocol_runtime) async fn handle_lock(
             ProtocolEnvelope::error(
                 request.rid.clone(),
                 code::BUSY.to_string(),
-                "Resource lock already held".to_string(),
+                wait_ms.map_or_else(
+                    || "Resource lock already held".to_string(),
+                    |wait_ms| format!("Resource lock still held after waiting {wait_ms}ms"),
+                ),
             )
-            .with_fix("sleep 1; swarm lock --resource <id> --agent <id> --ttl-ms 30000".to_string())
-            .with_ctx(json!({"resource": resource, "agent": agent})),
+            .with_fix(
+                "swarm locks; swarm lock --resource <id> --agent <id> --ttl-ms 30000 --wait-ms 10000"
+                    .to_string(),
+            )
+            .with_ctx(json!({
+                "resource": resource,
+                "agent": agent,
+                "waited_ms": waited_ms,
+                "queue_position": queue_position,
+            })),
         )),
     }
 }
@@ -130,6 +209,34 @@ pub(in crate::protocol_runtime) async fn handle_unlock(
     }
 }
 
+/// Lists every held resource lock with its remaining TTL, and the agents
+/// queued behind it.
+pub(in crate::protocol_runtime) async fn handle_locks(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let db = db_from_request(request).await?;
+    let locks = db
+        .list_resource_locks()
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({"locks": locks}),
+        next: "swarm lock --resource <id> --agent <id> --ttl-ms 30000 --wait-ms 10000".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+fn optional_text_arg(request: &ProtocolRequest, key: &str) -> Option<String> {
+    request
+        .args
+        .get(key)
+        .and_then(serde_json::Value::as_str)
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(ToString::to_string)
+}
+
 fn to_protocol_failure(error: SwarmError, rid: Option<String>) -> Box<ProtocolEnvelope> {
     super::super::helpers::to_protocol_failure(error, rid)
 }
@@ -0,0 +1,366 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, read_db_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{
+    month_start, month_start_at, months_to_cover, next_month, PartitionedTable,
+    PartitionedTableState, DEFAULT_PARTITIONS_AHEAD,
+};
+use crate::{code, MaintenanceInput, SwarmDb};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+const MAINTENANCE_FIX: &str = "swarm maintenance partitions list";
+
+/// `maintenance partitions` lists the monthly partitions of
+/// `execution_events` and `stage_artifacts`, partitions a table (`enable`),
+/// creates coming months ahead of the `partitions` job (`create`), and
+/// detaches or drops old months.
+pub(in crate::protocol_runtime) async fn handle_maintenance(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = MaintenanceInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(MAINTENANCE_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let ahead = input.ahead.unwrap_or(DEFAULT_PARTITIONS_AHEAD);
+    let now = Utc::now();
+
+    match (
+        input.action.as_str(),
+        input.table,
+        input.partition.as_deref(),
+    ) {
+        ("enable", Some(table), _) => enable(request, table, ahead, now).await,
+        ("create", table, _) => create(request, table, ahead, now).await,
+        ("detach", _, Some(partition)) => detach(request, partition, now).await,
+        ("drop", _, Some(partition)) => drop_detached(request, partition).await,
+        (_, table, _) => list(request, table).await,
+    }
+}
+
+async fn list(
+    request: &ProtocolRequest,
+    table: Option<PartitionedTable>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let states = db
+        .partitioned_tables()
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let tables = PartitionedTable::ALL
+        .into_iter()
+        .filter(|candidate| table.is_none_or(|table| table == *candidate))
+        .map(|candidate| {
+            states
+                .iter()
+                .find(|state| state.table == candidate)
+                .map_or_else(
+                    || json!({"table": candidate, "partitioned": false}),
+                    |state| {
+                        json!({
+                            "table": candidate,
+                            "partitioned": true,
+                            "cutover_at": state.cutover_at,
+                            "partitions": state.partitions,
+                        })
+                    },
+                )
+        })
+        .collect::<Vec<_>>();
+    let next = if states.is_empty() {
+        "swarm maintenance partitions enable --table execution_events"
+    } else {
+        "swarm maintenance partitions create"
+    };
+
+    Ok(CommandSuccess {
+        data: json!({"tables": tables}),
+        next: next.to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn enable(
+    request: &ProtocolRequest,
+    table: PartitionedTable,
+    ahead: u32,
+    now: DateTime<Utc>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    // Rows of the current month stay in the legacy partition, so nothing
+    // written today has to move.
+    let Some(cutover) = next_month(month_start(now)) else {
+        return Err(maintenance_error(
+            request,
+            code::INTERNAL,
+            "No month follows the current one".to_string(),
+        ));
+    };
+    let months = months_to_cover(cutover, now, ahead);
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "lock_table", "target": table}),
+                json!({"step": 2, "action": "attach_legacy_partition", "target": table.legacy_partition(), "until": month_start_at(cutover)}),
+                json!({"step": 3, "action": "create_default_partition", "target": table.default_partition()}),
+                json!({"step": 4, "action": "recreate_indexes_and_triggers", "target": table}),
+                json!({"step": 5, "action": "create_partitions", "target": table, "partitions": months.iter().map(|month| table.partition_name(*month)).collect::<Vec<_>>()}),
+            ],
+            MAINTENANCE_FIX,
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    if partitioned_state(request, &db, table).await?.is_some() {
+        return Err(maintenance_error(
+            request,
+            code::CONFLICT,
+            format!("{table} is already partitioned"),
+        ));
+    }
+    db.enable_partitioning(table, cutover)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let created = db
+        .create_partitions(table, &months)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "table": table,
+            "legacy_partition": table.legacy_partition(),
+            "cutover_at": month_start_at(cutover),
+            "created": created,
+        }),
+        next: MAINTENANCE_FIX.to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn create(
+    request: &ProtocolRequest,
+    table: Option<PartitionedTable>,
+    ahead: u32,
+    now: DateTime<Utc>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let db: SwarmDb = db_from_request(request).await?;
+    let states = db
+        .partitioned_tables()
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+        .into_iter()
+        .filter(|state| table.is_none_or(|table| table == state.table))
+        .collect::<Vec<_>>();
+    if let (Some(table), true) = (table, states.is_empty()) {
+        return Err(not_partitioned(request, table));
+    }
+    let plan = states
+        .iter()
+        .map(|state| {
+            (
+                state.table,
+                months_to_cover(state.cutover_at.date_naive(), now, ahead),
+            )
+        })
+        .collect::<Vec<_>>();
+    if dry_flag(request) {
+        let steps = plan
+            .iter()
+            .enumerate()
+            .map(|(index, (table, months))| {
+                json!({
+                    "step": index + 1,
+                    "action": "create_partitions",
+                    "target": table,
+                    "months": months,
+                })
+            })
+            .collect();
+        return Ok(dry_run_success(request, steps, MAINTENANCE_FIX));
+    }
+
+    let mut created = Vec::new();
+    for (table, months) in &plan {
+        created.extend(
+            db.create_partitions(*table, months)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?,
+        );
+    }
+
+    Ok(CommandSuccess {
+        data: json!({"ahead": ahead, "created": created}),
+        next: MAINTENANCE_FIX.to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn detach(
+    request: &ProtocolRequest,
+    partition: &str,
+    now: DateTime<Utc>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let (table, state) = owning_state(request, partition).await?;
+    let Some(found) = state
+        .partitions
+        .iter()
+        .find(|candidate| candidate.name == partition && candidate.attached)
+    else {
+        return Err(maintenance_error(
+            request,
+            code::NOTFOUND,
+            format!("{partition} is not an attached partition of {table}"),
+        ));
+    };
+    // Without a partition for it, a row for that month could not be written.
+    if found.to > month_start_at(month_start(now)) {
+        return Err(maintenance_error(
+            request,
+            code::INVALID,
+            format!("{partition} still takes rows for the current or a later month"),
+        ));
+    }
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "detach_partition", "target": partition, "table": table}),
+            ],
+            MAINTENANCE_FIX,
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    db.detach_partition(table, partition)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "table": table,
+            "partition": partition,
+            "detached": true,
+            "rows_estimate": found.rows_estimate,
+        }),
+        next: format!("swarm maintenance partitions drop --partition {partition}"),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn drop_detached(
+    request: &ProtocolRequest,
+    partition: &str,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let (table, state) = owning_state(request, partition).await?;
+    let Some(found) = state
+        .partitions
+        .iter()
+        .find(|candidate| candidate.name == partition)
+    else {
+        return Err(maintenance_error(
+            request,
+            code::NOTFOUND,
+            format!("{partition} does not exist"),
+        ));
+    };
+    if found.attached {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                format!("{partition} is still attached to {table}; detach it first"),
+            )
+            .with_fix(format!(
+                "swarm maintenance partitions detach --partition {partition}"
+            )),
+        ));
+    }
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "drop_partition", "target": partition, "rows_estimate": found.rows_estimate}),
+            ],
+            MAINTENANCE_FIX,
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    db.drop_partition(partition)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "table": table,
+            "partition": partition,
+            "dropped": true,
+            "rows_estimate": found.rows_estimate,
+        }),
+        next: MAINTENANCE_FIX.to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// The table `partition` belongs to and that table's partitioned state.
+async fn owning_state(
+    request: &ProtocolRequest,
+    partition: &str,
+) -> std::result::Result<(PartitionedTable, PartitionedTableState), Box<ProtocolEnvelope>> {
+    let Some(table) = PartitionedTable::of_partition(partition) else {
+        return Err(maintenance_error(
+            request,
+            code::INVALID,
+            format!("{partition} is not a partition name"),
+        ));
+    };
+    let db: SwarmDb = read_db_from_request(request).await?;
+    partitioned_state(request, &db, table)
+        .await?
+        .map(|state| (table, state))
+        .ok_or_else(|| not_partitioned(request, table))
+}
+
+async fn partitioned_state(
+    request: &ProtocolRequest,
+    db: &SwarmDb,
+    table: PartitionedTable,
+) -> std::result::Result<Option<PartitionedTableState>, Box<ProtocolEnvelope>> {
+    db.partitioned_table(table)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))
+}
+
+fn not_partitioned(request: &ProtocolRequest, table: PartitionedTable) -> Box<ProtocolEnvelope> {
+    Box::new(
+        ProtocolEnvelope::error(
+            request.rid.clone(),
+            code::INVALID.to_string(),
+            format!("{table} is not partitioned"),
+        )
+        .with_fix(format!(
+            "swarm maintenance partitions enable --table {table}"
+        )),
+    )
+}
+
+fn maintenance_error(
+    request: &ProtocolRequest,
+    error_code: &str,
+    msg: String,
+) -> Box<ProtocolEnvelope> {
+    Box::new(
+        ProtocolEnvelope::error(request.rid.clone(), error_code.to_string(), msg)
+            .with_fix(MAINTENANCE_FIX.to_string()),
+    )
+}
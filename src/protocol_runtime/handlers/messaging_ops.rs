@@ -1,13 +1,15 @@
 #![allow(clippy::too_many_lines)]
 
 use super::super::{
-    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, required_string_arg,
-    CommandSuccess, ProtocolRequest,
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    required_string_arg, CommandSuccess, ParseInput, ProtocolRequest,
 };
 use crate::protocol_envelope::ProtocolEnvelope;
-use crate::{code, SwarmError};
+use crate::{code, BroadcastInput, SwarmError};
 use serde_json::json;
 
+/// Posts a durable announcement: every agent sees it until it expires or
+/// the agent acknowledges it, including agents registered after it.
 pub(in crate::protocol_runtime) async fn handle_broadcast(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
@@ -37,23 +39,49 @@ pub(in crate::protocol_runtime) async fn handle_broadcast(
         ));
     }
 
+    let input = BroadcastInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(
+                "swarm broadcast --msg 'pausing claims' --from 1 --topic ops --ttl-secs 3600"
+                    .to_string(),
+            )
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
     if dry_flag(request) {
         return Ok(dry_run_success(
             request,
-            vec![json!({"step": 1, "action": "broadcast", "target": msg.clone()})],
-            "swarm agents",
+            vec![json!({
+                "step": 1,
+                "action": "create_announcement",
+                "target": input.topic,
+                "msg": msg,
+                "ttl_secs": input.ttl_secs,
+            })],
+            "swarm announcements",
         ));
     }
 
     let db = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let announcement = db
+        .create_announcement(&repo_id, &from, &input.topic, &msg, input.ttl_secs)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
     let delivered_to = db
-        .write_broadcast(&from, &msg)
+        .count_registered_agents(&repo_id)
         .await
         .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
 
     Ok(CommandSuccess {
-        data: json!({"delivered_to": delivered_to}),
-        next: "swarm agents".to_string(),
+        data: json!({"announcement": announcement, "delivered_to": delivered_to}),
+        next: "swarm announcements".to_string(),
         state: minimal_state_for_request(request).await,
     })
 }
@@ -1,14 +1,78 @@
 pub(super) mod agent_lifecycle;
+pub(super) mod announcements;
+pub(super) mod approve;
 pub(super) mod artifacts;
+pub(super) mod attest;
+pub(super) mod backlog;
 pub(super) mod batch_ops;
+pub(super) mod bead;
+pub(super) mod blackboard;
+pub(super) mod cancel;
+pub(super) mod chaos;
+pub(super) mod config;
+pub(super) mod context;
+pub(super) mod costs;
+pub(super) mod coverage;
+pub(super) mod diff;
 pub(super) mod doctor;
+pub(super) mod env_diff;
+pub(super) mod events;
+pub(super) mod explain_transition;
+pub(super) mod forecast;
+pub(super) mod invariants;
+pub(super) mod jobs;
+pub(super) mod kv;
+pub(super) mod labels;
+pub(super) mod landing;
 pub(super) mod load_profile;
+pub(super) mod localdb;
 pub(super) mod lock_ops;
+pub(super) mod maintenance;
 pub(super) mod messaging_ops;
 pub(super) mod monitoring;
+pub(super) mod notify;
 pub(super) mod orchestration;
+pub(super) mod perf;
+pub(super) mod profile;
 pub(super) mod prompts;
 pub(super) mod qa_ops;
+pub(super) mod quarantine;
+pub(super) mod recover;
+pub(super) mod replay;
+pub(super) mod report_usage;
+pub(super) mod reservation;
 pub(super) mod resume;
+pub(super) mod review;
+pub(super) mod session;
 pub(super) mod state_ops;
 pub(super) mod swarm_ops;
+pub(super) mod symbols;
+pub(super) mod sync;
+pub(super) mod takeover;
+pub(super) mod tenant;
+pub(super) mod undelete;
+pub(super) mod verify;
+pub(super) mod workspace;
+
+use super::ProtocolRequest;
+use crate::code;
+use crate::protocol_envelope::ProtocolEnvelope;
+use serde_json::json;
+
+/// `INVALID` envelope for a rejected request; the message is echoed in `ctx.error`.
+pub(super) fn invalid_request(
+    request: &ProtocolRequest,
+    message: String,
+    fix: Option<&str>,
+) -> Box<ProtocolEnvelope> {
+    let envelope = ProtocolEnvelope::error(
+        request.rid.clone(),
+        code::INVALID.to_string(),
+        message.clone(),
+    )
+    .with_ctx(json!({"error": message}));
+    Box::new(match fix {
+        Some(fix) => envelope.with_fix(fix.to_string()),
+        None => envelope,
+    })
+}
@@ -1,10 +1,18 @@
 use super::super::{
-    db_from_request, minimal_state_for_request, minimal_state_from_progress, repo_id_from_request,
-    run_external_json_command_with_ms, to_protocol_failure, CommandSuccess, ParseInput,
-    ProtocolRequest,
+    bounded_page_size, minimal_state_for_request, minimal_state_from_progress, next_page_cursor,
+    read_db_from_request, repo_id_from_request, run_external_json_command_with_ms,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
 };
+use crate::alerts::evaluate_alerts;
+use crate::db::swarm_db::ExecutionEventQuery;
+use crate::notifications::deliver;
 use crate::protocol_envelope::ProtocolEnvelope;
-use crate::{code, RepoId, SwarmDb};
+use crate::quarantines::evaluate_quarantines;
+use crate::types::{
+    AlertStatus, BeadCoverageTrend, BeadDriftReport, Notification, NotificationSeverity,
+    QuarantinePolicy, SlaAssessment, SlaPriorityAging, SlaStatus, FINGERPRINT_WINDOW_SECS,
+};
+use crate::{code, OrchestratorEvent, RepoId, RuntimeBeadId, RuntimeRepoId, SwarmDb};
 use serde_json::{json, Value};
 use std::collections::BTreeMap;
 use std::time::Instant;
@@ -25,7 +33,8 @@ pub(in crate::protocol_runtime) async fn handle_monitor(
     })?;
 
     let view = input.view.as_deref().map_or("active", |value| value);
-    let db: SwarmDb = db_from_request(request).await?;
+    let page_size = bounded_page_size(input.page_size);
+    let db: SwarmDb = read_db_from_request(request).await?;
 
     let data = match view {
         "active" => {
@@ -70,10 +79,26 @@ pub(in crate::protocol_runtime) async fn handle_monitor(
         }
         "failures" => {
             let repo_id = repo_id_from_request(request);
-            let rows = db
-                .get_execution_events(&repo_id, None, 200)
+            let events = db
+                .get_execution_events_page(
+                    &repo_id,
+                    &ExecutionEventQuery {
+                        bead_filter: None,
+                        after_seq: input.after_seq,
+                        limit: page_size,
+                        failures_only: true,
+                        since: input.since,
+                        until: input.until,
+                    },
+                )
                 .await
-                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let next_cursor = next_page_cursor(
+                events.last().map(|event| event.seq),
+                events.len(),
+                page_size,
+            );
+            let rows = events
                 .into_iter()
                 .filter_map(|event| {
                     event.diagnostics.map(|diagnostics| {
@@ -88,20 +113,37 @@ pub(in crate::protocol_runtime) async fn handle_monitor(
                             "retryable": diagnostics.retryable,
                             "next_command": diagnostics.next_command,
                             "detail": diagnostics.detail,
+                            "failing_tests": diagnostics.failing_tests,
                             "created_at": event.created_at,
                         })
                     })
                 })
                 .collect::<Vec<_>>();
-            json!({"view": "failures", "rows": rows})
+            json!({"view": "failures", "rows": rows, "page_size": page_size, "next_cursor": next_cursor})
         }
         "events" => {
             let bead_filter = request.args.get("bead_id").and_then(Value::as_str);
             let repo_id = repo_id_from_request(request);
-            let rows = db
-                .get_execution_events(&repo_id, bead_filter, 200)
+            let events = db
+                .get_execution_events_page(
+                    &repo_id,
+                    &ExecutionEventQuery {
+                        bead_filter,
+                        after_seq: input.after_seq,
+                        limit: page_size,
+                        failures_only: false,
+                        since: input.since,
+                        until: input.until,
+                    },
+                )
                 .await
-                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let next_cursor = next_page_cursor(
+                events.last().map(|event| event.seq),
+                events.len(),
+                page_size,
+            );
+            let rows = events
                 .into_iter()
                 .map(|event| {
                     json!({
@@ -119,13 +161,227 @@ pub(in crate::protocol_runtime) async fn handle_monitor(
                     })
                 })
                 .collect::<Vec<_>>();
-            json!({"view": "events", "rows": rows})
+            json!({"view": "events", "rows": rows, "page_size": page_size, "next_cursor": next_cursor})
         }
-        "messages" => {
-            let rows = db
-                .get_all_unread_messages()
+        "drift" => {
+            let bead_filter = request.args.get("bead_id").and_then(Value::as_str);
+            let repo_id = repo_id_from_request(request);
+            let observations = db
+                .get_symbol_observations(&repo_id, bead_filter)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let reports = BeadDriftReport::from_observations(&observations);
+            let rows = reports
+                .iter()
+                .flat_map(|bead| {
+                    bead.report.drifted_symbols.iter().map(|symbol| {
+                        json!({
+                            "bead_id": bead.bead_id,
+                            "kind": symbol.kind.as_str(),
+                            "module_path": symbol.module_path,
+                            "name": symbol.name,
+                            "first_attempt": bead.first_attempt,
+                            "latest_attempt": bead.latest_attempt,
+                            "first_signature": symbol.contract_signature,
+                            "latest_signature": symbol.implementation_signature,
+                            "description": symbol.description(),
+                        })
+                    })
+                })
+                .collect::<Vec<_>>();
+            let beads = reports
+                .iter()
+                .map(|bead| {
+                    json!({
+                        "bead_id": bead.bead_id,
+                        "first_attempt": bead.first_attempt,
+                        "latest_attempt": bead.latest_attempt,
+                        "total_checked": bead.report.total_checked,
+                        "drifted": bead.report.drifted_symbols.len(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({"view": "drift", "has_drift": !rows.is_empty(), "rows": rows, "beads": beads})
+        }
+        "health" => {
+            let repo_id = repo_id_from_request(request);
+            let evaluation = evaluate_quarantines(&db, &repo_id, &QuarantinePolicy::default())
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let rows = evaluation
+                .rows
+                .into_iter()
+                .map(|row| {
+                    let report = row.report;
+                    let quarantine = evaluation
+                        .quarantines
+                        .iter()
+                        .find(|q| q.agent_id == row.agent_id);
+                    json!({
+                        "agent_id": row.agent_id,
+                        "bead_id": row.bead_id,
+                        "agent_status": row.agent_status,
+                        "verdict": report.status.as_str(),
+                        "needs_intervention": report.status.needs_intervention(),
+                        "anomalies": report.anomalies,
+                        "consecutive_failures": report.fingerprint.consecutive_failures,
+                        "secs_since_progress": report.fingerprint.secs_since_progress,
+                        "retry_count": report.fingerprint.retry_count,
+                        "stage_runs": report.current.map(|window| window.stage_runs),
+                        "failure_rate": report.current.map(|window| window.failure_rate()),
+                        "avg_stage_ms": report.current.map(|window| window.avg_stage_ms),
+                        "stages_per_bead": report.current.map(|window| window.stages_per_bead()),
+                        "current": report.current,
+                        "baseline": report.baseline,
+                        "scope_violations": row.scope_violations,
+                        "quarantined": quarantine.is_some(),
+                        "quarantine": quarantine,
+                    })
+                })
+                .collect::<Vec<_>>();
+            let flagged = rows
+                .iter()
+                .filter(|row| row["verdict"] != "healthy")
+                .count();
+            json!({
+                "view": "health",
+                "window_secs": FINGERPRINT_WINDOW_SECS,
+                "window_start": evaluation.window_start,
+                "flagged": flagged,
+                "quarantined": evaluation.quarantines.len(),
+                "newly_quarantined": evaluation.newly_quarantined,
+                "rows": rows,
+            })
+        }
+        "alerts" => {
+            let repo_id = repo_id_from_request(request);
+            let rules = crate::config::alert_rules_from_env()
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let notifications = crate::config::notification_rules_from_env()
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let evaluation = evaluate_alerts(&db, &repo_id, &rules, &notifications)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let firing = evaluation
+                .open
+                .iter()
+                .filter(|alert| alert.status == AlertStatus::Firing)
+                .count();
+            json!({
+                "view": "alerts",
+                "firing": firing,
+                "rows": evaluation.open,
+                "fired": evaluation.fired,
+                "resolved": evaluation.resolved,
+                "observation": evaluation.observation,
+                "webhook_errors": evaluation.webhook_errors,
+                "notification_errors": evaluation.notification_errors,
+                "rules": rules,
+            })
+        }
+        "sla" => {
+            let repo_id = repo_id_from_request(request);
+            let targets = crate::config::sla_targets_from_env()
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let now = chrono::Utc::now();
+            let assessments = db
+                .get_backlog_ages(&repo_id)
                 .await
                 .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+                .iter()
+                .map(|item| targets.assess(item, now))
+                .collect::<Vec<_>>();
+            let (newly_breached, sink_errors, notification_errors) =
+                emit_sla_breaches(&db, &repo_id, &assessments)
+                    .await
+                    .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let priorities = SlaPriorityAging::summarize(&assessments);
+            let mut rows = assessments
+                .into_iter()
+                .filter(|assessment| assessment.sla != SlaStatus::Ok)
+                .collect::<Vec<_>>();
+            rows.sort_by(|a, b| {
+                b.percent_of_target
+                    .unwrap_or(0.0)
+                    .total_cmp(&a.percent_of_target.unwrap_or(0.0))
+            });
+            rows.truncate(usize::try_from(page_size).unwrap_or(usize::MAX));
+            json!({
+                "view": "sla",
+                "rows": rows,
+                "priorities": priorities,
+                "newly_breached": newly_breached,
+                "sink_errors": sink_errors,
+                "notification_errors": notification_errors,
+                "targets": targets,
+            })
+        }
+        "escalations" => {
+            let repo_id = repo_id_from_request(request);
+            let rules = crate::config::escalation_rules_from_env()
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let rows = db
+                .get_open_escalations(&repo_id)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            json!({
+                "view": "escalations",
+                "rows": rows,
+                "rules": rules,
+            })
+        }
+        "coverage" => {
+            let bead_filter = request.args.get("bead_id").and_then(Value::as_str);
+            let repo_id = repo_id_from_request(request);
+            let samples = db
+                .get_coverage_samples(&repo_id, bead_filter)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let min_coverage = crate::config::min_coverage_from_env()
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let rows = BeadCoverageTrend::from_samples(&samples)
+                .into_iter()
+                .map(|trend| {
+                    json!({
+                        "bead_id": trend.bead_id,
+                        "samples": trend.samples,
+                        "first_percent": trend.first_percent,
+                        "latest_percent": trend.latest_percent,
+                        "delta": trend.delta,
+                        "latest_stage": trend.latest_stage,
+                        "latest_at": trend.latest_at,
+                        "below_minimum": min_coverage.is_some_and(|min| trend.latest_percent < min),
+                    })
+                })
+                .collect::<Vec<_>>();
+            json!({"view": "coverage", "rows": rows, "min_coverage": min_coverage})
+        }
+        "sessions" => {
+            let rows = db
+                .list_agent_sessions(&repo_id_from_request(request), page_size)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            json!({"view": "sessions", "rows": rows})
+        }
+        "approvals" => {
+            let bead_filter = request.args.get("bead_id").and_then(Value::as_str);
+            let rows = db
+                .list_approvals(&repo_id_from_request(request), bead_filter, page_size)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            json!({"view": "approvals", "rows": rows})
+        }
+        "messages" => {
+            let messages = db
+                .get_unread_messages_page(input.after_seq, Some(page_size))
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let next_cursor = next_page_cursor(
+                messages.last().map(|message| message.id),
+                messages.len(),
+                page_size,
+            );
+            let rows = messages
                 .into_iter()
                 .map(|message: crate::AgentMessage| {
                     json!({
@@ -140,7 +396,7 @@ pub(in crate::protocol_runtime) async fn handle_monitor(
                     })
                 })
                 .collect::<Vec<_>>();
-            json!({"view": "messages", "rows": rows})
+            json!({"view": "messages", "rows": rows, "page_size": page_size, "next_cursor": next_cursor})
         }
         _ => {
             return Err(Box::new(
@@ -162,12 +418,73 @@ pub(in crate::protocol_runtime) async fn handle_monitor(
     })
 }
 
+/// Throughput window for `top` when `window_mins` is not given.
+const DEFAULT_TOP_WINDOW_MINS: u32 = 60;
+/// Heartbeat age past which a claim's lease (5 minutes) has run out.
+const STALE_HEARTBEAT_SECS: u64 = 300;
+
+pub(in crate::protocol_runtime) async fn handle_top(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::TopInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("echo '{\"cmd\":\"top\",\"window_mins\":60}' | swarm".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let window_mins = input.window_mins.unwrap_or(DEFAULT_TOP_WINDOW_MINS);
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let agents = db
+        .get_agent_activity(&repo_id_from_request(request), window_mins)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    let stale = agents
+        .iter()
+        .filter(|agent| {
+            agent
+                .heartbeat_age_secs
+                .is_some_and(|age| age > STALE_HEARTBEAT_SECS)
+        })
+        .map(|agent| agent.agent_id)
+        .collect::<Vec<_>>();
+    let next = stale.first().map_or_else(
+        || "swarm monitor --view health".to_string(),
+        |agent_id| format!("swarm release --agent-id {agent_id}"),
+    );
+    let stages_passed: u64 = agents
+        .iter()
+        .map(|agent| u64::from(agent.stages_passed))
+        .sum();
+    let beads_completed: u64 = agents
+        .iter()
+        .map(|agent| u64::from(agent.beads_completed))
+        .sum();
+    Ok(CommandSuccess {
+        data: json!({
+            "window_mins": window_mins,
+            "stages_passed": stages_passed,
+            "beads_completed": beads_completed,
+            "stale_heartbeats": stale,
+            "agents": agents,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
 pub(in crate::protocol_runtime) async fn handle_status(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
     let total_start = Instant::now();
     let connect_start = Instant::now();
-    let db: SwarmDb = db_from_request(request).await?;
+    let db: SwarmDb = read_db_from_request(request).await?;
     let db_connect_ms = elapsed_ms(connect_start);
     let repo_id = repo_id_from_request(request);
     let progress_start = Instant::now();
@@ -192,6 +509,7 @@ pub(in crate::protocol_runtime) async fn handle_status(
             "beads_by_status": beads_by_status,
             "timing": {
                 "db": {
+                    "read_replica": db.has_read_replica(),
                     "connect_ms": db_connect_ms,
                     "get_progress_ms": db_progress_ms,
                 },
@@ -257,3 +575,57 @@ async fn beads_by_status_summary_with_timing(rid: Option<String>) -> (Value, u64
 
     (json!(counts), elapsed)
 }
+
+/// Records each breached assessment once and, the first time, sends an
+/// `sla_breached` event to the sinks in `SWARM_EVENT_SINKS`. Returns the
+/// newly breached beads and any sink failures, which do not fail the view.
+async fn emit_sla_breaches(
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    assessments: &[SlaAssessment],
+) -> crate::Result<(Vec<String>, Vec<String>, Vec<String>)> {
+    let sink = crate::config::event_sinks_from_env()?.build(Some(db), repo_id)?;
+    let notifications = crate::config::notification_rules_from_env()?;
+    let mut newly_breached = Vec::new();
+    let mut sink_errors = Vec::new();
+    let mut notification_errors = Vec::new();
+    for assessment in assessments
+        .iter()
+        .filter(|assessment| assessment.sla == SlaStatus::Breached)
+    {
+        let Some(target_secs) = assessment.target_secs else {
+            continue;
+        };
+        if !db
+            .record_sla_breach(repo_id, assessment, target_secs)
+            .await?
+        {
+            continue;
+        }
+        newly_breached.push(assessment.bead_id.clone());
+        if let Some(sink) = sink.as_ref() {
+            let event = OrchestratorEvent::SlaBreached {
+                repo_id: RuntimeRepoId::new(repo_id.value()),
+                bead_id: RuntimeBeadId::new(assessment.bead_id.as_str()),
+                priority: assessment.priority.clone(),
+                age_secs: assessment.age_secs,
+                target_secs,
+            };
+            if let Err(error) = sink.append_event(event).await {
+                sink_errors.push(error.to_string());
+            }
+        }
+        let notification = Notification {
+            event: "sla_breached".to_string(),
+            severity: NotificationSeverity::Warning,
+            repo_id: repo_id.value().to_string(),
+            text: format!(
+                "Bead {} ({}) has waited {}s, past its {}s target",
+                assessment.bead_id, assessment.priority, assessment.age_secs, target_secs
+            ),
+            details: serde_json::to_value(assessment)?,
+        };
+        notification_errors.extend(deliver(&notifications, &notification).await.errors());
+    }
+    Ok((newly_breached, sink_errors, notification_errors))
+}
@@ -0,0 +1,141 @@
+use super::super::{
+    dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::notifications::deliver_to;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{Notification, NotificationRules, NotifierSpec};
+use crate::{code, NotifyInput};
+use serde_json::{json, Value};
+
+const NOTIFY_FIX: &str = "swarm notify test --notifier <name>";
+const TEST_EVENT: &str = "notify_test";
+
+/// `notify test`: sends a test notification to one notifier, or to every
+/// notifier the routes pick for `event` at `severity`, so a misconfigured
+/// webhook or mail server shows up before a real alert needs it.
+pub(in crate::protocol_runtime) async fn handle_notify(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = NotifyInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(NOTIFY_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let rules = crate::config::notification_rules_from_env()
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let notification = Notification {
+        event: input.event.unwrap_or_else(|| TEST_EVENT.to_string()),
+        severity: input.severity.unwrap_or_default(),
+        repo_id: repo_id_from_request(request).value().to_string(),
+        text: "Test notification from swarm".to_string(),
+        details: json!({"test": true, "rid": request.rid}),
+    };
+
+    let targets = match input.notifier.as_deref() {
+        Some(name) if rules.notifiers.contains_key(name) => vec![name],
+        Some(name) => {
+            return Err(Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("Unknown notifier {name}"),
+                )
+                .with_fix("Define it under notifiers in SWARM_NOTIFICATIONS".to_string())
+                .with_ctx(json!({"notifier": name, "notifiers": notifier_names(&rules)})),
+            ))
+        }
+        None => rules.recipients(&notification),
+    };
+    if targets.is_empty() {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::NOTFOUND.to_string(),
+                format!(
+                    "No route sends {} at {}",
+                    notification.event,
+                    notification.severity.as_str()
+                ),
+            )
+            .with_fix(format!(
+                "{NOTIFY_FIX}, or add a route to SWARM_NOTIFICATIONS"
+            ))
+            .with_ctx(json!({
+                "event": notification.event,
+                "severity": notification.severity,
+                "notifiers": notifier_names(&rules),
+            })),
+        ));
+    }
+    let planned = targets
+        .iter()
+        .map(|name| {
+            json!({
+                "notifier": name,
+                "provider": rules.notifiers.get(*name).map(NotifierSpec::provider),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if dry_flag(request) {
+        let steps = planned
+            .iter()
+            .enumerate()
+            .map(|(index, target)| {
+                json!({
+                    "step": index + 1,
+                    "action": "send_notification",
+                    "target": target["notifier"],
+                    "provider": target["provider"],
+                })
+            })
+            .collect();
+        return Ok(dry_run_success(request, steps, "swarm notify test"));
+    }
+
+    let delivery = deliver_to(&rules, &targets, &notification).await;
+    if !delivery.failed.is_empty() {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::DEPENDENCY.to_string(),
+                format!(
+                    "Notification failed for {} of {} notifiers",
+                    delivery.failed.len(),
+                    targets.len()
+                ),
+            )
+            .with_fix(
+                "Check the notifier's endpoint and credentials, then rerun 'swarm notify test'"
+                    .to_string(),
+            )
+            .with_ctx(json!({"sent": delivery.sent, "failed": delivery.failed})),
+        ));
+    }
+
+    Ok(CommandSuccess {
+        data: json!({
+            "event": notification.event,
+            "severity": notification.severity,
+            "sent": delivery.sent,
+            "notifiers": planned,
+        }),
+        next: "swarm monitor --view alerts".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+fn notifier_names(rules: &NotificationRules) -> Vec<Value> {
+    rules
+        .notifiers
+        .iter()
+        .map(|(name, spec)| json!({"notifier": name, "provider": spec.provider()}))
+        .collect()
+}
@@ -4,22 +4,26 @@
 
 use std::iter::FromIterator;
 
-use super::super::super::super::{db_from_request, handle_agent, ProtocolRequest};
+use super::super::super::super::{
+    db_from_request, handle_agent, repo_id_from_request, ProtocolRequest,
+};
 use super::super::helpers::protocol_failure_to_swarm_error;
-use crate::orchestrator_service::{AssignAgentSnapshot, AssignPorts, PortFuture};
-use crate::{AgentId, BeadId, RepoId, RuntimeAgentStatus, RuntimeRepoId};
+use crate::orchestrator_service::{
+    emit_to_configured_sinks, AssignAgentSnapshot, AssignPorts, PortFuture,
+};
+use crate::{
+    AgentId, AgentStatus, BeadId, OrchestratorEvent, RepoId, RuntimeAgentStatus, RuntimeRepoId,
+    SwarmDb, SwarmError,
+};
 use serde_json::{Map, Value};
 
+/// Reads a stored `agent_state.status`. A value the runtime does not know
+/// is schema drift, not an agent in error.
 pub(in crate::protocol_runtime) fn runtime_status_from_db_status(
     status: &str,
-) -> RuntimeAgentStatus {
-    match status {
-        "idle" => RuntimeAgentStatus::Idle,
-        "working" => RuntimeAgentStatus::Working,
-        "waiting" => RuntimeAgentStatus::Waiting,
-        "done" => RuntimeAgentStatus::Done,
-        _ => RuntimeAgentStatus::Error,
-    }
+) -> Result<RuntimeAgentStatus, SwarmError> {
+    RuntimeAgentStatus::try_from(status)
+        .map_err(|error| SwarmError::SchemaDrift(format!("agent_state.status: {error}")))
 }
 
 pub(in crate::protocol_runtime) fn build_agent_request(
@@ -71,14 +75,16 @@ pub(in crate::protocol_runtime) fn load_agent_snapshot<'a>(
         let agent_key = AgentId::new(repo.clone(), agent_id);
         let state = db.get_agent_state(&agent_key).await?;
 
-        Ok(state.map(|agent_state| {
-            let status = runtime_status_from_db_status(agent_state.status().as_str());
-            AssignAgentSnapshot {
-                valid_ids,
-                status,
-                current_bead: agent_state.bead_id().map(|bead| bead.value().to_string()),
-            }
-        }))
+        state
+            .map(|agent_state| {
+                let status = runtime_status_from_db_status(agent_state.status().as_str())?;
+                Ok(AssignAgentSnapshot {
+                    valid_ids,
+                    status,
+                    current_bead: agent_state.bead_id().map(|bead| bead.value().to_string()),
+                })
+            })
+            .transpose()
     })
 }
 
@@ -94,8 +100,18 @@ pub(in crate::protocol_runtime) fn claim_bead<'a>(
             .map_err(|failure| protocol_failure_to_swarm_error(*failure))?;
         let repo = RepoId::new(repo_id.value());
         let agent_key = AgentId::new(repo, agent_id);
-        db.claim_bead(&agent_key, &BeadId::new(bead_id.to_string()))
-            .await
+        let claimed = db
+            .claim_bead(&agent_key, &BeadId::new(bead_id.to_string()))
+            .await?;
+        if claimed {
+            emit_to_configured_sinks(
+                &db,
+                agent_key.repo_id(),
+                OrchestratorEvent::bead_claimed(&agent_key, bead_id),
+            )
+            .await;
+        }
+        Ok(claimed)
     })
 }
 
@@ -113,3 +129,50 @@ pub(in crate::protocol_runtime) fn release_agent<'a>(
         db.release_agent(&agent_key).await.map(|_| ())
     })
 }
+
+pub(in crate::protocol_runtime) fn pending_beads<'a>(
+    db: &'a SwarmDb,
+    repo_id: &'a RepoId,
+) -> PortFuture<'a, u64> {
+    Box::pin(db.count_pending_beads(repo_id))
+}
+
+pub(in crate::protocol_runtime) fn claim_next_backlog_bead<'a>(
+    db: &'a SwarmDb,
+    repo_id: &'a RepoId,
+    agent_id: u32,
+) -> PortFuture<'a, Option<String>> {
+    Box::pin(async move {
+        let agent_key = AgentId::new(repo_id.clone(), agent_id);
+        let bead_id = db
+            .claim_next_bead(&agent_key)
+            .await?
+            .map(|bead| bead.value().to_string());
+        if let Some(bead_id) = &bead_id {
+            emit_to_configured_sinks(
+                db,
+                agent_key.repo_id(),
+                OrchestratorEvent::bead_claimed(&agent_key, bead_id),
+            )
+            .await;
+        }
+        Ok(bead_id)
+    })
+}
+
+/// Agents the backlog can hand a bead to right now: registered, idle, and
+/// holding nothing.
+pub(in crate::protocol_runtime) fn idle_agents<'a>(
+    db: &'a SwarmDb,
+    repo_id: &'a RepoId,
+) -> PortFuture<'a, Vec<u32>> {
+    Box::pin(async move {
+        db.get_available_agents(repo_id).await.map(|agents| {
+            agents
+                .into_iter()
+                .filter(|agent| agent.status == AgentStatus::Idle)
+                .map(|agent| agent.agent_id)
+                .collect()
+        })
+    })
+}
@@ -9,8 +9,8 @@ mod monitor_adapter;
 mod tests;
 
 pub(in crate::protocol_runtime) use agent_adapter::{
-    build_agent_request, claim_bead, load_agent_snapshot, release_agent, run_agent,
-    runtime_status_from_db_status,
+    build_agent_request, claim_bead, claim_next_backlog_bead, idle_agents, load_agent_snapshot,
+    pending_beads, release_agent, run_agent, runtime_status_from_db_status,
 };
 pub(in crate::protocol_runtime) use external_command::{
     br_assign_in_progress, br_show_bead, br_update_in_progress, bv_robot_next, claim_next, doctor,
@@ -20,11 +20,12 @@ pub(in crate::protocol_runtime) use monitor_adapter::{
     build_monitor_progress_request, monitor_progress,
 };
 
-use super::super::super::ProtocolRequest;
+use super::super::super::{repo_id_from_request, ProtocolRequest};
 use crate::orchestrator_service::{
-    AssignAgentSnapshot, AssignPorts, ClaimNextPorts, PortFuture, RunOncePorts,
+    AssignAgentSnapshot, AssignPorts, ClaimNextPorts, PortFuture, RunAllPorts, RunLoopPorts,
+    RunOncePorts,
 };
-use crate::RuntimeRepoId;
+use crate::{RepoId, RuntimeRepoId, SwarmDb};
 
 #[derive(Clone)]
 pub(in crate::protocol_runtime) struct ProtocolCommandAdapter {
@@ -109,3 +110,51 @@ impl RunOncePorts for ProtocolCommandAdapter {
         monitor_progress(&self.request)
     }
 }
+
+/// Ports for the backlog loops (`run`, `run-all`) over one resolved
+/// [`SwarmDb`], so each cycle's count and claim reuse its pool instead of
+/// connecting again.
+#[derive(Clone)]
+pub(in crate::protocol_runtime) struct BacklogAdapter {
+    request: ProtocolRequest,
+    db: SwarmDb,
+    repo_id: RepoId,
+}
+
+impl BacklogAdapter {
+    pub(in crate::protocol_runtime) fn new(request: &ProtocolRequest, db: SwarmDb) -> Self {
+        Self {
+            request: request.clone(),
+            db,
+            repo_id: repo_id_from_request(request),
+        }
+    }
+}
+
+impl RunLoopPorts for BacklogAdapter {
+    fn pending_beads(&self) -> PortFuture<'_, u64> {
+        pending_beads(&self.db, &self.repo_id)
+    }
+
+    fn claim_next_bead(&self, agent_id: u32) -> PortFuture<'_, Option<String>> {
+        claim_next_backlog_bead(&self.db, &self.repo_id, agent_id)
+    }
+
+    fn run_agent(&self, agent_id: u32) -> PortFuture<'_, serde_json::Value> {
+        run_agent(&self.request, agent_id)
+    }
+}
+
+impl RunAllPorts for BacklogAdapter {
+    fn idle_agents(&self) -> PortFuture<'_, Vec<u32>> {
+        idle_agents(&self.db, &self.repo_id)
+    }
+
+    fn claim_next_bead(&self, agent_id: u32) -> PortFuture<'_, Option<String>> {
+        claim_next_backlog_bead(&self.db, &self.repo_id, agent_id)
+    }
+
+    fn run_agent(&self, agent_id: u32) -> PortFuture<'_, serde_json::Value> {
+        run_agent(&self.request, agent_id)
+    }
+}
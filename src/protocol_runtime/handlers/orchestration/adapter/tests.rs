@@ -199,7 +199,7 @@ mod load_agent_snapshot_tests {
     }
 
     #[tokio::test]
-    async fn load_agent_snapshot_with_valid_state_returns_snapshot() {
+    async fn load_agent_snapshot_with_valid_state_returns_snapshot() -> Result<()> {
         let db = MockDb::new()
             .with_available_agents(vec![1, 2, 3])
             .await
@@ -224,23 +224,19 @@ mod load_agent_snapshot_tests {
             .collect::<Vec<_>>();
 
         let agent_key = AgentId::new(RepoId::new("test-repo"), 1);
-        let state = mock_get_agent_state(&db, &agent_key).await.unwrap();
-
-        assert!(state.is_some());
-        let snapshot = state
-            .map(|s| {
-                let status = runtime_status_from_db_status(s.status().as_str());
-                AssignAgentSnapshot {
-                    valid_ids: valid_ids.clone(),
-                    status,
-                    current_bead: s.bead_id().map(|b| b.value().to_string()),
-                }
-            })
-            .unwrap();
+        let Some(state) = mock_get_agent_state(&db, &agent_key).await? else {
+            return Err(SwarmError::Internal("agent 1 has no state".to_string()));
+        };
+        let snapshot = AssignAgentSnapshot {
+            valid_ids,
+            status: runtime_status_from_db_status(state.status().as_str())?,
+            current_bead: state.bead_id().map(|b| b.value().to_string()),
+        };
 
         assert_eq!(snapshot.valid_ids, vec![1, 2, 3]);
         assert_eq!(snapshot.status, RuntimeAgentStatus::Idle);
         assert!(snapshot.current_bead.is_none());
+        Ok(())
     }
 
     #[tokio::test]
@@ -544,31 +540,41 @@ mod external_command_error_handling_tests {
 }
 
 #[test]
-fn given_known_agent_status_when_mapping_then_runtime_status_matches() {
+fn given_known_agent_status_when_mapping_then_runtime_status_matches() -> Result<()> {
     assert_eq!(
-        runtime_status_from_db_status("idle"),
+        runtime_status_from_db_status("idle")?,
         RuntimeAgentStatus::Idle
     );
     assert_eq!(
-        runtime_status_from_db_status("working"),
+        runtime_status_from_db_status("working")?,
         RuntimeAgentStatus::Working
     );
     assert_eq!(
-        runtime_status_from_db_status("waiting"),
+        runtime_status_from_db_status("waiting")?,
         RuntimeAgentStatus::Waiting
     );
     assert_eq!(
-        runtime_status_from_db_status("done"),
+        runtime_status_from_db_status("error")?,
+        RuntimeAgentStatus::Error
+    );
+    assert_eq!(
+        runtime_status_from_db_status("done")?,
         RuntimeAgentStatus::Done
     );
+    Ok(())
 }
 
 #[test]
-fn given_unknown_agent_status_when_mapping_then_error_status_is_used() {
-    assert_eq!(
-        runtime_status_from_db_status("stuck"),
-        RuntimeAgentStatus::Error
-    );
+fn given_unknown_agent_status_when_mapping_then_schema_drift_is_reported() -> Result<()> {
+    let Err(error) = runtime_status_from_db_status("stuck") else {
+        return Err(SwarmError::Internal(
+            "unknown status mapped to a runtime status".to_string(),
+        ));
+    };
+
+    assert!(matches!(error, SwarmError::SchemaDrift(_)));
+    assert!(error.to_string().contains("stuck"));
+    Ok(())
 }
 
 #[test]
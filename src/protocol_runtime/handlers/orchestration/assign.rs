@@ -1,12 +1,13 @@
 use super::super::super::{
-    dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request, CommandSuccess,
-    ProtocolRequest,
+    dry_flag, dry_run_plan, minimal_state_for_request, read_db_from_request, repo_id_from_request,
+    state_unavailable, CommandSuccess, ProtocolRequest,
 };
-use super::adapter::ProtocolCommandAdapter;
+use super::adapter::{br_show_bead, ProtocolCommandAdapter};
 use super::helpers::{issue_id_from_br_payload, issue_status_from_br_payload};
 use crate::orchestrator_service::{AssignAppService, AssignCommand};
 use crate::protocol_envelope::ProtocolEnvelope;
-use crate::{code, RuntimeRepoId, SwarmError};
+use crate::types::{DryRunConflict, DryRunConflictKind};
+use crate::{code, AgentId, BeadId, RuntimeRepoId, SwarmError};
 use serde_json::{json, Value};
 
 pub(in crate::protocol_runtime) async fn handle_assign(
@@ -53,16 +54,7 @@ pub(in crate::protocol_runtime) async fn handle_assign(
         })?;
 
     if dry_flag(request) {
-        return Ok(dry_run_success(
-            request,
-            vec![
-                json!({"step": 1, "action": "br_show", "target": format!("br show {bead_id} --json")}),
-                json!({"step": 2, "action": "claim_bead", "target": format!("agent:{agent_id}, bead:{bead_id}")}),
-                json!({"step": 3, "action": "br_update", "target": format!("br update {bead_id} --status in_progress --assignee swarm-agent-{agent_id} --json")}),
-                json!({"step": 4, "action": "br_verify", "target": format!("br show {bead_id} --json")}),
-            ],
-            "swarm monitor --view active",
-        ));
+        return Ok(assign_dry_run(request, &bead_id, agent_id).await);
     }
 
     let repo_id = repo_id_from_request(request);
@@ -105,6 +97,79 @@ pub(in crate::protocol_runtime) async fn handle_assign(
     })
 }
 
+/// Plans `assign` from the agent, claim and backlog rows and `br show`,
+/// all read-only, reporting what would make the real run fail.
+async fn assign_dry_run(request: &ProtocolRequest, bead_id: &str, agent_id: u32) -> CommandSuccess {
+    let repo_id = repo_id_from_request(request);
+    let mut conflicts = Vec::new();
+
+    let preview = match read_db_from_request(request).await {
+        Ok(db) => db
+            .assign_preview(
+                &AgentId::new(repo_id.clone(), agent_id),
+                &BeadId::new(bead_id),
+            )
+            .await
+            .map_err(|error| {
+                DryRunConflict::new(DryRunConflictKind::StateUnavailable, error.to_string())
+            }),
+        Err(failure) => Err(state_unavailable(&failure)),
+    };
+    let preview = match preview {
+        Ok(preview) => {
+            conflicts.extend(preview.conflicts(agent_id, bead_id));
+            Some(preview)
+        }
+        Err(conflict) => {
+            conflicts.push(conflict);
+            None
+        }
+    };
+
+    let br_status = match br_show_bead(request, bead_id).await {
+        Ok(payload) => issue_status_from_br_payload(&payload),
+        Err(error) => {
+            conflicts.push(DryRunConflict::new(
+                DryRunConflictKind::StateUnavailable,
+                error.to_string(),
+            ));
+            None
+        }
+    };
+    if let Some(status) = br_status.as_deref().filter(|status| *status != "open") {
+        conflicts.push(DryRunConflict::new(
+            DryRunConflictKind::BeadNotOpen,
+            format!("Bead {bead_id} is {status}, not open"),
+        ));
+    }
+
+    let backlog_write = match preview.as_ref().map(|p| p.backlog_status.as_deref()) {
+        Some(Some(status)) => {
+            json!({"table": "bead_backlog", "op": "update", "key": bead_id, "from": status, "to": "in_progress"})
+        }
+        _ => json!({"table": "bead_backlog", "op": "insert", "key": bead_id, "to": "in_progress"}),
+    };
+    let agent_from = preview.as_ref().and_then(|p| p.agent_status.clone());
+    let assignee = format!("swarm-agent-{agent_id}");
+    dry_run_plan(
+        request,
+        vec![
+            json!({"step": 1, "action": "br_show", "target": format!("br show {bead_id} --json"), "observed": {"status": br_status}}),
+            json!({"step": 2, "action": "claim_bead", "target": format!("agent:{agent_id}, bead:{bead_id}"), "repo": repo_id.value(), "rows": [
+                backlog_write,
+                {"table": "bead_claims", "op": "insert", "key": bead_id, "claimed_by": agent_id},
+                {"table": "agent_state", "op": "update", "key": agent_id, "from": agent_from, "to": "working"},
+            ]}),
+            json!({"step": 3, "action": "br_update", "target": format!("br update {bead_id} --status in_progress --assignee {assignee} --json")}),
+            json!({"step": 4, "action": "br_verify", "target": format!("br show {bead_id} --json")}),
+        ],
+        conflicts,
+        vec![format!("br marks {bead_id} in_progress for {assignee}")],
+        "swarm monitor --view active",
+    )
+    .await
+}
+
 fn map_assign_error(
     request: &ProtocolRequest,
     bead_id: &str,
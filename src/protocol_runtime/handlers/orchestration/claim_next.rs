@@ -1,27 +1,32 @@
 use super::super::super::{
-    bead_id_from_recommendation, dry_flag, dry_run_success, elapsed_ms, minimal_state_for_request,
-    CommandSuccess, ProtocolRequest,
+    bead_id_from_recommendation, db_from_request, dry_flag, dry_run_plan, dry_run_success,
+    elapsed_ms, minimal_state_for_request, repo_id_from_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
 };
-use super::adapter::ProtocolCommandAdapter;
+use super::adapter::{br_show_bead, bv_robot_next, ProtocolCommandAdapter};
+use super::helpers::issue_status_from_br_payload;
 use crate::code;
-use crate::orchestrator_service::ClaimNextAppService;
+use crate::orchestrator_service::{emit_to_configured_sinks, ClaimNextAppService};
 use crate::protocol_envelope::ProtocolEnvelope;
-use serde_json::json;
+use crate::types::{AgentId, DryRunConflict, DryRunConflictKind};
+use crate::{LabeledClaimInput, OrchestratorEvent, SwarmDb};
+use serde_json::{json, Value};
 use std::time::Instant;
 
+const LABELED_CLAIM_FIX: &str = "swarm claim-next --label docs --agent-id <id>";
+
 pub(in crate::protocol_runtime) async fn handle_claim_next(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    if request.args.get("reserve").and_then(Value::as_bool) == Some(true) {
+        return super::super::reservation::handle_reserve(request).await;
+    }
+    if request.args.contains_key("label") {
+        return claim_labeled(request).await;
+    }
     let total_start = Instant::now();
     if dry_flag(request) {
-        return Ok(dry_run_success(
-            request,
-            vec![
-                json!({"step": 1, "action": "bv_robot_next", "target": "bv --robot-next"}),
-                json!({"step": 2, "action": "br_update", "target": "br update <bead-id> --status in_progress --json"}),
-            ],
-            "swarm status",
-        ));
+        return Ok(claim_next_dry_run(request).await);
     }
 
     let adapter = ProtocolCommandAdapter::new(request);
@@ -64,3 +69,118 @@ pub(in crate::protocol_runtime) async fn handle_claim_next(
         state: minimal_state_for_request(request).await,
     })
 }
+
+/// `claim-next --label`: claims from the swarm backlog rather than through
+/// `bv`, taking the top pending bead that carries one of the labels.
+async fn claim_labeled(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = LabeledClaimInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(LABELED_CLAIM_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "claim_next_bead", "target": format!("agent:{}", input.agent_id), "labels": input.labels}),
+            ],
+            &format!("swarm agent --id {}", input.agent_id),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let agent_key = AgentId::new(repo_id_from_request(request), input.agent_id);
+    let bead_id = db
+        .claim_next_bead_with_labels(&agent_key, &input.labels)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    if let Some(bead_id) = &bead_id {
+        emit_to_configured_sinks(
+            &db,
+            agent_key.repo_id(),
+            OrchestratorEvent::bead_claimed(&agent_key, bead_id.value()),
+        )
+        .await;
+    }
+
+    let next = bead_id.as_ref().map_or_else(
+        || "swarm sync-backlog".to_string(),
+        |_| format!("swarm agent --id {}", input.agent_id),
+    );
+    Ok(CommandSuccess {
+        data: json!({
+            "claimed": bead_id.is_some(),
+            "bead_id": bead_id,
+            "agent_id": input.agent_id,
+            "labels": input.labels,
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Plans `claim-next` from what `bv --robot-next` recommends now and the
+/// bead's status in `br`; both commands only read.
+async fn claim_next_dry_run(request: &ProtocolRequest) -> CommandSuccess {
+    let mut conflicts = Vec::new();
+    let recommendation = match bv_robot_next(request).await {
+        Ok(recommendation) => Some(recommendation),
+        Err(error) => {
+            conflicts.push(DryRunConflict::new(
+                DryRunConflictKind::StateUnavailable,
+                error.to_string(),
+            ));
+            None
+        }
+    };
+    let bead_id = recommendation
+        .as_ref()
+        .and_then(bead_id_from_recommendation);
+    if recommendation.is_some() && bead_id.is_none() {
+        conflicts.push(DryRunConflict::new(
+            DryRunConflictKind::NoRecommendation,
+            "bv --robot-next returned no bead id",
+        ));
+    }
+
+    let mut status = None;
+    if let Some(bead_id) = bead_id.as_deref() {
+        match br_show_bead(request, bead_id).await {
+            Ok(payload) => status = issue_status_from_br_payload(&payload),
+            Err(error) => conflicts.push(DryRunConflict::new(
+                DryRunConflictKind::StateUnavailable,
+                error.to_string(),
+            )),
+        }
+    }
+    if let (Some(bead_id), Some(status)) = (bead_id.as_deref(), status.as_deref()) {
+        if status != "open" {
+            conflicts.push(DryRunConflict::new(
+                DryRunConflictKind::BeadNotOpen,
+                format!("Bead {bead_id} is {status}, not open"),
+            ));
+        }
+    }
+
+    let target = bead_id.as_deref().unwrap_or("<bead-id>");
+    dry_run_plan(
+        request,
+        vec![
+            json!({"step": 1, "action": "bv_robot_next", "target": "bv --robot-next", "observed": recommendation}),
+            json!({"step": 2, "action": "br_update", "target": format!("br update {target} --status in_progress --json"), "observed": {"status": status}}),
+        ],
+        conflicts,
+        vec![format!("br marks {target} in_progress")],
+        &bead_id.as_deref().map_or_else(|| "swarm status".to_string(), |id| format!("br show {id}")),
+    )
+    .await
+}
@@ -2,10 +2,14 @@ mod adapter;
 mod assign;
 mod claim_next;
 mod helpers;
+mod run_all;
+mod run_loop;
 mod run_once;
 
 pub(in crate::protocol_runtime) use assign::handle_assign;
 pub(in crate::protocol_runtime) use claim_next::handle_claim_next;
+pub(in crate::protocol_runtime) use run_all::handle_run_all;
+pub(in crate::protocol_runtime) use run_loop::handle_run;
 pub(in crate::protocol_runtime) use run_once::handle_run_once;
 
 #[cfg(test)]
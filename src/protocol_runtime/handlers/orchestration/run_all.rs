@@ -0,0 +1,63 @@
+use super::super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
+};
+use super::adapter::BacklogAdapter;
+use crate::code;
+use crate::orchestrator_service::{RunAllAppService, DEFAULT_RUN_ALL_CONCURRENCY};
+use crate::protocol_envelope::ProtocolEnvelope;
+use serde_json::json;
+
+const RUN_ALL_FIX: &str = "swarm run-all --concurrency 4";
+
+/// One tick for the whole swarm: every idle agent claims a bead and the
+/// agents that got one run their stages, a bounded number at a time.
+pub(in crate::protocol_runtime) async fn handle_run_all(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::RunAllInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(RUN_ALL_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let concurrency = input.concurrency.unwrap_or(DEFAULT_RUN_ALL_CONCURRENCY);
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "list_idle_agents", "target": "agent_state"}),
+                json!({"step": 2, "action": "claim_next", "target": "each_idle_agent"}),
+                json!({"step": 3, "action": "agent", "target": "each_claiming_agent", "concurrency": concurrency}),
+            ],
+            "swarm status",
+        ));
+    }
+
+    let db = db_from_request(request).await?;
+    let service = RunAllAppService::new(BacklogAdapter::new(request, db), concurrency);
+    let summary = service
+        .execute()
+        .await
+        .map_err(|error| to_protocol_failure(error, request.rid.clone()))?;
+
+    let next = if summary.failed > 0 {
+        "swarm monitor --view failures"
+    } else if summary.claimed == 0 {
+        "swarm status"
+    } else {
+        "swarm run-all"
+    };
+
+    Ok(CommandSuccess {
+        data: json!({"summary": summary}),
+        next: next.to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
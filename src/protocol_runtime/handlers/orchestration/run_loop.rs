@@ -0,0 +1,81 @@
+use super::super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
+};
+use super::adapter::BacklogAdapter;
+use crate::code;
+use crate::orchestrator_service::{RunLoopAppService, RunLoopConfig};
+use crate::protocol_envelope::ProtocolEnvelope;
+use serde_json::json;
+
+const RUN_FIX: &str = "swarm run --id 1 --until-empty";
+
+/// Claims, runs and finalizes beads for one agent until the backlog drains,
+/// the budget or cycle limit is hit, or failures pile up.
+pub(in crate::protocol_runtime) async fn handle_run(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::RunInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(RUN_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    let agent_id = input.id.unwrap_or(1);
+    let defaults = RunLoopConfig::default();
+    let config = RunLoopConfig {
+        budget_ms: input.budget_ms.unwrap_or(defaults.budget_ms),
+        max_cycles: input.max_cycles,
+        until_empty: input.until_empty.unwrap_or(defaults.until_empty),
+        ..defaults
+    };
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "count_pending", "target": "bead_backlog"}),
+                json!({"step": 2, "action": "claim_next", "target": agent_id}),
+                json!({"step": 3, "action": "agent", "target": agent_id}),
+                json!({"step": 4, "action": "pause", "target": "adaptive"}),
+                json!({"step": 5, "action": "repeat", "target": "until_stop"}),
+            ],
+            "swarm status",
+        ));
+    }
+
+    let db = db_from_request(request).await?;
+    let service = RunLoopAppService::new(BacklogAdapter::new(request, db), config.clone());
+    let summary = service
+        .execute(agent_id)
+        .await
+        .map_err(|error| to_protocol_failure(error, request.rid.clone()))?;
+
+    let next = if summary.failures.is_empty() {
+        "swarm status"
+    } else {
+        "swarm monitor --view failures"
+    };
+
+    Ok(CommandSuccess {
+        data: json!({
+            "summary": summary,
+            "config": {
+                "budget_ms": config.budget_ms,
+                "max_cycles": config.max_cycles,
+                "until_empty": config.until_empty,
+                "target_db_latency_ms": config.target_db_latency_ms,
+                "max_pause_ms": config.max_pause_ms,
+                "max_consecutive_failures": config.max_consecutive_failures,
+            },
+        }),
+        next: next.to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
@@ -56,6 +56,7 @@ fn given_protocol_failure_without_error_when_mapping_to_swarm_error_then_default
         t: 0,
         ms: None,
         d: None,
+        enc: None,
         err: None,
         fix: None,
         next: None,
@@ -124,6 +125,22 @@ async fn given_dry_assign_when_executed_then_it_returns_assignment_plan() {
             .map(std::vec::Vec::len),
         Some(4)
     );
+    let conflicts = success
+        .data
+        .get("conflicts")
+        .and_then(Value::as_array)
+        .expect("dry assign reports conflicts");
+    assert_eq!(
+        success.data.get("would_succeed").and_then(Value::as_bool),
+        Some(conflicts.is_empty())
+    );
+    assert!(success
+        .data
+        .pointer("/would_do/1/rows")
+        .and_then(Value::as_array)
+        .is_some_and(|rows| rows
+            .iter()
+            .any(|row| row.get("table").and_then(Value::as_str) == Some("bead_claims"))));
 }
 
 #[tokio::test]
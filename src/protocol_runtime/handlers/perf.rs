@@ -0,0 +1,80 @@
+use super::super::{
+    dry_flag, dry_run_success, minimal_state_for_request, read_db_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::query_perf::{
+    compare, DEFAULT_BASELINE_DAYS, DEFAULT_MIN_SAMPLES, DEFAULT_REGRESSION_THRESHOLD_PCT,
+};
+use crate::{code, PerfInput, SwarmDb};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+
+const PERF_FIX: &str = "swarm perf compare --since 2026-01-01T00:00:00Z";
+
+/// `perf compare`: ranks queries whose p95 latency since `since` grew more
+/// than `threshold_pct` over their p95 in the `baseline_days` before it, to
+/// catch a schema or index regression after an upgrade.
+pub(in crate::protocol_runtime) async fn handle_perf(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = PerfInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(PERF_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let threshold_pct = input
+        .threshold_pct
+        .unwrap_or(DEFAULT_REGRESSION_THRESHOLD_PCT);
+    let min_samples = input.min_samples.unwrap_or(DEFAULT_MIN_SAMPLES);
+    let baseline_days = input.baseline_days.unwrap_or(DEFAULT_BASELINE_DAYS);
+    let baseline_from = input
+        .since
+        .checked_sub_signed(Duration::days(i64::from(baseline_days)))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC);
+    let until = input.until.unwrap_or_else(Utc::now);
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "load_query_latency", "target": "query_latency", "from": baseline_from, "until": until}),
+                json!({"step": 2, "action": "compare_p95", "target": "query_latency", "threshold_pct": threshold_pct, "min_samples": min_samples}),
+            ],
+            "swarm perf compare",
+        ));
+    }
+
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let (baseline, current) = db
+        .query_latency_windows(baseline_from, input.since, until)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let comparison = compare(&baseline, &current, threshold_pct, min_samples);
+    let next = if comparison.regressions.is_empty() {
+        "swarm status".to_string()
+    } else {
+        "swarm db-health".to_string()
+    };
+
+    Ok(CommandSuccess {
+        data: json!({
+            "baseline": {"from": baseline_from, "until": input.since, "queries": baseline.len()},
+            "current": {"from": input.since, "until": until, "queries": current.len()},
+            "threshold_pct": threshold_pct,
+            "min_samples": min_samples,
+            "regressions": comparison.regressions,
+            "compared": comparison.compared,
+            "new_queries": comparison.new_queries,
+            "too_few_samples": comparison.too_few_samples,
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
@@ -0,0 +1,85 @@
+use super::super::{
+    dry_flag, dry_run_success, minimal_state_for_request, CommandSuccess, ParseInput,
+    ProtocolRequest,
+};
+use crate::profiling::{self, DEFAULT_PROFILE_DURATION_MS, MAX_PROFILE_DURATION_MS};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::{code, ProfileInput};
+use serde_json::json;
+use std::time::Duration;
+
+const PROFILE_FIX: &str = "swarm profile --duration-ms 5000";
+
+/// Samples what this process spends its time on for `duration_ms` and ranks
+/// it: database query classes, request and envelope serde, external
+/// commands and lock waits. Only work this process does during the window
+/// is seen, so run it in a protocol session beside the slow requests, or
+/// in a batch with them.
+pub(in crate::protocol_runtime) async fn handle_profile(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = ProfileInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(PROFILE_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    if !profiling::is_available() {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                "This swarm was built without the `profiling` feature".to_string(),
+            )
+            .with_fix("cargo build --release --features profiling".to_string()),
+        ));
+    }
+    let duration_ms = input.duration_ms.unwrap_or(DEFAULT_PROFILE_DURATION_MS);
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "open_sampling_window", "target": "profiling", "duration_ms": duration_ms}),
+                json!({"step": 2, "action": "rank_hotspots", "target": "profiling"}),
+            ],
+            "swarm profile",
+        ));
+    }
+
+    let Some(profile) = profiling::sample(Duration::from_millis(duration_ms)).await else {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INTERNAL.to_string(),
+                "Profiling is unavailable".to_string(),
+            )
+            .with_fix("cargo build --release --features profiling".to_string()),
+        ));
+    };
+    // An empty window usually means nothing ran; a longer one may catch it.
+    let next = if profile.hotspots.is_empty() {
+        format!(
+            "swarm profile --duration-ms {}",
+            duration_ms.saturating_mul(2).min(MAX_PROFILE_DURATION_MS)
+        )
+    } else {
+        format!("swarm profile --duration-ms {duration_ms}")
+    };
+
+    Ok(CommandSuccess {
+        data: json!({
+            "duration_ms": profile.duration_ms,
+            "sampled_ms": profile.sampled_ms,
+            "categories": profile.categories,
+            "hotspots": profile.hotspots,
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
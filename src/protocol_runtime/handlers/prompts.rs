@@ -1,59 +1,43 @@
 use super::super::{
     current_repo_root, db_from_request, dry_flag, dry_run_success, minimal_state_for_request,
     repo_id_from_request, to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+    BULK_WRITE_CONCURRENCY,
 };
 use crate::agent_runtime::run_smoke_once;
 use crate::protocol_envelope::ProtocolEnvelope;
 use crate::{code, AgentId, SwarmDb, SwarmError};
-use serde_json::{json, Value};
-use std::future::Future;
-use std::pin::Pin;
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use serde_json::{json, Map, Value};
+use std::path::{Path, PathBuf};
 use tokio::fs;
 
 pub(in crate::protocol_runtime) async fn handle_spawn_prompts(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
-    let (template_text, template_name) =
-        if let Some(path) = request.args.get("template").and_then(Value::as_str) {
-            let text = fs::read_to_string(path).await.map_err(|err| {
-                Box::new(
-                    ProtocolEnvelope::error(
-                        request.rid.clone(),
-                        code::NOTFOUND.to_string(),
-                        format!("Template file not found: {err}"),
-                    )
-                    .with_fix(format!("Ensure {path} exists"))
-                    .with_ctx(json!({"template": path})),
-                )
-            })?;
-            (text, path.to_string())
-        } else {
-            let repo_root = current_repo_root().await?;
-            let template_path = crate::prompts::canonical_agent_prompt_path(&repo_root);
-            let text = crate::prompts::load_agent_prompt_template(&repo_root)
-                .await
-                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
-            (text, template_path.to_string_lossy().to_string())
-        };
+    let input = crate::SpawnPromptsInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("echo '{\"cmd\":\"spawn-prompts\",\"count\":3}' | swarm".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let template = load_prompt_template(request, input.template.as_deref()).await?;
 
-    let out_dir = request
-        .args
-        .get("out_dir")
-        .and_then(Value::as_str)
-        .map_or(".agents/generated", |value| value);
+    let out_dir = input.out_dir.as_deref().unwrap_or(".agents/generated");
+    let count = input.count.unwrap_or(10);
 
-    let count = request
-        .args
-        .get("count")
-        .and_then(Value::as_u64)
-        .and_then(|v| u32::try_from(v).ok())
-        .unwrap_or(10);
+    // Render once up front so a missing variable fails the dry run too.
+    template.render(request, 1, input.vars.as_ref())?;
 
     if dry_flag(request) {
         return Ok(dry_run_success(
             request,
             vec![
-                json!({"step": 1, "action": "read_template", "target": template_name}),
+                json!({"step": 1, "action": "read_template", "target": template.name}),
                 json!({"step": 2, "action": "write_prompts", "target": count, "dir": out_dir}),
             ],
             "swarm monitor --view progress",
@@ -73,42 +57,170 @@ pub(in crate::protocol_runtime) async fn handle_spawn_prompts(
         .map_err(SwarmError::IoError)
         .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
 
-    spawn_prompts_recursive(
-        out_dir,
-        &template_text,
-        1,
-        configured_count,
-        request.rid.clone(),
-    )
-    .await?;
+    let rendered = (1..=configured_count)
+        .map(|agent_id| {
+            template
+                .render(request, agent_id, input.vars.as_ref())
+                .map(|text| (agent_id, text))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    spawn_prompts(out_dir, rendered, request.rid.clone()).await?;
 
     Ok(CommandSuccess {
-        data: json!({"count": configured_count, "out_dir": out_dir, "template": template_name}),
+        data: json!({"count": configured_count, "out_dir": out_dir, "template": template.name}),
         next: "swarm monitor --view active".to_string(),
         state: minimal_state_for_request(request).await,
     })
 }
 
-fn spawn_prompts_recursive<'a>(
-    out_dir: &'a str,
-    template_text: &'a str,
-    next: u32,
-    count: u32,
+async fn spawn_prompts(
+    out_dir: &str,
+    rendered: Vec<(u32, String)>,
     rid: Option<String>,
-) -> Pin<Box<dyn Future<Output = std::result::Result<(), Box<ProtocolEnvelope>>> + Send + 'a>> {
-    Box::pin(async move {
-        if next > count {
-            Ok(())
-        } else {
-            let file = format!("{out_dir}/agent_{next:02}.md");
-            fs::write(file, template_text.replace("{N}", &next.to_string()))
-                .await
-                .map_err(SwarmError::IoError)
-                .map_err(|e| to_protocol_failure(e, rid.clone()))?;
+) -> std::result::Result<(), Box<ProtocolEnvelope>> {
+    stream::iter(rendered)
+        .map(Ok)
+        .try_for_each_concurrent(BULK_WRITE_CONCURRENCY, |(next, text)| {
+            let rid = rid.clone();
+            async move {
+                let file = format!("{out_dir}/agent_{next:02}.md");
+                fs::write(file, text)
+                    .await
+                    .map_err(SwarmError::IoError)
+                    .map_err(|e| to_protocol_failure(e, rid))
+            }
+        })
+        .await
+}
 
-            spawn_prompts_recursive(out_dir, template_text, next.saturating_add(1), count, rid)
-                .await
+/// A prompt template plus what its variables and includes resolve against.
+struct PromptTemplate {
+    text: String,
+    name: String,
+    repo_root: PathBuf,
+    include_root: PathBuf,
+}
+
+impl PromptTemplate {
+    fn render(
+        &self,
+        request: &ProtocolRequest,
+        agent_id: u32,
+        overrides: Option<&Map<String, Value>>,
+    ) -> std::result::Result<String, Box<ProtocolEnvelope>> {
+        let vars = crate::prompts::default_prompt_vars(
+            &self.repo_root,
+            repo_id_from_request(request).value(),
+            agent_id,
+            overrides,
+        );
+        crate::prompts::render_prompt_template(&self.text, &vars, Some(&self.include_root)).map_err(
+            |error| {
+                Box::new(
+                    ProtocolEnvelope::error(
+                        request.rid.clone(),
+                        code::INVALID.to_string(),
+                        error.to_string(),
+                    )
+                    .with_fix(
+                        "Pass the missing values with --vars '{\"bead_id\":\"bd-1\"}'".to_string(),
+                    )
+                    .with_ctx(json!({"template": self.name})),
+                )
+            },
+        )
+    }
+}
+
+/// Repository root for `repo_name`, or the working directory outside git.
+pub(super) async fn repo_root_or_cwd(
+    request: &ProtocolRequest,
+) -> std::result::Result<PathBuf, Box<ProtocolEnvelope>> {
+    match current_repo_root().await {
+        Ok(root) => Ok(root),
+        Err(_) => std::env::current_dir()
+            .map_err(SwarmError::IoError)
+            .map_err(|e| to_protocol_failure(e, request.rid.clone())),
+    }
+}
+
+/// Reads `path`, or the canonical `.agents/agent_prompt.md` when no path is given.
+/// Includes resolve next to the template file.
+async fn load_prompt_template(
+    request: &ProtocolRequest,
+    path: Option<&str>,
+) -> std::result::Result<PromptTemplate, Box<ProtocolEnvelope>> {
+    if let Some(path) = path {
+        let text = fs::read_to_string(path).await.map_err(|err| {
+            Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("Template file not found: {err}"),
+                )
+                .with_fix(format!("Ensure {path} exists"))
+                .with_ctx(json!({"template": path})),
+            )
+        })?;
+        let repo_root = repo_root_or_cwd(request).await?;
+        let include_root = Path::new(path)
+            .parent()
+            .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+        return Ok(PromptTemplate {
+            text,
+            name: path.to_string(),
+            repo_root,
+            include_root,
+        });
+    }
+
+    let repo_root = current_repo_root().await?;
+    let template_path = crate::prompts::canonical_agent_prompt_path(&repo_root);
+    let text = crate::prompts::load_agent_prompt_template(&repo_root)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    Ok(PromptTemplate {
+        text,
+        name: template_path.to_string_lossy().to_string(),
+        include_root: repo_root.join(".agents"),
+        repo_root,
+    })
+}
+
+/// `prompt render`: the agent template (or `template`, or a skill prompt when
+/// `skill` is set) rendered with `vars`, without writing anything.
+async fn render_prompt_preview(
+    request: &ProtocolRequest,
+    input: &crate::PromptInput,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let template = match (input.template.as_deref(), input.skill.as_deref()) {
+        (None, Some(skill)) => {
+            let prompt = get_skill_prompt(request, skill).await?;
+            let repo_root = repo_root_or_cwd(request).await?;
+            PromptTemplate {
+                text: prompt.data["prompt"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                name: format!("skill:{skill}"),
+                include_root: repo_root.join(".agents"),
+                repo_root,
+            }
         }
+        (path, _) => load_prompt_template(request, path).await?,
+    };
+
+    let rendered = template.render(request, input.id, input.vars.as_ref())?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "template": template.name,
+            "agent_id": input.id,
+            "vars": input.vars.clone().unwrap_or_default(),
+            "prompt": rendered,
+        }),
+        next: "swarm spawn-prompts --dry".to_string(),
+        state: minimal_state_for_request(request).await,
     })
 }
 
@@ -127,25 +239,17 @@ pub(in crate::protocol_runtime) async fn handle_prompt(
         )
     })?;
 
-    if let Some(skill_name) = input.skill.as_deref() {
-        if let Some(prompt) = crate::skill_prompts::get_skill_prompt(skill_name) {
-            return Ok(CommandSuccess {
-                data: json!({"skill": skill_name, "prompt": prompt}),
-                next: "swarm monitor --view progress".to_string(),
-                state: minimal_state_for_request(request).await,
-            });
+    match input.action.as_deref() {
+        Some("list") => return list_skill_prompts(request, input.skill.as_deref()).await,
+        Some("render") => return render_prompt_preview(request, &input).await,
+        Some(action @ ("add" | "update")) => {
+            return write_skill_prompt(request, &input, action).await;
         }
-        return Err(Box::new(
-            ProtocolEnvelope::error(
-                request.rid.clone(),
-                code::NOTFOUND.to_string(),
-                format!("Skill prompt not found: {skill_name}"),
-            )
-            .with_fix(
-                "Use a valid skill: rust-contract, implement, qa-enforcer, red-queen".to_string(),
-            )
-            .with_ctx(json!({"skill": skill_name})),
-        ));
+        _ => {}
+    }
+
+    if let Some(skill_name) = input.skill.as_deref() {
+        return get_skill_prompt(request, skill_name).await;
     }
 
     let id = input.id;
@@ -162,6 +266,208 @@ pub(in crate::protocol_runtime) async fn handle_prompt(
     })
 }
 
+/// Stored prompt first; the embedded default when nothing is stored or the
+/// database is unreachable.
+async fn get_skill_prompt(
+    request: &ProtocolRequest,
+    skill_name: &str,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let stored = match db_from_request(request).await {
+        Ok(db) => db
+            .get_latest_skill_prompt(&repo_id_from_request(request), skill_name)
+            .await
+            .ok()
+            .flatten(),
+        Err(_) => None,
+    };
+
+    let data = match (stored, crate::skill_prompts::get_skill_prompt(skill_name)) {
+        (Some(stored), _) => json!({
+            "skill": skill_name,
+            "prompt": stored.body,
+            "source": "db",
+            "version": stored.version,
+        }),
+        (None, Some(prompt)) => json!({
+            "skill": skill_name,
+            "prompt": prompt,
+            "source": "embedded",
+        }),
+        (None, None) => return Err(unknown_skill(request, skill_name)),
+    };
+
+    Ok(CommandSuccess {
+        data,
+        next: "swarm monitor --view progress".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn list_skill_prompts(
+    request: &ProtocolRequest,
+    skill: Option<&str>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let db: SwarmDb = db_from_request(request).await?;
+    let stored = db
+        .list_skill_prompts(&repo_id_from_request(request), skill)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    let embedded = crate::skill_prompts::EMBEDDED_SKILLS
+        .iter()
+        .filter(|name| skill.is_none_or(|wanted| wanted == **name))
+        .filter(|name| !stored.iter().any(|prompt| prompt.skill == **name))
+        .map(|name| json!({"skill": name, "source": "embedded", "version": Value::Null}));
+    let prompts = stored
+        .iter()
+        .map(|prompt| {
+            json!({
+                "skill": prompt.skill,
+                "source": "db",
+                "version": prompt.version,
+                "created_at": prompt.created_at,
+                "chars": prompt.body.chars().count(),
+            })
+        })
+        .chain(embedded)
+        .collect::<Vec<_>>();
+
+    Ok(CommandSuccess {
+        data: json!({"prompts": prompts}),
+        next: "swarm prompt --skill <skill>".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+async fn write_skill_prompt(
+    request: &ProtocolRequest,
+    input: &crate::PromptInput,
+    action: &str,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let skill = input.skill.as_deref().ok_or_else(|| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                format!("prompt {action} requires skill"),
+            )
+            .with_fix(format!(
+                "swarm prompt {action} --skill rust-contract --file prompts/rust-contract.md"
+            )),
+        )
+    })?;
+    let body = prompt_body(request, input, action).await?;
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![json!({"step": 1, "action": format!("{action}_skill_prompt"), "target": skill})],
+            "swarm prompt list",
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let version = if action == "add" {
+        db.add_skill_prompt(&repo_id, skill, &body)
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+            .ok_or_else(|| {
+                Box::new(
+                    ProtocolEnvelope::error(
+                        request.rid.clone(),
+                        code::EXISTS.to_string(),
+                        format!("Skill prompt already stored: {skill}"),
+                    )
+                    .with_fix(format!("swarm prompt update --skill {skill} --body '...'"))
+                    .with_ctx(json!({"skill": skill})),
+                )
+            })?
+    } else {
+        let known = crate::skill_prompts::get_skill_prompt(skill).is_some()
+            || db
+                .get_latest_skill_prompt(&repo_id, skill)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+                .is_some();
+        if !known {
+            return Err(unknown_skill(request, skill));
+        }
+        db.update_skill_prompt(&repo_id, skill, &body)
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+    };
+
+    Ok(CommandSuccess {
+        data: json!({"skill": skill, "version": version, "source": "db"}),
+        next: format!("swarm prompt --skill {skill}"),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Prompt text from `body`, or read from `file` like `spawn-prompts --template`.
+async fn prompt_body(
+    request: &ProtocolRequest,
+    input: &crate::PromptInput,
+    action: &str,
+) -> std::result::Result<String, Box<ProtocolEnvelope>> {
+    let body = match (input.body.as_deref(), input.file.as_deref()) {
+        (Some(body), None) => body.to_string(),
+        (None, Some(path)) => fs::read_to_string(path).await.map_err(|err| {
+            Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("Prompt file not found: {err}"),
+                )
+                .with_fix(format!("Ensure {path} exists"))
+                .with_ctx(json!({"file": path})),
+            )
+        })?,
+        _ => {
+            return Err(Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::INVALID.to_string(),
+                    format!("prompt {action} requires exactly one of body or file"),
+                )
+                .with_fix(format!(
+                    "swarm prompt {action} --skill <skill> --body '...' (or --file <path>)"
+                )),
+            ))
+        }
+    };
+
+    if body.trim().is_empty() {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                "Prompt body must not be empty".to_string(),
+            )
+            .with_fix(format!(
+                "swarm prompt {action} --skill <skill> --body '...'"
+            )),
+        ));
+    }
+    Ok(body)
+}
+
+fn unknown_skill(request: &ProtocolRequest, skill_name: &str) -> Box<ProtocolEnvelope> {
+    Box::new(
+        ProtocolEnvelope::error(
+            request.rid.clone(),
+            code::NOTFOUND.to_string(),
+            format!("Skill prompt not found: {skill_name}"),
+        )
+        .with_fix(format!(
+            "Use a valid skill: {}, or store one with prompt add",
+            crate::skill_prompts::EMBEDDED_SKILLS.join(", ")
+        ))
+        .with_ctx(json!({"skill": skill_name})),
+    )
+}
+
 pub(in crate::protocol_runtime) async fn handle_smoke(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
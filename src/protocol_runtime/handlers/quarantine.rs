@@ -0,0 +1,121 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use super::invalid_request;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::QuarantineSource;
+use crate::{code, SwarmDb};
+use serde_json::json;
+
+const MANUAL_QUARANTINE_REASON: &str = "quarantined by operator";
+
+pub(in crate::protocol_runtime) async fn handle_quarantine(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::QuarantineInput::parse_input(request).map_err(|error| {
+        invalid_request(
+            request,
+            error.to_string(),
+            Some("swarm quarantine --agent-id <id> --reason <text>"),
+        )
+    })?;
+    let reason = input
+        .reason
+        .unwrap_or_else(|| MANUAL_QUARANTINE_REASON.to_string());
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "quarantine_agent", "target": format!("agent:{}", input.agent_id), "reason": reason}),
+            ],
+            "swarm monitor --view health",
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let quarantined = db
+        .quarantine_agent(&repo_id, input.agent_id, QuarantineSource::Manual, &reason)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    if !quarantined {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::CONFLICT.to_string(),
+                format!("Agent {} is already quarantined", input.agent_id),
+            )
+            .with_fix(format!(
+                "swarm unquarantine --agent-id {} --reason <text>",
+                input.agent_id
+            ))
+            .with_ctx(json!({"agent_id": input.agent_id})),
+        ));
+    }
+
+    Ok(CommandSuccess {
+        data: json!({
+            "agent_id": input.agent_id,
+            "quarantined": true,
+            "source": QuarantineSource::Manual.as_str(),
+            "reason": reason,
+        }),
+        next: format!(
+            "swarm unquarantine --agent-id {} --reason <text>",
+            input.agent_id
+        ),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+pub(in crate::protocol_runtime) async fn handle_unquarantine(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::UnquarantineInput::parse_input(request).map_err(|error| {
+        invalid_request(
+            request,
+            error.to_string(),
+            Some("swarm unquarantine --agent-id <id> --reason <text>"),
+        )
+    })?;
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "release_quarantine", "target": format!("agent:{}", input.agent_id), "reason": input.reason}),
+            ],
+            "swarm monitor --view health",
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let released = db
+        .release_agent_quarantine(&repo_id, input.agent_id, &input.reason)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+        .ok_or_else(|| {
+            Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("Agent {} is not quarantined", input.agent_id),
+                )
+                .with_fix("swarm monitor --view health".to_string())
+                .with_ctx(json!({"agent_id": input.agent_id})),
+            )
+        })?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "agent_id": input.agent_id,
+            "quarantined": false,
+            "quarantine": released,
+        }),
+        next: "swarm monitor --view health".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
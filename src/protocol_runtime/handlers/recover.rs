@@ -0,0 +1,200 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ProtocolRequest,
+};
+use crate::escalations::{evaluate_escalations, EscalationEvaluation};
+use crate::orchestrator_service::emit_to_configured_sinks;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::quarantines::evaluate_quarantines;
+use crate::types::{QuarantinePolicy, RecoveryAction, DEFAULT_RECOVERY_SCAN_INTERVAL_MS};
+use crate::{OrchestratorEvent, RepoId, Result, SwarmDb};
+use serde_json::json;
+use std::time::Duration;
+
+/// Releases claims an interrupted run left behind, requeues their beads,
+/// and sweeps lapsed reservations and locks, listing each correction. Then
+/// evaluates the escalation rules against the beads agents still hold and
+/// quarantines agents the quarantine policy trips.
+pub(in crate::protocol_runtime) async fn handle_recover(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let repo_id = repo_id_from_request(request);
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "requeue_orphaned_claims", "target": repo_id.value()}),
+                json!({"step": 2, "action": "requeue_stranded_backlog", "target": repo_id.value()}),
+                json!({"step": 3, "action": "drop_expired_reservations", "target": repo_id.value()}),
+                json!({"step": 4, "action": "drop_expired_locks", "target": "resource_locks"}),
+                json!({"step": 5, "action": "evaluate_escalations", "target": repo_id.value()}),
+                json!({"step": 6, "action": "evaluate_quarantines", "target": repo_id.value()}),
+            ],
+            "swarm status",
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let actions = db
+        .recover_interrupted_runs(Some(&repo_id))
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    emit_claims_recovered(&db, &actions).await;
+    let escalations = evaluate_repo_escalations(&db, &repo_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let quarantines = evaluate_quarantines(&db, &repo_id, &QuarantinePolicy::default())
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "recovered": actions.len(),
+            "actions": actions,
+            "escalations": {
+                "open": escalations.open.len(),
+                "raised": escalations.raised,
+                "resolved": escalations.resolved,
+                "unassigned": escalations.unassigned,
+                "webhook_errors": escalations.webhook_errors,
+                "notification_errors": escalations.notification_errors,
+            },
+            "quarantines": {
+                "active": quarantines.quarantines.len(),
+                "newly_quarantined": quarantines.newly_quarantined,
+            },
+        }),
+        next: "swarm status".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Runs the recovery pass across every repo when a protocol session starts,
+/// logging each correction. A failure is logged and does not stop the
+/// session.
+pub(in crate::protocol_runtime) async fn recover_on_startup(db: &SwarmDb) {
+    log_recovery_pass(db, "Startup").await;
+}
+
+/// Reruns the recovery pass for as long as the session lasts, waiting the
+/// configured `recovery_scan_interval_ms` between passes. The interval is
+/// read again before each wait, so `config set` applies without a restart.
+pub(in crate::protocol_runtime) async fn run_recovery_scans(db: SwarmDb) {
+    loop {
+        let interval_ms = match db.recovery_scan_interval_ms().await {
+            Ok(interval_ms) => interval_ms,
+            Err(error) => {
+                tracing::warn!("Reading recovery scan interval failed: {error}");
+                DEFAULT_RECOVERY_SCAN_INTERVAL_MS
+            }
+        };
+        tokio::time::sleep(Duration::from_millis(u64::from(interval_ms))).await;
+        log_recovery_pass(&db, "Periodic").await;
+    }
+}
+
+async fn log_recovery_pass(db: &SwarmDb, pass: &str) {
+    match db.recover_interrupted_runs(None).await {
+        Ok(actions) => {
+            for action in &actions {
+                tracing::info!(
+                    "{pass} recovery: {}",
+                    serde_json::to_string(action).unwrap_or_default()
+                );
+            }
+            emit_claims_recovered(db, &actions).await;
+        }
+        Err(error) => tracing::warn!("{pass} recovery failed: {error}"),
+    }
+    log_escalation_pass(db, pass).await;
+    log_quarantine_pass(db, pass).await;
+}
+
+/// Evaluates the escalation rules for every repo with a held bead or an open
+/// escalation, logging what was raised and resolved.
+async fn log_escalation_pass(db: &SwarmDb, pass: &str) {
+    let repo_ids = match db.get_escalation_repo_ids().await {
+        Ok(repo_ids) => repo_ids,
+        Err(error) => {
+            tracing::warn!("{pass} escalation pass failed: {error}");
+            return;
+        }
+    };
+    for repo_id in &repo_ids {
+        match evaluate_repo_escalations(db, repo_id).await {
+            Ok(evaluation) => {
+                for escalation in evaluation.raised.iter().chain(&evaluation.resolved) {
+                    tracing::info!(
+                        "{pass} escalation: {}",
+                        serde_json::to_string(escalation).unwrap_or_default()
+                    );
+                }
+                for error in evaluation
+                    .webhook_errors
+                    .iter()
+                    .chain(&evaluation.notification_errors)
+                {
+                    tracing::warn!("{pass} escalation delivery failed: {error}");
+                }
+            }
+            Err(error) => {
+                tracing::warn!(
+                    "{pass} escalation pass for {} failed: {error}",
+                    repo_id.value()
+                );
+            }
+        }
+    }
+}
+
+/// Checks the quarantine policy for every repo with a registered agent,
+/// logging each agent it quarantines.
+async fn log_quarantine_pass(db: &SwarmDb, pass: &str) {
+    let repo_ids = match db.get_agent_repo_ids().await {
+        Ok(repo_ids) => repo_ids,
+        Err(error) => {
+            tracing::warn!("{pass} quarantine pass failed: {error}");
+            return;
+        }
+    };
+    let policy = QuarantinePolicy::default();
+    for repo_id in &repo_ids {
+        match evaluate_quarantines(db, repo_id, &policy).await {
+            Ok(evaluation) => {
+                for quarantine in evaluation
+                    .quarantines
+                    .iter()
+                    .filter(|q| evaluation.newly_quarantined.contains(&q.agent_id))
+                {
+                    tracing::info!(
+                        "{pass} quarantine: {}",
+                        serde_json::to_string(quarantine).unwrap_or_default()
+                    );
+                }
+            }
+            Err(error) => {
+                tracing::warn!(
+                    "{pass} quarantine pass for {} failed: {error}",
+                    repo_id.value()
+                );
+            }
+        }
+    }
+}
+
+/// Evaluates `SWARM_ESCALATION_RULES` for `repo_id`: raises escalations,
+/// hands their beads to senior agents, and resolves the ones that cleared.
+async fn evaluate_repo_escalations(db: &SwarmDb, repo_id: &RepoId) -> Result<EscalationEvaluation> {
+    let rules = crate::config::escalation_rules_from_env()?;
+    let notifications = crate::config::notification_rules_from_env()?;
+    evaluate_escalations(db, repo_id, &rules, &notifications).await
+}
+
+/// Sends a `claim_recovered` event per repo whose claims were requeued to
+/// the sinks in `SWARM_EVENT_SINKS`.
+async fn emit_claims_recovered(db: &SwarmDb, actions: &[RecoveryAction]) {
+    for (repo_id, event) in OrchestratorEvent::claims_recovered(actions) {
+        emit_to_configured_sinks(db, &repo_id, event).await;
+    }
+}
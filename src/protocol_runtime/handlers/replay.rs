@@ -0,0 +1,246 @@
+use super::super::trace::{
+    comparable_outcome, diff_values, parse_trace, TraceEntry, MAX_TRACE_DIFFS_PER_ENTRY,
+};
+use super::super::{
+    dry_flag, execute_request, minimal_state_for_request, read_db_from_request,
+    repo_id_from_request, to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::BeadReplay;
+use crate::{code, ReplayTraceInput, SwarmDb, SwarmError};
+use serde_json::{json, Map, Value};
+use std::future::Future;
+use std::pin::Pin;
+
+/// Args of the `replay` request that replace each traced request's own, so
+/// a trace can run against another database or tenant.
+const CONNECTION_ARGS: &[&str] = &[
+    "database_url",
+    "connect_timeout_ms",
+    "tenant",
+    "repo_id",
+    "sslmode",
+    "ssl_root_cert",
+    "statement_timeout_ms",
+];
+
+/// Rebuilds a bead's lifecycle from its `transition_events`, or with `file`
+/// replays a recorded request trace.
+pub(in crate::protocol_runtime) async fn handle_replay(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    if request.args.contains_key("file") {
+        return replay_trace(request).await;
+    }
+    let input = crate::ReplayInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm replay --bead-id <bead-id>".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let events = db
+        .get_transition_events(&repo_id_from_request(request), &input.bead_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    if events.is_empty() {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::NOTFOUND.to_string(),
+                format!("No transition events for bead {}", input.bead_id),
+            )
+            .with_fix(format!(
+                "swarm monitor --view events --bead-id {}",
+                input.bead_id
+            ))
+            .with_ctx(json!({"bead_id": input.bead_id})),
+        ));
+    }
+
+    let replay = BeadReplay::from_events(&input.bead_id, events);
+    let next = if replay.anomalies.is_empty() {
+        format!("swarm artifacts --bead-id {}", input.bead_id)
+    } else {
+        format!("swarm monitor --view events --bead-id {}", input.bead_id)
+    };
+    Ok(CommandSuccess {
+        data: json!(replay),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Runs each traced request again, in order, and diffs its `ok`, error code
+/// and `d` against the recorded envelope. A dry replay runs every request
+/// dry and, for requests recorded live, compares only `ok` and the code.
+async fn replay_trace(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = ReplayTraceInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm replay --file trace.ndjson --dry".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let text = tokio::fs::read_to_string(&input.file)
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("Trace file {} not found", input.file),
+                )
+                .with_fix("Record one with: swarm --record trace.ndjson".to_string())
+                .with_ctx(json!({"file": input.file})),
+            ),
+            _ => to_protocol_failure(SwarmError::IoError(e), request.rid.clone()),
+        })?;
+    let entries = parse_trace(&text).map_err(|(line, error)| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                format!("Trace line {line} is not a trace entry: {error}"),
+            )
+            .with_fix(
+                "Each line must be {\"ts\", \"request\", \"envelope\"} as written by --record"
+                    .to_string(),
+            )
+            .with_ctx(json!({"file": input.file, "line": line})),
+        )
+    })?;
+
+    let dry = dry_flag(request);
+    let overrides = CONNECTION_ARGS
+        .iter()
+        .filter_map(|key| {
+            request
+                .args
+                .get(*key)
+                .map(|value| ((*key).to_string(), value.clone()))
+        })
+        .collect::<Map<_, _>>();
+    let total = entries.len();
+    let mut matched = 0_usize;
+    let mut skipped = 0_usize;
+    let mut divergences = Vec::new();
+    for (line, entry) in entries {
+        match replay_entry(line, entry, dry, &overrides, &input.ignore).await {
+            ReplayedEntry::Matched => matched += 1,
+            ReplayedEntry::Skipped(detail) => {
+                skipped += 1;
+                divergences.push(detail);
+            }
+            ReplayedEntry::Diverged(detail) => divergences.push(detail),
+        }
+    }
+
+    let diverged = total - matched - skipped;
+    Ok(CommandSuccess {
+        data: json!({
+            "file": input.file,
+            "mode": if dry { "dry" } else { "live" },
+            "total": total,
+            "matched": matched,
+            "diverged": diverged,
+            "skipped": skipped,
+            "divergences": divergences,
+        }),
+        next: if diverged == 0 {
+            "swarm history".to_string()
+        } else {
+            format!("swarm replay --file {} --ignore <key,...>", input.file)
+        },
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+enum ReplayedEntry {
+    Matched,
+    Diverged(Value),
+    Skipped(Value),
+}
+
+async fn replay_entry(
+    line: usize,
+    entry: TraceEntry,
+    dry: bool,
+    overrides: &Map<String, Value>,
+    ignore: &[String],
+) -> ReplayedEntry {
+    let traced_cmd = entry.request.get("cmd").cloned();
+    let skipped = |reason: &str| {
+        ReplayedEntry::Skipped(json!({
+            "line": line,
+            "cmd": traced_cmd,
+            "skipped": reason,
+        }))
+    };
+    let Ok(mut traced) = serde_json::from_value::<ProtocolRequest>(entry.request) else {
+        return skipped("not a request; it was rejected before dispatch");
+    };
+    if traced.cmd == "replay" && traced.args.contains_key("file") {
+        return skipped("replaying a trace from a trace is not supported");
+    }
+    let recorded_dry = traced.dry.unwrap_or(false);
+    if dry {
+        traced.dry = Some(true);
+    }
+    traced.args.extend(overrides.clone());
+    let cmd = traced.cmd.clone();
+    let rid = traced.rid.clone();
+
+    let envelope = match execute_boxed(traced).await {
+        Ok(success) => ProtocolEnvelope::success(rid.clone(), success.data),
+        Err(failure) => *failure,
+    };
+    let recorded = comparable_outcome(entry.envelope);
+    let replayed = comparable_outcome(envelope);
+    let compared = if dry && !recorded_dry {
+        "status"
+    } else {
+        "full"
+    };
+    let diffs = if compared == "status" {
+        diff_values(
+            &json!({"ok": recorded["ok"], "code": recorded["code"]}),
+            &json!({"ok": replayed["ok"], "code": replayed["code"]}),
+            ignore,
+            MAX_TRACE_DIFFS_PER_ENTRY,
+        )
+    } else {
+        diff_values(&recorded, &replayed, ignore, MAX_TRACE_DIFFS_PER_ENTRY)
+    };
+    if diffs.is_empty() {
+        return ReplayedEntry::Matched;
+    }
+    ReplayedEntry::Diverged(json!({
+        "line": line,
+        "cmd": cmd,
+        "rid": rid,
+        "compared": compared,
+        "diffs": diffs,
+    }))
+}
+
+/// Boxed so a traced request can itself be a `replay` without the handler's
+/// future containing itself.
+fn execute_boxed(
+    request: ProtocolRequest,
+) -> Pin<Box<dyn Future<Output = std::result::Result<CommandSuccess, Box<ProtocolEnvelope>>> + Send>>
+{
+    Box::pin(execute_request(request))
+}
@@ -0,0 +1,99 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::code;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{AgentId, BeadId, BudgetLimit, TokenUsage};
+use serde_json::{json, Value};
+
+/// Records the tokens one LLM call spent and checks the bead's budget, so
+/// the agent learns how much it has left in the same round trip.
+pub(in crate::protocol_runtime) async fn handle_report_usage(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::ReportUsageInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(
+                "swarm report-usage --agent-id 1 --bead-id <bead> --model <model> --prompt-tokens 0 --completion-tokens 0"
+                    .to_string(),
+            )
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "record_token_usage", "target": input.bead_id}),
+                json!({"step": 2, "action": "check_budget", "target": input.bead_id}),
+            ],
+            "swarm costs",
+        ));
+    }
+
+    let repo_id = repo_id_from_request(request);
+    let agent_id = AgentId::new(repo_id.clone(), input.agent_id);
+    let bead_id = BeadId::new(input.bead_id.clone());
+    let usage = TokenUsage::new(input.prompt_tokens, input.completion_tokens);
+    let db = db_from_request(request).await?;
+    let usage_id = db
+        .record_token_usage(
+            &agent_id,
+            Some(&bead_id),
+            input.stage,
+            Some(&input.model),
+            usage,
+        )
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let (priority, bead_usage) = db
+        .get_bead_budget_usage(&repo_id, &bead_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    let limit = BudgetLimit::for_priority(priority.as_deref());
+    let exceeded = limit.is_exceeded(&bead_usage);
+    let remaining = limit.remaining(&bead_usage);
+
+    let mut state = minimal_state_for_request(request).await;
+    if let Value::Object(fields) = &mut state {
+        fields.insert(
+            "budget".to_string(),
+            json!({
+                "bead_id": input.bead_id,
+                "remaining": remaining,
+                "exceeded": exceeded,
+            }),
+        );
+    }
+
+    let next = if exceeded {
+        format!("swarm cancel --bead-id {} --reason budget", input.bead_id)
+    } else {
+        "swarm costs".to_string()
+    };
+
+    Ok(CommandSuccess {
+        data: json!({
+            "id": usage_id,
+            "agent_id": input.agent_id,
+            "bead_id": input.bead_id,
+            "stage": input.stage.map(|stage| stage.as_str()),
+            "model": input.model,
+            "usage": usage,
+            "bead_usage": bead_usage,
+            "limit": limit,
+            "remaining": remaining,
+            "exceeded": exceeded,
+        }),
+        next,
+        state,
+    })
+}
@@ -0,0 +1,158 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use super::invalid_request;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{AgentId, BeadId, DEFAULT_RESERVATION_TTL_SECS};
+use crate::{code, SwarmDb};
+use serde_json::json;
+
+const RESERVE_FIX: &str =
+    "swarm claim-next --reserve --agent-id <id> [--ttl-secs <secs>] [--label <label>]";
+const DECISION_FIX: &str =
+    "swarm accept-claim --agent-id <id> --bead-id <bead-id> | swarm reject-claim --agent-id <id> --bead-id <bead-id> [--reason <text>]";
+
+/// `claim-next --reserve`: holds the top pending bead for the agent to
+/// inspect, without claiming it. `--label` overrides the agent's default
+/// labels for this pick.
+pub(in crate::protocol_runtime) async fn handle_reserve(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::ReserveClaimInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(RESERVE_FIX)))?;
+    let ttl_secs = input.ttl_secs.unwrap_or(DEFAULT_RESERVATION_TTL_SECS);
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "reserve_bead", "target": format!("agent:{}", input.agent_id), "ttl_secs": ttl_secs, "labels": input.labels}),
+            ],
+            &format!(
+                "swarm accept-claim --agent-id {} --bead-id <bead-id>",
+                input.agent_id
+            ),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let reservation = db
+        .reserve_next_bead(
+            &AgentId::new(repo_id_from_request(request), input.agent_id),
+            ttl_secs,
+            input.labels.as_deref(),
+        )
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    let next = reservation.as_ref().map_or_else(
+        || "swarm sync-backlog".to_string(),
+        |reservation| {
+            format!(
+                "br show {} then swarm accept-claim --agent-id {} --bead-id {}",
+                reservation.bead_id.value(),
+                input.agent_id,
+                reservation.bead_id.value()
+            )
+        },
+    );
+    Ok(CommandSuccess {
+        data: json!({
+            "reservation": reservation,
+            "ttl_secs": ttl_secs,
+            "labels": input.labels,
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Turns a live reservation into a claim.
+pub(in crate::protocol_runtime) async fn handle_accept_claim(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::ClaimDecisionInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(DECISION_FIX)))?;
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "accept_reservation", "target": format!("agent:{}, bead:{}", input.agent_id, input.bead_id)}),
+            ],
+            &format!("swarm agent --id {}", input.agent_id),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    db.accept_reservation(
+        &AgentId::new(repo_id_from_request(request), input.agent_id),
+        &BeadId::new(input.bead_id.clone()),
+    )
+    .await
+    .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "bead_id": input.bead_id,
+            "agent_id": input.agent_id,
+            "claimed": true,
+        }),
+        next: format!("swarm agent --id {}", input.agent_id),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Gives a reserved bead back to the pool before its reservation expires.
+pub(in crate::protocol_runtime) async fn handle_reject_claim(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::ClaimDecisionInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(DECISION_FIX)))?;
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "reject_reservation", "target": format!("agent:{}, bead:{}", input.agent_id, input.bead_id), "reason": input.reason}),
+            ],
+            &format!("swarm claim-next --reserve --agent-id {}", input.agent_id),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let rejected = db
+        .reject_reservation(
+            &AgentId::new(repo_id_from_request(request), input.agent_id),
+            &BeadId::new(input.bead_id.clone()),
+            input.reason.as_deref(),
+        )
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    if !rejected {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::NOTFOUND.to_string(),
+                format!(
+                    "Agent {} holds no reservation on {}",
+                    input.agent_id, input.bead_id
+                ),
+            )
+            .with_fix(RESERVE_FIX.to_string())
+            .with_ctx(json!({"agent_id": input.agent_id, "bead_id": input.bead_id})),
+        ));
+    }
+
+    Ok(CommandSuccess {
+        data: json!({
+            "bead_id": input.bead_id,
+            "agent_id": input.agent_id,
+            "rejected": true,
+            "reason": input.reason,
+        }),
+        next: format!("swarm claim-next --reserve --agent-id {}", input.agent_id),
+        state: minimal_state_for_request(request).await,
+    })
+}
@@ -1,15 +1,15 @@
 use super::super::{
-    db_from_request, minimal_state_for_request, repo_id_from_request, to_protocol_failure,
-    CommandSuccess, ProtocolRequest,
+    minimal_state_for_request, read_db_from_request, repo_id_from_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
 };
 use crate::protocol_envelope::ProtocolEnvelope;
-use crate::{code, ResumeContextContract, SwarmDb};
+use crate::{code, BeadId, ContextBudget, ResumeContextContract, SwarmDb};
 use serde_json::json;
 
 pub(in crate::protocol_runtime) async fn handle_resume(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
-    let db: SwarmDb = db_from_request(request).await?;
+    let db: SwarmDb = read_db_from_request(request).await?;
     let repo_id = repo_id_from_request(request);
     let contexts = db
         .get_resume_context_projections(&repo_id)
@@ -33,20 +33,18 @@ pub(in crate::protocol_runtime) async fn handle_resume_context(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
     let bead_filter = parse_resume_context_bead_filter(request)?;
+    let budget = context_budget_from_request(request)?;
 
-    let db: SwarmDb = db_from_request(request).await?;
+    let db: SwarmDb = read_db_from_request(request).await?;
     let repo_id = repo_id_from_request(request);
+    let bead_id = bead_filter.clone().map(BeadId::new);
     let contexts = db
-        .get_deep_resume_contexts(&repo_id)
+        .get_deep_resume_contexts(&repo_id, bead_id.as_ref(), budget)
         .await
         .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
 
-    let selected = if let Some(ref bead_id) = bead_filter {
-        let filtered = contexts
-            .into_iter()
-            .filter(|context| context.bead_id == *bead_id)
-            .collect::<Vec<_>>();
-        if filtered.is_empty() {
+    if let Some(ref bead_id) = bead_filter {
+        if contexts.is_empty() {
             return Err(Box::new(
                 ProtocolEnvelope::error(
                     request.rid.clone(),
@@ -57,18 +55,40 @@ pub(in crate::protocol_runtime) async fn handle_resume_context(
                 .with_ctx(json!({"bead_id": bead_id})),
             ));
         }
-        filtered
-    } else {
-        contexts
-    };
+    }
 
     Ok(CommandSuccess {
-        data: json!({"contexts": selected}),
+        data: json!({"contexts": contexts}),
         next: "swarm monitor --view failures".to_string(),
         state: minimal_state_for_request(request).await,
     })
 }
 
+/// `--max-bytes`/`--max-tokens` when either is given, otherwise the
+/// `SWARM_CONTEXT_MAX_*` environment defaults.
+pub(super) fn context_budget_from_request(
+    request: &ProtocolRequest,
+) -> std::result::Result<ContextBudget, Box<ProtocolEnvelope>> {
+    let input = crate::ContextBudgetInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(
+                "Use --max-bytes <n> or --max-tokens <n> with a non-negative integer".to_string(),
+            )
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    if input.max_bytes.is_none() && input.max_tokens.is_none() {
+        return Ok(crate::config::context_budget_from_env());
+    }
+    Ok(ContextBudget::new(input.max_bytes, input.max_tokens))
+}
+
 fn parse_resume_context_bead_filter(
     request: &ProtocolRequest,
 ) -> std::result::Result<Option<String>, Box<ProtocolEnvelope>> {
@@ -0,0 +1,139 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{RepoId, ReviewTally};
+use crate::{code, SwarmDb};
+use serde_json::json;
+
+const REVIEW_FIX: &str =
+    "swarm review submit --bead-id <bead> --verdict approve|changes --agent-id <id>";
+
+/// Records or lists approve/changes verdicts on a bead.
+pub(in crate::protocol_runtime) async fn handle_review(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::ReviewInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(REVIEW_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let bead_id = input.bead_id.as_str();
+
+    if dry_flag(request) {
+        let target = format!("bead:{bead_id}");
+        let step = if input.action == "submit" {
+            "submit_review"
+        } else {
+            "list_bead_reviews"
+        };
+        return Ok(dry_run_success(
+            request,
+            vec![json!({"step": 1, "action": step, "target": target})],
+            &format!("swarm review {} --bead-id {bead_id}", input.action),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let submitted = match (input.action.as_str(), input.verdict) {
+        ("submit", Some(verdict)) => {
+            let reviewer = authorized_reviewer(request, &db, &repo_id, bead_id, &input).await?;
+            Some(
+                db.submit_review(
+                    &repo_id,
+                    bead_id,
+                    &reviewer,
+                    input.agent_id,
+                    verdict,
+                    input.comments.as_deref(),
+                )
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?,
+            )
+        }
+        _ => None,
+    };
+    let reviews = db
+        .list_bead_reviews(&repo_id, bead_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let tally = ReviewTally::from_reviews(&reviews);
+
+    let (data, next) = match submitted {
+        Some(review) => (
+            json!({"bead_id": bead_id, "review": review, "tally": tally}),
+            format!("swarm review list --bead-id {bead_id}"),
+        ),
+        None => (
+            json!({"bead_id": bead_id, "reviews": reviews, "tally": tally}),
+            format!("swarm review submit --bead-id {bead_id} --verdict approve"),
+        ),
+    };
+
+    Ok(CommandSuccess {
+        data,
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Reviewer name for a submit: `agent:<id>` for an agent, which may not be
+/// the one holding the bead's claim, or a `reviewer` listed in
+/// `SWARM_HUMAN_REVIEWERS`.
+async fn authorized_reviewer(
+    request: &ProtocolRequest,
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    bead_id: &str,
+    input: &crate::ReviewInput,
+) -> std::result::Result<String, Box<ProtocolEnvelope>> {
+    let unauthorized = |message: String, fix: &str, ctx: serde_json::Value| {
+        Box::new(
+            ProtocolEnvelope::error(request.rid.clone(), code::UNAUTHORIZED.to_string(), message)
+                .with_fix(fix.to_string())
+                .with_ctx(ctx),
+        )
+    };
+    match (input.agent_id, input.reviewer.as_deref()) {
+        (Some(agent_id), _) => {
+            let holder = db
+                .bead_claim_holder(repo_id, bead_id)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            if holder == Some(agent_id) {
+                return Err(unauthorized(
+                    format!("Agent {agent_id} holds the claim on {bead_id} and cannot review it"),
+                    "Submit the review from an agent that is not working on the bead",
+                    json!({"bead_id": bead_id, "agent_id": agent_id}),
+                ));
+            }
+            Ok(format!("agent:{agent_id}"))
+        }
+        (None, Some(reviewer)) => {
+            if !crate::config::human_reviewers_from_env()
+                .iter()
+                .any(|allowed| allowed == reviewer)
+            {
+                return Err(unauthorized(
+                    format!("{reviewer} is not an allowed human reviewer"),
+                    "Add the reviewer to SWARM_HUMAN_REVIEWERS, or submit with --agent-id",
+                    json!({"reviewer": reviewer}),
+                ));
+            }
+            Ok(reviewer.to_string())
+        }
+        (None, None) => Err(unauthorized(
+            "Review needs agent_id or reviewer".to_string(),
+            REVIEW_FIX,
+            json!({"bead_id": bead_id}),
+        )),
+    }
+}
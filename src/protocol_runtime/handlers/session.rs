@@ -0,0 +1,116 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::{code, SwarmDb};
+use serde_json::json;
+
+const SESSION_FIX: &str = "swarm session start|end|show --agent-id <id>";
+
+/// Records which process is acting as an agent, so claims and events can be
+/// traced to a host and pid.
+pub(in crate::protocol_runtime) async fn handle_session(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::SessionInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(SESSION_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let agent_id = input.agent_id;
+
+    if dry_flag(request) {
+        let target = format!("agent:{agent_id}");
+        let steps = match input.action.as_str() {
+            "start" => vec![
+                json!({"step": 1, "action": "end_open_session", "target": target}),
+                json!({"step": 2, "action": "start_session", "target": target}),
+            ],
+            "end" => vec![json!({"step": 1, "action": "end_session", "target": target})],
+            _ => vec![json!({"step": 1, "action": "get_active_session", "target": target})],
+        };
+        return Ok(dry_run_success(
+            request,
+            steps,
+            &format!("swarm session {} --agent-id {agent_id}", input.action),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let data = match input.action.as_str() {
+        "start" => {
+            let host = input.host.clone().unwrap_or_else(local_hostname);
+            let pid = input.pid.unwrap_or_else(caller_pid);
+            let version = input
+                .version
+                .clone()
+                .unwrap_or_else(|| env!("CARGO_PKG_VERSION").to_string());
+            let (session, replaced) = db
+                .start_agent_session(&repo_id, agent_id, &host, pid, &version)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            json!({"agent_id": agent_id, "session": session, "replaced": replaced})
+        }
+        "end" => {
+            let ended = db
+                .end_agent_session(&repo_id, agent_id)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+                .ok_or_else(|| {
+                    Box::new(
+                        ProtocolEnvelope::error(
+                            request.rid.clone(),
+                            code::NOTFOUND.to_string(),
+                            format!("Agent {agent_id} has no open session"),
+                        )
+                        .with_fix(format!("swarm session start --agent-id {agent_id}"))
+                        .with_ctx(json!({"agent_id": agent_id})),
+                    )
+                })?;
+            json!({"agent_id": agent_id, "session": ended})
+        }
+        _ => {
+            let session = db
+                .get_active_session(&repo_id, agent_id)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            json!({"agent_id": agent_id, "session": session})
+        }
+    };
+
+    Ok(CommandSuccess {
+        data,
+        next: "swarm monitor --view sessions".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// `HOSTNAME`, else `/etc/hostname`, else `unknown`.
+fn local_hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// The process that ran `swarm`: the agent, whether it spawned a one-shot
+/// command or is holding a protocol session open.
+#[cfg(unix)]
+fn caller_pid() -> u32 {
+    std::os::unix::process::parent_id()
+}
+
+#[cfg(not(unix))]
+fn caller_pid() -> u32 {
+    std::process::id()
+}
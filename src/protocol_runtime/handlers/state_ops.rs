@@ -2,8 +2,10 @@
 
 use super::super::{
     bounded_history_limit, db_from_request, minimal_state_for_request, minimal_state_from_progress,
-    repo_id_from_request, CommandSuccess, ParseInput, ProtocolRequest,
+    next_page_cursor, read_db_from_request, repo_id_from_request, CommandSuccess, ParseInput,
+    ProtocolRequest,
 };
+use crate::db::swarm_db::CommandHistoryQuery;
 use crate::protocol_envelope::ProtocolEnvelope;
 use crate::{code, HistoryInput, SwarmError};
 use serde_json::{json, Value};
@@ -92,13 +94,23 @@ pub(in crate::protocol_runtime) async fn handle_history(
         )
     })?;
 
-    let requested_limit = input.limit;
+    let requested_limit = input.page_size.or(input.limit);
     let limit = bounded_history_limit(requested_limit);
-    let db = db_from_request(request).await?;
+    let db = read_db_from_request(request).await?;
     let actions = db
-        .get_command_history(limit)
+        .get_command_history_page(&CommandHistoryQuery {
+            after_seq: input.after_seq,
+            limit,
+            since: input.since,
+            until: input.until,
+        })
         .await
         .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let next_cursor = next_page_cursor(
+        actions.last().map(|(seq, _, _, _, _, _, _)| *seq),
+        actions.len(),
+        limit,
+    );
 
     let total = actions.len() as i64;
     let success = actions.iter().filter(|(_, _, _, _, ok, _, _)| *ok).count() as f64;
@@ -151,6 +163,9 @@ pub(in crate::protocol_runtime) async fn handle_history(
             "actions": actions_json,
             "requested_limit": requested_limit,
             "effective_limit": limit,
+            "next_cursor": next_cursor,
+            "since": input.since,
+            "until": input.until,
             "total": total,
             "aggregates": aggregates,
         }),
@@ -1,12 +1,17 @@
 #![allow(clippy::too_many_lines)]
 
+use super::super::db_resolution::{connect_options_for_request, db_scope_for_request};
 use super::super::{
-    dry_flag, dry_run_success, handle_register, load_schema_sql, mask_database_url,
-    minimal_state_for_request, resolve_database_url_for_init, CommandSuccess, ParseInput,
-    ProtocolRequest, EMBEDDED_COORDINATOR_SCHEMA_REF,
+    dry_flag, dry_run_plan, dry_run_success, handle_register, load_schema_sql, mask_database_url,
+    minimal_state_for_request, resolve_database_url_for_init, state_unavailable, CommandSuccess,
+    ParseInput, ProtocolRequest, DEFAULT_LOCAL_DB_CONTAINER, DEFAULT_LOCAL_DB_NAME,
+    DEFAULT_LOCAL_DB_PORT, DEFAULT_LOCAL_DB_POSTGRES_MAJOR, DEFAULT_LOCAL_DB_USER,
+    EMBEDDED_COORDINATOR_SCHEMA_REF,
 };
+use super::localdb::{self, LocalDb};
 use crate::protocol_envelope::ProtocolEnvelope;
-use crate::{code, SwarmDb, SwarmError};
+use crate::types::{DryRunConflict, DryRunConflictKind, SeedPlan};
+use crate::{code, RepoId, SwarmDb, SwarmError};
 use serde_json::{json, Map, Value};
 use std::path::PathBuf;
 use tokio::fs;
@@ -86,6 +91,11 @@ pub(in crate::protocol_runtime) async fn handle_init(
         .get("schema")
         .and_then(Value::as_str)
         .map(std::string::ToString::to_string);
+    let rollback_on_error = request
+        .args
+        .get("rollback_on_error")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
 
     if dry_flag(request) {
         return Ok(dry_run_success(
@@ -99,6 +109,10 @@ pub(in crate::protocol_runtime) async fn handle_init(
         ));
     }
 
+    if rollback_on_error {
+        return handle_init_atomically(request, schema, seed_agents).await;
+    }
+
     let mut steps = Vec::new();
     let mut errors = Vec::new();
 
@@ -176,6 +190,122 @@ pub(in crate::protocol_runtime) async fn handle_init(
     }
 }
 
+/// `init --rollback-on-error`: the database steps run in one transaction and
+/// the files bootstrap created are removed again when a later step fails, so
+/// a failed init leaves nothing behind.
+async fn handle_init_atomically(
+    request: &ProtocolRequest,
+    schema: Option<String>,
+    seed_agents: u32,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let repo_root = current_repo_root().await?;
+    let repo_id = RepoId::from_current_dir().ok_or_else(|| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                "Not in a git repository".to_string(),
+            )
+            .with_fix("Run command from a git repository root".to_string()),
+        )
+    })?;
+    let swarm_dir = repo_root.join(".swarm");
+    let absent_before = [
+        swarm_dir.clone(),
+        swarm_dir.join("config.toml"),
+        swarm_dir.join(".swarmignore"),
+    ]
+    .into_iter()
+    .filter(|path| !path.exists())
+    .collect::<Vec<_>>();
+
+    let outcome = match handle_bootstrap(request).await {
+        Ok(bootstrap) => init_database_atomically(request, schema, seed_agents, &repo_id)
+            .await
+            .map(|database| (bootstrap.data, database)),
+        Err(failure) => Err(failure),
+    };
+
+    match outcome {
+        Ok((bootstrap, (database_url, schema_ref, seed_plan))) => Ok(CommandSuccess {
+            data: json!({
+                "initialized": true,
+                "rollback_on_error": true,
+                "steps": [
+                    {"step": 1, "action": "bootstrap", "status": "ok", "d": bootstrap},
+                    {"step": 2, "action": "init_db", "status": "ok", "d": {"database_url": database_url, "schema": schema_ref, "seed": seed_plan}},
+                    {"step": 3, "action": "register", "status": "ok", "d": {"repo": repo_id.value(), "count": seed_agents}},
+                ],
+                "database_url": database_url,
+                "seed_agents": seed_agents,
+            }),
+            next: "swarm doctor".to_string(),
+            state: minimal_state_for_request(request).await,
+        }),
+        Err(failure) => {
+            let removed = remove_created_paths(&absent_before).await;
+            let (error_code, msg) = failure.err.as_ref().map_or_else(
+                || (code::INTERNAL.to_string(), "Init failed".to_string()),
+                |err| (err.code.clone(), err.msg.clone()),
+            );
+            Err(Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    error_code,
+                    format!("Init failed and was rolled back: {msg}"),
+                )
+                .with_fix(failure.fix.clone().unwrap_or_else(|| {
+                    "Fix the cause and run `swarm init --rollback-on-error` again".to_string()
+                }))
+                .with_ctx(json!({
+                    "error": failure.err,
+                    "rolled_back": {"database": true, "removed_files": removed},
+                })),
+            ))
+        }
+    }
+}
+
+/// Runs the `init-db` and `register` steps of `init` in one transaction.
+async fn init_database_atomically(
+    request: &ProtocolRequest,
+    schema: Option<String>,
+    seed_agents: u32,
+    repo_id: &RepoId,
+) -> std::result::Result<(String, String, SeedPlan), Box<ProtocolEnvelope>> {
+    let url = resolve_database_url_for_init(request).await?;
+    let scope = db_scope_for_request(request)?;
+    let (schema_sql, schema_ref) = load_schema_sql(request.rid.clone(), schema.as_deref()).await?;
+    let options = connect_options_for_request(request)?;
+    let db = SwarmDb::new_with_options(&url, scope.schema(), None, &options)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let seed_plan = db
+        .initialize_swarm(&schema_sql, seed_agents, repo_id, seed_agents)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    Ok((mask_database_url(&url), schema_ref, seed_plan))
+}
+
+/// Removes the paths in `created` that exist now, files before the
+/// directory holding them; a directory is only removed once empty.
+async fn remove_created_paths(created: &[PathBuf]) -> Vec<String> {
+    let mut removed = Vec::new();
+    for path in created.iter().rev() {
+        let result = if path.is_dir() {
+            fs::remove_dir(path).await
+        } else if path.exists() {
+            fs::remove_file(path).await
+        } else {
+            continue;
+        };
+        if result.is_ok() {
+            removed.push(path.display().to_string());
+        }
+    }
+    removed
+}
+
 pub(in crate::protocol_runtime) async fn handle_init_db(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
@@ -199,25 +329,18 @@ pub(in crate::protocol_runtime) async fn handle_init_db(
     let seed_agents = input.seed_agents.map_or(12, |value| value);
 
     if dry_flag(request) {
-        let dry_database_target = input.url.as_deref().map_or_else(
-            || "auto-discover-on-execution".to_string(),
-            mask_database_url,
-        );
-        return Ok(dry_run_success(
-            request,
-            vec![
-                json!({"step": 1, "action": "connect_db", "target": dry_database_target}),
-                json!({"step": 2, "action": "apply_schema", "target": schema.clone().unwrap_or_else(|| EMBEDDED_COORDINATOR_SCHEMA_REF.to_string())}),
-                json!({"step": 3, "action": "seed_agents", "target": seed_agents}),
-            ],
-            "swarm state",
-        ));
+        return init_db_dry_run(request, schema.as_deref(), seed_agents).await;
     }
 
     let url = resolve_database_url_for_init(request).await?;
+    let scope = db_scope_for_request(request)?;
 
     let (schema_sql, schema_ref) = load_schema_sql(request.rid.clone(), schema.as_deref()).await?;
-    let db: SwarmDb = SwarmDb::new(&url)
+    let options = connect_options_for_request(request)?;
+    let db = SwarmDb::new_with_options(&url, scope.schema(), None, &options)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    db.create_schema()
         .await
         .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
     db.initialize_schema_from_sql(&schema_sql)
@@ -245,148 +368,49 @@ pub(in crate::protocol_runtime) async fn handle_init_db(
 pub(in crate::protocol_runtime) async fn handle_init_local_db(
     request: &ProtocolRequest,
 ) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
-    let container_name = request
-        .args
-        .get("container_name")
-        .and_then(Value::as_str)
-        .map_or("shitty-swarm-manager-db", |value| value)
-        .to_string();
-    let port = request
-        .args
-        .get("port")
-        .and_then(Value::as_u64)
-        .map_or(5437, |value| value) as u16;
-    let user = request
-        .args
-        .get("user")
-        .and_then(Value::as_str)
-        .map_or("shitty_swarm_manager", |value| value)
-        .to_string();
-    let database = request
-        .args
-        .get("database")
-        .and_then(Value::as_str)
-        .map_or("shitty_swarm_manager_db", |value| value)
-        .to_string();
-    let schema = request
-        .args
-        .get("schema")
-        .and_then(Value::as_str)
-        .map(PathBuf::from)
-        .map(|value| value.display().to_string());
-    let seed_agents = request
-        .args
-        .get("seed_agents")
-        .and_then(Value::as_u64)
-        .map_or(12, |value| value) as u32;
+    let input = crate::InitLocalDbInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm init-local-db --port 5437 --volume swarm-pgdata".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let local = LocalDb {
+        container: input
+            .container_name
+            .unwrap_or_else(|| DEFAULT_LOCAL_DB_CONTAINER.to_string()),
+        port: input.port.unwrap_or(DEFAULT_LOCAL_DB_PORT),
+        user: input
+            .user
+            .unwrap_or_else(|| DEFAULT_LOCAL_DB_USER.to_string()),
+        database: input
+            .database
+            .unwrap_or_else(|| DEFAULT_LOCAL_DB_NAME.to_string()),
+        volume: input.volume,
+        major: DEFAULT_LOCAL_DB_POSTGRES_MAJOR,
+    };
+    let schema = input.schema;
+    let seed_agents = input.seed_agents.unwrap_or(12);
 
     if dry_flag(request) {
         return Ok(dry_run_success(
             request,
             vec![
-                json!({"step": 1, "action": "docker_start_or_run", "target": container_name.clone()}),
+                json!({"step": 1, "action": "docker_start_or_run", "target": local.container.clone(), "volume": local.volume.clone()}),
                 json!({"step": 2, "action": "init_db", "target": schema.clone().unwrap_or_else(|| EMBEDDED_COORDINATOR_SCHEMA_REF.to_string())}),
             ],
             "swarm state",
         ));
     }
 
-    let port_mapping = format!("{port}:5432");
-    let start_result = Command::new("docker")
-        .args(["start", container_name.as_str()])
-        .output()
-        .await;
-
-    let container_started = start_result
-        .as_ref()
-        .is_ok_and(|output| output.status.success());
-
-    if !container_started {
-        let run_result = Command::new("docker")
-            .args([
-                "run",
-                "-d",
-                "--name",
-                container_name.as_str(),
-                "-p",
-                port_mapping.as_str(),
-                "-e",
-                format!("POSTGRES_USER={user}").as_str(),
-                "-e",
-                "POSTGRES_HOST_AUTH_METHOD=trust",
-                "-e",
-                format!("POSTGRES_DB={database}").as_str(),
-                "postgres:16",
-            ])
-            .output()
-            .await;
-
-        if let Err(e) = run_result.as_ref() {
-            return Err(Box::new(
-                ProtocolEnvelope::error(
-                    request.rid.clone(),
-                    code::INTERNAL.to_string(),
-                    format!("Failed to run docker container: {e}"),
-                )
-                .with_fix("Ensure docker is running and container name is available".to_string()),
-            ));
-        }
-
-        if let Ok(output) = &run_result {
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
-                return Err(Box::new(
-                    ProtocolEnvelope::error(
-                        request.rid.clone(),
-                        code::INTERNAL.to_string(),
-                        format!("Docker run failed: {stderr}"),
-                    )
-                    .with_fix(
-                        "Check docker logs, ensure port is available and container name is unique"
-                            .to_string(),
-                    ),
-                ));
-            }
-        }
-    }
-
-    let mut retry_count = 0;
-    let max_retries = 10;
-    let mut last_error = String::new();
-    while retry_count < max_retries {
-        let ready_check = Command::new("docker")
-            .args(["exec", container_name.as_str(), "pg_isready", "-U", &user])
-            .output()
-            .await;
-
-        match ready_check {
-            Ok(check) if check.status.success() => break,
-            Ok(check) => {
-                last_error = String::from_utf8_lossy(&check.stderr).trim().to_string();
-            }
-            Err(e) => {
-                last_error = e.to_string();
-            }
-        }
-        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-        retry_count += 1;
-    }
-
-    if retry_count >= max_retries {
-        return Err(Box::new(
-            ProtocolEnvelope::error(
-                request.rid.clone(),
-                code::INTERNAL.to_string(),
-                format!("Database container not ready after {max_retries}s: {last_error}"),
-            )
-            .with_fix(
-                "Check docker logs for the container, verify postgres is starting correctly"
-                    .to_string(),
-            ),
-        ));
-    }
-
-    let url = format!("postgresql://{user}@localhost:{port}/{database}");
+    let container_action = localdb::start_or_run(request.rid.clone(), &local).await?;
+    let ready_after_attempts = localdb::wait_until_ready(request.rid.clone(), &local).await?;
+    let container_name = local.container.clone();
+    let url = local.url();
 
     let bootstrap_request = ProtocolRequest {
         cmd: "bootstrap".to_string(),
@@ -415,6 +439,9 @@ pub(in crate::protocol_runtime) async fn handle_init_local_db(
     Ok(CommandSuccess {
         data: json!({
             "container": container_name,
+            "container_action": container_action,
+            "volume": local.volume,
+            "ready_after_attempts": ready_after_attempts,
             "database_url": mask_database_url(&url),
             "seed_agents": seed_agents
         }),
@@ -423,6 +450,109 @@ pub(in crate::protocol_runtime) async fn handle_init_local_db(
     })
 }
 
+/// Plans `init-db` against the database it would initialize: which schema
+/// tables already exist and which agents seeding would keep, prune and add.
+/// Nothing is created, not even a tenant's schema.
+async fn init_db_dry_run(
+    request: &ProtocolRequest,
+    schema: Option<&str>,
+    seed_agents: u32,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let scope = db_scope_for_request(request)?;
+    let (schema_sql, schema_ref) = load_schema_sql(request.rid.clone(), schema).await?;
+    let mut conflicts = Vec::new();
+
+    let url = resolve_database_url_for_init(request)
+        .await
+        .map_err(|failure| conflicts.push(state_unavailable(&failure)))
+        .ok();
+    let options = connect_options_for_request(request)?;
+    let db = match url.as_deref() {
+        Some(url) => SwarmDb::new_with_options(url, scope.schema(), None, &options)
+            .await
+            .map_err(|error| {
+                conflicts.push(DryRunConflict::new(
+                    DryRunConflictKind::StateUnavailable,
+                    error.to_string(),
+                ));
+            })
+            .ok(),
+        None => None,
+    };
+    let existing_tables = match &db {
+        Some(db) => db
+            .existing_tables(scope.schema())
+            .await
+            .unwrap_or_else(|error| {
+                conflicts.push(DryRunConflict::new(
+                    DryRunConflictKind::StateUnavailable,
+                    error.to_string(),
+                ));
+                Vec::new()
+            }),
+        None => Vec::new(),
+    };
+
+    let (kept_tables, created_tables): (Vec<String>, Vec<String>) = schema_table_names(&schema_sql)
+        .into_iter()
+        .partition(|table| existing_tables.contains(table));
+    let seed_plan = match &db {
+        Some(db) if existing_tables.iter().any(|table| table == "agent_state") => {
+            let local = RepoId::new("local");
+            match (
+                db.registered_agent_ids(&local).await,
+                db.idle_unassigned_agent_ids(&local).await,
+            ) {
+                (Ok(existing), Ok(idle)) => SeedPlan::new(seed_agents, &existing, &idle),
+                (Err(error), _) | (_, Err(error)) => {
+                    conflicts.push(DryRunConflict::new(
+                        DryRunConflictKind::StateUnavailable,
+                        error.to_string(),
+                    ));
+                    SeedPlan::new(seed_agents, &[], &[])
+                }
+            }
+        }
+        _ => SeedPlan::new(seed_agents, &[], &[]),
+    };
+
+    let target = url.as_deref().map_or_else(
+        || "auto-discover-on-execution".to_string(),
+        mask_database_url,
+    );
+    Ok(dry_run_plan(
+        request,
+        vec![
+            json!({"step": 1, "action": "connect_db", "target": target, "db_schema": scope.schema()}),
+            json!({"step": 2, "action": "apply_schema", "target": schema_ref, "tables": {
+                "create": created_tables, "already_present": kept_tables,
+            }}),
+            json!({"step": 3, "action": "seed_agents", "target": seed_agents, "rows": [
+                {"table": "swarm_config", "op": "update", "max_agents": seed_agents},
+                {"table": "agent_state", "op": "soft_delete", "agent_ids": seed_plan.prune},
+                {"table": "agent_state", "op": "insert", "agent_ids": seed_plan.add},
+            ], "keep": seed_plan.keep}),
+        ],
+        conflicts,
+        Vec::new(),
+        "swarm state",
+    )
+    .await)
+}
+
+/// Tables a schema script creates, in the order it creates them.
+fn schema_table_names(schema_sql: &str) -> Vec<String> {
+    schema_sql
+        .lines()
+        .filter_map(|line| {
+            line.trim_start()
+                .strip_prefix("CREATE TABLE IF NOT EXISTS ")
+        })
+        .filter_map(|rest| rest.split(|c: char| c.is_whitespace() || c == '(').next())
+        .map(|name| name.rsplit('.').next().unwrap_or(name).to_string())
+        .collect()
+}
+
 fn to_protocol_failure(error: SwarmError, rid: Option<String>) -> Box<ProtocolEnvelope> {
     super::super::helpers::to_protocol_failure(error, rid)
 }
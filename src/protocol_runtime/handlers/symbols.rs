@@ -0,0 +1,192 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use super::invalid_request;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{SymbolKind, SymbolObservation};
+use crate::{code, SwarmDb};
+use serde_json::{json, Value};
+use tokio::fs;
+
+const RECORD_SYMBOLS_FIX: &str =
+    "swarm record-symbols --bead-id <bead-id> --symbols '[{\"name\":\"f\",\"kind\":\"function\",\"module_path\":\"crate::m\",\"signature\":\"fn()\"}]'";
+
+/// One entry of the `symbols` payload before an attempt is attached.
+#[derive(Debug, PartialEq, Eq)]
+struct SymbolEntry {
+    name: String,
+    kind: SymbolKind,
+    module_path: String,
+    signature: String,
+}
+
+pub(in crate::protocol_runtime) async fn handle_record_symbols(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::RecordSymbolsInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(RECORD_SYMBOLS_FIX)))?;
+    let payload = symbols_payload(request, &input).await?;
+    let entries = parse_symbol_entries(&payload)
+        .map_err(|message| invalid_request(request, message, Some(RECORD_SYMBOLS_FIX)))?;
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "resolve_attempt", "target": input.bead_id, "attempt": input.attempt}),
+                json!({"step": 2, "action": "record_symbols", "target": input.bead_id, "count": entries.len()}),
+            ],
+            "swarm monitor --view drift",
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let attempt = match input.attempt {
+        Some(attempt) => attempt,
+        None => db
+            .current_symbol_attempt(&repo_id, &input.bead_id)
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?,
+    };
+    let observations = entries
+        .into_iter()
+        .map(|entry| SymbolObservation {
+            bead_id: input.bead_id.clone(),
+            attempt,
+            name: entry.name,
+            kind: entry.kind,
+            module_path: entry.module_path,
+            signature: entry.signature,
+        })
+        .collect::<Vec<_>>();
+    db.record_symbols(&repo_id, input.agent_id, &observations)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "bead_id": input.bead_id,
+            "attempt": attempt,
+            "recorded": observations.len(),
+        }),
+        next: format!("swarm monitor --view drift --bead-id {}", input.bead_id),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Exactly one of inline `symbols` or a JSON `file`.
+async fn symbols_payload(
+    request: &ProtocolRequest,
+    input: &crate::RecordSymbolsInput,
+) -> std::result::Result<Value, Box<ProtocolEnvelope>> {
+    match (input.symbols.as_ref(), input.file.as_deref()) {
+        (Some(symbols), None) => Ok(symbols.clone()),
+        (None, Some(path)) => {
+            let raw = fs::read_to_string(path).await.map_err(|err| {
+                Box::new(
+                    ProtocolEnvelope::error(
+                        request.rid.clone(),
+                        code::NOTFOUND.to_string(),
+                        format!("Symbols file not found: {err}"),
+                    )
+                    .with_fix("Pass an existing JSON file with --file".to_string())
+                    .with_ctx(json!({"file": path})),
+                )
+            })?;
+            serde_json::from_str(&raw).map_err(|err| {
+                invalid_request(
+                    request,
+                    format!("Symbols file is not valid JSON: {err}"),
+                    Some(RECORD_SYMBOLS_FIX),
+                )
+            })
+        }
+        _ => Err(invalid_request(
+            request,
+            "record-symbols requires exactly one of symbols or file".to_string(),
+            Some(RECORD_SYMBOLS_FIX),
+        )),
+    }
+}
+
+/// Accepts a bare array or `{"symbols": [...]}`. Each entry needs `name`,
+/// `kind`, and `signature`; `module_path` defaults to `crate`.
+fn parse_symbol_entries(payload: &Value) -> std::result::Result<Vec<SymbolEntry>, String> {
+    let items = payload
+        .as_array()
+        .or_else(|| payload.get("symbols").and_then(Value::as_array))
+        .ok_or_else(|| "symbols must be an array or an object with a symbols array".to_string())?;
+    if items.is_empty() {
+        return Err("symbols must not be empty".to_string());
+    }
+
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let field = |name: &str| {
+                item.get(name)
+                    .and_then(Value::as_str)
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+            };
+            let required = |name: &str| {
+                field(name)
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("symbols[{index}].{name} must be a non-empty string"))
+            };
+            let kind = SymbolKind::try_from(required("kind")?.as_str())
+                .map_err(|err| format!("symbols[{index}].kind: {err}"))?;
+            Ok(SymbolEntry {
+                name: required("name")?,
+                kind,
+                module_path: field("module_path").unwrap_or("crate").to_string(),
+                signature: required("signature")?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_wrapped_symbols_when_parsing_then_module_path_defaults_to_crate() {
+        let payload = json!({"symbols": [
+            {"name": "load", "kind": "function", "signature": "fn(&Path) -> Result<Config>"},
+            {"name": "Config", "kind": "struct", "module_path": "crate::config", "signature": "struct Config"},
+        ]});
+
+        let entries = parse_symbol_entries(&payload);
+
+        assert_eq!(
+            entries,
+            Ok(vec![
+                SymbolEntry {
+                    name: "load".to_string(),
+                    kind: SymbolKind::Function,
+                    module_path: "crate".to_string(),
+                    signature: "fn(&Path) -> Result<Config>".to_string(),
+                },
+                SymbolEntry {
+                    name: "Config".to_string(),
+                    kind: SymbolKind::Struct,
+                    module_path: "crate::config".to_string(),
+                    signature: "struct Config".to_string(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn given_unknown_kind_when_parsing_then_error_names_the_entry() {
+        let payload = json!([{"name": "x", "kind": "macro", "signature": "macro_rules! x"}]);
+
+        let entries = parse_symbol_entries(&payload);
+
+        assert!(entries.is_err_and(|error| error.starts_with("symbols[0].kind")));
+    }
+}
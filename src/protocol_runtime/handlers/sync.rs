@@ -0,0 +1,141 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    run_external_json_command, to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use super::invalid_request;
+use crate::beads_sync::{detect_divergences, BrSyncAction, BrSyncDecision};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::parse_br_issues;
+use crate::{code, SwarmDb};
+use serde_json::{json, Value};
+use std::time::Instant;
+
+const SYNC_FIX: &str = "swarm sync repair [--dry]";
+
+/// Finds every bead whose claim and `br` status disagree and repairs it,
+/// recording each repair in `command_audit` as `sync-repair`.
+pub(in crate::protocol_runtime) async fn handle_sync(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    crate::SyncInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(SYNC_FIX)))?;
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let payload = run_external_json_command(
+        "br",
+        &["list", "--json"],
+        request.rid.clone(),
+        "Run `br list --json` manually and verify beads workspace is initialized",
+    )
+    .await?;
+    let claims = db
+        .get_claims_for_sync(&repo_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let decisions = detect_divergences(&claims, &parse_br_issues(&payload))
+        .into_iter()
+        .map(BrSyncDecision::from)
+        .collect::<Vec<_>>();
+
+    if dry_flag(request) {
+        let steps = decisions
+            .iter()
+            .zip(1_u32..)
+            .map(|(decision, step)| {
+                json!({
+                    "step": step,
+                    "action": decision.action.describe(),
+                    "target": decision.divergence.bead_id(),
+                    "divergence": decision.divergence,
+                })
+            })
+            .collect();
+        return Ok(dry_run_success(request, steps, "swarm sync repair"));
+    }
+
+    let mut repaired = Vec::new();
+    let mut failed = Vec::new();
+    for decision in decisions {
+        let started = Instant::now();
+        let outcome = apply_repair(request, &db, &repo_id, &decision.action).await;
+        let ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        let (error_code, error) = match &outcome {
+            Ok(()) => (None, None),
+            Err((error_code, message)) => (Some(error_code.as_str()), Some(message.as_str())),
+        };
+        db.record_command_audit(
+            "sync-repair",
+            request.rid.as_deref(),
+            json!({"repo_id": repo_id.value(), "decision": decision, "error": error}),
+            outcome.is_ok(),
+            ms,
+            error_code,
+        )
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+        match outcome {
+            Ok(()) => repaired.push(json!(decision)),
+            Err((error_code, message)) => failed.push(json!({
+                "decision": decision,
+                "code": error_code,
+                "error": message,
+            })),
+        }
+    }
+
+    let next = if failed.is_empty() {
+        "swarm status".to_string()
+    } else {
+        "swarm sync repair --dry".to_string()
+    };
+    Ok(CommandSuccess {
+        data: json!({
+            "repaired": repaired,
+            "failed": failed,
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// Runs one repair; a failure is reported as `(code, message)` so the
+/// remaining repairs still run.
+async fn apply_repair(
+    request: &ProtocolRequest,
+    db: &SwarmDb,
+    repo_id: &crate::RepoId,
+    action: &BrSyncAction,
+) -> std::result::Result<(), (String, String)> {
+    match action {
+        BrSyncAction::BrUpdate { .. } => {
+            let args = action.br_args().unwrap_or_default();
+            let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+            run_external_json_command("br", &args, request.rid.clone(), SYNC_FIX)
+                .await
+                .map(|_payload: Value| ())
+                .map_err(|failure| {
+                    failure.err.map_or_else(
+                        || (code::INTERNAL.to_string(), "br update failed".to_string()),
+                        |err| (err.code, err.msg),
+                    )
+                })
+        }
+        BrSyncAction::SettleClaim {
+            bead_id,
+            agent_id,
+            terminal,
+        } => match db
+            .settle_claim(repo_id, bead_id, *agent_id, *terminal)
+            .await
+        {
+            Ok(true) => Ok(()),
+            Ok(false) => Err((
+                code::CONFLICT.to_string(),
+                format!("Agent {agent_id} no longer holds {bead_id}"),
+            )),
+            Err(err) => Err((err.code().to_string(), err.to_string())),
+        },
+    }
+}
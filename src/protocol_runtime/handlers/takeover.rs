@@ -0,0 +1,101 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use super::invalid_request;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::BeadId;
+use crate::SwarmDb;
+use serde_json::json;
+
+const TAKEOVER_FIX: &str =
+    "swarm takeover --bead-id <bead-id> --to-agent <id> | swarm takeover --bead-id <bead-id> --from-agent <owner-id> --consent";
+
+/// Hands a claimed bead to another agent along with its latest resume
+/// context, provided the owner is stale or has consented. With `consent`,
+/// records the owner's consent on its claim instead.
+pub(in crate::protocol_runtime) async fn handle_takeover(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::TakeoverInput::parse_input(request)
+        .map_err(|error| invalid_request(request, error.to_string(), Some(TAKEOVER_FIX)))?;
+    let (Some(to_agent), false) = (input.to_agent, input.consent.unwrap_or(false)) else {
+        return handle_consent(request, input).await;
+    };
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "verify_owner_stale_or_consented", "target": input.bead_id, "from_agent": input.from_agent}),
+                json!({"step": 2, "action": "load_resume_context", "target": input.bead_id}),
+                json!({"step": 3, "action": "transfer_claim", "target": format!("bead:{}, agent:{to_agent}", input.bead_id)}),
+                json!({"step": 4, "action": "send_handoff_message", "target": to_agent}),
+            ],
+            &format!("swarm agent --id {to_agent}"),
+        ));
+    }
+
+    let budget = super::resume::context_budget_from_request(request)?;
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let bead_id = BeadId::new(input.bead_id);
+    let resume_context = db
+        .get_deep_resume_context(&repo_id, &bead_id, budget)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let transfer = db
+        .transfer_claim(
+            &repo_id,
+            &bead_id,
+            to_agent,
+            input.from_agent,
+            resume_context.map(|context| json!(context)),
+        )
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({"transfer": transfer}),
+        next: format!("swarm agent --id {to_agent}"),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+/// The owner, as `from_agent`, marks its own claim open to takeover. The
+/// claim row decides whether it is the owner.
+async fn handle_consent(
+    request: &ProtocolRequest,
+    input: crate::TakeoverInput,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let Some(owner) = input.from_agent else {
+        return Err(invalid_request(
+            request,
+            "consent needs from_agent".to_string(),
+            Some(TAKEOVER_FIX),
+        ));
+    };
+    let next = format!("swarm takeover --bead-id {} --to-agent <id>", input.bead_id);
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "record_takeover_consent", "target": input.bead_id, "from_agent": owner}),
+            ],
+            &next,
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let bead_id = BeadId::new(input.bead_id);
+    db.consent_to_takeover(&repo_id, &bead_id, owner)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({"bead_id": bead_id, "consented_by": owner}),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
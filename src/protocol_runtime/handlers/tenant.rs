@@ -0,0 +1,145 @@
+use super::super::db_resolution::connect_options_for_request;
+use super::super::{
+    dry_flag, dry_run_success, load_schema_sql, mask_database_url, minimal_state_for_request,
+    resolve_database_url_for_init, to_protocol_failure, CommandSuccess, ParseInput,
+    ProtocolRequest, EMBEDDED_COORDINATOR_SCHEMA_REF,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::tenant_schema;
+use crate::{code, SwarmDb};
+use serde_json::json;
+
+const TENANT_FIX: &str = "swarm tenant create|list|delete --name <tenant>";
+/// Agents a new tenant starts with, as for `init-db`.
+const DEFAULT_TENANT_AGENTS: u32 = 12;
+
+/// Admin for tenants, the isolated swarms one database hosts. Every other
+/// command reaches a tenant through the global `tenant` arg.
+pub(in crate::protocol_runtime) async fn handle_tenant(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::TenantInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(TENANT_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let name = input.name.clone().unwrap_or_default();
+    let schema = tenant_schema(&name).unwrap_or_default();
+    let seed_agents = input.seed_agents.unwrap_or(DEFAULT_TENANT_AGENTS);
+
+    if dry_flag(request) {
+        let steps = match input.action.as_str() {
+            "create" => vec![
+                json!({"step": 1, "action": "create_schema", "target": schema}),
+                json!({"step": 2, "action": "apply_schema", "target": EMBEDDED_COORDINATOR_SCHEMA_REF}),
+                json!({"step": 3, "action": "seed_agents", "target": seed_agents}),
+                json!({"step": 4, "action": "register_tenant", "target": name}),
+            ],
+            "delete" => vec![
+                json!({"step": 1, "action": "unregister_tenant", "target": name}),
+                json!({"step": 2, "action": "drop_schema", "target": schema}),
+            ],
+            _ => vec![json!({"step": 1, "action": "list_tenants", "target": "public.tenants"})],
+        };
+        return Ok(dry_run_success(request, steps, "swarm tenant list"));
+    }
+
+    let url = resolve_database_url_for_init(request).await?;
+    let options = connect_options_for_request(request)?;
+    let registry = SwarmDb::new_with_options(&url, None, None, &options)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let (data, next) = match input.action.as_str() {
+        "create" => {
+            let exists = registry
+                .tenant_exists(&name)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            if exists {
+                return Err(Box::new(
+                    ProtocolEnvelope::error(
+                        request.rid.clone(),
+                        code::EXISTS.to_string(),
+                        format!("Tenant {name} already exists"),
+                    )
+                    .with_fix(format!("swarm status --tenant {name}"))
+                    .with_ctx(json!({"tenant": name})),
+                ));
+            }
+            let (schema_sql, schema_ref) = load_schema_sql(request.rid.clone(), None).await?;
+            let db = SwarmDb::new_with_options(&url, Some(&schema), None, &options)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            db.create_schema()
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            db.initialize_schema_from_sql(&schema_sql)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            db.update_config(seed_agents)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            db.seed_idle_agents(seed_agents)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let tenant = registry
+                .register_tenant(&name, &schema)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            (
+                json!({
+                    "tenant": tenant,
+                    "created": tenant.is_some(),
+                    "database_url": mask_database_url(&url),
+                    "schema": schema_ref,
+                    "seed_agents": seed_agents,
+                }),
+                format!("swarm status --tenant {name}"),
+            )
+        }
+        "delete" => {
+            let tenant = registry
+                .delete_tenant(&name)
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+                .ok_or_else(|| {
+                    Box::new(
+                        ProtocolEnvelope::error(
+                            request.rid.clone(),
+                            code::NOTFOUND.to_string(),
+                            format!("Tenant {name} does not exist"),
+                        )
+                        .with_fix("swarm tenant list".to_string())
+                        .with_ctx(json!({"tenant": name})),
+                    )
+                })?;
+            (
+                json!({"tenant": tenant, "deleted": true}),
+                "swarm tenant list".to_string(),
+            )
+        }
+        _ => {
+            let tenants = registry
+                .list_tenants()
+                .await
+                .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+            let next = tenants.first().map_or_else(
+                || "swarm tenant create --name <tenant>".to_string(),
+                |tenant| format!("swarm status --tenant {}", tenant.name),
+            );
+            (json!({"tenants": tenants}), next)
+        }
+    };
+
+    Ok(CommandSuccess {
+        data,
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
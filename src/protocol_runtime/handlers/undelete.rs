@@ -0,0 +1,90 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::{code, SwarmDb};
+use serde_json::json;
+
+const UNDELETE_FIX: &str = "swarm undelete --table claims|backlog|agents --key <key>";
+const DEFAULT_DELETED_ROWS_LIMIT: u32 = 50;
+
+/// Restores a deleted claim, backlog entry or agent, or lists what can be
+/// restored. A deleted row stays in its table, marked deleted, until `gc`
+/// purges it.
+pub(in crate::protocol_runtime) async fn handle_undelete(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::UndeleteInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(UNDELETE_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let repo_id = repo_id_from_request(request);
+
+    let (Some(table), Some(key)) = (input.table, input.key.as_deref()) else {
+        let db: SwarmDb = db_from_request(request).await?;
+        let deleted = db
+            .list_deleted_rows(
+                &repo_id,
+                input.table,
+                input.limit.unwrap_or(DEFAULT_DELETED_ROWS_LIMIT),
+            )
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+        let next = deleted.first().map_or_else(
+            || "swarm status".to_string(),
+            |row| {
+                format!(
+                    "swarm undelete --table {} --key {}",
+                    row.table.as_str(),
+                    row.key
+                )
+            },
+        );
+        return Ok(CommandSuccess {
+            data: json!({"deleted": deleted}),
+            next,
+            state: minimal_state_for_request(request).await,
+        });
+    };
+
+    if dry_flag(request) {
+        return Ok(dry_run_success(
+            request,
+            vec![
+                json!({"step": 1, "action": "restore_row", "target": format!("{}:{key}", table.table_name())}),
+            ],
+            &format!("swarm undelete --table {} --key {key}", table.as_str()),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let restored = db
+        .undelete_row(&repo_id, table, key)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+        .ok_or_else(|| {
+            Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::NOTFOUND.to_string(),
+                    format!("No deleted {} row with key {key}", table.as_str()),
+                )
+                .with_fix(format!("swarm undelete --table {}", table.as_str()))
+                .with_ctx(json!({"table": table.as_str(), "key": key})),
+            )
+        })?;
+
+    Ok(CommandSuccess {
+        data: json!({"restored": restored}),
+        next: "swarm status".to_string(),
+        state: minimal_state_for_request(request).await,
+    })
+}
@@ -0,0 +1,84 @@
+use super::super::{
+    minimal_state_for_request, read_db_from_request, repo_id_from_request, to_protocol_failure,
+    CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::signing::verify_artifact_chain;
+use crate::{code, BeadId, SwarmDb};
+use serde_json::json;
+
+/// Checks every artifact of a bead against its content hash and the
+/// swarm's signing key, following the chain each signature links into.
+pub(in crate::protocol_runtime) async fn handle_verify(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::VerifyInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix("swarm verify --bead-id <bead-id>".to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+
+    let Some(verifier) = crate::config::artifact_verifier_from_env()
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+    else {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                "No key to verify against".to_string(),
+            )
+            .with_fix(
+                "Set SWARM_VERIFY_KEY to the swarm's hex public key, or SWARM_SIGNING_KEY"
+                    .to_string(),
+            )
+            .with_ctx(json!({"bead_id": input.bead_id})),
+        ));
+    };
+
+    let db: SwarmDb = read_db_from_request(request).await?;
+    let artifacts = db
+        .get_bead_signed_artifacts(
+            &repo_id_from_request(request),
+            &BeadId::new(input.bead_id.as_str()),
+        )
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    if artifacts.is_empty() {
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::NOTFOUND.to_string(),
+                format!("No artifacts for bead {}", input.bead_id),
+            )
+            .with_fix(format!("swarm artifacts --bead-id {}", input.bead_id))
+            .with_ctx(json!({"bead_id": input.bead_id})),
+        ));
+    }
+
+    let report = verify_artifact_chain(&input.bead_id, &artifacts, &verifier);
+    let next = if report.valid {
+        format!("swarm replay --bead-id {}", input.bead_id)
+    } else {
+        format!("swarm artifacts --bead-id {}", input.bead_id)
+    };
+    Ok(CommandSuccess {
+        data: json!({
+            "bead_id": input.bead_id,
+            "key_id": report.key_id,
+            "valid": report.valid,
+            "artifacts": report.rows.len(),
+            "verified": report.verified,
+            "unsigned": report.unsigned,
+            "failed": report.failed,
+            "rows": report.rows,
+        }),
+        next,
+        state: minimal_state_for_request(request).await,
+    })
+}
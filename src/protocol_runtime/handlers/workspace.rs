@@ -0,0 +1,252 @@
+use super::super::{
+    db_from_request, dry_flag, dry_run_success, minimal_state_for_request, repo_id_from_request,
+    to_protocol_failure, CommandSuccess, ParseInput, ProtocolRequest,
+};
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::types::{workspace_name, AgentWorkspace, RepoId};
+use crate::workspace::{
+    default_workspace_root, detect_backend, discard_workspace, provision_workspace,
+    remove_workspace,
+};
+use crate::{code, AgentId, SwarmDb};
+use serde_json::json;
+use std::path::PathBuf;
+
+const WORKSPACE_FIX: &str = "swarm workspace --agent-id <id> [--action show|create|remove]";
+
+/// Reports, creates, or removes the agent's own checkout.
+pub(in crate::protocol_runtime) async fn handle_workspace(
+    request: &ProtocolRequest,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let input = crate::WorkspaceInput::parse_input(request).map_err(|error| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::INVALID.to_string(),
+                error.to_string(),
+            )
+            .with_fix(WORKSPACE_FIX.to_string())
+            .with_ctx(json!({"error": error.to_string()})),
+        )
+    })?;
+    let action = input.action.as_deref().unwrap_or("show");
+
+    if dry_flag(request) {
+        let target = format!("agent:{}", input.agent_id);
+        let steps = match action {
+            "create" => vec![
+                json!({"step": 1, "action": "resolve_bead", "target": input.bead_id.clone().unwrap_or_else(|| "claimed bead".to_string())}),
+                json!({"step": 2, "action": "provision_workspace", "target": target}),
+                json!({"step": 3, "action": "record_workspace", "target": target}),
+            ],
+            "remove" => vec![
+                json!({"step": 1, "action": "remove_workspace", "target": target}),
+                json!({"step": 2, "action": "mark_workspace_removed", "target": target}),
+            ],
+            _ => vec![json!({"step": 1, "action": "get_active_workspace", "target": target})],
+        };
+        return Ok(dry_run_success(
+            request,
+            steps,
+            &format!("swarm workspace --agent-id {}", input.agent_id),
+        ));
+    }
+
+    let db: SwarmDb = db_from_request(request).await?;
+    let repo_id = repo_id_from_request(request);
+    let existing = db
+        .get_active_workspace(&repo_id, input.agent_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    match action {
+        "create" => create(request, &db, &repo_id, &input, existing).await,
+        "remove" => remove(request, &db, &repo_id, input.agent_id, existing).await,
+        _ => Ok(CommandSuccess {
+            data: json!({
+                "agent_id": input.agent_id,
+                "path": existing.as_ref().map(|workspace| workspace.path.clone()),
+                "workspace": existing,
+            }),
+            next: existing.as_ref().map_or_else(
+                || {
+                    format!(
+                        "swarm workspace --agent-id {} --action create",
+                        input.agent_id
+                    )
+                },
+                |workspace| work_in(input.agent_id, workspace),
+            ),
+            state: minimal_state_for_request(request).await,
+        }),
+    }
+}
+
+async fn create(
+    request: &ProtocolRequest,
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    input: &crate::WorkspaceInput,
+    existing: Option<AgentWorkspace>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let bead_id = match input.bead_id.clone() {
+        Some(bead_id) => bead_id,
+        None => db
+            .get_agent_state(&AgentId::new(repo_id.clone(), input.agent_id))
+            .await
+            .map_err(|e| to_protocol_failure(e, request.rid.clone()))?
+            .and_then(|state| state.bead_id().map(|bead| bead.value().to_string()))
+            .ok_or_else(|| {
+                Box::new(
+                    ProtocolEnvelope::error(
+                        request.rid.clone(),
+                        code::NOTFOUND.to_string(),
+                        format!("Agent {} has no claimed bead", input.agent_id),
+                    )
+                    .with_fix(format!(
+                        "swarm claim-next --agent-id {0} or swarm workspace --agent-id {0} --action create --bead-id <bead-id>",
+                        input.agent_id
+                    ))
+                    .with_ctx(json!({"agent_id": input.agent_id})),
+                )
+            })?,
+    };
+
+    if let Some(workspace) = existing {
+        if workspace.bead_id == bead_id {
+            return Ok(created(request, input.agent_id, &workspace, false).await);
+        }
+        return Err(Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::CONFLICT.to_string(),
+                format!(
+                    "Agent {} already has a workspace for bead {}",
+                    input.agent_id, workspace.bead_id
+                ),
+            )
+            .with_fix(format!(
+                "swarm workspace --agent-id {} --action remove",
+                input.agent_id
+            ))
+            .with_ctx(json!({"agent_id": input.agent_id, "workspace": workspace})),
+        ));
+    }
+
+    let repo_root = super::prompts::repo_root_or_cwd(request).await?;
+    let root: PathBuf = crate::config::workspace_root_from_env()
+        .unwrap_or_else(|| default_workspace_root(&repo_root));
+    let name = workspace_name(input.agent_id, &bead_id);
+    let path = root.join(&name);
+    let backend = detect_backend(&repo_root);
+    provision_workspace(&repo_root, backend, &name, &path)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    // The checkout is only kept once it is recorded; otherwise it would sit
+    // on disk with no row pointing at it and block the next create.
+    let recorded = db
+        .record_workspace(
+            repo_id,
+            input.agent_id,
+            &bead_id,
+            &name,
+            &path.to_string_lossy(),
+            backend,
+        )
+        .await;
+    let workspace = match recorded {
+        Ok(Some(workspace)) => workspace,
+        Ok(None) => {
+            let cleanup_error = discard_workspace(&repo_root, backend, &name, &path)
+                .await
+                .err()
+                .map(|error| error.to_string());
+            return Err(Box::new(
+                ProtocolEnvelope::error(
+                    request.rid.clone(),
+                    code::CONFLICT.to_string(),
+                    format!(
+                        "Workspace for agent {} was recorded concurrently",
+                        input.agent_id
+                    ),
+                )
+                .with_fix(format!("swarm workspace --agent-id {}", input.agent_id))
+                .with_ctx(json!({
+                    "agent_id": input.agent_id,
+                    "path": path.to_string_lossy(),
+                    "cleanup_error": cleanup_error,
+                })),
+            ));
+        }
+        Err(error) => {
+            let _discarded = discard_workspace(&repo_root, backend, &name, &path).await;
+            return Err(to_protocol_failure(error, request.rid.clone()));
+        }
+    };
+
+    Ok(created(request, input.agent_id, &workspace, true).await)
+}
+
+async fn created(
+    request: &ProtocolRequest,
+    agent_id: u32,
+    workspace: &AgentWorkspace,
+    created: bool,
+) -> CommandSuccess {
+    CommandSuccess {
+        data: json!({
+            "agent_id": agent_id,
+            "created": created,
+            "path": workspace.path,
+            "workspace": workspace,
+        }),
+        next: work_in(agent_id, workspace),
+        state: minimal_state_for_request(request).await,
+    }
+}
+
+async fn remove(
+    request: &ProtocolRequest,
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    agent_id: u32,
+    existing: Option<AgentWorkspace>,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let workspace = existing.ok_or_else(|| {
+        Box::new(
+            ProtocolEnvelope::error(
+                request.rid.clone(),
+                code::NOTFOUND.to_string(),
+                format!("Agent {agent_id} has no workspace"),
+            )
+            .with_fix(format!("swarm workspace --agent-id {agent_id}"))
+            .with_ctx(json!({"agent_id": agent_id})),
+        )
+    })?;
+    let repo_root = super::prompts::repo_root_or_cwd(request).await?;
+    remove_workspace(&repo_root, &workspace)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+    let removed = db
+        .mark_workspace_removed(repo_id, agent_id)
+        .await
+        .map_err(|e| to_protocol_failure(e, request.rid.clone()))?;
+
+    Ok(CommandSuccess {
+        data: json!({
+            "agent_id": agent_id,
+            "removed": true,
+            "workspace": removed.unwrap_or(workspace),
+        }),
+        next: format!("swarm workspace --agent-id {agent_id}"),
+        state: minimal_state_for_request(request).await,
+    })
+}
+
+fn work_in(agent_id: u32, workspace: &AgentWorkspace) -> String {
+    format!(
+        "Work in {}; swarm workspace --agent-id {agent_id} --action remove when the bead lands",
+        workspace.path
+    )
+}
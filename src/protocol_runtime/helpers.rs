@@ -40,6 +40,17 @@ pub(super) fn dry_flag(request: &ProtocolRequest) -> bool {
     request.dry.is_some_and(|value| value)
 }
 
+/// Cursor for the next page: the last seq when the page came back full,
+/// `None` once the listing is exhausted.
+pub(super) fn next_page_cursor(
+    last_seq: Option<i64>,
+    returned: usize,
+    page_size: i64,
+) -> Option<i64> {
+    let full_page = i64::try_from(returned).is_ok_and(|count| count >= page_size);
+    last_seq.filter(|_| full_page)
+}
+
 pub(super) fn now_ms() -> i64 {
     chrono::Utc::now().timestamp_millis()
 }
@@ -196,4 +207,15 @@ mod tests {
 
         assert_eq!(state, json!({"total": 10, "active": 6}));
     }
+
+    #[test]
+    fn given_full_page_when_next_page_cursor_then_returns_last_seq() {
+        assert_eq!(next_page_cursor(Some(41), 2, 2), Some(41));
+    }
+
+    #[test]
+    fn given_short_or_empty_page_when_next_page_cursor_then_listing_is_exhausted() {
+        assert_eq!(next_page_cursor(Some(41), 1, 2), None);
+        assert_eq!(next_page_cursor(None, 0, 2), None);
+    }
 }
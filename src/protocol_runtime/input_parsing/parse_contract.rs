@@ -1,5 +1,6 @@
 use super::super::ProtocolRequest;
-use serde_json::Value;
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
 
 pub trait ParseInput {
     type Input;
@@ -106,3 +107,113 @@ pub fn parse_optional_non_negative_u32(
             value: format!("{as_u64} exceeds max u32"),
         })
 }
+
+/// Accepts an RFC3339 string or non-negative epoch milliseconds.
+pub fn parse_optional_timestamp(
+    request: &ProtocolRequest,
+    field: &str,
+) -> Result<Option<DateTime<Utc>>, ParseError> {
+    let Some(raw) = request.args.get(field) else {
+        return Ok(None);
+    };
+    if let Some(text) = raw.as_str() {
+        return DateTime::parse_from_rfc3339(text)
+            .map(|parsed| Some(parsed.with_timezone(&Utc)))
+            .map_err(|error| ParseError::InvalidValue {
+                field: field.to_string(),
+                value: format!("expected RFC3339 timestamp: {error}"),
+            });
+    }
+    let millis = raw.as_i64().ok_or_else(|| ParseError::InvalidType {
+        field: field.to_string(),
+        expected: "RFC3339 string or epoch ms".to_string(),
+        got: json_value_type_name(raw).to_string(),
+    })?;
+    if millis < 0 {
+        return Err(ParseError::InvalidValue {
+            field: field.to_string(),
+            value: "must be non-negative".to_string(),
+        });
+    }
+    DateTime::from_timestamp_millis(millis)
+        .map(Some)
+        .ok_or_else(|| ParseError::InvalidValue {
+            field: field.to_string(),
+            value: format!("{millis} is out of range"),
+        })
+}
+
+/// Accepts a JSON object, e.g. template variables.
+pub fn parse_optional_object(
+    request: &ProtocolRequest,
+    field: &str,
+) -> Result<Option<Map<String, Value>>, ParseError> {
+    match request.args.get(field) {
+        None => Ok(None),
+        Some(Value::Object(object)) => Ok(Some(object.clone())),
+        Some(raw) => Err(ParseError::InvalidType {
+            field: field.to_string(),
+            expected: "object".to_string(),
+            got: json_value_type_name(raw).to_string(),
+        }),
+    }
+}
+
+/// Optional `since` and `until` bounds of a time window.
+pub type TimeRange = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// Parses `since`/`until` and rejects windows that end before they start.
+pub fn parse_time_range(request: &ProtocolRequest) -> Result<TimeRange, ParseError> {
+    let since = parse_optional_timestamp(request, "since")?;
+    let until = parse_optional_timestamp(request, "until")?;
+    if let (Some(start), Some(end)) = (since, until) {
+        if start > end {
+            return Err(ParseError::InvalidValue {
+                field: "until".to_string(),
+                value: "must not be earlier than since".to_string(),
+            });
+        }
+    }
+    Ok((since, until))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_optional_timestamp, parse_time_range, ParseError};
+    use crate::protocol_runtime::ProtocolRequest;
+    use serde_json::{json, Map, Value};
+
+    fn request_with(args: Value) -> ProtocolRequest {
+        ProtocolRequest {
+            cmd: "history".to_string(),
+            rid: None,
+            dry: None,
+            args: args.as_object().cloned().unwrap_or_else(Map::new),
+        }
+    }
+
+    #[test]
+    fn given_rfc3339_and_epoch_ms_when_parsing_timestamp_then_both_resolve_to_same_instant() {
+        let text = request_with(json!({"since": "2024-01-02T03:04:05Z"}));
+        let millis = request_with(json!({"since": 1_704_164_645_000_i64}));
+
+        let from_text = parse_optional_timestamp(&text, "since");
+        let from_millis = parse_optional_timestamp(&millis, "since");
+
+        assert!(matches!(from_text, Ok(Some(_))));
+        assert_eq!(from_text.ok(), from_millis.ok());
+    }
+
+    #[test]
+    fn given_until_before_since_when_parsing_time_range_then_invalid_value_is_returned() {
+        let request = request_with(json!({
+            "since": "2024-01-02T00:00:00Z",
+            "until": "2024-01-01T00:00:00Z",
+        }));
+
+        assert!(matches!(
+            parse_time_range(&request),
+            Err(ParseError::InvalidValue { field, .. }) if field == "until"
+        ));
+    }
+}
@@ -1,7 +1,7 @@
 use super::super::{ProtocolRequest, MAX_REGISTER_COUNT};
 use super::parse_contract::{
-    json_value_type_name, parse_optional_non_negative_u32, parse_optional_non_negative_u64,
-    ParseError, ParseInput,
+    json_value_type_name, parse_optional_non_negative_i64, parse_optional_non_negative_u32,
+    parse_optional_non_negative_u64, parse_time_range, ParseError, ParseInput,
 };
 use serde_json::Value;
 
@@ -11,6 +11,10 @@ impl ParseInput for crate::DoctorInput {
     fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
         Ok(Self {
             json: request.args.get("json").and_then(Value::as_bool),
+            show_candidates: request.args.get("show_candidates").and_then(Value::as_bool),
+            analyze_db: request.args.get("analyze_db").and_then(Value::as_bool),
+            apply: request.args.get("apply").and_then(Value::as_bool),
+            dry: request.args.get("dry").and_then(Value::as_bool),
         })
     }
 }
@@ -91,6 +95,10 @@ impl ParseInput for crate::InitInput {
                 .get("seed_agents")
                 .and_then(Value::as_u64)
                 .and_then(|value| u32::try_from(value).ok()),
+            rollback_on_error: request
+                .args
+                .get("rollback_on_error")
+                .and_then(Value::as_bool),
         })
     }
 }
@@ -170,6 +178,9 @@ impl ParseInput for crate::MonitorInput {
 
     fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
         let watch_ms = parse_optional_non_negative_u64(request, "watch_ms")?;
+        let after_seq = parse_optional_non_negative_i64(request, "after_seq")?;
+        let page_size = parse_optional_non_negative_i64(request, "page_size")?;
+        let (since, until) = parse_time_range(request)?;
 
         Ok(Self {
             view: request
@@ -178,6 +189,10 @@ impl ParseInput for crate::MonitorInput {
                 .and_then(Value::as_str)
                 .map(std::string::ToString::to_string),
             watch_ms,
+            after_seq,
+            page_size,
+            since,
+            until,
         })
     }
 }
@@ -246,6 +261,11 @@ impl ParseInput for crate::InitLocalDbInput {
                 .get("seed_agents")
                 .and_then(Value::as_u64)
                 .and_then(|value| u32::try_from(value).ok()),
+            volume: request
+                .args
+                .get("volume")
+                .and_then(Value::as_str)
+                .map(std::string::ToString::to_string),
             dry: request.args.get("dry").and_then(Value::as_bool),
         })
     }
@@ -1,87 +1,1572 @@
-use super::super::ProtocolRequest;
+use super::super::{ProtocolRequest, DEFAULT_LOCAL_DB_CONTAINER};
 use super::parse_contract::{
-    json_value_type_name, parse_optional_non_negative_i64, ParseError, ParseInput,
+    json_value_type_name, parse_optional_non_negative_i64, parse_optional_non_negative_u32,
+    parse_optional_non_negative_u64, parse_optional_object, parse_time_range, ParseError,
+    ParseInput,
+};
+use crate::orchestrator_service::MAX_RUN_ALL_CONCURRENCY;
+use crate::profiling::MAX_PROFILE_DURATION_MS;
+use crate::prompts::PROMPT_ACTIONS;
+use crate::query_perf::MAX_BASELINE_DAYS;
+use crate::types::{
+    is_valid_announcement_topic, is_valid_claim_label, ApprovalGate, ArtifactType,
+    BlackboardSection, ConfigKey, NotificationSeverity, PartitionedTable, ReviewVerdict,
+    ScheduledJobKind, SoftDeleteTable, DEFAULT_ANNOUNCEMENT_TOPIC, DEFAULT_ANNOUNCEMENT_TTL_SECS,
+    MAX_ANNOUNCEMENT_TOPIC_BYTES, MAX_ANNOUNCEMENT_TTL_SECS, MAX_BLACKBOARD_ENTRY_BYTES,
+    MAX_FORECAST_WINDOW_HOURS, MAX_KV_KEY_BYTES, MAX_KV_VALUE_BYTES, MAX_PARTITIONS_AHEAD,
+    MAX_RESERVATION_TTL_SECS, MAX_REVIEW_COMMENT_BYTES,
 };
 use serde_json::Value;
 
-impl ParseInput for crate::BootstrapInput {
+const WORKSPACE_ACTIONS: &[&str] = &["show", "create", "remove"];
+const BEAD_ACTIONS: &[&str] = &["snapshot", "restore"];
+const SYNC_ACTIONS: &[&str] = &["repair"];
+const EVENTS_ACTIONS: &[&str] = &["migrate"];
+const CHAOS_ACTIONS: &[&str] = &["status"];
+const SESSION_ACTIONS: &[&str] = &["start", "end", "show"];
+const KV_ACTIONS: &[&str] = &["set", "get", "delete"];
+const BLACKBOARD_ACTIONS: &[&str] = &["read", "append"];
+const REVIEW_ACTIONS: &[&str] = &["submit", "list"];
+const TENANT_ACTIONS: &[&str] = &["create", "list", "delete"];
+const LOCALDB_ACTIONS: &[&str] = &["status", "stop", "destroy", "logs", "upgrade"];
+const ARTIFACT_ACTIONS: &[&str] = &["put"];
+const DIFF_ACTIONS: &[&str] = &["put", "get"];
+const CONFIG_ACTIONS: &[&str] = &["get", "set"];
+const LABELS_ACTIONS: &[&str] = &["get", "set"];
+const BACKLOG_ACTIONS: &[&str] = &["preview", "set-priority", "bump", "remove"];
+const JOBS_ACTIONS: &[&str] = &["list", "run-now", "disable", "enable"];
+const NOTIFY_ACTIONS: &[&str] = &["test"];
+const PERF_ACTIONS: &[&str] = &["compare"];
+const MAINTENANCE_TARGETS: &[&str] = &["partitions"];
+const PARTITION_ACTIONS: &[&str] = &["list", "enable", "create", "detach", "drop"];
+const ANNOUNCEMENTS_ACTIONS: &[&str] = &["list", "ack"];
+const TRANSITION_RESULTS: &[&str] = &["passed", "failed", "error", "cancelled"];
+
+impl ParseInput for crate::BootstrapInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::SpawnPromptsInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            template: request
+                .args
+                .get("template")
+                .and_then(Value::as_str)
+                .map(std::string::ToString::to_string),
+            out_dir: request
+                .args
+                .get("out_dir")
+                .and_then(Value::as_str)
+                .map(std::string::ToString::to_string),
+            count: request
+                .args
+                .get("count")
+                .and_then(Value::as_u64)
+                .and_then(|value| u32::try_from(value).ok()),
+            vars: parse_optional_object(request, "vars")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::PromptInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let id = match request.args.get("id") {
+            None => 1,
+            Some(raw) => {
+                if raw.as_i64().is_some_and(|value| value <= 0) {
+                    return Err(ParseError::InvalidValue {
+                        field: "id".to_string(),
+                        value: "must be greater than 0".to_string(),
+                    });
+                }
+
+                let id_as_u64 = raw.as_u64().ok_or_else(|| ParseError::InvalidType {
+                    field: "id".to_string(),
+                    expected: "u32".to_string(),
+                    got: json_value_type_name(raw).to_string(),
+                })?;
+
+                let id = u32::try_from(id_as_u64).map_err(|_| ParseError::InvalidValue {
+                    field: "id".to_string(),
+                    value: format!("{id_as_u64} exceeds max u32"),
+                })?;
+
+                if id == 0 {
+                    return Err(ParseError::InvalidValue {
+                        field: "id".to_string(),
+                        value: "must be greater than 0".to_string(),
+                    });
+                }
+
+                id
+            }
+        };
+
+        let action = request
+            .args
+            .get("action")
+            .and_then(Value::as_str)
+            .map(std::string::ToString::to_string);
+        if let Some(action) = action.as_deref() {
+            if !PROMPT_ACTIONS.contains(&action) {
+                return Err(ParseError::InvalidValue {
+                    field: "action".to_string(),
+                    value: format!("{action} (expected one of {})", PROMPT_ACTIONS.join(", ")),
+                });
+            }
+        }
+
+        Ok(Self {
+            id,
+            skill: request
+                .args
+                .get("skill")
+                .and_then(Value::as_str)
+                .map(std::string::ToString::to_string),
+            action,
+            body: request
+                .args
+                .get("body")
+                .and_then(Value::as_str)
+                .map(std::string::ToString::to_string),
+            file: request
+                .args
+                .get("file")
+                .and_then(Value::as_str)
+                .map(std::string::ToString::to_string),
+            template: request
+                .args
+                .get("template")
+                .and_then(Value::as_str)
+                .map(std::string::ToString::to_string),
+            vars: parse_optional_object(request, "vars")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::ContextInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let raw = request
+            .args
+            .get("bead_id")
+            .ok_or_else(|| ParseError::MissingField {
+                field: "bead_id".to_string(),
+            })?;
+        let bead_id = raw.as_str().ok_or_else(|| ParseError::InvalidType {
+            field: "bead_id".to_string(),
+            expected: "string".to_string(),
+            got: json_value_type_name(raw).to_string(),
+        })?;
+        if bead_id.trim().is_empty() {
+            return Err(ParseError::InvalidValue {
+                field: "bead_id".to_string(),
+                value: "must not be empty".to_string(),
+            });
+        }
+
+        Ok(Self {
+            bead_id: bead_id.trim().to_string(),
+            skill: request
+                .args
+                .get("skill")
+                .and_then(Value::as_str)
+                .map(std::string::ToString::to_string),
+        })
+    }
+}
+
+impl ParseInput for crate::ContextBudgetInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            max_bytes: parse_optional_non_negative_u64(request, "max_bytes")?,
+            max_tokens: parse_optional_non_negative_u64(request, "max_tokens")?,
+        })
+    }
+}
+
+impl ParseInput for crate::RecordSymbolsInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let raw = request
+            .args
+            .get("bead_id")
+            .ok_or_else(|| ParseError::MissingField {
+                field: "bead_id".to_string(),
+            })?;
+        let bead_id = raw.as_str().ok_or_else(|| ParseError::InvalidType {
+            field: "bead_id".to_string(),
+            expected: "string".to_string(),
+            got: json_value_type_name(raw).to_string(),
+        })?;
+        if bead_id.trim().is_empty() {
+            return Err(ParseError::InvalidValue {
+                field: "bead_id".to_string(),
+                value: "must not be empty".to_string(),
+            });
+        }
+        if request.args.get("attempt").and_then(Value::as_u64) == Some(0) {
+            return Err(ParseError::InvalidValue {
+                field: "attempt".to_string(),
+                value: "must be greater than 0".to_string(),
+            });
+        }
+
+        let symbols = request.args.get("symbols").cloned();
+        if let Some(value) = symbols
+            .as_ref()
+            .filter(|value| !value.is_array() && !value.is_object())
+        {
+            return Err(ParseError::InvalidType {
+                field: "symbols".to_string(),
+                expected: "array".to_string(),
+                got: json_value_type_name(value).to_string(),
+            });
+        }
+
+        Ok(Self {
+            bead_id: bead_id.trim().to_string(),
+            agent_id: parse_optional_non_negative_u32(request, "agent_id")?,
+            attempt: parse_optional_non_negative_u32(request, "attempt")?,
+            symbols,
+            file: request
+                .args
+                .get("file")
+                .and_then(Value::as_str)
+                .map(std::string::ToString::to_string),
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::QuarantineInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            agent_id: parse_required_agent_id(request)?,
+            reason: parse_optional_non_empty_str(request, "reason")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::UnquarantineInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            agent_id: parse_required_agent_id(request)?,
+            reason: parse_required_non_empty_str(request, "reason")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::ReplayInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+        })
+    }
+}
+
+impl ParseInput for crate::ReplayTraceInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            file: parse_required_non_empty_str(request, "file")?,
+            ignore: parse_optional_str_list(request, "ignore")?.unwrap_or_default(),
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::VerifyInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+        })
+    }
+}
+
+impl ParseInput for crate::AttestInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+        })
+    }
+}
+
+impl ParseInput for crate::EnvDiffInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let attempt = |field: &str| match parse_optional_non_negative_u32(request, field)? {
+            None => Err(ParseError::MissingField {
+                field: field.to_string(),
+            }),
+            Some(0) => Err(ParseError::InvalidValue {
+                field: field.to_string(),
+                value: "attempts start at 1".to_string(),
+            }),
+            Some(attempt) => Ok(attempt),
+        };
+        let stage = parse_optional_non_empty_str(request, "stage")?
+            .map(|stage| {
+                crate::types::Stage::try_from(stage.as_str()).map_err(|value| {
+                    ParseError::InvalidValue {
+                        field: "stage".to_string(),
+                        value,
+                    }
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            stage,
+            from_attempt: attempt("from_attempt")?,
+            to_attempt: attempt("to_attempt")?,
+        })
+    }
+}
+
+impl ParseInput for crate::EnqueueInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let beads = request.args.get("beads").cloned();
+        if let Some(value) = beads
+            .as_ref()
+            .filter(|value| !value.is_array() && !value.is_string())
+        {
+            return Err(ParseError::InvalidType {
+                field: "beads".to_string(),
+                expected: "array".to_string(),
+                got: json_value_type_name(value).to_string(),
+            });
+        }
+
+        Ok(Self {
+            beads,
+            file: parse_optional_non_empty_str(request, "file")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::SyncBacklogInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let rounds = parse_optional_non_negative_u32(request, "rounds")?;
+        if rounds == Some(0) {
+            return Err(ParseError::InvalidValue {
+                field: "rounds".to_string(),
+                value: "must be greater than 0".to_string(),
+            });
+        }
+        Ok(Self {
+            rounds,
+            interval_secs: parse_optional_non_negative_u32(request, "interval_secs")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::ReserveClaimInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let ttl_secs = parse_optional_non_negative_u32(request, "ttl_secs")?;
+        if let Some(ttl) = ttl_secs.filter(|ttl| *ttl == 0 || *ttl > MAX_RESERVATION_TTL_SECS) {
+            return Err(ParseError::InvalidValue {
+                field: "ttl_secs".to_string(),
+                value: format!("{ttl} (expected 1-{MAX_RESERVATION_TTL_SECS})"),
+            });
+        }
+        Ok(Self {
+            agent_id: parse_required_agent_id(request)?,
+            ttl_secs,
+            labels: parse_optional_labels(request, "label")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::LabeledClaimInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let labels = parse_optional_labels(request, "label")?
+            .filter(|labels| !labels.is_empty())
+            .ok_or_else(|| ParseError::MissingField {
+                field: "label".to_string(),
+            })?;
+        Ok(Self {
+            agent_id: parse_required_agent_id(request)?,
+            labels,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::AgentLabelsInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !LABELS_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", LABELS_ACTIONS.join(", ")),
+            });
+        }
+        let agent_id = parse_optional_agent_field(request, "agent_id")?;
+        let labels = parse_optional_labels(request, "labels")?;
+        let labels = if action == "set" {
+            if agent_id.is_none() {
+                return Err(ParseError::MissingField {
+                    field: "agent_id".to_string(),
+                });
+            }
+            labels.ok_or_else(|| ParseError::MissingField {
+                field: "labels".to_string(),
+            })?
+        } else {
+            labels.unwrap_or_default()
+        };
+        Ok(Self {
+            action,
+            agent_id,
+            labels,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+/// Labels given as an array of strings or one comma-separated string, in
+/// order and without repeats. An empty string is an empty list.
+fn parse_optional_labels(
+    request: &ProtocolRequest,
+    field: &str,
+) -> Result<Option<Vec<String>>, ParseError> {
+    let Some(labels) = parse_optional_str_list(request, field)? else {
+        return Ok(None);
+    };
+    if let Some(label) = labels.iter().find(|label| !is_valid_claim_label(label)) {
+        return Err(ParseError::InvalidValue {
+            field: field.to_string(),
+            value: format!(
+                "{label} (labels are 1-{} bytes without whitespace)",
+                crate::types::MAX_CLAIM_LABEL_BYTES
+            ),
+        });
+    }
+    Ok(Some(labels))
+}
+
+/// An array of strings or one comma-separated string, trimmed, in order and
+/// without repeats or blanks.
+fn parse_optional_str_list(
+    request: &ProtocolRequest,
+    field: &str,
+) -> Result<Option<Vec<String>>, ParseError> {
+    let Some(raw) = request.args.get(field) else {
+        return Ok(None);
+    };
+    let invalid_type = || ParseError::InvalidType {
+        field: field.to_string(),
+        expected: "string or array of strings".to_string(),
+        got: json_value_type_name(raw).to_string(),
+    };
+    let parts = match raw {
+        Value::String(text) => text.split(',').map(str::to_string).collect::<Vec<_>>(),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| item.as_str().map(str::to_string).ok_or_else(invalid_type))
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => return Err(invalid_type()),
+    };
+    let mut items: Vec<String> = Vec::with_capacity(parts.len());
+    for item in parts
+        .iter()
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+    {
+        if !items.iter().any(|known| known == item) {
+            items.push(item.to_string());
+        }
+    }
+    Ok(Some(items))
+}
+
+impl ParseInput for crate::BacklogInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !BACKLOG_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", BACKLOG_ACTIONS.join(", ")),
+            });
+        }
+        let bead_ids = parse_optional_str_list(request, "bead_id")?.unwrap_or_default();
+        let labels = parse_optional_labels(request, "label")?.unwrap_or_default();
+        if labels.len() > 1 {
+            return Err(ParseError::InvalidValue {
+                field: "label".to_string(),
+                value: format!("{} (expected one label)", labels.join(",")),
+            });
+        }
+        let label = labels.into_iter().next();
+        let missing = match action.as_str() {
+            "set-priority" if bead_ids.is_empty() => Some("bead_id"),
+            "bump" if label.is_none() => Some("label"),
+            "remove" if bead_ids.is_empty() && label.is_none() => Some("bead_id or label"),
+            _ => None,
+        };
+        if let Some(field) = missing {
+            return Err(ParseError::MissingField {
+                field: field.to_string(),
+            });
+        }
+
+        let priority = if matches!(action.as_str(), "preview" | "remove") {
+            None
+        } else {
+            let field = if action == "bump" { "to" } else { "priority" };
+            let raw = request
+                .args
+                .get(field)
+                .ok_or_else(|| ParseError::MissingField {
+                    field: field.to_string(),
+                })?;
+            Some(crate::types::normalize_priority(raw).map_err(|value| {
+                ParseError::InvalidValue {
+                    field: field.to_string(),
+                    value,
+                }
+            })?)
+        };
+
+        Ok(Self {
+            action,
+            selector: crate::types::BacklogSelector { bead_ids, label },
+            priority,
+            limit: parse_optional_non_negative_u32(request, "limit")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::ClaimDecisionInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            agent_id: parse_required_agent_id(request)?,
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            reason: parse_optional_non_empty_str(request, "reason")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::CancelInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            reason: parse_optional_non_empty_str(request, "reason")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::RecoverInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::ReportUsageInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let tokens = |field: &str| {
+            parse_optional_non_negative_u64(request, field)?.ok_or_else(|| {
+                ParseError::MissingField {
+                    field: field.to_string(),
+                }
+            })
+        };
+        let stage = parse_optional_non_empty_str(request, "stage")?
+            .map(|stage| {
+                crate::types::Stage::try_from(stage.as_str()).map_err(|value| {
+                    ParseError::InvalidValue {
+                        field: "stage".to_string(),
+                        value,
+                    }
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            agent_id: parse_required_agent_id(request)?,
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            stage,
+            model: parse_required_non_empty_str(request, "model")?,
+            prompt_tokens: tokens("prompt_tokens")?,
+            completion_tokens: tokens("completion_tokens")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::ReportCoverageInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let stage = parse_optional_non_empty_str(request, "stage")?
+            .map(|stage| {
+                crate::types::Stage::try_from(stage.as_str()).map_err(|value| {
+                    ParseError::InvalidValue {
+                        field: "stage".to_string(),
+                        value,
+                    }
+                })
+            })
+            .transpose()?;
+        let format = parse_optional_non_empty_str(request, "format")?
+            .map(|format| {
+                crate::types::CoverageFormat::try_from(format.as_str()).map_err(|value| {
+                    ParseError::InvalidValue {
+                        field: "format".to_string(),
+                        value,
+                    }
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            agent_id: parse_required_agent_id(request)?,
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            stage,
+            path: parse_optional_non_empty_str(request, "path")?,
+            report: parse_optional_non_empty_str(request, "report")?,
+            format,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::CostsInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let (since, until) = parse_time_range(request)?;
+        let pricing = parse_optional_object(request, "pricing")?
+            .map(|object| {
+                serde_json::from_value::<crate::types::CostPricing>(Value::Object(object))
+                    .map_err(|error| error.to_string())
+                    .and_then(|pricing| pricing.validate().map(|()| pricing))
+                    .map_err(|value| ParseError::InvalidValue {
+                        field: "pricing".to_string(),
+                        value,
+                    })
+            })
+            .transpose()?;
+        Ok(Self {
+            bead_id: request
+                .args
+                .get("bead_id")
+                .and_then(Value::as_str)
+                .map(std::string::ToString::to_string),
+            since,
+            until,
+            pricing,
+        })
+    }
+}
+
+impl ParseInput for crate::RunInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let max_cycles = parse_optional_non_negative_u32(request, "max_cycles")?;
+        if max_cycles == Some(0) {
+            return Err(ParseError::InvalidValue {
+                field: "max_cycles".to_string(),
+                value: "must be at least 1".to_string(),
+            });
+        }
+        Ok(Self {
+            id: parse_optional_non_negative_u32(request, "id")?,
+            until_empty: request.args.get("until_empty").and_then(Value::as_bool),
+            budget_ms: parse_optional_non_negative_u64(request, "budget_ms")?,
+            max_cycles,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::RunAllInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let concurrency = parse_optional_non_negative_u32(request, "concurrency")?;
+        if concurrency.is_some_and(|value| !(1..=MAX_RUN_ALL_CONCURRENCY).contains(&value)) {
+            return Err(ParseError::InvalidValue {
+                field: "concurrency".to_string(),
+                value: format!("must be between 1 and {MAX_RUN_ALL_CONCURRENCY}"),
+            });
+        }
+        Ok(Self {
+            concurrency,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::TakeoverInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let consent = request.args.get("consent").and_then(Value::as_bool);
+        let bead_id = parse_required_non_empty_str(request, "bead_id")?;
+        let (to_agent, from_agent) = if consent == Some(true) {
+            if request.args.contains_key("to_agent") {
+                return Err(ParseError::InvalidValue {
+                    field: "to_agent".to_string(),
+                    value: "not allowed with consent".to_string(),
+                });
+            }
+            (
+                None,
+                Some(parse_required_agent_field(request, "from_agent")?),
+            )
+        } else {
+            (
+                Some(parse_required_agent_field(request, "to_agent")?),
+                parse_optional_agent_field(request, "from_agent")?,
+            )
+        };
+        Ok(Self {
+            bead_id,
+            to_agent,
+            from_agent,
+            consent,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::EventsInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !EVENTS_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", EVENTS_ACTIONS.join(", ")),
+            });
+        }
+        Ok(Self {
+            action,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::ChaosInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !CHAOS_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", CHAOS_ACTIONS.join(", ")),
+            });
+        }
+        Ok(Self { action })
+    }
+}
+
+impl ParseInput for crate::SyncInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !SYNC_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", SYNC_ACTIONS.join(", ")),
+            });
+        }
+        Ok(Self {
+            action,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::BeadInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !BEAD_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", BEAD_ACTIONS.join(", ")),
+            });
+        }
+        let bead_id = parse_optional_non_empty_str(request, "bead_id")?;
+        if action == "snapshot" && bead_id.is_none() {
+            return Err(ParseError::MissingField {
+                field: "bead_id".to_string(),
+            });
+        }
+        let agent_id = parse_optional_non_negative_u32(request, "agent_id")?;
+        if agent_id == Some(0) {
+            return Err(ParseError::InvalidValue {
+                field: "agent_id".to_string(),
+                value: "must be greater than 0".to_string(),
+            });
+        }
+        let snapshot = request.args.get("snapshot").cloned();
+        if let Some(value) = snapshot.as_ref().filter(|value| !value.is_object()) {
+            return Err(ParseError::InvalidType {
+                field: "snapshot".to_string(),
+                expected: "object".to_string(),
+                got: json_value_type_name(value).to_string(),
+            });
+        }
+
+        Ok(Self {
+            action,
+            bead_id,
+            agent_id,
+            snapshot,
+            file: parse_optional_non_empty_str(request, "file")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+fn parse_required_agent_id(request: &ProtocolRequest) -> Result<u32, ParseError> {
+    parse_required_agent_field(request, "agent_id")
+}
+
+fn parse_required_agent_field(request: &ProtocolRequest, field: &str) -> Result<u32, ParseError> {
+    parse_optional_agent_field(request, field)?.ok_or_else(|| ParseError::MissingField {
+        field: field.to_string(),
+    })
+}
+
+fn parse_optional_agent_field(
+    request: &ProtocolRequest,
+    field: &str,
+) -> Result<Option<u32>, ParseError> {
+    match parse_optional_non_negative_u32(request, field)? {
+        Some(0) => Err(ParseError::InvalidValue {
+            field: field.to_string(),
+            value: "must be greater than 0".to_string(),
+        }),
+        agent_id => Ok(agent_id),
+    }
+}
+
+fn parse_optional_non_empty_str(
+    request: &ProtocolRequest,
+    field: &str,
+) -> Result<Option<String>, ParseError> {
+    let Some(raw) = request.args.get(field) else {
+        return Ok(None);
+    };
+    let value = raw.as_str().ok_or_else(|| ParseError::InvalidType {
+        field: field.to_string(),
+        expected: "string".to_string(),
+        got: json_value_type_name(raw).to_string(),
+    })?;
+    if value.trim().is_empty() {
+        return Err(ParseError::InvalidValue {
+            field: field.to_string(),
+            value: "must not be empty".to_string(),
+        });
+    }
+    Ok(Some(value.trim().to_string()))
+}
+
+fn parse_required_non_empty_str(
+    request: &ProtocolRequest,
+    field: &str,
+) -> Result<String, ParseError> {
+    parse_optional_non_empty_str(request, field)?.ok_or_else(|| ParseError::MissingField {
+        field: field.to_string(),
+    })
+}
+
+impl ParseInput for crate::LandInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let change_id = parse_required_non_empty_str(request, "change_id")?;
+        if !crate::landing::is_valid_change_id(&change_id) {
+            return Err(ParseError::InvalidValue {
+                field: "change_id".to_string(),
+                value: format!(
+                    "must be at least {} alphanumeric characters",
+                    crate::landing::MIN_CHANGE_ID_LEN
+                ),
+            });
+        }
+        let remote = parse_optional_non_empty_str(request, "remote")?;
+        if let Some(remote) = remote.as_deref() {
+            if !crate::landing::is_valid_remote_name(remote) {
+                return Err(ParseError::InvalidValue {
+                    field: "remote".to_string(),
+                    value: format!(
+                        "{remote} (must not start with '-' and only use A-Z a-z 0-9 . _ / -)"
+                    ),
+                });
+            }
+        }
+        let override_push_check = request
+            .args
+            .get("override_push_check")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let reason = parse_optional_non_empty_str(request, "reason")?;
+        if override_push_check && reason.is_none() {
+            return Err(ParseError::MissingField {
+                field: "reason".to_string(),
+            });
+        }
+
+        Ok(Self {
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            agent_id: parse_required_agent_id(request)?,
+            change_id,
+            remote,
+            override_push_check,
+            reason,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::WorkspaceInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_optional_non_empty_str(request, "action")?;
+        if let Some(action) = action.as_deref() {
+            if !WORKSPACE_ACTIONS.contains(&action) {
+                return Err(ParseError::InvalidValue {
+                    field: "action".to_string(),
+                    value: format!(
+                        "{action} (expected one of {})",
+                        WORKSPACE_ACTIONS.join(", ")
+                    ),
+                });
+            }
+        }
+
+        Ok(Self {
+            agent_id: parse_required_agent_id(request)?,
+            action,
+            bead_id: parse_optional_non_empty_str(request, "bead_id")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::SessionInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !SESSION_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", SESSION_ACTIONS.join(", ")),
+            });
+        }
+
+        Ok(Self {
+            action,
+            agent_id: parse_required_agent_id(request)?,
+            host: parse_optional_non_empty_str(request, "host")?,
+            pid: parse_optional_non_negative_u32(request, "pid")?,
+            version: parse_optional_non_empty_str(request, "version")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::KvInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !KV_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", KV_ACTIONS.join(", ")),
+            });
+        }
+        let key = parse_optional_non_empty_str(request, "key")?;
+        if key.is_none() && action != "get" {
+            return Err(ParseError::MissingField {
+                field: "key".to_string(),
+            });
+        }
+        if let Some(key) = key.as_deref().filter(|key| key.len() > MAX_KV_KEY_BYTES) {
+            return Err(ParseError::InvalidValue {
+                field: "key".to_string(),
+                value: format!("{} bytes (at most {MAX_KV_KEY_BYTES} allowed)", key.len()),
+            });
+        }
+        let value = request.args.get("value").cloned();
+        if action == "set" && value.is_none() {
+            return Err(ParseError::MissingField {
+                field: "value".to_string(),
+            });
+        }
+        if let Some(size) = value
+            .as_ref()
+            .map(|value| value.to_string().len())
+            .filter(|size| *size > MAX_KV_VALUE_BYTES)
+        {
+            return Err(ParseError::InvalidValue {
+                field: "value".to_string(),
+                value: format!("{size} bytes (at most {MAX_KV_VALUE_BYTES} allowed)"),
+            });
+        }
+
+        Ok(Self {
+            action,
+            agent_id: parse_required_agent_id(request)?,
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            key,
+            value,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::BlackboardInput {
     type Input = Self;
 
     fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !BLACKBOARD_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!(
+                    "{action} (expected one of {})",
+                    BLACKBOARD_ACTIONS.join(", ")
+                ),
+            });
+        }
+        let section = parse_optional_non_empty_str(request, "section")?
+            .map(|section| {
+                BlackboardSection::try_from(section.as_str()).map_err(|_| {
+                    ParseError::InvalidValue {
+                        field: "section".to_string(),
+                        value: format!(
+                            "{section} (expected one of plan, decisions, open_questions)"
+                        ),
+                    }
+                })
+            })
+            .transpose()?;
+        let agent_id = parse_optional_agent_field(request, "agent_id")?;
+        let content = parse_optional_non_empty_str(request, "content")?;
+        let expected_version = parse_optional_non_negative_i64(request, "expected_version")?;
+        if action == "append" {
+            let missing = [
+                ("agent_id", agent_id.is_none()),
+                ("section", section.is_none()),
+                ("content", content.is_none()),
+                ("expected_version", expected_version.is_none()),
+            ]
+            .into_iter()
+            .find_map(|(field, missing)| missing.then_some(field));
+            if let Some(field) = missing {
+                return Err(ParseError::MissingField {
+                    field: field.to_string(),
+                });
+            }
+        }
+        if let Some(size) = content
+            .as_ref()
+            .map(String::len)
+            .filter(|size| *size > MAX_BLACKBOARD_ENTRY_BYTES)
+        {
+            return Err(ParseError::InvalidValue {
+                field: "content".to_string(),
+                value: format!("{size} bytes (at most {MAX_BLACKBOARD_ENTRY_BYTES} allowed)"),
+            });
+        }
+
         Ok(Self {
+            action,
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            agent_id,
+            section,
+            content,
+            expected_version,
             dry: request.args.get("dry").and_then(Value::as_bool),
         })
     }
 }
 
-impl ParseInput for crate::SpawnPromptsInput {
+impl ParseInput for crate::ApproveInput {
     type Input = Self;
 
     fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
         Ok(Self {
-            template: request
-                .args
-                .get("template")
-                .and_then(Value::as_str)
-                .map(std::string::ToString::to_string),
-            out_dir: request
-                .args
-                .get("out_dir")
-                .and_then(Value::as_str)
-                .map(std::string::ToString::to_string),
-            count: request
-                .args
-                .get("count")
-                .and_then(Value::as_u64)
-                .and_then(|value| u32::try_from(value).ok()),
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            token: parse_required_non_empty_str(request, "token")?,
+            approver: parse_optional_non_empty_str(request, "approver")?,
+            note: parse_optional_non_empty_str(request, "note")?,
             dry: request.args.get("dry").and_then(Value::as_bool),
         })
     }
 }
 
-impl ParseInput for crate::PromptInput {
+impl ParseInput for crate::ReissueApprovalInput {
     type Input = Self;
 
     fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
-        let id = match request.args.get("id") {
-            None => 1,
-            Some(raw) => {
-                if raw.as_i64().is_some_and(|value| value <= 0) {
+        let gate = parse_optional_non_empty_str(request, "gate")?
+            .map(|gate| {
+                ApprovalGate::try_from(gate.as_str()).map_err(|error| ParseError::InvalidValue {
+                    field: "gate".to_string(),
+                    value: error,
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            gate,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::TenantInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !TENANT_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", TENANT_ACTIONS.join(", ")),
+            });
+        }
+        let name = parse_optional_non_empty_str(request, "name")?;
+        if action != "list" {
+            let name = name.as_deref().ok_or_else(|| ParseError::MissingField {
+                field: "name".to_string(),
+            })?;
+            crate::types::tenant_schema(name).map_err(|error| ParseError::InvalidValue {
+                field: "name".to_string(),
+                value: error,
+            })?;
+        }
+        let confirm = parse_optional_non_empty_str(request, "confirm")?;
+        if action == "delete" && confirm != name {
+            return Err(ParseError::InvalidValue {
+                field: "confirm".to_string(),
+                value: "must repeat the tenant name to delete it".to_string(),
+            });
+        }
+        Ok(Self {
+            action,
+            name,
+            seed_agents: parse_optional_non_negative_u32(request, "seed_agents")?,
+            confirm,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::LocalDbInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !LOCALDB_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", LOCALDB_ACTIONS.join(", ")),
+            });
+        }
+        let container_name = parse_optional_non_empty_str(request, "container_name")?;
+        let to = parse_optional_non_negative_u32(request, "to")?;
+        if action == "upgrade" && to.is_none() {
+            return Err(ParseError::MissingField {
+                field: "to".to_string(),
+            });
+        }
+        let confirm = parse_optional_non_empty_str(request, "confirm")?;
+        let container = container_name
+            .as_deref()
+            .unwrap_or(DEFAULT_LOCAL_DB_CONTAINER);
+        if action == "destroy" && confirm.as_deref() != Some(container) {
+            return Err(ParseError::InvalidValue {
+                field: "confirm".to_string(),
+                value: "must repeat the container name to destroy it".to_string(),
+            });
+        }
+        Ok(Self {
+            action,
+            container_name,
+            to,
+            tail: parse_optional_non_negative_u32(request, "tail")?,
+            purge_volume: request.args.get("purge_volume").and_then(Value::as_bool),
+            confirm,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::ArtifactInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !ARTIFACT_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", ARTIFACT_ACTIONS.join(", ")),
+            });
+        }
+        let stage = parse_required_non_empty_str(request, "stage")?;
+        let stage = crate::types::Stage::try_from(stage.as_str()).map_err(|value| {
+            ParseError::InvalidValue {
+                field: "stage".to_string(),
+                value,
+            }
+        })?;
+        let artifact_type = parse_required_non_empty_str(request, "type")?;
+        let artifact_type = ArtifactType::try_from(artifact_type.as_str()).map_err(|value| {
+            ParseError::InvalidValue {
+                field: "type".to_string(),
+                value: format!(
+                    "{value} (expected one of {})",
+                    ArtifactType::names().join(", ")
+                ),
+            }
+        })?;
+        Ok(Self {
+            action,
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            stage,
+            artifact_type,
+            file: parse_required_non_empty_str(request, "file")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::DiffInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !DIFF_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", DIFF_ACTIONS.join(", ")),
+            });
+        }
+        let stage = parse_optional_non_empty_str(request, "stage")?
+            .map(|stage| {
+                crate::types::Stage::try_from(stage.as_str()).map_err(|value| {
+                    ParseError::InvalidValue {
+                        field: "stage".to_string(),
+                        value,
+                    }
+                })
+            })
+            .transpose()?;
+        let file = parse_optional_non_empty_str(request, "file")?;
+        let patch = parse_optional_non_empty_str(request, "patch")?;
+        if action == "put" {
+            match (&file, &patch) {
+                (None, None) => {
+                    return Err(ParseError::MissingField {
+                        field: "file or patch".to_string(),
+                    })
+                }
+                (Some(_), Some(_)) => {
                     return Err(ParseError::InvalidValue {
-                        field: "id".to_string(),
-                        value: "must be greater than 0".to_string(),
-                    });
+                        field: "patch".to_string(),
+                        value: "pass either file or patch, not both".to_string(),
+                    })
                 }
+                _ => {}
+            }
+        } else if patch.is_some() {
+            return Err(ParseError::InvalidValue {
+                field: "patch".to_string(),
+                value: "only diff put takes a patch".to_string(),
+            });
+        }
+        Ok(Self {
+            action,
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            stage,
+            file,
+            patch,
+            base_revision: parse_optional_non_empty_str(request, "base_revision")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
 
-                let id_as_u64 = raw.as_u64().ok_or_else(|| ParseError::InvalidType {
-                    field: "id".to_string(),
-                    expected: "u32".to_string(),
-                    got: json_value_type_name(raw).to_string(),
-                })?;
+impl ParseInput for crate::ConfigInput {
+    type Input = Self;
 
-                let id = u32::try_from(id_as_u64).map_err(|_| ParseError::InvalidValue {
-                    field: "id".to_string(),
-                    value: format!("{id_as_u64} exceeds max u32"),
-                })?;
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !CONFIG_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", CONFIG_ACTIONS.join(", ")),
+            });
+        }
+        let key = parse_optional_non_empty_str(request, "key")?
+            .map(|key| {
+                ConfigKey::try_from(key.as_str()).map_err(|value| ParseError::InvalidValue {
+                    field: "key".to_string(),
+                    value,
+                })
+            })
+            .transpose()?;
 
-                if id == 0 {
+        let mut changes = Vec::new();
+        if action == "set" {
+            if let Some(key) = key {
+                let raw = request
+                    .args
+                    .get("value")
+                    .ok_or_else(|| ParseError::MissingField {
+                        field: "value".to_string(),
+                    })?;
+                changes.push(parse_config_value(key, "value", raw)?);
+            }
+            for key in ConfigKey::ALL {
+                if let Some(raw) = request.args.get(key.as_str()) {
+                    changes.push(parse_config_value(key, key.as_str(), raw)?);
+                }
+            }
+            if changes.is_empty() {
+                return Err(ParseError::MissingField {
+                    field: "key and value, or a setting such as max_agents".to_string(),
+                });
+            }
+        }
+
+        Ok(Self {
+            action,
+            key,
+            changes,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+/// A config value given as a JSON string or number, checked for `key`.
+fn parse_config_value(
+    key: ConfigKey,
+    field: &str,
+    raw: &Value,
+) -> Result<crate::types::ConfigChange, ParseError> {
+    let text = match raw {
+        Value::String(text) => text.clone(),
+        Value::Number(number) => number.to_string(),
+        other => {
+            return Err(ParseError::InvalidType {
+                field: field.to_string(),
+                expected: "string or number".to_string(),
+                got: json_value_type_name(other).to_string(),
+            })
+        }
+    };
+    key.parse_value(&text)
+        .map_err(|value| ParseError::InvalidValue {
+            field: field.to_string(),
+            value,
+        })
+}
+
+impl ParseInput for crate::ExplainTransitionInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let stage = parse_required_non_empty_str(request, "stage")?;
+        let stage = crate::types::Stage::try_from(stage.as_str()).map_err(|value| {
+            ParseError::InvalidValue {
+                field: "stage".to_string(),
+                value,
+            }
+        })?;
+        let result = parse_required_non_empty_str(request, "result")?;
+        if !TRANSITION_RESULTS.contains(&result.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "result".to_string(),
+                value: format!(
+                    "{result} (expected one of {})",
+                    TRANSITION_RESULTS.join(", ")
+                ),
+            });
+        }
+        let max_attempts = parse_optional_non_negative_u32(request, "max_attempts")?;
+        if max_attempts == Some(0) {
+            return Err(ParseError::InvalidValue {
+                field: "max_attempts".to_string(),
+                value: "must be at least 1".to_string(),
+            });
+        }
+        Ok(Self {
+            stage,
+            result,
+            attempt: parse_optional_non_negative_u32(request, "attempt")?,
+            max_attempts,
+            category: parse_optional_non_empty_str(request, "category")?,
+            message: parse_optional_non_empty_str(request, "message")?,
+        })
+    }
+}
+
+impl ParseInput for crate::UndeleteInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let table = parse_optional_non_empty_str(request, "table")?
+            .map(|table| {
+                SoftDeleteTable::try_from(table.as_str()).map_err(|error| {
+                    ParseError::InvalidValue {
+                        field: "table".to_string(),
+                        value: error,
+                    }
+                })
+            })
+            .transpose()?;
+        let key = parse_optional_non_empty_str(request, "key")?;
+        if key.is_some() && table.is_none() {
+            return Err(ParseError::MissingField {
+                field: "table".to_string(),
+            });
+        }
+        Ok(Self {
+            table,
+            key,
+            limit: parse_optional_non_negative_u32(request, "limit")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::ReviewInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !REVIEW_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", REVIEW_ACTIONS.join(", ")),
+            });
+        }
+        let verdict = parse_optional_non_empty_str(request, "verdict")?
+            .map(|verdict| {
+                ReviewVerdict::try_from(verdict.as_str()).map_err(|_| ParseError::InvalidValue {
+                    field: "verdict".to_string(),
+                    value: format!("{verdict} (expected one of approve, changes)"),
+                })
+            })
+            .transpose()?;
+        let agent_id = parse_optional_agent_field(request, "agent_id")?;
+        let reviewer = parse_optional_non_empty_str(request, "reviewer")?;
+        if action == "submit" {
+            if verdict.is_none() {
+                return Err(ParseError::MissingField {
+                    field: "verdict".to_string(),
+                });
+            }
+            match (agent_id, reviewer.as_deref()) {
+                (None, None) => {
+                    return Err(ParseError::MissingField {
+                        field: "agent_id or reviewer".to_string(),
+                    });
+                }
+                (Some(_), Some(reviewer)) => {
                     return Err(ParseError::InvalidValue {
-                        field: "id".to_string(),
-                        value: "must be greater than 0".to_string(),
+                        field: "reviewer".to_string(),
+                        value: format!("{reviewer} (give agent_id or reviewer, not both)"),
                     });
                 }
-
-                id
+                _ => {}
             }
-        };
+        }
+        let comments = parse_optional_non_empty_str(request, "comments")?;
+        if let Some(size) = comments
+            .as_ref()
+            .map(String::len)
+            .filter(|size| *size > MAX_REVIEW_COMMENT_BYTES)
+        {
+            return Err(ParseError::InvalidValue {
+                field: "comments".to_string(),
+                value: format!("{size} bytes (at most {MAX_REVIEW_COMMENT_BYTES} allowed)"),
+            });
+        }
 
         Ok(Self {
-            id,
-            skill: request
-                .args
-                .get("skill")
-                .and_then(Value::as_str)
-                .map(std::string::ToString::to_string),
+            action,
+            bead_id: parse_required_non_empty_str(request, "bead_id")?,
+            verdict,
+            comments,
+            agent_id,
+            reviewer,
+            dry: request.args.get("dry").and_then(Value::as_bool),
         })
     }
 }
@@ -135,7 +1620,16 @@ impl ParseInput for crate::HistoryInput {
 
     fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
         let limit = parse_optional_non_negative_i64(request, "limit")?;
-        Ok(Self { limit })
+        let after_seq = parse_optional_non_negative_i64(request, "after_seq")?;
+        let page_size = parse_optional_non_negative_i64(request, "page_size")?;
+        let (since, until) = parse_time_range(request)?;
+        Ok(Self {
+            limit,
+            after_seq,
+            page_size,
+            since,
+            until,
+        })
     }
 }
 
@@ -173,6 +1667,9 @@ impl ParseInput for crate::LockInput {
             resource,
             agent,
             ttl_ms,
+            wait_ms: parse_optional_non_negative_u32(request, "wait_ms")?,
+            purpose: parse_optional_non_empty_str(request, "purpose")?,
+            bead_id: parse_optional_non_empty_str(request, "bead_id")?,
             dry: request.args.get("dry").and_then(Value::as_bool),
         })
     }
@@ -216,6 +1713,30 @@ impl ParseInput for crate::AgentsInput {
     }
 }
 
+impl ParseInput for crate::HealthzInput {
+    type Input = Self;
+
+    fn parse_input(_request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {})
+    }
+}
+
+impl ParseInput for crate::InvariantsInput {
+    type Input = Self;
+
+    fn parse_input(_request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {})
+    }
+}
+
+impl ParseInput for crate::LocksInput {
+    type Input = Self;
+
+    fn parse_input(_request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {})
+    }
+}
+
 impl ParseInput for crate::BroadcastInput {
     type Input = Self;
 
@@ -238,14 +1759,83 @@ impl ParseInput for crate::BroadcastInput {
             })?
             .to_string();
 
+        let topic = parse_optional_announcement_topic(request)?
+            .unwrap_or_else(|| DEFAULT_ANNOUNCEMENT_TOPIC.to_string());
+        let ttl_secs = parse_optional_non_negative_u64(request, "ttl_secs")?
+            .unwrap_or(DEFAULT_ANNOUNCEMENT_TTL_SECS);
+        if !(1..=MAX_ANNOUNCEMENT_TTL_SECS).contains(&ttl_secs) {
+            return Err(ParseError::InvalidValue {
+                field: "ttl_secs".to_string(),
+                value: format!("{ttl_secs} (expected 1..={MAX_ANNOUNCEMENT_TTL_SECS})"),
+            });
+        }
+
         Ok(Self {
             msg,
             from,
+            topic,
+            ttl_secs,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::AnnouncementsInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action =
+            parse_optional_non_empty_str(request, "action")?.unwrap_or_else(|| "list".to_string());
+        if !ANNOUNCEMENTS_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!(
+                    "{action} (expected one of {})",
+                    ANNOUNCEMENTS_ACTIONS.join(", ")
+                ),
+            });
+        }
+        let agent_id = parse_optional_agent_field(request, "agent_id")?;
+        if action == "ack" && agent_id.is_none() {
+            return Err(ParseError::MissingField {
+                field: "agent_id".to_string(),
+            });
+        }
+        let id = parse_optional_non_negative_i64(request, "id")?;
+        if id == Some(0) {
+            return Err(ParseError::InvalidValue {
+                field: "id".to_string(),
+                value: "0 (expected an announcement id from announcements list)".to_string(),
+            });
+        }
+        Ok(Self {
+            action,
+            agent_id,
+            id,
+            topic: parse_optional_announcement_topic(request)?,
             dry: request.args.get("dry").and_then(Value::as_bool),
         })
     }
 }
 
+fn parse_optional_announcement_topic(
+    request: &ProtocolRequest,
+) -> Result<Option<String>, ParseError> {
+    let topic = parse_optional_non_empty_str(request, "topic")?;
+    if let Some(topic) = topic
+        .as_deref()
+        .filter(|topic| !is_valid_announcement_topic(topic))
+    {
+        return Err(ParseError::InvalidValue {
+            field: "topic".to_string(),
+            value: format!(
+                "{topic} (expected 1-{MAX_ANNOUNCEMENT_TOPIC_BYTES} bytes without whitespace)"
+            ),
+        });
+    }
+    Ok(topic)
+}
+
 impl ParseInput for crate::LoadProfileInput {
     type Input = Self;
 
@@ -262,6 +1852,264 @@ impl ParseInput for crate::LoadProfileInput {
                 .and_then(Value::as_u64)
                 .and_then(|value| u32::try_from(value).ok()),
             timeout_ms: request.args.get("timeout_ms").and_then(Value::as_u64),
+            concurrency: request
+                .args
+                .get("concurrency")
+                .and_then(Value::as_u64)
+                .and_then(|value| u32::try_from(value).ok()),
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::DbHealthInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let samples = parse_optional_non_negative_u32(request, "samples")?;
+        if samples == Some(0) {
+            return Err(ParseError::InvalidValue {
+                field: "samples".to_string(),
+                value: "must be greater than 0".to_string(),
+            });
+        }
+        Ok(Self { samples })
+    }
+}
+
+impl ParseInput for crate::ForecastInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let window_hours = parse_optional_non_negative_u32(request, "window_hours")?;
+        if window_hours.is_some_and(|hours| !(1..=MAX_FORECAST_WINDOW_HOURS).contains(&hours)) {
+            return Err(ParseError::InvalidValue {
+                field: "window_hours".to_string(),
+                value: format!("must be between 1 and {MAX_FORECAST_WINDOW_HOURS}"),
+            });
+        }
+        Ok(Self {
+            window_hours,
+            agents: parse_optional_non_negative_u32(request, "agents")?,
+        })
+    }
+}
+
+impl ParseInput for crate::TopInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let window_mins = parse_optional_non_negative_u32(request, "window_mins")?;
+        if window_mins == Some(0) {
+            return Err(ParseError::InvalidValue {
+                field: "window_mins".to_string(),
+                value: "must be greater than 0".to_string(),
+            });
+        }
+        Ok(Self { window_mins })
+    }
+}
+
+impl ParseInput for crate::JobsInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action =
+            parse_optional_non_empty_str(request, "action")?.unwrap_or_else(|| "list".to_string());
+        if !JOBS_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", JOBS_ACTIONS.join(", ")),
+            });
+        }
+        let job = parse_optional_non_empty_str(request, "job")?
+            .map(|job| {
+                ScheduledJobKind::try_from(job.as_str()).map_err(|value| ParseError::InvalidValue {
+                    field: "job".to_string(),
+                    value,
+                })
+            })
+            .transpose()?;
+        if action != "list" && job.is_none() {
+            return Err(ParseError::MissingField {
+                field: "job".to_string(),
+            });
+        }
+        Ok(Self {
+            action,
+            job,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::ServeInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        Ok(Self {
+            budget_ms: parse_optional_non_negative_u64(request, "budget_ms")?,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::ProfileInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let duration_ms = parse_optional_non_negative_u64(request, "duration_ms")?;
+        if duration_ms.is_some_and(|value| !(1..=MAX_PROFILE_DURATION_MS).contains(&value)) {
+            return Err(ParseError::InvalidValue {
+                field: "duration_ms".to_string(),
+                value: format!("must be between 1 and {MAX_PROFILE_DURATION_MS}"),
+            });
+        }
+        Ok(Self {
+            duration_ms,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::PerfInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !PERF_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", PERF_ACTIONS.join(", ")),
+            });
+        }
+        let (since, until) = parse_time_range(request)?;
+        let baseline_days = parse_optional_non_negative_u32(request, "baseline_days")?;
+        if baseline_days.is_some_and(|days| !(1..=MAX_BASELINE_DAYS).contains(&days)) {
+            return Err(ParseError::InvalidValue {
+                field: "baseline_days".to_string(),
+                value: format!("must be between 1 and {MAX_BASELINE_DAYS}"),
+            });
+        }
+        Ok(Self {
+            action,
+            since: since.ok_or_else(|| ParseError::MissingField {
+                field: "since".to_string(),
+            })?,
+            until,
+            threshold_pct: parse_optional_non_negative_u32(request, "threshold_pct")?,
+            min_samples: parse_optional_non_negative_u64(request, "min_samples")?,
+            baseline_days,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::MaintenanceInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let target = parse_required_non_empty_str(request, "target")?;
+        if !MAINTENANCE_TARGETS.contains(&target.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "target".to_string(),
+                value: format!(
+                    "{target} (expected one of {})",
+                    MAINTENANCE_TARGETS.join(", ")
+                ),
+            });
+        }
+        let action =
+            parse_optional_non_empty_str(request, "action")?.unwrap_or_else(|| "list".to_string());
+        if !PARTITION_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!(
+                    "{action} (expected one of {})",
+                    PARTITION_ACTIONS.join(", ")
+                ),
+            });
+        }
+        let table = parse_optional_non_empty_str(request, "table")?
+            .map(|table| {
+                PartitionedTable::try_from(table.as_str()).map_err(|value| {
+                    ParseError::InvalidValue {
+                        field: "table".to_string(),
+                        value,
+                    }
+                })
+            })
+            .transpose()?;
+        let partition = parse_optional_non_empty_str(request, "partition")?;
+        if let Some(name) = partition.as_deref() {
+            let owner = PartitionedTable::of_partition(name);
+            if owner.is_none() || table.is_some_and(|table| Some(table) != owner) {
+                return Err(ParseError::InvalidValue {
+                    field: "partition".to_string(),
+                    value: format!(
+                        "{name} (expected <table>_legacy or <table>_pYYYY_MM{})",
+                        table.map_or_else(String::new, |table| format!(" of {table}"))
+                    ),
+                });
+            }
+        }
+        let ahead = parse_optional_non_negative_u32(request, "ahead")?;
+        if ahead.is_some_and(|months| !(1..=MAX_PARTITIONS_AHEAD).contains(&months)) {
+            return Err(ParseError::InvalidValue {
+                field: "ahead".to_string(),
+                value: format!("must be between 1 and {MAX_PARTITIONS_AHEAD}"),
+            });
+        }
+        match action.as_str() {
+            "enable" if table.is_none() => {
+                return Err(ParseError::MissingField {
+                    field: "table".to_string(),
+                })
+            }
+            "detach" | "drop" if partition.is_none() => {
+                return Err(ParseError::MissingField {
+                    field: "partition".to_string(),
+                })
+            }
+            _ => {}
+        }
+        Ok(Self {
+            target,
+            action,
+            table,
+            partition,
+            ahead,
+            dry: request.args.get("dry").and_then(Value::as_bool),
+        })
+    }
+}
+
+impl ParseInput for crate::NotifyInput {
+    type Input = Self;
+
+    fn parse_input(request: &ProtocolRequest) -> Result<Self::Input, ParseError> {
+        let action = parse_required_non_empty_str(request, "action")?;
+        if !NOTIFY_ACTIONS.contains(&action.as_str()) {
+            return Err(ParseError::InvalidValue {
+                field: "action".to_string(),
+                value: format!("{action} (expected one of {})", NOTIFY_ACTIONS.join(", ")),
+            });
+        }
+        let severity = parse_optional_non_empty_str(request, "severity")?
+            .map(|severity| {
+                NotificationSeverity::try_from(severity.as_str()).map_err(|value| {
+                    ParseError::InvalidValue {
+                        field: "severity".to_string(),
+                        value,
+                    }
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            action,
+            notifier: parse_optional_non_empty_str(request, "notifier")?,
+            event: parse_optional_non_empty_str(request, "event")?,
+            severity,
             dry: request.args.get("dry").and_then(Value::as_bool),
         })
     }
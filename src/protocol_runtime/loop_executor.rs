@@ -1,34 +1,377 @@
+use super::wire_format::{read_frame, write_envelope, RequestFrame, WireFormat};
+use super::FrameResponse;
+use crate::config::{database_connect_options_for_cli, database_url_candidates_for_cli};
+use crate::db::{PendingWrite, WriteBatcher, WriteBatcherConfig};
 use crate::protocol_envelope::ProtocolEnvelope;
 use crate::{code, SwarmError};
+use futures_util::stream::{FuturesUnordered, StreamExt};
 use serde_json::json;
-use std::time::Instant;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 
 /// # Errors
 /// Returns an error if stdin reading or stdout writing fails.
 pub async fn run_protocol_loop() -> std::result::Result<(), SwarmError> {
-    let stdin = BufReader::new(tokio::io::stdin());
-    let mut lines = stdin.lines();
+    run_protocol_loop_with_format(WireFormat::Json).await
+}
+
+/// Runs the protocol loop with requests and envelopes encoded as `format`.
+///
+/// Up to `SWARM_SESSION_CONCURRENCY` requests run at once; each envelope is
+/// written when its request finishes and carries the request's `rid`. The
+/// first failed request stops reading new ones, lets the running ones
+/// finish, and ends the loop with its error.
+///
+/// SIGINT or SIGTERM also stops reading. Running requests get
+/// `SWARM_SHUTDOWN_GRACE_MS` to finish, and each that does has its envelope
+/// written as usual, a failure included; any still running are then
+/// dropped, killing the external commands they started. The audit batch is
+/// flushed and a last `shutdown` envelope reports how many were drained.
+///
+/// # Errors
+/// Returns an error if stdin reading or stdout writing fails.
+pub async fn run_protocol_loop_with_format(
+    format: WireFormat,
+) -> std::result::Result<(), SwarmError> {
+    let settings = SessionSettings::from_env(format);
+    let (frames, reader) = spawn_frame_reader(tokio::io::stdin(), &settings);
+    let mut ports = DispatchPorts::default();
+    let outcome = drive_session(
+        &mut ports,
+        frames,
+        &mut tokio::io::stdout(),
+        shutdown_signal(),
+        settings,
+    )
+    .await;
+    reader.abort();
+    outcome
+}
+
+/// What a protocol session runs with, read from the environment.
+#[derive(Debug, Clone, Copy)]
+struct SessionSettings {
+    format: WireFormat,
+    concurrency: usize,
+    max_line_bytes: usize,
+    grace: Duration,
+}
+
+impl SessionSettings {
+    fn from_env(format: WireFormat) -> Self {
+        Self {
+            format,
+            concurrency: crate::config::session_concurrency_from_env(),
+            max_line_bytes: crate::config::request_limits_from_env().max_line_bytes,
+            grace: crate::config::shutdown_grace_from_env(),
+        }
+    }
+}
+
+type SessionFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// What a protocol session answers requests with. The loop dispatches them
+/// against the session's database; tests substitute requests that finish
+/// when told to.
+trait SessionPorts {
+    /// Runs once, before the first request that is not blank.
+    fn start(&mut self) -> SessionFuture<'_, ()>;
+    /// Answers one request without writing anything.
+    fn respond(&self, frame: RequestFrame) -> SessionFuture<'static, FrameResponse>;
+    /// Records the audit row of a request whose envelope was written.
+    fn record(&self, audit_write: PendingWrite) -> SessionFuture<'_, ()>;
+    /// Stops background work and flushes what is still queued.
+    fn finish(&mut self) -> SessionFuture<'_, ()>;
+}
+
+/// Dispatches requests, auditing them through a batcher on the session's
+/// database when one is reachable.
+#[derive(Default)]
+struct DispatchPorts {
+    audit_batcher: Option<WriteBatcher>,
+    recovery_scans: Option<JoinHandle<()>>,
+}
+
+impl SessionPorts for DispatchPorts {
+    fn start(&mut self) -> SessionFuture<'_, ()> {
+        Box::pin(async move {
+            if let Some((batcher, scans)) = start_session(
+                &database_url_candidates_for_cli(),
+                super::database_connect_timeout_ms(),
+            )
+            .await
+            {
+                self.audit_batcher = Some(batcher);
+                self.recovery_scans = Some(scans);
+            }
+        })
+    }
+
+    fn respond(&self, frame: RequestFrame) -> SessionFuture<'static, FrameResponse> {
+        Box::pin(super::respond_to_frame(frame))
+    }
+
+    fn record(&self, audit_write: PendingWrite) -> SessionFuture<'_, ()> {
+        Box::pin(async move {
+            let audit_batch = self.audit_batcher.as_ref().map(WriteBatcher::handle);
+            super::record_audit(audit_write, audit_batch.as_ref()).await;
+        })
+    }
+
+    fn finish(&mut self) -> SessionFuture<'_, ()> {
+        Box::pin(async move {
+            if let Some(scans) = self.recovery_scans.take() {
+                scans.abort();
+            }
+            if let Some(batcher) = self.audit_batcher.take() {
+                super::db_resolution::clear_session_write_batch();
+                if let Err(e) = batcher.shutdown().await {
+                    tracing::warn!(error = %e, "Audit trail recording failed");
+                }
+            }
+        })
+    }
+}
+
+/// Answers `frames` through `ports`, writing envelopes to `out` as their
+/// requests finish, until input ends, a request fails, or `signal`
+/// resolves.
+async fn drive_session<P, W>(
+    ports: &mut P,
+    mut frames: mpsc::Receiver<std::io::Result<RequestFrame>>,
+    out: &mut W,
+    signal: impl Future<Output = &'static str>,
+    settings: SessionSettings,
+) -> std::result::Result<(), SwarmError>
+where
+    P: SessionPorts,
+    W: AsyncWrite + Unpin,
+{
+    let mut in_flight = FuturesUnordered::new();
+    let mut accepting = true;
     let mut processed_non_empty_line = false;
+    let mut outcome = Ok(());
+    tokio::pin!(signal);
+    let mut shutdown: Option<Shutdown> = None;
+
+    loop {
+        let drain_deadline = shutdown.as_ref().map(|stop| stop.deadline);
+        // Finished requests are written before the deadline is checked, so a
+        // response that is ready when the grace runs out, failed or not, still
+        // reaches `out` instead of being counted as abandoned.
+        tokio::select! {
+            biased;
+            received = &mut signal, if shutdown.is_none() && (accepting || !in_flight.is_empty()) => {
+                accepting = false;
+                shutdown = Some(Shutdown::begin(received, in_flight.len(), settings.grace));
+            }
+            Some((envelope, audit_write, format)) = in_flight.next(), if !in_flight.is_empty() => {
+                let written = write_envelope(out, &envelope, format).await;
+                ports.record(audit_write).await;
+                if let Err(error) = written.and_then(|()| super::envelope_outcome(&envelope)) {
+                    if outcome.is_ok() {
+                        outcome = Err(error);
+                    }
+                    accepting = false;
+                }
+            }
+            () = sleep_until_deadline(drain_deadline), if drain_deadline.is_some() && !in_flight.is_empty() => {
+                break;
+            }
+            frame = frames.recv(), if accepting && in_flight.len() < settings.concurrency => {
+                let frame = match frame {
+                    Some(Ok(frame)) => frame,
+                    Some(Err(error)) => {
+                        outcome = Err(SwarmError::IoError(error));
+                        accepting = false;
+                        continue;
+                    }
+                    None => {
+                        accepting = false;
+                        continue;
+                    }
+                };
+                if frame.is_blank() {
+                    continue;
+                }
 
-    while let Some(line) = lines.next_line().await.map_err(SwarmError::IoError)? {
-        if line.trim().is_empty() {
-            continue;
+                if !processed_non_empty_line {
+                    ports.start().await;
+                }
+                processed_non_empty_line = true;
+                in_flight.push(ports.respond(frame));
+            }
+            else => break,
         }
+    }
+    let abandoned = in_flight.len();
+    drop(in_flight);
 
-        processed_non_empty_line = true;
-        super::process_protocol_line(&line).await?;
+    ports.finish().await;
+    if let Some(stop) = shutdown {
+        emit_shutdown_envelope(out, &stop, abandoned, settings.format).await?;
+        return outcome;
     }
+    outcome?;
 
     if !processed_non_empty_line {
-        emit_no_input_envelope().await?;
+        emit_no_input_envelope(out, settings.format).await?;
     }
 
     Ok(())
 }
 
-async fn emit_no_input_envelope() -> std::result::Result<(), SwarmError> {
-    let mut stdout = tokio::io::stdout();
+/// A shutdown signal and when the session stops waiting for requests.
+struct Shutdown {
+    signal: &'static str,
+    in_flight: usize,
+    started: Instant,
+    deadline: tokio::time::Instant,
+}
+
+impl Shutdown {
+    fn begin(signal: &'static str, in_flight: usize, grace: Duration) -> Self {
+        tracing::info!(
+            signal,
+            in_flight,
+            grace_ms = u64::try_from(grace.as_millis()).unwrap_or(u64::MAX),
+            "Shutdown requested; draining protocol session"
+        );
+        Self {
+            signal,
+            in_flight,
+            started: Instant::now(),
+            deadline: tokio::time::Instant::now() + grace,
+        }
+    }
+}
+
+/// Resolves with the name of the first SIGINT or SIGTERM. A signal that
+/// cannot be listened for never resolves.
+async fn shutdown_signal() -> &'static str {
+    let interrupt = async {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => "SIGINT",
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut stream) => {
+                stream.recv().await;
+                "SIGTERM"
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<&'static str>();
+
+    tokio::select! {
+        signal = interrupt => signal,
+        signal = terminate => signal,
+    }
+}
+
+async fn sleep_until_deadline(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn emit_shutdown_envelope<W>(
+    out: &mut W,
+    shutdown: &Shutdown,
+    abandoned: usize,
+    format: WireFormat,
+) -> std::result::Result<(), SwarmError>
+where
+    W: AsyncWrite + Unpin,
+{
+    let envelope = ProtocolEnvelope::success(
+        None,
+        json!({
+            "event": "shutdown",
+            "signal": shutdown.signal,
+            "drained": shutdown.in_flight.saturating_sub(abandoned),
+            "abandoned": abandoned,
+        }),
+    )
+    .with_ms(i64::try_from(elapsed_ms(shutdown.started)).unwrap_or(i64::MAX));
+
+    write_envelope(out, &envelope, format).await
+}
+
+/// Reads `input` on its own task so a frame half-read when a response
+/// finishes is never lost. Stops after end of input or a read error.
+fn spawn_frame_reader<R>(
+    input: R,
+    settings: &SessionSettings,
+) -> (
+    mpsc::Receiver<std::io::Result<RequestFrame>>,
+    JoinHandle<()>,
+)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    let SessionSettings {
+        format,
+        max_line_bytes,
+        concurrency,
+        ..
+    } = *settings;
+    let (sender, receiver) = mpsc::channel(concurrency);
+    let reader = tokio::spawn(async move {
+        let mut input = BufReader::new(input);
+        loop {
+            let read = read_frame(&mut input, format, max_line_bytes).await;
+            let stop = !matches!(read, Ok(Some(_)));
+            let Some(read) = read.transpose() else {
+                break;
+            };
+            if sender.send(read).await.is_err() || stop {
+                break;
+            }
+        }
+    });
+    (receiver, reader)
+}
+
+/// Connects once for the session, cleans up after any run that was
+/// interrupted, and starts the periodic recovery scans and a batcher so a
+/// long protocol session does not open a connection and issue an INSERT per
+/// command. Requests that connect to the same database queue their
+/// execution events on the batcher too.
+async fn start_session(
+    candidates: &[String],
+    timeout_ms: u64,
+) -> Option<(WriteBatcher, JoinHandle<()>)> {
+    let scope = super::db_resolution::DbScope::from_env();
+    let options = database_connect_options_for_cli().unwrap_or_default();
+    let (connected, _failures) =
+        super::db_resolution::try_connect_candidates(candidates, timeout_ms, &scope, &options)
+            .await;
+    let (db, used_url) = connected?;
+    super::handlers::recover::recover_on_startup(&db).await;
+    let scans = tokio::spawn(super::handlers::recover::run_recovery_scans(db.clone()));
+    let batcher = WriteBatcher::spawn(db, WriteBatcherConfig::default());
+    super::db_resolution::set_session_write_batch(&used_url, &scope, &options, batcher.handle());
+    Some((batcher, scans))
+}
+
+async fn emit_no_input_envelope<W>(
+    out: &mut W,
+    format: WireFormat,
+) -> std::result::Result<(), SwarmError>
+where
+    W: AsyncWrite + Unpin,
+{
     let envelope = ProtocolEnvelope::error(
         None,
         code::INVALID.to_string(),
@@ -41,15 +384,458 @@ async fn emit_no_input_envelope() -> std::result::Result<(), SwarmError> {
     .with_ctx(json!({"stdin": "empty"}))
     .with_ms(0);
 
-    let response_text = serde_json::to_string(&envelope).map_err(SwarmError::SerializationError)?;
-    stdout
-        .write_all(response_text.as_bytes())
-        .await
-        .map_err(SwarmError::IoError)?;
-    stdout.write_all(b"\n").await.map_err(SwarmError::IoError)
+    write_envelope(out, &envelope, format).await
 }
 
 pub fn elapsed_ms(start: Instant) -> u64 {
     let ms = start.elapsed().as_millis();
     u64::try_from(ms).map_or(u64::MAX, |value| value)
 }
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use crate::db::write_ops::CommandAuditRow;
+    use serde_json::Value;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::oneshot;
+
+    /// Requests are `{"rid": ...}` lines that finish, ok or failed, once
+    /// their test releases them.
+    #[derive(Default)]
+    struct FakePorts {
+        releases: Arc<Mutex<HashMap<String, oneshot::Receiver<bool>>>>,
+        started: Option<mpsc::UnboundedSender<String>>,
+        running: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+        starts: usize,
+        finished: bool,
+    }
+
+    impl SessionPorts for FakePorts {
+        fn start(&mut self) -> SessionFuture<'_, ()> {
+            self.starts += 1;
+            Box::pin(async {})
+        }
+
+        fn respond(&self, frame: RequestFrame) -> SessionFuture<'static, FrameResponse> {
+            let RequestFrame::Line(line) = frame else {
+                panic!("fake requests are JSON lines");
+            };
+            let rid = serde_json::from_str::<Value>(&line).unwrap()["rid"]
+                .as_str()
+                .unwrap()
+                .to_string();
+            let release = self.releases.lock().unwrap().remove(&rid).unwrap();
+            let started = self.started.clone();
+            let running = Arc::clone(&self.running);
+            let peak = Arc::clone(&self.peak);
+            Box::pin(async move {
+                if let Some(started) = started {
+                    let _ = started.send(rid.clone());
+                }
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                let ok = release.await.unwrap_or(false);
+                running.fetch_sub(1, Ordering::SeqCst);
+                let envelope = if ok {
+                    ProtocolEnvelope::success(Some(rid.clone()), json!({"answered": rid}))
+                } else {
+                    ProtocolEnvelope::error(
+                        Some(rid.clone()),
+                        code::INTERNAL.to_string(),
+                        format!("{rid} failed"),
+                    )
+                };
+                let audit_write = PendingWrite::Audit(CommandAuditRow {
+                    cmd: "fake".to_string(),
+                    rid: Some(rid),
+                    args: json!({}),
+                    ok,
+                    ms: 0,
+                    error_code: None,
+                });
+                (envelope, audit_write, WireFormat::Json)
+            })
+        }
+
+        fn record(&self, _audit_write: PendingWrite) -> SessionFuture<'_, ()> {
+            Box::pin(async {})
+        }
+
+        fn finish(&mut self) -> SessionFuture<'_, ()> {
+            self.finished = true;
+            Box::pin(async {})
+        }
+    }
+
+    struct Session {
+        ports: FakePorts,
+        frames: mpsc::Receiver<std::io::Result<RequestFrame>>,
+        started: mpsc::UnboundedReceiver<String>,
+        releases: HashMap<String, oneshot::Sender<bool>>,
+    }
+
+    /// A session with `rids` already waiting on its input, in order.
+    fn session(rids: &[&str]) -> Session {
+        let (started_tx, started) = mpsc::unbounded_channel();
+        let (frames_tx, frames) = mpsc::channel(rids.len().max(1));
+        let ports = FakePorts {
+            started: Some(started_tx),
+            ..FakePorts::default()
+        };
+        let mut releases = HashMap::new();
+        for rid in rids {
+            let (release, wait) = oneshot::channel();
+            releases.insert((*rid).to_string(), release);
+            ports
+                .releases
+                .lock()
+                .unwrap()
+                .insert((*rid).to_string(), wait);
+            frames_tx
+                .try_send(Ok(RequestFrame::Line(json!({"rid": rid}).to_string())))
+                .unwrap();
+        }
+        Session {
+            ports,
+            frames,
+            started,
+            releases,
+        }
+    }
+
+    fn settings(concurrency: usize) -> SessionSettings {
+        SessionSettings {
+            format: WireFormat::Json,
+            concurrency,
+            max_line_bytes: 1024,
+            grace: Duration::from_secs(30),
+        }
+    }
+
+    fn release(releases: &mut HashMap<String, oneshot::Sender<bool>>, rid: &str, ok: bool) {
+        releases.remove(rid).unwrap().send(ok).unwrap();
+    }
+
+    /// Lets the session run until it waits on something the test controls.
+    async fn settle() {
+        for _ in 0..16 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    fn envelopes(out: &[u8]) -> Vec<Value> {
+        String::from_utf8_lossy(out)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn rids(envelopes: &[Value]) -> Vec<&str> {
+        envelopes
+            .iter()
+            .map(|envelope| envelope["rid"].as_str().unwrap_or(""))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn given_concurrency_of_two_when_four_requests_wait_then_at_most_two_run_at_once() {
+        let Session {
+            mut ports,
+            frames,
+            mut started,
+            mut releases,
+        } = session(&["a", "b", "c", "d"]);
+        let mut out = Vec::new();
+
+        let (outcome, ()) = tokio::join!(
+            drive_session(
+                &mut ports,
+                frames,
+                &mut out,
+                std::future::pending(),
+                settings(2)
+            ),
+            async {
+                let mut first = vec![started.recv().await.unwrap(), started.recv().await.unwrap()];
+                first.sort();
+                assert_eq!(first, ["a", "b"]);
+                settle().await;
+                assert!(started.try_recv().is_err(), "a third request started");
+
+                release(&mut releases, "a", true);
+                assert_eq!(started.recv().await.unwrap(), "c");
+                settle().await;
+                assert!(started.try_recv().is_err(), "a third request started");
+
+                release(&mut releases, "b", true);
+                assert_eq!(started.recv().await.unwrap(), "d");
+                release(&mut releases, "c", true);
+                release(&mut releases, "d", true);
+            }
+        );
+
+        assert!(outcome.is_ok());
+        assert_eq!(ports.peak.load(Ordering::SeqCst), 2);
+        assert_eq!(envelopes(&out).len(), 4);
+        assert_eq!(ports.starts, 1);
+        assert!(ports.finished);
+    }
+
+    #[tokio::test]
+    async fn given_requests_finishing_out_of_order_when_writing_then_each_envelope_carries_its_rid()
+    {
+        let Session {
+            mut ports,
+            frames,
+            mut started,
+            mut releases,
+        } = session(&["a", "b", "c"]);
+        let mut out = Vec::new();
+
+        let (outcome, ()) = tokio::join!(
+            drive_session(
+                &mut ports,
+                frames,
+                &mut out,
+                std::future::pending(),
+                settings(3)
+            ),
+            async {
+                for _ in 0..3 {
+                    started.recv().await.unwrap();
+                }
+                for rid in ["c", "a", "b"] {
+                    release(&mut releases, rid, true);
+                    settle().await;
+                }
+            }
+        );
+
+        assert!(outcome.is_ok());
+        let written = envelopes(&out);
+        assert_eq!(rids(&written), ["c", "a", "b"]);
+        assert!(written
+            .iter()
+            .all(|envelope| envelope["d"]["answered"] == envelope["rid"]));
+    }
+
+    #[tokio::test]
+    async fn given_a_failed_request_when_others_are_running_then_reading_stops_and_they_finish() {
+        let Session {
+            mut ports,
+            frames,
+            mut started,
+            mut releases,
+        } = session(&["a", "b", "c"]);
+        let mut out = Vec::new();
+
+        let (outcome, ()) = tokio::join!(
+            drive_session(
+                &mut ports,
+                frames,
+                &mut out,
+                std::future::pending(),
+                settings(2)
+            ),
+            async {
+                started.recv().await.unwrap();
+                started.recv().await.unwrap();
+                release(&mut releases, "b", false);
+                settle().await;
+                release(&mut releases, "a", true);
+                settle().await;
+                assert!(
+                    started.try_recv().is_err(),
+                    "a request started after the failure"
+                );
+            }
+        );
+
+        assert!(matches!(outcome, Err(SwarmError::Internal(msg)) if msg == "b failed"));
+        let written = envelopes(&out);
+        assert_eq!(rids(&written), ["b", "a"]);
+        assert_eq!(written[0]["ok"], false);
+        assert_eq!(written[1]["ok"], true);
+        assert!(releases.contains_key("c"));
+    }
+
+    #[tokio::test]
+    async fn given_input_with_blank_lines_when_reading_then_each_request_is_answered_once() {
+        let mut ports = FakePorts::default();
+        for rid in ["a", "b"] {
+            let (release, wait) = oneshot::channel();
+            release.send(true).unwrap();
+            ports.releases.lock().unwrap().insert(rid.to_string(), wait);
+        }
+        let input: &'static [u8] = b"{\"rid\":\"a\"}\n\n   \n{\"rid\":\"b\"}\n";
+        let (frames, reader) = spawn_frame_reader(input, &settings(1));
+        let mut out = Vec::new();
+
+        let outcome = drive_session(
+            &mut ports,
+            frames,
+            &mut out,
+            std::future::pending(),
+            settings(1),
+        )
+        .await;
+        reader.abort();
+
+        assert!(outcome.is_ok());
+        assert_eq!(rids(&envelopes(&out)), ["a", "b"]);
+        assert_eq!(ports.starts, 1);
+    }
+
+    #[tokio::test]
+    async fn given_empty_input_when_the_session_ends_then_no_input_is_reported_without_starting() {
+        let mut ports = FakePorts::default();
+        let (frames, reader) = spawn_frame_reader(&b"\n"[..], &settings(1));
+        let mut out = Vec::new();
+
+        let outcome = drive_session(
+            &mut ports,
+            frames,
+            &mut out,
+            std::future::pending(),
+            settings(1),
+        )
+        .await;
+        reader.abort();
+
+        assert!(outcome.is_ok());
+        let written = envelopes(&out);
+        assert_eq!(written.len(), 1);
+        assert_eq!(written[0]["err"]["code"], code::INVALID);
+        assert_eq!(ports.starts, 0);
+    }
+
+    /// Resolves as a SIGTERM once the test sends on the returned sender.
+    fn signal() -> (oneshot::Sender<()>, impl Future<Output = &'static str>) {
+        let (send, received) = oneshot::channel();
+        (send, async move {
+            let _ = received.await;
+            "SIGTERM"
+        })
+    }
+
+    #[tokio::test]
+    async fn given_a_request_outliving_the_grace_when_shutting_down_then_it_is_abandoned_and_reported(
+    ) {
+        let Session {
+            mut ports,
+            frames,
+            mut started,
+            mut releases,
+        } = session(&["a", "b"]);
+        let (stop, signal) = signal();
+        let grace = Duration::from_millis(50);
+        let mut out = Vec::new();
+        let begun = Instant::now();
+
+        let (outcome, ()) = tokio::join!(
+            drive_session(
+                &mut ports,
+                frames,
+                &mut out,
+                signal,
+                SessionSettings {
+                    grace,
+                    ..settings(2)
+                }
+            ),
+            async {
+                started.recv().await.unwrap();
+                started.recv().await.unwrap();
+                stop.send(()).unwrap();
+                settle().await;
+                release(&mut releases, "a", true);
+            }
+        );
+
+        assert!(outcome.is_ok());
+        assert!(begun.elapsed() >= grace);
+        let written = envelopes(&out);
+        assert_eq!(rids(&written), ["a", ""]);
+        assert_eq!(
+            written[1]["d"],
+            json!({"event": "shutdown", "signal": "SIGTERM", "drained": 1, "abandoned": 1})
+        );
+        assert_eq!(ports.running.load(Ordering::SeqCst), 1);
+        assert!(ports.finished);
+    }
+
+    #[tokio::test]
+    async fn given_a_request_failing_while_draining_when_shutting_down_then_its_envelope_is_written(
+    ) {
+        let Session {
+            mut ports,
+            frames,
+            mut started,
+            mut releases,
+        } = session(&["a", "b", "c"]);
+        let (stop, signal) = signal();
+        let mut out = Vec::new();
+
+        let (outcome, ()) = tokio::join!(
+            drive_session(&mut ports, frames, &mut out, signal, settings(2)),
+            async {
+                started.recv().await.unwrap();
+                started.recv().await.unwrap();
+                stop.send(()).unwrap();
+                settle().await;
+                release(&mut releases, "a", false);
+                settle().await;
+                release(&mut releases, "b", true);
+            }
+        );
+
+        assert!(matches!(outcome, Err(SwarmError::Internal(msg)) if msg == "a failed"));
+        let written = envelopes(&out);
+        assert_eq!(rids(&written), ["a", "b", ""]);
+        assert_eq!(written[0]["err"]["msg"], "a failed");
+        assert_eq!(written[2]["d"]["drained"], 2);
+        assert_eq!(written[2]["d"]["abandoned"], 0);
+        assert!(releases.contains_key("c"));
+    }
+
+    #[tokio::test]
+    async fn given_a_response_ready_as_the_grace_runs_out_when_shutting_down_then_it_is_written() {
+        let Session {
+            mut ports,
+            frames,
+            mut started,
+            mut releases,
+        } = session(&["a"]);
+        let (stop, signal) = signal();
+        let mut out = Vec::new();
+
+        let (outcome, ()) = tokio::join!(
+            drive_session(
+                &mut ports,
+                frames,
+                &mut out,
+                signal,
+                SessionSettings {
+                    grace: Duration::ZERO,
+                    ..settings(1)
+                }
+            ),
+            async {
+                started.recv().await.unwrap();
+                release(&mut releases, "a", false);
+                stop.send(()).unwrap();
+            }
+        );
+
+        assert!(outcome.is_err());
+        let written = envelopes(&out);
+        assert_eq!(rids(&written), ["a", ""]);
+        assert_eq!(written[1]["d"]["drained"], 1);
+        assert_eq!(written[1]["d"]["abandoned"], 0);
+    }
+}
@@ -0,0 +1,128 @@
+//! Server-side projection: the `project` arg trims a command's data to the
+//! parts an agent asked for before the envelope is written, so a large
+//! `status` costs only the tokens of the fields read.
+
+use super::{CommandSuccess, ProtocolRequest};
+use crate::code;
+use crate::protocol_envelope::ProtocolEnvelope;
+use serde_json::{json, Map, Value};
+
+const PROJECT_FIX: &str =
+    "Use project as a JSON pointer (/beads_by_status/open) or a dotted path (d.working), or a list of them";
+
+/// Applies the request's `project`, if any. One path makes the data that
+/// value; several (an array, or a comma-separated string) make it an object
+/// keyed by each path as written.
+///
+/// # Errors
+/// Returns `INVALID` when `project` is malformed or a path is not in the data.
+pub(super) fn apply_projection(
+    request: &ProtocolRequest,
+    success: CommandSuccess,
+) -> std::result::Result<CommandSuccess, Box<ProtocolEnvelope>> {
+    let Some(raw) = request.args.get("project") else {
+        return Ok(success);
+    };
+    let invalid = |msg: String, ctx: Value| {
+        Box::new(
+            ProtocolEnvelope::error(request.rid.clone(), code::INVALID.to_string(), msg)
+                .with_fix(PROJECT_FIX.to_string())
+                .with_ctx(ctx),
+        )
+    };
+    let paths = match raw {
+        Value::String(paths) => paths
+            .split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>(),
+        Value::Array(paths) => paths
+            .iter()
+            .map(|path| path.as_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                invalid(
+                    "project must list paths as strings".to_string(),
+                    json!({"project": raw}),
+                )
+            })?,
+        _ => {
+            return Err(invalid(
+                "project must be a path or a list of paths".to_string(),
+                json!({"project": raw}),
+            ))
+        }
+    };
+    if paths.is_empty() {
+        return Err(invalid(
+            "project names no path".to_string(),
+            json!({"project": raw}),
+        ));
+    }
+
+    let lookup = |path: &str| {
+        project_path(&success.data, path).cloned().ok_or_else(|| {
+            invalid(
+                format!("{path} is not in the {} output", request.cmd),
+                json!({
+                    "path": path,
+                    "available": success
+                        .data
+                        .as_object()
+                        .map(|data| data.keys().cloned().collect::<Vec<_>>()),
+                }),
+            )
+        })
+    };
+    let data = match paths.as_slice() {
+        [path] => lookup(path)?,
+        _ => Value::Object(
+            paths
+                .iter()
+                .map(|path| lookup(path).map(|value| (path.clone(), value)))
+                .collect::<std::result::Result<Map<_, _>, _>>()?,
+        ),
+    };
+    Ok(CommandSuccess { data, ..success })
+}
+
+/// The value at `path`: a JSON pointer, or dot-separated keys and array
+/// indexes. A leading `d` stands for the data itself, as in the envelope.
+fn project_path<'a>(data: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.starts_with('/') {
+        return data.pointer(path);
+    }
+    let mut segments = path.split('.').peekable();
+    if segments.peek() == Some(&"d") {
+        segments.next();
+    }
+    segments.try_fold(data, |value, segment| match value {
+        Value::Object(fields) => fields.get(segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::project_path;
+    use serde_json::json;
+
+    #[test]
+    fn given_pointer_and_dotted_paths_when_projecting_then_both_reach_the_same_value() {
+        let data = json!({"working": 3, "beads_by_status": {"open": ["bd-1"]}});
+
+        assert_eq!(project_path(&data, "d.working"), Some(&json!(3)));
+        assert_eq!(
+            project_path(&data, "/beads_by_status/open"),
+            project_path(&data, "beads_by_status.open")
+        );
+        assert_eq!(
+            project_path(&data, "beads_by_status.open.0"),
+            Some(&json!("bd-1"))
+        );
+        assert_eq!(project_path(&data, "d"), Some(&data));
+        assert_eq!(project_path(&data, "idle"), None);
+    }
+}
@@ -95,6 +95,678 @@ fn given_missing_lock_resource_when_parsing_lock_input_then_parse_error_is_retur
     assert!(result.is_err());
 }
 
+#[test]
+fn given_negative_lock_wait_when_parsing_lock_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("resource".to_string(), json!("repo-123"));
+    args.insert("agent".to_string(), json!("agent-1"));
+    args.insert("ttl_ms".to_string(), json!(30_000));
+    args.insert("wait_ms".to_string(), json!(-1));
+    let request = make_request("lock", args);
+
+    let result = crate::LockInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_unknown_prompt_action_when_parsing_prompt_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("delete"));
+    args.insert("skill".to_string(), json!("rust-contract"));
+    let request = make_request("prompt", args);
+
+    let result = crate::PromptInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_blank_bead_id_when_parsing_context_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("bead_id".to_string(), json!("  "));
+    let request = make_request("context", args);
+
+    let result = crate::ContextInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_missing_reason_when_parsing_unquarantine_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("agent_id".to_string(), json!(3));
+    let request = make_request("unquarantine", args);
+
+    let result = crate::UnquarantineInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_override_without_reason_when_parsing_land_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("bead_id".to_string(), json!("bd-1"));
+    args.insert("agent_id".to_string(), json!(1));
+    args.insert("change_id".to_string(), json!("qpvuntsm"));
+    args.insert("override_push_check".to_string(), json!(true));
+    let request = make_request("land", args);
+
+    let result = crate::LandInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_option_like_remote_when_parsing_land_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("bead_id".to_string(), json!("bd-1"));
+    args.insert("agent_id".to_string(), json!(1));
+    args.insert("change_id".to_string(), json!("qpvuntsm"));
+    args.insert("remote".to_string(), json!("--upload-pack=touch /tmp/x"));
+    let request = make_request("land", args);
+
+    let result = crate::LandInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_unknown_action_when_parsing_workspace_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("agent_id".to_string(), json!(1));
+    args.insert("action".to_string(), json!("delete"));
+    let request = make_request("workspace", args);
+
+    let result = crate::WorkspaceInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_zero_window_when_parsing_forecast_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("window_hours".to_string(), json!(0));
+    args.insert("agents".to_string(), json!(4));
+    let request = make_request("forecast", args);
+
+    let result = crate::ForecastInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_window_past_a_year_when_parsing_forecast_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("window_hours".to_string(), json!(u64::from(u32::MAX)));
+    let request = make_request("forecast", args);
+
+    let result = crate::ForecastInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_blank_bead_id_when_parsing_replay_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("bead_id".to_string(), json!("  "));
+    let request = make_request("replay", args);
+
+    let result = crate::ReplayInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_blank_bead_id_when_parsing_verify_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("bead_id".to_string(), json!(""));
+    let request = make_request("verify", args);
+
+    let result = crate::VerifyInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_missing_bead_id_when_parsing_attest_input_then_parse_error_is_returned() {
+    let request = make_request("attest", Map::new());
+
+    let result = crate::AttestInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_zero_attempt_when_parsing_env_diff_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("bead_id".to_string(), json!("bd-1"));
+    args.insert("from_attempt".to_string(), json!(0));
+    args.insert("to_attempt".to_string(), json!(2));
+    let request = make_request("env-diff", args);
+
+    let result = crate::EnvDiffInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_snapshot_without_bead_id_when_parsing_bead_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("snapshot"));
+    let request = make_request("bead", args);
+
+    let result = crate::BeadInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_object_beads_when_parsing_enqueue_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("beads".to_string(), json!({"id": "bd-1"}));
+    let request = make_request("enqueue", args);
+
+    let result = crate::EnqueueInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_zero_rounds_when_parsing_sync_backlog_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("rounds".to_string(), json!(0));
+    let request = make_request("sync-backlog", args);
+
+    let result = crate::SyncBacklogInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_requests_when_resolving_command_timeout_then_default_applies_to_short_commands_only() {
+    let timeout = |cmd: &str, args: Value| {
+        let Value::Object(args) = args else {
+            panic!("args must be an object");
+        };
+        super::dispatcher::command_timeout(&make_request(cmd, args))
+    };
+
+    assert_eq!(
+        timeout("status", json!({})).ok().flatten(),
+        Some(std::time::Duration::from_millis(
+            super::DEFAULT_COMMAND_TIMEOUT_MS
+        ))
+    );
+    assert_eq!(timeout("run", json!({})).ok().flatten(), None);
+    assert_eq!(
+        timeout("run", json!({"timeout_ms": 250})).ok().flatten(),
+        Some(std::time::Duration::from_millis(250))
+    );
+    assert_eq!(
+        timeout("load-profile", json!({"timeout_ms": 250}))
+            .ok()
+            .flatten(),
+        None
+    );
+    assert!(timeout("status", json!({"timeout_ms": 0})).is_err());
+}
+
+#[test]
+fn given_unknown_action_when_parsing_sync_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("reset"));
+    let request = make_request("sync", args);
+
+    let result = crate::SyncInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_unknown_action_when_parsing_events_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("replay"));
+    let request = make_request("events", args);
+
+    let result = crate::EventsInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_tenant_delete_without_matching_confirm_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("delete"));
+    args.insert("name".to_string(), json!("acme"));
+    args.insert("confirm".to_string(), json!("acm"));
+    let request = make_request("tenant", args);
+
+    let result = crate::TenantInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_connection_option_args_when_resolving_then_they_apply_and_bad_sslmode_is_invalid() {
+    let mut args = Map::new();
+    args.insert("sslmode".to_string(), json!("verify-full"));
+    args.insert("statement_timeout_ms".to_string(), json!(5000));
+    let request = make_request("status", args);
+
+    let options = super::db_resolution::connect_options_for_request(&request).expect("options");
+
+    assert_eq!(options.ssl_mode, Some(crate::db::SslMode::VerifyFull));
+    assert_eq!(options.statement_timeout_ms, Some(5000));
+
+    let mut args = Map::new();
+    args.insert("sslmode".to_string(), json!("strict"));
+    let failure = super::db_resolution::connect_options_for_request(&make_request("status", args))
+        .expect_err("invalid sslmode");
+    assert_eq!(
+        failure.err.as_ref().map(|err| err.code.as_str()),
+        Some(crate::code::INVALID)
+    );
+}
+
+#[test]
+fn given_localdb_destroy_without_matching_confirm_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("destroy"));
+    args.insert("container_name".to_string(), json!("pg-dev"));
+    args.insert(
+        "confirm".to_string(),
+        json!(super::DEFAULT_LOCAL_DB_CONTAINER),
+    );
+    let request = make_request("localdb", args);
+
+    let result = crate::LocalDbInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_artifact_put_with_unknown_type_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("put"));
+    args.insert("bead_id".to_string(), json!("bd-abc"));
+    args.insert("stage".to_string(), json!("implement"));
+    args.insert("file".to_string(), json!("design.md"));
+    args.insert("type".to_string(), json!("design_document"));
+    let request = make_request("artifact", args.clone());
+    assert!(crate::ArtifactInput::parse_input(&request).is_ok());
+
+    args.insert("type".to_string(), json!("whiteboard"));
+    let request = make_request("artifact", args);
+
+    let result = crate::ArtifactInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_diff_put_without_exactly_one_patch_source_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("put"));
+    args.insert("bead_id".to_string(), json!("bd-abc"));
+    let request = make_request("diff", args.clone());
+    assert!(crate::DiffInput::parse_input(&request).is_err());
+
+    args.insert("file".to_string(), json!("changes.patch"));
+    let request = make_request("diff", args.clone());
+    assert!(crate::DiffInput::parse_input(&request).is_ok());
+
+    args.insert("patch".to_string(), json!("--- a/x\n+++ b/x\n"));
+    let request = make_request("diff", args);
+
+    let result = crate::DiffInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_config_set_with_lease_ttl_outside_bounds_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("set"));
+    let request = make_request("config", args.clone());
+    assert!(crate::ConfigInput::parse_input(&request).is_err());
+
+    args.insert("lease_ttl_ms".to_string(), json!(120_000));
+    let request = make_request("config", args.clone());
+    assert!(crate::ConfigInput::parse_input(&request).is_ok());
+
+    args.insert("lease_ttl_ms".to_string(), json!(500));
+    let request = make_request("config", args);
+
+    let result = crate::ConfigInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_explain_transition_with_zero_max_attempts_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("stage".to_string(), json!("qa-enforcer"));
+    args.insert("result".to_string(), json!("failed"));
+    args.insert("attempt".to_string(), json!(2));
+    let request = make_request("explain-transition", args.clone());
+    assert!(crate::ExplainTransitionInput::parse_input(&request).is_ok());
+
+    args.insert("max_attempts".to_string(), json!(0));
+    let request = make_request("explain-transition", args);
+
+    let result = crate::ExplainTransitionInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_undelete_key_without_table_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("key".to_string(), json!("bd-abc"));
+    let request = make_request("undelete", args);
+
+    let result = crate::UndeleteInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_init_rollback_flag_when_parsing_then_rollback_on_error_is_set() {
+    let mut args = Map::new();
+    args.insert("rollback_on_error".to_string(), json!(true));
+    args.insert("seed_agents".to_string(), json!(4));
+    let request = make_request("init", args);
+
+    let input = crate::InitInput::parse_input(&request).expect("init input");
+
+    assert_eq!(input.rollback_on_error, Some(true));
+    assert_eq!(input.seed_agents, Some(4));
+}
+
+#[test]
+fn given_blackboard_append_without_expected_version_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("append"));
+    args.insert("bead_id".to_string(), json!("bd-abc"));
+    args.insert("agent_id".to_string(), json!(7));
+    args.insert("section".to_string(), json!("plan"));
+    args.insert("content".to_string(), json!("Split the parser first"));
+    let request = make_request("blackboard", args);
+
+    let result = crate::BlackboardInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_review_submit_without_reviewer_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("submit"));
+    args.insert("bead_id".to_string(), json!("bd-abc"));
+    args.insert("verdict".to_string(), json!("approve"));
+    let request = make_request("review", args);
+
+    let result = crate::ReviewInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_review_submit_with_agent_and_reviewer_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("submit"));
+    args.insert("bead_id".to_string(), json!("bd-abc"));
+    args.insert("verdict".to_string(), json!("approve"));
+    args.insert("agent_id".to_string(), json!(3));
+    args.insert("reviewer".to_string(), json!("alice"));
+    let request = make_request("review", args);
+
+    let result = crate::ReviewInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_approve_without_token_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("bead_id".to_string(), json!("bd-abc"));
+    let request = make_request("approve", args);
+
+    let result = crate::ApproveInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_unknown_action_when_parsing_chaos_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("enable"));
+    let request = make_request("chaos", args);
+
+    let result = crate::ChaosInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_ttl_above_limit_when_parsing_reserve_claim_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("agent_id".to_string(), json!(3));
+    args.insert("ttl_secs".to_string(), json!(3600));
+    let request = make_request("claim-next", args);
+
+    let result = crate::ReserveClaimInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_cancel_without_bead_id_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("reason".to_string(), json!("superseded"));
+    let request = make_request("cancel", args);
+
+    let result = crate::CancelInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_consent_with_to_agent_when_parsing_takeover_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("bead_id".to_string(), json!("bd-1"));
+    args.insert("from_agent".to_string(), json!(2));
+    args.insert("to_agent".to_string(), json!(4));
+    args.insert("consent".to_string(), json!(true));
+    let request = make_request("takeover", args);
+
+    let result = crate::TakeoverInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_zero_from_agent_when_parsing_takeover_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("bead_id".to_string(), json!("bd-1"));
+    args.insert("to_agent".to_string(), json!(4));
+    args.insert("from_agent".to_string(), json!(0));
+    let request = make_request("takeover", args);
+
+    let result = crate::TakeoverInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_negative_model_rate_when_parsing_costs_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert(
+        "pricing".to_string(),
+        json!({"models": {"sonnet": {"input_usd_per_mtok": -3.0, "output_usd_per_mtok": 15.0}}}),
+    );
+    let request = make_request("costs", args);
+
+    let result = crate::CostsInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_unknown_stage_when_parsing_report_usage_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("agent_id".to_string(), json!(1));
+    args.insert("bead_id".to_string(), json!("bd-1"));
+    args.insert("stage".to_string(), json!("deploy"));
+    args.insert("model".to_string(), json!("sonnet"));
+    args.insert("prompt_tokens".to_string(), json!(10));
+    args.insert("completion_tokens".to_string(), json!(5));
+    let request = make_request("report-usage", args);
+
+    let result = crate::ReportUsageInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_unknown_format_when_parsing_report_coverage_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("agent_id".to_string(), json!(1));
+    args.insert("bead_id".to_string(), json!("bd-1"));
+    args.insert("report".to_string(), json!("LF:10\nLH:8\nend_of_record"));
+    args.insert("format".to_string(), json!("jacoco"));
+    let request = make_request("report-coverage", args);
+
+    let result = crate::ReportCoverageInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_zero_max_cycles_when_parsing_run_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("max_cycles".to_string(), json!(0));
+    let request = make_request("run", args);
+
+    let result = crate::RunInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_negative_budget_when_parsing_run_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("budget_ms".to_string(), json!(-1));
+    let request = make_request("run", args);
+
+    let result = crate::RunInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_concurrency_outside_bounds_when_parsing_run_all_input_then_parse_error_is_returned() {
+    for concurrency in [0, 33] {
+        let mut args = Map::new();
+        args.insert("concurrency".to_string(), json!(concurrency));
+        let request = make_request("run-all", args);
+
+        let result = crate::RunAllInput::parse_input(&request);
+
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+fn given_run_now_without_job_when_parsing_jobs_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("run-now"));
+    let request = make_request("jobs", args);
+
+    let result = crate::JobsInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_no_action_when_parsing_jobs_input_then_action_defaults_to_list() {
+    let request = make_request("jobs", Map::new());
+
+    let result = crate::JobsInput::parse_input(&request);
+
+    assert!(result.is_ok_and(|input| input.action == "list" && input.job.is_none()));
+}
+
+#[test]
+fn given_unknown_severity_when_parsing_notify_input_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("test"));
+    args.insert("severity".to_string(), json!("urgent"));
+    let request = make_request("notify", args);
+
+    let result = crate::NotifyInput::parse_input(&request);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn given_profile_window_out_of_range_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("duration_ms".to_string(), json!(2500));
+    let request = make_request("profile", args.clone());
+    assert!(crate::ProfileInput::parse_input(&request)
+        .is_ok_and(|input| input.duration_ms == Some(2500)));
+
+    for duration_ms in [0, crate::profiling::MAX_PROFILE_DURATION_MS + 1] {
+        args.insert("duration_ms".to_string(), json!(duration_ms));
+        let request = make_request("profile", args.clone());
+        assert!(crate::ProfileInput::parse_input(&request).is_err());
+    }
+}
+
+#[test]
+fn given_perf_compare_without_since_when_parsing_then_parse_error_is_returned() {
+    let mut args = Map::new();
+    args.insert("action".to_string(), json!("compare"));
+    let request = make_request("perf", args.clone());
+    assert!(crate::PerfInput::parse_input(&request).is_err());
+
+    args.insert("since".to_string(), json!("2026-03-01T12:00:00Z"));
+    let request = make_request("perf", args.clone());
+    assert!(crate::PerfInput::parse_input(&request)
+        .is_ok_and(|input| input.until.is_none() && input.baseline_days.is_none()));
+
+    args.insert("baseline_days".to_string(), json!(0));
+    let request = make_request("perf", args);
+    assert!(crate::PerfInput::parse_input(&request).is_err());
+}
+
+#[test]
+fn given_partition_detach_when_parsing_then_partition_must_belong_to_a_partitioned_table() {
+    let mut args = Map::new();
+    args.insert("target".to_string(), json!("partitions"));
+    args.insert("action".to_string(), json!("detach"));
+    let request = make_request("maintenance", args.clone());
+    assert!(crate::MaintenanceInput::parse_input(&request).is_err());
+
+    args.insert(
+        "partition".to_string(),
+        json!("execution_events; DROP TABLE bead_backlog"),
+    );
+    let request = make_request("maintenance", args.clone());
+    assert!(crate::MaintenanceInput::parse_input(&request).is_err());
+
+    args.insert("partition".to_string(), json!("execution_events_p2026_01"));
+    let request = make_request("maintenance", args.clone());
+    assert!(crate::MaintenanceInput::parse_input(&request)
+        .is_ok_and(|input| input.partition.as_deref() == Some("execution_events_p2026_01")));
+
+    args.insert("table".to_string(), json!("stage_artifacts"));
+    let request = make_request("maintenance", args);
+    assert!(crate::MaintenanceInput::parse_input(&request).is_err());
+}
+
 async fn write_all(mut writer: DuplexStream, bytes: Vec<u8>) -> std::io::Result<()> {
     writer.write_all(&bytes).await?;
     writer.shutdown().await
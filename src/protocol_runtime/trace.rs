@@ -0,0 +1,192 @@
+//! Request traces for reproducing a session.
+//!
+//! With `--record <file>` every request and the envelope it got are
+//! appended to an NDJSON file, and `replay --file <file>` runs them again
+//! and diffs the outcomes.
+
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::{Result, SwarmError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+/// Most differences reported for one replayed request.
+pub const MAX_TRACE_DIFFS_PER_ENTRY: usize = 20;
+
+static RECORDER: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// One line of a trace: a request as it arrived, passwords masked, and the
+/// envelope it was answered with.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub ts: DateTime<Utc>,
+    /// The request object, or `{"raw": ...}` for a line that did not parse.
+    pub request: Value,
+    pub envelope: ProtocolEnvelope,
+}
+
+/// Starts appending every request this process answers to `path`,
+/// creating it if needed. Only the first call takes effect.
+///
+/// # Errors
+/// Returns an error if the file cannot be opened or recording already
+/// started.
+pub fn start_recording(path: &Path) -> Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    RECORDER
+        .set(Mutex::new(file))
+        .map_err(|_| SwarmError::ConfigError("Already recording a trace".to_string()))
+}
+
+pub(super) fn is_recording() -> bool {
+    RECORDER.get().is_some()
+}
+
+/// Appends one entry. A failed write is reported on stderr and never fails
+/// the request it records.
+pub(super) fn record(request: Value, envelope: &ProtocolEnvelope) {
+    let Some(recorder) = RECORDER.get() else {
+        return;
+    };
+    let line = json!({"ts": Utc::now(), "request": request, "envelope": envelope});
+    let mut file = recorder.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Err(e) = writeln!(file, "{line}") {
+        tracing::warn!(error = %e, "Trace recording failed");
+    }
+}
+
+/// Parses a trace, one entry per non-blank line.
+///
+/// # Errors
+/// Returns the 1-based line number and why it is not a trace entry.
+pub fn parse_trace(text: &str) -> std::result::Result<Vec<(usize, TraceEntry)>, (usize, String)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str::<TraceEntry>(line)
+                .map(|entry| (index + 1, entry))
+                .map_err(|e| (index + 1, e.to_string()))
+        })
+        .collect()
+}
+
+/// Where `replayed` differs from `recorded`, at most `limit` of them.
+///
+/// Each difference is `{path, recorded, replayed}` with a JSON pointer
+/// path. Object keys named in `ignore` are skipped at any depth, for
+/// timestamps and generated ids.
+#[must_use]
+pub fn diff_values(
+    recorded: &Value,
+    replayed: &Value,
+    ignore: &[String],
+    limit: usize,
+) -> Vec<Value> {
+    let mut diffs = Vec::new();
+    collect_diffs(recorded, replayed, ignore, "", limit, &mut diffs);
+    diffs
+}
+
+fn collect_diffs(
+    recorded: &Value,
+    replayed: &Value,
+    ignore: &[String],
+    path: &str,
+    limit: usize,
+    diffs: &mut Vec<Value>,
+) {
+    if diffs.len() >= limit || recorded == replayed {
+        return;
+    }
+    match (recorded, replayed) {
+        (Value::Object(left), Value::Object(right)) => {
+            let keys = left
+                .keys()
+                .chain(right.keys().filter(|key| !left.contains_key(*key)))
+                .filter(|key| !ignore.contains(key));
+            for key in keys {
+                let child = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+                collect_diffs(
+                    left.get(key).unwrap_or(&Value::Null),
+                    right.get(key).unwrap_or(&Value::Null),
+                    ignore,
+                    &child,
+                    limit,
+                    diffs,
+                );
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for index in 0..left.len().max(right.len()) {
+                collect_diffs(
+                    left.get(index).unwrap_or(&Value::Null),
+                    right.get(index).unwrap_or(&Value::Null),
+                    ignore,
+                    &format!("{path}/{index}"),
+                    limit,
+                    diffs,
+                );
+            }
+        }
+        _ => diffs.push(json!({"path": path, "recorded": recorded, "replayed": replayed})),
+    }
+}
+
+/// `ok`, the error code, and `d` of an envelope: the parts a replay
+/// compares. A compressed `d` is decompressed first.
+#[must_use]
+pub fn comparable_outcome(envelope: ProtocolEnvelope) -> Value {
+    let envelope = match envelope.decompressed() {
+        Ok(envelope) => envelope,
+        Err(e) => return json!({"ok": false, "code": null, "d": e.to_string()}),
+    };
+    json!({
+        "ok": envelope.ok,
+        "code": envelope.err.as_ref().map(|err| err.code.clone()),
+        "d": envelope.d.map_or(Value::Null, |d| *d),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_changed_and_ignored_fields_when_diffing_then_only_real_changes_are_reported() {
+        let recorded = json!({"count": 2, "created_at": "a", "items": [{"id": 1}], "a/b": 1});
+        let replayed =
+            json!({"count": 3, "created_at": "b", "items": [{"id": 1}, {"id": 2}], "a/b": 1});
+
+        let diffs = diff_values(&recorded, &replayed, &["created_at".to_string()], 10);
+
+        assert_eq!(
+            diffs,
+            vec![
+                json!({"path": "/count", "recorded": 2, "replayed": 3}),
+                json!({"path": "/items/1", "recorded": null, "replayed": {"id": 2}}),
+            ]
+        );
+        assert_eq!(diff_values(&recorded, &replayed, &[], 1).len(), 1);
+    }
+
+    #[test]
+    fn given_trace_with_bad_line_when_parsing_then_its_line_number_is_reported() {
+        let entry = json!({
+            "ts": "2026-01-01T00:00:00Z",
+            "request": {"cmd": "status"},
+            "envelope": {"ok": true, "t": 0, "d": {}},
+        });
+        let text = format!("{entry}\n\n{{\"request\": 1}}\n");
+
+        let error = parse_trace(&text).err().unwrap();
+
+        assert_eq!(error.0, 3);
+        assert_eq!(parse_trace(&format!("{entry}\n")).unwrap()[0].0, 1);
+    }
+}
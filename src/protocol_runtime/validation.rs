@@ -1,8 +1,119 @@
+use super::constants::{
+    DEFAULT_MAX_ARGS_BYTES, DEFAULT_MAX_ARGS_DEPTH, DEFAULT_MAX_BATCH_OPS, DEFAULT_MAX_LINE_BYTES,
+};
 use crate::protocol_envelope::ProtocolEnvelope;
 use crate::{code, ProtocolRequest};
 use serde_json::{json, Map, Value};
 
-const GLOBAL_ALLOWED_REQUEST_ARGS: &[&str] = &["repo_id", "database_url", "connect_timeout_ms"];
+const GLOBAL_ALLOWED_REQUEST_ARGS: &[&str] = &[
+    "repo_id",
+    "database_url",
+    "connect_timeout_ms",
+    "timeout_ms",
+    "tenant",
+    "sslmode",
+    "ssl_root_cert",
+    "statement_timeout_ms",
+    "accept_encoding",
+];
+
+/// Size ceilings on incoming requests, so runaway agent output piped into
+/// the protocol loop is rejected instead of buffered and dispatched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestLimits {
+    /// Longest request line, or `MessagePack` frame, in bytes.
+    pub max_line_bytes: usize,
+    /// Most `ops` one `batch` request may carry.
+    pub max_batch_ops: usize,
+    /// Deepest nesting of arrays and objects inside the args.
+    pub max_args_depth: usize,
+    /// Largest args object, measured as compact JSON.
+    pub max_args_bytes: usize,
+}
+
+impl Default for RequestLimits {
+    fn default() -> Self {
+        Self {
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+            max_batch_ops: DEFAULT_MAX_BATCH_OPS,
+            max_args_depth: DEFAULT_MAX_ARGS_DEPTH,
+            max_args_bytes: DEFAULT_MAX_ARGS_BYTES,
+        }
+    }
+}
+
+/// `INVALID` envelope naming the limit a request broke and its value.
+pub(super) fn limit_exceeded(
+    rid: Option<String>,
+    limit: &str,
+    max: usize,
+    actual: usize,
+) -> Box<ProtocolEnvelope> {
+    Box::new(
+        ProtocolEnvelope::error(
+            rid,
+            code::INVALID.to_string(),
+            format!("Request exceeds {limit}: {actual} > {max}"),
+        )
+        .with_fix(format!(
+            "Shrink the request or raise SWARM_{}",
+            limit.to_ascii_uppercase()
+        ))
+        .with_ctx(json!({"limit": limit, "max": max, "actual": actual})),
+    )
+}
+
+pub(super) fn validate_request_limits(
+    request: &ProtocolRequest,
+    limits: &RequestLimits,
+) -> std::result::Result<(), Box<ProtocolEnvelope>> {
+    if request.cmd == "batch" {
+        let ops = request
+            .args
+            .get("ops")
+            .and_then(Value::as_array)
+            .map_or(0, Vec::len);
+        if ops > limits.max_batch_ops {
+            return Err(limit_exceeded(
+                request.rid.clone(),
+                "max_batch_ops",
+                limits.max_batch_ops,
+                ops,
+            ));
+        }
+    }
+
+    let depth = request.args.values().map(value_depth).max().unwrap_or(0);
+    if depth > limits.max_args_depth {
+        return Err(limit_exceeded(
+            request.rid.clone(),
+            "max_args_depth",
+            limits.max_args_depth,
+            depth,
+        ));
+    }
+
+    let bytes = serde_json::to_vec(&request.args).map_or(usize::MAX, |raw| raw.len());
+    if bytes > limits.max_args_bytes {
+        return Err(limit_exceeded(
+            request.rid.clone(),
+            "max_args_bytes",
+            limits.max_args_bytes,
+            bytes,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Levels of array and object nesting in `value`; scalars are 0.
+fn value_depth(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(value_depth).max().unwrap_or(0),
+        Value::Object(object) => 1 + object.values().map(value_depth).max().unwrap_or(0),
+        Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => 0,
+    }
+}
 
 pub(super) fn validate_request_args(
     request: &ProtocolRequest,
@@ -112,21 +223,155 @@ fn first_null_byte_field_in_value(value: &Value, field: &str) -> Option<String>
 fn allowed_command_args(cmd: &str) -> Option<&'static [&'static str]> {
     match cmd {
         "?" | "help" => Some(&["short", "s"]),
-        "state" | "history" => Some(&["limit"]),
-        "doctor" | "status" | "resume" | "agents" => Some(&[]),
-        "lock" => Some(&["resource", "agent", "ttl_ms", "dry"]),
+        "state" => Some(&["limit", "project"]),
+        "history" => Some(&["limit", "after_seq", "page_size", "since", "until"]),
+        "costs" => Some(&["bead_id", "since", "until", "pricing"]),
+        "report-usage" => Some(&[
+            "agent_id",
+            "bead_id",
+            "stage",
+            "model",
+            "prompt_tokens",
+            "completion_tokens",
+            "dry",
+        ]),
+        "report-coverage" => Some(&[
+            "agent_id", "bead_id", "stage", "path", "report", "format", "dry",
+        ]),
+        "doctor" => Some(&["show_candidates", "analyze_db", "apply", "dry"]),
+        "status" => Some(&["project"]),
+        "healthz" | "resume" | "agents" | "locks" | "invariants" => Some(&[]),
+        "db-health" => Some(&["samples"]),
+        "top" => Some(&["window_mins"]),
+        "forecast" => Some(&["window_hours", "agents"]),
+        "lock" => Some(&[
+            "resource", "agent", "ttl_ms", "wait_ms", "purpose", "bead_id", "dry",
+        ]),
         "unlock" => Some(&["resource", "agent", "dry"]),
-        "broadcast" => Some(&["msg", "from", "dry"]),
-        "monitor" => Some(&["view", "watch_ms"]),
+        "broadcast" => Some(&["msg", "from", "topic", "ttl_secs", "dry"]),
+        "monitor" => Some(&[
+            "view",
+            "watch_ms",
+            "bead_id",
+            "after_seq",
+            "page_size",
+            "since",
+            "until",
+            "project",
+        ]),
         "register" => Some(&["count", "dry"]),
         "agent" | "run-once" | "smoke" => Some(&["id", "dry"]),
-        "next" | "claim-next" | "bootstrap" => Some(&["dry"]),
+        "run" => Some(&["id", "until_empty", "budget_ms", "max_cycles", "dry"]),
+        "run-all" => Some(&["concurrency", "dry"]),
+        "jobs" => Some(&["action", "job", "dry"]),
+        "serve" => Some(&["budget_ms", "dry"]),
+        "notify" => Some(&["action", "notifier", "event", "severity", "dry"]),
+        "profile" => Some(&["duration_ms", "dry"]),
+        "perf" => Some(&[
+            "action",
+            "since",
+            "until",
+            "threshold_pct",
+            "min_samples",
+            "baseline_days",
+            "dry",
+        ]),
+        "maintenance" => Some(&["target", "action", "table", "partition", "ahead", "dry"]),
+        "next" | "bootstrap" | "recover" => Some(&["dry"]),
+        "claim-next" => Some(&["reserve", "agent_id", "ttl_secs", "label", "dry"]),
+        "accept-claim" => Some(&["agent_id", "bead_id", "dry"]),
+        "reject-claim" => Some(&["agent_id", "bead_id", "reason", "dry"]),
         "assign" => Some(&["bead_id", "agent_id", "dry"]),
+        "cancel" => Some(&["bead_id", "reason", "dry"]),
+        "takeover" => Some(&[
+            "bead_id",
+            "to_agent",
+            "from_agent",
+            "consent",
+            "max_bytes",
+            "max_tokens",
+            "dry",
+        ]),
         "qa" => Some(&["target", "id", "dry"]),
-        "resume-context" => Some(&["bead_id"]),
-        "artifacts" => Some(&["bead_id", "artifact_type"]),
+        "resume-context" => Some(&["bead_id", "max_bytes", "max_tokens"]),
+        "context" => Some(&["bead_id", "skill", "max_bytes", "max_tokens"]),
+        "record-symbols" => Some(&["bead_id", "agent_id", "attempt", "symbols", "file", "dry"]),
+        "artifacts" => Some(&["bead_id", "artifact_type", "out_dir"]),
+        "artifact" => Some(&["action", "bead_id", "stage", "type", "file", "dry"]),
+        "explain-transition" => Some(&[
+            "stage",
+            "result",
+            "attempt",
+            "max_attempts",
+            "category",
+            "message",
+        ]),
+        "diff" => Some(&[
+            "action",
+            "bead_id",
+            "stage",
+            "file",
+            "patch",
+            "base_revision",
+            "dry",
+        ]),
+        "replay" => Some(&["bead_id", "file", "ignore", "dry"]),
+        "verify" | "attest" => Some(&["bead_id"]),
+        "env-diff" => Some(&["bead_id", "stage", "from_attempt", "to_attempt"]),
+        "bead" => Some(&["action", "bead_id", "agent_id", "snapshot", "file", "dry"]),
+        "enqueue" => Some(&["beads", "file", "dry"]),
+        "sync-backlog" => Some(&["rounds", "interval_secs", "dry"]),
+        "backlog" => Some(&[
+            "action", "bead_id", "label", "priority", "to", "limit", "dry",
+        ]),
+        "sync" | "events" => Some(&["action", "dry"]),
+        "session" => Some(&["action", "agent_id", "host", "pid", "version", "dry"]),
+        "kv" => Some(&["action", "agent_id", "bead_id", "key", "value", "dry"]),
+        "blackboard" => Some(&[
+            "action",
+            "bead_id",
+            "agent_id",
+            "section",
+            "content",
+            "expected_version",
+            "dry",
+        ]),
+        "review" => Some(&[
+            "action", "bead_id", "verdict", "comments", "agent_id", "reviewer", "dry",
+        ]),
+        "approve" => Some(&["bead_id", "token", "approver", "note", "dry"]),
+        "reissue-approval" => Some(&["bead_id", "gate", "dry"]),
+        "chaos" => Some(&["action"]),
+        "config" => Some(&[
+            "action",
+            "key",
+            "value",
+            "max_agents",
+            "max_implementation_attempts",
+            "claim_label",
+            "swarm_status",
+            "lease_ttl_ms",
+            "heartbeat_grace_ms",
+            "recovery_scan_interval_ms",
+            "dry",
+        ]),
+        "labels" => Some(&["action", "agent_id", "labels", "dry"]),
+        "announcements" => Some(&["action", "agent_id", "id", "topic", "dry"]),
         "release" => Some(&["agent_id", "dry"]),
+        "quarantine" | "unquarantine" => Some(&["agent_id", "reason", "dry"]),
+        "land" => Some(&[
+            "bead_id",
+            "agent_id",
+            "change_id",
+            "remote",
+            "override_push_check",
+            "reason",
+            "dry",
+        ]),
+        "workspace" => Some(&["agent_id", "action", "bead_id", "dry"]),
         "init-db" => Some(&["url", "schema", "seed_agents", "dry"]),
+        "tenant" => Some(&["action", "name", "seed_agents", "confirm", "dry"]),
+        "undelete" => Some(&["table", "key", "limit", "dry"]),
         "init-local-db" => Some(&[
             "container_name",
             "port",
@@ -134,12 +379,30 @@ fn allowed_command_args(cmd: &str) -> Option<&'static [&'static str]> {
             "database",
             "schema",
             "seed_agents",
+            "volume",
             "dry",
         ]),
-        "spawn-prompts" => Some(&["template", "out_dir", "count", "dry"]),
-        "prompt" => Some(&["id", "skill"]),
-        "load-profile" => Some(&["agents", "rounds", "timeout_ms", "dry"]),
-        "init" => Some(&["dry", "database_url", "schema", "seed_agents"]),
+        "localdb" => Some(&[
+            "action",
+            "container_name",
+            "to",
+            "tail",
+            "purge_volume",
+            "confirm",
+            "dry",
+        ]),
+        "spawn-prompts" => Some(&["template", "out_dir", "count", "vars", "dry"]),
+        "prompt" => Some(&[
+            "id", "skill", "action", "body", "file", "template", "vars", "dry",
+        ]),
+        "load-profile" => Some(&["agents", "rounds", "timeout_ms", "concurrency", "dry"]),
+        "init" => Some(&[
+            "dry",
+            "database_url",
+            "schema",
+            "seed_agents",
+            "rollback_on_error",
+        ]),
         "batch" => Some(&["ops", "cmds", "dry"]),
         _ => None,
     }
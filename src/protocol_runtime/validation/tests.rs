@@ -1,4 +1,6 @@
-use super::{validate_request_args, validate_request_null_bytes};
+use super::{
+    validate_request_args, validate_request_limits, validate_request_null_bytes, RequestLimits,
+};
 use crate::{code, protocol_envelope::ProtocolEnvelope, ProtocolRequest};
 use serde_json::{json, Map, Value};
 
@@ -82,7 +84,22 @@ fn given_unknown_fields_when_validate_request_args_then_error_contains_unknown_a
     assert_eq!(ctx["unknown"], json!(["bogus", "extra"]));
     assert_eq!(
         ctx["allowed"],
-        json!(["connect_timeout_ms", "database_url", "limit", "repo_id"])
+        json!([
+            "accept_encoding",
+            "after_seq",
+            "connect_timeout_ms",
+            "database_url",
+            "limit",
+            "page_size",
+            "repo_id",
+            "since",
+            "ssl_root_cert",
+            "sslmode",
+            "statement_timeout_ms",
+            "tenant",
+            "timeout_ms",
+            "until"
+        ])
     );
 }
 
@@ -164,3 +181,86 @@ fn given_clean_request_when_validate_request_null_bytes_then_ok() {
 
     assert!(result.is_ok());
 }
+
+fn limit_ctx(result: Result<(), Box<ProtocolEnvelope>>) -> Value {
+    result
+        .err()
+        .and_then(|envelope| envelope.err)
+        .and_then(|error| error.ctx)
+        .map_or(Value::Null, |ctx| *ctx)
+}
+
+#[test]
+fn given_batch_over_max_ops_when_validate_request_limits_then_limit_is_named() {
+    let request = request(
+        "batch",
+        Map::from_iter(vec![(
+            "ops".to_string(),
+            json!([{"cmd": "status"}, {"cmd": "status"}, {"cmd": "status"}]),
+        )]),
+    );
+    let limits = RequestLimits {
+        max_batch_ops: 2,
+        ..RequestLimits::default()
+    };
+
+    let ctx = limit_ctx(validate_request_limits(&request, &limits));
+
+    assert_eq!(
+        ctx,
+        json!({"limit": "max_batch_ops", "max": 2, "actual": 3})
+    );
+}
+
+#[test]
+fn given_deeply_nested_args_when_validate_request_limits_then_depth_limit_is_named() {
+    let request = request(
+        "prompt",
+        Map::from_iter(vec![("vars".to_string(), json!({"a": {"b": [1]}}))]),
+    );
+    let limits = RequestLimits {
+        max_args_depth: 2,
+        ..RequestLimits::default()
+    };
+
+    let ctx = limit_ctx(validate_request_limits(&request, &limits));
+
+    assert_eq!(
+        ctx,
+        json!({"limit": "max_args_depth", "max": 2, "actual": 3})
+    );
+}
+
+#[test]
+fn given_large_args_when_validate_request_limits_then_size_limit_is_named() {
+    let request = request(
+        "prompt",
+        Map::from_iter(vec![("body".to_string(), json!("x".repeat(64)))]),
+    );
+    let limits = RequestLimits {
+        max_args_bytes: 32,
+        ..RequestLimits::default()
+    };
+
+    let result = validate_request_limits(&request, &limits);
+
+    assert_eq!(
+        result
+            .as_ref()
+            .err()
+            .and_then(|envelope| envelope.err.as_ref())
+            .map(|error| error.code.as_str()),
+        Some(code::INVALID)
+    );
+    assert_eq!(limit_ctx(result)["limit"], "max_args_bytes");
+}
+
+#[test]
+fn given_request_within_default_limits_when_validate_request_limits_then_ok() {
+    let request = request(
+        "batch",
+        Map::from_iter(vec![("ops".to_string(), json!([{"cmd": "status"}]))]),
+    );
+
+    assert!(validate_request_limits(&request, &RequestLimits::default()).is_ok());
+}
@@ -0,0 +1,328 @@
+use crate::profiling::HotspotCategory;
+use crate::protocol_envelope::ProtocolEnvelope;
+use crate::SwarmError;
+use serde_json::Value;
+use std::str::FromStr;
+use std::time::Instant;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Encoding of requests read from stdin and envelopes written to stdout.
+///
+/// `Json` is one object per line. `MessagePack` frames each request and
+/// envelope as a 4-byte big-endian length followed by a `MessagePack` map with
+/// the same keys the JSON form uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WireFormat {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl FromStr for WireFormat {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw {
+            "json" | "jsonl" => Ok(Self::Json),
+            "msgpack" => Ok(Self::MessagePack),
+            other => Err(format!("expected json or msgpack; got {other}")),
+        }
+    }
+}
+
+/// One request as read off the wire, before it is decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum RequestFrame {
+    Line(String),
+    MessagePack(Vec<u8>),
+    /// A request longer than `max_line_bytes`; its bytes were discarded
+    /// unread so the loop can answer and move on to the next one.
+    Oversized {
+        format: WireFormat,
+        bytes: usize,
+        max_bytes: usize,
+    },
+}
+
+impl RequestFrame {
+    /// Blank lines and zero-length frames are skipped without a response.
+    pub(super) fn is_blank(&self) -> bool {
+        match self {
+            Self::Line(line) => line.trim().is_empty(),
+            Self::MessagePack(bytes) => bytes.is_empty(),
+            Self::Oversized { .. } => false,
+        }
+    }
+}
+
+/// Reads the next request, or `None` at end of input. Never buffers more
+/// than `max_bytes` of one request.
+///
+/// # Errors
+/// Returns an error if reading fails, a line is not UTF-8, or input ends
+/// inside a `MessagePack` frame.
+pub(super) async fn read_frame<R>(
+    reader: &mut R,
+    format: WireFormat,
+    max_bytes: usize,
+) -> std::io::Result<Option<RequestFrame>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    match format {
+        WireFormat::Json => read_line_frame(reader, max_bytes).await,
+        WireFormat::MessagePack => {
+            if reader.fill_buf().await?.is_empty() {
+                return Ok(None);
+            }
+            let len = reader.read_u32().await?;
+            let bytes = usize::try_from(len).unwrap_or(usize::MAX);
+            if bytes > max_bytes {
+                let skipped = tokio::io::copy(
+                    &mut (&mut *reader).take(u64::from(len)),
+                    &mut tokio::io::sink(),
+                )
+                .await?;
+                if skipped < u64::from(len) {
+                    return Err(std::io::ErrorKind::UnexpectedEof.into());
+                }
+                return Ok(Some(RequestFrame::Oversized {
+                    format,
+                    bytes,
+                    max_bytes,
+                }));
+            }
+            let mut body = vec![0; bytes];
+            reader.read_exact(&mut body).await?;
+            Ok(Some(RequestFrame::MessagePack(body)))
+        }
+    }
+}
+
+/// Reads up to the next newline, keeping at most `max_bytes` of the line
+/// (`\n` or `\r\n` excluded) and skipping the rest of a longer one.
+async fn read_line_frame<R>(
+    reader: &mut R,
+    max_bytes: usize,
+) -> std::io::Result<Option<RequestFrame>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut line = Vec::new();
+    let mut total = 0_usize;
+    let mut oversized = false;
+    loop {
+        let buf = reader.fill_buf().await?;
+        if buf.is_empty() {
+            if total == 0 {
+                return Ok(None);
+            }
+            break;
+        }
+        let (chunk_len, done) = buf
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or((buf.len(), false), |newline| (newline + 1, true));
+        total = total.saturating_add(chunk_len);
+        if !oversized {
+            if line.len() + chunk_len > max_bytes.saturating_add(2) {
+                oversized = true;
+                line = Vec::new();
+            } else {
+                line.extend_from_slice(&buf[..chunk_len]);
+            }
+        }
+        reader.consume(chunk_len);
+        if done {
+            break;
+        }
+    }
+
+    if line.last() == Some(&b'\n') {
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+    }
+    if oversized || line.len() > max_bytes {
+        return Ok(Some(RequestFrame::Oversized {
+            format: WireFormat::Json,
+            bytes: if oversized { total } else { line.len() },
+            max_bytes,
+        }));
+    }
+    String::from_utf8(line)
+        .map(|line| Some(RequestFrame::Line(line)))
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+}
+
+/// Decodes a `MessagePack` request into the JSON value the protocol parses.
+///
+/// # Errors
+/// Returns the decoder's message when `bytes` is not a `MessagePack` value
+/// JSON can represent.
+pub(super) fn decode_msgpack(bytes: &[u8]) -> Result<Value, String> {
+    rmp_serde::from_slice::<Value>(bytes).map_err(|error| error.to_string())
+}
+
+/// Encodes `envelope` in `format`, terminated or length-prefixed so the
+/// reader can split responses.
+///
+/// # Errors
+/// Returns an error if the envelope cannot be serialized.
+pub fn encode_envelope(
+    envelope: &ProtocolEnvelope,
+    format: WireFormat,
+) -> std::result::Result<Vec<u8>, SwarmError> {
+    let started = Instant::now();
+    let encoded = match format {
+        WireFormat::Json => {
+            let mut bytes = serde_json::to_vec(envelope).map_err(SwarmError::SerializationError)?;
+            bytes.push(b'\n');
+            Ok(bytes)
+        }
+        WireFormat::MessagePack => {
+            let body = rmp_serde::to_vec_named(envelope).map_err(|error| {
+                SwarmError::Internal(format!("Failed to encode MessagePack envelope: {error}"))
+            })?;
+            let len = u32::try_from(body.len()).map_err(|_| {
+                SwarmError::Internal(format!(
+                    "MessagePack envelope of {} bytes exceeds the frame limit",
+                    body.len()
+                ))
+            })?;
+            let mut bytes = Vec::with_capacity(body.len() + 4);
+            bytes.extend_from_slice(&len.to_be_bytes());
+            bytes.extend_from_slice(&body);
+            Ok(bytes)
+        }
+    };
+    crate::profiling::record(HotspotCategory::Serde, "encode_envelope", started.elapsed());
+    encoded
+}
+
+/// Writes `envelope` to `writer` in `format`.
+///
+/// # Errors
+/// Returns an error if encoding or writing fails.
+pub async fn write_envelope<W>(
+    writer: &mut W,
+    envelope: &ProtocolEnvelope,
+    format: WireFormat,
+) -> std::result::Result<(), SwarmError>
+where
+    W: AsyncWrite + Unpin,
+{
+    let bytes = encode_envelope(envelope, format)?;
+    writer
+        .write_all(&bytes)
+        .await
+        .map_err(SwarmError::IoError)?;
+    writer.flush().await.map_err(SwarmError::IoError)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn msgpack_frame(value: &Value) -> Vec<u8> {
+        let body = rmp_serde::to_vec_named(value).unwrap();
+        let mut frame = u32::try_from(body.len()).unwrap().to_be_bytes().to_vec();
+        frame.extend_from_slice(&body);
+        frame
+    }
+
+    #[tokio::test]
+    async fn given_length_prefixed_frames_when_reading_then_each_request_decodes() {
+        let mut input = msgpack_frame(&json!({"cmd": "status", "rid": "r1"}));
+        input.extend(msgpack_frame(&json!({"cmd": "doctor"})));
+        let mut reader = tokio::io::BufReader::new(input.as_slice());
+
+        let mut requests = Vec::new();
+        while let Some(frame) = read_frame(&mut reader, WireFormat::MessagePack, 1024)
+            .await
+            .unwrap()
+        {
+            let RequestFrame::MessagePack(bytes) = frame else {
+                panic!("expected a MessagePack frame");
+            };
+            requests.push(decode_msgpack(&bytes).unwrap());
+        }
+
+        assert_eq!(
+            requests,
+            vec![
+                json!({"cmd": "status", "rid": "r1"}),
+                json!({"cmd": "doctor"})
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn given_truncated_frame_when_reading_then_error_is_returned() {
+        let mut input = msgpack_frame(&json!({"cmd": "status"}));
+        input.truncate(input.len() - 1);
+        let mut reader = tokio::io::BufReader::new(input.as_slice());
+
+        assert!(read_frame(&mut reader, WireFormat::MessagePack, 1024)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn given_line_over_limit_when_reading_then_it_is_skipped_and_next_line_is_read() {
+        let input = format!("{}\n{{\"cmd\":\"status\"}}\r\n", "x".repeat(40));
+        let mut reader = tokio::io::BufReader::with_capacity(8, input.as_bytes());
+
+        let first = read_frame(&mut reader, WireFormat::Json, 16).await.unwrap();
+        let second = read_frame(&mut reader, WireFormat::Json, 16).await.unwrap();
+
+        assert_eq!(
+            first,
+            Some(RequestFrame::Oversized {
+                format: WireFormat::Json,
+                bytes: 41,
+                max_bytes: 16,
+            })
+        );
+        assert_eq!(
+            second,
+            Some(RequestFrame::Line("{\"cmd\":\"status\"}".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn given_frame_over_limit_when_reading_then_body_is_skipped() {
+        let mut input = msgpack_frame(&json!({"cmd": "status", "pad": "x".repeat(64)}));
+        input.extend(msgpack_frame(&json!({"cmd": "doctor"})));
+        let mut reader = tokio::io::BufReader::new(input.as_slice());
+
+        let first = read_frame(&mut reader, WireFormat::MessagePack, 32)
+            .await
+            .unwrap();
+        let second = read_frame(&mut reader, WireFormat::MessagePack, 32)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            first,
+            Some(RequestFrame::Oversized { max_bytes: 32, .. })
+        ));
+        assert!(matches!(second, Some(RequestFrame::MessagePack(_))));
+    }
+
+    #[test]
+    fn given_envelope_when_encoding_msgpack_then_frame_round_trips_with_json_keys() {
+        let envelope = ProtocolEnvelope::success(Some("r1".to_string()), json!({"working": 2}))
+            .with_next("swarm status".to_string());
+
+        let bytes = encode_envelope(&envelope, WireFormat::MessagePack).unwrap();
+        let len = u32::from_be_bytes(bytes[..4].try_into().unwrap());
+        let decoded = rmp_serde::from_slice::<Value>(&bytes[4..]).unwrap();
+
+        assert_eq!(usize::try_from(len).unwrap(), bytes.len() - 4);
+        assert_eq!(decoded, serde_json::to_value(&envelope).unwrap());
+    }
+}
@@ -0,0 +1,106 @@
+//! Evaluating the quarantine policy against each agent's behaviour in the
+//! current fingerprint window. Agents past a threshold are quarantined
+//! automatically and stop claiming new beads.
+
+use crate::types::{
+    fingerprint_window_start, AgentHealthReport, AgentQuarantine, QuarantinePolicy,
+    QuarantineSource, FINGERPRINT_BASELINE_WINDOWS, FINGERPRINT_WINDOW_SECS,
+};
+use crate::{RepoId, Result, SwarmDb};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// One agent's health in the current window.
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentHealthRow {
+    pub agent_id: u32,
+    pub bead_id: Option<String>,
+    pub agent_status: String,
+    pub report: AgentHealthReport,
+    pub scope_violations: u32,
+}
+
+/// Outcome of one pass over the agents.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuarantineEvaluation {
+    pub window_start: DateTime<Utc>,
+    pub rows: Vec<AgentHealthRow>,
+    /// Active quarantines after this pass.
+    pub quarantines: Vec<AgentQuarantine>,
+    pub newly_quarantined: Vec<u32>,
+}
+
+/// Refreshes the agents' fingerprint windows and quarantines every agent
+/// `policy` trips that is not quarantined already.
+///
+/// # Errors
+/// Returns an error if reading agent behaviour or writing a quarantine fails.
+pub async fn evaluate_quarantines(
+    db: &SwarmDb,
+    repo_id: &RepoId,
+    policy: &QuarantinePolicy,
+) -> Result<QuarantineEvaluation> {
+    let window_start = fingerprint_window_start(Utc::now(), FINGERPRINT_WINDOW_SECS);
+    let windows = db
+        .refresh_agent_fingerprints(
+            repo_id,
+            FINGERPRINT_WINDOW_SECS,
+            FINGERPRINT_BASELINE_WINDOWS,
+            window_start,
+        )
+        .await?;
+    let scope_violations = db.get_scope_violation_counts(repo_id, window_start).await?;
+    let rows = db
+        .get_agent_fingerprints(repo_id)
+        .await?
+        .into_iter()
+        .map(|(agent_id, bead_id, agent_status, fingerprint)| {
+            let agent_windows = windows
+                .iter()
+                .filter(|window| window.agent_id == agent_id)
+                .copied()
+                .collect::<Vec<_>>();
+            AgentHealthRow {
+                agent_id,
+                bead_id,
+                agent_status,
+                report: AgentHealthReport::assess(fingerprint, &agent_windows, window_start),
+                scope_violations: scope_violations
+                    .iter()
+                    .find(|(id, _)| *id == agent_id)
+                    .map_or(0, |(_, count)| *count),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut quarantines = db.get_active_quarantines(repo_id).await?;
+    let mut newly_quarantined = Vec::new();
+    for row in &rows {
+        if quarantines.iter().any(|q| q.agent_id == row.agent_id) {
+            continue;
+        }
+        let Some(reason) = policy.evaluate(
+            &row.report.fingerprint,
+            row.report.current.as_ref(),
+            row.scope_violations,
+        ) else {
+            continue;
+        };
+        if db
+            .quarantine_agent(repo_id, row.agent_id, QuarantineSource::Automatic, &reason)
+            .await?
+        {
+            newly_quarantined.push(row.agent_id);
+        }
+    }
+    if !newly_quarantined.is_empty() {
+        quarantines = db.get_active_quarantines(repo_id).await?;
+    }
+
+    Ok(QuarantineEvaluation {
+        window_start,
+        rows,
+        quarantines,
+        newly_quarantined,
+    })
+}
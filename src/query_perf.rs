@@ -0,0 +1,385 @@
+//! Query latency baselines.
+//!
+//! Every SQL statement this process runs is timed into a per-query
+//! histogram, the histograms are written to `query_latency` every minute in
+//! a session (and when a one-shot command records its audit row), and
+//! `perf compare` ranks queries whose p95 got worse after a point in time
+//! such as an upgrade.
+//!
+//! Statements are timed from the `sqlx::query` events sqlx emits once
+//! [`query_layer`] is installed, so every read and write is covered
+//! without touching the query code. A query is named by its verb, first
+//! table and a fingerprint of its text, e.g. `select bead_backlog #1a2b3c4d`,
+//! so two different statements on one table are told apart while the name
+//! stays the same across releases that leave the SQL alone.
+
+use crate::profiling::{query_class, HotspotCategory};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::subscriber::Interest;
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Filter, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Upper bounds of the latency buckets, in ms. One more bucket holds
+/// everything slower than the last bound.
+pub const LATENCY_BUCKET_BOUNDS_MS: [f64; 14] = [
+    0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1_000.0, 2_000.0, 5_000.0,
+];
+/// How often a session writes its histograms out.
+pub const QUERY_LATENCY_FLUSH_MS: u64 = 60_000;
+/// Default p95 growth, in percent, that counts as a regression.
+pub const DEFAULT_REGRESSION_THRESHOLD_PCT: u32 = 50;
+/// Default fewest samples a query needs on both sides to be compared.
+pub const DEFAULT_MIN_SAMPLES: u64 = 20;
+/// Default length of the baseline window before `since`.
+pub const DEFAULT_BASELINE_DAYS: u32 = 7;
+/// Longest baseline window.
+pub const MAX_BASELINE_DAYS: u32 = 365;
+
+const SQLX_QUERY_TARGET: &str = "sqlx::query";
+const FINGERPRINT_HEX_CHARS: usize = 8;
+
+/// Histograms collected since the last flush, by query name.
+static HISTOGRAMS: Mutex<Option<HashMap<String, LatencyHistogram>>> = Mutex::new(None);
+
+/// Latencies of one query in fixed buckets; see [`LATENCY_BUCKET_BOUNDS_MS`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencyHistogram {
+    /// Samples per bucket; `counts[i]` took at most the `i`th bound and the
+    /// last entry took longer than every bound.
+    pub counts: Vec<u64>,
+    pub total_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencyHistogram {
+    pub fn observe(&mut self, ms: f64) {
+        self.counts
+            .resize(self.counts.len().max(LATENCY_BUCKET_BOUNDS_MS.len() + 1), 0);
+        let bucket = LATENCY_BUCKET_BOUNDS_MS.partition_point(|bound| *bound < ms);
+        self.counts[bucket] += 1;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, more) in self.counts.iter_mut().zip(&other.counts) {
+            *count += more;
+        }
+        self.total_ms += other.total_ms;
+        self.max_ms = self.max_ms.max(other.max_ms);
+    }
+
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The `q` quantile (0.95 for p95), interpolated inside its bucket and
+    /// never above the slowest sample. `None` without samples.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let rank = q.clamp(0.0, 1.0) * self.count() as f64;
+        let mut below = 0.0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            let count = *count as f64;
+            if below + count >= rank {
+                let lower = bucket
+                    .checked_sub(1)
+                    .and_then(|index| LATENCY_BUCKET_BOUNDS_MS.get(index))
+                    .copied()
+                    .unwrap_or(0.0);
+                let upper = LATENCY_BUCKET_BOUNDS_MS
+                    .get(bucket)
+                    .copied()
+                    .unwrap_or(self.max_ms)
+                    .max(lower);
+                let value = (upper - lower).mul_add((rank - below) / count, lower);
+                return Some(value.min(self.max_ms.max(lower)));
+            }
+            below += count;
+        }
+        None
+    }
+}
+
+/// A query whose p95 in the current window exceeds its baseline p95 by
+/// more than the threshold.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryRegression {
+    pub query: String,
+    pub baseline_p95_ms: f64,
+    pub current_p95_ms: f64,
+    pub change_pct: f64,
+    pub baseline_samples: u64,
+    pub current_samples: u64,
+}
+
+/// The outcome of comparing two windows of histograms.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PerfComparison {
+    /// Worst first.
+    pub regressions: Vec<QueryRegression>,
+    /// Queries with enough samples on both sides.
+    pub compared: usize,
+    /// Queries seen only in the current window, e.g. added by an upgrade.
+    pub new_queries: Vec<String>,
+    /// Queries below `min_samples` on either side.
+    pub too_few_samples: usize,
+}
+
+/// Compares each query's p95 in `current` against `baseline`.
+#[must_use]
+pub fn compare<S: BuildHasher>(
+    baseline: &HashMap<String, LatencyHistogram, S>,
+    current: &HashMap<String, LatencyHistogram, S>,
+    threshold_pct: u32,
+    min_samples: u64,
+) -> PerfComparison {
+    let mut comparison = PerfComparison::default();
+    for (query, now) in current {
+        let Some(before) = baseline.get(query) else {
+            comparison.new_queries.push(query.clone());
+            continue;
+        };
+        if before.count() < min_samples || now.count() < min_samples {
+            comparison.too_few_samples += 1;
+            continue;
+        }
+        comparison.compared += 1;
+        let (Some(baseline_p95), Some(current_p95)) = (before.quantile(0.95), now.quantile(0.95))
+        else {
+            continue;
+        };
+        let change_pct = if baseline_p95 > 0.0 {
+            (current_p95 - baseline_p95) / baseline_p95 * 100.0
+        } else {
+            0.0
+        };
+        if change_pct > f64::from(threshold_pct) {
+            comparison.regressions.push(QueryRegression {
+                query: query.clone(),
+                baseline_p95_ms: baseline_p95,
+                current_p95_ms: current_p95,
+                change_pct,
+                baseline_samples: before.count(),
+                current_samples: now.count(),
+            });
+        }
+    }
+    comparison
+        .regressions
+        .sort_by(|left, right| right.change_pct.total_cmp(&left.change_pct));
+    comparison.new_queries.sort();
+    comparison
+}
+
+/// `select bead_backlog #1a2b3c4d`: [`query_class`] plus a fingerprint of
+/// the statement with its whitespace collapsed.
+#[must_use]
+pub fn query_name(sql: &str) -> String {
+    let normalized = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    let digest = format!("{:x}", Sha256::digest(normalized.as_bytes()));
+    format!(
+        "{} #{}",
+        query_class(&normalized),
+        &digest[..FINGERPRINT_HEX_CHARS]
+    )
+}
+
+/// Adds one timed statement to the histograms.
+pub fn observe(query: &str, elapsed: Duration) {
+    let mut histograms = HISTOGRAMS.lock().unwrap_or_else(PoisonError::into_inner);
+    histograms
+        .get_or_insert_with(HashMap::new)
+        .entry(query.to_string())
+        .or_default()
+        .observe(elapsed.as_secs_f64() * 1000.0);
+}
+
+/// Takes the histograms collected since the last call.
+#[must_use]
+pub fn take_histograms() -> HashMap<String, LatencyHistogram> {
+    HISTOGRAMS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+        .unwrap_or_default()
+}
+
+/// Puts back histograms that could not be written, so the next flush
+/// retries them.
+pub fn restore_histograms<S: BuildHasher>(unwritten: HashMap<String, LatencyHistogram, S>) {
+    let mut guard = HISTOGRAMS.lock().unwrap_or_else(PoisonError::into_inner);
+    let histograms = guard.get_or_insert_with(HashMap::new);
+    for (query, histogram) in unwritten {
+        histograms.entry(query).or_default().merge(&histogram);
+    }
+    drop(guard);
+}
+
+/// Times each SQL statement from sqlx's `sqlx::query` events.
+///
+/// Samples go into the histograms and into any open `swarm profile` window.
+/// Install it next to the log output; it takes those events whatever the
+/// log filter says.
+#[must_use]
+pub fn query_layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    QueryLayer.with_filter(SqlxQueries)
+}
+
+struct QueryLayer;
+
+impl<S: Subscriber> Layer<S> for QueryLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = QueryFields::default();
+        event.record(&mut fields);
+        let Some(elapsed_secs) = fields.elapsed_secs else {
+            return;
+        };
+        // `db.statement` is empty when the summary is the whole statement.
+        let sql = fields
+            .statement
+            .filter(|statement| !statement.trim().is_empty())
+            .or(fields.summary)
+            .unwrap_or_default();
+        let elapsed = Duration::try_from_secs_f64(elapsed_secs).unwrap_or_default();
+        observe(&query_name(&sql), elapsed);
+        crate::profiling::record(HotspotCategory::Db, &query_class(&sql), elapsed);
+    }
+}
+
+#[derive(Default)]
+struct QueryFields {
+    summary: Option<String>,
+    statement: Option<String>,
+    elapsed_secs: Option<f64>,
+}
+
+impl Visit for QueryFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = Some(value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "summary" => self.summary = Some(value.to_string()),
+            "db.statement" => self.statement = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "summary" => self.summary = Some(format!("{value:?}")),
+            "db.statement" => self.statement = Some(format!("{value:?}")),
+            _ => {}
+        }
+    }
+}
+
+/// Enables `sqlx::query` events for [`QueryLayer`] only.
+struct SqlxQueries;
+
+impl<S> Filter<S> for SqlxQueries {
+    fn enabled(&self, meta: &Metadata<'_>, _cx: &Context<'_, S>) -> bool {
+        meta.target() == SQLX_QUERY_TARGET
+    }
+
+    fn callsite_enabled(&self, meta: &'static Metadata<'static>) -> Interest {
+        if meta.target() == SQLX_QUERY_TARGET {
+            Interest::always()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::DEBUG)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn histogram(samples: &[f64]) -> LatencyHistogram {
+        let mut histogram = LatencyHistogram::default();
+        for ms in samples {
+            histogram.observe(*ms);
+        }
+        histogram
+    }
+
+    #[test]
+    fn given_samples_when_taking_quantiles_then_they_interpolate_within_buckets() {
+        let fast = histogram(&[3.0; 100]);
+        let mut slow = histogram(&[3.0; 90]);
+        slow.merge(&histogram(&[400.0; 10]));
+
+        assert_eq!(fast.count(), 100);
+        assert!(fast
+            .quantile(0.95)
+            .is_some_and(|p95| p95 > 2.0 && p95 <= 3.0));
+        assert!(slow
+            .quantile(0.95)
+            .is_some_and(|p95| p95 > 200.0 && p95 <= 400.0));
+        assert!((histogram(&[9_000.0]).quantile(1.0).unwrap() - 9_000.0).abs() < 1e-9);
+        assert_eq!(LatencyHistogram::default().quantile(0.95), None);
+    }
+
+    #[test]
+    fn given_windows_when_comparing_then_only_p95_growth_past_threshold_is_reported() {
+        let baseline = HashMap::from([
+            ("select a #1".to_string(), histogram(&[1.5; 40])),
+            ("select b #2".to_string(), histogram(&[1.5; 40])),
+            ("select c #3".to_string(), histogram(&[1.5; 5])),
+        ]);
+        let current = HashMap::from([
+            ("select a #1".to_string(), histogram(&[40.0; 40])),
+            ("select b #2".to_string(), histogram(&[1.6; 40])),
+            ("select c #3".to_string(), histogram(&[40.0; 40])),
+            ("insert d #4".to_string(), histogram(&[1.0; 40])),
+        ]);
+
+        let comparison = compare(&baseline, &current, 50, 20);
+
+        assert_eq!(comparison.regressions.len(), 1);
+        assert_eq!(comparison.regressions[0].query, "select a #1");
+        assert_eq!(comparison.compared, 2);
+        assert_eq!(comparison.too_few_samples, 1);
+        assert_eq!(comparison.new_queries, ["insert d #4"]);
+    }
+
+    #[test]
+    fn given_reformatted_statement_when_naming_then_name_is_unchanged() {
+        let name = query_name("SELECT bead_id FROM bead_backlog WHERE repo_id = $1");
+
+        assert!(name.starts_with("select bead_backlog #"));
+        assert_eq!(
+            name,
+            query_name("SELECT bead_id\n  FROM bead_backlog\n  WHERE repo_id = $1")
+        );
+        assert_ne!(name, query_name("SELECT * FROM bead_backlog"));
+    }
+}
@@ -7,7 +7,10 @@
 
 use super::BeadExecutionStatus;
 use crate::runtime::shared::RuntimeError;
-use crate::runtime::stage::{decision_from_stage_dag, Stage, StageResult, TransitionDecision};
+use crate::runtime::stage::{
+    decision_from_stage_dag, Stage, StageResult, StageTransition, TransitionDecision,
+    TransitionReason,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -70,6 +73,12 @@ impl BeadExecution {
                 "Stage result Started cannot produce a transition decision".to_string(),
             ));
         }
+        if matches!(result, StageResult::Cancelled(_)) {
+            return Ok(TransitionDecision::new(
+                StageTransition::NoOp,
+                TransitionReason::StageCancelled,
+            ));
+        }
 
         let retry_exhausted = self.implementation_attempt >= self.max_implementation_attempts;
         Ok(decision_from_stage_dag(
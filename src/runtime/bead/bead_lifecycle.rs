@@ -0,0 +1,362 @@
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+#![deny(clippy::panic)]
+#![warn(clippy::pedantic)]
+#![warn(clippy::nursery)]
+#![forbid(unsafe_code)]
+
+use crate::runtime::shared::{Result, RuntimeError};
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// Where a bead is in its lifecycle, as a plain value for storage and
+/// reporting. [`BeadLifecycle`] is the typed form that only offers legal
+/// transitions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BeadLifecycleState {
+    Pending,
+    Claimed,
+    InProgress,
+    Blocked,
+    Completed,
+    Cancelled,
+    DeadLettered,
+}
+
+impl BeadLifecycleState {
+    pub const ALL: [Self; 7] = [
+        Self::Pending,
+        Self::Claimed,
+        Self::InProgress,
+        Self::Blocked,
+        Self::Completed,
+        Self::Cancelled,
+        Self::DeadLettered,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Claimed => "claimed",
+            Self::InProgress => "in_progress",
+            Self::Blocked => "blocked",
+            Self::Completed => "completed",
+            Self::Cancelled => "cancelled",
+            Self::DeadLettered => "dead_lettered",
+        }
+    }
+
+    /// The `bead_backlog.status` this state is stored as. A claim moves the
+    /// backlog row to `in_progress` straight away, and dead-lettered beads
+    /// stay `blocked` so nothing picks them up again.
+    #[must_use]
+    pub const fn backlog_status(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Claimed | Self::InProgress => "in_progress",
+            Self::Blocked | Self::DeadLettered => "blocked",
+            Self::Completed => "completed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    /// Reads a `bead_backlog.status`. An `in_progress` row is taken as
+    /// [`Self::InProgress`], since storage does not tell claimed and started
+    /// apart; `awaiting_approval` beads are still in progress.
+    #[must_use]
+    pub fn from_backlog_status(status: &str) -> Option<Self> {
+        match status {
+            "pending" => Some(Self::Pending),
+            "in_progress" | "awaiting_approval" => Some(Self::InProgress),
+            "blocked" => Some(Self::Blocked),
+            "completed" => Some(Self::Completed),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_terminal(self) -> bool {
+        matches!(self, Self::Completed | Self::Cancelled | Self::DeadLettered)
+    }
+
+    /// Whether the lifecycle allows moving from `self` to `next`; the same
+    /// edges [`BeadLifecycle`] offers as methods.
+    #[must_use]
+    pub const fn can_transition_to(self, next: Self) -> bool {
+        matches!(
+            (self, next),
+            (Self::Pending, Self::Claimed | Self::Cancelled)
+                | (
+                    Self::Claimed,
+                    Self::InProgress | Self::Pending | Self::Cancelled
+                )
+                | (
+                    Self::InProgress,
+                    Self::Blocked
+                        | Self::Completed
+                        | Self::Cancelled
+                        | Self::DeadLettered
+                        | Self::Pending
+                )
+                | (
+                    Self::Blocked,
+                    Self::Pending | Self::Cancelled | Self::DeadLettered
+                )
+        )
+    }
+
+    /// Checked transition for states only known at runtime, such as a status
+    /// read back from storage.
+    ///
+    /// # Errors
+    /// Returns an invariant violation if the lifecycle has no such edge.
+    pub fn transition_to(self, next: Self) -> Result<Self> {
+        if self.can_transition_to(next) {
+            Ok(next)
+        } else {
+            Err(RuntimeError::InvariantViolation(format!(
+                "bead cannot move from {} to {}",
+                self.as_str(),
+                next.as_str()
+            )))
+        }
+    }
+}
+
+impl std::fmt::Display for BeadLifecycleState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Marker types for [`BeadLifecycle`]'s state parameter.
+pub mod states {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Pending;
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Claimed;
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct InProgress;
+    /// In progress, held at an approval gate.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct AwaitingApproval;
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Blocked;
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Completed;
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Cancelled;
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct DeadLettered;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A state [`BeadLifecycle`] can be in. Sealed: the set of states is fixed.
+pub trait LifecycleState: sealed::Sealed + Copy {
+    const STATE: BeadLifecycleState;
+    /// The `bead_backlog.status` this state is stored as.
+    const BACKLOG_STATUS: &'static str = Self::STATE.backlog_status();
+}
+
+/// A state in which the bead has a row in `bead_claims`.
+pub trait ClaimedLifecycleState: LifecycleState {
+    /// The `bead_claims.status` this state is stored as.
+    const CLAIM_STATUS: &'static str;
+}
+
+macro_rules! lifecycle_state {
+    ($marker:ident $(, claim = $claim:literal)?) => {
+        impl sealed::Sealed for states::$marker {}
+        impl LifecycleState for states::$marker {
+            const STATE: BeadLifecycleState = BeadLifecycleState::$marker;
+        }
+        $(
+            impl ClaimedLifecycleState for states::$marker {
+                const CLAIM_STATUS: &'static str = $claim;
+            }
+        )?
+    };
+}
+
+lifecycle_state!(Pending);
+lifecycle_state!(Claimed, claim = "in_progress");
+lifecycle_state!(InProgress, claim = "in_progress");
+lifecycle_state!(Blocked, claim = "blocked");
+lifecycle_state!(Completed, claim = "completed");
+lifecycle_state!(Cancelled);
+lifecycle_state!(DeadLettered, claim = "blocked");
+
+impl sealed::Sealed for states::AwaitingApproval {}
+impl LifecycleState for states::AwaitingApproval {
+    const STATE: BeadLifecycleState = BeadLifecycleState::InProgress;
+    const BACKLOG_STATUS: &'static str = "awaiting_approval";
+}
+impl ClaimedLifecycleState for states::AwaitingApproval {
+    const CLAIM_STATUS: &'static str = "in_progress";
+}
+
+/// A bead's lifecycle with its state in the type, so only legal transitions
+/// compile:
+///
+/// ```text
+/// Pending ──claim──▶ Claimed ──start──▶ InProgress ──complete──▶ Completed
+///    ▲                  │                   │ block        ──▶ Blocked
+///    └──── release ─────┴──── requeue ──────┤ dead_letter  ──▶ DeadLettered
+///                                           │ cancel       ──▶ Cancelled
+///                                           └ hold_for_approval ──▶ AwaitingApproval
+/// ```
+///
+/// Blocked beads can be requeued, cancelled or dead-lettered; a bead
+/// awaiting approval can be approved back into progress, requeued or
+/// cancelled. The three terminal states have no transitions. Write paths
+/// start from [`BeadLifecycle::pending`] or, for rows whose status the query
+/// itself guards, [`BeadLifecycle::observed`], and store the status strings
+/// of the state they end in.
+///
+/// ```compile_fail
+/// use swarm::runtime::BeadLifecycle;
+///
+/// // A pending bead has to be claimed and started before it can complete.
+/// let _ = BeadLifecycle::pending().complete();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeadLifecycle<S: LifecycleState> {
+    state: PhantomData<S>,
+}
+
+impl BeadLifecycle<states::Pending> {
+    /// A bead newly added to the backlog.
+    #[must_use]
+    pub const fn pending() -> Self {
+        Self::observed()
+    }
+}
+
+impl<S: LifecycleState> BeadLifecycle<S> {
+    /// A bead already known to be in `S`, e.g. because the update that moves
+    /// it filters on `S`'s stored status.
+    #[must_use]
+    pub const fn observed() -> Self {
+        Self { state: PhantomData }
+    }
+
+    #[must_use]
+    pub const fn state(self) -> BeadLifecycleState {
+        S::STATE
+    }
+
+    #[must_use]
+    pub const fn backlog_status(self) -> &'static str {
+        S::BACKLOG_STATUS
+    }
+}
+
+impl<S: ClaimedLifecycleState> BeadLifecycle<S> {
+    #[must_use]
+    pub const fn claim_status(self) -> &'static str {
+        S::CLAIM_STATUS
+    }
+}
+
+impl BeadLifecycle<states::Pending> {
+    #[must_use]
+    pub const fn claim(self) -> BeadLifecycle<states::Claimed> {
+        BeadLifecycle::observed()
+    }
+
+    #[must_use]
+    pub const fn cancel(self) -> BeadLifecycle<states::Cancelled> {
+        BeadLifecycle::observed()
+    }
+}
+
+impl BeadLifecycle<states::Claimed> {
+    #[must_use]
+    pub const fn start(self) -> BeadLifecycle<states::InProgress> {
+        BeadLifecycle::observed()
+    }
+
+    #[must_use]
+    pub const fn release(self) -> BeadLifecycle<states::Pending> {
+        BeadLifecycle::observed()
+    }
+
+    #[must_use]
+    pub const fn cancel(self) -> BeadLifecycle<states::Cancelled> {
+        BeadLifecycle::observed()
+    }
+}
+
+impl BeadLifecycle<states::InProgress> {
+    #[must_use]
+    pub const fn block(self) -> BeadLifecycle<states::Blocked> {
+        BeadLifecycle::observed()
+    }
+
+    #[must_use]
+    pub const fn complete(self) -> BeadLifecycle<states::Completed> {
+        BeadLifecycle::observed()
+    }
+
+    #[must_use]
+    pub const fn cancel(self) -> BeadLifecycle<states::Cancelled> {
+        BeadLifecycle::observed()
+    }
+
+    #[must_use]
+    pub const fn dead_letter(self) -> BeadLifecycle<states::DeadLettered> {
+        BeadLifecycle::observed()
+    }
+
+    /// Back to the backlog, e.g. when the claim's lease expired.
+    #[must_use]
+    pub const fn requeue(self) -> BeadLifecycle<states::Pending> {
+        BeadLifecycle::observed()
+    }
+
+    #[must_use]
+    pub const fn hold_for_approval(self) -> BeadLifecycle<states::AwaitingApproval> {
+        BeadLifecycle::observed()
+    }
+}
+
+impl BeadLifecycle<states::AwaitingApproval> {
+    /// Every gate on the bead was granted; work resumes.
+    #[must_use]
+    pub const fn approve(self) -> BeadLifecycle<states::InProgress> {
+        BeadLifecycle::observed()
+    }
+
+    #[must_use]
+    pub const fn requeue(self) -> BeadLifecycle<states::Pending> {
+        BeadLifecycle::observed()
+    }
+
+    #[must_use]
+    pub const fn cancel(self) -> BeadLifecycle<states::Cancelled> {
+        BeadLifecycle::observed()
+    }
+}
+
+impl BeadLifecycle<states::Blocked> {
+    #[must_use]
+    pub const fn requeue(self) -> BeadLifecycle<states::Pending> {
+        BeadLifecycle::observed()
+    }
+
+    #[must_use]
+    pub const fn cancel(self) -> BeadLifecycle<states::Cancelled> {
+        BeadLifecycle::observed()
+    }
+
+    #[must_use]
+    pub const fn dead_letter(self) -> BeadLifecycle<states::DeadLettered> {
+        BeadLifecycle::observed()
+    }
+}
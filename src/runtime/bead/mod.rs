@@ -6,9 +6,13 @@
 #![forbid(unsafe_code)]
 
 mod bead_execution;
+mod bead_lifecycle;
 mod bead_status;
 
 pub use bead_execution::BeadExecution;
+pub use bead_lifecycle::{
+    states, BeadLifecycle, BeadLifecycleState, ClaimedLifecycleState, LifecycleState,
+};
 pub use bead_status::BeadExecutionStatus;
 
 #[cfg(test)]
@@ -7,7 +7,9 @@
 
 #[cfg(test)]
 mod bdd_tests {
-    use crate::runtime::bead::{BeadExecution, BeadExecutionStatus};
+    use crate::runtime::bead::{
+        BeadExecution, BeadExecutionStatus, BeadLifecycle, BeadLifecycleState,
+    };
     use crate::runtime::stage::{Stage, StageResult, StageTransition};
 
     fn given_an_active_bead_at(stage: Stage, attempt: u32) -> BeadExecution {
@@ -74,6 +76,19 @@ mod bdd_tests {
         );
     }
 
+    #[test]
+    fn when_stage_is_cancelled_then_no_transition_is_taken() -> Result<(), String> {
+        let bead = given_an_active_bead_at(Stage::QaEnforcer, 1);
+        let Ok(decision) =
+            bead.determine_transition(&StageResult::Cancelled("operator".to_string()))
+        else {
+            return Err("cancelled result was rejected".to_string());
+        };
+
+        assert_eq!(decision.transition(), StageTransition::NoOp);
+        Ok(())
+    }
+
     #[test]
     fn when_stage_fails_and_can_retry_then_retries() {
         let bead = given_an_active_bead_at(Stage::Implement, 1);
@@ -111,4 +126,62 @@ mod bdd_tests {
 
         assert_eq!(decision.transition(), StageTransition::Complete);
     }
+
+    #[test]
+    fn when_claimed_bead_is_started_and_completed_then_statuses_follow_the_lifecycle() {
+        let claimed = BeadLifecycle::pending().claim();
+        let completed = claimed.start().complete();
+
+        assert_eq!(claimed.backlog_status(), "in_progress");
+        assert_eq!(claimed.claim_status(), "in_progress");
+        assert_eq!(completed.state(), BeadLifecycleState::Completed);
+        assert_eq!(completed.backlog_status(), "completed");
+        assert_eq!(completed.claim_status(), "completed");
+    }
+
+    #[test]
+    fn when_held_for_approval_then_backlog_status_round_trips_to_in_progress() {
+        let active = BeadLifecycle::pending().claim().start();
+        let held = active.hold_for_approval();
+
+        assert_eq!(held.backlog_status(), "awaiting_approval");
+        assert_eq!(held.claim_status(), "in_progress");
+        assert_eq!(
+            BeadLifecycleState::from_backlog_status(held.backlog_status()),
+            Some(held.state())
+        );
+        assert_eq!(held.approve().backlog_status(), active.backlog_status());
+    }
+
+    #[test]
+    fn when_dead_lettered_then_bead_stays_blocked_in_storage() {
+        let dead = BeadLifecycle::pending()
+            .claim()
+            .start()
+            .block()
+            .dead_letter();
+
+        assert_eq!(dead.backlog_status(), "blocked");
+        assert!(dead.state().is_terminal());
+    }
+
+    #[test]
+    fn when_checking_runtime_edges_then_they_match_the_typed_transitions() {
+        assert!(BeadLifecycleState::Pending
+            .transition_to(BeadLifecycleState::Claimed)
+            .is_ok());
+        assert!(BeadLifecycleState::Pending
+            .transition_to(BeadLifecycleState::Completed)
+            .is_err());
+        assert!(BeadLifecycleState::ALL
+            .iter()
+            .all(|state| !state.is_terminal()
+                || BeadLifecycleState::ALL
+                    .iter()
+                    .all(|next| !state.can_transition_to(*next))));
+        assert_eq!(
+            BeadLifecycleState::from_backlog_status("awaiting_approval"),
+            Some(BeadLifecycleState::InProgress)
+        );
+    }
 }
@@ -13,7 +13,9 @@ pub mod stage;
 pub mod transition;
 
 pub use agent::{AgentState as RuntimeAgentState, AgentStatus as RuntimeAgentStatus};
-pub use bead::{BeadExecution, BeadExecutionStatus};
+pub use bead::{
+    states as bead_states, BeadExecution, BeadExecutionStatus, BeadLifecycle, BeadLifecycleState,
+};
 pub use repositories::{
     RuntimePgAgentRepository, RuntimePgBeadRepository, RuntimePgStageRepository,
 };
@@ -25,5 +27,6 @@ pub use stage::{
 };
 pub use transition::{
     runtime_determine_transition, runtime_determine_transition_decision,
-    validate_completion_requires_push_confirmation,
+    runtime_explain_transition_decision, validate_completion_requires_push_confirmation,
+    TransitionReasonStep,
 };
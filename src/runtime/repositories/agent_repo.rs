@@ -31,7 +31,7 @@ impl RuntimePgAgentRepository {
         agent_id: &RuntimeAgentId,
     ) -> crate::runtime::shared::Result<Option<AgentState>> {
         let maybe_row = sqlx::query_as::<_, (Option<String>, Option<String>, String, i32)>(
-            "SELECT bead_id, current_stage, status, implementation_attempt FROM agent_state WHERE repo_id = $1 AND agent_id = $2",
+            "SELECT bead_id, current_stage, status, implementation_attempt FROM agent_state WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL",
         )
         .bind(agent_id.repo_id().value())
         .bind(agent_id.number().cast_signed())
@@ -85,7 +85,7 @@ impl RuntimePgAgentRepository {
         agent_id: &RuntimeAgentId,
         status: AgentStatus,
     ) -> crate::runtime::shared::Result<()> {
-        sqlx::query("UPDATE agent_state SET status = $2, last_update = NOW() WHERE repo_id = $1 AND agent_id = $3")
+        sqlx::query("UPDATE agent_state SET status = $2, last_update = NOW() WHERE repo_id = $1 AND agent_id = $3 AND deleted_at IS NULL")
             .bind(agent_id.repo_id().value())
             .bind(status.as_str())
             .bind(agent_id.number().cast_signed())
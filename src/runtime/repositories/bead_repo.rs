@@ -43,7 +43,7 @@ impl RuntimePgBeadRepository {
     /// # Errors
     /// Returns an error if the database operation fails.
     pub async fn release(&self, agent_id: &RuntimeAgentId) -> crate::runtime::shared::Result<()> {
-        sqlx::query("UPDATE agent_state SET bead_id = NULL, current_stage = NULL, status = 'idle' WHERE repo_id = $1 AND agent_id = $2")
+        sqlx::query("UPDATE agent_state SET bead_id = NULL, current_stage = NULL, status = 'idle' WHERE repo_id = $1 AND agent_id = $2 AND deleted_at IS NULL")
              .bind(agent_id.repo_id().value())
              .bind(agent_id.number().cast_signed())
              .execute(&self.pool)
@@ -61,7 +61,8 @@ impl RuntimePgBeadRepository {
         _reason: &str,
     ) -> crate::runtime::shared::Result<()> {
         sqlx::query(
-            "UPDATE bead_backlog SET status = 'blocked' WHERE repo_id = $1 AND bead_id = $2",
+            "UPDATE bead_backlog SET status = 'blocked'
+             WHERE repo_id = $1 AND bead_id = $2 AND deleted_at IS NULL",
         )
         .bind(repo_id.value())
         .bind(bead_id.value())
@@ -91,8 +92,13 @@ impl RuntimePgBeadRepository {
         &self,
         agent_id: &RuntimeAgentId,
         bead_id: &RuntimeBeadId,
-        lease_extension_ms: i32,
+        lease_extension_ms: Option<i32>,
     ) -> crate::runtime::shared::Result<bool> {
+        crate::chaos::db_fault("heartbeat_claim")
+            .map_err(|e| RuntimeError::RepositoryError(format!("heartbeat_claim: {e}")))?;
+        if crate::chaos::drop_heartbeat() {
+            return Ok(true);
+        }
         sqlx::query_scalar::<_, bool>("SELECT heartbeat_bead_claim($1, $2, $3, $4)")
             .bind(agent_id.repo_id().value())
             .bind(agent_id.number().cast_signed())
@@ -150,7 +156,7 @@ impl crate::orchestrator_service::ClaimRepository for RuntimePgBeadRepository {
         &'a self,
         agent_id: &'a RuntimeAgentId,
         bead_id: &'a RuntimeBeadId,
-        lease_extension_ms: i32,
+        lease_extension_ms: Option<i32>,
     ) -> PortFuture<'a, bool> {
         Box::pin(async move {
             self.heartbeat_claim(agent_id, bead_id, lease_extension_ms)
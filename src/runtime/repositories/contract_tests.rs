@@ -63,7 +63,9 @@ mod heartbeat_claim_tests {
         let agent_id = make_agent_id("test-repo", 1);
         let bead_id = make_bead_id("test-bead");
 
-        let result = repo.heartbeat_claim(&agent_id, &bead_id, 60_000).await;
+        let result = repo
+            .heartbeat_claim(&agent_id, &bead_id, Some(60_000))
+            .await;
         assert!(result.is_ok(), "heartbeat_claim should return Ok");
     }
 
@@ -74,7 +76,9 @@ mod heartbeat_claim_tests {
         let agent_id = make_agent_id("test-repo", 1);
         let bead_id = make_bead_id("test-bead");
 
-        let result = repo.heartbeat_claim(&agent_id, &bead_id, 60_000).await;
+        let result = repo
+            .heartbeat_claim(&agent_id, &bead_id, Some(60_000))
+            .await;
         assert!(result.is_ok(), "heartbeat_claim should return Ok");
         let _: bool = result.unwrap();
     }
@@ -88,8 +92,10 @@ mod heartbeat_claim_tests {
         let agent_b = make_agent_id("repo-b", 1);
         let bead_id = make_bead_id("shared-bead-id");
 
-        let result_a: Result<bool, _> = repo.heartbeat_claim(&agent_a, &bead_id, 60_000).await;
-        let result_b: Result<bool, _> = repo.heartbeat_claim(&agent_b, &bead_id, 60_000).await;
+        let result_a: Result<bool, _> =
+            repo.heartbeat_claim(&agent_a, &bead_id, Some(60_000)).await;
+        let result_b: Result<bool, _> =
+            repo.heartbeat_claim(&agent_b, &bead_id, Some(60_000)).await;
 
         assert!(
             result_a.is_ok(),
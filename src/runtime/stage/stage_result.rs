@@ -13,6 +13,7 @@ pub enum StageResult {
     Passed,
     Failed(String),
     Error(String),
+    Cancelled(String),
 }
 
 impl StageResult {
@@ -24,7 +25,7 @@ impl StageResult {
     #[must_use]
     pub fn message(&self) -> Option<&str> {
         match self {
-            Self::Failed(m) | Self::Error(m) => Some(m),
+            Self::Failed(m) | Self::Error(m) | Self::Cancelled(m) => Some(m),
             _ => None,
         }
     }
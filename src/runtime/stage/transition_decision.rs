@@ -15,6 +15,7 @@ pub enum TransitionReason {
     RedQueenPassedComplete,
     StageFailedRetry,
     StageFailedMaxAttemptsReached,
+    StageCancelled,
 }
 
 impl TransitionReason {
@@ -26,6 +27,7 @@ impl TransitionReason {
             Self::RedQueenPassedComplete => "red_queen_passed_complete",
             Self::StageFailedRetry => "stage_failed_retry",
             Self::StageFailedMaxAttemptsReached => "stage_failed_max_attempts_reached",
+            Self::StageCancelled => "stage_cancelled",
         }
     }
 }
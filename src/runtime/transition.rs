@@ -8,6 +8,7 @@
 use crate::runtime::bead::{BeadExecution, BeadExecutionStatus};
 use crate::runtime::shared::RuntimeError;
 use crate::runtime::stage::{Stage, StageResult, StageTransition, TransitionDecision};
+use serde::Serialize;
 
 /// # Errors
 /// Returns an error if the transition requires push confirmation but it was not provided.
@@ -45,6 +46,12 @@ pub fn runtime_determine_transition_decision(
                     "Stage result Started cannot produce a transition decision".to_string(),
                 ));
             }
+            if matches!(result, StageResult::Cancelled(_)) {
+                return Ok(TransitionDecision::new(
+                    StageTransition::NoOp,
+                    crate::runtime::stage::TransitionReason::StageCancelled,
+                ));
+            }
 
             Ok(crate::runtime::stage::decision_from_stage_dag(
                 execution.current_stage(),
@@ -70,3 +77,137 @@ pub fn runtime_determine_transition(
 ) -> StageTransition {
     runtime_determine_transition_decision(stage, result, attempt, max_attempts).transition()
 }
+
+/// One check [`runtime_determine_transition_decision`] made on the way to
+/// its decision.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransitionReasonStep {
+    pub check: &'static str,
+    pub outcome: String,
+}
+
+impl TransitionReasonStep {
+    fn new(check: &'static str, outcome: impl Into<String>) -> Self {
+        Self {
+            check,
+            outcome: outcome.into(),
+        }
+    }
+}
+
+/// [`runtime_determine_transition_decision`] with the checks behind it, in
+/// the order it makes them.
+#[must_use]
+pub fn runtime_explain_transition_decision(
+    stage: Stage,
+    result: &StageResult,
+    attempt: u32,
+    max_attempts: u32,
+) -> (TransitionDecision, Vec<TransitionReasonStep>) {
+    let decision = runtime_determine_transition_decision(stage, result, attempt, max_attempts);
+    let status = if stage == Stage::Done {
+        BeadExecutionStatus::Completed
+    } else {
+        BeadExecutionStatus::Active
+    };
+
+    let mut steps = Vec::new();
+    if let Err(error) = BeadExecution::new(stage, attempt, max_attempts, status) {
+        steps.push(TransitionReasonStep::new(
+            "bead_execution",
+            format!("{error}; an invalid execution blocks the bead"),
+        ));
+        return (decision, steps);
+    }
+    steps.push(TransitionReasonStep::new(
+        "bead_execution",
+        format!(
+            "{} at attempt {attempt} of {max_attempts} is valid",
+            stage.as_str()
+        ),
+    ));
+
+    match result {
+        StageResult::Started => steps.push(TransitionReasonStep::new(
+            "result",
+            "started is not a finished result; it blocks the bead",
+        )),
+        StageResult::Cancelled(_) => steps.push(TransitionReasonStep::new(
+            "result",
+            "cancelled leaves the bead where it is",
+        )),
+        StageResult::Passed => {
+            steps.push(TransitionReasonStep::new("result", "passed"));
+            let outcome = match stage {
+                Stage::RedQueen => "red-queen passing completes the bead".to_string(),
+                Stage::Done => "done has no next stage".to_string(),
+                _ => format!(
+                    "{} advances to {}",
+                    stage.as_str(),
+                    stage.next().map_or("none", |next| next.as_str())
+                ),
+            };
+            steps.push(TransitionReasonStep::new("stage_dag", outcome));
+        }
+        StageResult::Failed(_) | StageResult::Error(_) => {
+            let kind = if matches!(result, StageResult::Failed(_)) {
+                "failed"
+            } else {
+                "error"
+            };
+            steps.push(TransitionReasonStep::new(
+                "result",
+                format!("{kind} is not a success"),
+            ));
+            let outcome = if attempt >= max_attempts {
+                format!("attempt {attempt} reached the limit of {max_attempts}; retries exhausted")
+            } else {
+                format!(
+                    "attempt {attempt} of {max_attempts}; {} retries left",
+                    max_attempts - attempt
+                )
+            };
+            steps.push(TransitionReasonStep::new("retry_budget", outcome));
+        }
+    }
+    (decision, steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{runtime_determine_transition_decision, runtime_explain_transition_decision};
+    use crate::runtime::stage::{Stage, StageResult, StageTransition, TransitionReason};
+
+    #[test]
+    fn given_failure_with_retries_left_when_explaining_then_chain_ends_at_retry_budget() {
+        let result = StageResult::Failed("tests failed".to_string());
+
+        let (decision, steps) =
+            runtime_explain_transition_decision(Stage::QaEnforcer, &result, 1, 3);
+
+        assert_eq!(
+            decision,
+            runtime_determine_transition_decision(Stage::QaEnforcer, &result, 1, 3)
+        );
+        assert_eq!(decision.transition(), StageTransition::Retry);
+        let checks = steps.iter().map(|step| step.check).collect::<Vec<_>>();
+        assert_eq!(checks, vec!["bead_execution", "result", "retry_budget"]);
+        assert_eq!(steps[2].outcome, "attempt 1 of 3; 2 retries left");
+    }
+
+    #[test]
+    fn given_attempt_past_the_limit_when_explaining_then_invalid_execution_blocks() {
+        let (decision, steps) =
+            runtime_explain_transition_decision(Stage::Implement, &StageResult::Passed, 5, 3);
+
+        assert_eq!(decision.transition(), StageTransition::Block);
+        assert_eq!(
+            decision.reason(),
+            TransitionReason::StageFailedMaxAttemptsReached
+        );
+        assert_eq!(steps.len(), 1);
+        assert!(steps[0]
+            .outcome
+            .contains("exceeds max_implementation_attempts"));
+    }
+}
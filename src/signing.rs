@@ -0,0 +1,370 @@
+//! Ed25519 provenance for stage artifacts.
+//!
+//! With `SWARM_SIGNING_KEY` set, every stored artifact (push verifications
+//! included) is signed over its bead, type, content hash and the signature of
+//! the bead's previous signed artifact, so the bead's artifacts form one
+//! chain. `swarm verify` walks that chain with [`verify_artifact_chain`].
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+
+/// Version tag at the start of every signed payload.
+pub const SIGNING_PAYLOAD_VERSION: &str = "swarm-artifact-v1";
+
+/// Signs artifacts with the swarm's private key.
+#[derive(Clone)]
+pub struct ArtifactSigner {
+    key: SigningKey,
+}
+
+impl std::fmt::Debug for ArtifactSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArtifactSigner")
+            .field("key_id", &self.key_id())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ArtifactSigner {
+    /// Builds a signer from a 32-byte seed written as 64 hex characters.
+    ///
+    /// # Errors
+    /// Returns a message when the seed is not 64 hex characters.
+    pub fn from_hex_seed(seed: &str) -> Result<Self, String> {
+        let bytes: [u8; 32] = decode_hex(seed.trim())
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| "signing key must be a 32-byte seed in hex".to_string())?;
+        Ok(Self {
+            key: SigningKey::from_bytes(&bytes),
+        })
+    }
+
+    /// Hex public key; stored beside each signature and used to verify.
+    #[must_use]
+    pub fn key_id(&self) -> String {
+        encode_hex(self.key.verifying_key().as_bytes())
+    }
+
+    #[must_use]
+    pub fn verifier(&self) -> ArtifactVerifier {
+        ArtifactVerifier {
+            key: self.key.verifying_key(),
+        }
+    }
+
+    /// Hex signature of `payload`.
+    #[must_use]
+    pub fn sign(&self, payload: &str) -> String {
+        encode_hex(&self.key.sign(payload.as_bytes()).to_bytes())
+    }
+}
+
+/// Checks signatures against the swarm's public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactVerifier {
+    key: VerifyingKey,
+}
+
+impl ArtifactVerifier {
+    /// # Errors
+    /// Returns a message when the key is not a valid 32-byte Ed25519 public
+    /// key in hex.
+    pub fn from_hex_key(key: &str) -> Result<Self, String> {
+        let bytes: [u8; 32] = decode_hex(key.trim())
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| "verify key must be a 32-byte public key in hex".to_string())?;
+        VerifyingKey::from_bytes(&bytes)
+            .map(|key| Self { key })
+            .map_err(|e| format!("invalid verify key: {e}"))
+    }
+
+    #[must_use]
+    pub fn key_id(&self) -> String {
+        encode_hex(self.key.as_bytes())
+    }
+
+    /// Whether `signature` (hex) signs `payload` under this key.
+    #[must_use]
+    pub fn verify(&self, payload: &str, signature: &str) -> bool {
+        decode_hex(signature)
+            .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+            .is_some_and(|bytes| {
+                self.key
+                    .verify(payload.as_bytes(), &Signature::from_bytes(&bytes))
+                    .is_ok()
+            })
+    }
+}
+
+/// What an artifact's signature covers. `previous_signature` links it to
+/// the bead's previous signed artifact.
+#[must_use]
+pub fn artifact_signing_payload(
+    bead_id: &str,
+    artifact_type: &str,
+    content_hash: &str,
+    previous_signature: Option<&str>,
+) -> String {
+    format!(
+        "{SIGNING_PAYLOAD_VERSION}\n{bead_id}\n{artifact_type}\n{content_hash}\n{}",
+        previous_signature.unwrap_or("")
+    )
+}
+
+/// Hex SHA-256 of `content`, matching the `content_hash` column.
+#[must_use]
+pub fn content_hash(content: &str) -> String {
+    encode_hex(&Sha256::digest(content.as_bytes()))
+}
+
+/// One stored artifact as `verify` reads it, in storage order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedArtifactRecord {
+    pub artifact_id: i64,
+    pub stage_history_id: i64,
+    pub artifact_type: String,
+    pub content: String,
+    pub content_hash: Option<String>,
+    pub signature: Option<String>,
+    pub signing_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactVerificationStatus {
+    Verified,
+    Unsigned,
+    HashMismatch,
+    ForeignKey,
+    BadSignature,
+}
+
+impl ArtifactVerificationStatus {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Verified => "verified",
+            Self::Unsigned => "unsigned",
+            Self::HashMismatch => "hash_mismatch",
+            Self::ForeignKey => "foreign_key",
+            Self::BadSignature => "bad_signature",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactVerification {
+    pub artifact_id: i64,
+    pub stage_history_id: i64,
+    pub artifact_type: String,
+    pub status: ArtifactVerificationStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainVerification {
+    pub key_id: String,
+    /// Every artifact is signed by `key_id`, matches its hash, and links to
+    /// the one before it.
+    pub valid: bool,
+    pub verified: usize,
+    pub unsigned: usize,
+    pub failed: usize,
+    pub rows: Vec<ArtifactVerification>,
+}
+
+/// Walks a bead's artifacts in storage order.
+///
+/// Each signature must verify under `verifier` over the artifact's
+/// recomputed hash and the previous signed artifact's signature, so
+/// removing, reordering or editing a signed artifact breaks every later
+/// link. Nothing signs over the newest artifact, so dropping artifacts from
+/// the end of the chain still leaves it valid.
+#[must_use]
+pub fn verify_artifact_chain(
+    bead_id: &str,
+    artifacts: &[SignedArtifactRecord],
+    verifier: &ArtifactVerifier,
+) -> ChainVerification {
+    let key_id = verifier.key_id();
+    let mut previous: Option<&str> = None;
+    let rows = artifacts
+        .iter()
+        .map(|artifact| {
+            let hash = content_hash(&artifact.content);
+            let status = if artifact
+                .content_hash
+                .as_deref()
+                .is_some_and(|stored| stored != hash)
+            {
+                ArtifactVerificationStatus::HashMismatch
+            } else if let Some(signature) = artifact.signature.as_deref() {
+                let payload =
+                    artifact_signing_payload(bead_id, &artifact.artifact_type, &hash, previous);
+                previous = Some(signature);
+                if artifact.signing_key.as_deref() != Some(key_id.as_str()) {
+                    ArtifactVerificationStatus::ForeignKey
+                } else if verifier.verify(&payload, signature) {
+                    ArtifactVerificationStatus::Verified
+                } else {
+                    ArtifactVerificationStatus::BadSignature
+                }
+            } else {
+                ArtifactVerificationStatus::Unsigned
+            };
+            ArtifactVerification {
+                artifact_id: artifact.artifact_id,
+                stage_history_id: artifact.stage_history_id,
+                artifact_type: artifact.artifact_type.clone(),
+                status,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let count =
+        |status: ArtifactVerificationStatus| rows.iter().filter(|row| row.status == status).count();
+    let verified_rows = count(ArtifactVerificationStatus::Verified);
+    let unsigned_rows = count(ArtifactVerificationStatus::Unsigned);
+    ChainVerification {
+        key_id,
+        valid: !rows.is_empty() && verified_rows == rows.len(),
+        verified: verified_rows,
+        unsigned: unsigned_rows,
+        failed: rows.len() - verified_rows - unsigned_rows,
+        rows,
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| {
+            hex.get(index..index + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    const SEED: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+
+    fn signed_chain(
+        signer: &ArtifactSigner,
+        contents: &[(&str, &str)],
+    ) -> Vec<SignedArtifactRecord> {
+        let mut previous: Option<String> = None;
+        contents
+            .iter()
+            .zip(1_i64..)
+            .map(|((artifact_type, content), id)| {
+                let hash = content_hash(content);
+                let signature = signer.sign(&artifact_signing_payload(
+                    "swm-1",
+                    artifact_type,
+                    &hash,
+                    previous.as_deref(),
+                ));
+                previous = Some(signature.clone());
+                SignedArtifactRecord {
+                    artifact_id: id,
+                    stage_history_id: 1,
+                    artifact_type: (*artifact_type).to_string(),
+                    content: (*content).to_string(),
+                    content_hash: Some(hash),
+                    signature: Some(signature),
+                    signing_key: Some(signer.key_id()),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn given_signed_chain_when_verifying_then_every_artifact_verifies() {
+        let signer = ArtifactSigner::from_hex_seed(SEED).unwrap();
+        let chain = signed_chain(
+            &signer,
+            &[("test_results", "{}"), ("push_verification", "ok")],
+        );
+
+        let report = verify_artifact_chain("swm-1", &chain, &signer.verifier());
+
+        assert!(report.valid);
+        assert_eq!(report.verified, 2);
+    }
+
+    #[test]
+    fn given_edited_or_removed_artifact_when_verifying_then_chain_breaks() {
+        let signer = ArtifactSigner::from_hex_seed(SEED).unwrap();
+        let chain = signed_chain(
+            &signer,
+            &[
+                ("test_results", "{}"),
+                ("stage_log", "a"),
+                ("push_verification", "ok"),
+            ],
+        );
+
+        let mut edited = chain.clone();
+        edited[1].content = "b".to_string();
+        let edited_report = verify_artifact_chain("swm-1", &edited, &signer.verifier());
+        assert_eq!(
+            edited_report.rows[1].status,
+            ArtifactVerificationStatus::HashMismatch
+        );
+
+        let removed = vec![chain[0].clone(), chain[2].clone()];
+        let removed_report = verify_artifact_chain("swm-1", &removed, &signer.verifier());
+        assert!(!removed_report.valid);
+        assert_eq!(
+            removed_report.rows[1].status,
+            ArtifactVerificationStatus::BadSignature
+        );
+    }
+
+    #[test]
+    fn given_other_key_or_unsigned_rows_when_verifying_then_chain_is_invalid() {
+        let signer = ArtifactSigner::from_hex_seed(SEED).unwrap();
+        let other = ArtifactSigner::from_hex_seed(&"11".repeat(32)).unwrap();
+        let mut chain = signed_chain(&signer, &[("test_results", "{}")]);
+        chain.push(SignedArtifactRecord {
+            artifact_id: 9,
+            stage_history_id: 1,
+            artifact_type: "stage_log".to_string(),
+            content: "log".to_string(),
+            content_hash: Some(content_hash("log")),
+            signature: None,
+            signing_key: None,
+        });
+
+        let report = verify_artifact_chain("swm-1", &chain, &other.verifier());
+
+        assert!(!report.valid);
+        assert_eq!(
+            report.rows[0].status,
+            ArtifactVerificationStatus::ForeignKey
+        );
+        assert_eq!(report.unsigned, 1);
+    }
+
+    #[test]
+    fn given_malformed_keys_when_parsing_then_they_are_rejected() {
+        assert!(ArtifactSigner::from_hex_seed("abc").is_err());
+        assert!(ArtifactSigner::from_hex_seed(&"zz".repeat(32)).is_err());
+        assert!(ArtifactVerifier::from_hex_key(&"00".repeat(31)).is_err());
+    }
+}
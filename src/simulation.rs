@@ -0,0 +1,22 @@
+//! Deterministic, in-memory orchestration for scripting scenarios without
+//! Postgres.
+//!
+//! [`SimulatedPorts`] implements every [`crate::OrchestratorPorts`] trait
+//! over plain collections and a [`VirtualClock`], so leases expire only when
+//! the scenario advances time. [`Scenario`] wires N agents and M beads to an
+//! [`crate::OrchestratorService`], replays a [`FailureSchedule`], and returns
+//! a [`ScenarioReport`] of every tick and stage transition. Swap the
+//! transition function to exercise a custom stage DAG.
+
+mod clock;
+mod ports;
+mod scenario;
+
+pub use clock::VirtualClock;
+pub use ports::{
+    FailureSchedule, SimulatedBeadStatus, SimulatedPorts, SimulatedTransition, TransitionFn,
+};
+pub use scenario::{Scenario, ScenarioReport, SimulatedTick};
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,579 @@
+use super::VirtualClock;
+use crate::orchestrator_service::{
+    ArtifactStore, ClaimRepository, EventSink, LandingGateway, LandingOutcome, OrchestratorEvent,
+    PortFuture, StageArtifactRecord, StageExecutionOutcome, StageExecutionRequest, StageExecutor,
+};
+use crate::{
+    runtime_determine_transition_decision, RuntimeAgentId, RuntimeAgentState, RuntimeAgentStatus,
+    RuntimeBeadId, RuntimeRepoId, RuntimeStage, RuntimeStageResult, RuntimeStageTransition,
+    RuntimeTransitionDecision,
+};
+use std::collections::BTreeMap;
+use std::future::ready;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Decides what follows a stage result: `(stage, result, attempt,
+/// max_attempts)`. Defaults to [`runtime_determine_transition_decision`];
+/// replace it to drive a custom stage DAG.
+pub type TransitionFn =
+    fn(RuntimeStage, &RuntimeStageResult, u32, u32) -> RuntimeTransitionDecision;
+
+const DEFAULT_LEASE_MS: u64 = 300_000;
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Scripted faults. Any stage run not listed here passes.
+#[derive(Debug, Clone, Default)]
+pub struct FailureSchedule {
+    stage_results: BTreeMap<(String, &'static str, u32), RuntimeStageResult>,
+    heartbeat_drops: Vec<(u32, u64, u64)>,
+    landing_rejections: BTreeMap<String, u32>,
+}
+
+impl FailureSchedule {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fails `stage` of `bead_id` on implementation attempt `attempt`.
+    #[must_use]
+    pub fn fail_stage(
+        self,
+        bead_id: &str,
+        stage: RuntimeStage,
+        attempt: u32,
+        message: impl Into<String>,
+    ) -> Self {
+        self.stage_result(
+            bead_id,
+            stage,
+            attempt,
+            RuntimeStageResult::Failed(message.into()),
+        )
+    }
+
+    /// Reports `result` for `stage` of `bead_id` on attempt `attempt`.
+    #[must_use]
+    pub fn stage_result(
+        mut self,
+        bead_id: &str,
+        stage: RuntimeStage,
+        attempt: u32,
+        result: RuntimeStageResult,
+    ) -> Self {
+        self.stage_results
+            .insert((bead_id.to_string(), stage.as_str(), attempt), result);
+        self
+    }
+
+    /// Loses agent `agent`'s heartbeats in `[from_ms, until_ms)`: they report
+    /// success without extending the lease.
+    #[must_use]
+    pub fn drop_heartbeats(mut self, agent: u32, from_ms: u64, until_ms: u64) -> Self {
+        self.heartbeat_drops.push((agent, from_ms, until_ms));
+        self
+    }
+
+    /// Rejects the first `times` pushes when landing `bead_id`.
+    #[must_use]
+    pub fn reject_landing(mut self, bead_id: &str, times: u32) -> Self {
+        self.landing_rejections.insert(bead_id.to_string(), times);
+        self
+    }
+
+    fn result_for(&self, bead_id: &str, stage: RuntimeStage, attempt: u32) -> RuntimeStageResult {
+        self.stage_results
+            .get(&(bead_id.to_string(), stage.as_str(), attempt))
+            .cloned()
+            .unwrap_or(RuntimeStageResult::Passed)
+    }
+
+    fn drops_heartbeat(&self, agent: u32, now_ms: u64) -> bool {
+        self.heartbeat_drops
+            .iter()
+            .any(|(dropped, from, until)| *dropped == agent && (*from..*until).contains(&now_ms))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulatedBeadStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Blocked,
+}
+
+/// One stage run and the decision taken on its result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedTransition {
+    pub at_ms: u64,
+    pub agent_id: RuntimeAgentId,
+    pub bead_id: RuntimeBeadId,
+    pub stage: RuntimeStage,
+    pub attempt: u32,
+    pub result: RuntimeStageResult,
+    pub decision: RuntimeTransitionDecision,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Claim {
+    agent: u32,
+    lease_expires_at_ms: u64,
+}
+
+#[derive(Debug, Default)]
+struct World {
+    agents: BTreeMap<u32, RuntimeAgentState>,
+    backlog: Vec<(RuntimeBeadId, SimulatedBeadStatus)>,
+    claims: BTreeMap<String, Claim>,
+    landing_attempts: BTreeMap<String, u32>,
+    workspaces: Vec<(RuntimeAgentId, RuntimeBeadId)>,
+    artifacts: Vec<StageArtifactRecord>,
+    events: Vec<OrchestratorEvent>,
+    transitions: Vec<SimulatedTransition>,
+}
+
+impl World {
+    fn set_bead_status(&mut self, bead_id: &str, status: SimulatedBeadStatus) {
+        if let Some(entry) = self
+            .backlog
+            .iter_mut()
+            .find(|(bead, _)| bead.value() == bead_id)
+        {
+            entry.1 = status;
+        }
+    }
+
+    fn release_claim(&mut self, bead_id: &str, status: SimulatedBeadStatus) {
+        self.claims.remove(bead_id);
+        self.set_bead_status(bead_id, status);
+    }
+}
+
+/// In-memory [`crate::OrchestratorPorts`]. Clones share state, so a caller
+/// can hand one to an [`crate::OrchestratorService`] and inspect another.
+#[derive(Debug, Clone)]
+pub struct SimulatedPorts {
+    clock: VirtualClock,
+    repo_id: RuntimeRepoId,
+    lease_ms: u64,
+    max_attempts: u32,
+    transition: TransitionFn,
+    schedule: Arc<FailureSchedule>,
+    world: Arc<Mutex<World>>,
+}
+
+impl SimulatedPorts {
+    #[must_use]
+    pub fn new(clock: VirtualClock, schedule: FailureSchedule) -> Self {
+        Self {
+            clock,
+            repo_id: RuntimeRepoId::new("local"),
+            lease_ms: DEFAULT_LEASE_MS,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            transition: runtime_determine_transition_decision,
+            schedule: Arc::new(schedule),
+            world: Arc::new(Mutex::new(World::default())),
+        }
+    }
+
+    /// Lease granted when a bead is claimed.
+    #[must_use]
+    pub const fn with_lease_ms(mut self, lease_ms: u64) -> Self {
+        self.lease_ms = lease_ms;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_transition(mut self, transition: TransitionFn) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    /// Registers agent `number` as idle.
+    #[must_use]
+    pub fn with_agent(self, number: u32) -> Self {
+        let agent_id = RuntimeAgentId::new(self.repo_id.clone(), number);
+        self.world().agents.insert(number, idle_state(agent_id));
+        self
+    }
+
+    /// Appends a pending bead to the backlog. Beads are claimed in the order
+    /// they are added.
+    #[must_use]
+    pub fn with_bead(self, bead_id: impl Into<String>) -> Self {
+        self.world()
+            .backlog
+            .push((RuntimeBeadId::new(bead_id), SimulatedBeadStatus::Pending));
+        self
+    }
+
+    #[must_use]
+    pub const fn clock(&self) -> &VirtualClock {
+        &self.clock
+    }
+
+    #[must_use]
+    pub const fn repo_id(&self) -> &RuntimeRepoId {
+        &self.repo_id
+    }
+
+    #[must_use]
+    pub fn agent_ids(&self) -> Vec<RuntimeAgentId> {
+        self.world()
+            .agents
+            .values()
+            .map(|state| state.agent_id().clone())
+            .collect()
+    }
+
+    #[must_use]
+    pub fn agent_states(&self) -> Vec<RuntimeAgentState> {
+        self.world().agents.values().cloned().collect()
+    }
+
+    #[must_use]
+    pub fn beads(&self) -> Vec<(RuntimeBeadId, SimulatedBeadStatus)> {
+        self.world().backlog.clone()
+    }
+
+    #[must_use]
+    pub fn bead_status(&self, bead_id: &str) -> Option<SimulatedBeadStatus> {
+        self.world()
+            .backlog
+            .iter()
+            .find(|(bead, _)| bead.value() == bead_id)
+            .map(|(_, status)| *status)
+    }
+
+    /// Whether any bead is still held under a lease.
+    #[must_use]
+    pub fn has_active_claims(&self) -> bool {
+        !self.world().claims.is_empty()
+    }
+
+    #[must_use]
+    pub fn transitions(&self) -> Vec<SimulatedTransition> {
+        self.world().transitions.clone()
+    }
+
+    #[must_use]
+    pub fn events(&self) -> Vec<OrchestratorEvent> {
+        self.world().events.clone()
+    }
+
+    #[must_use]
+    pub fn artifacts(&self) -> Vec<StageArtifactRecord> {
+        self.world().artifacts.clone()
+    }
+
+    #[must_use]
+    pub fn workspaces(&self) -> Vec<(RuntimeAgentId, RuntimeBeadId)> {
+        self.world().workspaces.clone()
+    }
+
+    fn world(&self) -> MutexGuard<'_, World> {
+        self.world.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn recover(&self, repo_id: &RuntimeRepoId) -> u32 {
+        if repo_id != &self.repo_id {
+            return 0;
+        }
+        let now = self.clock.now_ms();
+        let mut world = self.world();
+        let expired = world
+            .claims
+            .iter()
+            .filter(|(_, claim)| claim.lease_expires_at_ms <= now)
+            .map(|(bead, claim)| (bead.clone(), claim.agent))
+            .collect::<Vec<_>>();
+
+        for (bead, agent) in &expired {
+            world.release_claim(bead, SimulatedBeadStatus::Pending);
+            if let Some(state) = world.agents.get_mut(agent) {
+                if state.bead_id().map(RuntimeBeadId::value) == Some(bead.as_str()) {
+                    *state = idle_state(state.agent_id().clone());
+                }
+            }
+        }
+
+        let count = u32::try_from(expired.len()).unwrap_or(u32::MAX);
+        if count > 0 {
+            world
+                .events
+                .push(OrchestratorEvent::ClaimRecovered { count });
+        }
+        drop(world);
+        count
+    }
+
+    fn claim(&self, agent_id: &RuntimeAgentId) -> Option<RuntimeBeadId> {
+        let now = self.clock.now_ms();
+        let mut world = self.world();
+        if !world.agents.contains_key(&agent_id.number()) {
+            return None;
+        }
+        let bead_id = world
+            .backlog
+            .iter()
+            .find(|(_, status)| *status == SimulatedBeadStatus::Pending)
+            .map(|(bead, _)| bead.clone())?;
+
+        world.set_bead_status(bead_id.value(), SimulatedBeadStatus::InProgress);
+        world.claims.insert(
+            bead_id.value().to_string(),
+            Claim {
+                agent: agent_id.number(),
+                lease_expires_at_ms: now.saturating_add(self.lease_ms),
+            },
+        );
+        world.agents.insert(
+            agent_id.number(),
+            RuntimeAgentState::new(
+                agent_id.clone(),
+                Some(bead_id.clone()),
+                Some(RuntimeStage::RustContract),
+                RuntimeAgentStatus::Working,
+                0,
+            ),
+        );
+        world.events.push(OrchestratorEvent::BeadClaimed {
+            agent_id: agent_id.clone(),
+            bead_id: bead_id.clone(),
+        });
+        drop(world);
+        Some(bead_id)
+    }
+
+    fn heartbeat(
+        &self,
+        agent_id: &RuntimeAgentId,
+        bead_id: &RuntimeBeadId,
+        extension_ms: Option<i32>,
+    ) -> bool {
+        let now = self.clock.now_ms();
+        if self.schedule.drops_heartbeat(agent_id.number(), now) {
+            return true;
+        }
+        let mut world = self.world();
+        match world.claims.get_mut(bead_id.value()) {
+            Some(claim) if claim.agent == agent_id.number() => {
+                let extension_ms =
+                    extension_ms.map_or(self.lease_ms, |ms| u64::try_from(ms).unwrap_or(0));
+                claim.lease_expires_at_ms = now.saturating_add(extension_ms);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn land(&self, bead_id: &RuntimeBeadId) -> LandingOutcome {
+        let mut world = self.world();
+        let attempts = world
+            .landing_attempts
+            .entry(bead_id.value().to_string())
+            .or_insert(0);
+        *attempts = attempts.saturating_add(1);
+        let attempts = *attempts;
+        drop(world);
+        let rejections = self
+            .schedule
+            .landing_rejections
+            .get(bead_id.value())
+            .copied()
+            .unwrap_or(0);
+        if attempts <= rejections {
+            LandingOutcome::new(false, "simulated push rejected")
+        } else {
+            LandingOutcome::new(true, "simulated push")
+        }
+    }
+
+    /// Runs the agent's current stage against the schedule and applies the
+    /// transition function's decision.
+    fn execute(&self, request: &StageExecutionRequest) -> StageExecutionOutcome {
+        let agent_id = request.agent_id();
+        let agent = request.state();
+        let (Some(bead_id), Some(stage)) = (agent.bead_id(), agent.current_stage()) else {
+            return StageExecutionOutcome::Idle;
+        };
+        let owns_claim = self
+            .world()
+            .claims
+            .get(bead_id.value())
+            .is_some_and(|claim| claim.agent == agent_id.number());
+        if !owns_claim {
+            return StageExecutionOutcome::Idle;
+        }
+
+        let attempt = agent.implementation_attempt();
+        let result = self.schedule.result_for(bead_id.value(), stage, attempt);
+        let decision = (self.transition)(stage, &result, attempt, self.max_attempts);
+        let landing = decision
+            .transition()
+            .should_complete()
+            .then(|| self.land(bead_id));
+
+        let mut world = self.world();
+        world.artifacts.push(StageArtifactRecord::new(
+            bead_id.clone(),
+            stage,
+            result.clone(),
+            format!("simulated {} attempt {attempt}", stage.as_str()),
+        ));
+        world.transitions.push(SimulatedTransition {
+            at_ms: self.clock.now_ms(),
+            agent_id: agent_id.clone(),
+            bead_id: bead_id.clone(),
+            stage,
+            attempt,
+            result,
+            decision,
+        });
+
+        let next_state = |stage, status, attempt| {
+            RuntimeAgentState::new(
+                agent_id.clone(),
+                Some(bead_id.clone()),
+                Some(stage),
+                status,
+                attempt,
+            )
+        };
+        let outcome = match decision.transition() {
+            RuntimeStageTransition::Advance(next) => {
+                world.agents.insert(
+                    agent_id.number(),
+                    next_state(next, RuntimeAgentStatus::Working, attempt),
+                );
+                StageExecutionOutcome::Progressed
+            }
+            RuntimeStageTransition::Retry => {
+                world.agents.insert(
+                    agent_id.number(),
+                    next_state(
+                        RuntimeStage::Implement,
+                        RuntimeAgentStatus::Waiting,
+                        attempt.saturating_add(1),
+                    ),
+                );
+                StageExecutionOutcome::Progressed
+            }
+            RuntimeStageTransition::Complete
+                if landing.as_ref().is_some_and(LandingOutcome::push_confirmed) =>
+            {
+                world.release_claim(bead_id.value(), SimulatedBeadStatus::Completed);
+                world.agents.insert(
+                    agent_id.number(),
+                    next_state(RuntimeStage::Done, RuntimeAgentStatus::Done, attempt),
+                );
+                StageExecutionOutcome::Progressed
+            }
+            RuntimeStageTransition::Block => {
+                world.release_claim(bead_id.value(), SimulatedBeadStatus::Blocked);
+                world.agents.insert(
+                    agent_id.number(),
+                    next_state(stage, RuntimeAgentStatus::Error, attempt),
+                );
+                StageExecutionOutcome::Progressed
+            }
+            // An unconfirmed push leaves the agent on its final stage so the
+            // next tick reruns it and lands again.
+            RuntimeStageTransition::Complete | RuntimeStageTransition::NoOp => {
+                StageExecutionOutcome::Idle
+            }
+        };
+        world.events.push(OrchestratorEvent::StageExecuted {
+            agent_id: agent_id.clone(),
+            bead_id: bead_id.clone(),
+            outcome,
+        });
+        drop(world);
+        outcome
+    }
+}
+
+const fn idle_state(agent_id: RuntimeAgentId) -> RuntimeAgentState {
+    RuntimeAgentState::new(agent_id, None, None, RuntimeAgentStatus::Idle, 0)
+}
+
+impl ClaimRepository for SimulatedPorts {
+    fn recover_stale_claims<'a>(&'a self, repo_id: &'a RuntimeRepoId) -> PortFuture<'a, u32> {
+        Box::pin(ready(Ok(self.recover(repo_id))))
+    }
+
+    fn get_agent_state<'a>(
+        &'a self,
+        agent_id: &'a RuntimeAgentId,
+    ) -> PortFuture<'a, Option<RuntimeAgentState>> {
+        let state = self.world().agents.get(&agent_id.number()).cloned();
+        Box::pin(ready(Ok(state)))
+    }
+
+    fn claim_next_bead<'a>(
+        &'a self,
+        agent_id: &'a RuntimeAgentId,
+    ) -> PortFuture<'a, Option<RuntimeBeadId>> {
+        Box::pin(ready(Ok(self.claim(agent_id))))
+    }
+
+    fn create_workspace<'a>(
+        &'a self,
+        agent_id: &'a RuntimeAgentId,
+        bead_id: &'a RuntimeBeadId,
+    ) -> PortFuture<'a, ()> {
+        self.world()
+            .workspaces
+            .push((agent_id.clone(), bead_id.clone()));
+        Box::pin(ready(Ok(())))
+    }
+
+    fn heartbeat_claim<'a>(
+        &'a self,
+        agent_id: &'a RuntimeAgentId,
+        bead_id: &'a RuntimeBeadId,
+        lease_extension_ms: Option<i32>,
+    ) -> PortFuture<'a, bool> {
+        Box::pin(ready(Ok(self.heartbeat(
+            agent_id,
+            bead_id,
+            lease_extension_ms,
+        ))))
+    }
+}
+
+impl StageExecutor for SimulatedPorts {
+    fn execute_work(
+        &self,
+        request: StageExecutionRequest,
+    ) -> PortFuture<'_, StageExecutionOutcome> {
+        Box::pin(ready(Ok(self.execute(&request))))
+    }
+}
+
+impl ArtifactStore for SimulatedPorts {
+    fn store_artifact(&self, record: StageArtifactRecord) -> PortFuture<'_, ()> {
+        self.world().artifacts.push(record);
+        Box::pin(ready(Ok(())))
+    }
+}
+
+impl LandingGateway for SimulatedPorts {
+    fn execute_landing<'a>(&'a self, bead_id: &'a RuntimeBeadId) -> PortFuture<'a, LandingOutcome> {
+        Box::pin(ready(Ok(self.land(bead_id))))
+    }
+}
+
+impl EventSink for SimulatedPorts {
+    fn append_event(&self, event: OrchestratorEvent) -> PortFuture<'_, ()> {
+        self.world().events.push(event);
+        Box::pin(ready(Ok(())))
+    }
+}
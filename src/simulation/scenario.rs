@@ -0,0 +1,225 @@
+use super::{
+    FailureSchedule, SimulatedBeadStatus, SimulatedPorts, SimulatedTransition, TransitionFn,
+    VirtualClock,
+};
+use crate::orchestrator_service::{
+    OrchestratorEvent, OrchestratorService, OrchestratorTickOutcome, StageArtifactRecord,
+};
+use crate::{
+    runtime_determine_transition_decision, Result, RuntimeAgentId, RuntimeAgentState, RuntimeBeadId,
+};
+use std::time::Duration;
+
+/// A scripted run: agents tick in number order once per round, then the
+/// clock advances by `tick_ms`.
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    agents: u32,
+    beads: Vec<String>,
+    schedule: FailureSchedule,
+    pauses: Vec<(u32, u64, u64)>,
+    tick_ms: u64,
+    lease_ms: u64,
+    max_attempts: u32,
+    transition: TransitionFn,
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self {
+            agents: 1,
+            beads: Vec::new(),
+            schedule: FailureSchedule::default(),
+            pauses: Vec::new(),
+            tick_ms: 1_000,
+            lease_ms: 300_000,
+            max_attempts: 3,
+            transition: runtime_determine_transition_decision,
+        }
+    }
+}
+
+impl Scenario {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers agents `1..=count`.
+    #[must_use]
+    pub const fn agents(mut self, count: u32) -> Self {
+        self.agents = count;
+        self
+    }
+
+    /// Appends `count` beads named `sim-<position>` to the backlog.
+    #[must_use]
+    pub fn beads(mut self, count: u32) -> Self {
+        for _ in 0..count {
+            let next = self.beads.len() + 1;
+            self.beads.push(format!("sim-{next}"));
+        }
+        self
+    }
+
+    /// Appends one named bead to the backlog.
+    #[must_use]
+    pub fn bead(mut self, bead_id: impl Into<String>) -> Self {
+        self.beads.push(bead_id.into());
+        self
+    }
+
+    #[must_use]
+    pub fn schedule(mut self, schedule: FailureSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Stops ticking agent `agent` in `[from_ms, until_ms)`, as if its process
+    /// had stalled; its lease keeps running down.
+    #[must_use]
+    pub fn pause_agent(mut self, agent: u32, from_ms: u64, until_ms: u64) -> Self {
+        self.pauses.push((agent, from_ms, until_ms));
+        self
+    }
+
+    /// Virtual time between rounds.
+    #[must_use]
+    pub const fn tick_ms(mut self, tick_ms: u64) -> Self {
+        self.tick_ms = tick_ms;
+        self
+    }
+
+    #[must_use]
+    pub const fn lease_ms(mut self, lease_ms: u64) -> Self {
+        self.lease_ms = lease_ms;
+        self
+    }
+
+    #[must_use]
+    pub const fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Replaces the stage DAG used to decide each transition.
+    #[must_use]
+    pub const fn transition(mut self, transition: TransitionFn) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    /// Runs rounds until the swarm is quiet (no agent progressed, none is
+    /// paused, and no lease is outstanding) or `max_rounds` have run.
+    ///
+    /// # Errors
+    /// Returns any error raised by an orchestrator tick.
+    pub async fn run(self, max_rounds: u32) -> Result<ScenarioReport> {
+        let clock = VirtualClock::new();
+        let ports = (1..=self.agents).fold(
+            SimulatedPorts::new(clock.clone(), self.schedule.clone())
+                .with_lease_ms(self.lease_ms)
+                .with_max_attempts(self.max_attempts)
+                .with_transition(self.transition),
+            SimulatedPorts::with_agent,
+        );
+        let ports = self
+            .beads
+            .iter()
+            .cloned()
+            .fold(ports, SimulatedPorts::with_bead);
+        let service = OrchestratorService::new(ports.clone());
+
+        let mut ticks = Vec::new();
+        let mut rounds = 0;
+        while rounds < max_rounds {
+            rounds += 1;
+            let now = clock.now_ms();
+            let mut progressed = false;
+            let mut paused = false;
+            for agent_id in ports.agent_ids() {
+                if self.is_paused(agent_id.number(), now) {
+                    paused = true;
+                    continue;
+                }
+                let outcome = service.tick(&agent_id).await?;
+                progressed |= outcome == OrchestratorTickOutcome::Progressed;
+                ticks.push(SimulatedTick {
+                    at_ms: now,
+                    agent_id,
+                    outcome,
+                });
+            }
+            clock.advance(Duration::from_millis(self.tick_ms));
+            if !progressed && !paused && !ports.has_active_claims() {
+                break;
+            }
+        }
+
+        Ok(ScenarioReport {
+            rounds,
+            elapsed_ms: clock.now_ms(),
+            ticks,
+            transitions: ports.transitions(),
+            events: ports.events(),
+            artifacts: ports.artifacts(),
+            beads: ports.beads(),
+            agents: ports.agent_states(),
+        })
+    }
+
+    fn is_paused(&self, agent: u32, now_ms: u64) -> bool {
+        self.pauses
+            .iter()
+            .any(|(paused, from, until)| *paused == agent && (*from..*until).contains(&now_ms))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedTick {
+    pub at_ms: u64,
+    pub agent_id: RuntimeAgentId,
+    pub outcome: OrchestratorTickOutcome,
+}
+
+/// Everything a scenario run did, in order.
+#[derive(Debug, Clone)]
+pub struct ScenarioReport {
+    pub rounds: u32,
+    pub elapsed_ms: u64,
+    pub ticks: Vec<SimulatedTick>,
+    pub transitions: Vec<SimulatedTransition>,
+    pub events: Vec<OrchestratorEvent>,
+    pub artifacts: Vec<StageArtifactRecord>,
+    pub beads: Vec<(RuntimeBeadId, SimulatedBeadStatus)>,
+    pub agents: Vec<RuntimeAgentState>,
+}
+
+impl ScenarioReport {
+    #[must_use]
+    pub fn bead_status(&self, bead_id: &str) -> Option<SimulatedBeadStatus> {
+        self.beads
+            .iter()
+            .find(|(bead, _)| bead.value() == bead_id)
+            .map(|(_, status)| *status)
+    }
+
+    #[must_use]
+    pub fn transitions_for(&self, bead_id: &str) -> Vec<&SimulatedTransition> {
+        self.transitions
+            .iter()
+            .filter(|transition| transition.bead_id.value() == bead_id)
+            .collect()
+    }
+
+    /// Whether every bead ended completed or blocked.
+    #[must_use]
+    pub fn is_settled(&self) -> bool {
+        self.beads.iter().all(|(_, status)| {
+            matches!(
+                status,
+                SimulatedBeadStatus::Completed | SimulatedBeadStatus::Blocked
+            )
+        })
+    }
+}
@@ -0,0 +1,200 @@
+#![allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+
+use super::{FailureSchedule, Scenario, SimulatedBeadStatus};
+use crate::orchestrator_service::OrchestratorEvent;
+use crate::{
+    RuntimeStage, RuntimeStageResult, RuntimeStageTransition, RuntimeTransitionDecision,
+    RuntimeTransitionReason,
+};
+
+#[tokio::test]
+async fn given_passing_schedule_when_running_then_every_bead_walks_the_dag_and_lands() {
+    let report = Scenario::new().agents(2).beads(2).run(50).await.unwrap();
+
+    assert!(report.is_settled());
+    for bead in ["sim-1", "sim-2"] {
+        assert_eq!(
+            report.bead_status(bead),
+            Some(SimulatedBeadStatus::Completed)
+        );
+        let stages = report
+            .transitions_for(bead)
+            .iter()
+            .map(|transition| transition.stage)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stages,
+            vec![
+                RuntimeStage::RustContract,
+                RuntimeStage::Implement,
+                RuntimeStage::QaEnforcer,
+                RuntimeStage::RedQueen,
+            ]
+        );
+    }
+}
+
+#[tokio::test]
+async fn given_scheduled_qa_failure_when_running_then_bead_retries_implement_and_completes() {
+    let report = Scenario::new()
+        .beads(1)
+        .schedule(FailureSchedule::new().fail_stage(
+            "sim-1",
+            RuntimeStage::QaEnforcer,
+            0,
+            "tests failed",
+        ))
+        .run(50)
+        .await
+        .unwrap();
+
+    let transitions = report.transitions_for("sim-1");
+    let retry = transitions
+        .iter()
+        .find(|transition| transition.decision.transition() == RuntimeStageTransition::Retry)
+        .unwrap();
+    assert_eq!(retry.stage, RuntimeStage::QaEnforcer);
+    assert_eq!(retry.attempt, 0);
+    assert!(transitions
+        .iter()
+        .any(|transition| transition.stage == RuntimeStage::Implement && transition.attempt == 1));
+    assert_eq!(
+        report.bead_status("sim-1"),
+        Some(SimulatedBeadStatus::Completed)
+    );
+}
+
+#[tokio::test]
+async fn given_failures_past_max_attempts_when_running_then_bead_is_blocked() {
+    let schedule = (0..=2).fold(FailureSchedule::new(), |schedule, attempt| {
+        schedule.fail_stage("sim-1", RuntimeStage::Implement, attempt, "compile error")
+    });
+
+    let report = Scenario::new()
+        .beads(1)
+        .max_attempts(2)
+        .schedule(schedule)
+        .run(50)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report.bead_status("sim-1"),
+        Some(SimulatedBeadStatus::Blocked)
+    );
+    let last = report.transitions_for("sim-1").pop().unwrap();
+    assert_eq!(last.decision.transition(), RuntimeStageTransition::Block);
+}
+
+#[tokio::test]
+async fn given_stalled_agent_when_lease_expires_then_claim_is_recovered_by_another_agent() {
+    let report = Scenario::new()
+        .agents(2)
+        .beads(1)
+        .tick_ms(60_000)
+        .lease_ms(120_000)
+        .pause_agent(1, 60_000, 600_000)
+        .run(50)
+        .await
+        .unwrap();
+
+    assert!(report
+        .events
+        .contains(&OrchestratorEvent::ClaimRecovered { count: 1 }));
+    assert_eq!(
+        report.bead_status("sim-1"),
+        Some(SimulatedBeadStatus::Completed)
+    );
+    let finisher = report.transitions_for("sim-1").pop().unwrap();
+    assert_eq!(finisher.agent_id.number(), 2);
+}
+
+#[tokio::test]
+async fn given_rejected_push_when_landing_then_final_stage_reruns_until_push_is_confirmed() {
+    let report = Scenario::new()
+        .beads(1)
+        .schedule(FailureSchedule::new().reject_landing("sim-1", 2))
+        .run(50)
+        .await
+        .unwrap();
+
+    let red_queen_runs = report
+        .transitions_for("sim-1")
+        .iter()
+        .filter(|transition| transition.stage == RuntimeStage::RedQueen)
+        .count();
+    assert_eq!(red_queen_runs, 3);
+    assert_eq!(
+        report.bead_status("sim-1"),
+        Some(SimulatedBeadStatus::Completed)
+    );
+}
+
+#[tokio::test]
+async fn given_same_scenario_when_run_twice_then_reports_match() {
+    let scenario = Scenario::new()
+        .agents(3)
+        .beads(5)
+        .tick_ms(30_000)
+        .lease_ms(60_000)
+        .pause_agent(2, 30_000, 240_000)
+        .schedule(
+            FailureSchedule::new()
+                .fail_stage("sim-3", RuntimeStage::RedQueen, 0, "regression")
+                .drop_heartbeats(1, 0, 90_000),
+        );
+
+    let first = scenario.clone().run(100).await.unwrap();
+    let second = scenario.run(100).await.unwrap();
+
+    assert_eq!(first.ticks, second.ticks);
+    assert_eq!(first.transitions, second.transitions);
+    assert_eq!(first.events, second.events);
+    assert_eq!(first.beads, second.beads);
+}
+
+fn skip_qa(
+    stage: RuntimeStage,
+    result: &RuntimeStageResult,
+    _attempt: u32,
+    _max_attempts: u32,
+) -> RuntimeTransitionDecision {
+    let transition = match (stage, result.is_success()) {
+        (RuntimeStage::RustContract, true) => {
+            RuntimeStageTransition::Advance(RuntimeStage::Implement)
+        }
+        (RuntimeStage::Implement, true) => RuntimeStageTransition::Advance(RuntimeStage::RedQueen),
+        (RuntimeStage::RedQueen, true) => RuntimeStageTransition::Complete,
+        (_, true) => RuntimeStageTransition::NoOp,
+        (_, false) => RuntimeStageTransition::Block,
+    };
+    RuntimeTransitionDecision::new(transition, RuntimeTransitionReason::StagePassedAdvance)
+}
+
+#[tokio::test]
+async fn given_custom_dag_when_running_then_transitions_follow_it() {
+    let report = Scenario::new()
+        .beads(1)
+        .transition(skip_qa)
+        .run(50)
+        .await
+        .unwrap();
+
+    let stages = report
+        .transitions_for("sim-1")
+        .iter()
+        .map(|transition| transition.stage)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        stages,
+        vec![
+            RuntimeStage::RustContract,
+            RuntimeStage::Implement,
+            RuntimeStage::RedQueen,
+        ]
+    );
+    assert_eq!(
+        report.bead_status("sim-1"),
+        Some(SimulatedBeadStatus::Completed)
+    );
+}
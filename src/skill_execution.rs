@@ -1,7 +1,7 @@
 //! Skill execution framework for agent stages.
 
 use crate::error::{Result, SwarmError};
-use crate::skill_execution_parsing::{parse_test_results, TestResults};
+use crate::skill_execution_parsing::{parse_test_report, parse_test_results, TestResults};
 use crate::types::{ArtifactType, Stage};
 use crate::SwarmDb;
 use futures_util::future::try_join_all;
@@ -103,14 +103,19 @@ impl SkillOutput {
                 .insert("failure_details".to_string(), self.full_log.clone());
         }
 
-        // Parse test results if available
-        self.test_results = Some(parse_test_results(&self.full_log));
+        // Prefer a JUnit or TAP report; fall back to cargo's summary line.
+        self.test_results = Some(
+            parse_test_report(&self.full_log).unwrap_or_else(|| parse_test_results(&self.full_log)),
+        );
     }
 
     /// Extract artifacts for the red-queen stage.
     pub fn extract_red_queen_artifacts(&mut self) {
         // The red-queen skill produces adversarial test reports.
         // For now, we store the full log as the report.
+        if self.test_results.is_none() {
+            self.test_results = parse_test_report(&self.full_log);
+        }
         if self.success {
             self.artifacts
                 .insert("quality_gate_report".to_string(), self.full_log.clone());
@@ -201,6 +206,12 @@ pub async fn store_skill_artifacts(
             } else if let Some(ref report) = output.adversarial_report {
                 pending_artifacts.push((ArtifactType::AdversarialReport, report.clone(), None));
             }
+            if let Some(ref test_results) = output.test_results {
+                let results_json = serde_json::to_string(test_results).map_err(|e| {
+                    SwarmError::DatabaseError(format!("Failed to serialize test results: {e}"))
+                })?;
+                pending_artifacts.push((ArtifactType::TestResults, results_json, None));
+            }
         }
         Stage::Done => {}
     }
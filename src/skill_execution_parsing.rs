@@ -1,12 +1,42 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TestResults {
     pub passed: u32,
     pub failed: u32,
     pub skipped: u32,
     pub total: u32,
     pub failures: Vec<TestFailure>,
+    /// Every test the report named, in report order.
+    #[serde(default)]
+    pub cases: Vec<TestCase>,
+}
+
+impl TestResults {
+    /// Counts `case` and keeps it, adding a [`TestFailure`] when it failed.
+    pub fn record(&mut self, case: TestCase) {
+        self.total = self.total.saturating_add(1);
+        match case.status {
+            TestStatus::Passed => self.passed = self.passed.saturating_add(1),
+            TestStatus::Skipped => self.skipped = self.skipped.saturating_add(1),
+            TestStatus::Failed => {
+                self.failed = self.failed.saturating_add(1);
+                self.failures.push(TestFailure {
+                    name: case.name.clone(),
+                    file: case.file.clone(),
+                    line: case.line,
+                    reason: case
+                        .reason
+                        .clone()
+                        .unwrap_or_else(|| "See test output".to_string()),
+                });
+            }
+        }
+        self.cases.push(case);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +47,59 @@ pub struct TestFailure {
     pub reason: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+impl TestStatus {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Passed => "passed",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+        }
+    }
+}
+
+/// One test from a `JUnit`, TAP or cargo report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestCase {
+    pub name: String,
+    pub status: TestStatus,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub reason: Option<String>,
+}
+
+impl TestCase {
+    const fn named(name: String, status: TestStatus) -> Self {
+        Self {
+            name,
+            status,
+            file: None,
+            line: None,
+            reason: None,
+        }
+    }
+}
+
+/// Parses a `JUnit` XML or TAP report out of stage output. Returns `None` when
+/// the output is neither, so callers can fall back to cargo's summary.
+#[must_use]
+pub fn parse_test_report(output: &str) -> Option<TestResults> {
+    if output.contains("<testsuite") || output.contains("<testcase") {
+        let start = output.find("<?xml").or_else(|| output.find("<testsuite"))?;
+        return parse_junit_xml(&output[start..]).ok();
+    }
+    parse_tap(output)
+}
+
 #[must_use]
 pub fn parse_test_results(output: &str) -> TestResults {
     let result_line = output.lines().find(|line| line.contains("test result:"));
@@ -61,12 +144,216 @@ pub fn parse_test_results(output: &str) -> TestResults {
         skipped: 0,
         total: passed.saturating_add(failed),
         failures,
+        cases: output.lines().filter_map(cargo_test_case).collect(),
+    }
+}
+
+/// `test name ... ok` lines from libtest output.
+fn cargo_test_case(line: &str) -> Option<TestCase> {
+    let (name, outcome) = line.trim().strip_prefix("test ")?.split_once(" ... ")?;
+    let status = match outcome.trim() {
+        "ok" => TestStatus::Passed,
+        "FAILED" => TestStatus::Failed,
+        "ignored" => TestStatus::Skipped,
+        _ => return None,
+    };
+    Some(TestCase::named(name.trim().to_string(), status))
+}
+
+/// Parses TAP (Test Anything Protocol) output.
+///
+/// `# SKIP` and `# TODO` directives count as skipped; a failure's reason is
+/// the first comment or `message:` line after it. Returns `None` unless the
+/// output has a TAP version header or plan line and at least one test line.
+#[must_use]
+pub fn parse_tap(output: &str) -> Option<TestResults> {
+    let is_tap = output.lines().map(str::trim).any(|line| {
+        line.starts_with("TAP version")
+            || line
+                .strip_prefix("1..")
+                .is_some_and(|count| count.starts_with(|c: char| c.is_ascii_digit()))
+    });
+    if !is_tap {
+        return None;
     }
+
+    let mut results = TestResults::default();
+    let mut pending: Option<TestCase> = None;
+
+    for line in output.lines().map(str::trim) {
+        if let Some(case) = tap_test_line(line) {
+            if let Some(done) = pending.replace(case) {
+                results.record(done);
+            }
+            continue;
+        }
+        let Some(case) = pending
+            .as_mut()
+            .filter(|case| case.status == TestStatus::Failed && case.reason.is_none())
+        else {
+            continue;
+        };
+        let detail = line
+            .strip_prefix('#')
+            .or_else(|| line.strip_prefix("message:"))
+            .map(|detail| detail.trim().trim_matches(['\'', '"']))
+            .filter(|detail| !detail.is_empty());
+        if let Some(detail) = detail {
+            case.reason = Some(detail.to_string());
+        }
+    }
+    if let Some(done) = pending {
+        results.record(done);
+    }
+
+    (results.total > 0).then_some(results)
+}
+
+fn tap_test_line(line: &str) -> Option<TestCase> {
+    let (failed, rest) = line.strip_prefix("not ok").map_or_else(
+        || line.strip_prefix("ok").map(|rest| (false, rest)),
+        |rest| Some((true, rest)),
+    )?;
+    if !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    let (description, directive) = rest.split_once(" # ").unwrap_or((rest, ""));
+    let name = description
+        .trim()
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .trim()
+        .trim_start_matches('-')
+        .trim();
+    let directive = directive.trim_start().to_ascii_uppercase();
+    let status = if directive.starts_with("SKIP") || directive.starts_with("TODO") {
+        TestStatus::Skipped
+    } else if failed {
+        TestStatus::Failed
+    } else {
+        TestStatus::Passed
+    };
+    let name = if name.is_empty() { "unnamed" } else { name };
+    Some(TestCase::named(name.to_string(), status))
+}
+
+struct JunitCase {
+    case: TestCase,
+    reason: String,
+}
+
+/// Parses a `JUnit` XML report. A test case with a `failure` or `error` child
+/// counts as failed, one with `skipped` as skipped, any other as passed.
+///
+/// # Errors
+/// Returns a message naming the byte offset of malformed XML.
+pub fn parse_junit_xml(xml: &str) -> Result<TestResults, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut results = TestResults::default();
+    let mut case: Option<JunitCase> = None;
+    let mut in_failure = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(tag)) => {
+                in_failure = open_junit_tag(&tag, &mut case, &mut results);
+            }
+            Ok(Event::Empty(tag)) => {
+                open_junit_tag(&tag, &mut case, &mut results);
+                if tag.name().as_ref() == b"testcase" {
+                    close_junit_case(case.take(), &mut results);
+                }
+            }
+            Ok(Event::Text(text)) if in_failure => {
+                if let Some(open) = case.as_mut().filter(|open| open.reason.is_empty()) {
+                    open.reason = text
+                        .unescape()
+                        .map(|text| text.trim().lines().next().unwrap_or("").to_string())
+                        .unwrap_or_default();
+                }
+            }
+            Ok(Event::End(tag)) => {
+                in_failure = false;
+                if tag.name().as_ref() == b"testcase" {
+                    close_junit_case(case.take(), &mut results);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => {
+                return Err(format!(
+                    "Invalid JUnit XML at byte {}: {e}",
+                    reader.buffer_position()
+                ))
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// Starts a test case or records an outcome on the open one. Returns whether
+/// `tag` opens a failure whose text may carry the reason.
+fn open_junit_tag(
+    tag: &BytesStart<'_>,
+    case: &mut Option<JunitCase>,
+    results: &mut TestResults,
+) -> bool {
+    match tag.name().as_ref() {
+        b"testcase" => {
+            close_junit_case(case.take(), results);
+            let name = junit_attribute(tag, b"name").unwrap_or_else(|| "unknown".to_string());
+            let name = junit_attribute(tag, b"classname")
+                .filter(|class| !class.is_empty())
+                .map_or_else(|| name.clone(), |class| format!("{class}::{name}"));
+            *case = Some(JunitCase {
+                case: TestCase {
+                    name,
+                    status: TestStatus::Passed,
+                    file: junit_attribute(tag, b"file"),
+                    line: junit_attribute(tag, b"line").and_then(|line| line.parse().ok()),
+                    reason: None,
+                },
+                reason: String::new(),
+            });
+            false
+        }
+        b"failure" | b"error" => case.as_mut().is_some_and(|open| {
+            open.case.status = TestStatus::Failed;
+            open.reason = junit_attribute(tag, b"message")
+                .or_else(|| junit_attribute(tag, b"type"))
+                .unwrap_or_default();
+            true
+        }),
+        b"skipped" => {
+            if let Some(open) = case.as_mut() {
+                open.case.status = TestStatus::Skipped;
+            }
+            false
+        }
+        _ => false,
+    }
+}
+
+fn close_junit_case(case: Option<JunitCase>, results: &mut TestResults) {
+    if let Some(JunitCase { mut case, reason }) = case {
+        if case.status == TestStatus::Failed && !reason.is_empty() {
+            case.reason = Some(reason);
+        }
+        results.record(case);
+    }
+}
+
+fn junit_attribute(tag: &BytesStart<'_>, key: &[u8]) -> Option<String> {
+    tag.attributes()
+        .flatten()
+        .find(|attribute| attribute.key.as_ref() == key)
+        .and_then(|attribute| attribute.unescape_value().ok().map(Cow::into_owned))
 }
 
 #[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
 mod tests {
-    use super::parse_test_results;
+    use super::{parse_junit_xml, parse_tap, parse_test_report, parse_test_results, TestStatus};
 
     #[test]
     fn parse_cargo_test_results() {
@@ -83,4 +370,62 @@ test result: ok. 2 passed; 1 failed; 0 skipped
         assert_eq!(results.failed, 1);
         assert_eq!(results.total, 3);
     }
+
+    #[test]
+    fn parse_junit_report() {
+        let xml = r#"<?xml version="1.0"?>
+<testsuites>
+  <testsuite name="unit">
+    <testcase classname="math" name="adds"/>
+    <testcase classname="math" name="divides" file="src/math.rs" line="42">
+      <failure message="division by zero"/>
+    </testcase>
+    <testcase classname="io" name="reads"><error>timed out
+waiting</error></testcase>
+    <testcase classname="io" name="writes"><skipped/></testcase>
+  </testsuite>
+</testsuites>"#;
+        let results = parse_junit_xml(xml).expect("valid junit");
+        assert_eq!(results.total, 4);
+        assert_eq!(results.passed, 1);
+        assert_eq!(results.failed, 2);
+        assert_eq!(results.skipped, 1);
+        assert_eq!(results.failures[0].name, "math::divides");
+        assert_eq!(results.failures[0].line, Some(42));
+        assert_eq!(results.failures[0].reason, "division by zero");
+        assert_eq!(results.failures[1].reason, "timed out");
+        assert!(parse_junit_xml("<testsuite><testcase></testsuite>").is_err());
+    }
+
+    #[test]
+    fn parse_tap_report() {
+        let output = "TAP version 13
+1..4
+ok 1 - adds
+not ok 2 - divides
+  ---
+  message: 'division by zero'
+  ...
+ok 3 - reads # SKIP no fixture
+not ok 4 - writes # TODO not done
+";
+        let results = parse_tap(output).expect("valid tap");
+        assert_eq!(results.total, 4);
+        assert_eq!(results.passed, 1);
+        assert_eq!(results.failed, 1);
+        assert_eq!(results.skipped, 2);
+        assert_eq!(results.failures[0].name, "divides");
+        assert_eq!(results.failures[0].reason, "division by zero");
+        assert_eq!(results.cases[3].status, TestStatus::Skipped);
+    }
+
+    #[test]
+    fn parse_test_report_ignores_plain_cargo_output() {
+        assert!(parse_test_report("test a ... ok\ntest result: ok. 1 passed; 0 failed").is_none());
+        let junit = "running\n<testsuite><testcase name=\"a\"/></testsuite>";
+        assert_eq!(
+            parse_test_report(junit).map(|results| results.passed),
+            Some(1)
+        );
+    }
 }
@@ -1,3 +1,6 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
 #[allow(clippy::needless_raw_string_hashes)]
 pub const RUST_CONTRACT_SKILL: &str = r#"# Skill: Rust Contract Architect
 
@@ -75,6 +78,19 @@ You are an adversarial tester. Your goal is to "break" the code by finding subtl
 - The code is resilient to adversarial inputs.
 - A detailed report of the inspection is produced."#;
 
+/// Skills with a compiled-in prompt, in pipeline order.
+pub const EMBEDDED_SKILLS: &[&str] = &["rust-contract", "implement", "qa-enforcer", "red-queen"];
+
+/// A customised prompt stored in `skill_prompts`. Every update appends a new
+/// version; the highest version is the one served.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StoredSkillPrompt {
+    pub skill: String,
+    pub version: u32,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
 #[must_use]
 pub fn get_skill_prompt(skill_name: &str) -> Option<String> {
     match skill_name {
@@ -364,6 +364,114 @@ fn bullet_list(items: &[String]) -> String {
         .join("\n")
 }
 
+/// Bytes of each stream of a stage command held in memory by default; see
+/// `config::stage_output_memory_bytes_from_env`.
+pub const DEFAULT_STAGE_OUTPUT_MEMORY_BYTES: usize = 8 * 1024 * 1024;
+/// Largest piece of spilled stage output written at once.
+pub const STAGE_OUTPUT_CHUNK_BYTES: usize = 256 * 1024;
+
+/// One stream of a stage command's output, held in memory up to a cap.
+///
+/// Once the cap is passed the whole stream is handed out in chunks for the
+/// caller to write elsewhere, and only the latest chunk is kept, so a stage
+/// printing gigabytes costs a bounded amount of memory.
+#[derive(Debug)]
+pub struct StageOutputBuffer {
+    memory_cap: usize,
+    chunk_bytes: usize,
+    held: Vec<u8>,
+    last_chunk: Vec<u8>,
+    total_bytes: u64,
+    spilled: bool,
+}
+
+/// What a [`StageOutputBuffer`] kept once its stream ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferedOutput {
+    /// The whole stream, or once it spilled, a note of how much was left
+    /// out followed by the last `memory_cap` bytes.
+    pub text: String,
+    pub total_bytes: u64,
+    pub spilled: bool,
+}
+
+impl StageOutputBuffer {
+    #[must_use]
+    pub fn new(memory_cap: usize) -> Self {
+        let memory_cap = memory_cap.max(1);
+        Self {
+            memory_cap,
+            chunk_bytes: memory_cap.min(STAGE_OUTPUT_CHUNK_BYTES),
+            held: Vec::new(),
+            last_chunk: Vec::new(),
+            total_bytes: 0,
+            spilled: false,
+        }
+    }
+
+    /// Appends `bytes` and returns the chunks to write out, oldest first.
+    /// Nothing is returned until the stream first outgrows the cap.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        self.total_bytes = self
+            .total_bytes
+            .saturating_add(u64::try_from(bytes.len()).unwrap_or(u64::MAX));
+        self.held.extend_from_slice(bytes);
+        if !self.spilled && self.held.len() <= self.memory_cap {
+            return Vec::new();
+        }
+        self.spilled = true;
+
+        let whole = self.held.len() - self.held.len() % self.chunk_bytes;
+        let rest = self.held.split_off(whole);
+        let chunks = self
+            .held
+            .chunks(self.chunk_bytes)
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+        self.held = rest;
+        if let Some(last) = chunks.last() {
+            self.last_chunk.clone_from(last);
+        }
+        chunks
+    }
+
+    /// Ends the stream, returning the final chunk to write out, if it
+    /// spilled and anything is left, and what stays in memory.
+    #[must_use]
+    pub fn finish(self) -> (Option<Vec<u8>>, BufferedOutput) {
+        if !self.spilled {
+            return (
+                None,
+                BufferedOutput {
+                    text: String::from_utf8_lossy(&self.held).into_owned(),
+                    total_bytes: self.total_bytes,
+                    spilled: false,
+                },
+            );
+        }
+
+        let mut tail = self.last_chunk;
+        tail.extend_from_slice(&self.held);
+        let tail = &tail[tail.len().saturating_sub(self.memory_cap)..];
+        let omitted = self
+            .total_bytes
+            .saturating_sub(u64::try_from(tail.len()).unwrap_or(u64::MAX));
+        let text = format!(
+            "[{omitted} earlier bytes in stage_output_chunks]\n{}",
+            String::from_utf8_lossy(tail)
+        );
+        let final_chunk = (!self.held.is_empty()).then_some(self.held);
+        (
+            final_chunk,
+            BufferedOutput {
+                text,
+                total_bytes: self.total_bytes,
+                spilled: true,
+            },
+        )
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
 mod tests {
@@ -436,4 +544,40 @@ ai_hints: {
         let ai_hints = artifacts.get("ai_hints").unwrap();
         assert!(ai_hints.contains("Zero unwrap law"));
     }
+
+    #[test]
+    fn given_output_within_cap_when_finishing_then_nothing_spills() {
+        let mut buffer = StageOutputBuffer::new(16);
+
+        assert!(buffer.push(b"hello ").is_empty());
+        assert!(buffer.push(b"world").is_empty());
+        let (final_chunk, output) = buffer.finish();
+
+        assert_eq!(final_chunk, None);
+        assert_eq!(output.text, "hello world");
+        assert_eq!(output.total_bytes, 11);
+        assert!(!output.spilled);
+    }
+
+    #[test]
+    fn given_output_past_cap_when_pushing_then_whole_stream_spills_in_chunks() {
+        let mut buffer = StageOutputBuffer::new(4);
+
+        assert!(buffer.push(b"abc").is_empty());
+        assert_eq!(
+            buffer.push(b"defghij"),
+            vec![b"abcd".to_vec(), b"efgh".to_vec()]
+        );
+        assert_eq!(buffer.push(b"kl"), vec![b"ijkl".to_vec()]);
+        assert!(buffer.push(b"m").is_empty());
+        let (final_chunk, output) = buffer.finish();
+
+        assert_eq!(final_chunk, Some(b"m".to_vec()));
+        assert_eq!(
+            output.text,
+            "[9 earlier bytes in stage_output_chunks]\njklm"
+        );
+        assert_eq!(output.total_bytes, 13);
+        assert!(output.spilled);
+    }
 }
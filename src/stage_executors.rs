@@ -5,13 +5,19 @@
 
 use crate::gate_cache::GateExecutionCache;
 use crate::skill_execution::store_skill_artifacts;
-use crate::types::Stage;
+use crate::types::{ApprovalGate, Stage};
 use crate::{AgentId, BeadId, SwarmDb};
+use std::path::PathBuf;
+use std::time::Duration;
 
+mod backend;
 mod contract_stage;
 mod gate_stage;
 mod implement_stage;
+mod output_capture;
 mod output_mapping;
+mod remote;
+mod result_parsers;
 
 #[cfg(test)]
 mod tests_gate_stage;
@@ -25,33 +31,209 @@ mod tests_implement;
 mod tests_implement_helpers;
 #[cfg(test)]
 mod tests_output_and_gate;
+#[cfg(all(test, feature = "testsupport"))]
+mod tests_output_capture;
 
+pub use backend::{
+    stage_run_name, BackendKind, ContainerBackend, ContainerRuntime, ExecutionBackend,
+    SandboxLimits, SandboxNetwork, StageSandboxConfig, CONTAINER_WORKDIR,
+};
 use contract_stage::execute_rust_contract_stage;
 use gate_stage::{execute_qa_stage, execute_red_queen_stage};
 use implement_stage::execute_implement_stage;
+use output_capture::StageOutputSpool;
 use output_mapping::{error_output, output_to_stage_result, success_output};
+pub use remote::{remote_stage_command, RemoteBuilder, RemoteExecutorConfig};
+pub use result_parsers::{ParserFormat, StageParserConfig, StageParserRegistry, StageParserSpec};
+
+/// How often a running stage checks whether its bead was cancelled.
+pub const CANCEL_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How often a gated stage checks whether an operator approved it.
+pub const APPROVAL_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
 /// Execute a stage and return the result.
 ///
 /// This is the main entry point for stage execution, replacing shell commands
-/// with proper Rust implementations.
+/// with proper Rust implementations. Gate commands run on the builder
+/// `SWARM_REMOTE_EXECUTORS` routes the agent or `stage` to, else on the
+/// backend `sandbox` selects for `stage`. If `swarm cancel` marks the bead while the
+/// stage runs, the stage is dropped, killing any process it launched and
+/// removing its container or remote process group, and the result is
+/// `Cancelled`. A stage with a parser in `SWARM_STAGE_PARSERS`
+/// is passed or failed by that parser instead of its exit code. A stage named
+/// in `SWARM_APPROVAL_GATES` waits for `swarm approve` before doing anything.
+/// The host's toolchain fingerprint is stored on the stage run before it
+/// starts. A stage that runs to a result, passed or not, sends a
+/// `stage_executed` event to the sinks in `SWARM_EVENT_SINKS`. Stage output
+/// past `SWARM_STAGE_OUTPUT_MEMORY_BYTES` per stream, whether a gate command
+/// printed it locally, in a container or over ssh or the implement stage
+/// generated it, is streamed to `stage_output_chunks` as it arrives; the
+/// stage log keeps its tail.
 pub async fn execute_stage_rust(
     db: &SwarmDb,
     stage: Stage,
     bead_id: &BeadId,
     agent_id: &AgentId,
     stage_history_id: i64,
+    sandbox: &StageSandboxConfig,
+    cache: Option<&GateExecutionCache>,
+) -> crate::types::StageResult {
+    let result = tokio::select! {
+        result = run_stage(db, stage, bead_id, agent_id, stage_history_id, sandbox, cache) => result,
+        () = wait_for_cancellation(db, agent_id, bead_id) => {
+            tracing::warn!("Agent {} stage {} cancelled for bead {}", agent_id, stage, bead_id);
+            return crate::types::StageResult::Cancelled(format!("Bead {bead_id} was cancelled"));
+        }
+    };
+    crate::orchestrator_service::emit_to_configured_sinks(
+        db,
+        agent_id.repo_id(),
+        crate::OrchestratorEvent::stage_executed(agent_id, bead_id.value()),
+    )
+    .await;
+    result
+}
+
+/// Resolves once the bead is cancelled. A failed check is retried on the
+/// next poll rather than stopping the stage.
+async fn wait_for_cancellation(db: &SwarmDb, agent_id: &AgentId, bead_id: &BeadId) {
+    loop {
+        tokio::time::sleep(CANCEL_POLL_INTERVAL).await;
+        match db.is_bead_cancelled(agent_id.repo_id(), bead_id).await {
+            Ok(true) => return,
+            Ok(false) => {}
+            Err(err) => tracing::warn!("Cancellation check failed for bead {}: {}", bead_id, err),
+        }
+    }
+}
+
+/// Holds a gated stage until an operator approves the bead at it. The first
+/// stage run to reach the gate moves the bead to `awaiting_approval`; a
+/// failed check is retried on the next poll. A token that reached no
+/// notifier is logged with the `reissue-approval` command that sends a new
+/// one, and the stage keeps waiting for it.
+async fn wait_for_approval(
+    db: &SwarmDb,
+    stage: Stage,
+    bead_id: &BeadId,
+    agent_id: &AgentId,
+) -> Result<(), String> {
+    let gate = ApprovalGate::Stage(stage);
+    let gates = crate::config::approval_gates_from_env().map_err(|e| e.to_string())?;
+    if !gates.contains(&gate) {
+        return Ok(());
+    }
+    let requested = db
+        .request_approval(agent_id.repo_id(), bead_id, gate, Some(agent_id.number()))
+        .await
+        .map_err(|e| format!("Failed to request approval: {e}"))?;
+    match crate::notifications::deliver_approval_token(agent_id.repo_id(), &requested).await {
+        Ok(Some(delivery)) if delivery.sent.is_empty() => tracing::error!(
+            "Approval token for bead {} reached no notifier: {}; run `swarm reissue-approval --bead-id {} --gate {}` once notifications work",
+            bead_id,
+            delivery.errors().join("; "),
+            bead_id,
+            gate
+        ),
+        Ok(_) => {}
+        Err(err) => tracing::error!(
+            "Approval token for bead {} not sent: {}; run `swarm reissue-approval --bead-id {} --gate {}` once notifications work",
+            bead_id,
+            err,
+            bead_id,
+            gate
+        ),
+    }
+    let approval = requested.approval;
+    if approval.is_approved() {
+        return Ok(());
+    }
+    tracing::info!(
+        "Agent {} stage {} waiting for approval {} of bead {}",
+        agent_id,
+        stage,
+        approval.id,
+        bead_id
+    );
+    loop {
+        tokio::time::sleep(APPROVAL_POLL_INTERVAL).await;
+        match db
+            .get_approval(agent_id.repo_id(), bead_id.value(), gate)
+            .await
+        {
+            Ok(Some(approval)) if approval.is_approved() => return Ok(()),
+            Ok(_) => {}
+            Err(err) => tracing::warn!("Approval check failed for bead {}: {}", bead_id, err),
+        }
+    }
+}
+
+async fn run_stage(
+    db: &SwarmDb,
+    stage: Stage,
+    bead_id: &BeadId,
+    agent_id: &AgentId,
+    stage_history_id: i64,
+    sandbox: &StageSandboxConfig,
     cache: Option<&GateExecutionCache>,
 ) -> crate::types::StageResult {
     if stage == Stage::Done {
         return crate::types::StageResult::Passed;
     }
+    if let Err(message) = wait_for_approval(db, stage, bead_id, agent_id).await {
+        return crate::types::StageResult::Error(message);
+    }
+
+    let environment = crate::protocol_runtime::environment_fingerprint().await;
+    if let Err(err) = db
+        .record_stage_environment(stage_history_id, &environment)
+        .await
+    {
+        tracing::warn!(
+            "Failed to record environment for stage history {}: {}",
+            stage_history_id,
+            err
+        );
+    }
 
+    let parsers = match crate::config::stage_parsers_from_env() {
+        Ok(parsers) => parsers,
+        Err(err) => {
+            return crate::types::StageResult::Error(format!("Invalid stage parser config: {err}"))
+        }
+    };
+    let remote = match crate::config::remote_executors_from_env() {
+        Ok(remote) => remote,
+        Err(err) => {
+            return crate::types::StageResult::Error(format!(
+                "Invalid remote executor config: {err}"
+            ))
+        }
+    };
+    let repo_root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let run_name = stage_run_name(
+        agent_id.repo_id().value(),
+        bead_id.value(),
+        stage_history_id,
+    );
+    let backend = remote
+        .builder_for(agent_id.number(), stage.as_str())
+        .map_or_else(
+            || sandbox.backend_for(stage, &repo_root, &run_name),
+            |(_, builder)| ExecutionBackend::Remote {
+                builder: builder.clone(),
+                run_name: run_name.clone(),
+            },
+        );
+
+    let spool = Some(StageOutputSpool::new(db, stage_history_id));
     let stage_output = match stage {
         Stage::RustContract => Ok(execute_rust_contract_stage(bead_id, agent_id)),
-        Stage::Implement => execute_implement_stage(bead_id, agent_id, db).await,
-        Stage::QaEnforcer => execute_qa_stage(bead_id, agent_id, db, cache).await,
-        Stage::RedQueen => execute_red_queen_stage(bead_id, agent_id, db, cache).await,
+        Stage::Implement => execute_implement_stage(bead_id, agent_id, db, spool).await,
+        Stage::QaEnforcer => execute_qa_stage(bead_id, agent_id, db, &backend, cache, spool).await,
+        Stage::RedQueen => {
+            execute_red_queen_stage(bead_id, agent_id, db, &backend, cache, spool).await
+        }
         Stage::Done => Ok(success_output(
             "Done stage does not produce artifacts".to_string(),
         )),
@@ -59,12 +241,29 @@ pub async fn execute_stage_rust(
 
     match stage_output {
         Ok(output) => {
+            let output = parsers.apply(stage, output, &repo_root);
             let result = output_to_stage_result(&output);
             if let Err(err) = store_skill_artifacts(db, stage_history_id, stage, &output).await {
                 return crate::types::StageResult::Error(format!(
                     "Failed to store stage artifacts: {err}"
                 ));
             }
+            if let Some(results) = &output.test_results {
+                if let Err(err) = db
+                    .record_stage_test_results(
+                        stage_history_id,
+                        agent_id.repo_id(),
+                        bead_id,
+                        stage,
+                        results,
+                    )
+                    .await
+                {
+                    return crate::types::StageResult::Error(format!(
+                        "Failed to store stage test results: {err}"
+                    ));
+                }
+            }
             result
         }
         Err(err) => {
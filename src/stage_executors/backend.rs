@@ -0,0 +1,475 @@
+//! Where stage commands run: directly on the host, inside a docker/podman
+//! container with CPU, memory, environment, and network limits, or on a
+//! remote builder over SSH.
+
+use super::remote::{shell_quote, RemoteBuilder};
+use crate::error::{Result, SwarmError};
+use crate::types::Stage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Mount point of the repository inside a stage container.
+pub const CONTAINER_WORKDIR: &str = "/workspace";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendKind {
+    Local,
+    Docker,
+    Podman,
+}
+
+impl BackendKind {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxNetwork {
+    /// No network at all.
+    None,
+    /// The runtime's default bridge network.
+    Bridge,
+    /// The host's network stack.
+    Host,
+}
+
+impl SandboxNetwork {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Bridge => "bridge",
+            Self::Host => "host",
+        }
+    }
+}
+
+/// Limits for a containerized stage. Unset fields fall back to the
+/// sandbox-wide limits, and past those to the runtime's own defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SandboxLimits {
+    /// `--cpus`, e.g. `2.0`.
+    #[serde(default)]
+    pub cpus: Option<f64>,
+    /// `--memory`, e.g. `"4g"`.
+    #[serde(default)]
+    pub memory: Option<String>,
+    #[serde(default)]
+    pub network: Option<SandboxNetwork>,
+    /// Host variables passed into the container; nothing else is.
+    #[serde(default)]
+    pub env_allow: Option<Vec<String>>,
+}
+
+impl SandboxLimits {
+    /// `self`, with unset fields taken from `fallback`.
+    #[must_use]
+    pub fn or(&self, fallback: &Self) -> Self {
+        Self {
+            cpus: self.cpus.or(fallback.cpus),
+            memory: self.memory.clone().or_else(|| fallback.memory.clone()),
+            network: self.network.or(fallback.network),
+            env_allow: self
+                .env_allow
+                .clone()
+                .or_else(|| fallback.env_allow.clone()),
+        }
+    }
+}
+
+/// Stage sandbox settings, read from `SWARM_STAGE_SANDBOX`.
+///
+/// ```json
+/// {"backend": "docker", "image": "rust:1.85",
+///  "limits": {"cpus": 2.0, "memory": "4g", "network": "none", "env_allow": ["CARGO_HOME"]},
+///  "stages": {"red-queen": {"cpus": 8.0, "memory": "16g"}}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StageSandboxConfig {
+    pub backend: BackendKind,
+    /// Container image; required unless `backend` is `local`.
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub limits: SandboxLimits,
+    /// Per-stage overrides keyed by stage name.
+    #[serde(default)]
+    pub stages: HashMap<String, SandboxLimits>,
+}
+
+impl Default for StageSandboxConfig {
+    fn default() -> Self {
+        Self {
+            backend: BackendKind::Local,
+            image: None,
+            limits: SandboxLimits::default(),
+            stages: HashMap::new(),
+        }
+    }
+}
+
+impl StageSandboxConfig {
+    /// Parses and validates a sandbox config.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::ConfigError` for malformed JSON, an unknown stage
+    /// name, a missing image, or a non-positive CPU limit.
+    pub fn from_json(raw: &str) -> Result<Self> {
+        let config: Self = serde_json::from_str(raw)
+            .map_err(|e| SwarmError::ConfigError(format!("Invalid stage sandbox config: {e}")))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(stage) = self
+            .stages
+            .keys()
+            .find(|stage| Stage::try_from(stage.as_str()).is_err())
+        {
+            return Err(SwarmError::ConfigError(format!(
+                "Unknown stage in sandbox config: {stage}"
+            )));
+        }
+        if self.backend != BackendKind::Local
+            && self
+                .image
+                .as_deref()
+                .is_none_or(|image| image.trim().is_empty())
+        {
+            return Err(SwarmError::ConfigError(format!(
+                "Sandbox backend {} requires an image",
+                self.backend.as_str()
+            )));
+        }
+        if std::iter::once(&self.limits)
+            .chain(self.stages.values())
+            .filter_map(|limits| limits.cpus)
+            .any(|cpus| cpus <= 0.0)
+        {
+            return Err(SwarmError::ConfigError(
+                "Sandbox cpus must be greater than 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The backend `stage` runs on, mounting `repo_root` for containers and
+    /// naming them `run_name`.
+    #[must_use]
+    pub fn backend_for(&self, stage: Stage, repo_root: &Path, run_name: &str) -> ExecutionBackend {
+        let runtime = match self.backend {
+            BackendKind::Local => return ExecutionBackend::Local,
+            BackendKind::Docker => ContainerRuntime::Docker,
+            BackendKind::Podman => ContainerRuntime::Podman,
+        };
+        let limits = self
+            .stages
+            .get(stage.as_str())
+            .map_or_else(|| self.limits.clone(), |stage| stage.or(&self.limits));
+        ExecutionBackend::Container(ContainerBackend {
+            runtime,
+            name: run_name.to_string(),
+            image: self.image.clone().unwrap_or_default(),
+            repo_root: repo_root.to_path_buf(),
+            limits,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+}
+
+impl ContainerRuntime {
+    #[must_use]
+    pub const fn program(&self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerBackend {
+    pub runtime: ContainerRuntime,
+    /// `--name` of the container, unique per stage run.
+    pub name: String,
+    pub image: String,
+    pub repo_root: PathBuf,
+    pub limits: SandboxLimits,
+}
+
+impl ContainerBackend {
+    /// Arguments to the runtime that run `program args` in a throwaway
+    /// container. The network defaults to `none` when no limit names one.
+    #[must_use]
+    pub fn run_args(&self, program: &str, args: &[&str]) -> Vec<String> {
+        let mut run = vec![
+            "run".to_string(),
+            "--rm".to_string(),
+            "--init".to_string(),
+            "--name".to_string(),
+            self.name.clone(),
+        ];
+        if let Some(cpus) = self.limits.cpus {
+            run.extend(["--cpus".to_string(), cpus.to_string()]);
+        }
+        if let Some(memory) = &self.limits.memory {
+            run.extend(["--memory".to_string(), memory.clone()]);
+        }
+        let network = self.limits.network.unwrap_or(SandboxNetwork::None);
+        run.extend(["--network".to_string(), network.as_str().to_string()]);
+        for name in self.limits.env_allow.iter().flatten() {
+            run.extend(["--env".to_string(), name.clone()]);
+        }
+        run.extend([
+            "--volume".to_string(),
+            format!("{}:{CONTAINER_WORKDIR}", self.repo_root.display()),
+            "--workdir".to_string(),
+            CONTAINER_WORKDIR.to_string(),
+            self.image.clone(),
+            program.to_string(),
+        ]);
+        run.extend(args.iter().map(ToString::to_string));
+        run
+    }
+}
+
+/// How a stage command is launched.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum ExecutionBackend {
+    /// On the host, unconfined, in the current directory.
+    #[default]
+    Local,
+    Container(ContainerBackend),
+    /// On a `SWARM_REMOTE_EXECUTORS` builder, in its checkout, tracked on
+    /// the builder as `run_name`.
+    Remote {
+        builder: RemoteBuilder,
+        run_name: String,
+    },
+}
+
+impl ExecutionBackend {
+    /// A ready-to-run command for `program args` on this backend. The
+    /// process is killed if the stage future is dropped, which is how a
+    /// cancelled stage stops. On a container backend that process is the
+    /// runtime client, not the container; on a remote one it is `ssh`.
+    /// [`Self::cleanup_command`] stops what they started.
+    #[must_use]
+    pub fn command(&self, program: &str, args: &[&str]) -> Command {
+        let mut command = match self {
+            Self::Local => {
+                let mut command = Command::new(program);
+                command.args(args);
+                command
+            }
+            Self::Container(container) => {
+                let mut command = Command::new(container.runtime.program());
+                command.args(container.run_args(program, args));
+                command
+            }
+            Self::Remote { builder, run_name } => {
+                let remote = std::iter::once(program)
+                    .chain(args.iter().copied())
+                    .map(shell_quote)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let mut command = Command::new("ssh");
+                command.args(builder.tracked_ssh_args(run_name, &remote));
+                command
+            }
+        };
+        command.kill_on_drop(true);
+        command
+    }
+
+    /// What to run when a stage is dropped before its [`Self::command`]
+    /// exits: removes the container, or kills the remote process group.
+    /// Local commands need nothing beyond the kill on drop.
+    #[must_use]
+    pub fn cleanup_command(&self) -> Option<std::process::Command> {
+        match self {
+            Self::Local => None,
+            Self::Container(container) => {
+                let mut command = std::process::Command::new(container.runtime.program());
+                command.args(["rm", "-f", &container.name]);
+                Some(command)
+            }
+            Self::Remote { builder, run_name } => {
+                let mut command = std::process::Command::new("ssh");
+                command.args(builder.kill_args(run_name));
+                Some(command)
+            }
+        }
+    }
+}
+
+/// Container and remote process name for one stage run:
+/// `swarm-<repo>-<bead>-<stage history id>`, with anything a container
+/// name or shell word cannot hold replaced by `-`.
+#[must_use]
+pub fn stage_run_name(repo_id: &str, bead_id: &str, stage_history_id: i64) -> String {
+    format!("swarm-{repo_id}-{bead_id}-{stage_history_id}")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_stage_override_when_resolving_backend_then_limits_merge_over_defaults() {
+        let config = StageSandboxConfig::from_json(
+            r#"{"backend": "podman", "image": "rust:1.85",
+                "limits": {"cpus": 2.0, "memory": "4g", "env_allow": ["CARGO_HOME"]},
+                "stages": {"red-queen": {"cpus": 8.0}}}"#,
+        )
+        .expect("valid sandbox config");
+
+        let ExecutionBackend::Container(container) =
+            config.backend_for(Stage::RedQueen, Path::new("/src/swarm"), "swarm-local-b-7")
+        else {
+            panic!("expected a container backend");
+        };
+
+        assert_eq!(container.runtime, ContainerRuntime::Podman);
+        assert_eq!(
+            container.run_args("moon", &["run", ":test"]),
+            [
+                "run",
+                "--rm",
+                "--init",
+                "--name",
+                "swarm-local-b-7",
+                "--cpus",
+                "8",
+                "--memory",
+                "4g",
+                "--network",
+                "none",
+                "--env",
+                "CARGO_HOME",
+                "--volume",
+                "/src/swarm:/workspace",
+                "--workdir",
+                "/workspace",
+                "rust:1.85",
+                "moon",
+                "run",
+                ":test",
+            ]
+        );
+    }
+
+    #[test]
+    fn given_local_backend_when_resolving_then_stage_runs_on_host() {
+        let config = StageSandboxConfig::default();
+
+        assert_eq!(
+            config.backend_for(Stage::QaEnforcer, Path::new("."), "swarm-local-b-7"),
+            ExecutionBackend::Local
+        );
+    }
+
+    #[test]
+    fn given_remote_backend_when_building_command_then_ssh_runs_it_quoted_in_workdir() {
+        let backend = ExecutionBackend::Remote {
+            builder: RemoteBuilder {
+                host: "ci@builder-1".to_string(),
+                port: None,
+                identity_file: None,
+                workdir: "/srv/swarm".to_string(),
+            },
+            run_name: "swarm-local-b-7".to_string(),
+        };
+
+        let command = backend.command("moon", &["run", ":test"]);
+        let cleanup = backend.cleanup_command().expect("remote cleanup");
+
+        assert_eq!(command.as_std().get_program(), "ssh");
+        assert_eq!(
+            command.as_std().get_args().collect::<Vec<_>>(),
+            [
+                "-o",
+                "BatchMode=yes",
+                "--",
+                "ci@builder-1",
+                r#"cd '/srv/swarm' && { echo $$ > "${TMPDIR:-/tmp}/swarm-local-b-7.pid"; 'moon' 'run' ':test'; status=$?; rm -f "${TMPDIR:-/tmp}/swarm-local-b-7.pid"; exit $status; }"#,
+            ]
+        );
+        assert_eq!(cleanup.get_program(), "ssh");
+        assert_eq!(
+            cleanup.get_args().last().and_then(|arg| arg.to_str()),
+            Some(
+                r#"cd '/srv/swarm' && { [ -f "${TMPDIR:-/tmp}/swarm-local-b-7.pid" ] && kill -15 -"$(cat "${TMPDIR:-/tmp}/swarm-local-b-7.pid")"; rm -f "${TMPDIR:-/tmp}/swarm-local-b-7.pid"; }"#
+            )
+        );
+    }
+
+    #[test]
+    fn given_container_backend_when_cleaning_up_then_the_named_container_is_removed() {
+        let config =
+            StageSandboxConfig::from_json(r#"{"backend": "docker", "image": "rust:1.85"}"#)
+                .expect("valid sandbox config");
+        let run_name = stage_run_name("local", "bd 12/x", 7);
+
+        let cleanup = config
+            .backend_for(Stage::QaEnforcer, Path::new("."), &run_name)
+            .cleanup_command()
+            .expect("container cleanup");
+
+        assert_eq!(run_name, "swarm-local-bd-12-x-7");
+        assert_eq!(cleanup.get_program(), "docker");
+        assert_eq!(
+            cleanup.get_args().collect::<Vec<_>>(),
+            ["rm", "-f", "swarm-local-bd-12-x-7"]
+        );
+        assert!(ExecutionBackend::Local.cleanup_command().is_none());
+    }
+
+    #[test]
+    fn given_invalid_sandbox_configs_when_parsing_then_config_errors_are_returned() {
+        for raw in [
+            r#"{"backend": "docker"}"#,
+            r#"{"backend": "local", "stages": {"deploy": {}}}"#,
+            r#"{"backend": "docker", "image": "x", "limits": {"cpus": 0}}"#,
+            r#"{"backend": "docker", "image": "x", "limits": {"gpus": 1}}"#,
+        ] {
+            assert!(
+                matches!(
+                    StageSandboxConfig::from_json(raw),
+                    Err(SwarmError::ConfigError(_))
+                ),
+                "{raw}"
+            );
+        }
+    }
+}
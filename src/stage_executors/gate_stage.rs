@@ -1,15 +1,20 @@
-use crate::error::{Result, SwarmError};
+use super::backend::ExecutionBackend;
+use super::output_capture::{capture_output, StageOutputSpool};
+use super::output_mapping::{failure_output, log_child_output};
+use crate::error::Result;
 use crate::gate_cache::GateExecutionCache;
 use crate::skill_execution::SkillOutput;
 use crate::types::ArtifactType;
 use crate::{AgentId, BeadId, SwarmDb};
-use tokio::process::Command;
-
-use super::output_mapping::failure_output;
 
+/// Runs `moon run <task>`, holding at most the configured memory of its
+/// output and writing the rest through `spool`. Output that spilled is not
+/// cached, since the cache would only replay its tail.
 pub(super) async fn run_moon_task(
     task: &str,
+    backend: &ExecutionBackend,
     cache: Option<&GateExecutionCache>,
+    spool: Option<StageOutputSpool<'_>>,
 ) -> Result<SkillOutput> {
     if let Some(cache) = cache {
         if let Some((_success, exit_code, stdout, stderr)) = cache.get(task).await {
@@ -17,18 +22,30 @@ pub(super) async fn run_moon_task(
         }
     }
 
-    let output = Command::new("moon")
-        .args(["run", task])
-        .output()
-        .await
-        .map_err(SwarmError::IoError)?;
+    if let Some(delay) = crate::chaos::external_command_delay() {
+        tokio::time::sleep(delay).await;
+    }
+    let started = std::time::Instant::now();
+    let captured = capture_output(
+        backend.command("moon", &["run", task]),
+        backend.cleanup_command(),
+        spool,
+    )
+    .await?;
+    crate::profiling::record(
+        crate::profiling::HotspotCategory::ExternalCommand,
+        "moon",
+        started.elapsed(),
+    );
 
-    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
-    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
-    let exit_code = output.status.code();
+    let spilled = captured.spilled();
+    let stdout = captured.stdout.text;
+    let stderr = captured.stderr.text;
+    let exit_code = captured.exit_code;
+    log_child_output("moon", exit_code, &stdout, &stderr);
     let success = exit_code.is_none_or(|code| code == 0);
 
-    if let Some(cache) = cache {
+    if let Some(cache) = cache.filter(|_| !spilled) {
         cache
             .put(
                 task.to_string(),
@@ -51,7 +68,9 @@ pub(super) async fn execute_qa_stage(
     bead_id: &BeadId,
     agent_id: &AgentId,
     db: &SwarmDb,
+    backend: &ExecutionBackend,
     cache: Option<&GateExecutionCache>,
+    spool: Option<StageOutputSpool<'_>>,
 ) -> Result<SkillOutput> {
     if !db
         .bead_has_artifact_type(
@@ -66,7 +85,7 @@ pub(super) async fn execute_qa_stage(
         ));
     }
 
-    let mut output = run_moon_task(":quick", cache).await?;
+    let mut output = run_moon_task(":quick", backend, cache, spool).await?;
     output.extract_qa_artifacts();
 
     if output.success {
@@ -90,7 +109,9 @@ pub(super) async fn execute_red_queen_stage(
     bead_id: &BeadId,
     agent_id: &AgentId,
     db: &SwarmDb,
+    backend: &ExecutionBackend,
     cache: Option<&GateExecutionCache>,
+    spool: Option<StageOutputSpool<'_>>,
 ) -> Result<SkillOutput> {
     if !db
         .bead_has_artifact_type(agent_id.repo_id(), bead_id, ArtifactType::TestResults)
@@ -101,7 +122,7 @@ pub(super) async fn execute_red_queen_stage(
         ));
     }
 
-    let mut output = run_moon_task(":test", cache).await?;
+    let mut output = run_moon_task(":test", backend, cache, spool).await?;
     output.extract_red_queen_artifacts();
 
     if output.success {
@@ -6,15 +6,20 @@ use crate::{AgentId, BeadId, SwarmDb};
 use serde_json::Value;
 use std::collections::HashMap;
 
+use super::output_capture::{capture_text, StageOutputSpool};
 use super::output_mapping::failure_output;
 
 /// Execute the implement stage.
 ///
 /// This stage composes implementation artifacts from the contract context.
+/// The implementation grows with the context it is built from, so its log
+/// copy is held to the stage output cap like command output, with the rest
+/// written through `spool`.
 pub(super) async fn execute_implement_stage(
     bead_id: &BeadId,
     agent_id: &AgentId,
     db: &SwarmDb,
+    spool: Option<StageOutputSpool<'_>>,
 ) -> Result<SkillOutput> {
     let maybe_contract_artifact = db
         .get_first_bead_artifact_by_type(
@@ -95,6 +100,7 @@ pub(super) async fn execute_implement_stage(
 
     let aggregated_context = context_sections.join("\n\n");
     let implementation_code = implementation_scaffold(bead_id, &aggregated_context);
+    let log = capture_text(&implementation_code, "stdout", spool).await?;
 
     tracing::info!(
         "Agent {} generated implementation artifact for bead {}",
@@ -103,7 +109,7 @@ pub(super) async fn execute_implement_stage(
     );
 
     Ok(SkillOutput {
-        full_log: implementation_code.clone(),
+        full_log: log.text,
         success: true,
         exit_code: Some(0),
         artifacts: HashMap::new(),
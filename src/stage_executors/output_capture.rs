@@ -0,0 +1,162 @@
+//! Reads a stage command's stdout and stderr while it runs, holding at most
+//! `SWARM_STAGE_OUTPUT_MEMORY_BYTES` of each in memory and streaming the
+//! rest to `stage_output_chunks`.
+
+use crate::error::{Result, SwarmError};
+use crate::stage_executor_content::{BufferedOutput, StageOutputBuffer};
+use crate::SwarmDb;
+use std::process::Stdio;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+const READ_BYTES: usize = 64 * 1024;
+
+/// Where a stage run's spilled output goes.
+#[derive(Clone, Copy)]
+pub(super) struct StageOutputSpool<'a> {
+    db: &'a SwarmDb,
+    stage_history_id: i64,
+}
+
+impl<'a> StageOutputSpool<'a> {
+    #[must_use]
+    pub(super) const fn new(db: &'a SwarmDb, stage_history_id: i64) -> Self {
+        Self {
+            db,
+            stage_history_id,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct CapturedOutput {
+    pub(super) stdout: BufferedOutput,
+    pub(super) stderr: BufferedOutput,
+    pub(super) exit_code: Option<i32>,
+}
+
+impl CapturedOutput {
+    pub(super) const fn spilled(&self) -> bool {
+        self.stdout.spilled || self.stderr.spilled
+    }
+}
+
+/// Runs `cleanup` in the background if dropped before [`Self::disarm`].
+struct CleanupOnDrop(Option<std::process::Command>);
+
+impl CleanupOnDrop {
+    fn disarm(&mut self) {
+        self.0 = None;
+    }
+}
+
+impl Drop for CleanupOnDrop {
+    fn drop(&mut self) {
+        let Some(mut cleanup) = self.0.take() else {
+            return;
+        };
+        cleanup
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        match cleanup.spawn() {
+            Ok(mut child) => {
+                std::thread::spawn(move || {
+                    if let Err(err) = child.wait() {
+                        tracing::warn!("Stage cleanup did not finish: {}", err);
+                    }
+                });
+            }
+            Err(err) => tracing::warn!("Stage cleanup could not start: {}", err),
+        }
+    }
+}
+
+/// Runs `command` to completion. Without a spool, output past the memory
+/// cap is dropped and only its tail is kept. A chunk that cannot be stored
+/// fails the capture, which kills the command. `cleanup` runs if the
+/// capture is dropped or fails before the command exits, to stop what the
+/// command started outside its own process, such as a container.
+pub(super) async fn capture_output(
+    mut command: Command,
+    cleanup: Option<std::process::Command>,
+    spool: Option<StageOutputSpool<'_>>,
+) -> Result<CapturedOutput> {
+    let memory_cap = crate::config::stage_output_memory_bytes_from_env();
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command.spawn().map_err(SwarmError::IoError)?;
+    let mut cleanup = CleanupOnDrop(cleanup);
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    let (stdout, stderr, status) = tokio::try_join!(
+        read_stream(stdout, "stdout", memory_cap, spool),
+        read_stream(stderr, "stderr", memory_cap, spool),
+        async { child.wait().await.map_err(SwarmError::IoError) },
+    )?;
+    cleanup.disarm();
+    Ok(CapturedOutput {
+        stdout,
+        stderr,
+        exit_code: status.code(),
+    })
+}
+
+/// Holds `text`, output a stage produced in process rather than through a
+/// command, to the same memory cap, writing the rest through `spool` as
+/// `stream`.
+pub(super) async fn capture_text(
+    text: &str,
+    stream: &str,
+    spool: Option<StageOutputSpool<'_>>,
+) -> Result<BufferedOutput> {
+    let memory_cap = crate::config::stage_output_memory_bytes_from_env();
+    read_stream(Some(text.as_bytes()), stream, memory_cap, spool).await
+}
+
+async fn read_stream<R: AsyncRead + Unpin>(
+    pipe: Option<R>,
+    stream: &str,
+    memory_cap: usize,
+    spool: Option<StageOutputSpool<'_>>,
+) -> Result<BufferedOutput> {
+    let mut buffer = StageOutputBuffer::new(memory_cap);
+    let mut seq = 0_i32;
+    if let Some(mut pipe) = pipe {
+        let mut read = vec![0_u8; READ_BYTES];
+        loop {
+            let len = pipe.read(&mut read).await.map_err(SwarmError::IoError)?;
+            if len == 0 {
+                break;
+            }
+            for chunk in buffer.push(&read[..len]) {
+                spill(spool, stream, &mut seq, &chunk).await?;
+            }
+        }
+    }
+
+    let (final_chunk, output) = buffer.finish();
+    if let Some(chunk) = final_chunk {
+        spill(spool, stream, &mut seq, &chunk).await?;
+    }
+    Ok(output)
+}
+
+async fn spill(
+    spool: Option<StageOutputSpool<'_>>,
+    stream: &str,
+    seq: &mut i32,
+    chunk: &[u8],
+) -> Result<()> {
+    if let Some(spool) = spool {
+        spool
+            .db
+            .append_stage_output_chunk(spool.stage_history_id, stream, *seq, chunk)
+            .await?;
+    }
+    *seq = seq.saturating_add(1);
+    Ok(())
+}
@@ -1,6 +1,10 @@
 use crate::skill_execution::SkillOutput;
 use std::collections::HashMap;
 
+/// Most bytes of each stream of a stage command logged at debug. Longer
+/// output keeps its tail, where failures are reported.
+pub(super) const MAX_LOGGED_OUTPUT_BYTES: usize = 4 * 1024;
+
 pub(super) fn output_to_stage_result(output: &SkillOutput) -> crate::types::StageResult {
     if output.success {
         crate::types::StageResult::Passed
@@ -62,3 +66,27 @@ pub(super) fn error_output(message: String) -> SkillOutput {
         adversarial_report: None,
     }
 }
+
+/// Logs a finished stage command and its output at debug, each stream cut
+/// to [`MAX_LOGGED_OUTPUT_BYTES`].
+pub(super) fn log_child_output(program: &str, exit_code: Option<i32>, stdout: &str, stderr: &str) {
+    tracing::debug!(
+        program,
+        exit_code,
+        stdout_bytes = stdout.len(),
+        stderr_bytes = stderr.len(),
+        stdout = output_tail(stdout),
+        stderr = output_tail(stderr),
+        "stage command finished"
+    );
+}
+
+/// The last [`MAX_LOGGED_OUTPUT_BYTES`] of `text`, starting on a character
+/// boundary.
+pub(super) fn output_tail(text: &str) -> &str {
+    let mut start = text.len().saturating_sub(MAX_LOGGED_OUTPUT_BYTES);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
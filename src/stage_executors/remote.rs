@@ -0,0 +1,260 @@
+//! Running gate stages on a remote builder host over SSH, so heavy QA can use
+//! machines beefier than the coordinator.
+
+use crate::error::{Result, SwarmError};
+use crate::types::Stage;
+use crate::RuntimeStage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One SSH-reachable builder with a checkout of the repository.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteBuilder {
+    /// `host` or `user@host`.
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub identity_file: Option<String>,
+    /// Repository checkout on the builder that stage commands run in.
+    pub workdir: String,
+}
+
+impl RemoteBuilder {
+    /// `ssh` arguments that run `command`, already shell-quoted, in `workdir`
+    /// on the builder.
+    #[must_use]
+    pub fn ssh_args(&self, command: &str) -> Vec<String> {
+        let mut args = vec!["-o".to_string(), "BatchMode=yes".to_string()];
+        if let Some(port) = self.port {
+            args.extend(["-p".to_string(), port.to_string()]);
+        }
+        if let Some(identity_file) = &self.identity_file {
+            args.extend(["-i".to_string(), identity_file.clone()]);
+        }
+        args.extend([
+            "--".to_string(),
+            self.host.clone(),
+            format!("cd {} && {command}", shell_quote(&self.workdir)),
+        ]);
+        args
+    }
+
+    /// [`Self::ssh_args`], with the remote process group recorded under
+    /// `run_name` for [`Self::kill_args`]. sshd starts each session in a
+    /// group of its own, led by the remote shell, so `$$` names it.
+    #[must_use]
+    pub fn tracked_ssh_args(&self, run_name: &str, command: &str) -> Vec<String> {
+        let pid_file = pid_file(run_name);
+        self.ssh_args(&format!(
+            "{{ echo $$ > {pid_file}; {command}; status=$?; rm -f {pid_file}; exit $status; }}"
+        ))
+    }
+
+    /// `ssh` arguments that kill the process group `run_name` recorded, if
+    /// it is still running. Closing the first connection does not stop it.
+    #[must_use]
+    pub fn kill_args(&self, run_name: &str) -> Vec<String> {
+        let pid_file = pid_file(run_name);
+        self.ssh_args(&format!(
+            "{{ [ -f {pid_file} ] && kill -15 -\"$(cat {pid_file})\"; rm -f {pid_file}; }}"
+        ))
+    }
+}
+
+/// Where the builder keeps `run_name`'s process group id, quoted for its
+/// shell. Run names are made of shell-safe characters.
+fn pid_file(run_name: &str) -> String {
+    format!("\"${{TMPDIR:-/tmp}}/{run_name}.pid\"")
+}
+
+/// Remote builders and which agents or stages use them, read from
+/// `SWARM_REMOTE_EXECUTORS`.
+///
+/// ```json
+/// {"builders": {"big": {"host": "ci@builder-1", "workdir": "/srv/swarm"}},
+///  "stages": {"red-queen": "big"},
+///  "agents": {"4": "big"}}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteExecutorConfig {
+    #[serde(default)]
+    pub builders: HashMap<String, RemoteBuilder>,
+    /// Stage name to builder name.
+    #[serde(default)]
+    pub stages: HashMap<String, String>,
+    /// Agent id to builder name; takes precedence over `stages`.
+    #[serde(default)]
+    pub agents: HashMap<String, String>,
+}
+
+impl RemoteExecutorConfig {
+    /// Parses and validates a remote executor config.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::ConfigError` for malformed JSON, a builder host
+    /// that is empty or starts with `-`, an unknown stage, an agent key that
+    /// is not a positive integer, or a reference to a builder that is not
+    /// defined.
+    pub fn from_json(raw: &str) -> Result<Self> {
+        let config: Self = serde_json::from_str(raw)
+            .map_err(|e| SwarmError::ConfigError(format!("Invalid remote executor config: {e}")))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some((name, builder)) = self
+            .builders
+            .iter()
+            .find(|(_, builder)| builder.host.is_empty() || builder.host.starts_with('-'))
+        {
+            return Err(SwarmError::ConfigError(format!(
+                "Remote builder {name} has an invalid host: {:?}",
+                builder.host
+            )));
+        }
+        if let Some(stage) = self
+            .stages
+            .keys()
+            .find(|stage| Stage::try_from(stage.as_str()).is_err())
+        {
+            return Err(SwarmError::ConfigError(format!(
+                "Unknown stage in remote executor config: {stage}"
+            )));
+        }
+        if let Some(agent) = self
+            .agents
+            .keys()
+            .find(|agent| !agent.parse::<u32>().is_ok_and(|id| id > 0))
+        {
+            return Err(SwarmError::ConfigError(format!(
+                "Agent keys must be positive integers, got: {agent}"
+            )));
+        }
+        if let Some(builder) = self
+            .stages
+            .values()
+            .chain(self.agents.values())
+            .find(|builder| !self.builders.contains_key(builder.as_str()))
+        {
+            return Err(SwarmError::ConfigError(format!(
+                "Unknown remote builder: {builder}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// The builder `agent_id` uses for `stage`, if any; `None` runs locally.
+    #[must_use]
+    pub fn builder_for(&self, agent_id: u32, stage: &str) -> Option<(&str, &RemoteBuilder)> {
+        self.agents
+            .get(&agent_id.to_string())
+            .or_else(|| self.stages.get(stage))
+            .and_then(|name| {
+                self.builders
+                    .get_key_value(name)
+                    .map(|(name, builder)| (name.as_str(), builder))
+            })
+    }
+}
+
+/// Gate command a stage runs remotely; other stages have nothing to offload.
+#[must_use]
+pub const fn remote_stage_command(stage: RuntimeStage) -> Option<&'static str> {
+    match stage {
+        RuntimeStage::QaEnforcer => Some("moon run :quick"),
+        RuntimeStage::RedQueen => Some("moon run :test"),
+        RuntimeStage::RustContract | RuntimeStage::Implement | RuntimeStage::Done => None,
+    }
+}
+
+/// Single-quotes `value` for a POSIX shell.
+pub(super) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{
+        "builders": {
+            "big": {"host": "ci@builder-1", "port": 2222, "workdir": "/srv/it's"},
+            "gpu": {"host": "builder-2", "workdir": "/srv/swarm"}
+        },
+        "stages": {"red-queen": "big"},
+        "agents": {"4": "gpu"}
+    }"#;
+
+    #[test]
+    fn given_agent_and_stage_routes_when_resolving_then_agent_route_wins() {
+        let config = RemoteExecutorConfig::from_json(CONFIG).expect("valid config");
+
+        assert_eq!(
+            config.builder_for(4, "red-queen").map(|(name, _)| name),
+            Some("gpu")
+        );
+        assert_eq!(
+            config.builder_for(1, "red-queen").map(|(name, _)| name),
+            Some("big")
+        );
+        assert_eq!(config.builder_for(1, "qa-enforcer"), None);
+    }
+
+    #[test]
+    fn given_builder_when_building_ssh_args_then_workdir_is_quoted() {
+        let config = RemoteExecutorConfig::from_json(CONFIG).expect("valid config");
+        let (_, builder) = config.builder_for(1, "red-queen").expect("routed");
+
+        assert_eq!(
+            builder.ssh_args("moon run :test"),
+            [
+                "-o",
+                "BatchMode=yes",
+                "-p",
+                "2222",
+                "--",
+                "ci@builder-1",
+                r"cd '/srv/it'\''s' && moon run :test",
+            ]
+        );
+    }
+
+    #[test]
+    fn given_host_starting_with_dash_when_building_ssh_args_then_it_follows_the_option_terminator()
+    {
+        let builder = RemoteBuilder {
+            host: "-oProxyCommand=sh".to_string(),
+            port: None,
+            identity_file: None,
+            workdir: "/w".to_string(),
+        };
+
+        let args = builder.ssh_args("true");
+
+        assert_eq!(args[2..4], ["--", "-oProxyCommand=sh"]);
+    }
+
+    #[test]
+    fn given_bad_host_or_route_to_missing_builder_when_parsing_then_config_error_is_returned() {
+        for raw in [
+            r#"{"stages": {"red-queen": "nope"}}"#,
+            r#"{"builders": {"b": {"host": "h", "workdir": "/w"}}, "agents": {"0": "b"}}"#,
+            r#"{"builders": {"b": {"host": "h", "workdir": "/w"}}, "stages": {"lint": "b"}}"#,
+            r#"{"builders": {"b": {"host": "-oProxyCommand=sh", "workdir": "/w"}}}"#,
+            r#"{"builders": {"b": {"host": "", "workdir": "/w"}}}"#,
+        ] {
+            assert!(
+                matches!(
+                    RemoteExecutorConfig::from_json(raw),
+                    Err(SwarmError::ConfigError(_))
+                ),
+                "{raw}"
+            );
+        }
+    }
+}
@@ -0,0 +1,494 @@
+//! Config-driven interpretation of stage command output, so QA tools that do
+//! not speak cargo's conventions can still pass or fail a stage.
+
+use crate::error::{Result, SwarmError};
+use crate::skill_execution::SkillOutput;
+use crate::skill_execution_parsing::{parse_junit_xml, parse_tap, TestResults};
+use crate::types::Stage;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// How a stage's output decides pass or fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParserFormat {
+    /// Passes when the exit code is one of `success_codes`.
+    ExitCode,
+    /// Passes when the value at `status_pointer` is one of `pass_values`.
+    Json,
+    /// Passes when a `JUnit` XML report has no failed or errored test cases.
+    Junit,
+    /// Passes when a TAP report has no `not ok` lines outside a directive.
+    Tap,
+}
+
+impl ParserFormat {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::ExitCode => "exit_code",
+            Self::Json => "json",
+            Self::Junit => "junit",
+            Self::Tap => "tap",
+        }
+    }
+}
+
+/// How to read one stage's output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StageParserSpec {
+    pub format: ParserFormat,
+    /// `exit_code`: codes that count as a pass. Defaults to `[0]`.
+    #[serde(default)]
+    pub success_codes: Option<Vec<i32>>,
+    /// `json`: RFC 6901 pointer to the status value, e.g. `/status`.
+    #[serde(default)]
+    pub status_pointer: Option<String>,
+    /// `json`: status values that count as a pass. Defaults to `true`,
+    /// `"pass"`, `"passed"`, `"ok"` and `"success"`.
+    #[serde(default)]
+    pub pass_values: Option<Vec<Value>>,
+    /// `json`: pointer to a failure message.
+    #[serde(default)]
+    pub reason_pointer: Option<String>,
+    /// `junit`, `tap`: report path relative to the repository root. Unset reads
+    /// the XML from the command's output.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Any format: regex run over the output of a failed stage. The `reason`
+    /// group, or the whole match, becomes the failure reason.
+    #[serde(default)]
+    pub failure_pattern: Option<String>,
+}
+
+/// Stage result parsers, read from `SWARM_STAGE_PARSERS`.
+///
+/// ```json
+/// {"stages": {"qa-enforcer": {"format": "json", "status_pointer": "/status", "reason_pointer": "/message"},
+///             "red-queen": {"format": "junit", "path": "target/junit.xml"},
+///             "implement": {"format": "exit_code", "success_codes": [0], "failure_pattern": "(?m)^error: (?P<reason>.*)$"}}}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StageParserConfig {
+    /// Parsers keyed by stage name. Stages not listed keep the built-in
+    /// exit-code handling.
+    #[serde(default)]
+    pub stages: HashMap<String, StageParserSpec>,
+}
+
+#[derive(Debug, Clone)]
+struct StageResultParser {
+    spec: StageParserSpec,
+    failure_pattern: Option<Regex>,
+}
+
+/// Validated [`StageParserConfig`] with its patterns compiled.
+#[derive(Debug, Clone, Default)]
+pub struct StageParserRegistry {
+    parsers: HashMap<Stage, StageResultParser>,
+}
+
+impl StageParserRegistry {
+    /// Parses and validates a parser config.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::ConfigError` for malformed JSON, an unknown stage
+    /// name, a field the format does not use, a pointer not starting with
+    /// `/`, or a regex that does not compile.
+    pub fn from_json(raw: &str) -> Result<Self> {
+        let config: StageParserConfig = serde_json::from_str(raw)
+            .map_err(|e| SwarmError::ConfigError(format!("Invalid stage parser config: {e}")))?;
+        Self::from_config(config)
+    }
+
+    /// Validates `config` and compiles its patterns.
+    ///
+    /// # Errors
+    /// Returns `SwarmError::ConfigError` as for [`Self::from_json`].
+    pub fn from_config(config: StageParserConfig) -> Result<Self> {
+        config
+            .stages
+            .into_iter()
+            .map(|(name, spec)| {
+                let stage = Stage::try_from(name.as_str()).map_err(|_| {
+                    SwarmError::ConfigError(format!("Unknown stage in parser config: {name}"))
+                })?;
+                compile(&name, spec).map(|parser| (stage, parser))
+            })
+            .collect::<Result<HashMap<_, _>>>()
+            .map(|parsers| Self { parsers })
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.parsers.is_empty()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.parsers.len()
+    }
+
+    /// Format configured for `stage`, if any.
+    #[must_use]
+    pub fn format_for(&self, stage: Stage) -> Option<ParserFormat> {
+        self.parsers.get(&stage).map(|parser| parser.spec.format)
+    }
+
+    /// `output` with `success` and `feedback` decided by the parser for
+    /// `stage`. Stages without a parser are returned unchanged.
+    #[must_use]
+    pub fn apply(&self, stage: Stage, output: SkillOutput, repo_root: &Path) -> SkillOutput {
+        match self.parsers.get(&stage) {
+            Some(parser) => parser.apply(output, repo_root),
+            None => output,
+        }
+    }
+}
+
+fn compile(name: &str, spec: StageParserSpec) -> Result<StageResultParser> {
+    let invalid = |message: String| SwarmError::ConfigError(format!("Stage {name}: {message}"));
+
+    let format = spec.format;
+    let reports: &[ParserFormat] = &[ParserFormat::Junit, ParserFormat::Tap];
+    let fields: [(&str, bool, &[ParserFormat]); 5] = [
+        (
+            "success_codes",
+            spec.success_codes.is_some(),
+            &[ParserFormat::ExitCode],
+        ),
+        (
+            "status_pointer",
+            spec.status_pointer.is_some(),
+            &[ParserFormat::Json],
+        ),
+        (
+            "pass_values",
+            spec.pass_values.is_some(),
+            &[ParserFormat::Json],
+        ),
+        (
+            "reason_pointer",
+            spec.reason_pointer.is_some(),
+            &[ParserFormat::Json],
+        ),
+        ("path", spec.path.is_some(), reports),
+    ];
+    if let Some((field, _, _)) = fields
+        .into_iter()
+        .find(|(_, set, applies_to)| *set && !applies_to.contains(&format))
+    {
+        return Err(invalid(format!(
+            "{field} does not apply to the {} format",
+            format.as_str()
+        )));
+    }
+
+    if spec.format == ParserFormat::Json && spec.status_pointer.is_none() {
+        return Err(invalid("json format requires status_pointer".to_string()));
+    }
+    if let Some(pointer) = [&spec.status_pointer, &spec.reason_pointer]
+        .into_iter()
+        .flatten()
+        .find(|pointer| !pointer.is_empty() && !pointer.starts_with('/'))
+    {
+        return Err(invalid(format!(
+            "JSON pointer {pointer} must be empty or start with /"
+        )));
+    }
+    if spec.success_codes.as_ref().is_some_and(Vec::is_empty) {
+        return Err(invalid("success_codes must not be empty".to_string()));
+    }
+    if spec.pass_values.as_ref().is_some_and(Vec::is_empty) {
+        return Err(invalid("pass_values must not be empty".to_string()));
+    }
+    if spec
+        .path
+        .as_deref()
+        .is_some_and(|path| path.trim().is_empty())
+    {
+        return Err(invalid("path must not be empty".to_string()));
+    }
+
+    let failure_pattern = spec
+        .failure_pattern
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| invalid(format!("invalid failure_pattern: {e}")))?;
+
+    Ok(StageResultParser {
+        spec,
+        failure_pattern,
+    })
+}
+
+impl StageResultParser {
+    fn apply(&self, mut output: SkillOutput, repo_root: &Path) -> SkillOutput {
+        let (success, reason) = match self.spec.format {
+            ParserFormat::ExitCode => self.exit_code_verdict(&output),
+            ParserFormat::Json => self.json_verdict(&output),
+            ParserFormat::Junit | ParserFormat::Tap => {
+                let (success, reason, results) = self.report_verdict(&output, repo_root);
+                if results.is_some() {
+                    output.test_results = results;
+                }
+                (success, reason)
+            }
+        };
+
+        output.success = success;
+        output.feedback = if success {
+            String::new()
+        } else {
+            self.matched_reason(&output.full_log)
+                .or(reason)
+                .unwrap_or_else(|| output.full_log.clone())
+        };
+        output
+    }
+
+    fn exit_code_verdict(&self, output: &SkillOutput) -> (bool, Option<String>) {
+        let success = output.exit_code.is_some_and(|code| {
+            self.spec
+                .success_codes
+                .as_deref()
+                .map_or(code == 0, |codes| codes.contains(&code))
+        });
+        let reason = output.exit_code.map_or_else(
+            || "Stage command was terminated by a signal".to_string(),
+            |code| format!("Stage command exited with code {code}"),
+        );
+        (success, Some(reason))
+    }
+
+    fn json_verdict(&self, output: &SkillOutput) -> (bool, Option<String>) {
+        let pointer = self.spec.status_pointer.as_deref().unwrap_or_default();
+        let Some(document) = json_document(&output.full_log) else {
+            return (false, Some("Stage output contains no JSON".to_string()));
+        };
+        let Some(status) = document.pointer(pointer) else {
+            return (
+                false,
+                Some(format!(
+                    "Status pointer {pointer} not found in stage output"
+                )),
+            );
+        };
+
+        let success = self.spec.pass_values.as_deref().map_or_else(
+            || default_pass_value(status),
+            |values| values.contains(status),
+        );
+        let reason = self
+            .spec
+            .reason_pointer
+            .as_deref()
+            .and_then(|pointer| document.pointer(pointer))
+            .map_or_else(|| format!("Stage reported status {status}"), display_value);
+        (success, Some(reason))
+    }
+
+    fn report_verdict(
+        &self,
+        output: &SkillOutput,
+        repo_root: &Path,
+    ) -> (bool, Option<String>, Option<TestResults>) {
+        let kind = match self.spec.format {
+            ParserFormat::Tap => "TAP",
+            _ => "JUnit",
+        };
+        let report = match self.spec.path.as_deref() {
+            Some(path) => match std::fs::read_to_string(repo_root.join(path)) {
+                Ok(report) => report,
+                Err(e) => {
+                    return (
+                        false,
+                        Some(format!("Failed to read {kind} report {path}: {e}")),
+                        None,
+                    )
+                }
+            },
+            None => output.full_log.clone(),
+        };
+
+        let parsed = match self.spec.format {
+            ParserFormat::Tap => {
+                parse_tap(&report).ok_or_else(|| "Stage output contains no TAP report".to_string())
+            }
+            _ => parse_junit_xml(&report),
+        };
+        match parsed {
+            Ok(results) if results.total == 0 => (
+                false,
+                Some(format!("{kind} report contains no test cases")),
+                Some(results),
+            ),
+            Ok(results) if results.failed == 0 => (true, None, Some(results)),
+            Ok(results) => {
+                let names = results
+                    .failures
+                    .iter()
+                    .map(|failure| failure.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let reason = format!(
+                    "{} of {} tests failed: {names}",
+                    results.failed, results.total
+                );
+                (false, Some(reason), Some(results))
+            }
+            Err(e) => (false, Some(e), None),
+        }
+    }
+
+    fn matched_reason(&self, log: &str) -> Option<String> {
+        let captures = self.failure_pattern.as_ref()?.captures(log)?;
+        captures
+            .name("reason")
+            .or_else(|| captures.get(0))
+            .map(|found| found.as_str().trim().to_string())
+            .filter(|reason| !reason.is_empty())
+    }
+}
+
+/// The whole output as JSON, or failing that the last line that parses, for
+/// tools that log before printing their summary.
+fn json_document(log: &str) -> Option<Value> {
+    serde_json::from_str(log.trim()).ok().or_else(|| {
+        log.lines()
+            .rev()
+            .map(str::trim)
+            .filter(|line| line.starts_with('{') || line.starts_with('['))
+            .find_map(|line| serde_json::from_str(line).ok())
+    })
+}
+
+fn default_pass_value(status: &Value) -> bool {
+    match status {
+        Value::Bool(passed) => *passed,
+        Value::String(status) => ["pass", "passed", "ok", "success"]
+            .iter()
+            .any(|pass| status.eq_ignore_ascii_case(pass)),
+        _ => false,
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    value
+        .as_str()
+        .map_or_else(|| value.to_string(), str::to_string)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn registry(raw: &str) -> StageParserRegistry {
+        StageParserRegistry::from_json(raw).expect("valid parser config")
+    }
+
+    fn shell(stdout: &str, exit_code: i32) -> SkillOutput {
+        SkillOutput::from_shell_output(stdout, String::new(), Some(exit_code))
+    }
+
+    #[test]
+    fn given_json_parser_when_status_fails_then_reason_pointer_becomes_feedback() {
+        let parsers = registry(
+            r#"{"stages": {"qa-enforcer": {"format": "json", "status_pointer": "/status", "reason_pointer": "/message"}}}"#,
+        );
+        let output = shell(
+            "linting...\n{\"status\": \"failed\", \"message\": \"3 lint errors\"}",
+            0,
+        );
+
+        let parsed = parsers.apply(Stage::QaEnforcer, output, Path::new("."));
+
+        assert!(!parsed.success);
+        assert_eq!(parsed.feedback, "3 lint errors");
+    }
+
+    #[test]
+    fn given_extra_success_code_when_tool_exits_with_it_then_stage_passes() {
+        let parsers = registry(
+            r#"{"stages": {"red-queen": {"format": "exit_code", "success_codes": [0, 2]}}}"#,
+        );
+
+        let parsed = parsers.apply(Stage::RedQueen, shell("warnings only", 2), Path::new("."));
+
+        assert!(parsed.success);
+        assert!(parsed.feedback.is_empty());
+    }
+
+    #[test]
+    fn given_failure_pattern_when_stage_fails_then_reason_group_becomes_feedback() {
+        let parsers = registry(
+            r#"{"stages": {"implement": {"format": "exit_code", "failure_pattern": "(?m)^error: (?P<reason>.*)$"}}}"#,
+        );
+
+        let parsed = parsers.apply(
+            Stage::Implement,
+            shell("compiling\nerror: mismatched types\n", 1),
+            Path::new("."),
+        );
+
+        assert!(!parsed.success);
+        assert_eq!(parsed.feedback, "mismatched types");
+    }
+
+    #[test]
+    fn given_junit_on_stdout_when_a_case_fails_then_test_results_are_attached() {
+        let parsers = registry(r#"{"stages": {"red-queen": {"format": "junit"}}}"#);
+        let xml = r#"<testsuite><testcase name="ok"/><testcase name="bad"><failure message="boom"/></testcase></testsuite>"#;
+
+        let parsed = parsers.apply(Stage::RedQueen, shell(xml, 0), Path::new("."));
+
+        assert!(!parsed.success);
+        assert_eq!(parsed.feedback, "1 of 2 tests failed: bad");
+        assert_eq!(parsed.test_results.map(|results| results.passed), Some(1));
+    }
+
+    #[test]
+    fn given_unconfigured_stage_when_applying_then_output_is_unchanged() {
+        let parsers = registry(r#"{"stages": {"red-queen": {"format": "junit"}}}"#);
+
+        let parsed = parsers.apply(Stage::QaEnforcer, shell("boom", 1), Path::new("."));
+
+        assert!(!parsed.success);
+        assert_eq!(parsed.feedback, "boom");
+    }
+
+    #[test]
+    fn given_invalid_configs_when_loading_then_each_is_rejected() {
+        for raw in [
+            r#"{"stages": {"lint": {"format": "exit_code"}}}"#,
+            r#"{"stages": {"implement": {"format": "json"}}}"#,
+            r#"{"stages": {"implement": {"format": "json", "status_pointer": "status"}}}"#,
+            r#"{"stages": {"implement": {"format": "exit_code", "path": "x.xml"}}}"#,
+            r#"{"stages": {"implement": {"format": "exit_code", "failure_pattern": "("}}}"#,
+        ] {
+            assert!(StageParserRegistry::from_json(raw).is_err(), "{raw}");
+        }
+    }
+
+    #[test]
+    fn given_tap_parser_when_a_test_is_not_ok_then_stage_fails() {
+        let parsers = registry(r#"{"stages": {"qa-enforcer": {"format": "tap"}}}"#);
+
+        let parsed = parsers.apply(
+            Stage::QaEnforcer,
+            shell("1..2\nok 1 - adds\nnot ok 2 - divides\n", 0),
+            Path::new("."),
+        );
+
+        assert!(!parsed.success);
+        assert_eq!(parsed.feedback, "1 of 2 tests failed: divides");
+    }
+}
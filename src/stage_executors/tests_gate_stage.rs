@@ -1,43 +1,21 @@
 #![allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
 
 use crate::gate_cache::GateExecutionCache;
+#[cfg(feature = "testsupport")]
+use crate::testsupport::isolated_db;
+#[cfg(feature = "testsupport")]
 use crate::types::{ArtifactType, BeadId, RepoId};
+#[cfg(feature = "testsupport")]
 use crate::{AgentId, SwarmDb};
-use sqlx::postgres::PgPoolOptions;
+#[cfg(feature = "testsupport")]
 use sqlx::PgPool;
 
-use super::gate_stage::{execute_qa_stage, execute_red_queen_stage, run_moon_task};
-
-fn test_database_url() -> Option<String> {
-    std::env::var("SWARM_TEST_DATABASE_URL")
-        .ok()
-        .or_else(|| std::env::var("DATABASE_URL").ok())
-        .filter(|url| !url.trim().is_empty())
-        .filter(|url| url.starts_with("postgres://") || url.starts_with("postgresql://"))
-}
-
-async fn test_pool_or_skip() -> Option<PgPool> {
-    let run_integration = std::env::var("SWARM_RUN_DB_INTEGRATION")
-        .ok()
-        .is_some_and(|value| value == "1" || value.eq_ignore_ascii_case("true"));
-    if !run_integration {
-        return None;
-    }
-
-    let url = test_database_url()?;
-    PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&url)
-        .await
-        .ok()
-}
-
-async fn setup_schema(db: &SwarmDb) {
-    db.initialize_schema_from_sql(include_str!("../../schema.sql"))
-        .await
-        .expect("Failed to initialize schema");
-}
+use super::gate_stage::run_moon_task;
+#[cfg(feature = "testsupport")]
+use super::gate_stage::{execute_qa_stage, execute_red_queen_stage};
+use super::ExecutionBackend;
 
+#[cfg(feature = "testsupport")]
 async fn insert_bead_claim(pool: &PgPool, bead_id: &BeadId, agent_id: &AgentId) {
     sqlx::query(
         "INSERT INTO bead_claims (repo_id, bead_id, claimed_by, status) VALUES ($1, $2, $3, 'in_progress')",
@@ -50,6 +28,7 @@ async fn insert_bead_claim(pool: &PgPool, bead_id: &BeadId, agent_id: &AgentId)
     .expect("Failed to insert bead claim");
 }
 
+#[cfg(feature = "testsupport")]
 async fn insert_started_stage_history(pool: &PgPool, bead_id: &BeadId, agent_id: &AgentId) -> i64 {
     sqlx::query_scalar::<_, i64>(
         "INSERT INTO stage_history (repo_id, agent_id, bead_id, stage, attempt_number, status, started_at)\n             VALUES ($1, $2, $3, 'implement', 1, 'started', NOW())\n             RETURNING id",
@@ -62,6 +41,7 @@ async fn insert_started_stage_history(pool: &PgPool, bead_id: &BeadId, agent_id:
     .expect("Failed to insert stage history")
 }
 
+#[cfg(feature = "testsupport")]
 async fn seed_artifact(
     db: &SwarmDb,
     pool: &PgPool,
@@ -94,7 +74,7 @@ async fn given_cached_stderr_only_output_when_running_moon_task_then_log_transla
         .await
         .expect("cache write");
 
-    let output = run_moon_task(":quick", Some(&cache))
+    let output = run_moon_task(":quick", &ExecutionBackend::Local, Some(&cache), None)
         .await
         .expect("cached command output");
 
@@ -121,7 +101,7 @@ async fn given_cached_stdout_and_stderr_failure_when_running_moon_task_then_feed
         .await
         .expect("cache write");
 
-    let output = run_moon_task(":test", Some(&cache))
+    let output = run_moon_task(":test", &ExecutionBackend::Local, Some(&cache), None)
         .await
         .expect("cached command output");
 
@@ -130,21 +110,24 @@ async fn given_cached_stdout_and_stderr_failure_when_running_moon_task_then_feed
     assert_eq!(output.feedback, "stdout payload\nstderr payload");
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_missing_implementation_artifact_when_executing_qa_stage_then_failure_output_is_returned(
 ) {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool);
+    let db = isolated_db().await.expect("Failed to create test database");
     let bead_id = BeadId::new("qa-missing-impl");
     let agent_id = AgentId::new(RepoId::new("local"), 11);
-    setup_schema(&db).await;
 
-    let output = execute_qa_stage(&bead_id, &agent_id, &db, None)
-        .await
-        .expect("qa stage should complete with failure output");
+    let output = execute_qa_stage(
+        &bead_id,
+        &agent_id,
+        &db,
+        &ExecutionBackend::Local,
+        None,
+        None,
+    )
+    .await
+    .expect("qa stage should complete with failure output");
 
     assert!(!output.success);
     assert_eq!(
@@ -153,17 +136,14 @@ async fn given_missing_implementation_artifact_when_executing_qa_stage_then_fail
     );
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_cached_failed_gate_and_implementation_artifact_when_executing_qa_stage_then_failure_artifacts_are_extracted(
 ) {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("qa-failed-cache");
     let agent_id = AgentId::new(RepoId::new("local"), 12);
-    setup_schema(&db).await;
     seed_artifact(
         &db,
         &pool,
@@ -187,9 +167,16 @@ async fn given_cached_failed_gate_and_implementation_artifact_when_executing_qa_
         .await
         .expect("cache write");
 
-    let output = execute_qa_stage(&bead_id, &agent_id, &db, Some(&cache))
-        .await
-        .expect("qa stage should run from cache");
+    let output = execute_qa_stage(
+        &bead_id,
+        &agent_id,
+        &db,
+        &ExecutionBackend::Local,
+        Some(&cache),
+        None,
+    )
+    .await
+    .expect("qa stage should run from cache");
 
     assert!(!output.success);
     assert!(output.artifacts.contains_key("test_output"));
@@ -197,21 +184,24 @@ async fn given_cached_failed_gate_and_implementation_artifact_when_executing_qa_
     assert!(output.test_results.is_some());
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_missing_test_results_artifact_when_executing_red_queen_stage_then_failure_output_is_returned(
 ) {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool);
+    let db = isolated_db().await.expect("Failed to create test database");
     let bead_id = BeadId::new("rq-missing-tests");
     let agent_id = AgentId::new(RepoId::new("local"), 13);
-    setup_schema(&db).await;
 
-    let output = execute_red_queen_stage(&bead_id, &agent_id, &db, None)
-        .await
-        .expect("red-queen stage should complete with failure output");
+    let output = execute_red_queen_stage(
+        &bead_id,
+        &agent_id,
+        &db,
+        &ExecutionBackend::Local,
+        None,
+        None,
+    )
+    .await
+    .expect("red-queen stage should complete with failure output");
 
     assert!(!output.success);
     assert_eq!(
@@ -4,6 +4,7 @@ use crate::gate_cache::GateExecutionCache;
 use crate::skill_execution::SkillOutput;
 
 use super::gate_stage::run_moon_task;
+use super::ExecutionBackend;
 
 #[tokio::test]
 #[ignore = "requires moon binary not available in test environment"]
@@ -11,7 +12,13 @@ async fn given_nonexistent_command_when_running_moon_task_then_io_error_is_handl
     let temp_dir = tempfile::TempDir::new().expect("temp dir");
     let cache = GateExecutionCache::new(temp_dir.path()).expect("cache");
 
-    let result = run_moon_task("/nonexistent/moon/binary/that/does/not/exist", Some(&cache)).await;
+    let result = run_moon_task(
+        "/nonexistent/moon/binary/that/does/not/exist",
+        &ExecutionBackend::Local,
+        Some(&cache),
+        None,
+    )
+    .await;
 
     assert!(result.is_err());
     let error = result.unwrap_err();
@@ -24,9 +31,14 @@ async fn given_failing_task_when_running_moon_task_then_failure_output_is_return
     let temp_dir = tempfile::TempDir::new().expect("temp dir");
     let cache = GateExecutionCache::new(temp_dir.path()).expect("cache");
 
-    let output = run_moon_task(":fake-failing-task", Some(&cache))
-        .await
-        .expect("command should complete with failure");
+    let output = run_moon_task(
+        ":fake-failing-task",
+        &ExecutionBackend::Local,
+        Some(&cache),
+        None,
+    )
+    .await
+    .expect("command should complete with failure");
 
     assert!(!output.success);
     assert_eq!(output.exit_code, Some(1));
@@ -76,7 +88,7 @@ async fn given_cache_hit_when_running_moon_task_then_cached_result_is_returned()
         .await
         .expect("initial put");
 
-    let result = run_moon_task("failing-task", Some(&cache)).await;
+    let result = run_moon_task("failing-task", &ExecutionBackend::Local, Some(&cache), None).await;
 
     assert!(result.is_ok());
     let output = result.unwrap();
@@ -85,7 +97,7 @@ async fn given_cache_hit_when_running_moon_task_then_cached_result_is_returned()
 
 #[tokio::test]
 async fn given_no_cache_when_running_moon_task_then_actual_command_runs() {
-    let result = run_moon_task(":quick", None).await;
+    let result = run_moon_task(":quick", &ExecutionBackend::Local, None, None).await;
 
     match result {
         Ok(output) => {
@@ -101,7 +113,7 @@ async fn given_echo_task_when_running_moon_task_then_result_is_returned() {
     let temp_dir = tempfile::TempDir::new().expect("temp dir");
     let cache = GateExecutionCache::new(temp_dir.path()).expect("cache");
 
-    let result = run_moon_task(":echo-test", Some(&cache)).await;
+    let result = run_moon_task(":echo-test", &ExecutionBackend::Local, Some(&cache), None).await;
 
     match result {
         Ok(output) => {
@@ -166,7 +178,7 @@ async fn given_none_cache_when_running_moon_task_then_execution_occurs() {
         .await
         .expect("put");
 
-    let output = run_moon_task(":cached", None)
+    let output = run_moon_task(":cached", &ExecutionBackend::Local, None, None)
         .await
         .expect("should execute without cache");
 
@@ -1,43 +1,22 @@
 #![allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
 
+#[cfg(feature = "testsupport")]
 use crate::gate_cache::GateExecutionCache;
+#[cfg(feature = "testsupport")]
+use crate::testsupport::isolated_db;
+#[cfg(feature = "testsupport")]
 use crate::types::{ArtifactType, BeadId, RepoId};
+#[cfg(feature = "testsupport")]
 use crate::{AgentId, SwarmDb};
-use sqlx::postgres::PgPoolOptions;
+#[cfg(feature = "testsupport")]
 use sqlx::PgPool;
 
+#[cfg(feature = "testsupport")]
 use super::gate_stage::execute_red_queen_stage;
+#[cfg(feature = "testsupport")]
+use super::ExecutionBackend;
 
-fn test_database_url() -> Option<String> {
-    std::env::var("SWARM_TEST_DATABASE_URL")
-        .ok()
-        .or_else(|| std::env::var("DATABASE_URL").ok())
-        .filter(|url| !url.trim().is_empty())
-        .filter(|url| url.starts_with("postgres://") || url.starts_with("postgresql://"))
-}
-
-async fn test_pool_or_skip() -> Option<PgPool> {
-    let run_integration = std::env::var("SWARM_RUN_DB_INTEGRATION")
-        .ok()
-        .is_some_and(|value| value == "1" || value.eq_ignore_ascii_case("true"));
-    if !run_integration {
-        return None;
-    }
-
-    let url = test_database_url()?;
-    PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&url)
-        .await
-        .ok()
-}
-
-async fn setup_schema(db: &SwarmDb) {
-    db.initialize_schema_from_sql(include_str!("../../schema.sql"))
-        .await
-        .expect("Failed to initialize schema");
-}
-
+#[cfg(feature = "testsupport")]
 async fn insert_bead_claim(pool: &PgPool, bead_id: &BeadId, agent_id: &AgentId) {
     sqlx::query(
         "INSERT INTO bead_claims (repo_id, bead_id, claimed_by, status) VALUES ($1, $2, $3, 'in_progress')",
@@ -50,6 +29,7 @@ async fn insert_bead_claim(pool: &PgPool, bead_id: &BeadId, agent_id: &AgentId)
     .expect("Failed to insert bead claim");
 }
 
+#[cfg(feature = "testsupport")]
 async fn insert_started_stage_history(pool: &PgPool, bead_id: &BeadId, agent_id: &AgentId) -> i64 {
     sqlx::query_scalar::<_, i64>(
         "INSERT INTO stage_history (repo_id, agent_id, bead_id, stage, attempt_number, status, started_at)\n             VALUES ($1, $2, $3, 'implement', 1, 'started', NOW())\n             RETURNING id",
@@ -62,6 +42,7 @@ async fn insert_started_stage_history(pool: &PgPool, bead_id: &BeadId, agent_id:
     .expect("Failed to insert stage history")
 }
 
+#[cfg(feature = "testsupport")]
 async fn seed_artifact(
     db: &SwarmDb,
     pool: &PgPool,
@@ -77,17 +58,14 @@ async fn seed_artifact(
         .expect("Failed to seed stage artifact");
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_cached_success_gate_and_test_results_artifact_when_executing_red_queen_stage_then_quality_report_is_emitted(
 ) {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("rq-success-cache");
     let agent_id = AgentId::new(RepoId::new("local"), 141);
-    setup_schema(&db).await;
     seed_artifact(
         &db,
         &pool,
@@ -111,26 +89,30 @@ async fn given_cached_success_gate_and_test_results_artifact_when_executing_red_
         .await
         .expect("cache write");
 
-    let output = execute_red_queen_stage(&bead_id, &agent_id, &db, Some(&cache))
-        .await
-        .expect("red-queen stage should run from cache");
+    let output = execute_red_queen_stage(
+        &bead_id,
+        &agent_id,
+        &db,
+        &ExecutionBackend::Local,
+        Some(&cache),
+        None,
+    )
+    .await
+    .expect("red-queen stage should run from cache");
 
     assert!(output.success);
     assert!(output.artifacts.contains_key("quality_gate_report"));
     assert!(output.adversarial_report.is_none());
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_cached_failed_gate_and_test_results_artifact_when_executing_red_queen_stage_then_adversarial_report_is_emitted(
 ) {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("rq-failed-cache");
     let agent_id = AgentId::new(RepoId::new("local"), 14);
-    setup_schema(&db).await;
     seed_artifact(
         &db,
         &pool,
@@ -154,9 +136,16 @@ async fn given_cached_failed_gate_and_test_results_artifact_when_executing_red_q
         .await
         .expect("cache write");
 
-    let output = execute_red_queen_stage(&bead_id, &agent_id, &db, Some(&cache))
-        .await
-        .expect("red-queen stage should run from cache");
+    let output = execute_red_queen_stage(
+        &bead_id,
+        &agent_id,
+        &db,
+        &ExecutionBackend::Local,
+        Some(&cache),
+        None,
+    )
+    .await
+    .expect("red-queen stage should run from cache");
 
     assert!(!output.success);
     assert_eq!(
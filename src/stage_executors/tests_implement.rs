@@ -1,42 +1,18 @@
 #![allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
 
+#[cfg(feature = "testsupport")]
+use crate::testsupport::isolated_db;
+#[cfg(feature = "testsupport")]
 use crate::types::{ArtifactType, BeadId, RepoId};
-use crate::{AgentId, SwarmDb};
-use sqlx::postgres::PgPoolOptions;
+#[cfg(feature = "testsupport")]
+use crate::AgentId;
+#[cfg(feature = "testsupport")]
 use sqlx::PgPool;
 
+#[cfg(feature = "testsupport")]
 use super::implement_stage::execute_implement_stage;
 
-fn test_database_url() -> Option<String> {
-    std::env::var("SWARM_TEST_DATABASE_URL")
-        .ok()
-        .or_else(|| std::env::var("DATABASE_URL").ok())
-        .filter(|url| !url.trim().is_empty())
-        .filter(|url| url.starts_with("postgres://") || url.starts_with("postgresql://"))
-}
-
-async fn test_pool_or_skip() -> Option<PgPool> {
-    let run_integration = std::env::var("SWARM_RUN_DB_INTEGRATION")
-        .ok()
-        .is_some_and(|value| value == "1" || value.eq_ignore_ascii_case("true"));
-    if !run_integration {
-        return None;
-    }
-
-    let url = test_database_url()?;
-    PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&url)
-        .await
-        .ok()
-}
-
-async fn setup_schema(db: &SwarmDb) {
-    db.initialize_schema_from_sql(include_str!("../../schema.sql"))
-        .await
-        .expect("Failed to initialize schema");
-}
-
+#[cfg(feature = "testsupport")]
 async fn insert_started_stage_history(pool: &PgPool, bead_id: &BeadId, agent_id: &AgentId) -> i64 {
     sqlx::query_scalar::<_, i64>(
         "INSERT INTO stage_history (repo_id, agent_id, bead_id, stage, attempt_number, status, started_at)\n             VALUES ($1, $2, $3, 'implement', 1, 'started', NOW())\n             RETURNING id",
@@ -49,6 +25,7 @@ async fn insert_started_stage_history(pool: &PgPool, bead_id: &BeadId, agent_id:
     .expect("Failed to insert stage history")
 }
 
+#[cfg(feature = "testsupport")]
 async fn insert_bead_claim(pool: &PgPool, bead_id: &BeadId, agent_id: &AgentId) {
     sqlx::query(
         "INSERT INTO bead_claims (repo_id, bead_id, claimed_by, status) VALUES ($1, $2, $3, 'in_progress')",
@@ -61,16 +38,13 @@ async fn insert_bead_claim(pool: &PgPool, bead_id: &BeadId, agent_id: &AgentId)
     .expect("Failed to insert bead claim");
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_first_implement_attempt_when_contract_exists_then_only_contract_context_is_loaded() {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("test-bead-1");
     let agent_id = AgentId::new(RepoId::new("local"), 1);
-    setup_schema(&db).await;
     insert_bead_claim(&pool, &bead_id, &agent_id).await;
 
     let stage_history_id = insert_started_stage_history(&pool, &bead_id, &agent_id).await;
@@ -83,7 +57,7 @@ async fn given_first_implement_attempt_when_contract_exists_then_only_contract_c
     .await
     .expect("Failed to store contract");
 
-    let result = execute_implement_stage(&bead_id, &agent_id, &db)
+    let result = execute_implement_stage(&bead_id, &agent_id, &db, None)
         .await
         .expect("execute_implement_stage should succeed");
 
@@ -99,22 +73,22 @@ async fn given_first_implement_attempt_when_contract_exists_then_only_contract_c
     assert!(!implementation.contains("## Test Output"));
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_retry_implement_attempt_when_retry_artifacts_exist_then_retry_context_is_loaded() {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("test-bead-2");
     let agent_id = AgentId::new(RepoId::new("local"), 1);
-    setup_schema(&db).await;
     insert_bead_claim(&pool, &bead_id, &agent_id).await;
 
     sqlx::query(
-        "INSERT INTO agent_state (agent_id, status, implementation_attempt) VALUES ($1, 'working', 1)",
+        "INSERT INTO agent_state (repo_id, agent_id, bead_id, current_stage, status, implementation_attempt)
+         VALUES ($1, $2, $3, 'implement', 'working', 1)",
     )
+    .bind(agent_id.repo_id().value())
     .bind(agent_id.number().cast_signed())
+    .bind(bead_id.value())
     .execute(&pool)
     .await
     .expect("Failed to seed implementation attempt");
@@ -147,7 +121,7 @@ async fn given_retry_implement_attempt_when_retry_artifacts_exist_then_retry_con
     .await
     .expect("Failed to store test output");
 
-    let result = execute_implement_stage(&bead_id, &agent_id, &db)
+    let result = execute_implement_stage(&bead_id, &agent_id, &db, None)
         .await
         .expect("execute_implement_stage should succeed");
 
@@ -164,19 +138,16 @@ async fn given_retry_implement_attempt_when_retry_artifacts_exist_then_retry_con
     assert!(implementation.contains("## Test Output\ntest failure output"));
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_first_implement_attempt_when_contract_missing_then_failure_output_is_returned() {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("test-bead-3");
     let agent_id = AgentId::new(RepoId::new("local"), 1);
-    setup_schema(&db).await;
     insert_bead_claim(&pool, &bead_id, &agent_id).await;
 
-    let result = execute_implement_stage(&bead_id, &agent_id, &db)
+    let result = execute_implement_stage(&bead_id, &agent_id, &db, None)
         .await
         .expect("execute_implement_stage should return error");
 
@@ -184,22 +155,22 @@ async fn given_first_implement_attempt_when_contract_missing_then_failure_output
     assert!(result.feedback.contains("contract"));
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_retry_attempt_when_retry_packet_missing_then_failure_output_returned() {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("test-bead-retry-missing");
     let agent_id = AgentId::new(RepoId::new("local"), 1);
-    setup_schema(&db).await;
     insert_bead_claim(&pool, &bead_id, &agent_id).await;
 
     sqlx::query(
-        "INSERT INTO agent_state (agent_id, status, implementation_attempt) VALUES ($1, 'working', 1)",
+        "INSERT INTO agent_state (repo_id, agent_id, bead_id, current_stage, status, implementation_attempt)
+         VALUES ($1, $2, $3, 'implement', 'working', 1)",
     )
+    .bind(agent_id.repo_id().value())
     .bind(agent_id.number().cast_signed())
+    .bind(bead_id.value())
     .execute(&pool)
     .await
     .expect("Failed to seed implementation attempt");
@@ -214,7 +185,7 @@ async fn given_retry_attempt_when_retry_packet_missing_then_failure_output_retur
     .await
     .expect("Failed to store contract");
 
-    let result = execute_implement_stage(&bead_id, &agent_id, &db)
+    let result = execute_implement_stage(&bead_id, &agent_id, &db, None)
         .await
         .expect("execute_implement_stage should return error");
 
@@ -222,16 +193,13 @@ async fn given_retry_attempt_when_retry_packet_missing_then_failure_output_retur
     assert!(result.feedback.contains("retry packet"));
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_agent_state_missing_when_executing_then_defaults_to_zero_attempts() {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("test-bead-no-agent-state");
     let agent_id = AgentId::new(RepoId::new("local"), 1);
-    setup_schema(&db).await;
     insert_bead_claim(&pool, &bead_id, &agent_id).await;
 
     let stage_history_id = insert_started_stage_history(&pool, &bead_id, &agent_id).await;
@@ -244,7 +212,7 @@ async fn given_agent_state_missing_when_executing_then_defaults_to_zero_attempts
     .await
     .expect("Failed to store contract");
 
-    let result = execute_implement_stage(&bead_id, &agent_id, &db)
+    let result = execute_implement_stage(&bead_id, &agent_id, &db, None)
         .await
         .expect("execute_implement_stage should succeed");
 
@@ -253,16 +221,13 @@ async fn given_agent_state_missing_when_executing_then_defaults_to_zero_attempts
     assert!(result.implementation_code.is_some());
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_all_optional_artifacts_missing_when_executing_then_only_contract_in_context() {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("test-bead-only-contract");
     let agent_id = AgentId::new(RepoId::new("local"), 1);
-    setup_schema(&db).await;
     insert_bead_claim(&pool, &bead_id, &agent_id).await;
 
     let stage_history_id = insert_started_stage_history(&pool, &bead_id, &agent_id).await;
@@ -275,7 +240,7 @@ async fn given_all_optional_artifacts_missing_when_executing_then_only_contract_
     .await
     .expect("Failed to store contract");
 
-    let result = execute_implement_stage(&bead_id, &agent_id, &db)
+    let result = execute_implement_stage(&bead_id, &agent_id, &db, None)
         .await
         .expect("execute_implement_stage should succeed");
 
@@ -290,16 +255,13 @@ async fn given_all_optional_artifacts_missing_when_executing_then_only_contract_
     assert!(!implementation.contains("## Test Output"));
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_mixed_optional_artifacts_when_executing_then_correct_sections_included() {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("test-bead-mixed-optionals");
     let agent_id = AgentId::new(RepoId::new("local"), 1);
-    setup_schema(&db).await;
     insert_bead_claim(&pool, &bead_id, &agent_id).await;
 
     let stage_history_id = insert_started_stage_history(&pool, &bead_id, &agent_id).await;
@@ -328,7 +290,7 @@ async fn given_mixed_optional_artifacts_when_executing_then_correct_sections_inc
     .await
     .expect("Failed to store test output");
 
-    let result = execute_implement_stage(&bead_id, &agent_id, &db)
+    let result = execute_implement_stage(&bead_id, &agent_id, &db, None)
         .await
         .expect("execute_implement_stage should succeed");
 
@@ -343,16 +305,13 @@ async fn given_mixed_optional_artifacts_when_executing_then_correct_sections_inc
     assert!(!implementation.contains("## Test Results"));
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_empty_contract_content_when_executing_then_empty_context_passed_to_scaffold() {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("test-bead-empty-contract");
     let agent_id = AgentId::new(RepoId::new("local"), 1);
-    setup_schema(&db).await;
     insert_bead_claim(&pool, &bead_id, &agent_id).await;
 
     let stage_history_id = insert_started_stage_history(&pool, &bead_id, &agent_id).await;
@@ -360,7 +319,7 @@ async fn given_empty_contract_content_when_executing_then_empty_context_passed_t
         .await
         .expect("Failed to store empty contract");
 
-    let result = execute_implement_stage(&bead_id, &agent_id, &db)
+    let result = execute_implement_stage(&bead_id, &agent_id, &db, None)
         .await
         .expect("execute_implement_stage should succeed");
 
@@ -368,16 +327,13 @@ async fn given_empty_contract_content_when_executing_then_empty_context_passed_t
     assert!(result.implementation_code.is_some());
 }
 
+#[cfg(feature = "testsupport")]
 #[tokio::test]
 async fn given_all_sections_empty_when_executing_then_empty_context_to_scaffold() {
-    let Some(pool) = test_pool_or_skip().await else {
-        eprintln!("Skipping DB-dependent test: no reachable test database");
-        return;
-    };
-    let db = SwarmDb::new_with_pool(pool.clone());
+    let db = isolated_db().await.expect("Failed to create test database");
+    let pool = db.pool().clone();
     let bead_id = BeadId::new("test-bead-all-empty");
     let agent_id = AgentId::new(RepoId::new("local"), 1);
-    setup_schema(&db).await;
     insert_bead_claim(&pool, &bead_id, &agent_id).await;
 
     let stage_history_id = insert_started_stage_history(&pool, &bead_id, &agent_id).await;
@@ -385,7 +341,7 @@ async fn given_all_sections_empty_when_executing_then_empty_context_to_scaffold(
         .await
         .expect("Failed to store empty contract");
 
-    let result = execute_implement_stage(&bead_id, &agent_id, &db)
+    let result = execute_implement_stage(&bead_id, &agent_id, &db, None)
         .await
         .expect("execute_implement_stage should succeed");
 
@@ -8,7 +8,12 @@ use crate::AgentId;
 use super::contract_stage::execute_rust_contract_stage;
 use super::gate_stage::run_moon_task;
 use super::implement_stage::{append_section, format_retry_packet};
-use super::output_mapping::{error_output, failure_output, output_to_stage_result, success_output};
+use super::output_capture::capture_output;
+use super::output_mapping::{
+    error_output, failure_output, output_tail, output_to_stage_result, success_output,
+    MAX_LOGGED_OUTPUT_BYTES,
+};
+use super::ExecutionBackend;
 
 #[test]
 fn given_failed_output_when_feedback_present_then_stage_result_uses_feedback() {
@@ -170,10 +175,69 @@ async fn given_cached_gate_result_when_running_moon_task_then_cached_output_is_r
         .await
         .expect("cache write");
 
-    let output = run_moon_task(":quick", Some(&cache))
+    let output = run_moon_task(":quick", &ExecutionBackend::Local, Some(&cache), None)
         .await
         .expect("cached command output");
 
     assert!(output.success);
     assert_eq!(output.full_log, "cached stdout");
 }
+
+#[test]
+fn given_long_multibyte_output_when_cut_for_logging_then_tail_is_kept_on_char_boundary() {
+    let output = format!(
+        "{}{}",
+        "é".repeat(MAX_LOGGED_OUTPUT_BYTES),
+        "error: tests failed"
+    );
+
+    let tail = output_tail(&output);
+
+    assert!(tail.len() <= MAX_LOGGED_OUTPUT_BYTES);
+    assert!(tail.ends_with("error: tests failed"));
+    assert_eq!(output_tail("short"), "short");
+}
+
+/// Whether `pid` is alive; a zombie no one has reaped yet counts as gone.
+fn is_running(pid: &str) -> bool {
+    std::fs::read_to_string(format!("/proc/{pid}/stat")).is_ok_and(|stat| {
+        stat.rsplit(')')
+            .next()
+            .is_some_and(|rest| !rest.trim_start().starts_with('Z'))
+    })
+}
+
+#[tokio::test]
+async fn given_stage_dropped_mid_run_when_command_left_work_behind_then_cleanup_stops_it() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    let pid_file = dir.path().join("detached.pid");
+    // Like a container runtime client or ssh: the work runs in a process the
+    // kill on drop does not reach.
+    let mut command = tokio::process::Command::new("sh");
+    command
+        .arg("-c")
+        .arg(format!("sleep 30 & echo $! > {}; wait", pid_file.display()))
+        .kill_on_drop(true);
+    let mut cleanup = std::process::Command::new("sh");
+    cleanup
+        .arg("-c")
+        .arg(format!("kill $(cat {})", pid_file.display()));
+
+    let dropped = tokio::time::timeout(
+        std::time::Duration::from_millis(500),
+        capture_output(command, Some(cleanup), None),
+    )
+    .await;
+    let pid = std::fs::read_to_string(&pid_file).expect("detached pid");
+    let pid = pid.trim();
+
+    assert!(dropped.is_err(), "the command should still be running");
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while is_running(pid) && std::time::Instant::now() < deadline {
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    assert!(
+        !is_running(pid),
+        "detached process {pid} outlived the stage"
+    );
+}
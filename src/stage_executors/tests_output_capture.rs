@@ -0,0 +1,121 @@
+#![allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+
+use crate::stage_executor_content::DEFAULT_STAGE_OUTPUT_MEMORY_BYTES;
+use crate::testsupport::isolated_db;
+use crate::types::RepoId;
+use crate::{AgentId, SwarmDb};
+use tokio::process::Command;
+
+use super::output_capture::{capture_output, capture_text, StageOutputSpool};
+
+const OVER_CAP_BYTES: usize = DEFAULT_STAGE_OUTPUT_MEMORY_BYTES + 1024 * 1024;
+
+async fn started_stage_history(db: &SwarmDb, bead_id: &str) -> i64 {
+    let agent_id = AgentId::new(RepoId::new("local"), 1);
+    sqlx::query(
+        "INSERT INTO bead_claims (repo_id, bead_id, claimed_by, status) VALUES ($1, $2, $3, 'in_progress')",
+    )
+    .bind(agent_id.repo_id().value())
+    .bind(bead_id)
+    .bind(agent_id.number().cast_signed())
+    .execute(db.pool())
+    .await
+    .expect("Failed to insert bead claim");
+    sqlx::query_scalar::<_, i64>(
+        "INSERT INTO stage_history (repo_id, agent_id, bead_id, stage, attempt_number, status, started_at)
+         VALUES ($1, $2, $3, 'qa-enforcer', 1, 'started', NOW())
+         RETURNING id",
+    )
+    .bind(agent_id.repo_id().value())
+    .bind(agent_id.number().cast_signed())
+    .bind(bead_id)
+    .fetch_one(db.pool())
+    .await
+    .expect("Failed to insert stage history")
+}
+
+async fn spilled_bytes(db: &SwarmDb, stage_history_id: i64, stream: &str) -> Vec<u8> {
+    db.get_stage_output_chunks(stage_history_id, stream, None, i64::MAX)
+        .await
+        .expect("stage output chunks")
+        .into_iter()
+        .flat_map(|chunk| chunk.content)
+        .collect()
+}
+
+#[tokio::test]
+async fn given_command_output_past_default_cap_when_capturing_then_whole_stream_lands_in_stage_output_chunks(
+) {
+    let db = isolated_db().await.expect("Failed to create test database");
+    let stage_history_id = started_stage_history(&db, "spill-command").await;
+    let mut command = Command::new("sh");
+    command.args([
+        "-c",
+        &format!("head -c {OVER_CAP_BYTES} /dev/zero | tr '\\0' x; echo done >&2"),
+    ]);
+
+    let captured = capture_output(
+        command,
+        None,
+        Some(StageOutputSpool::new(&db, stage_history_id)),
+    )
+    .await
+    .expect("captured output");
+
+    assert!(captured.stdout.spilled);
+    assert!(!captured.stderr.spilled);
+    assert_eq!(captured.exit_code, Some(0));
+    assert_eq!(captured.stdout.total_bytes, OVER_CAP_BYTES as u64);
+    assert!(captured.stdout.text.len() < DEFAULT_STAGE_OUTPUT_MEMORY_BYTES + 64);
+    let stored = spilled_bytes(&db, stage_history_id, "stdout").await;
+    assert_eq!(stored.len(), OVER_CAP_BYTES);
+    assert!(stored.iter().all(|byte| *byte == b'x'));
+    assert!(spilled_bytes(&db, stage_history_id, "stderr")
+        .await
+        .is_empty());
+}
+
+#[tokio::test]
+async fn given_generated_output_past_default_cap_when_capturing_text_then_it_lands_in_stage_output_chunks(
+) {
+    let db = isolated_db().await.expect("Failed to create test database");
+    let stage_history_id = started_stage_history(&db, "spill-text").await;
+    let text = "y".repeat(OVER_CAP_BYTES);
+
+    let output = capture_text(
+        &text,
+        "stdout",
+        Some(StageOutputSpool::new(&db, stage_history_id)),
+    )
+    .await
+    .expect("captured text");
+
+    assert!(output.spilled);
+    assert!(output.text.starts_with('['));
+    assert_eq!(
+        spilled_bytes(&db, stage_history_id, "stdout").await,
+        text.into_bytes()
+    );
+}
+
+#[tokio::test]
+async fn given_output_under_default_cap_when_capturing_then_nothing_is_spilled() {
+    let db = isolated_db().await.expect("Failed to create test database");
+    let stage_history_id = started_stage_history(&db, "no-spill").await;
+    let mut command = Command::new("sh");
+    command.args(["-c", "echo small"]);
+
+    let captured = capture_output(
+        command,
+        None,
+        Some(StageOutputSpool::new(&db, stage_history_id)),
+    )
+    .await
+    .expect("captured output");
+
+    assert!(!captured.spilled());
+    assert_eq!(captured.stdout.text, "small\n");
+    assert!(spilled_bytes(&db, stage_history_id, "stdout")
+        .await
+        .is_empty());
+}
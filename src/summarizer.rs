@@ -0,0 +1,291 @@
+//! Short per-stage summaries for resume payloads.
+//!
+//! When a stage completes, its artifacts are condensed into one `summary`
+//! artifact on the stage run. With `SWARM_SUMMARIZER_COMMAND` set the command
+//! gets a [`StageSummaryInput`] as JSON on stdin and its stdout is the
+//! summary; otherwise, or when the command fails, [`builtin_summary`] keeps
+//! the outcome, the artifact list and the first lines of each non-transcript
+//! artifact. Resume contexts list these in place of the raw transcripts.
+
+use crate::types::{ArtifactType, StageArtifact, TRANSCRIPT_ARTIFACTS};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+pub const DEFAULT_SUMMARY_MAX_BYTES: usize = 2_048;
+pub const DEFAULT_SUMMARIZER_TIMEOUT_MS: u64 = 30_000;
+
+/// Lines kept from the start of each artifact by the built-in summary.
+const BUILTIN_HEAD_LINES: usize = 5;
+/// Longest line the built-in summary keeps before cutting it.
+const BUILTIN_LINE_BYTES: usize = 160;
+
+/// How stage summaries are produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageSummarizer {
+    /// Shell command run with `sh -c`; `None` uses [`builtin_summary`].
+    pub command: Option<String>,
+    pub timeout_ms: u64,
+    /// Summaries are cut to this many bytes, whoever wrote them.
+    pub max_bytes: usize,
+}
+
+impl Default for StageSummarizer {
+    fn default() -> Self {
+        Self {
+            command: None,
+            timeout_ms: DEFAULT_SUMMARIZER_TIMEOUT_MS,
+            max_bytes: DEFAULT_SUMMARY_MAX_BYTES,
+        }
+    }
+}
+
+/// What a summarizer command reads on stdin.
+#[derive(Debug, Clone, Serialize)]
+pub struct StageSummaryInput<'a> {
+    pub stage: &'a str,
+    pub attempt: u32,
+    pub status: &'a str,
+    pub message: &'a str,
+    pub artifacts: Vec<SummaryArtifact<'a>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryArtifact<'a> {
+    pub artifact_type: &'static str,
+    pub content: &'a str,
+}
+
+impl<'a> StageSummaryInput<'a> {
+    /// Input over a stage run's artifacts, leaving out earlier summaries.
+    #[must_use]
+    pub fn new(
+        stage: &'a str,
+        attempt: u32,
+        status: &'a str,
+        message: &'a str,
+        artifacts: &'a [StageArtifact],
+    ) -> Self {
+        Self {
+            stage,
+            attempt,
+            status,
+            message,
+            artifacts: artifacts
+                .iter()
+                .filter(|artifact| artifact.artifact_type != ArtifactType::Summary)
+                .map(|artifact| SummaryArtifact {
+                    artifact_type: artifact.artifact_type.as_str(),
+                    content: &artifact.content,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A summary and how it was made, for the artifact's metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageSummary {
+    pub text: String,
+    /// `command` or `builtin`.
+    pub summarizer: &'static str,
+    /// Why the command's output was not used, when it was configured.
+    pub fallback_reason: Option<String>,
+}
+
+impl StageSummarizer {
+    /// Summarizes with the command when one is set, falling back to the
+    /// built-in heuristics when it fails, times out or prints nothing.
+    pub async fn summarize(&self, input: &StageSummaryInput<'_>) -> StageSummary {
+        let fallback_reason = match self.command.as_deref() {
+            Some(command) => match self.run_command(command, input).await {
+                Ok(text) => {
+                    return StageSummary {
+                        text: clip(&text, self.max_bytes),
+                        summarizer: "command",
+                        fallback_reason: None,
+                    }
+                }
+                Err(reason) => Some(reason),
+            },
+            None => None,
+        };
+        StageSummary {
+            text: builtin_summary(input, self.max_bytes),
+            summarizer: "builtin",
+            fallback_reason,
+        }
+    }
+
+    async fn run_command(
+        &self,
+        command: &str,
+        input: &StageSummaryInput<'_>,
+    ) -> Result<String, String> {
+        let stdin = serde_json::to_vec(input).map_err(|e| format!("serialize input: {e}"))?;
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("failed to run: {e}"))?;
+        if let Some(mut pipe) = child.stdin.take() {
+            // A command that exits without reading stdin is not an error.
+            let _ = pipe.write_all(&stdin).await;
+        }
+        let output = tokio::time::timeout(
+            Duration::from_millis(self.timeout_ms),
+            child.wait_with_output(),
+        )
+        .await
+        .map_err(|_| format!("timed out after {} ms", self.timeout_ms))?
+        .map_err(|e| format!("failed to run: {e}"))?;
+        if !output.status.success() {
+            return Err(format!("exited with {}", output.status));
+        }
+        let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if text.is_empty() {
+            return Err("printed no summary".to_string());
+        }
+        Ok(text)
+    }
+}
+
+/// Outcome line, the stage's message, each artifact's type and size, then
+/// the first lines of every non-transcript artifact, cut to `max_bytes`.
+#[must_use]
+pub fn builtin_summary(input: &StageSummaryInput<'_>, max_bytes: usize) -> String {
+    let mut summary = format!(
+        "{} attempt {}: {}",
+        input.stage, input.attempt, input.status
+    );
+    if let Some(message) = first_line(input.message) {
+        let _ = write!(summary, "\n{message}");
+    }
+    if !input.artifacts.is_empty() {
+        let listed = input
+            .artifacts
+            .iter()
+            .map(|artifact| format!("{} ({} B)", artifact.artifact_type, artifact.content.len()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = write!(summary, "\nartifacts: {listed}");
+    }
+    for artifact in input.artifacts.iter().filter(|artifact| {
+        !TRANSCRIPT_ARTIFACTS
+            .iter()
+            .any(|kind| kind.as_str() == artifact.artifact_type)
+    }) {
+        let head = artifact
+            .content
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.trim().is_empty())
+            .take(BUILTIN_HEAD_LINES)
+            .map(|line| clip(line, BUILTIN_LINE_BYTES))
+            .collect::<Vec<_>>();
+        if !head.is_empty() {
+            let _ = write!(
+                summary,
+                "\n[{}]\n{}",
+                artifact.artifact_type,
+                head.join("\n")
+            );
+        }
+    }
+    clip(&summary, max_bytes)
+}
+
+fn first_line(text: &str) -> Option<String> {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| clip(line, BUILTIN_LINE_BYTES))
+}
+
+/// Cuts `text` to at most `max_bytes` on a char boundary, marking the cut.
+fn clip(text: &str, max_bytes: usize) -> String {
+    const MARK: &str = "…";
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let keep = max_bytes.saturating_sub(MARK.len());
+    let end = (0..=keep)
+        .rev()
+        .find(|index| text.is_char_boundary(*index))
+        .unwrap_or(0);
+    format!("{}{MARK}", &text[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{builtin_summary, StageSummarizer, StageSummaryInput, SummaryArtifact};
+
+    fn input(artifacts: Vec<SummaryArtifact<'_>>) -> StageSummaryInput<'_> {
+        StageSummaryInput {
+            stage: "qa-enforcer",
+            attempt: 2,
+            status: "failed",
+            message: "2 tests failed\nsee failure_details",
+            artifacts,
+        }
+    }
+
+    #[test]
+    fn given_stage_artifacts_when_summarizing_then_transcripts_are_listed_but_not_quoted() {
+        let log = "x".repeat(10_000);
+        let summary = builtin_summary(
+            &input(vec![
+                SummaryArtifact {
+                    artifact_type: "stage_log",
+                    content: &log,
+                },
+                SummaryArtifact {
+                    artifact_type: "failure_details",
+                    content: "tests::a panicked\n\ntests::b timed out\n",
+                },
+            ]),
+            2_048,
+        );
+
+        assert!(summary.starts_with("qa-enforcer attempt 2: failed\n2 tests failed\n"));
+        assert!(summary.contains("stage_log (10000 B), failure_details (38 B)"));
+        assert!(summary.contains("[failure_details]\ntests::a panicked\ntests::b timed out"));
+        assert!(!summary.contains("xxxx"));
+    }
+
+    #[test]
+    fn given_small_budget_when_summarizing_then_summary_is_cut_on_a_char_boundary() {
+        let notes = "é".repeat(400);
+        let summary = builtin_summary(
+            &input(vec![SummaryArtifact {
+                artifact_type: "implementation_notes",
+                content: &notes,
+            }]),
+            101,
+        );
+
+        assert!(summary.len() <= 101);
+        assert!(summary.ends_with('…'));
+    }
+
+    #[tokio::test]
+    async fn given_failing_command_when_summarizing_then_builtin_summary_is_used() {
+        let summarizer = StageSummarizer {
+            command: Some("exit 3".to_string()),
+            ..StageSummarizer::default()
+        };
+
+        let summary = summarizer.summarize(&input(Vec::new())).await;
+
+        assert_eq!(summary.summarizer, "builtin");
+        assert!(summary.fallback_reason.is_some());
+        assert!(summary.text.starts_with("qa-enforcer attempt 2: failed"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Ephemeral Postgres for database tests.
+//!
+//! Built only with the `testsupport` feature. [`isolated_db`] hands a test a
+//! [`TestDb`] in a Postgres schema of its own, built from the canonical
+//! coordinator schema, so tests run concurrently without seeing each other's
+//! rows. The server is a `postgres` container started through
+//! `testcontainers` on first use and shared by the rest of the test process,
+//! or the server at `SWARM_TEST_DATABASE_URL` when that is set. Dropping a
+//! [`TestDb`] drops its schema.
+//!
+//! ```ignore
+//! #[tokio::test]
+//! async fn claims_the_only_bead() -> swarm::Result<()> {
+//!     let db = swarm::testsupport::isolated_db().await?;
+//!     db.seed_idle_agents(1).await?;
+//!     // ...
+//!     Ok(())
+//! }
+//! ```
+
+use crate::db::SwarmDb;
+use crate::error::{Result, SwarmError};
+use sqlx::{Connection, Executor, PgConnection};
+use std::ops::Deref;
+use testcontainers_modules::postgres::Postgres;
+use testcontainers_modules::testcontainers::runners::AsyncRunner;
+use testcontainers_modules::testcontainers::ContainerAsync;
+use tokio::sync::OnceCell;
+
+/// Schema every test database is built from.
+pub const TEST_SCHEMA_SQL: &str = include_str!("../crates/swarm-coordinator/schema.sql");
+
+/// Server the tests of one process share. The container, when there is one,
+/// lives as long as the process and is reaped by `testcontainers` after it.
+struct TestServer {
+    url: String,
+    _container: Option<ContainerAsync<Postgres>>,
+}
+
+static SERVER: OnceCell<TestServer> = OnceCell::const_new();
+
+/// A database schema only the holding test touches.
+pub struct TestDb {
+    db: SwarmDb,
+    url: String,
+    schema: String,
+}
+
+impl TestDb {
+    #[must_use]
+    pub const fn db(&self) -> &SwarmDb {
+        &self.db
+    }
+
+    /// Connection string of the shared server. Pair it with [`Self::schema`]
+    /// (`SWARM_DB_SCHEMA`) to drive the `swarm` binary against this database.
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    #[must_use]
+    pub fn schema(&self) -> &str {
+        &self.schema
+    }
+}
+
+impl Deref for TestDb {
+    type Target = SwarmDb;
+
+    fn deref(&self) -> &SwarmDb {
+        &self.db
+    }
+}
+
+impl Drop for TestDb {
+    /// The test's runtime may already be shutting down, so the schema is
+    /// dropped over a fresh connection on a thread of its own.
+    fn drop(&mut self) {
+        let url = self.url.clone();
+        let statement = format!("DROP SCHEMA IF EXISTS \"{}\" CASCADE", self.schema);
+        let _ = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .ok()?;
+            runtime.block_on(async {
+                let mut connection = PgConnection::connect(&url).await.ok()?;
+                connection.execute(statement.as_str()).await.ok()?;
+                connection.close().await.ok()
+            })
+        })
+        .join();
+    }
+}
+
+/// Creates a fresh schema on the shared server and applies the coordinator
+/// schema inside it.
+///
+/// # Errors
+/// Returns an error if the container cannot be started (no Docker daemon,
+/// image pull failed) or the schema cannot be applied.
+pub async fn isolated_db() -> Result<TestDb> {
+    let url = server().await?.url.clone();
+    let schema = format!("swarm_test_{}", uuid::Uuid::new_v4().simple());
+    let db = SwarmDb::new_in_schema(&url, &schema, Some(30_000)).await?;
+    let test_db = TestDb { db, url, schema };
+    test_db.initialize_schema_from_sql(TEST_SCHEMA_SQL).await?;
+    Ok(test_db)
+}
+
+async fn server() -> Result<&'static TestServer> {
+    SERVER.get_or_try_init(start_server).await
+}
+
+/// Starts the server and installs `pgcrypto` in `public`, where every test
+/// schema resolves it. Left to the first test schema, the extension would
+/// be dropped along with it.
+async fn start_server() -> Result<TestServer> {
+    let server = if let Some(url) = std::env::var("SWARM_TEST_DATABASE_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+    {
+        TestServer {
+            url,
+            _container: None,
+        }
+    } else {
+        let container = Postgres::default().start().await.map_err(container_error)?;
+        let host = container.get_host().await.map_err(container_error)?;
+        let port = container
+            .get_host_port_ipv4(5432)
+            .await
+            .map_err(container_error)?;
+        TestServer {
+            url: format!("postgres://postgres:postgres@{host}:{port}/postgres"),
+            _container: Some(container),
+        }
+    };
+    let db = SwarmDb::new_with_timeout(&server.url, Some(30_000)).await?;
+    sqlx::query("CREATE EXTENSION IF NOT EXISTS pgcrypto")
+        .execute(db.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(format!("Failed to install pgcrypto: {e}")))?;
+    Ok(server)
+}
+
+fn container_error(error: impl std::fmt::Display) -> SwarmError {
+    SwarmError::DatabaseError(format!("Failed to start test Postgres container: {error}"))
+}
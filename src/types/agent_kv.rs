@@ -0,0 +1,24 @@
+//! Scratch state an agent keeps for one bead between stages, such as the
+//! approach it chose or partial notes. Entries are deleted when the bead is
+//! finalized or cancelled.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Longest key, in bytes.
+pub const MAX_KV_KEY_BYTES: usize = 128;
+/// Largest value, in bytes of compact JSON.
+pub const MAX_KV_VALUE_BYTES: usize = 64 * 1024;
+/// Most keys one agent can hold for one bead.
+pub const MAX_KV_KEYS: i64 = 256;
+
+/// One stored value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentKvEntry {
+    pub agent_id: u32,
+    pub bead_id: String,
+    pub key: String,
+    pub value: Value,
+    pub updated_at: DateTime<Utc>,
+}
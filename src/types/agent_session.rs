@@ -0,0 +1,63 @@
+//! Which process is acting as an agent. An agent id is a slot that
+//! successive processes fill; a session records one of them, so claims and
+//! events can be traced back to the host and pid that made them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Why a session stopped being the agent's active one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionEndReason {
+    /// `session end` was called.
+    Ended,
+    /// Another `session start` for the same agent took over, usually after
+    /// the previous process died without ending its session.
+    Replaced,
+}
+
+impl SessionEndReason {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ended => "ended",
+            Self::Replaced => "replaced",
+        }
+    }
+}
+
+impl TryFrom<&str> for SessionEndReason {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "ended" => Ok(Self::Ended),
+            "replaced" => Ok(Self::Replaced),
+            _ => Err(format!("Unknown session end reason: {value}")),
+        }
+    }
+}
+
+/// One process attached as an agent. `ended_at` is set once it detaches;
+/// an agent has at most one session that has not ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentSession {
+    pub id: i64,
+    pub agent_id: u32,
+    pub host: String,
+    pub pid: u32,
+    /// Version the process reported, e.g. its build or the swarm release.
+    pub version: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub end_reason: Option<SessionEndReason>,
+}
+
+impl AgentSession {
+    /// Whether the process is still attached.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.ended_at.is_none()
+    }
+}
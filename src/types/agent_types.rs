@@ -21,6 +21,14 @@ pub enum AgentStatus {
 }
 
 impl AgentStatus {
+    pub const ALL: [Self; 5] = [
+        Self::Idle,
+        Self::Working,
+        Self::Waiting,
+        Self::Error,
+        Self::Done,
+    ];
+
     #[must_use]
     pub const fn as_str(&self) -> &'static str {
         match self {
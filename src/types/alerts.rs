@@ -0,0 +1,406 @@
+//! Swarm-wide alerts: backlog growing faster than agents drain it, stages
+//! failing too often, or every agent stuck waiting.
+//!
+//! A breached rule is `pending` until it has held for the rule's duration,
+//! then `firing` until the condition clears and it is resolved.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Which rule an alert belongs to; a repo has at most one open alert per kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    BacklogDepth,
+    ErrorRate,
+    AllAgentsWaiting,
+}
+
+impl AlertKind {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::BacklogDepth => "backlog_depth",
+            Self::ErrorRate => "error_rate",
+            Self::AllAgentsWaiting => "all_agents_waiting",
+        }
+    }
+}
+
+impl TryFrom<&str> for AlertKind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "backlog_depth" => Ok(Self::BacklogDepth),
+            "error_rate" => Ok(Self::ErrorRate),
+            "all_agents_waiting" => Ok(Self::AllAgentsWaiting),
+            _ => Err(format!("Unknown alert kind: {value}")),
+        }
+    }
+}
+
+/// Fires when more than `max_pending` beads stay pending for `for_mins`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BacklogDepthRule {
+    pub max_pending: u32,
+    pub for_mins: u32,
+}
+
+/// Fires when more than `max_percent` of the stage runs finished in the last
+/// `window_mins` failed, once the window has `min_runs` runs.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ErrorRateRule {
+    pub max_percent: f64,
+    pub window_mins: u32,
+    pub min_runs: u32,
+}
+
+/// Fires when every registered agent has been `waiting` for `for_mins`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AllAgentsWaitingRule {
+    pub for_mins: u32,
+}
+
+/// Alert rules, read from `SWARM_ALERT_RULES`. A rule set to `null` is off;
+/// an omitted rule keeps its default.
+///
+/// ```json
+/// {"backlog_depth": {"max_pending": 50, "for_mins": 10},
+///  "error_rate": {"max_percent": 25.0, "window_mins": 15, "min_runs": 4},
+///  "all_agents_waiting": null,
+///  "webhook_url": "https://hooks.example.com/swarm"}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertRules {
+    pub backlog_depth: Option<BacklogDepthRule>,
+    pub error_rate: Option<ErrorRateRule>,
+    pub all_agents_waiting: Option<AllAgentsWaitingRule>,
+    /// Receives a JSON POST each time an alert fires or resolves.
+    pub webhook_url: Option<String>,
+}
+
+impl Default for AlertRules {
+    fn default() -> Self {
+        Self {
+            backlog_depth: Some(BacklogDepthRule {
+                max_pending: 50,
+                for_mins: 10,
+            }),
+            error_rate: Some(ErrorRateRule {
+                max_percent: 25.0,
+                window_mins: 15,
+                min_runs: 4,
+            }),
+            all_agents_waiting: Some(AllAgentsWaitingRule { for_mins: 5 }),
+            webhook_url: None,
+        }
+    }
+}
+
+/// Swarm counters the rules are evaluated against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertObservation {
+    pub pending_beads: u32,
+    /// Stage runs finished inside the error-rate window.
+    pub stage_runs: u32,
+    pub stage_failures: u32,
+    pub total_agents: u32,
+    pub waiting_agents: u32,
+}
+
+impl AlertObservation {
+    /// Failed share of the window's stage runs, as a percentage.
+    #[must_use]
+    pub fn error_percent(&self) -> f64 {
+        if self.stage_runs == 0 {
+            return 0.0;
+        }
+        f64::from(self.stage_failures) * 100.0 / f64::from(self.stage_runs)
+    }
+}
+
+/// A rule whose condition holds right now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertBreach {
+    pub kind: AlertKind,
+    pub detail: String,
+    /// Minutes the condition must hold before the alert fires.
+    pub for_mins: u32,
+}
+
+impl AlertRules {
+    /// Rejects limits no observation could ever breach or always breaches,
+    /// and webhooks that are not HTTP(S).
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(rule) = self.error_rate {
+            if !(0.0..100.0).contains(&rule.max_percent) {
+                return Err(format!(
+                    "error_rate.max_percent must be in [0, 100), got {}",
+                    rule.max_percent
+                ));
+            }
+            if rule.window_mins == 0 {
+                return Err("error_rate.window_mins must be greater than 0".to_string());
+            }
+        }
+        if let Some(url) = self
+            .webhook_url
+            .as_deref()
+            .filter(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+        {
+            return Err(format!("webhook_url must be http(s), got {url}"));
+        }
+        Ok(())
+    }
+
+    /// Lookback window for the error-rate counters; zero when the rule is off.
+    #[must_use]
+    pub fn error_window_mins(&self) -> u32 {
+        self.error_rate.map_or(0, |rule| rule.window_mins)
+    }
+
+    /// Every enabled rule whose condition holds for `observation`.
+    #[must_use]
+    pub fn breaches(&self, observation: &AlertObservation) -> Vec<AlertBreach> {
+        let backlog = self
+            .backlog_depth
+            .filter(|rule| observation.pending_beads > rule.max_pending)
+            .map(|rule| AlertBreach {
+                kind: AlertKind::BacklogDepth,
+                detail: format!(
+                    "{} beads pending (limit {})",
+                    observation.pending_beads, rule.max_pending
+                ),
+                for_mins: rule.for_mins,
+            });
+        let error_rate = self
+            .error_rate
+            .filter(|rule| observation.stage_runs >= rule.min_runs)
+            .filter(|rule| observation.error_percent() > rule.max_percent)
+            .map(|rule| AlertBreach {
+                kind: AlertKind::ErrorRate,
+                detail: format!(
+                    "{:.1}% of {} stage runs failed in {} min (limit {:.1}%)",
+                    observation.error_percent(),
+                    observation.stage_runs,
+                    rule.window_mins,
+                    rule.max_percent
+                ),
+                for_mins: 0,
+            });
+        let waiting = self
+            .all_agents_waiting
+            .filter(|_| observation.total_agents > 0)
+            .filter(|_| observation.waiting_agents == observation.total_agents)
+            .map(|rule| AlertBreach {
+                kind: AlertKind::AllAgentsWaiting,
+                detail: format!("all {} agents waiting", observation.total_agents),
+                for_mins: rule.for_mins,
+            });
+        [backlog, error_rate, waiting]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertStatus {
+    /// Breached, but not yet for the rule's duration.
+    Pending,
+    Firing,
+    Resolved,
+}
+
+impl AlertStatus {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Firing => "firing",
+            Self::Resolved => "resolved",
+        }
+    }
+}
+
+impl TryFrom<&str> for AlertStatus {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "pending" => Ok(Self::Pending),
+            "firing" => Ok(Self::Firing),
+            "resolved" => Ok(Self::Resolved),
+            _ => Err(format!("Unknown alert status: {value}")),
+        }
+    }
+}
+
+/// One breach of one rule, from first breach to resolution.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwarmAlert {
+    pub kind: AlertKind,
+    pub status: AlertStatus,
+    /// Latest description of the breach.
+    pub detail: String,
+    pub breached_at: DateTime<Utc>,
+    pub fired_at: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// What to do with one rule's alert after an evaluation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlertTransition {
+    /// Nothing changes.
+    Hold,
+    /// The condition just started holding; open a pending alert.
+    Open(AlertBreach),
+    /// The condition has held long enough; the pending alert fires.
+    Fire(AlertBreach),
+    /// The condition cleared; the open alert resolves.
+    Resolve,
+}
+
+/// Next step for one rule, given its open alert and its current breach.
+#[must_use]
+pub fn alert_transition(
+    open: Option<&SwarmAlert>,
+    breach: Option<&AlertBreach>,
+    now: DateTime<Utc>,
+) -> AlertTransition {
+    match (open, breach) {
+        (None, None) => AlertTransition::Hold,
+        (Some(_), None) => AlertTransition::Resolve,
+        (None, Some(breach)) if breach.for_mins == 0 => AlertTransition::Fire(breach.clone()),
+        (None, Some(breach)) => AlertTransition::Open(breach.clone()),
+        (Some(alert), Some(breach)) => {
+            let held_mins = (now - alert.breached_at).num_minutes();
+            if alert.status == AlertStatus::Pending && held_mins >= i64::from(breach.for_mins) {
+                AlertTransition::Fire(breach.clone())
+            } else {
+                AlertTransition::Hold
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn pending(kind: AlertKind, breached_at: DateTime<Utc>) -> SwarmAlert {
+        SwarmAlert {
+            kind,
+            status: AlertStatus::Pending,
+            detail: String::new(),
+            breached_at,
+            fired_at: None,
+            resolved_at: None,
+        }
+    }
+
+    #[test]
+    fn given_counters_past_limits_when_evaluating_then_each_rule_breaches() {
+        let observation = AlertObservation {
+            pending_beads: 51,
+            stage_runs: 8,
+            stage_failures: 3,
+            total_agents: 4,
+            waiting_agents: 4,
+        };
+
+        let kinds = AlertRules::default()
+            .breaches(&observation)
+            .into_iter()
+            .map(|breach| breach.kind)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            [
+                AlertKind::BacklogDepth,
+                AlertKind::ErrorRate,
+                AlertKind::AllAgentsWaiting
+            ]
+        );
+    }
+
+    #[test]
+    fn given_too_few_runs_or_no_agents_when_evaluating_then_nothing_breaches() {
+        let observation = AlertObservation {
+            pending_beads: 3,
+            stage_runs: 2,
+            stage_failures: 2,
+            total_agents: 0,
+            waiting_agents: 0,
+        };
+
+        assert!(AlertRules::default().breaches(&observation).is_empty());
+    }
+
+    #[test]
+    fn given_breach_held_past_duration_when_transitioning_then_pending_alert_fires() {
+        let now = Utc::now();
+        let breach = AlertBreach {
+            kind: AlertKind::BacklogDepth,
+            detail: "60 beads pending (limit 50)".to_string(),
+            for_mins: 10,
+        };
+        let young = pending(AlertKind::BacklogDepth, now - Duration::minutes(3));
+        let old = pending(AlertKind::BacklogDepth, now - Duration::minutes(10));
+
+        assert_eq!(
+            alert_transition(None, Some(&breach), now),
+            AlertTransition::Open(breach.clone())
+        );
+        assert_eq!(
+            alert_transition(Some(&young), Some(&breach), now),
+            AlertTransition::Hold
+        );
+        assert_eq!(
+            alert_transition(Some(&old), Some(&breach), now),
+            AlertTransition::Fire(breach)
+        );
+        assert_eq!(
+            alert_transition(Some(&old), None, now),
+            AlertTransition::Resolve
+        );
+    }
+
+    #[test]
+    fn given_partial_rules_json_when_parsing_then_null_disables_and_omitted_defaults() {
+        let rules: AlertRules =
+            serde_json::from_str(r#"{"all_agents_waiting": null, "webhook_url": "http://x"}"#)
+                .expect("valid rules");
+
+        assert_eq!(rules.all_agents_waiting, None);
+        assert_eq!(rules.backlog_depth, AlertRules::default().backlog_depth);
+        assert_eq!(rules.webhook_url.as_deref(), Some("http://x"));
+        assert_eq!(rules.validate(), Ok(()));
+    }
+
+    #[test]
+    fn given_out_of_range_settings_when_validating_then_they_are_rejected() {
+        for raw in [
+            r#"{"error_rate": {"max_percent": 100.0, "window_mins": 15, "min_runs": 4}}"#,
+            r#"{"error_rate": {"max_percent": 10.0, "window_mins": 0, "min_runs": 4}}"#,
+            r#"{"webhook_url": "file:///etc/passwd"}"#,
+        ] {
+            let rules: AlertRules = serde_json::from_str(raw).expect("parses");
+            assert!(rules.validate().is_err(), "{raw}");
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Swarm-wide announcements. Unlike a point-in-time broadcast, an
+//! announcement stays until it expires: agents registered later still see
+//! it, and each agent acknowledges it once it has acted on it.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Topic of a `broadcast` that names none.
+pub const DEFAULT_ANNOUNCEMENT_TOPIC: &str = "general";
+/// Lifetime of an announcement that sets no `ttl_secs`: one day.
+pub const DEFAULT_ANNOUNCEMENT_TTL_SECS: u64 = 24 * 60 * 60;
+/// Longest lifetime an announcement can ask for: 30 days.
+pub const MAX_ANNOUNCEMENT_TTL_SECS: u64 = 30 * 24 * 60 * 60;
+/// Longest topic, in bytes.
+pub const MAX_ANNOUNCEMENT_TOPIC_BYTES: usize = 64;
+
+/// Whether `topic` can name an announcement topic: 1-64 bytes without
+/// whitespace, like a label.
+#[must_use]
+pub fn is_valid_announcement_topic(topic: &str) -> bool {
+    !topic.is_empty()
+        && topic.len() <= MAX_ANNOUNCEMENT_TOPIC_BYTES
+        && !topic.contains(char::is_whitespace)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub id: i64,
+    pub topic: String,
+    pub msg: String,
+    pub from: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnnouncementAck {
+    pub agent_id: u32,
+    pub acked_at: DateTime<Utc>,
+}
+
+/// An unexpired announcement with who has acknowledged it and which
+/// registered agents have not yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnnouncementStatus {
+    #[serde(flatten)]
+    pub announcement: Announcement,
+    pub acked: Vec<AnnouncementAck>,
+    pub pending: Vec<u32>,
+}
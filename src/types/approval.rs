@@ -0,0 +1,254 @@
+//! Manual approval gates. `SWARM_APPROVAL_GATES` names the stages, and
+//! optionally `land`, that wait for an operator. Reaching a gate records an
+//! [`Approval`] with a one-off token and moves the bead to
+//! `awaiting_approval` until `swarm approve` releases it. Only the token's
+//! hash is stored; the token itself goes to the operator in the
+//! `approval_requested` notification, never to an agent-visible view.
+
+use super::notify::{Notification, NotificationSeverity};
+use super::stage::Stage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A point in a bead's life that can wait for an operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ApprovalGate {
+    /// Before the stage starts.
+    Stage(Stage),
+    /// Before `land` takes the landing slot.
+    Land,
+}
+
+impl ApprovalGate {
+    /// Get string representation: the stage name, or `land`.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stage(stage) => stage.as_str(),
+            Self::Land => "land",
+        }
+    }
+
+    /// Parses a comma-separated gate list such as `implement,land`, ignoring
+    /// blanks and repeats.
+    ///
+    /// # Errors
+    /// Returns a message naming the first entry that is neither a stage nor
+    /// `land`.
+    pub fn parse_list(raw: &str) -> Result<Vec<Self>, String> {
+        raw.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .try_fold(Vec::new(), |mut gates, entry| {
+                let gate = Self::try_from(entry)?;
+                if !gates.contains(&gate) {
+                    gates.push(gate);
+                }
+                Ok(gates)
+            })
+    }
+}
+
+impl fmt::Display for ApprovalGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for ApprovalGate {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "land" => Ok(Self::Land),
+            "done" => Err("done cannot be gated; use land".to_string()),
+            other => Stage::try_from(other)
+                .map(Self::Stage)
+                .map_err(|_| format!("Unknown approval gate: {other}")),
+        }
+    }
+}
+
+impl TryFrom<String> for ApprovalGate {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl From<ApprovalGate> for String {
+    fn from(gate: ApprovalGate) -> Self {
+        gate.as_str().to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+}
+
+impl ApprovalStatus {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+        }
+    }
+}
+
+impl TryFrom<&str> for ApprovalStatus {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "pending" => Ok(Self::Pending),
+            "approved" => Ok(Self::Approved),
+            _ => Err(format!("Unknown approval status: {value}")),
+        }
+    }
+}
+
+/// One request for an operator to release a bead at a gate. Once approved it
+/// stays approved, so a retried stage does not ask again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Approval {
+    pub id: i64,
+    pub bead_id: String,
+    pub gate: ApprovalGate,
+    pub status: ApprovalStatus,
+    /// Agent that reached the gate.
+    pub requested_by: Option<u32>,
+    pub requested_at: DateTime<Utc>,
+    pub approved_by: Option<String>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+}
+
+impl Approval {
+    #[must_use]
+    pub const fn is_approved(&self) -> bool {
+        matches!(self.status, ApprovalStatus::Approved)
+    }
+}
+
+/// Event and notification that carry a new approval's token to operators.
+pub const APPROVAL_REQUESTED_EVENT: &str = "approval_requested";
+
+/// What reaching a gate returned.
+///
+/// `token` is only set when this call created the approval; it must be
+/// passed to `approve`, so an operator releases the request they were told
+/// about rather than whatever is pending for the bead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalRequest {
+    pub approval: Approval,
+    pub token: Option<String>,
+}
+
+impl ApprovalRequest {
+    /// The `approval_requested` notification holding the token, or `None`
+    /// when the approval already existed.
+    #[must_use]
+    pub fn token_notification(&self, repo_id: &str) -> Option<Notification> {
+        let token = self.token.as_deref()?;
+        let approval = &self.approval;
+        Some(Notification {
+            event: APPROVAL_REQUESTED_EVENT.to_string(),
+            severity: NotificationSeverity::Warning,
+            repo_id: repo_id.to_string(),
+            text: format!(
+                "Bead {} is waiting for approval at {}",
+                approval.bead_id, approval.gate
+            ),
+            details: serde_json::json!({
+                "approval_id": approval.id,
+                "bead_id": approval.bead_id,
+                "gate": approval.gate,
+                "requested_by": approval.requested_by,
+                "token": token,
+                "approve": format!("swarm approve --bead-id {} --token {token}", approval.bead_id),
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_stage_names_and_land_when_parsing_gate_list_then_each_is_kept_once() {
+        assert_eq!(
+            ApprovalGate::parse_list(" implement, land,,implement "),
+            Ok(vec![
+                ApprovalGate::Stage(Stage::Implement),
+                ApprovalGate::Land
+            ])
+        );
+    }
+
+    #[test]
+    fn given_unknown_or_done_gate_when_parsing_then_it_is_rejected() {
+        assert!(ApprovalGate::parse_list("implement,deploy").is_err());
+        assert!(ApprovalGate::parse_list("done").is_err());
+    }
+
+    #[test]
+    fn given_new_approval_when_building_notification_then_only_it_carries_the_token() {
+        let approval = Approval {
+            id: 7,
+            bead_id: "bd-1".to_string(),
+            gate: ApprovalGate::Land,
+            status: ApprovalStatus::Pending,
+            requested_by: Some(2),
+            requested_at: Utc::now(),
+            approved_by: None,
+            approved_at: None,
+            note: None,
+        };
+        let created = ApprovalRequest {
+            approval: approval.clone(),
+            token: Some("abc123".to_string()),
+        };
+        let existing = ApprovalRequest {
+            approval: approval.clone(),
+            token: None,
+        };
+
+        let notification = created.token_notification("local");
+
+        assert_eq!(
+            notification.as_ref().map(|n| n.event.as_str()),
+            Some(APPROVAL_REQUESTED_EVENT)
+        );
+        assert_eq!(
+            notification.map(|n| n.details["token"].clone()),
+            Some(serde_json::json!("abc123"))
+        );
+        assert_eq!(existing.token_notification("local"), None);
+        assert!(serde_json::to_value(&approval)
+            .ok()
+            .and_then(|value| value.get("token").cloned())
+            .is_none());
+    }
+
+    #[test]
+    fn approval_gate_serializes_as_its_name() {
+        let gate = ApprovalGate::Stage(Stage::RedQueen);
+        assert_eq!(
+            serde_json::to_value(gate).ok(),
+            Some(serde_json::json!("red-queen"))
+        );
+        assert_eq!(
+            serde_json::from_value::<ApprovalGate>(serde_json::json!("land")).ok(),
+            Some(ApprovalGate::Land)
+        );
+    }
+}
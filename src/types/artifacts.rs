@@ -1,3 +1,5 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -30,6 +32,12 @@ pub enum ArtifactType {
     SkillInvocation,
     ErrorMessage,
     Feedback,
+    PushVerification,
+    DesignDocument,
+    Diff,
+    Screenshot,
+    Attachment,
+    Summary,
 }
 
 impl ArtifactType {
@@ -63,10 +71,16 @@ impl ArtifactType {
             Self::SkillInvocation => "skill_invocation",
             Self::ErrorMessage => "error_message",
             Self::Feedback => "feedback",
+            Self::PushVerification => "push_verification",
+            Self::DesignDocument => "design_document",
+            Self::Diff => "diff",
+            Self::Screenshot => "screenshot",
+            Self::Attachment => "attachment",
+            Self::Summary => "summary",
         }
     }
 
-    pub const ALL_STRINGS: [&'static str; 27] = [
+    pub const ALL_STRINGS: [&'static str; 33] = [
         "contract_document",
         "requirements",
         "system_context",
@@ -94,6 +108,12 @@ impl ArtifactType {
         "skill_invocation",
         "error_message",
         "feedback",
+        "push_verification",
+        "design_document",
+        "diff",
+        "screenshot",
+        "attachment",
+        "summary",
     ];
 
     #[must_use]
@@ -133,6 +153,12 @@ impl TryFrom<&str> for ArtifactType {
             "skill_invocation" => Ok(Self::SkillInvocation),
             "error_message" => Ok(Self::ErrorMessage),
             "feedback" => Ok(Self::Feedback),
+            "push_verification" => Ok(Self::PushVerification),
+            "design_document" => Ok(Self::DesignDocument),
+            "diff" => Ok(Self::Diff),
+            "screenshot" => Ok(Self::Screenshot),
+            "attachment" => Ok(Self::Attachment),
+            "summary" => Ok(Self::Summary),
             "retry_packet" => Ok(Self::RetryPacket),
             _ => Err(format!("Unknown artifact type: {value}")),
         }
@@ -149,3 +175,74 @@ pub struct StageArtifact {
     pub created_at: DateTime<Utc>,
     pub content_hash: Option<String>,
 }
+
+/// Where `artifact put` stored a file, and whether an identical artifact on
+/// the same stage run was reused instead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredArtifact {
+    pub id: i64,
+    pub stage_history_id: i64,
+    pub content_hash: String,
+    pub deduplicated: bool,
+}
+
+/// A piece of stage command output that did not fit in memory, in the
+/// order it was written for its stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageOutputChunk {
+    pub seq: i32,
+    pub content: Vec<u8>,
+}
+
+/// How an uploaded file is kept in the text `content` column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactEncoding {
+    Utf8,
+    Base64,
+}
+
+impl ArtifactEncoding {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf8",
+            Self::Base64 => "base64",
+        }
+    }
+
+    /// `bytes` as artifact content: UTF-8 text as it is, anything else
+    /// (binary, or text with NUL bytes Postgres will not store) as base64.
+    #[must_use]
+    pub fn encode(bytes: Vec<u8>) -> (String, Self) {
+        match String::from_utf8(bytes) {
+            Ok(text) if !text.contains('\0') => (text, Self::Utf8),
+            Ok(text) => (BASE64.encode(text.as_bytes()), Self::Base64),
+            Err(error) => (BASE64.encode(error.as_bytes()), Self::Base64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArtifactEncoding, ArtifactType};
+
+    #[test]
+    fn given_text_and_binary_files_when_encoding_then_only_binary_is_base64() {
+        assert_eq!(
+            ArtifactEncoding::encode(b"# Design\n".to_vec()),
+            ("# Design\n".to_string(), ArtifactEncoding::Utf8)
+        );
+        assert_eq!(
+            ArtifactEncoding::encode(vec![0x89, b'P', b'N', b'G']),
+            ("iVBORw==".to_string(), ArtifactEncoding::Base64)
+        );
+        assert_eq!(
+            ArtifactEncoding::encode(b"a\0b".to_vec()).1,
+            ArtifactEncoding::Base64
+        );
+        assert!(ArtifactType::names()
+            .iter()
+            .all(|name| ArtifactType::try_from(*name).is_ok_and(|kind| kind.as_str() == *name)));
+    }
+}
@@ -0,0 +1,591 @@
+//! Beads entering the backlog: submitted by hand as a JSON array or NDJSON,
+//! or reconciled from `br list`. A hand-submitted item is a bare bead id or
+//! an object with the id and optional priority, labels, and required
+//! capabilities.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Priorities the claim order understands, most urgent first.
+pub const BACKLOG_PRIORITIES: &[&str] = &["p0", "p1", "p2", "p3"];
+
+/// `bead_backlog.status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BacklogStatus {
+    Pending,
+    InProgress,
+    AwaitingApproval,
+    Completed,
+    Blocked,
+    Cancelled,
+}
+
+impl BacklogStatus {
+    pub const ALL: [Self; 6] = [
+        Self::Pending,
+        Self::InProgress,
+        Self::AwaitingApproval,
+        Self::Completed,
+        Self::Blocked,
+        Self::Cancelled,
+    ];
+
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::InProgress => "in_progress",
+            Self::AwaitingApproval => "awaiting_approval",
+            Self::Completed => "completed",
+            Self::Blocked => "blocked",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl TryFrom<&str> for BacklogStatus {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("Unknown backlog status: {s}"))
+    }
+}
+
+/// One bead to enqueue. Unset fields are filled from `br show` before the
+/// bead is inserted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacklogEntry {
+    pub bead_id: String,
+    pub priority: Option<String>,
+    pub labels: Option<Vec<String>>,
+    pub required_capabilities: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBacklogEntry {
+    #[serde(alias = "id")]
+    bead_id: String,
+    #[serde(default)]
+    priority: Option<Value>,
+    #[serde(default)]
+    labels: Option<Vec<String>>,
+    #[serde(default)]
+    required_capabilities: Vec<String>,
+}
+
+/// `p0`–`p3`, also accepting `P1` or the bare `br` integer `1`.
+///
+/// # Errors
+/// Returns a message when the value is not a known priority.
+pub fn normalize_priority(value: &Value) -> Result<String, String> {
+    let candidate = match value {
+        Value::Number(number) => number.as_u64().map(|n| format!("p{n}")),
+        Value::String(text) => {
+            let text = text.trim().to_ascii_lowercase();
+            Some(if text.chars().all(|c| c.is_ascii_digit()) {
+                format!("p{text}")
+            } else {
+                text
+            })
+        }
+        _ => None,
+    };
+    candidate
+        .filter(|priority| BACKLOG_PRIORITIES.contains(&priority.as_str()))
+        .ok_or_else(|| {
+            format!(
+                "priority must be one of {} or 0-3, got {value}",
+                BACKLOG_PRIORITIES.join(", ")
+            )
+        })
+}
+
+/// Parses a JSON array (optionally wrapped as `{"beads": [...]}`) or NDJSON
+/// into entries, in input order.
+///
+/// # Errors
+/// Returns a message naming the first malformed item, an empty list, or a
+/// bead id given twice.
+pub fn parse_backlog_entries(raw: &str) -> Result<Vec<BacklogEntry>, String> {
+    let trimmed = raw.trim();
+    let items = match serde_json::from_str::<Value>(trimmed) {
+        Ok(Value::Array(items)) => items,
+        Ok(Value::Object(object)) if object.contains_key("beads") => object
+            .get("beads")
+            .and_then(Value::as_array)
+            .cloned()
+            .ok_or_else(|| "beads must be an array".to_string())?,
+        _ => trimmed
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str::<Value>(line)
+                    .map_err(|err| format!("line {}: not valid JSON: {err}", index + 1))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    if items.is_empty() {
+        return Err("no beads to enqueue".to_string());
+    }
+
+    let mut entries: Vec<BacklogEntry> = Vec::with_capacity(items.len());
+    for (index, item) in items.into_iter().enumerate() {
+        let entry = parse_item(item).map_err(|err| format!("item {index}: {err}"))?;
+        if entries.iter().any(|seen| seen.bead_id == entry.bead_id) {
+            return Err(format!("item {index}: {} is listed twice", entry.bead_id));
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+fn parse_item(item: Value) -> Result<BacklogEntry, String> {
+    let raw = match item {
+        Value::String(bead_id) => RawBacklogEntry {
+            bead_id,
+            priority: None,
+            labels: None,
+            required_capabilities: Vec::new(),
+        },
+        Value::Object(_) => serde_json::from_value(item).map_err(|err| err.to_string())?,
+        other => return Err(format!("expected a bead id or an object, got {other}")),
+    };
+    let bead_id = raw.bead_id.trim().to_string();
+    if bead_id.is_empty() {
+        return Err("bead_id must not be empty".to_string());
+    }
+    if bead_id.starts_with('-') {
+        return Err(format!("bead_id {bead_id} must not start with '-'"));
+    }
+    Ok(BacklogEntry {
+        bead_id,
+        priority: raw.priority.as_ref().map(normalize_priority).transpose()?,
+        labels: raw.labels.map(trimmed_unique),
+        required_capabilities: trimmed_unique(raw.required_capabilities),
+    })
+}
+
+fn trimmed_unique(values: Vec<String>) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::with_capacity(values.len());
+    for value in values {
+        let value = value.trim().to_string();
+        if !value.is_empty() && !unique.contains(&value) {
+            unique.push(value);
+        }
+    }
+    unique
+}
+
+/// `br` statuses that make a bead eligible for the backlog.
+pub const BR_READY_STATUSES: &[&str] = &["open"];
+
+/// One issue from `br list --json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrIssue {
+    pub id: String,
+    pub status: String,
+    /// Normalized to `p0`–`p3`; `None` when `br` has none or an unknown one.
+    pub priority: Option<String>,
+    pub labels: Vec<String>,
+}
+
+/// Reads the issues out of a `br list --json` payload, a bare array or
+/// `{"issues": [...]}`. Items without an id or status are skipped.
+#[must_use]
+pub fn parse_br_issues(payload: &Value) -> Vec<BrIssue> {
+    payload
+        .as_array()
+        .or_else(|| payload.get("issues").and_then(Value::as_array))
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    let text = |name: &str| {
+                        item.get(name)
+                            .and_then(Value::as_str)
+                            .map(str::trim)
+                            .filter(|value| !value.is_empty())
+                            .map(ToString::to_string)
+                    };
+                    Some(BrIssue {
+                        id: text("id")?,
+                        status: text("status")?,
+                        priority: item
+                            .get("priority")
+                            .and_then(|priority| normalize_priority(priority).ok()),
+                        labels: item
+                            .get("labels")
+                            .and_then(|labels| serde_json::from_value(labels.clone()).ok())
+                            .unwrap_or_default(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A `bead_backlog` row as the sync sees it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacklogRow {
+    pub bead_id: String,
+    pub status: String,
+    pub priority: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriorityChange {
+    pub bead_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// What a sync changes. Only `pending` rows are dropped or reprioritized;
+/// claimed and finished beads belong to the swarm until they are released.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacklogSyncDiff {
+    pub added: Vec<BacklogEntry>,
+    /// Pending beads closed in `br`.
+    pub dropped: Vec<String>,
+    pub reprioritized: Vec<PriorityChange>,
+    pub unchanged: u32,
+}
+
+impl BacklogSyncDiff {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.dropped.is_empty() && self.reprioritized.is_empty()
+    }
+}
+
+/// Compares `br` with the backlog: ready issues missing from the backlog
+/// are added, pending beads closed in `br` are dropped, and pending beads
+/// whose `br` priority changed take the new one.
+#[must_use]
+pub fn reconcile_backlog(issues: &[BrIssue], rows: &[BacklogRow]) -> BacklogSyncDiff {
+    let mut diff = BacklogSyncDiff::default();
+    for issue in issues {
+        let Some(row) = rows.iter().find(|row| row.bead_id == issue.id) else {
+            if BR_READY_STATUSES.contains(&issue.status.as_str()) {
+                diff.added.push(BacklogEntry {
+                    bead_id: issue.id.clone(),
+                    priority: issue.priority.clone(),
+                    labels: Some(issue.labels.clone()),
+                    required_capabilities: Vec::new(),
+                });
+            }
+            continue;
+        };
+        if row.status != "pending" {
+            diff.unchanged = diff.unchanged.saturating_add(1);
+        } else if issue.status == "closed" {
+            diff.dropped.push(issue.id.clone());
+        } else if let Some(priority) = issue
+            .priority
+            .as_ref()
+            .filter(|priority| **priority != row.priority)
+        {
+            diff.reprioritized.push(PriorityChange {
+                bead_id: issue.id.clone(),
+                from: row.priority.clone(),
+                to: priority.clone(),
+            });
+        } else {
+            diff.unchanged = diff.unchanged.saturating_add(1);
+        }
+    }
+    diff
+}
+
+/// Which backlog rows an operator edit touches: the listed beads, those
+/// carrying `label`, or, with both, listed beads carrying it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacklogSelector {
+    pub bead_ids: Vec<String>,
+    pub label: Option<String>,
+}
+
+/// A selected bead an edit left alone, and why: its status when it is no
+/// longer pending, `reserved`, or `claimed_during_edit` when it was claimed
+/// between being read and written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacklogEditSkip {
+    pub bead_id: String,
+    pub reason: String,
+}
+
+/// What `backlog set-priority`, `bump` or `remove` changed. Only pending
+/// rows are edited; `missing` lists requested bead ids not in the backlog.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacklogEditOutcome {
+    pub reprioritized: Vec<PriorityChange>,
+    pub removed: Vec<String>,
+    pub skipped: Vec<BacklogEditSkip>,
+    pub missing: Vec<String>,
+    pub unchanged: u32,
+}
+
+/// A pending bead for `backlog preview`. Beads are given in claim order:
+/// priority, then age.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreviewBead {
+    pub bead_id: String,
+    pub priority: String,
+    pub labels: Vec<String>,
+    pub required_capabilities: Vec<String>,
+    /// Agent holding a live reservation on the bead.
+    pub reserved_by: Option<u32>,
+}
+
+/// An agent that could claim now: idle, holding no bead, not quarantined.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreviewAgent {
+    pub agent_id: u32,
+    /// Default claim labels; empty takes any bead.
+    pub labels: Vec<String>,
+}
+
+/// Where one pending bead would go.
+///
+/// `reason` is `reserved` (its reserving agent takes it), `claim_order`,
+/// `reserved_elsewhere` (held for an agent that cannot claim now),
+/// `label_mismatch` (free agents remain but none takes its labels or has its
+/// required capabilities) or `no_free_agent`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PreviewAssignment {
+    pub position: u32,
+    pub bead: PreviewBead,
+    pub agent_id: Option<u32>,
+    pub reason: &'static str,
+}
+
+/// Plays one claim round the way `claim_next_bead` would.
+///
+/// Reservations go to their holders first, then each free agent, lowest id
+/// first, takes the first unclaimed, unreserved bead whose labels it accepts
+/// and whose required capabilities are all among its labels.
+#[must_use]
+pub fn simulate_assignments(
+    beads: &[PreviewBead],
+    agents: &[PreviewAgent],
+) -> Vec<PreviewAssignment> {
+    let mut taken: Vec<Option<(u32, &'static str)>> = vec![None; beads.len()];
+    let mut free = agents.iter().collect::<Vec<_>>();
+    free.sort_by_key(|agent| agent.agent_id);
+
+    for (index, bead) in beads.iter().enumerate() {
+        if let Some(holder) = bead.reserved_by {
+            if let Some(slot) = free.iter().position(|agent| agent.agent_id == holder) {
+                free.remove(slot);
+                taken[index] = Some((holder, "reserved"));
+            }
+        }
+    }
+
+    let mut idle = Vec::new();
+    for agent in free {
+        let pick = beads.iter().enumerate().position(|(index, bead)| {
+            taken[index].is_none()
+                && bead.reserved_by.is_none()
+                && (agent.labels.is_empty()
+                    || bead.labels.iter().any(|label| agent.labels.contains(label)))
+                && bead
+                    .required_capabilities
+                    .iter()
+                    .all(|capability| agent.labels.contains(capability))
+        });
+        match pick {
+            Some(index) => taken[index] = Some((agent.agent_id, "claim_order")),
+            None => idle.push(agent.agent_id),
+        }
+    }
+
+    beads
+        .iter()
+        .zip(taken)
+        .enumerate()
+        .map(|(index, (bead, taken))| {
+            let (agent_id, reason) = match taken {
+                Some((agent_id, reason)) => (Some(agent_id), reason),
+                None if bead.reserved_by.is_some() => (None, "reserved_elsewhere"),
+                None if !idle.is_empty() => (None, "label_mismatch"),
+                None => (None, "no_free_agent"),
+            };
+            PreviewAssignment {
+                position: u32::try_from(index + 1).unwrap_or(u32::MAX),
+                bead: bead.clone(),
+                agent_id,
+                reason,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn given_json_array_and_ndjson_when_parsing_then_entries_match() {
+        let array = parse_backlog_entries(
+            r#"["bd-1", {"id": "bd-2", "priority": 1, "labels": ["api", "api"], "required_capabilities": ["gpu"]}]"#,
+        )
+        .expect("array parses");
+        let ndjson = parse_backlog_entries(
+            "\"bd-1\"\n\n{\"bead_id\": \"bd-2\", \"priority\": \"P1\", \"labels\": [\"api\"], \"required_capabilities\": [\"gpu\"]}\n",
+        )
+        .expect("ndjson parses");
+
+        assert_eq!(array, ndjson);
+        assert_eq!(array[0].priority, None);
+        assert_eq!(array[1].priority.as_deref(), Some("p1"));
+        assert_eq!(array[1].labels, Some(vec!["api".to_string()]));
+        assert_eq!(array[1].required_capabilities, ["gpu"]);
+    }
+
+    #[test]
+    fn given_bad_backlog_input_when_parsing_then_errors_name_the_item() {
+        for raw in [
+            "[]",
+            r#"["bd-1", "bd-1"]"#,
+            r#"[{"id": "bd-1", "priority": "urgent"}]"#,
+            r#"[{"id": "bd-1", "owner": "me"}]"#,
+            "[42]",
+            r#"["--help"]"#,
+            "bd-1",
+        ] {
+            assert!(parse_backlog_entries(raw).is_err(), "{raw}");
+        }
+        assert_eq!(normalize_priority(&json!(3)), Ok("p3".to_string()));
+        assert!(normalize_priority(&json!(4)).is_err());
+    }
+
+    #[test]
+    fn given_br_list_when_reconciling_then_only_pending_rows_change() {
+        let issues = parse_br_issues(&json!([
+            {"id": "bd-new", "status": "open", "priority": 1, "labels": ["api"]},
+            {"id": "bd-deferred", "status": "deferred", "priority": 0},
+            {"id": "bd-closed", "status": "closed", "priority": 2},
+            {"id": "bd-bumped", "status": "open", "priority": 0},
+            {"id": "bd-claimed", "status": "closed", "priority": 3},
+            {"id": "bd-same", "status": "open", "priority": 2},
+            {"status": "open"},
+        ]));
+        let row = |bead_id: &str, status: &str, priority: &str| BacklogRow {
+            bead_id: bead_id.to_string(),
+            status: status.to_string(),
+            priority: priority.to_string(),
+        };
+        let rows = [
+            row("bd-closed", "pending", "p2"),
+            row("bd-bumped", "pending", "p2"),
+            row("bd-claimed", "in_progress", "p2"),
+            row("bd-same", "pending", "p2"),
+        ];
+
+        let diff = reconcile_backlog(&issues, &rows);
+
+        assert_eq!(
+            diff.added
+                .iter()
+                .map(|entry| entry.bead_id.as_str())
+                .collect::<Vec<_>>(),
+            ["bd-new"]
+        );
+        assert_eq!(diff.added[0].priority.as_deref(), Some("p1"));
+        assert_eq!(diff.dropped, ["bd-closed"]);
+        assert_eq!(
+            diff.reprioritized,
+            [PriorityChange {
+                bead_id: "bd-bumped".to_string(),
+                from: "p2".to_string(),
+                to: "p0".to_string(),
+            }]
+        );
+        assert_eq!(diff.unchanged, 2);
+    }
+
+    #[test]
+    fn given_labeled_agents_when_simulating_then_beads_follow_claim_order_and_labels() {
+        let bead = |bead_id: &str, labels: &[&str], reserved_by: Option<u32>| PreviewBead {
+            bead_id: bead_id.to_string(),
+            priority: "p0".to_string(),
+            labels: labels.iter().map(ToString::to_string).collect(),
+            required_capabilities: Vec::new(),
+            reserved_by,
+        };
+        let beads = [
+            bead("bd-infra", &["infra"], None),
+            bead("bd-held", &[], Some(2)),
+            bead("bd-docs", &["docs"], None),
+            bead("bd-any", &[], None),
+            bead("bd-lost", &["infra"], Some(9)),
+        ];
+        let agents = [
+            PreviewAgent {
+                agent_id: 3,
+                labels: Vec::new(),
+            },
+            PreviewAgent {
+                agent_id: 1,
+                labels: vec!["docs".to_string()],
+            },
+            PreviewAgent {
+                agent_id: 2,
+                labels: Vec::new(),
+            },
+        ];
+
+        let plan = simulate_assignments(&beads, &agents)
+            .into_iter()
+            .map(|assignment| {
+                (
+                    assignment.bead.bead_id,
+                    assignment.agent_id,
+                    assignment.reason,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            plan,
+            [
+                ("bd-infra".to_string(), Some(3), "claim_order"),
+                ("bd-held".to_string(), Some(2), "reserved"),
+                ("bd-docs".to_string(), Some(1), "claim_order"),
+                ("bd-any".to_string(), None, "no_free_agent"),
+                ("bd-lost".to_string(), None, "reserved_elsewhere"),
+            ]
+        );
+    }
+
+    #[test]
+    fn given_required_capabilities_when_simulating_then_only_covering_agents_take_the_bead() {
+        let beads = [PreviewBead {
+            bead_id: "bd-gpu".to_string(),
+            priority: "p0".to_string(),
+            labels: vec!["gpu".to_string()],
+            required_capabilities: vec!["gpu".to_string()],
+            reserved_by: None,
+        }];
+        let agent = |agent_id: u32, labels: &[&str]| PreviewAgent {
+            agent_id,
+            labels: labels.iter().map(ToString::to_string).collect(),
+        };
+
+        let unmatched = simulate_assignments(&beads, &[agent(1, &[])]);
+        let matched = simulate_assignments(&beads, &[agent(1, &[]), agent(2, &["gpu"])]);
+
+        assert_eq!(unmatched[0].agent_id, None);
+        assert_eq!(unmatched[0].reason, "label_mismatch");
+        assert_eq!(matched[0].agent_id, Some(2));
+        assert_eq!(matched[0].reason, "claim_order");
+    }
+}
@@ -0,0 +1,303 @@
+//! A portable copy of one bead's execution state: its backlog entry, claim,
+//! agent assignment, and every stage attempt with its artifacts. Restoring a
+//! snapshot reinstates the bead in another swarm, or undoes a bad release in
+//! the same one.
+
+use super::{AgentStatus, ArtifactType, ClaimStatus, Stage};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Snapshot layout version; restore refuses any other.
+pub const BEAD_SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotBacklog {
+    pub priority: String,
+    /// `pending`, `in_progress`, `completed`, or `blocked`.
+    pub status: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotClaim {
+    pub claimed_by: u32,
+    /// `in_progress`, `completed`, or `blocked`.
+    pub status: String,
+    pub claimed_at: DateTime<Utc>,
+}
+
+/// The agent row that was working the bead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotAssignment {
+    pub agent_id: u32,
+    pub current_stage: Option<String>,
+    pub status: String,
+    pub implementation_attempt: u32,
+    pub feedback: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotArtifact {
+    pub artifact_type: String,
+    pub content: String,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One `stage_history` row with the artifacts stored against it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotStage {
+    pub agent_id: u32,
+    pub stage: String,
+    pub attempt_number: u32,
+    /// `started`, `passed`, `failed`, or `error`.
+    pub status: String,
+    pub result: Option<String>,
+    pub feedback: Option<String>,
+    pub transcript: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<u32>,
+    #[serde(default)]
+    pub artifacts: Vec<SnapshotArtifact>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BeadSnapshot {
+    pub version: u32,
+    pub bead_id: String,
+    /// Repository the snapshot was taken from.
+    pub repo_id: String,
+    pub captured_at: DateTime<Utc>,
+    pub backlog: Option<SnapshotBacklog>,
+    pub claim: Option<SnapshotClaim>,
+    pub assignment: Option<SnapshotAssignment>,
+    /// Stage attempts, oldest first.
+    #[serde(default)]
+    pub stages: Vec<SnapshotStage>,
+}
+
+impl BeadSnapshot {
+    /// Whether the snapshot holds anything to restore.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.backlog.is_none()
+            && self.claim.is_none()
+            && self.assignment.is_none()
+            && self.stages.is_empty()
+    }
+
+    /// Number of artifacts across all stage attempts.
+    #[must_use]
+    pub fn artifact_count(&self) -> usize {
+        self.stages.iter().map(|stage| stage.artifacts.len()).sum()
+    }
+
+    /// The snapshot with its claim and assignment handed to `agent_id`, for
+    /// restoring into a swarm whose agents are numbered differently. Stage
+    /// history keeps the agents that actually ran each attempt.
+    #[must_use]
+    pub const fn reassigned_to(mut self, agent_id: u32) -> Self {
+        if let Some(claim) = self.claim.as_mut() {
+            claim.claimed_by = agent_id;
+        }
+        if let Some(assignment) = self.assignment.as_mut() {
+            assignment.agent_id = agent_id;
+        }
+        self
+    }
+
+    /// Checks the snapshot before anything is written.
+    ///
+    /// # Errors
+    /// Returns a message naming the first field the database would reject,
+    /// or a version or agent mismatch.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version != BEAD_SNAPSHOT_VERSION {
+            return Err(format!(
+                "Unsupported snapshot version {}; expected {BEAD_SNAPSHOT_VERSION}",
+                self.version
+            ));
+        }
+        if self.bead_id.trim().is_empty() {
+            return Err("Snapshot bead_id must not be empty".to_string());
+        }
+        if self.is_empty() {
+            return Err(format!("Snapshot of {} holds no state", self.bead_id));
+        }
+        if let Some(backlog) = &self.backlog {
+            one_of(
+                "backlog.status",
+                &backlog.status,
+                &[
+                    "pending",
+                    "in_progress",
+                    "awaiting_approval",
+                    "completed",
+                    "blocked",
+                ],
+            )?;
+        }
+        if let Some(claim) = &self.claim {
+            positive_agent("claim.claimed_by", claim.claimed_by)?;
+            ClaimStatus::try_from(claim.status.as_str())?;
+        }
+        if let Some(assignment) = &self.assignment {
+            positive_agent("assignment.agent_id", assignment.agent_id)?;
+            AgentStatus::try_from(assignment.status.as_str())?;
+            if let Some(stage) = assignment.current_stage.as_deref() {
+                Stage::try_from(stage)?;
+            }
+            // agent_state.bead_id must point at a claim the same agent holds.
+            match &self.claim {
+                None => return Err("Snapshot assignment has no matching claim".to_string()),
+                Some(claim) if claim.claimed_by != assignment.agent_id => {
+                    return Err(format!(
+                        "Snapshot claim belongs to agent {} but the assignment is agent {}",
+                        claim.claimed_by, assignment.agent_id
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        for (index, stage) in self.stages.iter().enumerate() {
+            positive_agent(&format!("stages[{index}].agent_id"), stage.agent_id)?;
+            if Stage::try_from(stage.stage.as_str())? == Stage::Done {
+                return Err(format!("stages[{index}].stage cannot be done"));
+            }
+            if stage.attempt_number == 0 {
+                return Err(format!(
+                    "stages[{index}].attempt_number must be greater than 0"
+                ));
+            }
+            one_of(
+                &format!("stages[{index}].status"),
+                &stage.status,
+                &["started", "passed", "failed", "error"],
+            )?;
+            for artifact in &stage.artifacts {
+                ArtifactType::try_from(artifact.artifact_type.as_str())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn one_of(field: &str, value: &str, allowed: &[&str]) -> Result<(), String> {
+    if allowed.contains(&value) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{field} must be one of {}, got {value}",
+            allowed.join(", ")
+        ))
+    }
+}
+
+fn positive_agent(field: &str, agent_id: u32) -> Result<(), String> {
+    if agent_id == 0 {
+        Err(format!("{field} must be greater than 0"))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> BeadSnapshot {
+        BeadSnapshot {
+            version: BEAD_SNAPSHOT_VERSION,
+            bead_id: "bd-1".to_string(),
+            repo_id: "local".to_string(),
+            captured_at: DateTime::<Utc>::UNIX_EPOCH,
+            backlog: Some(SnapshotBacklog {
+                priority: "p0".to_string(),
+                status: "in_progress".to_string(),
+            }),
+            claim: Some(SnapshotClaim {
+                claimed_by: 3,
+                status: "in_progress".to_string(),
+                claimed_at: DateTime::<Utc>::UNIX_EPOCH,
+            }),
+            assignment: Some(SnapshotAssignment {
+                agent_id: 3,
+                current_stage: Some("qa-enforcer".to_string()),
+                status: "working".to_string(),
+                implementation_attempt: 1,
+                feedback: None,
+            }),
+            stages: vec![SnapshotStage {
+                agent_id: 3,
+                stage: "implement".to_string(),
+                attempt_number: 1,
+                status: "passed".to_string(),
+                result: None,
+                feedback: None,
+                transcript: None,
+                started_at: DateTime::<Utc>::UNIX_EPOCH,
+                completed_at: Some(DateTime::<Utc>::UNIX_EPOCH),
+                duration_ms: Some(1200),
+                artifacts: vec![SnapshotArtifact {
+                    artifact_type: "implementation_code".to_string(),
+                    content: "fn main() {}".to_string(),
+                    metadata: None,
+                    created_at: DateTime::<Utc>::UNIX_EPOCH,
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn given_snapshot_when_round_tripping_json_then_it_validates_unchanged() {
+        let original = snapshot();
+        let raw = serde_json::to_string(&original).expect("serialize snapshot");
+        let restored: BeadSnapshot = serde_json::from_str(&raw).expect("parse snapshot");
+
+        assert_eq!(restored, original);
+        assert_eq!(restored.validate(), Ok(()));
+        assert_eq!(restored.artifact_count(), 1);
+    }
+
+    #[test]
+    fn given_reassignment_when_restoring_elsewhere_then_history_keeps_original_agent() {
+        let moved = snapshot().reassigned_to(7);
+
+        assert_eq!(moved.claim.as_ref().map(|claim| claim.claimed_by), Some(7));
+        assert_eq!(
+            moved
+                .assignment
+                .as_ref()
+                .map(|assignment| assignment.agent_id),
+            Some(7)
+        );
+        assert_eq!(moved.stages[0].agent_id, 3);
+        assert_eq!(moved.validate(), Ok(()));
+    }
+
+    #[test]
+    fn given_invalid_snapshots_when_validating_then_errors_are_returned() {
+        let mut wrong_version = snapshot();
+        wrong_version.version = 2;
+        let mut mismatched = snapshot();
+        if let Some(claim) = mismatched.claim.as_mut() {
+            claim.claimed_by = 4;
+        }
+        let mut bad_artifact = snapshot();
+        bad_artifact.stages[0].artifacts[0].artifact_type = "hologram".to_string();
+        let mut done_stage = snapshot();
+        done_stage.stages[0].stage = "done".to_string();
+
+        for invalid in [wrong_version, mismatched, bad_artifact, done_stage] {
+            assert!(invalid.validate().is_err(), "{invalid:?}");
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Shared notes for one bead, so agents working on it (an implementer and a
+//! reviewer, say) can coordinate without ad-hoc messages. Entries are only
+//! ever appended; each append bumps the bead's blackboard version by one.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest entry, in bytes.
+pub const MAX_BLACKBOARD_ENTRY_BYTES: usize = 16 * 1024;
+
+/// Part of the blackboard an entry belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlackboardSection {
+    Plan,
+    Decisions,
+    OpenQuestions,
+}
+
+impl BlackboardSection {
+    pub const ALL: [Self; 3] = [Self::Plan, Self::Decisions, Self::OpenQuestions];
+
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Plan => "plan",
+            Self::Decisions => "decisions",
+            Self::OpenQuestions => "open_questions",
+        }
+    }
+}
+
+impl TryFrom<&str> for BlackboardSection {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "plan" => Ok(Self::Plan),
+            "decisions" => Ok(Self::Decisions),
+            "open_questions" => Ok(Self::OpenQuestions),
+            _ => Err(format!("Unknown blackboard section: {value}")),
+        }
+    }
+}
+
+/// One appended note. `version` is the blackboard version the append
+/// produced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlackboardEntry {
+    pub version: i64,
+    pub section: BlackboardSection,
+    pub agent_id: u32,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A bead's blackboard, entries oldest first within each section. `version`
+/// is 0 until the first append.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Blackboard {
+    pub bead_id: String,
+    pub version: i64,
+    pub plan: Vec<BlackboardEntry>,
+    pub decisions: Vec<BlackboardEntry>,
+    pub open_questions: Vec<BlackboardEntry>,
+}
+
+impl Blackboard {
+    /// Groups `entries` by section, keeping their order.
+    #[must_use]
+    pub fn from_entries(bead_id: &str, version: i64, entries: Vec<BlackboardEntry>) -> Self {
+        let mut board = Self {
+            bead_id: bead_id.to_string(),
+            version,
+            plan: Vec::new(),
+            decisions: Vec::new(),
+            open_questions: Vec::new(),
+        };
+        for entry in entries {
+            match entry.section {
+                BlackboardSection::Plan => board.plan.push(entry),
+                BlackboardSection::Decisions => board.decisions.push(entry),
+                BlackboardSection::OpenQuestions => board.open_questions.push(entry),
+            }
+        }
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blackboard_section_roundtrip_preserves_values() {
+        for section in BlackboardSection::ALL {
+            assert_eq!(BlackboardSection::try_from(section.as_str()), Ok(section));
+        }
+        assert!(BlackboardSection::try_from("open-questions").is_err());
+    }
+
+    #[test]
+    fn from_entries_groups_by_section_in_order() {
+        let entry = |version, section| BlackboardEntry {
+            version,
+            section,
+            agent_id: 1,
+            content: format!("note {version}"),
+            created_at: Utc::now(),
+        };
+        let board = Blackboard::from_entries(
+            "bd-abc",
+            3,
+            vec![
+                entry(1, BlackboardSection::Plan),
+                entry(2, BlackboardSection::OpenQuestions),
+                entry(3, BlackboardSection::Plan),
+            ],
+        );
+
+        assert_eq!(
+            board.plan.iter().map(|e| e.version).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert!(board.decisions.is_empty());
+        assert_eq!(board.open_questions.len(), 1);
+    }
+}
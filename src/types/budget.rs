@@ -40,6 +40,17 @@ impl BudgetLimit {
         Self::new(100_000, 50_000, 120_000)
     }
 
+    /// Budget for a bead of backlog `priority`: `p0` beads get
+    /// [`Self::high_priority`], everything else [`Self::default_bead`].
+    #[must_use]
+    pub fn for_priority(priority: Option<&str>) -> Self {
+        if priority.is_some_and(|priority| priority.eq_ignore_ascii_case("p0")) {
+            Self::high_priority()
+        } else {
+            Self::default_bead()
+        }
+    }
+
     /// Check if the given usage exceeds this budget.
     #[must_use]
     pub const fn is_exceeded(&self, usage: &TokenUsage) -> bool {
@@ -212,10 +223,16 @@ impl BudgetRecord {
 pub struct TokenUsageRecord {
     /// Unique identifier for this usage record.
     pub id: i64,
-    /// The budget this usage is associated with.
-    pub budget_id: i64,
+    /// The budget this usage is associated with, if any.
+    pub budget_id: Option<i64>,
     /// The agent that consumed these tokens.
     pub agent_id: String,
+    /// The bead the tokens were spent on.
+    pub bead_id: Option<String>,
+    /// The pipeline stage that spent them.
+    pub stage: Option<String>,
+    /// The model that was billed, matched against `CostPricing`.
+    pub model: Option<String>,
     /// Input tokens in this usage.
     pub input_tokens: u64,
     /// Output tokens in this usage.
@@ -226,6 +243,14 @@ pub struct TokenUsageRecord {
     pub recorded_at: DateTime<Utc>,
 }
 
+impl TokenUsageRecord {
+    /// Token counts of this record.
+    #[must_use]
+    pub const fn usage(&self) -> TokenUsage {
+        TokenUsage::new(self.input_tokens, self.output_tokens)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,6 +263,19 @@ mod tests {
         assert_eq!(limit.max_total_tokens, 60_000);
     }
 
+    #[test]
+    fn test_budget_limit_for_priority() {
+        assert_eq!(
+            BudgetLimit::for_priority(Some("p0")),
+            BudgetLimit::high_priority()
+        );
+        assert_eq!(
+            BudgetLimit::for_priority(Some("p2")),
+            BudgetLimit::default_bead()
+        );
+        assert_eq!(BudgetLimit::for_priority(None), BudgetLimit::default_bead());
+    }
+
     #[test]
     fn test_budget_limit_exceeded() {
         let limit = BudgetLimit::new(100, 50, 120);
@@ -18,6 +18,55 @@ pub struct BeadClaim {
     pub status: ClaimStatus,
 }
 
+/// Seconds a reservation holds its bead when no TTL is given.
+pub const DEFAULT_RESERVATION_TTL_SECS: u32 = 60;
+/// Longest reservation allowed; an agent that needs longer should claim.
+pub const MAX_RESERVATION_TTL_SECS: u32 = 900;
+
+/// A pending bead held for one agent to inspect. Until `expires_at` no other
+/// agent can claim it; afterwards it is back in the pool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeadReservation {
+    pub bead_id: BeadId,
+    pub agent_id: u32,
+    pub reserved_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// What `swarm cancel` did to a bead. The owner fields are `None` when no
+/// agent held the bead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeadCancellation {
+    pub bead_id: BeadId,
+    pub agent_id: Option<u32>,
+    /// The stage that was running, recorded as `cancelled` in its history.
+    pub stage: Option<String>,
+    pub stage_history_id: Option<i64>,
+    /// The `bead_cancelled` message sent to the owner.
+    pub message_id: Option<i64>,
+}
+
+/// Why a takeover was allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TakeoverBasis {
+    /// The owner's lease had lapsed.
+    Stale,
+    /// The owner recorded its consent on the claim.
+    Consent,
+}
+
+/// A claim moved by `swarm takeover`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimTransfer {
+    pub bead_id: BeadId,
+    pub from_agent: u32,
+    pub to_agent: u32,
+    pub basis: TakeoverBasis,
+    /// The handoff message carrying the resume context.
+    pub message_id: i64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClaimStatus {
     InProgress,
@@ -26,6 +75,8 @@ pub enum ClaimStatus {
 }
 
 impl ClaimStatus {
+    pub const ALL: [Self; 3] = [Self::InProgress, Self::Completed, Self::Blocked];
+
     #[must_use]
     pub const fn as_str(&self) -> &'static str {
         match self {
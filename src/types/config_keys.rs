@@ -0,0 +1,229 @@
+//! Typed keys and values for `config get` / `config set`.
+//!
+//! Each [`ConfigKey`] is a `swarm_config` column. A [`ConfigChange`] is a
+//! parsed and bounds-checked value for one key; applying it to a
+//! [`SwarmConfig`] gives the row `config set` stores.
+
+use super::swarm_types::{SwarmConfig, SwarmStatus};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Longest `claim_label` accepted, in bytes.
+pub const MAX_CLAIM_LABEL_BYTES: usize = 64;
+
+/// Whether `label` can be a claim label: 1-[`MAX_CLAIM_LABEL_BYTES`] bytes
+/// without whitespace. Backlog and agent labels follow the same rule.
+#[must_use]
+pub fn is_valid_claim_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= MAX_CLAIM_LABEL_BYTES
+        && !label.contains(char::is_whitespace)
+}
+
+/// A `swarm_config` setting `config get` and `config set` address by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigKey {
+    MaxAgents,
+    MaxImplementationAttempts,
+    ClaimLabel,
+    SwarmStatus,
+    LeaseTtlMs,
+    HeartbeatGraceMs,
+    RecoveryScanIntervalMs,
+}
+
+impl ConfigKey {
+    pub const ALL: [Self; 7] = [
+        Self::MaxAgents,
+        Self::MaxImplementationAttempts,
+        Self::ClaimLabel,
+        Self::SwarmStatus,
+        Self::LeaseTtlMs,
+        Self::HeartbeatGraceMs,
+        Self::RecoveryScanIntervalMs,
+    ];
+
+    /// The key's `swarm_config` column, also its name on the command line.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::MaxAgents => "max_agents",
+            Self::MaxImplementationAttempts => "max_implementation_attempts",
+            Self::ClaimLabel => "claim_label",
+            Self::SwarmStatus => "swarm_status",
+            Self::LeaseTtlMs => "lease_ttl_ms",
+            Self::HeartbeatGraceMs => "heartbeat_grace_ms",
+            Self::RecoveryScanIntervalMs => "recovery_scan_interval_ms",
+        }
+    }
+
+    /// Smallest and largest accepted value of a numeric key. The lease
+    /// bounds match the table's CHECK constraints; `max_agents` has the same
+    /// cap as `register`.
+    #[must_use]
+    pub const fn bounds(self) -> Option<(u32, u32)> {
+        match self {
+            Self::MaxAgents => Some((1, 100)),
+            Self::MaxImplementationAttempts => Some((1, 20)),
+            Self::LeaseTtlMs => Some((10_000, 86_400_000)),
+            Self::HeartbeatGraceMs => Some((0, 3_600_000)),
+            Self::RecoveryScanIntervalMs => Some((1_000, 3_600_000)),
+            Self::ClaimLabel | Self::SwarmStatus => None,
+        }
+    }
+
+    /// The key's current value in `config`, as `config get` reports it.
+    #[must_use]
+    pub fn value_in(self, config: &SwarmConfig) -> Value {
+        match self {
+            Self::MaxAgents => json!(config.max_agents),
+            Self::MaxImplementationAttempts => json!(config.max_implementation_attempts),
+            Self::ClaimLabel => json!(config.claim_label),
+            Self::SwarmStatus => json!(config.swarm_status.as_str()),
+            Self::LeaseTtlMs => json!(config.lease_ttl_ms),
+            Self::HeartbeatGraceMs => json!(config.heartbeat_grace_ms),
+            Self::RecoveryScanIntervalMs => json!(config.recovery_scan_interval_ms),
+        }
+    }
+
+    /// Parses and checks a value for this key.
+    ///
+    /// # Errors
+    /// Returns a message saying what the key accepts when `raw` is not a
+    /// valid value for it.
+    pub fn parse_value(self, raw: &str) -> std::result::Result<ConfigChange, String> {
+        let raw = raw.trim();
+        match self {
+            Self::ClaimLabel => {
+                if is_valid_claim_label(raw) {
+                    Ok(ConfigChange::ClaimLabel(raw.to_string()))
+                } else {
+                    Err(format!(
+                        "claim_label must be 1-{MAX_CLAIM_LABEL_BYTES} bytes without whitespace"
+                    ))
+                }
+            }
+            Self::SwarmStatus => SwarmStatus::try_from(raw).map(ConfigChange::SwarmStatus),
+            Self::MaxAgents
+            | Self::MaxImplementationAttempts
+            | Self::LeaseTtlMs
+            | Self::HeartbeatGraceMs
+            | Self::RecoveryScanIntervalMs => {
+                let (min, max) = self.bounds().unwrap_or((0, u32::MAX));
+                let value = raw
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|value| (min..=max).contains(value))
+                    .ok_or_else(|| {
+                        format!(
+                            "{} must be between {min} and {max}, got {raw}",
+                            self.as_str()
+                        )
+                    })?;
+                Ok(match self {
+                    Self::MaxAgents => ConfigChange::MaxAgents(value),
+                    Self::MaxImplementationAttempts => {
+                        ConfigChange::MaxImplementationAttempts(value)
+                    }
+                    Self::LeaseTtlMs => ConfigChange::LeaseTtlMs(value),
+                    Self::HeartbeatGraceMs => ConfigChange::HeartbeatGraceMs(value),
+                    _ => ConfigChange::RecoveryScanIntervalMs(value),
+                })
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for ConfigKey {
+    type Error = String;
+
+    fn try_from(s: &str) -> std::result::Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|key| key.as_str() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown config key: {s} (expected one of {})",
+                    Self::ALL.map(Self::as_str).join(", ")
+                )
+            })
+    }
+}
+
+/// A checked new value for one [`ConfigKey`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "key", content = "value", rename_all = "snake_case")]
+pub enum ConfigChange {
+    MaxAgents(u32),
+    MaxImplementationAttempts(u32),
+    ClaimLabel(String),
+    SwarmStatus(SwarmStatus),
+    LeaseTtlMs(u32),
+    HeartbeatGraceMs(u32),
+    RecoveryScanIntervalMs(u32),
+}
+
+impl ConfigChange {
+    #[must_use]
+    pub const fn key(&self) -> ConfigKey {
+        match self {
+            Self::MaxAgents(_) => ConfigKey::MaxAgents,
+            Self::MaxImplementationAttempts(_) => ConfigKey::MaxImplementationAttempts,
+            Self::ClaimLabel(_) => ConfigKey::ClaimLabel,
+            Self::SwarmStatus(_) => ConfigKey::SwarmStatus,
+            Self::LeaseTtlMs(_) => ConfigKey::LeaseTtlMs,
+            Self::HeartbeatGraceMs(_) => ConfigKey::HeartbeatGraceMs,
+            Self::RecoveryScanIntervalMs(_) => ConfigKey::RecoveryScanIntervalMs,
+        }
+    }
+
+    pub fn apply(self, config: &mut SwarmConfig) {
+        match self {
+            Self::MaxAgents(value) => config.max_agents = value,
+            Self::MaxImplementationAttempts(value) => config.max_implementation_attempts = value,
+            Self::ClaimLabel(value) => config.claim_label = value,
+            Self::SwarmStatus(value) => config.swarm_status = value,
+            Self::LeaseTtlMs(value) => config.lease_ttl_ms = value,
+            Self::HeartbeatGraceMs(value) => config.heartbeat_grace_ms = value,
+            Self::RecoveryScanIntervalMs(value) => config.recovery_scan_interval_ms = value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigChange, ConfigKey};
+    use crate::types::SwarmStatus;
+
+    #[test]
+    fn given_config_values_when_parsing_then_only_values_within_bounds_pass() {
+        assert_eq!(
+            ConfigKey::LeaseTtlMs.parse_value("60000"),
+            Ok(ConfigChange::LeaseTtlMs(60_000))
+        );
+        assert!(ConfigKey::LeaseTtlMs.parse_value("9999").is_err());
+        assert!(ConfigKey::MaxAgents.parse_value("0").is_err());
+        assert!(ConfigKey::MaxAgents.parse_value("-3").is_err());
+        assert!(ConfigKey::ClaimLabel.parse_value("two words").is_err());
+        assert_eq!(
+            ConfigKey::SwarmStatus.parse_value("paused"),
+            Ok(ConfigChange::SwarmStatus(SwarmStatus::Paused))
+        );
+        assert!(ConfigKey::SwarmStatus.parse_value("Paused").is_err());
+    }
+
+    #[test]
+    fn given_every_config_key_when_round_tripping_its_name_then_the_key_is_returned() {
+        for key in ConfigKey::ALL {
+            assert_eq!(ConfigKey::try_from(key.as_str()), Ok(key));
+        }
+        assert!(ConfigKey::try_from("repo_id").is_err());
+    }
+}
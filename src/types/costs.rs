@@ -0,0 +1,292 @@
+//! Cost accounting over recorded token usage and stage run time.
+//!
+//! `swarm costs` loads [`TokenUsageRecord`]s and per-stage compute time for a
+//! window and folds them into a [`CostReport`], pricing tokens with the
+//! per-model rates in [`CostPricing`].
+
+use super::budget::{TokenUsage, TokenUsageRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Key used for usage that names no bead, stage or model.
+pub const UNATTRIBUTED_COST_KEY: &str = "unattributed";
+
+/// Price of one model, in US dollars per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelPricing {
+    pub input_usd_per_mtok: f64,
+    pub output_usd_per_mtok: f64,
+}
+
+impl ModelPricing {
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn cost_usd(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64).mul_add(
+            self.input_usd_per_mtok,
+            usage.output_tokens as f64 * self.output_usd_per_mtok,
+        ) / 1_000_000.0
+    }
+}
+
+/// Per-model pricing, read from `SWARM_MODEL_PRICING` or passed inline as
+/// `pricing`. `default` prices models not listed; without it their tokens
+/// are reported as unpriced.
+///
+/// ```json
+/// {"models": {"claude-sonnet": {"input_usd_per_mtok": 3.0, "output_usd_per_mtok": 15.0}},
+///  "default": {"input_usd_per_mtok": 1.0, "output_usd_per_mtok": 5.0}}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CostPricing {
+    pub models: BTreeMap<String, ModelPricing>,
+    pub default: Option<ModelPricing>,
+}
+
+impl CostPricing {
+    /// Rejects negative or non-finite rates.
+    ///
+    /// # Errors
+    /// Returns a message naming the first bad rate.
+    pub fn validate(&self) -> Result<(), String> {
+        self.models
+            .iter()
+            .map(|(model, pricing)| (model.as_str(), pricing))
+            .chain(self.default.iter().map(|pricing| ("default", pricing)))
+            .find(|(_, pricing)| {
+                [pricing.input_usd_per_mtok, pricing.output_usd_per_mtok]
+                    .iter()
+                    .any(|rate| !rate.is_finite() || *rate < 0.0)
+            })
+            .map_or(Ok(()), |(model, _)| {
+                Err(format!(
+                    "pricing for {model} must be finite and non-negative"
+                ))
+            })
+    }
+
+    #[must_use]
+    pub fn for_model(&self, model: Option<&str>) -> Option<&ModelPricing> {
+        model
+            .and_then(|model| self.models.get(model))
+            .or(self.default.as_ref())
+    }
+}
+
+/// Wall-clock time finished stage runs took, grouped by bead, agent and stage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageCompute {
+    pub bead_id: String,
+    pub agent_id: u32,
+    pub stage: String,
+    pub runs: u32,
+    pub compute_ms: u64,
+}
+
+/// Spend attributed to one bead, agent or stage.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostLine {
+    pub key: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    /// Tokens from models with no price; not included in `cost_usd`.
+    pub unpriced_tokens: u64,
+    pub stage_runs: u32,
+    pub compute_ms: u64,
+}
+
+impl CostLine {
+    fn keyed(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            ..Self::default()
+        }
+    }
+
+    fn add_usage(&mut self, usage: &TokenUsage, cost_usd: Option<f64>) {
+        self.input_tokens = self.input_tokens.saturating_add(usage.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(usage.output_tokens);
+        match cost_usd {
+            Some(cost) => self.cost_usd += cost,
+            None => {
+                self.unpriced_tokens = self.unpriced_tokens.saturating_add(usage.total_tokens());
+            }
+        }
+    }
+
+    const fn add_compute(&mut self, compute: &StageCompute) {
+        self.stage_runs = self.stage_runs.saturating_add(compute.runs);
+        self.compute_ms = self.compute_ms.saturating_add(compute.compute_ms);
+    }
+}
+
+/// Token spend and compute time for a window, totalled and broken down by
+/// bead, agent and stage. Each breakdown is ordered by cost, highest first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostReport {
+    pub total: CostLine,
+    pub by_bead: Vec<CostLine>,
+    pub by_agent: Vec<CostLine>,
+    pub by_stage: Vec<CostLine>,
+    /// Models that had usage but no price.
+    pub unpriced_models: Vec<String>,
+}
+
+impl CostReport {
+    #[must_use]
+    pub fn build(
+        usage: &[TokenUsageRecord],
+        compute: &[StageCompute],
+        pricing: &CostPricing,
+    ) -> Self {
+        let mut total = CostLine::keyed("total");
+        let mut by_bead = BTreeMap::new();
+        let mut by_agent = BTreeMap::new();
+        let mut by_stage = BTreeMap::new();
+        let mut unpriced_models = BTreeSet::new();
+
+        for record in usage {
+            let tokens = record.usage();
+            let model = record.model.as_deref();
+            let cost = pricing
+                .for_model(model)
+                .map(|price| price.cost_usd(&tokens));
+            if cost.is_none() {
+                unpriced_models.insert(model.unwrap_or(UNATTRIBUTED_COST_KEY).to_string());
+            }
+            total.add_usage(&tokens, cost);
+            line(&mut by_bead, record.bead_id.as_deref()).add_usage(&tokens, cost);
+            line(&mut by_agent, Some(&record.agent_id)).add_usage(&tokens, cost);
+            line(&mut by_stage, record.stage.as_deref()).add_usage(&tokens, cost);
+        }
+
+        for run in compute {
+            total.add_compute(run);
+            line(&mut by_bead, Some(&run.bead_id)).add_compute(run);
+            line(&mut by_agent, Some(&run.agent_id.to_string())).add_compute(run);
+            line(&mut by_stage, Some(&run.stage)).add_compute(run);
+        }
+
+        Self {
+            total,
+            by_bead: by_cost(by_bead),
+            by_agent: by_cost(by_agent),
+            by_stage: by_cost(by_stage),
+            unpriced_models: unpriced_models.into_iter().collect(),
+        }
+    }
+}
+
+fn line<'a>(lines: &'a mut BTreeMap<String, CostLine>, key: Option<&str>) -> &'a mut CostLine {
+    let key = key.unwrap_or(UNATTRIBUTED_COST_KEY);
+    lines
+        .entry(key.to_string())
+        .or_insert_with(|| CostLine::keyed(key))
+}
+
+fn by_cost(lines: BTreeMap<String, CostLine>) -> Vec<CostLine> {
+    let mut lines: Vec<CostLine> = lines.into_values().collect();
+    lines.sort_by(|a, b| {
+        b.cost_usd
+            .total_cmp(&a.cost_usd)
+            .then_with(|| b.compute_ms.cmp(&a.compute_ms))
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    lines
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn record(
+        bead: &str,
+        agent: u32,
+        stage: &str,
+        model: &str,
+        input: u64,
+        output: u64,
+    ) -> TokenUsageRecord {
+        TokenUsageRecord {
+            id: 0,
+            budget_id: None,
+            agent_id: agent.to_string(),
+            bead_id: Some(bead.to_string()),
+            stage: Some(stage.to_string()),
+            model: Some(model.to_string()),
+            input_tokens: input,
+            output_tokens: output,
+            description: None,
+            recorded_at: Utc::now(),
+        }
+    }
+
+    fn pricing() -> CostPricing {
+        CostPricing {
+            models: BTreeMap::from([(
+                "sonnet".to_string(),
+                ModelPricing {
+                    input_usd_per_mtok: 3.0,
+                    output_usd_per_mtok: 15.0,
+                },
+            )]),
+            default: None,
+        }
+    }
+
+    #[test]
+    fn given_priced_usage_when_building_report_then_costs_roll_up_per_bead_agent_and_stage() {
+        let usage = [
+            record("bd-1", 1, "implement", "sonnet", 1_000_000, 0),
+            record("bd-1", 1, "qa-enforcer", "sonnet", 0, 100_000),
+            record("bd-2", 2, "implement", "sonnet", 500_000, 0),
+        ];
+        let compute = [StageCompute {
+            bead_id: "bd-1".to_string(),
+            agent_id: 1,
+            stage: "implement".to_string(),
+            runs: 2,
+            compute_ms: 4_000,
+        }];
+
+        let report = CostReport::build(&usage, &compute, &pricing());
+
+        assert!((report.total.cost_usd - 6.0).abs() < 1e-9);
+        assert_eq!(report.by_bead[0].key, "bd-1");
+        assert!((report.by_bead[0].cost_usd - 4.5).abs() < 1e-9);
+        assert_eq!(report.by_bead[0].compute_ms, 4_000);
+        assert_eq!(report.by_agent[1].key, "2");
+        assert_eq!(report.by_stage[0].key, "implement");
+        assert_eq!(report.by_stage[0].stage_runs, 2);
+        assert!(report.unpriced_models.is_empty());
+    }
+
+    #[test]
+    fn given_unknown_model_without_default_when_building_report_then_tokens_are_unpriced() {
+        let usage = [record("bd-1", 1, "implement", "mystery", 10, 5)];
+
+        let report = CostReport::build(&usage, &[], &pricing());
+
+        assert_eq!(report.total.unpriced_tokens, 15);
+        assert!(report.total.cost_usd.abs() < f64::EPSILON);
+        assert_eq!(report.unpriced_models, vec!["mystery".to_string()]);
+    }
+
+    #[test]
+    fn given_negative_rate_when_validating_pricing_then_model_is_named() {
+        let mut pricing = pricing();
+        pricing.default = Some(ModelPricing {
+            input_usd_per_mtok: -1.0,
+            output_usd_per_mtok: 0.0,
+        });
+
+        let error = pricing.validate().unwrap_err();
+
+        assert!(error.contains("default"));
+    }
+}
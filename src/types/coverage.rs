@@ -0,0 +1,370 @@
+//! Coverage summaries uploaded per stage and their trend per bead.
+//!
+//! `swarm report-coverage` parses an lcov or Cobertura report down to a
+//! [`CoverageSummary`] and stores it as a `coverage_report` artifact. The
+//! `coverage` monitor view folds the stored [`CoverageSample`]s into one
+//! [`BeadCoverageTrend`] per bead, and `land` refuses beads whose latest
+//! summary is under `SWARM_MIN_COVERAGE`.
+
+use chrono::{DateTime, Utc};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverageFormat {
+    Lcov,
+    Cobertura,
+}
+
+impl CoverageFormat {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lcov => "lcov",
+            Self::Cobertura => "cobertura",
+        }
+    }
+
+    /// Cobertura reports are XML; anything else is read as lcov.
+    #[must_use]
+    pub fn detect(report: &str) -> Self {
+        if report.trim_start().starts_with('<') {
+            Self::Cobertura
+        } else {
+            Self::Lcov
+        }
+    }
+}
+
+impl TryFrom<&str> for CoverageFormat {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "lcov" => Ok(Self::Lcov),
+            "cobertura" => Ok(Self::Cobertura),
+            _ => Err(value.to_string()),
+        }
+    }
+}
+
+/// Line and branch totals of one coverage report.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoverageSummary {
+    pub format: CoverageFormat,
+    pub lines_covered: u64,
+    pub lines_total: u64,
+    pub line_percent: f64,
+    pub branches_covered: Option<u64>,
+    pub branches_total: Option<u64>,
+}
+
+impl CoverageSummary {
+    /// # Errors
+    /// Returns a message when the report covers no lines or claims more
+    /// covered lines than it has.
+    pub fn new(
+        format: CoverageFormat,
+        lines_covered: u64,
+        lines_total: u64,
+        branches: Option<(u64, u64)>,
+    ) -> Result<Self, String> {
+        if lines_total == 0 {
+            return Err("coverage report has no instrumented lines".to_string());
+        }
+        if lines_covered > lines_total {
+            return Err(format!(
+                "coverage report covers {lines_covered} of {lines_total} lines"
+            ));
+        }
+        Ok(Self {
+            format,
+            lines_covered,
+            lines_total,
+            line_percent: percent(lines_covered, lines_total),
+            branches_covered: branches.map(|(covered, _)| covered),
+            branches_total: branches.map(|(_, total)| total),
+        })
+    }
+
+    /// Whether line coverage reaches `min_percent`.
+    #[must_use]
+    pub fn meets(&self, min_percent: f64) -> bool {
+        self.line_percent >= min_percent
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn percent(covered: u64, total: u64) -> f64 {
+    (covered as f64 * 10_000.0 / total as f64).round() / 100.0
+}
+
+/// Parses `report` as `format`, or as the detected format when `None`.
+///
+/// # Errors
+/// Returns a message when the report is malformed or has no lines.
+pub fn parse_coverage(
+    report: &str,
+    format: Option<CoverageFormat>,
+) -> Result<CoverageSummary, String> {
+    match format.unwrap_or_else(|| CoverageFormat::detect(report)) {
+        CoverageFormat::Lcov => parse_lcov(report),
+        CoverageFormat::Cobertura => parse_cobertura(report),
+    }
+}
+
+#[derive(Default)]
+struct LcovRecord {
+    lines_found: Option<u64>,
+    lines_hit: Option<u64>,
+    da_total: u64,
+    da_hit: u64,
+    branches_found: Option<u64>,
+    branches_hit: Option<u64>,
+}
+
+/// Sums `LF`/`LH` (and `BRF`/`BRH`) over every record, counting `DA` lines
+/// for records that carry no `LF`/`LH` totals.
+///
+/// # Errors
+/// Returns a message naming the first unparsable count.
+pub fn parse_lcov(report: &str) -> Result<CoverageSummary, String> {
+    let mut records = Vec::new();
+    let mut record = LcovRecord::default();
+
+    for line in report.lines().map(str::trim) {
+        if line == "end_of_record" {
+            records.push(std::mem::take(&mut record));
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let count = || {
+            value
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| format!("invalid lcov {key} count: {value}"))
+        };
+        match key {
+            "LF" => record.lines_found = Some(count()?),
+            "LH" => record.lines_hit = Some(count()?),
+            "BRF" => record.branches_found = Some(count()?),
+            "BRH" => record.branches_hit = Some(count()?),
+            "DA" => {
+                let hits = value
+                    .split(',')
+                    .nth(1)
+                    .map(str::trim)
+                    .and_then(|hits| hits.parse::<u64>().ok())
+                    .ok_or_else(|| format!("invalid lcov DA line: {value}"))?;
+                record.da_total += 1;
+                record.da_hit += u64::from(hits > 0);
+            }
+            _ => {}
+        }
+    }
+    records.push(record);
+
+    let (lines_covered, lines_total, branches_covered, branches_total, has_branches) =
+        records.iter().fold(
+            (0_u64, 0_u64, 0_u64, 0_u64, false),
+            |(covered, total, br_covered, br_total, has_branches), record| {
+                let (hit, found) = match (record.lines_hit, record.lines_found) {
+                    (Some(hit), Some(found)) => (hit, found),
+                    _ => (record.da_hit, record.da_total),
+                };
+                (
+                    covered.saturating_add(hit),
+                    total.saturating_add(found),
+                    br_covered.saturating_add(record.branches_hit.unwrap_or(0)),
+                    br_total.saturating_add(record.branches_found.unwrap_or(0)),
+                    has_branches || record.branches_found.is_some(),
+                )
+            },
+        );
+
+    CoverageSummary::new(
+        CoverageFormat::Lcov,
+        lines_covered,
+        lines_total,
+        has_branches.then_some((branches_covered, branches_total)),
+    )
+}
+
+/// Reads the totals on the root `<coverage>` element of a Cobertura report.
+///
+/// # Errors
+/// Returns a message when the XML is malformed or the root element lacks
+/// `lines-valid`/`lines-covered`.
+pub fn parse_cobertura(report: &str) -> Result<CoverageSummary, String> {
+    let mut reader = Reader::from_str(report);
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(tag) | Event::Empty(tag)) if tag.name().as_ref() == b"coverage" => {
+                let attribute = |key: &[u8]| -> Result<Option<u64>, String> {
+                    tag.attributes()
+                        .flatten()
+                        .find(|attribute| attribute.key.as_ref() == key)
+                        .map(|attribute| {
+                            let value = String::from_utf8_lossy(&attribute.value).into_owned();
+                            value.trim().parse::<u64>().map_err(|_| {
+                                format!(
+                                    "invalid cobertura {}: {value}",
+                                    String::from_utf8_lossy(key)
+                                )
+                            })
+                        })
+                        .transpose()
+                };
+                let lines_total = attribute(b"lines-valid")?
+                    .ok_or_else(|| "cobertura report has no lines-valid total".to_string())?;
+                let lines_covered = attribute(b"lines-covered")?
+                    .ok_or_else(|| "cobertura report has no lines-covered total".to_string())?;
+                let branches = attribute(b"branches-covered")?.zip(attribute(b"branches-valid")?);
+                return CoverageSummary::new(
+                    CoverageFormat::Cobertura,
+                    lines_covered,
+                    lines_total,
+                    branches,
+                );
+            }
+            Ok(Event::Eof) => return Err("cobertura report has no <coverage> element".to_string()),
+            Ok(_) => {}
+            Err(e) => {
+                return Err(format!(
+                    "Invalid Cobertura XML at byte {}: {e}",
+                    reader.buffer_position()
+                ))
+            }
+        }
+    }
+}
+
+/// One stored coverage summary, as read back from `stage_artifacts`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoverageSample {
+    pub bead_id: String,
+    pub stage: String,
+    pub line_percent: f64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Coverage of one bead from its first upload to its latest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BeadCoverageTrend {
+    pub bead_id: String,
+    pub samples: usize,
+    pub first_percent: f64,
+    pub latest_percent: f64,
+    pub delta: f64,
+    pub latest_stage: String,
+    pub latest_at: DateTime<Utc>,
+}
+
+impl BeadCoverageTrend {
+    /// Groups `samples` by bead, ordering each bead's samples by time.
+    #[must_use]
+    pub fn from_samples(samples: &[CoverageSample]) -> Vec<Self> {
+        let mut by_bead: BTreeMap<&str, Vec<&CoverageSample>> = BTreeMap::new();
+        for sample in samples {
+            by_bead
+                .entry(sample.bead_id.as_str())
+                .or_default()
+                .push(sample);
+        }
+
+        by_bead
+            .into_iter()
+            .filter_map(|(bead_id, mut bead_samples)| {
+                bead_samples.sort_by_key(|sample| sample.recorded_at);
+                let first = bead_samples.first()?;
+                let latest = bead_samples.last()?;
+                Some(Self {
+                    bead_id: bead_id.to_string(),
+                    samples: bead_samples.len(),
+                    first_percent: first.line_percent,
+                    latest_percent: latest.line_percent,
+                    delta: ((latest.line_percent - first.line_percent) * 100.0).round() / 100.0,
+                    latest_stage: latest.stage.clone(),
+                    latest_at: latest.recorded_at,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn given_lcov_records_when_parsing_then_totals_are_summed() {
+        let report = "TN:\nSF:src/a.rs\nDA:1,1\nDA:2,0\nLF:2\nLH:1\nBRF:4\nBRH:3\nend_of_record\n\
+                      SF:src/b.rs\nDA:1,3\nDA:2,1\nDA:3,0\nend_of_record\n";
+
+        let summary = parse_coverage(report, None).expect("lcov parses");
+
+        assert_eq!(summary.format, CoverageFormat::Lcov);
+        assert_eq!(summary.lines_covered, 3);
+        assert_eq!(summary.lines_total, 5);
+        assert!((summary.line_percent - 60.0).abs() < f64::EPSILON);
+        assert_eq!(summary.branches_covered, Some(3));
+        assert_eq!(summary.branches_total, Some(4));
+    }
+
+    #[test]
+    fn given_cobertura_report_when_parsing_then_root_totals_are_used() {
+        let report = r#"<?xml version="1.0"?>
+<coverage line-rate="0.75" lines-valid="200" lines-covered="150" branches-valid="10" branches-covered="5">
+  <packages/>
+</coverage>"#;
+
+        let summary = parse_coverage(report, None).expect("cobertura parses");
+
+        assert_eq!(summary.format, CoverageFormat::Cobertura);
+        assert_eq!(summary.lines_covered, 150);
+        assert_eq!(summary.lines_total, 200);
+        assert!(summary.meets(75.0));
+        assert!(!summary.meets(75.5));
+        assert_eq!(summary.branches_total, Some(10));
+    }
+
+    #[test]
+    fn given_report_without_lines_when_parsing_then_it_is_rejected() {
+        assert!(parse_lcov("TN:\nend_of_record\n").is_err());
+        assert!(parse_cobertura("<coverage line-rate=\"1\"/>").is_err());
+        assert!(parse_lcov("LF:x\n").is_err());
+    }
+
+    #[test]
+    fn given_samples_for_two_beads_when_folding_then_delta_is_latest_minus_first() {
+        let now = Utc::now();
+        let sample = |bead: &str, percent: f64, mins: i64| CoverageSample {
+            bead_id: bead.to_string(),
+            stage: "qa-enforcer".to_string(),
+            line_percent: percent,
+            recorded_at: now + Duration::minutes(mins),
+        };
+        let samples = vec![
+            sample("swm-1", 82.5, 10),
+            sample("swm-1", 70.0, 0),
+            sample("swm-2", 90.0, 0),
+        ];
+
+        let trends = BeadCoverageTrend::from_samples(&samples);
+
+        assert_eq!(trends.len(), 2);
+        assert_eq!(trends[0].bead_id, "swm-1");
+        assert_eq!(trends[0].samples, 2);
+        assert!((trends[0].first_percent - 70.0).abs() < f64::EPSILON);
+        assert!((trends[0].delta - 12.5).abs() < f64::EPSILON);
+        assert!(trends[1].delta.abs() < f64::EPSILON);
+    }
+}
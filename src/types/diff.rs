@@ -0,0 +1,221 @@
+//! Patch sets stored as `diff` artifacts.
+//!
+//! `swarm diff put` keeps the patch as the artifact content and a
+//! [`DiffStat`] as its metadata; `diff get` and deep resume contexts hand the
+//! latest one back as a [`BeadDiff`] so a resuming agent can re-apply it.
+
+use super::artifacts::{ArtifactType, StageArtifact};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Lines one file gains and loses in a patch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffFileStat {
+    pub path: String,
+    pub insertions: u32,
+    pub deletions: u32,
+}
+
+/// What a patch touches, kept as the metadata of a `diff` artifact.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffStat {
+    /// Revision the patch applies on top of, when the agent gave one.
+    pub base_revision: Option<String>,
+    pub files: Vec<DiffFileStat>,
+    pub insertions: u32,
+    pub deletions: u32,
+}
+
+impl DiffStat {
+    /// Counts a unified diff, with or without `diff --git` headers, per file.
+    /// Hunk lengths from the `@@` headers decide which lines are content, so
+    /// a removed line that itself starts with `--` is not taken for a header.
+    #[must_use]
+    pub fn parse(diff: &str, base_revision: Option<String>) -> Self {
+        let mut files: Vec<DiffFileStat> = Vec::new();
+        let mut old_path: Option<String> = None;
+        let mut git_header = false;
+        let (mut old_left, mut new_left) = (0_u32, 0_u32);
+
+        for line in diff.lines() {
+            if old_left > 0 || new_left > 0 {
+                let Some(file) = files.last_mut() else {
+                    break;
+                };
+                match line.as_bytes().first() {
+                    Some(b'+') => {
+                        file.insertions += 1;
+                        new_left = new_left.saturating_sub(1);
+                    }
+                    Some(b'-') => {
+                        file.deletions += 1;
+                        old_left = old_left.saturating_sub(1);
+                    }
+                    Some(b'\\') => {}
+                    _ => {
+                        old_left = old_left.saturating_sub(1);
+                        new_left = new_left.saturating_sub(1);
+                    }
+                }
+                continue;
+            }
+
+            if let Some(paths) = line.strip_prefix("diff --git ") {
+                let path = paths
+                    .split_once(" b/")
+                    .map_or(paths, |(_, new)| new)
+                    .to_string();
+                files.push(DiffFileStat {
+                    path,
+                    insertions: 0,
+                    deletions: 0,
+                });
+                old_path = None;
+                git_header = true;
+            } else if let Some(path) = line.strip_prefix("--- ") {
+                old_path = Some(header_path(path));
+            } else if let Some(path) = line.strip_prefix("+++ ") {
+                let new_path = header_path(path);
+                let path = if new_path == "/dev/null" {
+                    old_path.take().unwrap_or(new_path)
+                } else {
+                    new_path
+                };
+                match files.last_mut() {
+                    Some(file) if git_header => file.path = path,
+                    _ => files.push(DiffFileStat {
+                        path,
+                        insertions: 0,
+                        deletions: 0,
+                    }),
+                }
+                old_path = None;
+                git_header = false;
+            } else if let Some((old, new)) = hunk_lengths(line) {
+                old_left = old;
+                new_left = new;
+            }
+        }
+
+        Self {
+            base_revision,
+            insertions: files.iter().map(|file| file.insertions).sum(),
+            deletions: files.iter().map(|file| file.deletions).sum(),
+            files,
+        }
+    }
+}
+
+/// The path in a `---`/`+++` header, without the `a/`/`b/` prefix or a
+/// trailing timestamp.
+fn header_path(raw: &str) -> String {
+    let path = raw.split('\t').next().unwrap_or(raw).trim_end();
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// Old and new line counts from a `@@ -a,b +c,d @@` header; a missing count
+/// means one line.
+fn hunk_lengths(line: &str) -> Option<(u32, u32)> {
+    let ranges = line.strip_prefix("@@ -")?;
+    let (old, rest) = ranges.split_once(" +")?;
+    let (new, _) = rest.split_once(" @@")?;
+    let count = |range: &str| match range.split_once(',') {
+        Some((_, count)) => count.parse::<u32>().ok(),
+        None => range.parse::<u32>().ok().map(|_| 1),
+    };
+    Some((count(old)?, count(new)?))
+}
+
+/// A bead's stored patch with its stat, as `diff get` and resume contexts
+/// return it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeadDiff {
+    pub artifact_id: i64,
+    pub stage_history_id: i64,
+    pub created_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub stat: DiffStat,
+    pub patch: String,
+}
+
+impl BeadDiff {
+    /// `None` unless `artifact` is a `diff`. The stat comes from its metadata,
+    /// or is counted from the patch when it was stored without one.
+    #[must_use]
+    pub fn from_artifact(artifact: &StageArtifact) -> Option<Self> {
+        if artifact.artifact_type != ArtifactType::Diff {
+            return None;
+        }
+        let stat = artifact
+            .metadata
+            .clone()
+            .and_then(|metadata| serde_json::from_value::<DiffStat>(metadata).ok())
+            .unwrap_or_else(|| DiffStat::parse(&artifact.content, None));
+        Some(Self {
+            artifact_id: artifact.id,
+            stage_history_id: artifact.stage_history_id,
+            created_at: artifact.created_at,
+            stat,
+            patch: artifact.content.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DiffFileStat, DiffStat};
+
+    #[test]
+    fn given_git_patch_when_parsing_then_each_file_is_counted_by_its_hunks() {
+        let patch = "diff --git a/src/lib.rs b/src/lib.rs\n\
+index 1111111..2222222 100644\n\
+--- a/src/lib.rs\n\
++++ b/src/lib.rs\n\
+@@ -1,3 +1,3 @@\n\
+ fn a() {}\n\
+--- a comment that was removed\n\
++// a comment that was added\n\
+ fn b() {}\n\
+diff --git a/NEW.md b/NEW.md\n\
+new file mode 100644\n\
+--- /dev/null\n\
++++ b/NEW.md\n\
+@@ -0,0 +1,2 @@\n\
++# New\n\
++text\n";
+
+        let stat = DiffStat::parse(patch, Some("abc1234".to_string()));
+
+        assert_eq!(
+            stat.files,
+            vec![
+                DiffFileStat {
+                    path: "src/lib.rs".to_string(),
+                    insertions: 1,
+                    deletions: 1,
+                },
+                DiffFileStat {
+                    path: "NEW.md".to_string(),
+                    insertions: 2,
+                    deletions: 0,
+                },
+            ]
+        );
+        assert_eq!((stat.insertions, stat.deletions), (3, 1));
+        assert_eq!(stat.base_revision.as_deref(), Some("abc1234"));
+    }
+
+    #[test]
+    fn given_plain_unified_diff_of_a_deleted_file_when_parsing_then_old_path_is_kept() {
+        let patch = "--- old.txt\t2024-01-01\n+++ /dev/null\n@@ -1 +0,0 @@\n-gone\n";
+
+        let stat = DiffStat::parse(patch, None);
+
+        assert_eq!(stat.files.len(), 1);
+        assert_eq!(stat.files[0].path, "old.txt");
+        assert_eq!(stat.deletions, 1);
+    }
+}
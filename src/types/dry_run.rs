@@ -0,0 +1,172 @@
+//! Dry-run previews. A dry run reads current state and writes nothing; it
+//! reports the rows and commands the real run would touch and the conflicts
+//! that would stop it.
+
+use serde::{Deserialize, Serialize};
+
+/// Why the real run would not go through as planned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DryRunConflictKind {
+    AgentNotRegistered,
+    AgentBusy,
+    AgentQuarantined,
+    BeadClaimed,
+    BeadNotOpen,
+    NoRecommendation,
+    /// State the plan depends on could not be read, so the plan is partial.
+    StateUnavailable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DryRunConflict {
+    pub kind: DryRunConflictKind,
+    pub msg: String,
+}
+
+impl DryRunConflict {
+    #[must_use]
+    pub fn new(kind: DryRunConflictKind, msg: impl Into<String>) -> Self {
+        Self {
+            kind,
+            msg: msg.into(),
+        }
+    }
+}
+
+/// The coordinator rows `assign` would read and change for one agent and
+/// bead.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssignPreview {
+    /// `None` when the agent is not registered.
+    pub agent_status: Option<String>,
+    pub agent_bead_id: Option<String>,
+    pub quarantine_reason: Option<String>,
+    /// The agent holding an in-progress claim on the bead.
+    pub claimed_by: Option<u32>,
+    /// `None` when the bead is not in the backlog yet.
+    pub backlog_status: Option<String>,
+}
+
+impl AssignPreview {
+    /// What would make `assign` of `bead_id` to `agent_id` fail.
+    #[must_use]
+    pub fn conflicts(&self, agent_id: u32, bead_id: &str) -> Vec<DryRunConflict> {
+        let mut conflicts = Vec::new();
+        match self.agent_status.as_deref() {
+            None => conflicts.push(DryRunConflict::new(
+                DryRunConflictKind::AgentNotRegistered,
+                format!("Agent {agent_id} is not registered"),
+            )),
+            Some(status) if status != "idle" || self.agent_bead_id.is_some() => {
+                conflicts.push(DryRunConflict::new(
+                    DryRunConflictKind::AgentBusy,
+                    format!(
+                        "Agent {agent_id} is {status}{}",
+                        self.agent_bead_id
+                            .as_deref()
+                            .map_or_else(String::new, |bead| format!(" on {bead}"))
+                    ),
+                ));
+            }
+            Some(_) => {}
+        }
+        if let Some(reason) = &self.quarantine_reason {
+            conflicts.push(DryRunConflict::new(
+                DryRunConflictKind::AgentQuarantined,
+                format!("Agent {agent_id} is quarantined: {reason}"),
+            ));
+        }
+        if let Some(holder) = self.claimed_by {
+            conflicts.push(DryRunConflict::new(
+                DryRunConflictKind::BeadClaimed,
+                format!("Bead {bead_id} is already claimed by agent {holder}"),
+            ));
+        }
+        conflicts
+    }
+}
+
+/// Agent ids `init-db` would keep, prune and add to reach `count` idle,
+/// unassigned agents.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedPlan {
+    pub keep: Vec<u32>,
+    pub prune: Vec<u32>,
+    pub add: Vec<u32>,
+}
+
+impl SeedPlan {
+    /// Mirrors seeding: the `count` highest idle, unassigned agents stay and
+    /// the rest are pruned, then the lowest free ids are added until `count`
+    /// are idle.
+    #[must_use]
+    pub fn new(count: u32, existing: &[u32], idle_unassigned: &[u32]) -> Self {
+        let mut idle = idle_unassigned.to_vec();
+        idle.sort_unstable_by(|a, b| b.cmp(a));
+        let kept_idle = idle.len().min(usize::try_from(count).unwrap_or(usize::MAX));
+        let mut prune = idle.split_off(kept_idle);
+        prune.sort_unstable();
+        let keep = existing
+            .iter()
+            .copied()
+            .filter(|id| !prune.contains(id))
+            .collect::<Vec<_>>();
+        let missing = usize::try_from(count)
+            .unwrap_or(usize::MAX)
+            .saturating_sub(kept_idle);
+        let add = (1..=u32::MAX)
+            .filter(|id| !keep.contains(id))
+            .take(missing)
+            .collect();
+        Self { keep, prune, add }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_busy_agent_and_claimed_bead_when_previewing_then_both_conflicts_are_reported() {
+        let preview = AssignPreview {
+            agent_status: Some("working".to_string()),
+            agent_bead_id: Some("bd-1".to_string()),
+            quarantine_reason: None,
+            claimed_by: Some(4),
+            backlog_status: Some("in_progress".to_string()),
+        };
+
+        let kinds = preview
+            .conflicts(2, "bd-9")
+            .into_iter()
+            .map(|conflict| conflict.kind)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                DryRunConflictKind::AgentBusy,
+                DryRunConflictKind::BeadClaimed
+            ]
+        );
+        assert!(AssignPreview {
+            agent_status: Some("idle".to_string()),
+            ..AssignPreview::default()
+        }
+        .conflicts(2, "bd-9")
+        .is_empty());
+    }
+
+    #[test]
+    fn given_surplus_and_missing_idle_agents_when_planning_seed_then_it_matches_seeding() {
+        let surplus = SeedPlan::new(2, &[1, 2, 3, 4], &[1, 3, 4]);
+        assert_eq!(surplus.prune, vec![1]);
+        assert_eq!(surplus.keep, vec![2, 3, 4]);
+        assert!(surplus.add.is_empty());
+
+        let short = SeedPlan::new(3, &[2], &[]);
+        assert_eq!(short.add, vec![1, 3, 4]);
+        assert!(short.prune.is_empty());
+    }
+}
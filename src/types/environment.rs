@@ -0,0 +1,144 @@
+//! Toolchain fingerprints taken when a stage starts.
+//!
+//! Each stage run stores an [`EnvironmentFingerprint`] under
+//! `stage_history.metadata.environment`. `env-diff` compares two attempts
+//! with [`EnvironmentFingerprint::diff`] to explain a stage that passed on
+//! one attempt and failed on another.
+
+use super::provenance::ToolVersion;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Tools whose `--version` line goes into every fingerprint.
+pub const FINGERPRINT_TOOLS: [&str; 4] = ["rustc", "cargo", "moon", "br"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentFingerprint {
+    pub os: String,
+    pub arch: String,
+    pub tools: Vec<ToolVersion>,
+}
+
+/// One fingerprint entry that differs between two attempts. `None` means the
+/// entry was missing or the tool was not installed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentChange {
+    pub key: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// The fingerprint stored for one stage run; `environment` is `None` for
+/// runs recorded before fingerprinting or whose probe failed to store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageEnvironment {
+    pub stage_history_id: i64,
+    pub stage: String,
+    pub attempt: u32,
+    pub started_at: DateTime<Utc>,
+    pub environment: Option<EnvironmentFingerprint>,
+}
+
+impl EnvironmentFingerprint {
+    /// Entries that differ from `self` to `other`: `os`, `arch`, then each
+    /// tool by name in the order the fingerprints list them.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> Vec<EnvironmentChange> {
+        let platform = [
+            ("os", &self.os, &other.os),
+            ("arch", &self.arch, &other.arch),
+        ]
+        .into_iter()
+        .filter(|(_, from, to)| from != to)
+        .map(|(key, from, to)| EnvironmentChange {
+            key: key.to_string(),
+            from: Some(from.clone()),
+            to: Some(to.clone()),
+        });
+
+        let mut names = self
+            .tools
+            .iter()
+            .chain(&other.tools)
+            .map(|tool| tool.name.as_str())
+            .collect::<Vec<_>>();
+        let mut seen = std::collections::HashSet::new();
+        names.retain(|name| seen.insert(*name));
+        let tools = names.into_iter().filter_map(|name| {
+            let from = self.tool_version(name);
+            let to = other.tool_version(name);
+            (from != to).then(|| EnvironmentChange {
+                key: name.to_string(),
+                from,
+                to,
+            })
+        });
+
+        platform.chain(tools).collect()
+    }
+
+    fn tool_version(&self, name: &str) -> Option<String> {
+        self.tools
+            .iter()
+            .find(|tool| tool.name == name)
+            .and_then(|tool| tool.version.clone())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(os: &str, tools: &[(&str, Option<&str>)]) -> EnvironmentFingerprint {
+        EnvironmentFingerprint {
+            os: os.to_string(),
+            arch: "x86_64".to_string(),
+            tools: tools
+                .iter()
+                .map(|(name, version)| ToolVersion {
+                    name: (*name).to_string(),
+                    version: version.map(str::to_string),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn given_same_fingerprint_when_diffing_then_nothing_changes() {
+        let env = fingerprint("linux", &[("rustc", Some("rustc 1.84.0"))]);
+
+        assert!(env.diff(&env.clone()).is_empty());
+    }
+
+    #[test]
+    fn given_upgraded_and_missing_tools_when_diffing_then_each_change_is_listed() {
+        let passing = fingerprint(
+            "linux",
+            &[
+                ("rustc", Some("rustc 1.84.0")),
+                ("moon", Some("moon 1.30.0")),
+            ],
+        );
+        let failing = fingerprint(
+            "macos",
+            &[
+                ("rustc", Some("rustc 1.85.0")),
+                ("moon", None),
+                ("br", Some("br 0.4")),
+            ],
+        );
+
+        let changes = passing.diff(&failing);
+
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| change.key.as_str())
+                .collect::<Vec<_>>(),
+            vec!["os", "rustc", "moon", "br"]
+        );
+        assert_eq!(changes[2].to, None);
+        assert_eq!(changes[3].from, None);
+    }
+}
@@ -0,0 +1,269 @@
+//! Escalations for stuck beads: an agent left waiting on a bead too long, or
+//! a bead about to run out of implementation attempts. Each reason opens at
+//! most one escalation per bead, which resolves once the bead is unstuck.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Why a bead was escalated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscalationReason {
+    Waiting,
+    Attempts,
+}
+
+impl EscalationReason {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Waiting => "waiting",
+            Self::Attempts => "attempts",
+        }
+    }
+}
+
+impl TryFrom<&str> for EscalationReason {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "waiting" => Ok(Self::Waiting),
+            "attempts" => Ok(Self::Attempts),
+            _ => Err(format!("Unknown escalation reason: {value}")),
+        }
+    }
+}
+
+/// Escalates a bead whose agent has been `waiting` for more than `hours`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WaitingRule {
+    pub hours: f64,
+}
+
+/// Escalates a bead once its implementation attempt reaches the swarm's
+/// maximum minus `remaining`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AttemptsRule {
+    pub remaining: u32,
+}
+
+/// Escalation rules, read from `SWARM_ESCALATION_RULES`. A rule set to
+/// `null` is off; an omitted setting keeps its default.
+///
+/// ```json
+/// {"waiting": {"hours": 4.0},
+///  "attempts": {"remaining": 1},
+///  "senior_agents": [11, 12],
+///  "broadcast": true,
+///  "webhook_url": "https://hooks.example.com/swarm"}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EscalationRules {
+    pub waiting: Option<WaitingRule>,
+    pub attempts: Option<AttemptsRule>,
+    /// Agents an escalated bead is handed to, first idle one wins. Empty
+    /// leaves the bead with its agent.
+    pub senior_agents: Vec<u32>,
+    /// Announces each new escalation to every agent on the `escalation`
+    /// topic.
+    pub broadcast: bool,
+    /// Receives a JSON POST each time an escalation opens or resolves.
+    pub webhook_url: Option<String>,
+}
+
+impl Default for EscalationRules {
+    fn default() -> Self {
+        Self {
+            waiting: Some(WaitingRule { hours: 4.0 }),
+            attempts: Some(AttemptsRule { remaining: 1 }),
+            senior_agents: Vec::new(),
+            broadcast: true,
+            webhook_url: None,
+        }
+    }
+}
+
+/// An agent's hold on a bead, as the rules see it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EscalationCandidate {
+    pub bead_id: String,
+    pub agent_id: u32,
+    pub agent_status: String,
+    /// When the agent's state last changed.
+    pub since: DateTime<Utc>,
+    pub implementation_attempt: u32,
+    pub max_implementation_attempts: u32,
+}
+
+/// A rule that holds for a bead right now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscalationTrigger {
+    pub bead_id: String,
+    pub agent_id: u32,
+    pub reason: EscalationReason,
+    pub detail: String,
+}
+
+impl EscalationRules {
+    /// Rejects rules that can never trigger or always trigger, senior pools
+    /// with agent 0, and webhooks that are not HTTP(S).
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(rule) = self.waiting.filter(|rule| rule.hours <= 0.0) {
+            return Err(format!(
+                "waiting.hours must be greater than 0, got {}",
+                rule.hours
+            ));
+        }
+        if self.senior_agents.contains(&0) {
+            return Err("senior_agents must be agent ids of at least 1".to_string());
+        }
+        if let Some(url) = self
+            .webhook_url
+            .as_deref()
+            .filter(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+        {
+            return Err(format!("webhook_url must be http(s), got {url}"));
+        }
+        Ok(())
+    }
+
+    /// Every enabled rule that holds for `candidate` at `now`.
+    #[must_use]
+    pub fn triggers(
+        &self,
+        candidate: &EscalationCandidate,
+        now: DateTime<Utc>,
+    ) -> Vec<EscalationTrigger> {
+        let trigger = |reason, detail| EscalationTrigger {
+            bead_id: candidate.bead_id.clone(),
+            agent_id: candidate.agent_id,
+            reason,
+            detail,
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let waited_hours = (now - candidate.since).num_seconds() as f64 / 3600.0;
+        let waiting = self
+            .waiting
+            .filter(|_| candidate.agent_status == "waiting")
+            .filter(|rule| waited_hours > rule.hours)
+            .map(|rule| {
+                trigger(
+                    EscalationReason::Waiting,
+                    format!(
+                        "agent {} waiting {waited_hours:.1}h (limit {}h)",
+                        candidate.agent_id, rule.hours
+                    ),
+                )
+            });
+        let attempts = self
+            .attempts
+            .filter(|_| candidate.max_implementation_attempts > 0)
+            .filter(|rule| {
+                candidate.implementation_attempt
+                    >= candidate
+                        .max_implementation_attempts
+                        .saturating_sub(rule.remaining)
+            })
+            .map(|_| {
+                trigger(
+                    EscalationReason::Attempts,
+                    format!(
+                        "attempt {} of {}",
+                        candidate.implementation_attempt, candidate.max_implementation_attempts
+                    ),
+                )
+            });
+        [waiting, attempts].into_iter().flatten().collect()
+    }
+}
+
+/// One escalation of one bead, from the trigger to resolution.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Escalation {
+    pub id: i64,
+    pub bead_id: String,
+    pub reason: EscalationReason,
+    pub detail: String,
+    /// Agent holding the bead when it was escalated.
+    pub agent_id: Option<u32>,
+    /// Senior agent the bead was handed to, if any.
+    pub assigned_to: Option<u32>,
+    pub escalated_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn candidate(status: &str, hours_ago: i64, attempt: u32) -> EscalationCandidate {
+        EscalationCandidate {
+            bead_id: "bd-abc".to_string(),
+            agent_id: 3,
+            agent_status: status.to_string(),
+            since: Utc::now() - Duration::hours(hours_ago),
+            implementation_attempt: attempt,
+            max_implementation_attempts: 3,
+        }
+    }
+
+    fn reasons(rules: &EscalationRules, candidate: &EscalationCandidate) -> Vec<EscalationReason> {
+        rules
+            .triggers(candidate, Utc::now())
+            .into_iter()
+            .map(|trigger| trigger.reason)
+            .collect()
+    }
+
+    #[test]
+    fn given_long_wait_and_last_attempt_when_evaluating_then_both_rules_trigger() {
+        let rules = EscalationRules::default();
+
+        assert_eq!(
+            reasons(&rules, &candidate("waiting", 5, 2)),
+            [EscalationReason::Waiting, EscalationReason::Attempts]
+        );
+    }
+
+    #[test]
+    fn given_short_wait_or_working_agent_when_evaluating_then_nothing_triggers() {
+        let rules = EscalationRules::default();
+
+        assert!(reasons(&rules, &candidate("waiting", 1, 0)).is_empty());
+        assert!(reasons(&rules, &candidate("working", 9, 1)).is_empty());
+    }
+
+    #[test]
+    fn given_partial_rules_json_when_parsing_then_null_disables_and_omitted_defaults() {
+        let rules: EscalationRules =
+            serde_json::from_str(r#"{"attempts": null, "senior_agents": [9]}"#)
+                .expect("valid rules");
+
+        assert_eq!(rules.attempts, None);
+        assert_eq!(rules.waiting, EscalationRules::default().waiting);
+        assert_eq!(rules.senior_agents, [9]);
+        assert_eq!(rules.validate(), Ok(()));
+    }
+
+    #[test]
+    fn given_out_of_range_settings_when_validating_then_they_are_rejected() {
+        for raw in [
+            r#"{"waiting": {"hours": 0}}"#,
+            r#"{"senior_agents": [0]}"#,
+            r#"{"webhook_url": "file:///etc/passwd"}"#,
+        ] {
+            let rules: EscalationRules = serde_json::from_str(raw).expect("parses");
+            assert!(rules.validate().is_err(), "{raw}");
+        }
+    }
+}
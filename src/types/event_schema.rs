@@ -0,0 +1,112 @@
+//! Registry of `execution_events` payload versions.
+//!
+//! Each entry of [`UPGRADES`] turns a payload of one version into the next.
+//! Readers run [`upgrade_event_payload`] so consumers only see
+//! [`EventSchemaVersion::LATEST`] payloads; `events migrate` runs the same
+//! chain and writes the result back.
+
+use super::observability::{EventSchemaVersion, ExecutionEvent};
+use serde_json::{json, Value};
+
+/// Turns a payload of the version it is registered under into the next
+/// version. Gets the event type for upgrades that only touch some events.
+type PayloadUpgrade = fn(&str, Option<Value>) -> Option<Value>;
+
+/// Upgrade steps, oldest first, keyed by the version they upgrade from.
+const UPGRADES: [(EventSchemaVersion, PayloadUpgrade); 1] =
+    [(EventSchemaVersion::V1, upgrade_v1_to_v2)];
+
+/// v1 alert and bead events could be stored without a payload, and nothing
+/// stopped a bare scalar; v2 payloads are always objects.
+#[allow(clippy::unnecessary_wraps)]
+fn upgrade_v1_to_v2(_event_type: &str, payload: Option<Value>) -> Option<Value> {
+    match payload {
+        None | Some(Value::Null) => Some(json!({})),
+        Some(Value::Object(map)) => Some(Value::Object(map)),
+        Some(other) => Some(json!({"value": other})),
+    }
+}
+
+/// Runs every upgrade from `schema_version` to the latest version.
+///
+/// Returns the new version with the upgraded payload. Versions this build
+/// does not know, such as rows written by a newer release, are returned
+/// unchanged.
+#[must_use]
+pub fn upgrade_event_payload(
+    schema_version: i32,
+    event_type: &str,
+    mut payload: Option<Value>,
+) -> (i32, Option<Value>) {
+    let Some(mut version) = EventSchemaVersion::from_i32(schema_version) else {
+        return (schema_version, payload);
+    };
+    for (from, upgrade) in UPGRADES {
+        if from == version {
+            payload = upgrade(event_type, payload);
+            version = EventSchemaVersion::from_i32(from.as_i32() + 1)
+                .unwrap_or(EventSchemaVersion::LATEST);
+        }
+    }
+    (version.as_i32(), payload)
+}
+
+impl ExecutionEvent {
+    /// The event with its payload upgraded to the latest schema version.
+    #[must_use]
+    pub fn upgraded(self) -> Self {
+        let (schema_version, payload) =
+            upgrade_event_payload(self.schema_version, &self.event_type, self.payload);
+        Self {
+            schema_version,
+            payload,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_every_version_when_registry_is_read_then_each_has_an_upgrade_to_the_next() {
+        let mut expected = 1;
+        for (from, _) in UPGRADES {
+            assert_eq!(from.as_i32(), expected);
+            expected += 1;
+        }
+        assert_eq!(expected, EventSchemaVersion::LATEST.as_i32());
+    }
+
+    #[test]
+    fn given_v1_payloads_when_upgrading_then_each_becomes_an_object() {
+        assert_eq!(
+            upgrade_event_payload(1, "alert_fired", None),
+            (2, Some(json!({})))
+        );
+        assert_eq!(
+            upgrade_event_payload(1, "transition_retry", Some(json!({"transition": "retry"}))),
+            (2, Some(json!({"transition": "retry"})))
+        );
+        assert_eq!(
+            upgrade_event_payload(1, "landing_sync", Some(json!("synced"))),
+            (2, Some(json!({"value": "synced"})))
+        );
+    }
+
+    #[test]
+    fn given_latest_or_unknown_version_when_upgrading_then_payload_is_unchanged() {
+        let payload = Some(json!("kept"));
+
+        assert_eq!(
+            upgrade_event_payload(EventSchemaVersion::LATEST.as_i32(), "x", payload.clone()),
+            (EventSchemaVersion::LATEST.as_i32(), payload.clone())
+        );
+        assert_eq!(
+            upgrade_event_payload(99, "x", payload.clone()),
+            (99, payload)
+        );
+    }
+}
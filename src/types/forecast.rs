@@ -0,0 +1,143 @@
+//! Backlog drain forecasts from recent throughput. The swarm's completion
+//! rate per agent-hour over a lookback window is assumed to hold, and to
+//! scale linearly with the number of agents.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Lookback window, in hours, when `forecast` is given none.
+pub const DEFAULT_FORECAST_WINDOW_HOURS: u32 = 24;
+
+/// Longest lookback window, one year.
+pub const MAX_FORECAST_WINDOW_HOURS: u32 = 8760;
+
+/// Counters a forecast is projected from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThroughputHistory {
+    pub window_hours: u32,
+    /// Beads whose `red-queen` stage passed inside the window.
+    pub beads_completed: u32,
+    /// Agents that finished at least one stage run inside the window.
+    pub active_agents: u32,
+    /// Registered agents right now.
+    pub registered_agents: u32,
+    /// Backlog entries not yet completed or cancelled.
+    pub backlog_remaining: u32,
+}
+
+impl ThroughputHistory {
+    /// Beads completed per active agent per hour, or `None` when the window
+    /// saw no completions to extrapolate from.
+    #[must_use]
+    pub fn beads_per_agent_hour(&self) -> Option<f64> {
+        let agent_hours = f64::from(self.active_agents) * f64::from(self.window_hours);
+        (self.beads_completed > 0 && agent_hours > 0.0)
+            .then(|| f64::from(self.beads_completed) / agent_hours)
+    }
+}
+
+/// When the backlog drains at `agents` agents.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Forecast {
+    pub history: ThroughputHistory,
+    pub beads_per_agent_hour: Option<f64>,
+    pub agents: u32,
+    /// `agents` came from the caller rather than the registered count.
+    pub what_if: bool,
+    /// `None` when there is no rate to project or no agents to do the work.
+    pub hours_to_drain: Option<f64>,
+    /// Also `None` when the drain lies past what a timestamp can hold.
+    pub drains_at: Option<DateTime<Utc>>,
+}
+
+impl Forecast {
+    /// Projects `history` forward from `now` with `agents` agents, or the
+    /// registered count when `agents` is `None`.
+    #[must_use]
+    pub fn project(history: ThroughputHistory, agents: Option<u32>, now: DateTime<Utc>) -> Self {
+        let rate = history.beads_per_agent_hour();
+        let agent_count = agents.unwrap_or(history.registered_agents);
+        let hours_to_drain = if history.backlog_remaining == 0 {
+            Some(0.0)
+        } else {
+            rate.filter(|_| agent_count > 0)
+                .map(|rate| f64::from(history.backlog_remaining) / (rate * f64::from(agent_count)))
+        };
+        #[allow(clippy::cast_possible_truncation)]
+        let drains_at = hours_to_drain
+            .and_then(|hours| TimeDelta::try_seconds((hours * 3600.0).ceil() as i64))
+            .and_then(|delta| now.checked_add_signed(delta));
+        Self {
+            history,
+            beads_per_agent_hour: rate,
+            agents: agent_count,
+            what_if: agents.is_some(),
+            hours_to_drain,
+            drains_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(beads_completed: u32, backlog_remaining: u32) -> ThroughputHistory {
+        ThroughputHistory {
+            window_hours: 10,
+            beads_completed,
+            active_agents: 2,
+            registered_agents: 4,
+            backlog_remaining,
+        }
+    }
+
+    #[test]
+    fn given_recent_completions_when_projecting_then_drain_time_scales_with_agents() {
+        let now = Utc::now();
+
+        let current = Forecast::project(history(20, 40), None, now);
+        let doubled = Forecast::project(history(20, 40), Some(8), now);
+
+        assert_eq!(current.beads_per_agent_hour, Some(1.0));
+        assert_eq!(current.agents, 4);
+        assert_eq!(current.hours_to_drain, Some(10.0));
+        assert_eq!(current.drains_at, Some(now + TimeDelta::hours(10)));
+        assert!(doubled.what_if);
+        assert_eq!(doubled.hours_to_drain, Some(5.0));
+    }
+
+    #[test]
+    fn given_no_completions_or_no_agents_when_projecting_then_there_is_no_eta() {
+        let now = Utc::now();
+
+        assert_eq!(
+            Forecast::project(history(0, 40), None, now).hours_to_drain,
+            None
+        );
+        assert_eq!(
+            Forecast::project(history(20, 40), Some(0), now).drains_at,
+            None
+        );
+        assert_eq!(
+            Forecast::project(history(0, 0), None, now).hours_to_drain,
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn given_a_drain_past_the_last_timestamp_when_projecting_then_drains_at_is_none() {
+        let trickle = ThroughputHistory {
+            window_hours: MAX_FORECAST_WINDOW_HOURS,
+            beads_completed: 1,
+            active_agents: u32::MAX,
+            registered_agents: 1,
+            backlog_remaining: u32::MAX,
+        };
+
+        let forecast = Forecast::project(trickle, None, Utc::now());
+
+        assert!(forecast.hours_to_drain.is_some_and(|hours| hours > 1e20));
+        assert_eq!(forecast.drains_at, None);
+    }
+}
@@ -0,0 +1,390 @@
+//! Declarative landing gates.
+//!
+//! A [`GatePolicy`] is read from `SWARM_GATE_POLICY` as JSON or TOML. `land`
+//! collects [`GateEvidence`] for the bead once the push check has run and
+//! finalizes only when [`GatePolicy::evaluate`] returns no violations.
+//!
+//! ```toml
+//! required_artifacts = ["test_results", "coverage_report"]
+//! max_diff_lines = 800
+//! min_coverage = 80.0
+//! forbid_scope_violations = true
+//! require_push_confirmed = true
+//! min_approvals = 1
+//! ```
+
+use super::artifacts::ArtifactType;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GatePolicy {
+    /// Artifact types, by their stored name, the bead must have on any of its
+    /// stage runs.
+    pub required_artifacts: Vec<String>,
+    /// Most lines the landed change may add plus delete.
+    pub max_diff_lines: Option<u64>,
+    /// Minimum line coverage, in percent, of the bead's latest coverage
+    /// summary.
+    pub min_coverage: Option<f64>,
+    pub forbid_scope_violations: bool,
+    pub require_push_confirmed: bool,
+    /// Reviewers whose latest verdict must approve, with none asking for
+    /// changes.
+    pub min_approvals: Option<u32>,
+}
+
+impl Default for GatePolicy {
+    fn default() -> Self {
+        Self {
+            required_artifacts: Vec::new(),
+            max_diff_lines: None,
+            min_coverage: None,
+            forbid_scope_violations: false,
+            require_push_confirmed: true,
+            min_approvals: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GateRule {
+    RequiredArtifact,
+    MaxDiffLines,
+    MinCoverage,
+    ScopeViolations,
+    PushConfirmed,
+    Approvals,
+}
+
+impl GateRule {
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::RequiredArtifact => "required_artifact",
+            Self::MaxDiffLines => "max_diff_lines",
+            Self::MinCoverage => "min_coverage",
+            Self::ScopeViolations => "scope_violations",
+            Self::PushConfirmed => "push_confirmed",
+            Self::Approvals => "approvals",
+        }
+    }
+}
+
+/// What the gates are checked against, gathered when the bead lands.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GateEvidence {
+    pub artifact_types: Vec<ArtifactType>,
+    /// `None` when the change's diff could not be measured.
+    pub diff_lines: Option<u64>,
+    /// `None` when no coverage summary was uploaded.
+    pub coverage_percent: Option<f64>,
+    pub scope_violations: u64,
+    pub push_confirmed: bool,
+    pub approvals: u64,
+    pub changes_requested: u64,
+}
+
+/// One failed rule, with what the policy wanted and what the bead had.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GateViolation {
+    pub rule: GateRule,
+    pub detail: String,
+    pub expected: Value,
+    pub actual: Value,
+}
+
+impl GateViolation {
+    const fn new(rule: GateRule, detail: String, expected: Value, actual: Value) -> Self {
+        Self {
+            rule,
+            detail,
+            expected,
+            actual,
+        }
+    }
+}
+
+impl GatePolicy {
+    /// Parses a policy written as JSON (starting with `{`) or TOML.
+    ///
+    /// # Errors
+    /// Returns a message when the policy does not parse or fails
+    /// [`GatePolicy::validate`].
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let policy: Self = if raw.trim_start().starts_with('{') {
+            serde_json::from_str(raw).map_err(|e| e.to_string())?
+        } else {
+            toml::from_str(raw).map_err(|e| e.to_string())?
+        };
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Rejects unknown artifact types and a coverage threshold outside
+    /// 0..=100.
+    ///
+    /// # Errors
+    /// Returns a message naming the first bad setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(unknown) = self
+            .required_artifacts
+            .iter()
+            .find(|name| ArtifactType::try_from(name.as_str()).is_err())
+        {
+            return Err(format!("unknown required artifact type: {unknown}"));
+        }
+        match self.min_coverage {
+            Some(percent) if !(0.0..=100.0).contains(&percent) => Err(format!(
+                "min_coverage must be a percent from 0 to 100, got {percent}"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Every rule `evidence` breaks, in policy order. A rule whose evidence
+    /// is missing (an unmeasured diff, no coverage summary) counts as broken.
+    #[must_use]
+    pub fn evaluate(&self, evidence: &GateEvidence) -> Vec<GateViolation> {
+        let missing_artifacts = self
+            .required_artifacts
+            .iter()
+            .filter(|name| {
+                !evidence
+                    .artifact_types
+                    .iter()
+                    .any(|present| present.as_str() == name.as_str())
+            })
+            .map(|name| {
+                GateViolation::new(
+                    GateRule::RequiredArtifact,
+                    format!("missing required artifact {name}"),
+                    json!(name),
+                    Value::Null,
+                )
+            });
+
+        let diff = self
+            .max_diff_lines
+            .and_then(|max| match evidence.diff_lines {
+                Some(lines) if lines <= max => None,
+                Some(lines) => Some(GateViolation::new(
+                    GateRule::MaxDiffLines,
+                    format!("diff changes {lines} lines, more than the {max} allowed"),
+                    json!(max),
+                    json!(lines),
+                )),
+                None => Some(GateViolation::new(
+                    GateRule::MaxDiffLines,
+                    "diff size could not be measured".to_string(),
+                    json!(max),
+                    Value::Null,
+                )),
+            });
+
+        let coverage = self
+            .min_coverage
+            .and_then(|min| match evidence.coverage_percent {
+                Some(percent) if percent >= min => None,
+                Some(percent) => Some(GateViolation::new(
+                    GateRule::MinCoverage,
+                    format!("line coverage {percent:.2}% is below {min:.2}%"),
+                    json!(min),
+                    json!(percent),
+                )),
+                None => Some(GateViolation::new(
+                    GateRule::MinCoverage,
+                    "no coverage report uploaded".to_string(),
+                    json!(min),
+                    Value::Null,
+                )),
+            });
+
+        let scope = (self.forbid_scope_violations && evidence.scope_violations > 0).then(|| {
+            GateViolation::new(
+                GateRule::ScopeViolations,
+                format!(
+                    "{} scope violation(s) recorded for the bead",
+                    evidence.scope_violations
+                ),
+                json!(0),
+                json!(evidence.scope_violations),
+            )
+        });
+
+        let push = (self.require_push_confirmed && !evidence.push_confirmed).then(|| {
+            GateViolation::new(
+                GateRule::PushConfirmed,
+                "push to the remote was not confirmed".to_string(),
+                json!(true),
+                json!(false),
+            )
+        });
+
+        let approvals = self
+            .min_approvals
+            .and_then(|min| approvals_violation(min, evidence));
+
+        missing_artifacts
+            .chain(diff)
+            .chain(coverage)
+            .chain(scope)
+            .chain(push)
+            .chain(approvals)
+            .collect()
+    }
+}
+
+/// A review asking for changes blocks landing whatever the approval count.
+fn approvals_violation(min: u32, evidence: &GateEvidence) -> Option<GateViolation> {
+    if evidence.changes_requested > 0 {
+        Some(GateViolation::new(
+            GateRule::Approvals,
+            format!(
+                "{} reviewer(s) asked for changes",
+                evidence.changes_requested
+            ),
+            json!(0),
+            json!(evidence.changes_requested),
+        ))
+    } else if evidence.approvals < u64::from(min) {
+        Some(GateViolation::new(
+            GateRule::Approvals,
+            format!(
+                "{} approval(s), fewer than the {min} required",
+                evidence.approvals
+            ),
+            json!(min),
+            json!(evidence.approvals),
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn passing_evidence() -> GateEvidence {
+        GateEvidence {
+            artifact_types: vec![ArtifactType::TestResults, ArtifactType::CoverageReport],
+            diff_lines: Some(120),
+            coverage_percent: Some(85.0),
+            scope_violations: 0,
+            push_confirmed: true,
+            approvals: 2,
+            changes_requested: 0,
+        }
+    }
+
+    #[test]
+    fn given_toml_and_json_policies_when_parsing_then_both_are_accepted() {
+        let toml_policy = GatePolicy::parse(
+            "required_artifacts = [\"test_results\"]\nmax_diff_lines = 500\nforbid_scope_violations = true\n",
+        )
+        .expect("toml policy parses");
+        let json_policy =
+            GatePolicy::parse(r#"{"required_artifacts": ["test_results"], "max_diff_lines": 500, "forbid_scope_violations": true}"#)
+                .expect("json policy parses");
+
+        assert_eq!(toml_policy, json_policy);
+        assert!(toml_policy.require_push_confirmed);
+    }
+
+    #[test]
+    fn given_bad_settings_when_parsing_then_they_are_rejected() {
+        assert!(GatePolicy::parse(r#"{"required_artifacts": ["screenshots"]}"#).is_err());
+        assert!(GatePolicy::parse(r#"{"min_coverage": 120.0}"#).is_err());
+        assert!(GatePolicy::parse(r#"{"max_diff": 10}"#).is_err());
+    }
+
+    #[test]
+    fn given_evidence_meeting_every_rule_when_evaluating_then_nothing_fails() {
+        let policy = GatePolicy {
+            required_artifacts: vec!["test_results".to_string(), "coverage_report".to_string()],
+            max_diff_lines: Some(500),
+            min_coverage: Some(80.0),
+            forbid_scope_violations: true,
+            require_push_confirmed: true,
+            min_approvals: Some(2),
+        };
+
+        assert!(policy.evaluate(&passing_evidence()).is_empty());
+    }
+
+    #[test]
+    fn given_evidence_breaking_every_rule_when_evaluating_then_each_rule_is_listed() {
+        let policy = GatePolicy {
+            required_artifacts: vec![
+                "test_results".to_string(),
+                "quality_gate_report".to_string(),
+            ],
+            max_diff_lines: Some(100),
+            min_coverage: Some(90.0),
+            forbid_scope_violations: true,
+            require_push_confirmed: true,
+            min_approvals: Some(2),
+        };
+        let evidence = GateEvidence {
+            scope_violations: 2,
+            push_confirmed: false,
+            approvals: 1,
+            ..passing_evidence()
+        };
+
+        let rules = policy
+            .evaluate(&evidence)
+            .iter()
+            .map(|violation| violation.rule)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            rules,
+            vec![
+                GateRule::RequiredArtifact,
+                GateRule::MaxDiffLines,
+                GateRule::MinCoverage,
+                GateRule::ScopeViolations,
+                GateRule::PushConfirmed,
+                GateRule::Approvals,
+            ]
+        );
+    }
+
+    #[test]
+    fn given_enough_approvals_but_a_change_request_when_evaluating_then_approvals_fail() {
+        let policy = GatePolicy {
+            min_approvals: Some(1),
+            ..GatePolicy::default()
+        };
+        let evidence = GateEvidence {
+            changes_requested: 1,
+            ..passing_evidence()
+        };
+
+        let violations = policy.evaluate(&evidence);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, GateRule::Approvals);
+    }
+
+    #[test]
+    fn given_unmeasured_evidence_when_evaluating_then_the_rule_fails_closed() {
+        let policy = GatePolicy {
+            max_diff_lines: Some(100),
+            min_coverage: Some(50.0),
+            ..GatePolicy::default()
+        };
+        let evidence = GateEvidence {
+            diff_lines: None,
+            coverage_percent: None,
+            ..passing_evidence()
+        };
+
+        assert_eq!(policy.evaluate(&evidence).len(), 2);
+    }
+}
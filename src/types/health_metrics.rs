@@ -156,10 +156,244 @@ impl AgentHealthStatus {
     }
 }
 
+/// Length of one fingerprint window.
+pub const FINGERPRINT_WINDOW_SECS: u32 = 3600;
+/// Completed windows averaged into an agent's baseline.
+pub const FINGERPRINT_BASELINE_WINDOWS: u32 = 24;
+/// Windows needed before deviations from the baseline count as anomalies.
+const MIN_BASELINE_WINDOWS: usize = 3;
+/// Stage runs needed in the current window before it is judged.
+const MIN_WINDOW_STAGE_RUNS: u32 = 3;
+/// Absolute increase in failure or retry rate treated as anomalous.
+const RATE_DEVIATION: f64 = 0.25;
+/// Multiple of the baseline stage time or stages-per-bead treated as anomalous.
+const RATIO_DEVIATION: f64 = 2.0;
+
+/// Start of the fixed window containing `at`; windows align to the epoch.
+#[must_use]
+pub fn fingerprint_window_start(at: DateTime<Utc>, window_secs: u32) -> DateTime<Utc> {
+    let secs = at.timestamp();
+    DateTime::from_timestamp(secs - secs.rem_euclid(i64::from(window_secs.max(1))), 0).unwrap_or(at)
+}
+
+/// Aggregated `stage_history` for one agent over one fixed window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentBehaviorWindow {
+    pub agent_id: u32,
+    pub window_start: DateTime<Utc>,
+    pub window_secs: u32,
+    pub stage_runs: u32,
+    pub failures: u32,
+    pub retries: u32,
+    pub beads: u32,
+    pub avg_stage_ms: u64,
+}
+
+impl AgentBehaviorWindow {
+    #[must_use]
+    pub fn failure_rate(&self) -> f64 {
+        ratio(self.failures, self.stage_runs)
+    }
+
+    /// Share of stage runs that were a second or later attempt.
+    #[must_use]
+    pub fn retry_rate(&self) -> f64 {
+        ratio(self.retries, self.stage_runs)
+    }
+
+    /// Stage runs ("commands") per distinct bead touched.
+    #[must_use]
+    pub fn stages_per_bead(&self) -> f64 {
+        ratio(self.stage_runs, self.beads)
+    }
+}
+
+fn ratio(numerator: u32, denominator: u32) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        f64::from(numerator) / f64::from(denominator)
+    }
+}
+
+/// Mean of each per-window rate across `windows`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BehaviorBaseline {
+    pub windows: u32,
+    pub failure_rate: f64,
+    pub retry_rate: f64,
+    pub stages_per_bead: f64,
+    pub avg_stage_ms: f64,
+}
+
+impl BehaviorBaseline {
+    #[must_use]
+    pub fn from_windows(windows: &[AgentBehaviorWindow]) -> Option<Self> {
+        if windows.is_empty() {
+            return None;
+        }
+        let count = windows.len() as f64;
+        let mean =
+            |value: fn(&AgentBehaviorWindow) -> f64| windows.iter().map(value).sum::<f64>() / count;
+        Some(Self {
+            windows: windows.len() as u32,
+            failure_rate: mean(AgentBehaviorWindow::failure_rate),
+            retry_rate: mean(AgentBehaviorWindow::retry_rate),
+            stages_per_bead: mean(AgentBehaviorWindow::stages_per_bead),
+            avg_stage_ms: mean(|window| window.avg_stage_ms as f64),
+        })
+    }
+
+    /// Names of the metrics in `current` that deviate from this baseline.
+    /// Thin baselines and near-empty windows never produce anomalies.
+    #[must_use]
+    pub fn anomalies(&self, current: &AgentBehaviorWindow) -> Vec<&'static str> {
+        if (self.windows as usize) < MIN_BASELINE_WINDOWS
+            || current.stage_runs < MIN_WINDOW_STAGE_RUNS
+        {
+            return Vec::new();
+        }
+        let exceeds_ratio =
+            |value: f64, baseline: f64| baseline > 0.0 && value > baseline * RATIO_DEVIATION;
+        [
+            (
+                "failure_rate",
+                current.failure_rate() > self.failure_rate + RATE_DEVIATION,
+            ),
+            (
+                "retry_rate",
+                current.retry_rate() > self.retry_rate + RATE_DEVIATION,
+            ),
+            (
+                "stage_time",
+                exceeds_ratio(current.avg_stage_ms as f64, self.avg_stage_ms),
+            ),
+            (
+                "stages_per_bead",
+                exceeds_ratio(current.stages_per_bead(), self.stages_per_bead),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(name, anomalous)| anomalous.then_some(name))
+        .collect()
+    }
+}
+
+/// Verdict for one agent: the live fingerprint's status, raised to
+/// `Degraded` when the current window is anomalous against the baseline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentHealthReport {
+    pub status: AgentHealthStatus,
+    pub fingerprint: BehavioralFingerprint,
+    pub current: Option<AgentBehaviorWindow>,
+    pub baseline: Option<BehaviorBaseline>,
+    pub anomalies: Vec<String>,
+}
+
+impl AgentHealthReport {
+    /// `windows` holds this agent's stored windows, oldest first; the last one
+    /// is the current window when it starts at `current_window_start`.
+    #[must_use]
+    pub fn assess(
+        fingerprint: BehavioralFingerprint,
+        windows: &[AgentBehaviorWindow],
+        current_window_start: DateTime<Utc>,
+    ) -> Self {
+        let (current, history) = match windows.split_last() {
+            Some((last, history)) if last.window_start == current_window_start => {
+                (Some(*last), history)
+            }
+            _ => (None, windows),
+        };
+        let baseline = BehaviorBaseline::from_windows(history);
+        let anomalies = match (baseline.as_ref(), current.as_ref()) {
+            (Some(baseline), Some(current)) => baseline.anomalies(current),
+            _ => Vec::new(),
+        };
+        let status = match fingerprint.health_status() {
+            AgentHealthStatus::Healthy if !anomalies.is_empty() => AgentHealthStatus::Degraded,
+            status => status,
+        };
+        Self {
+            status,
+            fingerprint,
+            current,
+            baseline,
+            anomalies: anomalies.into_iter().map(str::to_string).collect(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn window(hour: i64, stage_runs: u32, failures: u32, avg_stage_ms: u64) -> AgentBehaviorWindow {
+        AgentBehaviorWindow {
+            agent_id: 1,
+            window_start: DateTime::<Utc>::UNIX_EPOCH + chrono::Duration::hours(hour),
+            window_secs: FINGERPRINT_WINDOW_SECS,
+            stage_runs,
+            failures,
+            retries: 0,
+            beads: stage_runs.div_ceil(4),
+            avg_stage_ms,
+        }
+    }
+
+    fn idle_fingerprint() -> BehavioralFingerprint {
+        BehavioralFingerprint::new("1".to_string(), None, "implement".to_string(), 0, 0, 0)
+    }
+
+    #[test]
+    fn given_timestamp_when_computing_window_start_then_it_aligns_to_the_window() {
+        let at = DateTime::<Utc>::UNIX_EPOCH + chrono::Duration::seconds(7_500);
+
+        assert_eq!(
+            fingerprint_window_start(at, FINGERPRINT_WINDOW_SECS),
+            DateTime::<Utc>::UNIX_EPOCH + chrono::Duration::seconds(7_200)
+        );
+    }
+
+    #[test]
+    fn given_stable_history_when_current_window_fails_more_then_agent_is_degraded() {
+        let windows = [
+            window(0, 8, 1, 1_000),
+            window(1, 8, 0, 1_100),
+            window(2, 8, 1, 900),
+            window(3, 8, 6, 1_000),
+        ];
+
+        let report =
+            AgentHealthReport::assess(idle_fingerprint(), &windows, windows[3].window_start);
+
+        assert_eq!(report.status, AgentHealthStatus::Degraded);
+        assert_eq!(report.anomalies, vec!["failure_rate".to_string()]);
+        assert_eq!(report.baseline.map(|baseline| baseline.windows), Some(3));
+    }
+
+    #[test]
+    fn given_thin_baseline_when_assessing_then_no_anomaly_is_reported() {
+        let windows = [window(0, 8, 0, 1_000), window(1, 8, 8, 9_000)];
+
+        let report =
+            AgentHealthReport::assess(idle_fingerprint(), &windows, windows[1].window_start);
+
+        assert_eq!(report.status, AgentHealthStatus::Healthy);
+        assert!(report.anomalies.is_empty());
+    }
+
+    #[test]
+    fn given_no_current_window_when_assessing_then_all_windows_form_the_baseline() {
+        let windows = [window(0, 8, 0, 1_000), window(1, 8, 0, 1_000)];
+        let now = windows[1].window_start + chrono::Duration::hours(1);
+
+        let report = AgentHealthReport::assess(idle_fingerprint(), &windows, now);
+
+        assert!(report.current.is_none());
+        assert_eq!(report.baseline.map(|baseline| baseline.windows), Some(2));
+    }
+
     #[test]
     fn health_metrics_success_rate_calculates_correctly() {
         let metrics = HealthMetrics::new(100, 80, 20, 5);
@@ -0,0 +1,66 @@
+//! Coordinator invariants checked against live state.
+//!
+//! `swarm invariants` and [`crate::SwarmDb::check_invariants`] run each
+//! [`InvariantCheck`] as a SQL assertion and report every row that breaks
+//! it as an [`InvariantViolation`] carrying the command that repairs it.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvariantCheck {
+    /// At most one active agent works a bead, and it is the claim owner.
+    SingleAssignee,
+    /// A working or waiting agent holds the in-progress claim on its bead.
+    WorkingAgentHasClaim,
+    /// No agent's implementation attempt exceeds the configured maximum.
+    AttemptsWithinMax,
+    /// An in-progress claim's lease does not end before its last heartbeat.
+    LeaseAfterHeartbeat,
+}
+
+impl InvariantCheck {
+    pub const ALL: [Self; 4] = [
+        Self::SingleAssignee,
+        Self::WorkingAgentHasClaim,
+        Self::AttemptsWithinMax,
+        Self::LeaseAfterHeartbeat,
+    ];
+
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::SingleAssignee => "single_assignee",
+            Self::WorkingAgentHasClaim => "working_agent_has_claim",
+            Self::AttemptsWithinMax => "attempts_within_max",
+            Self::LeaseAfterHeartbeat => "lease_after_heartbeat",
+        }
+    }
+}
+
+/// One row that breaks an invariant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvariantViolation {
+    pub check: InvariantCheck,
+    pub repo_id: String,
+    pub bead_id: Option<String>,
+    pub agent_id: Option<u32>,
+    pub detail: String,
+    /// Command that clears the violation.
+    pub fix: String,
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn given_every_check_when_serialized_then_name_matches_as_str() {
+        for check in InvariantCheck::ALL {
+            assert_eq!(serde_json::to_value(check).unwrap(), json!(check.as_str()));
+        }
+    }
+}
@@ -15,6 +15,7 @@ pub enum MessageType {
     StageFailed,
     BlockingIssue,
     Coordination,
+    BeadCancelled,
 }
 
 impl MessageType {
@@ -32,6 +33,7 @@ impl MessageType {
             Self::StageFailed => "stage_failed",
             Self::BlockingIssue => "blocking_issue",
             Self::Coordination => "coordination",
+            Self::BeadCancelled => "bead_cancelled",
         }
     }
 }
@@ -52,6 +54,7 @@ impl TryFrom<&str> for MessageType {
             "stage_failed" => Ok(Self::StageFailed),
             "blocking_issue" => Ok(Self::BlockingIssue),
             "coordination" => Ok(Self::Coordination),
+            "bead_cancelled" => Ok(Self::BeadCancelled),
             _ => Err(format!("Unknown message type: {value}")),
         }
     }
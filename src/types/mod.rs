@@ -1,40 +1,158 @@
+mod agent_kv;
+mod agent_session;
 mod agent_types;
+mod alerts;
+mod announcement;
+mod approval;
 mod artifacts;
+mod backlog;
+mod bead_snapshot;
+mod blackboard;
 mod budget;
 mod circuit_breaker;
 mod claim_types;
+mod config_keys;
+mod costs;
+mod coverage;
+mod diff;
+mod dry_run;
+mod environment;
+mod escalation;
+mod event_schema;
 mod file_manifest;
+mod forecast;
+mod gate_policy;
 mod health_metrics;
 mod identifiers;
+mod invariants;
 mod messaging;
+mod notify;
 mod observability;
+mod partition;
+mod provenance;
+mod quarantine;
+mod recovery;
+mod resource_locks;
 mod resume_types;
+mod review;
+mod schedule;
+mod schema_drift;
+mod sla;
+mod soft_delete;
 mod stage;
 mod swarm_types;
 mod symbols;
+mod tenant;
+mod transition_events;
+mod workspace;
 
+pub use agent_kv::{AgentKvEntry, MAX_KV_KEYS, MAX_KV_KEY_BYTES, MAX_KV_VALUE_BYTES};
+pub use agent_session::{AgentSession, SessionEndReason};
 pub use agent_types::{AgentState, AgentStatus};
-pub use artifacts::{ArtifactType, StageArtifact};
+pub use alerts::{
+    alert_transition, AlertBreach, AlertKind, AlertObservation, AlertRules, AlertStatus,
+    AlertTransition, AllAgentsWaitingRule, BacklogDepthRule, ErrorRateRule, SwarmAlert,
+};
+pub use announcement::{
+    is_valid_announcement_topic, Announcement, AnnouncementAck, AnnouncementStatus,
+    DEFAULT_ANNOUNCEMENT_TOPIC, DEFAULT_ANNOUNCEMENT_TTL_SECS, MAX_ANNOUNCEMENT_TOPIC_BYTES,
+    MAX_ANNOUNCEMENT_TTL_SECS,
+};
+pub use approval::{
+    Approval, ApprovalGate, ApprovalRequest, ApprovalStatus, APPROVAL_REQUESTED_EVENT,
+};
+pub use artifacts::{
+    ArtifactEncoding, ArtifactType, StageArtifact, StageOutputChunk, StoredArtifact,
+};
+pub use backlog::{
+    normalize_priority, parse_backlog_entries, parse_br_issues, reconcile_backlog,
+    simulate_assignments, BacklogEditOutcome, BacklogEditSkip, BacklogEntry, BacklogRow,
+    BacklogSelector, BacklogStatus, BacklogSyncDiff, BrIssue, PreviewAgent, PreviewAssignment,
+    PreviewBead, PriorityChange, BACKLOG_PRIORITIES, BR_READY_STATUSES,
+};
+pub use bead_snapshot::{
+    BeadSnapshot, SnapshotArtifact, SnapshotAssignment, SnapshotBacklog, SnapshotClaim,
+    SnapshotStage, BEAD_SNAPSHOT_VERSION,
+};
+pub use blackboard::{Blackboard, BlackboardEntry, BlackboardSection, MAX_BLACKBOARD_ENTRY_BYTES};
 pub use budget::{
     BudgetLimit, BudgetRecord, BudgetRemaining, BudgetStatus, TokenUsage, TokenUsageRecord,
 };
 pub use circuit_breaker::{CircuitBreakerRecord, CircuitConfig, CircuitState};
-pub use claim_types::{BeadClaim, ClaimStatus};
+pub use claim_types::{
+    BeadCancellation, BeadClaim, BeadReservation, ClaimStatus, ClaimTransfer, TakeoverBasis,
+    DEFAULT_RESERVATION_TTL_SECS, MAX_RESERVATION_TTL_SECS,
+};
+pub use config_keys::{is_valid_claim_label, ConfigChange, ConfigKey, MAX_CLAIM_LABEL_BYTES};
+pub use costs::{
+    CostLine, CostPricing, CostReport, ModelPricing, StageCompute, UNATTRIBUTED_COST_KEY,
+};
+pub use coverage::{
+    parse_cobertura, parse_coverage, parse_lcov, BeadCoverageTrend, CoverageFormat, CoverageSample,
+    CoverageSummary,
+};
+pub use diff::{BeadDiff, DiffFileStat, DiffStat};
+pub use dry_run::{AssignPreview, DryRunConflict, DryRunConflictKind, SeedPlan};
+pub use environment::{
+    EnvironmentChange, EnvironmentFingerprint, StageEnvironment, FINGERPRINT_TOOLS,
+};
+pub use escalation::{
+    AttemptsRule, Escalation, EscalationCandidate, EscalationReason, EscalationRules,
+    EscalationTrigger, WaitingRule,
+};
+pub use event_schema::upgrade_event_payload;
 pub use file_manifest::{
     detect_conflicts, ConflictReport, FileClaimRecord, FileConflict, FileDeclaration, FileManifest,
     ModificationType, ScopeValidation, ScopeViolation, ViolationReason,
 };
-pub use health_metrics::{AgentHealthStatus, BehavioralFingerprint, HealthMetrics};
+pub use forecast::{
+    Forecast, ThroughputHistory, DEFAULT_FORECAST_WINDOW_HOURS, MAX_FORECAST_WINDOW_HOURS,
+};
+pub use gate_policy::{GateEvidence, GatePolicy, GateRule, GateViolation};
+pub use health_metrics::{
+    fingerprint_window_start, AgentBehaviorWindow, AgentHealthReport, AgentHealthStatus,
+    BehaviorBaseline, BehavioralFingerprint, HealthMetrics, FINGERPRINT_BASELINE_WINDOWS,
+    FINGERPRINT_WINDOW_SECS,
+};
 pub use identifiers::{AgentId, BeadId, RepoId};
+pub use invariants::{InvariantCheck, InvariantViolation};
 pub use messaging::{AgentMessage, MessageType};
+pub use notify::{
+    Notification, NotificationRoute, NotificationRules, NotificationSeverity, NotifierSpec,
+};
 pub use observability::{EventSchemaVersion, ExecutionEvent, FailureDiagnostics};
+pub use partition::{
+    month_start, month_start_at, months_to_cover, next_month, PartitionedTable,
+    PartitionedTableState, TablePartition, DEFAULT_PARTITIONS_AHEAD, MAX_PARTITIONS_AHEAD,
+    PARTITIONED_TABLES,
+};
+pub use provenance::{
+    provenance_statement, ProvenanceStageRun, ToolVersion, IN_TOTO_STATEMENT_TYPE,
+    SLSA_PROVENANCE_TYPE, SWARM_BUILD_TYPE,
+};
+pub use quarantine::{AgentQuarantine, QuarantinePolicy, QuarantineSource};
+pub use recovery::{ClaimRecoveryReason, RecoveryAction};
+pub use resource_locks::{LockHolder, LockMetadata, LockWaiter, ResourceLockView};
 pub use resume_types::{
-    DeepResumeContextContract, ResumeArtifactDetailContract, ResumeArtifactSummary,
-    ResumeArtifactSummaryContract, ResumeContextContract, ResumeContextProjection,
-    ResumeStageAttempt, ResumeStageAttemptContract,
+    prefer_stage_summaries, ContextBudget, DeepResumeContextContract, ResumeArtifactDetailContract,
+    ResumeArtifactSummary, ResumeArtifactSummaryContract, ResumeContextContract,
+    ResumeContextProjection, ResumeStageAttempt, ResumeStageAttemptContract, TruncationManifest,
+    BYTES_PER_TOKEN, TRANSCRIPT_ARTIFACTS,
 };
+pub use review::{BeadReview, ReviewTally, ReviewVerdict, MAX_REVIEW_COMMENT_BYTES};
+pub use schedule::{CronSchedule, JobSchedules, ScheduledJobKind, ScheduledJobRun};
+pub use schema_drift::{enum_columns, EnumColumn, EnumDrift};
+pub use sla::{BacklogAge, SlaAssessment, SlaPriorityAging, SlaStatus, SlaTargets};
+pub use soft_delete::{DeletedRow, SoftDeleteTable, SOFT_DELETE_TABLES};
 pub use stage::{Stage, StageResult};
-pub use swarm_types::{AvailableAgent, ProgressSummary, SwarmConfig, SwarmStatus};
+pub use swarm_types::{
+    AgentActivity, AvailableAgent, ProgressSummary, SwarmConfig, SwarmStatus,
+    DEFAULT_HEARTBEAT_GRACE_MS, DEFAULT_LEASE_TTL_MS, DEFAULT_RECOVERY_SCAN_INTERVAL_MS,
+};
 pub use symbols::{
-    DriftReport, DriftedSymbol, SymbolKind, SymbolRecord, TrackedSymbol, TypeSignature,
+    BeadDriftReport, DriftReport, DriftedSymbol, SymbolKind, SymbolObservation, SymbolRecord,
+    TrackedSymbol, TypeSignature,
 };
+pub use tenant::{tenant_schema, Tenant, MAX_TENANT_NAME_LEN, TENANT_SCHEMA_PREFIX};
+pub use transition_events::{BeadReplay, ReplayAnomaly, ReplayOutcome, TransitionEvent};
+pub use workspace::{workspace_name, AgentWorkspace, WorkspaceBackend};
@@ -0,0 +1,303 @@
+//! Routing swarm events to the people who should hear about them: named
+//! notifiers (Slack, Discord, email) and rules saying which events, at
+//! which severity, go to which notifiers.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// How urgent a notification is; routes drop anything below their
+/// `min_severity`.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationSeverity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+impl NotificationSeverity {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl TryFrom<&str> for NotificationSeverity {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "info" => Ok(Self::Info),
+            "warning" => Ok(Self::Warning),
+            "critical" => Ok(Self::Critical),
+            _ => Err(format!(
+                "Unknown notification severity: {value} (expected info, warning or critical)"
+            )),
+        }
+    }
+}
+
+/// A provider and where it delivers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum NotifierSpec {
+    /// Slack incoming webhook.
+    Slack { webhook_url: String },
+    /// Discord channel webhook.
+    Discord { webhook_url: String },
+    /// Mail over SMTP. `smtps://` connects over TLS; `smtp://` with
+    /// `starttls` upgrades the connection and refuses to send without it.
+    /// With `netrc` the login comes from `~/.netrc`, so no password sits in
+    /// the config.
+    Email {
+        smtp_url: String,
+        from: String,
+        to: Vec<String>,
+        #[serde(default)]
+        starttls: bool,
+        #[serde(default)]
+        netrc: bool,
+    },
+}
+
+impl NotifierSpec {
+    #[must_use]
+    pub const fn provider(&self) -> &'static str {
+        match self {
+            Self::Slack { .. } => "slack",
+            Self::Discord { .. } => "discord",
+            Self::Email { .. } => "email",
+        }
+    }
+
+    fn validate(&self, name: &str) -> Result<(), String> {
+        match self {
+            Self::Slack { webhook_url } | Self::Discord { webhook_url }
+                if !webhook_url.starts_with("https://") && !webhook_url.starts_with("http://") =>
+            {
+                Err(format!("notifier {name}: webhook_url must be http(s)"))
+            }
+            Self::Email { smtp_url, .. }
+                if !smtp_url.starts_with("smtp://") && !smtp_url.starts_with("smtps://") =>
+            {
+                Err(format!(
+                    "notifier {name}: smtp_url must be smtp:// or smtps://"
+                ))
+            }
+            Self::Email { to, .. } if to.is_empty() => Err(format!(
+                "notifier {name}: email needs at least one recipient in to"
+            )),
+            Self::Email { from, to, .. }
+                if std::iter::once(from)
+                    .chain(to)
+                    .any(|address| !address.contains('@') || address.contains(['\r', '\n'])) =>
+            {
+                Err(format!(
+                    "notifier {name}: from and to must be email addresses"
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Sends `events` at or above `min_severity` to the notifiers named in
+/// `to`. An `events` entry of `"*"` matches every event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationRoute {
+    #[serde(default = "all_events")]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub min_severity: NotificationSeverity,
+    pub to: Vec<String>,
+}
+
+fn all_events() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+impl NotificationRoute {
+    #[must_use]
+    pub fn matches(&self, notification: &Notification) -> bool {
+        notification.severity >= self.min_severity
+            && self
+                .events
+                .iter()
+                .any(|event| event == "*" || *event == notification.event)
+    }
+}
+
+/// Notifiers and routes, read from `SWARM_NOTIFICATIONS`. Unset sends
+/// nothing.
+///
+/// ```json
+/// {"notifiers": {
+///    "ops": {"type": "slack", "webhook_url": "https://hooks.slack.com/services/..."},
+///    "oncall": {"type": "email", "smtp_url": "smtps://smtp.example.com:465",
+///               "from": "swarm@example.com", "to": ["oncall@example.com"], "netrc": true}},
+///  "routes": [
+///    {"events": ["alert_fired", "bead_escalated"], "min_severity": "warning", "to": ["ops"]},
+///    {"events": ["*"], "min_severity": "critical", "to": ["oncall"]}]}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationRules {
+    pub notifiers: BTreeMap<String, NotifierSpec>,
+    pub routes: Vec<NotificationRoute>,
+}
+
+impl NotificationRules {
+    /// Rejects notifiers with unusable endpoints or addresses, and routes
+    /// that send nowhere or to a notifier that is not defined.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        self.notifiers
+            .iter()
+            .try_for_each(|(name, spec)| spec.validate(name))?;
+        self.routes
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, route)| {
+                if route.to.is_empty() || route.events.is_empty() {
+                    return Err(format!("routes[{index}] needs events and to"));
+                }
+                route
+                    .to
+                    .iter()
+                    .find(|name| !self.notifiers.contains_key(*name))
+                    .map_or(Ok(()), |name| {
+                        Err(format!("routes[{index}] names unknown notifier {name}"))
+                    })
+            })
+    }
+
+    /// Notifiers any route sends `notification` to, each once, in the order
+    /// the routes name them.
+    #[must_use]
+    pub fn recipients(&self, notification: &Notification) -> Vec<&str> {
+        let mut names = Vec::new();
+        for name in self
+            .routes
+            .iter()
+            .filter(|route| route.matches(notification))
+            .flat_map(|route| &route.to)
+        {
+            if !names.contains(&name.as_str()) {
+                names.push(name.as_str());
+            }
+        }
+        names
+    }
+}
+
+/// One thing worth telling a human about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    /// Event type, as in the execution event log (`alert_fired`,
+    /// `bead_escalated`, ...).
+    pub event: String,
+    pub severity: NotificationSeverity,
+    pub repo_id: String,
+    /// One-line summary.
+    pub text: String,
+    /// The alert, escalation or breach behind it.
+    pub details: Value,
+}
+
+impl Notification {
+    /// `[severity] event (repo): text`, the line every provider leads with.
+    #[must_use]
+    pub fn headline(&self) -> String {
+        format!(
+            "[{}] {} ({}): {}",
+            self.severity.as_str(),
+            self.event,
+            self.repo_id,
+            self.text
+        )
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rules() -> NotificationRules {
+        serde_json::from_value(json!({
+            "notifiers": {
+                "ops": {"type": "slack", "webhook_url": "https://hooks.example.com/a"},
+                "chat": {"type": "discord", "webhook_url": "https://hooks.example.com/b"},
+            },
+            "routes": [
+                {"events": ["alert_fired"], "min_severity": "warning", "to": ["ops", "chat"]},
+                {"min_severity": "critical", "to": ["ops"]},
+            ],
+        }))
+        .unwrap()
+    }
+
+    fn notification(event: &str, severity: NotificationSeverity) -> Notification {
+        Notification {
+            event: event.to_string(),
+            severity,
+            repo_id: "repo".to_string(),
+            text: "text".to_string(),
+            details: Value::Null,
+        }
+    }
+
+    #[test]
+    fn given_routes_when_routing_then_event_and_severity_pick_notifiers_once_each() {
+        let rules = rules();
+
+        assert_eq!(
+            rules.recipients(&notification("alert_fired", NotificationSeverity::Critical)),
+            vec!["ops", "chat"]
+        );
+        assert!(rules
+            .recipients(&notification("alert_fired", NotificationSeverity::Info))
+            .is_empty());
+        assert_eq!(
+            rules.recipients(&notification(
+                "bead_escalated",
+                NotificationSeverity::Critical
+            )),
+            vec!["ops"]
+        );
+    }
+
+    #[test]
+    fn given_invalid_rules_when_validating_then_they_are_rejected() {
+        let mut unknown = rules();
+        unknown.routes[0].to.push("pager".to_string());
+        let insecure: NotificationRules = serde_json::from_value(json!({
+            "notifiers": {"ops": {"type": "slack", "webhook_url": "file:///etc/passwd"}},
+        }))
+        .unwrap();
+        let no_recipients: NotificationRules = serde_json::from_value(json!({
+            "notifiers": {"mail": {
+                "type": "email", "smtp_url": "smtp://localhost", "from": "a@b", "to": []
+            }},
+        }))
+        .unwrap();
+
+        assert!(rules().validate().is_ok());
+        assert!(unknown.validate().is_err());
+        assert!(insecure.validate().is_err());
+        assert!(no_recipients.validate().is_err());
+    }
+}
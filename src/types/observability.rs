@@ -1,16 +1,33 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Layout of an `execution_events` payload. Older rows are upgraded on read
+/// by the chain in [`crate::types::upgrade_event_payload`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum EventSchemaVersion {
     V1,
+    /// Payload is always a JSON object.
+    V2,
 }
 
 impl EventSchemaVersion {
+    /// The version every writer stamps on new events.
+    pub const LATEST: Self = Self::V2;
+
     #[must_use]
     pub const fn as_i32(&self) -> i32 {
         match self {
             Self::V1 => 1,
+            Self::V2 => 2,
+        }
+    }
+
+    #[must_use]
+    pub const fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            1 => Some(Self::V1),
+            2 => Some(Self::V2),
+            _ => None,
         }
     }
 }
@@ -22,6 +39,9 @@ pub struct FailureDiagnostics {
     pub retryable: bool,
     pub next_command: String,
     pub detail: Option<String>,
+    /// Tests the failed stage's `JUnit`, TAP or cargo report marked failed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failing_tests: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +92,7 @@ mod tests {
                 retryable: true,
                 next_command: NEXT_COMMAND.to_string(),
                 detail: Some("assertion mismatch".to_string()),
+                failing_tests: vec!["math::divides".to_string()],
             }),
             payload: Some(json!({"transition": "retry"})),
             created_at: timestamp,
@@ -128,6 +149,7 @@ mod tests {
             retryable: true,
             next_command: NEXT_COMMAND.to_string(),
             detail: Some("operation exceeded budget".to_string()),
+            failing_tests: Vec::new(),
         };
 
         let encoded = serde_json::to_value(diagnostics).map_err(|e| e.to_string())?;
@@ -136,6 +158,7 @@ mod tests {
         assert_eq!(encoded["retryable"], json!(true));
         assert_eq!(encoded["next_command"], json!(NEXT_COMMAND));
         assert_eq!(encoded["detail"], json!("operation exceeded budget"));
+        assert!(encoded.get("failing_tests").is_none());
 
         Ok(())
     }
@@ -0,0 +1,224 @@
+//! Monthly range partitions for the tables that grow without bound.
+//! `maintenance partitions enable` turns `execution_events` or
+//! `stage_artifacts` into a table partitioned on `created_at`, keeping the
+//! rows it already had as one `<table>_legacy` partition. From then on the
+//! `partitions` job keeps a partition per month created ahead of time, and
+//! old months can be detached and dropped. A `<table>_default` partition
+//! takes rows no month covers yet, so a missed job never fails a write. Queries keep using the table's
+//! own name, so reads and writes do not change.
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Names `maintenance partitions` accepts for `table`.
+pub const PARTITIONED_TABLES: &[&str] = &["execution_events", "stage_artifacts"];
+/// Months after the current one that get a partition by default.
+pub const DEFAULT_PARTITIONS_AHEAD: u32 = 3;
+/// Most months ahead a partition can be created for.
+pub const MAX_PARTITIONS_AHEAD: u32 = 24;
+
+const LEGACY_SUFFIX: &str = "_legacy";
+const DEFAULT_SUFFIX: &str = "_default";
+
+/// A table that can be partitioned by month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartitionedTable {
+    ExecutionEvents,
+    StageArtifacts,
+}
+
+impl PartitionedTable {
+    pub const ALL: [Self; 2] = [Self::ExecutionEvents, Self::StageArtifacts];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::ExecutionEvents => "execution_events",
+            Self::StageArtifacts => "stage_artifacts",
+        }
+    }
+
+    /// The serial key column; partitioned, the primary key becomes this
+    /// column plus `created_at`.
+    #[must_use]
+    pub const fn key_column(self) -> &'static str {
+        match self {
+            Self::ExecutionEvents => "seq",
+            Self::StageArtifacts => "id",
+        }
+    }
+
+    /// The partition holding every row from before partitioning.
+    #[must_use]
+    pub fn legacy_partition(self) -> String {
+        format!("{}{LEGACY_SUFFIX}", self.as_str())
+    }
+
+    /// The partition catching rows past the last monthly partition, until
+    /// their month's partition is created.
+    #[must_use]
+    pub fn default_partition(self) -> String {
+        format!("{}{DEFAULT_SUFFIX}", self.as_str())
+    }
+
+    /// The partition for the month starting at `month`, e.g.
+    /// `execution_events_p2026_10`.
+    #[must_use]
+    pub fn partition_name(self, month: NaiveDate) -> String {
+        format!(
+            "{}_p{:04}_{:02}",
+            self.as_str(),
+            month.year(),
+            month.month()
+        )
+    }
+
+    /// The month a partition named by [`Self::partition_name`] holds.
+    #[must_use]
+    pub fn partition_month(self, name: &str) -> Option<NaiveDate> {
+        let suffix = name.strip_prefix(self.as_str())?.strip_prefix("_p")?;
+        let (year, month) = suffix.split_once('_')?;
+        let digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if year.len() != 4 || month.len() != 2 || !digits(year) || !digits(month) {
+            return None;
+        }
+        NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1)
+    }
+
+    /// Whether `name` is one of this table's partitions.
+    #[must_use]
+    pub fn owns_partition(self, name: &str) -> bool {
+        name == self.legacy_partition() || self.partition_month(name).is_some()
+    }
+
+    /// The table a partition belongs to.
+    #[must_use]
+    pub fn of_partition(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|table| table.owns_partition(name))
+    }
+}
+
+impl std::fmt::Display for PartitionedTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for PartitionedTable {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|table| table.as_str() == value)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown table: {value}, expected one of {}",
+                    PARTITIONED_TABLES.join(", ")
+                )
+            })
+    }
+}
+
+/// One partition of a partitioned table, attached or detached.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TablePartition {
+    pub name: String,
+    pub attached: bool,
+    /// First `created_at` it holds; `None` for the legacy partition.
+    pub from: Option<DateTime<Utc>>,
+    /// First `created_at` past it.
+    pub to: DateTime<Utc>,
+    pub rows_estimate: i64,
+}
+
+/// A partitioned table and its partitions, oldest first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartitionedTableState {
+    pub table: PartitionedTable,
+    /// Where the legacy partition ends and monthly partitions begin.
+    pub cutover_at: DateTime<Utc>,
+    pub partitions: Vec<TablePartition>,
+}
+
+/// The first day of the month `at` falls in.
+#[must_use]
+pub fn month_start(at: DateTime<Utc>) -> NaiveDate {
+    at.date_naive()
+        .with_day(1)
+        .unwrap_or_else(|| at.date_naive())
+}
+
+/// Midnight UTC at the start of `month`.
+#[must_use]
+pub fn month_start_at(month: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&month.and_time(chrono::NaiveTime::MIN))
+}
+
+/// The month after `month`.
+#[must_use]
+pub const fn next_month(month: NaiveDate) -> Option<NaiveDate> {
+    month.checked_add_months(Months::new(1))
+}
+
+/// Months that need a partition so rows can be written from `now` through
+/// `ahead` months after it. Months before `cutover` are the legacy
+/// partition's.
+#[must_use]
+pub fn months_to_cover(cutover: NaiveDate, now: DateTime<Utc>, ahead: u32) -> Vec<NaiveDate> {
+    let current = month_start(now);
+    (0..=ahead)
+        .filter_map(|offset| current.checked_add_months(Months::new(offset)))
+        .filter(|month| *month >= cutover)
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("valid date")
+    }
+
+    #[test]
+    fn given_partition_names_when_parsing_then_only_this_tables_months_match() {
+        let table = PartitionedTable::ExecutionEvents;
+        let name = table.partition_name(date(2026, 10, 1));
+
+        assert_eq!(name, "execution_events_p2026_10");
+        assert_eq!(table.partition_month(&name), Some(date(2026, 10, 1)));
+        assert_eq!(table.partition_month("execution_events_p2026_13"), None);
+        assert_eq!(table.partition_month("execution_events_p26_10"), None);
+        assert_eq!(table.partition_month("execution_events_p+202_10"), None);
+        assert_eq!(
+            PartitionedTable::of_partition("stage_artifacts_legacy"),
+            Some(PartitionedTable::StageArtifacts)
+        );
+        assert_eq!(
+            PartitionedTable::of_partition("execution_events; DROP TABLE x"),
+            None
+        );
+    }
+
+    #[test]
+    fn given_cutover_when_listing_months_to_cover_then_legacy_months_are_skipped() {
+        let now = Utc
+            .with_ymd_and_hms(2026, 11, 20, 12, 0, 0)
+            .single()
+            .expect("valid time");
+
+        assert_eq!(
+            months_to_cover(date(2026, 12, 1), now, 3),
+            vec![date(2026, 12, 1), date(2027, 1, 1), date(2027, 2, 1)]
+        );
+        assert_eq!(
+            months_to_cover(date(2026, 1, 1), now, 1),
+            vec![date(2026, 11, 1), date(2026, 12, 1)]
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Execution provenance for a bead, in the in-toto Statement v1 layout with
+//! a SLSA v1 provenance predicate.
+//!
+//! The statement's subjects are the bead's stored artifacts, keyed by
+//! content hash. The predicate records the stage runs that produced them,
+//! the agents that ran each stage, the gate commands, and the toolchain
+//! versions each stage run recorded as it started.
+
+use super::environment::EnvironmentFingerprint;
+use crate::signing::{content_hash, SignedArtifactRecord};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub const IN_TOTO_STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+pub const SLSA_PROVENANCE_TYPE: &str = "https://slsa.dev/provenance/v1";
+pub const SWARM_BUILD_TYPE: &str =
+    "https://github.com/lprior-repo/shitty-swarm-manager/attest/pipeline/v1";
+
+/// One `stage_history` row of the bead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvenanceStageRun {
+    pub stage_history_id: i64,
+    pub agent_id: u32,
+    pub stage: String,
+    pub attempt: u32,
+    pub status: String,
+    /// Gate command the stage ran; `None` for the LLM skill stages.
+    pub command: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    /// Fingerprint from `stage_history.metadata.environment`; `None` for
+    /// runs recorded without one.
+    pub environment: Option<EnvironmentFingerprint>,
+}
+
+/// A tool's `--version` line, or `None` when it is not installed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolVersion {
+    pub name: String,
+    pub version: Option<String>,
+}
+
+/// Builds the provenance statement for `bead_id`. `stage_runs` and
+/// `artifacts` are expected oldest first.
+#[must_use]
+pub fn provenance_statement(
+    repo_id: &str,
+    bead_id: &str,
+    stage_runs: &[ProvenanceStageRun],
+    artifacts: &[SignedArtifactRecord],
+) -> Value {
+    let subject = artifacts
+        .iter()
+        .map(|artifact| {
+            json!({
+                "name": format!("{bead_id}/{}/{}", artifact.artifact_type, artifact.artifact_id),
+                "digest": {
+                    "sha256": artifact
+                        .content_hash
+                        .clone()
+                        .unwrap_or_else(|| content_hash(&artifact.content)),
+                },
+                "annotations": {
+                    "stage_history_id": artifact.stage_history_id,
+                    "signing_key": artifact.signing_key,
+                    "signature": artifact.signature,
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let resolved_dependencies = resolved_dependencies(stage_runs)
+        .into_iter()
+        .map(|(tool, stage_history_ids)| {
+            json!({
+                "name": tool.name,
+                "annotations": {
+                    "version": tool.version,
+                    "stage_history_ids": stage_history_ids,
+                },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let byproducts = stage_runs
+        .iter()
+        .map(|run| {
+            json!({
+                "name": format!("stage/{}/attempt-{}", run.stage, run.attempt),
+                "annotations": run,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut agents = stage_runs
+        .iter()
+        .map(|run| run.agent_id)
+        .collect::<Vec<_>>();
+    agents.sort_unstable();
+    agents.dedup();
+
+    let started_on = stage_runs.iter().map(|run| run.started_at).min();
+    let finished_on = stage_runs.iter().filter_map(|run| run.completed_at).max();
+
+    json!({
+        "_type": IN_TOTO_STATEMENT_TYPE,
+        "subject": subject,
+        "predicateType": SLSA_PROVENANCE_TYPE,
+        "predicate": {
+            "buildDefinition": {
+                "buildType": SWARM_BUILD_TYPE,
+                "externalParameters": {
+                    "repo_id": repo_id,
+                    "bead_id": bead_id,
+                },
+                "internalParameters": {
+                    "stages": stage_runs
+                        .iter()
+                        .map(|run| run.stage.as_str())
+                        .collect::<Vec<_>>(),
+                    "agents": agents
+                        .iter()
+                        .map(|agent_id| format!("{repo_id}/agent-{agent_id}"))
+                        .collect::<Vec<_>>(),
+                },
+                "resolvedDependencies": resolved_dependencies,
+            },
+            "runDetails": {
+                "builder": {
+                    "id": format!("swarm://{repo_id}"),
+                    "version": {"shitty-swarm-manager": env!("CARGO_PKG_VERSION")},
+                },
+                "metadata": {
+                    "invocationId": format!("{repo_id}/{bead_id}"),
+                    "startedOn": started_on,
+                    "finishedOn": finished_on,
+                },
+                "byproducts": byproducts,
+            },
+        },
+    })
+}
+
+/// Each distinct tool version the runs' fingerprints recorded, in first-seen
+/// order, with the stage runs that saw it.
+fn resolved_dependencies(stage_runs: &[ProvenanceStageRun]) -> Vec<(&ToolVersion, Vec<i64>)> {
+    let mut resolved: Vec<(&ToolVersion, Vec<i64>)> = Vec::new();
+    for run in stage_runs {
+        let Some(environment) = run.environment.as_ref() else {
+            continue;
+        };
+        for tool in &environment.tools {
+            match resolved.iter_mut().find(|(seen, _)| *seen == tool) {
+                Some((_, ids)) => ids.push(run.stage_history_id),
+                None => resolved.push((tool, vec![run.stage_history_id])),
+            }
+        }
+    }
+    resolved
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn run(id: i64, agent_id: u32, stage: &str, minute: u32) -> ProvenanceStageRun {
+        ProvenanceStageRun {
+            stage_history_id: id,
+            agent_id,
+            stage: stage.to_string(),
+            attempt: 1,
+            status: "passed".to_string(),
+            command: None,
+            started_at: Utc.with_ymd_and_hms(2026, 1, 1, 10, minute, 0).unwrap(),
+            completed_at: Some(Utc.with_ymd_and_hms(2026, 1, 1, 10, minute + 1, 0).unwrap()),
+            duration_ms: Some(60_000),
+            environment: Some(EnvironmentFingerprint {
+                os: "linux".to_string(),
+                arch: "x86_64".to_string(),
+                tools: vec![ToolVersion {
+                    name: "moon".to_string(),
+                    version: Some(format!("moon 1.{minute}.0")),
+                }],
+            }),
+        }
+    }
+
+    #[test]
+    fn given_stage_runs_and_artifacts_when_attesting_then_statement_follows_slsa_layout() {
+        let mut runs = vec![
+            run(1, 2, "implement", 0),
+            run(2, 1, "red-queen", 5),
+            run(3, 1, "red-queen", 5),
+        ];
+        runs[2].attempt = 2;
+        let artifacts = vec![SignedArtifactRecord {
+            artifact_id: 7,
+            stage_history_id: 2,
+            artifact_type: "test_results".to_string(),
+            content: "{}".to_string(),
+            content_hash: None,
+            signature: None,
+            signing_key: None,
+        }];
+
+        let statement = provenance_statement("repo", "swm-1", &runs, &artifacts);
+
+        assert_eq!(statement["_type"], IN_TOTO_STATEMENT_TYPE);
+        assert_eq!(statement["predicateType"], SLSA_PROVENANCE_TYPE);
+        assert_eq!(statement["subject"][0]["name"], "swm-1/test_results/7");
+        assert_eq!(
+            statement["subject"][0]["digest"]["sha256"],
+            content_hash("{}")
+        );
+        let predicate = &statement["predicate"];
+        assert_eq!(
+            predicate["buildDefinition"]["internalParameters"]["agents"],
+            json!(["repo/agent-1", "repo/agent-2"])
+        );
+        assert_eq!(
+            predicate["buildDefinition"]["resolvedDependencies"],
+            json!([
+                {"name": "moon", "annotations": {"version": "moon 1.0.0", "stage_history_ids": [1]}},
+                {"name": "moon", "annotations": {"version": "moon 1.5.0", "stage_history_ids": [2, 3]}},
+            ])
+        );
+        assert_eq!(
+            predicate["runDetails"]["metadata"]["finishedOn"],
+            json!(runs[1].completed_at)
+        );
+        assert_eq!(
+            predicate["runDetails"]["byproducts"][1]["name"],
+            "stage/red-queen/attempt-1"
+        );
+    }
+}
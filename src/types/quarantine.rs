@@ -0,0 +1,191 @@
+//! Quarantine types for keeping misbehaving agents away from new beads.
+//!
+//! A quarantined agent keeps any bead it already holds but cannot claim
+//! another one until an operator releases it with a recorded reason.
+
+use super::health_metrics::{AgentBehaviorWindow, BehavioralFingerprint};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Who put an agent into quarantine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuarantineSource {
+    /// An operator ran `swarm quarantine`.
+    Manual,
+    /// The health monitor tripped a [`QuarantinePolicy`] threshold.
+    Automatic,
+}
+
+impl QuarantineSource {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Automatic => "automatic",
+        }
+    }
+}
+
+impl TryFrom<&str> for QuarantineSource {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "manual" => Ok(Self::Manual),
+            "automatic" => Ok(Self::Automatic),
+            _ => Err(format!("Unknown quarantine source: {value}")),
+        }
+    }
+}
+
+/// One quarantine of one agent. `released_at` and `release_reason` are set
+/// together when an operator lifts it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentQuarantine {
+    pub agent_id: u32,
+    pub source: QuarantineSource,
+    pub reason: String,
+    pub quarantined_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub release_reason: Option<String>,
+}
+
+impl AgentQuarantine {
+    /// Whether the agent is still barred from claiming.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.released_at.is_none()
+    }
+}
+
+/// Thresholds past which the health monitor quarantines an agent.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QuarantinePolicy {
+    /// Failure rate in the current window above which the agent is quarantined.
+    pub max_failure_rate: f64,
+    /// Stage runs the current window needs before its failure rate counts.
+    pub min_stage_runs: u32,
+    /// Consecutive failed stages above which the agent is quarantined.
+    pub max_consecutive_failures: u32,
+    /// Scope violations in the current window above which the agent is quarantined.
+    pub max_scope_violations: u32,
+}
+
+impl Default for QuarantinePolicy {
+    fn default() -> Self {
+        Self {
+            max_failure_rate: 0.5,
+            min_stage_runs: 4,
+            max_consecutive_failures: 4,
+            max_scope_violations: 2,
+        }
+    }
+}
+
+impl QuarantinePolicy {
+    /// The reason to quarantine, or `None` while every threshold holds.
+    #[must_use]
+    pub fn evaluate(
+        &self,
+        fingerprint: &BehavioralFingerprint,
+        current: Option<&AgentBehaviorWindow>,
+        scope_violations: u32,
+    ) -> Option<String> {
+        if scope_violations > self.max_scope_violations {
+            return Some(format!(
+                "{scope_violations} scope violations this window (limit {})",
+                self.max_scope_violations
+            ));
+        }
+        if fingerprint.consecutive_failures > self.max_consecutive_failures {
+            return Some(format!(
+                "{} consecutive failures (limit {})",
+                fingerprint.consecutive_failures, self.max_consecutive_failures
+            ));
+        }
+        current
+            .filter(|window| window.stage_runs >= self.min_stage_runs)
+            .filter(|window| window.failure_rate() > self.max_failure_rate)
+            .map(|window| {
+                format!(
+                    "failure rate {:.2} over {} stage runs (limit {:.2})",
+                    window.failure_rate(),
+                    window.stage_runs,
+                    self.max_failure_rate
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FINGERPRINT_WINDOW_SECS;
+
+    fn fingerprint(consecutive_failures: u32) -> BehavioralFingerprint {
+        BehavioralFingerprint::new(
+            "1".to_string(),
+            None,
+            "implement".to_string(),
+            consecutive_failures,
+            0,
+            0,
+        )
+    }
+
+    fn window(stage_runs: u32, failures: u32) -> AgentBehaviorWindow {
+        AgentBehaviorWindow {
+            agent_id: 1,
+            window_start: DateTime::<Utc>::UNIX_EPOCH,
+            window_secs: FINGERPRINT_WINDOW_SECS,
+            stage_runs,
+            failures,
+            retries: 0,
+            beads: 1,
+            avg_stage_ms: 1_000,
+        }
+    }
+
+    #[test]
+    fn given_agent_within_thresholds_when_evaluating_then_no_quarantine() {
+        let policy = QuarantinePolicy::default();
+
+        assert_eq!(
+            policy.evaluate(&fingerprint(1), Some(&window(8, 2)), 1),
+            None
+        );
+    }
+
+    #[test]
+    fn given_high_failure_rate_when_evaluating_then_reason_names_the_rate() {
+        let policy = QuarantinePolicy::default();
+
+        let reason = policy.evaluate(&fingerprint(0), Some(&window(6, 4)), 0);
+
+        assert!(reason.is_some_and(|reason| reason.starts_with("failure rate 0.67")));
+    }
+
+    #[test]
+    fn given_too_few_runs_when_evaluating_then_failure_rate_is_ignored() {
+        let policy = QuarantinePolicy::default();
+
+        assert_eq!(
+            policy.evaluate(&fingerprint(0), Some(&window(2, 2)), 0),
+            None
+        );
+    }
+
+    #[test]
+    fn given_scope_violations_over_limit_when_evaluating_then_they_take_precedence() {
+        let policy = QuarantinePolicy::default();
+
+        let reason = policy.evaluate(&fingerprint(9), None, 3);
+
+        assert_eq!(
+            reason.as_deref(),
+            Some("3 scope violations this window (limit 2)")
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Recovery types for cleaning up after an interrupted coordinator.
+//!
+//! `swarm recover` and the protocol loop's startup pass report every row
+//! they touched as a [`RecoveryAction`], so an operator can see what a crash
+//! left behind.
+
+use serde::{Deserialize, Serialize};
+
+/// Why a claim was taken back from its agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimRecoveryReason {
+    /// The agent stopped heartbeating and the lease ran out.
+    LeaseExpired,
+    /// The claim names an agent that is no longer registered.
+    AgentUnregistered,
+    /// The agent is registered but no longer working the bead.
+    AgentMovedOn,
+}
+
+impl ClaimRecoveryReason {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::LeaseExpired => "lease_expired",
+            Self::AgentUnregistered => "agent_unregistered",
+            Self::AgentMovedOn => "agent_moved_on",
+        }
+    }
+}
+
+/// One correction made by a recovery pass.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RecoveryAction {
+    /// An in-progress claim was released and its bead put back to pending.
+    ClaimRequeued {
+        repo_id: String,
+        bead_id: String,
+        agent_id: u32,
+        reason: ClaimRecoveryReason,
+    },
+    /// A backlog bead marked in progress with no claim behind it was put
+    /// back to pending.
+    BacklogRequeued { repo_id: String, bead_id: String },
+    /// A lapsed bead reservation was dropped.
+    ReservationExpired {
+        repo_id: String,
+        bead_id: String,
+        agent_id: u32,
+    },
+    /// A lapsed resource lock was dropped.
+    LockExpired { resource: String, agent: String },
+    /// A lock waiter whose wait ran out was dropped from the queue.
+    LockWaiterExpired { resource: String, agent: String },
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn given_requeued_claim_when_serialized_then_action_and_reason_are_snake_case() {
+        let action = RecoveryAction::ClaimRequeued {
+            repo_id: "local".to_string(),
+            bead_id: "bd-1".to_string(),
+            agent_id: 3,
+            reason: ClaimRecoveryReason::AgentUnregistered,
+        };
+
+        let value = serde_json::to_value(&action).unwrap();
+
+        assert_eq!(value["action"], json!("claim_requeued"));
+        assert_eq!(
+            value["reason"],
+            json!(ClaimRecoveryReason::AgentUnregistered.as_str())
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Resource lock types for `swarm lock` and the `swarm locks` view.
+//!
+//! A lock has at most one holder. Agents that asked to wait sit in a FIFO
+//! queue per resource; only the head of the queue may take a freed lock.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Why a lock is held, recorded alongside the holder and each waiter.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockMetadata {
+    pub purpose: Option<String>,
+    pub bead_id: Option<String>,
+}
+
+/// The agent currently holding a resource.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockHolder {
+    pub agent: String,
+    #[serde(flatten)]
+    pub metadata: LockMetadata,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Time left before the lock expires on its own.
+    pub remaining_ttl_ms: i64,
+}
+
+/// An agent queued for a resource. `position` starts at 1 for the head.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockWaiter {
+    pub position: i64,
+    pub agent: String,
+    #[serde(flatten)]
+    pub metadata: LockMetadata,
+    pub ttl_ms: i64,
+    pub enqueued_at: DateTime<Utc>,
+    /// Time left before the waiter gives up.
+    pub remaining_wait_ms: i64,
+}
+
+/// One resource with its holder, if any, and its waiters in queue order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLockView {
+    pub resource: String,
+    pub holder: Option<LockHolder>,
+    pub waiters: Vec<LockWaiter>,
+}
@@ -6,13 +6,15 @@
 #![forbid(unsafe_code)]
 
 use super::agent_types::AgentStatus;
-use super::artifacts::ArtifactType;
+use super::artifacts::{ArtifactType, StageArtifact};
+use super::diff::BeadDiff;
 use super::identifiers::BeadId;
 use super::observability::FailureDiagnostics;
 use super::stage::Stage;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashSet;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResumeContextProjection {
@@ -38,10 +40,31 @@ pub struct ResumeStageAttempt {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResumeArtifactSummary {
+    pub stage_history_id: i64,
     pub artifact_type: ArtifactType,
     pub created_at: DateTime<Utc>,
     pub content_hash: Option<String>,
     pub byte_length: u64,
+    /// Content of a `summary` artifact; other kinds carry only their size.
+    pub summary: Option<String>,
+}
+
+/// Drops the transcript artifacts of every stage run that has a `summary`,
+/// keeping the rest in order.
+#[must_use]
+pub fn prefer_stage_summaries(artifacts: Vec<ResumeArtifactSummary>) -> Vec<ResumeArtifactSummary> {
+    let summarized = artifacts
+        .iter()
+        .filter(|artifact| artifact.artifact_type == ArtifactType::Summary)
+        .map(|artifact| artifact.stage_history_id)
+        .collect::<HashSet<_>>();
+    artifacts
+        .into_iter()
+        .filter(|artifact| {
+            !(summarized.contains(&artifact.stage_history_id)
+                && TRANSCRIPT_ARTIFACTS.contains(&artifact.artifact_type))
+        })
+        .collect()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +81,7 @@ pub struct ResumeContextContract {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResumeArtifactDetailContract {
+    pub id: i64,
     pub artifact_type: String,
     pub created_at: DateTime<Utc>,
     pub content: String,
@@ -66,6 +90,20 @@ pub struct ResumeArtifactDetailContract {
     pub byte_length: u64,
 }
 
+impl From<StageArtifact> for ResumeArtifactDetailContract {
+    fn from(artifact: StageArtifact) -> Self {
+        Self {
+            id: artifact.id,
+            artifact_type: artifact.artifact_type.as_str().to_string(),
+            created_at: artifact.created_at,
+            byte_length: u64::try_from(artifact.content.len()).unwrap_or(u64::MAX),
+            content: artifact.content,
+            metadata: artifact.metadata,
+            content_hash: artifact.content_hash,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeepResumeContextContract {
     pub agent_id: u32,
@@ -77,6 +115,130 @@ pub struct DeepResumeContextContract {
     pub attempts: Vec<ResumeStageAttemptContract>,
     pub diagnostics: Option<FailureDiagnostics>,
     pub artifacts: Vec<ResumeArtifactDetailContract>,
+    /// The bead's newest `diff` artifact, so a resuming agent can re-apply it.
+    #[serde(default)]
+    pub latest_diff: Option<BeadDiff>,
+    #[serde(default)]
+    pub truncated: Option<TruncationManifest>,
+}
+
+/// Rough bytes-per-token ratio used to turn a token budget into bytes.
+pub const BYTES_PER_TOKEN: u64 = 4;
+
+/// Artifact kinds holding raw transcripts; these are the first to go, and a
+/// stage run's `summary` stands in for them.
+pub const TRANSCRIPT_ARTIFACTS: &[ArtifactType] = &[
+    ArtifactType::StageLog,
+    ArtifactType::SkillInvocation,
+    ArtifactType::TestOutput,
+];
+
+/// Size ceiling for a serialized resume context. Both limits may be set;
+/// the tighter one wins.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContextBudget {
+    pub max_bytes: Option<u64>,
+    pub max_tokens: Option<u64>,
+}
+
+impl ContextBudget {
+    #[must_use]
+    pub const fn new(max_bytes: Option<u64>, max_tokens: Option<u64>) -> Self {
+        Self {
+            max_bytes,
+            max_tokens,
+        }
+    }
+
+    /// Effective limit in bytes, or `None` when the budget is unbounded.
+    #[must_use]
+    pub fn limit_bytes(&self) -> Option<u64> {
+        let from_tokens = self
+            .max_tokens
+            .map(|tokens| tokens.saturating_mul(BYTES_PER_TOKEN));
+        match (self.max_bytes, from_tokens) {
+            (Some(bytes), Some(tokens)) => Some(bytes.min(tokens)),
+            (bytes, tokens) => bytes.or(tokens),
+        }
+    }
+}
+
+/// What was cut to fit a context into its budget. Elided artifacts keep their
+/// entry (id, type, hash, length) with empty content so callers can fetch
+/// them later via `artifacts --bead-id`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TruncationManifest {
+    pub budget_bytes: u64,
+    pub original_bytes: u64,
+    /// Serialized size after truncation, not counting this manifest.
+    pub final_bytes: u64,
+    pub within_budget: bool,
+    pub elided_artifact_ids: Vec<i64>,
+    pub elided_attempt_feedback: u32,
+}
+
+impl DeepResumeContextContract {
+    /// Shrinks the context until it fits `budget`, recording every cut in
+    /// `truncated`. Diagnostics, top-level feedback, the latest attempt and
+    /// `latest_diff` are always kept. The artifact entry duplicating
+    /// `latest_diff` is elided first, then transcript artifacts, then the
+    /// remaining artifacts oldest first with stage summaries last, then
+    /// feedback on older attempts.
+    pub fn fit_to_budget(&mut self, budget: ContextBudget) {
+        self.truncated = None;
+        let Some(limit) = budget.limit_bytes() else {
+            return;
+        };
+        let original_bytes = self.encoded_len();
+        if original_bytes <= limit {
+            return;
+        }
+
+        let mut manifest = TruncationManifest {
+            budget_bytes: limit,
+            original_bytes,
+            ..TruncationManifest::default()
+        };
+
+        let latest_diff_id = self.latest_diff.as_ref().map(|diff| diff.artifact_id);
+        let mut order = (0..self.artifacts.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&index| {
+            let duplicate = Some(self.artifacts[index].id) == latest_diff_id;
+            let kind = self.artifacts[index].artifact_type.as_str();
+            let transcript = TRANSCRIPT_ARTIFACTS
+                .iter()
+                .any(|transcript| transcript.as_str() == kind);
+            let summary = kind == ArtifactType::Summary.as_str();
+            (!duplicate, !transcript, summary, index)
+        });
+        for index in order {
+            if self.encoded_len() <= limit {
+                break;
+            }
+            let artifact = &mut self.artifacts[index];
+            artifact.content.clear();
+            artifact.metadata = None;
+            manifest.elided_artifact_ids.push(artifact.id);
+        }
+
+        let older_attempts = self.attempts.len().saturating_sub(1);
+        for index in 0..older_attempts {
+            if self.encoded_len() <= limit {
+                break;
+            }
+            if self.attempts[index].feedback.take().is_some() {
+                manifest.elided_attempt_feedback += 1;
+            }
+        }
+
+        manifest.final_bytes = self.encoded_len();
+        manifest.within_budget = manifest.final_bytes <= limit;
+        self.truncated = Some(manifest);
+    }
+
+    fn encoded_len(&self) -> u64 {
+        serde_json::to_vec(self).map_or(0, |bytes| u64::try_from(bytes.len()).unwrap_or(u64::MAX))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +257,8 @@ pub struct ResumeArtifactSummaryContract {
     pub created_at: DateTime<Utc>,
     pub content_hash: Option<String>,
     pub byte_length: u64,
+    #[serde(default)]
+    pub summary: Option<String>,
 }
 
 impl ResumeContextContract {
@@ -120,6 +284,7 @@ impl ResumeContextContract {
                 created_at: artifact.created_at,
                 content_hash: artifact.content_hash.clone(),
                 byte_length: artifact.byte_length,
+                summary: artifact.summary.clone(),
             })
             .collect::<Vec<_>>();
 
@@ -161,14 +326,177 @@ mod tests {
                 completed_at: Some(now),
             }],
             artifacts: vec![ResumeArtifactSummary {
+                stage_history_id: 1,
                 artifact_type: ArtifactType::FailureDetails,
                 created_at: now,
                 content_hash: Some("abc123".to_string()),
                 byte_length: 42,
+                summary: None,
             }],
         }
     }
 
+    fn deep_context(artifacts: &[(i64, ArtifactType, usize)]) -> DeepResumeContextContract {
+        let now = Utc::now();
+        let attempt = |attempt_number, feedback: &str| ResumeStageAttemptContract {
+            stage: "implement".to_string(),
+            attempt_number,
+            status: "failed".to_string(),
+            feedback: Some(feedback.to_string()),
+            started_at: now,
+            completed_at: Some(now),
+        };
+        DeepResumeContextContract {
+            agent_id: 3,
+            bead_id: "swm-big".to_string(),
+            status: "working".to_string(),
+            current_stage: Some("implement".to_string()),
+            implementation_attempt: 2,
+            feedback: Some("retry".to_string()),
+            attempts: vec![attempt(1, &"a".repeat(200)), attempt(2, "latest")],
+            diagnostics: Some(FailureDiagnostics {
+                category: "test_failure".to_string(),
+                retryable: true,
+                next_command: "swarm agent --id 3".to_string(),
+                detail: Some("2 tests failed".to_string()),
+                failing_tests: vec!["tests::a".to_string(), "tests::b".to_string()],
+            }),
+            artifacts: artifacts
+                .iter()
+                .map(|(id, kind, size)| ResumeArtifactDetailContract {
+                    id: *id,
+                    artifact_type: kind.as_str().to_string(),
+                    created_at: now,
+                    content: "x".repeat(*size),
+                    metadata: None,
+                    content_hash: None,
+                    byte_length: *size as u64,
+                })
+                .collect(),
+            latest_diff: None,
+            truncated: None,
+        }
+    }
+
+    #[test]
+    fn given_context_within_budget_when_fitting_then_nothing_is_truncated() {
+        let mut context = deep_context(&[(1, ArtifactType::StageLog, 10)]);
+
+        context.fit_to_budget(ContextBudget::new(Some(1_000_000), None));
+
+        assert!(context.truncated.is_none());
+        assert_eq!(context.artifacts[0].content.len(), 10);
+    }
+
+    #[test]
+    fn given_oversized_context_when_fitting_then_transcripts_are_elided_before_newer_artifacts(
+    ) -> Result<(), String> {
+        let mut context = deep_context(&[
+            (1, ArtifactType::ContractDocument, 2_000),
+            (2, ArtifactType::StageLog, 4_000),
+            (3, ArtifactType::FailureDetails, 300),
+        ]);
+
+        context.fit_to_budget(ContextBudget::new(None, Some(500)));
+
+        let Some(manifest) = context.truncated.clone() else {
+            return Err("fitting left no truncation manifest".to_string());
+        };
+        assert_eq!(manifest.budget_bytes, 2_000);
+        assert_eq!(manifest.elided_artifact_ids, vec![2, 1]);
+        assert_eq!(manifest.elided_attempt_feedback, 0);
+        assert!(manifest.within_budget);
+        assert_eq!(context.artifacts[2].content.len(), 300);
+        assert!(context.diagnostics.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn given_tiny_budget_when_fitting_then_latest_attempt_and_diagnostics_survive(
+    ) -> Result<(), String> {
+        let mut context = deep_context(&[(1, ArtifactType::ImplementationCode, 300)]);
+
+        context.fit_to_budget(ContextBudget::new(Some(64), Some(1_000)));
+
+        let Some(manifest) = context.truncated.clone() else {
+            return Err("fitting left no truncation manifest".to_string());
+        };
+        assert!(!manifest.within_budget);
+        assert_eq!(manifest.elided_artifact_ids, vec![1]);
+        assert_eq!(manifest.elided_attempt_feedback, 1);
+        assert_eq!(context.attempts[1].feedback.as_deref(), Some("latest"));
+        assert!(context.diagnostics.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn given_latest_diff_when_fitting_then_its_duplicate_entry_goes_first_and_diff_survives(
+    ) -> Result<(), String> {
+        let mut context = deep_context(&[
+            (1, ArtifactType::StageLog, 1_500),
+            (2, ArtifactType::Diff, 1_500),
+        ]);
+        context.latest_diff = Some(BeadDiff {
+            artifact_id: 2,
+            stage_history_id: 9,
+            created_at: Utc::now(),
+            stat: crate::types::DiffStat::default(),
+            patch: "x".repeat(1_500),
+        });
+
+        context.fit_to_budget(ContextBudget::new(Some(4_500), None));
+
+        let Some(manifest) = context.truncated.clone() else {
+            return Err("fitting left no truncation manifest".to_string());
+        };
+        assert_eq!(manifest.elided_artifact_ids, vec![2]);
+        assert_eq!(context.artifacts[0].content.len(), 1_500);
+        assert_eq!(
+            context.latest_diff.as_ref().map(|diff| diff.patch.len()),
+            Some(1_500)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn given_summarized_stage_run_when_preferring_summaries_then_only_its_transcripts_are_dropped()
+    {
+        let now = Utc::now();
+        let artifact =
+            |stage_history_id, artifact_type, summary: Option<&str>| ResumeArtifactSummary {
+                stage_history_id,
+                artifact_type,
+                created_at: now,
+                content_hash: None,
+                byte_length: 10,
+                summary: summary.map(str::to_string),
+            };
+
+        let kept = prefer_stage_summaries(vec![
+            artifact(1, ArtifactType::StageLog, None),
+            artifact(1, ArtifactType::FailureDetails, None),
+            artifact(
+                1,
+                ArtifactType::Summary,
+                Some("implement attempt 1: failed"),
+            ),
+            artifact(2, ArtifactType::StageLog, None),
+        ]);
+
+        let kinds = kept
+            .iter()
+            .map(|artifact| (artifact.stage_history_id, artifact.artifact_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (1, ArtifactType::FailureDetails),
+                (1, ArtifactType::Summary),
+                (2, ArtifactType::StageLog),
+            ]
+        );
+    }
+
     #[test]
     fn resume_context_contract_from_projection_exposes_stable_minimal_payload() {
         let projection = sample_projection();
@@ -229,16 +557,20 @@ mod tests {
             ],
             artifacts: vec![
                 ResumeArtifactSummary {
+                    stage_history_id: 1,
                     artifact_type: ArtifactType::ContractDocument,
                     created_at: now,
                     content_hash: Some("hash-a".to_string()),
                     byte_length: 128,
+                    summary: None,
                 },
                 ResumeArtifactSummary {
+                    stage_history_id: 1,
                     artifact_type: ArtifactType::FailureDetails,
                     created_at: now,
                     content_hash: Some("hash-b".to_string()),
                     byte_length: 64,
+                    summary: None,
                 },
             ],
         };
@@ -278,10 +610,12 @@ mod tests {
                 completed_at: Some(now),
             }],
             artifacts: vec![ResumeArtifactSummary {
+                stage_history_id: 1,
                 artifact_type: ArtifactType::ErrorMessage,
                 created_at: now,
                 content_hash: None,
                 byte_length: 34,
+                summary: None,
             }],
         };
 
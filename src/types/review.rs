@@ -0,0 +1,127 @@
+//! Review verdicts on a bead. Reviewers are agents or people; only each
+//! reviewer's latest verdict counts, so a reviewer who asked for changes
+//! approves by submitting again.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Longest review comment, in bytes.
+pub const MAX_REVIEW_COMMENT_BYTES: usize = 16 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewVerdict {
+    Approve,
+    /// The reviewer wants changes before the bead lands.
+    Changes,
+}
+
+impl ReviewVerdict {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Approve => "approve",
+            Self::Changes => "changes",
+        }
+    }
+}
+
+impl TryFrom<&str> for ReviewVerdict {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "approve" => Ok(Self::Approve),
+            "changes" => Ok(Self::Changes),
+            _ => Err(format!("Unknown review verdict: {value}")),
+        }
+    }
+}
+
+/// One submitted review. `agent_id` is set when an agent reviewed; people
+/// are identified by `reviewer` alone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeadReview {
+    pub id: i64,
+    pub bead_id: String,
+    pub reviewer: String,
+    pub agent_id: Option<u32>,
+    pub verdict: ReviewVerdict,
+    pub comments: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Reviewers whose latest verdict on a bead is each verdict.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewTally {
+    pub approvals: u64,
+    pub changes_requested: u64,
+}
+
+impl ReviewTally {
+    /// Counts the latest verdict of each reviewer in `reviews`, which must be
+    /// oldest first.
+    #[must_use]
+    pub fn from_reviews(reviews: &[BeadReview]) -> Self {
+        let mut latest = std::collections::HashMap::new();
+        for review in reviews {
+            latest.insert(review.reviewer.as_str(), review.verdict);
+        }
+        latest
+            .values()
+            .fold(Self::default(), |tally, verdict| match verdict {
+                ReviewVerdict::Approve => Self {
+                    approvals: tally.approvals + 1,
+                    ..tally
+                },
+                ReviewVerdict::Changes => Self {
+                    changes_requested: tally.changes_requested + 1,
+                    ..tally
+                },
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn review(reviewer: &str, verdict: ReviewVerdict) -> BeadReview {
+        BeadReview {
+            id: 0,
+            bead_id: "bd-abc".to_string(),
+            reviewer: reviewer.to_string(),
+            agent_id: None,
+            verdict,
+            comments: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn review_verdict_roundtrip_preserves_values() {
+        for verdict in [ReviewVerdict::Approve, ReviewVerdict::Changes] {
+            assert_eq!(ReviewVerdict::try_from(verdict.as_str()), Ok(verdict));
+        }
+        assert!(ReviewVerdict::try_from("request-changes").is_err());
+    }
+
+    #[test]
+    fn tally_counts_only_each_reviewers_latest_verdict() {
+        let tally = ReviewTally::from_reviews(&[
+            review("agent:2", ReviewVerdict::Changes),
+            review("alice", ReviewVerdict::Approve),
+            review("agent:2", ReviewVerdict::Approve),
+            review("bob", ReviewVerdict::Changes),
+        ]);
+
+        assert_eq!(
+            tally,
+            ReviewTally {
+                approvals: 2,
+                changes_requested: 1,
+            }
+        );
+    }
+}
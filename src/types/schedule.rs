@@ -0,0 +1,433 @@
+//! Recurring coordinator jobs and the cron expressions that schedule them.
+//! `serve` runs each enabled job when its expression next matches after
+//! the job's last run; `jobs` lists, runs, disables and enables them.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A job `serve` runs on a schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScheduledJobKind {
+    /// `sync-backlog` for one round.
+    SyncBacklog,
+    /// `recover`: requeue orphaned claims and drop lapsed locks.
+    Recover,
+    /// Purge soft-deleted rows past their retention and SLA breaches of
+    /// beads no longer in the backlog.
+    Gc,
+    /// `monitor --view sla`, which records and emits new breaches.
+    SlaCheck,
+    /// Drain the audit and event write batcher.
+    MetricsFlush,
+    /// Create the coming months' partitions of partitioned tables.
+    Partitions,
+}
+
+impl ScheduledJobKind {
+    pub const ALL: [Self; 6] = [
+        Self::SyncBacklog,
+        Self::Recover,
+        Self::Gc,
+        Self::SlaCheck,
+        Self::MetricsFlush,
+        Self::Partitions,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::SyncBacklog => "sync-backlog",
+            Self::Recover => "recover",
+            Self::Gc => "gc",
+            Self::SlaCheck => "sla-check",
+            Self::MetricsFlush => "metrics-flush",
+            Self::Partitions => "partitions",
+        }
+    }
+
+    /// The schedule a job keeps when `SWARM_SCHEDULED_JOBS` does not name it.
+    #[must_use]
+    pub const fn default_cron(self) -> &'static str {
+        match self {
+            Self::SyncBacklog => "*/5 * * * *",
+            Self::Recover | Self::MetricsFlush => "* * * * *",
+            Self::Gc => "17 3 * * *",
+            Self::SlaCheck => "*/15 * * * *",
+            Self::Partitions => "43 2 * * *",
+        }
+    }
+}
+
+impl std::fmt::Display for ScheduledJobKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for ScheduledJobKind {
+    type Error = String;
+
+    fn try_from(s: &str) -> std::result::Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|job| job.as_str() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown job: {s} (expected one of {})",
+                    Self::ALL.map(Self::as_str).join(", ")
+                )
+            })
+    }
+}
+
+/// A five-field cron expression in UTC.
+///
+/// The fields are minute, hour, day of month, month and day of week (0-7,
+/// both 0 and 7 are Sunday). Fields take `*`, numbers, ranges `a-b`, steps
+/// `*/n` or `a-b/n`, and comma lists; `@hourly`, `@daily`, `@weekly` and
+/// `@monthly` are shorthands. As in cron, when both day fields are
+/// restricted a day matching either one matches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CronSchedule {
+    expr: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+/// How far ahead [`CronSchedule::next_after`] looks before giving up on an
+/// expression that never matches, such as February 31st.
+const CRON_SEARCH_DAYS: i64 = 366 * 8;
+
+impl CronSchedule {
+    /// Parses a cron expression.
+    ///
+    /// # Errors
+    /// Returns a message naming the field that does not parse or is out of
+    /// range.
+    pub fn parse(expr: &str) -> std::result::Result<Self, String> {
+        let expr = expr.trim();
+        let expanded = match expr {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields = expanded.split_whitespace().collect::<Vec<_>>();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(format!(
+                "cron expression {expr:?} must have 5 fields, got {}",
+                fields.len()
+            ));
+        };
+        let weekdays = parse_cron_field(weekday, "day of week", 0, 7)?;
+        Ok(Self {
+            expr: expr.to_string(),
+            minutes: parse_cron_field(minute, "minute", 0, 59)?,
+            hours: parse_cron_field(hour, "hour", 0, 23)?,
+            days: parse_cron_field(day, "day of month", 1, 31)?,
+            months: parse_cron_field(month, "month", 1, 12)?,
+            // Fold 7 onto 0 so Sunday is one bit.
+            weekdays: (weekdays | (weekdays >> 7)) & 0x7f,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.expr
+    }
+
+    /// Whether the expression matches the minute `at` falls in.
+    #[must_use]
+    pub fn matches(&self, at: DateTime<Utc>) -> bool {
+        has_bit(self.months, at.month())
+            && self.day_matches(at)
+            && has_bit(self.hours, at.hour())
+            && has_bit(self.minutes, at.minute())
+    }
+
+    /// The first matching minute strictly after `after`, or `None` if the
+    /// expression matches nothing in the next eight years.
+    #[must_use]
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let limit = after + Duration::days(CRON_SEARCH_DAYS);
+        let mut at = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        while at <= limit {
+            if !has_bit(self.months, at.month()) {
+                let (year, month) = if at.month() == 12 {
+                    (at.year() + 1, 1)
+                } else {
+                    (at.year(), at.month() + 1)
+                };
+                at = start_of_day(NaiveDate::from_ymd_opt(year, month, 1)?);
+            } else if !self.day_matches(at) {
+                at = start_of_day(at.date_naive().succ_opt()?);
+            } else if !has_bit(self.hours, at.hour()) {
+                at = at.with_minute(0)? + Duration::hours(1);
+            } else if !has_bit(self.minutes, at.minute()) {
+                at += Duration::minutes(1);
+            } else {
+                return Some(at);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, at: DateTime<Utc>) -> bool {
+        let day = has_bit(self.days, at.day());
+        let weekday = has_bit(self.weekdays, at.weekday().num_days_from_sunday());
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_time(chrono::NaiveTime::MIN))
+}
+
+const fn has_bit(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+fn parse_cron_field(raw: &str, name: &str, min: u32, max: u32) -> std::result::Result<u64, String> {
+    let invalid = || format!("cron {name} field {raw:?} is not valid ({min}-{max})");
+    let number = |text: &str| {
+        text.parse::<u32>()
+            .ok()
+            .filter(|value| (min..=max).contains(value))
+            .ok_or_else(invalid)
+    };
+    raw.split(',').try_fold(0_u64, |bits, part| {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(invalid)?,
+            ),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (number(start)?, number(end)?),
+                // `5/15` runs from 5 to the end of the range.
+                None if step > 1 => (number(range)?, max),
+                None => {
+                    let value = number(range)?;
+                    (value, value)
+                }
+            },
+        };
+        if start > end {
+            return Err(invalid());
+        }
+        Ok((start..=end)
+            .step_by(usize::try_from(step).unwrap_or(usize::MAX))
+            .fold(bits, |bits, value| bits | (1 << value)))
+    })
+}
+
+impl TryFrom<String> for CronSchedule {
+    type Error = String;
+
+    fn try_from(expr: String) -> std::result::Result<Self, String> {
+        Self::parse(&expr)
+    }
+}
+
+impl From<CronSchedule> for String {
+    fn from(schedule: CronSchedule) -> Self {
+        schedule.expr
+    }
+}
+
+impl std::fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expr)
+    }
+}
+
+/// Each job's schedule, read from `SWARM_SCHEDULED_JOBS`. A job the config
+/// does not name keeps its [`ScheduledJobKind::default_cron`]; `null` leaves
+/// it unscheduled, so only `jobs run-now` runs it.
+///
+/// ```json
+/// {"gc": "0 4 * * 0", "sla-check": "*/5 * * * *", "metrics-flush": null}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JobSchedules {
+    pub jobs: BTreeMap<ScheduledJobKind, Option<CronSchedule>>,
+}
+
+impl Default for JobSchedules {
+    fn default() -> Self {
+        Self {
+            jobs: ScheduledJobKind::ALL
+                .into_iter()
+                .map(|job| (job, CronSchedule::parse(job.default_cron()).ok()))
+                .collect(),
+        }
+    }
+}
+
+impl JobSchedules {
+    /// Parses a schedule config on top of the defaults.
+    ///
+    /// # Errors
+    /// Returns a message for malformed JSON, an unknown job, or an invalid
+    /// cron expression.
+    pub fn from_json(raw: &str) -> std::result::Result<Self, String> {
+        let overrides: BTreeMap<ScheduledJobKind, Option<CronSchedule>> =
+            serde_json::from_str(raw).map_err(|e| e.to_string())?;
+        let mut schedules = Self::default();
+        schedules.jobs.extend(overrides);
+        Ok(schedules)
+    }
+
+    #[must_use]
+    pub fn schedule(&self, job: ScheduledJobKind) -> Option<&CronSchedule> {
+        self.jobs.get(&job).and_then(Option::as_ref)
+    }
+
+    /// When `job` next runs: the first match after its last run, or after
+    /// `since` (when `serve` started) if it has never run.
+    #[must_use]
+    pub fn next_run_at(
+        &self,
+        job: ScheduledJobKind,
+        last_run_at: Option<DateTime<Utc>>,
+        since: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        self.schedule(job)
+            .and_then(|schedule| schedule.next_after(last_run_at.map_or(since, |at| at.max(since))))
+    }
+
+    /// Enabled jobs whose next run is at or before `now`, in
+    /// [`ScheduledJobKind::ALL`] order.
+    #[must_use]
+    pub fn due(
+        &self,
+        runs: &[ScheduledJobRun],
+        since: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Vec<ScheduledJobKind> {
+        ScheduledJobKind::ALL
+            .into_iter()
+            .filter(|job| {
+                let run = runs.iter().find(|run| run.job == *job);
+                run.is_none_or(|run| run.enabled)
+                    && self
+                        .next_run_at(*job, run.and_then(|run| run.last_run_at), since)
+                        .is_some_and(|next| next <= now)
+            })
+            .collect()
+    }
+}
+
+/// A job's stored state: whether it is enabled and how its last run went.
+/// Jobs that were never run or disabled have no row and are enabled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledJobRun {
+    pub job: ScheduledJobKind,
+    pub enabled: bool,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_ok: Option<bool>,
+    pub last_ms: Option<u64>,
+    pub last_result: Option<Value>,
+    pub last_error: Option<String>,
+}
+
+impl ScheduledJobRun {
+    #[must_use]
+    pub const fn never_run(job: ScheduledJobKind) -> Self {
+        Self {
+            job,
+            enabled: true,
+            last_run_at: None,
+            last_ok: None,
+            last_ms: None,
+            last_result: None,
+            last_error: None,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::{CronSchedule, JobSchedules, ScheduledJobKind, ScheduledJobRun};
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn given_cron_expressions_when_finding_next_run_then_the_first_later_match_is_returned() {
+        let at = |y, mo, d, h, mi| Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).single();
+        let now = Utc
+            .with_ymd_and_hms(2026, 12, 31, 23, 58, 30)
+            .single()
+            .unwrap();
+
+        let every_five = CronSchedule::parse("*/5 * * * *").unwrap();
+        assert_eq!(every_five.next_after(now), at(2027, 1, 1, 0, 0));
+
+        let nightly = CronSchedule::parse("17 3 * * *").unwrap();
+        assert_eq!(nightly.next_after(now), at(2027, 1, 1, 3, 17));
+
+        // 2027-01-01 is a Friday; the 15th or any Monday matches.
+        let either_day = CronSchedule::parse("0 9 15 * 1").unwrap();
+        assert_eq!(either_day.next_after(now), at(2027, 1, 4, 9, 0));
+
+        let sunday = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(sunday.next_after(now), at(2027, 1, 3, 0, 0));
+
+        assert_eq!(
+            CronSchedule::parse("0 0 31 2 *").unwrap().next_after(now),
+            None
+        );
+        assert!(CronSchedule::parse("60 * * * *").is_err());
+        assert!(CronSchedule::parse("* * * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[test]
+    fn given_job_runs_when_checking_due_jobs_then_disabled_and_recent_jobs_are_skipped() {
+        let schedules = JobSchedules::from_json(r#"{"metrics-flush": null}"#).unwrap();
+        let since = Utc
+            .with_ymd_and_hms(2026, 10, 16, 8, 0, 0)
+            .single()
+            .unwrap();
+        let now = Utc
+            .with_ymd_and_hms(2026, 10, 16, 8, 1, 0)
+            .single()
+            .unwrap();
+        let runs = vec![
+            ScheduledJobRun {
+                enabled: false,
+                ..ScheduledJobRun::never_run(ScheduledJobKind::SyncBacklog)
+            },
+            ScheduledJobRun {
+                last_run_at: Some(now),
+                ..ScheduledJobRun::never_run(ScheduledJobKind::Gc)
+            },
+        ];
+
+        let due = schedules.due(&runs, since, now);
+
+        assert_eq!(due, vec![ScheduledJobKind::Recover]);
+    }
+}
@@ -0,0 +1,120 @@
+//! Stored enum values the code cannot read.
+//!
+//! Status, stage and type columns are decoded with `TryFrom<&str>`, and a
+//! value outside the enum fails with `SwarmError::SchemaDrift` instead of
+//! being read as something else. [`enum_columns`] lists those columns with
+//! the values their enums accept; `swarm doctor` reports every other value
+//! still stored as an [`EnumDrift`].
+
+use super::agent_types::AgentStatus;
+use super::artifacts::ArtifactType;
+use super::backlog::BacklogStatus;
+use super::claim_types::ClaimStatus;
+use super::stage::Stage;
+use super::swarm_types::SwarmStatus;
+use serde::{Deserialize, Serialize};
+
+/// A column read as an enum, and the values that enum accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumColumn {
+    pub table: &'static str,
+    pub column: &'static str,
+    pub values: Vec<&'static str>,
+}
+
+/// Stored values of one column that its enum rejects, with how many rows
+/// hold each.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnumDrift {
+    pub table: String,
+    pub column: String,
+    pub value: String,
+    pub rows: u64,
+}
+
+/// Every column decoded through an enum, with its accepted values taken
+/// from the enum itself.
+#[must_use]
+pub fn enum_columns() -> Vec<EnumColumn> {
+    let entry = |table, column, values: Vec<&'static str>| EnumColumn {
+        table,
+        column,
+        values,
+    };
+    let stages = || Stage::ALL.iter().map(Stage::as_str).collect::<Vec<_>>();
+    vec![
+        entry(
+            "agent_state",
+            "status",
+            AgentStatus::ALL.iter().map(AgentStatus::as_str).collect(),
+        ),
+        entry("agent_state", "current_stage", stages()),
+        entry(
+            "bead_backlog",
+            "status",
+            BacklogStatus::ALL
+                .iter()
+                .map(BacklogStatus::as_str)
+                .collect(),
+        ),
+        entry(
+            "bead_claims",
+            "status",
+            ClaimStatus::ALL.iter().map(ClaimStatus::as_str).collect(),
+        ),
+        entry("stage_history", "stage", stages()),
+        entry(
+            "stage_artifacts",
+            "artifact_type",
+            ArtifactType::ALL_STRINGS.to_vec(),
+        ),
+        entry(
+            "swarm_config",
+            "swarm_status",
+            SwarmStatus::ALL.iter().map(SwarmStatus::as_str).collect(),
+        ),
+    ]
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::enum_columns;
+    use crate::types::{AgentStatus, ArtifactType, BacklogStatus, ClaimStatus, Stage, SwarmStatus};
+
+    #[test]
+    fn given_enum_columns_when_decoding_their_values_then_every_value_round_trips() {
+        for column in enum_columns() {
+            for value in &column.values {
+                let decoded = match (column.table, column.column) {
+                    ("agent_state", "status") => {
+                        AgentStatus::try_from(*value).map(|status| status.as_str())
+                    }
+                    ("bead_backlog", "status") => {
+                        BacklogStatus::try_from(*value).map(|status| status.as_str())
+                    }
+                    ("bead_claims", "status") => {
+                        ClaimStatus::try_from(*value).map(|status| status.as_str())
+                    }
+                    ("swarm_config", "swarm_status") => {
+                        SwarmStatus::try_from(*value).map(|status| status.as_str())
+                    }
+                    ("stage_artifacts", "artifact_type") => {
+                        ArtifactType::try_from(*value).map(|kind| kind.as_str())
+                    }
+                    _ => Stage::try_from(*value).map(|stage| stage.as_str()),
+                };
+                assert_eq!(decoded, Ok(*value), "{}.{}", column.table, column.column);
+            }
+        }
+    }
+
+    #[test]
+    fn given_unknown_backlog_status_when_decoding_then_it_is_rejected() {
+        assert!(BacklogStatus::try_from("stuck").is_err());
+        assert_eq!(
+            BacklogStatus::try_from("awaiting_approval"),
+            Ok(BacklogStatus::AwaitingApproval)
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! Backlog aging against per-priority SLA targets. A bead's age runs from
+//! when it was enqueued until it completes or is cancelled.
+
+use super::backlog::BACKLOG_PRIORITIES;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Per-priority SLA targets, read from `SWARM_SLA_TARGETS`. A priority
+/// without a target has no SLA; an omitted setting keeps its default.
+///
+/// ```json
+/// {"targets": {"p0": 4.0, "p1": 24.0}, "warn_percent": 75.0}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SlaTargets {
+    /// Hours a bead of each priority may stay open.
+    pub targets: BTreeMap<String, f64>,
+    /// Share of its target, in percent, past which a bead is approaching.
+    pub warn_percent: f64,
+}
+
+impl Default for SlaTargets {
+    fn default() -> Self {
+        Self {
+            targets: [("p0", 4.0), ("p1", 24.0), ("p2", 72.0), ("p3", 168.0)]
+                .into_iter()
+                .map(|(priority, hours)| (priority.to_string(), hours))
+                .collect(),
+            warn_percent: 80.0,
+        }
+    }
+}
+
+/// Where a bead stands against its priority's target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlaStatus {
+    /// Inside the target, or no target for its priority.
+    Ok,
+    Approaching,
+    Breached,
+}
+
+/// An open backlog entry and when it was enqueued.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacklogAge {
+    pub bead_id: String,
+    pub priority: String,
+    pub status: String,
+    pub enqueued_at: DateTime<Utc>,
+}
+
+/// One open bead measured against its target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlaAssessment {
+    pub bead_id: String,
+    pub priority: String,
+    pub status: String,
+    pub enqueued_at: DateTime<Utc>,
+    pub age_secs: i64,
+    pub target_secs: Option<i64>,
+    /// Age as a share of the target, in percent.
+    pub percent_of_target: Option<f64>,
+    pub sla: SlaStatus,
+}
+
+/// Aging of one priority's open beads.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlaPriorityAging {
+    pub priority: String,
+    pub target_secs: Option<i64>,
+    pub open: u32,
+    pub oldest_age_secs: i64,
+    pub mean_age_secs: i64,
+    pub approaching: u32,
+    pub breached: u32,
+}
+
+impl SlaTargets {
+    /// Rejects unknown priorities, targets that are not positive, and a
+    /// warning threshold outside (0, 100].
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(priority) = self
+            .targets
+            .keys()
+            .find(|priority| !BACKLOG_PRIORITIES.contains(&priority.as_str()))
+        {
+            return Err(format!(
+                "targets has unknown priority {priority}, expected one of {}",
+                BACKLOG_PRIORITIES.join(", ")
+            ));
+        }
+        if let Some((priority, hours)) = self.targets.iter().find(|(_, hours)| **hours <= 0.0) {
+            return Err(format!(
+                "targets.{priority} must be greater than 0 hours, got {hours}"
+            ));
+        }
+        if self.warn_percent <= 0.0 || self.warn_percent > 100.0 {
+            return Err(format!(
+                "warn_percent must be in (0, 100], got {}",
+                self.warn_percent
+            ));
+        }
+        Ok(())
+    }
+
+    /// Measures `item` against its priority's target at `now`.
+    #[must_use]
+    pub fn assess(&self, item: &BacklogAge, now: DateTime<Utc>) -> SlaAssessment {
+        let age_secs = (now - item.enqueued_at).num_seconds().max(0);
+        #[allow(clippy::cast_possible_truncation)]
+        let target_secs = self
+            .targets
+            .get(&item.priority)
+            .map(|hours| (hours * 3600.0).round() as i64)
+            .filter(|secs| *secs > 0);
+        #[allow(clippy::cast_precision_loss)]
+        let percent_of_target = target_secs.map(|target| age_secs as f64 * 100.0 / target as f64);
+        let sla = match percent_of_target {
+            Some(percent) if percent >= 100.0 => SlaStatus::Breached,
+            Some(percent) if percent >= self.warn_percent => SlaStatus::Approaching,
+            _ => SlaStatus::Ok,
+        };
+        SlaAssessment {
+            bead_id: item.bead_id.clone(),
+            priority: item.priority.clone(),
+            status: item.status.clone(),
+            enqueued_at: item.enqueued_at,
+            age_secs,
+            target_secs,
+            percent_of_target,
+            sla,
+        }
+    }
+}
+
+impl SlaPriorityAging {
+    /// One summary per priority with open beads, in priority order.
+    #[must_use]
+    pub fn summarize(assessments: &[SlaAssessment]) -> Vec<Self> {
+        let mut by_priority = BTreeMap::<&str, Vec<&SlaAssessment>>::new();
+        for assessment in assessments {
+            by_priority
+                .entry(assessment.priority.as_str())
+                .or_default()
+                .push(assessment);
+        }
+        by_priority
+            .into_iter()
+            .map(|(priority, items)| {
+                let open = u32::try_from(items.len()).unwrap_or(u32::MAX);
+                let total_age = items.iter().map(|item| item.age_secs).sum::<i64>();
+                let count = |sla| {
+                    u32::try_from(items.iter().filter(|item| item.sla == sla).count())
+                        .unwrap_or(u32::MAX)
+                };
+                Self {
+                    priority: priority.to_string(),
+                    target_secs: items.first().and_then(|item| item.target_secs),
+                    open,
+                    oldest_age_secs: items.iter().map(|item| item.age_secs).max().unwrap_or(0),
+                    mean_age_secs: total_age / i64::from(open.max(1)),
+                    approaching: count(SlaStatus::Approaching),
+                    breached: count(SlaStatus::Breached),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn aged(bead_id: &str, priority: &str, hours_ago: i64, now: DateTime<Utc>) -> BacklogAge {
+        BacklogAge {
+            bead_id: bead_id.to_string(),
+            priority: priority.to_string(),
+            status: "pending".to_string(),
+            enqueued_at: now - Duration::hours(hours_ago),
+        }
+    }
+
+    #[test]
+    fn given_ages_around_target_when_assessing_then_status_follows_warn_threshold() {
+        let targets = SlaTargets::default();
+        let now = Utc::now();
+
+        let statuses = [
+            aged("a", "p0", 1, now),
+            aged("b", "p1", 20, now),
+            aged("c", "p0", 4, now),
+        ]
+        .iter()
+        .map(|item| targets.assess(item, now).sla)
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            statuses,
+            [SlaStatus::Ok, SlaStatus::Approaching, SlaStatus::Breached]
+        );
+    }
+
+    #[test]
+    fn given_priority_without_target_when_assessing_then_it_is_never_breached() {
+        let targets: SlaTargets =
+            serde_json::from_str(r#"{"targets": {"p0": 1.0}}"#).expect("valid targets");
+        let now = Utc::now();
+
+        let assessment = targets.assess(&aged("a", "p2", 500, now), now);
+
+        assert_eq!(assessment.target_secs, None);
+        assert_eq!(assessment.sla, SlaStatus::Ok);
+        assert_eq!(targets.validate(), Ok(()));
+    }
+
+    #[test]
+    fn summarize_groups_by_priority_with_oldest_and_breach_counts() {
+        let targets = SlaTargets::default();
+        let now = Utc::now();
+        let assessments = [
+            aged("a", "p1", 2, now),
+            aged("b", "p0", 6, now),
+            aged("c", "p0", 2, now),
+        ]
+        .iter()
+        .map(|item| targets.assess(item, now))
+        .collect::<Vec<_>>();
+
+        let summary = SlaPriorityAging::summarize(&assessments);
+
+        assert_eq!(
+            summary
+                .iter()
+                .map(|s| s.priority.as_str())
+                .collect::<Vec<_>>(),
+            ["p0", "p1"]
+        );
+        assert_eq!(summary[0].open, 2);
+        assert_eq!(summary[0].breached, 1);
+        assert_eq!(summary[0].oldest_age_secs, 6 * 3600);
+        assert_eq!(summary[0].mean_age_secs, 4 * 3600);
+    }
+
+    #[test]
+    fn given_out_of_range_settings_when_validating_then_they_are_rejected() {
+        for raw in [
+            r#"{"targets": {"p9": 1.0}}"#,
+            r#"{"targets": {"p0": 0.0}}"#,
+            r#"{"warn_percent": 0.0}"#,
+        ] {
+            let targets: SlaTargets = serde_json::from_str(raw).expect("parses");
+            assert!(targets.validate().is_err(), "{raw}");
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Soft deletes. A claim, backlog entry or agent that is deleted keeps its
+//! row, with `deleted_at` and `deleted_by` saying when and by what it was
+//! deleted. No query on live rows sees it, `undelete` puts it back, and
+//! writing the key again (enqueueing the bead, claiming it, seeding the
+//! agent) revives it in place.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Names `undelete` accepts for the tables that keep deleted rows.
+pub const SOFT_DELETE_TABLES: &[&str] = &["claims", "backlog", "agents"];
+
+/// A table whose deleted rows are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SoftDeleteTable {
+    Claims,
+    Backlog,
+    Agents,
+}
+
+impl SoftDeleteTable {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Claims => "claims",
+            Self::Backlog => "backlog",
+            Self::Agents => "agents",
+        }
+    }
+
+    /// Every table that keeps its deleted rows.
+    pub const ALL: [Self; 3] = [Self::Claims, Self::Backlog, Self::Agents];
+
+    /// The table the rows live in.
+    #[must_use]
+    pub const fn table_name(&self) -> &'static str {
+        match self {
+            Self::Claims => "bead_claims",
+            Self::Backlog => "bead_backlog",
+            Self::Agents => "agent_state",
+        }
+    }
+
+    /// The column `undelete` takes the key from.
+    #[must_use]
+    pub const fn key_column(&self) -> &'static str {
+        match self {
+            Self::Claims | Self::Backlog => "bead_id",
+            Self::Agents => "agent_id",
+        }
+    }
+}
+
+impl TryFrom<&str> for SoftDeleteTable {
+    type Error = String;
+
+    /// Accepts the short name or the live table's name.
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "claims" | "bead_claims" => Ok(Self::Claims),
+            "backlog" | "bead_backlog" => Ok(Self::Backlog),
+            "agents" | "agent_state" => Ok(Self::Agents),
+            _ => Err(format!(
+                "Unknown table: {value}, expected one of {}",
+                SOFT_DELETE_TABLES.join(", ")
+            )),
+        }
+    }
+}
+
+/// A deleted row as it was when it was deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeletedRow {
+    pub table: SoftDeleteTable,
+    pub repo_id: String,
+    /// `bead_id` for claims and backlog entries, `agent_id` for agents.
+    pub key: String,
+    /// The row's other columns.
+    pub row: Value,
+    pub deleted_at: DateTime<Utc>,
+    /// The command that deleted it, or `lease_expired` for a claim the
+    /// lease sweep let go.
+    pub deleted_by: String,
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::unwrap_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_short_or_table_name_when_parsing_then_both_resolve_to_the_table() {
+        for name in SOFT_DELETE_TABLES {
+            let table = SoftDeleteTable::try_from(*name).expect("known table");
+            assert_eq!(table.as_str(), *name);
+            assert_eq!(SoftDeleteTable::try_from(table.table_name()), Ok(table));
+            assert!(SoftDeleteTable::ALL.contains(&table));
+        }
+        assert!(SoftDeleteTable::try_from("stage_history").is_err());
+    }
+}
@@ -11,6 +11,14 @@ pub enum Stage {
 }
 
 impl Stage {
+    pub const ALL: [Self; 5] = [
+        Self::RustContract,
+        Self::Implement,
+        Self::QaEnforcer,
+        Self::RedQueen,
+        Self::Done,
+    ];
+
     #[must_use]
     pub const fn as_str(&self) -> &'static str {
         match self {
@@ -61,6 +69,8 @@ pub enum StageResult {
     Passed,
     Failed(String),
     Error(String),
+    /// Stopped by `swarm cancel`; carries the cancel reason.
+    Cancelled(String),
 }
 
 impl StageResult {
@@ -71,13 +81,14 @@ impl StageResult {
             Self::Passed => "passed".to_string(),
             Self::Failed(_) => "failed".to_string(),
             Self::Error(_) => "error".to_string(),
+            Self::Cancelled(_) => "cancelled".to_string(),
         }
     }
 
     #[must_use]
     pub fn message(&self) -> Option<&str> {
         match self {
-            Self::Failed(msg) | Self::Error(msg) => Some(msg),
+            Self::Failed(msg) | Self::Error(msg) | Self::Cancelled(msg) => Some(msg),
             _ => None,
         }
     }
@@ -120,5 +131,10 @@ mod tests {
         assert_eq!(errored.as_str(), "error");
         assert_eq!(errored.message(), Some("boom"));
         assert!(!errored.is_success());
+
+        let cancelled = StageResult::Cancelled("operator".to_string());
+        assert_eq!(cancelled.as_str(), "cancelled");
+        assert_eq!(cancelled.message(), Some("operator"));
+        assert!(!cancelled.is_success());
     }
 }
@@ -18,6 +18,27 @@ pub struct SwarmConfig {
     pub claim_label: String,
     pub swarm_started_at: Option<DateTime<Utc>>,
     pub swarm_status: SwarmStatus,
+    /// How long a claim lasts without a heartbeat.
+    #[serde(default = "default_lease_ttl_ms")]
+    pub lease_ttl_ms: u32,
+    /// Extra time past a lapsed lease before recovery requeues the claim.
+    #[serde(default)]
+    pub heartbeat_grace_ms: u32,
+    /// How often a protocol session reruns the recovery pass.
+    #[serde(default = "default_recovery_scan_interval_ms")]
+    pub recovery_scan_interval_ms: u32,
+}
+
+pub const DEFAULT_LEASE_TTL_MS: u32 = 300_000;
+pub const DEFAULT_HEARTBEAT_GRACE_MS: u32 = 0;
+pub const DEFAULT_RECOVERY_SCAN_INTERVAL_MS: u32 = 30_000;
+
+const fn default_lease_ttl_ms() -> u32 {
+    DEFAULT_LEASE_TTL_MS
+}
+
+const fn default_recovery_scan_interval_ms() -> u32 {
+    DEFAULT_RECOVERY_SCAN_INTERVAL_MS
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -30,6 +51,14 @@ pub enum SwarmStatus {
 }
 
 impl SwarmStatus {
+    pub const ALL: [Self; 5] = [
+        Self::Initializing,
+        Self::Running,
+        Self::Paused,
+        Self::Complete,
+        Self::Error,
+    ];
+
     #[must_use]
     pub const fn as_str(&self) -> &'static str {
         match self {
@@ -77,6 +106,24 @@ pub struct AvailableAgent {
     pub max_agents: u32,
 }
 
+/// What one agent is doing right now, as shown by `swarm top`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentActivity {
+    pub agent_id: u32,
+    pub status: AgentStatus,
+    pub bead_id: Option<String>,
+    pub stage: Option<String>,
+    /// Seconds since the current stage started.
+    pub stage_elapsed_secs: Option<u64>,
+    /// Seconds since the agent last heartbeated its claim; `None` without one.
+    pub heartbeat_age_secs: Option<u64>,
+    pub attempt: u32,
+    /// Stages passed inside the throughput window.
+    pub stages_passed: u32,
+    /// Beads the agent took through `red-queen` inside the throughput window.
+    pub beads_completed: u32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -6,6 +6,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// A symbol kind in the codebase.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -299,6 +300,89 @@ impl SymbolRecord {
     }
 }
 
+/// A signature an agent reported for one attempt at a bead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolObservation {
+    /// Bead the symbol was recorded for.
+    pub bead_id: String,
+    /// Implementation attempt the signature belongs to.
+    pub attempt: u32,
+    /// Symbol name.
+    pub name: String,
+    /// Symbol kind.
+    pub kind: SymbolKind,
+    /// Module path.
+    pub module_path: String,
+    /// Signature as extracted by the agent.
+    pub signature: String,
+}
+
+/// Drift across attempts for one bead. The first attempt that recorded a
+/// symbol stands in for its contract; the latest attempt is the implementation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeadDriftReport {
+    /// Bead the report covers.
+    pub bead_id: String,
+    /// Earliest attempt with recorded symbols.
+    pub first_attempt: u32,
+    /// Latest attempt with recorded symbols.
+    pub latest_attempt: u32,
+    /// Symbols whose signature changed between attempts.
+    pub report: DriftReport,
+}
+
+impl BeadDriftReport {
+    /// Build one report per bead, ordered by bead id. Input order does not matter.
+    #[must_use]
+    pub fn from_observations(observations: &[SymbolObservation]) -> Vec<Self> {
+        let mut by_bead: BTreeMap<&str, BTreeMap<(&str, &str), Vec<&SymbolObservation>>> =
+            BTreeMap::new();
+        for observation in observations {
+            by_bead
+                .entry(observation.bead_id.as_str())
+                .or_default()
+                .entry((observation.module_path.as_str(), observation.name.as_str()))
+                .or_default()
+                .push(observation);
+        }
+
+        by_bead
+            .into_iter()
+            .map(|(bead_id, symbols)| {
+                let attempts = symbols
+                    .values()
+                    .flatten()
+                    .map(|observation| observation.attempt);
+                let first_attempt = attempts.clone().min().unwrap_or_default();
+                let latest_attempt = attempts.max().unwrap_or_default();
+                let tracked = symbols
+                    .into_values()
+                    .filter_map(|mut history| {
+                        history.sort_by_key(|observation| observation.attempt);
+                        let first = history.first()?;
+                        let latest = history.last()?;
+                        Some(
+                            TrackedSymbol::new(
+                                latest.name.clone(),
+                                latest.kind,
+                                latest.module_path.clone(),
+                            )
+                            .with_contract_signature(first.signature.clone())
+                            .with_implementation_signature(latest.signature.clone()),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                Self {
+                    bead_id: bead_id.to_string(),
+                    first_attempt,
+                    latest_attempt,
+                    report: DriftReport::from_symbols(&tracked),
+                }
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -363,6 +447,49 @@ mod tests {
         assert_eq!(report.drifted_symbols[0].name, "func_b");
     }
 
+    fn observation(bead_id: &str, attempt: u32, name: &str, signature: &str) -> SymbolObservation {
+        SymbolObservation {
+            bead_id: bead_id.to_string(),
+            attempt,
+            name: name.to_string(),
+            kind: SymbolKind::Function,
+            module_path: "crate::api".to_string(),
+            signature: signature.to_string(),
+        }
+    }
+
+    #[test]
+    fn given_signature_changed_between_attempts_when_building_bead_reports_then_drift_is_reported()
+    {
+        let observations = vec![
+            observation("bd-1", 2, "load", "fn(&str) -> Result<Config>"),
+            observation("bd-1", 1, "load", "fn(&Path) -> Result<Config>"),
+            observation("bd-1", 1, "save", "fn(&Config) -> Result<()>"),
+            observation("bd-1", 2, "save", "fn(&Config) -> Result<()>"),
+            observation("bd-2", 1, "init", "fn() -> Self"),
+        ];
+
+        let reports = BeadDriftReport::from_observations(&observations);
+
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].bead_id, "bd-1");
+        assert_eq!(
+            (reports[0].first_attempt, reports[0].latest_attempt),
+            (1, 2)
+        );
+        assert_eq!(reports[0].report.total_checked, 2);
+        assert_eq!(reports[0].report.drifted_symbols.len(), 1);
+        assert_eq!(
+            reports[0].report.drifted_symbols[0].contract_signature,
+            "fn(&Path) -> Result<Config>"
+        );
+        assert_eq!(
+            reports[0].report.drifted_symbols[0].implementation_signature,
+            "fn(&str) -> Result<Config>"
+        );
+        assert!(!reports[1].report.has_drift);
+    }
+
     #[test]
     fn test_drifted_symbol_description() {
         let drifted = DriftedSymbol {
@@ -0,0 +1,55 @@
+//! Tenants: isolated swarms hosted by one coordinator database. Each tenant
+//! lives in a Postgres schema of its own with its own config, agents and
+//! backlog; `public.tenants` lists them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of a tenant's schema name, so tenant schemas never collide with
+/// `public` or a `SWARM_DB_SCHEMA` swarm.
+pub const TENANT_SCHEMA_PREFIX: &str = "tenant_";
+
+/// Longest tenant name whose schema name Postgres keeps whole (63 bytes).
+pub const MAX_TENANT_NAME_LEN: usize = 63 - TENANT_SCHEMA_PREFIX.len();
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tenant {
+    pub name: String,
+    pub schema: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The schema a tenant's tables live in.
+///
+/// # Errors
+/// Returns a description of why `name` is not a valid tenant name: it must
+/// start with a lowercase letter and hold only lowercase letters, digits and
+/// underscores, at most [`MAX_TENANT_NAME_LEN`] of them.
+pub fn tenant_schema(name: &str) -> Result<String, String> {
+    let well_formed = name.len() <= MAX_TENANT_NAME_LEN
+        && name.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if well_formed {
+        Ok(format!("{TENANT_SCHEMA_PREFIX}{name}"))
+    } else {
+        Err(format!(
+            "tenant {name:?} must start with a lowercase letter and hold at most {MAX_TENANT_NAME_LEN} lowercase letters, digits or underscores"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_tenant_names_when_mapping_to_schemas_then_only_plain_identifiers_pass() {
+        assert_eq!(tenant_schema("acme_2"), Ok("tenant_acme_2".to_string()));
+        let too_long = "a".repeat(MAX_TENANT_NAME_LEN + 1);
+        for invalid in ["", "Acme", "2fast", "_x", "a-b", "a b", too_long.as_str()] {
+            assert!(tenant_schema(invalid).is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! The append-only record of stage transition decisions, and replaying it
+//! into a bead's lifecycle.
+//!
+//! Every completed stage yields one decision. Replaying a bead's decisions
+//! in order shows how it moved through the pipeline, and flags any step the
+//! previous decision did not lead to.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One transition decision with the inputs it was made from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransitionEvent {
+    pub seq: i64,
+    pub agent_id: u32,
+    /// Stage that completed.
+    pub stage: String,
+    /// `passed`, `failed`, or `error`.
+    pub result: String,
+    pub attempt: u32,
+    /// `advance`, `retry`, `complete`, `block`, or `noop`.
+    pub transition: String,
+    pub next_stage: Option<String>,
+    pub reason: String,
+    /// Failure message the stage reported, if any.
+    pub message: Option<String>,
+    pub stage_history_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TransitionEvent {
+    /// Stage the bead should run next; `None` once it is complete or blocked.
+    #[must_use]
+    pub fn expected_next_stage(&self) -> Option<&str> {
+        match self.transition.as_str() {
+            "advance" => self.next_stage.as_deref(),
+            "retry" => Some("implement"),
+            "complete" | "block" => None,
+            _ => Some(self.stage.as_str()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayOutcome {
+    InProgress,
+    Completed,
+    Blocked,
+}
+
+/// A decision the replay could not reconcile with the one before it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayAnomaly {
+    pub seq: i64,
+    pub detail: String,
+}
+
+/// A bead's lifecycle rebuilt from its transition events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BeadReplay {
+    pub bead_id: String,
+    /// Where the last decision left the bead.
+    pub outcome: ReplayOutcome,
+    /// Stage the bead is in after the last decision; `None` once complete
+    /// or blocked.
+    pub current_stage: Option<String>,
+    /// Highest attempt number seen.
+    pub attempts: u32,
+    pub retries: u32,
+    /// Agents that made decisions, in order of first appearance.
+    pub agents: Vec<u32>,
+    pub steps: Vec<TransitionEvent>,
+    pub anomalies: Vec<ReplayAnomaly>,
+}
+
+impl BeadReplay {
+    /// Folds `events`, oldest first, into the bead's lifecycle.
+    #[must_use]
+    pub fn from_events(bead_id: &str, events: Vec<TransitionEvent>) -> Self {
+        let mut replay = Self {
+            bead_id: bead_id.to_string(),
+            outcome: ReplayOutcome::InProgress,
+            current_stage: None,
+            attempts: 0,
+            retries: 0,
+            agents: Vec::new(),
+            steps: Vec::with_capacity(events.len()),
+            anomalies: Vec::new(),
+        };
+
+        for event in events {
+            let anomaly = match (replay.steps.last(), replay.outcome) {
+                (Some(_), ReplayOutcome::Completed | ReplayOutcome::Blocked) => Some(format!(
+                    "{} decided on {} after the bead was already {}",
+                    event.transition,
+                    event.stage,
+                    if replay.outcome == ReplayOutcome::Completed {
+                        "completed"
+                    } else {
+                        "blocked"
+                    }
+                )),
+                (Some(previous), ReplayOutcome::InProgress)
+                    if previous.expected_next_stage() != Some(event.stage.as_str()) =>
+                {
+                    Some(format!(
+                        "ran {} but the previous decision expected {}",
+                        event.stage,
+                        previous.expected_next_stage().unwrap_or("nothing")
+                    ))
+                }
+                (Some(previous), _) if event.attempt < previous.attempt => Some(format!(
+                    "attempt went back from {} to {}",
+                    previous.attempt, event.attempt
+                )),
+                _ => None,
+            };
+            if let Some(detail) = anomaly {
+                replay.anomalies.push(ReplayAnomaly {
+                    seq: event.seq,
+                    detail,
+                });
+            }
+
+            if !replay.agents.contains(&event.agent_id) {
+                replay.agents.push(event.agent_id);
+            }
+            replay.attempts = replay.attempts.max(event.attempt);
+            if event.transition == "retry" {
+                replay.retries = replay.retries.saturating_add(1);
+            }
+            replay.outcome = match event.transition.as_str() {
+                "complete" => ReplayOutcome::Completed,
+                "block" => ReplayOutcome::Blocked,
+                _ => ReplayOutcome::InProgress,
+            };
+            replay.current_stage = event.expected_next_stage().map(ToString::to_string);
+            replay.steps.push(event);
+        }
+        replay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(
+        seq: i64,
+        stage: &str,
+        result: &str,
+        attempt: u32,
+        transition: &str,
+        next: Option<&str>,
+    ) -> TransitionEvent {
+        TransitionEvent {
+            seq,
+            agent_id: 1,
+            stage: stage.to_string(),
+            result: result.to_string(),
+            attempt,
+            transition: transition.to_string(),
+            next_stage: next.map(ToString::to_string),
+            reason: String::new(),
+            message: None,
+            stage_history_id: None,
+            created_at: DateTime::<Utc>::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn given_clean_lifecycle_when_replaying_then_bead_completes_without_anomalies() {
+        let replay = BeadReplay::from_events(
+            "bd-1",
+            vec![
+                event(
+                    1,
+                    "rust-contract",
+                    "passed",
+                    1,
+                    "advance",
+                    Some("implement"),
+                ),
+                event(2, "implement", "passed", 1, "advance", Some("qa-enforcer")),
+                event(3, "qa-enforcer", "failed", 1, "retry", Some("implement")),
+                event(4, "implement", "passed", 2, "advance", Some("qa-enforcer")),
+                event(5, "qa-enforcer", "passed", 2, "advance", Some("red-queen")),
+                event(6, "red-queen", "passed", 2, "complete", None),
+            ],
+        );
+
+        assert_eq!(replay.outcome, ReplayOutcome::Completed);
+        assert_eq!(replay.current_stage, None);
+        assert_eq!(replay.attempts, 2);
+        assert_eq!(replay.retries, 1);
+        assert_eq!(replay.agents, [1]);
+        assert!(replay.anomalies.is_empty(), "{:?}", replay.anomalies);
+    }
+
+    #[test]
+    fn given_skipped_stage_and_late_decision_when_replaying_then_anomalies_are_flagged() {
+        let replay = BeadReplay::from_events(
+            "bd-2",
+            vec![
+                event(1, "implement", "passed", 1, "advance", Some("qa-enforcer")),
+                event(2, "red-queen", "passed", 1, "complete", None),
+                event(3, "implement", "passed", 1, "advance", Some("qa-enforcer")),
+            ],
+        );
+
+        assert_eq!(
+            replay
+                .anomalies
+                .iter()
+                .map(|anomaly| anomaly.seq)
+                .collect::<Vec<_>>(),
+            [2, 3]
+        );
+        assert_eq!(replay.outcome, ReplayOutcome::InProgress);
+        assert_eq!(replay.current_stage.as_deref(), Some("qa-enforcer"));
+    }
+}
@@ -0,0 +1,97 @@
+//! Per-agent checkouts. Each agent works its bead in its own jj workspace or
+//! git worktree, so agents sharing a host never edit the same files.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Version control tool that owns a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceBackend {
+    /// `jj workspace add`, for repositories with a `.jj` directory.
+    Jj,
+    /// `git worktree add`, for plain git repositories.
+    Git,
+}
+
+impl WorkspaceBackend {
+    /// Get string representation.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Jj => "jj",
+            Self::Git => "git",
+        }
+    }
+}
+
+impl TryFrom<&str> for WorkspaceBackend {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, String> {
+        match value {
+            "jj" => Ok(Self::Jj),
+            "git" => Ok(Self::Git),
+            _ => Err(format!("Unknown workspace backend: {value}")),
+        }
+    }
+}
+
+/// One provisioned checkout. `removed_at` is set once it is cleaned up; an
+/// agent has at most one workspace that is not removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentWorkspace {
+    pub agent_id: u32,
+    pub bead_id: String,
+    /// jj workspace name, also the directory name under the workspace root.
+    pub name: String,
+    pub path: String,
+    pub backend: WorkspaceBackend,
+    pub created_at: DateTime<Utc>,
+    pub removed_at: Option<DateTime<Utc>>,
+}
+
+impl AgentWorkspace {
+    /// Whether the checkout is still on disk and owned by the agent.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.removed_at.is_none()
+    }
+}
+
+/// Workspace name for `agent_id` working `bead_id`. Anything outside
+/// `[A-Za-z0-9_-]` becomes `-`, so the name is safe as a directory and as a
+/// jj workspace name.
+#[must_use]
+pub fn workspace_name(agent_id: u32, bead_id: &str) -> String {
+    let bead: String = bead_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("agent-{agent_id}-{bead}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_bead_id_with_path_characters_when_naming_then_they_are_replaced() {
+        assert_eq!(workspace_name(3, "bd-1a2"), "agent-3-bd-1a2");
+        assert_eq!(workspace_name(12, "../bd 9/x"), "agent-12----bd-9-x");
+    }
+
+    #[test]
+    fn given_backend_strings_when_parsing_then_round_trip() {
+        for backend in [WorkspaceBackend::Jj, WorkspaceBackend::Git] {
+            assert_eq!(WorkspaceBackend::try_from(backend.as_str()), Ok(backend));
+        }
+        assert!(WorkspaceBackend::try_from("svn").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Creating and removing agent checkouts on disk. The `workspaces` table is
+//! the record of which checkout belongs to which agent; this module only
+//! runs the jj or git commands.
+
+use crate::types::{AgentWorkspace, WorkspaceBackend};
+use crate::{Result, SwarmError};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Suffix of the directory, next to the repository, that holds workspaces
+/// when `SWARM_WORKSPACE_ROOT` is not set.
+pub const WORKSPACE_ROOT_SUFFIX: &str = ".swarm-workspaces";
+
+/// Backend for `repo_root`: jj when it has a `.jj` directory, git otherwise.
+#[must_use]
+pub fn detect_backend(repo_root: &Path) -> WorkspaceBackend {
+    if repo_root.join(".jj").is_dir() {
+        WorkspaceBackend::Jj
+    } else {
+        WorkspaceBackend::Git
+    }
+}
+
+/// `<parent>/<repo>.swarm-workspaces`, a sibling of the repository so the
+/// main checkout never sees workspace files as untracked changes.
+#[must_use]
+pub fn default_workspace_root(repo_root: &Path) -> PathBuf {
+    let repo_name = repo_root
+        .file_name()
+        .map_or_else(|| "repo".into(), |name| name.to_string_lossy());
+    repo_root
+        .parent()
+        .unwrap_or(repo_root)
+        .join(format!("{repo_name}{WORKSPACE_ROOT_SUFFIX}"))
+}
+
+/// Creates a checkout named `name` at `path`. A jj workspace starts on a new
+/// change on top of the repository's working-copy parent; a git worktree
+/// starts detached at `HEAD`.
+///
+/// # Errors
+/// Returns an error if `path` already exists or the jj/git command fails.
+pub async fn provision_workspace(
+    repo_root: &Path,
+    backend: WorkspaceBackend,
+    name: &str,
+    path: &Path,
+) -> Result<()> {
+    if tokio::fs::try_exists(path).await? {
+        return Err(SwarmError::AgentError(format!(
+            "Workspace path {} already exists",
+            path.display()
+        )));
+    }
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let path_arg = path.to_string_lossy().to_string();
+    match backend {
+        WorkspaceBackend::Jj => {
+            run_vcs(
+                repo_root,
+                "jj",
+                &["workspace", "add", "--name", name, &path_arg],
+            )
+            .await
+        }
+        WorkspaceBackend::Git => {
+            run_vcs(
+                repo_root,
+                "git",
+                &["worktree", "add", "--detach", &path_arg],
+            )
+            .await
+        }
+    }
+}
+
+/// Deletes the checkout and unregisters it from jj or git. A directory that
+/// is already gone is not an error.
+///
+/// # Errors
+/// Returns an error if the jj/git command or the directory removal fails.
+pub async fn remove_workspace(repo_root: &Path, workspace: &AgentWorkspace) -> Result<()> {
+    discard_workspace(
+        repo_root,
+        workspace.backend,
+        &workspace.name,
+        Path::new(&workspace.path),
+    )
+    .await
+}
+
+/// [`remove_workspace`] for a checkout [`provision_workspace`] made that was
+/// never recorded, so no [`AgentWorkspace`] describes it yet.
+///
+/// # Errors
+/// Returns an error if the jj/git command or the directory removal fails.
+pub async fn discard_workspace(
+    repo_root: &Path,
+    backend: WorkspaceBackend,
+    name: &str,
+    path: &Path,
+) -> Result<()> {
+    match backend {
+        WorkspaceBackend::Jj => {
+            run_vcs(repo_root, "jj", &["workspace", "forget", name]).await?;
+            if tokio::fs::try_exists(path).await? {
+                tokio::fs::remove_dir_all(path).await?;
+            }
+            Ok(())
+        }
+        WorkspaceBackend::Git => {
+            if tokio::fs::try_exists(path).await? {
+                let path_arg = path.to_string_lossy().to_string();
+                run_vcs(
+                    repo_root,
+                    "git",
+                    &["worktree", "remove", "--force", &path_arg],
+                )
+                .await?;
+            }
+            run_vcs(repo_root, "git", &["worktree", "prune"]).await
+        }
+    }
+}
+
+async fn run_vcs(repo_root: &Path, program: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(repo_root)
+        .output()
+        .await?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(SwarmError::IoError(std::io::Error::other(format!(
+        "`{program} {}` failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr).trim_end()
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn given_repo_root_when_defaulting_then_workspaces_sit_beside_the_repo() {
+        assert_eq!(
+            default_workspace_root(Path::new("/src/swarm")),
+            PathBuf::from("/src/swarm.swarm-workspaces")
+        );
+    }
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use swarm::testsupport::isolated_db;
+use swarm::{AgentId, RepoId, SwarmError};
+
+#[tokio::test]
+async fn given_existing_and_deleted_agents_when_registering_then_every_id_up_to_count_is_live(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.register_agent(&AgentId::new(repo.clone(), 2)).await?;
+    sqlx::query(
+        "UPDATE agent_state SET deleted_at = NOW(), deleted_by = 'test' WHERE agent_id = 2",
+    )
+    .execute(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+
+    db.register_agents(&repo, 4).await?;
+    db.register_agents(&repo, 4).await?;
+
+    assert_eq!(db.registered_agent_ids(&repo).await?, vec![1, 2, 3, 4]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_one_insert_failing_when_registering_then_the_error_is_returned_and_no_agent_is_left(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    sqlx::query("ALTER TABLE agent_state ADD CONSTRAINT no_agent_three CHECK (agent_id <> 3)")
+        .execute(db.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+
+    let result = db.register_agents(&repo, 5).await;
+
+    assert!(
+        matches!(&result, Err(SwarmError::DatabaseError(message)) if message.contains("no_agent_three")),
+        "{result:?}"
+    );
+    assert!(db.registered_agent_ids(&repo).await?.is_empty());
+    Ok(())
+}
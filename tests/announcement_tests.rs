@@ -0,0 +1,128 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use swarm::testsupport::isolated_db;
+use swarm::types::{DEFAULT_ANNOUNCEMENT_TOPIC, DEFAULT_ANNOUNCEMENT_TTL_SECS};
+use swarm::{AgentId, RepoId, SwarmDb, SwarmError};
+
+fn agent(number: u32) -> AgentId {
+    AgentId::new(RepoId::new("local"), number)
+}
+
+async fn expire(db: &SwarmDb, id: i64) -> swarm::Result<()> {
+    sqlx::query("UPDATE announcements SET expires_at = NOW() - INTERVAL '1 minute' WHERE id = $1")
+        .bind(id)
+        .execute(db.pool())
+        .await
+        .map(|_| ())
+        .map_err(|e| SwarmError::DatabaseError(e.to_string()))
+}
+
+#[tokio::test]
+async fn given_announcement_when_agents_register_later_then_it_is_in_their_inbox(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let posted = db
+        .create_announcement(
+            &repo,
+            "1",
+            DEFAULT_ANNOUNCEMENT_TOPIC,
+            "pausing claims",
+            DEFAULT_ANNOUNCEMENT_TTL_SECS,
+        )
+        .await?;
+
+    db.seed_idle_agents(2).await?;
+
+    let inbox = db.get_announcements(&repo, None, Some(2)).await?;
+    assert_eq!(inbox, vec![posted.clone()]);
+    let statuses = db.get_announcement_statuses(&repo, None).await?;
+    assert_eq!(statuses.len(), 1);
+    assert_eq!(statuses[0].announcement, posted);
+    assert!(statuses[0].acked.is_empty());
+    assert_eq!(statuses[0].pending, vec![1, 2]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_announcements_on_two_topics_when_acking_one_topic_then_only_it_leaves_the_inbox(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.seed_idle_agents(2).await?;
+    let general = db
+        .create_announcement(&repo, "1", "general", "lunch", 3600)
+        .await?;
+    let deploy = db
+        .create_announcement(&repo, "1", "deploy", "freeze at 5pm", 3600)
+        .await?;
+
+    let acked = db
+        .ack_announcements(&agent(1), None, Some("deploy"))
+        .await?;
+
+    assert_eq!(acked, vec![deploy.id]);
+    assert!(db
+        .ack_announcements(&agent(1), None, Some("deploy"))
+        .await?
+        .is_empty());
+    assert_eq!(
+        db.get_announcements(&repo, None, Some(1))
+            .await?
+            .into_iter()
+            .map(|a| a.id)
+            .collect::<Vec<_>>(),
+        vec![general.id]
+    );
+    assert_eq!(db.get_announcements(&repo, None, Some(2)).await?.len(), 2);
+    let statuses = db.get_announcement_statuses(&repo, Some("deploy")).await?;
+    assert_eq!(statuses.len(), 1);
+    assert_eq!(
+        statuses[0]
+            .acked
+            .iter()
+            .map(|ack| ack.agent_id)
+            .collect::<Vec<_>>(),
+        vec![1]
+    );
+    assert_eq!(statuses[0].pending, vec![2]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_expired_announcement_when_collecting_garbage_then_it_and_its_acks_are_purged(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.seed_idle_agents(1).await?;
+    let stale = db
+        .create_announcement(&repo, "1", "general", "old news", 3600)
+        .await?;
+    let live = db
+        .create_announcement(&repo, "1", "general", "still true", 3600)
+        .await?;
+    db.ack_announcements(&agent(1), Some(stale.id), None)
+        .await?;
+    expire(&db, stale.id).await?;
+
+    assert_eq!(db.get_announcements(&repo, None, None).await?, vec![live]);
+    assert!(db
+        .ack_announcements(&agent(1), Some(stale.id), None)
+        .await?
+        .is_empty());
+
+    let collected = db.collect_garbage(&repo).await?;
+
+    assert_eq!(collected["announcements"], 1);
+    let acks = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM announcement_acks WHERE announcement_id = $1",
+    )
+    .bind(stale.id)
+    .fetch_one(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+    assert_eq!(acks, 0);
+    Ok(())
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use swarm::testsupport::isolated_db;
+use swarm::types::{ApprovalGate, Stage};
+use swarm::{BeadId, RepoId, SwarmError};
+
+const GATE: ApprovalGate = ApprovalGate::Stage(Stage::Implement);
+
+fn token(request: &swarm::types::ApprovalRequest) -> swarm::Result<String> {
+    request
+        .token
+        .clone()
+        .ok_or_else(|| SwarmError::Internal("request carries no token".to_string()))
+}
+
+#[tokio::test]
+async fn given_pending_approval_when_reissuing_then_only_the_new_token_approves(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let bead = BeadId::new("bd-lost-token");
+    let lost = token(&db.request_approval(&repo, &bead, GATE, Some(1)).await?)?;
+
+    let reissued = db
+        .reissue_approval_token(&repo, &bead, GATE, "alice")
+        .await?
+        .ok_or_else(|| SwarmError::Internal("nothing reissued".to_string()))?;
+    let fresh = token(&reissued)?;
+
+    assert_ne!(fresh, lost);
+    assert!(!reissued.approval.is_approved());
+    assert!(db
+        .grant_approval(&repo, &bead, &lost, "alice", None)
+        .await?
+        .is_none());
+    assert!(db
+        .grant_approval(&repo, &bead, &fresh, "alice", None)
+        .await?
+        .is_some_and(|approval| approval.is_approved()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_reissue_when_reading_events_then_who_reissued_is_recorded() -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let bead = BeadId::new("bd-audited");
+    db.request_approval(&repo, &bead, GATE, Some(1)).await?;
+
+    db.reissue_approval_token(&repo, &bead, GATE, "alice")
+        .await?;
+
+    let reissued_by = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT payload->>'reissued_by' FROM execution_events
+         WHERE event_type = 'approval_token_reissued' AND bead_id = $1",
+    )
+    .bind(bead.value())
+    .fetch_all(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+    assert_eq!(reissued_by, [Some("alice".to_string())]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_approved_or_unknown_gate_when_reissuing_then_nothing_changes() -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let bead = BeadId::new("bd-approved");
+    let granted = token(&db.request_approval(&repo, &bead, GATE, Some(1)).await?)?;
+    db.grant_approval(&repo, &bead, &granted, "alice", None)
+        .await?;
+
+    assert!(db
+        .reissue_approval_token(&repo, &bead, GATE, "alice")
+        .await?
+        .is_none());
+    assert!(db
+        .reissue_approval_token(&repo, &bead, ApprovalGate::Land, "alice")
+        .await?
+        .is_none());
+    assert!(db
+        .reissue_approval_token(&repo, &BeadId::new("bd-never-gated"), GATE, "alice")
+        .await?
+        .is_none());
+    Ok(())
+}
@@ -0,0 +1,183 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use swarm::testsupport::isolated_db;
+use swarm::types::{BacklogEditSkip, BacklogEntry, BacklogSelector, PriorityChange};
+use swarm::{AgentId, RepoId, SwarmDb, SwarmError};
+
+fn selector(bead_ids: &[&str], label: Option<&str>) -> BacklogSelector {
+    BacklogSelector {
+        bead_ids: bead_ids.iter().map(ToString::to_string).collect(),
+        label: label.map(ToString::to_string),
+    }
+}
+
+async fn priorities(db: &SwarmDb) -> swarm::Result<Vec<(String, String)>> {
+    let mut rows = db
+        .get_backlog_rows(&RepoId::new("local"))
+        .await?
+        .into_iter()
+        .map(|row| (row.bead_id, row.priority))
+        .collect::<Vec<_>>();
+    rows.sort();
+    Ok(rows)
+}
+
+async fn backlog_edit_events(db: &SwarmDb) -> swarm::Result<i64> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM execution_events
+         WHERE event_type = 'backlog_edited' AND entity_id = 'repo:local:backlog'",
+    )
+    .fetch_one(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))
+}
+
+#[tokio::test]
+async fn given_claimed_and_pending_beads_when_setting_priority_then_only_pending_ones_change(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.seed_idle_agents(1).await?;
+    db.enqueue_backlog_batch(&repo, "edit", 3).await?;
+    let claimed = db
+        .claim_next_bead(&AgentId::new(repo.clone(), 1))
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 claimed nothing".to_string()))?;
+    let pending = ["edit-1", "edit-2", "edit-3"]
+        .into_iter()
+        .filter(|bead_id| *bead_id != claimed.value())
+        .collect::<Vec<_>>();
+
+    let outcome = db
+        .reprioritize_backlog(
+            &repo,
+            &selector(&["edit-1", "edit-2", "edit-3", "ghost"], None),
+            "p2",
+        )
+        .await?;
+
+    assert_eq!(
+        outcome.reprioritized,
+        pending
+            .iter()
+            .map(|bead_id| PriorityChange {
+                bead_id: (*bead_id).to_string(),
+                from: "p0".to_string(),
+                to: "p2".to_string(),
+            })
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        outcome.skipped,
+        vec![BacklogEditSkip {
+            bead_id: claimed.value().to_string(),
+            reason: "in_progress".to_string(),
+        }]
+    );
+    assert_eq!(outcome.missing, vec!["ghost".to_string()]);
+    let stored = priorities(&db).await?;
+    for (bead_id, priority) in &stored {
+        let expected = if bead_id == claimed.value() {
+            "p0"
+        } else {
+            "p2"
+        };
+        assert_eq!(priority, expected, "{bead_id}");
+    }
+    assert_eq!(backlog_edit_events(&db).await?, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_beads_already_at_the_target_when_setting_priority_then_nothing_is_written(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.enqueue_backlog_batch(&repo, "same", 2).await?;
+
+    let outcome = db
+        .reprioritize_backlog(&repo, &selector(&[], None), "P0")
+        .await?;
+
+    assert_eq!(outcome.unchanged, 2);
+    assert!(outcome.reprioritized.is_empty() && outcome.skipped.is_empty());
+    assert_eq!(backlog_edit_events(&db).await?, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_labeled_beads_when_bumping_by_label_then_only_those_beads_move() -> swarm::Result<()>
+{
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let entry = |bead_id: &str, labels: &[&str]| BacklogEntry {
+        bead_id: bead_id.to_string(),
+        priority: Some("p3".to_string()),
+        labels: Some(labels.iter().map(ToString::to_string).collect()),
+        required_capabilities: Vec::new(),
+    };
+    db.enqueue_backlog_entries(
+        &repo,
+        &[
+            entry("cve", &["security", "backend"]),
+            entry("audit", &["security"]),
+            entry("docs", &["docs"]),
+        ],
+    )
+    .await?;
+
+    let outcome = db
+        .reprioritize_backlog(&repo, &selector(&[], Some("security")), "p0")
+        .await?;
+
+    assert_eq!(outcome.reprioritized.len(), 2);
+    assert_eq!(
+        priorities(&db).await?,
+        vec![
+            ("audit".to_string(), "p0".to_string()),
+            ("cve".to_string(), "p0".to_string()),
+            ("docs".to_string(), "p3".to_string()),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_reserved_bead_when_removing_then_it_is_skipped_and_the_rest_are_removed(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.seed_idle_agents(1).await?;
+    db.enqueue_backlog_batch(&repo, "drop", 2).await?;
+    let reserved = db
+        .reserve_next_bead(&AgentId::new(repo.clone(), 1), 60, None)
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 reserved nothing".to_string()))?
+        .bead_id;
+    let other = if reserved.value() == "drop-1" {
+        "drop-2"
+    } else {
+        "drop-1"
+    };
+
+    let outcome = db
+        .remove_backlog_beads(&repo, &selector(&["drop-1", "drop-2"], None))
+        .await?;
+
+    assert_eq!(outcome.removed, vec![other.to_string()]);
+    assert_eq!(
+        outcome.skipped,
+        vec![BacklogEditSkip {
+            bead_id: reserved.value().to_string(),
+            reason: "reserved".to_string(),
+        }]
+    );
+    assert_eq!(
+        priorities(&db).await?,
+        vec![(reserved.value().to_string(), "p0".to_string())]
+    );
+    assert_eq!(backlog_edit_events(&db).await?, 1);
+    Ok(())
+}
@@ -192,11 +192,16 @@ fn given_unknown_command_when_processed_then_returns_actionable_error_contract(
             scenario.output
         ));
     }
-    if !scenario.output["fix"].is_string() {
+    let Some(fix) = scenario.output["fix"].as_str() else {
         return Err(format!(
             "expected actionable fix guidance for invalid command, got: {}",
             scenario.output
         ));
+    };
+    if !fix.contains("run-once, ") || !fix.contains("lock, ") || fix.contains("run-ononce") {
+        return Err(format!(
+            "expected the fix to list the registered commands, got: {fix}"
+        ));
     }
 
     Ok(())
@@ -0,0 +1,121 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use swarm::testsupport::{isolated_db, TestDb};
+use swarm::types::BacklogEntry;
+use swarm::{AgentId, BeadId, RepoId};
+
+fn agent(number: u32) -> AgentId {
+    AgentId::new(RepoId::new("local"), number)
+}
+
+fn labels(values: &[&str]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+/// Enqueues an unlabeled `plain` bead and a `frontend` one at `p0` ahead of
+/// a `backend` bead at `p2`, so a label filter is the only reason to skip
+/// to the backend bead.
+async fn labeled_backlog(db: &TestDb) -> swarm::Result<()> {
+    let entry = |bead_id: &str, priority: &str, labels: &[&str]| BacklogEntry {
+        bead_id: bead_id.to_string(),
+        priority: Some(priority.to_string()),
+        labels: Some(labels.iter().map(ToString::to_string).collect()),
+        required_capabilities: Vec::new(),
+    };
+    db.enqueue_backlog_entries(
+        &RepoId::new("local"),
+        &[
+            entry("plain", "p0", &[]),
+            entry("ui", "p0", &["frontend"]),
+            entry("db", "p2", &["backend", "infra"]),
+        ],
+    )
+    .await
+    .map(|_| ())
+}
+
+#[tokio::test]
+async fn given_agent_with_default_labels_when_claiming_then_only_a_matching_bead_is_taken(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    db.seed_idle_agents(2).await?;
+    labeled_backlog(&db).await?;
+
+    assert!(
+        db.set_agent_labels(&agent(1), &labels(&["backend"]))
+            .await?
+    );
+
+    assert_eq!(
+        db.get_agent_labels(&RepoId::new("local"), None).await?,
+        vec![(1, labels(&["backend"])), (2, Vec::new())]
+    );
+    assert_eq!(
+        db.claim_next_bead(&agent(1)).await?,
+        Some(BeadId::new("db"))
+    );
+    assert!(db
+        .claim_next_bead(&agent(2))
+        .await?
+        .is_some_and(|bead| bead.value() != "db"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_explicit_labels_when_claiming_then_they_replace_the_agent_defaults(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    db.seed_idle_agents(1).await?;
+    labeled_backlog(&db).await?;
+    db.set_agent_labels(&agent(1), &labels(&["backend"]))
+        .await?;
+
+    assert!(db
+        .claim_next_bead_with_labels(&agent(1), &labels(&["docs"]))
+        .await?
+        .is_none());
+    assert_eq!(
+        db.claim_next_bead_with_labels(&agent(1), &labels(&["docs", "frontend"]))
+            .await?,
+        Some(BeadId::new("ui"))
+    );
+    assert_eq!(
+        db.get_agent_labels(&RepoId::new("local"), Some(1)).await?,
+        vec![(1, labels(&["backend"]))]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_agent_labels_when_reserving_then_the_reservation_honours_them() -> swarm::Result<()>
+{
+    let db = isolated_db().await?;
+    db.seed_idle_agents(1).await?;
+    labeled_backlog(&db).await?;
+    db.set_agent_labels(&agent(1), &labels(&["infra"])).await?;
+
+    let defaulted = db.reserve_next_bead(&agent(1), 60, None).await?;
+    assert_eq!(defaulted.map(|r| r.bead_id), Some(BeadId::new("db")));
+
+    let explicit = db
+        .reserve_next_bead(&agent(1), 60, Some(&labels(&["frontend"])))
+        .await?;
+    assert_eq!(explicit.map(|r| r.bead_id), Some(BeadId::new("ui")));
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_unregistered_agent_when_setting_labels_then_nothing_is_stored() -> swarm::Result<()>
+{
+    let db = isolated_db().await?;
+    db.seed_idle_agents(1).await?;
+
+    assert!(!db.set_agent_labels(&agent(9), &labels(&["docs"])).await?);
+    assert!(db
+        .get_agent_labels(&RepoId::new("local"), Some(9))
+        .await?
+        .is_empty());
+    Ok(())
+}
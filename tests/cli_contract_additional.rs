@@ -97,7 +97,6 @@ fn given_typo_command_when_invoked_then_suggested_command_is_rendered_on_stderr(
 }
 
 #[test]
-#[ignore = "ensure_no_unknown_flags not integrated into CLI parser yet"]
 fn given_doctor_command_with_unknown_flag_when_invoked_then_parser_fails_fast() {
     let binary_path = assert_cmd::cargo::cargo_bin!("swarm");
     Command::new(binary_path)
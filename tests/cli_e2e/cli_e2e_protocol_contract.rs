@@ -16,6 +16,7 @@ fn help_command_returns_protocol_envelope() -> Result<(), String> {
     assert!(json["t"].is_i64());
     assert!(json["ms"].is_i64());
     assert!(json["d"]["commands"].is_object());
+    assert!(json["d"]["commands"]["locks"].is_string());
     assert!(json["state"]["total"].is_number());
 
     Ok(())
@@ -287,7 +288,6 @@ fn status_cli_help_flag_returns_help_envelope_instead_of_executing_status() -> R
 }
 
 #[test]
-#[ignore = "ensure_no_unknown_flags not integrated into CLI parser yet"]
 fn status_cli_unknown_flag_fails_fast() {
     let binary_path = assert_cmd::cargo::cargo_bin!("swarm");
     Command::new(binary_path)
@@ -0,0 +1,84 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use swarm::testsupport::isolated_db;
+use swarm::types::{ConfigKey, SwarmStatus};
+use swarm::{AgentId, RepoId, SwarmError};
+
+#[tokio::test]
+async fn given_every_key_at_its_upper_bound_when_saving_then_config_get_reads_them_back(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let mut config = db.get_config(&repo).await?;
+    for key in ConfigKey::ALL {
+        let raw = match key.bounds() {
+            Some((_, max)) => max.to_string(),
+            None if key == ConfigKey::ClaimLabel => "backend".to_string(),
+            None => SwarmStatus::Paused.as_str().to_string(),
+        };
+        key.parse_value(&raw)
+            .map_err(SwarmError::ConfigError)?
+            .apply(&mut config);
+    }
+
+    db.save_swarm_config(&config).await?;
+
+    let stored = db.get_config(&repo).await?;
+    for key in ConfigKey::ALL {
+        assert_eq!(key.value_in(&stored), key.value_in(&config), "{key}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_lease_below_the_check_constraint_when_saving_then_it_is_rejected_and_nothing_changes(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let before = db.get_config(&repo).await?;
+    let mut config = before.clone();
+    config.max_agents = before.max_agents + 1;
+    config.lease_ttl_ms = 500;
+
+    let rejected = db.save_swarm_config(&config).await;
+
+    assert!(matches!(rejected, Err(SwarmError::DatabaseError(_))));
+    let after = db.get_config(&repo).await?;
+    for key in ConfigKey::ALL {
+        assert_eq!(key.value_in(&after), key.value_in(&before), "{key}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_new_lease_ttl_when_claiming_then_the_claim_lease_runs_that_long() -> swarm::Result<()>
+{
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.seed_idle_agents(1).await?;
+    db.enqueue_backlog_batch(&repo, "lease", 1).await?;
+    let mut config = db.get_config(&repo).await?;
+    ConfigKey::LeaseTtlMs
+        .parse_value("60000")
+        .map_err(SwarmError::ConfigError)?
+        .apply(&mut config);
+    db.save_swarm_config(&config).await?;
+
+    let bead = db
+        .claim_next_bead(&AgentId::new(repo.clone(), 1))
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 claimed nothing".to_string()))?;
+
+    let lease_secs = sqlx::query_scalar::<_, f64>(
+        "SELECT EXTRACT(EPOCH FROM lease_expires_at - claimed_at)::FLOAT8
+         FROM bead_claims WHERE repo_id = 'local' AND bead_id = $1",
+    )
+    .bind(bead.value())
+    .fetch_one(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+    assert!((59.0..=61.0).contains(&lease_secs), "lease {lease_secs}s");
+    Ok(())
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use serde_json::{Map, Value};
+use swarm::conformance::{default_cases, generate, verify};
+use swarm::testsupport::{isolated_db, TestDb};
+
+/// Points protocol requests at `db`. The only test in this binary, so
+/// setting the process environment cannot race another test.
+fn connection_for(db: &TestDb) -> Map<String, Value> {
+    std::env::set_var("SWARM_DB_SCHEMA", db.schema());
+    let mut connection = Map::new();
+    connection.insert("database_url".to_string(), Value::from(db.url()));
+    connection
+}
+
+#[tokio::test]
+async fn given_fixtures_from_one_fresh_db_when_verifying_on_another_then_every_case_matches(
+) -> swarm::Result<()> {
+    let fixtures = tempfile::tempdir()?;
+    let first = isolated_db().await?;
+    let written = generate(
+        &first,
+        &connection_for(&first),
+        fixtures.path(),
+        default_cases(),
+    )
+    .await?;
+
+    let second = isolated_db().await?;
+    let report = verify(&second, &connection_for(&second), fixtures.path(), &[]).await?;
+
+    assert_eq!(written, default_cases().len());
+    assert_eq!(report.total, written);
+    assert!(report.passed(), "{:#?}", report.mismatches);
+    Ok(())
+}
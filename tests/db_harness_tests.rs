@@ -0,0 +1,41 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use swarm::testsupport::isolated_db;
+use swarm::{AgentId, RepoId};
+
+#[tokio::test]
+async fn given_two_idle_agents_when_both_claim_then_each_gets_a_different_bead() -> swarm::Result<()>
+{
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.seed_idle_agents(2).await?;
+    db.enqueue_backlog_batch(&repo, "harness", 3).await?;
+
+    let first = db.claim_next_bead(&AgentId::new(repo.clone(), 1)).await?;
+    let second = db.claim_next_bead(&AgentId::new(repo.clone(), 2)).await?;
+
+    assert!(first.is_some());
+    assert!(second.is_some());
+    assert_ne!(first, second);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_another_test_db_with_rows_when_taking_a_db_then_it_starts_empty() -> swarm::Result<()>
+{
+    let repo = RepoId::new("local");
+    let busy = isolated_db().await?;
+    busy.seed_idle_agents(4).await?;
+    busy.enqueue_backlog_batch(&repo, "elsewhere", 2).await?;
+
+    let db = isolated_db().await?;
+    let progress = db.get_progress(&repo).await?;
+
+    assert_ne!(db.schema(), busy.schema());
+    assert_eq!(progress.total_agents, 0);
+    assert!(db.get_backlog_ages(&repo).await?.is_empty());
+    assert_eq!(busy.get_progress(&repo).await?.total_agents, 4);
+    Ok(())
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use serde_json::json;
+use swarm::testsupport::isolated_db;
+use swarm::types::MAX_KV_KEYS;
+use swarm::{AgentId, BeadId, RepoId, SwarmError};
+
+#[tokio::test]
+async fn given_stored_value_when_setting_it_again_then_reads_see_only_the_newest_for_that_agent(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.set_kv_entry(&repo, 1, "bd-1", "approach", &json!("tdd"))
+        .await?;
+    db.set_kv_entry(&repo, 1, "bd-1", "notes", &json!({"step": 1}))
+        .await?;
+
+    let replaced = db
+        .set_kv_entry(&repo, 1, "bd-1", "approach", &json!("spike"))
+        .await?;
+
+    assert_eq!(replaced.map(|entry| entry.value), Some(json!("spike")));
+    assert_eq!(
+        db.get_kv_entry(&repo, 1, "bd-1", "approach")
+            .await?
+            .map(|entry| entry.value),
+        Some(json!("spike"))
+    );
+    assert_eq!(
+        db.list_kv_entries(&repo, 1, "bd-1")
+            .await?
+            .into_iter()
+            .map(|entry| entry.key)
+            .collect::<Vec<_>>(),
+        vec!["approach".to_string(), "notes".to_string()]
+    );
+    assert!(db
+        .get_kv_entry(&repo, 2, "bd-1", "approach")
+        .await?
+        .is_none());
+    assert!(db.delete_kv_entry(&repo, 1, "bd-1", "approach").await?);
+    assert!(!db.delete_kv_entry(&repo, 1, "bd-1", "approach").await?);
+    assert!(db
+        .get_kv_entry(&repo, 1, "bd-1", "approach")
+        .await?
+        .is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_agent_at_the_key_limit_when_setting_then_only_existing_keys_change(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    sqlx::query(
+        "INSERT INTO agent_kv (repo_id, agent_id, bead_id, key, value)
+         SELECT 'local', 1, 'bd-1', 'key-' || g, to_jsonb(g)
+         FROM generate_series(1, $1) AS g",
+    )
+    .bind(MAX_KV_KEYS)
+    .execute(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+
+    assert!(db
+        .set_kv_entry(&repo, 1, "bd-1", "one-more", &json!(true))
+        .await?
+        .is_none());
+    assert!(db
+        .set_kv_entry(&repo, 1, "bd-1", "key-1", &json!("updated"))
+        .await?
+        .is_some());
+    assert!(db
+        .set_kv_entry(&repo, 1, "bd-2", "one-more", &json!(true))
+        .await?
+        .is_some());
+    assert_eq!(
+        db.list_kv_entries(&repo, 1, "bd-1").await?.len(),
+        usize::try_from(MAX_KV_KEYS).unwrap_or(usize::MAX)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_claimed_bead_with_scratch_state_when_cancelling_then_every_agents_entries_go(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.seed_idle_agents(1).await?;
+    db.enqueue_backlog_batch(&repo, "kv", 2).await?;
+    let bead = db
+        .claim_next_bead(&AgentId::new(repo.clone(), 1))
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 claimed nothing".to_string()))?;
+    let other = BeadId::new(if bead.value() == "kv-1" {
+        "kv-2"
+    } else {
+        "kv-1"
+    });
+    for agent_id in [1, 2] {
+        db.set_kv_entry(&repo, agent_id, bead.value(), "notes", &json!("wip"))
+            .await?;
+    }
+    db.set_kv_entry(&repo, 1, other.value(), "notes", &json!("keep"))
+        .await?;
+
+    db.cancel_bead(&repo, &bead, "no longer needed").await?;
+
+    for agent_id in [1, 2] {
+        assert!(db
+            .list_kv_entries(&repo, agent_id, bead.value())
+            .await?
+            .is_empty());
+    }
+    assert_eq!(db.list_kv_entries(&repo, 1, other.value()).await?.len(), 1);
+    Ok(())
+}
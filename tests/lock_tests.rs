@@ -0,0 +1,141 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use sqlx::{Connection, PgConnection};
+use std::time::Duration;
+use swarm::testsupport::{isolated_db, TestDb};
+use swarm::types::LockMetadata;
+use swarm::{SwarmDb, SwarmError};
+
+const TTL_MS: i64 = 60_000;
+const WAIT_MS: i64 = 60_000;
+
+/// A second handle on the test schema with a pool, and so connections, of
+/// its own.
+async fn second_connection(db: &TestDb) -> swarm::Result<SwarmDb> {
+    SwarmDb::new_in_schema(db.url(), db.schema(), Some(30_000)).await
+}
+
+/// Advisory locks are database-wide, so resources are named per test schema.
+fn resource(db: &TestDb, name: &str) -> String {
+    format!("{}:{name}", db.schema())
+}
+
+#[tokio::test]
+async fn given_two_connections_racing_for_a_free_resource_when_acquiring_then_one_holds_it(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let other = second_connection(&db).await?;
+    let resource = resource(&db, "race");
+    let metadata = LockMetadata::default();
+
+    let (first, second) = tokio::join!(
+        db.acquire_resource_lock(&resource, "agent-a", TTL_MS, &metadata),
+        other.acquire_resource_lock(&resource, "agent-b", TTL_MS, &metadata),
+    );
+
+    assert_eq!(
+        [first?.is_some(), second?.is_some()]
+            .iter()
+            .filter(|won| **won)
+            .count(),
+        1
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_advisory_lock_held_elsewhere_when_acquiring_then_it_waits_for_that_transaction(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let other = second_connection(&db).await?;
+    let resource = resource(&db, "serialized");
+    let mut blocker = PgConnection::connect(db.url())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+    let mut tx = blocker
+        .begin()
+        .await
+        .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(&resource)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+
+    let mut acquiring = tokio::spawn({
+        let resource = resource.clone();
+        async move {
+            other
+                .acquire_resource_lock(&resource, "agent-a", TTL_MS, &LockMetadata::default())
+                .await
+        }
+    });
+    assert!(
+        tokio::time::timeout(Duration::from_millis(300), &mut acquiring)
+            .await
+            .is_err(),
+        "acquire finished while another transaction held the advisory lock"
+    );
+    tx.commit()
+        .await
+        .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+
+    let acquired = acquiring
+        .await
+        .map_err(|e| SwarmError::Internal(e.to_string()))??;
+    assert!(acquired.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_queued_waiters_on_two_connections_when_the_lock_frees_then_it_passes_in_queue_order(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let other = second_connection(&db).await?;
+    let resource = resource(&db, "fifo");
+    let metadata = LockMetadata::default();
+    assert!(db
+        .acquire_resource_lock(&resource, "holder", TTL_MS, &metadata)
+        .await?
+        .is_some());
+
+    assert_eq!(
+        other
+            .enqueue_lock_waiter(&resource, "first", TTL_MS, WAIT_MS, &metadata)
+            .await?,
+        1
+    );
+    assert_eq!(
+        db.enqueue_lock_waiter(&resource, "second", TTL_MS, WAIT_MS, &metadata)
+            .await?,
+        2
+    );
+    assert!(db.unlock_resource(&resource, "holder").await?);
+
+    assert!(db
+        .acquire_resource_lock(&resource, "second", TTL_MS, &metadata)
+        .await?
+        .is_none());
+    assert!(db
+        .acquire_resource_lock(&resource, "holder", TTL_MS, &metadata)
+        .await?
+        .is_none());
+    assert!(other
+        .acquire_resource_lock(&resource, "first", TTL_MS, &metadata)
+        .await?
+        .is_some());
+    assert!(db
+        .acquire_resource_lock(&resource, "second", TTL_MS, &metadata)
+        .await?
+        .is_none());
+
+    assert!(other.unlock_resource(&resource, "first").await?);
+    assert!(db
+        .acquire_resource_lock(&resource, "second", TTL_MS, &metadata)
+        .await?
+        .is_some());
+    assert!(!other.dequeue_lock_waiter(&resource, "second").await?);
+    Ok(())
+}
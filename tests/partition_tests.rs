@@ -0,0 +1,49 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use chrono::{Months, Utc};
+use swarm::testsupport::isolated_db;
+use swarm::types::{month_start, month_start_at, next_month, PartitionedTable};
+use swarm::SwarmError;
+
+async fn count(db: &swarm::SwarmDb, table: &str) -> swarm::Result<i64> {
+    sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {table}"))
+        .fetch_one(db.pool())
+        .await
+        .map_err(|e| SwarmError::DatabaseError(e.to_string()))
+}
+
+#[tokio::test]
+async fn given_partitioned_events_when_inserting_past_the_last_month_then_the_row_lands_and_later_moves(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let table = PartitionedTable::ExecutionEvents;
+    let cutover = next_month(month_start(Utc::now()))
+        .ok_or_else(|| SwarmError::Internal("no next month".to_string()))?;
+    let later = cutover
+        .checked_add_months(Months::new(2))
+        .ok_or_else(|| SwarmError::Internal("no later month".to_string()))?;
+    db.enable_partitioning(table, cutover).await?;
+    db.create_partitions(table, &[cutover]).await?;
+
+    sqlx::query(
+        "INSERT INTO execution_events (event_type, entity_id, created_at)
+         VALUES ('test', 'repo:local:bead:bd-1', $1)",
+    )
+    .bind(month_start_at(later))
+    .execute(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+
+    assert_eq!(count(&db, "execution_events").await?, 1);
+    assert_eq!(count(&db, &table.default_partition()).await?, 1);
+
+    let created = db.create_partitions(table, &[later]).await?;
+
+    assert_eq!(created, vec![table.partition_name(later)]);
+    assert_eq!(count(&db, "execution_events").await?, 1);
+    assert_eq!(count(&db, &table.default_partition()).await?, 0);
+    assert_eq!(count(&db, &table.partition_name(later)).await?, 1);
+    Ok(())
+}
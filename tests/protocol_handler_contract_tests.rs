@@ -662,8 +662,9 @@ mod protocol_handler_contract_tests {
                 .as_array()
                 .ok_or("expected would_do array")?;
             assert_eq!(would_do.len(), 1);
-            assert_eq!(would_do[0]["action"], "broadcast");
-            assert_eq!(would_do[0]["target"], "hello world");
+            assert_eq!(would_do[0]["action"], "create_announcement");
+            assert_eq!(would_do[0]["target"], "general");
+            assert_eq!(would_do[0]["msg"], "hello world");
 
             Ok(())
         }
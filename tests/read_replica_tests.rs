@@ -0,0 +1,179 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use sqlx::postgres::PgConnectOptions;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use swarm::testsupport::isolated_db;
+use swarm::{AgentId, RepoId, SwarmDb, SwarmError};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Forwards connections to the test database while up. Taking it down
+/// drops every forwarded connection and refuses new ones, as a replica that
+/// went away would.
+struct ReplicaProxy {
+    port: u16,
+    up: Arc<AtomicBool>,
+    connections: Arc<AtomicUsize>,
+    forwards: Arc<Mutex<Vec<JoinHandle<()>>>>,
+    accept: JoinHandle<()>,
+}
+
+impl ReplicaProxy {
+    async fn start(target: String) -> swarm::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(SwarmError::IoError)?;
+        let port = listener.local_addr().map_err(SwarmError::IoError)?.port();
+        let up = Arc::new(AtomicBool::new(true));
+        let connections = Arc::new(AtomicUsize::new(0));
+        let forwards = Arc::new(Mutex::new(Vec::new()));
+        let accept = tokio::spawn({
+            let (up, connections, forwards) = (
+                Arc::clone(&up),
+                Arc::clone(&connections),
+                Arc::clone(&forwards),
+            );
+            async move {
+                while let Ok((mut client, _)) = listener.accept().await {
+                    if !up.load(Ordering::SeqCst) {
+                        continue;
+                    }
+                    connections.fetch_add(1, Ordering::SeqCst);
+                    let target = target.clone();
+                    let forward = tokio::spawn(async move {
+                        if let Ok(mut server) = TcpStream::connect(target).await {
+                            let _ = tokio::io::copy_bidirectional(&mut client, &mut server).await;
+                        }
+                    });
+                    if let Ok(mut forwards) = forwards.lock() {
+                        forwards.push(forward);
+                    }
+                }
+            }
+        });
+        Ok(Self {
+            port,
+            up,
+            connections,
+            forwards,
+            accept,
+        })
+    }
+
+    fn down(&self) {
+        self.up.store(false, Ordering::SeqCst);
+        if let Ok(mut forwards) = self.forwards.lock() {
+            forwards.drain(..).for_each(|forward| forward.abort());
+        }
+    }
+
+    fn up(&self) {
+        self.up.store(true, Ordering::SeqCst);
+    }
+
+    fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for ReplicaProxy {
+    fn drop(&mut self) {
+        self.down();
+        self.accept.abort();
+    }
+}
+
+/// The test database's URL with its host and port swapped for `port`.
+fn via_proxy(url: &str, port: u16) -> swarm::Result<(String, String)> {
+    let options =
+        PgConnectOptions::from_str(url).map_err(|e| SwarmError::ConfigError(e.to_string()))?;
+    let target = format!("{}:{}", options.get_host(), options.get_port());
+    Ok((
+        url.replacen(&target, &format!("127.0.0.1:{port}"), 1),
+        target,
+    ))
+}
+
+async fn read(db: &SwarmDb) -> swarm::Result<()> {
+    db.get_agent_state(&AgentId::new(RepoId::new("local"), 1))
+        .await
+        .map(|_state| ())
+}
+
+#[tokio::test]
+async fn given_replica_that_goes_away_when_reading_then_reads_fall_back_until_the_probe_sees_it_again(
+) -> swarm::Result<()> {
+    let test_db = isolated_db().await?;
+    let (_, target) = via_proxy(test_db.url(), 0)?;
+    let proxy = ReplicaProxy::start(target).await?;
+    let (replica_url, _) = via_proxy(test_db.url(), proxy.port)?;
+    let db = SwarmDb::new_in_schema(test_db.url(), test_db.schema(), Some(2_000))
+        .await?
+        .with_read_replica(&replica_url, Some(2_000))
+        .await;
+    assert!(db.read_replica_healthy());
+    let clone = db.clone();
+
+    read(&db).await?;
+    assert!(proxy.connections() > 0);
+
+    proxy.down();
+    read(&db).await?;
+    assert!(!clone.read_replica_healthy());
+    read(&db).await?;
+    read(&clone).await?;
+    assert!(!db.probe_read_replica().await);
+    assert!(db.has_read_replica());
+
+    proxy.up();
+    let before = proxy.connections();
+    assert!(db.probe_read_replica().await);
+    assert!(clone.read_replica_healthy());
+    read(&clone).await?;
+    assert!(proxy.connections() > before);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_replica_port_closed_when_reading_status_then_the_first_read_succeeds_on_the_primary(
+) -> swarm::Result<()> {
+    let test_db = isolated_db().await?;
+    let (_, target) = via_proxy(test_db.url(), 0)?;
+    let proxy = ReplicaProxy::start(target).await?;
+    let (replica_url, _) = via_proxy(test_db.url(), proxy.port)?;
+    let db = SwarmDb::new_in_schema(test_db.url(), test_db.schema(), Some(2_000))
+        .await?
+        .with_read_replica(&replica_url, Some(2_000))
+        .await;
+    assert!(db.read_replica_healthy());
+
+    drop(proxy);
+    db.get_progress(&RepoId::new("local")).await?;
+    assert!(!db.read_replica_healthy());
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_query_error_on_replica_when_reading_then_reads_stay_on_the_replica(
+) -> swarm::Result<()> {
+    let test_db = isolated_db().await?;
+    let db = SwarmDb::new_in_schema(test_db.url(), test_db.schema(), Some(2_000))
+        .await?
+        .with_read_replica(test_db.url(), Some(2_000))
+        .await;
+
+    let rejected = sqlx::query("SELECT * FROM no_such_table")
+        .execute(db.read_pool())
+        .await;
+    if let Err(error) = &rejected {
+        db.note_read_error(error);
+    }
+
+    assert!(rejected.is_err());
+    assert!(db.read_replica_healthy());
+    Ok(())
+}
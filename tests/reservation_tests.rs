@@ -0,0 +1,140 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use futures_util::future::join_all;
+use std::time::Duration;
+use swarm::testsupport::isolated_db;
+use swarm::{AgentId, BeadId, RepoId, SwarmDb, SwarmError};
+
+fn agent(number: u32) -> AgentId {
+    AgentId::new(RepoId::new("local"), number)
+}
+
+async fn status_of(db: &SwarmDb, bead_id: &BeadId) -> swarm::Result<Option<String>> {
+    Ok(db
+        .get_backlog_rows(&RepoId::new("local"))
+        .await?
+        .into_iter()
+        .find(|row| row.bead_id == bead_id.value())
+        .map(|row| row.status))
+}
+
+#[tokio::test]
+async fn given_expired_reservation_when_another_agent_reserves_then_it_gets_the_bead(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    db.seed_idle_agents(2).await?;
+    db.enqueue_backlog_batch(&RepoId::new("local"), "expiring", 1)
+        .await?;
+
+    let held = db
+        .reserve_next_bead(&agent(1), 1, None)
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 reserved nothing".to_string()))?;
+    assert!(db.reserve_next_bead(&agent(2), 60, None).await?.is_none());
+
+    tokio::time::sleep(Duration::from_millis(1_200)).await;
+    let taken = db.reserve_next_bead(&agent(2), 60, None).await?;
+
+    assert_eq!(
+        taken.map(|reservation| reservation.bead_id),
+        Some(held.bead_id.clone())
+    );
+    assert!(matches!(
+        db.accept_reservation(&agent(1), &held.bead_id).await,
+        Err(SwarmError::AgentError(_))
+    ));
+    db.accept_reservation(&agent(2), &held.bead_id).await?;
+    assert_eq!(
+        status_of(&db, &held.bead_id).await?.as_deref(),
+        Some("in_progress")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_rejected_reservation_when_another_agent_reserves_then_the_bead_is_back_in_the_pool(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    db.seed_idle_agents(2).await?;
+    db.enqueue_backlog_batch(&RepoId::new("local"), "rejected", 1)
+        .await?;
+    let held = db
+        .reserve_next_bead(&agent(1), 600, None)
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 reserved nothing".to_string()))?;
+
+    assert!(
+        db.reject_reservation(&agent(1), &held.bead_id, Some("too big"))
+            .await?
+    );
+    assert!(
+        !db.reject_reservation(&agent(1), &held.bead_id, Some("again"))
+            .await?
+    );
+    let requeued = db.reserve_next_bead(&agent(2), 600, None).await?;
+
+    assert_eq!(
+        requeued.map(|reservation| reservation.bead_id),
+        Some(held.bead_id.clone())
+    );
+    assert_eq!(
+        status_of(&db, &held.bead_id).await?.as_deref(),
+        Some("pending")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_agents_racing_for_one_bead_when_reserving_then_exactly_one_wins() -> swarm::Result<()>
+{
+    let db = isolated_db().await?;
+    db.seed_idle_agents(8).await?;
+    db.enqueue_backlog_batch(&RepoId::new("local"), "contested", 1)
+        .await?;
+    let agents = (1..=8).map(agent).collect::<Vec<_>>();
+
+    let outcomes = join_all(
+        agents
+            .iter()
+            .map(|agent_id| db.reserve_next_bead(agent_id, 600, None)),
+    )
+    .await
+    .into_iter()
+    .collect::<swarm::Result<Vec<_>>>()?;
+    let winners = outcomes.into_iter().flatten().collect::<Vec<_>>();
+
+    assert_eq!(winners.len(), 1);
+    let accepts = join_all(
+        agents
+            .iter()
+            .map(|agent_id| db.accept_reservation(agent_id, &winners[0].bead_id)),
+    )
+    .await;
+    assert_eq!(accepts.iter().filter(|accept| accept.is_ok()).count(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_reserved_bead_when_another_agent_claims_next_then_it_is_skipped() -> swarm::Result<()>
+{
+    let db = isolated_db().await?;
+    db.seed_idle_agents(3).await?;
+    db.enqueue_backlog_batch(&RepoId::new("local"), "skip", 2)
+        .await?;
+    let reserved = db
+        .reserve_next_bead(&agent(1), 600, None)
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 reserved nothing".to_string()))?;
+
+    let claimed = db
+        .claim_next_bead(&agent(2))
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 2 claimed nothing".to_string()))?;
+
+    assert_ne!(claimed, reserved.bead_id);
+    assert!(db.claim_next_bead(&agent(3)).await?.is_none());
+    db.accept_reservation(&agent(1), &reserved.bead_id).await?;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use swarm::testsupport::isolated_db;
+use swarm::types::SessionEndReason;
+use swarm::{AgentId, RepoId, SwarmError};
+
+#[tokio::test]
+async fn given_open_session_when_starting_another_then_the_old_one_ends_as_replaced(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let (first, none) = db
+        .start_agent_session(&repo, 1, "host-a", 100, "0.1.0")
+        .await?;
+    assert!(none.is_none());
+
+    let (second, replaced) = db
+        .start_agent_session(&repo, 1, "host-b", 200, "0.1.1")
+        .await?;
+
+    let replaced =
+        replaced.ok_or_else(|| SwarmError::Internal("nothing was replaced".to_string()))?;
+    assert_eq!(replaced.id, first.id);
+    assert_eq!(replaced.end_reason, Some(SessionEndReason::Replaced));
+    assert!(!replaced.is_active());
+    assert_eq!(
+        db.get_active_session(&repo, 1).await?.map(|s| s.id),
+        Some(second.id)
+    );
+    let listed = db.list_agent_sessions(&repo, 10).await?;
+    assert_eq!(
+        listed.iter().map(|s| s.session.id).collect::<Vec<_>>(),
+        vec![second.id, first.id]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_session_when_its_agent_claims_then_the_claim_is_counted_against_it(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.seed_idle_agents(2).await?;
+    db.enqueue_backlog_batch(&repo, "session", 2).await?;
+    let (session, _) = db
+        .start_agent_session(&repo, 1, "host-a", 100, "0.1.0")
+        .await?;
+
+    db.claim_next_bead(&AgentId::new(repo.clone(), 1)).await?;
+    db.claim_next_bead(&AgentId::new(repo.clone(), 2)).await?;
+
+    let listed = db.list_agent_sessions(&repo, 10).await?;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].session.id, session.id);
+    assert_eq!(listed[0].claims, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_open_session_when_ending_it_then_it_is_kept_as_history_and_a_second_end_finds_nothing(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let (session, _) = db
+        .start_agent_session(&repo, 3, "host-a", 100, "0.1.0")
+        .await?;
+
+    let ended = db.end_agent_session(&repo, 3).await?;
+
+    assert_eq!(
+        ended.map(|s| (s.id, s.end_reason)),
+        Some((session.id, Some(SessionEndReason::Ended)))
+    );
+    assert!(db.get_active_session(&repo, 3).await?.is_none());
+    assert!(db.end_agent_session(&repo, 3).await?.is_none());
+    let listed = db.list_agent_sessions(&repo, 10).await?;
+    assert_eq!(listed.len(), 1);
+    assert!(listed[0].session.ended_at.is_some());
+    Ok(())
+}
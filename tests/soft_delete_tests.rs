@@ -0,0 +1,177 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use swarm::testsupport::{isolated_db, TestDb};
+use swarm::types::{BacklogSelector, SoftDeleteTable};
+use swarm::{AgentId, BeadId, RepoId, SwarmDb, SwarmError};
+
+fn agent(number: u32) -> AgentId {
+    AgentId::new(RepoId::new("local"), number)
+}
+
+/// Seeds one agent and one bead, claimed and then released by agent 1.
+async fn released_bead(db: &TestDb) -> swarm::Result<BeadId> {
+    db.seed_idle_agents(1).await?;
+    db.enqueue_backlog_batch(&RepoId::new("local"), "released", 1)
+        .await?;
+    db.claim_next_bead(&agent(1))
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 claimed nothing".to_string()))?;
+    db.release_agent(&agent(1))
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 released nothing".to_string()))
+}
+
+/// The claim row whether deleted or not: its owner and `deleted_by`.
+async fn claim_row(db: &SwarmDb, bead_id: &BeadId) -> swarm::Result<Option<(i32, Option<String>)>> {
+    sqlx::query_as::<_, (i32, Option<String>)>(
+        "SELECT claimed_by, deleted_by FROM bead_claims WHERE repo_id = 'local' AND bead_id = $1",
+    )
+    .bind(bead_id.value())
+    .fetch_optional(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))
+}
+
+async fn backlog_status(db: &SwarmDb, bead_id: &BeadId) -> swarm::Result<Option<String>> {
+    Ok(db
+        .get_backlog_rows(&RepoId::new("local"))
+        .await?
+        .into_iter()
+        .find(|row| row.bead_id == bead_id.value())
+        .map(|row| row.status))
+}
+
+#[tokio::test]
+async fn given_released_claim_when_reading_then_the_row_stays_marked_and_only_undelete_lists_it(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let bead = released_bead(&db).await?;
+
+    assert_eq!(
+        claim_row(&db, &bead).await?,
+        Some((1, Some("release".to_string())))
+    );
+    assert!(db
+        .get_claims_for_sync(&RepoId::new("local"))
+        .await?
+        .is_empty());
+    let deleted = db
+        .list_deleted_rows(&RepoId::new("local"), None, 10)
+        .await?;
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0].table, SoftDeleteTable::Claims);
+    assert_eq!(deleted[0].key, bead.value());
+    assert_eq!(deleted[0].deleted_by, "release");
+    assert_eq!(deleted[0].row["claimed_by"], 1);
+    assert!(deleted[0].row.get("deleted_at").is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_released_claim_when_undeleting_then_the_agent_and_backlog_follow_it_back(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let bead = released_bead(&db).await?;
+
+    let restored = db
+        .undelete_row(&repo, SoftDeleteTable::Claims, bead.value())
+        .await?;
+
+    assert_eq!(
+        restored.map(|row| row.deleted_by),
+        Some("release".to_string())
+    );
+    assert_eq!(claim_row(&db, &bead).await?, Some((1, None)));
+    assert_eq!(
+        backlog_status(&db, &bead).await?.as_deref(),
+        Some("in_progress")
+    );
+    let state = db
+        .get_agent_state(&agent(1))
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 is gone".to_string()))?;
+    assert_eq!(
+        state.bead_id().map(swarm::RuntimeBeadId::value),
+        Some(bead.value())
+    );
+    assert!(db.list_deleted_rows(&repo, None, 10).await?.is_empty());
+    assert!(db
+        .undelete_row(&repo, SoftDeleteTable::Claims, bead.value())
+        .await?
+        .is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_removed_backlog_entry_when_enqueuing_it_again_then_the_row_comes_back(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    db.seed_idle_agents(1).await?;
+    db.enqueue_backlog_batch(&repo, "removed", 1).await?;
+    let bead = BeadId::new("removed-1");
+
+    let outcome = db
+        .remove_backlog_beads(
+            &repo,
+            &BacklogSelector {
+                bead_ids: vec![bead.value().to_string()],
+                label: None,
+            },
+        )
+        .await?;
+    assert_eq!(outcome.removed, vec![bead.value().to_string()]);
+    assert_eq!(backlog_status(&db, &bead).await?, None);
+    assert!(db.claim_next_bead(&agent(1)).await?.is_none());
+
+    db.enqueue_backlog_batch(&repo, "removed", 1).await?;
+
+    assert_eq!(
+        backlog_status(&db, &bead).await?.as_deref(),
+        Some("pending")
+    );
+    assert!(db
+        .list_deleted_rows(&repo, Some(SoftDeleteTable::Backlog), 10)
+        .await?
+        .is_empty());
+    assert_eq!(db.claim_next_bead(&agent(1)).await?, Some(bead));
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_claim_deleted_past_retention_when_collecting_garbage_then_only_it_is_purged(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let old = released_bead(&db).await?;
+    db.enqueue_backlog_batch(&repo, "recent", 1).await?;
+    db.remove_backlog_beads(
+        &repo,
+        &BacklogSelector {
+            bead_ids: vec!["recent-1".to_string()],
+            label: None,
+        },
+    )
+    .await?;
+    sqlx::query(
+        "UPDATE bead_claims SET deleted_at = NOW() - INTERVAL '31 days'
+         WHERE repo_id = 'local' AND bead_id = $1",
+    )
+    .bind(old.value())
+    .execute(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+
+    let collected = db.collect_garbage(&repo).await?;
+
+    assert_eq!(collected["deleted"]["claims"], 1);
+    assert_eq!(collected["deleted"]["backlog"], 0);
+    assert_eq!(claim_row(&db, &old).await?, None);
+    let kept = db.list_deleted_rows(&repo, None, 10).await?;
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].key, "recent-1");
+    Ok(())
+}
@@ -0,0 +1,176 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use swarm::testsupport::{isolated_db, TestDb};
+use swarm::types::TakeoverBasis;
+use swarm::{AgentId, BeadId, RepoId, SwarmDb, SwarmError};
+
+fn agent(number: u32) -> AgentId {
+    AgentId::new(RepoId::new("local"), number)
+}
+
+/// Seeds `agents` idle agents and one bead, claimed by agent 1.
+async fn claimed_bead(db: &TestDb, agents: u32) -> swarm::Result<BeadId> {
+    db.seed_idle_agents(agents).await?;
+    db.enqueue_backlog_batch(&RepoId::new("local"), "takeover", 1)
+        .await?;
+    db.claim_next_bead(&agent(1))
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 claimed nothing".to_string()))
+}
+
+/// The claim's owner, whether takeover consent is recorded, and whether its
+/// lease is still running.
+async fn claim_of(db: &SwarmDb, bead_id: &BeadId) -> swarm::Result<(i32, bool, bool)> {
+    sqlx::query_as::<_, (i32, bool, bool)>(
+        "SELECT claimed_by, takeover_consented_at IS NOT NULL, lease_expires_at > NOW()
+         FROM bead_claims
+         WHERE repo_id = 'local' AND bead_id = $1 AND status = 'in_progress'",
+    )
+    .bind(bead_id.value())
+    .fetch_one(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))
+}
+
+async fn agent_of(db: &SwarmDb, number: i32) -> swarm::Result<(String, Option<String>)> {
+    sqlx::query_as::<_, (String, Option<String>)>(
+        "SELECT status, bead_id FROM agent_state WHERE repo_id = 'local' AND agent_id = $1",
+    )
+    .bind(number)
+    .fetch_one(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))
+}
+
+async fn transfer_events(db: &SwarmDb, bead_id: &BeadId) -> swarm::Result<i64> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM execution_events
+         WHERE event_type = 'claim_transferred' AND bead_id = $1",
+    )
+    .bind(bead_id.value())
+    .fetch_one(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))
+}
+
+#[tokio::test]
+async fn given_lapsed_lease_when_taking_over_then_the_claim_moves_as_stale() -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let bead = claimed_bead(&db, 2).await?;
+    sqlx::query(
+        "UPDATE bead_claims SET lease_expires_at = NOW() - INTERVAL '1 day'
+         WHERE repo_id = 'local' AND bead_id = $1",
+    )
+    .bind(bead.value())
+    .execute(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+
+    let transfer = db
+        .transfer_claim(&RepoId::new("local"), &bead, 2, None, None)
+        .await?;
+
+    assert_eq!(transfer.basis, TakeoverBasis::Stale);
+    assert_eq!((transfer.from_agent, transfer.to_agent), (1, 2));
+    assert_eq!(claim_of(&db, &bead).await?, (2, false, true));
+    assert_eq!(agent_of(&db, 1).await?, ("idle".to_string(), None));
+    assert_eq!(
+        agent_of(&db, 2).await?,
+        ("working".to_string(), Some(bead.value().to_string()))
+    );
+    assert_eq!(transfer_events(&db, &bead).await?, 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_live_lease_without_consent_when_taking_over_then_it_is_refused_and_nothing_moves(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let bead = claimed_bead(&db, 2).await?;
+
+    let refused = db
+        .transfer_claim(&RepoId::new("local"), &bead, 2, None, None)
+        .await;
+
+    assert!(matches!(refused, Err(SwarmError::AgentError(_))));
+    assert_eq!(claim_of(&db, &bead).await?, (1, false, true));
+    assert_eq!(
+        agent_of(&db, 1).await?,
+        ("working".to_string(), Some(bead.value().to_string()))
+    );
+    assert_eq!(agent_of(&db, 2).await?, ("idle".to_string(), None));
+    assert_eq!(transfer_events(&db, &bead).await?, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_owner_consent_when_handing_off_then_the_lease_moves_with_the_agents(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let bead = claimed_bead(&db, 2).await?;
+    db.consent_to_takeover(&repo, &bead, 1).await?;
+    assert_eq!(claim_of(&db, &bead).await?, (1, true, true));
+
+    let transfer = db
+        .transfer_claim(
+            &repo,
+            &bead,
+            2,
+            Some(1),
+            Some(serde_json::json!({"step": 3})),
+        )
+        .await?;
+
+    assert_eq!(transfer.basis, TakeoverBasis::Consent);
+    assert_eq!(claim_of(&db, &bead).await?, (2, false, true));
+    assert_eq!(agent_of(&db, 1).await?, ("idle".to_string(), None));
+    assert_eq!(
+        agent_of(&db, 2).await?,
+        ("working".to_string(), Some(bead.value().to_string()))
+    );
+    let resume_step = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT metadata->'resume_context'->>'step' FROM agent_messages WHERE id = $1",
+    )
+    .bind(transfer.message_id)
+    .fetch_one(db.pool())
+    .await
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))?;
+    assert_eq!(resume_step.as_deref(), Some("3"));
+    assert!(matches!(
+        db.transfer_claim(&repo, &bead, 1, Some(2), None).await,
+        Err(SwarmError::AgentError(_))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_consented_claim_when_two_agents_take_over_at_once_then_exactly_one_gets_it(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    let repo = RepoId::new("local");
+    let bead = claimed_bead(&db, 3).await?;
+    db.consent_to_takeover(&repo, &bead, 1).await?;
+
+    let (second, third) = tokio::join!(
+        db.transfer_claim(&repo, &bead, 2, Some(1), None),
+        db.transfer_claim(&repo, &bead, 3, Some(1), None),
+    );
+
+    let winner = match (second, third) {
+        (Ok(transfer), Err(SwarmError::AgentError(_)))
+        | (Err(SwarmError::AgentError(_)), Ok(transfer)) => transfer.to_agent,
+        (second, third) => {
+            return Err(SwarmError::Internal(format!(
+                "expected one takeover, got {:?} and {:?}",
+                second.map(|transfer| transfer.to_agent),
+                third.map(|transfer| transfer.to_agent)
+            )))
+        }
+    };
+    assert_eq!(claim_of(&db, &bead).await?.0, winner.cast_signed());
+    assert_eq!(transfer_events(&db, &bead).await?, 1);
+    Ok(())
+}
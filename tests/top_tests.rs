@@ -0,0 +1,113 @@
+#![cfg(feature = "testsupport")]
+#![deny(clippy::unwrap_used)]
+#![deny(clippy::expect_used)]
+
+use swarm::testsupport::isolated_db;
+use swarm::types::{AgentActivity, AgentStatus, Stage};
+use swarm::{AgentId, RepoId, SwarmDb, SwarmError};
+
+fn agent(number: u32) -> AgentId {
+    AgentId::new(RepoId::new("local"), number)
+}
+
+/// Records a `status` run of `stage` on `bead_id` by `agent_id` in
+/// `repo_id`, completed `mins_ago` minutes ago.
+async fn finished_stage(
+    db: &SwarmDb,
+    repo_id: &str,
+    agent_id: u32,
+    bead_id: &str,
+    stage: Stage,
+    status: &str,
+    mins_ago: i32,
+) -> swarm::Result<()> {
+    sqlx::query(
+        "INSERT INTO stage_history
+            (repo_id, agent_id, bead_id, stage, attempt_number, status, completed_at)
+         VALUES ($1, $2, $3, $4, 1, $5, NOW() - make_interval(mins => $6))",
+    )
+    .bind(repo_id)
+    .bind(agent_id.cast_signed())
+    .bind(bead_id)
+    .bind(stage.as_str())
+    .bind(status)
+    .bind(mins_ago)
+    .execute(db.pool())
+    .await
+    .map(|_| ())
+    .map_err(|e| SwarmError::DatabaseError(e.to_string()))
+}
+
+fn activity_of(activity: &[AgentActivity], agent_id: u32) -> swarm::Result<&AgentActivity> {
+    activity
+        .iter()
+        .find(|row| row.agent_id == agent_id)
+        .ok_or_else(|| SwarmError::Internal(format!("agent {agent_id} missing from top")))
+}
+
+#[tokio::test]
+async fn given_working_and_idle_agents_when_reading_top_then_only_the_worker_shows_a_bead_and_heartbeat(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    db.seed_idle_agents(2).await?;
+    db.enqueue_backlog_batch(&RepoId::new("local"), "top", 1)
+        .await?;
+    let bead = db
+        .claim_next_bead(&agent(1))
+        .await?
+        .ok_or_else(|| SwarmError::Internal("agent 1 claimed nothing".to_string()))?;
+    db.record_stage_started(&agent(1), &bead, Stage::Implement, 2)
+        .await?;
+
+    let activity = db.get_agent_activity(&RepoId::new("local"), 60).await?;
+
+    assert_eq!(
+        activity.iter().map(|row| row.agent_id).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    let worker = activity_of(&activity, 1)?;
+    assert_eq!(worker.status, AgentStatus::Working);
+    assert_eq!(worker.bead_id.as_deref(), Some(bead.value()));
+    assert_eq!(worker.stage.as_deref(), Some(Stage::Implement.as_str()));
+    assert!(worker.stage_elapsed_secs.is_some_and(|secs| secs < 60));
+    assert!(worker.heartbeat_age_secs.is_some_and(|secs| secs < 60));
+    let idle = activity_of(&activity, 2)?;
+    assert_eq!(idle.status, AgentStatus::Idle);
+    assert_eq!(
+        (idle.bead_id.as_deref(), idle.stage.as_deref()),
+        (None, None)
+    );
+    assert_eq!(
+        (idle.stage_elapsed_secs, idle.heartbeat_age_secs),
+        (None, None)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn given_stage_history_when_reading_top_then_throughput_counts_this_repo_inside_the_window(
+) -> swarm::Result<()> {
+    let db = isolated_db().await?;
+    db.seed_idle_agents(1).await?;
+    db.register_agent(&AgentId::new(RepoId::new("elsewhere"), 1))
+        .await?;
+    for (repo, bead, stage, status, mins_ago) in [
+        ("local", "a", Stage::Implement, "passed", 5),
+        ("local", "a", Stage::RedQueen, "passed", 4),
+        ("local", "b", Stage::RedQueen, "passed", 3),
+        ("local", "b", Stage::RedQueen, "passed", 2),
+        ("local", "c", Stage::QaEnforcer, "failed", 1),
+        ("local", "d", Stage::RedQueen, "passed", 90),
+        ("elsewhere", "e", Stage::RedQueen, "passed", 1),
+    ] {
+        finished_stage(&db, repo, 1, bead, stage, status, mins_ago).await?;
+    }
+
+    let activity = db.get_agent_activity(&RepoId::new("local"), 60).await?;
+
+    let agent = activity_of(&activity, 1)?;
+    assert_eq!((agent.stages_passed, agent.beads_completed), (4, 2));
+    let wider = db.get_agent_activity(&RepoId::new("local"), 120).await?;
+    assert_eq!(activity_of(&wider, 1)?.beads_completed, 3);
+    Ok(())
+}